@@ -7,6 +7,7 @@ use crate::config::{BuildMode, CloudConfig, DbConfig, LocalInstanceConfig};
 use crate::docker::DockerManager;
 use crate::errors::project_error;
 use crate::output::{Operation, Step};
+use crate::port;
 use crate::project::ProjectContext;
 use crate::prompts;
 use crate::utils::print_instructions;
@@ -227,11 +228,49 @@ async fn run_add_inner(
                 CloudConfig::FlyIo(instance_config.clone()),
             );
         }
+        CloudDeploymentTypeCommand::StandbyOf { instance, .. } => {
+            let Some(leader) = project_context.config.local.get(&instance) else {
+                return Err(project_error(format!(
+                    "'{instance}' is not a local instance in helix.toml"
+                ))
+                .with_hint("standbys can only copy local instances")
+                .into());
+            };
+            if let Some(its_leader) = &leader.standby_of {
+                return Err(project_error(format!(
+                    "'{instance}' is itself a standby of '{its_leader}'"
+                ))
+                .with_hint(format!("add a standby of '{its_leader}' instead"))
+                .into());
+            }
+
+            // Both run at once, so keep the standby off the leader's port even before it's pushed
+            if let Some(leader_port) = leader.port {
+                port::record_port(leader_port, &project_context.root, &instance);
+            }
+
+            // Same settings as the leader, so the standby can take over its data as is
+            let port = port::assign_port(&project_context.root, &instance_name)?;
+            let local_config = LocalInstanceConfig {
+                port: Some(port),
+                standby_of: Some(instance.clone()),
+                ..leader.clone()
+            };
+
+            project_context
+                .config
+                .local
+                .insert(instance_name.clone(), local_config);
+            Step::verbose_substep(&format!("Standby of '{instance}' configuration added"));
+        }
         _ => {
-            // Add local instance with default configuration
+            // Add local instance with default configuration, on a port no other instance uses
+            let port = port::assign_port(&project_context.root, &instance_name)?;
             let local_config = LocalInstanceConfig {
-                port: None, // Let the system assign a port
+                port: Some(port),
                 build_mode: BuildMode::Dev,
+                resources: None,
+                standby_of: None,
                 db_config: DbConfig::default(),
             };
 
@@ -1,12 +1,15 @@
 use crate::{
     AuthAction,
     commands::integrations::helix::CLOUD_AUTHORITY,
+    config::{ApiKeyConfig, ApiKeyScope, HelixConfig},
     metrics_sender::{load_metrics_config, save_metrics_config},
     output,
+    project::ProjectContext,
     sse_client::{SseClient, SseEvent},
 };
 use color_eyre::owo_colors::OwoColorize;
 use eyre::{OptionExt, Result, eyre};
+use helix_db::helix_gateway::api_keys::{generate_api_key, hash_api_key};
 use std::{
     fs::{self, File},
     path::PathBuf,
@@ -16,7 +19,12 @@ pub async fn run(action: AuthAction) -> Result<()> {
     match action {
         AuthAction::Login => login().await,
         AuthAction::Logout => logout().await,
-        AuthAction::CreateKey { cluster } => create_key(&cluster).await,
+        AuthAction::CreateKey {
+            instance,
+            name,
+            read_only,
+            admin,
+        } => create_key(&instance, name, read_only, admin),
     }
 }
 
@@ -78,19 +86,59 @@ async fn logout() -> Result<()> {
     Ok(())
 }
 
-async fn create_key(cluster: &str) -> Result<()> {
-    output::info(&format!("Creating API key for cluster: {cluster}"));
+fn create_key(instance: &str, name: Option<String>, read_only: bool, admin: bool) -> Result<()> {
+    let mut project = ProjectContext::find_and_load(None)?;
+    let scope = match (read_only, admin) {
+        (true, _) => ApiKeyScope::ReadOnly,
+        (false, true) => ApiKeyScope::Admin,
+        (false, false) => ApiKeyScope::ReadWrite,
+    };
+    let key = add_api_key(&mut project.config, instance, name, scope)?;
+    project
+        .config
+        .save_to_file(&project.root.join("helix.toml"))?;
+
+    output::success(&format!("Created API key '{}' for {instance}", key.name));
+    println!("\n  {}\n", key.secret.bold());
+    output::warning("This key will not be shown again; only its hash is stored in helix.toml");
+    output::info(&format!(
+        "Send it as `Authorization: Bearer <key>`. Run `helix push {instance}` to apply"
+    ));
 
-    // TODO: Implement API key creation
-    // This would:
-    // 1. Authenticate with cloud
-    // 2. Create new API key for specified cluster
-    // 3. Display the key to the user
+    Ok(())
+}
 
-    output::warning("API key creation not yet implemented");
-    println!("  This will create a new API key for cluster: {cluster}");
+pub struct CreatedKey {
+    pub name: String,
+    pub secret: String,
+}
 
-    Ok(())
+/// Generate a key and store its hash in the instance's gateway config
+pub fn add_api_key(
+    config: &mut HelixConfig,
+    instance: &str,
+    name: Option<String>,
+    scope: ApiKeyScope,
+) -> Result<CreatedKey> {
+    let keys = config
+        .db_config_mut(instance)?
+        .gateway_config
+        .api_keys
+        .get_or_insert_default();
+    let name = name.unwrap_or_else(|| format!("key-{}", keys.len() + 1));
+    if keys.iter().any(|key| key.name == name) {
+        return Err(eyre!(
+            "Instance '{instance}' already has an API key named '{name}'"
+        ));
+    }
+
+    let secret = generate_api_key();
+    keys.push(ApiKeyConfig {
+        name: name.clone(),
+        key_hash: hash_api_key(&secret),
+        scope,
+    });
+    Ok(CreatedKey { name, secret })
 }
 
 #[derive(Debug)]
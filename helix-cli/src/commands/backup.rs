@@ -1,3 +1,4 @@
+use crate::location::Location;
 use crate::output::{Operation, Step, Verbosity};
 use crate::project::ProjectContext;
 use crate::utils::{print_confirm, print_warning};
@@ -6,9 +7,8 @@ use heed3::{CompactionOption, EnvFlags, EnvOpenOptions};
 use std::fs;
 use std::fs::create_dir_all;
 use std::path::Path;
-use std::path::PathBuf;
 
-pub async fn run(output: Option<PathBuf>, instance_name: String) -> Result<()> {
+pub async fn run(output: Option<String>, instance_name: String) -> Result<()> {
     // Load project context
     let project = ProjectContext::find_and_load(None)?;
 
@@ -47,14 +47,24 @@ pub async fn run(output: Option<PathBuf>, instance_name: String) -> Result<()> {
         ));
     }
 
-    // Get path to backup instance
-    let backup_dir = match output {
-        Some(path) => path,
-        None => {
-            let ts = chrono::Local::now()
-                .format("backup-%Y%m%d-%H%M%S")
-                .to_string();
-            project.root.join("backups").join(ts)
+    // Get path to backup instance. Backups to object storage are taken into a temporary
+    // directory and uploaded from there.
+    let default_dir = || {
+        let ts = chrono::Local::now()
+            .format("backup-%Y%m%d-%H%M%S")
+            .to_string();
+        project.root.join("backups").join(ts)
+    };
+    let (backup_dir, remote, _staging) = match output.as_deref().map(Location::parse) {
+        None => (default_dir(), None, None),
+        Some(Ok(Location::Local(path))) => (path, None, None),
+        Some(Ok(Location::Remote(remote))) => {
+            let staging = tempfile::tempdir()?;
+            (staging.path().to_path_buf(), Some(remote), Some(staging))
+        }
+        Some(Err(e)) => {
+            op.failure();
+            return Err(e);
         }
     };
 
@@ -98,10 +108,25 @@ pub async fn run(output: Option<PathBuf>, instance_name: String) -> Result<()> {
     env.copy_to_path(backup_dir.join("data.mdb"), CompactionOption::Disabled)?;
 
     copy_step.done();
+
+    let backup_location = match &remote {
+        Some(remote) => {
+            let mut upload_step = Step::with_messages("Uploading backup", "Backup uploaded");
+            upload_step.start();
+            if let Err(e) = remote.upload(&backup_dir).await {
+                upload_step.fail();
+                op.failure();
+                return Err(e);
+            }
+            upload_step.done();
+            remote.to_string()
+        }
+        None => backup_dir.display().to_string(),
+    };
     op.success();
 
     if Verbosity::current().show_normal() {
-        Operation::print_details(&[("Backup location", &backup_dir.display().to_string())]);
+        Operation::print_details(&[("Backup location", &backup_location)]);
     }
 
     Ok(())
@@ -0,0 +1,584 @@
+//! `helix bench` command for measuring an instance's throughput and latency.
+//!
+//! A workload is a weighted mix of queries, given with `--query` or in a TOML file:
+//!
+//! ```toml
+//! duration = "30s"
+//! concurrency = 16
+//!
+//! [[op]]
+//! query = "getUser"
+//! weight = 8
+//! params = { id = "{key}" }
+//! keys = 10000
+//! distribution = "zipf"
+//!
+//! [[op]]
+//! query = "searchDocs"
+//! kind = "search"
+//! params = { vector = "{vector:384}", k = 10 }
+//! ```
+//!
+//! Parameter strings are templates: `{key}` is a key drawn from the op's key space (`0..keys`,
+//! or the lines of `keys_file`), `{vector:N}` a random embedding of N dimensions and `{uuid}` a
+//! fresh id. Workers send queries back to back for the whole duration, and every request's
+//! latency is kept so the percentiles are exact.
+
+use crate::commands::replay::{percentile_of_sorted, target_url};
+use crate::errors::CliError;
+use crate::output::{Operation, Step, Verbosity};
+use crate::utils::{print_field, print_header, print_newline, print_warning};
+use eyre::{Result, eyre};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// How long a workload runs when neither the file nor `--duration` say
+const DEFAULT_DURATION: Duration = Duration::from_secs(30);
+/// Workers sending queries when neither the file nor `--concurrency` say
+const DEFAULT_CONCURRENCY: usize = 16;
+/// Key space of ops that don't give one
+const DEFAULT_KEYS: usize = 10_000;
+/// Skew of the Zipf distribution when `zipf_exponent` isn't given
+const DEFAULT_ZIPF_EXPONENT: f64 = 1.0;
+
+/// What an op exercises, to tell its numbers apart in the report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpKind {
+    #[default]
+    Read,
+    Write,
+    Search,
+}
+
+impl fmt::Display for OpKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            OpKind::Read => "read",
+            OpKind::Write => "write",
+            OpKind::Search => "search",
+        })
+    }
+}
+
+/// How an op picks keys from its key space
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDistribution {
+    /// Every key equally likely
+    #[default]
+    Uniform,
+    /// A few hot keys, as in most real workloads
+    Zipf,
+    /// Every key in turn, across all workers
+    Sequential,
+}
+
+/// A query of a workload and how to call it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BenchOp {
+    pub query: String,
+    #[serde(default)]
+    pub kind: OpKind,
+    /// Share of the requests this op gets, relative to the other ops
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Request body, with `{key}`, `{vector:N}` and `{uuid}` placeholders
+    #[serde(default)]
+    pub params: serde_json::Value,
+    /// Number of keys, `0..keys`
+    pub keys: Option<usize>,
+    /// File with a key per line, used instead of `keys`
+    pub keys_file: Option<PathBuf>,
+    #[serde(default)]
+    pub distribution: KeyDistribution,
+    pub zipf_exponent: Option<f64>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// A benchmark workload, as read from a TOML file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workload {
+    /// How long to run, such as `30s`, `2m` or `500ms`
+    pub duration: Option<String>,
+    pub concurrency: Option<usize>,
+    #[serde(rename = "op")]
+    pub ops: Vec<BenchOp>,
+}
+
+impl Workload {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read workload {}: {e}", path.display()))?;
+        toml::from_str(&text).map_err(|e| eyre!("Invalid workload {}: {e}", path.display()))
+    }
+}
+
+/// Options of a benchmark run
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub duration: Duration,
+    pub concurrency: usize,
+    pub api_key: Option<String>,
+}
+
+/// Parse a duration such as `30s`, `2m`, `1h` or `500ms`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let seconds = match unit {
+        "ms" => 0.001,
+        "s" | "" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => f64::NAN,
+    };
+    match amount.parse::<f64>().map(|amount| amount * seconds) {
+        Ok(seconds) if seconds.is_finite() && seconds > 0.0 => Ok(Duration::from_secs_f64(seconds)),
+        _ => {
+            let error = CliError::new(format!("invalid duration '{text}'"))
+                .with_hint("use a number with ms, s, m or h, such as 30s");
+            Err(eyre!("{}", error.render()))
+        }
+    }
+}
+
+/// Where an op's `{key}` values come from
+#[derive(Debug)]
+enum Keys {
+    Range(usize),
+    Listed(Vec<String>),
+}
+
+impl Keys {
+    fn len(&self) -> usize {
+        match self {
+            Keys::Range(count) => *count,
+            Keys::Listed(keys) => keys.len(),
+        }
+    }
+
+    fn value(&self, index: usize) -> serde_json::Value {
+        match self {
+            Keys::Range(_) => serde_json::Value::from(index),
+            Keys::Listed(keys) => serde_json::Value::from(keys[index].as_str()),
+        }
+    }
+}
+
+/// Picks the index of the next key of an op
+#[derive(Debug)]
+enum KeySampler {
+    Uniform,
+    /// Cumulative probabilities of the keys, from the hottest
+    Zipf(Vec<f64>),
+    Sequential(AtomicUsize),
+}
+
+impl KeySampler {
+    fn new(distribution: KeyDistribution, keys: usize, exponent: f64) -> Self {
+        match distribution {
+            KeyDistribution::Uniform => KeySampler::Uniform,
+            KeyDistribution::Sequential => KeySampler::Sequential(AtomicUsize::new(0)),
+            KeyDistribution::Zipf => {
+                let mut total = 0.0;
+                let mut cumulative = (1..=keys)
+                    .map(|rank| {
+                        total += 1.0 / (rank as f64).powf(exponent);
+                        total
+                    })
+                    .collect::<Vec<_>>();
+                cumulative.iter_mut().for_each(|p| *p /= total);
+                KeySampler::Zipf(cumulative)
+            }
+        }
+    }
+
+    fn sample(&self, keys: usize, rng: &mut SmallRng) -> usize {
+        match self {
+            KeySampler::Uniform => rng.random_range(0..keys),
+            KeySampler::Zipf(cumulative) => {
+                let p = rng.random::<f64>();
+                cumulative.partition_point(|&c| c < p).min(keys - 1)
+            }
+            KeySampler::Sequential(next) => next.fetch_add(1, Ordering::Relaxed) % keys,
+        }
+    }
+}
+
+/// An op ready to send
+#[derive(Debug)]
+struct PreparedOp {
+    query: String,
+    kind: OpKind,
+    weight: u32,
+    params: serde_json::Value,
+    keys: Keys,
+    sampler: KeySampler,
+}
+
+impl PreparedOp {
+    fn new(op: &BenchOp) -> Result<Self> {
+        let keys = match &op.keys_file {
+            Some(path) => {
+                let keys = fs::read_to_string(path)
+                    .map_err(|e| eyre!("Failed to read keys {}: {e}", path.display()))?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                Keys::Listed(keys)
+            }
+            None => Keys::Range(op.keys.unwrap_or(DEFAULT_KEYS)),
+        };
+        if keys.len() == 0 {
+            return Err(eyre!("{} has no keys to pick from", op.query));
+        }
+        let exponent = op.zipf_exponent.unwrap_or(DEFAULT_ZIPF_EXPONENT);
+        Ok(Self {
+            query: op.query.clone(),
+            kind: op.kind,
+            weight: op.weight,
+            params: match &op.params {
+                serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+                params => params.clone(),
+            },
+            sampler: KeySampler::new(op.distribution, keys.len(), exponent),
+            keys,
+        })
+    }
+
+    /// The op's request body with its placeholders filled in
+    fn body(&self, rng: &mut SmallRng) -> serde_json::Value {
+        let key = self.keys.value(self.sampler.sample(self.keys.len(), rng));
+        fill_placeholders(&self.params, &key, rng)
+    }
+}
+
+/// Replace the placeholders of a params template. A string that is only `{key}` becomes the
+/// key itself, so numeric keys stay numbers.
+pub fn fill_placeholders(
+    template: &serde_json::Value,
+    key: &serde_json::Value,
+    rng: &mut impl Rng,
+) -> serde_json::Value {
+    use serde_json::Value;
+    match template {
+        Value::String(text) if text == "{key}" => key.clone(),
+        Value::String(text) if text == "{uuid}" => Value::from(Uuid::new_v4().to_string()),
+        Value::String(text) => {
+            let dimensions = text
+                .strip_prefix("{vector:")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .and_then(|n| n.parse::<usize>().ok());
+            match dimensions {
+                Some(n) => (0..n).map(|_| rng.random_range(-1.0..1.0)).collect(),
+                None => {
+                    let key = match key {
+                        Value::String(key) => key.clone(),
+                        key => key.to_string(),
+                    };
+                    Value::from(text.replace("{key}", &key))
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| fill_placeholders(item, key, rng))
+            .collect(),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), fill_placeholders(value, key, rng)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Numbers of one op of a run
+#[derive(Debug, Clone, Serialize)]
+pub struct OpReport {
+    pub query: String,
+    pub kind: OpKind,
+    pub requests: usize,
+    pub errors: usize,
+    pub throughput: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Outcome of a run, as `--json` prints it
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub duration_secs: f64,
+    pub concurrency: usize,
+    pub requests: usize,
+    pub errors: usize,
+    pub throughput: f64,
+    pub ops: Vec<OpReport>,
+    /// Error of the first failed request, to show what went wrong
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_error: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    target: String,
+    workload: Option<PathBuf>,
+    query: Option<String>,
+    params: Option<String>,
+    duration: Option<String>,
+    concurrency: Option<usize>,
+    keys: Option<usize>,
+    distribution: Option<KeyDistribution>,
+    json: bool,
+) -> Result<()> {
+    let mut workload = match (workload, query) {
+        (Some(path), None) => Workload::load(&path)?,
+        (None, Some(query)) => {
+            let params = params
+                .map(|params| serde_json::from_str(&params))
+                .transpose()
+                .map_err(|e| eyre!("--params is not valid JSON: {e}"))?
+                .unwrap_or_default();
+            Workload {
+                duration: None,
+                concurrency: None,
+                ops: vec![BenchOp {
+                    query,
+                    kind: OpKind::default(),
+                    weight: default_weight(),
+                    params,
+                    keys,
+                    keys_file: None,
+                    distribution: distribution.unwrap_or_default(),
+                    zipf_exponent: None,
+                }],
+            }
+        }
+        _ => {
+            let error = CliError::new("nothing to benchmark")
+                .with_hint("give a workload file with --workload, or a single query with --query");
+            return Err(eyre!("{}", error.render()));
+        }
+    };
+    if let Some(duration) = duration {
+        workload.duration = Some(duration);
+    }
+    let options = BenchOptions {
+        duration: workload
+            .duration
+            .as_deref()
+            .map(parse_duration)
+            .transpose()?
+            .unwrap_or(DEFAULT_DURATION),
+        concurrency: concurrency
+            .or(workload.concurrency)
+            .unwrap_or(DEFAULT_CONCURRENCY),
+        api_key: std::env::var("HELIX_API_KEY").ok(),
+    };
+    let url = target_url(&target)?;
+
+    if json {
+        // Progress output would get mixed into the JSON
+        Verbosity::set(Verbosity::Quiet);
+        let report = bench(&workload.ops, &url, &options).await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let op = Operation::new("Benchmarking", &target);
+    let mut bench_step = Step::with_messages(
+        &format!(
+            "Running {} workers for {:.0}s",
+            options.concurrency,
+            options.duration.as_secs_f64()
+        ),
+        "Benchmark finished",
+    );
+    bench_step.start();
+    let report = match bench(&workload.ops, &url, &options).await {
+        Ok(report) => report,
+        Err(e) => {
+            bench_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    bench_step.done_with_info(&format!("{} requests", report.requests));
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        print_report(&report);
+    }
+    if let Some(first_error) = &report.first_error {
+        print_warning(&format!(
+            "{} of {} requests failed, the first with {first_error}",
+            report.errors, report.requests
+        ));
+    }
+    Ok(())
+}
+
+/// Send the workload's ops to the instance at `url` from `concurrency` workers for `duration`
+pub async fn bench(ops: &[BenchOp], url: &str, options: &BenchOptions) -> Result<BenchReport> {
+    if ops.is_empty() {
+        return Err(eyre!("the workload has no ops"));
+    }
+    if options.concurrency == 0 {
+        return Err(eyre!("--concurrency must be at least 1"));
+    }
+    let ops = Arc::new(
+        ops.iter()
+            .map(PreparedOp::new)
+            .collect::<Result<Vec<_>>>()?,
+    );
+    let total_weight = ops.iter().map(|op| op.weight).sum::<u32>();
+    if total_weight == 0 {
+        return Err(eyre!("the workload's ops all have a weight of 0"));
+    }
+
+    let client = reqwest::Client::new();
+    let start = Instant::now();
+    let deadline = start + options.duration;
+    let mut workers = JoinSet::new();
+    for _ in 0..options.concurrency {
+        let ops = Arc::clone(&ops);
+        let client = client.clone();
+        let url = url.to_string();
+        let api_key = options.api_key.clone();
+        workers.spawn(async move {
+            let mut rng = SmallRng::from_rng(&mut rand::rng());
+            let mut latencies = vec![Vec::new(); ops.len()];
+            let mut errors = vec![0; ops.len()];
+            let mut first_error = None;
+            while Instant::now() < deadline {
+                let mut pick = rng.random_range(0..total_weight);
+                let index = ops
+                    .iter()
+                    .position(|op| {
+                        if pick < op.weight {
+                            return true;
+                        }
+                        pick -= op.weight;
+                        false
+                    })
+                    .expect("a pick below the total weight should land on an op");
+                let op = &ops[index];
+                let mut request = client
+                    .post(format!("{url}/{}", op.query))
+                    .json(&op.body(&mut rng));
+                if let Some(api_key) = &api_key {
+                    request = request.header("x-api-key", api_key).bearer_auth(api_key);
+                }
+                let sent = Instant::now();
+                let result = match request.send().await {
+                    Ok(response) => match response.error_for_status() {
+                        // Read the body so the latency covers the whole response
+                        Ok(response) => response.bytes().await.map(|_| ()),
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => latencies[index].push(sent.elapsed()),
+                    Err(e) => {
+                        errors[index] += 1;
+                        first_error.get_or_insert_with(|| format!("{}: {e}", op.query));
+                    }
+                }
+            }
+            (latencies, errors, first_error)
+        });
+    }
+
+    let mut latencies = vec![Vec::new(); ops.len()];
+    let mut errors = vec![0; ops.len()];
+    let mut first_error = None;
+    while let Some(worker) = workers.join_next().await {
+        let (worker_latencies, worker_errors, worker_error) = worker?;
+        for (all, worker) in latencies.iter_mut().zip(worker_latencies) {
+            all.extend(worker);
+        }
+        for (all, worker) in errors.iter_mut().zip(worker_errors) {
+            *all += worker;
+        }
+        first_error = first_error.or(worker_error);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let reports = ops
+        .iter()
+        .zip(latencies.iter_mut())
+        .zip(errors)
+        .map(|((op, latencies), errors)| {
+            latencies.sort_unstable();
+            let requests = latencies.len() + errors;
+            OpReport {
+                query: op.query.clone(),
+                kind: op.kind,
+                requests,
+                errors,
+                throughput: requests as f64 / elapsed,
+                p50_ms: ms(percentile_of_sorted(latencies, 50.0)),
+                p90_ms: ms(percentile_of_sorted(latencies, 90.0)),
+                p99_ms: ms(percentile_of_sorted(latencies, 99.0)),
+                max_ms: ms(latencies.last().copied().unwrap_or_default()),
+            }
+        })
+        .collect::<Vec<_>>();
+    let requests = reports.iter().map(|op| op.requests).sum();
+    Ok(BenchReport {
+        duration_secs: elapsed,
+        concurrency: options.concurrency,
+        requests,
+        errors: reports.iter().map(|op| op.errors).sum(),
+        throughput: requests as f64 / elapsed,
+        ops: reports,
+        first_error,
+    })
+}
+
+fn print_report(report: &BenchReport) {
+    print_newline();
+    print_header("Queries:");
+    for op in &report.ops {
+        print_field(
+            &format!("{} ({})", op.query, op.kind),
+            &format!(
+                "{:.1} req/s, {} errors, p50 {:.1}ms, p90 {:.1}ms, p99 {:.1}ms, max {:.1}ms",
+                op.throughput, op.errors, op.p50_ms, op.p90_ms, op.p99_ms, op.max_ms
+            ),
+        );
+    }
+    let requests = report.requests.to_string();
+    let errors = report.errors.to_string();
+    let throughput = format!("{:.1} req/s", report.throughput);
+    Operation::print_details(&[
+        ("Requests", requests.as_str()),
+        ("Errors", errors.as_str()),
+        ("Throughput", throughput.as_str()),
+    ]);
+}
@@ -2,6 +2,7 @@ use crate::config::InstanceInfo;
 use crate::docker::{DockerBuildError, DockerManager};
 use crate::github_issue::{GitHubIssueBuilder, filter_errors_only};
 use crate::metrics_sender::MetricsSender;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step};
 use crate::project::{ProjectContext, get_helix_repo_cache};
 use crate::prompts;
@@ -11,6 +12,7 @@ use crate::utils::{
     print_confirm, print_error, print_warning,
 };
 use eyre::{Result, eyre};
+use std::path::PathBuf;
 use std::process::Command;
 use std::time::Instant;
 
@@ -23,14 +25,14 @@ use helix_db::{
     helix_engine::traversal_core::config::Config,
     helixc::{
         analyzer::analyze,
-        generator::Source as GeneratedSource,
+        generator::{CodegenProfile, Source as GeneratedSource, generate},
         parser::{
             HelixParser,
             types::{Content, HxFile, Source},
         },
     },
 };
-use std::{fmt::Write, fs};
+use std::fs;
 
 // Development flag - set to true when working on V2 locally
 const DEV_MODE: bool = cfg!(debug_assertions);
@@ -42,10 +44,14 @@ const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 pub async fn run(
     instance_name: Option<String>,
     bin: Option<String>,
+    codegen_profile: Option<CodegenProfile>,
     metrics_sender: &MetricsSender,
 ) -> Result<MetricsData> {
     // Load project context
-    let project = ProjectContext::find_and_load(None)?;
+    let mut project = ProjectContext::find_and_load(None)?;
+    if codegen_profile.is_some() {
+        project.config.project.codegen_profile = codegen_profile;
+    }
 
     // Get instance name - prompt if not provided
     let instance_name = match instance_name {
@@ -77,14 +83,13 @@ pub async fn run(
     let op = Operation::new("Building", &instance_name);
 
     // Run the build steps
-    let result = run_build_steps(
-        &op,
-        &project,
-        &instance_name,
-        bin.as_deref(),
-        metrics_sender,
-    )
-    .await;
+    let output = match &bin {
+        Some(bin) => BuildOutput::Binary(bin),
+        None => BuildOutput::Image,
+    };
+    let result = run_build_steps(&op, &project, &instance_name, output, metrics_sender)
+        .await
+        .map(|(metrics_data, _)| metrics_data);
 
     match &result {
         Ok(_) => op.success(),
@@ -94,14 +99,25 @@ pub async fn run(
     result
 }
 
-/// Run the build steps without creating an Operation (for use by other commands like push)
+/// What the build steps produce once the queries are compiled
+pub enum BuildOutput<'a> {
+    /// The Docker image, for instances that run as containers
+    Image,
+    /// A helix-container binary, built with cargo into this target directory
+    Binary(&'a str),
+    /// The binary a native instance runs, left in its cargo target directory
+    Native,
+}
+
+/// Run the build steps without creating an Operation (for use by other commands like push).
+/// Answers with the path of the native binary when building one.
 pub async fn run_build_steps(
     _op: &Operation,
     project: &ProjectContext,
     instance_name: &str,
-    bin: Option<&str>,
+    output: BuildOutput<'_>,
     metrics_sender: &MetricsSender,
-) -> Result<MetricsData> {
+) -> Result<(MetricsData, Option<PathBuf>)> {
     let start_time = Instant::now();
 
     // Get instance config
@@ -157,7 +173,22 @@ pub async fn run_build_steps(
     }
 
     // Binary output or Docker build
-    if let Some(binary_output) = bin {
+    if let BuildOutput::Native = output {
+        let mut cargo_step = Step::with_messages("Building binary", "Binary built");
+        cargo_step.start();
+        let native = NativeManager::new(project);
+        return match native.build_binary(instance_name, instance_config.build_mode()) {
+            Ok(binary) => {
+                cargo_step.done();
+                Ok((metrics_data, Some(binary)))
+            }
+            Err(e) => {
+                cargo_step.fail();
+                Err(e)
+            }
+        };
+    }
+    if let BuildOutput::Binary(binary_output) = output {
         let mut cargo_step = Step::with_messages("Building binary", "Binary built");
         cargo_step.start();
         match build_binary_using_cargo(project, instance_name, binary_output) {
@@ -194,7 +225,7 @@ pub async fn run_build_steps(
         }
     }
 
-    Ok(metrics_data.clone())
+    Ok((metrics_data, None))
 }
 
 pub(crate) async fn ensure_helix_repo_cached() -> Result<()> {
@@ -370,12 +401,12 @@ pub(crate) async fn compile_project(
     let hx_files = collect_hx_files(&project.root, &project.config.project.queries)?;
 
     // Generate content and compile using helix-db compilation logic
-    let (analyzed_source, metrics_data) = compile_helix_files(&hx_files, &src_dir)?;
+    let (mut analyzed_source, metrics_data) = compile_helix_files(&hx_files, &src_dir)?;
+    analyzed_source.codegen_profile = project.config.project.codegen_profile.unwrap_or_default();
 
-    // Write the generated Rust code to queries.rs
-    let mut generated_rust_code = String::new();
-    write!(&mut generated_rust_code, "{analyzed_source}")?;
-    fs::write(src_dir.join("queries.rs"), generated_rust_code)?;
+    // Write the generated Rust code to queries.rs and a module per query under queries/, along
+    // with the gRPC service definition for generating clients
+    generate(analyzed_source, &src_dir)?;
 
     Ok(metrics_data)
 }
@@ -1,15 +1,19 @@
 //! Check command - validates project configuration, queries, and generated Rust code.
 
 use crate::commands::build;
+use crate::diagnostics::DiagnosticsFormat;
 use crate::github_issue::{GitHubIssueBuilder, filter_errors_only};
 use crate::metrics_sender::MetricsSender;
 use crate::output::{Operation, Step};
 use crate::project::ProjectContext;
 use crate::utils::helixc_utils::{
-    analyze_source, collect_hx_contents, collect_hx_files, generate_content, parse_content,
+    analyze_source_with_budget, collect_hx_contents, collect_hx_files, generate_content,
+    parse_content,
 };
-use crate::utils::{print_confirm, print_error, print_warning};
+use crate::utils::{copy_dir_recursively, print_confirm, print_error, print_warning};
 use eyre::Result;
+use helix_db::helixc::analyzer::{DEFAULT_COMPLEXITY_BUDGET, analyze_with_complexity_budget};
+use helix_db::helixc::parser::types::{Content, Source};
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -23,10 +27,18 @@ struct CargoCheckOutput {
     errors_only: String,
 }
 
-pub async fn run(instance: Option<String>, metrics_sender: &MetricsSender) -> Result<()> {
+pub async fn run(
+    instance: Option<String>,
+    format: Option<DiagnosticsFormat>,
+    metrics_sender: &MetricsSender,
+) -> Result<()> {
     // Load project context
     let project = ProjectContext::find_and_load(None)?;
 
+    if let Some(format) = format {
+        return report_diagnostics(&project, format);
+    }
+
     match instance {
         Some(instance_name) => check_instance(&project, &instance_name, metrics_sender).await,
         None => check_all_instances(&project, metrics_sender).await,
@@ -71,11 +83,15 @@ async fn check_instance(
     let generated_src = instance_workspace.join("helix-container/src");
     let cargo_check_src = instance_workspace.join("helix-repo-copy/helix-container/src");
 
-    // Copy queries.rs and config.hx.json
+    // Copy queries.rs, the query modules and config.hx.json
     fs::copy(
         generated_src.join("queries.rs"),
         cargo_check_src.join("queries.rs"),
     )?;
+    copy_dir_recursively(
+        &generated_src.join("queries"),
+        &cargo_check_src.join("queries"),
+    )?;
     fs::copy(
         generated_src.join("config.hx.json"),
         cargo_check_src.join("config.hx.json"),
@@ -105,7 +121,7 @@ async fn check_instance(
         );
 
         // Read generated Rust for issue
-        let generated_rust = fs::read_to_string(cargo_check_src.join("queries.rs"))
+        let generated_rust = read_generated_rust(&cargo_check_src)
             .unwrap_or_else(|_| String::from("[Could not read generated code]"));
 
         // Handle failure - print errors and offer GitHub issue
@@ -147,6 +163,37 @@ async fn check_all_instances(
 
 /// Validate project syntax by parsing queries and schema (similar to build.rs but without generating files)
 fn validate_project_syntax(project: &ProjectContext) -> Result<()> {
+    let (content, source) = parse_project(project)?;
+
+    // Run static analysis to catch validation errors and queries over the complexity budget
+    analyze_source_with_budget(source, &content.files, Some(complexity_budget(project)))?;
+
+    Ok(())
+}
+
+/// Print the analyzer's diagnostics to stdout in `format`, failing when there are any.
+fn report_diagnostics(project: &ProjectContext, format: DiagnosticsFormat) -> Result<()> {
+    let (content, source) = parse_project(project)?;
+    let (diagnostics, _) =
+        analyze_with_complexity_budget(&source, Some(complexity_budget(project)))
+            .map_err(|e| eyre::eyre!("Analysis error: {}", e))?;
+
+    let base = std::env::current_dir()?;
+    let base = base.canonicalize().unwrap_or(base);
+    let document = format.render(&diagnostics, &content.files, &base);
+    println!("{}", serde_json::to_string_pretty(&document)?);
+
+    if !diagnostics.is_empty() {
+        return Err(eyre::eyre!(
+            "Found {} diagnostic(s) in the queries",
+            diagnostics.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Parse the project's .hx files, failing when they define no schema.
+fn parse_project(project: &ProjectContext) -> Result<(Content, Source)> {
     // Collect all .hx files for validation
     let hx_files = collect_hx_files(&project.root, &project.config.project.queries)?;
 
@@ -162,10 +209,15 @@ fn validate_project_syntax(project: &ProjectContext) -> Result<()> {
         return Err(eyre::eyre!("{}", error.render()));
     }
 
-    // Run static analysis to catch validation errors
-    analyze_source(source, &content.files)?;
+    Ok((content, source))
+}
 
-    Ok(())
+fn complexity_budget(project: &ProjectContext) -> u64 {
+    project
+        .config
+        .project
+        .complexity_budget
+        .unwrap_or(DEFAULT_COMPLEXITY_BUDGET)
 }
 
 /// Run cargo check on the generated code.
@@ -191,6 +243,21 @@ fn run_cargo_check(helix_container_dir: &Path) -> Result<CargoCheckOutput> {
     })
 }
 
+/// Read queries.rs followed by the query modules, each headed by its path.
+fn read_generated_rust(src_dir: &Path) -> Result<String> {
+    let mut generated = fs::read_to_string(src_dir.join("queries.rs"))?;
+    let mut modules = fs::read_dir(src_dir.join("queries"))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    modules.sort();
+    for module in modules {
+        let name = module.file_name().unwrap_or_default().to_string_lossy();
+        generated.push_str(&format!("\n// queries/{name}\n"));
+        generated.push_str(&fs::read_to_string(&module)?);
+    }
+    Ok(generated)
+}
+
 /// Handle cargo check failure - print errors and offer GitHub issue creation.
 fn handle_cargo_check_failure(
     cargo_output: &CargoCheckOutput,
@@ -1,6 +1,8 @@
+use std::fs;
 use std::path::PathBuf;
 
 use eyre::Result;
+use helix_db::helixc::generator::CodegenProfile;
 
 use crate::{
     output::{Operation, Step},
@@ -10,7 +12,12 @@ use crate::{
     },
 };
 
-pub async fn run(output_dir: Option<String>, path: Option<String>) -> Result<()> {
+pub async fn run(
+    output_dir: Option<String>,
+    path: Option<String>,
+    client: Option<String>,
+    codegen_profile: Option<CodegenProfile>,
+) -> Result<()> {
     let op = Operation::new("Compiling", "queries");
 
     // Load project context from the specified path (helix.toml directory) or find it automatically
@@ -47,9 +54,15 @@ pub async fn run(output_dir: Option<String>, path: Option<String>) -> Result<()>
     // Run static analysis to catch validation errors
     let mut analyze_step = Step::with_messages("Analyzing", "Analysis complete");
     analyze_step.start();
-    let generated_source = analyze_source(source, &content.files)?;
+    let mut generated_source = analyze_source(source, &content.files)?;
+    generated_source.codegen_profile = codegen_profile
+        .or(project.config.project.codegen_profile)
+        .unwrap_or_default();
     analyze_step.done();
 
+    // Typed client bindings are generated from the analyzed source before codegen consumes it
+    let client = client.map(|path| (path, generated_source.to_rust_client()));
+
     // Generate Rust code
     let mut codegen_step = Step::with_messages("Generating Rust code", "Rust code generated");
     codegen_step.start();
@@ -59,6 +72,20 @@ pub async fn run(output_dir: Option<String>, path: Option<String>) -> Result<()>
     generate_rust_code(generated_source, &output_dir)?;
     codegen_step.done();
 
+    if let Some((client_path, client_code)) = client {
+        let mut client_step =
+            Step::with_messages("Generating Rust client", "Rust client generated");
+        client_step.start();
+        if let Err(e) = fs::write(&client_path, client_code) {
+            client_step.fail();
+            op.failure();
+            return Err(eyre::eyre!(
+                "Failed to write Rust client to {client_path}: {e}"
+            ));
+        }
+        client_step.done();
+    }
+
     op.success();
     Ok(())
 }
@@ -0,0 +1,71 @@
+use crate::ConfigAction;
+use crate::metrics_sender::load_metrics_config;
+use crate::output;
+use crate::user_config::{DEFAULT_REGION, DEFAULT_TEMPLATE, KEYS, UserConfig};
+use crate::utils::{print_field, print_header, print_line};
+use eyre::Result;
+
+pub async fn run(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Set { key, value } => set(&key, &value),
+        ConfigAction::Get { key } => get(&key),
+        ConfigAction::Unset { key } => unset(&key),
+        ConfigAction::List => list(),
+    }
+}
+
+fn set(key: &str, value: &str) -> Result<()> {
+    let mut config = UserConfig::load()?;
+    config.set(key, value)?;
+    config.save()?;
+    let value = config.get(key)?.unwrap_or_default();
+    output::success(&format!("Set {key} to {value}"));
+    Ok(())
+}
+
+fn get(key: &str) -> Result<()> {
+    let config = UserConfig::load()?;
+    let value = match config.get(key)? {
+        Some(value) => value,
+        None => default_value(key),
+    };
+    print_line(&value);
+    Ok(())
+}
+
+fn unset(key: &str) -> Result<()> {
+    let mut config = UserConfig::load()?;
+    config.unset(key)?;
+    config.save()?;
+    output::success(&format!("Reset {key} to {}", default_value(key)));
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let config = UserConfig::load()?;
+    print_header(&format!("Settings ({})", UserConfig::path()?.display()));
+    for (key, _) in KEYS {
+        let value = match config.get(key)? {
+            Some(value) => value,
+            None => format!("{} (default)", default_value(key)),
+        };
+        print_field(key, &value);
+    }
+    Ok(())
+}
+
+/// What applies while `key` is unset
+fn default_value(key: &str) -> String {
+    match key {
+        "region" => DEFAULT_REGION.to_string(),
+        "telemetry" => {
+            // Left to `helix metrics`
+            let level = load_metrics_config().unwrap_or_default().level;
+            format!("{level:?}").to_lowercase()
+        }
+        "update_channel" => "stable".to_string(),
+        "output" => "normal".to_string(),
+        "template" => DEFAULT_TEMPLATE.to_string(),
+        _ => String::new(),
+    }
+}
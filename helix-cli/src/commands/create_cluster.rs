@@ -5,6 +5,7 @@ use crate::{
     output,
     project::ProjectContext,
     sse_client::SseEvent,
+    user_config::UserConfig,
     utils::print_error,
 };
 use eyre::{OptionExt, Result, eyre};
@@ -40,7 +41,7 @@ pub async fn run(instance_name: &str, region: Option<String>) -> Result<()> {
     let credentials = require_auth().await?;
 
     // Get or default region
-    let region = region.unwrap_or_else(|| "us-east-1".to_string());
+    let region = region.unwrap_or_else(|| UserConfig::current().region());
 
     // Connect to SSE stream for cluster creation
     // The server will send CheckoutRequired, PaymentConfirmed, CreatingProject, ProjectCreated events
@@ -6,6 +6,7 @@ use crate::commands::integrations::helix::CLOUD_AUTHORITY;
 use crate::config::{BuildMode, ContainerRuntime, InstanceInfo};
 use crate::docker::DockerManager;
 use crate::metrics_sender::MetricsSender;
+use crate::native::NativeManager;
 use crate::output::{self, Operation};
 use crate::project::ProjectContext;
 use crate::prompts;
@@ -302,11 +303,24 @@ async fn check_dev_mode_requirement(
         let config_path = project.root.join("helix.toml");
         config.save_to_file(&config_path)?;
 
-        // Reload the project context and push
-        crate::commands::push::run(Some(instance_name.to_string()), false, &metrics_sender).await?;
+        // Reload the project context and push, the same way it runs now
+        let native = NativeManager::new(project).is_native(instance_name);
+        crate::commands::push::run(
+            Some(instance_name.to_string()),
+            false,
+            native,
+            &metrics_sender,
+        )
+        .await?;
     } else {
         // For cloud instances, use the --dev flag
-        crate::commands::push::run(Some(instance_name.to_string()), true, &metrics_sender).await?;
+        crate::commands::push::run(
+            Some(instance_name.to_string()),
+            true,
+            false,
+            &metrics_sender,
+        )
+        .await?;
     }
 
     output::success(&format!(
@@ -323,19 +337,22 @@ async fn check_instance_running(project: &ProjectContext, instance_name: &str) -
     // Check if Docker/Podman is available
     DockerManager::check_runtime_available(docker.runtime)?;
 
-    // Get container status
-    let statuses = docker.get_project_status()?;
-    let container_prefix = format!("helix-{}-{}", project.config.project.name, instance_name);
-
-    // Find the container for this instance
-    let container_status = statuses
-        .iter()
-        .find(|s| s.container_name.starts_with(&container_prefix));
-
-    // Check if container is running
-    let is_running = container_status
-        .map(|s| s.status.to_lowercase().starts_with("up"))
-        .unwrap_or(false);
+    let native = NativeManager::new(project);
+    let native_instance = native.is_native(instance_name);
+    let is_running = if native_instance {
+        native.running_pid(instance_name).is_some()
+    } else {
+        // Get container status
+        let statuses = docker.get_project_status()?;
+        let container_prefix = format!("helix-{}-{}", project.config.project.name, instance_name);
+
+        // Find the container for this instance, and check if it's running
+        statuses
+            .iter()
+            .find(|s| s.container_name.starts_with(&container_prefix))
+            .map(|s| s.status.to_lowercase().starts_with("up"))
+            .unwrap_or(false)
+    };
 
     if is_running {
         // Instance is running, nothing to do
@@ -369,7 +386,13 @@ async fn check_instance_running(project: &ProjectContext, instance_name: &str) -
         instance_name
     ));
     let metrics_sender = MetricsSender::new()?;
-    crate::commands::push::run(Some(instance_name.to_string()), false, &metrics_sender).await?;
+    crate::commands::push::run(
+        Some(instance_name.to_string()),
+        false,
+        native_instance,
+        &metrics_sender,
+    )
+    .await?;
     output::success(&format!("Instance '{}' built and started", instance_name));
 
     Ok(())
@@ -492,6 +515,9 @@ fn start_dashboard_container(
 ) -> Result<()> {
     output::info("Starting dashboard container...");
 
+    // A dashboard kept around by its restart policy would hold on to the name
+    remove_dashboard_container(runtime);
+
     let image = format!("{DASHBOARD_IMAGE}:{DASHBOARD_TAG}");
 
     let mut args = vec![
@@ -500,13 +526,12 @@ fn start_dashboard_container(
         DASHBOARD_CONTAINER_NAME.to_string(),
         "-p".to_string(),
         format!("{port}:3000"),
-        "--rm".to_string(),
     ];
-
-    // Add detach flag if not attaching
-    if !attach {
-        args.push("-d".to_string());
-    }
+    args.extend(
+        lifecycle_args(attach, DockerManager::detect_platform())
+            .iter()
+            .map(|arg| arg.to_string()),
+    );
 
     // Add environment variables
     for env in env_vars {
@@ -545,6 +570,24 @@ fn start_dashboard_container(
     Ok(())
 }
 
+/// How long the dashboard container lives. Detached on Windows it runs like a service,
+/// restarted with Docker Desktop until `helix dashboard stop`, as there's no shell session
+/// holding on to it there.
+pub(crate) fn lifecycle_args(attach: bool, platform: &str) -> &'static [&'static str] {
+    match (attach, platform) {
+        (true, _) => &["--rm"],
+        (false, "windows") => &["-d", "--restart", "unless-stopped"],
+        (false, _) => &["--rm", "-d"],
+    }
+}
+
+/// Remove the dashboard container if one is left, running or not
+fn remove_dashboard_container(runtime: ContainerRuntime) {
+    let _ = Command::new(runtime.binary())
+        .args(["rm", "-f", DASHBOARD_CONTAINER_NAME])
+        .output();
+}
+
 fn stop_dashboard_container(runtime: ContainerRuntime) -> Result<()> {
     let output = Command::new(runtime.binary())
         .args(["stop", DASHBOARD_CONTAINER_NAME])
@@ -558,6 +601,8 @@ fn stop_dashboard_container(runtime: ContainerRuntime) -> Result<()> {
             return Err(eyre!("Failed to stop dashboard:\n{stderr}"));
         }
     }
+    // Containers started without --rm stay behind once stopped
+    remove_dashboard_container(runtime);
 
     Ok(())
 }
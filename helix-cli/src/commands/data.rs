@@ -0,0 +1,467 @@
+//! `helix data` commands for inspecting the data of backups and local instances.
+//!
+//! `helix data diff` compares two snapshots, each a backup directory (or object store URL) or
+//! a local instance of the project, and reports the nodes, edges and vectors added, removed and
+//! changed per label. Both stores are walked in key order side by side, so snapshots of any
+//! size are compared without loading them into memory.
+
+use crate::DataAction;
+use crate::errors::CliError;
+use crate::location::Location;
+use crate::output::{Operation, Step, Verbosity};
+use crate::project::ProjectContext;
+use crate::utils::{print_field, print_header, print_newline};
+use bumpalo::Bump;
+use eyre::{Result, eyre};
+use heed3::byteorder::BE;
+use heed3::types::{Bytes, U128};
+use heed3::{Database, Env, EnvFlags, EnvOpenOptions, RoTxn};
+use helix_db::helix_engine::vector_core::vector_core::VectorCore;
+use helix_db::helix_engine::vector_core::vector_without_data::VectorWithoutData;
+use helix_db::protocol::value::Value;
+use helix_db::utils::items::{Edge, Node};
+use helix_db::utils::properties::ImmutablePropertiesMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Items compared between resets of the allocation arena
+const DIFF_BATCH_SIZE: usize = 10_000;
+
+/// What kind of item a label's counts are of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Node,
+    Edge,
+    Vector,
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ItemKind::Node => "Nodes",
+            ItemKind::Edge => "Edges",
+            ItemKind::Vector => "Vectors",
+        })
+    }
+}
+
+/// How the items of one label differ between two snapshots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LabelDiff {
+    /// Only in the second snapshot
+    pub added: u64,
+    /// Only in the first snapshot
+    pub removed: u64,
+    /// In both, with a different label, properties, endpoints or embedding
+    pub changed: u64,
+    pub unchanged: u64,
+}
+
+impl LabelDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.changed == 0
+    }
+}
+
+/// The differences between two snapshots. Changed items are counted under their label in the
+/// second snapshot.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DataDiff {
+    pub labels: BTreeMap<(ItemKind, String), LabelDiff>,
+}
+
+impl DataDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.labels.values().all(LabelDiff::is_unchanged)
+    }
+
+    fn entry(&mut self, kind: ItemKind, label: &str) -> &mut LabelDiff {
+        self.labels.entry((kind, label.to_string())).or_default()
+    }
+}
+
+/// A label's counts as `helix data diff --json` prints them
+#[derive(Serialize)]
+struct LabelDiffRow<'a> {
+    kind: ItemKind,
+    label: &'a str,
+    #[serde(flatten)]
+    diff: LabelDiff,
+}
+
+pub async fn run(action: DataAction) -> Result<()> {
+    match action {
+        DataAction::Diff { a, b, json } => diff_command(&a, &b, json).await,
+    }
+}
+
+async fn diff_command(a: &str, b: &str, json: bool) -> Result<()> {
+    if json {
+        // Progress output would get mixed into the JSON
+        Verbosity::set(Verbosity::Quiet);
+        let diff = compare(a, b).await?;
+        let rows = diff
+            .labels
+            .iter()
+            .map(|((kind, label), diff)| LabelDiffRow {
+                kind: *kind,
+                label,
+                diff: *diff,
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    let op = Operation::new("Comparing", &format!("{a} and {b}"));
+    let diff = match compare(a, b).await {
+        Ok(diff) => diff,
+        Err(e) => {
+            op.failure();
+            return Err(e);
+        }
+    };
+    op.success();
+    if Verbosity::current().show_normal() {
+        print_diff(&diff);
+    }
+    Ok(())
+}
+
+async fn compare(a: &str, b: &str) -> Result<DataDiff> {
+    // Instances are looked up by name, so a project is only needed when one is given
+    let project = ProjectContext::find_and_load(None).ok();
+    let staging = tempfile::tempdir()?;
+    let a = resolve_snapshot(a, project.as_ref(), &staging.path().join("a")).await?;
+    let b = resolve_snapshot(b, project.as_ref(), &staging.path().join("b")).await?;
+
+    let mut diff_step = Step::with_messages("Comparing data", "Data compared");
+    diff_step.start();
+    match diff(&a, &b) {
+        Ok(diff) => {
+            diff_step.done();
+            Ok(diff)
+        }
+        Err(e) => {
+            diff_step.fail();
+            Err(e)
+        }
+    }
+}
+
+fn print_diff(diff: &DataDiff) {
+    if diff.is_unchanged() {
+        print_field("Result", "No differences");
+        return;
+    }
+    let mut kind = None;
+    for ((item_kind, label), counts) in &diff.labels {
+        if counts.is_unchanged() && !Verbosity::current().show_verbose() {
+            continue;
+        }
+        if kind != Some(*item_kind) {
+            print_newline();
+            print_header(&format!("{item_kind}:"));
+            kind = Some(*item_kind);
+        }
+        print_field(
+            label,
+            &format!(
+                "+{} added, -{} removed, ~{} changed ({} unchanged)",
+                counts.added, counts.removed, counts.changed, counts.unchanged
+            ),
+        );
+    }
+}
+
+/// Local directory holding the `data.mdb` of a snapshot: a backup directory or its `data.mdb`,
+/// a backup at an object store URL, downloaded into `staging`, or a local instance's volume
+async fn resolve_snapshot(
+    snapshot: &str,
+    project: Option<&ProjectContext>,
+    staging: &Path,
+) -> Result<PathBuf> {
+    let path = match Location::parse(snapshot)? {
+        Location::Remote(remote) => {
+            let mut download_step = Step::with_messages("Downloading backup", "Backup downloaded");
+            download_step.start();
+            match remote.download(staging).await {
+                Ok(path) => {
+                    download_step.done_with_info(&remote.to_string());
+                    path
+                }
+                Err(e) => {
+                    download_step.fail();
+                    return Err(e);
+                }
+            }
+        }
+        Location::Local(path) if path.exists() => path,
+        Location::Local(path) => match project.map(|p| (p, p.config.get_instance(snapshot))) {
+            Some((project, Ok(instance))) => {
+                if !instance.is_local() {
+                    let error =
+                        CliError::new(format!("instance '{snapshot}' is not a local instance"))
+                            .with_hint("back it up into a local directory, then compare that");
+                    return Err(eyre!("{}", error.render()));
+                }
+                project.instance_volume(snapshot).join("user")
+            }
+            _ => {
+                let error = CliError::new(format!("{} was not found", path.display()))
+                    .with_hint("give a backup directory, an s3://, gs:// or az:// URL, or the name of a local instance");
+                return Err(eyre!("{}", error.render()));
+            }
+        },
+    };
+
+    let dir = match path.file_name().is_some_and(|name| name == "data.mdb") {
+        true => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        false => path,
+    };
+    if !dir.join("data.mdb").exists() {
+        let error = CliError::new(format!("{snapshot} has no data"))
+            .with_hint(format!("no data.mdb was found in {}", dir.display()));
+        return Err(eyre!("{}", error.render()));
+    }
+    Ok(dir)
+}
+
+/// An LMDB environment opened read-only, so a running instance's writes aren't affected
+struct Snapshot {
+    path: PathBuf,
+    env: Env,
+}
+
+impl Snapshot {
+    fn open(path: &Path) -> Result<Self> {
+        // SAFETY: the environment is opened read-only and isn't modified while it's open
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .flags(EnvFlags::READ_ONLY)
+                .max_dbs(200)
+                .max_readers(200)
+                .open(path)?
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            env,
+        })
+    }
+
+    fn database<K: 'static, V: 'static>(
+        &self,
+        txn: &RoTxn,
+        name: &str,
+    ) -> Result<Option<Database<K, V>>> {
+        Ok(self.env.open_database(txn, Some(name))?)
+    }
+
+    fn required_database(&self, txn: &RoTxn, name: &str) -> Result<Database<U128<BE>, Bytes>> {
+        self.database(txn, name)?
+            .ok_or_else(|| eyre!("{} has no {name} database", self.path.display()))
+    }
+}
+
+/// Compare the nodes, edges and vectors of the snapshots in directories `a` and `b`
+pub fn diff(a: &Path, b: &Path) -> Result<DataDiff> {
+    let (a, b) = (Snapshot::open(a)?, Snapshot::open(b)?);
+    let (txn_a, txn_b) = (a.env.read_txn()?, b.env.read_txn()?);
+    let mut diff = DataDiff::default();
+    let mut arena = Bump::new();
+
+    let (nodes_a, nodes_b) = (
+        a.required_database(&txn_a, "nodes")?,
+        b.required_database(&txn_b, "nodes")?,
+    );
+    let mut compared = 0;
+    merge_join(
+        nodes_a.iter(&txn_a)?,
+        nodes_b.iter(&txn_b)?,
+        |id, old, new| {
+            let decode = |bytes| {
+                Node::from_bincode_bytes(id, bytes, &arena)
+                    .map_err(|e| eyre!("Failed to read node {}: {e}", Uuid::from_u128(id)))
+            };
+            match (old.map(decode).transpose()?, new.map(decode).transpose()?) {
+                (Some(old), Some(new)) => {
+                    let counts = diff.entry(ItemKind::Node, new.label);
+                    match old.label == new.label
+                        && same_properties(&old.properties, &new.properties)
+                    {
+                        true => counts.unchanged += 1,
+                        false => counts.changed += 1,
+                    }
+                }
+                (Some(old), None) => diff.entry(ItemKind::Node, old.label).removed += 1,
+                (None, Some(new)) => diff.entry(ItemKind::Node, new.label).added += 1,
+                (None, None) => {}
+            }
+            compared += 1;
+            if compared % DIFF_BATCH_SIZE == 0 {
+                arena.reset();
+            }
+            Ok(())
+        },
+    )?;
+
+    let (edges_a, edges_b) = (
+        a.required_database(&txn_a, "edges")?,
+        b.required_database(&txn_b, "edges")?,
+    );
+    merge_join(
+        edges_a.iter(&txn_a)?,
+        edges_b.iter(&txn_b)?,
+        |id, old, new| {
+            let decode = |bytes| {
+                Edge::from_bincode_bytes(id, bytes, &arena)
+                    .map_err(|e| eyre!("Failed to read edge {}: {e}", Uuid::from_u128(id)))
+            };
+            match (old.map(decode).transpose()?, new.map(decode).transpose()?) {
+                (Some(old), Some(new)) => {
+                    let counts = diff.entry(ItemKind::Edge, new.label);
+                    match old.label == new.label
+                        && (old.from_node, old.to_node) == (new.from_node, new.to_node)
+                        && same_properties(&old.properties, &new.properties)
+                    {
+                        true => counts.unchanged += 1,
+                        false => counts.changed += 1,
+                    }
+                }
+                (Some(old), None) => diff.entry(ItemKind::Edge, old.label).removed += 1,
+                (None, Some(new)) => diff.entry(ItemKind::Edge, new.label).added += 1,
+                (None, None) => {}
+            }
+            compared += 1;
+            if compared % DIFF_BATCH_SIZE == 0 {
+                arena.reset();
+            }
+            Ok(())
+        },
+    )?;
+
+    // Instances that never stored a vector may not have the vector databases
+    let vectors_a = a.database::<U128<BE>, Bytes>(&txn_a, "vector_data")?;
+    let vectors_b = b.database::<U128<BE>, Bytes>(&txn_b, "vector_data")?;
+    let embeddings_a = a.database::<Bytes, Bytes>(&txn_a, "vectors")?;
+    let embeddings_b = b.database::<Bytes, Bytes>(&txn_b, "vectors")?;
+    let iter_a = vectors_a.map(|db| db.iter(&txn_a)).transpose()?;
+    let iter_b = vectors_b.map(|db| db.iter(&txn_b)).transpose()?;
+    merge_join(
+        iter_a.into_iter().flatten(),
+        iter_b.into_iter().flatten(),
+        |id, old, new| {
+            let decode = |bytes| {
+                VectorWithoutData::from_bincode_bytes(&arena, bytes, id)
+                    .map_err(|e| eyre!("Failed to read vector {}: {e}", Uuid::from_u128(id)))
+            };
+            // Deleted vectors are kept with a flag, and are as good as removed
+            let old = old.map(decode).transpose()?.filter(|v| !v.deleted);
+            let new = new.map(decode).transpose()?.filter(|v| !v.deleted);
+            match (old, new) {
+                (Some(old), Some(new)) => {
+                    let key = VectorCore::vector_key(id, 0);
+                    let embedding_a = embeddings_a
+                        .map(|db| db.get(&txn_a, &key))
+                        .transpose()?
+                        .flatten();
+                    let embedding_b = embeddings_b
+                        .map(|db| db.get(&txn_b, &key))
+                        .transpose()?
+                        .flatten();
+                    let counts = diff.entry(ItemKind::Vector, new.label);
+                    match old.label == new.label
+                        && embedding_a == embedding_b
+                        && same_properties(&old.properties, &new.properties)
+                    {
+                        true => counts.unchanged += 1,
+                        false => counts.changed += 1,
+                    }
+                }
+                (Some(old), None) => diff.entry(ItemKind::Vector, old.label).removed += 1,
+                (None, Some(new)) => diff.entry(ItemKind::Vector, new.label).added += 1,
+                (None, None) => {}
+            }
+            compared += 1;
+            if compared % DIFF_BATCH_SIZE == 0 {
+                arena.reset();
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(diff)
+}
+
+/// Walk two key-ordered iterators together, visiting every key with its value on each side
+fn merge_join<'a, 'b, A, B>(
+    a: A,
+    b: B,
+    mut visit: impl FnMut(u128, Option<&'a [u8]>, Option<&'b [u8]>) -> Result<()>,
+) -> Result<()>
+where
+    A: Iterator<Item = heed3::Result<(u128, &'a [u8])>>,
+    B: Iterator<Item = heed3::Result<(u128, &'b [u8])>>,
+{
+    fn peek_key<I, V>(iter: &mut Peekable<I>) -> Result<Option<u128>>
+    where
+        I: Iterator<Item = heed3::Result<(u128, V)>>,
+    {
+        match iter.peek() {
+            Some(Ok((key, _))) => Ok(Some(*key)),
+            Some(Err(_)) => match iter.next() {
+                Some(Err(e)) => Err(e.into()),
+                _ => unreachable!("the peeked entry is an error"),
+            },
+            None => Ok(None),
+        }
+    }
+
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    loop {
+        let (key, in_a, in_b) = match (peek_key(&mut a)?, peek_key(&mut b)?) {
+            (None, None) => return Ok(()),
+            (Some(key_a), Some(key_b)) if key_a == key_b => (key_a, true, true),
+            (Some(key_a), Some(key_b)) if key_a < key_b => (key_a, true, false),
+            (Some(key_a), None) => (key_a, true, false),
+            (_, Some(key_b)) => (key_b, false, true),
+        };
+        let old = match in_a {
+            true => a.next().transpose()?.map(|(_, value)| value),
+            false => None,
+        };
+        let new = match in_b {
+            true => b.next().transpose()?.map(|(_, value)| value),
+            false => None,
+        };
+        visit(key, old, new)?;
+    }
+}
+
+/// Whether two items have the same properties, regardless of their order. Values must have
+/// the same type as well, so a number stored with another width counts as a change.
+fn same_properties(
+    a: &Option<ImmutablePropertiesMap<'_>>,
+    b: &Option<ImmutablePropertiesMap<'_>>,
+) -> bool {
+    fn sorted<'a>(properties: &Option<ImmutablePropertiesMap<'a>>) -> Vec<(&'a str, &'a Value)> {
+        let mut properties = properties
+            .iter()
+            .flat_map(|properties| properties.iter())
+            .collect::<Vec<_>>();
+        properties.sort_unstable_by_key(|(key, _)| *key);
+        properties
+    }
+    let same_value =
+        |a: &Value, b: &Value| std::mem::discriminant(a) == std::mem::discriminant(b) && a == b;
+    let (a, b) = (sorted(a), sorted(b));
+    a.len() == b.len()
+        && a.iter()
+            .zip(&b)
+            .all(|((key_a, a), (key_b, b))| key_a == key_b && same_value(a, b))
+}
@@ -3,8 +3,11 @@ use crate::commands::integrations::ecr::EcrManager;
 use crate::commands::integrations::fly::FlyManager;
 use crate::config::InstanceInfo;
 use crate::docker::DockerManager;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step};
+use crate::port;
 use crate::project::ProjectContext;
+use crate::registry;
 use crate::utils::{print_confirm, print_lines, print_newline, print_warning};
 use eyre::Result;
 
@@ -57,6 +60,9 @@ pub async fn run(instance_name: String) -> Result<()> {
         docker_step.done();
     }
 
+    // Stop a native process, which runs from the workspace
+    NativeManager::new(&project).stop_instance(&instance_name)?;
+
     // Remove instance workspace
     let workspace = project.instance_workspace(&instance_name);
     if workspace.exists() {
@@ -93,7 +99,10 @@ pub async fn run(instance_name: String) -> Result<()> {
             todo!()
         }
         InstanceInfo::Local(_) => {
-            // Local instances don't have cloud resources to delete
+            // Local instances don't have cloud resources to delete, only a reserved port and
+            // their entry in the instance registry
+            port::release_port(&project.root, &instance_name);
+            registry::forget_instance(&project.root, &instance_name);
         }
     }
 
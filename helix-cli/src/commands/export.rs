@@ -0,0 +1,735 @@
+//! `helix export` command for writing a local instance's graph to GraphML or GEXF, so it can be
+//! opened in Gephi, NetworkX and other graph tools or re-imported with `helix import`, or to
+//! Parquet tables for Spark, DuckDB and warehouses. `helix export subgraph` writes the subgraph a
+//! query returns with `::SUBGRAPH` instead, from a running instance, and `helix export walks`
+//! the random walks its `/admin/walks` takes for training node embeddings.
+
+use crate::commands::import::{Dump, DumpNode, DumpRelationship, Properties, gexf, graphml};
+use crate::commands::replay::target_url;
+use crate::errors::CliError;
+use crate::location::Location;
+use crate::output::{Operation, Step, Verbosity};
+use crate::project::ProjectContext;
+use crate::utils::print_warning;
+use bumpalo::Bump;
+use eyre::{Result, eyre};
+use heed3::byteorder::BE;
+use heed3::types::{Bytes, U128};
+use heed3::{Database, EnvFlags, EnvOpenOptions};
+use helix_db::helix_engine::graph::random_walk::WalkConfig;
+use helix_db::protocol::parquet;
+use helix_db::protocol::value::Value;
+use helix_db::utils::items::{Edge, Node};
+use helix_db::utils::properties::ImmutablePropertiesMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Items decoded between resets of the allocation arena
+const EXPORT_BATCH_SIZE: usize = 10_000;
+
+/// Counts reported once an export finishes
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub nodes: usize,
+    pub relationships: usize,
+}
+
+/// How an export is written
+enum Target {
+    /// One graph file, GraphML or GEXF
+    Graph(fn(&Dump) -> String),
+    /// The Parquet table of one node label or edge type
+    Table(String),
+    /// A directory with a Parquet table per node label (`nodes/<Label>.parquet`) and edge type
+    /// (`edges/<Type>.parquet`)
+    Tables,
+}
+
+pub async fn run(instance: String, output: String, label: Option<String>) -> Result<()> {
+    let project = ProjectContext::find_and_load(None)?;
+    let remote = match Location::parse(&output)? {
+        Location::Local(output) => {
+            return export(&project, &instance, &output, label.as_deref()).map(|_| ());
+        }
+        Location::Remote(remote) => remote,
+    };
+
+    // Exports to object storage are written to a temporary directory, in the format the
+    // URL's extension names, and uploaded from there
+    let staging = tempfile::tempdir()?;
+    let local = staging.path().join(remote.file_name().unwrap_or("export"));
+    export(&project, &instance, &local, label.as_deref())?;
+    let mut upload_step = Step::with_messages("Uploading export", "Export uploaded");
+    upload_step.start();
+    match remote.upload(&local).await {
+        Ok(_) => {
+            upload_step.done_with_info(&remote.to_string());
+            Ok(())
+        }
+        Err(e) => {
+            upload_step.fail();
+            Err(e)
+        }
+    }
+}
+
+/// Export the nodes and edges of a local instance to `output`, in the format its extension names.
+/// A `.parquet` file holds the table of `label`, and an output without an extension is a
+/// directory of Parquet tables, of `label` only when it's given.
+pub fn export(
+    project: &ProjectContext,
+    instance_name: &str,
+    output: &Path,
+    label: Option<&str>,
+) -> Result<ExportSummary> {
+    let instance = project.config.get_instance(instance_name)?;
+    if !instance.is_local() {
+        let error = CliError::new(format!(
+            "instance '{instance_name}' is not a local instance"
+        ))
+        .with_hint("pull or back up its data into a local instance, then export that");
+        return Err(eyre!("{}", error.render()));
+    }
+    let extension = output
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let target = match (extension.as_deref(), label) {
+        (Some("graphml"), None) => Target::Graph(graphml::write),
+        (Some("gexf"), None) => Target::Graph(gexf::write),
+        (Some("graphml" | "gexf"), Some(_)) => {
+            let error = CliError::new("--label only applies to Parquet exports")
+                .with_hint("export the whole graph, or a .parquet table of the label");
+            return Err(eyre!("{}", error.render()));
+        }
+        (Some("parquet"), Some(label)) => Target::Table(label.to_string()),
+        (Some("parquet"), None) => {
+            let error = CliError::new("a Parquet file holds the table of a single label")
+                .with_hint("name it with --label, or export to a directory for every label");
+            return Err(eyre!("{}", error.render()));
+        }
+        (None, _) => Target::Tables,
+        _ => {
+            let error = CliError::new(format!(
+                "can't tell which format to export {} in",
+                output.display()
+            ))
+            .with_hint("name the output file .graphml, .gexf or .parquet, or give a directory");
+            return Err(eyre!("{}", error.render()));
+        }
+    };
+
+    let path = project.instance_volume(instance_name).join("user");
+    if !path.join("data.mdb").exists() {
+        let error = CliError::new(format!("instance '{instance_name}' has no data yet"))
+            .with_hint(format!("nothing was found at {}", path.display()));
+        return Err(eyre!("{}", error.render()));
+    }
+
+    let op = Operation::new("Exporting", instance_name);
+    let mut read_step = Step::with_messages("Reading graph", "Graph read");
+    read_step.start();
+    let (dump, dangling) = match read_graph(&path) {
+        Ok(read) => read,
+        Err(e) => {
+            read_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    read_step.done_with_info(&format!(
+        "{} nodes, {} relationships",
+        dump.nodes.len(),
+        dump.relationships.len()
+    ));
+    if dangling > 0 {
+        print_warning(&format!(
+            "skipped {dangling} edges whose nodes no longer exist"
+        ));
+    }
+
+    let mut write_step = Step::with_messages("Writing export", "Export written");
+    write_step.start();
+    let written = match target {
+        Target::Graph(write) => fs::write(output, write(&dump))
+            .map_err(|e| eyre!("Failed to write {}: {e}", output.display()))
+            .map(|_| ExportSummary {
+                nodes: dump.nodes.len(),
+                relationships: dump.relationships.len(),
+            }),
+        Target::Table(label) => write_table(&dump, &label, output),
+        Target::Tables => write_tables(&dump, label, output),
+    };
+    let summary = match written {
+        Ok(summary) => summary,
+        Err(e) => {
+            write_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    write_step.done();
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        let nodes = summary.nodes.to_string();
+        let relationships = summary.relationships.to_string();
+        let output = output.display().to_string();
+        Operation::print_details(&[
+            ("Nodes", nodes.as_str()),
+            ("Relationships", relationships.as_str()),
+            ("Output", output.as_str()),
+        ]);
+    }
+    Ok(summary)
+}
+
+/// The nodes and edges of the `::SUBGRAPH` values in a query's response, as the instance
+/// serializes them: a node is its `id`, `label` and `version` with its properties, and an edge
+/// also has the ids of its `from_node` and `to_node`
+#[derive(Debug, Default, PartialEq)]
+pub struct Subgraph {
+    pub nodes: Vec<serde_json::Value>,
+    pub edges: Vec<serde_json::Value>,
+}
+
+impl Subgraph {
+    /// Collect the `{"nodes": [...], "edges": [...]}` objects anywhere in `response`, merging
+    /// those of several returned subgraphs. Nodes and edges in more than one are kept once.
+    pub fn from_response(response: &serde_json::Value) -> Option<Subgraph> {
+        fn collect(value: &serde_json::Value, found: &mut Vec<Subgraph>) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    if let (Some(nodes), Some(edges), 2) = (
+                        map.get("nodes").and_then(|nodes| nodes.as_array()),
+                        map.get("edges").and_then(|edges| edges.as_array()),
+                        map.len(),
+                    ) {
+                        found.push(Subgraph {
+                            nodes: nodes.clone(),
+                            edges: edges.clone(),
+                        });
+                    } else {
+                        map.values().for_each(|value| collect(value, found));
+                    }
+                }
+                serde_json::Value::Array(values) => {
+                    values.iter().for_each(|value| collect(value, found))
+                }
+                _ => {}
+            }
+        }
+
+        let mut found = Vec::new();
+        collect(response, &mut found);
+        if found.is_empty() {
+            return None;
+        }
+        let mut seen = std::collections::HashSet::new();
+        let mut merged = Subgraph::default();
+        for subgraph in found {
+            for (items, merged) in [
+                (subgraph.nodes, &mut merged.nodes),
+                (subgraph.edges, &mut merged.edges),
+            ] {
+                for item in items {
+                    let id = item
+                        .get("id")
+                        .and_then(|id| id.as_str())
+                        .map(str::to_string);
+                    if id.is_none_or(|id| seen.insert(id)) {
+                        merged.push(item);
+                    }
+                }
+            }
+        }
+        Some(merged)
+    }
+
+    /// The subgraph as a dump for the graph file writers, with the nodes and edges labeled by
+    /// their `label` and their other fields as properties
+    pub fn to_dump(&self) -> Result<Dump> {
+        fn fields(
+            item: &serde_json::Value,
+            reserved: &[&str],
+        ) -> Result<(Option<String>, String, Properties)> {
+            let object = item
+                .as_object()
+                .ok_or_else(|| eyre!("expected a node or edge object, got {item}"))?;
+            let text = |key: &str| object.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let label = text("label").ok_or_else(|| eyre!("{item} has no label"))?;
+            let properties = object
+                .iter()
+                .filter(|(key, _)| !reserved.contains(&key.as_str()))
+                .map(|(key, value)| {
+                    serde_json::from_value::<Value>(value.clone())
+                        .map(|value| (key.clone(), value))
+                        .map_err(|e| eyre!("Failed to read property {key} of {item}: {e}"))
+                })
+                .collect::<Result<_>>()?;
+            Ok((text("id"), label, properties))
+        }
+
+        let mut dump = Dump::default();
+        let mut index = HashMap::new();
+        for node in &self.nodes {
+            let (id, label, properties) = fields(node, &["id", "label", "version"])?;
+            if let Some(id) = &id {
+                index.insert(id.clone(), dump.nodes.len());
+            }
+            dump.nodes.push(DumpNode {
+                id,
+                labels: vec![label],
+                properties,
+            });
+        }
+        for edge in &self.edges {
+            let (id, rel_type, properties) =
+                fields(edge, &["id", "label", "version", "from_node", "to_node"])?;
+            let end = |key: &str| {
+                edge.get(key)
+                    .and_then(|id| id.as_str())
+                    .and_then(|id| index.get(id).copied())
+                    .ok_or_else(|| eyre!("the {key} of edge {edge} isn't a node of the subgraph"))
+            };
+            dump.relationships.push(DumpRelationship {
+                id,
+                rel_type,
+                start: end("from_node")?,
+                end: end("to_node")?,
+                properties,
+            });
+        }
+        Ok(dump)
+    }
+
+    /// Write the subgraph to `output`, as JSON or GraphML by its extension
+    pub fn write(&self, output: &Path) -> Result<()> {
+        let extension = output
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        let contents = match extension.as_deref() {
+            Some("json") => serde_json::to_string_pretty(&serde_json::json!({
+                "nodes": self.nodes,
+                "edges": self.edges,
+            }))?,
+            Some("graphml") => graphml::write(&self.to_dump()?),
+            _ => {
+                let error = CliError::new(format!(
+                    "can't tell which format to export {} in",
+                    output.display()
+                ))
+                .with_hint("name the output file .json or .graphml");
+                return Err(eyre!("{}", error.render()));
+            }
+        };
+        fs::write(output, contents).map_err(|e| eyre!("Failed to write {}: {e}", output.display()))
+    }
+}
+
+/// Run `query` on the instance at `url` with `params` as its body and collect the subgraph
+/// its response holds
+pub async fn fetch_subgraph(
+    url: &str,
+    query: &str,
+    params: &serde_json::Value,
+    api_key: Option<&str>,
+) -> Result<Subgraph> {
+    let client = reqwest::Client::new();
+    let mut request = client.post(format!("{url}/{query}")).json(params);
+    if let Some(api_key) = api_key {
+        request = request.header("x-api-key", api_key).bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| eyre!("Failed to reach {url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!("{query} answered {status}: {body}"));
+    }
+    let response: serde_json::Value = response.json().await?;
+    Subgraph::from_response(&response).ok_or_else(|| {
+        let error = CliError::new(format!("{query} didn't return a subgraph"))
+            .with_hint("return a traversal ending in ::SUBGRAPH(depth: n) from the query");
+        eyre!("{}", error.render())
+    })
+}
+
+pub async fn run_subgraph(
+    target: String,
+    query: String,
+    params: Option<String>,
+    output: String,
+) -> Result<()> {
+    let url = target_url(&target)?;
+    let params = params
+        .map(|params| serde_json::from_str(&params))
+        .transpose()
+        .map_err(|e| eyre!("--params is not valid JSON: {e}"))?
+        .unwrap_or_else(|| serde_json::json!({}));
+    let output = Path::new(&output);
+
+    let op = Operation::new("Exporting", &query);
+    let mut query_step = Step::with_messages("Running query", "Query run");
+    query_step.start();
+    let api_key = std::env::var("HELIX_API_KEY").ok();
+    let subgraph = match fetch_subgraph(&url, &query, &params, api_key.as_deref()).await {
+        Ok(subgraph) => subgraph,
+        Err(e) => {
+            query_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    query_step.done_with_info(&format!(
+        "{} nodes, {} relationships",
+        subgraph.nodes.len(),
+        subgraph.edges.len()
+    ));
+
+    let mut write_step = Step::with_messages("Writing export", "Export written");
+    write_step.start();
+    if let Err(e) = subgraph.write(output) {
+        write_step.fail();
+        op.failure();
+        return Err(e);
+    }
+    write_step.done();
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        let nodes = subgraph.nodes.len().to_string();
+        let relationships = subgraph.edges.len().to_string();
+        let output = output.display().to_string();
+        Operation::print_details(&[
+            ("Nodes", nodes.as_str()),
+            ("Relationships", relationships.as_str()),
+            ("Output", output.as_str()),
+        ]);
+    }
+    Ok(())
+}
+
+/// Random walks, or the co-occurring pairs of nodes along them, as rows of node ids
+#[derive(Debug, Default, PartialEq)]
+pub struct Walks {
+    /// The rows are pairs rather than walks
+    pub pairs: bool,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Walks {
+    /// Read an `/admin/walks` response, `{"walks": [...]}` or `{"pairs": [...]}`
+    pub fn from_response(response: &serde_json::Value) -> Option<Walks> {
+        let (pairs, rows) = match (response.get("walks"), response.get("pairs")) {
+            (Some(rows), None) => (false, rows),
+            (None, Some(rows)) => (true, rows),
+            _ => return None,
+        };
+        let rows = rows
+            .as_array()?
+            .iter()
+            .map(|row| {
+                row.as_array()?
+                    .iter()
+                    .map(|id| id.as_str().map(str::to_string))
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+        Some(Walks { pairs, rows })
+    }
+
+    /// Write the rows to `output` by its extension: a line of space separated ids per row for
+    /// .txt, as word2vec style trainers read a corpus, comma separated for .csv, or JSON
+    pub fn write(&self, output: &Path) -> Result<()> {
+        let extension = output
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        let lines = |separator: &str| {
+            self.rows
+                .iter()
+                .map(|row| row.join(separator) + "\n")
+                .collect::<String>()
+        };
+        let contents = match extension.as_deref() {
+            Some("txt") => lines(" "),
+            Some("csv") => match self.pairs {
+                true => format!("node,context\n{}", lines(",")),
+                false => lines(","),
+            },
+            Some("json") => serde_json::to_string_pretty(&match self.pairs {
+                true => serde_json::json!({ "pairs": self.rows }),
+                false => serde_json::json!({ "walks": self.rows }),
+            })?,
+            _ => {
+                let error = CliError::new(format!(
+                    "can't tell which format to export {} in",
+                    output.display()
+                ))
+                .with_hint("name the output file .txt, .csv or .json");
+                return Err(eyre!("{}", error.render()));
+            }
+        };
+        fs::write(output, contents).map_err(|e| eyre!("Failed to write {}: {e}", output.display()))
+    }
+}
+
+/// Take random walks on the instance at `url` through its `/admin/walks`, or the pairs of
+/// nodes within `window` steps of each other along them
+pub async fn fetch_walks(
+    url: &str,
+    config: &WalkConfig,
+    window: Option<usize>,
+    api_key: Option<&str>,
+) -> Result<Walks> {
+    let mut body = serde_json::to_value(config)?;
+    if let Some(window) = window {
+        body["window"] = window.into();
+    }
+    let client = reqwest::Client::new();
+    let mut request = client.post(format!("{url}/admin/walks")).json(&body);
+    if let Some(api_key) = api_key {
+        request = request.header("x-api-key", api_key).bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| eyre!("Failed to reach {url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!("Walking the graph failed with {status}: {body}"));
+    }
+    let response: serde_json::Value = response.json().await?;
+    Walks::from_response(&response).ok_or_else(|| eyre!("Unexpected walks response: {response}"))
+}
+
+pub async fn run_walks(
+    target: String,
+    output: String,
+    config: WalkConfig,
+    window: Option<usize>,
+) -> Result<()> {
+    let url = target_url(&target)?;
+    let output = Path::new(&output);
+
+    let op = Operation::new("Exporting", "random walks");
+    let mut walk_step = Step::with_messages("Walking graph", "Graph walked");
+    walk_step.start();
+    let api_key = std::env::var("HELIX_API_KEY").ok();
+    let walks = match fetch_walks(&url, &config, window, api_key.as_deref()).await {
+        Ok(walks) => walks,
+        Err(e) => {
+            walk_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    let kind = match walks.pairs {
+        true => "Pairs",
+        false => "Walks",
+    };
+    walk_step.done_with_info(&format!("{} {}", walks.rows.len(), kind.to_lowercase()));
+
+    let mut write_step = Step::with_messages("Writing export", "Export written");
+    write_step.start();
+    if let Err(e) = walks.write(output) {
+        write_step.fail();
+        op.failure();
+        return Err(e);
+    }
+    write_step.done();
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        let rows = walks.rows.len().to_string();
+        let output = output.display().to_string();
+        Operation::print_details(&[(kind, rows.as_str()), ("Output", output.as_str())]);
+    }
+    Ok(())
+}
+
+type Row = Vec<(String, Value)>;
+
+/// Rows of a node label's or edge type's Parquet table, as `/admin/export` writes them: the
+/// item's id, the ids of an edge's nodes, then its properties
+fn table_rows(dump: &Dump, ids: &[String], label: &str) -> (Vec<Row>, Vec<Row>) {
+    let id = |id: &str| Value::String(id.to_string());
+    let nodes = dump
+        .nodes
+        .iter()
+        .zip(ids)
+        .filter(|(node, _)| node.labels.iter().any(|l| l == label))
+        .map(|(node, node_id)| {
+            let mut row = vec![("id".to_string(), id(node_id))];
+            row.extend(node.properties.iter().cloned());
+            row
+        })
+        .collect();
+    let edges = dump
+        .relationships
+        .iter()
+        .enumerate()
+        .filter(|(_, relationship)| relationship.rel_type == label)
+        .map(|(i, relationship)| {
+            let edge_id = relationship.id.clone().unwrap_or_else(|| format!("e{i}"));
+            let mut row = vec![
+                ("id".to_string(), id(&edge_id)),
+                ("from_node".to_string(), id(&ids[relationship.start])),
+                ("to_node".to_string(), id(&ids[relationship.end])),
+            ];
+            row.extend(relationship.properties.iter().cloned());
+            row
+        })
+        .collect();
+    (nodes, edges)
+}
+
+fn write_parquet(rows: &[Row], path: &Path) -> Result<()> {
+    let encoded = parquet::encode_rows(rows)
+        .map_err(|e| eyre!("Failed to encode {}: {e}", path.display()))?;
+    fs::write(path, encoded).map_err(|e| eyre!("Failed to write {}: {e}", path.display()))
+}
+
+/// Write the table of one node label or edge type
+fn write_table(dump: &Dump, label: &str, output: &Path) -> Result<ExportSummary> {
+    let (nodes, edges) = table_rows(dump, &dump.node_ids(), label);
+    let rows = match (nodes.is_empty(), edges.is_empty()) {
+        (false, true) => &nodes,
+        (true, false) => &edges,
+        (false, false) => {
+            return Err(eyre!(
+                "{label} is both a node label and an edge type, export to a directory to get both tables"
+            ));
+        }
+        (true, true) => return Err(eyre!("no nodes or edges are labeled {label}")),
+    };
+    write_parquet(rows, output)?;
+    Ok(ExportSummary {
+        nodes: nodes.len(),
+        relationships: edges.len(),
+    })
+}
+
+/// Write a table per node label and edge type, or only those of `label`
+fn write_tables(dump: &Dump, label: Option<&str>, output: &Path) -> Result<ExportSummary> {
+    let mut node_labels = dump
+        .nodes
+        .iter()
+        .flat_map(|node| node.labels.iter().map(String::as_str))
+        .collect::<Vec<_>>();
+    let mut edge_types = dump
+        .relationships
+        .iter()
+        .map(|relationship| relationship.rel_type.as_str())
+        .collect::<Vec<_>>();
+    for labels in [&mut node_labels, &mut edge_types] {
+        labels.sort_unstable();
+        labels.dedup();
+        labels.retain(|l| label.is_none_or(|label| *l == label));
+    }
+    if let Some(label) = label
+        && node_labels.is_empty()
+        && edge_types.is_empty()
+    {
+        return Err(eyre!("no nodes or edges are labeled {label}"));
+    }
+
+    let mut summary = ExportSummary::default();
+    let ids = dump.node_ids();
+    for (kind, labels) in [("nodes", &node_labels), ("edges", &edge_types)] {
+        if labels.is_empty() {
+            continue;
+        }
+        let dir = output.join(kind);
+        fs::create_dir_all(&dir).map_err(|e| eyre!("Failed to create {}: {e}", dir.display()))?;
+        for label in labels {
+            let (nodes, edges) = table_rows(dump, &ids, label);
+            let rows = match kind {
+                "nodes" => {
+                    summary.nodes += nodes.len();
+                    nodes
+                }
+                _ => {
+                    summary.relationships += edges.len();
+                    edges
+                }
+            };
+            write_parquet(&rows, &dir.join(format!("{label}.parquet")))?;
+        }
+    }
+    Ok(summary)
+}
+
+/// Read every node and edge of an instance's storage, with the number of edges skipped because
+/// an endpoint is missing. Vectors aren't part of the graph formats and are left out.
+fn read_graph(path: &Path) -> Result<(Dump, usize)> {
+    // SAFETY: the environment is opened read-only, so a running instance's writes aren't affected
+    let env = unsafe {
+        EnvOpenOptions::new()
+            .flags(EnvFlags::READ_ONLY)
+            .max_dbs(200)
+            .max_readers(200)
+            .open(path)?
+    };
+    let txn = env.read_txn()?;
+    let database = |name: &str| -> Result<Database<U128<BE>, Bytes>> {
+        env.open_database(&txn, Some(name))?
+            .ok_or_else(|| eyre!("{} has no {name} database", path.display()))
+    };
+    let nodes_db = database("nodes")?;
+    let edges_db = database("edges")?;
+
+    let mut dump = Dump::default();
+    let mut index = HashMap::new();
+    let mut arena = Bump::new();
+    for (i, entry) in nodes_db.iter(&txn)?.enumerate() {
+        let (id, bytes) = entry?;
+        let node = Node::from_bincode_bytes(id, bytes, &arena)
+            .map_err(|e| eyre!("Failed to read node {}: {e}", Uuid::from_u128(id)))?;
+        index.insert(id, dump.nodes.len());
+        dump.nodes.push(DumpNode {
+            id: Some(Uuid::from_u128(id).to_string()),
+            labels: vec![node.label.to_string()],
+            properties: owned(node.properties),
+        });
+        if (i + 1) % EXPORT_BATCH_SIZE == 0 {
+            arena.reset();
+        }
+    }
+
+    let mut dangling = 0;
+    for (i, entry) in edges_db.iter(&txn)?.enumerate() {
+        let (id, bytes) = entry?;
+        let edge = Edge::from_bincode_bytes(id, bytes, &arena)
+            .map_err(|e| eyre!("Failed to read edge {}: {e}", Uuid::from_u128(id)))?;
+        match (index.get(&edge.from_node), index.get(&edge.to_node)) {
+            (Some(&start), Some(&end)) => dump.relationships.push(DumpRelationship {
+                id: Some(Uuid::from_u128(id).to_string()),
+                rel_type: edge.label.to_string(),
+                start,
+                end,
+                properties: owned(edge.properties),
+            }),
+            _ => dangling += 1,
+        }
+        if (i + 1) % EXPORT_BATCH_SIZE == 0 {
+            arena.reset();
+        }
+    }
+    Ok((dump, dangling))
+}
+
+fn owned(properties: Option<ImmutablePropertiesMap<'_>>) -> Properties {
+    properties
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,194 @@
+//! `helix fsck` command for checking that an instance's tables agree with each other.
+//!
+//! Edges should join stored nodes, and adjacency lists, secondary indices, the HNSW graph and
+//! BM25 postings should match the records they index. Nodes should have the edges the
+//! schema's edge constraints require. A local instance's data is opened directly, so stop the
+//! instance before checking it; a running instance is checked through its `/admin/fsck`
+//! endpoint by passing its URL. `--repair` fixes what can be rebuilt from the primary
+//! records, such as dropping edges to deleted nodes and re-indexing nodes.
+
+use crate::commands::import::{
+    acyclic_edges, edge_constraints, materialized_counts, project_schema, secondary_indices,
+    storage_config,
+};
+use crate::errors::CliError;
+use crate::output::{Operation, Step, Verbosity};
+use crate::project::ProjectContext;
+use crate::utils::{print_field, print_header, print_lines, print_newline};
+use eyre::{Result, eyre};
+use helix_db::helix_engine::storage_core::fsck::{self, FsckReport, IssueKind};
+use helix_db::helix_engine::storage_core::{HelixGraphStorage, version_info::VersionInfo};
+use std::collections::BTreeMap;
+
+/// Issues listed per kind before the rest are summarized
+const ISSUES_SHOWN: usize = 5;
+
+pub async fn run(target: String, repair: bool, json: bool) -> Result<()> {
+    if json {
+        // Progress output would get mixed into the JSON
+        Verbosity::set(Verbosity::Quiet);
+    }
+    let op = Operation::new(if repair { "Repairing" } else { "Checking" }, &target);
+    let mut scan_step = Step::with_messages("Checking tables", "Tables checked");
+    scan_step.start();
+    let online = target.starts_with("http://") || target.starts_with("https://");
+    let report = match online {
+        true => check_online(&target, repair).await,
+        false => ProjectContext::find_and_load(None)
+            .and_then(|project| check_offline(&project, &target, repair)),
+    };
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            scan_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    scan_step.done_with_info(&format!("{} issues", report.issues.len()));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if Verbosity::current().show_normal() {
+        print_report(&report);
+    }
+
+    if report.is_consistent() {
+        op.success();
+        return Ok(());
+    }
+    op.failure();
+    let remaining = report.issues.len() - report.repaired();
+    let repairable = report
+        .issues
+        .iter()
+        .filter(|issue| issue.repairable && !issue.repaired)
+        .count();
+    let hint = if repairable > 0 {
+        format!("run `helix fsck {target} --repair` to fix {repairable} of them")
+    } else {
+        "these can't be fixed without losing data; restore a backup or re-insert the items"
+            .to_string()
+    };
+    let error = CliError::new(format!("{remaining} consistency issues remain")).with_hint(hint);
+    Err(eyre!("{}", error.render()))
+}
+
+/// Check a local instance's data in place
+pub(crate) fn check_offline(
+    project: &ProjectContext,
+    instance_name: &str,
+    repair: bool,
+) -> Result<FsckReport> {
+    let instance = project.config.get_instance(instance_name)?;
+    if !instance.is_local() {
+        let error = CliError::new(format!("can't open the data of instance '{instance_name}'"))
+            .with_hint("pass the instance's URL to check it through its admin API");
+        return Err(eyre!("{}", error.render()));
+    }
+    let path = project.instance_volume(instance_name).join("user");
+    if !path.join("data.mdb").exists() {
+        return Err(eyre!(
+            "Instance '{instance_name}' has no data at {}",
+            path.display()
+        ));
+    }
+    // Indices and constraints are declared by the schema, so they're only checked when it can
+    // be read
+    let (indices, counts, constraints, acyclic) = match project_schema(project)? {
+        Some(source) => {
+            let schema = source
+                .get_latest_schema()
+                .map_err(|e| eyre!("Failed to read schema: {e}"))?;
+            (
+                secondary_indices(schema),
+                materialized_counts(schema),
+                edge_constraints(schema),
+                acyclic_edges(schema),
+            )
+        }
+        None => (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+    };
+    let storage = HelixGraphStorage::new(
+        &path.display().to_string(),
+        storage_config(&instance, indices, counts, constraints, acyclic),
+        VersionInfo::default(),
+    )
+    .map_err(|e| eyre!("Failed to open instance data at {}: {e}", path.display()))?;
+    let report = match repair {
+        true => fsck::repair(&storage),
+        false => fsck::check(&storage),
+    };
+    report.map_err(|e| eyre!("Failed to check instance data: {e}"))
+}
+
+/// Check a running instance through its admin API
+async fn check_online(url: &str, repair: bool) -> Result<FsckReport> {
+    let client = reqwest::Client::new();
+    let url = url.trim_end_matches('/');
+    let mut request = match repair {
+        true => client.post(format!("{url}/admin/fsck/repair")),
+        false => client.get(format!("{url}/admin/fsck")),
+    };
+    if let Ok(api_key) = std::env::var("HELIX_API_KEY") {
+        request = request.header("x-api-key", &api_key).bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| eyre!("Failed to reach {url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!("{url} answered {status}: {body}"));
+    }
+    Ok(response.json().await?)
+}
+
+fn print_report(report: &FsckReport) {
+    print_newline();
+    print_header("Checked:");
+    for (name, count) in [
+        ("Nodes", report.nodes),
+        ("Edges", report.edges),
+        ("Vectors", report.vectors),
+        ("Index entries", report.index_entries),
+        ("HNSW links", report.hnsw_edges),
+        ("BM25 postings", report.postings),
+    ] {
+        print_field(name, &count.to_string());
+    }
+    if report.issues.is_empty() {
+        return;
+    }
+
+    let mut by_kind: BTreeMap<IssueKind, Vec<_>> = BTreeMap::new();
+    for issue in &report.issues {
+        by_kind.entry(issue.kind).or_default().push(issue);
+    }
+    print_newline();
+    print_header("Issues:");
+    for (kind, issues) in by_kind {
+        let repaired = issues.iter().filter(|issue| issue.repaired).count();
+        let mut summary = issues.len().to_string();
+        if repaired > 0 {
+            summary.push_str(&format!(", {repaired} repaired"));
+        } else if !issues[0].repairable {
+            summary.push_str(", not repairable");
+        }
+        print_field(kind.description(), &summary);
+        let shown = match Verbosity::current().show_verbose() {
+            true => issues.len(),
+            false => ISSUES_SHOWN,
+        };
+        let mut lines = issues
+            .iter()
+            .take(shown)
+            .map(|issue| format!("  {} {}", issue.id, issue.detail))
+            .collect::<Vec<_>>();
+        if issues.len() > shown {
+            lines.push(format!("  ... and {} more", issues.len() - shown));
+        }
+        print_lines(&lines.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+}
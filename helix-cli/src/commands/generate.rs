@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::PathBuf;
+
+use eyre::Result;
+use helix_db::helixc::generator::{Source as GeneratedSource, docs::DocsFormat};
+use helix_db::helixc::parser::schema_diagram::{DiagramFormat, schema_diagram};
+
+use crate::{
+    GenerateAction,
+    errors::CliError,
+    output::{Operation, Step},
+    project::ProjectContext,
+    utils::helixc_utils::{analyze_source, collect_hx_files, generate_content, parse_content},
+};
+
+/// Directory schemas are written to when no output is given, relative to the project root
+const DEFAULT_SCHEMA_DIR: &str = "schemas";
+
+/// Directory docs are written to when no output is given, relative to the project root
+const DEFAULT_DOCS_DIR: &str = "docs";
+
+pub async fn run(action: GenerateAction) -> Result<()> {
+    match action {
+        GenerateAction::JsonSchema { path, output } => json_schema(path, output),
+        GenerateAction::Docs {
+            path,
+            output,
+            format,
+            url,
+        } => docs(path, output, format, &url),
+        GenerateAction::SchemaDiagram {
+            path,
+            output,
+            format,
+        } => diagram(path, output, format),
+    }
+}
+
+/// Load the project and parse and analyze its queries
+fn analyze_project(path: Option<String>) -> Result<(ProjectContext, GeneratedSource)> {
+    let project = ProjectContext::find_and_load(path.map(PathBuf::from).as_deref())?;
+
+    let mut parse_step = Step::with_messages("Parsing queries", "Queries parsed");
+    parse_step.start();
+    let hx_files = collect_hx_files(&project.root, &project.config.project.queries)?;
+    let content = generate_content(&hx_files)?;
+    let source = parse_content(&content)?;
+    parse_step.done_with_info(&format!("{} queries", source.queries.len()));
+
+    let mut analyze_step = Step::with_messages("Analyzing", "Analysis complete");
+    analyze_step.start();
+    let generated_source = analyze_source(source, &content.files)?;
+    analyze_step.done();
+
+    Ok((project, generated_source))
+}
+
+/// Write `<query>.input.json` and `<query>.output.json` for every query of the project
+fn json_schema(path: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    let op = Operation::new("Generating", "JSON Schema");
+
+    let (project, generated_source) = analyze_project(path)?;
+
+    let mut write_step = Step::with_messages("Writing schemas", "Schemas written");
+    write_step.start();
+    let output = output.unwrap_or_else(|| project.root.join(DEFAULT_SCHEMA_DIR));
+    let schemas = generated_source.to_json_schemas();
+    let written = fs::create_dir_all(&output).and_then(|()| {
+        for schema in &schemas {
+            for (suffix, document) in [("input", &schema.input), ("output", &schema.output)] {
+                let json = serde_json::to_string_pretty(document)?;
+                fs::write(output.join(format!("{}.{suffix}.json", schema.query)), json)?;
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        write_step.fail();
+        op.failure();
+        return Err(eyre::eyre!(
+            "Failed to write JSON Schema to {}: {e}",
+            output.display()
+        ));
+    }
+    write_step.done_with_info(&format!(
+        "{} files in {}",
+        schemas.len() * 2,
+        output.display()
+    ));
+
+    op.success();
+    Ok(())
+}
+
+/// Write an index page and a page per query of the project
+fn docs(
+    path: Option<String>,
+    output: Option<PathBuf>,
+    format: DocsFormat,
+    url: &str,
+) -> Result<()> {
+    let op = Operation::new("Generating", "query docs");
+
+    let (project, generated_source) = analyze_project(path)?;
+
+    let mut write_step = Step::with_messages("Writing docs", "Docs written");
+    write_step.start();
+    let output = output.unwrap_or_else(|| project.root.join(DEFAULT_DOCS_DIR));
+    let pages = generated_source.to_docs(format, url);
+    let written = fs::create_dir_all(&output).and_then(|()| {
+        for page in &pages {
+            fs::write(output.join(&page.file_name), &page.content)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        write_step.fail();
+        op.failure();
+        return Err(eyre::eyre!(
+            "Failed to write docs to {}: {e}",
+            output.display()
+        ));
+    }
+    write_step.done_with_info(&format!("{} pages in {}", pages.len(), output.display()));
+
+    op.success();
+    Ok(())
+}
+
+/// Write a diagram of the project's latest schema, to stdout unless an output file is given
+fn diagram(path: Option<String>, output: Option<PathBuf>, format: DiagramFormat) -> Result<()> {
+    let project = ProjectContext::find_and_load(path.map(PathBuf::from).as_deref())?;
+
+    let hx_files = collect_hx_files(&project.root, &project.config.project.queries)?;
+    let content = generate_content(&hx_files)?;
+    let source = parse_content(&content)?;
+    let Ok(schema) = source.get_latest_schema() else {
+        let error = CliError::new("no schema definitions found in project").with_hint(
+            "add at least one schema definition like 'N::User { name: String }' to your .hx files",
+        );
+        return Err(eyre::eyre!("{}", error.render()));
+    };
+    let diagram = schema_diagram(schema, format);
+
+    // Progress output would get mixed into a diagram written to stdout
+    let Some(output) = output else {
+        print!("{diagram}");
+        return Ok(());
+    };
+    let op = Operation::new("Generating", "schema diagram");
+    let mut write_step = Step::with_messages("Writing diagram", "Diagram written");
+    write_step.start();
+    if let Err(e) = fs::write(&output, diagram) {
+        write_step.fail();
+        op.failure();
+        return Err(eyre::eyre!(
+            "Failed to write diagram to {}: {e}",
+            output.display()
+        ));
+    }
+    write_step.done_with_info(&format!(
+        "{} node, {} vector and {} edge types in {}",
+        schema.node_schemas.len(),
+        schema.vector_schemas.len(),
+        schema.edge_schemas.len(),
+        output.display()
+    ));
+
+    op.success();
+    Ok(())
+}
@@ -0,0 +1,378 @@
+//! Reader for directories of CSV files in the format `neo4j-admin database import` loads.
+//!
+//! Node files have an `:ID` column and relationship files `:START_ID`, `:END_ID` and `:TYPE`
+//! columns. Other columns are properties named `name:type`, arrays (`type[]`) are separated by
+//! `;`. A file's header is its first line, unless the directory has a `<name>_header.csv` (or
+//! `-header`) file, which then heads every other file starting with `<name>`.
+
+use super::{Dump, DumpNode, DumpRelationship, Properties, temporal_value};
+use eyre::{Result, bail, eyre};
+use helix_db::protocol::value::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Separator of array elements and of a node's labels
+const ARRAY_DELIMITER: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    String,
+    Int,
+    Float,
+    Boolean,
+    /// A Neo4j temporal type, by the name of its Cypher function
+    Temporal(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Column {
+    Property {
+        name: String,
+        kind: Kind,
+        array: bool,
+    },
+    /// Node id within an id space, also stored as a property when the column is named
+    Id {
+        space: String,
+        name: Option<String>,
+    },
+    Label,
+    StartId(String),
+    EndId(String),
+    Type,
+    Ignore,
+}
+
+fn column(field: &str) -> Result<Column> {
+    let (name, spec) = match field.rsplit_once(':') {
+        Some((name, spec)) => (name.trim(), spec.trim()),
+        None => (field.trim(), "string"),
+    };
+    // Options such as `datetime{timezone:Europe/Paris}` don't affect how values are read
+    let spec = spec.split('{').next().unwrap_or(spec).to_ascii_lowercase();
+    let (spec, space) = match spec.split_once('(') {
+        Some((spec, space)) => (spec.to_string(), space.trim_end_matches(')').to_string()),
+        None => (spec, String::new()),
+    };
+    let (spec, array) = match spec.strip_suffix("[]") {
+        Some(spec) => (spec, true),
+        None => (spec.as_str(), false),
+    };
+    let kind = match spec {
+        "id" => {
+            return Ok(Column::Id {
+                space,
+                name: (!name.is_empty()).then(|| name.to_string()),
+            });
+        }
+        "label" => return Ok(Column::Label),
+        "start_id" => return Ok(Column::StartId(space)),
+        "end_id" => return Ok(Column::EndId(space)),
+        "type" => return Ok(Column::Type),
+        "ignore" => return Ok(Column::Ignore),
+        "string" | "char" | "point" => Kind::String,
+        "int" | "long" | "short" | "byte" => Kind::Int,
+        "float" | "double" => Kind::Float,
+        "boolean" => Kind::Boolean,
+        "date" | "localdate" => Kind::Temporal("date"),
+        "datetime" => Kind::Temporal("datetime"),
+        "localdatetime" => Kind::Temporal("localdatetime"),
+        "time" | "localtime" | "duration" => Kind::Temporal("time"),
+        other => bail!("unknown type `{other}` in header field `{field}`"),
+    };
+    if name.is_empty() {
+        bail!("header field `{field}` has no name");
+    }
+    Ok(Column::Property {
+        name: name.to_string(),
+        kind,
+        array,
+    })
+}
+
+fn scalar(text: &str, kind: Kind) -> Result<Value> {
+    Ok(match kind {
+        Kind::String => Value::String(text.to_string()),
+        Kind::Int => Value::I64(
+            text.trim()
+                .parse()
+                .map_err(|_| eyre!("`{text}` is not an integer"))?,
+        ),
+        Kind::Float => Value::F64(
+            text.trim()
+                .parse()
+                .map_err(|_| eyre!("`{text}` is not a number"))?,
+        ),
+        // As neo4j-admin reads them, anything but `true` is false
+        Kind::Boolean => Value::Boolean(text.trim().eq_ignore_ascii_case("true")),
+        Kind::Temporal(function) => temporal_value(function, text.trim()),
+    })
+}
+
+/// A CSV field and whether it was quoted. Unquoted empty fields are missing values.
+pub(super) type Field = (String, bool);
+
+/// Records of an RFC 4180 CSV file, skipping blank lines
+pub(super) fn records(text: &str, delimiter: char) -> Result<Vec<Vec<Field>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => bail!("unterminated quoted field"),
+                    }
+                }
+            }
+            c if c == delimiter => {
+                record.push((std::mem::take(&mut field), std::mem::take(&mut quoted)))
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push((std::mem::take(&mut field), std::mem::take(&mut quoted)));
+                let blank = record.len() == 1 && record[0] == (String::new(), false);
+                if !blank {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        record.push((field, quoted));
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// A data file with the columns of its header
+struct CsvFile {
+    path: PathBuf,
+    columns: Vec<Column>,
+    records: Vec<Vec<Field>>,
+}
+
+impl CsvFile {
+    fn has(&self, matches: impl Fn(&Column) -> bool) -> bool {
+        self.columns.iter().any(matches)
+    }
+}
+
+/// Read the nodes and relationships of the CSV files in `dir`
+pub fn read_dir(dir: &Path) -> Result<Dump> {
+    let mut paths = fs::read_dir(dir)
+        .map_err(|e| eyre!("Failed to read {}: {e}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+    });
+    paths.sort();
+
+    let stem = |path: &Path| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut headers = Vec::new();
+    let mut data = Vec::new();
+    for path in paths {
+        let name = stem(&path);
+        let prefix = name
+            .strip_suffix("header")
+            .map(|prefix| prefix.trim_end_matches(['_', '-', '.']).to_string());
+        match prefix {
+            Some(prefix) => headers.push((prefix, path)),
+            None => data.push(path),
+        }
+    }
+    if data.is_empty() {
+        bail!("No CSV data files found in {}", dir.display());
+    }
+
+    let mut files = Vec::new();
+    for path in data {
+        let read = |path: &Path| -> Result<Vec<Vec<Field>>> {
+            let text = fs::read_to_string(path)
+                .map_err(|e| eyre!("Failed to read {}: {e}", path.display()))?;
+            records(&text, ',').map_err(|e| eyre!("{}: {e}", path.display()))
+        };
+        let name = stem(&path);
+        let header_file = headers
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len());
+        let mut records = read(&path)?;
+        let header = match header_file {
+            Some((_, header_path)) => read(header_path)?.into_iter().next(),
+            None if records.is_empty() => None,
+            None => Some(records.remove(0)),
+        };
+        let Some(header) = header else {
+            continue;
+        };
+        let columns = header
+            .iter()
+            .map(|(field, _)| column(field))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| eyre!("{}: {e}", path.display()))?;
+        files.push(CsvFile {
+            path,
+            columns,
+            records,
+        });
+    }
+
+    let mut dump = Dump::default();
+    let mut ids: HashMap<(String, String), usize> = HashMap::new();
+    let (node_files, rel_files): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|file| file.has(|column| matches!(column, Column::Id { .. })));
+
+    for file in node_files {
+        for (i, record) in file.records.iter().enumerate() {
+            let (id, node) = read_node(&file.columns, record)
+                .map_err(|e| eyre!("{} record {}: {e}", file.path.display(), i + 1))?;
+            if ids.insert(id.clone(), dump.nodes.len()).is_some() {
+                bail!(
+                    "{} record {}: duplicate node id `{}`",
+                    file.path.display(),
+                    i + 1,
+                    id.1
+                );
+            }
+            dump.nodes.push(node);
+        }
+    }
+
+    for file in rel_files {
+        if !file.has(|column| matches!(column, Column::StartId(_)))
+            || !file.has(|column| matches!(column, Column::EndId(_)))
+        {
+            bail!(
+                "{} has neither an :ID column nor :START_ID and :END_ID columns",
+                file.path.display()
+            );
+        }
+        for (i, record) in file.records.iter().enumerate() {
+            let relationship = read_relationship(&file.columns, record, &ids)
+                .map_err(|e| eyre!("{} record {}: {e}", file.path.display(), i + 1))?;
+            dump.relationships.push(relationship);
+        }
+    }
+    Ok(dump)
+}
+
+/// Fields of a record that have a value, with their columns
+fn values<'r>(
+    columns: &'r [Column],
+    record: &'r [Field],
+) -> Result<impl Iterator<Item = (&'r Column, &'r str)>> {
+    if record.len() != columns.len() {
+        bail!(
+            "{} fields where the header has {}",
+            record.len(),
+            columns.len()
+        );
+    }
+    Ok(columns
+        .iter()
+        .zip(record)
+        .filter(|(_, (text, quoted))| !text.is_empty() || *quoted)
+        .map(|(column, (text, _))| (column, text.as_str())))
+}
+
+fn add_property(
+    properties: &mut Properties,
+    name: &str,
+    kind: Kind,
+    array: bool,
+    text: &str,
+) -> Result<()> {
+    let value = match array {
+        true => Value::Array(
+            text.split(ARRAY_DELIMITER)
+                .map(|item| scalar(item, kind))
+                .collect::<Result<_>>()?,
+        ),
+        false => scalar(text, kind)?,
+    };
+    properties.push((name.to_string(), value));
+    Ok(())
+}
+
+fn read_node(columns: &[Column], record: &[Field]) -> Result<((String, String), DumpNode)> {
+    let mut id = None;
+    let mut node = DumpNode::default();
+    for (column, text) in values(columns, record)? {
+        match column {
+            Column::Id { space, name } => {
+                id = Some((space.clone(), text.to_string()));
+                if let Some(name) = name {
+                    node.properties
+                        .push((name.clone(), Value::String(text.to_string())));
+                }
+            }
+            Column::Label => node.labels.extend(
+                text.split(ARRAY_DELIMITER)
+                    .map(str::trim)
+                    .filter(|label| !label.is_empty())
+                    .map(str::to_string),
+            ),
+            Column::Property { name, kind, array } => {
+                add_property(&mut node.properties, name, *kind, *array, text)?
+            }
+            _ => {}
+        }
+    }
+    let id = id.ok_or_else(|| eyre!("missing node id"))?;
+    Ok((id, node))
+}
+
+fn read_relationship(
+    columns: &[Column],
+    record: &[Field],
+    ids: &HashMap<(String, String), usize>,
+) -> Result<DumpRelationship> {
+    let mut start = None;
+    let mut end = None;
+    let mut rel_type = None;
+    let mut properties = Properties::new();
+    let node = |space: &String, id: &str| {
+        ids.get(&(space.clone(), id.to_string()))
+            .copied()
+            .ok_or_else(|| eyre!("no node has id `{id}`"))
+    };
+    for (column, text) in values(columns, record)? {
+        match column {
+            Column::StartId(space) => start = Some(node(space, text)?),
+            Column::EndId(space) => end = Some(node(space, text)?),
+            Column::Type => rel_type = Some(text.to_string()),
+            Column::Property { name, kind, array } => {
+                add_property(&mut properties, name, *kind, *array, text)?
+            }
+            _ => {}
+        }
+    }
+    Ok(DumpRelationship {
+        id: None,
+        rel_type: rel_type.ok_or_else(|| eyre!("missing relationship type"))?,
+        start: start.ok_or_else(|| eyre!("missing start node id"))?,
+        end: end.ok_or_else(|| eyre!("missing end node id"))?,
+        properties,
+    })
+}
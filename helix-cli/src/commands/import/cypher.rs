@@ -0,0 +1,944 @@
+//! Reader for Cypher dumps: the statements `apoc.export.cypher.*` writes (with or without
+//! `UNWIND` batching, in the `cypher-shell` and `plain` formats) and `neo4j-shell` dumps.
+//!
+//! Dumps only use a small part of Cypher, so instead of a full parser this interprets
+//! `UNWIND`, `CREATE`, `MERGE`, `MATCH` (by label and properties) and `SET` clauses over
+//! literal values. Schema statements, procedure calls and the `REMOVE` statements APOC
+//! cleans up its import label with are skipped.
+
+use super::{Dump, DumpNode, DumpRelationship, Properties, set_property, temporal_value};
+use eyre::{Result, bail, eyre};
+use helix_db::protocol::value::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Label and property APOC tags nodes with to match them up in relationship statements
+const IMPORT_LABEL: &str = "UNIQUE IMPORT LABEL";
+const IMPORT_ID: &str = "UNIQUE IMPORT ID";
+
+/// Read the nodes and relationships a Cypher dump creates
+pub fn read(text: &str) -> Result<Dump> {
+    let mut lexer = Lexer {
+        src: text.as_bytes(),
+        pos: 0,
+    };
+    let mut interpreter = Interpreter::default();
+    let mut number = 0;
+    while let Some(tokens) = lexer.next_statement()? {
+        number += 1;
+        let statement = Parser {
+            tokens: &tokens,
+            pos: 0,
+        }
+        .statement()
+        .map_err(|e| eyre!("Unsupported Cypher in statement {number}: {e}"))?;
+        if let Some(clauses) = statement {
+            interpreter
+                .execute(&clauses, &mut Scope::new())
+                .map_err(|e| eyre!("Failed to run statement {number}: {e}"))?;
+        }
+    }
+
+    let mut dump = interpreter.dump;
+    for node in &mut dump.nodes {
+        node.labels.retain(|label| label != IMPORT_LABEL);
+        node.properties.retain(|(key, _)| key != IMPORT_ID);
+    }
+    Ok(dump)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifiers and keywords
+    Word(String),
+    /// Names in backticks
+    Quoted(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Punct(char),
+}
+
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Lexer<'_> {
+    /// Tokens up to the next `;`, or `None` at the end of the dump
+    fn next_statement(&mut self) -> Result<Option<Vec<Token>>> {
+        let mut tokens = Vec::new();
+        loop {
+            match self.next_token()? {
+                None => return Ok((!tokens.is_empty()).then_some(tokens)),
+                Some(Token::Punct(';')) if !tokens.is_empty() => return Ok(Some(tokens)),
+                Some(Token::Punct(';')) => {}
+                Some(token) => tokens.push(token),
+            }
+        }
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.src.get(self.pos + offset).copied()
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(b) = self.peek_at(0) {
+            self.pos += 1;
+            if b == b'\n' {
+                break;
+            }
+        }
+    }
+
+    /// Whether only whitespace precedes the current position on its line
+    fn at_line_start(&self) -> bool {
+        self.src[..self.pos]
+            .iter()
+            .rev()
+            .take_while(|b| **b != b'\n')
+            .all(u8::is_ascii_whitespace)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>> {
+        loop {
+            let Some(b) = self.peek_at(0) else {
+                return Ok(None);
+            };
+            match b {
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                b'/' if self.peek_at(1) == Some(b'/') => self.skip_line(),
+                b'/' if self.peek_at(1) == Some(b'*') => {
+                    let end = self.src[self.pos + 2..]
+                        .windows(2)
+                        .position(|w| w == b"*/")
+                        .ok_or_else(|| eyre!("unterminated comment"))?;
+                    self.pos += end + 4;
+                }
+                // cypher-shell commands such as `:begin` and `:commit`
+                b':' if self.peek_at(1).is_some_and(|c| c.is_ascii_alphabetic())
+                    && self.at_line_start() =>
+                {
+                    self.skip_line()
+                }
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let b = self.src[start];
+        let token = match b {
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                while self
+                    .peek_at(0)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+                {
+                    self.pos += 1;
+                }
+                Token::Word(self.text(start, self.pos)?)
+            }
+            b'`' => Token::Quoted(self.quoted()?),
+            b'"' | b'\'' => Token::Str(self.string(b)?),
+            b if b.is_ascii_digit() => self.number()?,
+            b if b.is_ascii() => {
+                self.pos += 1;
+                Token::Punct(b as char)
+            }
+            _ => bail!("unexpected character at byte {start}"),
+        };
+        Ok(Some(token))
+    }
+
+    fn text(&self, start: usize, end: usize) -> Result<String> {
+        String::from_utf8(self.src[start..end].to_vec()).map_err(|e| eyre!("invalid UTF-8: {e}"))
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let mut name = Vec::new();
+        self.pos += 1;
+        loop {
+            match self.peek_at(0) {
+                None => bail!("unterminated backtick name"),
+                // Backticks are escaped by doubling them
+                Some(b'`') if self.peek_at(1) == Some(b'`') => {
+                    name.push(b'`');
+                    self.pos += 2;
+                }
+                Some(b'`') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b) => {
+                    name.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+        String::from_utf8(name).map_err(|e| eyre!("invalid UTF-8: {e}"))
+    }
+
+    fn string(&mut self, quote: u8) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.pos += 1;
+        loop {
+            let Some(b) = self.peek_at(0) else {
+                bail!("unterminated string");
+            };
+            self.pos += 1;
+            match b {
+                b if b == quote => break,
+                b'\\' => {
+                    let Some(escaped) = self.peek_at(0) else {
+                        bail!("unterminated string");
+                    };
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' | b'U' => {
+                            let len = if escaped == b'u' { 4 } else { 8 };
+                            let hex = self
+                                .src
+                                .get(self.pos..self.pos + len)
+                                .ok_or_else(|| eyre!("unterminated unicode escape"))?;
+                            self.pos += len;
+                            std::str::from_utf8(hex)
+                                .ok()
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| eyre!("invalid unicode escape"))?
+                        }
+                        other => other as char,
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b => bytes.push(b),
+            }
+        }
+        String::from_utf8(bytes).map_err(|e| eyre!("invalid UTF-8: {e}"))
+    }
+
+    fn number(&mut self) -> Result<Token> {
+        let start = self.pos;
+        let digits = |lexer: &mut Self| {
+            while lexer.peek_at(0).is_some_and(|c| c.is_ascii_digit()) {
+                lexer.pos += 1;
+            }
+        };
+        digits(self);
+        let mut float = false;
+        if self.peek_at(0) == Some(b'.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            float = true;
+            self.pos += 1;
+            digits(self);
+        }
+        if matches!(self.peek_at(0), Some(b'e' | b'E')) {
+            float = true;
+            self.pos += 1;
+            if matches!(self.peek_at(0), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            digits(self);
+        }
+        let text = self.text(start, self.pos)?;
+        match float {
+            true => text.parse().map(Token::Float),
+            false => text
+                .parse()
+                .map(Token::Int)
+                .or_else(|_| text.parse().map(Token::Float)),
+        }
+        .map_err(|_| eyre!("invalid number {text}"))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Map(Vec<(String, Expr)>),
+    Var(String),
+    Property(Box<Expr>, String),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug)]
+struct NodePattern {
+    var: Option<String>,
+    labels: Vec<String>,
+    properties: Vec<(String, Expr)>,
+}
+
+#[derive(Debug)]
+struct RelPattern {
+    var: Option<String>,
+    rel_type: Option<String>,
+    properties: Vec<(String, Expr)>,
+    outgoing: bool,
+}
+
+#[derive(Debug)]
+struct PathPattern {
+    start: NodePattern,
+    steps: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug)]
+enum SetItem {
+    /// `n += {..}`
+    Merge(String, Expr),
+    /// `n = {..}`
+    Replace(String, Expr),
+    /// `n:Label`
+    Labels(String, Vec<String>),
+    /// `n.key = ..`
+    Property(String, String, Expr),
+}
+
+#[derive(Debug)]
+enum Clause {
+    Unwind(Expr, String),
+    Create(Vec<PathPattern>),
+    Merge(PathPattern),
+    Match(Vec<PathPattern>),
+    Set(Vec<SetItem>),
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<&Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| eyre!("unexpected end of statement"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let is_keyword = self.is_keyword(keyword);
+        if is_keyword {
+            self.pos += 1;
+        }
+        is_keyword
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        let is_punct = self.peek() == Some(&Token::Punct(punct));
+        if is_punct {
+            self.pos += 1;
+        }
+        is_punct
+    }
+
+    fn expect(&mut self, punct: char) -> Result<()> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => bail!("expected `{punct}`, found {:?}", self.peek()),
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Word(name) | Token::Quoted(name) => Ok(name.clone()),
+            other => bail!("expected a name, found {other:?}"),
+        }
+    }
+
+    fn skip_transaction_keywords(&mut self) {
+        while self.eat_keyword("begin")
+            || self.eat_keyword("commit")
+            || self.eat_keyword("rollback")
+        {}
+    }
+
+    /// The statement's clauses, or `None` for statements the import skips
+    fn statement(&mut self) -> Result<Option<Vec<Clause>>> {
+        self.skip_transaction_keywords();
+        let Some(Token::Word(first)) = self.peek() else {
+            return match self.peek() {
+                None => Ok(None),
+                Some(other) => bail!("unexpected {other:?}"),
+            };
+        };
+        let first = first.to_ascii_lowercase();
+        let second = match self.tokens.get(self.pos + 1) {
+            Some(Token::Word(word)) => word.to_ascii_lowercase(),
+            _ => String::new(),
+        };
+        let is_schema = first == "create" && matches!(second.as_str(), "index" | "constraint");
+        if is_schema || matches!(first.as_str(), "drop" | "call" | "schema") {
+            return Ok(None);
+        }
+        // APOC's cleanup of its import label and id
+        let mut depth = 0i32;
+        for token in &self.tokens[self.pos..] {
+            match token {
+                Token::Punct('(' | '[' | '{') => depth += 1,
+                Token::Punct(')' | ']' | '}') => depth -= 1,
+                Token::Word(word) if depth == 0 && word.eq_ignore_ascii_case("remove") => {
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+
+        let mut clauses = Vec::new();
+        while self.peek().is_some() {
+            self.skip_transaction_keywords();
+            if self.peek().is_none() {
+                break;
+            }
+            let clause = match self.name()?.to_ascii_lowercase().as_str() {
+                "unwind" => {
+                    let list = self.expr()?;
+                    if !self.eat_keyword("as") {
+                        bail!("expected AS after UNWIND");
+                    }
+                    Clause::Unwind(list, self.name()?)
+                }
+                "create" => Clause::Create(self.paths()?),
+                "merge" => Clause::Merge(self.path()?),
+                "match" => Clause::Match(self.paths()?),
+                "set" => {
+                    let mut items = vec![self.set_item()?];
+                    while self.eat(',') {
+                        items.push(self.set_item()?);
+                    }
+                    Clause::Set(items)
+                }
+                other => bail!("unsupported clause {}", other.to_uppercase()),
+            };
+            clauses.push(clause);
+        }
+        Ok(Some(clauses))
+    }
+
+    fn paths(&mut self) -> Result<Vec<PathPattern>> {
+        let mut paths = vec![self.path()?];
+        while self.eat(',') {
+            paths.push(self.path()?);
+        }
+        Ok(paths)
+    }
+
+    fn path(&mut self) -> Result<PathPattern> {
+        let start = self.node()?;
+        let mut steps = Vec::new();
+        loop {
+            let outgoing = match self.peek() {
+                Some(Token::Punct('-')) => true,
+                Some(Token::Punct('<')) => {
+                    self.pos += 1;
+                    false
+                }
+                _ => break,
+            };
+            self.expect('-')?;
+            self.expect('[')?;
+            let var = match self.peek() {
+                Some(Token::Word(_) | Token::Quoted(_)) => Some(self.name()?),
+                _ => None,
+            };
+            let rel_type = match self.eat(':') {
+                true => Some(self.name()?),
+                false => None,
+            };
+            let properties = match self.peek() {
+                Some(Token::Punct('{')) => self.map()?,
+                _ => Vec::new(),
+            };
+            self.expect(']')?;
+            self.expect('-')?;
+            if outgoing {
+                self.expect('>')?;
+            }
+            steps.push((
+                RelPattern {
+                    var,
+                    rel_type,
+                    properties,
+                    outgoing,
+                },
+                self.node()?,
+            ));
+        }
+        Ok(PathPattern { start, steps })
+    }
+
+    fn node(&mut self) -> Result<NodePattern> {
+        self.expect('(')?;
+        let var = match self.peek() {
+            Some(Token::Word(_) | Token::Quoted(_)) => Some(self.name()?),
+            _ => None,
+        };
+        let mut labels = Vec::new();
+        while self.eat(':') {
+            labels.push(self.name()?);
+        }
+        let properties = match self.peek() {
+            Some(Token::Punct('{')) => self.map()?,
+            _ => Vec::new(),
+        };
+        self.expect(')')?;
+        Ok(NodePattern {
+            var,
+            labels,
+            properties,
+        })
+    }
+
+    fn map(&mut self) -> Result<Vec<(String, Expr)>> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        if self.eat('}') {
+            return Ok(entries);
+        }
+        loop {
+            let key = match self.next()? {
+                Token::Word(key) | Token::Quoted(key) | Token::Str(key) => key.clone(),
+                other => bail!("expected a map key, found {other:?}"),
+            };
+            self.expect(':')?;
+            entries.push((key, self.expr()?));
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(entries)
+    }
+
+    fn set_item(&mut self) -> Result<SetItem> {
+        let var = self.name()?;
+        if self.eat('+') {
+            self.expect('=')?;
+            return Ok(SetItem::Merge(var, self.expr()?));
+        }
+        if self.eat('=') {
+            return Ok(SetItem::Replace(var, self.expr()?));
+        }
+        if self.eat('.') {
+            let key = self.name()?;
+            self.expect('=')?;
+            return Ok(SetItem::Property(var, key, self.expr()?));
+        }
+        let mut labels = Vec::new();
+        while self.eat(':') {
+            labels.push(self.name()?);
+        }
+        match labels.is_empty() {
+            true => bail!("unsupported SET item for `{var}`"),
+            false => Ok(SetItem::Labels(var, labels)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut expr = match self.next()?.clone() {
+            Token::Str(s) => Expr::Literal(Value::String(s)),
+            Token::Int(i) => Expr::Literal(Value::I64(i)),
+            Token::Float(f) => Expr::Literal(Value::F64(f)),
+            Token::Punct('-') => match self.next()? {
+                Token::Int(i) => Expr::Literal(Value::I64(-i)),
+                Token::Float(f) => Expr::Literal(Value::F64(-f)),
+                other => bail!("expected a number after `-`, found {other:?}"),
+            },
+            Token::Punct('[') => {
+                let mut items = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        items.push(self.expr()?);
+                        if !self.eat(',') {
+                            break;
+                        }
+                    }
+                    self.expect(']')?;
+                }
+                Expr::List(items)
+            }
+            Token::Punct('{') => {
+                self.pos -= 1;
+                Expr::Map(self.map()?)
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("true") => {
+                Expr::Literal(Value::Boolean(true))
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("false") => {
+                Expr::Literal(Value::Boolean(false))
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("null") => Expr::Literal(Value::Empty),
+            Token::Word(name) if self.peek() == Some(&Token::Punct('(')) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expr()?);
+                        if !self.eat(',') {
+                            break;
+                        }
+                    }
+                    self.expect(')')?;
+                }
+                Expr::Call(name, args)
+            }
+            Token::Word(name) | Token::Quoted(name) => Expr::Var(name),
+            other => bail!("unexpected {other:?}"),
+        };
+        while self.eat('.') {
+            expr = Expr::Property(Box::new(expr), self.name()?);
+        }
+        Ok(expr)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Binding {
+    Node(usize),
+    Relationship(usize),
+    Value(Value),
+}
+
+type Scope = HashMap<String, Binding>;
+
+#[derive(Default)]
+struct Interpreter {
+    dump: Dump,
+    /// Nodes by label, property key and value, built the first time a pattern looks one up
+    indexes: HashMap<(String, String), HashMap<String, usize>>,
+    /// Relationships created by `MERGE`, which only creates them once
+    merged: HashSet<(usize, usize, String)>,
+}
+
+/// Key of a property value in [`Interpreter::indexes`]
+fn index_key(value: &Value) -> String {
+    format!("{value:?}")
+}
+
+impl Interpreter {
+    fn execute(&mut self, clauses: &[Clause], scope: &mut Scope) -> Result<()> {
+        for (i, clause) in clauses.iter().enumerate() {
+            match clause {
+                Clause::Unwind(list, var) => {
+                    let rows = match self.eval(list, scope)? {
+                        Value::Array(rows) => rows,
+                        Value::Empty => Vec::new(),
+                        other => bail!("UNWIND of {other:?}, which isn't a list"),
+                    };
+                    // The rest of the statement runs once per row
+                    for row in rows {
+                        let mut row_scope = scope.clone();
+                        row_scope.insert(var.clone(), Binding::Value(row));
+                        self.execute(&clauses[i + 1..], &mut row_scope)?;
+                    }
+                    return Ok(());
+                }
+                Clause::Create(paths) => {
+                    for path in paths {
+                        let start = self.create_node(&path.start, scope)?;
+                        self.create_steps(start, &path.steps, scope, false)?;
+                    }
+                }
+                Clause::Merge(path) => {
+                    let start = match self.find_node(&path.start, scope)? {
+                        Some(node) => node,
+                        None => self.create_node(&path.start, scope)?,
+                    };
+                    self.create_steps(start, &path.steps, scope, true)?;
+                }
+                Clause::Match(paths) => {
+                    for path in paths {
+                        if !path.steps.is_empty() {
+                            bail!("MATCH of relationships is not supported");
+                        }
+                        if self.find_node(&path.start, scope)?.is_none() {
+                            bail!("no node matches {:?}", path.start);
+                        }
+                    }
+                }
+                Clause::Set(items) => {
+                    for item in items {
+                        self.set(item, scope)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn create_steps(
+        &mut self,
+        start: usize,
+        steps: &[(RelPattern, NodePattern)],
+        scope: &mut Scope,
+        merge: bool,
+    ) -> Result<()> {
+        let mut previous = start;
+        for (rel, node) in steps {
+            let next = match merge {
+                true => match self.find_node(node, scope)? {
+                    Some(next) => next,
+                    None => self.create_node(node, scope)?,
+                },
+                false => self.create_node(node, scope)?,
+            };
+            let rel_type = rel
+                .rel_type
+                .clone()
+                .ok_or_else(|| eyre!("relationships need a type"))?;
+            let (from, to) = match rel.outgoing {
+                true => (previous, next),
+                false => (next, previous),
+            };
+            if merge && !self.merged.insert((from, to, rel_type.clone())) {
+                previous = next;
+                continue;
+            }
+            let properties = self.eval_entries(&rel.properties, scope)?;
+            self.dump.relationships.push(DumpRelationship {
+                id: None,
+                rel_type,
+                start: from,
+                end: to,
+                properties,
+            });
+            if let Some(var) = &rel.var {
+                scope.insert(
+                    var.clone(),
+                    Binding::Relationship(self.dump.relationships.len() - 1),
+                );
+            }
+            previous = next;
+        }
+        Ok(())
+    }
+
+    /// The node bound to the pattern's variable, or a new one
+    fn create_node(&mut self, pattern: &NodePattern, scope: &mut Scope) -> Result<usize> {
+        if let Some(var) = &pattern.var
+            && let Some(binding) = scope.get(var)
+        {
+            return match binding {
+                Binding::Node(node) => Ok(*node),
+                _ => bail!("`{var}` is not a node"),
+            };
+        }
+        let properties = self.eval_entries(&pattern.properties, scope)?;
+        self.dump.nodes.push(DumpNode {
+            id: None,
+            labels: pattern.labels.clone(),
+            properties,
+        });
+        let node = self.dump.nodes.len() - 1;
+        self.reindex(node);
+        if let Some(var) = &pattern.var {
+            scope.insert(var.clone(), Binding::Node(node));
+        }
+        Ok(node)
+    }
+
+    /// The node bound to the pattern's variable, or the node with its labels and properties
+    fn find_node(&mut self, pattern: &NodePattern, scope: &mut Scope) -> Result<Option<usize>> {
+        if let Some(var) = &pattern.var
+            && let Some(binding) = scope.get(var)
+        {
+            return match binding {
+                Binding::Node(node) => Ok(Some(*node)),
+                _ => bail!("`{var}` is not a node"),
+            };
+        }
+        let properties = self.eval_entries(&pattern.properties, scope)?;
+        let (Some(label), Some((key, value))) = (pattern.labels.first(), properties.first()) else {
+            bail!("nodes can only be matched by a label and properties");
+        };
+
+        let nodes = &self.dump.nodes;
+        let index = self
+            .indexes
+            .entry((label.clone(), key.clone()))
+            .or_insert_with(|| {
+                nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, node)| node.labels.contains(label))
+                    .filter_map(|(i, node)| {
+                        let (_, value) = node.properties.iter().find(|(k, _)| k == key)?;
+                        Some((index_key(value), i))
+                    })
+                    .collect()
+            });
+        let found = index.get(&index_key(value)).copied().filter(|&i| {
+            let node = &self.dump.nodes[i];
+            pattern
+                .labels
+                .iter()
+                .all(|label| node.labels.contains(label))
+                && properties
+                    .iter()
+                    .all(|property| node.properties.contains(property))
+        });
+        if let (Some(node), Some(var)) = (found, &pattern.var) {
+            scope.insert(var.clone(), Binding::Node(node));
+        }
+        Ok(found)
+    }
+
+    /// Add a node to the indexes of its labels and properties
+    fn reindex(&mut self, node: usize) {
+        let node_data = &self.dump.nodes[node];
+        for ((label, key), index) in &mut self.indexes {
+            if !node_data.labels.contains(label) {
+                continue;
+            }
+            if let Some((_, value)) = node_data.properties.iter().find(|(k, _)| k == key) {
+                index.insert(index_key(value), node);
+            }
+        }
+    }
+
+    fn set(&mut self, item: &SetItem, scope: &Scope) -> Result<()> {
+        let (var, properties) = match item {
+            SetItem::Labels(var, labels) => {
+                let Some(Binding::Node(node)) = scope.get(var) else {
+                    bail!("labels can only be set on nodes");
+                };
+                let node_labels = &mut self.dump.nodes[*node].labels;
+                for label in labels {
+                    if !node_labels.contains(label) {
+                        node_labels.push(label.clone());
+                    }
+                }
+                self.reindex(*node);
+                return Ok(());
+            }
+            SetItem::Merge(var, expr) | SetItem::Replace(var, expr) => {
+                (var, self.eval_properties(expr, scope)?)
+            }
+            SetItem::Property(var, key, expr) => {
+                (var, vec![(key.clone(), self.eval(expr, scope)?)])
+            }
+        };
+        let target = match scope.get(var) {
+            Some(Binding::Node(node)) => &mut self.dump.nodes[*node].properties,
+            Some(Binding::Relationship(rel)) => &mut self.dump.relationships[*rel].properties,
+            _ => bail!("`{var}` is not a node or relationship"),
+        };
+        if matches!(item, SetItem::Replace(..)) {
+            target.clear();
+        }
+        for (key, value) in properties {
+            set_property(target, &key, value);
+        }
+        if let Some(Binding::Node(node)) = scope.get(var) {
+            self.reindex(*node);
+        }
+        Ok(())
+    }
+
+    /// Properties of a map literal, in the order written, without nulls
+    fn eval_entries(&self, entries: &[(String, Expr)], scope: &Scope) -> Result<Properties> {
+        let mut properties = Properties::with_capacity(entries.len());
+        for (key, expr) in entries {
+            set_property(&mut properties, key, self.eval(expr, scope)?);
+        }
+        Ok(properties)
+    }
+
+    /// Properties of a map expression. Maps that aren't literals are unordered, so their
+    /// properties are sorted by key.
+    fn eval_properties(&self, expr: &Expr, scope: &Scope) -> Result<Properties> {
+        if let Expr::Map(entries) = expr {
+            return self.eval_entries(entries, scope);
+        }
+        match self.eval(expr, scope)? {
+            Value::Object(map) => {
+                let mut properties = map.into_iter().collect::<Properties>();
+                properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Ok(properties)
+            }
+            Value::Empty => Ok(Properties::new()),
+            other => bail!("expected a map of properties, found {other:?}"),
+        }
+    }
+
+    /// A variable or property of one without copying its container
+    fn resolve<'v>(&'v self, expr: &Expr, scope: &'v Scope) -> Result<Option<&'v Value>> {
+        match expr {
+            Expr::Var(name) => match scope.get(name) {
+                Some(Binding::Value(value)) => Ok(Some(value)),
+                Some(_) => bail!("`{name}` is a node or relationship, not a value"),
+                None => bail!("unknown variable `{name}`"),
+            },
+            Expr::Property(base, key) => {
+                if let Expr::Var(name) = &**base {
+                    let properties = match scope.get(name) {
+                        Some(Binding::Node(node)) => Some(&self.dump.nodes[*node].properties),
+                        Some(Binding::Relationship(rel)) => {
+                            Some(&self.dump.relationships[*rel].properties)
+                        }
+                        _ => None,
+                    };
+                    if let Some(properties) = properties {
+                        return Ok(properties.iter().find(|(k, _)| k == key).map(|(_, v)| v));
+                    }
+                }
+                match self.resolve(base, scope)? {
+                    Some(Value::Object(map)) => Ok(map.get(key)),
+                    None | Some(Value::Empty) => Ok(None),
+                    Some(other) => bail!("`.{key}` of {other:?}, which isn't a map"),
+                }
+            }
+            _ => bail!("expected a variable or property"),
+        }
+    }
+
+    fn eval(&self, expr: &Expr, scope: &Scope) -> Result<Value> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::List(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item, scope))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Map(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, expr)| Ok((key.clone(), self.eval(expr, scope)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Var(_) | Expr::Property(..) => {
+                self.resolve(expr, scope)?.cloned().unwrap_or(Value::Empty)
+            }
+            Expr::Call(name, args) => {
+                let function = name.to_ascii_lowercase();
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, scope))
+                    .collect::<Result<Vec<_>>>()?;
+                match (function.as_str(), args.as_slice()) {
+                    (
+                        "date" | "datetime" | "localdatetime" | "time" | "localtime" | "duration",
+                        [Value::String(text)],
+                    ) => temporal_value(&function, text),
+                    ("point", [point @ Value::Object(_)]) => point.clone(),
+                    _ => bail!("unsupported function {name}()"),
+                }
+            }
+        })
+    }
+}
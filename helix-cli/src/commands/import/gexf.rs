@@ -0,0 +1,284 @@
+//! GEXF 1.1 to 1.3, as written by Gephi and NetworkX.
+//!
+//! A node's label is read from a `labels`, `label` or `type` attribute, then from its `label`
+//! unless that only repeats its id (as NetworkX writes it). An edge's type is read from a `label`
+//! or `type` attribute, then from its `label` or `kind`. Unlabeled items are `Node` and `Edge`.
+
+use super::graphml::{DEFAULT_EDGE_LABEL, DEFAULT_NODE_LABEL};
+use super::xml::{self, AttrType, Element, Kind, attributes, escape};
+use super::{Dump, DumpNode, DumpRelationship, Properties};
+use eyre::{Result, bail, eyre};
+use helix_db::protocol::value::Value;
+use std::collections::HashMap;
+
+/// Attributes holding a node's label or an edge's type rather than a property
+const LABEL_ATTRIBUTES: [&str; 3] = ["labels", "label", "type"];
+
+/// An `<attribute>` declaration
+struct Attribute {
+    title: String,
+    ty: AttrType,
+    default: Option<String>,
+}
+
+fn attr_type(name: &str) -> AttrType {
+    let (list, name) = match name.strip_prefix("list") {
+        Some(item) => (true, item),
+        None => (false, name),
+    };
+    let kind = match name {
+        "boolean" => Kind::Boolean,
+        "integer" | "long" | "short" | "byte" | "biginteger" => Kind::Long,
+        "float" | "double" | "bigdecimal" => Kind::Double,
+        "date" => Kind::Date,
+        _ => Kind::String,
+    };
+    AttrType { kind, list }
+}
+
+fn type_name(ty: AttrType) -> String {
+    let name = match ty.kind {
+        Kind::Boolean => "boolean",
+        Kind::Long => "long",
+        Kind::Double => "double",
+        Kind::Date => "date",
+        Kind::String => "string",
+    };
+    match ty.list {
+        true => format!("list{name}"),
+        false => name.to_string(),
+    }
+}
+
+/// The declared attributes of one class, by id
+fn declarations(graph: &Element, class: &str) -> HashMap<String, Attribute> {
+    graph
+        .children("attributes")
+        .filter(|attributes| attributes.attribute("class") == Some(class))
+        .flat_map(|attributes| attributes.children("attribute"))
+        .filter_map(|attribute| {
+            let id = attribute.attribute("id")?;
+            Some((
+                id.to_string(),
+                Attribute {
+                    title: attribute.attribute("title").unwrap_or(id).to_string(),
+                    ty: attr_type(attribute.attribute("type").unwrap_or("string")),
+                    default: attribute
+                        .child("default")
+                        .map(|default| default.text.clone()),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Properties of a `<node>` or `<edge>`, with the value of its label attribute
+fn attvalues(
+    element: &Element,
+    declarations: &HashMap<String, Attribute>,
+) -> (Properties, Option<String>) {
+    let mut properties = Properties::new();
+    let mut label = None;
+    let values = element
+        .children("attvalues")
+        .flat_map(|values| values.children("attvalue"));
+    for value in values {
+        // GEXF 1.1 refers to the attribute with `id`
+        let Some(attribute) = value
+            .attribute("for")
+            .or_else(|| value.attribute("id"))
+            .and_then(|id| declarations.get(id))
+        else {
+            continue;
+        };
+        let text = value.attribute("value").unwrap_or_default();
+        if LABEL_ATTRIBUTES.contains(&attribute.title.as_str()) {
+            label.get_or_insert_with(|| text.trim().to_string());
+        } else {
+            properties.push((attribute.title.clone(), attribute.ty.parse(text)));
+        }
+    }
+    for attribute in declarations.values() {
+        let missing = !properties.iter().any(|(name, _)| name == &attribute.title);
+        if let Some(default) = attribute.default.as_ref().filter(|_| missing)
+            && !LABEL_ATTRIBUTES.contains(&attribute.title.as_str())
+        {
+            properties.push((attribute.title.clone(), attribute.ty.parse(default)));
+        }
+    }
+    (properties, label.filter(|label| !label.is_empty()))
+}
+
+/// Read the graph of a GEXF document
+pub fn read(text: &str) -> Result<Dump> {
+    let root = xml::parse(text)?;
+    if root.name != "gexf" {
+        bail!("not a GEXF document: the root element is <{}>", root.name);
+    }
+    let graph = root
+        .child("graph")
+        .ok_or_else(|| eyre!("the GEXF document has no <graph>"))?;
+    let node_attributes = declarations(graph, "node");
+    let edge_attributes = declarations(graph, "edge");
+
+    let mut dump = Dump::default();
+    let mut ids = HashMap::new();
+    let nodes = graph
+        .children("nodes")
+        .flat_map(|nodes| nodes.children("node"));
+    for node in nodes {
+        let id = node
+            .attribute("id")
+            .ok_or_else(|| eyre!("a <node> has no id"))?;
+        let (properties, label) = attvalues(node, &node_attributes);
+        let labels = match label {
+            Some(labels) => labels
+                .split(':')
+                .filter(|label| !label.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => vec![
+                node.attribute("label")
+                    .filter(|label| !label.is_empty() && *label != id)
+                    .unwrap_or(DEFAULT_NODE_LABEL)
+                    .to_string(),
+            ],
+        };
+        if ids.insert(id.to_string(), dump.nodes.len()).is_some() {
+            bail!("more than one <node> has id `{id}`");
+        }
+        dump.nodes.push(DumpNode {
+            id: Some(id.to_string()),
+            labels,
+            properties,
+        });
+    }
+
+    let edges = graph
+        .children("edges")
+        .flat_map(|edges| edges.children("edge"));
+    for edge in edges {
+        let endpoint = |attribute: &str| -> Result<usize> {
+            let id = edge
+                .attribute(attribute)
+                .ok_or_else(|| eyre!("an <edge> has no {attribute}"))?;
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| eyre!("an <edge> {attribute} is `{id}`, which isn't a node"))
+        };
+        let (mut properties, label) = attvalues(edge, &edge_attributes);
+        if let Some(weight) = edge.attribute("weight") {
+            properties.push((
+                "weight".to_string(),
+                AttrType {
+                    kind: Kind::Double,
+                    list: false,
+                }
+                .parse(weight),
+            ));
+        }
+        let rel_type = label
+            .or_else(|| edge.attribute("label").map(str::to_string))
+            .or_else(|| edge.attribute("kind").map(str::to_string))
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| DEFAULT_EDGE_LABEL.to_string());
+        dump.relationships.push(DumpRelationship {
+            id: edge.attribute("id").map(str::to_string),
+            rel_type,
+            start: endpoint("source")?,
+            end: endpoint("target")?,
+            properties,
+        });
+    }
+    Ok(dump)
+}
+
+/// Write a directed, static GEXF 1.3 document
+pub fn write(dump: &Dump) -> String {
+    let node_attributes = attributes(dump.nodes.iter().map(|node| node.properties.as_slice()));
+    let edge_attributes = attributes(
+        dump.relationships
+            .iter()
+            .map(|relationship| relationship.properties.as_slice()),
+    );
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<gexf xmlns=\"http://gexf.net/1.3\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://gexf.net/1.3 http://gexf.net/1.3/gexf.xsd\" \
+         version=\"1.3\">\n",
+    );
+    out.push_str("  <meta>\n    <creator>HelixDB</creator>\n  </meta>\n");
+    out.push_str("  <graph defaultedgetype=\"directed\" mode=\"static\">\n");
+    declare_attributes(&mut out, "node", &node_attributes);
+    declare_attributes(&mut out, "edge", &edge_attributes);
+
+    let ids = dump.node_ids();
+    out.push_str("    <nodes>\n");
+    for (node, id) in dump.nodes.iter().zip(&ids) {
+        let label = node.labels.first().map(String::as_str).unwrap_or_default();
+        out.push_str(&format!(
+            "      <node id=\"{}\" label=\"{}\">\n",
+            escape(id),
+            escape(label)
+        ));
+        write_attvalues(&mut out, &node_attributes, &node.properties);
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n    <edges>\n");
+    for (i, relationship) in dump.relationships.iter().enumerate() {
+        let id = relationship.id.clone().unwrap_or_else(|| format!("e{i}"));
+        out.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\">\n",
+            escape(&id),
+            escape(&ids[relationship.start]),
+            escape(&ids[relationship.end]),
+            escape(&relationship.rel_type),
+        ));
+        write_attvalues(&mut out, &edge_attributes, &relationship.properties);
+        out.push_str("      </edge>\n");
+    }
+    out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    out
+}
+
+fn declare_attributes(out: &mut String, class: &str, attributes: &[(String, AttrType)]) {
+    if attributes.is_empty() {
+        return;
+    }
+    out.push_str(&format!("    <attributes class=\"{class}\">\n"));
+    for (i, (title, ty)) in attributes.iter().enumerate() {
+        out.push_str(&format!(
+            "      <attribute id=\"{i}\" title=\"{}\" type=\"{}\"/>\n",
+            escape(title),
+            type_name(*ty)
+        ));
+    }
+    out.push_str("    </attributes>\n");
+}
+
+fn write_attvalues(out: &mut String, attributes: &[(String, AttrType)], properties: &Properties) {
+    let values = attributes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (title, _))| {
+            properties
+                .iter()
+                .find(|(key, value)| key == title && !matches!(value, Value::Empty))
+                .map(|(_, value)| (i, value))
+        })
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        return;
+    }
+    out.push_str("        <attvalues>\n");
+    for (i, value) in values {
+        out.push_str(&format!(
+            "          <attvalue for=\"{i}\" value=\"{}\"/>\n",
+            escape(&xml::text(value))
+        ));
+    }
+    out.push_str("        </attvalues>\n");
+}
@@ -0,0 +1,259 @@
+//! GraphML, as written by Gephi, NetworkX, yEd and `apoc.export.graphml`.
+//!
+//! GraphML has no node labels or edge types. Node labels are read from a `labels` attribute
+//! (`:Person:Author`, as APOC writes it) or a `label` attribute, edge types from the edge's
+//! `label` or a `label`/`type` attribute. Nodes and edges without one are `Node` and `Edge`.
+
+use super::xml::{self, AttrType, Element, Kind, attributes, escape};
+use super::{Dump, DumpNode, DumpRelationship, Properties};
+use eyre::{Result, bail, eyre};
+use helix_db::protocol::value::Value;
+use std::collections::HashMap;
+
+pub(crate) const DEFAULT_NODE_LABEL: &str = "Node";
+pub(crate) const DEFAULT_EDGE_LABEL: &str = "Edge";
+
+/// A `<key>` declaring an attribute
+struct Key {
+    name: String,
+    ty: AttrType,
+    default: Option<String>,
+}
+
+fn kind(name: &str) -> Kind {
+    match name {
+        "boolean" => Kind::Boolean,
+        "int" | "long" => Kind::Long,
+        "float" | "double" => Kind::Double,
+        _ => Kind::String,
+    }
+}
+
+fn type_name(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Boolean => "boolean",
+        Kind::Long => "long",
+        Kind::Double => "double",
+        // GraphML has no date type
+        Kind::Date | Kind::String => "string",
+    }
+}
+
+/// The declared keys for one kind of element, by id
+fn keys(root: &Element, domain: &str) -> HashMap<String, Key> {
+    root.children("key")
+        .filter(|key| {
+            matches!(key.attribute("for"), None | Some("all"))
+                || key.attribute("for") == Some(domain)
+        })
+        .filter_map(|key| {
+            let id = key.attribute("id")?;
+            // APOC declares lists with `attr.list` as the element type
+            let ty = match key.attribute("attr.list") {
+                Some(item) => AttrType {
+                    kind: kind(item),
+                    list: true,
+                },
+                None => AttrType {
+                    kind: kind(key.attribute("attr.type").unwrap_or("string")),
+                    list: false,
+                },
+            };
+            Some((
+                id.to_string(),
+                Key {
+                    name: key.attribute("attr.name").unwrap_or(id).to_string(),
+                    ty,
+                    default: key.child("default").map(|default| default.text.clone()),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Properties of a `<node>` or `<edge>`, with the value of its label attributes
+fn data(
+    element: &Element,
+    keys: &HashMap<String, Key>,
+    label_names: &[&str],
+) -> (Properties, Option<String>) {
+    let mut properties = Properties::new();
+    let mut label = None;
+    for data in element.children("data") {
+        let Some(key) = data.attribute("key").and_then(|id| keys.get(id)) else {
+            continue;
+        };
+        if label_names.contains(&key.name.as_str()) {
+            label.get_or_insert_with(|| data.text.trim().to_string());
+        } else {
+            properties.push((key.name.clone(), key.ty.parse(&data.text)));
+        }
+    }
+    for key in keys.values() {
+        let missing = !properties.iter().any(|(name, _)| name == &key.name);
+        if let Some(default) = key.default.as_ref().filter(|_| missing)
+            && !label_names.contains(&key.name.as_str())
+        {
+            properties.push((key.name.clone(), key.ty.parse(default)));
+        }
+    }
+    (properties, label)
+}
+
+/// Read the first graph of a GraphML document
+pub fn read(text: &str) -> Result<Dump> {
+    let root = xml::parse(text)?;
+    if root.name != "graphml" {
+        bail!(
+            "not a GraphML document: the root element is <{}>",
+            root.name
+        );
+    }
+    let graph = root
+        .child("graph")
+        .ok_or_else(|| eyre!("the GraphML document has no <graph>"))?;
+    let node_keys = keys(&root, "node");
+    let edge_keys = keys(&root, "edge");
+
+    let mut dump = Dump::default();
+    let mut ids = HashMap::new();
+    for node in graph.children("node") {
+        let id = node
+            .attribute("id")
+            .ok_or_else(|| eyre!("a <node> has no id"))?;
+        let (properties, labels) = data(node, &node_keys, &["labels", "label"]);
+        let labels = labels
+            .map(|labels| {
+                labels
+                    .split(':')
+                    .map(str::trim)
+                    .filter(|label| !label.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|labels| !labels.is_empty())
+            .unwrap_or_else(|| vec![DEFAULT_NODE_LABEL.to_string()]);
+        if ids.insert(id.to_string(), dump.nodes.len()).is_some() {
+            bail!("more than one <node> has id `{id}`");
+        }
+        dump.nodes.push(DumpNode {
+            id: Some(id.to_string()),
+            labels,
+            properties,
+        });
+    }
+
+    for edge in graph.children("edge") {
+        let endpoint = |attribute: &str| -> Result<usize> {
+            let id = edge
+                .attribute(attribute)
+                .ok_or_else(|| eyre!("an <edge> has no {attribute}"))?;
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| eyre!("an <edge> {attribute} is `{id}`, which isn't a node"))
+        };
+        let (properties, label) = data(edge, &edge_keys, &["label", "type"]);
+        let rel_type = edge
+            .attribute("label")
+            .map(str::to_string)
+            .or(label)
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| DEFAULT_EDGE_LABEL.to_string());
+        dump.relationships.push(DumpRelationship {
+            id: edge.attribute("id").map(str::to_string),
+            rel_type,
+            start: endpoint("source")?,
+            end: endpoint("target")?,
+            properties,
+        });
+    }
+    Ok(dump)
+}
+
+/// Write a directed GraphML document, with node labels as APOC writes them
+pub fn write(dump: &Dump) -> String {
+    let node_attributes = attributes(dump.nodes.iter().map(|node| node.properties.as_slice()));
+    let edge_attributes = attributes(
+        dump.relationships
+            .iter()
+            .map(|relationship| relationship.properties.as_slice()),
+    );
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns \
+         http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+    out.push_str("  <key id=\"labels\" for=\"node\" attr.name=\"labels\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    declare_keys(&mut out, "n", "node", &node_attributes);
+    declare_keys(&mut out, "e", "edge", &edge_attributes);
+    out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+
+    let ids = dump.node_ids();
+    for (node, id) in dump.nodes.iter().zip(&ids) {
+        out.push_str(&format!("    <node id=\"{}\">\n", escape(id)));
+        let labels = node
+            .labels
+            .iter()
+            .map(|label| format!(":{label}"))
+            .collect::<String>();
+        out.push_str(&format!(
+            "      <data key=\"labels\">{}</data>\n",
+            escape(&labels)
+        ));
+        write_data(&mut out, "n", &node_attributes, &node.properties);
+        out.push_str("    </node>\n");
+    }
+    for (i, relationship) in dump.relationships.iter().enumerate() {
+        let id = relationship.id.clone().unwrap_or_else(|| format!("e{i}"));
+        let label = escape(&relationship.rel_type);
+        out.push_str(&format!(
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{label}\">\n",
+            escape(&id),
+            escape(&ids[relationship.start]),
+            escape(&ids[relationship.end]),
+        ));
+        out.push_str(&format!("      <data key=\"label\">{label}</data>\n"));
+        write_data(&mut out, "e", &edge_attributes, &relationship.properties);
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Declare a `<key>` for each attribute, with ids `<prefix><index>`
+fn declare_keys(out: &mut String, prefix: &str, domain: &str, attributes: &[(String, AttrType)]) {
+    for (i, (name, ty)) in attributes.iter().enumerate() {
+        let (attr_type, list) = match ty.list {
+            true => ("string", format!(" attr.list=\"{}\"", type_name(ty.kind))),
+            false => (type_name(ty.kind), String::new()),
+        };
+        out.push_str(&format!(
+            "  <key id=\"{prefix}{i}\" for=\"{domain}\" attr.name=\"{}\" attr.type=\"{attr_type}\"{list}/>\n",
+            escape(name),
+        ));
+    }
+}
+
+fn write_data(
+    out: &mut String,
+    prefix: &str,
+    attributes: &[(String, AttrType)],
+    properties: &Properties,
+) {
+    for (i, (name, _)) in attributes.iter().enumerate() {
+        let value = properties
+            .iter()
+            .find(|(key, value)| key == name && !matches!(value, Value::Empty));
+        if let Some((_, value)) = value {
+            out.push_str(&format!(
+                "      <data key=\"{prefix}{i}\">{}</data>\n",
+                escape(&xml::text(value))
+            ));
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! `helix import` command for loading data exported from other graph databases and tools.
+//!
+//! `helix import neo4j` reads either a Cypher dump (`apoc.export.cypher.*` or a `neo4j-shell`
+//! dump) or a directory of CSV files in the `neo4j-admin database import` format, and
+//! `helix import graphml`/`helix import gexf` read the formats of Gephi, NetworkX and yEd, and
+//! `helix import csv` reads plain node and edge tables. Labels and relationship types are mapped to the project's `N::`/`E::` schema, a schema is proposed
+//! from the data when the project has none, and the data is written into a local instance's
+//! volume.
+
+mod csv;
+mod cypher;
+pub(crate) mod gexf;
+pub(crate) mod graphml;
+mod schema;
+mod table;
+mod xml;
+
+use crate::ImportSource;
+use crate::config::InstanceInfo;
+use crate::errors::CliError;
+use crate::location::Location;
+use crate::output::{Operation, Step, Verbosity};
+use crate::project::ProjectContext;
+use crate::utils::helixc_utils::{collect_hx_files, generate_content, parse_content};
+use crate::utils::print_warning;
+use bumpalo::Bump;
+use chrono::{NaiveDateTime, Utc};
+use eyre::{Result, eyre};
+use helix_db::helix_engine::storage_core::{
+    HelixGraphStorage, version_info::VersionInfo, write_log,
+};
+use helix_db::helix_engine::traversal_core::config::{
+    Config, GraphConfig, IvfFlatConfig, VectorConfig,
+};
+use helix_db::helix_engine::traversal_core::ops::g::G;
+use helix_db::helix_engine::traversal_core::ops::source::{add_e::AddEAdapter, add_n::AddNAdapter};
+use helix_db::helix_engine::types::{EdgeConstraint, MaterializedCount, SecondaryIndex};
+use helix_db::helixc::parser::types::{Content, HxFile, Schema, Source};
+use helix_db::protocol::{date::Date, value::Value};
+use helix_db::utils::properties::ImmutablePropertiesMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub use schema::{Mapping, propose_schema};
+pub use table::{EdgeTable, NodeTable, TableMapping};
+
+/// Items written between resets of the allocation arena
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+/// Property key the importer reads an item's properties into, in first-seen order
+pub type Properties = Vec<(String, Value)>;
+
+/// A node read from an export
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DumpNode {
+    /// Id in the source graph, kept by formats that write ids back out
+    pub id: Option<String>,
+    pub labels: Vec<String>,
+    pub properties: Properties,
+}
+
+/// A relationship read from an export, between nodes given by their index in [`Dump::nodes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpRelationship {
+    pub id: Option<String>,
+    pub rel_type: String,
+    pub start: usize,
+    pub end: usize,
+    pub properties: Properties,
+}
+
+/// The nodes and relationships of an export
+#[derive(Debug, Default)]
+pub struct Dump {
+    pub nodes: Vec<DumpNode>,
+    pub relationships: Vec<DumpRelationship>,
+}
+
+impl Dump {
+    /// Ids to write the nodes with: their source ids, or `n<index>` for nodes without one
+    pub fn node_ids(&self) -> Vec<String> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| node.id.clone().unwrap_or_else(|| format!("n{i}")))
+            .collect()
+    }
+}
+
+/// Set `key` to `value`, removing it when `value` is null as Cypher does
+pub(crate) fn set_property(properties: &mut Properties, key: &str, value: Value) {
+    let existing = properties.iter().position(|(k, _)| k == key);
+    match (existing, value) {
+        (Some(i), Value::Empty) => {
+            properties.remove(i);
+        }
+        (Some(i), value) => properties[i].1 = value,
+        (None, Value::Empty) => {}
+        (None, value) => properties.push((key.to_string(), value)),
+    }
+}
+
+/// A Neo4j temporal value. Dates and date-times become Helix dates, other temporal types
+/// (times, durations) and values chrono can't read are kept as strings.
+pub(crate) fn temporal_value(function: &str, text: &str) -> Value {
+    // Named zones, as in `2024-01-01T10:00:00+01:00[Europe/Paris]`, come after the offset
+    let text = text.split('[').next().unwrap_or(text);
+    let parsed = match function {
+        "date" | "datetime" => Date::new(&Value::String(text.to_string())).ok(),
+        "localdatetime" => NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .and_then(|date| Date::new(&Value::String(date.and_utc().to_rfc3339())).ok()),
+        _ => None,
+    };
+    match parsed {
+        Some(date) => Value::Date(date),
+        None => Value::String(text.to_string()),
+    }
+}
+
+/// Read a Cypher dump file or a directory of neo4j-admin import CSV files
+pub fn read_dump(path: &Path) -> Result<Dump> {
+    if path.is_dir() {
+        return csv::read_dir(path);
+    }
+    cypher::read(&read_text(path)?)
+}
+
+fn read_text(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| eyre!("Failed to read {}: {e}", path.display()))
+}
+
+/// Formats `helix import` reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A Cypher dump or neo4j-admin CSV export
+    Neo4j,
+    GraphMl,
+    Gexf,
+}
+
+impl Format {
+    pub fn read(self, path: &Path) -> Result<Dump> {
+        match self {
+            Format::Neo4j => read_dump(path),
+            Format::GraphMl => graphml::read(&read_text(path)?),
+            Format::Gexf => gexf::read(&read_text(path)?),
+        }
+    }
+}
+
+/// Counts reported once an import finishes
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub nodes: usize,
+    pub relationships: usize,
+    /// Path the proposed schema was written to, when the project had none
+    pub schema_written: Option<String>,
+}
+
+pub async fn run(source: ImportSource) -> Result<()> {
+    let (format, instance, from, schema_out, dry_run) = match source {
+        ImportSource::Neo4j {
+            instance,
+            from,
+            schema_out,
+            dry_run,
+        } => (Format::Neo4j, instance, from, schema_out, dry_run),
+        ImportSource::Graphml {
+            instance,
+            from,
+            schema_out,
+            dry_run,
+        } => (Format::GraphMl, instance, from, schema_out, dry_run),
+        ImportSource::Gexf {
+            instance,
+            from,
+            schema_out,
+            dry_run,
+        } => (Format::Gexf, instance, from, schema_out, dry_run),
+        ImportSource::Csv {
+            instance,
+            nodes,
+            edges,
+            mapping,
+            schema_out,
+            dry_run,
+        } => {
+            let project = ProjectContext::find_and_load(None)?;
+            let mut tables = match &mapping {
+                Some(path) => TableMapping::load(path)?,
+                None => TableMapping::default(),
+            };
+            for spec in &nodes {
+                tables.add_nodes(NodeTable::from_spec(spec)?);
+            }
+            for spec in &edges {
+                tables.add_edges(EdgeTable::from_spec(spec)?);
+            }
+            let staging = tempfile::tempdir()?;
+            let files = tables
+                .nodes
+                .iter_mut()
+                .map(|table| &mut table.file)
+                .chain(tables.edges.iter_mut().map(|table| &mut table.file))
+                .filter(|file| Location::is_remote(file));
+            for (i, file) in files.enumerate() {
+                let dir = staging.path().join(i.to_string());
+                *file = fetch(&file.to_string_lossy(), &dir).await?;
+            }
+            return import_tables(&project, &instance, &tables, schema_out.as_deref(), dry_run)
+                .map(|_| ());
+        }
+    };
+    let project = ProjectContext::find_and_load(None)?;
+    let staging = tempfile::tempdir()?;
+    let from = fetch(&from, staging.path()).await?;
+    import(
+        &project,
+        &instance,
+        format,
+        &from,
+        schema_out.as_deref(),
+        dry_run,
+    )
+    .map(|_| ())
+}
+
+/// Local path of a file or directory to import, downloaded into `staging` when it's in object
+/// storage
+async fn fetch(from: &str, staging: &Path) -> Result<PathBuf> {
+    let remote = match Location::parse(from)? {
+        Location::Local(path) => return Ok(path),
+        Location::Remote(remote) => remote,
+    };
+    let mut download_step = Step::with_messages("Downloading import", "Import downloaded");
+    download_step.start();
+    match remote.download(staging).await {
+        Ok(path) => {
+            download_step.done();
+            Ok(path)
+        }
+        Err(e) => {
+            download_step.fail();
+            Err(e)
+        }
+    }
+}
+
+/// Import an export into `instance`, which has to be local and should be stopped
+pub fn import(
+    project: &ProjectContext,
+    instance_name: &str,
+    format: Format,
+    from: &Path,
+    schema_out: Option<&Path>,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    let description = from.display().to_string();
+    import_dump(
+        project,
+        instance_name,
+        &description,
+        || format.read(from),
+        schema_out,
+        dry_run,
+    )
+}
+
+/// Import node and edge tables into `instance`, which has to be local and should be stopped
+pub fn import_tables(
+    project: &ProjectContext,
+    instance_name: &str,
+    tables: &TableMapping,
+    schema_out: Option<&Path>,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    if tables.is_empty() {
+        let error = CliError::new("no tables to import")
+            .with_hint("name them with --nodes users.csv:User and --edges follows.csv:Follows(from,to), or in a --mapping file");
+        return Err(eyre!("{}", error.render()));
+    }
+    let description = match tables.nodes.len() + tables.edges.len() {
+        1 => "1 table".to_string(),
+        n => format!("{n} tables"),
+    };
+    import_dump(
+        project,
+        instance_name,
+        &description,
+        || tables.read(),
+        schema_out,
+        dry_run,
+    )
+}
+
+/// Map the graph `read` returns onto the project's schema and write it into `instance`
+fn import_dump(
+    project: &ProjectContext,
+    instance_name: &str,
+    description: &str,
+    read: impl FnOnce() -> Result<Dump>,
+    schema_out: Option<&Path>,
+    dry_run: bool,
+) -> Result<ImportSummary> {
+    let instance = project.config.get_instance(instance_name)?;
+    if !instance.is_local() {
+        let error = CliError::new(format!(
+            "instance '{instance_name}' is not a local instance"
+        ))
+        .with_hint("import into a local instance, then push its data or replay it through queries");
+        return Err(eyre!("{}", error.render()));
+    }
+
+    let op = Operation::new("Importing", description);
+    let mut summary = ImportSummary::default();
+
+    let mut read_step = Step::with_messages("Reading export", "Export read");
+    read_step.start();
+    let dump = match read() {
+        Ok(dump) => dump,
+        Err(e) => {
+            read_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    read_step.done_with_info(&format!(
+        "{} nodes, {} relationships",
+        dump.nodes.len(),
+        dump.relationships.len()
+    ));
+
+    // Map onto the project's schema, or onto one proposed from the export
+    let source = match project_schema(project)? {
+        Some(source) => source,
+        None => {
+            let proposal = propose_schema(&dump);
+            let path = schema_out.map(Path::to_path_buf).unwrap_or_else(|| {
+                project
+                    .root
+                    .join(&project.config.project.queries)
+                    .join("schema.hx")
+            });
+            let path = path.display().to_string();
+            if dry_run {
+                crate::output::info(&format!(
+                    "The project has no schema, this one would be written to {path}:\n\n{proposal}"
+                ));
+            } else {
+                write_schema_proposal(Path::new(&path), &proposal)?;
+                summary.schema_written = Some(path.clone());
+            }
+            parse_content(&Content {
+                content: proposal.clone(),
+                files: vec![HxFile {
+                    name: path,
+                    content: proposal,
+                }],
+                source: Source::default(),
+            })?
+        }
+    };
+
+    let mut map_step = Step::with_messages("Mapping to schema", "Mapped to schema");
+    map_step.start();
+    let schema = source
+        .get_latest_schema()
+        .map_err(|e| eyre!("Failed to read schema: {e}"))?;
+    let mapping = match Mapping::new(schema, &dump) {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            map_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    let plan = mapping.plan(dump);
+    map_step.done_with_info(&format!(
+        "{} labels, {} relationship types",
+        mapping.labels().len(),
+        mapping.rel_types().len()
+    ));
+    for warning in &plan.warnings {
+        print_warning(warning);
+    }
+
+    if dry_run {
+        for (label, node) in mapping.labels() {
+            crate::output::info(&format!("(:{label}) -> N::{node}"));
+        }
+        for (rel_type, edge) in mapping.rel_types() {
+            crate::output::info(&format!("[:{rel_type}] -> E::{edge}"));
+        }
+        op.success();
+        summary.nodes = plan.nodes.len();
+        summary.relationships = plan.edges.len();
+        return Ok(summary);
+    }
+
+    let mut load_step = Step::with_messages("Loading data", "Data loaded");
+    load_step.start();
+    let path = project.instance_volume(instance_name).join("user");
+    let storage = HelixGraphStorage::new(
+        &path.display().to_string(),
+        storage_config(
+            &instance,
+            secondary_indices(schema),
+            materialized_counts(schema),
+            edge_constraints(schema),
+            acyclic_edges(schema),
+        ),
+        VersionInfo::default(),
+    )
+    .map_err(|e| eyre!("Failed to open instance data at {}: {e}", path.display()))?;
+    if let Err(e) = load(&storage, &plan) {
+        load_step.fail();
+        op.failure();
+        return Err(e);
+    }
+    load_step.done();
+    op.success();
+
+    summary.nodes = plan.nodes.len();
+    summary.relationships = plan.edges.len();
+    if Verbosity::current().show_normal() {
+        let nodes = summary.nodes.to_string();
+        let relationships = summary.relationships.to_string();
+        let data = path.display().to_string();
+        let mut details = vec![
+            ("Nodes", nodes.as_str()),
+            ("Relationships", relationships.as_str()),
+            ("Instance data", data.as_str()),
+        ];
+        if let Some(schema) = &summary.schema_written {
+            details.push(("Proposed schema", schema.as_str()));
+        }
+        Operation::print_details(&details);
+    }
+    Ok(summary)
+}
+
+/// The project's parsed queries, or `None` when they define no schema
+pub(crate) fn project_schema(project: &ProjectContext) -> Result<Option<Source>> {
+    let queries_dir = project.root.join(&project.config.project.queries);
+    if !queries_dir.is_dir() {
+        return Ok(None);
+    }
+    let hx_files = collect_hx_files(&project.root, &project.config.project.queries)?;
+    let source = parse_content(&generate_content(&hx_files)?)?;
+    Ok((!source.schema.is_empty()).then_some(source))
+}
+
+/// Write a proposed schema, after the comments of an existing file such as `helix init`'s
+fn write_schema_proposal(path: &Path, proposal: &str) -> Result<()> {
+    let contents = match fs::read_to_string(path) {
+        Ok(existing) => format!("{}\n\n{proposal}", existing.trim_end()),
+        Err(_) => proposal.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+        .map_err(|e| eyre!("Failed to write proposed schema to {}: {e}", path.display()))
+}
+
+/// Secondary indices of the fields a schema marks as indexed
+pub(crate) fn secondary_indices(schema: &Schema) -> Vec<SecondaryIndex> {
+    schema
+        .node_schemas
+        .iter()
+        .flat_map(|node| node.fields.iter().filter(|field| field.is_indexed()))
+        .map(SecondaryIndex::from_field)
+        .collect()
+}
+
+/// Edge counts a schema keeps as node properties
+pub(crate) fn materialized_counts(schema: &Schema) -> Vec<MaterializedCount> {
+    schema
+        .node_schemas
+        .iter()
+        .flat_map(|node| {
+            node.fields
+                .iter()
+                .filter_map(|field| MaterializedCount::from_field(&node.name.1, field))
+        })
+        .collect()
+}
+
+/// Bounds a schema puts on the edges of its nodes
+pub(crate) fn edge_constraints(schema: &Schema) -> Vec<EdgeConstraint> {
+    schema
+        .edge_schemas
+        .iter()
+        .flat_map(EdgeConstraint::from_schema)
+        .collect()
+}
+
+/// Labels of the edge types a schema declares `@acyclic`
+pub(crate) fn acyclic_edges(schema: &Schema) -> Vec<String> {
+    schema
+        .edge_schemas
+        .iter()
+        .filter(|edge| edge.acyclic)
+        .map(|edge| edge.name.1.clone())
+        .collect()
+}
+
+/// Storage settings matching what the instance runs with, so indices and counts are kept up
+/// to date and constraints are checked
+pub(crate) fn storage_config(
+    instance: &InstanceInfo<'_>,
+    secondary_indices: Vec<SecondaryIndex>,
+    materialized_counts: Vec<MaterializedCount>,
+    edge_constraints: Vec<EdgeConstraint>,
+    acyclic_edges: Vec<String>,
+) -> Config {
+    let db_config = instance.db_config();
+    Config {
+        vector_config: Some(VectorConfig {
+            m: Some(db_config.vector_config.m as usize),
+            ef_construction: Some(db_config.vector_config.ef_construction as usize),
+            ef_search: Some(db_config.vector_config.ef_search as usize),
+            ivf_flat: db_config.vector_config.ivf_flat.as_ref().map(|ivf_flat| {
+                ivf_flat
+                    .iter()
+                    .map(|(label, config)| {
+                        let config = IvfFlatConfig {
+                            nlist: config.nlist,
+                            nprobe: config.nprobe,
+                        };
+                        (label.clone(), config)
+                    })
+                    .collect()
+            }),
+        }),
+        graph_config: Some(GraphConfig {
+            secondary_indices: Some(secondary_indices),
+            materialized_counts: Some(materialized_counts),
+            edge_constraints: Some(edge_constraints),
+            acyclic_edges: Some(acyclic_edges),
+        }),
+        db_max_size_gb: Some(db_config.vector_config.db_max_size_gb as usize),
+        mcp: Some(false),
+        bm25: Some(db_config.bm25),
+        ..Config::default()
+    }
+}
+
+/// Write the planned nodes and edges in a single transaction
+fn load(storage: &HelixGraphStorage, plan: &schema::Plan) -> Result<()> {
+    let mut txn = storage
+        .graph_env
+        .write_txn()
+        .map_err(|e| eyre!("Failed to start write transaction: {e}"))?;
+
+    let mut ids = Vec::with_capacity(plan.nodes.len());
+    for batch in plan.nodes.chunks(IMPORT_BATCH_SIZE) {
+        let arena = Bump::new();
+        for node in batch {
+            let indices = plan.indices.get(node.label).map(Vec::as_slice);
+            let node_value = G::new_mut(storage, &arena, &mut txn)
+                .add_n(
+                    arena.alloc_str(node.label),
+                    properties(&node.properties, &arena),
+                    indices,
+                )
+                .collect_to_obj()
+                .map_err(|e| eyre!("Failed to import {} node: {e}", node.label))?;
+            ids.push(node_value.id());
+        }
+        write_log::reset();
+    }
+
+    for batch in plan.edges.chunks(IMPORT_BATCH_SIZE) {
+        let arena = Bump::new();
+        for edge in batch {
+            G::new_mut(storage, &arena, &mut txn)
+                .add_edge(
+                    arena.alloc_str(edge.label),
+                    properties(&edge.properties, &arena),
+                    ids[edge.from],
+                    ids[edge.to],
+                    false,
+                    edge.is_unique,
+                )
+                .collect_to_obj()
+                .map_err(|e| eyre!("Failed to import {} edge: {e}", edge.label))?;
+        }
+        write_log::reset();
+    }
+
+    storage
+        .check_constraints(&txn)
+        .map_err(|e| eyre!("Imported data breaks the schema: {e}"))?;
+    txn.commit()
+        .map_err(|e| eyre!("Failed to commit imported data: {e}"))
+}
+
+fn properties<'arena>(
+    properties: &Properties,
+    arena: &'arena Bump,
+) -> Option<ImmutablePropertiesMap<'arena>> {
+    (!properties.is_empty()).then(|| {
+        ImmutablePropertiesMap::new(
+            properties.len(),
+            properties
+                .iter()
+                .map(|(key, value)| (arena.alloc_str(key) as &str, value.clone())),
+            arena,
+        )
+    })
+}
+
+/// The current time as a Helix date, for `DEFAULT NOW` fields
+pub(crate) fn now() -> Value {
+    Date::new(&Value::String(Utc::now().to_rfc3339()))
+        .map(Value::Date)
+        .unwrap_or(Value::Empty)
+}
@@ -0,0 +1,604 @@
+//! Mapping Neo4j labels and relationship types onto a Helix schema, and proposing a schema
+//! for exports imported into a project without one.
+
+use super::{Dump, Properties, now};
+use crate::errors::CliError;
+use eyre::{Result, eyre};
+use helix_db::helixc::parser::types::{
+    DefaultValue, EdgeSchema, Field, FieldType, NodeSchema, Schema,
+};
+use helix_db::protocol::{date::Date, value::Value};
+use helix_db::utils::id::ID;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// Field names the schema reserves for item metadata
+const RESERVED_FIELD_NAMES: &[&str] = &["id", "label", "type", "version", "to_node", "from_node"];
+
+/// Lowercase alphanumerics of a name, so `ACTED_IN` matches `ActedIn`
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// The schema item name proposed for a label or relationship type: `ACTED_IN` -> `ActedIn`
+fn type_name(name: &str) -> String {
+    let mut type_name = String::new();
+    for word in name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            type_name.push(first.to_ascii_uppercase());
+        }
+        // Words in all caps, as relationship types usually are, are capitalized
+        match word.chars().any(|c| c.is_ascii_lowercase()) {
+            true => type_name.extend(chars),
+            false => type_name.extend(chars.map(|c| c.to_ascii_lowercase())),
+        }
+    }
+    match type_name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => type_name,
+        _ => format!("Label{type_name}"),
+    }
+}
+
+/// The field name proposed for a property key, avoiding the reserved field names
+fn field_name(key: &str) -> String {
+    let mut name = key
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name = format!("field_{name}");
+    }
+    match RESERVED_FIELD_NAMES.contains(&name.to_lowercase().as_str()) {
+        true => format!("source_{name}"),
+        false => name,
+    }
+}
+
+/// The field a property key is stored in: the field with the same name, the name
+/// [`propose_schema`] would give it, or a name that differs only in case and underscores
+fn find_field<'s>(fields: &'s [Field], key: &str) -> Option<&'s Field> {
+    let proposed = field_name(key);
+    let normalized = normalize(key);
+    fields
+        .iter()
+        .find(|field| field.name == key)
+        .or_else(|| fields.iter().find(|field| field.name == proposed))
+        .or_else(|| {
+            fields
+                .iter()
+                .find(|field| normalize(&field.name) == normalized)
+        })
+}
+
+/// Labels and relationship types of an export and the schema items they map to
+pub struct Mapping<'s> {
+    labels: Vec<(String, &'s NodeSchema)>,
+    rel_types: Vec<(String, &'s EdgeSchema)>,
+}
+
+/// Nodes and edges converted to the schema, ready to be written
+pub struct Plan<'s> {
+    pub nodes: Vec<PlannedNode<'s>>,
+    pub edges: Vec<PlannedEdge<'s>>,
+    /// Indexed fields of each node type
+    pub indices: HashMap<&'s str, Vec<&'s str>>,
+    /// Data that couldn't be mapped and was left out
+    pub warnings: Vec<String>,
+}
+
+pub struct PlannedNode<'s> {
+    pub label: &'s str,
+    pub properties: Properties,
+}
+
+/// An edge between nodes given by their index in [`Plan::nodes`]
+pub struct PlannedEdge<'s> {
+    pub label: &'s str,
+    pub from: usize,
+    pub to: usize,
+    pub is_unique: bool,
+    pub properties: Properties,
+}
+
+impl<'s> Mapping<'s> {
+    /// Map every label and relationship type of `dump` onto `schema`. Fails when a node has no
+    /// label with a schema node, or a relationship type has no schema edge.
+    pub fn new(schema: &'s Schema, dump: &Dump) -> Result<Self> {
+        let find_node = |label: &str| {
+            let normalized = normalize(label);
+            schema
+                .node_schemas
+                .iter()
+                .find(|node| node.name.1 == label)
+                .or_else(|| {
+                    schema
+                        .node_schemas
+                        .iter()
+                        .find(|node| normalize(&node.name.1) == normalized)
+                })
+        };
+        let find_edge = |rel_type: &str| {
+            let normalized = normalize(rel_type);
+            schema
+                .edge_schemas
+                .iter()
+                .find(|edge| edge.name.1 == rel_type)
+                .or_else(|| {
+                    schema
+                        .edge_schemas
+                        .iter()
+                        .find(|edge| normalize(&edge.name.1) == normalized)
+                })
+        };
+
+        let mut labels: Vec<(String, &'s NodeSchema)> = Vec::new();
+        // Labels without a schema node, and those of them on nodes with no other label
+        let mut missing = Vec::new();
+        let mut unmapped = Vec::new();
+        let mut unlabeled = 0;
+        for node in &dump.nodes {
+            let mut mapped = false;
+            for label in &node.labels {
+                if labels.iter().any(|(l, _)| l == label) {
+                    mapped = true;
+                } else if !missing.contains(label) {
+                    match find_node(label) {
+                        Some(node_schema) => {
+                            labels.push((label.clone(), node_schema));
+                            mapped = true;
+                        }
+                        None => missing.push(label.clone()),
+                    }
+                }
+            }
+            if node.labels.is_empty() {
+                unlabeled += 1;
+            } else if !mapped {
+                for label in &node.labels {
+                    if !unmapped.contains(label) {
+                        unmapped.push(label.clone());
+                    }
+                }
+            }
+        }
+
+        let mut rel_types: Vec<(String, &'s EdgeSchema)> = Vec::new();
+        let mut unmapped_types = Vec::new();
+        for relationship in &dump.relationships {
+            let rel_type = &relationship.rel_type;
+            if rel_types.iter().any(|(t, _)| t == rel_type) || unmapped_types.contains(rel_type) {
+                continue;
+            }
+            match find_edge(rel_type) {
+                Some(edge_schema) => rel_types.push((rel_type.clone(), edge_schema)),
+                None => unmapped_types.push(rel_type.clone()),
+            }
+        }
+
+        let mut problems = Vec::new();
+        if !unmapped.is_empty() {
+            problems.push(format!(
+                "labels without a schema node: {}",
+                unmapped.join(", ")
+            ));
+        }
+        if !unmapped_types.is_empty() {
+            problems.push(format!(
+                "relationship types without a schema edge: {}",
+                unmapped_types.join(", ")
+            ));
+        }
+        if unlabeled > 0 {
+            problems.push(format!("{unlabeled} nodes without a label"));
+        }
+        if !problems.is_empty() {
+            let error = CliError::new("the export doesn't fit the project's schema")
+                .with_context(problems.join("\n"))
+                .with_hint("add matching N:: and E:: definitions; names match ignoring case and underscores, so ACTED_IN maps to E::ActedIn");
+            return Err(eyre!("{}", error.render()));
+        }
+
+        Ok(Self { labels, rel_types })
+    }
+
+    /// Each mapped label with the name of its schema node
+    pub fn labels(&self) -> Vec<(&str, &str)> {
+        self.labels
+            .iter()
+            .map(|(label, node)| (label.as_str(), node.name.1.as_str()))
+            .collect()
+    }
+
+    /// Each mapped relationship type with the name of its schema edge
+    pub fn rel_types(&self) -> Vec<(&str, &str)> {
+        self.rel_types
+            .iter()
+            .map(|(rel_type, edge)| (rel_type.as_str(), edge.name.1.as_str()))
+            .collect()
+    }
+
+    /// Convert the export's items to their schema types. Properties without a field, values
+    /// that can't be converted to their field's type and relationships between nodes the edge
+    /// doesn't connect are left out and reported in [`Plan::warnings`].
+    pub fn plan(&self, dump: Dump) -> Plan<'s> {
+        let mut report = Report::default();
+
+        let mut nodes = Vec::with_capacity(dump.nodes.len());
+        for node in dump.nodes {
+            // Mapping::new checked that every node has a mapped label
+            let mut mapped = node.labels.iter().filter_map(|label| {
+                self.labels
+                    .iter()
+                    .find(|(l, _)| l == label)
+                    .map(|(_, node_schema)| *node_schema)
+            });
+            let node_schema = mapped.next().expect("node labels were mapped");
+            let name = node_schema.name.1.as_str();
+            for extra in mapped.filter(|extra| extra.name.1 != name) {
+                *report
+                    .extra_labels
+                    .entry((name.to_string(), extra.name.1.clone()))
+                    .or_default() += 1;
+            }
+            nodes.push(PlannedNode {
+                label: name,
+                properties: report.convert(name, &node_schema.fields, node.properties),
+            });
+        }
+
+        let mut edges = Vec::with_capacity(dump.relationships.len());
+        for relationship in dump.relationships {
+            let edge_schema = self
+                .rel_types
+                .iter()
+                .find(|(t, _)| *t == relationship.rel_type)
+                .map(|(_, edge_schema)| *edge_schema)
+                .expect("relationship types were mapped");
+            let name = edge_schema.name.1.as_str();
+            if nodes[relationship.start].label != edge_schema.from.1
+                || nodes[relationship.end].label != edge_schema.to.1
+            {
+                *report
+                    .mismatched_edges
+                    .entry(format!(
+                        "E::{name} connects {} to {}",
+                        edge_schema.from.1, edge_schema.to.1
+                    ))
+                    .or_default() += 1;
+                continue;
+            }
+            let fields = edge_schema.properties.as_deref().unwrap_or_default();
+            edges.push(PlannedEdge {
+                label: name,
+                from: relationship.start,
+                to: relationship.end,
+                is_unique: edge_schema.unique,
+                properties: report.convert(name, fields, relationship.properties),
+            });
+        }
+
+        let indices = self
+            .labels
+            .iter()
+            .map(|(_, node_schema)| {
+                let indexed = node_schema
+                    .fields
+                    .iter()
+                    .filter(|field| field.is_indexed())
+                    .map(|field| field.name.as_str())
+                    .collect();
+                (node_schema.name.1.as_str(), indexed)
+            })
+            .collect();
+
+        Plan {
+            nodes,
+            edges,
+            indices,
+            warnings: report.warnings(),
+        }
+    }
+}
+
+/// Counts of the data left out of an import, by what was left out
+#[derive(Default)]
+struct Report {
+    /// (item, property) of properties without a field
+    unknown_properties: BTreeMap<(String, String), usize>,
+    /// (item, field) of values that couldn't be converted to the field's type
+    unconverted: BTreeMap<(String, String), usize>,
+    /// (stored label, dropped label) of nodes with several mapped labels
+    extra_labels: BTreeMap<(String, String), usize>,
+    /// Relationships skipped because the edge doesn't connect their nodes' types
+    mismatched_edges: BTreeMap<String, usize>,
+}
+
+impl Report {
+    /// Convert `properties` to the types of `fields`, filling in defaults of missing fields
+    fn convert(&mut self, item: &str, fields: &[Field], properties: Properties) -> Properties {
+        let mut converted: Properties = Vec::with_capacity(properties.len());
+        for (key, value) in properties {
+            let Some(field) = find_field(fields, &key) else {
+                *self
+                    .unknown_properties
+                    .entry((item.to_string(), key))
+                    .or_default() += 1;
+                continue;
+            };
+            match coerce(value, &field.field_type) {
+                Some(value) => converted.push((field.name.clone(), value)),
+                None => {
+                    *self
+                        .unconverted
+                        .entry((item.to_string(), field.name.clone()))
+                        .or_default() += 1
+                }
+            }
+        }
+        for field in fields {
+            if converted.iter().any(|(name, _)| *name == field.name) {
+                continue;
+            }
+            if let Some(value) = field.defaults.as_ref().and_then(default_value) {
+                converted.push((field.name.clone(), value));
+            }
+        }
+        converted
+    }
+
+    fn warnings(self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for ((item, key), count) in self.unknown_properties {
+            warnings.push(format!(
+                "Left out {count} `{key}` properties of {item}: the schema has no such field"
+            ));
+        }
+        for ((item, field), count) in self.unconverted {
+            warnings.push(format!(
+                "Left out {count} values of {item}.{field}: they don't convert to the field's type"
+            ));
+        }
+        for ((label, extra), count) in self.extra_labels {
+            warnings.push(format!(
+                "Stored {count} nodes labeled both {label} and {extra} as {label}"
+            ));
+        }
+        for (edge, count) in self.mismatched_edges {
+            warnings.push(format!(
+                "Skipped {count} relationships between other node types: {edge}"
+            ));
+        }
+        warnings
+    }
+}
+
+fn default_value(default: &DefaultValue) -> Option<Value> {
+    Some(match default {
+        DefaultValue::Now => now(),
+        DefaultValue::String(s) => Value::String(s.clone()),
+        DefaultValue::F32(f) => Value::F32(*f),
+        DefaultValue::F64(f) => Value::F64(*f),
+        DefaultValue::I8(i) => Value::I8(*i),
+        DefaultValue::I16(i) => Value::I16(*i),
+        DefaultValue::I32(i) => Value::I32(*i),
+        DefaultValue::I64(i) => Value::I64(*i),
+        DefaultValue::U8(u) => Value::U8(*u),
+        DefaultValue::U16(u) => Value::U16(*u),
+        DefaultValue::U32(u) => Value::U32(*u),
+        DefaultValue::U64(u) => Value::U64(*u),
+        DefaultValue::U128(u) => Value::U128(*u),
+        DefaultValue::Boolean(b) => Value::Boolean(*b),
+        DefaultValue::Empty => return None,
+    })
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::I64(i) => Some(*i as i128),
+        Value::F64(f) if f.fract() == 0.0 => Some(*f as i128),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::I64(i) => Some(*i as f64),
+        Value::F64(f) => Some(*f),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Convert an exported value to a field's type, `None` if it doesn't fit
+fn coerce(value: Value, field_type: &FieldType) -> Option<Value> {
+    match (field_type, value) {
+        (FieldType::String, Value::String(s)) => Some(Value::String(s)),
+        (FieldType::String, Value::Date(date)) => Some(Value::String(date.to_rfc3339())),
+        (FieldType::String, value @ (Value::Array(_) | Value::Object(_))) => {
+            serde_json::to_string(&value).ok().map(Value::String)
+        }
+        (FieldType::String, value) => Some(Value::String(value.inner_stringify())),
+        (FieldType::F32, value) => number(&value).map(|f| Value::F32(f as f32)),
+        (FieldType::F64, value) => number(&value).map(Value::F64),
+        (FieldType::I8, value) => integer(&value)?.try_into().ok().map(Value::I8),
+        (FieldType::I16, value) => integer(&value)?.try_into().ok().map(Value::I16),
+        (FieldType::I32, value) => integer(&value)?.try_into().ok().map(Value::I32),
+        (FieldType::I64, value) => integer(&value)?.try_into().ok().map(Value::I64),
+        (FieldType::U8, value) => integer(&value)?.try_into().ok().map(Value::U8),
+        (FieldType::U16, value) => integer(&value)?.try_into().ok().map(Value::U16),
+        (FieldType::U32, value) => integer(&value)?.try_into().ok().map(Value::U32),
+        (FieldType::U64, value) => integer(&value)?.try_into().ok().map(Value::U64),
+        (FieldType::U128, value) => integer(&value)?.try_into().ok().map(Value::U128),
+        (FieldType::Boolean, Value::Boolean(b)) => Some(Value::Boolean(b)),
+        (FieldType::Boolean, Value::String(s)) => s.to_lowercase().parse().ok().map(Value::Boolean),
+        (FieldType::Date, Value::Date(date)) => Some(Value::Date(date)),
+        (FieldType::Date, value @ (Value::String(_) | Value::I64(_))) => {
+            Date::new(&value).ok().map(Value::Date)
+        }
+        (FieldType::Uuid, Value::String(s)) => uuid::Uuid::parse_str(&s)
+            .ok()
+            .map(|uuid| Value::Id(ID::from(uuid.as_u128()))),
+        (FieldType::Array(inner), Value::Array(items)) => items
+            .into_iter()
+            .map(|item| coerce(item, inner))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        _ => None,
+    }
+}
+
+/// Field type inferred from the values of a property
+#[derive(Clone, PartialEq)]
+enum Inferred {
+    I64,
+    F64,
+    Boolean,
+    Date,
+    String,
+    Array(Box<Inferred>),
+}
+
+impl Inferred {
+    fn of(value: &Value) -> Option<Self> {
+        Some(match value {
+            Value::I64(_) => Self::I64,
+            Value::F64(_) => Self::F64,
+            Value::Boolean(_) => Self::Boolean,
+            Value::Date(_) => Self::Date,
+            Value::Array(items) => {
+                let mut items = items.iter().filter_map(Self::of);
+                let first = items.next()?;
+                Self::Array(Box::new(items.fold(first, Self::merge)))
+            }
+            Value::Empty => return None,
+            _ => Self::String,
+        })
+    }
+
+    /// The type both `self` and `other` values fit in
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::I64, Self::F64) | (Self::F64, Self::I64) => Self::F64,
+            (Self::Array(a), Self::Array(b)) => Self::Array(Box::new(a.merge(*b))),
+            _ => Self::String,
+        }
+    }
+
+    fn hql(&self) -> String {
+        match self {
+            Self::I64 => "I64".to_string(),
+            Self::F64 => "F64".to_string(),
+            Self::Boolean => "Boolean".to_string(),
+            Self::Date => "Date".to_string(),
+            Self::String => "String".to_string(),
+            Self::Array(inner) => format!("[{}]", inner.hql()),
+        }
+    }
+}
+
+/// Proposed fields of a schema item, in the order their properties were first seen
+#[derive(Default)]
+struct ProposedFields(Vec<(String, Inferred)>);
+
+impl ProposedFields {
+    fn add(&mut self, properties: &Properties) {
+        for (key, value) in properties {
+            let Some(inferred) = Inferred::of(value) else {
+                continue;
+            };
+            let name = field_name(key);
+            match self.0.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, existing)) => *existing = existing.clone().merge(inferred),
+                None => self.0.push((name, inferred)),
+            }
+        }
+    }
+}
+
+/// An HQL schema with a node per label and an edge per relationship type of `dump`.
+/// Nodes are proposed for their first label, and edges connect the label pair most of their
+/// relationships connect.
+pub fn propose_schema(dump: &Dump) -> String {
+    let mut nodes: Vec<(String, ProposedFields)> = Vec::new();
+    for node in &dump.nodes {
+        let Some(label) = node.labels.first() else {
+            continue;
+        };
+        let name = type_name(label);
+        let index = match nodes.iter().position(|(n, _)| *n == name) {
+            Some(index) => index,
+            None => {
+                nodes.push((name, ProposedFields::default()));
+                nodes.len() - 1
+            }
+        };
+        nodes[index].1.add(&node.properties);
+    }
+
+    #[allow(clippy::type_complexity)]
+    let mut edges: Vec<(String, Vec<((String, String), usize)>, ProposedFields)> = Vec::new();
+    for relationship in &dump.relationships {
+        let (Some(from), Some(to)) = (
+            dump.nodes[relationship.start].labels.first(),
+            dump.nodes[relationship.end].labels.first(),
+        ) else {
+            continue;
+        };
+        let name = type_name(&relationship.rel_type);
+        let index = match edges.iter().position(|(n, _, _)| *n == name) {
+            Some(index) => index,
+            None => {
+                edges.push((name, Vec::new(), ProposedFields::default()));
+                edges.len() - 1
+            }
+        };
+        let (_, pairs, fields) = &mut edges[index];
+        let pair = (type_name(from), type_name(to));
+        match pairs.iter_mut().find(|(p, _)| *p == pair) {
+            Some((_, count)) => *count += 1,
+            None => pairs.push((pair, 1)),
+        }
+        fields.add(&relationship.properties);
+    }
+
+    let mut schema = String::from(
+        "// Proposed by `helix import neo4j` from the labels and relationship types of the export\n",
+    );
+    for (name, fields) in &nodes {
+        let _ = writeln!(schema, "\nN::{name} {{");
+        for (field, inferred) in &fields.0 {
+            let _ = writeln!(schema, "    {field}: {},", inferred.hql());
+        }
+        schema.push_str("}\n");
+    }
+    for (name, pairs, fields) in &edges {
+        // The first of the most common pairs
+        let ((from, to), _) = pairs
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .expect("edges have a label pair");
+        let _ = writeln!(schema, "\nE::{name} {{\n    From: {from},\n    To: {to},");
+        if !fields.0.is_empty() {
+            schema.push_str("    Properties: {\n");
+            for (field, inferred) in &fields.0 {
+                let _ = writeln!(schema, "        {field}: {},", inferred.hql());
+            }
+            schema.push_str("    }\n");
+        }
+        schema.push_str("}\n");
+    }
+    schema
+}
@@ -0,0 +1,534 @@
+//! Node and edge tables in plain CSV files, the way spreadsheets export them.
+//!
+//! Each file holds one label: a node table has a row per node, an edge table a row per edge with
+//! the ids of its endpoints in two columns. The first row names the columns. A column is read as
+//! the type the mapping gives it or, without one, as the narrowest type all its values parse as.
+
+use super::csv::{Field, records};
+use super::{Dump, DumpNode, DumpRelationship, Properties};
+use crate::location::Location;
+use eyre::{Result, bail, eyre};
+use helix_db::protocol::date::Date;
+use helix_db::protocol::value::Value;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Column node tables are keyed by when the mapping doesn't name one
+const DEFAULT_ID_COLUMN: &str = "id";
+
+/// The tables to import and how to read their columns, as written in a `--mapping` TOML file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableMapping {
+    #[serde(default)]
+    pub nodes: Vec<NodeTable>,
+    #[serde(default)]
+    pub edges: Vec<EdgeTable>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeTable {
+    pub file: PathBuf,
+    pub label: String,
+    /// Column edge tables refer to the nodes by, `id` when the table has one
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Field separator, `,` or a tab for `.tsv` files by default
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnMapping>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeTable {
+    pub file: PathBuf,
+    pub label: String,
+    /// Column holding the id of the edge's source node
+    pub from: String,
+    /// Column holding the id of the edge's target node
+    pub to: String,
+    /// Label of the source nodes, needed when an id is used by more than one node table
+    #[serde(default)]
+    pub from_label: Option<String>,
+    #[serde(default)]
+    pub to_label: Option<String>,
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnMapping>,
+}
+
+/// How to read one column: `age = "U8"`, or `signup = { name = "created_at", type = "Date" }`
+/// and `notes = { skip = true }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnMapping {
+    Type(String),
+    Column {
+        /// Property the column is stored as, the column's name by default
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default, rename = "type")]
+        ty: Option<String>,
+        #[serde(default)]
+        skip: bool,
+    },
+}
+
+/// Helix types a column can be coerced into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    String,
+    Boolean,
+    F32,
+    F64,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Date,
+}
+
+impl ColumnType {
+    const ALL: [(&'static str, ColumnType); 14] = [
+        ("String", Self::String),
+        ("Boolean", Self::Boolean),
+        ("F32", Self::F32),
+        ("F64", Self::F64),
+        ("I8", Self::I8),
+        ("I16", Self::I16),
+        ("I32", Self::I32),
+        ("I64", Self::I64),
+        ("U8", Self::U8),
+        ("U16", Self::U16),
+        ("U32", Self::U32),
+        ("U64", Self::U64),
+        ("U128", Self::U128),
+        ("Date", Self::Date),
+    ];
+
+    fn from_name(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|(type_name, _)| type_name.eq_ignore_ascii_case(name))
+            .map(|(_, ty)| *ty)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|(name, _)| name).join(", ");
+                eyre!("unknown column type `{name}`, expected one of {names}")
+            })
+    }
+
+    /// The value of a cell, `None` when it doesn't parse as this type
+    fn parse(self, text: &str) -> Option<Value> {
+        let trimmed = text.trim();
+        Some(match self {
+            Self::String => Value::String(text.to_string()),
+            Self::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Value::Boolean(true),
+                "false" | "no" | "0" => Value::Boolean(false),
+                _ => return None,
+            },
+            Self::F32 => Value::F32(trimmed.parse().ok()?),
+            Self::F64 => Value::F64(trimmed.parse().ok()?),
+            Self::I8 => Value::I8(trimmed.parse().ok()?),
+            Self::I16 => Value::I16(trimmed.parse().ok()?),
+            Self::I32 => Value::I32(trimmed.parse().ok()?),
+            Self::I64 => Value::I64(trimmed.parse().ok()?),
+            Self::U8 => Value::U8(trimmed.parse().ok()?),
+            Self::U16 => Value::U16(trimmed.parse().ok()?),
+            Self::U32 => Value::U32(trimmed.parse().ok()?),
+            Self::U64 => Value::U64(trimmed.parse().ok()?),
+            Self::U128 => Value::U128(trimmed.parse().ok()?),
+            Self::Date => Value::Date(Date::new(&Value::String(trimmed.to_string())).ok()?),
+        })
+    }
+
+    /// The narrowest type every value parses as. Integers with leading zeros, such as zip codes,
+    /// are kept as strings, and only `true`/`false` make a column boolean.
+    fn infer<'a>(mut values: impl Iterator<Item = &'a str> + Clone) -> Self {
+        // Numbers as spreadsheets write them: no `inf`/`NaN` and no leading zeros
+        let number = |text: &str| {
+            let digits = text.trim().trim_start_matches(['-', '+']);
+            let leading_zero = digits.len() > 1
+                && digits.starts_with('0')
+                && digits[1..].starts_with(|c: char| c.is_ascii_digit());
+            !leading_zero
+                && digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && digits
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'))
+        };
+        let integer = |text: &str| number(text) && text.trim().parse::<i64>().is_ok();
+        let boolean =
+            |text: &str| matches!(text.trim().to_ascii_lowercase().as_str(), "true" | "false");
+        if values.clone().all(integer) {
+            Self::I64
+        } else if values
+            .clone()
+            .all(|text| number(text) && Self::F64.parse(text).is_some())
+        {
+            Self::F64
+        } else if values.clone().all(boolean) {
+            Self::Boolean
+        } else if values.all(|text| Self::Date.parse(text).is_some()) {
+            Self::Date
+        } else {
+            Self::String
+        }
+    }
+}
+
+/// A column of a table as it's read: skipped, or stored as `name` with `ty`
+struct Column {
+    name: String,
+    ty: ColumnType,
+    skip: bool,
+}
+
+/// A table's rows, with the position of each column name
+struct Table {
+    path: PathBuf,
+    header: Vec<String>,
+    rows: Vec<Vec<Field>>,
+}
+
+impl Table {
+    fn read(path: &Path, delimiter: Option<char>) -> Result<Self> {
+        let delimiter = delimiter.unwrap_or_else(|| {
+            match path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+            {
+                true => '\t',
+                false => ',',
+            }
+        });
+        let text = fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read {}: {e}", path.display()))?;
+        let mut rows = records(&text, delimiter).map_err(|e| eyre!("{}: {e}", path.display()))?;
+        if rows.is_empty() {
+            bail!(
+                "{} is empty, its first row should name the columns",
+                path.display()
+            );
+        }
+        let header = rows
+            .remove(0)
+            .into_iter()
+            .map(|(name, _)| name.trim().to_string())
+            .collect::<Vec<_>>();
+        if let Some(i) = header.iter().position(String::is_empty) {
+            bail!("{}: column {} has no name", path.display(), i + 1);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            header,
+            rows,
+        })
+    }
+
+    fn position(&self, column: &str) -> Option<usize> {
+        self.header.iter().position(|name| name == column)
+    }
+
+    fn require(&self, column: &str) -> Result<usize> {
+        self.position(column)
+            .ok_or_else(|| eyre!("{} has no `{column}` column", self.path.display()))
+    }
+
+    /// Text of the non-empty cells of a column. Unquoted empty cells are missing values.
+    fn cell<'a>(&self, row: &'a [Field], i: usize) -> Option<&'a str> {
+        row.get(i)
+            .filter(|(text, quoted)| *quoted || !text.is_empty())
+            .map(|(text, _)| text.as_str())
+    }
+
+    /// How each column is read, checking the mapping only names columns the table has
+    fn columns(&self, mapping: &HashMap<String, ColumnMapping>) -> Result<Vec<Column>> {
+        if let Some(unknown) = mapping.keys().find(|name| self.position(name).is_none()) {
+            bail!(
+                "the mapping for {} has a `{unknown}` column the file doesn't",
+                self.path.display()
+            );
+        }
+        self.header
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                let (name, ty, skip) = match mapping.get(header) {
+                    None => (None, None, false),
+                    Some(ColumnMapping::Type(ty)) => (None, Some(ty.as_str()), false),
+                    Some(ColumnMapping::Column { name, ty, skip }) => {
+                        (name.clone(), ty.as_deref(), *skip)
+                    }
+                };
+                let ty = match ty {
+                    Some(ty) => ColumnType::from_name(ty)
+                        .map_err(|e| eyre!("{} column `{header}`: {e}", self.path.display()))?,
+                    None => ColumnType::infer(self.rows.iter().filter_map(|row| self.cell(row, i))),
+                };
+                Ok(Column {
+                    name: name.unwrap_or_else(|| header.clone()),
+                    ty,
+                    skip,
+                })
+            })
+            .collect()
+    }
+
+    /// Properties of a row, skipping `exclude`d columns
+    fn properties(&self, columns: &[Column], row: usize, exclude: &[usize]) -> Result<Properties> {
+        let mut properties = Properties::new();
+        for (i, column) in columns.iter().enumerate() {
+            if column.skip || exclude.contains(&i) {
+                continue;
+            }
+            let Some(text) = self.cell(&self.rows[row], i) else {
+                continue;
+            };
+            let value = column.ty.parse(text).ok_or_else(|| {
+                eyre!(
+                    "{} row {}: `{text}` in column `{}` isn't a {:?}",
+                    self.path.display(),
+                    row + 1,
+                    self.header[i],
+                    column.ty
+                )
+            })?;
+            properties.push((column.name.clone(), value));
+        }
+        Ok(properties)
+    }
+
+    fn check_width(&self, row: usize) -> Result<()> {
+        let width = self.rows[row].len();
+        if width != self.header.len() {
+            bail!(
+                "{} row {}: {width} fields where the header has {}",
+                self.path.display(),
+                row + 1,
+                self.header.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Nodes by label and id, to look up the endpoints of edges
+#[derive(Default)]
+struct NodeIds {
+    by_label: HashMap<(String, String), usize>,
+    labels: HashMap<String, Vec<String>>,
+}
+
+impl NodeIds {
+    fn find(&self, label: Option<&str>, id: &str) -> Result<usize> {
+        let label = match label {
+            Some(label) => label,
+            None => match self.labels.get(id).map(Vec::as_slice) {
+                Some([label]) => label.as_str(),
+                Some(labels) => bail!(
+                    "id `{id}` is used by {} nodes, set the edge table's from_label/to_label",
+                    labels.join(" and ")
+                ),
+                None => bail!("no node has id `{id}`"),
+            },
+        };
+        self.by_label
+            .get(&(label.to_string(), id.to_string()))
+            .copied()
+            .ok_or_else(|| eyre!("no {label} node has id `{id}`"))
+    }
+}
+
+fn parse_spec(spec: &str) -> Result<(PathBuf, &str)> {
+    let (file, label) = spec
+        .rsplit_once(':')
+        .filter(|(file, label)| !file.is_empty() && !label.is_empty())
+        .ok_or_else(|| eyre!("`{spec}` should be written as FILE:LABEL"))?;
+    Ok((PathBuf::from(file), label.trim()))
+}
+
+impl NodeTable {
+    /// A `users.csv:User` table from the command line
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        let (file, label) = parse_spec(spec)?;
+        Ok(Self {
+            file,
+            label: label.to_string(),
+            id: None,
+            delimiter: None,
+            columns: HashMap::new(),
+        })
+    }
+}
+
+impl EdgeTable {
+    /// A `follows.csv:Follows(from,to)` table from the command line, where `from` and `to` name
+    /// the endpoint id columns, as `column:Label` when ids repeat across node tables
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        let invalid = || eyre!("`{spec}` should be written as FILE:TYPE(FROM,TO)");
+        let (table, endpoints) = spec
+            .strip_suffix(')')
+            .and_then(|spec| spec.rsplit_once('('))
+            .ok_or_else(invalid)?;
+        let (file, label) = parse_spec(table).map_err(|_| invalid())?;
+        let (from, to) = endpoints.split_once(',').ok_or_else(invalid)?;
+        let endpoint = |endpoint: &str| match endpoint.split_once(':') {
+            Some((column, label)) => (column.trim().to_string(), Some(label.trim().to_string())),
+            None => (endpoint.trim().to_string(), None),
+        };
+        let ((from, from_label), (to, to_label)) = (endpoint(from), endpoint(to));
+        if from.is_empty() || to.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            file,
+            label: label.to_string(),
+            from,
+            to,
+            from_label,
+            to_label,
+            delimiter: None,
+            columns: HashMap::new(),
+        })
+    }
+}
+
+/// The same file, however the two paths spell it
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl TableMapping {
+    /// Read a mapping file, with table files relative to it unless they're object storage URLs
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read {}: {e}", path.display()))?;
+        let mut mapping: Self =
+            toml::from_str(&text).map_err(|e| eyre!("Invalid mapping {}: {e}", path.display()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let files = mapping
+            .nodes
+            .iter_mut()
+            .map(|table| &mut table.file)
+            .chain(mapping.edges.iter_mut().map(|table| &mut table.file))
+            .filter(|file| !Location::is_remote(file));
+        for file in files {
+            *file = dir.join(&*file);
+        }
+        Ok(mapping)
+    }
+
+    /// Add a table from the command line, which takes the column mapping of the same file's
+    /// table in the mapping file if there is one
+    pub fn add_nodes(&mut self, table: NodeTable) {
+        match self
+            .nodes
+            .iter_mut()
+            .find(|t| same_file(&t.file, &table.file))
+        {
+            Some(existing) => existing.label = table.label,
+            None => self.nodes.push(table),
+        }
+    }
+
+    pub fn add_edges(&mut self, table: EdgeTable) {
+        match self
+            .edges
+            .iter_mut()
+            .find(|t| same_file(&t.file, &table.file))
+        {
+            Some(existing) => {
+                existing.label = table.label;
+                existing.from = table.from;
+                existing.to = table.to;
+                existing.from_label = table.from_label.or(existing.from_label.take());
+                existing.to_label = table.to_label.or(existing.to_label.take());
+            }
+            None => self.edges.push(table),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    /// Read every table, nodes first so edges can refer to them
+    pub fn read(&self) -> Result<Dump> {
+        let mut dump = Dump::default();
+        let mut ids = NodeIds::default();
+        for node_table in &self.nodes {
+            let table = Table::read(&node_table.file, node_table.delimiter)?;
+            let columns = table.columns(&node_table.columns)?;
+            let id_column = match &node_table.id {
+                Some(id) => Some(table.require(id)?),
+                None => table.position(DEFAULT_ID_COLUMN),
+            };
+            for row in 0..table.rows.len() {
+                table.check_width(row)?;
+                let id = id_column.and_then(|i| table.cell(&table.rows[row], i));
+                if let Some(id) = id {
+                    let key = (node_table.label.clone(), id.to_string());
+                    if ids.by_label.insert(key, dump.nodes.len()).is_some() {
+                        bail!(
+                            "{} row {}: another {} has id `{id}`",
+                            table.path.display(),
+                            row + 1,
+                            node_table.label
+                        );
+                    }
+                    ids.labels
+                        .entry(id.to_string())
+                        .or_default()
+                        .push(node_table.label.clone());
+                }
+                dump.nodes.push(DumpNode {
+                    id: id.map(str::to_string),
+                    labels: vec![node_table.label.clone()],
+                    properties: table.properties(&columns, row, &[])?,
+                });
+            }
+        }
+
+        for edge_table in &self.edges {
+            let table = Table::read(&edge_table.file, edge_table.delimiter)?;
+            let columns = table.columns(&edge_table.columns)?;
+            let from = table.require(&edge_table.from)?;
+            let to = table.require(&edge_table.to)?;
+            for row in 0..table.rows.len() {
+                table.check_width(row)?;
+                let endpoint = |column: usize, label: &Option<String>| -> Result<usize> {
+                    let id = table
+                        .cell(&table.rows[row], column)
+                        .ok_or_else(|| eyre!("column `{}` is empty", table.header[column]))?;
+                    ids.find(label.as_deref(), id)
+                };
+                let (start, end) = endpoint(from, &edge_table.from_label)
+                    .and_then(|start| Ok((start, endpoint(to, &edge_table.to_label)?)))
+                    .map_err(|e| eyre!("{} row {}: {e}", table.path.display(), row + 1))?;
+                dump.relationships.push(DumpRelationship {
+                    id: None,
+                    rel_type: edge_table.label.clone(),
+                    start,
+                    end,
+                    properties: table.properties(&columns, row, &[from, to])?,
+                });
+            }
+        }
+        Ok(dump)
+    }
+}
@@ -0,0 +1,251 @@
+//! What GraphML and GEXF share: a small element tree to read them from, and the mapping between
+//! Helix values and typed attribute text.
+
+use super::temporal_value;
+use eyre::{Result, eyre};
+use helix_db::protocol::value::Value;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use std::borrow::Cow;
+
+/// An XML element, by local name (`viz:color` is `color`)
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Text directly inside the element
+    pub text: String,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+fn element(start: &BytesStart) -> Result<Element> {
+    let mut element = Element {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        ..Element::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        element.attributes.push((
+            String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+            attribute.unescape_value()?.into_owned(),
+        ));
+    }
+    Ok(element)
+}
+
+/// Parse a document into its root element
+pub(crate) fn parse(text: &str) -> Result<Element> {
+    let mut reader = Reader::from_str(text);
+    let mut open: Vec<Element> = Vec::new();
+    let mut root = None;
+    let mut close = |open: &mut Vec<Element>, element: Element| match open.last_mut() {
+        Some(parent) => parent.children.push(element),
+        None => root = Some(element),
+    };
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| eyre!("Invalid XML at byte {}: {e}", reader.error_position()))?;
+        match event {
+            Event::Start(start) => open.push(element(&start)?),
+            Event::Empty(start) => {
+                let element = element(&start)?;
+                close(&mut open, element);
+            }
+            Event::End(_) => {
+                let element = open
+                    .pop()
+                    .ok_or_else(|| eyre!("Invalid XML: unexpected closing tag"))?;
+                close(&mut open, element);
+            }
+            Event::Text(text) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = open.last_mut() {
+                    element
+                        .text
+                        .push_str(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        return Err(eyre!("Invalid XML: <{}> is never closed", open[0].name));
+    }
+    root.ok_or_else(|| eyre!("Invalid XML: the document is empty"))
+}
+
+pub(crate) fn escape(text: &str) -> Cow<'_, str> {
+    quick_xml::escape::escape(text)
+}
+
+/// Type of an attribute's values, as both formats declare them up front
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Boolean,
+    Long,
+    Double,
+    Date,
+    String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AttrType {
+    pub kind: Kind,
+    /// Values are lists of `kind`, written as JSON arrays
+    pub list: bool,
+}
+
+impl AttrType {
+    pub const STRING: Self = Self {
+        kind: Kind::String,
+        list: false,
+    };
+
+    /// Type of a value, `None` for nulls
+    pub fn of(value: &Value) -> Option<Self> {
+        let kind = match value {
+            Value::Empty => return None,
+            Value::Boolean(_) => Kind::Boolean,
+            Value::I8(_)
+            | Value::I16(_)
+            | Value::I32(_)
+            | Value::I64(_)
+            | Value::U8(_)
+            | Value::U16(_)
+            | Value::U32(_)
+            | Value::U64(_)
+            | Value::U128(_) => Kind::Long,
+            Value::F32(_) | Value::F64(_) => Kind::Double,
+            Value::Date(_) => Kind::Date,
+            Value::Array(items) => {
+                let kind = items
+                    .iter()
+                    .filter_map(Self::of)
+                    .map(|item| match item.list {
+                        true => Kind::String,
+                        false => item.kind,
+                    })
+                    .reduce(merge)
+                    .unwrap_or(Kind::String);
+                return Some(Self { kind, list: true });
+            }
+            Value::String(_) | Value::Id(_) | Value::Object(_) | Value::Path(_) => Kind::String,
+        };
+        Some(Self { kind, list: false })
+    }
+
+    /// A type both `self` and `other` values can be written as
+    pub fn merge(self, other: Self) -> Self {
+        match self.list == other.list {
+            true => Self {
+                kind: merge(self.kind, other.kind),
+                list: self.list,
+            },
+            false => Self::STRING,
+        }
+    }
+
+    /// Read a value written as this type, keeping text that doesn't parse as a string
+    pub fn parse(self, text: &str) -> Value {
+        if !self.list {
+            return scalar(self.kind, text);
+        }
+        let items = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Array(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    serde_json::Value::String(text) => scalar(self.kind, &text),
+                    other => scalar(self.kind, &other.to_string()),
+                })
+                .collect(),
+            // GEXF 1.2 separates list items with `|`, 1.3 brackets them
+            _ => text
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split(if text.contains('|') { '|' } else { ',' })
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| scalar(self.kind, item))
+                .collect(),
+        };
+        Value::Array(items)
+    }
+}
+
+fn merge(a: Kind, b: Kind) -> Kind {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (Kind::Long, Kind::Double) | (Kind::Double, Kind::Long) => Kind::Double,
+        _ => Kind::String,
+    }
+}
+
+fn scalar(kind: Kind, text: &str) -> Value {
+    let trimmed = text.trim();
+    let parsed = match kind {
+        Kind::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "1" => Some(Value::Boolean(true)),
+            "false" | "0" => Some(Value::Boolean(false)),
+            _ => None,
+        },
+        Kind::Long => trimmed.parse().ok().map(Value::I64),
+        Kind::Double => trimmed.parse().ok().map(Value::F64),
+        Kind::Date => Some(temporal_value("datetime", trimmed)),
+        Kind::String => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+/// A value as attribute text: dates in RFC 3339, lists and maps as JSON
+pub(crate) fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Date(date) => date.to_rfc3339(),
+        Value::Array(_) | Value::Object(_) => {
+            serde_json::to_string(value).expect("values serialize to JSON")
+        }
+        Value::Empty => String::new(),
+        other => other.inner_stringify(),
+    }
+}
+
+/// Names and types of the properties in `items`, in first-seen order
+pub(crate) fn attributes<'a>(
+    items: impl Iterator<Item = &'a [(String, Value)]>,
+) -> Vec<(String, AttrType)> {
+    let mut attributes: Vec<(String, AttrType)> = Vec::new();
+    for (key, value) in items.flatten() {
+        let Some(found) = AttrType::of(value) else {
+            continue;
+        };
+        match attributes.iter_mut().find(|(name, _)| name == key) {
+            Some((_, ty)) => *ty = ty.merge(found),
+            None => attributes.push((key.clone(), found)),
+        }
+    }
+    attributes
+}
@@ -7,6 +7,7 @@ use crate::config::{CloudConfig, HelixConfig};
 use crate::docker::DockerManager;
 use crate::errors::project_error;
 use crate::output::{Operation, Step};
+use crate::port;
 use crate::project::ProjectContext;
 use crate::prompts;
 use crate::utils::print_instructions;
@@ -73,6 +74,14 @@ async fn run_init_inner(
         .into());
     }
 
+    if let Some(CloudDeploymentTypeCommand::StandbyOf { instance, .. }) = &deployment_type {
+        return Err(project_error(format!(
+            "a new project has no instance '{instance}' to be a standby of"
+        ))
+        .with_hint("initialize the project first, then run 'helix add standby-of <instance>'")
+        .into());
+    }
+
     let op = Operation::new("Initializing", project_name);
 
     // Create project directory if it doesn't exist
@@ -86,6 +95,11 @@ async fn run_init_inner(
     let mut config = HelixConfig::default_config(project_name);
     config.project.queries = std::path::PathBuf::from(&queries_path);
 
+    // Give the dev instance a port no other project on this machine uses
+    if let Some(dev) = config.local.get_mut("dev") {
+        dev.port = Some(port::assign_port(&project_dir, "dev")?);
+    }
+
     // Save initial config and track it
     config.save_to_file(&config_path)?;
     cleanup_tracker.track_file(config_path.clone());
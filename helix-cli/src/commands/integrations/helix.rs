@@ -3,6 +3,7 @@ use crate::config::{BuildMode, CloudInstanceConfig, DbConfig, InstanceInfo};
 use crate::output;
 use crate::project::ProjectContext;
 use crate::sse_client::{SseEvent, SseProgressHandler};
+use crate::user_config::UserConfig;
 use crate::utils::helixc_utils::{collect_hx_files, generate_content};
 use crate::utils::print_error;
 use eyre::{Result, eyre};
@@ -44,8 +45,8 @@ impl<'a> HelixManager<'a> {
         // let cluster_id = format!("helix-{}-{}", instance_name, Uuid::new_v4());
         let cluster_id = "YOUR_CLUSTER_ID".to_string();
 
-        // Use provided region or default to us-east-1
-        let region = region.or_else(|| Some("us-east-1".to_string()));
+        // Use provided region or the configured default
+        let region = region.or_else(|| Some(UserConfig::current().region()));
 
         Ok(CloudInstanceConfig {
             cluster_id,
@@ -3,14 +3,26 @@
 use super::log_source::LogSource;
 use chrono::{DateTime, Duration, Utc};
 use eyre::{Result, eyre};
+use helix_db::helix_gateway::slow_query::SLOW_QUERY_TARGET;
+
+/// Whether a log line is a slow query event emitted by the gateway.
+pub fn is_slow_query_line(line: &str) -> bool {
+    line.contains(SLOW_QUERY_TARGET)
+}
 
 /// Stream live logs to stdout until interrupted.
-pub async fn stream_live(log_source: &LogSource) -> Result<()> {
-    println!("Streaming logs (Ctrl+C to stop)...\n");
+pub async fn stream_live(log_source: &LogSource, slow: bool) -> Result<()> {
+    if slow {
+        println!("Streaming slow queries (Ctrl+C to stop)...\n");
+    } else {
+        println!("Streaming logs (Ctrl+C to stop)...\n");
+    }
 
     log_source
         .stream_live(|line| {
-            println!("{}", line);
+            if !slow || is_slow_query_line(&line) {
+                println!("{}", line);
+            }
         })
         .await
 }
@@ -20,6 +32,7 @@ pub async fn query_range(
     log_source: &LogSource,
     start: Option<String>,
     end: Option<String>,
+    slow: bool,
 ) -> Result<()> {
     let (start_time, end_time) = parse_time_range(start, end)?;
 
@@ -42,9 +55,14 @@ pub async fn query_range(
         end_time.format("%Y-%m-%d %H:%M:%S UTC")
     );
 
-    let logs = log_source.query_range(start_time, end_time).await?;
+    let mut logs = log_source.query_range(start_time, end_time).await?;
+    if slow {
+        logs.retain(|line| is_slow_query_line(line));
+    }
 
-    if logs.is_empty() {
+    if logs.is_empty() && slow {
+        println!("No slow queries found in the specified time range.");
+    } else if logs.is_empty() {
         println!("No logs found in the specified time range.");
     } else {
         for line in logs {
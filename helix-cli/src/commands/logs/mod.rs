@@ -1,7 +1,8 @@
 //! `helix logs` command for viewing instance logs.
 //!
 //! Supports two modes:
-//! - CLI mode (with flags): Non-interactive log streaming/querying
+//! - CLI mode (with flags): Non-interactive log streaming/querying, optionally
+//!   restricted to slow query events with `--slow`
 //! - TUI mode (no flags): Interactive terminal UI with tabs and hotkeys
 
 mod cli;
@@ -22,6 +23,7 @@ pub async fn run(
     range: bool,
     start: Option<String>,
     end: Option<String>,
+    slow: bool,
 ) -> Result<()> {
     // Load project context
     let project = ProjectContext::find_and_load(None)?;
@@ -62,9 +64,9 @@ pub async fn run(
 
     // Route to appropriate mode
     if live {
-        cli::stream_live(&log_source).await
-    } else if range {
-        cli::query_range(&log_source, start, end).await
+        cli::stream_live(&log_source, slow).await
+    } else if range || slow {
+        cli::query_range(&log_source, start, end, slow).await
     } else {
         // TUI mode (default when no flags)
         tui::run(log_source, instance_name).await
@@ -2,20 +2,34 @@ use std::{io, sync::LazyLock};
 
 use crate::{
     MetricsAction,
-    metrics_sender::{MetricsLevel, load_metrics_config, save_metrics_config},
+    metrics_sender::{MetricsLevel, load_metrics_config, metrics_level, save_metrics_config},
     output,
+    user_config::UserConfig,
+    utils::print_warning,
 };
 use color_eyre::owo_colors::OwoColorize;
 use eyre::Result;
 use regex::Regex;
 
 pub async fn run(action: MetricsAction) -> Result<()> {
-    match action {
+    let result = match action {
         MetricsAction::Full => enable_full_metrics().await,
         MetricsAction::Basic => enable_basic_metrics().await,
         MetricsAction::Off => disable_metrics().await,
-        MetricsAction::Status => show_metrics_status().await,
+        MetricsAction::Status => return show_metrics_status().await,
+    };
+    if let Some(level) = configured_level() {
+        print_warning(&format!(
+            "telemetry is set to {level:?} in the user config, which takes precedence; \
+             run 'helix config unset telemetry' to use this level"
+        ));
     }
+    result
+}
+
+/// Level set with `helix config set telemetry`, overriding this command's
+fn configured_level() -> Option<MetricsLevel> {
+    UserConfig::load().ok().and_then(|config| config.telemetry)
 }
 
 async fn enable_full_metrics() -> Result<()> {
@@ -77,8 +91,11 @@ async fn show_metrics_status() -> Result<()> {
     println!(
         "  {}: {:?}",
         "Metrics Level".bright_white().bold(),
-        config.level
+        metrics_level(&config)
     );
+    if configured_level().is_some() {
+        println!("  (set by telemetry in the user config)");
+    }
 
     if let Some(user_id) = &config.user_id {
         println!("  {}: {user_id}", "User ID".bright_white().bold());
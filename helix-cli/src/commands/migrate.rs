@@ -1,6 +1,6 @@
 use crate::config::{
-    BuildMode, ContainerRuntime, DbConfig, GraphConfig, HelixConfig, LocalInstanceConfig,
-    ProjectConfig, VectorConfig,
+    BuildMode, ContainerRuntime, DbConfig, GatewayConfig, GraphConfig, HelixConfig,
+    LocalInstanceConfig, ProjectConfig, VectorConfig,
 };
 use crate::errors::{CliError, project_error};
 use crate::output;
@@ -452,6 +452,7 @@ fn create_v2_config(ctx: &MigrationContext) -> Result<()> {
         ef_construction: ctx.v1_config.vector_config.ef_construction,
         ef_search: ctx.v1_config.vector_config.ef_search,
         db_max_size_gb: ctx.v1_config.db_max_size_gb,
+        ivf_flat: None,
     };
 
     // Create graph config
@@ -468,12 +469,15 @@ fn create_v2_config(ctx: &MigrationContext) -> Result<()> {
         schema: None,
         embedding_model: Some("text-embedding-ada-002".to_string()),
         graphvis_node_label: None,
+        gateway_config: GatewayConfig::default(),
     };
 
     // Create local instance config
     let local_config = LocalInstanceConfig {
         port: Some(ctx.port),
         build_mode: BuildMode::Dev,
+        resources: None,
+        standby_of: None,
         db_config,
     };
 
@@ -486,6 +490,8 @@ fn create_v2_config(ctx: &MigrationContext) -> Result<()> {
         name: ctx.project_name.clone(),
         queries: PathBuf::from(&ctx.queries_dir),
         container_runtime: ContainerRuntime::Docker,
+        complexity_budget: None,
+        codegen_profile: None,
     };
 
     // Create final helix config
@@ -1,22 +1,34 @@
 pub mod add;
 pub mod auth;
 pub mod backup;
+pub mod bench;
 pub mod build;
 pub mod check;
 pub mod compile;
+pub mod config;
 pub mod create_cluster;
 pub mod dashboard;
+pub mod data;
 pub mod delete;
+pub mod export;
 pub mod feedback;
+pub mod fsck;
+pub mod generate;
+pub mod import;
 pub mod init;
 pub mod integrations;
 pub mod logs;
 pub mod metrics;
 pub mod migrate;
+pub mod promote;
 pub mod prune;
 pub mod pull;
+pub mod ps;
 pub mod push;
+pub mod reload;
+pub mod replay;
 pub mod restart;
+pub mod seed;
 pub mod start;
 pub mod status;
 pub mod stop;
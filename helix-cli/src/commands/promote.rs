@@ -0,0 +1,112 @@
+use crate::config::LocalInstanceConfig;
+use crate::docker::DockerManager;
+use crate::errors::project_error;
+use crate::output::{Operation, Step};
+use crate::project::ProjectContext;
+use crate::registry;
+use crate::utils::print_instructions;
+use eyre::Result;
+use std::fs;
+
+/// Make a standby the leader: stop its leader so only one instance takes writes, swap their
+/// roles in helix.toml, and restart the standby, which takes over the last copy it synced
+pub async fn run(instance_name: String) -> Result<()> {
+    let mut project = ProjectContext::find_and_load(None)?;
+
+    let leader_name = match project.config.local.get(&instance_name) {
+        Some(LocalInstanceConfig {
+            standby_of: Some(leader),
+            ..
+        }) => leader.clone(),
+        Some(_) => {
+            return Err(
+                project_error(format!("instance '{instance_name}' is not a standby"))
+                    .with_hint("add one with 'helix add standby-of <instance>'")
+                    .into(),
+            );
+        }
+        None => {
+            return Err(project_error(format!(
+                "'{instance_name}' is not a local instance in helix.toml"
+            ))
+            .into());
+        }
+    };
+
+    let op = Operation::new("Promoting", &instance_name);
+    let docker = DockerManager::new(&project);
+    DockerManager::check_runtime_available(docker.runtime)?;
+
+    if docker.instance_running(&leader_name)? {
+        let mut stop_step = Step::with_messages(
+            &format!("Stopping old leader '{leader_name}'"),
+            "Old leader stopped",
+        );
+        stop_step.start();
+        docker.stop_instance(&leader_name)?;
+        stop_step.done();
+    }
+
+    // The old leader comes back as a standby of the new one
+    if let Some(standby) = project.config.local.get_mut(&instance_name) {
+        standby.standby_of = None;
+    }
+    if let Some(leader) = project.config.local.get_mut(&leader_name) {
+        leader.standby_of = Some(instance_name.clone());
+    }
+    project
+        .config
+        .save_to_file(&project.root.join("helix.toml"))?;
+    Step::verbose_substep("Swapped the roles in helix.toml");
+
+    // Roles are passed to the containers in their compose files
+    let docker = DockerManager::new(&project);
+    for name in [&instance_name, &leader_name] {
+        let compose_path = project.docker_compose_path(name);
+        if compose_path.exists() {
+            let instance = project.config.get_instance(name)?;
+            fs::write(
+                &compose_path,
+                docker.generate_docker_compose(name, instance, None)?,
+            )?;
+        }
+    }
+
+    if !project.docker_compose_path(&instance_name).exists() {
+        op.failure();
+        let error = crate::errors::CliError::new(format!(
+            "instance '{instance_name}' has not been built yet"
+        ))
+        .with_hint(format!(
+            "run 'helix push {instance_name}' to start it as the leader"
+        ));
+        return Err(eyre::eyre!("{}", error.render()));
+    }
+
+    let mut start_step = Step::with_messages("Restarting as leader", "Restarted as leader");
+    start_step.start();
+    docker.stop_instance(&instance_name)?;
+    docker.start_instance(&instance_name)?;
+    start_step.done();
+    let container = (docker.runtime, docker.container_name(&instance_name));
+    registry::record_start(&project, &instance_name, Some(container), None);
+
+    op.success();
+
+    let port = project
+        .config
+        .get_instance(&instance_name)?
+        .port()
+        .unwrap_or(6969);
+    print_instructions(
+        "Next steps:",
+        &[
+            &format!("Send requests to '{instance_name}' on port {port}"),
+            &format!(
+                "Run 'helix start {leader_name}' to bring the old leader back as a standby of '{instance_name}'"
+            ),
+        ],
+    );
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::config::ContainerRuntime;
 use crate::docker::DockerManager;
 use crate::errors::project_error;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step, Verbosity};
 use crate::project::ProjectContext;
 use crate::utils::{print_confirm, print_lines, print_newline, print_warning};
@@ -53,6 +54,9 @@ async fn prune_instance(project: &ProjectContext, instance_name: &str) -> Result
         docker_step.done();
     }
 
+    // Stop a native process, which runs from the workspace
+    NativeManager::new(project).stop_instance(instance_name)?;
+
     // Remove instance workspace directory
     let workspace = project.instance_workspace(instance_name);
     if workspace.exists() {
@@ -118,6 +122,9 @@ async fn prune_all_instances(project: &ProjectContext) -> Result<()> {
             // Remove Docker images
             let _ = docker.remove_instance_images(instance_name);
 
+            // Stop a native process before its workspace goes
+            let _ = NativeManager::new(project).stop_instance(instance_name);
+
             // Remove workspace
             let workspace = project.instance_workspace(instance_name);
             if workspace.exists() {
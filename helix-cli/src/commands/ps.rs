@@ -0,0 +1,136 @@
+//! `helix ps` command for listing running local instances, from the registry of every
+//! instance started on this machine.
+//!
+//! Without `--all-projects` only the current project's instances are listed. Instances whose
+//! project directory is gone are still listed while they run, so their container or process
+//! can be found and stopped.
+
+use crate::commands::status::format_table;
+use crate::errors::CliError;
+use crate::native;
+use crate::port::project_key;
+use crate::project::ProjectContext;
+use crate::registry::{InstanceRegistry, StartedInstance};
+use crate::utils::{print_info, print_line, print_warning};
+use eyre::{Result, eyre};
+use std::collections::HashSet;
+use std::process::Command;
+
+const PS_COLUMNS: [&str; 6] = [
+    "PROJECT",
+    "INSTANCE",
+    "PORT",
+    "RUNS AS",
+    "DATA",
+    "DIRECTORY",
+];
+
+pub async fn run(all_projects: bool) -> Result<()> {
+    let project = match all_projects {
+        true => None,
+        false => match ProjectContext::find_and_load(None) {
+            Ok(project) => Some(project_key(&project.root)),
+            Err(_) => {
+                let error = CliError::new("not in a Helix project directory").with_hint(
+                    "run 'helix ps --all-projects' to list the instances of every project",
+                );
+                return Err(eyre!("{}", error.render()));
+            }
+        },
+    };
+
+    let mut registry = InstanceRegistry::load()?;
+    let running_containers = running_containers(registry.instances());
+    let is_running = |instance: &StartedInstance| instance_running(instance, &running_containers);
+
+    // Nothing is left to find of a stopped instance whose project is gone
+    let keep = |instance: &StartedInstance| instance.project_exists() || is_running(instance);
+    if registry.retain(keep)
+        && let Err(e) = InstanceRegistry::update(|registry| registry.retain(keep))
+    {
+        print_warning(&format!("Could not save instance registry: {e}"));
+    }
+
+    let running = registry
+        .instances()
+        .iter()
+        .filter(|instance| {
+            project
+                .as_ref()
+                .is_none_or(|root| &instance.project == root)
+        })
+        .filter(|instance| is_running(instance))
+        .collect::<Vec<_>>();
+    if running.is_empty() {
+        print_info(match project {
+            Some(_) => "No instances of this project are running, see 'helix ps --all-projects'",
+            None => "No local instances are running",
+        });
+        return Ok(());
+    }
+    print_line(&format_table(&PS_COLUMNS, &ps_rows(&running)));
+    Ok(())
+}
+
+/// Names of the running containers of every runtime the instances were started with. A
+/// runtime that can't be reached has none.
+fn running_containers(instances: &[StartedInstance]) -> HashSet<String> {
+    let mut runtimes = Vec::new();
+    for runtime in instances.iter().filter_map(|instance| instance.runtime) {
+        if !runtimes.contains(&runtime) {
+            runtimes.push(runtime);
+        }
+    }
+    let mut names = HashSet::new();
+    for runtime in runtimes {
+        let Ok(output) = Command::new(runtime.binary())
+            .args(["ps", "--format", "{{.Names}}"])
+            .output()
+        else {
+            continue;
+        };
+        if output.status.success() {
+            names.extend(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|name| name.trim().to_string()),
+            );
+        }
+    }
+    names
+}
+
+/// Whether the instance's container or native process is still running
+pub fn instance_running(instance: &StartedInstance, running_containers: &HashSet<String>) -> bool {
+    match (&instance.container, instance.pid) {
+        (Some(container), _) => running_containers.contains(container),
+        (None, Some(pid)) => native::process_running(pid),
+        (None, None) => false,
+    }
+}
+
+/// One row per instance, flagging those whose project directory is gone
+pub fn ps_rows(instances: &[&StartedInstance]) -> Vec<Vec<String>> {
+    instances
+        .iter()
+        .map(|instance| {
+            let runs_as = match (&instance.container, instance.pid) {
+                (Some(container), _) => container.clone(),
+                (None, Some(pid)) => format!("pid {pid}"),
+                (None, None) => "-".to_string(),
+            };
+            let directory = match instance.project_exists() {
+                true => instance.project.display().to_string(),
+                false => format!("{} (missing)", instance.project.display()),
+            };
+            vec![
+                instance.project_name.clone(),
+                instance.instance.clone(),
+                instance.port.to_string(),
+                runs_as,
+                instance.data_dir.display().to_string(),
+                directory,
+            ]
+        })
+        .collect()
+}
@@ -1,21 +1,26 @@
 use crate::commands::auth::require_auth;
-use crate::commands::build::MetricsData;
+use crate::commands::build::{self, BuildOutput, MetricsData};
 use crate::commands::integrations::ecr::EcrManager;
 use crate::commands::integrations::fly::FlyManager;
 use crate::commands::integrations::helix::HelixManager;
-use crate::config::{BuildMode, CloudConfig, InstanceInfo};
+use crate::config::{BuildMode, CloudConfig, HelixConfig, InstanceInfo};
 use crate::docker::DockerManager;
+use crate::errors::CliError;
 use crate::metrics_sender::MetricsSender;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step, Verbosity};
 use crate::port;
 use crate::project::ProjectContext;
 use crate::prompts;
-use eyre::Result;
+use crate::registry;
+use eyre::{OptionExt, Result};
+use std::path::Path;
 use std::time::Instant;
 
 pub async fn run(
     instance_name: Option<String>,
     dev: bool,
+    native: bool,
     metrics_sender: &MetricsSender,
 ) -> Result<()> {
     let start_time = Instant::now();
@@ -57,7 +62,17 @@ pub async fn run(
         require_auth().await?;
     }
 
-    let deploy_result = if instance_config.is_local() {
+    if native && !instance_config.is_local() {
+        let error = CliError::new(format!(
+            "'{instance_name}' is not a local instance, only local instances run natively"
+        ))
+        .with_hint(format!("run 'helix push {instance_name}' without --native"));
+        return Err(eyre::eyre!("{}", error.render()));
+    }
+
+    let deploy_result = if native {
+        push_native_instance(&project, &instance_name, metrics_sender).await
+    } else if instance_config.is_local() {
         push_local_instance(&project, &instance_name, metrics_sender).await
     } else {
         push_cloud_instance(
@@ -132,6 +147,12 @@ async fn push_local_instance(
     // Check Docker availability
     DockerManager::check_runtime_available(docker.runtime)?;
 
+    // The instance runs as a container again, so its native process must free the port
+    let native = NativeManager::new(project);
+    if native.is_native(instance_name) {
+        native.remove(instance_name)?;
+    }
+
     // Check port availability before building
     let instance_config = project.config.get_instance(instance_name)?;
     let requested_port = instance_config.port().unwrap_or(port::DEFAULT_PORT);
@@ -139,15 +160,27 @@ async fn push_local_instance(
 
     if port_changed {
         crate::output::warning(&format!(
-            "Port {} is in use. Using port {} instead.",
+            "Port {} is in use. Using port {} instead, saved to helix.toml.",
             requested_port, actual_port
         ));
+        let config_path = project.root.join("helix.toml");
+        let mut config = HelixConfig::from_file(&config_path)?;
+        if let Some(local) = config.local.get_mut(instance_name) {
+            local.port = Some(actual_port);
+        }
+        config.save_to_file(&config_path)?;
     }
+    port::record_port(actual_port, &project.root, instance_name);
 
     // Build the instance first (this ensures it's up to date) and get metrics data
-    let metrics_data =
-        crate::commands::build::run_build_steps(&op, project, instance_name, None, metrics_sender)
-            .await?;
+    let (metrics_data, _) = build::run_build_steps(
+        &op,
+        project,
+        instance_name,
+        BuildOutput::Image,
+        metrics_sender,
+    )
+    .await?;
 
     // If port changed, regenerate docker-compose with new port
     if port_changed {
@@ -165,6 +198,11 @@ async fn push_local_instance(
     start_step.start();
     docker.start_instance(instance_name)?;
     start_step.done();
+    let container = (docker.runtime, docker.container_name(instance_name));
+    registry::record_start(project, instance_name, Some(container), None);
+    if let Err(e) = project.record_deploy(instance_name) {
+        crate::output::warning(&format!("Could not record deploy time: {e}"));
+    }
 
     op.success();
 
@@ -186,6 +224,94 @@ async fn push_local_instance(
     Ok(metrics_data)
 }
 
+/// Build the instance's binary and run it as a background process instead of a container
+async fn push_native_instance(
+    project: &ProjectContext,
+    instance_name: &str,
+    metrics_sender: &MetricsSender,
+) -> Result<MetricsData> {
+    let op = Operation::new("Deploying", instance_name);
+
+    let native = NativeManager::new(project);
+    let docker = DockerManager::new(project);
+    // Without a container runtime there's no container to replace
+    let container_running = docker.instance_running(instance_name).unwrap_or(false);
+
+    // The instance's own process or container frees its port once replaced
+    let instance_config = project.config.get_instance(instance_name)?;
+    let port = instance_config.port().unwrap_or(port::DEFAULT_PORT);
+    if native.running_pid(instance_name).is_none()
+        && !container_running
+        && let Err(e) = port::check_instance_port(port, &project.root, instance_name)
+    {
+        op.failure();
+        return Err(e);
+    }
+    port::record_port(port, &project.root, instance_name);
+
+    let (metrics_data, binary) = build::run_build_steps(
+        &op,
+        project,
+        instance_name,
+        BuildOutput::Native,
+        metrics_sender,
+    )
+    .await?;
+    let binary = binary.ok_or_eyre("native builds produce a binary")?;
+
+    let mut start_step = Step::with_messages("Starting process", "Process started");
+    start_step.start();
+    let pid = match replace_with_native(&docker, &native, instance_name, &binary, container_running)
+    {
+        Ok(pid) => pid,
+        Err(e) => {
+            start_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    start_step.done();
+    registry::record_start(project, instance_name, None, Some(pid));
+    if let Err(e) = project.record_deploy(instance_name) {
+        crate::output::warning(&format!("Could not record deploy time: {e}"));
+    }
+
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        Operation::print_details(&[
+            ("Local URL", &format!("http://localhost:{port}")),
+            ("Process", &pid.to_string()),
+            (
+                "Log file",
+                &native.log_path(instance_name).display().to_string(),
+            ),
+            (
+                "Data volume",
+                &project.instance_volume(instance_name).display().to_string(),
+            ),
+        ]);
+    }
+
+    Ok(metrics_data)
+}
+
+/// Stop whatever runs the instance now and start the freshly built binary in its place
+fn replace_with_native(
+    docker: &DockerManager,
+    native: &NativeManager,
+    instance_name: &str,
+    binary: &Path,
+    container_running: bool,
+) -> Result<u32> {
+    if container_running {
+        docker.stop_instance(instance_name)?;
+    }
+    native.stop_instance(instance_name)?;
+    native.install_binary(instance_name, binary)?;
+    native.start_instance(instance_name)
+}
+
 async fn push_cloud_instance(
     project: &ProjectContext,
     instance_name: &str,
@@ -209,7 +335,8 @@ async fn push_cloud_instance(
 
     let metrics_data = if instance_config.should_build_docker_image() {
         // Build happens, get metrics data from build
-        crate::commands::build::run(Some(instance_name.to_string()), None, metrics_sender).await?
+        crate::commands::build::run(Some(instance_name.to_string()), None, None, metrics_sender)
+            .await?
     } else {
         // No build, use lightweight parsing
         parse_queries_for_metrics(project)?
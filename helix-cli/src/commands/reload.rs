@@ -0,0 +1,110 @@
+//! `helix reload` command for applying an instance's tunables without restarting it.
+//!
+//! The log level, slow query threshold, result cache size and rate limits in the instance's
+//! `gateway_config` are sent to its `/admin/reload` endpoint. Tunables left unset go back to
+//! their defaults. The rest of the configuration only changes on the next `helix push`.
+
+use crate::config::GatewayConfig;
+use crate::errors::CliError;
+use crate::output::{Operation, Step};
+use crate::project::ProjectContext;
+use crate::utils::{print_field, print_header, print_newline};
+use eyre::{Result, eyre};
+use helix_db::helix_gateway::reload::Tunables;
+
+const DEFAULT_HELIX_PORT: u16 = 6969;
+
+pub async fn run(instance_name: String, url: Option<String>) -> Result<()> {
+    let project = ProjectContext::find_and_load(None)?;
+    let instance = project.config.get_instance(&instance_name)?;
+    let url = match url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None if instance.is_local() => format!(
+            "http://localhost:{}",
+            instance.port().unwrap_or(DEFAULT_HELIX_PORT)
+        ),
+        None => {
+            let error = CliError::new(format!("can't tell the URL of instance '{instance_name}'"))
+                .with_hint(
+                    "pass the instance's URL with --url, e.g. https://my-instance.example.com",
+                );
+            return Err(eyre!("{}", error.render()));
+        }
+    };
+    let tunables = tunables(&instance.db_config().gateway_config)?;
+
+    let op = Operation::new("Reloading", &instance_name);
+    let mut reload_step = Step::with_messages("Sending tunables", "Tunables applied");
+    reload_step.start();
+    let effective = match send(&url, &tunables).await {
+        Ok(effective) => effective,
+        Err(e) => {
+            reload_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    reload_step.done();
+    op.success();
+
+    print_newline();
+    print_header("Now running with:");
+    print_field("Log level", effective.log_level.as_deref().unwrap_or("-"));
+    print_field(
+        "Slow queries",
+        &effective
+            .slow_query_ms
+            .map_or("not logged".to_string(), |ms| {
+                format!("{ms}ms and slower logged")
+            }),
+    );
+    print_field(
+        "Cached results",
+        &effective
+            .cache_max_entries
+            .map_or("-".to_string(), |entries| entries.to_string()),
+    );
+    print_field(
+        "Rate limits",
+        match effective.rate_limit {
+            Some(_) => "configured",
+            None => "none",
+        },
+    );
+    Ok(())
+}
+
+/// The tunables of an instance's gateway config
+pub fn tunables(config: &GatewayConfig) -> Result<Tunables> {
+    let rate_limit = config
+        .rate_limit
+        .as_ref()
+        .map(|rate_limit| serde_json::from_value(serde_json::to_value(rate_limit)?))
+        .transpose()?;
+    Ok(Tunables {
+        log_level: config.log_level.clone(),
+        slow_query_ms: config.slow_query_ms,
+        cache_max_entries: config.cache_max_entries,
+        rate_limit,
+    })
+}
+
+/// POST the tunables to the instance at `url`, answering with the values it now runs with
+pub async fn send(url: &str, tunables: &Tunables) -> Result<Tunables> {
+    let mut request = reqwest::Client::new()
+        .post(format!("{url}/admin/reload"))
+        .json(tunables);
+    if let Ok(api_key) = std::env::var("HELIX_API_KEY") {
+        request = request.header("x-api-key", &api_key).bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| eyre!("Failed to reach {url}: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!("{url} answered {status}: {body}"));
+    }
+    Ok(response.json().await?)
+}
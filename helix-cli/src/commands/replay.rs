@@ -0,0 +1,325 @@
+//! `helix replay` command for re-running a recorded workload against another instance.
+//!
+//! The log is NDJSON with a query per line: its route name, JSON parameters and when it ran,
+//! e.g. `{"query": "getUser", "params": {"id": "..."}, "timestamp": "2026-01-15T10:00:00Z"}`.
+//! `name` is read as `query`, and timestamps are RFC 3339 strings or milliseconds since the
+//! Unix epoch (`timestamp_ms`, as in the audit log). Queries are sent at the gaps they were
+//! logged with, scaled by `--speed`, so the target sees the same shape of load.
+
+use crate::errors::CliError;
+use crate::location::Location;
+use crate::output::{Operation, Step, Verbosity};
+use crate::project::ProjectContext;
+use crate::utils::{print_field, print_header, print_newline};
+use chrono::DateTime;
+use eyre::{Result, eyre};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Port local instances listen on when helix.toml doesn't set one
+const DEFAULT_HELIX_PORT: u16 = 6969;
+
+/// When a logged query ran: an RFC 3339 string or milliseconds since the Unix epoch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    Millis(i64),
+    Rfc3339(String),
+}
+
+#[derive(Deserialize)]
+struct LogLine {
+    #[serde(alias = "name")]
+    query: String,
+    #[serde(default)]
+    params: serde_json::Value,
+    #[serde(alias = "timestamp_ms")]
+    timestamp: Timestamp,
+}
+
+/// A query read from a log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedQuery {
+    pub query: String,
+    pub params: serde_json::Value,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: i64,
+}
+
+/// Parse an NDJSON query log, in the order the queries ran. Blank lines are skipped.
+pub fn parse_log(text: &str) -> Result<Vec<LoggedQuery>> {
+    let mut queries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 1;
+        let entry: LogLine = serde_json::from_str(line)
+            .map_err(|e| eyre!("Invalid query log entry on line {line_number}: {e}"))?;
+        let timestamp_ms = match entry.timestamp {
+            Timestamp::Millis(ms) => ms,
+            Timestamp::Rfc3339(text) => DateTime::parse_from_rfc3339(&text)
+                .map_err(|e| eyre!("Invalid timestamp '{text}' on line {line_number}: {e}"))?
+                .timestamp_millis(),
+        };
+        let params = match entry.params {
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            params => params,
+        };
+        queries.push(LoggedQuery {
+            query: entry.query,
+            params,
+            timestamp_ms,
+        });
+    }
+    // Logs written by several gateways may interleave out of order
+    queries.sort_by_key(|query| query.timestamp_ms);
+    Ok(queries)
+}
+
+/// Parse a replay speed such as `2x`, `0.5x` or `1`. `max` sends every query as soon as a
+/// connection is free, and gives `None`.
+pub fn parse_speed(speed: &str) -> Result<Option<f64>> {
+    if speed.eq_ignore_ascii_case("max") {
+        return Ok(None);
+    }
+    match speed
+        .strip_suffix(['x', 'X'])
+        .unwrap_or(speed)
+        .parse::<f64>()
+    {
+        Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(Some(factor)),
+        _ => {
+            let error = CliError::new(format!("invalid replay speed '{speed}'"))
+                .with_hint("use a positive factor like 2x or 0.5x, or max");
+            Err(eyre!("{}", error.render()))
+        }
+    }
+}
+
+/// How a replayed query fared on the target
+#[derive(Debug, Default, Clone)]
+pub struct QueryReplay {
+    pub requests: usize,
+    /// Requests that failed to send or got an error status
+    pub errors: usize,
+    pub latencies: Vec<Duration>,
+}
+
+impl QueryReplay {
+    /// Latency below which `percentile` percent of the requests completed
+    pub fn latency_percentile(&self, percentile: f64) -> Duration {
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+        percentile_of_sorted(&latencies, percentile)
+    }
+}
+
+/// The `percentile`th value of sorted samples, by the nearest-rank method
+pub fn percentile_of_sorted(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Outcome of a replay, per query
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub queries: BTreeMap<String, QueryReplay>,
+    pub elapsed: Duration,
+    /// Error of the first failed request, to show what went wrong
+    pub first_error: Option<String>,
+}
+
+impl ReplayReport {
+    pub fn requests(&self) -> usize {
+        self.queries.values().map(|query| query.requests).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.queries.values().map(|query| query.errors).sum()
+    }
+}
+
+pub async fn run(log: String, target: String, speed: String, concurrency: usize) -> Result<()> {
+    let speed = parse_speed(&speed)?;
+    if concurrency == 0 {
+        return Err(eyre!("--concurrency must be at least 1"));
+    }
+    let url = target_url(&target)?;
+
+    let staging = tempfile::tempdir()?;
+    let path = match Location::parse(&log)? {
+        Location::Local(path) => path,
+        Location::Remote(remote) => {
+            let mut download_step = Step::with_messages("Downloading log", "Log downloaded");
+            download_step.start();
+            match remote.download(staging.path()).await {
+                Ok(path) => {
+                    download_step.done();
+                    path
+                }
+                Err(e) => {
+                    download_step.fail();
+                    return Err(e);
+                }
+            }
+        }
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| eyre!("Failed to read query log {}: {e}", path.display()))?;
+    let queries = parse_log(&text)?;
+    if queries.is_empty() {
+        return Err(eyre!("{} has no queries to replay", path.display()));
+    }
+
+    let op = Operation::new("Replaying", &target);
+    let mut replay_step = Step::with_messages(
+        &format!("Replaying {} queries", queries.len()),
+        "Queries replayed",
+    );
+    replay_step.start();
+    let api_key = std::env::var("HELIX_API_KEY").ok();
+    let report = match replay(&queries, &url, speed, concurrency, api_key).await {
+        Ok(report) => report,
+        Err(e) => {
+            replay_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    replay_step.done_with_info(&format!(
+        "{} requests in {:.1}s",
+        report.requests(),
+        report.elapsed.as_secs_f64()
+    ));
+
+    if Verbosity::current().show_normal() {
+        print_report(&report);
+    }
+    let errors = report.errors();
+    if errors > 0 {
+        op.failure();
+        let error = CliError::new(format!(
+            "{errors} of {} replayed queries failed",
+            report.requests()
+        ))
+        .with_hint(report.first_error.unwrap_or_default());
+        return Err(eyre!("{}", error.render()));
+    }
+    op.success();
+    Ok(())
+}
+
+/// Base URL of the instance given as `--target`: an `http(s)://` URL, or a local instance of the
+/// project
+pub(crate) fn target_url(target: &str) -> Result<String> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Ok(target.trim_end_matches('/').to_string());
+    }
+    let project = ProjectContext::find_and_load(None)?;
+    let instance = project.config.get_instance(target)?;
+    if !instance.is_local() {
+        let error = CliError::new(format!("can't tell the URL of instance '{target}'"))
+            .with_hint("pass the instance's URL as --target, e.g. https://my-instance.example.com");
+        return Err(eyre!("{}", error.render()));
+    }
+    let port = instance.port().unwrap_or(DEFAULT_HELIX_PORT);
+    Ok(format!("http://localhost:{port}"))
+}
+
+/// Send `queries` to the instance at `url`, at their logged gaps divided by `speed`, or as
+/// fast as `concurrency` requests in flight allow when there's no speed
+pub async fn replay(
+    queries: &[LoggedQuery],
+    url: &str,
+    speed: Option<f64>,
+    concurrency: usize,
+    api_key: Option<String>,
+) -> Result<ReplayReport> {
+    let client = reqwest::Client::new();
+    let in_flight = Arc::new(Semaphore::new(concurrency));
+    let first_ms = queries.first().map_or(0, |query| query.timestamp_ms);
+    let start = Instant::now();
+    let mut requests = JoinSet::new();
+
+    for query in queries {
+        if let Some(speed) = speed {
+            let gap_ms = (query.timestamp_ms - first_ms).max(0) as f64 / speed;
+            let due = start + Duration::from_secs_f64(gap_ms / 1000.0);
+            tokio::time::sleep_until(due.into()).await;
+        }
+        let permit = Arc::clone(&in_flight).acquire_owned().await?;
+        let mut request = client
+            .post(format!("{url}/{}", query.query))
+            .json(&query.params);
+        if let Some(api_key) = &api_key {
+            request = request.header("x-api-key", api_key).bearer_auth(api_key);
+        }
+        let name = query.query.clone();
+        requests.spawn(async move {
+            let sent = Instant::now();
+            let result = match request.send().await {
+                Ok(response) => match response.error_for_status() {
+                    // Read the body so the latency covers the whole response
+                    Ok(response) => response.bytes().await.map(|_| ()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            drop(permit);
+            (name, sent.elapsed(), result.map_err(|e| e.to_string()))
+        });
+    }
+
+    let mut report = ReplayReport::default();
+    while let Some(outcome) = requests.join_next().await {
+        let (name, latency, result) = outcome?;
+        let stats = report.queries.entry(name.clone()).or_default();
+        stats.requests += 1;
+        match result {
+            Ok(()) => stats.latencies.push(latency),
+            Err(e) => {
+                stats.errors += 1;
+                report.first_error.get_or_insert(format!("{name}: {e}"));
+            }
+        }
+    }
+    report.elapsed = start.elapsed();
+    Ok(report)
+}
+
+fn print_report(report: &ReplayReport) {
+    let ms = |duration: Duration| format!("{:.1}ms", duration.as_secs_f64() * 1000.0);
+    print_newline();
+    print_header("Queries:");
+    for (name, stats) in &report.queries {
+        print_field(
+            name,
+            &format!(
+                "{} requests, {} errors, p50 {}, p95 {}, p99 {}",
+                stats.requests,
+                stats.errors,
+                ms(stats.latency_percentile(50.0)),
+                ms(stats.latency_percentile(95.0)),
+                ms(stats.latency_percentile(99.0)),
+            ),
+        );
+    }
+    let throughput = report.requests() as f64 / report.elapsed.as_secs_f64().max(f64::EPSILON);
+    let requests = report.requests().to_string();
+    let errors = report.errors().to_string();
+    let throughput = format!("{throughput:.1} req/s");
+    Operation::print_details(&[
+        ("Requests", requests.as_str()),
+        ("Errors", errors.as_str()),
+        ("Throughput", throughput.as_str()),
+    ]);
+}
@@ -1,9 +1,11 @@
 use crate::commands::integrations::fly::FlyManager;
 use crate::config::CloudConfig;
 use crate::docker::DockerManager;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step};
 use crate::project::ProjectContext;
 use crate::prompts;
+use crate::registry;
 use eyre::{OptionExt, Result};
 
 pub async fn run(instance_name: Option<String>) -> Result<()> {
@@ -43,6 +45,27 @@ pub async fn run(instance_name: Option<String>) -> Result<()> {
 async fn restart_local_instance(project: &ProjectContext, instance_name: &str) -> Result<()> {
     let op = Operation::new("Restarting", instance_name);
 
+    let native = NativeManager::new(project);
+    if native.is_native(instance_name) {
+        let mut restart_step = Step::with_messages("Restarting process", "Process restarted");
+        restart_step.start();
+        let restarted = native
+            .stop_instance(instance_name)
+            .and_then(|_| native.start_instance(instance_name));
+        let pid = match restarted {
+            Ok(pid) => pid,
+            Err(e) => {
+                restart_step.fail();
+                op.failure();
+                return Err(e);
+            }
+        };
+        restart_step.done();
+        registry::record_start(project, instance_name, None, Some(pid));
+        op.success();
+        return Ok(());
+    }
+
     let docker = DockerManager::new(project);
 
     // Check Docker availability
@@ -68,6 +91,8 @@ async fn restart_local_instance(project: &ProjectContext, instance_name: &str) -
     restart_step.start();
     docker.restart_instance(instance_name)?;
     restart_step.done();
+    let container = (docker.runtime, docker.container_name(instance_name));
+    registry::record_start(project, instance_name, Some(container), None);
 
     op.success();
 
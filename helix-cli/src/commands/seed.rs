@@ -0,0 +1,573 @@
+//! `helix seed` command for loading fixture data into a local instance.
+//!
+//! Fixtures live in the project's `seeds/` directory and are loaded in file name order, each
+//! call sent to the instance's query route in turn. `.ndjson` and `.jsonl` files hold a call
+//! per line, e.g. `{"query": "createUser", "params": {"name": "Alice"}, "as": "alice"}`, and
+//! `.hql` files write the same calls the way HQL does:
+//!
+//! ```text
+//! // people
+//! alice <- createUser({name: "Alice", age: 30})
+//! bob <- createUser({name: "Bob"})
+//! follow({from: alice.user.id, to: bob.user.id})
+//! ```
+//!
+//! A call bound with `as` or `<-` keeps its response, so later calls, in any file, can use the
+//! ids it returned: by a path like `alice.user.id` in HQL, or a `"$alice.user.id"` string in
+//! NDJSON. `--reset` stops the instance, clears its data and starts it again before seeding.
+
+use crate::commands::status::probe_instance;
+use crate::docker::DockerManager;
+use crate::errors::CliError;
+use crate::native::NativeManager;
+use crate::output::{Operation, Step};
+use crate::port::DEFAULT_PORT;
+use crate::project::ProjectContext;
+use crate::registry;
+use eyre::{Result, eyre};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Directory of the project holding the fixtures
+const SEEDS_DIR: &str = "seeds";
+
+/// How long a reset instance gets to come back up
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A parameter value of a fixture call
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeedValue {
+    Json(serde_json::Value),
+    /// A value from the response of an earlier bound call
+    Ref {
+        binding: String,
+        path: Vec<String>,
+    },
+    Array(Vec<SeedValue>),
+    Object(Vec<(String, SeedValue)>),
+}
+
+/// A call of one of the project's queries, read from a fixture
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedCall {
+    pub query: String,
+    pub params: SeedValue,
+    /// Name the response is kept under
+    pub bind: Option<String>,
+    /// Line the call starts on
+    pub line: usize,
+}
+
+#[derive(Deserialize)]
+struct NdjsonCall {
+    #[serde(alias = "name")]
+    query: String,
+    #[serde(default)]
+    params: serde_json::Value,
+    #[serde(default, rename = "as")]
+    bind: Option<String>,
+}
+
+pub async fn run(instance_name: String, reset: bool) -> Result<()> {
+    let project = ProjectContext::find_and_load(None)?;
+    let instance = project.config.get_instance(&instance_name)?;
+    if !instance.is_local() {
+        let error = CliError::new(format!("can't seed instance '{instance_name}'"))
+            .with_hint("only local instances can be seeded");
+        return Err(eyre!("{}", error.render()));
+    }
+    let url = format!(
+        "http://localhost:{}",
+        instance.port().unwrap_or(DEFAULT_PORT)
+    );
+
+    let seeds_dir = project.root.join(SEEDS_DIR);
+    let files = seed_files(&seeds_dir)?;
+    if files.is_empty() {
+        let error = CliError::new(format!("no fixtures in {}", seeds_dir.display()))
+            .with_hint("add .hql, .ndjson or .jsonl fixture files to the seeds/ directory");
+        return Err(eyre!("{}", error.render()));
+    }
+    // Read every fixture first, so a mistake in one is caught before the data is touched
+    let fixtures = files
+        .iter()
+        .map(|path| Ok((path.as_path(), parse_fixture(path)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let op = Operation::new("Seeding", &instance_name);
+
+    if reset {
+        let mut reset_step = Step::with_messages("Resetting data", "Data reset");
+        reset_step.start();
+        if let Err(e) = reset_instance(&project, &instance_name, &url).await {
+            reset_step.fail();
+            op.failure();
+            return Err(e);
+        }
+        reset_step.done();
+    } else if let Some(problem) = probe_instance(&url).await.problem {
+        op.failure();
+        let error = CliError::new(format!("instance '{instance_name}' is {problem}"))
+            .with_hint(format!("run 'helix start {instance_name}' first"));
+        return Err(eyre!("{}", error.render()));
+    }
+
+    let calls = fixtures.iter().map(|(_, calls)| calls.len()).sum::<usize>();
+    let mut seed_step = Step::with_messages(
+        &format!("Loading {} fixture files", fixtures.len()),
+        "Fixtures loaded",
+    );
+    seed_step.start();
+    let api_key = std::env::var("HELIX_API_KEY").ok();
+    let client = reqwest::Client::new();
+    let mut bindings = HashMap::new();
+    for (path, file_calls) in &fixtures {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        Step::verbose_substep(&format!("Loading {name} ({} calls)", file_calls.len()));
+        if let Err(e) = seed(&client, file_calls, &url, api_key.as_deref(), &mut bindings).await {
+            seed_step.fail();
+            op.failure();
+            return Err(eyre!("{name}:{e}"));
+        }
+    }
+    seed_step.done_with_info(&format!("{calls} calls"));
+    op.success();
+    Ok(())
+}
+
+/// The fixture files in `dir`, in the order they're loaded
+pub fn seed_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| {
+                    ["hql", "ndjson", "jsonl"].contains(&&*ext.to_string_lossy())
+                })
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Read the calls of a fixture file
+pub fn parse_fixture(path: &Path) -> Result<Vec<SeedCall>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| eyre!("Failed to read fixture {}: {e}", path.display()))?;
+    let calls = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("hql") => parse_hql(&text),
+        _ => parse_ndjson(&text),
+    };
+    calls.map_err(|e| eyre!("{}:{e}", path.display()))
+}
+
+/// Parse an NDJSON fixture. Blank lines are skipped.
+pub fn parse_ndjson(text: &str) -> Result<Vec<SeedCall>> {
+    let mut calls = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 1;
+        let call: NdjsonCall = serde_json::from_str(line)
+            .map_err(|e| eyre!("{line_number}: invalid fixture call: {e}"))?;
+        let params = match call.params {
+            serde_json::Value::Null => SeedValue::Object(Vec::new()),
+            params => from_json(params),
+        };
+        calls.push(SeedCall {
+            query: call.query,
+            params,
+            bind: call.bind,
+            line: line_number,
+        });
+    }
+    Ok(calls)
+}
+
+/// A JSON value with its `"$binding.path"` strings read as references
+fn from_json(value: serde_json::Value) -> SeedValue {
+    match value {
+        serde_json::Value::String(text) => match text.strip_prefix('$').and_then(parse_ref) {
+            Some((binding, path)) => SeedValue::Ref { binding, path },
+            None => SeedValue::Json(serde_json::Value::String(text)),
+        },
+        serde_json::Value::Array(items) => {
+            SeedValue::Array(items.into_iter().map(from_json).collect())
+        }
+        serde_json::Value::Object(fields) => SeedValue::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, from_json(value)))
+                .collect(),
+        ),
+        value => SeedValue::Json(value),
+    }
+}
+
+fn parse_ref(text: &str) -> Option<(String, Vec<String>)> {
+    let mut segments = text.split('.');
+    let binding = segments.next()?;
+    let path = segments.map(str::to_string).collect::<Vec<_>>();
+    let valid = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    (valid(binding) && path.iter().all(|segment| valid(segment)))
+        .then(|| (binding.to_string(), path))
+}
+
+/// Parse an HQL fixture: calls like `name <- query({key: value})`, with `//` comments
+pub fn parse_hql(text: &str) -> Result<Vec<SeedCall>> {
+    let mut parser = HqlParser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut calls = Vec::new();
+    loop {
+        parser.skip_trivia();
+        if parser.peek().is_none() {
+            return Ok(calls);
+        }
+        calls.push(parser.call()?);
+    }
+}
+
+struct HqlParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl HqlParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Skip whitespace, comments and the semicolons statements may end with
+    fn skip_trivia(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ';' {
+                self.bump();
+            } else if c == '/' && self.chars.get(self.pos + 1) == Some(&'/') {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> eyre::Report {
+        match self.peek() {
+            Some(c) => eyre!("{}: {message}, found '{c}'", self.line),
+            None => eyre!("{}: {message}, found the end of the file", self.line),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_trivia();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{expected}'")));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        self.skip_trivia();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.bump();
+        }
+        if start == self.pos {
+            return Err(self.error("expected a name"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// `[binding <-] query([params])`
+    fn call(&mut self) -> Result<SeedCall> {
+        let line = self.line;
+        let mut query = self.ident()?;
+        let mut bind = None;
+        self.skip_trivia();
+        if self.peek() == Some('<') {
+            self.bump();
+            self.expect('-')?;
+            bind = Some(query);
+            query = self.ident()?;
+        }
+        self.expect('(')?;
+        self.skip_trivia();
+        let params = if self.peek() == Some(')') {
+            SeedValue::Object(Vec::new())
+        } else {
+            self.value()?
+        };
+        self.expect(')')?;
+        Ok(SeedCall {
+            query,
+            params,
+            bind,
+            line,
+        })
+    }
+
+    fn value(&mut self) -> Result<SeedValue> {
+        self.skip_trivia();
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                let mut fields = Vec::new();
+                let mut closed = self.closes('}');
+                while !closed {
+                    self.skip_trivia();
+                    let key = match self.peek() {
+                        Some('"') => self.string()?,
+                        _ => self.ident()?,
+                    };
+                    self.expect(':')?;
+                    fields.push((key, self.value()?));
+                    closed = self.separator('}')?;
+                }
+                Ok(SeedValue::Object(fields))
+            }
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                let mut closed = self.closes(']');
+                while !closed {
+                    items.push(self.value()?);
+                    closed = self.separator(']')?;
+                }
+                Ok(SeedValue::Array(items))
+            }
+            Some('"') => Ok(SeedValue::Json(serde_json::Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self.ident()?;
+                match name.as_str() {
+                    "true" => return Ok(SeedValue::Json(true.into())),
+                    "false" => return Ok(SeedValue::Json(false.into())),
+                    "null" | "NONE" => return Ok(SeedValue::Json(serde_json::Value::Null)),
+                    _ => {}
+                }
+                let mut path = Vec::new();
+                while self.peek() == Some('.') {
+                    self.bump();
+                    path.push(self.ident()?);
+                }
+                Ok(SeedValue::Ref {
+                    binding: name,
+                    path,
+                })
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Consume `close` if it's next
+    fn closes(&mut self, close: char) -> bool {
+        self.skip_trivia();
+        let closes = self.peek() == Some(close);
+        if closes {
+            self.bump();
+        }
+        closes
+    }
+
+    /// Consume what follows an element of a list closed by `close`, answering whether the
+    /// list ended. A trailing comma is allowed.
+    fn separator(&mut self, close: char) -> Result<bool> {
+        if self.closes(close) {
+            return Ok(true);
+        }
+        if self.peek() != Some(',') {
+            return Err(self.error(&format!("expected ',' or '{close}'")));
+        }
+        self.bump();
+        Ok(self.closes(close))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some('"') => break,
+                Some(_) => {}
+                None => return Err(eyre!("{}: unterminated string", self.line)),
+            }
+        }
+        let literal = self.chars[start..self.pos].iter().collect::<String>();
+        serde_json::from_str(&literal).map_err(|e| eyre!("{}: invalid string: {e}", self.line))
+    }
+
+    fn number(&mut self) -> Result<SeedValue> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.bump();
+        }
+        let literal = self.chars[start..self.pos].iter().collect::<String>();
+        match serde_json::from_str::<serde_json::Value>(&literal) {
+            Ok(number @ serde_json::Value::Number(_)) => Ok(SeedValue::Json(number)),
+            _ => Err(eyre!("{}: invalid number '{literal}'", self.line)),
+        }
+    }
+}
+
+/// The JSON a value stands for, with references looked up in the responses bound so far
+pub fn resolve(
+    value: &SeedValue,
+    bindings: &HashMap<String, serde_json::Value>,
+) -> Result<serde_json::Value> {
+    Ok(match value {
+        SeedValue::Json(value) => value.clone(),
+        SeedValue::Ref { binding, path } => {
+            let mut current = bindings
+                .get(binding)
+                .ok_or_else(|| eyre!("'{binding}' isn't bound by an earlier call"))?;
+            for segment in path {
+                let next = match current {
+                    serde_json::Value::Array(items) => segment
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| items.get(index)),
+                    value => value.get(segment),
+                };
+                current = next
+                    .ok_or_else(|| eyre!("'{binding}.{}' isn't in the response", path.join(".")))?;
+            }
+            current.clone()
+        }
+        SeedValue::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| resolve(item, bindings))
+                .collect::<Result<_>>()?,
+        ),
+        SeedValue::Object(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), resolve(value, bindings)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+/// Send `calls` to the instance at `url` one after another, keeping the responses of bound
+/// calls in `bindings`. Answers how many were sent; errors start with the failing call's line.
+pub async fn seed(
+    client: &reqwest::Client,
+    calls: &[SeedCall],
+    url: &str,
+    api_key: Option<&str>,
+    bindings: &mut HashMap<String, serde_json::Value>,
+) -> Result<usize> {
+    for call in calls {
+        let line = call.line;
+        let query = &call.query;
+        let params = resolve(&call.params, bindings).map_err(|e| eyre!("{line}: {e}"))?;
+        let mut request = client.post(format!("{url}/{query}")).json(&params);
+        if let Some(api_key) = api_key {
+            request = request.header("x-api-key", api_key).bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| eyre!("{line}: {query} failed: {e}"))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(eyre!("{line}: {query} failed ({status}): {}", body.trim()));
+        }
+        if let Some(bind) = &call.bind {
+            let response = serde_json::from_str(&body)
+                .map_err(|e| eyre!("{line}: {query} didn't answer with JSON: {e}"))?;
+            bindings.insert(bind.clone(), response);
+        }
+    }
+    Ok(calls.len())
+}
+
+/// Stop the instance, clear its data and start it again, waiting until it's ready
+async fn reset_instance(project: &ProjectContext, instance_name: &str, url: &str) -> Result<()> {
+    let volume = project.instance_volume(instance_name);
+    let native = NativeManager::new(project);
+    if native.is_native(instance_name) {
+        native.stop_instance(instance_name)?;
+        clear_dir(&volume)?;
+        let pid = native.start_instance(instance_name)?;
+        registry::record_start(project, instance_name, None, Some(pid));
+    } else {
+        let docker = DockerManager::new(project);
+        DockerManager::check_runtime_available(docker.runtime)?;
+        let compose_file = project
+            .instance_workspace(instance_name)
+            .join("docker-compose.yml");
+        if !compose_file.exists() {
+            let error = CliError::new(format!("instance '{instance_name}' has not been built yet"))
+                .with_hint(format!("run 'helix push {instance_name}' first"));
+            return Err(eyre!("{}", error.render()));
+        }
+        docker.stop_instance(instance_name)?;
+        clear_dir(&volume)?;
+        docker.start_instance(instance_name)?;
+        let container = (docker.runtime, docker.container_name(instance_name));
+        registry::record_start(project, instance_name, Some(container), None);
+    }
+
+    let started = Instant::now();
+    loop {
+        let Some(problem) = probe_instance(url).await.problem else {
+            return Ok(());
+        };
+        if started.elapsed() >= READY_TIMEOUT {
+            return Err(eyre!(
+                "Instance '{instance_name}' didn't become ready after the reset: {problem}"
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+fn clear_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        Step::verbose_substep(&format!("Removing {}", dir.display()));
+        fs::remove_dir_all(dir)
+            .map_err(|e| eyre!("Failed to remove the data in {}: {e}", dir.display()))?;
+    }
+    fs::create_dir_all(dir)?;
+    Ok(())
+}
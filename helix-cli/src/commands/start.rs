@@ -1,9 +1,12 @@
 use crate::commands::integrations::fly::FlyManager;
 use crate::config::CloudConfig;
 use crate::docker::DockerManager;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step, Verbosity};
+use crate::port;
 use crate::project::ProjectContext;
 use crate::prompts;
+use crate::registry;
 use eyre::{OptionExt, Result};
 
 pub async fn run(instance_name: Option<String>) -> Result<()> {
@@ -41,6 +44,11 @@ pub async fn run(instance_name: Option<String>) -> Result<()> {
 }
 
 async fn start_local_instance(project: &ProjectContext, instance_name: &str) -> Result<()> {
+    let native = NativeManager::new(project);
+    if native.is_native(instance_name) {
+        return start_native_instance(project, &native, instance_name);
+    }
+
     let op = Operation::new("Starting", instance_name);
 
     let docker = DockerManager::new(project);
@@ -63,15 +71,24 @@ async fn start_local_instance(project: &ProjectContext, instance_name: &str) ->
         return Err(eyre::eyre!("{}", error.render()));
     }
 
+    // Catch port collisions before Docker fails to bind. A running container holds its
+    // own port, and starting it again is a no-op.
+    let instance_config = project.config.get_instance(instance_name)?;
+    let port = instance_config.port().unwrap_or(port::DEFAULT_PORT);
+    if !docker.instance_running(instance_name)?
+        && let Err(e) = port::check_instance_port(port, &project.root, instance_name)
+    {
+        op.failure();
+        return Err(e);
+    }
+
     // Start the instance
     let mut start_step = Step::with_messages("Starting container", "Container started");
     start_step.start();
     docker.start_instance(instance_name)?;
     start_step.done();
-
-    // Get the instance configuration to show connection info
-    let instance_config = project.config.get_instance(instance_name)?;
-    let port = instance_config.port().unwrap_or(6969);
+    let container = (docker.runtime, docker.container_name(instance_name));
+    registry::record_start(project, instance_name, Some(container), None);
 
     op.success();
 
@@ -93,6 +110,55 @@ async fn start_local_instance(project: &ProjectContext, instance_name: &str) ->
     Ok(())
 }
 
+fn start_native_instance(
+    project: &ProjectContext,
+    native: &NativeManager,
+    instance_name: &str,
+) -> Result<()> {
+    let op = Operation::new("Starting", instance_name);
+
+    // A running process holds its own port, and starting it again is a no-op
+    let port = project
+        .config
+        .get_instance(instance_name)?
+        .port()
+        .unwrap_or(port::DEFAULT_PORT);
+    if native.running_pid(instance_name).is_none()
+        && let Err(e) = port::check_instance_port(port, &project.root, instance_name)
+    {
+        op.failure();
+        return Err(e);
+    }
+
+    let mut start_step = Step::with_messages("Starting process", "Process started");
+    start_step.start();
+    let pid = match native.start_instance(instance_name) {
+        Ok(pid) => pid,
+        Err(e) => {
+            start_step.fail();
+            op.failure();
+            return Err(e);
+        }
+    };
+    start_step.done();
+    registry::record_start(project, instance_name, None, Some(pid));
+
+    op.success();
+
+    if Verbosity::current().show_normal() {
+        Operation::print_details(&[
+            ("Local URL", &format!("http://localhost:{port}")),
+            ("Process", &pid.to_string()),
+            (
+                "Log file",
+                &native.log_path(instance_name).display().to_string(),
+            ),
+        ]);
+    }
+
+    Ok(())
+}
+
 async fn start_cloud_instance(
     project: &ProjectContext,
     instance_name: &str,
@@ -1,9 +1,15 @@
 use crate::docker::DockerManager;
+use crate::native::NativeManager;
 use crate::project::ProjectContext;
-use crate::utils::{print_error, print_field, print_header, print_newline};
-use eyre::Result;
+use crate::utils::{print_error, print_field, print_header, print_line, print_newline};
+use chrono::DateTime;
+use eyre::{Result, eyre};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
-pub async fn run() -> Result<()> {
+pub async fn run(detailed: bool) -> Result<()> {
     // Load project context
     let project = match ProjectContext::find_and_load(None) {
         Ok(project) => project,
@@ -24,7 +30,11 @@ pub async fn run() -> Result<()> {
     // Show local instances
     for (name, config) in &project.config.local {
         let port = config.port.unwrap_or(6969);
-        print_field(&format!("{name} (Local)"), &format!("port {port}"));
+        let limits = config.resources.as_ref().and_then(|r| r.summary());
+        print_field(
+            &format!("{name} (Local)"),
+            &limits.map_or_else(|| format!("port {port}"), |l| format!("port {port}, {l}")),
+        );
     }
 
     // Show cloud instances
@@ -70,7 +80,53 @@ pub async fn run() -> Result<()> {
 
     // Show running containers (for local instances)
     show_container_status(&project).await?;
+    show_native_status(&project);
+
+    // Probe every deployed local instance, whether or not the container runtime is reachable
+    let mut unhealthy = 0;
+    let mut deployed = 0;
+    let mut rows = Vec::new();
+    let mut probes = Vec::new();
+    let native = NativeManager::new(&project);
+    let mut names = project.config.local.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let last_deploy_ms = project.last_deploy_ms(name);
+        if !project.docker_compose_path(name).exists() && !native.is_native(name) {
+            rows.push(not_deployed_row(name));
+            continue;
+        }
+        let port = project.config.local[name].port.unwrap_or(6969);
+        let probe = probe_instance(&local_url(port)).await;
+        deployed += 1;
+        if !probe.healthy() {
+            unhealthy += 1;
+        }
+        rows.push(health_row(name, &probe, last_deploy_ms));
+        probes.push((name, port, probe));
+    }
+    if !rows.is_empty() {
+        print_newline();
+        print_header("Health:");
+        print_line(&format_table(&HEALTH_COLUMNS, &rows));
+    }
+
+    for (name, port, probe) in &probes {
+        if !probe.healthy() {
+            continue;
+        }
+        if detailed {
+            show_admin_stats(name, probe.stats.as_ref());
+        }
+        show_schedules(name, *port).await;
+    }
 
+    if unhealthy > 0 {
+        return Err(eyre!(
+            "{unhealthy} of {deployed} deployed instances {} unhealthy",
+            if unhealthy == 1 { "is" } else { "are" }
+        ));
+    }
     Ok(())
 }
 
@@ -98,7 +154,7 @@ async fn show_container_status(project: &ProjectContext) -> Result<()> {
     }
 
     print_header("Running Containers:");
-    for status in statuses {
+    for status in &statuses {
         let status_icon = if status.status.contains("Up") {
             "[UP]"
         } else {
@@ -119,3 +175,382 @@ async fn show_container_status(project: &ProjectContext) -> Result<()> {
 
     Ok(())
 }
+
+/// Local instances last pushed with `--native`, which run as processes rather than containers
+fn show_native_status(project: &ProjectContext) {
+    let native = NativeManager::new(project);
+    let mut names = project
+        .config
+        .local
+        .keys()
+        .filter(|name| native.is_native(name))
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return;
+    }
+    names.sort();
+
+    print_newline();
+    print_header("Native Processes:");
+    for name in names {
+        let port = project.config.local[name].port.unwrap_or(6969);
+        match native.running_pid(name) {
+            Some(pid) => print_field(&format!("[UP] {name}"), &format!("pid {pid} (port {port})")),
+            None => print_field(&format!("[DOWN] {name}"), "not running"),
+        }
+    }
+}
+
+fn local_url(port: u16) -> String {
+    format!("http://localhost:{port}")
+}
+
+/// Readiness checks as reported by an instance's `/readyz` route
+#[derive(Debug, Deserialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub storage: bool,
+    pub workers: bool,
+    pub schema: bool,
+    /// Why the stored data is incompatible with the instance's build
+    #[serde(default)]
+    pub schema_mismatch: Option<SchemaMismatch>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchemaMismatch {
+    pub reasons: Vec<String>,
+}
+
+/// What probing an instance's health, readiness and stats routes found
+#[derive(Debug)]
+pub struct InstanceProbe {
+    /// Why the instance is unhealthy, `None` when it's healthy
+    pub problem: Option<String>,
+    /// Round trip of the `/healthz` request
+    pub latency: Option<Duration>,
+    /// Only available when the admin API is reachable with the configured credentials
+    pub stats: Option<AdminStats>,
+}
+
+impl InstanceProbe {
+    pub fn healthy(&self) -> bool {
+        self.problem.is_none()
+    }
+}
+
+/// Probe the instance at `url`: it's healthy when `/healthz` answers and `/readyz` reports
+/// ready. Instances too old to serve `/readyz` are judged by `/healthz` alone.
+pub async fn probe_instance(url: &str) -> InstanceProbe {
+    let started = Instant::now();
+    let health = fetch_json::<serde_json::Value>(url, "/healthz").await;
+    let latency = started.elapsed();
+    let problem = match health {
+        Err(e) => Some(format!("unreachable ({})", e.root_cause())),
+        Ok(None) => Some("no /healthz route".to_string()),
+        Ok(Some(_)) => match fetch_readiness(url).await {
+            Ok(Some(readiness)) if !readiness.ready => {
+                let mut failing = [
+                    ("storage", readiness.storage),
+                    ("workers", readiness.workers),
+                    ("schema", readiness.schema),
+                ]
+                .into_iter()
+                .filter(|(_, ok)| !ok)
+                .map(|(check, _)| check.to_string())
+                .collect::<Vec<_>>();
+                if let Some(mismatch) = readiness.schema_mismatch {
+                    failing.push(format!(
+                        "incompatible data: {}",
+                        mismatch.reasons.join("; ")
+                    ));
+                }
+                Some(format!("not ready ({})", failing.join(", ")))
+            }
+            Ok(_) => None,
+            Err(e) => Some(format!("readiness check failed ({})", e.root_cause())),
+        },
+    };
+    let stats = match problem {
+        None => fetch_json::<AdminStats>(url, "/admin/stats")
+            .await
+            .ok()
+            .flatten(),
+        Some(_) => None,
+    };
+    InstanceProbe {
+        latency: problem.is_none().then_some(latency),
+        problem,
+        stats,
+    }
+}
+
+/// `/readyz` answers 503 with the failing checks, so its body is read whatever the status
+async fn fetch_readiness(url: &str) -> Result<Option<Readiness>> {
+    let response = request(url, "/readyz")?.send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.json().await?))
+}
+
+const HEALTH_COLUMNS: [&str; 8] = [
+    "INSTANCE", "HEALTH", "LATENCY", "VERSION", "SCHEMA", "UPTIME", "DATA", "DEPLOYED",
+];
+
+/// The health table row of a probed instance
+pub fn health_row(name: &str, probe: &InstanceProbe, last_deploy_ms: Option<i64>) -> Vec<String> {
+    let health = match &probe.problem {
+        None => "healthy".to_string(),
+        Some(problem) => format!("unhealthy: {problem}"),
+    };
+    let stats = probe.stats.as_ref();
+    vec![
+        name.to_string(),
+        health,
+        probe.latency.map_or_else(
+            || "-".to_string(),
+            |l| format!("{:.1}ms", l.as_secs_f64() * 1000.0),
+        ),
+        stats.map_or_else(|| "-".to_string(), |s| s.version.clone()),
+        stats.map_or_else(|| "-".to_string(), |s| schema_versions(&s.schema_versions)),
+        stats.map_or_else(|| "-".to_string(), |s| format_uptime(s.uptime_secs)),
+        stats
+            .and_then(|s| s.data_size_bytes)
+            .map_or_else(|| "-".to_string(), format_bytes),
+        last_deploy_ms.map_or_else(|| "-".to_string(), format_time),
+    ]
+}
+
+fn not_deployed_row(name: &str) -> Vec<String> {
+    let mut row = vec![name.to_string(), "not deployed".to_string()];
+    row.resize(HEALTH_COLUMNS.len(), "-".to_string());
+    row
+}
+
+/// Left aligned columns under a header, two spaces apart
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = header.iter().map(|h| h.len()).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    std::iter::once(&header)
+        .chain(rows)
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A schedule as reported by an instance's `/schedules` route
+#[derive(Debug, Deserialize)]
+pub struct ScheduleStatus {
+    pub name: String,
+    pub query: String,
+    pub cron: String,
+    pub next_run_ms: Option<i64>,
+    pub runs: Vec<ScheduleRun>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleRun {
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SchedulesResponse {
+    schedules: Vec<ScheduleStatus>,
+}
+
+async fn show_schedules(instance_name: &str, port: u16) {
+    match fetch_json::<SchedulesResponse>(&local_url(port), "/schedules").await {
+        // Instances without schedules don't serve the route
+        Ok(None) => {}
+        Ok(Some(response)) => {
+            print_newline();
+            print_header(&format!("Schedules ({instance_name}):"));
+            for schedule in &response.schedules {
+                print_field(&schedule.name, &schedule_summary(schedule));
+            }
+        }
+        Err(e) => print_field(
+            &format!("Schedules ({instance_name})"),
+            &format!("Error getting schedules ({e})"),
+        ),
+    }
+}
+
+/// Instance stats as reported by its `/admin/stats` route
+#[derive(Debug, Deserialize)]
+pub struct AdminStats {
+    pub version: String,
+    pub uptime_secs: u64,
+    pub schema_versions: BTreeMap<String, u8>,
+    pub workers: WorkerStats,
+    /// Missing from instances older than the field
+    #[serde(default)]
+    pub data_size_bytes: Option<u64>,
+    pub routes: Vec<RouteCounters>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkerStats {
+    pub workers: usize,
+    pub in_flight: usize,
+    pub read_queue_depth: usize,
+    pub write_queue_depth: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteCounters {
+    pub route: String,
+    pub success: u64,
+    pub error: u64,
+    pub mean_latency_ms: f64,
+}
+
+fn show_admin_stats(instance_name: &str, stats: Option<&AdminStats>) {
+    print_newline();
+    print_header(&format!("Details ({instance_name}):"));
+    match stats {
+        Some(stats) => {
+            for (key, value) in admin_stats_fields(stats) {
+                print_field(&key, &value);
+            }
+        }
+        None => print_field(
+            "Admin API",
+            "Not served by this instance's version, or HELIX_API_KEY lacks admin scope",
+        ),
+    }
+}
+
+/// The fields `helix status --detailed` prints for an instance
+pub fn admin_stats_fields(stats: &AdminStats) -> Vec<(String, String)> {
+    let mut fields = vec![
+        ("Version".to_string(), stats.version.clone()),
+        ("Uptime".to_string(), format_uptime(stats.uptime_secs)),
+        (
+            "Schema".to_string(),
+            schema_versions(&stats.schema_versions),
+        ),
+        (
+            "Workers".to_string(),
+            format!(
+                "{} ({} in flight, {} queued reads, {} queued writes)",
+                stats.workers.workers,
+                stats.workers.in_flight,
+                stats.workers.read_queue_depth,
+                stats.workers.write_queue_depth
+            ),
+        ),
+    ];
+    if let Some(size) = stats.data_size_bytes {
+        fields.push(("Data".to_string(), format_bytes(size)));
+    }
+    fields.extend(stats.routes.iter().map(|route| {
+        (
+            route.route.clone(),
+            format!(
+                "{} ok, {} errors, {:.2}ms mean",
+                route.success, route.error, route.mean_latency_ms
+            ),
+        )
+    }));
+    fields
+}
+
+fn schema_versions(versions: &BTreeMap<String, u8>) -> String {
+    match versions.is_empty() {
+        true => "unversioned".to_string(),
+        false => versions
+            .iter()
+            .map(|(label, version)| format!("{label} v{version}"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    format!("{}h {}m {}s", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+/// GET `path` from the instance at `url`, with `HELIX_API_KEY` as the API key when set
+fn request(url: &str, path: &str) -> Result<reqwest::RequestBuilder> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()?;
+    let mut request = client.get(format!("{url}{path}"));
+    if let Ok(api_key) = std::env::var("HELIX_API_KEY") {
+        request = request.header("x-api-key", &api_key).bearer_auth(api_key);
+    }
+    Ok(request)
+}
+
+/// GET `path` from the instance at `url`. `None` when the instance doesn't serve the route.
+async fn fetch_json<T: DeserializeOwned>(url: &str, path: &str) -> Result<Option<T>> {
+    let response = request(url, path)?.send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.json().await?))
+}
+
+/// One line describing a schedule's query, last run and next run
+pub fn schedule_summary(schedule: &ScheduleStatus) -> String {
+    let last_run = match schedule.runs.first() {
+        None => "never run".to_string(),
+        Some(run) => {
+            let outcome = if run.ok {
+                format!("ok in {}ms", run.duration_ms)
+            } else {
+                format!(
+                    "failed: {}",
+                    run.error.as_deref().unwrap_or("unknown error")
+                )
+            };
+            format!("last run {} {outcome}", format_time(run.started_at_ms))
+        }
+    };
+    let next_run = schedule
+        .next_run_ms
+        .map_or_else(|| "not scheduled".to_string(), format_time);
+    format!(
+        "{} ({}), {last_run}, next {next_run}",
+        schedule.query, schedule.cron
+    )
+}
+
+fn format_time(ms: i64) -> String {
+    DateTime::from_timestamp_millis(ms).map_or_else(
+        || ms.to_string(),
+        |time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    )
+}
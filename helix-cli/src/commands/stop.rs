@@ -1,6 +1,7 @@
 use crate::commands::integrations::fly::FlyManager;
 use crate::config::CloudConfig;
 use crate::docker::DockerManager;
+use crate::native::NativeManager;
 use crate::output::{Operation, Step};
 use crate::project::ProjectContext;
 use crate::prompts;
@@ -43,6 +44,18 @@ pub async fn run(instance_name: Option<String>) -> Result<()> {
 async fn stop_local_instance(project: &ProjectContext, instance_name: &str) -> Result<()> {
     let op = Operation::new("Stopping", instance_name);
 
+    let native = NativeManager::new(project);
+    if native.is_native(instance_name) {
+        let mut stop_step = Step::with_messages("Stopping process", "Process stopped");
+        stop_step.start();
+        if !native.stop_instance(instance_name)? {
+            Step::verbose_substep(&format!("'{instance_name}' was not running"));
+        }
+        stop_step.done();
+        op.success();
+        return Ok(());
+    }
+
     let docker = DockerManager::new(project);
 
     // Check Docker availability
@@ -2,6 +2,7 @@ use eyre::Result;
 use self_update::cargo_crate_version;
 
 use crate::output::{Operation, Step, Verbosity};
+use crate::user_config::{UpdateChannel, UserConfig};
 use crate::utils::print_error_with_hint;
 
 pub async fn run(force: bool) -> Result<()> {
@@ -17,19 +18,36 @@ fn run_sync(force: bool) -> Result<()> {
     let mut check_step = Step::with_messages("Checking for updates", "Checked for updates");
     check_step.start();
 
-    let status = self_update::backends::github::Update::configure()
+    let mut update = self_update::backends::github::Update::configure();
+    update
         .repo_owner("HelixDB")
         .repo_name("helix-db")
         .bin_name("helix")
         .show_download_progress(true)
         .show_output(false)
-        .current_version(cargo_crate_version!())
-        .build()?;
+        .current_version(cargo_crate_version!());
+    if UserConfig::current().update_channel() == UpdateChannel::Prerelease {
+        // The newest release, which may be a pre-release the latest release endpoint skips
+        let newest = self_update::backends::github::ReleaseList::configure()
+            .repo_owner("HelixDB")
+            .repo_name("helix-db")
+            .build()?
+            .fetch()?
+            .into_iter()
+            .next();
+        if let Some(release) = newest {
+            update.target_version_tag(&format!("v{}", release.version));
+        }
+    }
+    let status = update.build()?;
 
     let current_version = cargo_crate_version!();
 
     if !force {
-        let latest_release = status.get_latest_release()?;
+        let latest_release = match status.target_version() {
+            Some(tag) => status.get_release_version(&tag)?,
+            None => status.get_latest_release()?,
+        };
         if latest_release.version == current_version {
             check_step.done_with_info("already up to date");
             op.success();
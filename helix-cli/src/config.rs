@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 
 use crate::commands::integrations::ecr::EcrConfig;
 use crate::commands::integrations::fly::FlyInstanceConfig;
+use helix_db::helixc::generator::CodegenProfile;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HelixConfig {
@@ -27,6 +28,12 @@ pub struct ProjectConfig {
     pub queries: PathBuf,
     #[serde(default = "default_container_runtime")]
     pub container_runtime: ContainerRuntime,
+    /// Worst-case items a query may expand to before `helix check` fails it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complexity_budget: Option<u64>,
+    /// Whether the generated queries favour build time or speed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codegen_profile: Option<CodegenProfile>,
 }
 
 fn default_queries_path() -> PathBuf {
@@ -86,6 +93,19 @@ pub struct VectorConfig {
     pub ef_search: u32,
     #[serde(default = "default_db_max_size_gb")]
     pub db_max_size_gb: u32,
+    /// Vector types kept in IVF-Flat indices instead of HNSW, for types too large to index in
+    /// memory or re-indexed in bulk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ivf_flat: Option<HashMap<String, IvfFlatConfig>>,
+}
+
+/// IVF-Flat index settings for one vector type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct IvfFlatConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nlist: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nprobe: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -94,6 +114,327 @@ pub struct GraphConfig {
     pub secondary_indices: Vec<String>,
 }
 
+/// Gateway/worker pool tunables. Unset fields fall back to the runtime defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct GatewayConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workers_per_core: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_capacity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shed_on_overload: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_entries: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_query_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_keys: Option<Vec<ApiKeyConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<JwtConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_rate_limit: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cors: Option<CorsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cypher: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qdrant: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrow_flight: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub udfs: Option<Vec<UdfConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedules: Option<Vec<ScheduleConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kafka: Option<KafkaConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postgres_sync: Option<PostgresSyncConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_up: Option<WarmUpConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bm25_merge: Option<Bm25MergeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_search: Option<TextSearchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ego_cache: Option<Vec<EgoCacheConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<SessionConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_request_bytes: Option<usize>,
+}
+
+/// Reading the vector index into memory on start, so the first queries after a restart
+/// aren't slowed by page faults
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct WarmUpConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<WarmUpMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefault: Option<bool>,
+}
+
+/// `eager` warms up before serving, `deferred` in the background while serving
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmUpMode {
+    Eager,
+    Deferred,
+    Off,
+}
+
+/// When BM25 postings are merged. Writes add a segment to every term of a document, so terms
+/// are merged once they have `merge_factor` segments or too many postings of deleted documents.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Bm25MergeConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_factor: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_deleted_percent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_terms_per_merge: Option<usize>,
+}
+
+/// Synonyms and dictionary terms of text search. Paths are as seen by the instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TextSearchConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synonym_files: Option<Vec<PathBuf>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_files: Option<Vec<PathBuf>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<Vec<String>>,
+}
+
+/// An edge type whose k-hop neighborhoods the instance caches for `::EGO` steps
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EgoCacheConfig {
+    pub edge: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+}
+
+/// Read sessions that run several queries against one snapshot of the graph
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SessionConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+}
+
+/// A WebAssembly function queries can `CALL`. The path is as seen by the instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UdfConfig {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_bytes: Option<usize>,
+}
+
+/// A query the instance runs on a cron schedule, in UTC
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScheduleConfig {
+    pub name: String,
+    pub query: String,
+    pub cron: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
+/// Kafka topics the instance consumes mutation events from or publishes its writes to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KafkaConfig {
+    pub brokers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ingest: Option<KafkaIngestConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdc: Option<KafkaCdcConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KafkaIngestConfig {
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<Vec<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<KafkaStartOffset>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KafkaStartOffset {
+    Earliest,
+    Latest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KafkaCdcConfig {
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<i32>,
+}
+
+/// Postgres tables whose rows the instance keeps upserted as nodes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PostgresSyncConfig {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
+    pub tables: Vec<PostgresTableConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PostgresTableConfig {
+    pub table: String,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+}
+
+/// Response compression negotiated by `Accept-Encoding`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct CompressionConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_size_bytes: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithms: Option<Vec<CompressionAlgorithm>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Br,
+    Zstd,
+}
+
+/// Origins, methods and headers browser apps may use to call the gateway cross-origin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_methods: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_headers: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expose_headers: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_credentials: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+}
+
+/// HTTPS served by the gateway, from PEM files or ACME. Paths are as seen by the instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TlsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acme: Option<AcmeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ca_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AcmeConfig {
+    pub domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Vec<String>>,
+    pub cache_dir: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory_url: Option<String>,
+}
+
+/// Per-client request rate limits, as token buckets
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct RateLimitConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routes: Option<HashMap<String, RateLimit>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_forwarded_for: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_sec: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+}
+
+/// JWT bearer token verification against an identity provider's JSON Web Key Set
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JwtConfig {
+    pub jwks_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roles_claim: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_refresh_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    ReadOnly,
+    #[default]
+    ReadWrite,
+    Admin,
+}
+
+/// An API key accepted by the instance's gateway. Only the key's SHA-256 hash is stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key_hash: String,
+    #[serde(default)]
+    pub scope: ApiKeyScope,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbConfig {
     #[serde(default, skip_serializing_if = "is_default_vector_config")]
@@ -113,6 +454,8 @@ pub struct DbConfig {
     pub embedding_model: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub graphvis_node_label: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default_gateway_config")]
+    pub gateway_config: GatewayConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,10 +464,74 @@ pub struct LocalInstanceConfig {
     pub port: Option<u16>,
     #[serde(default = "default_dev_build_mode")]
     pub build_mode: BuildMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceLimits>,
+    /// Local instance this one is a warm standby of, set by `helix add standby-of` and
+    /// cleared by `helix promote`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standby_of: Option<String>,
     #[serde(flatten)]
     pub db_config: DbConfig,
 }
 
+/// Limits applied to a local instance's container, so one instance can't starve the machine
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// Relative CPU weight under contention, Docker's default is 1024
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    /// Hard memory limit in Docker's format, e.g. `512m` or `2g`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    /// LMDB map size, overriding `db_max_size_gb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_size_gb: Option<u32>,
+}
+
+impl ResourceLimits {
+    fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(shares) = self.cpu_shares
+            && shares < 2
+        {
+            return Err(format!("cpu_shares must be at least 2, got {shares}"));
+        }
+        if let Some(memory) = &self.memory
+            && !is_memory_limit(memory)
+        {
+            return Err(format!(
+                "memory must be a size like \"512m\" or \"2g\", got \"{memory}\""
+            ));
+        }
+        if self.map_size_gb == Some(0) {
+            return Err("map_size_gb must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// One line describing the limits, `None` when nothing is limited
+    pub fn summary(&self) -> Option<String> {
+        let limits = [
+            self.cpu_shares.map(|shares| format!("cpu shares {shares}")),
+            self.memory
+                .as_ref()
+                .map(|memory| format!("memory {memory}")),
+            self.map_size_gb.map(|size| format!("map size {size} GB")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        (!limits.is_empty()).then(|| limits.join(", "))
+    }
+}
+
+/// A byte count with an optional `b`, `k`, `m` or `g` unit, as Docker takes memory limits
+fn is_memory_limit(value: &str) -> bool {
+    let digits = value
+        .strip_suffix(|c: char| "bkmgBKMG".contains(c))
+        .unwrap_or(value);
+    digits.chars().all(|c| c.is_ascii_digit()) && digits.parse::<u64>().is_ok_and(|n| n > 0)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloudInstanceConfig {
     pub cluster_id: String,
@@ -225,6 +632,10 @@ fn is_default_graph_config(value: &GraphConfig) -> bool {
     *value == GraphConfig::default()
 }
 
+fn is_default_gateway_config(value: &GatewayConfig) -> bool {
+    *value == GatewayConfig::default()
+}
+
 impl Default for VectorConfig {
     fn default() -> Self {
         VectorConfig {
@@ -232,6 +643,7 @@ impl Default for VectorConfig {
             ef_construction: default_ef_construction(),
             ef_search: default_ef_search(),
             db_max_size_gb: default_db_max_size_gb(),
+            ivf_flat: None,
         }
     }
 }
@@ -246,6 +658,7 @@ impl Default for DbConfig {
             schema: None,
             embedding_model: default_embedding_model(),
             graphvis_node_label: None,
+            gateway_config: GatewayConfig::default(),
         }
     }
 }
@@ -268,6 +681,22 @@ impl<'a> InstanceInfo<'a> {
         }
     }
 
+    /// Container limits, only set for local instances
+    pub fn resources(&self) -> Option<&ResourceLimits> {
+        match self {
+            InstanceInfo::Local(config) => config.resources.as_ref(),
+            InstanceInfo::Helix(_) | InstanceInfo::FlyIo(_) | InstanceInfo::Ecr(_) => None,
+        }
+    }
+
+    /// Local instance this one is a warm standby of, only local instances can be standbys
+    pub fn standby_of(&self) -> Option<&str> {
+        match self {
+            InstanceInfo::Local(config) => config.standby_of.as_deref(),
+            InstanceInfo::Helix(_) | InstanceInfo::FlyIo(_) | InstanceInfo::Ecr(_) => None,
+        }
+    }
+
     pub fn port(&self) -> Option<u16> {
         match self {
             InstanceInfo::Local(config) => config.port,
@@ -340,6 +769,16 @@ impl<'a> InstanceInfo<'a> {
             json["graphvis_node_label"] = serde_json::Value::String(graphvis_node_label.clone());
         }
 
+        if let Some(ivf_flat) = &db_config.vector_config.ivf_flat {
+            json["vector_config"]["ivf_flat"] =
+                serde_json::to_value(ivf_flat).unwrap_or(serde_json::Value::Null);
+        }
+
+        if !is_default_gateway_config(&db_config.gateway_config) {
+            json["gateway_config"] =
+                serde_json::to_value(&db_config.gateway_config).unwrap_or(serde_json::Value::Null);
+        }
+
         json
     }
 }
@@ -415,6 +854,27 @@ impl HelixConfig {
                     relative_path.display()
                 ));
             }
+
+            if let Some(resources) = &config.resources {
+                resources.validate().map_err(|e| {
+                    eyre!(
+                        "Invalid resources for instance '{}' in {}: {e}",
+                        name,
+                        relative_path.display()
+                    )
+                })?;
+            }
+
+            if let Some(leader) = &config.standby_of
+                && (leader == name || !self.local.contains_key(leader))
+            {
+                return Err(eyre!(
+                    "Instance '{}' in {} is a standby of '{}', which must be another local instance",
+                    name,
+                    relative_path.display(),
+                    leader
+                ));
+            }
         }
 
         // Validate cloud instances
@@ -467,6 +927,19 @@ impl HelixConfig {
         Err(eyre!("Instance '{}' not found in helix.toml", name))
     }
 
+    pub fn db_config_mut(&mut self, name: &str) -> Result<&mut DbConfig> {
+        if let Some(local_config) = self.local.get_mut(name) {
+            return Ok(&mut local_config.db_config);
+        }
+
+        match self.cloud.get_mut(name) {
+            Some(CloudConfig::Helix(config)) => Ok(&mut config.db_config),
+            Some(CloudConfig::FlyIo(config)) => Ok(&mut config.db_config),
+            Some(CloudConfig::Ecr(config)) => Ok(&mut config.db_config),
+            None => Err(eyre!("Instance '{}' not found in helix.toml", name)),
+        }
+    }
+
     pub fn list_instances(&self) -> Vec<&String> {
         let mut instances = Vec::new();
         instances.extend(self.local.keys());
@@ -504,6 +977,8 @@ impl HelixConfig {
             LocalInstanceConfig {
                 port: Some(6969),
                 build_mode: BuildMode::Dev,
+                resources: None,
+                standby_of: None,
                 db_config: DbConfig::default(),
             },
         );
@@ -513,6 +988,8 @@ impl HelixConfig {
                 name: project_name.to_string(),
                 queries: default_queries_path(),
                 container_runtime: default_container_runtime(),
+                complexity_budget: None,
+                codegen_profile: None,
             },
             local,
             cloud: HashMap::new(),
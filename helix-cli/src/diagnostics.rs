@@ -0,0 +1,219 @@
+//! Machine-readable analyzer diagnostics for `helix check --format`.
+//!
+//! `json` is a stable document of this CLI's own, versioned by its `version` field. `sarif` is
+//! SARIF 2.1.0, which CI systems such as GitHub code scanning turn into annotations on the
+//! `.hx` files. Paths are relative to the given base directory when they're under it.
+
+use helix_db::helixc::{
+    analyzer::diagnostic::Diagnostic,
+    parser::{location::Loc, types::HxFile},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+
+/// Version of the `json` document, bumped on breaking changes to its shape
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// Format of `helix check --format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiagnosticsFormat {
+    /// The CLI's own JSON document
+    Json,
+    /// SARIF 2.1.0, for code scanning in CI
+    Sarif,
+}
+
+impl DiagnosticsFormat {
+    pub fn render(&self, diagnostics: &[Diagnostic], files: &[HxFile], base: &Path) -> Value {
+        let diagnostics: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| ReportedDiagnostic::new(diagnostic, files, base))
+            .collect();
+        match self {
+            DiagnosticsFormat::Json => json!({
+                "version": JSON_FORMAT_VERSION,
+                "diagnostics": diagnostics,
+            }),
+            DiagnosticsFormat::Sarif => sarif(&diagnostics),
+        }
+    }
+}
+
+/// A diagnostic as written to the `json` document
+#[derive(Debug, Serialize)]
+pub struct ReportedDiagnostic {
+    pub code: String,
+    pub severity: &'static str,
+    pub message: String,
+    /// File the diagnostic is in, missing for diagnostics about the project as a whole
+    pub file: Option<String>,
+    pub span: Option<Range>,
+    /// What to change, in words
+    pub suggestion: Option<String>,
+    /// Edit that applies the suggestion
+    pub fix: Option<Edit>,
+    #[serde(skip)]
+    description: &'static str,
+}
+
+/// Start and end of a span, the end being exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// 1-based line and column, counting columns in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Edit {
+    pub span: Range,
+    pub replacement: String,
+}
+
+impl ReportedDiagnostic {
+    fn new(diagnostic: &Diagnostic, files: &[HxFile], base: &Path) -> Self {
+        let src = diagnostic.filepath.as_deref().and_then(|filepath| {
+            match files.iter().find(|file| file.name == filepath) {
+                Some(file) => Some(file.content.clone()),
+                None => fs::read_to_string(filepath).ok(),
+            }
+        });
+        let range = |loc: &Loc| src.as_deref().and_then(|src| Range::of(loc, src));
+        let fix = diagnostic.fix.as_ref().and_then(|fix| {
+            let span = fix.to_remove.as_ref().or(fix.span.as_ref())?;
+            Some(Edit {
+                span: range(span)?,
+                replacement: fix.to_add.clone().unwrap_or_default(),
+            })
+        });
+        Self {
+            code: diagnostic.error_code.to_string(),
+            severity: diagnostic.severity_str(),
+            message: diagnostic.message.clone(),
+            file: diagnostic
+                .filepath
+                .as_deref()
+                .map(|filepath| relative_path(filepath, base)),
+            span: range(&diagnostic.location),
+            suggestion: diagnostic.hint.clone(),
+            fix,
+            description: diagnostic.error_code.description(),
+        }
+    }
+}
+
+impl Range {
+    /// The range of `loc` in `src`, or `None` when its offsets aren't in the file
+    fn of(loc: &Loc, src: &str) -> Option<Self> {
+        Some(Self {
+            start: Position::at(src, loc.start.byte_offset)?,
+            end: Position::at(src, loc.end.byte_offset)?,
+        })
+    }
+}
+
+impl Position {
+    fn at(src: &str, byte_offset: usize) -> Option<Self> {
+        let before = src.get(..byte_offset)?;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Some(Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        })
+    }
+}
+
+fn relative_path(filepath: &str, base: &Path) -> String {
+    let path = Path::new(filepath);
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sarif(diagnostics: &[ReportedDiagnostic]) -> Value {
+    let mut rules: Vec<(&str, &str)> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.code.as_str(), diagnostic.description))
+        .collect();
+    rules.sort();
+    rules.dedup();
+    let rules: Vec<_> = rules
+        .into_iter()
+        .map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+        .collect();
+
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut result = json!({
+                "ruleId": diagnostic.code,
+                "level": match diagnostic.severity {
+                    "error" => "error",
+                    "warning" => "warning",
+                    _ => "note",
+                },
+                "message": { "text": match &diagnostic.suggestion {
+                    Some(suggestion) => format!("{}\nhelp: {suggestion}", diagnostic.message),
+                    None => diagnostic.message.clone(),
+                } },
+            });
+            if let Some(file) = &diagnostic.file {
+                let mut location = json!({ "artifactLocation": { "uri": file } });
+                if let Some(span) = diagnostic.span {
+                    location["region"] = sarif_region(span);
+                }
+                result["locations"] = json!([{ "physicalLocation": location }]);
+                if let Some(fix) = &diagnostic.fix {
+                    result["fixes"] = json!([{
+                        "description": { "text": diagnostic.suggestion.as_deref().unwrap_or("") },
+                        "artifactChanges": [{
+                            "artifactLocation": { "uri": file },
+                            "replacements": [{
+                                "deletedRegion": sarif_region(fix.span),
+                                "insertedContent": { "text": fix.replacement },
+                            }],
+                        }],
+                    }]);
+                }
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "helix",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/HelixDB/helix-db",
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn sarif_region(span: Range) -> Value {
+    json!({
+        "startLine": span.start.line,
+        "startColumn": span.start.column,
+        "endLine": span.end.line,
+        "endColumn": span.end.column,
+    })
+}
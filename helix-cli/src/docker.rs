@@ -3,7 +3,8 @@
 //! Despite the module name, this works with both Docker and Podman as they
 //! share the same CLI interface and support standard Dockerfile formats.
 
-use crate::config::{BuildMode, ContainerRuntime, InstanceInfo};
+use crate::config::{BuildMode, ContainerRuntime, InstanceInfo, ResourceLimits};
+use crate::errors::CliError;
 use crate::output::Step;
 use crate::project::ProjectContext;
 use crate::utils::{print_confirm, print_info, print_warning};
@@ -13,6 +14,50 @@ use std::process::{Command, Output};
 use std::thread;
 use std::time::Duration;
 
+/// Host name a standby's container reaches the host, and so its leader's published port, at
+const STANDBY_HOST: &str = "host.docker.internal";
+
+/// Named pipe Docker Desktop's engine listens on under Windows
+const DOCKER_DESKTOP_PIPE: &str = r"\\.\pipe\docker_engine";
+
+/// A host path as docker compose reads it on every platform: forward slashes, without the
+/// `\\?\` prefix Windows puts on canonical paths
+pub fn compose_path(path: &str) -> String {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(share) => format!(r"\\{share}"),
+        None => path.strip_prefix(r"\\?\").unwrap_or(path).to_string(),
+    };
+    path.replace('\\', "/")
+}
+
+/// What to check when the Docker daemon can't be reached on Windows, given `DOCKER_HOST`
+/// and whether Docker Desktop's engine pipe exists
+pub fn windows_daemon_hint(docker_host: Option<&str>, pipe_exists: bool) -> String {
+    match docker_host {
+        Some(host) if !host.starts_with("npipe://") => format!(
+            "DOCKER_HOST is set to '{host}', unset it to use Docker Desktop's engine at \
+             npipe:////./pipe/docker_engine"
+        ),
+        _ if pipe_exists => "Docker Desktop is starting, wait for its engine to be running \
+                             and try again"
+            .to_string(),
+        _ => "start Docker Desktop, Helix reaches its engine through the named pipe \
+              //./pipe/docker_engine"
+            .to_string(),
+    }
+}
+
+/// Error when the engine answering `docker info` runs Windows containers, which can't run
+/// the Linux images Helix builds
+pub fn windows_containers_error(os_type: &str) -> Option<CliError> {
+    (os_type.trim() == "windows").then(|| {
+        CliError::new("Docker Desktop is running Windows containers").with_hint(
+            "switch it to Linux containers from its tray icon menu, or run \
+             'DockerCli.exe -SwitchLinuxEngine' from its install directory",
+        )
+    })
+}
+
 /// Error type for Docker build failures that may be Rust compilation errors.
 #[derive(Debug)]
 pub enum DockerBuildError {
@@ -93,7 +138,10 @@ impl<'a> DockerManager<'a> {
 
     #[inline]
     pub(crate) fn data_dir(&self, instance_name: &str) -> String {
-        std::env::var("HELIX_DATA_DIR").unwrap_or_else(|_| format!("../.volumes/{instance_name}"))
+        match std::env::var("HELIX_DATA_DIR") {
+            Ok(dir) => compose_path(&dir),
+            Err(_) => format!("../.volumes/{instance_name}"),
+        }
     }
 
     /// Get environment variables for an instance
@@ -113,15 +161,10 @@ impl<'a> DockerManager<'a> {
             print_info(&format!("Overriding environment from {}", db_env.display()));
         }
 
+        let instance = self.project.config.get_instance(instance_name).unwrap();
         let mut env_vars = vec![
             {
-                let port = self
-                    .project
-                    .config
-                    .get_instance(instance_name)
-                    .unwrap()
-                    .port()
-                    .unwrap_or(6969);
+                let port = instance.port().unwrap_or(6969);
                 format!("HELIX_PORT={port}")
             },
             format!("HELIX_DATA_DIR=/data"),
@@ -131,6 +174,24 @@ impl<'a> DockerManager<'a> {
                 format!("HELIX_PROJECT={project_name}")
             },
         ];
+        if let Some(map_size_gb) = instance.resources().and_then(|r| r.map_size_gb) {
+            env_vars.push(format!("HELIX_MAP_SIZE_GB={map_size_gb}"));
+        }
+        if let Some(leader) = instance.standby_of() {
+            // The leader publishes its port on the host, which the standby reaches through
+            // the host gateway added to its compose file
+            let port = self
+                .project
+                .config
+                .get_instance(leader)
+                .ok()
+                .and_then(|leader| leader.port())
+                .unwrap_or(6969);
+            env_vars.push(format!("HELIX_STANDBY_OF=http://{STANDBY_HOST}:{port}"));
+            if let Ok(api_key) = std::env::var("HELIX_LEADER_API_KEY") {
+                env_vars.push(format!("HELIX_LEADER_API_KEY={api_key}"));
+            }
+        }
         if let Ok(core_override) = std::env::var("HELIX_CORES_OVERRIDE") {
             env_vars.push(format!("HELIX_CORES_OVERRIDE={core_override}"));
         }
@@ -147,7 +208,7 @@ impl<'a> DockerManager<'a> {
     }
 
     /// Get the container name for an instance
-    fn container_name(&self, instance_name: &str) -> String {
+    pub(crate) fn container_name(&self, instance_name: &str) -> String {
         let project_name = self.compose_project_name(instance_name);
         format!("{project_name}_app")
     }
@@ -199,7 +260,7 @@ impl<'a> DockerManager<'a> {
     }
 
     /// Detect the current operating system platform
-    fn detect_platform() -> &'static str {
+    pub(crate) fn detect_platform() -> &'static str {
         #[cfg(target_os = "macos")]
         return "macos";
 
@@ -313,16 +374,16 @@ impl<'a> DockerManager<'a> {
                         // Modern Docker Desktop CLI worked
                     }
                     _ => {
-                        // Fallback to direct executable path for older versions
-                        // Note: Empty string "" is required as window title parameter
-                        Command::new("cmd")
-                            .args([
-                                "/c",
-                                "start",
-                                "",
-                                "\"C:\\Program Files\\Docker\\Docker\\Docker Desktop.exe\"",
-                            ])
-                            .output()
+                        // Fallback to launching the executable for older versions, from
+                        // wherever Program Files is on this machine
+                        let program_files = std::env::var("ProgramFiles")
+                            .unwrap_or_else(|_| r"C:\Program Files".to_string());
+                        let desktop = std::path::Path::new(&program_files)
+                            .join("Docker")
+                            .join("Docker")
+                            .join("Docker Desktop.exe");
+                        Command::new(&desktop)
+                            .spawn()
                             .map_err(|e| eyre!("Failed to start Docker Desktop: {}", e))?;
                     }
                 }
@@ -449,10 +510,13 @@ impl<'a> DockerManager<'a> {
                     .map_err(|_| eyre!("Failed to verify {} daemon status", cmd))?;
 
                 if !verify_output.status.success() {
-                    return Err(eyre!(
-                        "{} daemon failed to start. Please start {} manually and try again.",
-                        runtime.label(),
-                        cmd
+                    return Err(Self::daemon_error(
+                        runtime,
+                        format!(
+                            "{} daemon failed to start. Please start {} manually and try again.",
+                            runtime.label(),
+                            cmd
+                        ),
                     ));
                 }
             } else {
@@ -460,15 +524,47 @@ impl<'a> DockerManager<'a> {
                     "{} daemon must be running to execute this command.",
                     runtime.label()
                 ));
-                return Err(eyre!(
-                    "{} daemon is not running. Please start {}.",
-                    cmd,
-                    cmd
+                return Err(Self::daemon_error(
+                    runtime,
+                    format!("{} daemon is not running. Please start {}.", cmd, cmd),
                 ));
             }
         }
 
-        Ok(())
+        Self::check_linux_engine(runtime)
+    }
+
+    /// Error for an unreachable daemon, with what to check on platforms where it's not obvious
+    fn daemon_error(runtime: ContainerRuntime, message: String) -> eyre::Report {
+        match (runtime, Self::detect_platform()) {
+            (ContainerRuntime::Docker, "windows") => {
+                let hint = windows_daemon_hint(
+                    std::env::var("DOCKER_HOST").ok().as_deref(),
+                    std::path::Path::new(DOCKER_DESKTOP_PIPE).exists(),
+                );
+                eyre!("{}", CliError::new(message).with_hint(hint).render())
+            }
+            _ => eyre!(message),
+        }
+    }
+
+    /// Docker Desktop on Windows can run Windows containers instead of the Linux ones Helix
+    /// builds, which only shows once a build fails
+    fn check_linux_engine(runtime: ContainerRuntime) -> Result<()> {
+        if !matches!(
+            (runtime, Self::detect_platform()),
+            (ContainerRuntime::Docker, "windows")
+        ) {
+            return Ok(());
+        }
+        let output = Command::new("docker")
+            .args(["info", "--format", "{{.OSType}}"])
+            .output()
+            .map_err(|_| eyre!("Failed to check docker daemon status"))?;
+        match windows_containers_error(&String::from_utf8_lossy(&output.stdout)) {
+            Some(error) => Err(eyre!("{}", error.render())),
+            None => Ok(()),
+        }
     }
 
     /// Generate Dockerfile for an instance
@@ -591,7 +687,7 @@ services:
       - {data_dir}:/data
     environment:
 {env_section}
-    restart: unless-stopped
+{limits}{hosts}    restart: unless-stopped
     networks:
       - {network_name}
 
@@ -602,12 +698,34 @@ networks:
             platform = instance_config
                 .docker_build_target()
                 .map_or("".to_string(), |p| format!("platforms:\n        - {p}")),
-            data_dir = self.data_dir(instance_name)
+            data_dir = self.data_dir(instance_name),
+            limits = Self::compose_limits(instance_config.resources()),
+            hosts = match instance_config.standby_of() {
+                Some(_) => format!("    extra_hosts:\n      - \"{STANDBY_HOST}:host-gateway\"\n"),
+                None => String::new(),
+            }
         );
 
         Ok(compose)
     }
 
+    /// Service keys limiting the container's CPU and memory, one indented line each
+    fn compose_limits(resources: Option<&ResourceLimits>) -> String {
+        let Some(resources) = resources else {
+            return String::new();
+        };
+        let mut limits = String::new();
+        if let Some(shares) = resources.cpu_shares {
+            limits.push_str(&format!("    cpu_shares: {shares}\n"));
+        }
+        if let Some(memory) = &resources.memory {
+            // Matching the swap limit keeps the container from swapping past its memory limit
+            limits.push_str(&format!("    mem_limit: {memory}\n"));
+            limits.push_str(&format!("    memswap_limit: {memory}\n"));
+        }
+        limits
+    }
+
     /// Build Docker/Podman image for an instance
     pub fn build_image(&self, instance_name: &str, _build_target: Option<&str>) -> Result<()> {
         Step::verbose_substep(&format!(
@@ -704,6 +822,25 @@ networks:
         Ok(())
     }
 
+    /// Check if an instance's container is running
+    pub fn instance_running(&self, instance_name: &str) -> Result<bool> {
+        let filter = format!("name=^{}$", self.container_name(instance_name));
+        let output = self.run_docker_command(&[
+            "ps",
+            "-q",
+            "--filter",
+            &filter,
+            "--filter",
+            "status=running",
+        ])?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(eyre!("Failed to get container status:\n{stderr}"));
+        }
+        Ok(!String::from_utf8_lossy(&output.stdout).trim().is_empty())
+    }
+
     /// Check if an instance container exists (running or stopped)
     pub fn instance_exists(&self, instance_name: &str) -> Result<bool> {
         let statuses = self.get_project_status()?;
@@ -1,19 +1,27 @@
 // Library interface for helix-cli to enable testing
 use clap::Subcommand;
+use helix_db::helixc::{generator::docs::DocsFormat, parser::schema_diagram::DiagramFormat};
+use std::path::PathBuf;
 
 pub mod cleanup;
 pub mod commands;
 pub mod config;
+pub mod diagnostics;
 pub mod docker;
 pub mod errors;
 pub mod github_issue;
+pub mod location;
 pub mod metrics_sender;
+pub mod native;
 pub mod output;
 pub mod port;
 pub mod project;
 pub mod prompts;
+pub mod registry;
+pub mod registry_file;
 pub mod sse_client;
 pub mod update;
+pub mod user_config;
 pub mod utils;
 
 #[derive(Subcommand)]
@@ -22,10 +30,22 @@ pub enum AuthAction {
     Login,
     /// Logout from Helix cloud
     Logout,
-    /// Create a new API key
+    /// Create an API key for an instance's query routes
     CreateKey {
-        /// Cluster ID
-        cluster: String,
+        /// Instance the key authenticates against
+        instance: String,
+
+        /// Name identifying the key (defaults to key-<n>)
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Only allow the key to call read routes
+        #[clap(long, conflicts_with = "admin")]
+        read_only: bool,
+
+        /// Also allow the key to use the instance's `/admin` API
+        #[clap(long)]
+        admin: bool,
     },
 }
 
@@ -41,6 +61,28 @@ pub enum MetricsAction {
     Status,
 }
 
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Change a setting
+    Set {
+        /// Setting to change
+        key: String,
+        value: String,
+    },
+    /// Show a setting's value
+    Get {
+        /// Setting to show
+        key: String,
+    },
+    /// Go back to a setting's default
+    Unset {
+        /// Setting to clear
+        key: String,
+    },
+    /// Show every setting
+    List,
+}
+
 #[derive(Subcommand)]
 pub enum DashboardAction {
     /// Start the dashboard
@@ -74,13 +116,233 @@ pub enum DashboardAction {
     Status,
 }
 
+#[derive(Subcommand)]
+pub enum DataAction {
+    /// Compare two backups, or a backup and a local instance, and report the nodes, edges and
+    /// vectors added, removed and changed per label
+    Diff {
+        /// Backup directory, s3://, gs:// or az:// URL of a backup, or local instance name
+        a: String,
+
+        /// Backup directory, s3://, gs:// or az:// URL of a backup, or local instance name to
+        /// compare against the first
+        b: String,
+
+        /// Print the counts per label as JSON
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GenerateAction {
+    /// Write JSON Schema documents of each query's request body and response
+    JsonSchema {
+        /// Directory containing helix.toml (defaults to current directory or project root)
+        #[clap(short, long)]
+        path: Option<String>,
+
+        /// Directory to write `<query>.input.json` and `<query>.output.json` into
+        /// (defaults to schemas/ in the project root)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a documentation page per query, with its doc comment, parameters, response and
+    /// an example curl call, and an index page
+    Docs {
+        /// Directory containing helix.toml (defaults to current directory or project root)
+        #[clap(short, long)]
+        path: Option<String>,
+
+        /// Directory to write the pages into (defaults to docs/ in the project root)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Write markdown or html pages
+        #[clap(long, default_value_t = DocsFormat::Markdown)]
+        format: DocsFormat,
+
+        /// URL of the instance the example calls go to
+        #[clap(long, default_value = "http://localhost:6969")]
+        url: String,
+    },
+    /// Write a diagram of the schema's node, vector and edge types and their fields
+    SchemaDiagram {
+        /// Directory containing helix.toml (defaults to current directory or project root)
+        #[clap(short, long)]
+        path: Option<String>,
+
+        /// File to write the diagram to (defaults to stdout)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Write a dot (Graphviz) or mermaid diagram
+        #[clap(long, default_value_t = DiagramFormat::Mermaid)]
+        format: DiagramFormat,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportAction {
+    /// Write the subgraph a query returns with `::SUBGRAPH` to a JSON or GraphML file
+    Subgraph {
+        /// Instance to run the query on: a local instance name or an http(s):// URL
+        #[clap(long)]
+        target: String,
+
+        /// Query returning a subgraph
+        #[clap(long)]
+        query: String,
+
+        /// JSON request body of the query
+        #[clap(long)]
+        params: Option<String>,
+
+        /// File to write, ending in .json or .graphml
+        #[clap(short, long)]
+        output: String,
+    },
+
+    /// Write Node2Vec style random walks over an instance's graph, or the co-occurring pairs of
+    /// nodes along them, for training node embeddings
+    Walks {
+        /// Instance to walk: a local instance name or an http(s):// URL
+        #[clap(long)]
+        target: String,
+
+        /// File to write, ending in .txt (a walk per line), .csv or .json
+        #[clap(short, long)]
+        output: String,
+
+        /// Nodes per walk
+        #[clap(long, default_value_t = 80)]
+        walk_length: usize,
+
+        /// Walks started from each node
+        #[clap(long, default_value_t = 10)]
+        walks_per_node: usize,
+
+        /// Return parameter: higher steps straight back less often
+        #[clap(long, default_value_t = 1.0)]
+        p: f64,
+
+        /// In-out parameter: higher stays closer to where the walk started
+        #[clap(long, default_value_t = 1.0)]
+        q: f64,
+
+        /// Only start walks from nodes with this label
+        #[clap(long)]
+        label: Option<String>,
+
+        /// Only follow edges with this label (repeatable)
+        #[clap(long = "edge-label")]
+        edge_labels: Vec<String>,
+
+        /// Seed to walk the same way every time
+        #[clap(long)]
+        seed: Option<u64>,
+
+        /// Write the pairs of nodes at most this many steps apart along a walk instead
+        #[clap(long)]
+        window: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportSource {
+    /// Import a Neo4j Cypher dump or neo4j-admin CSV export into a local instance
+    Neo4j {
+        /// Local instance to load the data into (stop it first)
+        instance: String,
+
+        /// Cypher dump file (apoc.export.cypher or neo4j-shell dump) or directory of
+        /// neo4j-admin import CSV files, locally or at an s3://, gs:// or az:// URL
+        #[clap(long)]
+        from: String,
+
+        /// Where to write the schema proposed from the data when the project has none
+        /// (defaults to schema.hx in the queries directory)
+        #[clap(long)]
+        schema_out: Option<PathBuf>,
+
+        /// Show how the data maps to the schema without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Import a GraphML file (Gephi, NetworkX, yEd, apoc.export.graphml) into a local instance
+    Graphml {
+        /// Local instance to load the data into (stop it first)
+        instance: String,
+
+        /// GraphML file to import, locally or at an s3://, gs:// or az:// URL
+        #[clap(long)]
+        from: String,
+
+        /// Where to write the schema proposed from the data when the project has none
+        /// (defaults to schema.hx in the queries directory)
+        #[clap(long)]
+        schema_out: Option<PathBuf>,
+
+        /// Show how the data maps to the schema without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Import a GEXF file (Gephi, NetworkX) into a local instance
+    Gexf {
+        /// Local instance to load the data into (stop it first)
+        instance: String,
+
+        /// GEXF file to import, locally or at an s3://, gs:// or az:// URL
+        #[clap(long)]
+        from: String,
+
+        /// Where to write the schema proposed from the data when the project has none
+        /// (defaults to schema.hx in the queries directory)
+        #[clap(long)]
+        schema_out: Option<PathBuf>,
+
+        /// Show how the data maps to the schema without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Import CSV node and edge tables into a local instance
+    Csv {
+        /// Local instance to load the data into (stop it first)
+        instance: String,
+
+        /// Node table as FILE:LABEL, e.g. users.csv:User (repeatable). Files can be s3://,
+        /// gs:// or az:// URLs
+        #[clap(long)]
+        nodes: Vec<String>,
+
+        /// Edge table as FILE:TYPE(FROM,TO) naming the endpoint id columns, e.g.
+        /// follows.csv:Follows(from,to) or follows.csv:Follows(from:User,to:User) (repeatable)
+        #[clap(long)]
+        edges: Vec<String>,
+
+        /// TOML file listing tables and the name, type or skipping of their columns
+        #[clap(long)]
+        mapping: Option<PathBuf>,
+
+        /// Where to write the schema proposed from the data when the project has none
+        /// (defaults to schema.hx in the queries directory)
+        #[clap(long)]
+        schema_out: Option<PathBuf>,
+
+        /// Show how the data maps to the schema without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum CloudDeploymentTypeCommand {
     /// Initialize Helix Cloud deployment
     #[clap(name = "cloud")]
     Helix {
-        /// Region for Helix cloud instance (default: us-east-1)
-        #[clap(long, default_value = "us-east-1")]
+        /// Region for Helix cloud instance (defaults to the configured region, or us-east-1)
+        #[clap(long)]
         region: Option<String>,
 
         /// Instance name
@@ -122,6 +384,15 @@ pub enum CloudDeploymentTypeCommand {
         #[clap(short, long)]
         name: Option<String>,
     },
+    /// Add a local warm standby of a local instance, promoted with `helix promote`
+    StandbyOf {
+        /// Local instance the standby copies
+        instance: String,
+
+        /// Instance name (defaults to `<instance>-standby`)
+        #[clap(short, long)]
+        name: Option<String>,
+    },
 }
 
 impl CloudDeploymentTypeCommand {
@@ -131,6 +402,9 @@ impl CloudDeploymentTypeCommand {
             CloudDeploymentTypeCommand::Ecr { name } => name.clone(),
             CloudDeploymentTypeCommand::Fly { name, .. } => name.clone(),
             CloudDeploymentTypeCommand::Local { name } => name.clone(),
+            CloudDeploymentTypeCommand::StandbyOf { instance, name } => {
+                Some(name.clone().unwrap_or_else(|| format!("{instance}-standby")))
+            }
         }
     }
 }
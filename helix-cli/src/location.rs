@@ -0,0 +1,228 @@
+//! Locations commands read from and write to: local paths, or objects in S3 (`s3://`), Google
+//! Cloud Storage (`gs://`) and Azure Blob Storage (`az://`, `abfss://`), all through the
+//! `ObjectStore` trait so every command gains the same cloud targets. Credentials come from the
+//! environment the way the clouds' own tools read them, such as `AWS_ACCESS_KEY_ID`,
+//! `GOOGLE_SERVICE_ACCOUNT` or `AZURE_STORAGE_ACCOUNT_KEY`.
+
+use crate::errors::CliError;
+use eyre::{Result, eyre};
+use futures_util::TryStreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, WriteMultipart};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+/// Bytes read from a local file per part of an upload
+const UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+/// Parts of an upload in flight at once
+const UPLOAD_CONCURRENCY: usize = 4;
+
+/// Where a command reads or writes its files
+#[derive(Debug)]
+pub enum Location {
+    Local(PathBuf),
+    Remote(RemoteLocation),
+}
+
+impl Location {
+    /// A URL with a cloud scheme is a remote location, anything else a local path
+    pub fn parse(location: &str) -> Result<Self> {
+        match Url::parse(location) {
+            Ok(url) if is_remote_scheme(url.scheme()) => {
+                RemoteLocation::open(url).map(Self::Remote)
+            }
+            _ => Ok(Self::Local(PathBuf::from(location))),
+        }
+    }
+
+    /// Whether a path names an object in a cloud store rather than a local file
+    pub fn is_remote(path: &Path) -> bool {
+        path.to_str()
+            .and_then(|path| Url::parse(path).ok())
+            .is_some_and(|url| is_remote_scheme(url.scheme()))
+    }
+}
+
+fn is_remote_scheme(scheme: &str) -> bool {
+    matches!(
+        scheme,
+        "s3" | "s3a" | "gs" | "az" | "azure" | "abfs" | "abfss"
+    )
+}
+
+/// An object, or a prefix of objects, in a cloud store
+pub struct RemoteLocation {
+    url: Url,
+    store: Arc<dyn ObjectStore>,
+    path: ObjectPath,
+}
+
+impl fmt::Debug for RemoteLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RemoteLocation")
+            .field(&self.url.as_str())
+            .finish()
+    }
+}
+
+impl fmt::Display for RemoteLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.url.as_str())
+    }
+}
+
+impl RemoteLocation {
+    fn open(url: Url) -> Result<Self> {
+        let store: Result<Arc<dyn ObjectStore>, object_store::Error> = match url.scheme() {
+            "s3" | "s3a" => AmazonS3Builder::from_env()
+                .with_url(url.as_str())
+                .build()
+                .map(|store| Arc::new(store) as _),
+            "gs" => GoogleCloudStorageBuilder::from_env()
+                .with_url(url.as_str())
+                .build()
+                .map(|store| Arc::new(store) as _),
+            _ => MicrosoftAzureBuilder::from_env()
+                .with_url(url.as_str())
+                .build()
+                .map(|store| Arc::new(store) as _),
+        };
+        let store = store.map_err(|e| {
+            let error = CliError::new(format!("can't open {url}")).with_hint(format!(
+                "{e}; check the bucket in the URL and the credentials in the environment"
+            ));
+            eyre!("{}", error.render())
+        })?;
+        Self::new(url, store)
+    }
+
+    /// A location in `store`, at the path of `url`
+    pub fn new(url: Url, store: Arc<dyn ObjectStore>) -> Result<Self> {
+        let path = ObjectPath::from_url_path(url.path())
+            .map_err(|e| eyre!("Invalid object path in {url}: {e}"))?;
+        Ok(Self { url, store, path })
+    }
+
+    /// Last segment of the object path, such as `graph.graphml`
+    pub fn file_name(&self) -> Option<&str> {
+        self.path.filename()
+    }
+
+    /// Upload a local file to the location, or the files of a directory to objects under it.
+    /// Returns how many objects were written.
+    pub async fn upload(&self, local: &Path) -> Result<usize> {
+        if !local.is_dir() {
+            self.upload_file(local, &self.path).await?;
+            return Ok(1);
+        }
+        let mut uploaded = 0;
+        let mut dirs = vec![local.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(local)
+                    .expect("files found under a directory should be inside it");
+                let object = relative
+                    .components()
+                    .fold(self.path.clone(), |object, part| {
+                        object.child(part.as_os_str().to_string_lossy().as_ref())
+                    });
+                self.upload_file(&path, &object).await?;
+                uploaded += 1;
+            }
+        }
+        Ok(uploaded)
+    }
+
+    async fn upload_file(&self, local: &Path, object: &ObjectPath) -> Result<()> {
+        let failed =
+            |e: &dyn fmt::Display| eyre!("Failed to upload {} to {object}: {e}", local.display());
+        let mut file = tokio::fs::File::open(local)
+            .await
+            .map_err(|e| eyre!("Failed to read {}: {e}", local.display()))?;
+        let upload = self
+            .store
+            .put_multipart(object)
+            .await
+            .map_err(|e| failed(&e))?;
+        let mut upload = WriteMultipart::new_with_chunk_size(upload, UPLOAD_CHUNK_SIZE);
+        let mut buffer = vec![0; UPLOAD_CHUNK_SIZE];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .await
+                .map_err(|e| eyre!("Failed to read {}: {e}", local.display()))?;
+            if read == 0 {
+                break;
+            }
+            upload
+                .wait_for_capacity(UPLOAD_CONCURRENCY)
+                .await
+                .map_err(|e| failed(&e))?;
+            upload.write(&buffer[..read]);
+        }
+        upload.finish().await.map_err(|e| failed(&e))?;
+        Ok(())
+    }
+
+    /// Download the object at the location into `dir`, or when there is none, the objects
+    /// under it as a directory. Returns the local path of what was downloaded.
+    pub async fn download(&self, dir: &Path) -> Result<PathBuf> {
+        let name = self.file_name().unwrap_or("download");
+        let local = dir.join(name);
+        match self.download_file(&self.path, &local).await {
+            Ok(()) => return Ok(local),
+            Err(object_store::Error::NotFound { .. }) => {}
+            Err(e) => return Err(eyre!("Failed to download {self}: {e}")),
+        }
+
+        let objects: Vec<_> = self
+            .store
+            .list(Some(&self.path))
+            .try_collect()
+            .await
+            .map_err(|e| eyre!("Failed to list {self}: {e}"))?;
+        if objects.is_empty() {
+            let error = CliError::new(format!("nothing was found at {self}"))
+                .with_hint("check the bucket and path in the URL");
+            return Err(eyre!("{}", error.render()));
+        }
+        for object in objects {
+            let Some(parts) = object.location.prefix_match(&self.path) else {
+                continue;
+            };
+            let file = parts.fold(local.clone(), |file, part| file.join(part.as_ref()));
+            self.download_file(&object.location, &file)
+                .await
+                .map_err(|e| eyre!("Failed to download {}: {e}", object.location))?;
+        }
+        Ok(local)
+    }
+
+    async fn download_file(&self, object: &ObjectPath, local: &Path) -> object_store::Result<()> {
+        let mut stream = self.store.get(object).await?.into_stream();
+        let io_error = |e: std::io::Error| object_store::Error::Generic {
+            store: "local",
+            source: Box::new(e),
+        };
+        if let Some(parent) = local.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+        let mut file = tokio::fs::File::create(local).await.map_err(io_error)?;
+        while let Some(bytes) = stream.try_next().await? {
+            file.write_all(&bytes).await.map_err(io_error)?;
+        }
+        file.flush().await.map_err(io_error)
+    }
+}
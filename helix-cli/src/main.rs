@@ -1,21 +1,34 @@
 use clap::{Parser, Subcommand};
+use commands::bench::KeyDistribution;
+use diagnostics::DiagnosticsFormat;
 use eyre::Result;
-use helix_cli::{AuthAction, CloudDeploymentTypeCommand, DashboardAction, MetricsAction};
+use helix_cli::{
+    AuthAction, CloudDeploymentTypeCommand, ConfigAction, DashboardAction, DataAction,
+    ExportAction, GenerateAction, ImportSource, MetricsAction,
+};
+use helix_db::helix_engine::graph::random_walk::WalkConfig;
+use helix_db::helixc::generator::CodegenProfile;
 use std::path::PathBuf;
 
 mod cleanup;
 mod commands;
 mod config;
+mod diagnostics;
 mod docker;
 mod errors;
 mod github_issue;
+mod location;
 mod metrics_sender;
+mod native;
 mod output;
 mod port;
 mod project;
 mod prompts;
+mod registry;
+mod registry_file;
 mod sse_client;
 mod update;
+mod user_config;
 mod utils;
 
 #[derive(Parser)]
@@ -42,8 +55,9 @@ enum Commands {
         #[clap(short, long)]
         path: Option<String>,
 
-        #[clap(short, long, default_value = "empty")]
-        template: String,
+        /// Project template (defaults to the configured template, or empty)
+        #[clap(short, long)]
+        template: Option<String>,
 
         /// Queries directory path (defaults to ./db/)
         #[clap(short = 'q', long = "queries-path", default_value = "./db/")]
@@ -64,7 +78,7 @@ enum Commands {
         /// Instance name
         instance: String,
 
-        /// Region for cluster (defaults to us-east-1)
+        /// Region for cluster (defaults to the configured region, or us-east-1)
         #[clap(short, long)]
         region: Option<String>,
     },
@@ -73,6 +87,11 @@ enum Commands {
     Check {
         /// Instance to check (defaults to all instances)
         instance: Option<String>,
+
+        /// Only analyze the queries, printing their diagnostics to stdout as JSON or SARIF for
+        /// editors and CI
+        #[arg(long, value_enum, conflicts_with = "instance")]
+        format: Option<DiagnosticsFormat>,
     },
 
     /// Compile project queries into the workspace
@@ -84,6 +103,20 @@ enum Commands {
         /// Path to output compiled queries
         #[clap(short, long)]
         output: Option<String>,
+
+        /// Also write typed Rust bindings for the queries, using the helix-client crate, to this file
+        #[clap(long)]
+        client: Option<String>,
+
+        /// Generate for build time (fast-compile) or speed (max-perf), overriding helix.toml
+        #[clap(long)]
+        codegen_profile: Option<CodegenProfile>,
+    },
+
+    /// Generate artifacts from the project's queries
+    Generate {
+        #[clap(subcommand)]
+        target: GenerateAction,
     },
 
     /// Build and compile project for an instance
@@ -94,6 +127,9 @@ enum Commands {
         /// Should build HelixDB into a binary at the specified directory location
         #[clap(long)]
         bin: Option<String>,
+        /// Generate for build time (fast-compile) or speed (max-perf), overriding helix.toml
+        #[clap(long)]
+        codegen_profile: Option<CodegenProfile>,
     },
 
     /// Deploy/start an instance
@@ -103,6 +139,9 @@ enum Commands {
         /// Use development profile for faster builds (Helix Cloud only)
         #[clap(long)]
         dev: bool,
+        /// Build with cargo and run as a local process instead of a container (local only)
+        #[clap(long)]
+        native: bool,
     },
 
     /// Pull .hql files from instance back to local project
@@ -123,14 +162,45 @@ enum Commands {
         instance: Option<String>,
     },
 
+    /// Promote a standby to leader, stopping the instance it follows
+    Promote {
+        /// Standby instance to promote
+        instance: String,
+    },
+
+    /// Apply the log level, slow query threshold, cache size and rate limits in helix.toml
+    /// to a running instance without restarting it
+    Reload {
+        /// Instance whose gateway_config is applied
+        instance: String,
+
+        /// URL of the running instance (default: the local instance's port)
+        #[arg(long)]
+        url: Option<String>,
+    },
+
     /// Restart an instance (stop then start)
     Restart {
         /// Instance name to restart (interactive selection if not provided)
         instance: Option<String>,
     },
 
-    /// Show status of all instances
-    Status,
+    /// Show status of all instances and probe the health of deployed local instances.
+    /// Exits non-zero when any of them is unhealthy.
+    Status {
+        /// Also show version, uptime, schema versions and per-route stats of running
+        /// local instances
+        #[clap(long)]
+        detailed: bool,
+    },
+
+    /// List running local instances with their ports and data directories
+    Ps {
+        /// List the instances of every project started on this machine, including projects
+        /// whose directory was moved or deleted
+        #[clap(long)]
+        all_projects: bool,
+    },
 
     /// View logs for an instance
     Logs {
@@ -152,6 +222,10 @@ enum Commands {
         /// End time (ISO 8601: 2024-01-15T11:00:00Z)
         #[clap(long, requires = "range")]
         end: Option<String>,
+
+        /// Only show slow query events (set `slow_query_ms` in the instance's gateway_config)
+        #[clap(long)]
+        slow: bool,
     },
 
     /// Cloud operations (login, keys, etc.)
@@ -182,6 +256,12 @@ enum Commands {
         action: MetricsAction,
     },
 
+    /// Manage user-level CLI settings in ~/.helix/config.toml
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+
     /// Launch the Helix Dashboard
     Dashboard {
         #[clap(subcommand)]
@@ -227,9 +307,129 @@ enum Commands {
         /// Instance name to backup
         instance: String,
 
-        /// Output directory for the backup. If omitted, ./backups/backup-<ts>/ will be used
+        /// Output directory for the backup, or an s3://, gs:// or az:// URL to upload it under.
+        /// If omitted, ./backups/backup-<ts>/ will be used
         #[arg(short, long)]
-        output: Option<PathBuf>,
+        output: Option<String>,
+    },
+
+    /// Export a local instance's graph to a GraphML or GEXF file or Parquet tables (vectors
+    /// aren't included)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Export {
+        /// Instance name to export
+        #[arg(required = true)]
+        instance: Option<String>,
+
+        /// File to write, ending in .graphml, .gexf or .parquet, or a directory to write a
+        /// Parquet table per node label and edge type into. An s3://, gs:// or az:// URL
+        /// uploads the export there
+        #[arg(short, long, required = true)]
+        output: Option<String>,
+
+        /// Node label or edge type to export as Parquet
+        #[arg(long)]
+        label: Option<String>,
+
+        #[clap(subcommand)]
+        action: Option<ExportAction>,
+    },
+
+    /// Import data exported from another graph database or graph tool
+    Import {
+        #[clap(subcommand)]
+        source: ImportSource,
+    },
+
+    /// Load the fixtures in seeds/ into a local instance, in file name order
+    Seed {
+        /// Local instance to load the fixtures into
+        instance: String,
+
+        /// Clear the instance's data before loading
+        #[arg(long)]
+        reset: bool,
+    },
+
+    /// Re-run the queries of a structured query log against an instance
+    Replay {
+        /// NDJSON query log with a `query`, `params` and `timestamp` per line, or an s3://,
+        /// gs:// or az:// URL of one
+        #[arg(long)]
+        log: String,
+
+        /// Instance to replay against: a local instance name or an http(s):// URL
+        #[arg(long)]
+        target: String,
+
+        /// How much faster than logged to send the queries, e.g. 2x or 0.5x, or max to send
+        /// them as fast as possible
+        #[arg(long, default_value = "1x")]
+        speed: String,
+
+        /// Most requests in flight at once
+        #[arg(long, default_value = "64")]
+        concurrency: usize,
+    },
+
+    /// Measure an instance's throughput and latency under a read, write or search workload
+    Bench {
+        /// Instance to benchmark: a local instance name or an http(s):// URL
+        #[arg(long)]
+        target: String,
+
+        /// TOML file describing the workload's queries, their weights, params and keys
+        #[arg(long, conflicts_with = "query")]
+        workload: Option<PathBuf>,
+
+        /// Benchmark a single query instead of a workload file
+        #[arg(long)]
+        query: Option<String>,
+
+        /// JSON request body of --query, where "{key}" is replaced by a key, "{vector:N}" by
+        /// a random N-dimensional vector and "{uuid}" by a new id
+        #[arg(long, requires = "query")]
+        params: Option<String>,
+
+        /// How long to run, such as 30s or 2m (defaults to the workload's, or 30s)
+        #[arg(long)]
+        duration: Option<String>,
+
+        /// Workers sending queries at once (defaults to the workload's, or 16)
+        #[arg(long)]
+        concurrency: Option<usize>,
+
+        /// Number of keys --query picks from (defaults to 10000)
+        #[arg(long, requires = "query")]
+        keys: Option<usize>,
+
+        /// How --query picks keys
+        #[arg(long, value_enum, requires = "query")]
+        distribution: Option<KeyDistribution>,
+
+        /// Print the results as JSON, to compare runs across versions
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Inspect the data of backups and local instances
+    Data {
+        #[clap(subcommand)]
+        action: DataAction,
+    },
+
+    /// Check that an instance's nodes, edges, indices and vectors are consistent
+    Fsck {
+        /// Instance to check: a stopped local instance or a running instance's http(s):// URL
+        target: String,
+
+        /// Fix the issues that can be rebuilt from the stored nodes, edges and vectors
+        #[arg(long)]
+        repair: bool,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Send feedback to the Helix team
@@ -255,8 +455,9 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Set verbosity level from flags
-    output::Verbosity::set(output::Verbosity::from_flags(cli.quiet, cli.verbose));
+    // Set verbosity level from flags, falling back to the user config
+    let user_config = user_config::UserConfig::current();
+    output::Verbosity::set(user_config.verbosity(cli.quiet, cli.verbose));
 
     let result = match cli.command {
         Commands::Init {
@@ -264,35 +465,57 @@ async fn main() -> Result<()> {
             template,
             queries_path,
             cloud,
-        } => commands::init::run(path, template, queries_path, cloud).await,
+        } => {
+            let template = template.unwrap_or_else(|| user_config.template());
+            commands::init::run(path, template, queries_path, cloud).await
+        }
         Commands::Add { cloud } => commands::add::run(cloud).await,
         Commands::CreateCluster { instance, region } => {
             commands::create_cluster::run(&instance, region).await
         }
-        Commands::Check { instance } => commands::check::run(instance, &metrics_sender).await,
-        Commands::Compile { output, path } => commands::compile::run(output, path).await,
-        Commands::Build { instance, bin } => commands::build::run(instance, bin, &metrics_sender)
+        Commands::Check { instance, format } => {
+            commands::check::run(instance, format, &metrics_sender).await
+        }
+        Commands::Compile {
+            output,
+            path,
+            client,
+            codegen_profile,
+        } => commands::compile::run(output, path, client, codegen_profile).await,
+        Commands::Generate { target } => commands::generate::run(target).await,
+        Commands::Build {
+            instance,
+            bin,
+            codegen_profile,
+        } => commands::build::run(instance, bin, codegen_profile, &metrics_sender)
             .await
             .map(|_| ()),
-        Commands::Push { instance, dev } => {
-            commands::push::run(instance, dev, &metrics_sender).await
-        }
+        Commands::Push {
+            instance,
+            dev,
+            native,
+        } => commands::push::run(instance, dev, native, &metrics_sender).await,
         Commands::Pull { instance } => commands::pull::run(instance).await,
         Commands::Start { instance } => commands::start::run(instance).await,
         Commands::Stop { instance } => commands::stop::run(instance).await,
         Commands::Restart { instance } => commands::restart::run(instance).await,
-        Commands::Status => commands::status::run().await,
+        Commands::Promote { instance } => commands::promote::run(instance).await,
+        Commands::Reload { instance, url } => commands::reload::run(instance, url).await,
+        Commands::Status { detailed } => commands::status::run(detailed).await,
+        Commands::Ps { all_projects } => commands::ps::run(all_projects).await,
         Commands::Logs {
             instance,
             live,
             range,
             start,
             end,
-        } => commands::logs::run(instance, live, range, start, end).await,
+            slow,
+        } => commands::logs::run(instance, live, range, start, end, slow).await,
         Commands::Auth { action } => commands::auth::run(action).await,
         Commands::Prune { instance, all } => commands::prune::run(instance, all).await,
         Commands::Delete { instance } => commands::delete::run(instance).await,
         Commands::Metrics { action } => commands::metrics::run(action).await,
+        Commands::Config { action } => commands::config::run(action).await,
         Commands::Dashboard { action } => commands::dashboard::run(action).await,
         Commands::Update { force } => commands::update::run(force).await,
         Commands::Migrate {
@@ -306,6 +529,92 @@ async fn main() -> Result<()> {
             commands::migrate::run(path, queries_dir, instance_name, port, dry_run, no_backup).await
         }
         Commands::Backup { instance, output } => commands::backup::run(output, instance).await,
+        Commands::Export {
+            instance,
+            output,
+            label,
+            action,
+        } => match (action, instance, output) {
+            (
+                Some(ExportAction::Subgraph {
+                    target,
+                    query,
+                    params,
+                    output,
+                }),
+                _,
+                _,
+            ) => commands::export::run_subgraph(target, query, params, output).await,
+            (
+                Some(ExportAction::Walks {
+                    target,
+                    output,
+                    walk_length,
+                    walks_per_node,
+                    p,
+                    q,
+                    label,
+                    edge_labels,
+                    seed,
+                    window,
+                }),
+                _,
+                _,
+            ) => {
+                let config = WalkConfig {
+                    walk_length,
+                    walks_per_node,
+                    p,
+                    q,
+                    label,
+                    edge_labels,
+                    seed,
+                };
+                commands::export::run_walks(target, output, config, window).await
+            }
+            (None, Some(instance), Some(output)) => {
+                commands::export::run(instance, output, label).await
+            }
+            _ => unreachable!("clap requires an instance and --output without a subcommand"),
+        },
+        Commands::Import { source } => commands::import::run(source).await,
+        Commands::Seed { instance, reset } => commands::seed::run(instance, reset).await,
+        Commands::Replay {
+            log,
+            target,
+            speed,
+            concurrency,
+        } => commands::replay::run(log, target, speed, concurrency).await,
+        Commands::Bench {
+            target,
+            workload,
+            query,
+            params,
+            duration,
+            concurrency,
+            keys,
+            distribution,
+            json,
+        } => {
+            commands::bench::run(
+                target,
+                workload,
+                query,
+                params,
+                duration,
+                concurrency,
+                keys,
+                distribution,
+                json,
+            )
+            .await
+        }
+        Commands::Data { action } => commands::data::run(action).await,
+        Commands::Fsck {
+            target,
+            repair,
+            json,
+        } => commands::fsck::run(target, repair, json).await,
         Commands::Feedback { message } => commands::feedback::run(message).await,
     };
 
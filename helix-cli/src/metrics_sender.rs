@@ -1,3 +1,4 @@
+use crate::user_config::UserConfig;
 use chrono::{Local, NaiveDate};
 use dirs::home_dir;
 use eyre::{OptionExt, Result, eyre};
@@ -17,7 +18,7 @@ use tokio::task::JoinHandle;
 
 const METRICS_URL: &str = "https://logs.helix-db.com/v2";
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MetricsLevel {
     Full,
@@ -109,7 +110,7 @@ async fn metrics_task(rx: Receiver<MetricsMessage>) -> Result<()> {
 
     let config = load_metrics_config().unwrap_or_default();
 
-    if config.level != MetricsLevel::Off {
+    if metrics_level(&config) != MetricsLevel::Off {
         let _ = upload_previous_logs().await;
 
         let metrics_dir = get_metrics_dir()?;
@@ -153,6 +154,15 @@ pub(crate) fn load_metrics_config() -> Result<MetricsConfig> {
     Ok(config)
 }
 
+/// The level in effect: `telemetry` from the user config when set, otherwise the one chosen
+/// with `helix metrics`
+pub(crate) fn metrics_level(config: &MetricsConfig) -> MetricsLevel {
+    UserConfig::load()
+        .ok()
+        .and_then(|user_config| user_config.telemetry)
+        .unwrap_or(config.level)
+}
+
 pub(crate) fn save_metrics_config(config: &MetricsConfig) -> Result<()> {
     let config_path = get_metrics_config_path()?;
     let content = toml::to_string_pretty(config)?;
@@ -0,0 +1,311 @@
+//! Running local instances as plain processes instead of containers, for machines where
+//! Docker or Podman is unavailable or too heavy.
+//!
+//! `helix push <instance> --native` builds helix-container with cargo and starts it in the
+//! background. Everything about the process lives in `.helix/<instance>/native/`: the binary,
+//! the pid file and the log. While that directory holds a binary, `helix start`, `stop`,
+//! `restart` and `status` manage the process instead of a container. A push without
+//! `--native` removes it again. Both modes keep their data in the same volume directory.
+
+use crate::config::BuildMode;
+use crate::docker::DockerManager;
+use crate::output::Step;
+use crate::project::ProjectContext;
+use crate::utils::copy_dir_recursive_excluding;
+use eyre::{Result, eyre};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name of the process the binary runs as
+const PROCESS_NAME: &str = "helix-container";
+
+/// How long a stopping instance gets to flush and exit before it's killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a started instance must stay up before it counts as started
+const STARTUP_GRACE: Duration = Duration::from_secs(2);
+
+/// Log lines shown when an instance exits during startup
+const LOG_TAIL_LINES: usize = 20;
+
+pub struct NativeManager<'a> {
+    project: &'a ProjectContext,
+}
+
+impl<'a> NativeManager<'a> {
+    pub fn new(project: &'a ProjectContext) -> Self {
+        Self { project }
+    }
+
+    /// Directory holding the binary, pid file and log of a native instance
+    pub fn native_dir(&self, instance_name: &str) -> PathBuf {
+        self.project
+            .instance_workspace(instance_name)
+            .join("native")
+    }
+
+    pub fn binary_path(&self, instance_name: &str) -> PathBuf {
+        self.native_dir(instance_name)
+            .join(format!("{PROCESS_NAME}{}", std::env::consts::EXE_SUFFIX))
+    }
+
+    pub fn pid_path(&self, instance_name: &str) -> PathBuf {
+        self.native_dir(instance_name).join("helix.pid")
+    }
+
+    pub fn log_path(&self, instance_name: &str) -> PathBuf {
+        self.native_dir(instance_name).join("helix.log")
+    }
+
+    /// Whether the instance was last pushed with `--native`
+    pub fn is_native(&self, instance_name: &str) -> bool {
+        self.binary_path(instance_name).exists()
+    }
+
+    /// Build helix-container with the compiled queries, leaving it in the instance's cargo
+    /// target directory so later pushes build incrementally
+    pub fn build_binary(&self, instance_name: &str, build_mode: BuildMode) -> Result<PathBuf> {
+        let workspace = self.project.instance_workspace(instance_name);
+        let repo_copy = workspace.join("helix-repo-copy");
+
+        // Overlay the generated queries, as the Dockerfile does
+        copy_dir_recursive_excluding(
+            &self.project.container_dir(instance_name),
+            &repo_copy.join("helix-container"),
+        )?;
+
+        let target_dir = workspace.join("target");
+        let mut command = Command::new("cargo");
+        command
+            .args(["build", "--package", PROCESS_NAME])
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .current_dir(&repo_copy);
+        let profile = match build_mode {
+            BuildMode::Release => {
+                command.arg("--release");
+                "release"
+            }
+            BuildMode::Dev => {
+                command.args(["--features", "dev"]);
+                "debug"
+            }
+            BuildMode::Debug => "debug",
+        };
+
+        Step::verbose_substep(&format!(
+            "cargo: Building {PROCESS_NAME} into {}",
+            target_dir.display()
+        ));
+        let output = command.output().map_err(|e| {
+            eyre!("Failed to run cargo, which native instances are built with: {e}")
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(eyre!("cargo build failed:\n{stderr}"));
+        }
+
+        Ok(target_dir
+            .join(profile)
+            .join(format!("{PROCESS_NAME}{}", std::env::consts::EXE_SUFFIX)))
+    }
+
+    /// Make `binary` the one the instance runs. The instance must be stopped, as a running
+    /// binary can't be replaced on every platform.
+    pub fn install_binary(&self, instance_name: &str, binary: &Path) -> Result<()> {
+        fs::create_dir_all(self.native_dir(instance_name))?;
+        fs::copy(binary, self.binary_path(instance_name))
+            .map_err(|e| eyre!("Failed to install {}: {e}", binary.display()))?;
+        Ok(())
+    }
+
+    /// Pid of the instance's process, if it's running
+    pub fn running_pid(&self, instance_name: &str) -> Option<u32> {
+        let pid = fs::read_to_string(self.pid_path(instance_name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        process_running(pid).then_some(pid)
+    }
+
+    /// Start the instance in the background, answering with its pid. Starting a running
+    /// instance is a no-op.
+    pub fn start_instance(&self, instance_name: &str) -> Result<u32> {
+        if let Some(pid) = self.running_pid(instance_name) {
+            return Ok(pid);
+        }
+        let binary = self.binary_path(instance_name);
+        if !binary.exists() {
+            return Err(eyre!(
+                "Instance '{instance_name}' has no native binary, run 'helix push {instance_name} --native'"
+            ));
+        }
+
+        let data_dir = self.project.instance_volume(instance_name);
+        fs::create_dir_all(&data_dir)?;
+        let env_vars = DockerManager::new(self.project).environment_variables(instance_name);
+
+        let log_path = self.log_path(instance_name);
+        let log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        let mut command = Command::new(&binary);
+        command
+            .envs(native_environment(&env_vars, &data_dir))
+            .current_dir(self.native_dir(instance_name))
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log);
+        detach(&mut command);
+
+        Step::verbose_substep(&format!(
+            "Starting {} for instance '{instance_name}'...",
+            binary.display()
+        ));
+        let mut child = command
+            .spawn()
+            .map_err(|e| eyre!("Failed to start {}: {e}", binary.display()))?;
+        let pid = child.id();
+        fs::write(self.pid_path(instance_name), pid.to_string())?;
+
+        // Catch instances that can't open their data or bind their port
+        let started = Instant::now();
+        while started.elapsed() < STARTUP_GRACE {
+            if let Some(status) = child.try_wait()? {
+                let _ = fs::remove_file(self.pid_path(instance_name));
+                return Err(eyre!(
+                    "Instance '{instance_name}' exited during startup ({status}), last log lines from {}:\n{}",
+                    log_path.display(),
+                    log_tail(&log_path, LOG_TAIL_LINES)
+                ));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Ok(pid)
+    }
+
+    /// Stop the instance, giving it time to shut down cleanly. Answers whether it was running.
+    pub fn stop_instance(&self, instance_name: &str) -> Result<bool> {
+        let Some(pid) = self.running_pid(instance_name) else {
+            let _ = fs::remove_file(self.pid_path(instance_name));
+            return Ok(false);
+        };
+        Step::verbose_substep(&format!("Stopping process {pid} of '{instance_name}'..."));
+        if let Err(e) = terminate(pid, false)
+            && process_running(pid)
+        {
+            return Err(e);
+        }
+
+        let stopping = Instant::now();
+        while process_running(pid) {
+            if stopping.elapsed() >= STOP_TIMEOUT {
+                Step::verbose_substep(&format!("Process {pid} did not exit in time, killing it"));
+                terminate(pid, true)?;
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        let _ = fs::remove_file(self.pid_path(instance_name));
+        Ok(true)
+    }
+
+    /// Stop the instance and forget its binary, so it runs as a container again
+    pub fn remove(&self, instance_name: &str) -> Result<()> {
+        self.stop_instance(instance_name)?;
+        let native_dir = self.native_dir(instance_name);
+        if native_dir.exists() {
+            fs::remove_dir_all(&native_dir)?;
+        }
+        Ok(())
+    }
+}
+
+/// The environment of a native instance, from the one its container gets: the data lives in
+/// `data_dir` and a leader is reached on localhost rather than through the container's host
+/// gateway
+pub fn native_environment(env_vars: &[String], data_dir: &Path) -> Vec<(String, String)> {
+    env_vars
+        .iter()
+        .filter_map(|var| var.split_once('='))
+        .map(|(key, value)| {
+            let value = match key {
+                "HELIX_DATA_DIR" => data_dir.display().to_string(),
+                "HELIX_STANDBY_OF" => value.replace("host.docker.internal", "localhost"),
+                _ => value.to_string(),
+            };
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+/// The last `lines` lines of a log file
+pub fn log_tail(path: &Path, lines: usize) -> String {
+    let log = fs::read_to_string(path).unwrap_or_default();
+    let all = log.lines().collect::<Vec<_>>();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Keep the process running once the CLI exits, and out of reach of the terminal's Ctrl-C
+fn detach(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Whether `pid` is a running helix-container, so a pid reused by another program after a
+/// crash isn't mistaken for the instance
+pub fn process_running(pid: u32) -> bool {
+    if cfg!(windows) {
+        return Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+            .output()
+            .is_ok_and(|output| {
+                output.status.success()
+                    && String::from_utf8_lossy(&output.stdout).contains(PROCESS_NAME)
+            });
+    }
+    Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "stat=", "-o", "comm="])
+        .output()
+        .is_ok_and(|output| {
+            let process = String::from_utf8_lossy(&output.stdout);
+            // Exited but not yet reaped by its parent
+            let zombie = process.trim_start().starts_with('Z');
+            output.status.success() && !zombie && process.contains(PROCESS_NAME)
+        })
+}
+
+/// Ask the process to shut down, or kill it when `force` is set
+fn terminate(pid: u32, force: bool) -> Result<()> {
+    let pid = pid.to_string();
+    let output = if cfg!(windows) {
+        // Detached console processes can't be asked to close, only killed
+        Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .output()
+    } else {
+        let signal = if force { "-KILL" } else { "-TERM" };
+        Command::new("kill").args([signal, &pid]).output()
+    }
+    .map_err(|e| eyre!("Failed to stop process {pid}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("Failed to stop process {pid}:\n{stderr}"));
+    }
+    Ok(())
+}
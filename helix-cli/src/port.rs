@@ -1,5 +1,10 @@
+use crate::errors::CliError;
+use crate::registry_file;
+use crate::utils::print_warning;
 use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
 use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 
 pub const DEFAULT_PORT: u16 = 6969;
 const MAX_PORT_ATTEMPTS: u16 = 100;
@@ -33,3 +38,203 @@ pub fn ensure_port_available(requested_port: u16) -> Result<(u16, bool)> {
     let available = find_available_port(requested_port + 1)?;
     Ok((available, true))
 }
+
+/// A port held by a local instance, as recorded in the registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortReservation {
+    pub port: u16,
+    /// Root of the project the instance belongs to
+    pub project: PathBuf,
+    pub instance: String,
+}
+
+impl PortReservation {
+    fn is_for(&self, project: &Path, instance: &str) -> bool {
+        self.project == project && self.instance == instance
+    }
+}
+
+/// Directory of the machine-wide registries: `HELIX_HOME` when set, `~/.helix` otherwise
+pub fn helix_home() -> Result<PathBuf> {
+    match std::env::var("HELIX_HOME") {
+        Ok(home) => Ok(PathBuf::from(home)),
+        Err(_) => Ok(dirs::home_dir()
+            .ok_or_else(|| eyre!("Cannot find home directory"))?
+            .join(".helix")),
+    }
+}
+
+/// Ports reserved by local instances across all projects on this machine, kept in
+/// `~/.helix/ports.toml` so a new instance doesn't pick a port another project already uses
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PortRegistry {
+    #[serde(default, rename = "reservation")]
+    reservations: Vec<PortReservation>,
+}
+
+impl PortRegistry {
+    /// Path of the registry, under `HELIX_HOME` when set
+    pub fn path() -> Result<PathBuf> {
+        Ok(helix_home()?.join("ports.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    /// Load the registry at `path`, dropping reservations of projects that no longer exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let mut registry: PortRegistry = registry_file::load(path)?;
+        registry
+            .reservations
+            .retain(|r| r.project.join("helix.toml").exists());
+        Ok(registry)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        registry_file::save(self, path)
+    }
+
+    /// Load the registry, apply `f` and save it, with no other command updating it meanwhile
+    pub fn update<R>(f: impl FnOnce(&mut Self) -> R) -> Result<R> {
+        Self::update_at(&Self::path()?, f)
+    }
+
+    pub fn update_at<R>(path: &Path, f: impl FnOnce(&mut Self) -> R) -> Result<R> {
+        registry_file::locked(path, || {
+            let mut registry = Self::load_from(path)?;
+            let result = f(&mut registry);
+            registry.save_to(path)?;
+            Ok(result)
+        })
+    }
+
+    /// The reservation of `port` by an instance other than `instance` of `project`
+    pub fn held_by_other(
+        &self,
+        port: u16,
+        project: &Path,
+        instance: &str,
+    ) -> Option<&PortReservation> {
+        self.reservations
+            .iter()
+            .find(|r| r.port == port && !r.is_for(project, instance))
+    }
+
+    /// Record `port` as the instance's port, replacing any port it held before
+    pub fn reserve(&mut self, port: u16, project: &Path, instance: &str) {
+        self.release(project, instance);
+        self.reservations.push(PortReservation {
+            port,
+            project: project.to_path_buf(),
+            instance: instance.to_string(),
+        });
+        self.reservations.sort_by_key(|r| r.port);
+    }
+
+    pub fn release(&mut self, project: &Path, instance: &str) {
+        self.reservations.retain(|r| !r.is_for(project, instance));
+    }
+
+    /// The first port from `starting_port` that no other instance has reserved and nothing
+    /// is listening on
+    pub fn find_free_port(
+        &self,
+        starting_port: u16,
+        project: &Path,
+        instance: &str,
+    ) -> Result<u16> {
+        for offset in 0..MAX_PORT_ATTEMPTS {
+            let port = starting_port.saturating_add(offset);
+            if self.held_by_other(port, project, instance).is_none() && is_port_available(port) {
+                return Ok(port);
+            }
+        }
+        Err(eyre!(
+            "Could not find a free, unreserved port in range {}-{}",
+            starting_port,
+            starting_port + MAX_PORT_ATTEMPTS - 1
+        ))
+    }
+}
+
+/// Registry entries name projects by their canonical root
+pub(crate) fn project_key(project: &Path) -> PathBuf {
+    project
+        .canonicalize()
+        .unwrap_or_else(|_| project.to_path_buf())
+}
+
+/// Pick a free port for a new local instance and reserve it in the registry.
+///
+/// The registry is a convenience: when it can't be read or written the port is still
+/// chosen by probing, with a warning.
+pub fn assign_port(project: &Path, instance: &str) -> Result<u16> {
+    let project = &project_key(project);
+    let assigned = PortRegistry::update(|registry| {
+        let port = registry.find_free_port(DEFAULT_PORT, project, instance)?;
+        registry.reserve(port, project, instance);
+        Ok(port)
+    });
+    match assigned {
+        Ok(port) => port,
+        Err(e) => {
+            print_warning(&format!("Ignoring port registry: {e}"));
+            PortRegistry::default().find_free_port(DEFAULT_PORT, project, instance)
+        }
+    }
+}
+
+/// Record the port a local instance runs on, warning instead of failing
+pub fn record_port(port: u16, project: &Path, instance: &str) {
+    let project = &project_key(project);
+    let result = PortRegistry::update(|registry| registry.reserve(port, project, instance));
+    if let Err(e) = result {
+        print_warning(&format!("Could not save port registry: {e}"));
+    }
+}
+
+/// Drop a deleted instance's reservation, warning instead of failing
+pub fn release_port(project: &Path, instance: &str) {
+    let project = &project_key(project);
+    let result = PortRegistry::update(|registry| registry.release(project, instance));
+    if let Err(e) = result {
+        print_warning(&format!("Could not save port registry: {e}"));
+    }
+}
+
+/// Check a local instance's port before its container starts, so a collision is reported
+/// by name instead of as Docker's bind error. Only a port something is listening on fails;
+/// one merely reserved by another instance gets a warning.
+pub fn check_instance_port(port: u16, project: &Path, instance: &str) -> Result<()> {
+    let project = &project_key(project);
+    let registry = PortRegistry::load().unwrap_or_default();
+    let owner = registry.held_by_other(port, project, instance);
+    let owner_desc = owner.map(|r| {
+        format!(
+            "port {port} is reserved by instance '{}' of the project at {}",
+            r.instance,
+            r.project.display()
+        )
+    });
+
+    if !is_port_available(port) {
+        let mut error = CliError::new(format!(
+            "port {port} for instance '{instance}' is already in use"
+        ))
+        .with_hint(format!(
+            "stop whatever is using port {port}, or change `port` for '{instance}' in helix.toml and run 'helix build {instance}'"
+        ));
+        if let Some(owner_desc) = owner_desc {
+            error = error.with_context(owner_desc);
+        }
+        return Err(eyre!("{}", error.render()));
+    }
+
+    if let Some(owner_desc) = owner_desc {
+        print_warning(&format!(
+            "{owner_desc}, the two instances can't run at the same time"
+        ));
+    }
+    Ok(())
+}
@@ -63,6 +63,27 @@ impl ProjectContext {
             .join("helix-container")
     }
 
+    /// Get the file recording when an instance was last deployed
+    pub fn deployed_at_path(&self, instance_name: &str) -> PathBuf {
+        self.instance_workspace(instance_name).join("deployed_at")
+    }
+
+    /// Record that an instance was just deployed
+    pub fn record_deploy(&self, instance_name: &str) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        std::fs::write(self.deployed_at_path(instance_name), now.to_string())?;
+        Ok(())
+    }
+
+    /// When an instance was last deployed, in milliseconds since the epoch
+    pub fn last_deploy_ms(&self, instance_name: &str) -> Option<i64> {
+        std::fs::read_to_string(self.deployed_at_path(instance_name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
     /// Ensure all necessary directories exist for an instance
     pub fn ensure_instance_dirs(&self, instance_name: &str) -> Result<()> {
         let workspace = self.instance_workspace(instance_name);
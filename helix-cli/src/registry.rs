@@ -0,0 +1,153 @@
+//! Every local instance started on this machine, across projects, kept in
+//! `~/.helix/instances.toml`.
+//!
+//! An entry is written each time an instance starts and outlives its project directory, so
+//! `helix ps --all-projects` can still point at a container or process left running from a
+//! directory that was moved or deleted. Entries are dropped once their instance is deleted,
+//! or once it has stopped and its project is gone.
+
+use crate::config::ContainerRuntime;
+use crate::port::{self, project_key};
+use crate::project::ProjectContext;
+use crate::registry_file;
+use crate::utils::print_warning;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A local instance as it was last started
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartedInstance {
+    /// Root of the project the instance belongs to
+    pub project: PathBuf,
+    pub project_name: String,
+    pub instance: String,
+    pub port: u16,
+    /// Where the instance keeps its data
+    pub data_dir: PathBuf,
+    /// Runtime of the instance's container, unset when it runs natively
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<ContainerRuntime>,
+    /// Name of the instance's container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// Process of a native instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// When it was started, in milliseconds since the epoch
+    pub started_at: i64,
+}
+
+impl StartedInstance {
+    fn is_for(&self, project: &Path, instance: &str) -> bool {
+        self.project == project && self.instance == instance
+    }
+
+    /// Whether the project directory the instance was started from still exists
+    pub fn project_exists(&self) -> bool {
+        self.project.join("helix.toml").exists()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InstanceRegistry {
+    #[serde(default, rename = "instance")]
+    instances: Vec<StartedInstance>,
+}
+
+impl InstanceRegistry {
+    /// Path of the registry, under `HELIX_HOME` when set
+    pub fn path() -> Result<PathBuf> {
+        Ok(port::helix_home()?.join("instances.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        registry_file::load(path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        registry_file::save(self, path)
+    }
+
+    /// Load the registry, apply `f` and save it, with no other command updating it meanwhile
+    pub fn update<R>(f: impl FnOnce(&mut Self) -> R) -> Result<R> {
+        Self::update_at(&Self::path()?, f)
+    }
+
+    pub fn update_at<R>(path: &Path, f: impl FnOnce(&mut Self) -> R) -> Result<R> {
+        registry_file::locked(path, || {
+            let mut registry = Self::load_from(path)?;
+            let result = f(&mut registry);
+            registry.save_to(path)?;
+            Ok(result)
+        })
+    }
+
+    pub fn instances(&self) -> &[StartedInstance] {
+        &self.instances
+    }
+
+    /// Record a start, replacing what was recorded for the instance before
+    pub fn record(&mut self, started: StartedInstance) {
+        self.forget(&started.project, &started.instance);
+        self.instances.push(started);
+        self.instances
+            .sort_by(|a, b| (&a.project, &a.instance).cmp(&(&b.project, &b.instance)));
+    }
+
+    pub fn forget(&mut self, project: &Path, instance: &str) {
+        self.instances.retain(|i| !i.is_for(project, instance));
+    }
+
+    /// Drop the entries `keep` rejects, answering whether any were
+    pub fn retain(&mut self, keep: impl FnMut(&StartedInstance) -> bool) -> bool {
+        let before = self.instances.len();
+        self.instances.retain(keep);
+        self.instances.len() != before
+    }
+}
+
+/// Record that a local instance just started, in `container` of the given runtime or,
+/// without one, as the native process `pid`. Warns instead of failing.
+pub fn record_start(
+    project: &ProjectContext,
+    instance: &str,
+    container: Option<(ContainerRuntime, String)>,
+    pid: Option<u32>,
+) {
+    let port = project
+        .config
+        .get_instance(instance)
+        .ok()
+        .and_then(|config| config.port())
+        .unwrap_or(port::DEFAULT_PORT);
+    let (runtime, container) = container.unzip();
+    let started = StartedInstance {
+        project: project_key(&project.root),
+        project_name: project.config.project.name.clone(),
+        instance: instance.to_string(),
+        port,
+        data_dir: project_key(&project.instance_volume(instance)),
+        runtime,
+        container,
+        pid,
+        started_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let result = InstanceRegistry::update(|registry| registry.record(started));
+    if let Err(e) = result {
+        print_warning(&format!("Could not save instance registry: {e}"));
+    }
+}
+
+/// Drop a deleted instance from the registry, warning instead of failing
+pub fn forget_instance(project: &Path, instance: &str) {
+    let project = &project_key(project);
+    let result = InstanceRegistry::update(|registry| registry.forget(project, instance));
+    if let Err(e) = result {
+        print_warning(&format!("Could not save instance registry: {e}"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Reading and writing the machine-wide registries under `~/.helix`, which every project's
+//! `helix` commands share.
+//!
+//! Writes go through a temporary file renamed over the registry, so readers never see half
+//! of one. Updates hold a lock on a `.lock` file next to the registry from loading it to
+//! saving it, so two commands updating it at once don't drop each other's changes.
+
+use eyre::{Result, eyre};
+use serde::{Serialize, de::DeserializeOwned};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Load the registry at `path`, or an empty one if there is none yet
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            toml::from_str(&content).map_err(|e| eyre!("Failed to parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(eyre!("Failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Replace the registry at `path` with `registry`
+pub fn save<T: Serialize>(registry: &T, path: &Path) -> Result<()> {
+    let dir = parent_dir(path);
+    fs::create_dir_all(dir)?;
+    let content = toml::to_string_pretty(registry)
+        .map_err(|e| eyre!("Failed to serialize {}: {e}", path.display()))?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    tmp.write_all(content.as_bytes())?;
+    tmp.persist(path)
+        .map_err(|e| eyre!("Failed to write {}: {}", path.display(), e.error))?;
+    Ok(())
+}
+
+/// Run `f` while holding the lock of the registry at `path`, waiting for other commands
+/// holding it. The lock is released when `f` returns or the process exits.
+pub fn locked<R>(path: &Path, f: impl FnOnce() -> Result<R>) -> Result<R> {
+    let dir = parent_dir(path);
+    fs::create_dir_all(dir)?;
+    let lock_path = lock_path(path);
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| eyre!("Failed to open {}: {e}", lock_path.display()))?;
+    lock.lock()
+        .map_err(|e| eyre!("Failed to lock {}: {e}", lock_path.display()))?;
+    f()
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
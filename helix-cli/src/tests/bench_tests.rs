@@ -0,0 +1,137 @@
+use crate::commands::bench::{
+    BenchOp, BenchOptions, KeyDistribution, OpKind, Workload, bench, fill_placeholders,
+    parse_duration,
+};
+use axum::Router;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::post;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+use std::time::Duration;
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+    assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+    assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+    assert_eq!(parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+    assert_eq!(parse_duration("10").unwrap(), Duration::from_secs(10));
+    assert!(parse_duration("0s").is_err());
+    assert!(parse_duration("soon").is_err());
+    assert!(parse_duration("10d").is_err());
+}
+
+#[test]
+fn test_fill_placeholders() {
+    let template = serde_json::json!({
+        "id": "{key}",
+        "email": "user-{key}@example.com",
+        "embedding": "{vector:3}",
+        "new_id": "{uuid}",
+        "k": 10,
+        "tags": ["{key}"]
+    });
+    let mut rng = SmallRng::seed_from_u64(7);
+
+    let body = fill_placeholders(&template, &serde_json::json!(42), &mut rng);
+
+    assert_eq!(body["id"], 42);
+    assert_eq!(body["email"], "user-42@example.com");
+    let embedding = body["embedding"].as_array().expect("a vector");
+    assert_eq!(embedding.len(), 3);
+    assert!(
+        embedding
+            .iter()
+            .all(|x| (-1.0..1.0).contains(&x.as_f64().unwrap()))
+    );
+    assert!(uuid::Uuid::parse_str(body["new_id"].as_str().unwrap()).is_ok());
+    assert_eq!(body["k"], 10);
+    assert_eq!(body["tags"], serde_json::json!([42]));
+}
+
+#[test]
+fn test_workload_file_parses() {
+    let workload: Workload = toml::from_str(
+        r#"
+duration = "10s"
+concurrency = 4
+
+[[op]]
+query = "getUser"
+weight = 8
+params = { id = "{key}" }
+keys = 100
+distribution = "zipf"
+
+[[op]]
+query = "searchDocs"
+kind = "search"
+params = { vector = "{vector:384}", k = 10 }
+"#,
+    )
+    .expect("workload should parse");
+
+    assert_eq!(workload.duration.as_deref(), Some("10s"));
+    assert_eq!(workload.concurrency, Some(4));
+    assert_eq!(workload.ops.len(), 2);
+    assert_eq!(workload.ops[0].weight, 8);
+    assert_eq!(workload.ops[0].distribution, KeyDistribution::Zipf);
+    assert_eq!(workload.ops[1].kind, OpKind::Search);
+    assert_eq!(workload.ops[1].weight, 1);
+    assert_eq!(workload.ops[1].params["k"], 10);
+
+    let unknown = toml::from_str::<Workload>("[[op]]\nquery = \"getUser\"\nweigth = 2\n");
+    assert!(unknown.is_err(), "misspelled fields should be rejected");
+}
+
+fn op(query: &str, weight: u32) -> BenchOp {
+    BenchOp {
+        query: query.to_string(),
+        kind: OpKind::Read,
+        weight,
+        params: serde_json::json!({ "id": "{key}" }),
+        keys: Some(10),
+        keys_file: None,
+        distribution: KeyDistribution::Sequential,
+        zipf_exponent: None,
+    }
+}
+
+#[tokio::test]
+async fn test_bench_reports_each_op() {
+    let app = Router::new().route(
+        "/{query}",
+        post(|Path(query): Path<String>| async move {
+            match query.as_str() {
+                "missing" => StatusCode::NOT_FOUND,
+                _ => StatusCode::OK,
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let options = BenchOptions {
+        duration: Duration::from_millis(300),
+        concurrency: 2,
+        api_key: None,
+    };
+    let report = bench(&[op("getUser", 3), op("missing", 1)], &url, &options)
+        .await
+        .expect("bench should run");
+
+    assert_eq!(report.ops.len(), 2);
+    let (get_user, missing) = (&report.ops[0], &report.ops[1]);
+    assert!(get_user.requests > 0);
+    assert_eq!(get_user.errors, 0);
+    assert!(get_user.p50_ms <= get_user.p99_ms && get_user.p99_ms <= get_user.max_ms);
+    assert_eq!(missing.errors, missing.requests);
+    assert_eq!(report.requests, get_user.requests + missing.requests);
+    assert!(report.first_error.unwrap().starts_with("missing"));
+    assert!(report.duration_secs >= 0.3);
+
+    let none = bench(&[op("getUser", 0)], &url, &options).await;
+    assert!(none.is_err(), "ops without weight can't be picked");
+}
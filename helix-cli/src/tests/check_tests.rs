@@ -1,7 +1,10 @@
 use crate::commands::check::run;
 use crate::config::{DbConfig, HelixConfig, LocalInstanceConfig};
+use crate::diagnostics::DiagnosticsFormat;
 use crate::metrics_sender::MetricsSender;
 use crate::tests::test_utils::TestContext;
+use crate::utils::helixc_utils::{collect_hx_files, generate_content, parse_content};
+use helix_db::helixc::analyzer::analyze;
 use std::fs;
 use std::path::PathBuf;
 
@@ -21,7 +24,7 @@ async fn test_check_all_instances_success() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should succeed with valid project: {:?}",
@@ -40,7 +43,7 @@ async fn test_check_specific_instance_success() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(Some("dev".to_string()), &metrics_sender).await;
+    let result = run(Some("dev".to_string()), None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should succeed for valid instance: {:?}",
@@ -56,7 +59,7 @@ async fn test_check_nonexistent_instance_fails() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(Some("nonexistent".to_string()), &metrics_sender).await;
+    let result = run(Some("nonexistent".to_string()), None, &metrics_sender).await;
     assert!(
         result.is_err(),
         "Check should fail for nonexistent instance"
@@ -76,7 +79,7 @@ async fn test_check_fails_without_schema() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(result.is_err(), "Check should fail without schema");
     let error_msg = format!("{:?}", result.err().unwrap());
     assert!(
@@ -93,7 +96,7 @@ async fn test_check_fails_with_invalid_syntax() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(result.is_err(), "Check should fail with invalid syntax");
 }
 
@@ -105,7 +108,7 @@ async fn test_check_fails_without_helix_toml() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_err(),
         "Check should fail without helix.toml in project"
@@ -131,6 +134,8 @@ async fn test_check_with_multiple_instances() {
         LocalInstanceConfig {
             port: Some(6970),
             build_mode: crate::config::BuildMode::Dev,
+            resources: None,
+            standby_of: None,
             db_config: DbConfig::default(),
         },
     );
@@ -139,6 +144,8 @@ async fn test_check_with_multiple_instances() {
         LocalInstanceConfig {
             port: Some(6971),
             build_mode: crate::config::BuildMode::Dev,
+            resources: None,
+            standby_of: None,
             db_config: DbConfig::default(),
         },
     );
@@ -169,7 +176,7 @@ E::Follows {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should succeed with multiple instances: {:?}",
@@ -189,7 +196,7 @@ async fn test_check_validates_each_instance_individually() {
     let metrics_sender = create_test_metrics_sender();
 
     // Check the specific instance
-    let result = run(Some("dev".to_string()), &metrics_sender).await;
+    let result = run(Some("dev".to_string()), None, &metrics_sender).await;
     assert!(result.is_ok(), "Check should validate dev instance");
 }
 
@@ -214,7 +221,7 @@ async fn test_check_with_empty_queries_directory() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_err(),
         "Check should fail with empty queries directory"
@@ -232,7 +239,7 @@ async fn test_check_with_schema_only() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should succeed with schema only (queries are optional): {:?}",
@@ -294,7 +301,7 @@ QUERY GetUser(id: ID) =>
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should succeed with multiple .hx files: {:?}",
@@ -334,10 +341,73 @@ N::User {
     let _guard = std::env::set_current_dir(&ctx.project_path);
     let metrics_sender = create_test_metrics_sender();
 
-    let result = run(None, &metrics_sender).await;
+    let result = run(None, None, &metrics_sender).await;
     assert!(
         result.is_ok(),
         "Check should work with custom queries path: {:?}",
         result.err()
     );
 }
+
+/// Analyze the project's queries, rendering their diagnostics in `format`
+fn render_diagnostics(ctx: &TestContext, format: DiagnosticsFormat) -> serde_json::Value {
+    let hx_files =
+        collect_hx_files(&ctx.project_path, &PathBuf::from("db")).expect("Failed to collect");
+    let content = generate_content(&hx_files).expect("Failed to read .hx files");
+    let source = parse_content(&content).expect("Failed to parse");
+    let (diagnostics, _) = analyze(&source).expect("Failed to analyze");
+    let base = ctx.project_path.canonicalize().unwrap();
+    format.render(&diagnostics, &content.files, &base)
+}
+
+fn setup_project_with_unknown_node(ctx: &TestContext) {
+    ctx.setup_valid_project();
+    let queries = "QUERY GetMissing(id: ID) =>\n    user <- N<Usr>(id)\n    RETURN user\n";
+    fs::write(ctx.project_path.join("db/queries.hx"), queries).expect("Failed to write queries");
+}
+
+#[test]
+fn test_check_diagnostics_as_json() {
+    let ctx = TestContext::new();
+    setup_project_with_unknown_node(&ctx);
+
+    let document = render_diagnostics(&ctx, DiagnosticsFormat::Json);
+
+    assert_eq!(document["version"], 1);
+    let diagnostic = &document["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "E101");
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["file"], "db/queries.hx");
+    assert_eq!(diagnostic["span"]["start"]["line"], 2);
+    assert_eq!(diagnostic["span"]["start"]["column"], 13);
+    assert!(diagnostic["message"].as_str().unwrap().contains("Usr"));
+    assert!(diagnostic["suggestion"].is_string());
+}
+
+#[test]
+fn test_check_diagnostics_as_sarif() {
+    let ctx = TestContext::new();
+    setup_project_with_unknown_node(&ctx);
+
+    let document = render_diagnostics(&ctx, DiagnosticsFormat::Sarif);
+
+    assert_eq!(document["version"], "2.1.0");
+    let run = &document["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E101");
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E101");
+    assert_eq!(result["level"], "error");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "db/queries.hx");
+    assert_eq!(location["region"]["startLine"], 2);
+}
+
+#[test]
+fn test_check_diagnostics_empty_for_valid_project() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+
+    let document = render_diagnostics(&ctx, DiagnosticsFormat::Json);
+
+    assert_eq!(document["diagnostics"], serde_json::json!([]));
+}
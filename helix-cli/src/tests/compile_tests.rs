@@ -1,6 +1,8 @@
 use crate::commands::compile::run;
 use crate::config::HelixConfig;
 use crate::tests::test_utils::TestContext;
+use helix_db::helixc::generator::{CodegenProfile, docs::DocsFormat};
+use helix_db::helixc::parser::schema_diagram::DiagramFormat;
 use std::fs;
 use std::path::PathBuf;
 
@@ -10,7 +12,13 @@ async fn test_compile_success() {
     ctx.setup_valid_project();
 
     // Use explicit path instead of changing current directory
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Compile should succeed with valid project: {:?}",
@@ -36,6 +44,8 @@ async fn test_compile_with_custom_output_path() {
     let result = run(
         Some(output_dir.to_str().unwrap().to_string()),
         Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
     )
     .await;
     assert!(
@@ -52,12 +62,43 @@ async fn test_compile_with_custom_output_path() {
     );
 }
 
+#[tokio::test]
+async fn test_compile_writes_rust_client() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+
+    let client_file = ctx.project_path.join("client.rs");
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        Some(client_file.to_str().unwrap().to_string()),
+        None,
+    )
+    .await;
+    assert!(
+        result.is_ok(),
+        "Compile should succeed with a client path: {:?}",
+        result.err()
+    );
+
+    let client = fs::read_to_string(&client_file).expect("Rust client should be written");
+    assert!(client.contains("use helix_client::{ClientError, HelixClient};"));
+    assert!(client.contains("pub struct Queries {"));
+    assert!(ctx.project_path.join("queries.rs").exists());
+}
+
 #[tokio::test]
 async fn test_compile_with_explicit_project_path() {
     let ctx = TestContext::new();
     ctx.setup_valid_project();
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Compile should succeed with explicit project path: {:?}",
@@ -74,7 +115,13 @@ async fn test_compile_fails_without_schema() {
     let ctx = TestContext::new();
     ctx.setup_project_without_schema();
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(result.is_err(), "Compile should fail without schema");
     let error_msg = format!("{:?}", result.err().unwrap());
     assert!(
@@ -88,7 +135,13 @@ async fn test_compile_fails_with_invalid_syntax() {
     let ctx = TestContext::new();
     ctx.setup_project_with_invalid_syntax();
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(result.is_err(), "Compile should fail with invalid syntax");
 }
 
@@ -97,7 +150,13 @@ async fn test_compile_fails_without_helix_toml() {
     let ctx = TestContext::new();
     // Don't set up any project
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_err(),
         "Compile should fail without helix.toml in project"
@@ -114,7 +173,13 @@ async fn test_compile_with_schema_only() {
     let ctx = TestContext::new();
     ctx.setup_schema_only_project();
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Compile should succeed with schema only (queries are optional): {:?}",
@@ -178,7 +243,13 @@ QUERY GetUser(id: ID) =>
 "#;
     fs::write(queries_dir.join("3_queries.hx"), queries).expect("Failed to write 3_queries.hx");
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Compile should succeed with multiple .hx files: {:?}",
@@ -216,7 +287,13 @@ N::User {
 "#;
     fs::write(queries_dir.join("schema.hx"), schema_content).expect("Failed to write schema.hx");
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Compile should work with custom queries path: {:?}",
@@ -233,7 +310,13 @@ async fn test_compile_creates_all_required_files() {
     let ctx = TestContext::new();
     ctx.setup_valid_project();
 
-    let result = run(None, Some(ctx.project_path.to_str().unwrap().to_string())).await;
+    let result = run(
+        None,
+        Some(ctx.project_path.to_str().unwrap().to_string()),
+        None,
+        None,
+    )
+    .await;
     assert!(result.is_ok(), "Compile should succeed");
 
     // Check for common generated files
@@ -253,3 +336,151 @@ async fn test_compile_creates_all_required_files() {
         "Generated queries.rs should contain Rust code"
     );
 }
+
+#[tokio::test]
+async fn test_compile_writes_module_per_query() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let project_path = ctx.project_path.to_str().unwrap().to_string();
+    let modules_dir = ctx.project_path.join("queries");
+    // Left over from a query that no longer exists, next to a file that isn't generated
+    fs::create_dir_all(&modules_dir).expect("Failed to create queries directory");
+    fs::write(
+        modules_dir.join("removed_query.rs"),
+        "// Generated from the project's queries, do not edit\n",
+    )
+    .expect("Failed to write stale module");
+    fs::write(modules_dir.join("notes.rs"), "// mine\n").expect("Failed to write user file");
+
+    let result = run(None, Some(project_path.clone()), None, None).await;
+    assert!(result.is_ok(), "Compile should succeed: {:?}", result.err());
+
+    let aggregator =
+        fs::read_to_string(ctx.project_path.join("queries.rs")).expect("Failed to read queries.rs");
+    assert!(aggregator.contains("mod get_user;"));
+    assert!(aggregator.contains("mod get_user_posts;"));
+    let module =
+        fs::read_to_string(modules_dir.join("get_user.rs")).expect("Failed to read module");
+    assert!(module.contains("use super::*;"));
+    assert!(!modules_dir.join("removed_query.rs").exists());
+    assert!(modules_dir.join("notes.rs").exists());
+
+    // Unchanged queries aren't rewritten, so cargo doesn't rebuild them
+    let modified = |path: PathBuf| fs::metadata(path).unwrap().modified().unwrap();
+    let before = modified(modules_dir.join("get_user.rs"));
+    let result = run(None, Some(project_path), None, None).await;
+    assert!(result.is_ok(), "Compile should succeed: {:?}", result.err());
+    assert_eq!(modified(modules_dir.join("get_user.rs")), before);
+}
+
+#[tokio::test]
+async fn test_compile_codegen_profile_from_config_and_flag() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+
+    let config_path = ctx.project_path.join("helix.toml");
+    let mut config = HelixConfig::from_file(&config_path).expect("Failed to load config");
+    config.project.codegen_profile = Some(CodegenProfile::FastCompile);
+    config
+        .save_to_file(&config_path)
+        .expect("Failed to save config");
+    let project_path = ctx.project_path.to_str().unwrap().to_string();
+    let queries_file = ctx.project_path.join("queries/get_user_posts.rs");
+
+    let result = run(None, Some(project_path.clone()), None, None).await;
+    assert!(result.is_ok(), "Compile should succeed: {:?}", result.err());
+    let fast = fs::read_to_string(&queries_file).expect("Failed to read query module");
+    assert!(fast.contains(".out_node(\"Authored\")\n.boxed()"));
+
+    // The flag wins over helix.toml
+    let result = run(
+        None,
+        Some(project_path),
+        None,
+        Some(CodegenProfile::MaxPerf),
+    )
+    .await;
+    assert!(result.is_ok(), "Compile should succeed: {:?}", result.err());
+    let max_perf = fs::read_to_string(&queries_file).expect("Failed to read query module");
+    assert!(!max_perf.contains(".boxed()"));
+}
+
+#[tokio::test]
+async fn test_generate_json_schema_writes_documents_per_query() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+
+    let result = crate::commands::generate::run(crate::GenerateAction::JsonSchema {
+        path: Some(ctx.project_path.to_str().unwrap().to_string()),
+        output: None,
+    })
+    .await;
+    assert!(
+        result.is_ok(),
+        "JSON Schema generation should succeed: {:?}",
+        result.err()
+    );
+
+    let schemas = ctx.project_path.join("schemas");
+    let input: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(schemas.join("GetUser.input.json")).expect("input schema"),
+    )
+    .unwrap();
+    assert_eq!(input["title"], "GetUserInput");
+    assert_eq!(input["required"], serde_json::json!(["user_id"]));
+    assert_eq!(input["properties"]["user_id"]["format"], "uuid");
+    assert!(schemas.join("GetUser.output.json").exists());
+    assert!(schemas.join("GetUserPosts.input.json").exists());
+    assert!(schemas.join("GetUserPosts.output.json").exists());
+}
+
+#[tokio::test]
+async fn test_generate_docs_writes_page_per_query() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let output = ctx.project_path.join("api-docs");
+
+    let result = crate::commands::generate::run(crate::GenerateAction::Docs {
+        path: Some(ctx.project_path.to_str().unwrap().to_string()),
+        output: Some(output.clone()),
+        format: DocsFormat::Html,
+        url: "https://helix.internal".to_string(),
+    })
+    .await;
+    assert!(
+        result.is_ok(),
+        "Docs generation should succeed: {:?}",
+        result.err()
+    );
+
+    let index = fs::read_to_string(output.join("index.html")).expect("index page");
+    assert!(index.contains("<a href=\"GetUser.html\">GetUser</a>"));
+    let page = fs::read_to_string(output.join("GetUser.html")).expect("query page");
+    assert!(page.contains("<code>POST https://helix.internal/GetUser</code>"));
+    assert!(page.contains("<td><code>user_id</code></td>"));
+    assert!(output.join("GetUserPosts.html").exists());
+}
+
+#[tokio::test]
+async fn test_generate_schema_diagram_writes_file() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let output = ctx.project_path.join("schema.dot");
+
+    let result = crate::commands::generate::run(crate::GenerateAction::SchemaDiagram {
+        path: Some(ctx.project_path.to_str().unwrap().to_string()),
+        output: Some(output.clone()),
+        format: DiagramFormat::Dot,
+    })
+    .await;
+    assert!(
+        result.is_ok(),
+        "Schema diagram generation should succeed: {:?}",
+        result.err()
+    );
+
+    let diagram = fs::read_to_string(&output).expect("diagram");
+    assert!(diagram.starts_with("digraph schema {"));
+    assert!(diagram.contains("\"User\" [label=\"{User|name: String\\lemail: String\\l}\"];"));
+    assert!(diagram.contains("\"User\" -> \"Post\" [label=\"Authored\"];"));
+}
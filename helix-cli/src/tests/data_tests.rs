@@ -0,0 +1,144 @@
+use crate::commands::data::{DataDiff, ItemKind, LabelDiff, diff};
+use crate::commands::import::{Format, import};
+use crate::project::ProjectContext;
+use crate::tests::test_utils::TestContext;
+use bumpalo::Bump;
+use helix_db::helix_engine::storage_core::{HelixGraphStorage, version_info::VersionInfo};
+use helix_db::helix_engine::traversal_core::config::Config;
+use helix_db::utils::items::Node;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DUMP: &str = r#"CREATE (:User {name: "Ada", email: "ada@example.com"});
+CREATE (:User {name: "Grace", email: "grace@example.com"});
+CREATE (:Post {title: "Notes", content: "On the engine"});
+MATCH (u:User {name: "Ada"}), (p:Post {title: "Notes"}) CREATE (u)-[:AUTHORED]->(p);
+MATCH (u:User {name: "Grace"}), (p:Post {title: "Notes"}) CREATE (u)-[:LIKES]->(p);
+"#;
+
+/// A project whose dev instance holds three nodes and two edges, with the path of its data
+fn imported_project(ctx: &TestContext) -> (ProjectContext, PathBuf) {
+    ctx.setup_valid_project();
+    let dump = ctx.project_path.join("dump.cypher");
+    fs::write(&dump, DUMP).expect("Failed to write dump");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+    import(&project, "dev", Format::Neo4j, &dump, None, false).expect("import should succeed");
+    let data = project.instance_volume("dev").join("user");
+    (project, data)
+}
+
+/// Copy an instance's data into a backup directory, as `helix backup` lays it out
+fn back_up(data: &Path, backup: &Path) {
+    fs::create_dir_all(backup).expect("Failed to create backup directory");
+    fs::copy(data.join("data.mdb"), backup.join("data.mdb")).expect("Failed to copy data");
+}
+
+fn open_storage(data: &Path) -> HelixGraphStorage {
+    HelixGraphStorage::new(
+        &data.display().to_string(),
+        Config::default(),
+        VersionInfo::default(),
+    )
+    .expect("Failed to open data")
+}
+
+fn counts(diff: &DataDiff, kind: ItemKind, label: &str) -> LabelDiff {
+    diff.labels
+        .get(&(kind, label.to_string()))
+        .copied()
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_diff_of_identical_snapshots_is_unchanged() {
+    let ctx = TestContext::new();
+    let (_project, data) = imported_project(&ctx);
+    let backup = ctx.project_path.join("backups").join("a");
+    back_up(&data, &backup);
+
+    let diff = diff(&backup, &data).expect("diff should succeed");
+
+    assert!(diff.is_unchanged());
+    assert_eq!(counts(&diff, ItemKind::Node, "User").unchanged, 2);
+    assert_eq!(counts(&diff, ItemKind::Node, "Post").unchanged, 1);
+    assert_eq!(counts(&diff, ItemKind::Edge, "Authored").unchanged, 1);
+    assert_eq!(counts(&diff, ItemKind::Edge, "Likes").unchanged, 1);
+}
+
+#[test]
+fn test_diff_reports_changed_and_removed_items() {
+    let ctx = TestContext::new();
+    let (_project, data) = imported_project(&ctx);
+    let backup = ctx.project_path.join("backups").join("a");
+    back_up(&data, &backup);
+
+    {
+        let storage = open_storage(&data);
+        let mut txn = storage.graph_env.write_txn().expect("write txn");
+        let arena = Bump::new();
+        let post = storage
+            .nodes_db
+            .iter(&txn)
+            .expect("node iterator")
+            .map(|entry| entry.expect("node entry"))
+            .map(|(id, bytes)| Node::from_bincode_bytes(id, bytes, &arena).expect("node"))
+            .find(|node| node.label == "Post")
+            .expect("a post should be stored");
+        let cleared = Node {
+            properties: None,
+            ..post
+        }
+        .to_bincode_bytes()
+        .expect("node bytes");
+        storage
+            .nodes_db
+            .put(&mut txn, &post.id, &cleared)
+            .expect("node should be updated");
+        let edge = storage
+            .edges_db
+            .first(&txn)
+            .expect("edge lookup")
+            .expect("an edge should be stored")
+            .0;
+        storage
+            .edges_db
+            .delete(&mut txn, &edge)
+            .expect("edge should be deleted");
+        txn.commit().expect("commit");
+    }
+
+    let forward = diff(&backup, &data).expect("diff should succeed");
+
+    assert!(!forward.is_unchanged());
+    assert_eq!(
+        counts(&forward, ItemKind::Node, "Post"),
+        LabelDiff {
+            changed: 1,
+            ..LabelDiff::default()
+        }
+    );
+    assert_eq!(counts(&forward, ItemKind::Node, "User").unchanged, 2);
+    let removed = ["Authored", "Likes"]
+        .iter()
+        .map(|label| counts(&forward, ItemKind::Edge, label).removed)
+        .sum::<u64>();
+    assert_eq!(removed, 1);
+
+    // Compared the other way round, the removed edge was added
+    let reverse = diff(&data, &backup).expect("diff should succeed");
+    let added = ["Authored", "Likes"]
+        .iter()
+        .map(|label| counts(&reverse, ItemKind::Edge, label).added)
+        .sum::<u64>();
+    assert_eq!(added, 1);
+}
+
+#[test]
+fn test_diff_without_data_fails() {
+    let ctx = TestContext::new();
+    let (_project, data) = imported_project(&ctx);
+    let empty = ctx.project_path.join("backups").join("empty");
+    fs::create_dir_all(&empty).expect("Failed to create directory");
+
+    assert!(diff(&empty, &data).is_err());
+}
@@ -80,3 +80,155 @@ fn test_host_data_dir_respects_env_var() {
     let default_dir = docker.data_dir("myinstance");
     assert_eq!(default_dir, "../.volumes/myinstance");
 }
+
+fn setup_project_with_resources(resources: &str) -> (TempDir, ProjectContext) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_path = temp_dir.path().to_path_buf();
+    let config = format!(
+        "[project]\nname = \"test-project\"\n\n[local.dev]\nport = 6969\n\n[local.dev.resources]\n{resources}"
+    );
+    fs::write(project_path.join("helix.toml"), config).expect("Failed to write config");
+    fs::create_dir_all(project_path.join(".helix")).expect("Failed to create .helix");
+
+    let context = ProjectContext::find_and_load(Some(&project_path)).unwrap();
+    (temp_dir, context)
+}
+
+/// Resource limits in helix.toml are applied to the container and its LMDB map size
+#[test]
+fn test_docker_compose_applies_resource_limits() {
+    let (_temp_dir, context) =
+        setup_project_with_resources("cpu_shares = 512\nmemory = \"2g\"\nmap_size_gb = 4\n");
+    let docker = DockerManager::new(&context);
+
+    let instance_config = context.config.get_instance("dev").unwrap();
+    let compose = docker
+        .generate_docker_compose("dev", instance_config, None)
+        .unwrap();
+
+    assert!(compose.contains("    cpu_shares: 512\n"));
+    assert!(compose.contains("    mem_limit: 2g\n"));
+    assert!(compose.contains("    memswap_limit: 2g\n"));
+    assert!(compose.contains("      - HELIX_MAP_SIZE_GB=4\n"));
+}
+
+/// A standby's container follows its leader through the host's published port
+#[test]
+fn test_docker_compose_points_standby_at_leader() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_path = temp_dir.path().to_path_buf();
+    let config = "[project]\nname = \"test-project\"\n\n[local.dev]\nport = 6969\n\n[local.dev-standby]\nport = 6970\nstandby_of = \"dev\"\n";
+    fs::write(project_path.join("helix.toml"), config).expect("Failed to write config");
+    fs::create_dir_all(project_path.join(".helix")).expect("Failed to create .helix");
+    let context = ProjectContext::find_and_load(Some(&project_path)).unwrap();
+    let docker = DockerManager::new(&context);
+
+    let standby = docker
+        .generate_docker_compose(
+            "dev-standby",
+            context.config.get_instance("dev-standby").unwrap(),
+            None,
+        )
+        .unwrap();
+    assert!(standby.contains("      - HELIX_STANDBY_OF=http://host.docker.internal:6969\n"));
+    assert!(standby.contains("host.docker.internal:host-gateway"));
+
+    let leader = docker
+        .generate_docker_compose("dev", context.config.get_instance("dev").unwrap(), None)
+        .unwrap();
+    assert!(!leader.contains("HELIX_STANDBY_OF"));
+    assert!(!leader.contains("extra_hosts"));
+}
+
+#[test]
+fn test_docker_compose_without_resource_limits() {
+    let (_temp_dir, context) = setup_test_project();
+    let docker = DockerManager::new(&context);
+
+    let instance_config = context.config.get_instance("dev").unwrap();
+    let compose = docker
+        .generate_docker_compose("dev", instance_config, None)
+        .unwrap();
+
+    assert!(!compose.contains("cpu_shares"));
+    assert!(!compose.contains("mem_limit"));
+    assert!(!compose.contains("HELIX_MAP_SIZE_GB"));
+}
+
+#[test]
+fn test_invalid_resource_limits_are_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let config_path = temp_dir.path().join("helix.toml");
+
+    for resources in [
+        "memory = \"lots\"",
+        "memory = \"0m\"",
+        "cpu_shares = 1",
+        "map_size_gb = 0",
+    ] {
+        let config = format!(
+            "[project]\nname = \"test-project\"\n\n[local.dev]\nport = 6969\n\n[local.dev.resources]\n{resources}\n"
+        );
+        fs::write(&config_path, config).expect("Failed to write config");
+
+        let error = HelixConfig::from_file(&config_path).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Invalid resources for instance 'dev'"),
+            "{resources}: {error}"
+        );
+    }
+}
+
+#[test]
+fn test_compose_path_normalizes_windows_paths() {
+    use crate::docker::compose_path;
+
+    assert_eq!(
+        compose_path(r"C:\Users\me\helix\data"),
+        "C:/Users/me/helix/data"
+    );
+    assert_eq!(compose_path(r"\\?\C:\Users\me\data"), "C:/Users/me/data");
+    assert_eq!(
+        compose_path(r"\\?\UNC\server\share\data"),
+        "//server/share/data"
+    );
+    assert_eq!(compose_path("/var/lib/helix"), "/var/lib/helix");
+    assert_eq!(compose_path("../.volumes/dev"), "../.volumes/dev");
+}
+
+#[test]
+fn test_windows_daemon_hint() {
+    use crate::docker::windows_daemon_hint;
+
+    let hint = windows_daemon_hint(Some("unix:///var/run/docker.sock"), true);
+    assert!(hint.contains("DOCKER_HOST"), "{hint}");
+    assert!(
+        windows_daemon_hint(Some("npipe:////./pipe/docker_engine"), false)
+            .contains("start Docker Desktop")
+    );
+    assert!(windows_daemon_hint(None, true).contains("starting"));
+    assert!(windows_daemon_hint(None, false).contains("//./pipe/docker_engine"));
+}
+
+#[test]
+fn test_windows_containers_are_rejected() {
+    use crate::docker::windows_containers_error;
+
+    assert!(windows_containers_error("windows\n").is_some());
+    assert!(windows_containers_error("linux\n").is_none());
+    assert!(windows_containers_error("").is_none());
+}
+
+#[test]
+fn test_dashboard_runs_as_a_service_on_windows() {
+    use crate::commands::dashboard::lifecycle_args;
+
+    assert_eq!(
+        lifecycle_args(false, "windows"),
+        ["-d", "--restart", "unless-stopped"]
+    );
+    assert_eq!(lifecycle_args(false, "linux"), ["--rm", "-d"]);
+    assert_eq!(lifecycle_args(true, "windows"), ["--rm"]);
+}
@@ -0,0 +1,343 @@
+use crate::commands::export::{Subgraph, Walks, export, fetch_subgraph, fetch_walks};
+use crate::commands::import::{Format, import};
+use crate::project::ProjectContext;
+use crate::tests::test_utils::TestContext;
+use arrow_array::RecordBatch;
+use arrow_array::cast::AsArray;
+use axum::Router;
+use axum::routing::post;
+use helix_db::helix_engine::graph::random_walk::WalkConfig;
+use helix_db::protocol::value::Value;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs;
+use std::path::Path;
+
+const DUMP: &str = r#"CREATE (:User {name: "Ada", email: "ada@example.com"});
+CREATE (:User {name: "Grace", email: "grace@example.com"});
+CREATE (:Post {title: "Notes", content: "On the engine"});
+MATCH (u:User {name: "Ada"}), (p:Post {title: "Notes"}) CREATE (u)-[:AUTHORED]->(p);
+MATCH (u:User {name: "Grace"}), (p:Post {title: "Notes"}) CREATE (u)-[:LIKES]->(p);
+"#;
+
+/// A project whose dev instance holds three nodes and two edges
+fn imported_project(ctx: &TestContext) -> ProjectContext {
+    ctx.setup_valid_project();
+    let dump = ctx.project_path.join("dump.cypher");
+    fs::write(&dump, DUMP).expect("Failed to write dump");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+    import(&project, "dev", Format::Neo4j, &dump, None, false).expect("import should succeed");
+    project
+}
+
+fn sorted_labels(dump: &crate::commands::import::Dump) -> Vec<String> {
+    let mut labels = dump
+        .nodes
+        .iter()
+        .flat_map(|node| node.labels.clone())
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels
+}
+
+#[test]
+fn test_export_graphml() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    let output = ctx.project_path.join("graph.graphml");
+
+    let summary = export(&project, "dev", &output, None).expect("export should succeed");
+
+    assert_eq!((summary.nodes, summary.relationships), (3, 2));
+    let dump = Format::GraphMl.read(&output).expect("export should parse");
+    assert_eq!(sorted_labels(&dump), vec!["Post", "User", "User"]);
+    let ada = dump
+        .nodes
+        .iter()
+        .find(|node| {
+            node.properties
+                .contains(&("name".to_string(), Value::String("Ada".to_string())))
+        })
+        .expect("Ada should be exported");
+    assert!(ada.properties.iter().any(|(key, _)| key == "email"));
+    let mut types = dump
+        .relationships
+        .iter()
+        .map(|relationship| relationship.rel_type.as_str())
+        .collect::<Vec<_>>();
+    types.sort();
+    assert_eq!(types, vec!["Authored", "Likes"]);
+}
+
+#[test]
+fn test_export_gexf_imports_into_another_project() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    let output = ctx.project_path.join("graph.gexf");
+    export(&project, "dev", &output, None).expect("export should succeed");
+
+    let other = TestContext::new();
+    other.setup_valid_project();
+    let other_project = ProjectContext::find_and_load(Some(&other.project_path)).expect("project");
+    let summary = import(&other_project, "dev", Format::Gexf, &output, None, false)
+        .expect("exported graph should import");
+
+    assert_eq!((summary.nodes, summary.relationships), (3, 2));
+}
+
+#[test]
+fn test_export_unknown_extension_fails() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    let output = ctx.project_path.join("graph.json");
+
+    assert!(export(&project, "dev", &output, None).is_err());
+    assert!(!output.exists());
+}
+
+#[test]
+fn test_export_without_data_fails() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let error = export(
+        &project,
+        "dev",
+        &ctx.project_path.join("graph.graphml"),
+        None,
+    )
+    .expect_err("an instance without data can't be exported");
+    assert!(error.to_string().contains("no data"), "{error}");
+}
+
+fn read_parquet(path: &Path) -> RecordBatch {
+    let file = fs::File::open(path).expect("Failed to open table");
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("table should be Parquet")
+        .build()
+        .expect("reader");
+    reader
+        .next()
+        .expect("a record batch")
+        .expect("readable batch")
+}
+
+fn column_names(batch: &RecordBatch) -> Vec<String> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect()
+}
+
+#[test]
+fn test_export_parquet_table() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    let output = ctx.project_path.join("users.parquet");
+
+    let summary = export(&project, "dev", &output, Some("User")).expect("export should succeed");
+
+    assert_eq!((summary.nodes, summary.relationships), (2, 0));
+    let batch = read_parquet(&output);
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(column_names(&batch)[0], "id");
+    let name = column_names(&batch)
+        .iter()
+        .position(|column| column == "name")
+        .expect("name column");
+    let mut names = batch
+        .column(name)
+        .as_string::<i32>()
+        .iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["Ada", "Grace"]);
+
+    let error = export(&project, "dev", &output, None).expect_err("a Parquet file needs a label");
+    assert!(error.to_string().contains("--label"), "{error}");
+    assert!(export(&project, "dev", &output, Some("Missing")).is_err());
+}
+
+#[test]
+fn test_export_parquet_directory() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    let output = ctx.project_path.join("tables");
+
+    let summary = export(&project, "dev", &output, None).expect("export should succeed");
+
+    assert_eq!((summary.nodes, summary.relationships), (3, 2));
+    assert_eq!(
+        read_parquet(&output.join("nodes/User.parquet")).num_rows(),
+        2
+    );
+    assert_eq!(
+        read_parquet(&output.join("nodes/Post.parquet")).num_rows(),
+        1
+    );
+    let authored = read_parquet(&output.join("edges/Authored.parquet"));
+    assert_eq!(
+        column_names(&authored)[..3],
+        ["id", "from_node", "to_node"].map(String::from)
+    );
+    assert!(output.join("edges/Likes.parquet").exists());
+}
+
+const ADA: &str = "00000000-0000-0000-0000-000000000001";
+const GRACE: &str = "00000000-0000-0000-0000-000000000002";
+const KNOWS: &str = "00000000-0000-0000-0000-000000000003";
+
+/// A query response returning the subgraph around Ada and the one around Grace, as
+/// `::SUBGRAPH` values serialize
+fn subgraph_response() -> serde_json::Value {
+    let ada = serde_json::json!({"id": ADA, "label": "User", "version": 1, "name": "Ada"});
+    let grace = serde_json::json!({"id": GRACE, "label": "User", "version": 1, "name": "Grace"});
+    let knows = serde_json::json!({
+        "id": KNOWS, "label": "Knows", "version": 1, "from_node": ADA, "to_node": GRACE,
+        "since": 1843,
+    });
+    serde_json::json!({
+        "count": 2,
+        "around": [
+            {"nodes": [ada, grace], "edges": [knows]},
+            {"nodes": [grace, ada], "edges": [knows]},
+        ],
+    })
+}
+
+#[test]
+fn test_subgraph_from_response_merges_returned_subgraphs() {
+    let subgraph = Subgraph::from_response(&subgraph_response()).expect("subgraph");
+
+    assert_eq!(subgraph.nodes.len(), 2);
+    assert_eq!(subgraph.edges.len(), 1);
+    assert!(Subgraph::from_response(&serde_json::json!({"count": 2})).is_none());
+
+    let dump = subgraph.to_dump().expect("subgraph should convert");
+    assert_eq!(sorted_labels(&dump), vec!["User", "User"]);
+    assert_eq!(dump.nodes[0].id.as_deref(), Some(ADA));
+    assert_eq!(
+        dump.nodes[0].properties,
+        vec![("name".to_string(), Value::String("Ada".to_string()))]
+    );
+    let knows = &dump.relationships[0];
+    assert_eq!(
+        (knows.rel_type.as_str(), knows.start, knows.end),
+        ("Knows", 0, 1)
+    );
+    assert_eq!(
+        knows.properties,
+        vec![("since".to_string(), Value::I64(1843))]
+    );
+}
+
+#[test]
+fn test_subgraph_with_an_edge_to_a_missing_node_fails() {
+    let subgraph = Subgraph {
+        nodes: vec![serde_json::json!({"id": ADA, "label": "User", "version": 1})],
+        edges: vec![serde_json::json!({
+            "id": KNOWS, "label": "Knows", "version": 1, "from_node": ADA, "to_node": GRACE,
+        })],
+    };
+
+    assert!(subgraph.to_dump().is_err());
+}
+
+#[tokio::test]
+async fn test_export_subgraph_writes_json_and_graphml() {
+    let app = Router::new().route(
+        "/around",
+        post(|| async { axum::Json(subgraph_response()) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let subgraph = fetch_subgraph(&url, "around", &serde_json::json!({"id": ADA}), None)
+        .await
+        .expect("query should return a subgraph");
+    assert!(
+        fetch_subgraph(&url, "missing", &serde_json::json!({}), None)
+            .await
+            .is_err()
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let graphml = dir.path().join("around.graphml");
+    subgraph.write(&graphml).expect("graphml should be written");
+    let dump = Format::GraphMl.read(&graphml).expect("export should parse");
+    assert_eq!(sorted_labels(&dump), vec!["User", "User"]);
+    assert_eq!(dump.relationships.len(), 1);
+
+    let json = dir.path().join("around.json");
+    subgraph.write(&json).expect("json should be written");
+    let written: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(Subgraph::from_response(&written), Some(subgraph));
+
+    assert!(
+        Subgraph::default()
+            .write(&dir.path().join("around.gexf"))
+            .is_err()
+    );
+}
+
+/// Answers `/admin/walks` with a walk per node, or the pairs along them when asked for a window
+async fn walks_handler(
+    axum::Json(body): axum::Json<serde_json::Value>,
+) -> axum::Json<serde_json::Value> {
+    assert_eq!(body["walk_length"], 2);
+    assert_eq!(body["edge_labels"], serde_json::json!(["Knows"]));
+    axum::Json(match body.get("window") {
+        Some(_) => serde_json::json!({"pairs": [[ADA, GRACE], [GRACE, ADA]]}),
+        None => serde_json::json!({"walks": [[ADA, GRACE], [GRACE, ADA]]}),
+    })
+}
+
+#[tokio::test]
+async fn test_export_walks_writes_walks_and_pairs() {
+    let app = Router::new().route("/admin/walks", post(walks_handler));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let config = WalkConfig {
+        walk_length: 2,
+        edge_labels: vec!["Knows".to_string()],
+        ..Default::default()
+    };
+    let walks = fetch_walks(&url, &config, None, None)
+        .await
+        .expect("walks should be returned");
+    assert!(!walks.pairs);
+    assert_eq!(walks.rows, vec![vec![ADA, GRACE], vec![GRACE, ADA]]);
+    let pairs = fetch_walks(&url, &config, Some(1), None)
+        .await
+        .expect("pairs should be returned");
+    assert!(pairs.pairs);
+
+    let dir = tempfile::tempdir().unwrap();
+    let txt = dir.path().join("walks.txt");
+    walks.write(&txt).expect("walks should be written");
+    assert_eq!(
+        fs::read_to_string(&txt).unwrap(),
+        format!("{ADA} {GRACE}\n{GRACE} {ADA}\n")
+    );
+    let csv = dir.path().join("pairs.csv");
+    pairs.write(&csv).expect("pairs should be written");
+    assert!(
+        fs::read_to_string(&csv)
+            .unwrap()
+            .starts_with("node,context\n")
+    );
+    let json = dir.path().join("pairs.json");
+    pairs.write(&json).expect("json should be written");
+    let written: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(Walks::from_response(&written), Some(pairs));
+
+    assert!(walks.write(&dir.path().join("walks.parquet")).is_err());
+}
@@ -0,0 +1,137 @@
+use crate::commands::fsck::check_offline;
+use crate::commands::import::{Format, import};
+use crate::project::ProjectContext;
+use crate::tests::test_utils::TestContext;
+use helix_db::helix_engine::storage_core::fsck::IssueKind;
+use helix_db::helix_engine::storage_core::{HelixGraphStorage, version_info::VersionInfo};
+use helix_db::helix_engine::traversal_core::config::Config;
+use std::fs;
+
+const DUMP: &str = r#"CREATE (:User {name: "Ada", email: "ada@example.com"});
+CREATE (:User {name: "Grace", email: "grace@example.com"});
+CREATE (:Post {title: "Notes", content: "On the engine"});
+MATCH (u:User {name: "Ada"}), (p:Post {title: "Notes"}) CREATE (u)-[:AUTHORED]->(p);
+MATCH (u:User {name: "Grace"}), (p:Post {title: "Notes"}) CREATE (u)-[:LIKES]->(p);
+"#;
+
+/// A project whose dev instance holds three nodes and two edges
+fn imported_project(ctx: &TestContext) -> ProjectContext {
+    ctx.setup_valid_project();
+    let dump = ctx.project_path.join("dump.cypher");
+    fs::write(&dump, DUMP).expect("Failed to write dump");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+    import(&project, "dev", Format::Neo4j, &dump, None, false).expect("import should succeed");
+    project
+}
+
+#[test]
+fn test_fsck_of_imported_instance_is_consistent() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+
+    let report = check_offline(&project, "dev", false).expect("fsck should succeed");
+
+    assert!(report.is_consistent(), "{:?}", report.issues);
+    assert_eq!(report.nodes, 3);
+    assert_eq!(report.edges, 2);
+}
+
+#[test]
+fn test_fsck_repairs_edges_to_deleted_nodes() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+
+    {
+        let data = project.instance_volume("dev").join("user");
+        let storage = HelixGraphStorage::new(
+            &data.display().to_string(),
+            Config::default(),
+            VersionInfo::default(),
+        )
+        .expect("Failed to open data");
+        let mut txn = storage.graph_env.write_txn().expect("write txn");
+        // Both edges point at the only post, the only node with incoming edges
+        let (key, _) = storage
+            .in_edges_db
+            .first(&txn)
+            .expect("first")
+            .expect("an incoming edge");
+        let post = u128::from_be_bytes(key[..16].try_into().expect("node id"));
+        storage.nodes_db.delete(&mut txn, &post).expect("delete");
+        txn.commit().expect("commit");
+    }
+
+    let report = check_offline(&project, "dev", false).expect("fsck should succeed");
+    assert!(!report.is_consistent());
+    let dangling = report
+        .issues
+        .iter()
+        .filter(|issue| issue.kind == IssueKind::DanglingEdge)
+        .count();
+    assert_eq!(dangling, 2);
+
+    let repaired = check_offline(&project, "dev", true).expect("repair should succeed");
+    assert!(repaired.is_consistent(), "{:?}", repaired.issues);
+
+    let after = check_offline(&project, "dev", false).expect("fsck should succeed");
+    assert!(after.issues.is_empty(), "{:?}", after.issues);
+    assert_eq!(after.nodes, 2);
+    assert_eq!(after.edges, 0);
+}
+
+#[test]
+fn test_fsck_of_instance_without_data_fails() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let error = check_offline(&project, "dev", false).expect_err("fsck should fail");
+
+    assert!(error.to_string().contains("has no data"), "{error}");
+}
+
+/// The test schema, with every user the author of exactly one post
+fn require_authors(ctx: &TestContext) {
+    let schema = ctx.project_path.join("db/schema.hx");
+    let content = fs::read_to_string(&schema).expect("Failed to read schema");
+    // `Authored` is declared before `Likes`, which has the same ends
+    let content = content.replacen("From: User,", "From: User [1],", 1);
+    fs::write(&schema, content).expect("Failed to write schema");
+}
+
+#[test]
+fn test_fsck_reports_edge_constraint_violations() {
+    let ctx = TestContext::new();
+    let project = imported_project(&ctx);
+    require_authors(&ctx);
+
+    let report = check_offline(&project, "dev", true).expect("fsck should succeed");
+
+    // Grace authored nothing, and only she is missing an edge
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    let issue = &report.issues[0];
+    assert_eq!(issue.kind, IssueKind::EdgeConstraintViolation);
+    assert!(!issue.repaired);
+    assert!(
+        issue
+            .detail
+            .contains("must have exactly 1 outgoing Authored edge"),
+        "{}",
+        issue.detail
+    );
+}
+
+#[test]
+fn test_import_breaking_edge_constraints_fails() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    require_authors(&ctx);
+    let dump = ctx.project_path.join("dump.cypher");
+    fs::write(&dump, DUMP).expect("Failed to write dump");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let error =
+        import(&project, "dev", Format::Neo4j, &dump, None, false).expect_err("import should fail");
+
+    assert!(error.to_string().contains("breaks the schema"), "{error}");
+}
@@ -0,0 +1,830 @@
+use crate::commands::import::{
+    Dump, DumpNode, DumpRelationship, EdgeTable, Format, NodeTable, TableMapping, gexf, graphml,
+    import, import_tables, propose_schema, read_dump,
+};
+use crate::project::ProjectContext;
+use crate::tests::test_utils::TestContext;
+use helix_db::helix_engine::storage_core::HelixGraphStorage;
+use helix_db::helix_engine::storage_core::version_info::VersionInfo;
+use helix_db::helix_engine::traversal_core::config::Config;
+use helix_db::protocol::date::Date;
+use helix_db::protocol::value::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `apoc.export.cypher.all` output in its default cypher-shell format
+const APOC_DUMP: &str = r#":begin
+CREATE CONSTRAINT UNIQUE_IMPORT_NAME FOR (node:`UNIQUE IMPORT LABEL`) REQUIRE (node.`UNIQUE IMPORT ID`) IS UNIQUE;
+:commit
+CALL db.awaitIndexes(300);
+:begin
+UNWIND [{_id:0, properties:{name:"Ada", email:"ada@example.com"}}, {_id:1, properties:{name:"Grace", email:"grace@example.com"}}] AS row
+CREATE (n:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row._id}) SET n += row.properties SET n:User;
+UNWIND [{_id:2, properties:{title:"Notes", content:"On the engine"}}] AS row
+CREATE (n:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row._id}) SET n += row.properties SET n:Post;
+:commit
+:begin
+UNWIND [{start: {_id:0}, end: {_id:2}, properties:{}}] AS row
+MATCH (start:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row.start._id})
+MATCH (end:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row.end._id})
+CREATE (start)-[r:AUTHORED]->(end) SET r += row.properties;
+UNWIND [{start: {_id:1}, end: {_id:2}, properties:{}}] AS row
+MATCH (start:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row.start._id})
+MATCH (end:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`: row.end._id})
+CREATE (start)-[r:LIKES]->(end) SET r += row.properties;
+:commit
+:begin
+MATCH (n:`UNIQUE IMPORT LABEL`)  WITH n LIMIT 20000 REMOVE n:`UNIQUE IMPORT LABEL`, n.`UNIQUE IMPORT ID`;
+:commit
+:begin
+DROP CONSTRAINT UNIQUE_IMPORT_NAME;
+:commit
+"#;
+
+fn write_dump(ctx: &TestContext, contents: &str) -> PathBuf {
+    let path = ctx.project_path.join("dump.cypher");
+    fs::write(&path, contents).expect("Failed to write dump");
+    path
+}
+
+fn property<'a>(properties: &'a [(String, Value)], key: &str) -> Option<&'a Value> {
+    properties.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn labels(dump: &Dump) -> Vec<&str> {
+    dump.nodes
+        .iter()
+        .flat_map(|node| node.labels.iter().map(String::as_str))
+        .collect()
+}
+
+/// Node and edge counts of an instance's data directory
+fn stored_counts(path: &Path) -> (u64, u64) {
+    let storage = HelixGraphStorage::new(
+        &path.display().to_string(),
+        Config::default(),
+        VersionInfo::default(),
+    )
+    .expect("Failed to open imported data");
+    let txn = storage.graph_env.read_txn().expect("read txn");
+    (
+        storage.nodes_db.len(&txn).expect("node count"),
+        storage.edges_db.len(&txn).expect("edge count"),
+    )
+}
+
+#[test]
+fn test_read_apoc_cypher_shell_dump() {
+    let ctx = TestContext::new();
+    let dump = read_dump(&write_dump(&ctx, APOC_DUMP)).expect("dump should parse");
+
+    assert_eq!(labels(&dump), vec!["User", "User", "Post"]);
+    assert_eq!(
+        property(&dump.nodes[0].properties, "name"),
+        Some(&Value::String("Ada".to_string()))
+    );
+    // The importer's bookkeeping id isn't kept
+    assert!(property(&dump.nodes[0].properties, "UNIQUE IMPORT ID").is_none());
+
+    assert_eq!(dump.relationships.len(), 2);
+    assert_eq!(dump.relationships[0].rel_type, "AUTHORED");
+    assert_eq!(
+        (dump.relationships[0].start, dump.relationships[0].end),
+        (0, 2)
+    );
+    assert_eq!(dump.relationships[1].rel_type, "LIKES");
+    assert_eq!(
+        (dump.relationships[1].start, dump.relationships[1].end),
+        (1, 2)
+    );
+}
+
+#[test]
+fn test_read_plain_cypher_dump() {
+    let ctx = TestContext::new();
+    let dump = read_dump(&write_dump(
+        &ctx,
+        r#"begin
+CREATE (:`Person`:`UNIQUE IMPORT LABEL` {`name`:"Ada", `born`:1815, `score`:-1.5, `tags`:["math", "poetry"], `since`:date('2020-01-02'), `UNIQUE IMPORT ID`:0});
+CREATE (:`Person`:`UNIQUE IMPORT LABEL` {`name`:"Charles \"CB\"", `UNIQUE IMPORT ID`:1});
+commit
+begin
+MATCH (n1:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`:0}), (n2:`UNIQUE IMPORT LABEL`{`UNIQUE IMPORT ID`:1}) CREATE (n1)-[r:`KNOWS` {`weight`:2}]->(n2);
+commit
+"#,
+    ))
+    .expect("dump should parse");
+
+    assert_eq!(labels(&dump), vec!["Person", "Person"]);
+    let ada = &dump.nodes[0].properties;
+    assert_eq!(property(ada, "born"), Some(&Value::I64(1815)));
+    assert_eq!(property(ada, "score"), Some(&Value::F64(-1.5)));
+    assert_eq!(
+        property(ada, "tags"),
+        Some(&Value::Array(vec![
+            Value::String("math".to_string()),
+            Value::String("poetry".to_string()),
+        ]))
+    );
+    assert!(matches!(property(ada, "since"), Some(Value::Date(_))));
+    assert_eq!(
+        property(&dump.nodes[1].properties, "name"),
+        Some(&Value::String("Charles \"CB\"".to_string()))
+    );
+
+    assert_eq!(dump.relationships.len(), 1);
+    assert_eq!(dump.relationships[0].rel_type, "KNOWS");
+    assert_eq!(
+        property(&dump.relationships[0].properties, "weight"),
+        Some(&Value::I64(2))
+    );
+}
+
+#[test]
+fn test_read_neo4j_shell_dump() {
+    let ctx = TestContext::new();
+    let dump = read_dump(&write_dump(
+        &ctx,
+        r#"begin
+create (_0:`User` {`name`:"Ada"})
+create (_1:`Post` {`title`:"Notes"})
+create (_0)-[:`AUTHORED`]->(_1)
+;
+commit
+"#,
+    ))
+    .expect("dump should parse");
+
+    assert_eq!(labels(&dump), vec!["User", "Post"]);
+    assert_eq!(dump.relationships.len(), 1);
+    assert_eq!(
+        (dump.relationships[0].start, dump.relationships[0].end),
+        (0, 1)
+    );
+}
+
+#[test]
+fn test_read_unsupported_cypher_fails() {
+    let ctx = TestContext::new();
+    let result = read_dump(&write_dump(&ctx, "MATCH (n) DETACH DELETE n;"));
+
+    assert!(result.is_err(), "Unsupported clauses should be reported");
+}
+
+#[test]
+fn test_read_neo4j_admin_csv_export() {
+    let ctx = TestContext::new();
+    let export = ctx.project_path.join("export");
+    fs::create_dir_all(&export).expect("Failed to create export dir");
+    fs::write(
+        export.join("users_header.csv"),
+        "userId:ID(User),name,age:int,nicknames:string[],:LABEL\n",
+    )
+    .expect("write");
+    fs::write(
+        export.join("users.csv"),
+        "u1,Ada,36,\"Countess;Enchantress\",User\nu2,\"Grace, RADM\",,,User\n",
+    )
+    .expect("write");
+    fs::write(
+        export.join("posts.csv"),
+        "postId:ID(Post),title,:LABEL\np1,\"\",Post\n",
+    )
+    .expect("write");
+    fs::write(
+        export.join("authored.csv"),
+        ":START_ID(User),:END_ID(Post),:TYPE\nu1,p1,AUTHORED\nu2,p1,AUTHORED\n",
+    )
+    .expect("write");
+
+    let dump = read_dump(&export).expect("export should parse");
+
+    assert_eq!(labels(&dump), vec!["Post", "User", "User"]);
+    let ada = &dump.nodes[1].properties;
+    assert_eq!(
+        property(ada, "userId"),
+        Some(&Value::String("u1".to_string()))
+    );
+    assert_eq!(property(ada, "age"), Some(&Value::I64(36)));
+    assert_eq!(
+        property(ada, "nicknames"),
+        Some(&Value::Array(vec![
+            Value::String("Countess".to_string()),
+            Value::String("Enchantress".to_string()),
+        ]))
+    );
+    let grace = &dump.nodes[2].properties;
+    assert_eq!(
+        property(grace, "name"),
+        Some(&Value::String("Grace, RADM".to_string()))
+    );
+    // Unquoted empty fields are missing, quoted ones are empty strings
+    assert!(property(grace, "age").is_none());
+    assert_eq!(
+        property(&dump.nodes[0].properties, "title"),
+        Some(&Value::String(String::new()))
+    );
+
+    assert_eq!(dump.relationships.len(), 2);
+    assert!(dump.relationships.iter().all(|rel| rel.end == 0));
+}
+
+#[test]
+fn test_read_csv_export_with_unknown_node_fails() {
+    let ctx = TestContext::new();
+    let export = ctx.project_path.join("export");
+    fs::create_dir_all(&export).expect("Failed to create export dir");
+    fs::write(export.join("nodes.csv"), "id:ID,:LABEL\n1,User\n").expect("write");
+    fs::write(
+        export.join("rels.csv"),
+        ":START_ID,:END_ID,:TYPE\n1,2,FOLLOWS\n",
+    )
+    .expect("write");
+
+    let error = read_dump(&export).expect_err("unknown end node should fail");
+    assert!(error.to_string().contains("`2`"), "{error}");
+}
+
+#[test]
+fn test_propose_schema_from_dump() {
+    let ctx = TestContext::new();
+    let dump = read_dump(&write_dump(
+        &ctx,
+        r#"CREATE (:Person {name: "Ada", born: 1815, active: true});
+CREATE (:Person {name: "Grace", born: 1906.5});
+CREATE (:Company {name: "Analytical Engines"});
+MATCH (a:Person {name: "Ada"}), (c:Company {name: "Analytical Engines"}) CREATE (a)-[:WORKS_AT {since: 1842}]->(c);
+"#,
+    ))
+    .expect("dump should parse");
+
+    let schema = propose_schema(&dump);
+    assert!(schema.contains("N::Person {"), "{schema}");
+    assert!(schema.contains("    name: String,"), "{schema}");
+    // Integers and floats seen in the same property widen to F64
+    assert!(schema.contains("    born: F64,"), "{schema}");
+    assert!(schema.contains("    active: Boolean,"), "{schema}");
+    assert!(schema.contains("N::Company {"), "{schema}");
+    assert!(schema.contains("E::WorksAt {"), "{schema}");
+    assert!(schema.contains("    From: Person,"), "{schema}");
+    assert!(schema.contains("    To: Company,"), "{schema}");
+    assert!(schema.contains("        since: I64,"), "{schema}");
+}
+
+#[test]
+fn test_import_unmapped_labels_fails() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let dump = write_dump(&ctx, "CREATE (:Company {name: \"Acme\"});");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let error = import(&project, "dev", Format::Neo4j, &dump, None, false)
+        .expect_err("labels missing from the schema should fail");
+    assert!(error.to_string().contains("Company"), "{error}");
+    assert!(!project.instance_volume("dev").join("user").exists());
+}
+
+#[test]
+fn test_import_unknown_instance_fails() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let dump = write_dump(&ctx, APOC_DUMP);
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    assert!(import(&project, "missing", Format::Neo4j, &dump, None, false).is_err());
+}
+
+#[test]
+fn test_import_into_project_schema() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let dump = write_dump(&ctx, APOC_DUMP);
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let summary =
+        import(&project, "dev", Format::Neo4j, &dump, None, false).expect("import should succeed");
+
+    assert_eq!(summary.nodes, 3);
+    assert_eq!(summary.relationships, 2);
+    assert!(summary.schema_written.is_none());
+    assert_eq!(
+        stored_counts(&project.instance_volume("dev").join("user")),
+        (3, 2)
+    );
+}
+
+#[test]
+fn test_import_dry_run_writes_nothing() {
+    let ctx = TestContext::new();
+    ctx.setup_project_without_schema();
+    let dump = write_dump(&ctx, APOC_DUMP);
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let summary =
+        import(&project, "dev", Format::Neo4j, &dump, None, true).expect("dry run should succeed");
+
+    assert_eq!(summary.nodes, 3);
+    assert!(summary.schema_written.is_none());
+    assert!(!ctx.project_path.join("db/schema.hx").exists());
+    assert!(!project.instance_volume("dev").join("user").exists());
+}
+
+#[test]
+fn test_import_without_schema_writes_proposal() {
+    let ctx = TestContext::new();
+    ctx.setup_project_without_schema();
+    let dump = write_dump(&ctx, APOC_DUMP);
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let summary =
+        import(&project, "dev", Format::Neo4j, &dump, None, false).expect("import should succeed");
+
+    let schema_path = PathBuf::from(summary.schema_written.expect("schema should be proposed"));
+    assert_eq!(
+        schema_path.canonicalize().expect("proposed schema path"),
+        ctx.project_path
+            .join("db/schema.hx")
+            .canonicalize()
+            .expect("schema path")
+    );
+    let schema = fs::read_to_string(&schema_path).expect("proposed schema");
+    assert!(schema.contains("N::User {"), "{schema}");
+    assert!(schema.contains("E::Authored {"), "{schema}");
+    assert_eq!(
+        stored_counts(&project.instance_volume("dev").join("user")),
+        (3, 2)
+    );
+}
+
+fn write_file(ctx: &TestContext, name: &str, contents: &str) -> PathBuf {
+    let path = ctx.project_path.join(name);
+    fs::write(&path, contents).expect("Failed to write file");
+    path
+}
+
+#[test]
+fn test_read_graphml() {
+    let ctx = TestContext::new();
+    let path = write_file(
+        &ctx,
+        "graph.graphml",
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="labels" for="node" attr.name="labels" attr.type="string"/>
+  <key id="d0" for="node" attr.name="name" attr.type="string"/>
+  <key id="d1" for="node" attr.name="age" attr.type="int">
+    <default>30</default>
+  </key>
+  <key id="d2" for="node" attr.name="tags" attr.type="string" attr.list="string"/>
+  <key id="d3" for="edge" attr.name="weight" attr.type="double"/>
+  <key id="label" for="edge" attr.name="label" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="n0" labels=":User">
+      <data key="labels">:User</data>
+      <data key="d0">Ada &amp; co</data>
+      <data key="d1">36</data>
+      <data key="d2">["math","poetry"]</data>
+    </node>
+    <node id="n1"><data key="d0">Plain</data></node>
+    <edge source="n0" target="n1" label="KNOWS"><data key="d3">0.5</data></edge>
+    <edge source="n1" target="n0"><data key="label">FOLLOWS</data></edge>
+  </graph>
+</graphml>"#,
+    );
+
+    let dump = Format::GraphMl.read(&path).expect("GraphML should parse");
+
+    assert_eq!(labels(&dump), vec!["User", "Node"]);
+    let ada = &dump.nodes[0].properties;
+    assert_eq!(
+        property(ada, "name"),
+        Some(&Value::String("Ada & co".to_string()))
+    );
+    assert_eq!(property(ada, "age"), Some(&Value::I64(36)));
+    assert_eq!(
+        property(ada, "tags"),
+        Some(&Value::Array(vec![
+            Value::String("math".to_string()),
+            Value::String("poetry".to_string()),
+        ]))
+    );
+    // Missing data takes the key's default
+    assert_eq!(
+        property(&dump.nodes[1].properties, "age"),
+        Some(&Value::I64(30))
+    );
+
+    assert_eq!(dump.relationships.len(), 2);
+    assert_eq!(dump.relationships[0].rel_type, "KNOWS");
+    assert_eq!(
+        property(&dump.relationships[0].properties, "weight"),
+        Some(&Value::F64(0.5))
+    );
+    assert_eq!(dump.relationships[1].rel_type, "FOLLOWS");
+    assert_eq!(
+        (dump.relationships[1].start, dump.relationships[1].end),
+        (1, 0)
+    );
+}
+
+#[test]
+fn test_read_graphml_with_unknown_edge_endpoint_fails() {
+    let ctx = TestContext::new();
+    let path = write_file(
+        &ctx,
+        "graph.graphml",
+        r#"<graphml><graph><node id="a"/><edge source="a" target="b"/></graph></graphml>"#,
+    );
+
+    let error = Format::GraphMl
+        .read(&path)
+        .expect_err("unknown target should fail");
+    assert!(error.to_string().contains("`b`"), "{error}");
+}
+
+#[test]
+fn test_read_gexf() {
+    let ctx = TestContext::new();
+    let path = write_file(
+        &ctx,
+        "graph.gexf",
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://www.gexf.net/1.2draft" xmlns:viz="http://www.gexf.net/1.2draft/viz" version="1.2">
+  <graph defaultedgetype="directed" mode="static">
+    <attributes class="node">
+      <attribute id="0" title="score" type="double"/>
+      <attribute id="1" title="active" type="boolean">
+        <default>false</default>
+      </attribute>
+      <attribute id="2" title="tags" type="liststring"/>
+    </attributes>
+    <nodes>
+      <node id="0" label="Person">
+        <attvalues>
+          <attvalue for="0" value="1.5"/>
+          <attvalue for="1" value="true"/>
+          <attvalue for="2" value="a|b"/>
+        </attvalues>
+        <viz:color r="255" g="0" b="0"/>
+      </node>
+      <node id="1" label="1"/>
+    </nodes>
+    <edges>
+      <edge id="0" source="0" target="1" weight="2.0" kind="KNOWS"/>
+    </edges>
+  </graph>
+</gexf>"#,
+    );
+
+    let dump = Format::Gexf.read(&path).expect("GEXF should parse");
+
+    // A label repeating the node's id isn't a type
+    assert_eq!(labels(&dump), vec!["Person", "Node"]);
+    let person = &dump.nodes[0].properties;
+    assert_eq!(property(person, "score"), Some(&Value::F64(1.5)));
+    assert_eq!(property(person, "active"), Some(&Value::Boolean(true)));
+    assert_eq!(
+        property(person, "tags"),
+        Some(&Value::Array(vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ]))
+    );
+    assert_eq!(
+        property(&dump.nodes[1].properties, "active"),
+        Some(&Value::Boolean(false))
+    );
+
+    assert_eq!(dump.relationships.len(), 1);
+    assert_eq!(dump.relationships[0].rel_type, "KNOWS");
+    assert_eq!(
+        property(&dump.relationships[0].properties, "weight"),
+        Some(&Value::F64(2.0))
+    );
+}
+
+#[test]
+fn test_read_wrong_document_fails() {
+    let ctx = TestContext::new();
+    let path = write_file(&ctx, "graph.gexf", "<graphml><graph/></graphml>");
+
+    assert!(Format::Gexf.read(&path).is_err());
+}
+
+/// A dump with one property of each type the graph formats map
+fn typed_dump() -> Dump {
+    let date = Date::new(&Value::String("2024-05-06T07:08:09+00:00".to_string())).expect("date");
+    Dump {
+        nodes: vec![
+            DumpNode {
+                id: Some("a".to_string()),
+                labels: vec!["Person".to_string()],
+                properties: vec![
+                    (
+                        "name".to_string(),
+                        Value::String("<Ada> & \"co\"".to_string()),
+                    ),
+                    ("age".to_string(), Value::U32(36)),
+                    ("score".to_string(), Value::F64(1.25)),
+                    ("active".to_string(), Value::Boolean(true)),
+                    ("born".to_string(), Value::Date(date)),
+                    (
+                        "scores".to_string(),
+                        Value::Array(vec![Value::I64(1), Value::I64(2)]),
+                    ),
+                ],
+            },
+            DumpNode {
+                id: None,
+                labels: vec!["Person".to_string()],
+                properties: vec![("age".to_string(), Value::F64(2.5))],
+            },
+        ],
+        relationships: vec![DumpRelationship {
+            id: None,
+            rel_type: "KNOWS".to_string(),
+            start: 0,
+            end: 1,
+            properties: vec![("since".to_string(), Value::I64(2020))],
+        }],
+    }
+}
+
+fn assert_round_trip(format: Format, text: String, ctx: &TestContext, name: &str) {
+    let dump = format
+        .read(&write_file(ctx, name, &text))
+        .expect("written document should parse");
+
+    assert_eq!(labels(&dump), vec!["Person", "Person"]);
+    assert_eq!(dump.nodes[0].id.as_deref(), Some("a"));
+    assert_eq!(dump.nodes[1].id.as_deref(), Some("n1"));
+    let ada = &dump.nodes[0].properties;
+    assert_eq!(
+        property(ada, "name"),
+        Some(&Value::String("<Ada> & \"co\"".to_string()))
+    );
+    // An integer in one node and a float in another widen to floats
+    assert_eq!(property(ada, "age"), Some(&Value::F64(36.0)));
+    assert_eq!(property(ada, "active"), Some(&Value::Boolean(true)));
+    assert_eq!(
+        property(ada, "scores"),
+        Some(&Value::Array(vec![Value::I64(1), Value::I64(2)]))
+    );
+    assert_eq!(dump.relationships.len(), 1);
+    assert_eq!(dump.relationships[0].rel_type, "KNOWS");
+    assert_eq!(
+        property(&dump.relationships[0].properties, "since"),
+        Some(&Value::I64(2020))
+    );
+}
+
+#[test]
+fn test_graphml_round_trip() {
+    let ctx = TestContext::new();
+    let text = graphml::write(&typed_dump());
+
+    assert_round_trip(Format::GraphMl, text.clone(), &ctx, "graph.graphml");
+    // GraphML has no date type, dates are written as RFC 3339 strings
+    let dump = Format::GraphMl
+        .read(&write_file(&ctx, "graph.graphml", &text))
+        .expect("parse");
+    assert!(matches!(
+        property(&dump.nodes[0].properties, "born"),
+        Some(Value::String(date)) if date.starts_with("2024-05-06T07:08:09")
+    ));
+}
+
+#[test]
+fn test_gexf_round_trip() {
+    let ctx = TestContext::new();
+    let text = gexf::write(&typed_dump());
+
+    assert_round_trip(Format::Gexf, text.clone(), &ctx, "graph.gexf");
+    let dump = Format::Gexf
+        .read(&write_file(&ctx, "graph.gexf", &text))
+        .expect("parse");
+    assert!(matches!(
+        property(&dump.nodes[0].properties, "born"),
+        Some(Value::Date(_))
+    ));
+}
+
+#[test]
+fn test_import_graphml_into_project_schema() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let path = write_file(
+        &ctx,
+        "graph.graphml",
+        r#"<graphml>
+  <key id="labels" for="node" attr.name="labels" attr.type="string"/>
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="title" for="node" attr.name="title" attr.type="string"/>
+  <graph edgedefault="directed">
+    <node id="u"><data key="labels">:User</data><data key="name">Ada</data></node>
+    <node id="p"><data key="labels">:Post</data><data key="title">Notes</data></node>
+    <edge source="u" target="p" label="AUTHORED"/>
+  </graph>
+</graphml>"#,
+    );
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+
+    let summary = import(&project, "dev", Format::GraphMl, &path, None, false)
+        .expect("import should succeed");
+
+    assert_eq!((summary.nodes, summary.relationships), (2, 1));
+    assert_eq!(
+        stored_counts(&project.instance_volume("dev").join("user")),
+        (2, 1)
+    );
+}
+
+#[test]
+fn test_parse_table_specs() {
+    let nodes = NodeTable::from_spec("data/users.csv:User").expect("node spec");
+    assert_eq!(nodes.file, PathBuf::from("data/users.csv"));
+    assert_eq!(nodes.label, "User");
+
+    let edges = EdgeTable::from_spec("follows.csv:Follows(from,to:User)").expect("edge spec");
+    assert_eq!(edges.file, PathBuf::from("follows.csv"));
+    assert_eq!(edges.label, "Follows");
+    assert_eq!((edges.from.as_str(), edges.to.as_str()), ("from", "to"));
+    assert_eq!(edges.from_label, None);
+    assert_eq!(edges.to_label.as_deref(), Some("User"));
+
+    assert!(NodeTable::from_spec("users.csv").is_err());
+    assert!(EdgeTable::from_spec("follows.csv:Follows").is_err());
+    assert!(EdgeTable::from_spec("follows.csv:Follows(from)").is_err());
+}
+
+#[test]
+fn test_read_tables_infers_column_types() {
+    let ctx = TestContext::new();
+    let users = write_file(
+        &ctx,
+        "users.csv",
+        "id,name,age,score,active,joined,zip\n\
+         1,Ada,36,9.5,true,2024-01-02,01234\n\
+         2,\"Hopper, Grace\",,7,false,2024-03-04T10:00:00Z,90210\n",
+    );
+    let follows = write_file(&ctx, "follows.tsv", "from\tto\tsince\n1\t2\t2020\n");
+    let mut tables = TableMapping::default();
+    tables.add_nodes(NodeTable::from_spec(&format!("{}:User", users.display())).expect("spec"));
+    tables.add_edges(
+        EdgeTable::from_spec(&format!("{}:Follows(from,to)", follows.display())).expect("spec"),
+    );
+
+    let dump = tables.read().expect("tables should read");
+
+    assert_eq!(labels(&dump), vec!["User", "User"]);
+    let ada = &dump.nodes[0].properties;
+    assert_eq!(property(ada, "id"), Some(&Value::I64(1)));
+    assert_eq!(property(ada, "age"), Some(&Value::I64(36)));
+    assert_eq!(property(ada, "score"), Some(&Value::F64(9.5)));
+    assert_eq!(property(ada, "active"), Some(&Value::Boolean(true)));
+    assert!(matches!(property(ada, "joined"), Some(Value::Date(_))));
+    assert_eq!(
+        property(ada, "zip"),
+        Some(&Value::String("01234".to_string()))
+    );
+    let grace = &dump.nodes[1].properties;
+    assert_eq!(
+        property(grace, "name"),
+        Some(&Value::String("Hopper, Grace".to_string()))
+    );
+    assert_eq!(property(grace, "age"), None);
+
+    assert_eq!(dump.relationships.len(), 1);
+    let follows = &dump.relationships[0];
+    assert_eq!((follows.start, follows.end), (0, 1));
+    assert_eq!(follows.rel_type, "Follows");
+    assert_eq!(
+        follows.properties,
+        vec![("since".to_string(), Value::I64(2020))]
+    );
+}
+
+#[test]
+fn test_read_tables_with_mapping_file() {
+    let ctx = TestContext::new();
+    fs::create_dir_all(ctx.project_path.join("data")).expect("data dir");
+    write_file(
+        &ctx,
+        "data/people.csv",
+        "key,name,age,notes\nada,Ada,36,skip me\nalan,Alan,41,\n",
+    );
+    write_file(&ctx, "data/teams.csv", "key,name\nada,Engines\n");
+    write_file(&ctx, "data/members.csv", "person,team\nada,ada\nalan,ada\n");
+    let mapping = write_file(
+        &ctx,
+        "data/mapping.toml",
+        r#"
+[[nodes]]
+file = "people.csv"
+label = "Person"
+id = "key"
+columns = { age = "U8", notes = { skip = true }, name = { name = "full_name" } }
+
+[[nodes]]
+file = "teams.csv"
+label = "Team"
+id = "key"
+
+[[edges]]
+file = "members.csv"
+label = "MemberOf"
+from = "person"
+to = "team"
+from_label = "Person"
+to_label = "Team"
+"#,
+    );
+
+    let dump = TableMapping::load(&mapping)
+        .and_then(|tables| tables.read())
+        .expect("mapped tables should read");
+
+    let ada = &dump.nodes[0].properties;
+    assert_eq!(property(ada, "age"), Some(&Value::U8(36)));
+    assert_eq!(
+        property(ada, "full_name"),
+        Some(&Value::String("Ada".to_string()))
+    );
+    assert_eq!(property(ada, "notes"), None);
+    let ends = dump
+        .relationships
+        .iter()
+        .map(|relationship| (relationship.start, relationship.end))
+        .collect::<Vec<_>>();
+    assert_eq!(ends, vec![(0, 2), (1, 2)]);
+}
+
+#[test]
+fn test_read_tables_rejects_bad_values() {
+    let ctx = TestContext::new();
+    let users = write_file(&ctx, "users.csv", "id,age\n1,36\n2,old\n");
+    let mapping = write_file(
+        &ctx,
+        "mapping.toml",
+        "[[nodes]]\nfile = \"users.csv\"\nlabel = \"User\"\ncolumns = { age = \"I32\" }\n",
+    );
+    let error = TableMapping::load(&mapping)
+        .and_then(|tables| tables.read())
+        .expect_err("a value that isn't an I32 should fail");
+    let message = error.to_string();
+    assert!(
+        message.contains("row 2") && message.contains("`age`"),
+        "{message}"
+    );
+
+    // Ids used by two node tables need the edge to say which one it means
+    write_file(&ctx, "admins.csv", "id\n1\n");
+    let edges = write_file(&ctx, "follows.csv", "from,to\n1,2\n");
+    let mut tables = TableMapping::default();
+    tables.add_nodes(NodeTable::from_spec(&format!("{}:User", users.display())).expect("spec"));
+    tables.add_nodes(
+        NodeTable::from_spec(&format!(
+            "{}:Admin",
+            ctx.project_path.join("admins.csv").display()
+        ))
+        .expect("spec"),
+    );
+    tables.add_edges(
+        EdgeTable::from_spec(&format!("{}:Follows(from,to)", edges.display())).expect("spec"),
+    );
+    let error = tables.read().expect_err("an ambiguous id should fail");
+    assert!(error.to_string().contains("from_label"), "{error}");
+}
+
+#[test]
+fn test_import_tables_into_project_schema() {
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+    let users = write_file(
+        &ctx,
+        "users.csv",
+        "id,name,email\n1,Ada,ada@example.com\n2,Grace,grace@example.com\n",
+    );
+    let posts = write_file(
+        &ctx,
+        "posts.csv",
+        "id,title,content\np1,Notes,On the engine\n",
+    );
+    let authored = write_file(&ctx, "authored.csv", "user,post\n1,p1\n2,p1\n");
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).expect("project");
+    let mut tables = TableMapping::default();
+    tables.add_nodes(NodeTable::from_spec(&format!("{}:User", users.display())).expect("spec"));
+    tables.add_nodes(NodeTable::from_spec(&format!("{}:Post", posts.display())).expect("spec"));
+    tables.add_edges(
+        EdgeTable::from_spec(&format!("{}:Authored(user,post)", authored.display())).expect("spec"),
+    );
+
+    let summary =
+        import_tables(&project, "dev", &tables, None, false).expect("import should succeed");
+
+    assert_eq!((summary.nodes, summary.relationships), (3, 2));
+    assert_eq!(
+        stored_counts(&project.instance_volume("dev").join("user")),
+        (3, 2)
+    );
+}
@@ -235,7 +235,7 @@ async fn test_status_handles_no_project_gracefully() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
 
     // Status command should handle missing project gracefully (not panic)
-    let result = status::run().await;
+    let result = status::run(false).await;
     // Status should succeed but print an error message
     assert!(result.is_ok(), "Status should not panic without project");
 }
@@ -248,7 +248,7 @@ async fn test_status_shows_project_info() {
     let _guard = std::env::set_current_dir(&ctx.project_path);
 
     // Status command should succeed when in a valid project
-    let result = status::run().await;
+    let result = status::run(false).await;
     // Status will try to check Docker which may not be available,
     // but it should still succeed by handling Docker errors gracefully
     assert!(result.is_ok(), "Status should succeed in valid project");
@@ -265,6 +265,8 @@ async fn test_status_with_multiple_instances() {
         crate::config::LocalInstanceConfig {
             port: Some(6970),
             build_mode: crate::config::BuildMode::Dev,
+            resources: None,
+            standby_of: None,
             db_config: crate::config::DbConfig::default(),
         },
     );
@@ -273,6 +275,8 @@ async fn test_status_with_multiple_instances() {
         crate::config::LocalInstanceConfig {
             port: Some(6971),
             build_mode: crate::config::BuildMode::Release,
+            resources: None,
+            standby_of: None,
             db_config: crate::config::DbConfig::default(),
         },
     );
@@ -284,7 +288,7 @@ async fn test_status_with_multiple_instances() {
 
     let _guard = std::env::set_current_dir(&ctx.project_path);
 
-    let result = status::run().await;
+    let result = status::run(false).await;
     assert!(
         result.is_ok(),
         "Status should succeed with multiple instances"
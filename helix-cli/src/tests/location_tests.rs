@@ -0,0 +1,100 @@
+use crate::location::{Location, RemoteLocation};
+use object_store::ObjectStore;
+use object_store::memory::InMemory;
+use object_store::path::Path as ObjectPath;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use url::Url;
+
+fn remote(store: &Arc<InMemory>, url: &str) -> RemoteLocation {
+    let store: Arc<dyn ObjectStore> = Arc::clone(store) as _;
+    RemoteLocation::new(Url::parse(url).unwrap(), store).expect("location")
+}
+
+#[test]
+fn test_location_parse_tells_urls_from_paths() {
+    assert!(matches!(
+        Location::parse("backups/latest").unwrap(),
+        Location::Local(path) if path == Path::new("backups/latest")
+    ));
+    assert!(matches!(
+        Location::parse("C:\\backups").unwrap(),
+        Location::Local(_)
+    ));
+    match Location::parse("s3://helix-backups/exports/graph.graphml").unwrap() {
+        Location::Remote(remote) => {
+            assert_eq!(remote.file_name(), Some("graph.graphml"));
+            assert_eq!(
+                remote.to_string(),
+                "s3://helix-backups/exports/graph.graphml"
+            );
+        }
+        other => panic!("expected a remote location, got {other:?}"),
+    }
+
+    assert!(Location::is_remote(Path::new("gs://bucket/users.csv")));
+    assert!(Location::is_remote(Path::new("az://container/users.csv")));
+    assert!(!Location::is_remote(Path::new("data/users.csv")));
+    assert!(!Location::is_remote(Path::new(
+        "https://example.com/users.csv"
+    )));
+}
+
+#[tokio::test]
+async fn test_remote_location_round_trips_a_file() {
+    let store = Arc::new(InMemory::new());
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("graph.gexf");
+    fs::write(&file, "<gexf/>").unwrap();
+
+    let location = remote(&store, "s3://bucket/exports/graph.gexf");
+    assert_eq!(location.upload(&file).await.unwrap(), 1);
+    let uploaded = store
+        .get(&ObjectPath::from("exports/graph.gexf"))
+        .await
+        .unwrap();
+    assert_eq!(uploaded.bytes().await.unwrap().as_ref(), b"<gexf/>");
+
+    let downloads = TempDir::new().unwrap();
+    let downloaded = location.download(downloads.path()).await.unwrap();
+    assert_eq!(downloaded, downloads.path().join("graph.gexf"));
+    assert_eq!(fs::read_to_string(downloaded).unwrap(), "<gexf/>");
+}
+
+#[tokio::test]
+async fn test_remote_location_round_trips_a_directory() {
+    let store = Arc::new(InMemory::new());
+    let dir = TempDir::new().unwrap();
+    let export = dir.path().join("tables");
+    fs::create_dir_all(export.join("nodes")).unwrap();
+    fs::create_dir_all(export.join("edges")).unwrap();
+    fs::write(export.join("nodes/User.parquet"), "users").unwrap();
+    fs::write(export.join("edges/Follows.parquet"), "follows").unwrap();
+
+    let location = remote(&store, "gs://bucket/runs/tables");
+    assert_eq!(location.upload(&export).await.unwrap(), 2);
+    assert!(
+        store
+            .head(&ObjectPath::from("runs/tables/nodes/User.parquet"))
+            .await
+            .is_ok()
+    );
+
+    let downloads = TempDir::new().unwrap();
+    let downloaded = location.download(downloads.path()).await.unwrap();
+    assert_eq!(downloaded, downloads.path().join("tables"));
+    assert_eq!(
+        fs::read_to_string(downloaded.join("edges/Follows.parquet")).unwrap(),
+        "follows"
+    );
+    assert_eq!(
+        fs::read_to_string(downloaded.join("nodes/User.parquet")).unwrap(),
+        "users"
+    );
+
+    let missing = remote(&store, "gs://bucket/runs/other");
+    let error = missing.download(downloads.path()).await.unwrap_err();
+    assert!(error.to_string().contains("nothing was found"));
+}
@@ -1,23 +1,49 @@
 // CLI test modules
 #[cfg(test)]
+pub mod bench_tests;
+#[cfg(test)]
 pub mod check_tests;
 #[cfg(test)]
 pub mod compile_tests;
 #[cfg(test)]
+pub mod data_tests;
+#[cfg(test)]
 pub mod docker_tests;
 #[cfg(test)]
+pub mod export_tests;
+#[cfg(test)]
+pub mod fsck_tests;
+#[cfg(test)]
+pub mod import_tests;
+#[cfg(test)]
 pub mod init_tests;
 #[cfg(test)]
 pub mod lifecycle_tests;
 #[cfg(test)]
+pub mod location_tests;
+#[cfg(test)]
+pub mod native_tests;
+#[cfg(test)]
+pub mod port_tests;
+#[cfg(test)]
+pub mod registry_tests;
+#[cfg(test)]
+pub mod reload_tests;
+#[cfg(test)]
+pub mod replay_tests;
+#[cfg(test)]
+pub mod seed_tests;
+#[cfg(test)]
+pub mod status_tests;
+#[cfg(test)]
 pub mod test_utils;
 #[cfg(test)]
+pub mod user_config_tests;
+#[cfg(test)]
 pub mod utility_tests;
 // #[cfg(test)]
 // pub mod build_tests;
 // #[cfg(test)]
 // pub mod start_stop_tests;
-// #[cfg(test)]
-// pub mod status_tests;
 #[cfg(test)]
 pub mod project_tests;
@@ -0,0 +1,123 @@
+use crate::config::HelixConfig;
+use crate::native::{NativeManager, log_tail, native_environment};
+use crate::project::ProjectContext;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn setup_test_project() -> (TempDir, ProjectContext) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_path = temp_dir.path().to_path_buf();
+
+    let config = HelixConfig::default_config("test-project");
+    config
+        .save_to_file(&project_path.join("helix.toml"))
+        .expect("Failed to save config");
+    fs::create_dir_all(project_path.join(".helix")).expect("Failed to create .helix");
+
+    let context = ProjectContext::find_and_load(Some(&project_path)).unwrap();
+    (temp_dir, context)
+}
+
+#[test]
+fn test_native_environment_points_at_host_paths() {
+    let env_vars = vec![
+        "HELIX_PORT=6969".to_string(),
+        "HELIX_DATA_DIR=/data".to_string(),
+        "HELIX_STANDBY_OF=http://host.docker.internal:6970".to_string(),
+        "OPENAI_API_KEY=sk-a=b".to_string(),
+    ];
+    let env = native_environment(&env_vars, Path::new("/project/.helix/.volumes/dev"));
+    assert_eq!(
+        env,
+        vec![
+            ("HELIX_PORT".to_string(), "6969".to_string()),
+            (
+                "HELIX_DATA_DIR".to_string(),
+                "/project/.helix/.volumes/dev".to_string()
+            ),
+            (
+                "HELIX_STANDBY_OF".to_string(),
+                "http://localhost:6970".to_string()
+            ),
+            ("OPENAI_API_KEY".to_string(), "sk-a=b".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_log_tail() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("helix.log");
+    fs::write(&log, "one\ntwo\nthree\n").unwrap();
+    assert_eq!(log_tail(&log, 2), "two\nthree");
+    assert_eq!(log_tail(&log, 10), "one\ntwo\nthree");
+    assert_eq!(log_tail(&temp_dir.path().join("missing.log"), 2), "");
+}
+
+#[test]
+fn test_instance_without_native_binary() {
+    let (_temp_dir, context) = setup_test_project();
+    let native = NativeManager::new(&context);
+    assert!(!native.is_native("dev"));
+    assert_eq!(native.running_pid("dev"), None);
+    assert!(!native.stop_instance("dev").unwrap());
+    assert!(native.start_instance("dev").is_err());
+}
+
+/// Install a shell script standing in for helix-container
+#[cfg(unix)]
+fn install_fake_binary(native: &NativeManager, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let binary = temp_dir.path().join("helix-container");
+    fs::write(&binary, script).unwrap();
+    fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+    native.install_binary("dev", &binary).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_native_instance_lifecycle() {
+    let (_temp_dir, context) = setup_test_project();
+    let native = NativeManager::new(&context);
+    install_fake_binary(
+        &native,
+        "#!/bin/sh\ntrap 'echo stopping; exit 0' TERM\necho \"serving $HELIX_DATA_DIR\"\nwhile true; do sleep 0.1; done\n",
+    );
+    assert!(native.is_native("dev"));
+
+    let pid = native.start_instance("dev").unwrap();
+    assert_eq!(native.running_pid("dev"), Some(pid));
+    // Starting a running instance is a no-op
+    assert_eq!(native.start_instance("dev").unwrap(), pid);
+    let data_dir = context.instance_volume("dev");
+    assert!(data_dir.is_dir());
+
+    assert!(native.stop_instance("dev").unwrap());
+    assert_eq!(native.running_pid("dev"), None);
+    assert!(!native.pid_path("dev").exists());
+    let log = fs::read_to_string(native.log_path("dev")).unwrap();
+    assert!(
+        log.contains(&format!("serving {}", data_dir.display())),
+        "{log}"
+    );
+    assert!(log.contains("stopping"), "{log}");
+
+    native.remove("dev").unwrap();
+    assert!(!native.is_native("dev"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_native_instance_exiting_on_startup_is_reported() {
+    let (_temp_dir, context) = setup_test_project();
+    let native = NativeManager::new(&context);
+    install_fake_binary(&native, "#!/bin/sh\necho 'port 6969 is taken'\nexit 3\n");
+
+    let error = native.start_instance("dev").unwrap_err().to_string();
+    assert!(error.contains("exited during startup"), "{error}");
+    assert!(error.contains("port 6969 is taken"), "{error}");
+    assert_eq!(native.running_pid("dev"), None);
+}
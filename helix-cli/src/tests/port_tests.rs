@@ -0,0 +1,126 @@
+use crate::port::{PortRegistry, assign_port, check_instance_port, release_port};
+use crate::tests::test_utils::TestContext;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A directory that looks like a project to the registry
+fn project_dir(root: &Path, name: &str) -> PathBuf {
+    let dir = root.join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("helix.toml"), "").unwrap();
+    dir.canonicalize().unwrap()
+}
+
+#[test]
+fn test_reserve_replaces_the_instance_port() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = project_dir(temp_dir.path(), "app");
+    let other = project_dir(temp_dir.path(), "other");
+
+    let mut registry = PortRegistry::default();
+    registry.reserve(7000, &app, "dev");
+    registry.reserve(7001, &app, "dev");
+
+    assert!(registry.held_by_other(7000, &other, "dev").is_none());
+    let owner = registry.held_by_other(7001, &other, "dev").unwrap();
+    assert_eq!(owner.project, app);
+    assert_eq!(owner.instance, "dev");
+    // An instance never collides with itself
+    assert!(registry.held_by_other(7001, &app, "dev").is_none());
+    assert!(registry.held_by_other(7001, &app, "staging").is_some());
+
+    registry.release(&app, "dev");
+    assert!(registry.held_by_other(7001, &other, "dev").is_none());
+}
+
+#[test]
+fn test_registry_round_trips_and_drops_removed_projects() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("home/ports.toml");
+    let kept = project_dir(temp_dir.path(), "kept");
+    let removed = project_dir(temp_dir.path(), "removed");
+
+    let mut registry = PortRegistry::default();
+    registry.reserve(7000, &kept, "dev");
+    registry.reserve(7001, &removed, "dev");
+    registry.save_to(&path).unwrap();
+    fs::remove_dir_all(&removed).unwrap();
+
+    let registry = PortRegistry::load_from(&path).unwrap();
+    let elsewhere = temp_dir.path().join("elsewhere");
+    assert!(registry.held_by_other(7000, &elsewhere, "dev").is_some());
+    assert!(registry.held_by_other(7001, &elsewhere, "dev").is_none());
+
+    // A missing registry is an empty one
+    let missing = PortRegistry::load_from(&temp_dir.path().join("missing.toml")).unwrap();
+    assert!(missing.held_by_other(7000, &elsewhere, "dev").is_none());
+}
+
+#[test]
+fn test_find_free_port_skips_reserved_and_bound_ports() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = project_dir(temp_dir.path(), "app");
+    let other = project_dir(temp_dir.path(), "other");
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let bound = listener.local_addr().unwrap().port();
+    let registry = PortRegistry::default();
+    assert_ne!(registry.find_free_port(bound, &app, "dev").unwrap(), bound);
+
+    let free = TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut registry = PortRegistry::default();
+    registry.reserve(free, &other, "dev");
+    assert_ne!(registry.find_free_port(free, &app, "dev").unwrap(), free);
+
+    // The instance's own reservation is free for it to take again
+    registry.release(&other, "dev");
+    registry.reserve(free, &app, "dev");
+    assert_eq!(registry.find_free_port(free, &app, "dev").unwrap(), free);
+}
+
+#[test]
+fn test_assign_port_gives_each_project_its_own_port() {
+    let ctx = TestContext::new();
+    let first = project_dir(&ctx.project_path, "first");
+    let second = project_dir(&ctx.project_path, "second");
+
+    let first_port = assign_port(&first, "dev").unwrap();
+    let second_port = assign_port(&second, "dev").unwrap();
+    assert_ne!(first_port, second_port);
+    // The first project's port stays reserved against the second
+    let registry = PortRegistry::load().unwrap();
+    assert!(registry.held_by_other(first_port, &second, "dev").is_some());
+
+    release_port(&first, "dev");
+    let registry = PortRegistry::load().unwrap();
+    assert!(registry.held_by_other(first_port, &second, "dev").is_none());
+}
+
+#[test]
+fn test_check_instance_port_names_the_owner_of_a_bound_port() {
+    let ctx = TestContext::new();
+    let app = project_dir(&ctx.project_path, "app");
+    let other = project_dir(&ctx.project_path, "other");
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let bound = listener.local_addr().unwrap().port();
+    PortRegistry::update(|registry| registry.reserve(bound, &other, "dev")).unwrap();
+
+    let error = check_instance_port(bound, &app, "dev")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!(
+        "port {bound} for instance 'dev' is already in use"
+    )));
+    assert!(error.contains("reserved by instance 'dev'"), "{error}");
+
+    // Reserved elsewhere but free only warns
+    drop(listener);
+    check_instance_port(bound, &app, "dev").unwrap();
+}
@@ -0,0 +1,156 @@
+use crate::commands::ps::{instance_running, ps_rows};
+use crate::config::{ContainerRuntime, HelixConfig};
+use crate::project::ProjectContext;
+use crate::registry::{InstanceRegistry, StartedInstance, forget_instance, record_start};
+use crate::tests::test_utils::TestContext;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn started(project: &Path, instance: &str, port: u16) -> StartedInstance {
+    StartedInstance {
+        project: project.to_path_buf(),
+        project_name: "app".to_string(),
+        instance: instance.to_string(),
+        port,
+        data_dir: project.join(".helix/.volumes").join(instance),
+        runtime: Some(ContainerRuntime::Docker),
+        container: Some(format!("helix-app-{instance}_app")),
+        pid: None,
+        started_at: 1_700_000_000_000,
+    }
+}
+
+#[test]
+fn test_record_replaces_the_instance_entry() {
+    let app = PathBuf::from("/projects/app");
+    let mut registry = InstanceRegistry::default();
+    registry.record(started(&app, "dev", 6969));
+    registry.record(started(&app, "staging", 6970));
+    registry.record(started(&app, "dev", 7000));
+
+    let ports = registry
+        .instances()
+        .iter()
+        .map(|i| (i.instance.as_str(), i.port))
+        .collect::<Vec<_>>();
+    assert_eq!(ports, [("dev", 7000), ("staging", 6970)]);
+
+    registry.forget(&app, "dev");
+    assert_eq!(registry.instances().len(), 1);
+    assert!(registry.retain(|i| i.instance != "staging"));
+    assert!(!registry.retain(|_| true));
+}
+
+#[test]
+fn test_registry_keeps_instances_of_removed_projects() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("home/instances.toml");
+    let removed = temp_dir.path().join("removed");
+
+    let mut registry = InstanceRegistry::default();
+    registry.record(started(&removed, "dev", 6969));
+    registry.record(StartedInstance {
+        runtime: None,
+        container: None,
+        pid: Some(4242),
+        ..started(&removed, "native", 6970)
+    });
+    registry.save_to(&path).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("runtime = \"docker\""), "{saved}");
+
+    let loaded = InstanceRegistry::load_from(&path).unwrap();
+    assert_eq!(loaded.instances(), registry.instances());
+    assert!(!loaded.instances()[0].project_exists());
+
+    let missing = InstanceRegistry::load_from(&temp_dir.path().join("missing.toml")).unwrap();
+    assert!(missing.instances().is_empty());
+}
+
+#[test]
+fn test_concurrent_updates_keep_every_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("home/instances.toml");
+    let app = temp_dir.path().join("app");
+
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            let (path, app) = (&path, &app);
+            scope.spawn(move || {
+                InstanceRegistry::update_at(path, |registry| {
+                    registry.record(started(app, &format!("dev-{i}"), 7000 + i))
+                })
+                .unwrap();
+            });
+        }
+    });
+
+    let registry = InstanceRegistry::load_from(&path).unwrap();
+    assert_eq!(registry.instances().len(), 8);
+    // Only the registry and its lock are left behind, no temporary files
+    let mut files = fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, ["instances.toml", "instances.toml.lock"]);
+}
+
+#[test]
+fn test_record_start_and_forget() {
+    let ctx = TestContext::new();
+    HelixConfig::default_config("app")
+        .save_to_file(&ctx.project_path.join("helix.toml"))
+        .unwrap();
+    let project = ProjectContext::find_and_load(Some(&ctx.project_path)).unwrap();
+
+    record_start(&project, "dev", None, Some(4242));
+    let registry = InstanceRegistry::load().unwrap();
+    let [entry] = registry.instances() else {
+        panic!("expected one entry: {:?}", registry.instances());
+    };
+    assert_eq!(entry.project, project.root.canonicalize().unwrap());
+    assert_eq!(entry.project_name, "app");
+    assert_eq!(entry.pid, Some(4242));
+    assert_eq!(entry.runtime, None);
+    assert!(entry.data_dir.ends_with(".volumes/dev"));
+
+    forget_instance(&project.root, "dev");
+    assert!(InstanceRegistry::load().unwrap().instances().is_empty());
+}
+
+#[test]
+fn test_ps_lists_running_instances_and_flags_missing_projects() {
+    let temp_dir = TempDir::new().unwrap();
+    let kept = temp_dir.path().join("kept");
+    fs::create_dir_all(&kept).unwrap();
+    fs::write(kept.join("helix.toml"), "").unwrap();
+    let removed = temp_dir.path().join("removed");
+
+    let running_containers = HashSet::from(["helix-app-dev_app".to_string()]);
+    let dev = started(&kept, "dev", 6969);
+    let stopped = started(&kept, "staging", 6970);
+    let orphan = started(&removed, "dev", 6971);
+    assert!(instance_running(&dev, &running_containers));
+    assert!(!instance_running(&stopped, &running_containers));
+    let no_process = StartedInstance {
+        container: None,
+        pid: None,
+        ..stopped.clone()
+    };
+    assert!(!instance_running(&no_process, &running_containers));
+
+    let rows = ps_rows(&[&dev, &orphan]);
+    assert_eq!(rows[0][..4], ["app", "dev", "6969", "helix-app-dev_app"]);
+    assert_eq!(rows[0][5], kept.display().to_string());
+    assert_eq!(rows[1][5], format!("{} (missing)", removed.display()));
+
+    let native = StartedInstance {
+        container: None,
+        pid: Some(4242),
+        ..dev
+    };
+    assert_eq!(ps_rows(&[&native])[0][3], "pid 4242");
+}
@@ -0,0 +1,69 @@
+use crate::commands::reload::{send, tunables};
+use crate::config::{GatewayConfig, RateLimit, RateLimitConfig};
+use axum::Router;
+use axum::http::StatusCode;
+use axum::routing::post;
+use helix_db::helix_gateway::reload::Tunables;
+use std::collections::HashMap;
+
+/// Serve an `/admin/reload` that answers with `status` and the tunables it was sent
+async fn mock_instance(status: StatusCode) -> String {
+    let app = Router::new().route(
+        "/admin/reload",
+        post(
+            move |axum::Json(tunables): axum::Json<Tunables>| async move {
+                (status, axum::Json(tunables))
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+#[test]
+fn test_tunables_are_taken_from_gateway_config() {
+    let config = GatewayConfig {
+        log_level: Some("info,helix_db=debug".to_string()),
+        slow_query_ms: Some(250),
+        queue_capacity: Some(4),
+        rate_limit: Some(RateLimitConfig {
+            global: None,
+            routes: Some(HashMap::from([(
+                "getUser".to_string(),
+                RateLimit {
+                    requests_per_sec: 10,
+                    burst: Some(20),
+                },
+            )])),
+            trust_forwarded_for: Some(true),
+        }),
+        ..Default::default()
+    };
+    let tunables = tunables(&config).unwrap();
+
+    assert_eq!(tunables.log_level.as_deref(), Some("info,helix_db=debug"));
+    assert_eq!(tunables.slow_query_ms, Some(250));
+    assert_eq!(tunables.cache_max_entries, None);
+    let rate_limit = tunables.rate_limit.unwrap();
+    assert!(rate_limit.trust_forwarded_for());
+    assert_eq!(rate_limit.routes.unwrap()["getUser"].burst(), 20);
+}
+
+#[tokio::test]
+async fn test_send_returns_the_effective_tunables() {
+    let url = mock_instance(StatusCode::OK).await;
+    let tunables = Tunables {
+        slow_query_ms: Some(100),
+        ..Default::default()
+    };
+    assert_eq!(send(&url, &tunables).await.unwrap(), tunables);
+}
+
+#[tokio::test]
+async fn test_send_reports_rejected_tunables() {
+    let url = mock_instance(StatusCode::BAD_REQUEST).await;
+    let error = send(&url, &Tunables::default()).await.unwrap_err();
+    assert!(error.to_string().contains("400"));
+}
@@ -0,0 +1,109 @@
+use crate::commands::replay::{LoggedQuery, parse_log, parse_speed, percentile_of_sorted, replay};
+use axum::Router;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::post;
+use std::time::Duration;
+
+#[test]
+fn test_parse_log_reads_names_params_and_timestamps() {
+    let log = r#"{"query": "getUser", "params": {"id": "u1"}, "timestamp": "2026-01-15T10:00:01Z"}
+
+{"name": "listPosts", "timestamp_ms": 1768471200000}
+"#;
+    let queries = parse_log(log).expect("log should parse");
+
+    assert_eq!(
+        queries,
+        vec![
+            LoggedQuery {
+                query: "listPosts".to_string(),
+                params: serde_json::json!({}),
+                timestamp_ms: 1_768_471_200_000,
+            },
+            LoggedQuery {
+                query: "getUser".to_string(),
+                params: serde_json::json!({"id": "u1"}),
+                timestamp_ms: 1_768_471_201_000,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_log_reports_bad_lines() {
+    let error = parse_log("{\"query\": \"getUser\", \"timestamp\": 1}\n{\"query\": 3}\n")
+        .expect_err("second line is invalid");
+    assert!(error.to_string().contains("line 2"), "{error}");
+
+    let error = parse_log(r#"{"query": "getUser", "timestamp": "yesterday"}"#)
+        .expect_err("timestamp is invalid");
+    assert!(error.to_string().contains("yesterday"), "{error}");
+}
+
+#[test]
+fn test_parse_speed() {
+    assert_eq!(parse_speed("2x").unwrap(), Some(2.0));
+    assert_eq!(parse_speed("0.5X").unwrap(), Some(0.5));
+    assert_eq!(parse_speed("1").unwrap(), Some(1.0));
+    assert_eq!(parse_speed("max").unwrap(), None);
+    assert!(parse_speed("0x").is_err());
+    assert!(parse_speed("fast").is_err());
+}
+
+#[test]
+fn test_percentile_of_sorted() {
+    let samples = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+    assert_eq!(
+        percentile_of_sorted(&samples, 50.0),
+        Duration::from_millis(50)
+    );
+    assert_eq!(
+        percentile_of_sorted(&samples, 99.0),
+        Duration::from_millis(99)
+    );
+    assert_eq!(
+        percentile_of_sorted(&samples, 100.0),
+        Duration::from_millis(100)
+    );
+    assert_eq!(percentile_of_sorted(&[], 50.0), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_replay_sends_queries_at_scaled_gaps() {
+    let app = Router::new().route(
+        "/{query}",
+        post(|Path(query): Path<String>| async move {
+            match query.as_str() {
+                "missing" => StatusCode::NOT_FOUND,
+                _ => StatusCode::OK,
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let logged = |query: &str, timestamp_ms| LoggedQuery {
+        query: query.to_string(),
+        params: serde_json::json!({}),
+        timestamp_ms,
+    };
+    let queries = vec![
+        logged("getUser", 0),
+        logged("getUser", 200),
+        logged("missing", 400),
+    ];
+
+    let report = replay(&queries, &url, Some(2.0), 4, None)
+        .await
+        .expect("replay should run");
+
+    assert_eq!(report.requests(), 3);
+    assert_eq!(report.queries["getUser"].requests, 2);
+    assert_eq!(report.queries["getUser"].errors, 0);
+    assert_eq!(report.queries["missing"].errors, 1);
+    assert!(report.first_error.unwrap().starts_with("missing"));
+    // The last query was logged 400ms after the first, so at 2x it's sent after 200ms
+    assert!(report.elapsed >= Duration::from_millis(200));
+}
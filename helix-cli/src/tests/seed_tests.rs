@@ -0,0 +1,172 @@
+use crate::commands::seed::{
+    SeedCall, SeedValue, parse_hql, parse_ndjson, resolve, seed, seed_files,
+};
+use axum::Json;
+use axum::Router;
+use axum::extract::Path;
+use axum::routing::post;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+#[test]
+fn test_parse_hql_reads_calls_bindings_and_references() {
+    let text = r#"
+// people
+alice <- createUser({name: "Alice \"Al\"", age: 30, tags: ["a", "b",],})
+follow({"from": alice.user.id, to: bob.users.0.id, weight: -1.5, active: true});
+ping()
+"#;
+    let calls = parse_hql(text).expect("fixture should parse");
+
+    assert_eq!(calls.len(), 3);
+    assert_eq!(calls[0].bind.as_deref(), Some("alice"));
+    assert_eq!(calls[0].query, "createUser");
+    assert_eq!(calls[0].line, 3);
+    assert_eq!(
+        resolve(&calls[0].params, &HashMap::new()).unwrap(),
+        serde_json::json!({"name": "Alice \"Al\"", "age": 30, "tags": ["a", "b"]})
+    );
+    assert_eq!(calls[1].bind, None);
+    assert_eq!(
+        calls[1].params,
+        SeedValue::Object(vec![
+            (
+                "from".to_string(),
+                SeedValue::Ref {
+                    binding: "alice".to_string(),
+                    path: vec!["user".to_string(), "id".to_string()],
+                },
+            ),
+            (
+                "to".to_string(),
+                SeedValue::Ref {
+                    binding: "bob".to_string(),
+                    path: vec!["users".to_string(), "0".to_string(), "id".to_string()],
+                },
+            ),
+            (
+                "weight".to_string(),
+                SeedValue::Json(serde_json::json!(-1.5))
+            ),
+            (
+                "active".to_string(),
+                SeedValue::Json(serde_json::json!(true))
+            ),
+        ])
+    );
+    assert_eq!(calls[2].params, SeedValue::Object(Vec::new()));
+}
+
+#[test]
+fn test_parse_hql_reports_the_line_of_a_mistake() {
+    let error =
+        parse_hql("ping()\n\ncreateUser({name: \"Alice\" age: 30})\n").expect_err("missing comma");
+    assert!(
+        error.to_string().starts_with("3: expected ',' or '}'"),
+        "{error}"
+    );
+
+    let error = parse_hql("createUser({name: \"Alice\"}").expect_err("unclosed call");
+    assert!(error.to_string().contains("end of the file"), "{error}");
+}
+
+#[test]
+fn test_parse_ndjson_reads_references() {
+    let text = r#"{"query": "createUser", "params": {"name": "$5 plan"}, "as": "alice"}
+
+{"name": "follow", "params": {"from": "$alice.user.id"}}
+"#;
+    let calls = parse_ndjson(text).expect("fixture should parse");
+
+    assert_eq!(
+        calls,
+        vec![
+            SeedCall {
+                query: "createUser".to_string(),
+                params: SeedValue::Object(vec![(
+                    "name".to_string(),
+                    SeedValue::Json(serde_json::json!("$5 plan")),
+                )]),
+                bind: Some("alice".to_string()),
+                line: 1,
+            },
+            SeedCall {
+                query: "follow".to_string(),
+                params: SeedValue::Object(vec![(
+                    "from".to_string(),
+                    SeedValue::Ref {
+                        binding: "alice".to_string(),
+                        path: vec!["user".to_string(), "id".to_string()],
+                    },
+                )]),
+                bind: None,
+                line: 3,
+            },
+        ]
+    );
+    assert!(parse_ndjson("{\"params\": {}}").is_err());
+}
+
+#[test]
+fn test_seed_files_are_loaded_in_name_order() {
+    let dir = TempDir::new().unwrap();
+    for name in [
+        "02_follows.hql",
+        "01_users.ndjson",
+        "03_posts.jsonl",
+        "README.md",
+    ] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    let names = seed_files(dir.path())
+        .unwrap()
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["01_users.ndjson", "02_follows.hql", "03_posts.jsonl"]
+    );
+    assert!(seed_files(&dir.path().join("missing")).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_seed_passes_bound_responses_to_later_calls() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&received);
+    let app = Router::new().route(
+        "/{query}",
+        post(
+            move |Path(query): Path<String>, Json(params): Json<serde_json::Value>| async move {
+                log.lock().unwrap().push((query.clone(), params.clone()));
+                Json(serde_json::json!({"user": {"id": format!("id-{}", params["name"])}}))
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let calls = parse_hql(
+        "alice <- createUser({name: \"alice\"})\nfollow({name: \"x\", from: alice.user.id})\n",
+    )
+    .unwrap();
+    let client = reqwest::Client::new();
+    let mut bindings = HashMap::new();
+    let sent = seed(&client, &calls, &url, None, &mut bindings)
+        .await
+        .expect("seed should run");
+
+    assert_eq!(sent, 2);
+    let received = received.lock().unwrap();
+    assert_eq!(received[1].0, "follow");
+    assert_eq!(received[1].1["from"], "id-\"alice\"");
+
+    let unbound = parse_hql("follow({from: bob.user.id})").unwrap();
+    let error = seed(&client, &unbound, &url, None, &mut bindings)
+        .await
+        .expect_err("bob isn't bound");
+    assert!(error.to_string().contains("'bob' isn't bound"), "{error}");
+}
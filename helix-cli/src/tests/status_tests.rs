@@ -0,0 +1,160 @@
+use crate::commands::status::{format_bytes, format_table, health_row, probe_instance};
+use axum::Router;
+use axum::http::StatusCode;
+use axum::routing::get;
+use serde_json::json;
+
+/// Serve `/healthz` and a `/readyz` reporting `ready`, with `/admin/stats` when `stats` is set
+async fn mock_instance(ready: bool, stats: Option<serde_json::Value>) -> String {
+    let readiness = json!({ "ready": ready, "storage": true, "workers": ready, "schema": true });
+    let status = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let mut app = Router::new()
+        .route(
+            "/healthz",
+            get(|| async { axum::Json(json!({ "status": "ok" })) }),
+        )
+        .route(
+            "/readyz",
+            get(move || async move { (status, axum::Json(readiness)) }),
+        );
+    if let Some(stats) = stats {
+        app = app.route(
+            "/admin/stats",
+            get(move || async move { axum::Json(stats) }),
+        );
+    }
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url
+}
+
+fn stats() -> serde_json::Value {
+    json!({
+        "version": "1.2.7",
+        "uptime_secs": 90,
+        "schema_versions": { "User": 2 },
+        "workers": {
+            "workers": 4,
+            "queue_capacity": 100,
+            "read_queue_depth": 0,
+            "batch_queue_depth": 0,
+            "write_queue_depth": 0,
+            "in_flight": 0,
+            "shed_total": 0,
+            "cache_entries": 0,
+            "cache_hits": 0,
+            "cache_misses": 0
+        },
+        "data_size_bytes": 3 * 1024 * 1024,
+        "routes": []
+    })
+}
+
+#[tokio::test]
+async fn test_probe_reports_a_ready_instance_healthy() {
+    let url = mock_instance(true, Some(stats())).await;
+    let probe = probe_instance(&url).await;
+
+    assert!(probe.healthy(), "{:?}", probe.problem);
+    assert!(probe.latency.is_some());
+    let row = health_row("dev", &probe, None);
+    assert_eq!(row[0], "dev");
+    assert_eq!(row[1], "healthy");
+    assert_eq!(row[3..], ["1.2.7", "User v2", "0h 1m 30s", "3.0 MiB", "-"]);
+}
+
+#[tokio::test]
+async fn test_probe_lists_failing_readiness_checks() {
+    let url = mock_instance(false, Some(stats())).await;
+    let probe = probe_instance(&url).await;
+
+    assert!(!probe.healthy());
+    assert_eq!(probe.problem.as_deref(), Some("not ready (workers)"));
+    assert!(probe.stats.is_none());
+}
+
+#[tokio::test]
+async fn test_probe_reports_incompatible_data() {
+    let readiness = json!({
+        "ready": false,
+        "storage": true,
+        "workers": true,
+        "schema": true,
+        "schema_mismatch": { "reasons": ["`User` is stored at schema version 2"] }
+    });
+    let app = Router::new()
+        .route(
+            "/healthz",
+            get(|| async { axum::Json(json!({ "status": "ok" })) }),
+        )
+        .route(
+            "/readyz",
+            get(move || async move { (StatusCode::SERVICE_UNAVAILABLE, axum::Json(readiness)) }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let probe = probe_instance(&url).await;
+    assert_eq!(
+        probe.problem.as_deref(),
+        Some("not ready (incompatible data: `User` is stored at schema version 2)")
+    );
+}
+
+#[tokio::test]
+async fn test_probe_without_admin_access_is_still_healthy() {
+    let url = mock_instance(true, None).await;
+    let probe = probe_instance(&url).await;
+
+    assert!(probe.healthy());
+    assert!(probe.stats.is_none());
+    assert_eq!(health_row("dev", &probe, None)[3], "-");
+}
+
+#[tokio::test]
+async fn test_probe_reports_an_unreachable_instance() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let probe = probe_instance(&format!("http://127.0.0.1:{port}")).await;
+
+    assert!(!probe.healthy());
+    assert!(probe.problem.unwrap().starts_with("unreachable"));
+    assert!(probe.latency.is_none());
+}
+
+#[test]
+fn test_format_table_pads_columns() {
+    let rows = vec![
+        vec![
+            "dev".to_string(),
+            "healthy".to_string(),
+            "1.2ms".to_string(),
+        ],
+        vec![
+            "staging".to_string(),
+            "not deployed".to_string(),
+            "-".to_string(),
+        ],
+    ];
+    assert_eq!(
+        format_table(&["INSTANCE", "HEALTH", "LATENCY"], &rows),
+        "INSTANCE  HEALTH        LATENCY\n\
+         dev       healthy       1.2ms\n\
+         staging   not deployed  -"
+    );
+}
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.0 GiB");
+}
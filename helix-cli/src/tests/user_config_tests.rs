@@ -0,0 +1,75 @@
+use crate::metrics_sender::MetricsLevel;
+use crate::output::Verbosity;
+use crate::user_config::{DEFAULT_REGION, DEFAULT_TEMPLATE, UpdateChannel, UserConfig};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_set_round_trips_through_the_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+
+    let mut config = UserConfig::default();
+    config.set("region", "eu-west-1").unwrap();
+    config.set("telemetry", "off").unwrap();
+    config.set("update_channel", "prerelease").unwrap();
+    config.set("output", "quiet").unwrap();
+    config.set("template", "rag").unwrap();
+    config.save_to(&path).unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("update_channel = \"prerelease\""));
+
+    let loaded = UserConfig::load_from(&path).unwrap();
+    assert_eq!(loaded, config);
+    assert_eq!(loaded.telemetry, Some(MetricsLevel::Off));
+    assert_eq!(loaded.update_channel(), UpdateChannel::Prerelease);
+    assert_eq!(loaded.get("output").unwrap().as_deref(), Some("quiet"));
+    assert_eq!(loaded.region(), "eu-west-1");
+}
+
+#[test]
+fn test_unset_settings_fall_back_to_defaults() {
+    let dir = TempDir::new().unwrap();
+    let mut config = UserConfig::load_from(&dir.path().join("config.toml")).unwrap();
+    assert_eq!(config, UserConfig::default());
+    assert_eq!(config.region(), DEFAULT_REGION);
+    assert_eq!(config.template(), DEFAULT_TEMPLATE);
+    assert_eq!(config.update_channel(), UpdateChannel::Stable);
+    assert_eq!(config.get("telemetry").unwrap(), None);
+
+    config.set("template", "rag").unwrap();
+    config.unset("template").unwrap();
+    assert_eq!(config.template(), DEFAULT_TEMPLATE);
+}
+
+#[test]
+fn test_rejects_unknown_keys_and_invalid_values() {
+    let mut config = UserConfig::default();
+    assert!(config.set("colour", "never").is_err());
+    assert!(config.get("colour").is_err());
+    assert!(config.unset("colour").is_err());
+    assert!(config.set("telemetry", "some").is_err());
+    assert!(config.set("output", "loud").is_err());
+    assert!(config.set("region", " ").is_err());
+    assert_eq!(config, UserConfig::default());
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "colour = \"never\"\n").unwrap();
+    assert!(UserConfig::load_from(&path).is_err());
+}
+
+#[test]
+fn test_flags_override_configured_output() {
+    let config = UserConfig {
+        output: Some(Verbosity::Verbose),
+        ..UserConfig::default()
+    };
+    assert_eq!(config.verbosity(false, false), Verbosity::Verbose);
+    assert_eq!(config.verbosity(true, false), Verbosity::Quiet);
+    assert_eq!(
+        UserConfig::default().verbosity(false, false),
+        Verbosity::Normal
+    );
+}
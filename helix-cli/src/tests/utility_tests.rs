@@ -87,6 +87,51 @@ async fn test_add_rejects_duplicate_instance_name() {
     );
 }
 
+#[tokio::test]
+async fn test_add_standby_of_copies_leader_settings() {
+    use crate::CloudDeploymentTypeCommand;
+    use crate::commands::add;
+
+    let ctx = TestContext::new();
+    ctx.setup_valid_project();
+
+    let _guard = std::env::set_current_dir(&ctx.project_path);
+
+    let result = add::run(Some(CloudDeploymentTypeCommand::StandbyOf {
+        instance: "dev".to_string(),
+        name: None,
+    }))
+    .await;
+    assert!(
+        result.is_ok(),
+        "Add standby should succeed: {:?}",
+        result.err()
+    );
+
+    let config = HelixConfig::from_file(&ctx.project_path.join("helix.toml"))
+        .expect("Config should stay valid");
+    let leader = config.local.get("dev").unwrap();
+    let standby = config
+        .local
+        .get("dev-standby")
+        .expect("Standby should be named after its leader");
+    assert_eq!(standby.standby_of.as_deref(), Some("dev"));
+    assert_ne!(standby.port, leader.port);
+    assert_eq!(standby.build_mode, leader.build_mode);
+
+    // A standby can't be followed in turn
+    let result = add::run(Some(CloudDeploymentTypeCommand::StandbyOf {
+        instance: "dev-standby".to_string(),
+        name: Some("chained".to_string()),
+    }))
+    .await;
+    let error_msg = format!("{:?}", result.err().expect("Chained standby should fail"));
+    assert!(
+        error_msg.contains("itself a standby"),
+        "Error should mention the standby: {error_msg}"
+    );
+}
+
 #[tokio::test]
 async fn test_add_requires_deployment_type_in_non_interactive() {
     use crate::commands::add;
@@ -410,6 +455,32 @@ port = 6969
     }
 }
 
+#[test]
+fn test_config_validates_standby_of_names_another_local_instance() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("helix.toml");
+
+    for leader in ["dev", "missing"] {
+        let config_content = format!(
+            r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+standby_of = "{leader}"
+"#
+        );
+        fs::write(&path, config_content).unwrap();
+        let result = HelixConfig::from_file(&path);
+        assert!(
+            result.is_err(),
+            "standby_of = \"{leader}\" should be rejected"
+        );
+    }
+}
+
 #[test]
 fn test_config_validates_build_mode_debug_is_rejected() {
     // BuildMode::Debug should be rejected when loading from file
@@ -434,6 +505,550 @@ build_mode = "debug"
     );
 }
 
+#[test]
+fn test_config_gateway_section_reaches_legacy_json() {
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config]
+queue_capacity = 250
+shed_on_overload = false
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let instance = config.get_instance("dev").expect("dev instance");
+    let json = instance.to_legacy_json();
+
+    assert_eq!(json["gateway_config"]["queue_capacity"], 250);
+    assert_eq!(json["gateway_config"]["shed_on_overload"], false);
+    assert!(json["gateway_config"].get("workers_per_core").is_none());
+
+    // Default gateway settings are left out of the generated config entirely
+    let default_config = HelixConfig::default_config("my-project");
+    let default_json = default_config.get_instance("dev").unwrap().to_legacy_json();
+    assert!(default_json.get("gateway_config").is_none());
+}
+
+#[test]
+fn test_config_jwt_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.jwt]
+jwks_url = "https://id.example.com/.well-known/jwks.json"
+audience = "helix"
+roles_claim = "realm_access.roles"
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let jwt = gateway_config.jwt.expect("jwt config");
+    assert_eq!(jwt.jwks_url, "https://id.example.com/.well-known/jwks.json");
+    assert_eq!(jwt.audience.as_deref(), Some("helix"));
+    assert_eq!(jwt.issuer, None);
+    assert_eq!(jwt.roles_claim(), "realm_access.roles");
+}
+
+#[test]
+fn test_config_warm_up_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{GatewayConfig, WarmUpMode};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.warm_up]
+mode = "deferred"
+prefault = true
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let warm_up = gateway_config.warm_up();
+    assert_eq!(warm_up.mode(), WarmUpMode::Deferred);
+    assert!(warm_up.prefault());
+}
+
+#[test]
+fn test_config_bm25_merge_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.bm25_merge]
+merge_factor = 4
+interval_ms = 0
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let merge = gateway_config.bm25_merge();
+    assert_eq!(merge.merge_factor(), 4);
+    assert_eq!(merge.max_deleted_percent(), 25);
+    assert_eq!(merge.interval(), None);
+}
+
+#[test]
+fn test_config_text_search_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.text_search]
+synonyms = ["car, automobile", "k8s => kubernetes"]
+synonym_files = ["/data/synonyms.txt"]
+dictionary = ["c++"]
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let text_search = gateway_config.text_search();
+    assert_eq!(text_search.synonyms.unwrap().len(), 2);
+    assert_eq!(
+        text_search.synonym_files.unwrap(),
+        vec![std::path::PathBuf::from("/data/synonyms.txt")]
+    );
+    assert_eq!(text_search.dictionary.unwrap(), vec!["c++".to_string()]);
+}
+
+#[test]
+fn test_config_ego_cache_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{EgoCacheConfig, GatewayConfig};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[[local.dev.gateway_config.ego_cache]]
+edge = "Follows"
+
+[[local.dev.gateway_config.ego_cache]]
+edge = "Likes"
+max_depth = 3
+max_entries = 500
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let ego_cache = gateway_config.ego_cache();
+    assert_eq!(ego_cache.len(), 2);
+    assert_eq!(ego_cache[0].edge, "Follows");
+    assert_eq!(ego_cache[0].max_depth(), EgoCacheConfig::DEFAULT_MAX_DEPTH);
+    assert_eq!(ego_cache[1].max_depth(), 3);
+    assert_eq!(ego_cache[1].max_entries(), 500);
+}
+
+#[test]
+fn test_config_sessions_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{GatewayConfig, SessionConfig};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.sessions]
+idle_timeout_secs = 120
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let sessions = gateway_config.sessions.unwrap();
+    assert_eq!(sessions.max_sessions(), SessionConfig::DEFAULT_MAX_SESSIONS);
+    assert_eq!(sessions.idle_timeout().as_secs(), 120);
+}
+
+#[test]
+fn test_config_max_request_bytes_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config]
+max_request_bytes = 1048576
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    assert_eq!(gateway_config.max_request_bytes(), 1024 * 1024);
+    assert_eq!(
+        GatewayConfig::default().max_request_bytes(),
+        GatewayConfig::DEFAULT_MAX_REQUEST_BYTES
+    );
+}
+
+#[test]
+fn test_config_ivf_flat_section_reaches_vector_config() {
+    use helix_db::helix_engine::traversal_core::config::VectorConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.vector_config.ivf_flat.Embedding]
+nlist = 4096
+nprobe = 48
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let vector_config: VectorConfig =
+        serde_json::from_value(json["vector_config"].clone()).unwrap();
+    assert_eq!(vector_config.m, Some(16));
+    let ivf_flat = vector_config.ivf_flat.expect("ivf-flat config");
+    assert_eq!(ivf_flat["Embedding"].nlist(), 4096);
+    assert_eq!(ivf_flat["Embedding"].nprobe(), 48);
+}
+
+#[test]
+fn test_config_rate_limit_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.rate_limit]
+global = { requests_per_sec = 100, burst = 200 }
+routes = { bulkImport = { requests_per_sec = 1 } }
+trust_forwarded_for = true
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let rate_limit = gateway_config.rate_limit.expect("rate limit config");
+    let global = rate_limit.global.expect("global limit");
+    assert_eq!(global.requests_per_sec(), 100);
+    assert_eq!(global.burst(), 200);
+    assert!(rate_limit.trust_forwarded_for());
+    assert_eq!(rate_limit.routes.unwrap()["bulkImport"].burst(), 1);
+}
+
+#[test]
+fn test_config_tls_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{AcmeConfig, GatewayConfig};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.tls]
+client_ca_path = "/etc/helix/clients.pem"
+
+[local.dev.gateway_config.tls.acme]
+domains = ["db.example.com"]
+contact = ["mailto:ops@example.com"]
+cache_dir = "/var/lib/helix/acme"
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let tls = gateway_config.tls.expect("tls config");
+    assert_eq!(tls.cert_path, None);
+    assert_eq!(
+        tls.client_ca_path.as_deref(),
+        Some(std::path::Path::new("/etc/helix/clients.pem"))
+    );
+    let acme = tls.acme.expect("acme config");
+    assert_eq!(acme.domains, vec!["db.example.com".to_string()]);
+    assert_eq!(acme.directory_url(), AcmeConfig::DEFAULT_DIRECTORY_URL);
+}
+
+#[test]
+fn test_config_cors_section_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.cors]
+allowed_origins = ["https://app.example.com"]
+allow_credentials = true
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let cors = gateway_config.cors.expect("cors config");
+    assert_eq!(
+        cors.allowed_origins,
+        vec!["https://app.example.com".to_string()]
+    );
+    assert!(cors.allow_credentials());
+    assert_eq!(cors.allowed_methods, None);
+}
+
+#[test]
+fn test_config_udfs_reach_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{GatewayConfig, UdfConfig};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[[local.dev.gateway_config.udfs]]
+name = "boost"
+path = "/data/udfs/scoring.wasm"
+export = "boost_score"
+fuel = 1000
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let udfs = gateway_config.udfs.expect("udfs");
+    assert_eq!(udfs.len(), 1);
+    assert_eq!(udfs[0].name, "boost");
+    assert_eq!(udfs[0].export(), "boost_score");
+    assert_eq!(udfs[0].fuel(), 1000);
+    assert_eq!(
+        udfs[0].max_memory_bytes(),
+        UdfConfig::DEFAULT_MAX_MEMORY_BYTES
+    );
+}
+
+#[test]
+fn test_config_schedules_reach_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::Config;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[[local.dev.gateway_config.schedules]]
+name = "nightly-rollup"
+query = "RollupDaily"
+cron = "0 3 * * *"
+params = { days = 1 }
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    // Parsed the way the container reads config.hx.json, since params are kept as sonic JSON
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.hx.json");
+    fs::write(&config_path, json.to_string()).unwrap();
+    let config = Config::from_file(config_path).unwrap();
+    let schedules = config.gateway_config().schedules.expect("schedules");
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].name, "nightly-rollup");
+    assert_eq!(schedules[0].query, "RollupDaily");
+    assert_eq!(schedules[0].cron, "0 3 * * *");
+    let params = schedules[0].params.as_ref().expect("params");
+    assert_eq!(
+        serde_json::to_value(params).unwrap(),
+        serde_json::json!({ "days": 1 })
+    );
+}
+
+#[test]
+fn test_config_kafka_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::{GatewayConfig, KafkaStartOffset};
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config]
+audit_log = true
+
+[local.dev.gateway_config.kafka]
+brokers = ["kafka:9092"]
+ingest = { topic = "mutations", partitions = [0, 1], start_offset = "latest" }
+cdc = { topic = "helix-changes" }
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let kafka = gateway_config.kafka.expect("kafka");
+    assert_eq!(kafka.brokers, vec!["kafka:9092"]);
+    assert_eq!(kafka.client_id(), "helix-db");
+    let ingest = kafka.ingest.expect("ingest");
+    assert_eq!(ingest.topic, "mutations");
+    assert_eq!(ingest.partitions(), [0, 1]);
+    assert_eq!(ingest.start_offset(), KafkaStartOffset::Latest);
+    let cdc = kafka.cdc.expect("cdc");
+    assert_eq!((cdc.topic.as_str(), cdc.partition()), ("helix-changes", 0));
+}
+
+#[test]
+fn test_config_postgres_sync_reaches_gateway_config() {
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+
+    let config_content = r#"
+[project]
+name = "test"
+queries = "./db/"
+
+[local.dev]
+port = 6969
+
+[local.dev.gateway_config.postgres_sync]
+url = "postgres://helix@localhost/app"
+tables = [
+    { table = "public.users", label = "User", columns = ["name", "email"] },
+    { table = "orders", label = "Order", key = "order_id", updated_at = "modified" },
+]
+"#;
+
+    let config: HelixConfig = toml::from_str(config_content).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let sync = gateway_config.postgres_sync.expect("postgres_sync");
+    assert_eq!(sync.url, "postgres://helix@localhost/app");
+    assert_eq!(sync.poll_interval_secs(), 5);
+    let (users, orders) = (&sync.tables[0], &sync.tables[1]);
+    assert_eq!((users.key(), users.updated_at()), ("id", "updated_at"));
+    assert_eq!(
+        users.columns.as_deref(),
+        Some(&["name".to_string(), "email".to_string()][..])
+    );
+    assert_eq!(
+        (orders.key(), orders.updated_at()),
+        ("order_id", "modified")
+    );
+    assert_eq!(orders.columns, None);
+}
+
+#[test]
+fn test_create_key_stores_only_hash() {
+    use crate::commands::auth::add_api_key;
+    use crate::config::ApiKeyScope;
+    use helix_db::helix_engine::traversal_core::config::GatewayConfig;
+    use helix_db::helix_gateway::api_keys::ApiKeys;
+
+    let mut config = HelixConfig::default_config("my-project");
+    let writer = add_api_key(&mut config, "dev", None, ApiKeyScope::ReadWrite).unwrap();
+    let reader = add_api_key(
+        &mut config,
+        "dev",
+        Some("dashboard".to_string()),
+        ApiKeyScope::ReadOnly,
+    )
+    .unwrap();
+    assert_eq!(writer.name, "key-1");
+    assert_eq!(reader.name, "dashboard");
+    assert!(
+        add_api_key(
+            &mut config,
+            "dev",
+            Some("dashboard".to_string()),
+            ApiKeyScope::ReadOnly
+        )
+        .is_err()
+    );
+    assert!(add_api_key(&mut config, "missing", None, ApiKeyScope::ReadOnly).is_err());
+
+    let toml = toml::to_string_pretty(&config).unwrap();
+    assert!(!toml.contains(&writer.secret));
+    assert!(!toml.contains(&reader.secret));
+    assert!(toml.contains("read_only"));
+
+    // The gateway accepts the printed keys from the generated config
+    let config: HelixConfig = toml::from_str(&toml).expect("Should parse");
+    let json = config.get_instance("dev").unwrap().to_legacy_json();
+    let gateway_config: GatewayConfig =
+        serde_json::from_value(json["gateway_config"].clone()).unwrap();
+    let keys = ApiKeys::from_config(gateway_config.api_keys());
+    let reader_key = keys.verify(&reader.secret).expect("reader key accepted");
+    assert_eq!(reader_key.name, "dashboard");
+    assert!(!reader_key.can_call(true));
+    assert!(keys.verify(&writer.secret).unwrap().can_call(true));
+}
+
 #[test]
 fn test_config_default_has_dev_instance() {
     let config = HelixConfig::default_config("my-project");
@@ -468,6 +1083,8 @@ fn test_config_list_instances() {
         crate::config::LocalInstanceConfig {
             port: Some(6970),
             build_mode: crate::config::BuildMode::Dev,
+            resources: None,
+            standby_of: None,
             db_config: crate::config::DbConfig::default(),
         },
     );
@@ -477,3 +1094,97 @@ fn test_config_list_instances() {
     assert!(instances.contains(&&"dev".to_string()));
     assert!(instances.contains(&&"staging".to_string()));
 }
+
+#[test]
+fn test_resource_limits_summary() {
+    use crate::config::ResourceLimits;
+
+    assert_eq!(ResourceLimits::default().summary(), None);
+
+    let limits = ResourceLimits {
+        cpu_shares: Some(512),
+        memory: Some("2g".to_string()),
+        map_size_gb: Some(4),
+    };
+    assert_eq!(
+        limits.summary().unwrap(),
+        "cpu shares 512, memory 2g, map size 4 GB"
+    );
+
+    let limits = ResourceLimits {
+        memory: Some("512m".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(limits.summary().unwrap(), "memory 512m");
+}
+
+#[test]
+fn test_schedule_summary() {
+    use crate::commands::status::{ScheduleRun, ScheduleStatus, schedule_summary};
+
+    let mut schedule = ScheduleStatus {
+        name: "nightly-rollup".to_string(),
+        query: "RollupDaily".to_string(),
+        cron: "0 3 * * *".to_string(),
+        next_run_ms: Some(1_767_236_400_000),
+        runs: vec![],
+    };
+    assert_eq!(
+        schedule_summary(&schedule),
+        "RollupDaily (0 3 * * *), never run, next 2026-01-01 03:00:00 UTC"
+    );
+
+    schedule.runs.push(ScheduleRun {
+        started_at_ms: 1_767_150_000_000,
+        duration_ms: 42,
+        ok: false,
+        error: Some("Couldn't find `RollupDaily`".to_string()),
+    });
+    assert_eq!(
+        schedule_summary(&schedule),
+        "RollupDaily (0 3 * * *), last run 2025-12-31 03:00:00 UTC failed: Couldn't find `RollupDaily`, next 2026-01-01 03:00:00 UTC"
+    );
+}
+
+#[test]
+fn test_admin_stats_fields() {
+    use crate::commands::status::{AdminStats, admin_stats_fields};
+
+    let stats: AdminStats = serde_json::from_value(serde_json::json!({
+        "version": "1.2.7",
+        "uptime_secs": 3725,
+        "schema_versions": { "User": 2, "Follows": 1 },
+        "workers": {
+            "workers": 16,
+            "queue_capacity": 1000,
+            "read_queue_depth": 3,
+            "batch_queue_depth": 0,
+            "write_queue_depth": 1,
+            "in_flight": 4,
+            "shed_total": 0,
+            "cache_entries": 0,
+            "cache_hits": 0,
+            "cache_misses": 0
+        },
+        "routes": [
+            { "route": "getUser", "success": 10, "error": 1, "mean_latency_ms": 1.5 }
+        ]
+    }))
+    .unwrap();
+
+    let fields = admin_stats_fields(&stats);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(field("Version"), Some("1.2.7"));
+    assert_eq!(field("Uptime"), Some("1h 2m 5s"));
+    assert_eq!(field("Schema"), Some("Follows v1, User v2"));
+    assert_eq!(
+        field("Workers"),
+        Some("16 (4 in flight, 3 queued reads, 1 queued writes)")
+    );
+    assert_eq!(field("getUser"), Some("10 ok, 1 errors, 1.50ms mean"));
+}
@@ -1,3 +1,4 @@
+use crate::user_config::{UpdateChannel, UserConfig};
 use dirs::home_dir;
 use eyre::{Result, eyre};
 use reqwest::Client;
@@ -8,6 +9,9 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const GITHUB_API_URL: &str = "https://api.github.com/repos/helixdb/helix-db/releases/latest";
+/// Newest release of any kind, pre-releases included
+const GITHUB_RELEASES_URL: &str =
+    "https://api.github.com/repos/helixdb/helix-db/releases?per_page=1";
 const UPDATE_CHECK_INTERVAL: u64 = 24 * 60 * 60; // 24 hours in seconds
 
 #[derive(Deserialize)]
@@ -22,6 +26,9 @@ struct GitHubRelease {
 struct UpdateCache {
     last_check: u64,
     latest_version: Option<String>,
+    /// Channel `latest_version` was found on
+    #[serde(default)]
+    channel: UpdateChannel,
 }
 
 fn get_update_cache_path() -> Result<PathBuf> {
@@ -34,13 +41,17 @@ fn get_update_cache_path() -> Result<PathBuf> {
     Ok(helix_dir.join("update_cache.toml"))
 }
 
-async fn fetch_latest_version() -> Result<String> {
+async fn fetch_latest_version(channel: UpdateChannel) -> Result<String> {
     let client = Client::builder()
         .user_agent(format!("helix-cli/{CURRENT_VERSION}"))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let response = client.get(GITHUB_API_URL).send().await?;
+    let url = match channel {
+        UpdateChannel::Prerelease => GITHUB_RELEASES_URL,
+        _ => GITHUB_API_URL,
+    };
+    let response = client.get(url).send().await?;
 
     if !response.status().is_success() {
         return Err(eyre!(
@@ -49,7 +60,15 @@ async fn fetch_latest_version() -> Result<String> {
         ));
     }
 
-    let release: GitHubRelease = response.json().await?;
+    let release: GitHubRelease = match channel {
+        UpdateChannel::Prerelease => response
+            .json::<Vec<GitHubRelease>>()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre!("No releases found"))?,
+        _ => response.json().await?,
+    };
 
     // Remove 'v' prefix if present
     let version = release
@@ -59,7 +78,7 @@ async fn fetch_latest_version() -> Result<String> {
     Ok(version.to_string())
 }
 
-fn should_check_for_updates() -> Result<bool> {
+fn should_check_for_updates(channel: UpdateChannel) -> Result<bool> {
     let cache_path = get_update_cache_path()?;
 
     if !cache_path.exists() {
@@ -72,16 +91,17 @@ fn should_check_for_updates() -> Result<bool> {
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
     let time_since_check = now.saturating_sub(update_cache.last_check);
 
-    Ok(time_since_check >= UPDATE_CHECK_INTERVAL)
+    Ok(time_since_check >= UPDATE_CHECK_INTERVAL || update_cache.channel != channel)
 }
 
-fn save_update_check(latest_version: Option<String>) -> Result<()> {
+fn save_update_check(latest_version: Option<String>, channel: UpdateChannel) -> Result<()> {
     let cache_path = get_update_cache_path()?;
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
 
     let update_cache = UpdateCache {
         last_check: now,
         latest_version,
+        channel,
     };
 
     let cache_content = toml::to_string_pretty(&update_cache)?;
@@ -115,8 +135,15 @@ fn is_newer_version(current: &str, latest: &str) -> bool {
 }
 
 pub async fn check_for_updates() -> Result<()> {
+    let channel = UserConfig::load()
+        .map(|config| config.update_channel())
+        .unwrap_or_default();
+    if channel == UpdateChannel::Off {
+        return Ok(());
+    }
+
     // Skip update check if not needed (to avoid slowing down every command)
-    if !should_check_for_updates().unwrap_or(true) {
+    if !should_check_for_updates(channel).unwrap_or(true) {
         // Still check cache for any previously found updates
         let cache_path = get_update_cache_path()?;
         let cache_content = fs::read_to_string(&cache_path)?;
@@ -133,18 +160,18 @@ pub async fn check_for_updates() -> Result<()> {
     }
 
     // Perform actual update check
-    match fetch_latest_version().await {
+    match fetch_latest_version(channel).await {
         Ok(latest_version) => {
             if is_newer_version(CURRENT_VERSION, &latest_version) {
                 print_update_available(&latest_version);
-                save_update_check(Some(latest_version))?;
+                save_update_check(Some(latest_version), channel)?;
             } else {
-                save_update_check(Some(latest_version))?;
+                save_update_check(Some(latest_version), channel)?;
             }
         }
         Err(_) => {
             // Silently fail - don't block CLI usage due to network issues
-            save_update_check(None)?;
+            save_update_check(None, channel)?;
         }
     }
 
@@ -0,0 +1,211 @@
+//! User-level CLI settings, kept in `~/.helix/config.toml` and edited with `helix config`.
+//!
+//! Every setting is optional. An unset one falls back to the CLI's built-in default, and flags
+//! given on the command line always win over the file.
+
+use crate::errors::CliError;
+use crate::metrics_sender::MetricsLevel;
+use crate::output::Verbosity;
+use crate::port;
+use crate::utils::print_warning;
+use eyre::{Result, eyre};
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Helix Cloud region used when neither the command nor the config names one
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+/// Project template used by `helix init` when neither the command nor the config names one
+pub const DEFAULT_TEMPLATE: &str = "empty";
+
+/// Settings `helix config` knows about, with what each controls
+pub const KEYS: [(&str, &str); 5] = [
+    (
+        "region",
+        "Helix Cloud region new instances and clusters are created in",
+    ),
+    ("telemetry", "Metrics collection level: full, basic or off"),
+    (
+        "update_channel",
+        "Releases to check for and update to: stable, prerelease or off",
+    ),
+    (
+        "output",
+        "Output verbosity without -q or -v: quiet, normal or verbose",
+    ),
+    ("template", "Template used by 'helix init'"),
+];
+
+/// Which releases the CLI checks for and `helix update` installs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Also pre-releases
+    Prerelease,
+    /// Never check for updates, `helix update` installs the latest stable release
+    Off,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<MetricsLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_channel: Option<UpdateChannel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Verbosity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl UserConfig {
+    /// Path of the config, under `HELIX_HOME` when set
+    pub fn path() -> Result<PathBuf> {
+        Ok(port::helix_home()?.join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| eyre!("Failed to parse {}: {e}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(UserConfig::default()),
+            Err(e) => Err(eyre!("Failed to read {}: {e}", path.display())),
+        }
+    }
+
+    /// The config commands should follow. A config that can't be read is reported and
+    /// ignored, so a bad edit never locks the CLI out.
+    pub fn current() -> Self {
+        Self::load().unwrap_or_else(|e| {
+            print_warning(&format!("Ignoring user config: {e}"));
+            UserConfig::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| eyre!("Failed to serialize user config: {e}"))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// The value of `key` as it's written in the file, if it's set
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(match key {
+            "region" => self.region.clone(),
+            "telemetry" => self.telemetry.as_ref().map(choice_name),
+            "update_channel" => self.update_channel.as_ref().map(choice_name),
+            "output" => self.output.as_ref().map(choice_name),
+            "template" => self.template.clone(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "region" => self.region = Some(non_empty(key, value)?),
+            "telemetry" => self.telemetry = Some(parse_choice(key, value)?),
+            "update_channel" => self.update_channel = Some(parse_choice(key, value)?),
+            "output" => self.output = Some(parse_choice(key, value)?),
+            "template" => self.template = Some(non_empty(key, value)?),
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Clear `key`, so its built-in default applies again
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        match key {
+            "region" => self.region = None,
+            "telemetry" => self.telemetry = None,
+            "update_channel" => self.update_channel = None,
+            "output" => self.output = None,
+            "template" => self.template = None,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    pub fn region(&self) -> String {
+        self.region
+            .clone()
+            .unwrap_or_else(|| DEFAULT_REGION.to_string())
+    }
+
+    pub fn template(&self) -> String {
+        self.template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+    }
+
+    pub fn update_channel(&self) -> UpdateChannel {
+        self.update_channel.unwrap_or_default()
+    }
+
+    /// Verbosity from the `-q` and `-v` flags, or the configured one when neither is given
+    pub fn verbosity(&self, quiet: bool, verbose: bool) -> Verbosity {
+        if quiet || verbose {
+            Verbosity::from_flags(quiet, verbose)
+        } else {
+            self.output.unwrap_or(Verbosity::Normal)
+        }
+    }
+}
+
+/// How a setting's value is written, e.g. `prerelease`
+fn choice_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn parse_choice<T: DeserializeOwned>(key: &str, value: &str) -> Result<T> {
+    let deserializer: serde::de::value::StrDeserializer<'_, serde::de::value::Error> =
+        value.into_deserializer();
+    T::deserialize(deserializer).map_err(|_| invalid_value(key, value))
+}
+
+fn non_empty(key: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid_value(key, value));
+    }
+    Ok(value.to_string())
+}
+
+fn invalid_value(key: &str, value: &str) -> eyre::Report {
+    let description = KEYS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map_or("", |(_, description)| description);
+    let error =
+        CliError::new(format!("'{value}' is not a valid value for '{key}'")).with_hint(description);
+    eyre!("{}", error.render())
+}
+
+fn unknown_key(key: &str) -> eyre::Report {
+    let keys = KEYS.map(|(name, _)| name).join(", ");
+    let error =
+        CliError::new(format!("Unknown setting '{key}'")).with_hint(format!("settings: {keys}"));
+    eyre!("{}", error.render())
+}
@@ -304,7 +304,7 @@ impl Template {
 pub mod helixc_utils {
     use eyre::Result;
     use helix_db::helixc::{
-        analyzer::analyze,
+        analyzer::analyze_with_complexity_budget,
         generator::{Source as GeneratedSource, generate},
         parser::{
             HelixParser,
@@ -388,8 +388,18 @@ pub mod helixc_utils {
 
     /// Analyze source for validation (similar to build.rs)
     pub fn analyze_source(source: Source, files: &[HxFile]) -> Result<GeneratedSource> {
+        analyze_source_with_budget(source, files, None)
+    }
+
+    /// Analyze source, also failing queries over the complexity budget when one is given
+    pub fn analyze_source_with_budget(
+        source: Source,
+        files: &[HxFile],
+        complexity_budget: Option<u64>,
+    ) -> Result<GeneratedSource> {
         let (diagnostics, generated_source) =
-            analyze(&source).map_err(|e| eyre::eyre!("Analysis error: {}", e))?;
+            analyze_with_complexity_budget(&source, complexity_budget)
+                .map_err(|e| eyre::eyre!("Analysis error: {}", e))?;
 
         if !diagnostics.is_empty() {
             // Format diagnostics properly using the helix-db pretty printer
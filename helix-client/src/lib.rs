@@ -0,0 +1,125 @@
+//! Client for the query endpoints of a HelixDB instance.
+//!
+//! `helix compile --client <file>` generates a module for a project's queries: an input and
+//! output struct per query and a `Queries` wrapper with an async method per query, so calls
+//! are checked at compile time. Queries can also be called by name with [`HelixClient::query`].
+
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+/// Address of an instance started locally with the default port
+pub const DEFAULT_URL: &str = "http://localhost:6969";
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The instance refused or failed the query
+    #[error("Query failed with status {status}: {message}")]
+    Query {
+        status: u16,
+        /// Stable error code, such as `R503` when rate limited, when the instance sent one
+        code: Option<String>,
+        message: String,
+        /// What the instance said more about the failure, such as the rejected parameters
+        details: Option<serde_json::Value>,
+        /// Whether sending the query again later may succeed
+        retryable: bool,
+    },
+    #[error("Unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+/// Error body the gateway sends with failed requests
+#[derive(Deserialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+    details: Option<serde_json::Value>,
+    retryable: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct HelixClient {
+    http: reqwest::Client,
+    url: String,
+    bearer_token: Option<String>,
+    api_key: Option<String>,
+}
+
+impl HelixClient {
+    /// A client for the instance at `url`, such as [`DEFAULT_URL`]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            bearer_token: None,
+            api_key: None,
+        }
+    }
+
+    /// Send requests with `http`, to share its connection pool or set timeouts and TLS options
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Authenticate with an API key or JWT sent as `Authorization: Bearer <token>`
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    /// Send the key instances built with the `api-key` feature check in `x-api-key`
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Call the query `name` with `input` as its JSON body
+    pub async fn query<I, O>(&self, name: &str, input: &I) -> Result<O, ClientError>
+    where
+        I: Serialize + ?Sized,
+        O: DeserializeOwned,
+    {
+        let mut request = self
+            .http
+            .post(format!("{}/{name}", self.url))
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(input)?);
+        if let Some(token) = &self.bearer_token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        if let Some(key) = &self.api_key {
+            request = request.header("x-api-key", key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if !status.is_success() {
+            return Err(match serde_json::from_slice::<ErrorBody>(&body) {
+                Ok(error) => ClientError::Query {
+                    status: status.as_u16(),
+                    code: Some(error.code),
+                    message: error.message,
+                    details: error.details,
+                    retryable: error.retryable,
+                },
+                Err(_) => ClientError::Query {
+                    status: status.as_u16(),
+                    code: None,
+                    message: String::from_utf8_lossy(&body).into_owned(),
+                    details: None,
+                    retryable: false,
+                },
+            });
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
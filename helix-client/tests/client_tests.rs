@@ -0,0 +1,122 @@
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Json, Router, routing::post};
+use helix_client::{ClientError, HelixClient};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize)]
+struct GetUserInput<'a> {
+    name: &'a str,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct GetUserOutput {
+    user: Value,
+    authorization: Option<String>,
+    api_key: Option<String>,
+}
+
+/// Serve a stand-in gateway on a free port and return its URL
+async fn serve() -> String {
+    let app = Router::new()
+        .route(
+            "/getUser",
+            post(|headers: HeaderMap, Json(body): Json<Value>| async move {
+                let header = |name: &str| {
+                    headers
+                        .get(name)
+                        .map(|value| value.to_str().unwrap().to_string())
+                };
+                Json(json!({
+                    "user": {"name": body["name"]},
+                    "authorization": header("authorization"),
+                    "api_key": header("x-api-key"),
+                }))
+            }),
+        )
+        .route(
+            "/limited",
+            post(|| async {
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(json!({
+                        "code": "R503",
+                        "message": "Too many requests",
+                        "details": {"limit": 10, "retry_after_secs": 1},
+                        "retryable": true,
+                    })),
+                )
+            }),
+        )
+        .route(
+            "/broken",
+            post(|| async { (StatusCode::BAD_GATEWAY, "upstream down") }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/")
+}
+
+#[tokio::test]
+async fn test_query_sends_input_and_credentials() {
+    let url = serve().await;
+    let client = HelixClient::new(&url)
+        .with_bearer_token("token")
+        .with_api_key("key");
+    assert_eq!(client.url(), url.trim_end_matches('/'));
+
+    let output: GetUserOutput = client
+        .query("getUser", &GetUserInput { name: "alice" })
+        .await
+        .unwrap();
+    assert_eq!(
+        output,
+        GetUserOutput {
+            user: json!({"name": "alice"}),
+            authorization: Some("Bearer token".to_string()),
+            api_key: Some("key".to_string()),
+        }
+    );
+
+    let output: GetUserOutput = HelixClient::new(&url)
+        .query("getUser", &GetUserInput { name: "bob" })
+        .await
+        .unwrap();
+    assert_eq!(output.authorization, None);
+    assert_eq!(output.api_key, None);
+}
+
+#[tokio::test]
+async fn test_query_errors_carry_status_and_code() {
+    let client = HelixClient::new(serve().await);
+
+    let err = client
+        .query::<_, Value>("limited", &json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Query { status: 429, code: Some(ref code), ref message, ref details, retryable: true }
+            if code == "R503"
+                && message == "Too many requests"
+                && details.as_ref().is_some_and(|d| d["retry_after_secs"] == 1)
+    ));
+
+    let err = client
+        .query::<_, Value>("broken", &json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Query { status: 502, code: None, ref message, details: None, retryable: false }
+            if message == "upstream down"
+    ));
+
+    // A body that doesn't match the output type
+    let err = client
+        .query::<_, Vec<u32>>("getUser", &GetUserInput { name: "carol" })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::Decode(_)));
+}
@@ -2,14 +2,22 @@ use helix_db::helix_engine::{
     storage_core::version_info::{
         ItemInfo, Transition, TransitionFn, TransitionSubmission, VersionInfo,
     },
-    traversal_core::{HelixGraphEngine, HelixGraphEngineOpts},
+    traversal_core::{HelixGraphEngine, HelixGraphEngineOpts, config::WarmUpMode},
 };
 use helix_db::helix_gateway::mcp::mcp::{MCPHandlerFn, MCPHandlerSubmission};
 use helix_db::helix_gateway::{
     gateway::{GatewayOpts, HelixGateway},
+    otel,
+    reload::LogLevel,
+    replication,
     router::router::{HandlerFn, HandlerSubmission},
 };
-use std::{collections::HashMap, sync::Arc};
+use helix_db::protocol::request::Priority;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use tracing::info;
 use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -17,7 +25,21 @@ mod queries;
 
 fn main() {
     let env_res = dotenvy::dotenv();
+    let mut config = queries::config().unwrap_or_default();
+
+    let (otel_layer, _otel_guard) = match otel::layer(&config.gateway_config()) {
+        Ok(Some((layer, guard))) => (Some(layer), Some(guard)),
+        Ok(None) => (None, None),
+        Err(e) => {
+            eprintln!("Failed to start OpenTelemetry exporter: {e}");
+            (None, None)
+        }
+    };
+    let (log_filter, log_level) = LogLevel::layer(config.gateway_config().log_level())
+        .unwrap_or_else(|e| panic!("log_level: {e}"));
     tracing_subscriber::registry()
+        .with(log_filter)
+        .with(otel_layer)
         .with(
             tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::filter::filter_fn(
                 |metadata| {
@@ -37,8 +59,6 @@ fn main() {
         Err(e) => info!(?e, "Didn't load .env file"),
     }
 
-    let config = queries::config().unwrap_or_default();
-
     let path = match std::env::var("HELIX_DATA_DIR") {
         Ok(val) => std::path::PathBuf::from(val).join("user"),
         Err(_) => {
@@ -48,6 +68,29 @@ fn main() {
         }
     };
 
+    if let Ok(val) = std::env::var("HELIX_MAP_SIZE_GB") {
+        config.db_max_size_gb = Some(
+            val.parse::<usize>()
+                .expect("HELIX_MAP_SIZE_GB must be a whole number of gigabytes"),
+        );
+    }
+
+    if let Ok(val) = std::env::var("HELIX_WARM_UP") {
+        let mode = val
+            .parse::<WarmUpMode>()
+            .unwrap_or_else(|e| panic!("HELIX_WARM_UP: {e}"));
+        let gateway_config = config.gateway_config.get_or_insert_with(Default::default);
+        gateway_config
+            .warm_up
+            .get_or_insert_with(Default::default)
+            .mode = Some(mode);
+    }
+
+    if let Ok(leader) = std::env::var("HELIX_STANDBY_OF") {
+        let gateway_config = config.gateway_config.get_or_insert_with(Default::default);
+        gateway_config.standby.get_or_insert_with(Default::default).leader = leader;
+    }
+
     let port = match std::env::var("HELIX_PORT") {
         Ok(val) => val
             .parse::<u16>()
@@ -97,6 +140,15 @@ fn main() {
             },
         );
 
+    // A standby restarted as a leader takes over the last copy it synced
+    if config.gateway_config().standby.is_none() {
+        match replication::promote_synced_snapshot(&path) {
+            Ok(true) => println!("Promoted the standby copy in {}", path.display()),
+            Ok(false) => {}
+            Err(e) => panic!("Failed to promote the standby copy: {e}"),
+        }
+    }
+
     let path_str = path.to_str().expect("Could not convert path to string");
     let opts = HelixGraphEngineOpts {
         path: path_str.to_string(),
@@ -113,47 +165,94 @@ fn main() {
     let submissions: Vec<_> = inventory::iter::<HandlerSubmission>.into_iter().collect();
     println!("Found {} route submissions", submissions.len());
 
-    let (query_routes, write_routes): (
-        HashMap<String, HandlerFn>,
-        std::collections::HashSet<String>,
-    ) = inventory::iter::<HandlerSubmission>.into_iter().fold(
-        (HashMap::new(), std::collections::HashSet::new()),
-        |(mut routes, mut writes), submission| {
-            println!(
-                "Processing POST submission for handler: {} (is_write: {})",
-                submission.0.name, submission.0.is_write
+    let mut query_routes: HashMap<String, HandlerFn> = HashMap::new();
+    let mut write_routes = HashSet::new();
+    let mut batch_routes = HashSet::new();
+    let mut cache_routes = HashMap::new();
+    let mut route_labels = HashMap::new();
+    let mut route_roles = HashMap::new();
+    let mut route_concurrency = HashMap::new();
+    for submission in inventory::iter::<HandlerSubmission> {
+        println!(
+            "Processing POST submission for handler: {} (is_write: {})",
+            submission.0.name, submission.0.is_write
+        );
+        let handler = &submission.0;
+        let name = handler.name.to_string();
+        let func: HandlerFn = Arc::new(handler.func);
+        query_routes.insert(name.clone(), func);
+        if handler.is_write {
+            write_routes.insert(name.clone());
+        }
+        if handler.priority == Priority::Batch {
+            batch_routes.insert(name.clone());
+        }
+        if let Some(ttl_ms) = handler.cache_ttl_ms {
+            cache_routes.insert(name.clone(), Duration::from_millis(ttl_ms));
+        }
+        if !handler.roles.is_empty() {
+            route_roles.insert(
+                name.clone(),
+                handler.roles.iter().map(|r| r.to_string()).collect(),
             );
-            let handler = &submission.0;
-            let func: HandlerFn = Arc::new(handler.func);
-            routes.insert(handler.name.to_string(), func);
-            if handler.is_write {
-                writes.insert(handler.name.to_string());
-            }
-            (routes, writes)
-        },
-    );
+        }
+        if let Some(limit) = handler.max_concurrency {
+            route_concurrency.insert(name.clone(), limit);
+        }
+        if let Some(labels) = handler.labels {
+            route_labels.insert(name, labels.iter().map(|l| l.to_string()).collect());
+        }
+    }
 
-    let mcp_routes = inventory::iter::<MCPHandlerSubmission>
-        .into_iter()
-        .map(|submission| {
-            println!("Processing submission for handler: {}", submission.0.name);
-            let handler = &submission.0;
-            let func: MCPHandlerFn = Arc::new(handler.func);
-            (handler.name.to_string(), func)
-        })
-        .collect::<HashMap<String, MCPHandlerFn>>();
+    let mut mcp_routes: HashMap<String, MCPHandlerFn> = HashMap::new();
+    let mut mcp_write_routes = HashSet::new();
+    let mut mcp_route_roles = HashMap::new();
+    for submission in inventory::iter::<MCPHandlerSubmission> {
+        println!(
+            "Processing MCP submission for handler: {} (is_write: {})",
+            submission.0.name, submission.0.is_write
+        );
+        let handler = &submission.0;
+        let name = handler.name.to_string();
+        let func: MCPHandlerFn = Arc::new(handler.func);
+        mcp_routes.insert(name.clone(), func);
+        if handler.is_write {
+            mcp_write_routes.insert(name.clone());
+        }
+        if !handler.roles.is_empty() {
+            mcp_route_roles.insert(name, handler.roles.iter().map(|r| r.to_string()).collect());
+        }
+    }
 
     println!("Routes: {:?}", query_routes.keys());
     println!("Write routes: {:?}", write_routes);
+    println!("Batch routes: {:?}", batch_routes);
+    println!("Cached routes: {:?}", cache_routes);
+    println!("Role-restricted routes: {:?}", route_roles);
+    println!("Concurrency-limited routes: {:?}", route_concurrency);
+    let workers_per_core = opts
+        .config
+        .gateway_config()
+        .workers_per_core
+        .unwrap_or(GatewayOpts::DEFAULT_WORKERS_PER_CORE);
     let gateway = HelixGateway::new(
         &format!("0.0.0.0:{port}"),
         graph,
-        GatewayOpts::DEFAULT_WORKERS_PER_CORE,
+        workers_per_core,
         Some(query_routes),
         Some(mcp_routes),
         Some(write_routes),
         Some(opts),
-    );
+    )
+    .with_batch_routes(batch_routes)
+    .with_cache_routes(cache_routes)
+    .with_route_labels(route_labels)
+    .with_route_roles(route_roles)
+    .with_route_concurrency(route_concurrency)
+    .with_mcp_write_routes(mcp_write_routes)
+    .with_mcp_route_roles(mcp_route_roles)
+    .with_grpc_proto(queries::GRPC_PROTO)
+    .with_log_level(log_level);
 
     gateway.run().expect("Failed to run gateway")
 }
@@ -4,3 +4,5 @@ use helix_db::helix_engine::traversal_core::config::Config;
 pub fn config() -> Option<Config> {
     None
 }
+
+pub const GRPC_PROTO: &str = "";
@@ -0,0 +1,12 @@
+#![no_main]
+
+use helix_db::helixc::parser::roundtrip::check_roundtrip;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data)
+        && let Err(error) = check_roundtrip(&[("fuzz.hx", input)])
+    {
+        panic!("{error}");
+    }
+});
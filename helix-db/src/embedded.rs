@@ -0,0 +1,372 @@
+//! HelixDB as a library, without the gateway.
+//!
+//! [`HelixEmbedded`] opens a database directory and runs the queries compiled into the
+//! application: the `#[handler]` functions `helix compile` generates register themselves, and
+//! others can be added with [`HelixEmbedded::register`]. Queries run on the calling thread.
+//! There is no worker pool, scheduler or server behind them, so calls take effect in the order
+//! they are made and a query's results are the same however many threads the application runs.
+//!
+//! Build with `default-features = false, features = ["embedded"]` to leave out the gateway and
+//! the axum and hyper stacks it needs. Queries that embed text then also need the `reqwest`
+//! feature to reach their embedding provider.
+//!
+//! ```no_run
+//! use helix_db::{Config, HelixEmbedded};
+//! use sonic_rs::json;
+//!
+//! let db = HelixEmbedded::open("./data", Config::default())?;
+//! let results = db.query_results("get_user", &json!({ "name": "ada" }))?;
+//! for (name, value) in results.iter() {
+//!     println!("{name}: {value}");
+//! }
+//! # Ok::<(), helix_db::HelixError>(())
+//! ```
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use bytes::Bytes;
+use indexmap::IndexMap;
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{runtime::Runtime, sync::oneshot};
+
+use crate::{
+    helix_engine::{
+        storage_core::{constraints, version_info::VersionInfo, write_log},
+        traversal_core::{HelixGraphEngine, HelixGraphEngineOpts, config::Config},
+        types::GraphError,
+    },
+    helix_gateway::router::router::{ContMsg, Handler, HandlerInput, HandlerSubmission, IoContFn},
+    protocol::{Format, HelixError, Request, Response, request::RequestType},
+};
+
+/// A database opened in-process, with the queries it can run
+pub struct HelixEmbedded {
+    engine: Arc<HelixGraphEngine>,
+    handlers: HashMap<&'static str, Handler>,
+    /// Drives the IO, such as fetching embeddings, that queries yield for
+    io_rt: Runtime,
+}
+
+impl HelixEmbedded {
+    /// Open the database at `path`, creating it if needed, with every query compiled into the
+    /// application and the migrations between their schema versions
+    pub fn open(path: impl AsRef<Path>, config: Config) -> Result<Self, HelixError> {
+        let engine = HelixGraphEngine::new(HelixGraphEngineOpts {
+            path: path.as_ref().display().to_string(),
+            config,
+            version_info: VersionInfo::from_submissions(),
+        })?;
+        let io_rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(GraphError::from)?;
+        let handlers = inventory::iter::<HandlerSubmission>
+            .into_iter()
+            .map(|HandlerSubmission(handler)| (handler.name, handler.clone()))
+            .collect();
+        Ok(HelixEmbedded {
+            engine: Arc::new(engine),
+            handlers,
+            io_rt,
+        })
+    }
+
+    /// Add a query, replacing any with the same name
+    pub fn register(&mut self, handler: Handler) {
+        self.handlers.insert(handler.name, handler);
+    }
+
+    /// The engine queries run against, for direct traversals and storage access
+    pub fn engine(&self) -> &Arc<HelixGraphEngine> {
+        &self.engine
+    }
+
+    /// Names of the queries that can be run, sorted
+    pub fn queries(&self) -> Vec<&'static str> {
+        let mut names = self.handlers.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Whether the query `name` writes, or `None` if there is no such query
+    pub fn is_write(&self, name: &str) -> Option<bool> {
+        self.handlers.get(name).map(|handler| handler.is_write)
+    }
+
+    /// Run the query `name` with `input` as its parameters and decode its result
+    pub fn query<I, O>(&self, name: &str, input: &I) -> Result<O, HelixError>
+    where
+        I: Serialize,
+        O: DeserializeOwned,
+    {
+        let response = self.run(name, Format::Json, encode(input)?)?;
+        Ok(Format::Json.deserialize_owned(&response.body)?)
+    }
+
+    /// Run the query `name` with `input` as its parameters, keeping its results as JSON
+    pub fn query_results<I: Serialize>(
+        &self,
+        name: &str,
+        input: &I,
+    ) -> Result<QueryResults, HelixError> {
+        let response = self.run(name, Format::Json, encode(input)?)?;
+        QueryResults::from_json(&response.body)
+    }
+
+    /// Run the query `name` on parameters already encoded in `in_fmt`, as the gateway does.
+    /// The response is JSON.
+    pub fn run(
+        &self,
+        name: &str,
+        in_fmt: Format,
+        body: impl Into<Bytes>,
+    ) -> Result<Response, HelixError> {
+        let handler = self
+            .handlers
+            .get(name)
+            .ok_or_else(|| HelixError::NotFound {
+                ty: RequestType::Query,
+                name: name.to_string(),
+            })?;
+        let request = Request {
+            name: name.to_string(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: body.into(),
+            in_fmt,
+            out_fmt: Format::Json,
+        };
+
+        // Nothing takes the written ids without a change feed, so keep the log from growing
+        write_log::reset();
+        // Nodes noted by a write that failed before checking them
+        constraints::reset();
+        let input = HandlerInput {
+            request,
+            graph: Arc::clone(&self.engine),
+        };
+        match (handler.func)(input) {
+            Err(GraphError::IoNeeded(cont)) => self.finish_after_io(cont),
+            res => res.map_err(Into::into),
+        }
+    }
+
+    /// Wait for the IO a query yielded for, then finish the query on this thread
+    fn finish_after_io(&self, cont: IoContFn) -> Result<Response, HelixError> {
+        let (cont_tx, cont_rx) = flume::bounded::<ContMsg>(1);
+        let (ret_tx, mut ret_rx) = oneshot::channel();
+        self.io_rt.block_on((cont.0)(cont_tx, ret_tx));
+        match cont_rx.try_recv() {
+            Ok((_, finish)) => {
+                write_log::reset();
+                constraints::reset();
+                finish().map_err(Into::into)
+            }
+            // The continuation answered without handing the rest of the query back
+            Err(_) => ret_rx.try_recv().unwrap_or_else(|_| {
+                Err(GraphError::New("query finished its IO without a result".to_string()).into())
+            }),
+        }
+    }
+}
+
+fn encode<I: Serialize>(input: &I) -> Result<Vec<u8>, HelixError> {
+    sonic_rs::to_vec(input)
+        .map_err(|e| GraphError::ConversionError(format!("Failed to encode input: {e}")).into())
+}
+
+/// Named results of a query, in the order its `RETURN` lists them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResults(Vec<(String, sonic_rs::Value)>);
+
+impl QueryResults {
+    /// Split a JSON query response into its named results. Queries returning nothing
+    /// respond with `null`.
+    pub fn from_json(body: &[u8]) -> Result<Self, HelixError> {
+        // sonic_rs objects don't keep key order, an IndexMap keeps the order the query wrote
+        let results: Option<IndexMap<String, sonic_rs::Value>> =
+            Format::Json.deserialize_owned(body)?;
+        Ok(QueryResults(
+            results.unwrap_or_default().into_iter().collect(),
+        ))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&sonic_rs::Value> {
+        self.0
+            .iter()
+            .find(|(result, _)| result == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Decode the result `name`
+    pub fn decode<O: DeserializeOwned>(&self, name: &str) -> Result<O, HelixError> {
+        let value = self.get(name).ok_or_else(|| {
+            GraphError::DecodeError(format!("query returned no result named `{name}`"))
+        })?;
+        Ok(sonic_rs::from_value(value).map_err(|e| GraphError::DecodeError(e.to_string()))?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &sonic_rs::Value)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for QueryResults {
+    type Item = (String, sonic_rs::Value);
+    type IntoIter = std::vec::IntoIter<(String, sonic_rs::Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helix_engine::traversal_core::ops::{
+        g::G,
+        source::{add_n::AddNAdapter, n_from_type::NFromTypeAdapter},
+    };
+    use bumpalo::Bump;
+    use serde::Deserialize;
+    use sonic_rs::json;
+    use tempfile::TempDir;
+
+    #[derive(Deserialize)]
+    struct Input {
+        label: String,
+    }
+
+    fn add(input: HandlerInput) -> Result<Response, GraphError> {
+        let params: Input = input
+            .request
+            .in_fmt
+            .deserialize_owned(&input.request.body)?;
+        let storage = &input.graph.storage;
+        let arena = Bump::new();
+        let mut txn = storage.graph_env.write_txn()?;
+        let label = arena.alloc_str(&params.label);
+        G::new_mut(storage, &arena, &mut txn)
+            .add_n(label, None, None)
+            .collect::<Result<Vec<_>, _>>()?;
+        txn.commit()?;
+        Ok(input.request.out_fmt.create_response(&()))
+    }
+
+    fn count(input: HandlerInput) -> Result<Response, GraphError> {
+        let params: Input = input
+            .request
+            .in_fmt
+            .deserialize_owned(&input.request.body)?;
+        let storage = &input.graph.storage;
+        let arena = Bump::new();
+        let txn = storage.graph_env.read_txn()?;
+        let count = G::new(storage, &txn, &arena)
+            .n_from_type(&params.label)
+            .count();
+        #[derive(Serialize)]
+        struct Output {
+            label: String,
+            count: usize,
+        }
+        let response = Output {
+            label: params.label,
+            count,
+        };
+        Ok(input.request.out_fmt.create_response(&response))
+    }
+
+    fn after_io(_: HandlerInput) -> Result<Response, GraphError> {
+        Err(IoContFn::create_err(|cont_tx, ret_chan| {
+            Box::pin(async move {
+                // Stands in for a request to an embedding provider
+                tokio::task::yield_now().await;
+                cont_tx
+                    .send_async((
+                        ret_chan,
+                        Box::new(|| Ok(Format::Json.create_response(&json!({ "fetched": true })))),
+                    ))
+                    .await
+                    .expect("continuation channel should be open");
+            })
+        }))
+    }
+
+    fn open(dir: &TempDir) -> HelixEmbedded {
+        let mut db = HelixEmbedded::open(dir.path(), Config::default()).unwrap();
+        db.register(Handler::new("add", add, true));
+        db.register(Handler::new("count", count, false));
+        db.register(Handler::new("after_io", after_io, false));
+        db
+    }
+
+    #[test]
+    fn test_queries_run_in_call_order() {
+        let dir = TempDir::new().unwrap();
+        let db = open(&dir);
+
+        for _ in 0..3 {
+            let () = db.query("add", &json!({ "label": "person" })).unwrap();
+        }
+        let results = db
+            .query_results("count", &json!({ "label": "person" }))
+            .unwrap();
+
+        let names = results.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, ["label", "count"]);
+        assert_eq!(results.decode::<u64>("count").unwrap(), 3);
+        assert_eq!(db.is_write("add"), Some(true));
+        assert_eq!(db.is_write("count"), Some(false));
+    }
+
+    #[test]
+    fn test_data_outlives_the_handle() {
+        let dir = TempDir::new().unwrap();
+        {
+            let db = open(&dir);
+            let () = db.query("add", &json!({ "label": "city" })).unwrap();
+        }
+
+        let db = open(&dir);
+        let results = db
+            .query_results("count", &json!({ "label": "city" }))
+            .unwrap();
+        assert_eq!(results.decode::<u64>("count").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_query_finishes_after_io() {
+        let dir = TempDir::new().unwrap();
+        let db = open(&dir);
+
+        let results = db.query_results("after_io", &json!({})).unwrap();
+
+        assert_eq!(results.get("fetched"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn test_unknown_query_is_not_found() {
+        let dir = TempDir::new().unwrap();
+        let db = open(&dir);
+
+        let error = db.query_results("missing", &json!({})).unwrap_err();
+
+        assert!(matches!(error, HelixError::NotFound { .. }), "{error}");
+        assert!(!db.queries().contains(&"missing"));
+        assert!(db.queries().contains(&"count"));
+    }
+
+    #[test]
+    fn test_results_of_query_returning_nothing_are_empty() {
+        assert!(QueryResults::from_json(b"null").unwrap().is_empty());
+        assert!(QueryResults::from_json(b"[1]").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Synonyms and the user dictionary of BM25 text search, loaded from the instance config.
+//!
+//! Dictionary terms are kept whole by the tokenizer, in documents and queries alike, so short
+//! or punctuated domain terms like `ml` or `c++` can be searched. Synonyms only apply to
+//! queries: each query term with a rule is swapped for the terms the rule gives it, so changing
+//! synonyms never needs a reindex.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::helix_engine::{traversal_core::config::TextSearchConfig, types::GraphError};
+
+/// Punctuation trimmed off words before they're looked up in the dictionary
+const TRIMMED: &[char] = &[
+    ',', '.', ';', ':', '!', '?', '"', '\'', '(', ')', '[', ']', '{', '}',
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct TextAnalysis {
+    dictionary: HashSet<String>,
+    /// The terms a query term is searched as, itself included unless the rule replaces it
+    synonyms: HashMap<String, Vec<String>>,
+}
+
+impl TextAnalysis {
+    /// Read the synonyms and dictionary terms of the config and its files
+    pub fn load(config: &TextSearchConfig) -> Result<Self, GraphError> {
+        let mut analysis = TextAnalysis::default();
+
+        let mut dictionary = config.dictionary.clone().unwrap_or_default();
+        for path in config.dictionary_files.iter().flatten() {
+            let file = std::fs::read_to_string(path).map_err(|e| {
+                GraphError::New(format!("can't read dictionary {}: {e}", path.display()))
+            })?;
+            dictionary.extend(rules(&file).map(str::to_string));
+        }
+        for term in dictionary {
+            let term = term.trim().to_lowercase();
+            if term.contains(char::is_whitespace) {
+                return Err(GraphError::New(format!(
+                    "dictionary term '{term}' has whitespace; add its words separately"
+                )));
+            }
+            analysis.dictionary.insert(term);
+        }
+
+        let mut synonyms = config.synonyms.clone().unwrap_or_default();
+        for path in config.synonym_files.iter().flatten() {
+            let file = std::fs::read_to_string(path).map_err(|e| {
+                GraphError::New(format!("can't read synonyms {}: {e}", path.display()))
+            })?;
+            synonyms.extend(rules(&file).map(str::to_string));
+        }
+        for rule in &synonyms {
+            analysis.add_synonym_rule(rule)?;
+        }
+        Ok(analysis)
+    }
+
+    /// Add a `a, b, c` or `a, b => c` rule
+    fn add_synonym_rule(&mut self, rule: &str) -> Result<(), GraphError> {
+        // Equivalent terms expand to each other, themselves included
+        let (from, to) = rule.split_once("=>").unwrap_or((rule, rule));
+        let to: Vec<String> = to
+            .split(',')
+            .flat_map(|phrase| self.tokenize::<true>(phrase))
+            .collect();
+        let from: Vec<&str> = from.split(',').map(str::trim).collect();
+        if to.is_empty() || from.iter().all(|term| term.is_empty()) {
+            return Err(GraphError::New(format!(
+                "synonym rule '{rule}' has no terms"
+            )));
+        }
+        for term in from {
+            // Phrases expand the terms equivalent to them but aren't matched in queries
+            let mut key = self.tokenize::<false>(term);
+            if key.len() != 1 {
+                continue;
+            }
+            let expansion = self.synonyms.entry(key.remove(0)).or_default();
+            for term in &to {
+                if !expansion.contains(term) {
+                    expansion.push(term.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowercase `text` and split it into terms at anything but letters and digits, keeping
+    /// dictionary terms whole. With `SHOULD_FILTER`, terms of two bytes or fewer that aren't in
+    /// the dictionary are dropped.
+    pub fn tokenize<const SHOULD_FILTER: bool>(&self, text: &str) -> Vec<String> {
+        let text = text.to_lowercase();
+        let split = |word: &str| -> Vec<String> {
+            word.split(|c: char| !c.is_alphanumeric())
+                .filter(|s| !s.is_empty())
+                .filter_map(|s| (!SHOULD_FILTER || s.len() > 2).then_some(s.to_string()))
+                .collect()
+        };
+        if self.dictionary.is_empty() {
+            return split(&text);
+        }
+        let mut terms = Vec::new();
+        for word in text.split_whitespace() {
+            let trimmed = word.trim_matches(TRIMMED);
+            if self.dictionary.contains(word) {
+                terms.push(word.to_string());
+            } else if self.dictionary.contains(trimmed) {
+                terms.push(trimmed.to_string());
+            } else {
+                terms.extend(split(word));
+            }
+        }
+        terms
+    }
+
+    /// The terms a query is searched as: its own, with synonyms applied
+    pub fn query_terms(&self, query: &str) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for term in self.tokenize::<false>(query) {
+            match self.synonyms.get(&term) {
+                Some(expansion) => {
+                    for term in expansion {
+                        if !terms.contains(term) {
+                            terms.push(term.clone());
+                        }
+                    }
+                }
+                None if term.len() > 2 || self.dictionary.contains(&term) => terms.push(term),
+                None => {}
+            }
+        }
+        terms
+    }
+}
+
+/// The lines of a file that aren't blank or comments
+fn rules(file: &str) -> impl Iterator<Item = &str> {
+    file.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
@@ -1,7 +1,10 @@
 use crate::{
     debug_println,
     helix_engine::{
+        bm25::analysis::TextAnalysis,
+        bm25::segments::{DocTerms, POSTING_SIZE, Posting, TermSegments, segment_key, term_prefix},
         storage_core::HelixGraphStorage,
+        traversal_core::config::Bm25MergeConfig,
         types::GraphError,
         vector_core::{hnsw::HNSW, vector::HVector},
     },
@@ -14,14 +17,21 @@ use bumpalo::{
 };
 use heed3::{Database, Env, RoTxn, RwTxn, types::*};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::task;
 
-const DB_BM25_INVERTED_INDEX: &str = "bm25_inverted_index"; // term -> list of (doc_id, tf)
+const DB_BM25_INVERTED_INDEX: &str = "bm25_inverted_index"; // term -> list of (doc_id, tf), before segments
+const DB_BM25_POSTINGS: &str = "bm25_postings"; // term, segment -> postings
+const DB_BM25_DOC_TERMS: &str = "bm25_doc_terms"; // doc_id -> generation, length and terms
+const DB_BM25_TERM_SEGMENTS: &str = "bm25_term_segments"; // term -> segments and dead postings
+const DB_BM25_MERGE_QUEUE: &str = "bm25_merge_queue"; // terms waiting for a merge
 const DB_BM25_DOC_LENGTHS: &str = "bm25_doc_lengths"; // doc_id -> document length
 const DB_BM25_TERM_FREQUENCIES: &str = "bm25_term_frequencies"; // term -> document frequency
 const DB_BM25_METADATA: &str = "bm25_metadata"; // stores total docs, avgdl, etc.
 pub const METADATA_KEY: &[u8] = b"metadata";
+/// Last generation given to a written document
+const GENERATION_KEY: &[u8] = b"generation";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BM25Metadata {
@@ -31,13 +41,27 @@ pub struct BM25Metadata {
     pub b: f32,  // controls document length normalization
 }
 
-/// For inverted index
+/// For the inverted index postings were kept in before segments
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PostingListEntry {
     pub doc_id: u128,
     pub term_frequency: u32,
 }
 
+/// Size of the segmented postings and the merges they wait for
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Bm25Stats {
+    pub documents: u64,
+    pub terms: u64,
+    pub segments: u64,
+    pub postings: u64,
+    pub dead_postings: u64,
+    pub queued_merges: u64,
+    /// Terms merged since the instance started
+    pub merges: u64,
+    pub merge_policy: Bm25MergeConfig,
+}
+
 pub trait BM25 {
     fn tokenize<const SHOULD_FILTER: bool>(&self, text: &str) -> Vec<String>;
 
@@ -68,109 +92,323 @@ pub trait BM25 {
 
 pub struct HBM25Config {
     pub graph_env: Env,
-    pub inverted_index_db: Database<Bytes, Bytes>,
+    pub postings_db: Database<Bytes, Bytes>,
+    pub doc_terms_db: Database<U128<heed3::byteorder::BE>, Bytes>,
+    pub term_segments_db: Database<Bytes, Bytes>,
+    pub merge_queue_db: Database<Bytes, Unit>,
     pub doc_lengths_db: Database<U128<heed3::byteorder::BE>, U32<heed3::byteorder::BE>>,
     pub term_frequencies_db: Database<Bytes, U32<heed3::byteorder::BE>>,
     pub metadata_db: Database<Bytes, Bytes>,
+    merge_policy: Bm25MergeConfig,
+    merges: AtomicU64,
+    analysis: TextAnalysis,
     k1: f64,
     b: f64,
 }
 
 impl HBM25Config {
     pub fn new(graph_env: &Env, wtxn: &mut RwTxn) -> Result<HBM25Config, GraphError> {
-        let inverted_index_db: Database<Bytes, Bytes> = graph_env
+        Self::open(graph_env, wtxn, |name| name.to_string())
+    }
+
+    pub fn new_temp(
+        graph_env: &Env,
+        wtxn: &mut RwTxn,
+        uuid: &str,
+    ) -> Result<HBM25Config, GraphError> {
+        Self::open(graph_env, wtxn, |name| format!("{name}_{uuid}"))
+    }
+
+    fn open(
+        graph_env: &Env,
+        wtxn: &mut RwTxn,
+        name: impl Fn(&str) -> String,
+    ) -> Result<HBM25Config, GraphError> {
+        let postings_db: Database<Bytes, Bytes> = graph_env
             .database_options()
             .types::<Bytes, Bytes>()
-            .flags(heed3::DatabaseFlags::DUP_SORT)
-            .name(DB_BM25_INVERTED_INDEX)
+            .name(&name(DB_BM25_POSTINGS))
+            .create(wtxn)?;
+
+        let doc_terms_db: Database<U128<heed3::byteorder::BE>, Bytes> = graph_env
+            .database_options()
+            .types::<U128<heed3::byteorder::BE>, Bytes>()
+            .name(&name(DB_BM25_DOC_TERMS))
+            .create(wtxn)?;
+
+        let term_segments_db: Database<Bytes, Bytes> = graph_env
+            .database_options()
+            .types::<Bytes, Bytes>()
+            .name(&name(DB_BM25_TERM_SEGMENTS))
+            .create(wtxn)?;
+
+        let merge_queue_db: Database<Bytes, Unit> = graph_env
+            .database_options()
+            .types::<Bytes, Unit>()
+            .name(&name(DB_BM25_MERGE_QUEUE))
             .create(wtxn)?;
 
         let doc_lengths_db: Database<U128<heed3::byteorder::BE>, U32<heed3::byteorder::BE>> =
             graph_env
                 .database_options()
                 .types::<U128<heed3::byteorder::BE>, U32<heed3::byteorder::BE>>()
-                .name(DB_BM25_DOC_LENGTHS)
+                .name(&name(DB_BM25_DOC_LENGTHS))
                 .create(wtxn)?;
 
         let term_frequencies_db: Database<Bytes, U32<heed3::byteorder::BE>> = graph_env
             .database_options()
             .types::<Bytes, U32<heed3::byteorder::BE>>()
-            .name(DB_BM25_TERM_FREQUENCIES)
+            .name(&name(DB_BM25_TERM_FREQUENCIES))
             .create(wtxn)?;
 
         let metadata_db: Database<Bytes, Bytes> = graph_env
             .database_options()
             .types::<Bytes, Bytes>()
-            .name(DB_BM25_METADATA)
+            .name(&name(DB_BM25_METADATA))
             .create(wtxn)?;
 
-        Ok(HBM25Config {
+        let config = HBM25Config {
             graph_env: graph_env.clone(),
-            inverted_index_db,
+            postings_db,
+            doc_terms_db,
+            term_segments_db,
+            merge_queue_db,
             doc_lengths_db,
             term_frequencies_db,
             metadata_db,
+            merge_policy: Bm25MergeConfig::default(),
+            merges: AtomicU64::new(0),
+            analysis: TextAnalysis::default(),
             k1: 1.2,
             b: 0.75,
-        })
+        };
+        config.migrate_inverted_index(graph_env, wtxn, &name(DB_BM25_INVERTED_INDEX))?;
+        Ok(config)
     }
 
-    pub fn new_temp(
+    /// Merge postings by `policy` rather than the defaults
+    pub fn with_merge_policy(mut self, policy: Bm25MergeConfig) -> Self {
+        self.merge_policy = policy;
+        self
+    }
+
+    /// Tokenize with `analysis`'s dictionary and expand queries with its synonyms
+    pub fn with_text_analysis(mut self, analysis: TextAnalysis) -> Self {
+        self.analysis = analysis;
+        self
+    }
+
+    /// The terms a query is searched as, with synonyms applied
+    pub fn query_terms(&self, query: &str) -> Vec<String> {
+        self.analysis.query_terms(query)
+    }
+
+    pub fn merge_policy(&self) -> &Bm25MergeConfig {
+        &self.merge_policy
+    }
+
+    /// Move the postings of an index written before segments into a segment per term
+    fn migrate_inverted_index(
+        &self,
         graph_env: &Env,
         wtxn: &mut RwTxn,
-        uuid: &str,
-    ) -> Result<HBM25Config, GraphError> {
-        let inverted_index_db: Database<Bytes, Bytes> = graph_env
+        name: &str,
+    ) -> Result<(), GraphError> {
+        let Some(inverted_index_db) = graph_env
             .database_options()
             .types::<Bytes, Bytes>()
             .flags(heed3::DatabaseFlags::DUP_SORT)
-            .name(format!("{DB_BM25_INVERTED_INDEX}_{uuid}").as_str())
-            .create(wtxn)?;
+            .name(name)
+            .open(wtxn)?
+        else {
+            return Ok(());
+        };
+        let mut terms: BTreeMap<Vec<u8>, Vec<Posting>> = BTreeMap::new();
+        let mut doc_terms: HashMap<u128, Vec<String>> = HashMap::new();
+        for item in inverted_index_db.iter(wtxn)? {
+            let (term, posting) = item?;
+            let posting: PostingListEntry = bincode::deserialize(posting)?;
+            terms.entry(term.to_vec()).or_default().push(Posting {
+                doc_id: posting.doc_id,
+                term_frequency: posting.term_frequency,
+                generation: 0,
+            });
+            doc_terms
+                .entry(posting.doc_id)
+                .or_default()
+                .push(String::from_utf8_lossy(term).into_owned());
+        }
+        if terms.is_empty() {
+            return Ok(());
+        }
 
-        let doc_lengths_db: Database<U128<heed3::byteorder::BE>, U32<heed3::byteorder::BE>> =
-            graph_env
-                .database_options()
-                .types::<U128<heed3::byteorder::BE>, U32<heed3::byteorder::BE>>()
-                .name(format!("{DB_BM25_DOC_LENGTHS}_{uuid}").as_str())
-                .create(wtxn)?;
+        for (term, mut postings) in terms {
+            postings.sort_unstable_by_key(|posting| posting.doc_id);
+            let mut segment = Vec::with_capacity(postings.len() * POSTING_SIZE);
+            for posting in &postings {
+                posting.encode(&mut segment);
+            }
+            self.postings_db
+                .put(wtxn, &segment_key(&term, 0), &segment)?;
+            let state = TermSegments {
+                next: 1,
+                segments: 1,
+                dead: 0,
+            };
+            self.term_segments_db.put(wtxn, &term, &state.encode())?;
+        }
+        for (doc_id, terms) in doc_terms {
+            let length = self.doc_lengths_db.get(wtxn, &doc_id)?.unwrap_or(0);
+            let entry = DocTerms::encode(0, length, terms.iter().map(String::as_str));
+            self.doc_terms_db.put(wtxn, &doc_id, &entry)?;
+        }
+        inverted_index_db.clear(wtxn)?;
+        Ok(())
+    }
 
-        let term_frequencies_db: Database<Bytes, U32<heed3::byteorder::BE>> = graph_env
-            .database_options()
-            .types::<Bytes, U32<heed3::byteorder::BE>>()
-            .name(format!("{DB_BM25_TERM_FREQUENCIES}_{uuid}").as_str())
-            .create(wtxn)?;
+    fn next_generation(&self, txn: &mut RwTxn) -> Result<u64, GraphError> {
+        let generation =
+            match self.metadata_db.get(txn, GENERATION_KEY)? {
+                Some(bytes) => u64::from_be_bytes(bytes.try_into().map_err(|_| {
+                    GraphError::New("BM25 generation can't be decoded".to_string())
+                })?),
+                None => 0,
+            } + 1;
+        self.metadata_db
+            .put(txn, GENERATION_KEY, &generation.to_be_bytes())?;
+        Ok(generation)
+    }
 
-        let metadata_db: Database<Bytes, Bytes> = graph_env
-            .database_options()
-            .types::<Bytes, Bytes>()
-            .name(format!("{DB_BM25_METADATA}_{uuid}").as_str())
-            .create(wtxn)?;
+    fn term_segments(&self, txn: &RoTxn, term: &[u8]) -> Result<TermSegments, GraphError> {
+        match self.term_segments_db.get(txn, term)? {
+            Some(bytes) => TermSegments::decode(bytes),
+            None => Ok(TermSegments::default()),
+        }
+    }
 
-        Ok(HBM25Config {
-            graph_env: graph_env.clone(),
-            inverted_index_db,
-            doc_lengths_db,
-            term_frequencies_db,
-            metadata_db,
-            k1: 1.2,
-            b: 0.75,
-        })
+    /// Store a term's segments after a write, and queue or run its merge as the policy says
+    fn segments_written(
+        &self,
+        txn: &mut RwTxn,
+        term: &[u8],
+        state: TermSegments,
+    ) -> Result<(), GraphError> {
+        self.term_segments_db.put(txn, term, &state.encode())?;
+        let live = self.term_frequencies_db.get(txn, term)?.unwrap_or(0);
+        if !self.merge_policy.wants_merge(&state, live) {
+            return Ok(());
+        }
+        if self.merge_policy.must_merge(&state) || self.merge_policy.interval().is_none() {
+            self.merge_term(txn, term)
+        } else {
+            self.merge_queue_db.put(txn, term, &())?;
+            Ok(())
+        }
+    }
+
+    /// Fold a term's segments into one, leaving out the postings of deleted or rewritten
+    /// documents
+    pub fn merge_term(&self, txn: &mut RwTxn, term: &[u8]) -> Result<(), GraphError> {
+        let mut keys = Vec::new();
+        let mut postings = Vec::new();
+        for item in self.postings_db.prefix_iter(txn, &term_prefix(term))? {
+            let (key, segment) = item?;
+            keys.push(key.to_vec());
+            for posting in Posting::decode_segment(segment)? {
+                let live = match self.doc_terms_db.get(txn, &posting.doc_id)? {
+                    Some(entry) => DocTerms::decode(entry)?.generation == posting.generation,
+                    None => false,
+                };
+                if live {
+                    postings.push(posting);
+                }
+            }
+        }
+        for key in keys {
+            self.postings_db.delete(txn, &key)?;
+        }
+        self.merge_queue_db.delete(txn, term)?;
+        self.merges.fetch_add(1, Ordering::Relaxed);
+
+        if postings.is_empty() {
+            self.term_segments_db.delete(txn, term)?;
+            return Ok(());
+        }
+        postings.sort_unstable_by_key(|posting| posting.doc_id);
+        let mut segment = Vec::with_capacity(postings.len() * POSTING_SIZE);
+        for posting in &postings {
+            posting.encode(&mut segment);
+        }
+        self.postings_db.put(txn, &segment_key(term, 0), &segment)?;
+        let state = TermSegments {
+            next: 1,
+            segments: 1,
+            dead: 0,
+        };
+        self.term_segments_db.put(txn, term, &state.encode())?;
+        Ok(())
+    }
+
+    /// Merge up to `limit` queued terms, returning how many were merged
+    pub fn merge_queued(&self, txn: &mut RwTxn, limit: usize) -> Result<usize, GraphError> {
+        let terms = self
+            .merge_queue_db
+            .iter(txn)?
+            .take(limit)
+            .map(|item| item.map(|(term, _)| term.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for term in &terms {
+            self.merge_term(txn, term)?;
+        }
+        Ok(terms.len())
+    }
+
+    pub fn has_queued_merges(&self, txn: &RoTxn) -> Result<bool, GraphError> {
+        Ok(!self.merge_queue_db.is_empty(txn)?)
+    }
+
+    pub fn stats(&self, txn: &RoTxn) -> Result<Bm25Stats, GraphError> {
+        let mut stats = Bm25Stats {
+            documents: 0,
+            terms: 0,
+            segments: 0,
+            postings: 0,
+            dead_postings: 0,
+            queued_merges: self.merge_queue_db.len(txn)?,
+            merges: self.merges.load(Ordering::Relaxed),
+            merge_policy: self.merge_policy.effective(),
+        };
+        if let Some(metadata) = self.metadata_db.get(txn, METADATA_KEY)? {
+            stats.documents = bincode::deserialize::<BM25Metadata>(metadata)?.total_docs;
+        }
+        for item in self.term_segments_db.iter(txn)? {
+            let (term, state) = item?;
+            let state = TermSegments::decode(state)?;
+            let live = self.term_frequencies_db.get(txn, term)?.unwrap_or(0);
+            stats.terms += 1;
+            stats.segments += state.segments as u64;
+            stats.postings += live as u64 + state.dead as u64;
+            stats.dead_postings += state.dead as u64;
+        }
+        Ok(stats)
     }
 }
 
 impl BM25 for HBM25Config {
-    /// Converts text to lowercase, removes non-alphanumeric chars, splits into words
+    /// Converts text to lowercase, removes non-alphanumeric chars, splits into words. Terms of
+    /// the user dictionary are kept whole.
     fn tokenize<const SHOULD_FILTER: bool>(&self, text: &str) -> Vec<String> {
-        text.to_lowercase()
-            .split(|c: char| !c.is_alphanumeric())
-            .filter(|s| !s.is_empty())
-            .filter_map(|s| (!SHOULD_FILTER || s.len() > 2).then_some(s.to_string()))
-            .collect()
+        self.analysis.tokenize::<SHOULD_FILTER>(text)
     }
 
-    /// Inserts needed information into doc_lengths_db, inverted_index_db, term_frequencies_db, and
-    /// metadata_db
+    /// Writes a segment with the document's posting for each of its terms, and updates
+    /// doc_terms_db, doc_lengths_db, term_frequencies_db and metadata_db. A document that is
+    /// already indexed is replaced.
     fn insert_doc(&self, txn: &mut RwTxn, doc_id: u128, doc: &str) -> Result<(), GraphError> {
+        if self.doc_terms_db.get(txn, &doc_id)?.is_some() {
+            self.delete_doc(txn, doc_id)?;
+        }
+
         let tokens = self.tokenize::<true>(doc);
         let doc_length = tokens.len() as u32;
 
@@ -179,24 +417,36 @@ impl BM25 for HBM25Config {
             *term_counts.entry(token).or_insert(0) += 1;
         }
 
+        let generation = self.next_generation(txn)?;
+        let doc_terms = DocTerms::encode(
+            generation,
+            doc_length,
+            term_counts.keys().map(String::as_str),
+        );
+        self.doc_terms_db.put(txn, &doc_id, &doc_terms)?;
         self.doc_lengths_db.put(txn, &doc_id, &doc_length)?;
 
+        let mut segment = Vec::with_capacity(POSTING_SIZE);
         for (term, tf) in term_counts {
             let term_bytes = term.as_bytes();
 
-            let posting_entry = PostingListEntry {
-                doc_id,
-                term_frequency: tf,
-            };
-
-            let posting_bytes = bincode::serialize(&posting_entry)?;
-
-            self.inverted_index_db
-                .put(txn, term_bytes, &posting_bytes)?;
-
             let current_df = self.term_frequencies_db.get(txn, term_bytes)?.unwrap_or(0);
             self.term_frequencies_db
                 .put(txn, term_bytes, &(current_df + 1))?;
+
+            segment.clear();
+            Posting {
+                doc_id,
+                term_frequency: tf,
+                generation,
+            }
+            .encode(&mut segment);
+            let mut state = self.term_segments(txn, term_bytes)?;
+            self.postings_db
+                .put(txn, &segment_key(term_bytes, state.next), &segment)?;
+            state.next += 1;
+            state.segments += 1;
+            self.segments_written(txn, term_bytes, state)?;
         }
 
         let mut metadata = if let Some(data) = self.metadata_db.get(txn, METADATA_KEY)? {
@@ -221,56 +471,28 @@ impl BM25 for HBM25Config {
         Ok(())
     }
 
+    /// Marks the document's postings dead by forgetting its generation; merges remove them
     fn delete_doc(&self, txn: &mut RwTxn, doc_id: u128) -> Result<(), GraphError> {
-        let terms_to_update = {
-            let mut terms = Vec::new();
-            let mut iter = self.inverted_index_db.iter(txn)?;
-
-            while let Some((term_bytes, posting_bytes)) = iter.next().transpose()? {
-                let posting: PostingListEntry = bincode::deserialize(posting_bytes)?;
-                if posting.doc_id == doc_id {
-                    terms.push(term_bytes.to_vec());
-                }
-            }
-            terms
+        self.doc_lengths_db.delete(txn, &doc_id)?;
+        let Some(entry) = self.doc_terms_db.get(txn, &doc_id)? else {
+            return Ok(());
         };
+        let entry = entry.to_vec();
+        let entry = DocTerms::decode(&entry)?;
+        self.doc_terms_db.delete(txn, &doc_id)?;
 
-        // remove postings and update term frequencies
-        for term_bytes in terms_to_update {
-            // collect entries to keep
-            let entries_to_keep = {
-                let mut entries = Vec::new();
-                if let Some(duplicates) = self.inverted_index_db.get_duplicates(txn, &term_bytes)? {
-                    for result in duplicates {
-                        let (_, posting_bytes) = result?;
-                        let posting: PostingListEntry = bincode::deserialize(posting_bytes)?;
-                        if posting.doc_id != doc_id {
-                            entries.push(posting_bytes.to_vec());
-                        }
-                    }
-                }
-                entries
-            };
-
-            // delete all entries for this term
-            self.inverted_index_db.delete(txn, &term_bytes)?;
-
-            // re-add the entries we want to keep
-            for entry_bytes in entries_to_keep {
-                self.inverted_index_db.put(txn, &term_bytes, &entry_bytes)?;
-            }
-
-            let current_df = self.term_frequencies_db.get(txn, &term_bytes)?.unwrap_or(0);
+        for term_bytes in entry.terms() {
+            let current_df = self.term_frequencies_db.get(txn, term_bytes)?.unwrap_or(0);
             if current_df > 0 {
                 self.term_frequencies_db
-                    .put(txn, &term_bytes, &(current_df - 1))?;
+                    .put(txn, term_bytes, &(current_df - 1))?;
             }
+            let mut state = self.term_segments(txn, term_bytes)?;
+            state.dead += 1;
+            self.segments_written(txn, term_bytes, state)?;
         }
 
-        let doc_length = self.doc_lengths_db.get(txn, &doc_id)?.unwrap_or(0);
-
-        self.doc_lengths_db.delete(txn, &doc_id)?;
-
+        let doc_length = entry.length;
         let metadata_data = self
             .metadata_db
             .get(txn, METADATA_KEY)?
@@ -338,7 +560,7 @@ impl BM25 for HBM25Config {
         arena: &Bump,
     ) -> Result<Vec<(u128, f32)>, GraphError> {
         let query_terms: BVec<BString> = BVec::from_iter_in(
-            self.tokenize::<true>(query)
+            self.query_terms(query)
                 .into_iter()
                 .map(|s| BString::from_str_in(&s, arena)),
             arena,
@@ -362,19 +584,25 @@ impl BM25 for HBM25Config {
                 continue;
             }
 
-            // Get all documents containing this term
-            if let Some(duplicates) = self.inverted_index_db.get_duplicates(txn, term_bytes)? {
-                for result in duplicates {
-                    let (_, posting_bytes) = result?;
-                    let posting: PostingListEntry = bincode::deserialize(posting_bytes)?;
-
-                    // Get document length
-                    let doc_length = self.doc_lengths_db.get(txn, &posting.doc_id)?.unwrap_or(0);
+            // Get all live postings of this term
+            for item in self
+                .postings_db
+                .prefix_iter(txn, &term_prefix(term_bytes))?
+            {
+                let (_, segment) = item?;
+                for posting in Posting::decode_segment(segment)? {
+                    let Some(entry) = self.doc_terms_db.get(txn, &posting.doc_id)? else {
+                        continue;
+                    };
+                    let entry = DocTerms::decode(entry)?;
+                    if entry.generation != posting.generation {
+                        continue;
+                    }
 
                     // Calculate BM25 score for this term in this document
                     let score = self.calculate_bm25_score(
                         posting.term_frequency,
-                        doc_length,
+                        entry.length,
                         doc_frequency,
                         metadata.total_docs,
                         metadata.avgdl,
@@ -458,31 +686,47 @@ impl HybridSearch for HelixGraphStorage {
             Err(e) => return Err(GraphError::from(e.to_string())),
         };
 
-        let mut combined_scores: HashMap<u128, f32> = HashMap::new();
+        let bm25_results = bm25_results?;
+        let vector_results = vector_results?.unwrap_or_default();
+        Ok(combine_hybrid_scores(
+            bm25_results,
+            vector_results,
+            alpha,
+            limit,
+        ))
+    }
+}
 
-        for (doc_id, score) in bm25_results? {
-            combined_scores.insert(doc_id, alpha * score);
-        }
+/// Combine BM25 scores and vector distances of the same documents into hybrid scores,
+/// `alpha * bm25_score + (1.0 - alpha) * vector_similarity`, highest first
+pub fn combine_hybrid_scores(
+    bm25_results: Vec<(u128, f32)>,
+    vector_results: Vec<(u128, f64)>,
+    alpha: f32,
+    limit: usize,
+) -> Vec<(u128, f32)> {
+    let mut combined_scores: HashMap<u128, f32> = HashMap::new();
+
+    for (doc_id, score) in bm25_results {
+        combined_scores.insert(doc_id, alpha * score);
+    }
 
-        // correct_score = alpha * bm25_score + (1.0 - alpha) * vector_score
-        if let Some(vector_results) = vector_results? {
-            for (doc_id, score) in vector_results {
-                let similarity = (1.0 / (1.0 + score)) as f32;
-                combined_scores
-                    .entry(doc_id)
-                    .and_modify(|existing_score| *existing_score += (1.0 - alpha) * similarity)
-                    .or_insert((1.0 - alpha) * similarity); // correction made here from score as f32 to similarity
-            }
-        }
+    // correct_score = alpha * bm25_score + (1.0 - alpha) * vector_score
+    for (doc_id, score) in vector_results {
+        let similarity = (1.0 / (1.0 + score)) as f32;
+        combined_scores
+            .entry(doc_id)
+            .and_modify(|existing_score| *existing_score += (1.0 - alpha) * similarity)
+            .or_insert((1.0 - alpha) * similarity); // correction made here from score as f32 to similarity
+    }
 
-        // Pre-allocate with exact capacity to avoid reallocation during collection
-        let mut results = Vec::with_capacity(combined_scores.len());
-        results.extend(combined_scores);
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        results.truncate(limit);
+    // Pre-allocate with exact capacity to avoid reallocation during collection
+    let mut results = Vec::with_capacity(combined_scores.len());
+    results.extend(combined_scores);
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
 
-        Ok(results)
-    }
+    results
 }
 
 pub trait BM25Flatten {
@@ -2,11 +2,14 @@
 mod tests {
     use crate::{
         helix_engine::{
+            bm25::analysis::TextAnalysis,
             bm25::bm25::{
                 BM25, BM25Flatten, BM25Metadata, HBM25Config, HybridSearch, METADATA_KEY,
+                PostingListEntry,
             },
+            bm25::highlight::{HighlightOptions, highlight},
             storage_core::{HelixGraphStorage, version_info::VersionInfo},
-            traversal_core::config::Config,
+            traversal_core::config::{Bm25MergeConfig, Config, GatewayConfig, TextSearchConfig},
             vector_core::{hnsw::HNSW, vector::HVector},
         },
         protocol::value::Value,
@@ -1886,4 +1889,401 @@ mod tests {
         assert!(flattened.contains("count"));
         assert!(flattened.contains("42"));
     }
+
+    // ============================================================================
+    // Segments and Merges
+    // ============================================================================
+
+    fn search_all(bm25: &HBM25Config, query: &str) -> Vec<(u128, f32)> {
+        let rtxn = bm25.graph_env.read_txn().unwrap();
+        let arena = Bump::new();
+        let mut results = bm25.search(&rtxn, query, 100, &arena).unwrap();
+        results.sort_by_key(|(doc_id, _)| *doc_id);
+        results
+    }
+
+    #[test]
+    fn test_updates_leave_dead_postings_until_merged() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let mut wtxn = bm25.graph_env.write_txn().unwrap();
+        bm25.insert_doc(&mut wtxn, 1u128, "graph database engine")
+            .unwrap();
+        for i in 0..5 {
+            bm25.update_doc(&mut wtxn, 2u128, &format!("database revision{i}"))
+                .unwrap();
+        }
+
+        let stats = bm25.stats(&wtxn).unwrap();
+        assert_eq!(stats.documents, 2);
+        assert_eq!(stats.dead_postings, 8);
+        assert!(stats.queued_merges > 0);
+
+        while bm25.merge_queued(&mut wtxn, 2).unwrap() > 0 {}
+        let stats = bm25.stats(&wtxn).unwrap();
+        assert_eq!(stats.dead_postings, 0);
+        assert_eq!(stats.queued_merges, 0);
+        assert_eq!(stats.segments, stats.terms);
+        // graph, database, engine and the last revision
+        assert_eq!(stats.terms, 4);
+        assert_eq!(stats.postings, 5);
+        wtxn.commit().unwrap();
+
+        let results = search_all(&bm25, "database");
+        assert_eq!(results.len(), 2);
+        assert!(search_all(&bm25, "revision3").is_empty());
+        assert_eq!(search_all(&bm25, "revision4")[0].0, 2u128);
+    }
+
+    #[test]
+    fn test_search_unchanged_by_merges() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let mut wtxn = bm25.graph_env.write_txn().unwrap();
+        for i in 0..30u128 {
+            let doc = format!("shared term{} words{} content", i % 7, i % 3);
+            bm25.insert_doc(&mut wtxn, i, &doc).unwrap();
+        }
+        for i in (0..30u128).step_by(4) {
+            bm25.delete_doc(&mut wtxn, i).unwrap();
+        }
+        wtxn.commit().unwrap();
+        let before = search_all(&bm25, "shared term3 words1");
+
+        let mut wtxn = bm25.graph_env.write_txn().unwrap();
+        let terms: Vec<Vec<u8>> = bm25
+            .term_segments_db
+            .iter(&wtxn)
+            .unwrap()
+            .map(|item| item.unwrap().0.to_vec())
+            .collect();
+        for term in &terms {
+            bm25.merge_term(&mut wtxn, term).unwrap();
+        }
+        wtxn.commit().unwrap();
+
+        assert_eq!(search_all(&bm25, "shared term3 words1"), before);
+        assert!(before.iter().all(|(doc_id, _)| doc_id % 4 != 0));
+    }
+
+    #[test]
+    fn test_insert_existing_document_replaces_it() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let mut wtxn = bm25.graph_env.write_txn().unwrap();
+        bm25.insert_doc(&mut wtxn, 1u128, "first version").unwrap();
+        bm25.insert_doc(&mut wtxn, 1u128, "second version").unwrap();
+
+        let metadata_bytes = bm25.metadata_db.get(&wtxn, METADATA_KEY).unwrap().unwrap();
+        let metadata: BM25Metadata = bincode::deserialize(metadata_bytes).unwrap();
+        assert_eq!(metadata.total_docs, 1);
+        assert_eq!(
+            bm25.term_frequencies_db.get(&wtxn, b"version").unwrap(),
+            Some(1)
+        );
+        wtxn.commit().unwrap();
+
+        assert!(search_all(&bm25, "first").is_empty());
+        assert_eq!(search_all(&bm25, "version").len(), 1);
+    }
+
+    #[test]
+    fn test_writes_merge_when_background_merges_are_off() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let bm25 = bm25.with_merge_policy(Bm25MergeConfig {
+            merge_factor: Some(3),
+            interval_ms: Some(0),
+            ..Default::default()
+        });
+        let mut wtxn = bm25.graph_env.write_txn().unwrap();
+        for i in 0..10u128 {
+            bm25.insert_doc(&mut wtxn, i, "common words").unwrap();
+        }
+
+        let stats = bm25.stats(&wtxn).unwrap();
+        assert_eq!(stats.queued_merges, 0);
+        assert!(stats.segments < 2 * 3);
+        assert!(stats.merges > 0);
+        wtxn.commit().unwrap();
+        assert_eq!(search_all(&bm25, "common").len(), 10);
+    }
+
+    #[test]
+    fn test_postings_written_before_segments_are_migrated() {
+        let (env, _temp_dir) = setup_test_env();
+        let mut wtxn = env.write_txn().unwrap();
+        let legacy: heed3::Database<heed3::types::Bytes, heed3::types::Bytes> = env
+            .database_options()
+            .types()
+            .flags(heed3::DatabaseFlags::DUP_SORT)
+            .name("bm25_inverted_index")
+            .create(&mut wtxn)
+            .unwrap();
+        let doc_lengths: heed3::Database<
+            heed3::types::U128<heed3::byteorder::BE>,
+            heed3::types::U32<heed3::byteorder::BE>,
+        > = env
+            .database_options()
+            .types()
+            .name("bm25_doc_lengths")
+            .create(&mut wtxn)
+            .unwrap();
+        let metadata_db: heed3::Database<heed3::types::Bytes, heed3::types::Bytes> = env
+            .database_options()
+            .types()
+            .name("bm25_metadata")
+            .create(&mut wtxn)
+            .unwrap();
+        let term_frequencies: heed3::Database<
+            heed3::types::Bytes,
+            heed3::types::U32<heed3::byteorder::BE>,
+        > = env
+            .database_options()
+            .types()
+            .name("bm25_term_frequencies")
+            .create(&mut wtxn)
+            .unwrap();
+        for (doc_id, terms) in [(1u128, ["old", "index"]), (2u128, ["old", "postings"])] {
+            for term in terms {
+                let posting = PostingListEntry {
+                    doc_id,
+                    term_frequency: 1,
+                };
+                legacy
+                    .put(
+                        &mut wtxn,
+                        term.as_bytes(),
+                        &bincode::serialize(&posting).unwrap(),
+                    )
+                    .unwrap();
+                let df = term_frequencies.get(&wtxn, term.as_bytes()).unwrap();
+                term_frequencies
+                    .put(&mut wtxn, term.as_bytes(), &(df.unwrap_or(0) + 1))
+                    .unwrap();
+            }
+            doc_lengths.put(&mut wtxn, &doc_id, &2).unwrap();
+        }
+        let metadata = BM25Metadata {
+            total_docs: 2,
+            avgdl: 2.0,
+            k1: 1.2,
+            b: 0.75,
+        };
+        metadata_db
+            .put(
+                &mut wtxn,
+                METADATA_KEY,
+                &bincode::serialize(&metadata).unwrap(),
+            )
+            .unwrap();
+
+        let bm25 = HBM25Config::new(&env, &mut wtxn).unwrap();
+        assert!(legacy.is_empty(&wtxn).unwrap());
+        wtxn.commit().unwrap();
+
+        assert_eq!(search_all(&bm25, "old").len(), 2);
+        assert_eq!(search_all(&bm25, "postings")[0].0, 2u128);
+
+        let mut wtxn = env.write_txn().unwrap();
+        bm25.delete_doc(&mut wtxn, 1u128).unwrap();
+        wtxn.commit().unwrap();
+        assert!(search_all(&bm25, "index").is_empty());
+        assert_eq!(search_all(&bm25, "old").len(), 1);
+    }
+
+    // ============================================================================
+    // Highlighting
+    // ============================================================================
+
+    fn terms(bm25: &HBM25Config, query: &str) -> Vec<String> {
+        bm25.tokenize::<true>(query)
+    }
+
+    #[test]
+    fn test_highlight_marks_terms_case_insensitively() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let snippets = highlight(
+            "Vectors, VECTORS and vectorized data",
+            &terms(&bm25, "vectors"),
+            &HighlightOptions::default(),
+        );
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(
+            snippets[0].text,
+            "<em>Vectors</em>, <em>VECTORS</em> and vectorized data"
+        );
+        assert_eq!(snippets[0].matches, vec![[0, 7], [9, 16]]);
+    }
+
+    #[test]
+    fn test_highlight_without_matches_is_empty() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let snippets = highlight(
+            "nothing relevant here",
+            &terms(&bm25, "graph"),
+            &HighlightOptions::default(),
+        );
+        assert!(snippets.is_empty());
+    }
+
+    #[test]
+    fn test_highlight_cuts_fragments_at_whitespace() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let filler = "lorem ipsum dolor sit amet ".repeat(20);
+        let text = format!("{filler}first needle here {filler}second needle there {filler}");
+        let options = HighlightOptions {
+            pre_tag: Some("[".to_string()),
+            post_tag: Some("]".to_string()),
+            fragment_chars: Some(40),
+            max_fragments: Some(5),
+        };
+        let snippets = highlight(&text, &terms(&bm25, "needle"), &options);
+
+        assert_eq!(snippets.len(), 2);
+        for snippet in &snippets {
+            assert!(snippet.text.contains("[needle]"));
+            assert!(snippet.text.chars().count() <= 40 + 2);
+            assert!(!snippet.text.starts_with(' ') && !snippet.text.ends_with(' '));
+            let words: Vec<&str> = snippet.text.split_whitespace().collect();
+            for word in [words[0], words[words.len() - 1]] {
+                let word = word.trim_matches(|c| c == '[' || c == ']');
+                assert!(text.split_whitespace().any(|whole| whole == word), "{word}");
+            }
+        }
+        let start = snippets[1].matches[0][0];
+        let chars: String = text.chars().skip(start).take(6).collect();
+        assert_eq!(chars, "needle");
+    }
+
+    #[test]
+    fn test_highlight_prefers_fragments_with_more_terms() {
+        let (bm25, _temp_dir) = setup_bm25_config();
+        let filler = "padding words between matches ".repeat(10);
+        let text = format!("alpha only {filler}alpha and beta together {filler}beta only");
+        let options = HighlightOptions {
+            fragment_chars: Some(50),
+            max_fragments: Some(1),
+            ..Default::default()
+        };
+        let snippets = highlight(&text, &terms(&bm25, "alpha beta"), &options);
+        assert_eq!(snippets.len(), 1);
+        assert!(
+            snippets[0]
+                .text
+                .contains("<em>alpha</em> and <em>beta</em>")
+        );
+    }
+
+    // ============================================================================
+    // Synonyms and Dictionary
+    // ============================================================================
+
+    fn analysis(synonyms: &[&str], dictionary: &[&str]) -> TextAnalysis {
+        TextAnalysis::load(&TextSearchConfig {
+            synonyms: Some(synonyms.iter().map(|s| s.to_string()).collect()),
+            dictionary: Some(dictionary.iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_equivalent_synonyms_expand_each_other() {
+        let analysis = analysis(&["car, automobile, auto"], &[]);
+        assert_eq!(
+            analysis.query_terms("red AUTO"),
+            vec!["red", "car", "automobile", "auto"]
+        );
+        assert_eq!(
+            analysis.query_terms("car"),
+            analysis.query_terms("automobile")
+        );
+    }
+
+    #[test]
+    fn test_replacing_synonyms_drop_the_original_term() {
+        let analysis = analysis(&["k8s => kubernetes", "ml => machine learning"], &[]);
+        assert_eq!(
+            analysis.query_terms("k8s cluster"),
+            vec!["kubernetes", "cluster"]
+        );
+        // Short terms with a rule aren't dropped before they're expanded
+        assert_eq!(analysis.query_terms("ml"), vec!["machine", "learning"]);
+        assert!(analysis.query_terms("go").is_empty());
+    }
+
+    #[test]
+    fn test_dictionary_terms_are_kept_whole() {
+        let analysis = analysis(&[], &["c++", "ML", ".net"]);
+        assert_eq!(
+            analysis.tokenize::<true>("Learning C++, ML and .NET on go"),
+            vec!["learning", "c++", "ml", "and", ".net"]
+        );
+        assert_eq!(analysis.query_terms("(c++)"), vec!["c++"]);
+    }
+
+    #[test]
+    fn test_text_analysis_reads_files() {
+        let dir = tempdir().unwrap();
+        let synonyms = dir.path().join("synonyms.txt");
+        let dictionary = dir.path().join("dictionary.txt");
+        std::fs::write(&synonyms, "# vehicles\ncar, automobile\n\nc++ => cpp\n").unwrap();
+        std::fs::write(&dictionary, "c++\n").unwrap();
+        let analysis = TextAnalysis::load(&TextSearchConfig {
+            synonym_files: Some(vec![synonyms]),
+            dictionary_files: Some(vec![dictionary]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            analysis.query_terms("car c++"),
+            vec!["car", "automobile", "cpp"]
+        );
+
+        let missing = TextAnalysis::load(&TextSearchConfig {
+            synonym_files: Some(vec![dir.path().join("missing.txt")]),
+            ..Default::default()
+        });
+        assert!(missing.is_err());
+        let phrase = TextAnalysis::load(&TextSearchConfig {
+            dictionary: Some(vec!["two words".to_string()]),
+            ..Default::default()
+        });
+        assert!(phrase.is_err());
+    }
+
+    #[test]
+    fn test_search_applies_configured_synonyms() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config {
+            gateway_config: Some(GatewayConfig {
+                text_search: Some(TextSearchConfig {
+                    synonyms: Some(vec!["car, automobile".to_string()]),
+                    dictionary: Some(vec!["c++".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let storage = HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            config,
+            VersionInfo::default(),
+        )
+        .unwrap();
+        let bm25 = storage.bm25.as_ref().unwrap();
+
+        let mut wtxn = storage.graph_env.write_txn().unwrap();
+        bm25.insert_doc(&mut wtxn, 1u128, "automobile repair manual")
+            .unwrap();
+        bm25.insert_doc(&mut wtxn, 2u128, "modern c++ templates")
+            .unwrap();
+        bm25.insert_doc(&mut wtxn, 3u128, "c programming").unwrap();
+        wtxn.commit().unwrap();
+
+        let results = search_all(bm25, "car");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 1u128);
+        let results = search_all(bm25, "c++");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 2u128);
+    }
 }
@@ -0,0 +1,201 @@
+//! Snippets of text search results with the query's terms highlighted.
+//!
+//! Matches are found at query time by splitting the text the way BM25 tokenizes it, so a word
+//! is highlighted exactly when it is a query term the index matched. Fragments are windows of
+//! the text around matches, cut at whitespace; the fragments matching the most distinct terms
+//! are returned, in the order they appear in the text.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// How snippets are cut and marked
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HighlightOptions {
+    /// Inserted before each match (default: `<em>`)
+    #[serde(default)]
+    pub pre_tag: Option<String>,
+    /// Inserted after each match (default: `</em>`)
+    #[serde(default)]
+    pub post_tag: Option<String>,
+    /// Characters of text per fragment (default: 150)
+    #[serde(default)]
+    pub fragment_chars: Option<usize>,
+    /// Fragments returned per property (default: 3)
+    #[serde(default)]
+    pub max_fragments: Option<usize>,
+}
+
+impl HighlightOptions {
+    pub const DEFAULT_PRE_TAG: &str = "<em>";
+    pub const DEFAULT_POST_TAG: &str = "</em>";
+    pub const DEFAULT_FRAGMENT_CHARS: usize = 150;
+    pub const DEFAULT_MAX_FRAGMENTS: usize = 3;
+
+    pub fn pre_tag(&self) -> &str {
+        self.pre_tag.as_deref().unwrap_or(Self::DEFAULT_PRE_TAG)
+    }
+
+    pub fn post_tag(&self) -> &str {
+        self.post_tag.as_deref().unwrap_or(Self::DEFAULT_POST_TAG)
+    }
+
+    pub fn fragment_chars(&self) -> usize {
+        self.fragment_chars
+            .unwrap_or(Self::DEFAULT_FRAGMENT_CHARS)
+            .max(1)
+    }
+
+    pub fn max_fragments(&self) -> usize {
+        self.max_fragments.unwrap_or(Self::DEFAULT_MAX_FRAGMENTS)
+    }
+}
+
+/// A fragment of text with its matches marked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    /// The fragment, with the tags around each match
+    pub text: String,
+    /// Character offsets of the fragment's matches in the whole text, as start and end
+    pub matches: Vec<[usize; 2]>,
+}
+
+/// A query term's occurrence, as character and byte ranges of the text
+#[derive(Debug, Clone, Copy)]
+struct Match {
+    chars: (usize, usize),
+    bytes: (usize, usize),
+    term: usize,
+}
+
+/// The words of `text` that are among `terms`, which are tokenized as BM25 tokenizes queries
+fn find_matches(text: &str, terms: &[String]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut word: Option<(usize, usize)> = None;
+    let mut chars = 0;
+    let mut close = |word: &mut Option<(usize, usize)>, end_char: usize, end_byte: usize| {
+        if let Some((start_char, start_byte)) = word.take() {
+            let token = text[start_byte..end_byte].to_lowercase();
+            if let Some(term) = terms.iter().position(|term| *term == token) {
+                matches.push(Match {
+                    chars: (start_char, end_char),
+                    bytes: (start_byte, end_byte),
+                    term,
+                });
+            }
+        }
+    };
+    for (byte, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            word.get_or_insert((chars, byte));
+        } else {
+            close(&mut word, chars, byte);
+        }
+        chars += 1;
+    }
+    close(&mut word, chars, text.len());
+    matches
+}
+
+/// The byte offset of the `n`th character of `text`
+fn byte_of(text: &str, n: usize) -> usize {
+    text.char_indices()
+        .nth(n)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Snippets of `text` around its occurrences of `terms`; empty when none occur
+pub fn highlight(text: &str, terms: &[String], options: &HighlightOptions) -> Vec<Snippet> {
+    let matches = find_matches(text, terms);
+    if matches.is_empty() || options.max_fragments() == 0 {
+        return Vec::new();
+    }
+    let total_chars = text.chars().count();
+    let width = options.fragment_chars();
+
+    // Each fragment starts a little before its first match and takes the matches that end
+    // within it, without overlapping the fragment before
+    let mut fragments: Vec<(usize, usize, &[Match])> = Vec::new();
+    let mut first = 0;
+    let mut previous_end = 0;
+    while first < matches.len() {
+        let (match_start, match_end) = matches[first].chars;
+        let start = match_start.saturating_sub(width / 4).max(previous_end);
+        let end = (start + width).max(match_end).min(total_chars);
+        let start = start
+            .min(end.saturating_sub(width))
+            .max(previous_end)
+            .min(match_start);
+        let last = first
+            + matches[first..]
+                .iter()
+                .take_while(|m| m.chars.1 <= end)
+                .count();
+        fragments.push((start, end, &matches[first..last]));
+        first = last;
+        previous_end = end;
+    }
+
+    let mut ranked: Vec<usize> = (0..fragments.len()).collect();
+    ranked.sort_by_key(|&i| {
+        let terms: HashSet<usize> = fragments[i].2.iter().map(|m| m.term).collect();
+        (
+            std::cmp::Reverse(terms.len()),
+            std::cmp::Reverse(fragments[i].2.len()),
+            i,
+        )
+    });
+    ranked.truncate(options.max_fragments());
+    ranked.sort_unstable();
+
+    ranked
+        .into_iter()
+        .map(|i| {
+            let (start, end, in_fragment) = fragments[i];
+            snippet(text, start, end, in_fragment, options)
+        })
+        .collect()
+}
+
+/// The fragment of `text` between two character offsets, widened or narrowed to whitespace
+/// so words aren't cut, with its matches tagged
+fn snippet(
+    text: &str,
+    start: usize,
+    end: usize,
+    matches: &[Match],
+    options: &HighlightOptions,
+) -> Snippet {
+    let first_match = matches[0].bytes.0;
+    let last_match = matches[matches.len() - 1].bytes.1;
+    let mut from = byte_of(text, start);
+    if from > 0 && !text[..from].ends_with(char::is_whitespace) {
+        // Start after the whitespace before the first whole word
+        from = text[from..first_match]
+            .find(char::is_whitespace)
+            .map_or(first_match, |offset| from + offset);
+        from += text[from..].len() - text[from..].trim_start().len();
+    }
+    let mut to = byte_of(text, end);
+    if to < text.len() && !text[to..].starts_with(char::is_whitespace) {
+        to = text[last_match..to]
+            .rfind(char::is_whitespace)
+            .map_or(last_match, |offset| last_match + offset);
+    }
+
+    let mut tagged = String::with_capacity(to - from + matches.len() * 9);
+    let mut cursor = from;
+    for m in matches {
+        tagged.push_str(&text[cursor..m.bytes.0]);
+        tagged.push_str(options.pre_tag());
+        tagged.push_str(&text[m.bytes.0..m.bytes.1]);
+        tagged.push_str(options.post_tag());
+        cursor = m.bytes.1;
+    }
+    tagged.push_str(text[cursor..to].trim_end());
+
+    Snippet {
+        text: tagged,
+        matches: matches.iter().map(|m| [m.chars.0, m.chars.1]).collect(),
+    }
+}
@@ -1,4 +1,7 @@
+pub mod analysis;
 pub mod bm25;
+pub mod highlight;
+pub mod segments;
 
 #[cfg(test)]
 pub mod bm25_tests;
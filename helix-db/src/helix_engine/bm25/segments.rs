@@ -0,0 +1,160 @@
+//! Segmented BM25 postings.
+//!
+//! Writing a document adds a segment holding its posting to each of its terms, rather than
+//! rewriting the terms' posting lists. Deleting or rewriting a document leaves its postings in
+//! place, dead: a posting is live while its document's generation is the one it was written
+//! with, so a delete only reads the document's own terms.
+//!
+//! Merges fold a term's segments into one and drop its dead postings. A write queues a term for
+//! merging once it has `merge_factor` segments or more than `max_deleted_percent` of its
+//! postings are dead, and the engine merges queued terms in the background. A term with four
+//! times `merge_factor` segments is merged by the write itself, so searches stay fast when
+//! background merges fall behind or are off.
+//!
+//! - `bm25_postings`: term, `0`, segment number → postings of document id, term frequency and
+//!   generation, ordered by document id
+//! - `bm25_doc_terms`: document id → generation, length and the document's terms
+//! - `bm25_term_segments`: term → next segment number, segments and dead postings
+//! - `bm25_merge_queue`: terms waiting for a merge
+
+use crate::helix_engine::{traversal_core::config::Bm25MergeConfig, types::GraphError};
+
+/// Bytes of an encoded posting
+pub const POSTING_SIZE: usize = 28;
+
+/// A document's occurrences of a term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posting {
+    pub doc_id: u128,
+    pub term_frequency: u32,
+    pub generation: u64,
+}
+
+impl Posting {
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.doc_id.to_be_bytes());
+        out.extend_from_slice(&self.term_frequency.to_be_bytes());
+        out.extend_from_slice(&self.generation.to_be_bytes());
+    }
+
+    /// The postings of a segment
+    pub fn decode_segment(bytes: &[u8]) -> Result<impl Iterator<Item = Posting> + '_, GraphError> {
+        if !bytes.len().is_multiple_of(POSTING_SIZE) {
+            return Err(GraphError::New(format!(
+                "BM25 segment of {} bytes isn't a whole number of postings",
+                bytes.len()
+            )));
+        }
+        Ok(bytes.chunks_exact(POSTING_SIZE).map(|chunk| Posting {
+            doc_id: u128::from_be_bytes(chunk[..16].try_into().unwrap()),
+            term_frequency: u32::from_be_bytes(chunk[16..20].try_into().unwrap()),
+            generation: u64::from_be_bytes(chunk[20..].try_into().unwrap()),
+        }))
+    }
+}
+
+/// Key of every segment of a term
+pub fn term_prefix(term: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(term.len() + 5);
+    key.extend_from_slice(term);
+    key.push(0);
+    key
+}
+
+pub fn segment_key(term: &[u8], segment: u32) -> Vec<u8> {
+    let mut key = term_prefix(term);
+    key.extend_from_slice(&segment.to_be_bytes());
+    key
+}
+
+/// Where a term's segments stand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermSegments {
+    /// Number of the next segment written
+    pub next: u32,
+    pub segments: u32,
+    /// Postings of deleted or rewritten documents not merged away yet
+    pub dead: u32,
+}
+
+impl TermSegments {
+    pub fn encode(&self) -> [u8; 12] {
+        let mut bytes = [0; 12];
+        bytes[..4].copy_from_slice(&self.next.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.segments.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.dead.to_be_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, GraphError> {
+        let bytes: &[u8; 12] = bytes
+            .try_into()
+            .map_err(|_| GraphError::New("BM25 term segments can't be decoded".to_string()))?;
+        Ok(Self {
+            next: u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+            segments: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            dead: u32::from_be_bytes(bytes[8..].try_into().unwrap()),
+        })
+    }
+}
+
+/// The indexed version of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocTerms<'a> {
+    pub generation: u64,
+    pub length: u32,
+    terms: &'a [u8],
+}
+
+impl<'a> DocTerms<'a> {
+    pub fn encode<'t>(
+        generation: u64,
+        length: u32,
+        terms: impl Iterator<Item = &'t str>,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&generation.to_be_bytes());
+        bytes.extend_from_slice(&length.to_be_bytes());
+        for (i, term) in terms.enumerate() {
+            if i > 0 {
+                bytes.push(0);
+            }
+            bytes.extend_from_slice(term.as_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, GraphError> {
+        if bytes.len() < 12 {
+            return Err(GraphError::New(
+                "BM25 document terms can't be decoded".to_string(),
+            ));
+        }
+        Ok(Self {
+            generation: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            length: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            terms: &bytes[12..],
+        })
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.terms
+            .split(|&b| b == 0)
+            .filter(|term| !term.is_empty())
+    }
+}
+
+impl Bm25MergeConfig {
+    /// Whether a term should be queued for a merge, given its live postings
+    pub fn wants_merge(&self, state: &TermSegments, live: u32) -> bool {
+        state.segments >= self.merge_factor()
+            || (state.dead > 0
+                && state.dead as u64 * 100
+                    > self.max_deleted_percent() as u64 * (state.dead as u64 + live as u64))
+    }
+
+    /// Whether a term has too many segments to wait for a background merge
+    pub fn must_merge(&self, state: &TermSegments) -> bool {
+        state.segments >= self.merge_factor().saturating_mul(4)
+    }
+}
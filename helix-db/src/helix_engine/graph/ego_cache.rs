@@ -0,0 +1,190 @@
+//! Cached k-hop neighborhoods ("ego networks") of the edge types listed in
+//! `gateway_config.ego_cache`, so `::EGO` steps expanding the same hub nodes again and again
+//! skip walking their edges.
+//!
+//! The gateway drops an edge type's neighborhoods whenever the change feed reports a write
+//! touching it. Each neighborhood remembers the transaction it was read in, and one read
+//! before the latest drop of its edge type is neither stored nor served, so a read racing a
+//! write can't leave a stale neighborhood behind.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::helix_engine::traversal_core::config::EgoCacheConfig;
+use crate::utils::label_hash::hash_label;
+
+pub struct EgoCache {
+    /// By edge label hash
+    edges: HashMap<[u8; 4], EdgeCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct EdgeCache {
+    max_depth: usize,
+    max_entries: usize,
+    state: Mutex<EdgeCacheState>,
+}
+
+#[derive(Default)]
+struct EdgeCacheState {
+    /// By node id and depth
+    entries: HashMap<(u128, usize), Entry>,
+    /// Neighborhoods read in transactions before this one may be stale
+    fresh_from: usize,
+    /// Ticks on every lookup, to find the least recently used entry
+    clock: u64,
+}
+
+struct Entry {
+    nodes: Arc<[u128]>,
+    txn_id: usize,
+    last_used: u64,
+}
+
+impl EgoCache {
+    pub fn from_config(configs: &[EgoCacheConfig]) -> Self {
+        EgoCache {
+            edges: configs
+                .iter()
+                .filter(|config| config.max_entries() > 0)
+                .map(|config| {
+                    let cache = EdgeCache {
+                        max_depth: config.max_depth(),
+                        max_entries: config.max_entries(),
+                        state: Mutex::default(),
+                    };
+                    (hash_label(&config.edge, None), cache)
+                })
+                .collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether any edge type is cached
+    pub fn is_enabled(&self) -> bool {
+        !self.edges.is_empty()
+    }
+
+    /// Whether neighborhoods `depth` hops deep along `label` edges are cached
+    pub fn caches(&self, label: &[u8; 4], depth: usize) -> bool {
+        self.edges
+            .get(label)
+            .is_some_and(|cache| depth <= cache.max_depth)
+    }
+
+    /// The cached neighborhood of `node` for a read in transaction `txn_id`
+    pub fn get(
+        &self,
+        label: &[u8; 4],
+        node: u128,
+        depth: usize,
+        txn_id: usize,
+    ) -> Option<Arc<[u128]>> {
+        let cache = self.edges.get(label)?;
+        let mut state = cache.state();
+        state.clock += 1;
+        let (clock, fresh_from) = (state.clock, state.fresh_from);
+        let hit = match state.entries.get_mut(&(node, depth)) {
+            Some(entry) if entry.txn_id >= fresh_from && txn_id >= fresh_from => {
+                entry.last_used = clock;
+                Some(Arc::clone(&entry.nodes))
+            }
+            _ => None,
+        };
+        drop(state);
+
+        match hit {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        hit
+    }
+
+    /// Store the neighborhood of `node` read in transaction `txn_id`, dropping the least
+    /// recently used one when full
+    pub fn insert(
+        &self,
+        label: &[u8; 4],
+        node: u128,
+        depth: usize,
+        txn_id: usize,
+        nodes: Arc<[u128]>,
+    ) {
+        let Some(cache) = self.edges.get(label) else {
+            return;
+        };
+        if depth > cache.max_depth {
+            return;
+        }
+        let mut state = cache.state();
+        if txn_id < state.fresh_from {
+            return;
+        }
+        if state.entries.len() >= cache.max_entries
+            && !state.entries.contains_key(&(node, depth))
+            && let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+        {
+            state.entries.remove(&oldest);
+        }
+        let last_used = state.clock;
+        state.entries.insert(
+            (node, depth),
+            Entry {
+                nodes,
+                txn_id,
+                last_used,
+            },
+        );
+    }
+
+    /// Drop the neighborhoods of the edge types in `labels` after a write committed, or of
+    /// every edge type when `labels` is `None`. `txn_id` is a transaction that sees the write.
+    pub fn invalidate(&self, labels: Option<&[String]>, txn_id: usize) {
+        let hashes = labels.map(|labels| {
+            labels
+                .iter()
+                .map(|label| hash_label(label, None))
+                .collect::<Vec<_>>()
+        });
+        for (label, cache) in &self.edges {
+            if hashes.as_ref().is_none_or(|hashes| hashes.contains(label)) {
+                let mut state = cache.state();
+                state.entries.clear();
+                state.fresh_from = state.fresh_from.max(txn_id);
+            }
+        }
+    }
+
+    /// Neighborhoods cached across edge types
+    pub fn len(&self) -> usize {
+        self.edges
+            .values()
+            .map(|cache| cache.state().entries.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+impl EdgeCache {
+    fn state(&self) -> MutexGuard<'_, EdgeCacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
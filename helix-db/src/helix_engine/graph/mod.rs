@@ -1,4 +1,6 @@
 pub mod claim_filter;
+pub mod ego_cache;
 pub mod ppr;
 pub mod ppr_cache;
 pub mod ppr_warmup;
+pub mod random_walk;
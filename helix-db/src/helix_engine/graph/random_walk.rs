@@ -0,0 +1,225 @@
+//! Biased random walks in the manner of Node2Vec, for training node embeddings outside the
+//! database.
+//!
+//! A walk steps from node to node along edges in either direction. Having come from `t` to
+//! `v`, it steps back to `t` with weight `1 / p`, to a neighbor of `t` with weight 1 and
+//! further away with weight `1 / q`, so a low `p` keeps walks local and a low `q` sends them
+//! outwards. Parallel edges weigh their neighbor once per edge.
+
+use std::collections::HashMap;
+
+use heed3::RoTxn;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::traversal_core::LMDB_STRING_HEADER_LENGTH;
+use crate::helix_engine::types::GraphError;
+use crate::utils::label_hash::hash_label;
+
+/// How to walk the graph
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WalkConfig {
+    /// Nodes per walk, counting the one it starts from
+    pub walk_length: usize,
+    /// Walks started from each node
+    pub walks_per_node: usize,
+    /// Return parameter, the higher the less often a walk steps straight back
+    pub p: f64,
+    /// In-out parameter, the higher the closer a walk stays to where it started
+    pub q: f64,
+    /// Only start walks from nodes with this label
+    pub label: Option<String>,
+    /// Only follow edges with these labels, or every edge when empty
+    pub edge_labels: Vec<String>,
+    /// Seed for walks that are the same every time over the same graph
+    pub seed: Option<u64>,
+}
+
+impl Default for WalkConfig {
+    fn default() -> Self {
+        WalkConfig {
+            walk_length: 80,
+            walks_per_node: 10,
+            p: 1.0,
+            q: 1.0,
+            label: None,
+            edge_labels: Vec::new(),
+            seed: None,
+        }
+    }
+}
+
+impl WalkConfig {
+    fn validate(&self) -> Result<(), GraphError> {
+        if self.walk_length == 0 {
+            return Err(GraphError::New(
+                "walk_length must be at least 1".to_string(),
+            ));
+        }
+        for (name, value) in [("p", self.p), ("q", self.q)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(GraphError::New(format!(
+                    "{name} must be a positive number, got {value}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `walks_per_node` walks from every node (with `label`, if set), in node id order with each
+/// node's walks together. A walk ends early at a node without edges to follow.
+pub fn random_walks(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    config: &WalkConfig,
+) -> Result<Vec<Vec<u128>>, GraphError> {
+    config.validate()?;
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let mut walker = Walker {
+        storage,
+        txn,
+        edge_labels: config
+            .edge_labels
+            .iter()
+            .map(|label| hash_label(label, None))
+            .collect(),
+        neighbors: HashMap::new(),
+    };
+
+    let mut starts = Vec::new();
+    for result in storage.nodes_db.iter(txn)? {
+        let (id, value) = result?;
+        if config
+            .label
+            .as_deref()
+            .is_none_or(|label| stored_label(value) == Some(label.as_bytes()))
+        {
+            starts.push(id);
+        }
+    }
+
+    let mut walks = Vec::with_capacity(starts.len() * config.walks_per_node);
+    for start in starts {
+        for _ in 0..config.walks_per_node {
+            walks.push(walker.walk(start, config, &mut rng)?);
+        }
+    }
+    Ok(walks)
+}
+
+/// The skip-gram pairs of `walks`: each node with every node at most `window` steps before or
+/// after it in the same walk
+pub fn co_occurrences(walks: &[Vec<u128>], window: usize) -> Vec<(u128, u128)> {
+    let mut pairs = Vec::new();
+    for walk in walks {
+        for (i, &node) in walk.iter().enumerate() {
+            let end = (i + window + 1).min(walk.len());
+            for (j, &other) in walk
+                .iter()
+                .enumerate()
+                .take(end)
+                .skip(i.saturating_sub(window))
+            {
+                if j != i {
+                    pairs.push((node, other));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+struct Walker<'a> {
+    storage: &'a HelixGraphStorage,
+    txn: &'a RoTxn<'a>,
+    edge_labels: Vec<[u8; 4]>,
+    /// Sorted neighbors of the nodes walked so far
+    neighbors: HashMap<u128, Vec<u128>>,
+}
+
+impl Walker<'_> {
+    fn walk(
+        &mut self,
+        start: u128,
+        config: &WalkConfig,
+        rng: &mut StdRng,
+    ) -> Result<Vec<u128>, GraphError> {
+        let mut walk = Vec::with_capacity(config.walk_length);
+        walk.push(start);
+        while walk.len() < config.walk_length {
+            let current = walk[walk.len() - 1];
+            self.load(current)?;
+            let previous = match walk.len() {
+                1 => None,
+                len => Some(walk[len - 2]),
+            };
+            if let Some(previous) = previous {
+                self.load(previous)?;
+            }
+            let neighbors = &self.neighbors[&current];
+            if neighbors.is_empty() {
+                break;
+            }
+            let next = match previous {
+                None => neighbors[rng.random_range(0..neighbors.len())],
+                Some(previous) => {
+                    let behind = &self.neighbors[&previous];
+                    let weight = |node: &u128| match *node == previous {
+                        true => 1.0 / config.p,
+                        false if behind.binary_search(node).is_ok() => 1.0,
+                        false => 1.0 / config.q,
+                    };
+                    let total: f64 = neighbors.iter().map(weight).sum();
+                    let mut target = rng.random_range(0.0..total);
+                    *neighbors
+                        .iter()
+                        .find(|node| {
+                            target -= weight(node);
+                            target < 0.0
+                        })
+                        .unwrap_or(&neighbors[neighbors.len() - 1])
+                }
+            };
+            walk.push(next);
+        }
+        Ok(walk)
+    }
+
+    /// Read the nodes joined to `node` by edges either way, leaving out vectors
+    fn load(&mut self, node: u128) -> Result<(), GraphError> {
+        if self.neighbors.contains_key(&node) {
+            return Ok(());
+        }
+        let mut neighbors = Vec::new();
+        for db in [&self.storage.out_edges_db, &self.storage.in_edges_db] {
+            for result in db.prefix_iter(self.txn, &node.to_be_bytes())? {
+                let (key, value) = result?;
+                let label = &key[16..];
+                if !self.edge_labels.is_empty() && !self.edge_labels.iter().any(|l| label == l) {
+                    continue;
+                }
+                let (_, neighbor) = HelixGraphStorage::unpack_adj_edge_data(value)?;
+                if self.storage.nodes_db.get(self.txn, &neighbor)?.is_some() {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+        neighbors.sort_unstable();
+        self.neighbors.insert(node, neighbors);
+        Ok(())
+    }
+}
+
+/// The label a stored node starts with
+fn stored_label(value: &[u8]) -> Option<&[u8]> {
+    let header = value.get(..LMDB_STRING_HEADER_LENGTH)?;
+    let len = u64::from_le_bytes(header.try_into().ok()?) as usize;
+    value.get(LMDB_STRING_HEADER_LENGTH..LMDB_STRING_HEADER_LENGTH + len)
+}
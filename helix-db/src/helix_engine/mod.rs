@@ -5,6 +5,7 @@ pub mod reranker;
 pub mod storage_core;
 pub mod traversal_core;
 pub mod types;
+pub mod udf;
 pub mod vector_core;
 
 #[cfg(test)]
@@ -11,7 +11,7 @@
 use crate::helix_engine::{
     reranker::{
         errors::RerankerResult,
-        reranker::{extract_score, update_score},
+        reranker::{Reranker, extract_score, update_score},
     },
     traversal_core::traversal_value::TraversalValue,
 };
@@ -190,6 +190,24 @@ pub fn apply_signal_boosts<'arena>(
     Ok(boosted_items.into_iter().map(|(item, _)| item).collect())
 }
 
+/// Boosting as a pipeline stage, so `::BOOST` can follow or precede other rerankers
+impl Reranker for SignalBoostConfig {
+    fn rerank<'arena, I>(
+        &self,
+        items: I,
+        _query: Option<&str>,
+    ) -> RerankerResult<Vec<TraversalValue<'arena>>>
+    where
+        I: Iterator<Item = TraversalValue<'arena>>,
+    {
+        apply_signal_boosts(items.collect(), self)
+    }
+
+    fn name(&self) -> &str {
+        "SignalBoost"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,6 +446,22 @@ mod tests {
         assert_eq!(config.recency_base_time, Some(12345));
     }
 
+    #[test]
+    fn test_reranker_applies_boosts() {
+        let arena = Bump::new();
+        let config = disabled_boosts_config();
+
+        let items = vec![
+            create_test_vector(&arena, 1, 0.2),
+            create_test_vector(&arena, 2, 0.7),
+        ];
+        let result = config.rerank(items.into_iter(), None).unwrap();
+
+        assert_eq!(config.name(), "SignalBoost");
+        assert_approx_eq(extract_score_from_result(&result[0]), 0.7, "first score");
+        assert_approx_eq(extract_score_from_result(&result[1]), 0.2, "second score");
+    }
+
     #[test]
     fn test_empty_input() {
         let config = SignalBoostConfig::default();
@@ -70,14 +70,22 @@ pub fn extract_score(item: &TraversalValue) -> RerankerResult<f64> {
 /// Update the score of a TraversalValue.
 ///
 /// This modifies the distance/score field of the item to reflect
-/// the new reranked score.
+/// the new reranked score. The score the search gave the item is kept
+/// the first time it is reranked, so it can still be read afterwards.
 pub fn update_score(item: &mut TraversalValue, new_score: f64) -> RerankerResult<()> {
     match item {
         TraversalValue::Vector(v) => {
+            let search_distance = v.score();
+            v.search_distance.get_or_insert(search_distance);
             v.distance = Some(new_score);
             Ok(())
         }
-        TraversalValue::NodeWithScore { score, .. } => {
+        TraversalValue::NodeWithScore {
+            score,
+            search_score,
+            ..
+        } => {
+            search_score.get_or_insert(*score);
             *score = new_score;
             Ok(())
         }
@@ -0,0 +1,64 @@
+//! Edge types declared `@acyclic`, like `E::DependsOn @acyclic { From: Task, To: Task }`, for
+//! dependency and hierarchy graphs where a cycle is corrupt data.
+//!
+//! Before an edge of such a type is added, [`HelixGraphStorage::check_acyclic`] searches the
+//! type's edges from the new edge's `To` node. The edge closes a cycle exactly when that search
+//! reaches its `From` node, so a graph kept acyclic this way stays acyclic, and each check only
+//! walks what the `To` node already reaches.
+
+use std::collections::HashSet;
+
+use heed3::RoTxn;
+use uuid::Uuid;
+
+use crate::{
+    helix_engine::{storage_core::HelixGraphStorage, types::GraphError},
+    utils::label_hash::hash_label,
+};
+
+/// The label hashes of the edge types that must not form cycles
+pub fn by_edge_label(labels: Vec<String>) -> HashSet<[u8; 4]> {
+    labels.iter().map(|label| hash_label(label, None)).collect()
+}
+
+impl HelixGraphStorage {
+    /// Fail when an edge labelled `label` from `from_node` to `to_node` would close a cycle of
+    /// an `@acyclic` edge type
+    pub fn check_acyclic(
+        &self,
+        txn: &RoTxn,
+        label: &str,
+        from_node: u128,
+        to_node: u128,
+    ) -> Result<(), GraphError> {
+        let label_hash = hash_label(label, None);
+        if !self.acyclic_edges.contains(&label_hash) {
+            return Ok(());
+        }
+        let mut seen = HashSet::from([to_node]);
+        let mut frontier = vec![to_node];
+        while let Some(node) = frontier.pop() {
+            if node == from_node {
+                return Err(GraphError::CycleDetected(format!(
+                    "{label} edge from {} to {} would close a cycle",
+                    Uuid::from_u128(from_node),
+                    Uuid::from_u128(to_node)
+                )));
+            }
+            let Some(edges) = self
+                .out_edges_db
+                .get_duplicates(txn, &Self::out_edge_key(&node, &label_hash))?
+            else {
+                continue;
+            };
+            for result in edges {
+                let (_, value) = result?;
+                let (_, next) = Self::unpack_adj_edge_data(value)?;
+                if seen.insert(next) {
+                    frontier.push(next);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+//! Append-only audit trail of committed write route invocations.
+//!
+//! The writer thread [`begin`]s an audit before running a write route. Each transaction the
+//! route commits appends its entry with [`HelixGraphStorage::audit_write`] just before the
+//! commit, so an entry exists exactly when its write does.
+
+use std::cell::RefCell;
+use std::ops::Bound;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use heed3::{PutFlags, RwTxn};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, write_log},
+        types::GraphError,
+    },
+    utils::id::v6_uuid,
+};
+
+thread_local! {
+    /// Route name and actor of the write running on this thread, when it is audited
+    static PENDING: RefCell<Option<(String, Option<String>)>> = const { RefCell::new(None) };
+}
+
+/// Audit the transactions the current thread commits until [`end`] is called
+pub fn begin(query: String, actor: Option<String>) {
+    PENDING.with(|pending| *pending.borrow_mut() = Some((query, actor)));
+}
+
+/// Stop auditing the current thread's transactions
+pub fn end() {
+    PENDING.with(|pending| *pending.borrow_mut() = None);
+}
+
+/// A single successful write route invocation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Time ordered id of the entry
+    pub id: Uuid,
+    /// Milliseconds since the Unix epoch at which the write committed
+    pub timestamp_ms: u64,
+    /// Name of the write route
+    pub query: String,
+    /// Identity of the caller, when the request was authenticated
+    pub actor: Option<String>,
+    /// Nodes, edges and vectors the write created, updated or dropped
+    pub item_ids: Vec<Uuid>,
+}
+
+impl AuditEntry {
+    pub fn new(query: String, actor: Option<String>, item_ids: Vec<u128>) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        AuditEntry {
+            id: Uuid::from_u128(v6_uuid()),
+            timestamp_ms,
+            query,
+            actor,
+            item_ids: item_ids.into_iter().map(Uuid::from_u128).collect(),
+        }
+    }
+}
+
+/// Query parameters for reading the audit log. All conditions must match.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    /// Only entries at or after this time (ms since the Unix epoch)
+    pub since_ms: Option<u64>,
+    /// Only entries before this time (ms since the Unix epoch)
+    pub until_ms: Option<u64>,
+    pub query: Option<String>,
+    pub actor: Option<String>,
+    /// Only entries touching this item id
+    pub item_id: Option<Uuid>,
+    /// Maximum number of entries returned (default: 100)
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    pub const DEFAULT_LIMIT: usize = 100;
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.since_ms
+            .is_none_or(|since| entry.timestamp_ms >= since)
+            && self.until_ms.is_none_or(|until| entry.timestamp_ms < until)
+            && self.query.as_ref().is_none_or(|q| *q == entry.query)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|a| entry.actor.as_ref() == Some(a))
+            && self.item_id.is_none_or(|id| entry.item_ids.contains(&id))
+    }
+}
+
+impl HelixGraphStorage {
+    /// Append an entry in `txn`, so it commits or aborts with the write.
+    /// Entries are never updated or removed.
+    pub fn append_audit_entry(
+        &self,
+        txn: &mut RwTxn,
+        entry: &AuditEntry,
+    ) -> Result<(), GraphError> {
+        let bytes = sonic_rs::to_vec(entry).map_err(|e| GraphError::New(e.to_string()))?;
+        self.audit_db
+            .put_with_flags(txn, PutFlags::NO_OVERWRITE, &entry.id.as_u128(), &bytes)?;
+        Ok(())
+    }
+
+    /// Append the entry of the audited write running on this thread, covering the items
+    /// written since the write log was last taken. Does nothing outside an audited write.
+    pub fn audit_write(&self, txn: &mut RwTxn) -> Result<(), GraphError> {
+        let Some((query, actor)) = PENDING.with(|pending| pending.borrow().clone()) else {
+            return Ok(());
+        };
+        self.append_audit_entry(txn, &AuditEntry::new(query, actor, write_log::take()))
+    }
+
+    /// Entries matching `filter`, newest first
+    pub fn audit_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, GraphError> {
+        let limit = filter.limit.unwrap_or(AuditFilter::DEFAULT_LIMIT);
+        let txn = self.graph_env.read_txn()?;
+        let mut entries = Vec::new();
+        for item in self.audit_db.rev_iter(&txn)? {
+            if entries.len() >= limit {
+                break;
+            }
+            let (_, bytes) = item?;
+            let entry: AuditEntry =
+                sonic_rs::from_slice(bytes).map_err(|e| GraphError::New(e.to_string()))?;
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Up to `limit` entries recorded after the entry `after`, oldest first, for consumers
+    /// that follow the log. Starts from the oldest entry when `after` is `None`.
+    pub fn audit_entries_after(
+        &self,
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, GraphError> {
+        let txn = self.graph_env.read_txn()?;
+        let start = after.map_or(Bound::Unbounded, |id| Bound::Excluded(id.as_u128()));
+        let mut entries = Vec::new();
+        for item in self
+            .audit_db
+            .range(&txn, &(start, Bound::Unbounded))?
+            .take(limit)
+        {
+            let (_, bytes) = item?;
+            entries.push(sonic_rs::from_slice(bytes).map_err(|e| GraphError::New(e.to_string()))?);
+        }
+        Ok(entries)
+    }
+}
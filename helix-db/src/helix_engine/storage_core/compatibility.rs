@@ -0,0 +1,213 @@
+//! Startup check that the data in the environment can be served by this build.
+//!
+//! Every start that passes the check records in the metadata table the schema it served: the
+//! version of each item label and the types of its properties. The next start compares its
+//! compiled schema against that record and the stored storage version. The data is
+//! incompatible when it was written in a newer storage version, when an item is stored at a
+//! version this build has no migration to, or when a property changed type without a migration.
+//! The engine then leaves the data as it is, skipping its storage migrations and backfills,
+//! and keeps the reasons in [`HelixGraphStorage::schema_mismatch`]. The gateway refuses writes
+//! with them and fails `/readyz`, while reads are still served.
+
+use std::collections::BTreeMap;
+
+use heed3::{Database, Env, RoTxn, RwTxn, WithTls, types::Bytes};
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::{
+    storage_core::{
+        HelixGraphStorage,
+        metadata::{LATEST_STORAGE_VERSION, StorageMetadata},
+        version_info::VersionInfo,
+    },
+    types::GraphError,
+};
+
+pub const SCHEMA_RECORD_KEY: &[u8] = b"schema_record";
+
+/// The schema the data was last served with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaRecord {
+    /// Latest version of each item label
+    #[serde(default)]
+    pub item_versions: BTreeMap<String, u8>,
+    /// Property types of each item label
+    #[serde(default)]
+    pub properties: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Why the stored data can't be served by this build
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaMismatch {
+    pub reasons: Vec<String>,
+}
+
+pub enum Compatibility {
+    /// The data can be served, and `record` saved once it's migrated
+    Compatible(SchemaRecord),
+    Incompatible(SchemaMismatch),
+}
+
+#[derive(Deserialize)]
+struct SchemaJson {
+    #[serde(default)]
+    schema: SchemaData,
+}
+
+#[derive(Deserialize, Default)]
+struct SchemaData {
+    #[serde(default)]
+    nodes: Vec<TypeData>,
+    #[serde(default)]
+    vectors: Vec<TypeData>,
+    #[serde(default)]
+    edges: Vec<TypeData>,
+}
+
+#[derive(Deserialize)]
+struct TypeData {
+    name: String,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+impl SchemaRecord {
+    /// The schema of this build, from its migrations and the schema JSON it was compiled with
+    pub fn compiled(
+        schema_json: Option<&str>,
+        version_info: &VersionInfo,
+    ) -> Result<Self, GraphError> {
+        let mut record = SchemaRecord::default();
+        if let Some(schema_json) = schema_json {
+            let SchemaJson { schema } = sonic_rs::from_str(schema_json)
+                .map_err(|e| GraphError::New(format!("compiled schema is invalid: {e}")))?;
+            for ty in schema
+                .nodes
+                .into_iter()
+                .chain(schema.vectors)
+                .chain(schema.edges)
+            {
+                record.properties.insert(ty.name, ty.properties);
+            }
+        }
+        let labels = record
+            .properties
+            .keys()
+            .cloned()
+            .chain(version_info.0.keys().map(|label| label.to_string()))
+            .collect::<Vec<_>>();
+        for label in labels {
+            let version = version_info.get_latest(&label);
+            record.item_versions.insert(label, version);
+        }
+        Ok(record)
+    }
+
+    pub fn read(
+        txn: &RoTxn<WithTls>,
+        metadata_db: &Database<Bytes, Bytes>,
+    ) -> Result<Option<Self>, GraphError> {
+        match metadata_db.get(txn, SCHEMA_RECORD_KEY)? {
+            None => Ok(None),
+            Some(bytes) => sonic_rs::from_slice(bytes)
+                .map(Some)
+                .map_err(|e| GraphError::New(format!("stored schema record is invalid: {e}"))),
+        }
+    }
+
+    pub fn save(
+        &self,
+        txn: &mut RwTxn,
+        metadata_db: &Database<Bytes, Bytes>,
+    ) -> Result<(), GraphError> {
+        let bytes = sonic_rs::to_vec(self)
+            .map_err(|e| GraphError::New(format!("could not encode schema record: {e}")))?;
+        metadata_db.put(txn, SCHEMA_RECORD_KEY, &bytes)?;
+        Ok(())
+    }
+
+    /// Why data last served with `stored` can't be served with this schema
+    pub fn incompatibilities(&self, stored: &SchemaRecord) -> Vec<String> {
+        let mut reasons = Vec::new();
+        for (label, &stored_version) in &stored.item_versions {
+            let version = match self.item_versions.get(label) {
+                Some(&version) => version,
+                // Dropped from the schema, so nothing reads its items
+                None if !self.properties.is_empty() => continue,
+                None => 1,
+            };
+            if stored_version > version {
+                reasons.push(format!(
+                    "`{label}` is stored at schema version {stored_version}, this build only has migrations up to version {version}"
+                ));
+                continue;
+            }
+            if stored_version < version {
+                // A migration upgrades the stored items as they're read
+                continue;
+            }
+            let (Some(properties), Some(stored_properties)) =
+                (self.properties.get(label), stored.properties.get(label))
+            else {
+                continue;
+            };
+            for (name, ty) in properties {
+                if let Some(stored_ty) = stored_properties.get(name)
+                    && stored_ty != ty
+                {
+                    reasons.push(format!(
+                        "property `{name}` of `{label}` changed from {stored_ty} to {ty} without a migration"
+                    ));
+                }
+            }
+        }
+        reasons
+    }
+}
+
+/// Compare the schema this build was compiled with against what the environment was last
+/// served with
+pub fn check(
+    env: &Env,
+    metadata_db: &Database<Bytes, Bytes>,
+    schema_json: Option<&str>,
+    version_info: &VersionInfo,
+) -> Result<Compatibility, GraphError> {
+    let txn = env.read_txn()?;
+    let mut reasons = Vec::new();
+    if let Some(version) = StorageMetadata::read_version(&txn, metadata_db)?
+        && version > LATEST_STORAGE_VERSION
+    {
+        reasons.push(format!(
+            "data is in storage version {version}, this build only reads up to version {LATEST_STORAGE_VERSION}"
+        ));
+    }
+
+    let mut record = SchemaRecord::compiled(schema_json, version_info)?;
+    if let Some(stored) = SchemaRecord::read(&txn, metadata_db)? {
+        reasons.extend(record.incompatibilities(&stored));
+        if schema_json.is_none() {
+            // Nothing to compare the stored properties against, keep them for the next build
+            record.properties = stored.properties;
+        }
+        for (label, version) in stored.item_versions {
+            record.item_versions.entry(label).or_insert(version);
+        }
+    }
+
+    if reasons.is_empty() {
+        Ok(Compatibility::Compatible(record))
+    } else {
+        Ok(Compatibility::Incompatible(SchemaMismatch { reasons }))
+    }
+}
+
+impl HelixGraphStorage {
+    /// Record the schema the data is now served with
+    pub(crate) fn record_schema(&self, record: &SchemaRecord) -> Result<(), GraphError> {
+        let mut txn = self.graph_env.write_txn()?;
+        record.save(&mut txn, &self.metadata_db)?;
+        txn.commit()?;
+        Ok(())
+    }
+}
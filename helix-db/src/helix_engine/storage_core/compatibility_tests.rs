@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use tempfile::TempDir;
+
+use super::{
+    HelixGraphStorage,
+    compatibility::SchemaRecord,
+    metadata::{LATEST_STORAGE_VERSION, STORAGE_VERSION_KEY, StorageMetadata},
+    version_info::{ItemInfo, VersionInfo},
+};
+use crate::helix_engine::traversal_core::config::Config;
+
+fn schema_json(age_type: &str) -> String {
+    format!(
+        r#"{{"schema": {{"nodes": [{{"name": "User", "properties": {{"id": "ID", "age": "{age_type}"}}}}], "vectors": [], "edges": []}}, "queries": []}}"#
+    )
+}
+
+/// `User` migrated up to `latest`
+fn user_at(latest: u8) -> VersionInfo {
+    VersionInfo(HashMap::from([(
+        "User",
+        ItemInfo {
+            latest,
+            transition_fns: Vec::new(),
+        },
+    )]))
+}
+
+fn open(dir: &TempDir, schema: Option<String>, version_info: VersionInfo) -> HelixGraphStorage {
+    let config = Config {
+        schema,
+        ..Config::default()
+    };
+    HelixGraphStorage::new(dir.path().to_str().unwrap(), config, version_info).unwrap()
+}
+
+fn stored_record(storage: &HelixGraphStorage) -> SchemaRecord {
+    let txn = storage.graph_env.read_txn().unwrap();
+    SchemaRecord::read(&txn, &storage.metadata_db)
+        .unwrap()
+        .expect("schema is recorded")
+}
+
+#[test]
+fn test_start_records_compiled_schema() {
+    let dir = TempDir::new().unwrap();
+    let storage = open(&dir, Some(schema_json("I32")), VersionInfo::default());
+
+    assert!(storage.schema_mismatch.is_none());
+    let record = stored_record(&storage);
+    assert_eq!(record.item_versions.get("User"), Some(&1));
+    assert_eq!(
+        record.properties["User"].get("age").map(String::as_str),
+        Some("I32")
+    );
+}
+
+#[test]
+fn test_restart_with_same_schema_is_compatible() {
+    let dir = TempDir::new().unwrap();
+    drop(open(&dir, Some(schema_json("I32")), VersionInfo::default()));
+
+    let storage = open(&dir, Some(schema_json("I32")), VersionInfo::default());
+    assert!(storage.schema_mismatch.is_none());
+}
+
+#[test]
+fn test_type_change_without_migration_refuses_writes() {
+    let dir = TempDir::new().unwrap();
+    drop(open(&dir, Some(schema_json("I32")), VersionInfo::default()));
+
+    let storage = open(&dir, Some(schema_json("String")), VersionInfo::default());
+    let mismatch = storage.schema_mismatch.clone().expect("incompatible");
+    assert_eq!(
+        mismatch.reasons,
+        vec!["property `age` of `User` changed from I32 to String without a migration"]
+    );
+    // The data keeps the schema it was written with
+    assert_eq!(
+        stored_record(&storage).properties["User"]["age"],
+        "I32".to_string()
+    );
+}
+
+#[test]
+fn test_type_change_with_migration_is_compatible() {
+    let dir = TempDir::new().unwrap();
+    drop(open(&dir, Some(schema_json("I32")), VersionInfo::default()));
+
+    let storage = open(&dir, Some(schema_json("String")), user_at(2));
+    assert!(storage.schema_mismatch.is_none());
+    let record = stored_record(&storage);
+    assert_eq!(record.item_versions.get("User"), Some(&2));
+    assert_eq!(record.properties["User"]["age"], "String".to_string());
+}
+
+#[test]
+fn test_downgraded_build_refuses_writes() {
+    let dir = TempDir::new().unwrap();
+    drop(open(&dir, Some(schema_json("String")), user_at(2)));
+
+    let storage = open(&dir, Some(schema_json("I32")), VersionInfo::default());
+    let mismatch = storage.schema_mismatch.clone().expect("incompatible");
+    assert_eq!(
+        mismatch.reasons,
+        vec![
+            "`User` is stored at schema version 2, this build only has migrations up to version 1"
+        ]
+    );
+}
+
+#[test]
+fn test_restart_without_schema_keeps_recorded_properties() {
+    let dir = TempDir::new().unwrap();
+    drop(open(&dir, Some(schema_json("I32")), VersionInfo::default()));
+    drop(open(&dir, None, VersionInfo::default()));
+
+    let storage = open(&dir, Some(schema_json("String")), VersionInfo::default());
+    assert!(storage.schema_mismatch.is_some());
+}
+
+#[test]
+fn test_newer_storage_version_is_left_alone() {
+    let dir = TempDir::new().unwrap();
+    let storage = open(&dir, None, VersionInfo::default());
+    let newer = LATEST_STORAGE_VERSION + 1;
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage
+        .metadata_db
+        .put(&mut txn, STORAGE_VERSION_KEY, &newer.to_le_bytes())
+        .unwrap();
+    txn.commit().unwrap();
+    drop(storage);
+
+    // Opening no longer fails on the unknown version, it refuses writes instead
+    let storage = open(&dir, None, VersionInfo::default());
+    let mismatch = storage.schema_mismatch.clone().expect("incompatible");
+    assert_eq!(
+        mismatch.reasons,
+        vec![format!(
+            "data is in storage version {newer}, this build only reads up to version {LATEST_STORAGE_VERSION}"
+        )]
+    );
+    let txn = storage.graph_env.read_txn().unwrap();
+    assert_eq!(
+        StorageMetadata::read_version(&txn, &storage.metadata_db).unwrap(),
+        Some(newer)
+    );
+}
@@ -0,0 +1,129 @@
+//! Edge constraints, declared in the schema as bounds after the node type of an edge's end,
+//! like `E::AuthoredBy { From: Comment [1], To: User }`.
+//!
+//! Writes adding nodes, or adding and removing edges, note the nodes whose bounds they may
+//! break. Before the write commits, [`HelixGraphStorage::check_constraints`] counts the edges
+//! of the nodes noted, so a node can be added and given its required edges in the same write.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use bumpalo::Bump;
+use heed3::RoTxn;
+use uuid::Uuid;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        types::{ConstraintViolation, EdgeConstraint, GraphError},
+    },
+    utils::{items::Node, label_hash::hash_label},
+};
+
+thread_local! {
+    static PENDING: RefCell<HashSet<u128>> = RefCell::new(HashSet::new());
+}
+
+/// The constraints of each edge label hash
+pub fn by_edge_label(constraints: Vec<EdgeConstraint>) -> HashMap<[u8; 4], Vec<EdgeConstraint>> {
+    let mut by_label: HashMap<[u8; 4], Vec<EdgeConstraint>> = HashMap::new();
+    for constraint in constraints {
+        by_label
+            .entry(hash_label(&constraint.edge_label, None))
+            .or_default()
+            .push(constraint);
+    }
+    by_label
+}
+
+/// Forget the nodes noted on the current thread, such as by a write that was aborted
+pub fn reset() {
+    PENDING.with(|pending| pending.borrow_mut().clear());
+}
+
+fn note(id: u128) {
+    PENDING.with(|pending| pending.borrow_mut().insert(id));
+}
+
+impl HelixGraphStorage {
+    /// Note a node just added, when a constraint requires nodes of its label to have edges
+    pub fn constrain_node(&self, label: &str, id: u128) {
+        let required = self
+            .edge_constraints
+            .values()
+            .flatten()
+            .any(|constraint| constraint.node_label == label && constraint.min > 0);
+        if required {
+            note(id);
+        }
+    }
+
+    /// Note a node that gained or lost an edge of the label hash `label`
+    pub fn constrain_edge_end(&self, label: &[u8; 4], node_id: u128) {
+        if self.edge_constraints.contains_key(label) {
+            note(node_id);
+        }
+    }
+
+    /// Check the constraints of the nodes noted by the write running on this thread, failing
+    /// with the first one broken. Writes call this right before committing.
+    pub fn check_constraints(&self, txn: &RoTxn) -> Result<(), GraphError> {
+        let mut pending: Vec<u128> = PENDING
+            .with(|pending| std::mem::take(&mut *pending.borrow_mut()))
+            .into_iter()
+            .collect();
+        // Report the same violation however the set is ordered
+        pending.sort_unstable();
+        for id in pending {
+            let arena = Bump::new();
+            let node = match self.get_node(txn, &id, &arena) {
+                Ok(node) => node,
+                // Dropped since, or a vector
+                Err(GraphError::NodeNotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            if let Some(violation) = self.constraint_violations(txn, &node)?.into_iter().next() {
+                return Err(GraphError::ConstraintViolation(violation));
+            }
+        }
+        Ok(())
+    }
+
+    /// The constraints a stored node breaks
+    pub fn constraint_violations(
+        &self,
+        txn: &RoTxn,
+        node: &Node,
+    ) -> Result<Vec<ConstraintViolation>, GraphError> {
+        let mut violations = Vec::new();
+        for (label, constraints) in &self.edge_constraints {
+            for constraint in constraints
+                .iter()
+                .filter(|constraint| constraint.node_label == node.label)
+            {
+                let (db, key) = match constraint.incoming {
+                    true => (&self.in_edges_db, Self::in_edge_key(&node.id, label)),
+                    false => (&self.out_edges_db, Self::out_edge_key(&node.id, label)),
+                };
+                let edges = match db.get_duplicates(txn, &key)? {
+                    Some(duplicates) => duplicates.count() as u64,
+                    None => 0,
+                };
+                if !constraint.allows(edges) {
+                    violations.push(ConstraintViolation {
+                        node: Uuid::from_u128(node.id),
+                        constraint: constraint.clone(),
+                        edges,
+                    });
+                }
+            }
+        }
+        violations.sort_by(|a, b| {
+            (&a.constraint.edge_label, a.constraint.incoming)
+                .cmp(&(&b.constraint.edge_label, b.constraint.incoming))
+        });
+        Ok(violations)
+    }
+}
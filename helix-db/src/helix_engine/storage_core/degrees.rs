@@ -0,0 +1,136 @@
+//! Edge counts per node, edge label and direction, kept so `::DEGREE` and `::NEIGHBOR_COUNT`
+//! read a number instead of walking the node's edges.
+//!
+//! Every write adding or removing an edge adjusts the counts of both its ends in the same
+//! transaction. Storages written before the counts existed are counted when opened.
+
+use heed3::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::{storage_core::HelixGraphStorage, types::GraphError};
+
+/// Metadata key set once the counts cover every stored edge
+const DEGREES_COUNTED_KEY: &[u8] = b"degrees_counted";
+
+/// Which of a node's edges a degree counts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegreeDirection {
+    /// Edges from the node
+    Out,
+    /// Edges to the node
+    In,
+    /// Edges either way, so an edge from the node to itself counts twice
+    Both,
+}
+
+impl HelixGraphStorage {
+    /// Degree key generator. Creates a 21 byte array from the node id, 4 byte label and
+    /// direction.
+    ///
+    /// key = `node(16)` | `label-id(4)` | `incoming(1)`                 ← 21 B
+    #[inline(always)]
+    pub fn degree_key(node_id: &u128, label: &[u8; 4], incoming: bool) -> [u8; 21] {
+        let mut key = [0u8; 21];
+        key[0..16].copy_from_slice(&node_id.to_be_bytes());
+        key[16..20].copy_from_slice(label);
+        key[20] = incoming as u8;
+        key
+    }
+
+    /// Adjust the count of one end of an edge, `incoming` when the edge goes to the node
+    pub fn count_degree(
+        &self,
+        txn: &mut RwTxn,
+        label: &[u8; 4],
+        node_id: u128,
+        incoming: bool,
+        delta: i64,
+    ) -> Result<(), GraphError> {
+        let key = Self::degree_key(&node_id, label, incoming);
+        let count = self.degrees_db.get(txn, &key)?.unwrap_or(0) as i64 + delta;
+        match count > 0 {
+            true => self.degrees_db.put(txn, &key, &(count as u64))?,
+            false => {
+                self.degrees_db.delete(txn, &key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of edges with the label at the node in `direction`
+    pub fn degree(
+        &self,
+        txn: &RoTxn,
+        node_id: u128,
+        label: &[u8; 4],
+        direction: DegreeDirection,
+    ) -> Result<u64, GraphError> {
+        let count = |incoming| -> Result<u64, GraphError> {
+            let key = Self::degree_key(&node_id, label, incoming);
+            Ok(self.degrees_db.get(txn, &key)?.unwrap_or(0))
+        };
+        Ok(match direction {
+            DegreeDirection::Out => count(false)?,
+            DegreeDirection::In => count(true)?,
+            DegreeDirection::Both => count(false)? + count(true)?,
+        })
+    }
+
+    /// The number of edges of any label to or from the node, so a neighbor joined by two edges
+    /// counts twice
+    pub fn neighbor_count(&self, txn: &RoTxn, node_id: u128) -> Result<u64, GraphError> {
+        let mut total = 0;
+        for result in self.degrees_db.prefix_iter(txn, &node_id.to_be_bytes())? {
+            total += result?.1;
+        }
+        Ok(total)
+    }
+
+    /// Forget the counts of a node being deleted
+    pub(crate) fn drop_degrees(&self, txn: &mut RwTxn, node_id: u128) -> Result<(), GraphError> {
+        let keys = self
+            .degrees_db
+            .prefix_iter(txn, &node_id.to_be_bytes())?
+            .map(|result| result.map(|(key, _)| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for key in keys {
+            self.degrees_db.delete(txn, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Count the edges of every node again from the adjacency lists
+    pub(crate) fn recount_degrees(&self, txn: &mut RwTxn) -> Result<(), GraphError> {
+        self.degrees_db.clear(txn)?;
+        for (db, incoming) in [(&self.out_edges_db, false), (&self.in_edges_db, true)] {
+            let mut counts: Vec<([u8; 21], u64)> = Vec::new();
+            for result in db.iter(txn)? {
+                let (adjacency_key, _) = result?;
+                // Adjacency keys are the node id and label a degree key starts with
+                let mut key = [0u8; 21];
+                key[0..20].copy_from_slice(adjacency_key);
+                key[20] = incoming as u8;
+                match counts.last_mut() {
+                    Some((last, count)) if *last == key => *count += 1,
+                    _ => counts.push((key, 1)),
+                }
+            }
+            for (key, count) in counts {
+                self.degrees_db.put(txn, &key, &count)?;
+            }
+        }
+        self.metadata_db.put(txn, DEGREES_COUNTED_KEY, &[])?;
+        Ok(())
+    }
+
+    /// Count the edges of a storage written before degrees were kept
+    pub(crate) fn backfill_degrees(&self) -> Result<(), GraphError> {
+        let mut txn = self.graph_env.write_txn()?;
+        if self.metadata_db.get(&txn, DEGREES_COUNTED_KEY)?.is_some() {
+            return Ok(());
+        }
+        self.recount_degrees(&mut txn)?;
+        txn.commit()?;
+        Ok(())
+    }
+}
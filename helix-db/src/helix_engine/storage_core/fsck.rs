@@ -0,0 +1,718 @@
+//! Consistency checks between the tables of a stored graph, behind `helix fsck`.
+//!
+//! Every node, edge and vector is written to several LMDB tables: the primary record, the
+//! adjacency lists, secondary indices, the HNSW graph and BM25 postings. A crash in older
+//! versions or a bug in a write path can leave these out of step. [`check`] scans the tables
+//! in a read transaction and reports where they disagree; [`repair`] runs the same scan in a
+//! write transaction and fixes what can be rebuilt from primary records without losing data.
+//! Nodes breaking the schema's edge constraints, such as ones written before a constraint
+//! was declared, are reported too, but only the application can fix them.
+
+use bumpalo::Bump;
+use heed3::{Database, RoTxn, RwTxn, byteorder::BE, types::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+use crate::{
+    helix_engine::{
+        bm25::{bm25::BM25, segments::Posting},
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        types::{GraphError, SecondaryIndex},
+        vector_core::vector_core::{ENTRY_POINT_KEY, VECTOR_PREFIX, VectorCore},
+    },
+    utils::{
+        items::{Edge, Node},
+        label_hash::hash_label,
+    },
+};
+
+/// What is inconsistent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A record that can't be decoded
+    UnreadableRecord,
+    /// An edge whose from or to node doesn't exist
+    DanglingEdge,
+    /// An adjacency list entry without a matching edge
+    OrphanedAdjacency,
+    /// An edge missing from the adjacency list of one of its nodes
+    MissingAdjacency,
+    /// A secondary index entry for a node that doesn't exist or no longer has the value
+    StaleIndexEntry,
+    /// A node property missing from its secondary index
+    MissingIndexEntry,
+    /// A unique index value held by two nodes
+    UniqueIndexConflict,
+    /// A node with fewer or more edges of a type than a schema constraint allows
+    EdgeConstraintViolation,
+    /// A vector record without vector data
+    VectorWithoutData,
+    /// Vector data without a vector record
+    VectorDataWithoutRecord,
+    /// An HNSW link to or from a vector without data, which fails searches that reach it
+    OrphanedHnswEdge,
+    /// An HNSW entry point that isn't a stored vector
+    MissingEntryPoint,
+    /// BM25 postings of a document that doesn't exist
+    OrphanedPosting,
+}
+
+impl IssueKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            IssueKind::UnreadableRecord => "Unreadable records",
+            IssueKind::DanglingEdge => "Edges to missing nodes",
+            IssueKind::OrphanedAdjacency => "Orphaned adjacency entries",
+            IssueKind::MissingAdjacency => "Missing adjacency entries",
+            IssueKind::StaleIndexEntry => "Stale secondary index entries",
+            IssueKind::MissingIndexEntry => "Missing secondary index entries",
+            IssueKind::UniqueIndexConflict => "Unique index conflicts",
+            IssueKind::EdgeConstraintViolation => "Edge constraint violations",
+            IssueKind::VectorWithoutData => "Vectors without data",
+            IssueKind::VectorDataWithoutRecord => "Vector data without records",
+            IssueKind::OrphanedHnswEdge => "Orphaned HNSW links",
+            IssueKind::MissingEntryPoint => "Missing HNSW entry points",
+            IssueKind::OrphanedPosting => "Orphaned BM25 postings",
+        }
+    }
+}
+
+/// An inconsistency found by a check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// The node, edge, vector or document the issue is about
+    pub id: Uuid,
+    pub detail: String,
+    /// Whether a repair can fix the issue without losing data
+    pub repairable: bool,
+    /// Whether a repair fixed the issue
+    #[serde(default)]
+    pub repaired: bool,
+    #[serde(skip)]
+    fix: Option<Fix>,
+}
+
+/// Adjacency list of an edge entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Out,
+    In,
+}
+
+/// A change that brings the tables back in step
+#[derive(Debug, Clone, PartialEq)]
+enum Fix {
+    DropEdge(u128),
+    DeleteAdjacency(Direction, [u8; 20], [u8; 32]),
+    PutAdjacency(Direction, [u8; 20], [u8; 32]),
+    DeleteIndexEntry {
+        index: String,
+        key: Vec<u8>,
+        node: u128,
+    },
+    PutIndexEntry {
+        index: String,
+        key: Vec<u8>,
+        node: u128,
+    },
+    DeleteHnswEdge(Vec<u8>),
+    DeleteDocument(u128),
+}
+
+impl Fix {
+    /// Removals run before insertions, so a stale unique index entry is gone before the
+    /// entry that replaces it is written
+    fn is_removal(&self) -> bool {
+        !matches!(self, Fix::PutAdjacency(..) | Fix::PutIndexEntry { .. })
+    }
+}
+
+/// What a check looked at and found
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FsckReport {
+    pub nodes: u64,
+    pub edges: u64,
+    pub vectors: u64,
+    pub index_entries: u64,
+    pub hnsw_edges: u64,
+    pub postings: u64,
+    pub issues: Vec<Issue>,
+}
+
+impl FsckReport {
+    /// Whether nothing is left inconsistent
+    pub fn is_consistent(&self) -> bool {
+        self.issues.iter().all(|issue| issue.repaired)
+    }
+
+    pub fn repaired(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.repaired).count()
+    }
+
+    fn push(&mut self, kind: IssueKind, id: u128, detail: String, fix: Option<Fix>) {
+        self.issues.push(Issue {
+            kind,
+            id: Uuid::from_u128(id),
+            detail,
+            repairable: fix.is_some(),
+            repaired: false,
+            fix,
+        });
+    }
+}
+
+/// Check the stored graph for inconsistencies between its tables. Runs in a read
+/// transaction, so writes carry on while it scans.
+pub fn check(storage: &HelixGraphStorage) -> Result<FsckReport, GraphError> {
+    let txn = storage.graph_env.read_txn()?;
+    scan(storage, &txn)
+}
+
+/// Check the stored graph and fix every repairable issue in the same write transaction, so
+/// nothing changes between finding an issue and fixing it. Writes wait until it commits.
+pub fn repair(storage: &HelixGraphStorage) -> Result<FsckReport, GraphError> {
+    let mut txn = storage.graph_env.write_txn()?;
+    let mut report = scan(storage, &txn)?;
+    for removals in [true, false] {
+        for issue in &mut report.issues {
+            if let Some(fix) = &issue.fix
+                && fix.is_removal() == removals
+            {
+                apply(storage, &mut txn, fix)?;
+                issue.repaired = true;
+            }
+        }
+    }
+    if report.repaired() > 0 {
+        // Fixed adjacency lists change the edges each node has
+        storage.recount_degrees(&mut txn)?;
+    }
+    txn.commit()?;
+    Ok(report)
+}
+
+fn apply(storage: &HelixGraphStorage, txn: &mut RwTxn, fix: &Fix) -> Result<(), GraphError> {
+    let adjacency = |direction: &Direction| match direction {
+        Direction::Out => storage.out_edges_db,
+        Direction::In => storage.in_edges_db,
+    };
+    match fix {
+        Fix::DropEdge(id) => storage.drop_edge(txn, id)?,
+        Fix::DeleteAdjacency(direction, key, value) => {
+            adjacency(direction).delete_one_duplicate(txn, key, value)?;
+        }
+        Fix::PutAdjacency(direction, key, value) => adjacency(direction).put(txn, key, value)?,
+        Fix::DeleteIndexEntry { index, key, node } => {
+            if let Some((db, kind)) = storage.secondary_indices.get(index) {
+                match kind {
+                    SecondaryIndex::Unique(_) => db.delete(txn, key)?,
+                    _ => db.delete_one_duplicate(txn, key, node)?,
+                };
+            }
+        }
+        Fix::PutIndexEntry { index, key, node } => {
+            if let Some((db, _)) = storage.secondary_indices.get(index) {
+                db.put(txn, key, node)?;
+            }
+        }
+        Fix::DeleteHnswEdge(key) => {
+            storage.vectors.edges_db.delete(txn, key)?;
+        }
+        Fix::DeleteDocument(id) => {
+            if let Some(bm25) = &storage.bm25 {
+                bm25.delete_doc(txn, *id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scan(storage: &HelixGraphStorage, txn: &RoTxn) -> Result<FsckReport, GraphError> {
+    let mut report = FsckReport::default();
+    check_nodes(storage, txn, &mut report)?;
+    check_indices(storage, txn, &mut report)?;
+    check_edges(storage, txn, &mut report)?;
+    check_vectors(storage, txn, &mut report)?;
+    check_postings(storage, txn, &mut report)?;
+    Ok(report)
+}
+
+/// Whether a node or vector is stored under `id`
+fn exists(storage: &HelixGraphStorage, txn: &RoTxn, id: u128) -> Result<bool, GraphError> {
+    Ok(storage.nodes_db.get(txn, &id)?.is_some()
+        || storage
+            .vectors
+            .vector_properties_db
+            .get(txn, &id)?
+            .is_some())
+}
+
+fn has_vector_data(storage: &HelixGraphStorage, txn: &RoTxn, id: u128) -> Result<bool, GraphError> {
+    Ok(storage
+        .vectors
+        .vectors_db
+        .get(txn, &VectorCore::vector_key(id, 0))?
+        .is_some())
+}
+
+fn read_node<'arena>(
+    storage: &HelixGraphStorage,
+    id: u128,
+    bytes: &[u8],
+    arena: &'arena Bump,
+) -> Option<Node<'arena>> {
+    Node::from_bincode_bytes(id, bytes, arena)
+        .ok()
+        .map(|node| storage.version_info.upgrade_to_node_latest(node))
+}
+
+/// The secondary index key of a node's value for `index`
+fn index_key(node: &Node, index: &str) -> Result<Option<Vec<u8>>, GraphError> {
+    node.get_property(index)
+        .map(|value| bincode::serialize(value).map_err(GraphError::from))
+        .transpose()
+}
+
+/// Whether the node stored under `id` has the value `key` for `index`
+fn node_has_key(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    index: &str,
+    key: &[u8],
+    id: u128,
+) -> Result<bool, GraphError> {
+    let arena = Bump::new();
+    let Some(bytes) = storage.nodes_db.get(txn, &id)? else {
+        return Ok(false);
+    };
+    match read_node(storage, id, bytes, &arena) {
+        Some(node) => Ok(index_key(&node, index)?.as_deref() == Some(key)),
+        None => Ok(false),
+    }
+}
+
+/// Secondary index entries every node should have, and the edges its constraints require
+fn check_nodes(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    report: &mut FsckReport,
+) -> Result<(), GraphError> {
+    let mut arena = Bump::new();
+    for item in storage.nodes_db.iter(txn)? {
+        let (id, bytes) = item?;
+        report.nodes += 1;
+        let Some(node) = read_node(storage, id, bytes, &arena) else {
+            report.push(
+                IssueKind::UnreadableRecord,
+                id,
+                "node record can't be decoded".to_string(),
+                None,
+            );
+            continue;
+        };
+        for (index, (db, kind)) in &storage.secondary_indices {
+            let Some(key) = index_key(&node, index)? else {
+                continue;
+            };
+            let indexed = match kind {
+                SecondaryIndex::Unique(_) => match db.get(txn, &key)? {
+                    Some(holder) if holder == id => true,
+                    Some(holder) if node_has_key(storage, txn, index, &key, holder)? => {
+                        report.push(
+                            IssueKind::UniqueIndexConflict,
+                            id,
+                            format!(
+                                "unique index {index} already maps its value to node {}",
+                                Uuid::from_u128(holder)
+                            ),
+                            None,
+                        );
+                        continue;
+                    }
+                    _ => false,
+                },
+                _ => contains_duplicate(db, txn, &key, &id)?,
+            };
+            if !indexed {
+                report.push(
+                    IssueKind::MissingIndexEntry,
+                    id,
+                    format!("node's {index} is not in its secondary index"),
+                    Some(Fix::PutIndexEntry {
+                        index: index.clone(),
+                        key,
+                        node: id,
+                    }),
+                );
+            }
+        }
+        for violation in storage.constraint_violations(txn, &node)? {
+            report.push(
+                IssueKind::EdgeConstraintViolation,
+                id,
+                format!("{} {}", node.label, violation.requirement()),
+                None,
+            );
+        }
+        arena.reset();
+    }
+    Ok(())
+}
+
+fn contains_duplicate(
+    db: &Database<Bytes, U128<BE>>,
+    txn: &RoTxn,
+    key: &[u8],
+    id: &u128,
+) -> Result<bool, GraphError> {
+    if let Some(duplicates) = db.get_duplicates(txn, key)? {
+        for item in duplicates {
+            if item?.1 == *id {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Secondary index entries should point at nodes that still have the indexed value
+fn check_indices(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    report: &mut FsckReport,
+) -> Result<(), GraphError> {
+    for (index, (db, _)) in &storage.secondary_indices {
+        for item in db.iter(txn)? {
+            let (key, id) = item?;
+            report.index_entries += 1;
+            let detail = if storage.nodes_db.get(txn, &id)?.is_none() {
+                format!("{index} index entry points at a node that doesn't exist")
+            } else if !node_has_key(storage, txn, index, key, id)? {
+                format!("{index} index entry holds a value the node no longer has")
+            } else {
+                continue;
+            };
+            report.push(
+                IssueKind::StaleIndexEntry,
+                id,
+                detail,
+                Some(Fix::DeleteIndexEntry {
+                    index: index.clone(),
+                    key: key.to_vec(),
+                    node: id,
+                }),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Edges should join stored nodes and be in the adjacency lists of both, and every
+/// adjacency entry should belong to an edge
+fn check_edges(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    report: &mut FsckReport,
+) -> Result<(), GraphError> {
+    let mut arena = Bump::new();
+    let mut listed = Vec::new();
+    for item in storage.edges_db.iter(txn)? {
+        let (id, bytes) = item?;
+        report.edges += 1;
+        let Ok(edge) = Edge::from_bincode_bytes(id, bytes, &arena) else {
+            report.push(
+                IssueKind::UnreadableRecord,
+                id,
+                "edge record can't be decoded".to_string(),
+                None,
+            );
+            continue;
+        };
+        let mut missing = Vec::new();
+        for (end, node) in [("from", edge.from_node), ("to", edge.to_node)] {
+            if !exists(storage, txn, node)? {
+                missing.push(format!("{end} node {}", Uuid::from_u128(node)));
+            }
+        }
+        if missing.is_empty() {
+            listed.push((
+                id,
+                edge.from_node,
+                edge.to_node,
+                hash_label(edge.label, None),
+            ));
+        } else {
+            report.push(
+                IssueKind::DanglingEdge,
+                id,
+                format!(
+                    "{} edge's {} doesn't exist",
+                    edge.label,
+                    missing.join(" and ")
+                ),
+                Some(Fix::DropEdge(id)),
+            );
+        }
+        arena.reset();
+    }
+
+    for direction in [Direction::Out, Direction::In] {
+        let matched = check_adjacency(storage, txn, direction, report)?;
+        if matched == listed.len() as u64 {
+            continue;
+        }
+        // Some edges aren't listed; find which by looking each one up
+        let db = match direction {
+            Direction::Out => storage.out_edges_db,
+            Direction::In => storage.in_edges_db,
+        };
+        for &(id, from, to, label) in &listed {
+            let (key, value) = match direction {
+                Direction::Out => (
+                    HelixGraphStorage::out_edge_key(&from, &label),
+                    HelixGraphStorage::pack_edge_data(&id, &to),
+                ),
+                Direction::In => (
+                    HelixGraphStorage::in_edge_key(&to, &label),
+                    HelixGraphStorage::pack_edge_data(&id, &from),
+                ),
+            };
+            if !lists(&db, txn, &key, &value)? {
+                let node = match direction {
+                    Direction::Out => from,
+                    Direction::In => to,
+                };
+                report.push(
+                    IssueKind::MissingAdjacency,
+                    id,
+                    format!(
+                        "edge is missing from the {} edges of node {}",
+                        direction.name(),
+                        Uuid::from_u128(node)
+                    ),
+                    Some(Fix::PutAdjacency(direction, key, value)),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Direction {
+    fn name(&self) -> &'static str {
+        match self {
+            Direction::Out => "outgoing",
+            Direction::In => "incoming",
+        }
+    }
+}
+
+fn lists(
+    db: &Database<Bytes, Bytes>,
+    txn: &RoTxn,
+    key: &[u8],
+    value: &[u8],
+) -> Result<bool, GraphError> {
+    if let Some(duplicates) = db.get_duplicates(txn, key)? {
+        for item in duplicates {
+            if item?.1 == value {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Report adjacency entries without a matching edge, returning how many entries did match
+fn check_adjacency(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    direction: Direction,
+    report: &mut FsckReport,
+) -> Result<u64, GraphError> {
+    let db = match direction {
+        Direction::Out => storage.out_edges_db,
+        Direction::In => storage.in_edges_db,
+    };
+    let mut arena = Bump::new();
+    let mut matched = 0;
+    for item in db.iter(txn)? {
+        let (key, value) = item?;
+        let (Ok(key), Ok(value)) = (<[u8; 20]>::try_from(key), <[u8; 32]>::try_from(value)) else {
+            report.push(
+                IssueKind::UnreadableRecord,
+                0,
+                format!("{} adjacency entry has the wrong size", direction.name()),
+                None,
+            );
+            continue;
+        };
+        let node = u128::from_be_bytes(key[..16].try_into().expect("key is 20 bytes"));
+        let (edge_id, other) = HelixGraphStorage::unpack_adj_edge_data(&value)?;
+        let edge = match storage.edges_db.get(txn, &edge_id)? {
+            Some(bytes) => Edge::from_bincode_bytes(edge_id, bytes, &arena).ok(),
+            None => None,
+        };
+        let belongs = edge.is_some_and(|edge| {
+            let ends = match direction {
+                Direction::Out => (edge.from_node, edge.to_node),
+                Direction::In => (edge.to_node, edge.from_node),
+            };
+            ends == (node, other) && hash_label(edge.label, None) == key[16..20]
+        });
+        // Entries of edges to missing nodes count as matched, as dropping the edge removes them
+        if belongs {
+            matched += 1;
+        } else {
+            report.push(
+                IssueKind::OrphanedAdjacency,
+                edge_id,
+                format!(
+                    "{} edges of node {} list an edge that doesn't exist",
+                    direction.name(),
+                    Uuid::from_u128(node)
+                ),
+                Some(Fix::DeleteAdjacency(direction, key, value)),
+            );
+        }
+        arena.reset();
+    }
+    Ok(matched)
+}
+
+/// Vector records and data should come in pairs, and the HNSW graph should only link
+/// vectors that have data
+fn check_vectors(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    report: &mut FsckReport,
+) -> Result<(), GraphError> {
+    let vectors = &storage.vectors;
+    for item in vectors.vector_properties_db.iter(txn)? {
+        let (id, _) = item?;
+        report.vectors += 1;
+        if !has_vector_data(storage, txn, id)? {
+            report.push(
+                IssueKind::VectorWithoutData,
+                id,
+                "vector record has no vector data".to_string(),
+                None,
+            );
+        }
+    }
+
+    for item in vectors.vectors_db.prefix_iter(txn, VECTOR_PREFIX)? {
+        let (key, _) = item?;
+        let Some(id) = key
+            .get(VECTOR_PREFIX.len()..VECTOR_PREFIX.len() + 16)
+            .and_then(|id| id.try_into().ok())
+            .map(u128::from_be_bytes)
+        else {
+            continue;
+        };
+        if vectors.vector_properties_db.get(txn, &id)?.is_none() {
+            report.push(
+                IssueKind::VectorDataWithoutRecord,
+                id,
+                "vector data has no vector record".to_string(),
+                None,
+            );
+        }
+    }
+
+    if let Some(entry_point) = vectors.vectors_db.get(txn, ENTRY_POINT_KEY)? {
+        let mut id = [0u8; 16];
+        let len = entry_point.len().min(16);
+        id[..len].copy_from_slice(&entry_point[..len]);
+        let id = u128::from_be_bytes(id);
+        if !has_vector_data(storage, txn, id)? {
+            report.push(
+                IssueKind::MissingEntryPoint,
+                id,
+                "HNSW entry point is not a stored vector".to_string(),
+                None,
+            );
+        }
+    }
+
+    for item in vectors.edges_db.iter(txn)? {
+        let (key, _) = item?;
+        report.hnsw_edges += 1;
+        // source(16) | level(8) | sink(16)
+        let ids = (key.len() == 40).then(|| {
+            let source = u128::from_be_bytes(key[..16].try_into().expect("key is 40 bytes"));
+            let sink = u128::from_be_bytes(key[24..].try_into().expect("key is 40 bytes"));
+            (source, sink)
+        });
+        let Some((source, sink)) = ids else {
+            report.push(
+                IssueKind::UnreadableRecord,
+                0,
+                "HNSW link has the wrong size".to_string(),
+                None,
+            );
+            continue;
+        };
+        for (end, id) in [("from", source), ("to", sink)] {
+            if !has_vector_data(storage, txn, id)? {
+                report.push(
+                    IssueKind::OrphanedHnswEdge,
+                    source,
+                    format!(
+                        "HNSW link {end} vector {} that has no data",
+                        Uuid::from_u128(id)
+                    ),
+                    Some(Fix::DeleteHnswEdge(key.to_vec())),
+                );
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// BM25 postings and document lengths should belong to stored nodes or vectors
+fn check_postings(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    report: &mut FsckReport,
+) -> Result<(), GraphError> {
+    let Some(bm25) = &storage.bm25 else {
+        return Ok(());
+    };
+    let mut documents = BTreeSet::new();
+    for item in bm25.postings_db.iter(txn)? {
+        let (_, segment) = item?;
+        // Postings are live while their document is in doc_terms_db, so the documents
+        // checked are the ones there
+        match Posting::decode_segment(segment) {
+            Ok(postings) => report.postings += postings.count() as u64,
+            Err(_) => report.push(
+                IssueKind::UnreadableRecord,
+                0,
+                "BM25 posting segment can't be decoded".to_string(),
+                None,
+            ),
+        }
+    }
+    for item in bm25.doc_terms_db.iter(txn)? {
+        documents.insert(item?.0);
+    }
+    for item in bm25.doc_lengths_db.iter(txn)? {
+        documents.insert(item?.0);
+    }
+    for id in documents {
+        if !exists(storage, txn, id)? {
+            report.push(
+                IssueKind::OrphanedPosting,
+                id,
+                "BM25 index has postings for a document that doesn't exist".to_string(),
+                Some(Fix::DeleteDocument(id)),
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use bumpalo::Bump;
+use heed3::RoTxn;
+use tempfile::TempDir;
+
+use super::{
+    HelixGraphStorage,
+    fsck::{FsckReport, IssueKind, check, repair},
+};
+use crate::{
+    helix_engine::{
+        tests::traversal_tests::test_utils::props_option,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{add_e::AddEAdapter, add_n::AddNAdapter},
+                vectors::insert::InsertVAdapter,
+            },
+        },
+        types::SecondaryIndex,
+        vector_core::{vector::HVector, vector_core::VectorCore},
+    },
+    props,
+};
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+/// A graph of two people who know each other, indexed on `name`, and three vectors
+struct Fixture {
+    storage: HelixGraphStorage,
+    alice: u128,
+    bob: u128,
+    edge: u128,
+    vectors: Vec<u128>,
+    _temp_dir: TempDir,
+}
+
+fn setup() -> Fixture {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.graph_config.as_mut().unwrap().secondary_indices =
+        Some(vec![SecondaryIndex::Index("name".to_string())]);
+    let storage = HelixGraphStorage::new(
+        temp_dir.path().to_str().unwrap(),
+        config,
+        Default::default(),
+    )
+    .unwrap();
+
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let mut people = Vec::new();
+    for name in ["Alice", "Bob"] {
+        let node = G::new_mut(&storage, &arena, &mut txn)
+            .add_n(
+                "person",
+                props_option(&arena, props! { "name" => name }),
+                Some(&["name"]),
+            )
+            .collect_to_obj()
+            .unwrap();
+        people.push(node.id());
+    }
+    let edge = G::new_mut(&storage, &arena, &mut txn)
+        .add_edge("knows", None, people[0], people[1], false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    let vectors = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+        .iter()
+        .map(|data| {
+            G::new_mut(&storage, &arena, &mut txn)
+                .insert_v::<Filter>(data, "embedding", None)
+                .collect_to_obj()
+                .unwrap()
+                .id()
+        })
+        .collect();
+    txn.commit().unwrap();
+
+    Fixture {
+        storage,
+        alice: people[0],
+        bob: people[1],
+        edge,
+        vectors,
+        _temp_dir: temp_dir,
+    }
+}
+
+fn kinds(report: &FsckReport) -> Vec<IssueKind> {
+    let mut kinds = report
+        .issues
+        .iter()
+        .map(|issue| issue.kind)
+        .collect::<Vec<_>>();
+    kinds.sort();
+    kinds.dedup();
+    kinds
+}
+
+#[test]
+fn test_consistent_graph_has_no_issues() {
+    let fixture = setup();
+
+    let report = check(&fixture.storage).unwrap();
+
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+    assert!(report.is_consistent());
+    assert_eq!(report.nodes, 2);
+    assert_eq!(report.edges, 1);
+    assert_eq!(report.vectors, 3);
+    assert_eq!(report.index_entries, 2);
+    assert!(report.hnsw_edges > 0);
+    assert!(report.postings > 0);
+}
+
+#[test]
+fn test_missing_node_is_reported_and_repaired() {
+    let fixture = setup();
+    let storage = &fixture.storage;
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.nodes_db.delete(&mut txn, &fixture.bob).unwrap();
+    txn.commit().unwrap();
+
+    let report = check(storage).unwrap();
+    assert_eq!(
+        kinds(&report),
+        vec![
+            IssueKind::DanglingEdge,
+            IssueKind::StaleIndexEntry,
+            IssueKind::OrphanedPosting
+        ]
+    );
+    assert!(report.issues.iter().all(|issue| issue.repairable));
+    // Checking changes nothing
+    assert_eq!(check(storage).unwrap().issues.len(), report.issues.len());
+
+    let repaired = repair(storage).unwrap();
+    assert!(repaired.is_consistent());
+    assert_eq!(repaired.repaired(), report.issues.len());
+
+    let after = check(storage).unwrap();
+    assert!(after.issues.is_empty(), "{:?}", after.issues);
+    assert_eq!(after.edges, 0);
+    let txn = storage.graph_env.read_txn().unwrap();
+    assert!(storage.edges_db.get(&txn, &fixture.edge).unwrap().is_none());
+}
+
+#[test]
+fn test_adjacency_lists_are_rebuilt_from_edges() {
+    let fixture = setup();
+    let storage = &fixture.storage;
+    let label = crate::utils::label_hash::hash_label("knows", None);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage
+        .out_edges_db
+        .delete(
+            &mut txn,
+            &HelixGraphStorage::out_edge_key(&fixture.alice, &label),
+        )
+        .unwrap();
+    // An entry for an edge that was never written
+    storage
+        .in_edges_db
+        .put(
+            &mut txn,
+            &HelixGraphStorage::in_edge_key(&fixture.alice, &label),
+            &HelixGraphStorage::pack_edge_data(&42, &fixture.bob),
+        )
+        .unwrap();
+    txn.commit().unwrap();
+
+    let report = check(storage).unwrap();
+    assert_eq!(
+        kinds(&report),
+        vec![IssueKind::OrphanedAdjacency, IssueKind::MissingAdjacency]
+    );
+
+    repair(storage).unwrap();
+
+    assert!(check(storage).unwrap().issues.is_empty());
+    let txn = storage.graph_env.read_txn().unwrap();
+    let listed = storage
+        .out_edges_db
+        .get(
+            &txn,
+            &HelixGraphStorage::out_edge_key(&fixture.alice, &label),
+        )
+        .unwrap()
+        .map(HelixGraphStorage::unpack_adj_edge_data)
+        .transpose()
+        .unwrap();
+    assert_eq!(listed, Some((fixture.edge, fixture.bob)));
+}
+
+#[test]
+fn test_missing_index_entry_is_restored() {
+    let fixture = setup();
+    let storage = &fixture.storage;
+    let (index, _) = &storage.secondary_indices["name"];
+    let key = bincode::serialize(&crate::protocol::value::Value::from("Alice")).unwrap();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    index.delete(&mut txn, &key).unwrap();
+    txn.commit().unwrap();
+
+    let report = check(storage).unwrap();
+    assert_eq!(kinds(&report), vec![IssueKind::MissingIndexEntry]);
+    assert_eq!(report.issues[0].id.as_u128(), fixture.alice);
+
+    repair(storage).unwrap();
+
+    let txn = storage.graph_env.read_txn().unwrap();
+    assert_eq!(index.get(&txn, &key).unwrap(), Some(fixture.alice));
+}
+
+#[test]
+fn test_vector_without_data_is_not_repaired() {
+    let fixture = setup();
+    let storage = &fixture.storage;
+    let lost = fixture.vectors[1];
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage
+        .vectors
+        .vectors_db
+        .delete(&mut txn, &VectorCore::vector_key(lost, 0))
+        .unwrap();
+    txn.commit().unwrap();
+
+    let report = check(storage).unwrap();
+    assert!(kinds(&report).contains(&IssueKind::VectorWithoutData));
+    assert!(kinds(&report).contains(&IssueKind::OrphanedHnswEdge));
+
+    let repaired = repair(storage).unwrap();
+    assert!(!repaired.is_consistent());
+
+    // Links to the vector are gone, the vector itself needs its data re-inserted
+    let after = check(storage).unwrap();
+    assert!(
+        after
+            .issues
+            .iter()
+            .all(|issue| !issue.repairable && issue.id.as_u128() == lost),
+        "{:?}",
+        after.issues
+    );
+}
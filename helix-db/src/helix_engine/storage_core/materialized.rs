@@ -0,0 +1,187 @@
+//! Edge counts kept as node properties, declared in the schema as materialized computed fields
+//! like `follower_count: COUNT(_::In<Follows>) @materialized`.
+//!
+//! Every write adding or removing an edge adjusts the counts of its nodes in the same
+//! transaction, so reading a count is a property read. Counts added to the schema after nodes
+//! were written are computed from the stored edges when the storage is opened.
+
+use std::collections::HashMap;
+
+use heed3::RwTxn;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods, write_log},
+        types::{GraphError, MaterializedCount},
+    },
+    protocol::value::Value,
+    utils::{items::Node, label_hash::hash_label, properties::ImmutablePropertiesMap},
+};
+
+/// Metadata key of the counts computed for the stored nodes
+const MATERIALIZED_COUNTS_KEY: &[u8] = b"materialized_counts";
+
+/// The counts of each edge label hash
+pub fn by_edge_label(counts: Vec<MaterializedCount>) -> HashMap<[u8; 4], Vec<MaterializedCount>> {
+    let mut by_label: HashMap<[u8; 4], Vec<MaterializedCount>> = HashMap::new();
+    for count in counts {
+        by_label
+            .entry(hash_label(&count.edge_label, None))
+            .or_default()
+            .push(count);
+    }
+    by_label
+}
+
+impl HelixGraphStorage {
+    /// Count an edge added (`delta` 1) or removed (`delta` -1) between two nodes
+    pub fn count_edge(
+        &self,
+        txn: &mut RwTxn,
+        label: &[u8; 4],
+        from_node: u128,
+        to_node: u128,
+        delta: i64,
+    ) -> Result<(), GraphError> {
+        self.count_edge_end(txn, label, from_node, false, delta)?;
+        self.count_edge_end(txn, label, to_node, true, delta)
+    }
+
+    /// Adjust the degree and counts of one end of an edge, `incoming` when the edge goes to the
+    /// node. The node's edge constraints are checked when the write commits.
+    pub fn count_edge_end(
+        &self,
+        txn: &mut RwTxn,
+        label: &[u8; 4],
+        node_id: u128,
+        incoming: bool,
+        delta: i64,
+    ) -> Result<(), GraphError> {
+        self.count_degree(txn, label, node_id, incoming, delta)?;
+        if self.materialized_counts.is_empty() && self.edge_constraints.is_empty() {
+            return Ok(());
+        }
+        self.constrain_edge_end(label, node_id);
+        let Some(counts) = self.materialized_counts.get(label) else {
+            return Ok(());
+        };
+        if !counts.iter().any(|count| count.incoming == incoming) {
+            return Ok(());
+        }
+        let arena = bumpalo::Bump::new();
+        let node = match self.get_node(txn, &node_id, &arena) {
+            Ok(node) => node,
+            // The other end is a vector
+            Err(GraphError::NodeNotFound) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let fields: Vec<&str> = counts
+            .iter()
+            .filter(|count| count.incoming == incoming && count.node_label == node.label)
+            .map(|count| count.field.as_str())
+            .collect();
+        if fields.is_empty() {
+            return Ok(());
+        }
+        let count_of = |field: &str| match node.properties.as_ref().and_then(|p| p.get(field)) {
+            Some(Value::I64(count)) => *count,
+            _ => 0,
+        };
+        let updated: Vec<(&str, i64)> = fields
+            .into_iter()
+            .map(|field| (field, (count_of(field) + delta).max(0)))
+            .collect();
+        self.put_counts(txn, node, &updated, &arena)
+    }
+
+    /// Write a node with its counts set
+    fn put_counts<'arena>(
+        &self,
+        txn: &mut RwTxn,
+        mut node: Node<'arena>,
+        counts: &[(&str, i64)],
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<(), GraphError> {
+        let old: Vec<(&'arena str, Value)> = node
+            .properties
+            .iter()
+            .flat_map(|properties| properties.iter())
+            .filter(|(key, _)| counts.iter().all(|(field, _)| field != key))
+            .map(|(key, value)| (key, value.clone()))
+            .collect();
+        let len = old.len() + counts.len();
+        let properties = old.into_iter().chain(
+            counts
+                .iter()
+                .map(|(field, count)| (&*arena.alloc_str(field), Value::I64(*count))),
+        );
+        node.properties = Some(ImmutablePropertiesMap::new(len, properties, arena));
+        self.nodes_db
+            .put(txn, &node.id, &bincode::serialize(&node)?)?;
+        write_log::record(node.id);
+        Ok(())
+    }
+
+    /// Compute the counts not computed for the stored nodes yet, such as counts just added to
+    /// the schema
+    pub(crate) fn backfill_materialized_counts(&self) -> Result<(), GraphError> {
+        let mut txn = self.graph_env.write_txn()?;
+        let computed: Vec<MaterializedCount> =
+            match self.metadata_db.get(&txn, MATERIALIZED_COUNTS_KEY)? {
+                Some(bytes) => sonic_rs::from_slice(bytes)?,
+                None => Vec::new(),
+            };
+        let current: Vec<MaterializedCount> = self
+            .materialized_counts
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        let missing: Vec<&MaterializedCount> = current
+            .iter()
+            .filter(|count| !computed.contains(count))
+            .collect();
+        if missing.is_empty() && computed.len() == current.len() {
+            return Ok(());
+        }
+
+        let arena = bumpalo::Bump::new();
+        let mut nodes = Vec::new();
+        for result in self.nodes_db.iter(&txn)? {
+            let (id, bytes) = result?;
+            let node = Node::from_bincode_bytes(id, bytes, &arena)?;
+            if missing.iter().any(|count| count.node_label == node.label) {
+                nodes.push(id);
+            }
+        }
+        for id in nodes {
+            let arena = bumpalo::Bump::new();
+            let node = self.get_node(&txn, &id, &arena)?;
+            let mut counts = Vec::new();
+            for count in missing
+                .iter()
+                .filter(|count| count.node_label == node.label)
+            {
+                let label = hash_label(&count.edge_label, None);
+                let (db, key) = match count.incoming {
+                    true => (&self.in_edges_db, Self::in_edge_key(&id, &label)),
+                    false => (&self.out_edges_db, Self::out_edge_key(&id, &label)),
+                };
+                let edges = match db.get_duplicates(&txn, &key)? {
+                    Some(duplicates) => duplicates.count(),
+                    None => 0,
+                };
+                counts.push((count.field.as_str(), edges as i64));
+            }
+            self.put_counts(&mut txn, node, &counts, &arena)?;
+        }
+
+        self.metadata_db.put(
+            &mut txn,
+            MATERIALIZED_COUNTS_KEY,
+            &sonic_rs::to_vec(&current)?,
+        )?;
+        txn.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! Merging duplicate nodes, for entity resolution.
+//!
+//! [`HelixGraphStorage::merge_nodes`] folds one node into another of the same label: the
+//! removed node's edges are re-pointed to the kept node, the two nodes' properties are merged,
+//! the kept node's secondary index and BM25 entries are rewritten for the merged properties, and
+//! the removed node is dropped. Edges to vectors are re-pointed like any other, so vectors stay
+//! connected to the kept node.
+//!
+//! The removed node leaves a tombstone naming the node it was merged into, so ids held from
+//! before the merge still resolve through [`HelixGraphStorage::merged_into`].
+
+use std::collections::BTreeSet;
+
+use heed3::{PutFlags, RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helix_engine::{
+        bm25::bm25::{BM25, BM25Flatten},
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods, write_log},
+        types::{GraphError, SecondaryIndex},
+    },
+    utils::{
+        items::{Edge, Node},
+        label_hash::hash_label,
+        properties::ImmutablePropertiesMap,
+    },
+};
+
+const TOMBSTONE_KEY_PREFIX: &[u8] = b"merged_node:";
+
+/// Which value a merged node takes for a property both nodes set to different values
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The kept node's value
+    #[default]
+    Keep,
+    /// The removed node's value
+    Overwrite,
+    /// Fail the merge
+    Fail,
+}
+
+fn tombstone_key(id: u128) -> Vec<u8> {
+    let mut key = TOMBSTONE_KEY_PREFIX.to_vec();
+    key.extend_from_slice(&id.to_be_bytes());
+    key
+}
+
+impl HelixGraphStorage {
+    /// Merge the node `remove` into the node `keep`, returning the kept node as merged
+    pub fn merge_nodes<'arena>(
+        &self,
+        txn: &mut RwTxn,
+        arena: &'arena bumpalo::Bump,
+        keep: u128,
+        remove: u128,
+        conflict: ConflictPolicy,
+    ) -> Result<Node<'arena>, GraphError> {
+        if keep == remove {
+            return Err(GraphError::New(
+                "can't merge a node into itself".to_string(),
+            ));
+        }
+        let kept = self.get_node(txn, &keep, arena)?;
+        let removed = self.get_node(txn, &remove, arena)?;
+        if kept.label != removed.label {
+            return Err(GraphError::New(format!(
+                "can't merge {} node into {} node",
+                removed.label, kept.label
+            )));
+        }
+
+        self.repoint_edges(txn, arena, keep, remove)?;
+
+        // Re-pointing the edges updated both nodes' materialized counts
+        let kept = self.get_node(txn, &keep, arena)?;
+        let removed = self.get_node(txn, &remove, arena)?;
+        let merged = Node {
+            properties: merge_properties(self, &kept, &removed, conflict, arena)?,
+            ..kept
+        };
+
+        self.drop_node(txn, &remove)?;
+        if let Some(bm25) = &self.bm25 {
+            bm25.delete_doc(txn, remove)?;
+        }
+
+        for (index_name, (db, secondary_index)) in &self.secondary_indices {
+            let old = kept.get_property(index_name);
+            let new = merged.get_property(index_name);
+            if old == new {
+                continue;
+            }
+            if let Some(old) = old {
+                db.delete_one_duplicate(txn, &bincode::serialize(old)?, &keep)?;
+            }
+            if let Some(new) = new {
+                let flags = match secondary_index {
+                    SecondaryIndex::Unique(_) => PutFlags::NO_OVERWRITE,
+                    _ => PutFlags::empty(),
+                };
+                db.put_with_flags(txn, flags, &bincode::serialize(new)?, &keep)?;
+            }
+        }
+
+        self.nodes_db
+            .put(txn, Self::node_key(&keep), &merged.to_bincode_bytes()?)?;
+        if let Some(bm25) = &self.bm25
+            && let Some(properties) = merged.properties.as_ref()
+        {
+            let mut data = properties.flatten_bm25();
+            data.push_str(merged.label);
+            bm25.update_doc(txn, keep, &data)?;
+        }
+        self.metadata_db
+            .put(txn, &tombstone_key(remove), &keep.to_be_bytes())?;
+        write_log::record(keep);
+
+        Ok(merged)
+    }
+
+    /// The node a merged node was folded into, following later merges of that node too, or
+    /// `None` for ids never merged
+    pub fn merged_into(&self, txn: &RoTxn, id: u128) -> Result<Option<u128>, GraphError> {
+        let mut merged_into = None;
+        let mut current = id;
+        while let Some(bytes) = self.metadata_db.get(txn, &tombstone_key(current))? {
+            let bytes: [u8; 16] = bytes
+                .try_into()
+                .map_err(|_| GraphError::New("merged node tombstone is not an id".to_string()))?;
+            current = u128::from_be_bytes(bytes);
+            merged_into = Some(current);
+        }
+        Ok(merged_into)
+    }
+
+    /// Move every edge of `remove` onto `keep`, keeping the edges' ids and properties
+    fn repoint_edges(
+        &self,
+        txn: &mut RwTxn,
+        arena: &bumpalo::Bump,
+        keep: u128,
+        remove: u128,
+    ) -> Result<(), GraphError> {
+        // Ordered so edges between the same nodes are re-pointed in the order they were added
+        let mut edge_ids = BTreeSet::new();
+        for db in [&self.out_edges_db, &self.in_edges_db] {
+            for result in db.prefix_iter(txn, &remove.to_be_bytes())? {
+                let (_, value) = result?;
+                edge_ids.insert(Self::unpack_adj_edge_data(value)?.0);
+            }
+        }
+
+        let repoint = |node: u128| if node == remove { keep } else { node };
+        for edge_id in edge_ids {
+            let edge = self.get_edge(txn, &edge_id, arena)?;
+            let label = hash_label(edge.label, None);
+            let (from_node, to_node) = (repoint(edge.from_node), repoint(edge.to_node));
+
+            self.out_edges_db.delete_one_duplicate(
+                txn,
+                &Self::out_edge_key(&edge.from_node, &label),
+                &Self::pack_edge_data(&edge_id, &edge.to_node),
+            )?;
+            self.in_edges_db.delete_one_duplicate(
+                txn,
+                &Self::in_edge_key(&edge.to_node, &label),
+                &Self::pack_edge_data(&edge_id, &edge.from_node),
+            )?;
+            self.count_edge(txn, &label, edge.from_node, edge.to_node, -1)?;
+
+            self.check_acyclic(txn, edge.label, from_node, to_node)?;
+            self.out_edges_db.put(
+                txn,
+                &Self::out_edge_key(&from_node, &label),
+                &Self::pack_edge_data(&edge_id, &to_node),
+            )?;
+            self.in_edges_db.put(
+                txn,
+                &Self::in_edge_key(&to_node, &label),
+                &Self::pack_edge_data(&edge_id, &from_node),
+            )?;
+            self.count_edge(txn, &label, from_node, to_node, 1)?;
+
+            let edge = Edge {
+                from_node,
+                to_node,
+                ..edge
+            };
+            self.edges_db
+                .put(txn, Self::edge_key(&edge_id), &edge.to_bincode_bytes()?)?;
+            write_log::record(edge_id);
+        }
+        Ok(())
+    }
+}
+
+/// The properties of `kept` with those only `removed` sets added, resolving properties both set
+/// by `conflict`. Materialized counts are the kept node's, as its edges now include the removed
+/// node's.
+fn merge_properties<'arena>(
+    storage: &HelixGraphStorage,
+    kept: &Node<'arena>,
+    removed: &Node<'arena>,
+    conflict: ConflictPolicy,
+    arena: &'arena bumpalo::Bump,
+) -> Result<Option<ImmutablePropertiesMap<'arena>>, GraphError> {
+    let counted: Vec<&str> = storage
+        .materialized_counts
+        .values()
+        .flatten()
+        .filter(|count| count.node_label == kept.label)
+        .map(|count| count.field.as_str())
+        .collect();
+
+    let mut properties: Vec<_> = kept
+        .properties
+        .iter()
+        .flat_map(|properties| properties.iter())
+        .map(|(key, value)| (key, value.clone()))
+        .collect();
+    for (key, value) in removed
+        .properties
+        .iter()
+        .flat_map(|properties| properties.iter())
+    {
+        if counted.contains(&key) {
+            continue;
+        }
+        match properties.iter_mut().find(|(existing, _)| *existing == key) {
+            None => properties.push((key, value.clone())),
+            Some((_, existing)) if existing == value => {}
+            Some((_, existing)) => match conflict {
+                ConflictPolicy::Keep => {}
+                ConflictPolicy::Overwrite => *existing = value.clone(),
+                ConflictPolicy::Fail => {
+                    return Err(GraphError::MergeConflict(format!(
+                        "both nodes set `{key}`, to {} and {}",
+                        existing.inner_stringify(),
+                        value.inner_stringify()
+                    )));
+                }
+            },
+        }
+    }
+
+    Ok((!properties.is_empty())
+        .then(|| ImmutablePropertiesMap::new(properties.len(), properties.into_iter(), arena)))
+}
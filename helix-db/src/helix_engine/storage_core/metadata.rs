@@ -21,6 +21,9 @@ mod storage_version_tag {
     pub const VECTOR_NATIVE_ENDIANNESS: u64 = 1;
 }
 
+/// Newest storage version this build can read and migrate to
+pub const LATEST_STORAGE_VERSION: u64 = storage_version_tag::VECTOR_NATIVE_ENDIANNESS;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum VectorEndianness {
     BigEndian,
@@ -43,19 +46,26 @@ impl StorageMetadata {
         txn: &RoTxn<WithTls>,
         metadata_db: &Database<Bytes, Bytes>,
     ) -> Result<Self, GraphError> {
-        match metadata_db.get(txn, STORAGE_VERSION_KEY)? {
+        match Self::read_version(txn, metadata_db)? {
             None => Ok(Self::PreMetadata),
-            Some(version_bytes) => {
-                let version_byte_array: [u8; std::mem::size_of::<u64>()] =
-                    version_bytes.try_into().map_err(|e| {
-                        GraphError::New(format!("storage metadata version tag is not a u64: {e:?}"))
-                    })?;
+            Some(version) => Self::parse(version, txn, metadata_db),
+        }
+    }
 
-                let version = u64::from_le_bytes(version_byte_array);
+    /// The stored storage version tag, `None` before the metadata table existed
+    pub fn read_version(
+        txn: &RoTxn<WithTls>,
+        metadata_db: &Database<Bytes, Bytes>,
+    ) -> Result<Option<u64>, GraphError> {
+        let Some(version_bytes) = metadata_db.get(txn, STORAGE_VERSION_KEY)? else {
+            return Ok(None);
+        };
+        let version_byte_array: [u8; std::mem::size_of::<u64>()] =
+            version_bytes.try_into().map_err(|e| {
+                GraphError::New(format!("storage metadata version tag is not a u64: {e:?}"))
+            })?;
 
-                Self::parse(version, txn, metadata_db)
-            }
-        }
+        Ok(Some(u64::from_le_bytes(version_byte_array)))
     }
 
     pub fn save(
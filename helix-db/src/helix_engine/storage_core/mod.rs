@@ -1,23 +1,46 @@
+pub mod acyclic;
+pub mod audit_log;
+pub mod compatibility;
+pub mod constraints;
+pub mod degrees;
+pub mod fsck;
 pub mod graph_visualization;
+pub mod materialized;
+pub mod merge;
 pub mod metadata;
+pub mod scan_counter;
+pub mod snapshot;
 pub mod storage_methods;
 pub mod storage_migration;
+pub mod triggers;
 pub mod version_info;
+pub mod views;
+pub mod warm_up;
+pub mod write_log;
 
+#[cfg(test)]
+mod compatibility_tests;
+#[cfg(test)]
+mod fsck_tests;
 #[cfg(test)]
 mod storage_concurrent_tests;
 #[cfg(test)]
 mod storage_migration_tests;
+#[cfg(test)]
+mod warm_up_tests;
 
 use crate::{
     helix_engine::{
-        bm25::bm25::HBM25Config,
+        bm25::{analysis::TextAnalysis, bm25::HBM25Config},
+        graph::ego_cache::EgoCache,
         storage_core::{
+            compatibility::{Compatibility, SchemaMismatch},
             storage_methods::{DBMethods, StorageMethods},
             version_info::VersionInfo,
         },
         traversal_core::config::Config,
-        types::{GraphError, SecondaryIndex},
+        types::{EdgeConstraint, GraphError, MaterializedCount, SecondaryIndex},
+        udf::Udfs,
         vector_core::{
             hnsw::HNSW,
             vector_core::{HNSWConfig, VectorCore},
@@ -34,6 +57,7 @@ use std::{
     fs,
     path::Path,
 };
+use tracing::error;
 
 // database names for different stores
 const DB_NODES: &str = "nodes"; // for node data (n:)
@@ -41,6 +65,8 @@ const DB_EDGES: &str = "edges"; // for edge data (e:)
 const DB_OUT_EDGES: &str = "out_edges"; // for outgoing edge indices (o:)
 const DB_IN_EDGES: &str = "in_edges"; // for incoming edge indices (i:)
 const DB_STORAGE_METADATA: &str = "storage_metadata"; // for storage metadata key/value pairs
+const DB_AUDIT_LOG: &str = "audit_log"; // for committed write route invocations
+const DB_DEGREES: &str = "degrees"; // for edge counts per node, label and direction
 
 pub type NodeId = u128;
 pub type EdgeId = u128;
@@ -58,11 +84,26 @@ pub struct HelixGraphStorage {
     pub edges_db: Database<U128<BE>, Bytes>,
     pub out_edges_db: Database<Bytes, Bytes>,
     pub in_edges_db: Database<Bytes, Bytes>,
+    /// Edge counts by node, edge label hash and direction
+    pub degrees_db: Database<Bytes, U64<BE>>,
     pub secondary_indices: HashMap<String, (Database<Bytes, U128<BE>>, SecondaryIndex)>,
+    /// Edge counts kept as node properties, by edge label hash
+    pub materialized_counts: HashMap<[u8; 4], Vec<MaterializedCount>>,
+    /// Bounds on the edges of each node, by edge label hash
+    pub edge_constraints: HashMap<[u8; 4], Vec<EdgeConstraint>>,
+    /// Label hashes of the edge types that must not form cycles
+    pub acyclic_edges: HashSet<[u8; 4]>,
     pub vectors: VectorCore,
     pub bm25: Option<HBM25Config>,
     pub metadata_db: Database<Bytes, Bytes>,
+    pub audit_db: Database<U128<BE>, Bytes>,
     pub version_info: VersionInfo,
+    /// User-defined functions queries can `CALL`
+    pub udfs: Udfs,
+    /// k-hop neighborhoods cached for `::EGO` steps
+    pub ego_cache: EgoCache,
+    /// Why the stored data can't be served by this build, in which case writes are refused
+    pub schema_mismatch: Option<SchemaMismatch>,
 
     pub storage_config: StorageConfig,
 }
@@ -81,6 +122,14 @@ impl HelixGraphStorage {
             config.db_max_size_gb.unwrap_or(100)
         };
 
+        let udfs = Udfs::from_config(
+            config
+                .gateway_config
+                .as_ref()
+                .and_then(|gateway| gateway.udfs.as_deref())
+                .unwrap_or_default(),
+        )?;
+
         let graph_env = unsafe {
             EnvOpenOptions::new()
                 .map_size(db_size * 1024 * 1024 * 1024)
@@ -135,12 +184,28 @@ impl HelixGraphStorage {
             .name(DB_IN_EDGES)
             .create(&mut wtxn)?;
 
+        // Degrees: [node_id + label + direction]->[edge count]
+        //          [16 + 4 + 1 bytes]->[8 bytes]
+        let degrees_db: Database<Bytes, U64<BE>> = graph_env
+            .database_options()
+            .types::<Bytes, U64<BE>>()
+            .name(DB_DEGREES)
+            .create(&mut wtxn)?;
+
         let metadata_db: Database<Bytes, Bytes> = graph_env
             .database_options()
             .types::<Bytes, Bytes>()
             .name(DB_STORAGE_METADATA)
             .create(&mut wtxn)?;
 
+        // Audit log: [entry_id]->[json audit entry]
+        //            [16 bytes]->[dynamic]
+        let audit_db = graph_env
+            .database_options()
+            .types::<U128<BE>, Bytes>()
+            .name(DB_AUDIT_LOG)
+            .create(&mut wtxn)?;
+
         let mut secondary_indices = HashMap::new();
         if let Some(indexes) = config.get_graph_config().secondary_indices {
             for index in indexes {
@@ -174,6 +239,20 @@ impl HelixGraphStorage {
                 };
             }
         }
+        let materialized_counts = materialized::by_edge_label(
+            config
+                .get_graph_config()
+                .materialized_counts
+                .unwrap_or_default(),
+        );
+        let edge_constraints = constraints::by_edge_label(
+            config
+                .get_graph_config()
+                .edge_constraints
+                .unwrap_or_default(),
+        );
+        let acyclic_edges =
+            acyclic::by_edge_label(config.get_graph_config().acyclic_edges.unwrap_or_default());
         let vector_config = config.get_vector_config();
         let vectors = VectorCore::new(
             &graph_env,
@@ -184,11 +263,21 @@ impl HelixGraphStorage {
                 vector_config.ef_search,
             ),
         )?;
+        vectors.configure_ivf_flat(&mut wtxn, &vector_config.ivf_flat.unwrap_or_default())?;
+
+        let bm25_merge = config.gateway_config().bm25_merge();
+        let bm25 = match config.get_bm25() {
+            true => Some(
+                HBM25Config::new(&graph_env, &mut wtxn)?
+                    .with_merge_policy(bm25_merge)
+                    .with_text_analysis(TextAnalysis::load(
+                        &config.gateway_config().text_search(),
+                    )?),
+            ),
+            false => None,
+        };
 
-        let bm25 = config
-            .get_bm25()
-            .then(|| HBM25Config::new(&graph_env, &mut wtxn))
-            .transpose()?;
+        let ego_cache = EgoCache::from_config(config.gateway_config().ego_cache());
 
         let storage_config = StorageConfig::new(
             config.schema,
@@ -204,15 +293,46 @@ impl HelixGraphStorage {
             edges_db,
             out_edges_db,
             in_edges_db,
+            degrees_db,
             secondary_indices,
+            materialized_counts,
+            edge_constraints,
+            acyclic_edges,
             vectors,
             bm25,
             metadata_db,
+            audit_db,
             storage_config,
             version_info,
+            udfs,
+            ego_cache,
+            schema_mismatch: None,
         };
 
-        storage_migration::migrate(&mut storage)?;
+        let compatibility = compatibility::check(
+            &storage.graph_env,
+            &storage.metadata_db,
+            storage.storage_config.schema.as_deref(),
+            &storage.version_info,
+        )?;
+        match compatibility {
+            Compatibility::Compatible(record) => {
+                storage_migration::migrate(&mut storage)?;
+                storage.backfill_materialized_counts()?;
+                storage.backfill_degrees()?;
+                storage.record_schema(&record)?;
+            }
+            Compatibility::Incompatible(mismatch) => {
+                // Leave the data as it is for a build that can read it
+                for reason in &mismatch.reasons {
+                    error!(
+                        reason = %reason,
+                        "Stored data is incompatible with this build, refusing writes"
+                    );
+                }
+                storage.schema_mismatch = Some(mismatch);
+            }
+        }
 
         Ok(storage)
     }
@@ -292,6 +412,17 @@ impl HelixGraphStorage {
         );
         Ok((edge_id, node_id))
     }
+
+    /// The embedding already stored for a vector of `label` upserted from `content`
+    ///
+    /// Queries upserting vectors from text check this before calling the embedding model.
+    pub fn stored_embedding(&self, label: &str, content: &str) -> Option<Vec<f64>> {
+        let txn = self.graph_env.read_txn().ok()?;
+        self.vectors
+            .embedding_for_content(&txn, label, content)
+            .ok()
+            .flatten()
+    }
 }
 
 impl StorageConfig {
@@ -352,6 +483,7 @@ impl StorageMethods for HelixGraphStorage {
         id: &u128,
         arena: &'arena bumpalo::Bump,
     ) -> Result<Node<'arena>, GraphError> {
+        scan_counter::record(1);
         let node = match self.nodes_db.get(txn, Self::node_key(id))? {
             Some(data) => data,
             None => return Err(GraphError::NodeNotFound),
@@ -368,6 +500,7 @@ impl StorageMethods for HelixGraphStorage {
         id: &u128,
         arena: &'arena bumpalo::Bump,
     ) -> Result<Edge<'arena>, GraphError> {
+        scan_counter::record(1);
         let edge = match self.edges_db.get(txn, Self::edge_key(id))? {
             Some(data) => data,
             None => return Err(GraphError::EdgeNotFound),
@@ -422,6 +555,7 @@ impl StorageMethods for HelixGraphStorage {
         // Delete all related data
         for edge in edges {
             self.edges_db.delete(txn, Self::edge_key(&edge))?;
+            write_log::record(edge);
         }
         for label_bytes in out_edges.iter() {
             self.out_edges_db
@@ -438,6 +572,9 @@ impl StorageMethods for HelixGraphStorage {
                 &Self::out_edge_key(other_node_id, label_bytes),
                 &Self::pack_edge_data(edge_id, id),
             )?;
+            if other_node_id != id {
+                self.count_edge_end(txn, label_bytes, *other_node_id, false, -1)?;
+            }
         }
         for (other_node_id, label_bytes, edge_id) in other_in_edges.iter() {
             self.in_edges_db.delete_one_duplicate(
@@ -445,6 +582,9 @@ impl StorageMethods for HelixGraphStorage {
                 &Self::in_edge_key(other_node_id, label_bytes),
                 &Self::pack_edge_data(edge_id, id),
             )?;
+            if other_node_id != id {
+                self.count_edge_end(txn, label_bytes, *other_node_id, true, -1)?;
+            }
         }
 
         // delete secondary indices
@@ -468,7 +608,9 @@ impl StorageMethods for HelixGraphStorage {
         }
 
         // Delete node data and label
+        self.drop_degrees(txn, *id)?;
         self.nodes_db.delete(txn, Self::node_key(id))?;
+        write_log::record(*id);
 
         Ok(())
     }
@@ -496,6 +638,8 @@ impl StorageMethods for HelixGraphStorage {
             &Self::in_edge_key(&edge.to_node, &label_hash),
             &in_edge_value,
         )?;
+        self.count_edge(txn, &label_hash, edge.from_node, edge.to_node, -1)?;
+        write_log::record(*edge_id);
 
         Ok(())
     }
@@ -544,6 +688,7 @@ impl StorageMethods for HelixGraphStorage {
         // Delete all related data
         for edge in edges {
             self.edges_db.delete(txn, Self::edge_key(&edge))?;
+            write_log::record(edge);
         }
         for label_bytes in out_edges.iter() {
             self.out_edges_db
@@ -560,6 +705,9 @@ impl StorageMethods for HelixGraphStorage {
                 &Self::out_edge_key(other_node_id, label_bytes),
                 &Self::pack_edge_data(edge_id, id),
             )?;
+            if other_node_id != id {
+                self.count_edge_end(txn, label_bytes, *other_node_id, false, -1)?;
+            }
         }
         for (other_node_id, label_bytes, edge_id) in other_in_edges.iter() {
             self.in_edges_db.delete_one_duplicate(
@@ -567,10 +715,15 @@ impl StorageMethods for HelixGraphStorage {
                 &Self::in_edge_key(other_node_id, label_bytes),
                 &Self::pack_edge_data(edge_id, id),
             )?;
+            if other_node_id != id {
+                self.count_edge_end(txn, label_bytes, *other_node_id, true, -1)?;
+            }
         }
 
         // Delete vector data
+        self.drop_degrees(txn, *id)?;
         self.vectors.delete(txn, *id, &arena)?;
+        write_log::record(*id);
 
         Ok(())
     }
@@ -0,0 +1,33 @@
+//! Per-thread count of records read from storage.
+//!
+//! Handlers run to completion on a single worker thread, so the worker resets the
+//! counter before a handler starts and takes it afterwards to report how many items
+//! the query scanned.
+
+use std::cell::Cell;
+
+thread_local! {
+    static SCANNED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Count `n` records read on the current thread
+#[inline]
+pub fn record(n: u64) {
+    SCANNED.with(|scanned| scanned.set(scanned.get() + n));
+}
+
+/// The current thread's count so far
+#[inline]
+pub fn current() -> u64 {
+    SCANNED.with(Cell::get)
+}
+
+/// Reset the current thread's count
+pub fn reset() {
+    SCANNED.with(|scanned| scanned.set(0));
+}
+
+/// Take the current thread's count, leaving it at zero
+pub fn take() -> u64 {
+    SCANNED.with(|scanned| scanned.replace(0))
+}
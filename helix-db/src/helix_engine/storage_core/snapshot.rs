@@ -0,0 +1,76 @@
+//! Read snapshots shared by several requests, so a client paginating or building a report sees
+//! the graph as it was when it started even while writes commit in between.
+//!
+//! LMDB read transactions belong to the thread that opened them, so a snapshot is pinned to a
+//! thread and every read made on that thread through [`HelixGraphStorage::read_txn`] uses it
+//! until the [`PinnedSnapshot`] is dropped. Reads opening `graph_env.read_txn()` directly on a
+//! pinned thread fail, as LMDB only allows one read transaction per thread.
+
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use heed3::{RoTxn, WithTls};
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+
+thread_local! {
+    static PINNED: RefCell<Option<Rc<RoTxn<'static, WithTls>>>> = const { RefCell::new(None) };
+}
+
+/// A read transaction of its own, or the snapshot pinned to the current thread
+pub enum ReadTxn<'a> {
+    Fresh(RoTxn<'a, WithTls>),
+    Pinned(Rc<RoTxn<'static, WithTls>>),
+}
+
+impl<'a> Deref for ReadTxn<'a> {
+    type Target = RoTxn<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ReadTxn::Fresh(txn) => txn,
+            ReadTxn::Pinned(txn) => txn,
+        }
+    }
+}
+
+/// Keeps a snapshot pinned to the thread that created it, unpinning and closing it on drop
+pub struct PinnedSnapshot {
+    txn_id: usize,
+    // Neither `Send` nor `Sync`, like the transaction it stands for
+    _thread: Rc<()>,
+}
+
+impl PinnedSnapshot {
+    /// Open a read transaction and pin it to the current thread
+    pub fn pin(storage: &HelixGraphStorage) -> Result<Self, heed3::Error> {
+        let txn = storage.graph_env.clone().static_read_txn()?;
+        let txn_id = txn.id();
+        PINNED.with(|pinned| *pinned.borrow_mut() = Some(Rc::new(txn)));
+        Ok(PinnedSnapshot {
+            txn_id,
+            _thread: Rc::new(()),
+        })
+    }
+
+    /// The id of the transaction generation the snapshot reads
+    pub fn txn_id(&self) -> usize {
+        self.txn_id
+    }
+}
+
+impl Drop for PinnedSnapshot {
+    fn drop(&mut self) {
+        PINNED.with(|pinned| pinned.borrow_mut().take());
+    }
+}
+
+impl HelixGraphStorage {
+    /// A read transaction for a request: the snapshot pinned to this thread if there is one,
+    /// otherwise a new one
+    pub fn read_txn(&self) -> Result<ReadTxn<'_>, heed3::Error> {
+        match PINNED.with(|pinned| pinned.borrow().clone()) {
+            Some(txn) => Ok(ReadTxn::Pinned(txn)),
+            None => self.graph_env.read_txn().map(ReadTxn::Fresh),
+        }
+    }
+}
@@ -301,6 +301,8 @@ pub(crate) fn convert_old_vector_properties_to_new_format(
         deleted: is_deleted == true,
         level: 0,
         distance: None,
+        search_distance: None,
+        reembedded: false,
         data: &[],
         properties: Some(new_properties),
     };
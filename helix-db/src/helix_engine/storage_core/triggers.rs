@@ -0,0 +1,110 @@
+//! Triggers, declared in HelixQL as `ON AddN<Order> AS order DO { ... }`.
+//!
+//! A trigger's statements compile into a function registered with `#[trigger(...)]`. Adding a
+//! node or edge of the trigger's type runs it in the same write transaction, right after the
+//! item is stored, so the write and everything the trigger does commit or fail together.
+
+use std::{cell::Cell, collections::HashMap, fmt, sync::LazyLock};
+
+use heed3::RwTxn;
+
+use crate::helix_engine::{storage_core::HelixGraphStorage, types::GraphError};
+
+/// Runs a trigger's statements for the item with the given id
+pub type TriggerFn =
+    for<'db> fn(&'db HelixGraphStorage, &mut RwTxn<'db>, u128) -> Result<(), GraphError>;
+
+/// Triggers started from inside other triggers before a write fails, so triggers firing each
+/// other can't recurse forever
+pub const MAX_TRIGGER_DEPTH: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TriggerEvent {
+    AddN,
+    AddE,
+}
+
+impl fmt::Display for TriggerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerEvent::AddN => write!(f, "AddN"),
+            TriggerEvent::AddE => write!(f, "AddE"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    pub event: TriggerEvent,
+    /// Node or edge label whose writes run the trigger
+    pub label: &'static str,
+    pub func: TriggerFn,
+}
+
+impl Trigger {
+    pub const fn new(event: TriggerEvent, label: &'static str, func: TriggerFn) -> Self {
+        Self { event, label, func }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TriggerSubmission(pub Trigger);
+
+inventory::collect!(TriggerSubmission);
+
+static TRIGGERS: LazyLock<HashMap<(TriggerEvent, &'static str), &'static Trigger>> =
+    LazyLock::new(|| {
+        inventory::iter::<TriggerSubmission>
+            .into_iter()
+            .map(|submission| ((submission.0.event, submission.0.label), &submission.0))
+            .collect()
+    });
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The trigger that runs after `event` on items labelled `label`, if one is declared
+pub fn find(event: TriggerEvent, label: &str) -> Option<&'static Trigger> {
+    TRIGGERS.get(&(event, label)).copied()
+}
+
+/// Run the trigger declared for `event` on items labelled `label`, if any, for the item with
+/// `id` that was just written in `txn`
+pub fn fire<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    event: TriggerEvent,
+    label: &str,
+    id: u128,
+) -> Result<(), GraphError> {
+    let Some(trigger) = find(event, label) else {
+        return Ok(());
+    };
+    let depth = DEPTH.get();
+    if depth >= MAX_TRIGGER_DEPTH {
+        return Err(GraphError::New(format!(
+            "Triggers nested more than {MAX_TRIGGER_DEPTH} deep at {event}<{label}>; \
+             check for triggers that run each other"
+        )));
+    }
+    let _depth = DepthGuard::enter(depth);
+    (trigger.func)(storage, txn, id)
+}
+
+/// Holds the nesting depth one level up while a trigger runs, and puts it back when the
+/// trigger returns or panics, so a panicking trigger doesn't count against later writes
+struct DepthGuard(usize);
+
+impl DepthGuard {
+    fn enter(depth: usize) -> Self {
+        DEPTH.set(depth + 1);
+        DepthGuard(depth)
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.set(self.0);
+    }
+}
@@ -8,6 +8,27 @@ use std::collections::HashMap;
 pub struct VersionInfo(pub HashMap<&'static str, ItemInfo>);
 
 impl<'arena> VersionInfo {
+    /// Transitions registered by the compiled migrations, keyed by the label they upgrade
+    pub fn from_submissions() -> Self {
+        let mut items: HashMap<&'static str, ItemInfo> = HashMap::new();
+        for TransitionSubmission(transition) in inventory::iter::<TransitionSubmission> {
+            assert!(
+                transition.from_version > 0 && transition.to_version == transition.from_version + 1,
+                "transition of {} must go up one version at a time",
+                transition.item_label
+            );
+            let item = items.entry(transition.item_label).or_default();
+            item.latest = item.latest.max(transition.to_version);
+            item.transition_fns.push(TransitionFn {
+                from_version: transition.from_version,
+                to_version: transition.to_version,
+                func: transition.func,
+            });
+            item.transition_fns.sort_by_key(|f| f.from_version);
+        }
+        VersionInfo(items)
+    }
+
     pub fn upgrade_to_node_latest(&self, node: Node<'arena>) -> Node<'arena> {
         match self.0.get(&node.label) {
             Some(item_info) => item_info.upgrade_node_to_latest(node),
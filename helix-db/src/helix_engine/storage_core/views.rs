@@ -0,0 +1,99 @@
+//! Materialized views, declared in HelixQL as `VIEW PopularPosts AS N<Post>::WHERE(...)`.
+//!
+//! A view's traversal compiles into a function registered with `#[view(...)]`. Refreshing the
+//! view runs it and stores the ids of the nodes it returned, in order, so queries reading the
+//! view look those nodes up instead of running the traversal again. The gateway refreshes
+//! views after writes to the labels they read, or on their cron schedule.
+
+use heed3::RoTxn;
+
+use crate::helix_engine::{storage_core::HelixGraphStorage, types::GraphError};
+
+/// Runs a view's traversal, returning the ids of the nodes in the view
+pub type ViewFn =
+    for<'db> fn(&'db HelixGraphStorage, &RoTxn<'db>) -> Result<Vec<u128>, GraphError>;
+
+/// Metadata key prefix of the stored results of each view
+const VIEW_KEY_PREFIX: &[u8] = b"view:";
+
+#[derive(Clone, Debug)]
+pub struct View {
+    pub name: &'static str,
+    pub func: ViewFn,
+    /// Node, edge and vector labels the view reads. `None` means unknown.
+    pub labels: Option<&'static [&'static str]>,
+    /// Cron expression the view refreshes on, instead of after writes
+    pub refresh_cron: Option<&'static str>,
+}
+
+impl View {
+    pub const fn new(name: &'static str, func: ViewFn) -> Self {
+        Self {
+            name,
+            func,
+            labels: None,
+            refresh_cron: None,
+        }
+    }
+
+    /// Declare the labels the view reads, so only writes to them refresh it
+    pub const fn with_labels(mut self, labels: &'static [&'static str]) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Refresh the view on a cron schedule rather than after writes
+    pub const fn with_refresh_cron(mut self, cron: &'static str) -> Self {
+        self.refresh_cron = Some(cron);
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ViewSubmission(pub View);
+
+inventory::collect!(ViewSubmission);
+
+/// The views declared by the compiled queries
+pub fn registered() -> impl Iterator<Item = &'static View> {
+    inventory::iter::<ViewSubmission>
+        .into_iter()
+        .map(|submission| &submission.0)
+}
+
+/// The declared view named `name`
+pub fn find(name: &str) -> Option<&'static View> {
+    registered().find(|view| view.name == name)
+}
+
+fn view_key(name: &str) -> Vec<u8> {
+    [VIEW_KEY_PREFIX, name.as_bytes()].concat()
+}
+
+impl HelixGraphStorage {
+    /// Run a view's traversal and store the ids it returned, returning how many there were
+    pub fn refresh_view(&self, view: &View) -> Result<usize, GraphError> {
+        let txn = self.graph_env.read_txn()?;
+        let ids = (view.func)(self, &txn)?;
+        drop(txn);
+
+        let bytes: Vec<u8> = ids.iter().flat_map(|id| id.to_be_bytes()).collect();
+        let mut txn = self.graph_env.write_txn()?;
+        self.metadata_db.put(&mut txn, &view_key(view.name), &bytes)?;
+        txn.commit()?;
+        Ok(ids.len())
+    }
+
+    /// Ids the view returned when it was last refreshed, `None` if it never was
+    pub fn view_ids(&self, txn: &RoTxn, name: &str) -> Result<Option<Vec<u128>>, GraphError> {
+        let Some(bytes) = self.metadata_db.get(txn, &view_key(name))? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            bytes
+                .chunks_exact(16)
+                .map(|id| u128::from_be_bytes(id.try_into().expect("chunks are 16 bytes")))
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,157 @@
+//! Warm-up of the HNSW index after the engine starts.
+//!
+//! LMDB maps the data file, so after a restart every page a query touches is read from disk on
+//! first use. Searches of a large index touch pages all over the file and the first queries
+//! run far slower than later ones. [`warm_up`] walks the index tables in a read transaction,
+//! touching every page of the entry point, the HNSW links, the vectors and their properties,
+//! so they sit in the page cache before queries arrive. With `prefault` it first reads the
+//! whole data file, which also warms nodes, edges and secondary indices.
+
+use heed3::{Database, RoTxn, types::Bytes};
+use std::{
+    fs::File,
+    hint::black_box,
+    io::Read,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+use crate::helix_engine::{
+    storage_core::HelixGraphStorage, traversal_core::config::WarmUpConfig, types::GraphError,
+    vector_core::vector_core::ENTRY_POINT_KEY,
+};
+
+/// Pages are touched by reading one byte every `PAGE_SIZE` bytes of a value
+const PAGE_SIZE: usize = 4096;
+/// Progress is logged every time another tenth of a phase is done
+const PROGRESS_STEPS: u64 = 10;
+/// Chunk the data file is read in when prefaulting
+const PREFAULT_CHUNK: usize = 8 << 20;
+
+/// What a warm-up read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// Whether the index has an entry point, i.e. holds any vectors
+    pub entry_point: bool,
+    /// HNSW links across all layers
+    pub links: u64,
+    /// Vector records across all layers
+    pub vectors: u64,
+    /// Vector property records
+    pub properties: u64,
+    /// Bytes of index records read
+    pub bytes: u64,
+    /// Bytes of the data file read when prefaulting
+    pub prefaulted_bytes: u64,
+    pub duration: Duration,
+}
+
+/// Read the vector index, and with `prefault` the whole data file, into the page cache,
+/// logging progress as each phase goes
+pub fn warm_up(
+    storage: &HelixGraphStorage,
+    config: &WarmUpConfig,
+) -> Result<WarmUpReport, GraphError> {
+    let start = Instant::now();
+    let mut report = WarmUpReport::default();
+
+    if config.prefault() {
+        report.prefaulted_bytes = prefault(storage)?;
+    }
+
+    let txn = storage.graph_env.read_txn()?;
+    let vectors = &storage.vectors;
+    if let Some(entry_point) = vectors.vectors_db.get(&txn, ENTRY_POINT_KEY)? {
+        report.entry_point = true;
+        report.bytes += touch(entry_point) as u64;
+    }
+
+    // Links first: every search walks the graph, most only read a few vectors per layer
+    let (links, bytes) = touch_table(&txn, "HNSW links", vectors.edges_db.remap_types())?;
+    report.links = links;
+    report.bytes += bytes;
+
+    let (records, bytes) = touch_table(&txn, "vectors", vectors.vectors_db)?;
+    // The entry point lives in the same table
+    report.vectors = records - u64::from(report.entry_point);
+    report.bytes += bytes;
+
+    let (properties, bytes) = touch_table(
+        &txn,
+        "vector properties",
+        vectors.vector_properties_db.remap_types(),
+    )?;
+    report.properties = properties;
+    report.bytes += bytes;
+
+    report.duration = start.elapsed();
+    info!(
+        links = report.links,
+        vectors = report.vectors,
+        properties = report.properties,
+        bytes = report.bytes,
+        prefaulted_bytes = report.prefaulted_bytes,
+        duration_ms = report.duration.as_millis() as u64,
+        "Vector index warm-up done"
+    );
+    Ok(report)
+}
+
+/// Touch every record of a table, returning the records and bytes read
+fn touch_table(
+    txn: &RoTxn,
+    phase: &str,
+    db: Database<Bytes, Bytes>,
+) -> Result<(u64, u64), GraphError> {
+    let total = db.len(txn)?;
+    let step = total.div_ceil(PROGRESS_STEPS).max(1);
+    let (mut records, mut bytes) = (0u64, 0u64);
+    for entry in db.iter(txn)? {
+        let (key, value) = entry?;
+        bytes += (touch(key) + touch(value)) as u64;
+        records += 1;
+        if records % step == 0 && records < total {
+            info!(
+                "Warming up {phase}: {}% ({records}/{total})",
+                records * 100 / total
+            );
+        }
+    }
+    Ok((records, bytes))
+}
+
+/// Read one byte of every page of `bytes`, returning its length
+fn touch(bytes: &[u8]) -> usize {
+    let checksum = bytes
+        .iter()
+        .step_by(PAGE_SIZE)
+        .chain(bytes.last())
+        .fold(0u8, |acc, b| acc ^ b);
+    black_box(checksum);
+    bytes.len()
+}
+
+/// Read the data file through the page cache, returning the bytes read
+fn prefault(storage: &HelixGraphStorage) -> Result<u64, GraphError> {
+    let path = storage.graph_env.path().join("data.mdb");
+    let total = std::fs::metadata(&path)?.len();
+    let step = total.div_ceil(PROGRESS_STEPS).max(1);
+    let mut file = File::open(&path)?;
+    let mut buf = vec![0u8; PREFAULT_CHUNK];
+    let (mut read, mut next_log) = (0u64, step);
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        read += n as u64;
+        if read >= next_log && read < total {
+            info!(
+                "Prefaulting data file: {}% ({read}/{total} bytes)",
+                read * 100 / total
+            );
+            next_log += step;
+        }
+    }
+    Ok(read)
+}
@@ -0,0 +1,113 @@
+use bumpalo::Bump;
+use heed3::RoTxn;
+use tempfile::TempDir;
+
+use super::{
+    HelixGraphStorage,
+    warm_up::{WarmUpReport, warm_up},
+};
+use crate::helix_engine::{
+    traversal_core::{
+        HelixGraphEngine, HelixGraphEngineOpts,
+        config::{Config, GatewayConfig, WarmUpConfig, WarmUpMode},
+        ops::{g::G, vectors::insert::InsertVAdapter},
+    },
+    vector_core::vector::HVector,
+};
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+fn setup(vectors: usize) -> (HelixGraphStorage, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = HelixGraphStorage::new(
+        temp_dir.path().to_str().unwrap(),
+        Config::default(),
+        Default::default(),
+    )
+    .unwrap();
+
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    for i in 0..vectors {
+        let data = [i as f64, 1.0, (i % 7) as f64];
+        G::new_mut(&storage, &arena, &mut txn)
+            .insert_v::<Filter>(&data, "embedding", None)
+            .collect_to_obj()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    (storage, temp_dir)
+}
+
+#[test]
+fn test_warm_up_reads_the_whole_index() {
+    let (storage, _temp_dir) = setup(50);
+    let report = warm_up(&storage, &WarmUpConfig::default()).unwrap();
+
+    assert!(report.entry_point);
+    assert!(report.vectors >= 50, "{report:?}");
+    assert_eq!(report.properties, 50);
+    assert!(report.links > 0);
+    assert!(report.bytes > 0);
+    assert_eq!(report.prefaulted_bytes, 0);
+}
+
+#[test]
+fn test_warm_up_of_an_empty_index() {
+    let (storage, _temp_dir) = setup(0);
+    let report = warm_up(&storage, &WarmUpConfig::default()).unwrap();
+
+    assert_eq!(
+        report,
+        WarmUpReport {
+            duration: report.duration,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_prefault_reads_the_data_file() {
+    let (storage, temp_dir) = setup(10);
+    let config = WarmUpConfig {
+        prefault: Some(true),
+        ..Default::default()
+    };
+    let report = warm_up(&storage, &config).unwrap();
+
+    let size = std::fs::metadata(temp_dir.path().join("data.mdb"))
+        .unwrap()
+        .len();
+    assert_eq!(report.prefaulted_bytes, size);
+}
+
+#[test]
+fn test_engine_starts_with_every_warm_up_mode() {
+    for mode in [WarmUpMode::Eager, WarmUpMode::Deferred, WarmUpMode::Off] {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            gateway_config: Some(GatewayConfig {
+                warm_up: Some(WarmUpConfig {
+                    mode: Some(mode),
+                    prefault: Some(true),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        HelixGraphEngine::new(HelixGraphEngineOpts {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            config,
+            ..Default::default()
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_warm_up_mode_from_str() {
+    assert_eq!("Deferred".parse::<WarmUpMode>(), Ok(WarmUpMode::Deferred));
+    assert_eq!(" off ".parse::<WarmUpMode>(), Ok(WarmUpMode::Off));
+    assert!("lazy".parse::<WarmUpMode>().unwrap_err().contains("'lazy'"));
+    assert_eq!(WarmUpConfig::default().mode(), WarmUpMode::Eager);
+}
@@ -0,0 +1,42 @@
+//! Per-thread record of the items written by the running handler.
+//!
+//! Writes run to completion on a writer thread, which resets the log before a handler
+//! starts and takes it afterwards to audit which items the write touched.
+
+use std::cell::RefCell;
+
+use crate::helix_engine::{traversal_core::traversal_value::TraversalValue, types::GraphError};
+
+thread_local! {
+    static WRITTEN: RefCell<Vec<u128>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Record that the item with `id` was created, updated or dropped
+#[inline]
+pub fn record(id: u128) {
+    WRITTEN.with(|written| written.borrow_mut().push(id));
+}
+
+/// Record the item produced by a write step, if it succeeded
+#[inline]
+pub fn record_value(value: &Result<TraversalValue<'_>, GraphError>) {
+    // Values without an item id (paths, scalars) report 0
+    if let Ok(value) = value
+        && value.id() != 0
+    {
+        record(value.id());
+    }
+}
+
+/// Clear the current thread's log
+pub fn reset() {
+    WRITTEN.with(|written| written.borrow_mut().clear());
+}
+
+/// Take the ids written on the current thread, in order and without duplicates
+pub fn take() -> Vec<u128> {
+    let mut ids = WRITTEN.with(|written| std::mem::take(&mut *written.borrow_mut()));
+    let mut seen = std::collections::HashSet::with_capacity(ids.len());
+    ids.retain(|id| seen.insert(*id));
+    ids
+}
@@ -576,3 +576,241 @@ fn test_hnsw_search_returns_results() {
         .unwrap();
     assert!(!results.is_empty());
 }
+
+#[test]
+fn test_hnsw_search_batch_matches_single_searches() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+
+    let mut rng = rand::rng();
+    for _ in 0..256 {
+        let arena = Bump::new();
+        let vector: Vec<f64> = (0..8).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let data = arena.alloc_slice_copy(&vector);
+        let _ = index
+            .insert::<Filter>(&mut txn, "vector", data, None, &arena)
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = env.read_txn().unwrap();
+    let queries: Vec<&[f64]> = (0..16)
+        .map(|_| {
+            let query: Vec<f64> = (0..8).map(|_| rng.random_range(-1.0..1.0)).collect();
+            &*arena.alloc_slice_copy(&query)
+        })
+        .collect();
+    let batch = index
+        .search_batch::<Filter>(&txn, &queries, 10, "vector", None, false, &arena)
+        .unwrap();
+    assert_eq!(batch.len(), queries.len());
+
+    for (query, found) in queries.iter().zip(&batch) {
+        let single = index
+            .search::<Filter>(&txn, query, 10, "vector", None, false, &arena)
+            .unwrap();
+        let batch_ids: Vec<u128> = found.iter().map(|v| v.id).collect();
+        let single_ids: Vec<u128> = single.iter().map(|v| v.id).collect();
+        assert_eq!(batch_ids, single_ids);
+        for (b, s) in found.iter().zip(single.iter()) {
+            assert!((b.get_distance() - s.get_distance()).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn test_hnsw_search_batch_empty_index() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = env.read_txn().unwrap();
+    let query = [0.5, 0.5, 0.5, 0.5];
+    let result = index.search_batch::<Filter>(&txn, &[&query], 5, "vector", None, false, &arena);
+    assert!(matches!(result, Err(VectorError::EntryPointNotFound)));
+}
+
+/// Share of the true `k` nearest vectors by cosine distance that the index finds
+fn recall(
+    index: &VectorCore,
+    env: &Env,
+    vectors: &[Vec<f64>],
+    queries: &[Vec<f64>],
+    k: usize,
+) -> f64 {
+    use crate::helix_engine::vector_core::vector_distance::cosine_similarity;
+
+    let txn = env.read_txn().unwrap();
+    let mut found = 0;
+    for query in queries {
+        let arena = Bump::new();
+        let mut exact: Vec<(f64, usize)> = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (1.0 - cosine_similarity(query, v).unwrap(), i))
+            .collect();
+        exact.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let cutoff = exact[k - 1].0 + 1e-9;
+        let results = index
+            .search::<Filter>(
+                &txn,
+                arena.alloc_slice_copy(query),
+                k,
+                "vector",
+                None,
+                false,
+                &arena,
+            )
+            .unwrap();
+        found += results
+            .iter()
+            .filter(|v| v.get_distance() <= cutoff)
+            .count();
+    }
+    found as f64 / (queries.len() * k) as f64
+}
+
+fn random_vectors(n: usize, dimensions: usize) -> Vec<Vec<f64>> {
+    let mut rng = rand::rng();
+    (0..n)
+        .map(|_| {
+            (0..dimensions)
+                .map(|_| rng.random_range(-1.0..1.0))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_bulk_load_links_vectors_searchably() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+
+    let vectors = random_vectors(2000, 16);
+    let arena = Bump::new();
+    let items = vectors
+        .iter()
+        .map(|v| (&*arena.alloc_slice_copy(v), None))
+        .collect();
+    let inserted = index.bulk_load(&mut txn, "vector", items, &arena).unwrap();
+    assert_eq!(inserted.len(), vectors.len());
+    txn.commit().unwrap();
+
+    let txn = env.read_txn().unwrap();
+    let arena = Bump::new();
+    let stored = index
+        .get_full_vector(&txn, inserted[10].id, &arena)
+        .unwrap();
+    assert_eq!(stored.data, vectors[10].as_slice());
+    drop(txn);
+
+    assert!(recall(&index, &env, &vectors, &random_vectors(20, 16), 10) > 0.9);
+}
+
+#[test]
+fn test_bulk_load_into_existing_index() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+
+    let vectors = random_vectors(1000, 8);
+    for vector in &vectors[..300] {
+        let arena = Bump::new();
+        let data = arena.alloc_slice_copy(vector);
+        index
+            .insert::<Filter>(&mut txn, "vector", data, None, &arena)
+            .unwrap();
+    }
+    let arena = Bump::new();
+    let items = vectors[300..]
+        .iter()
+        .map(|v| (&*arena.alloc_slice_copy(v), None))
+        .collect();
+    index.bulk_load(&mut txn, "vector", items, &arena).unwrap();
+    txn.commit().unwrap();
+
+    assert!(recall(&index, &env, &vectors, &random_vectors(20, 8), 10) > 0.9);
+}
+
+#[test]
+fn test_rebuild_hnsw_drops_deleted_vectors() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+
+    let vectors = random_vectors(300, 8);
+    let mut ids = Vec::new();
+    for vector in &vectors {
+        let arena = Bump::new();
+        let data = arena.alloc_slice_copy(vector);
+        let inserted = index
+            .insert::<Filter>(&mut txn, "vector", data, None, &arena)
+            .unwrap();
+        ids.push(inserted.id);
+    }
+    let arena = Bump::new();
+    for id in &ids[..60] {
+        index.delete(&mut txn, *id, &arena).unwrap();
+    }
+    assert_eq!(index.rebuild_hnsw(&mut txn).unwrap(), 240);
+    txn.commit().unwrap();
+
+    let txn = env.read_txn().unwrap();
+    let deleted: std::collections::HashSet<u128> = ids[..60].iter().copied().collect();
+    for item in index.edges_db.iter(&txn).unwrap() {
+        let (key, _) = item.unwrap();
+        let source = u128::from_be_bytes(key[..16].try_into().unwrap());
+        let sink = u128::from_be_bytes(key[24..].try_into().unwrap());
+        assert!(!deleted.contains(&source) && !deleted.contains(&sink));
+    }
+    drop(txn);
+
+    assert!(recall(&index, &env, &vectors[60..], &random_vectors(20, 8), 10) > 0.9);
+}
+
+#[test]
+fn test_insert_with_id_relinks_replaced_vector() {
+    let (env, _temp_dir) = setup_env();
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+
+    let vectors = random_vectors(200, 8);
+    let arena = Bump::new();
+    let ids = vectors
+        .iter()
+        .map(|v| {
+            let data = arena.alloc_slice_copy(v);
+            index
+                .insert::<Filter>(&mut txn, "vector", data, None, &arena)
+                .unwrap()
+                .id
+        })
+        .collect::<Vec<_>>();
+
+    let moved = [10.0; 8];
+    for _ in 0..3 {
+        let data = arena.alloc_slice_copy(&moved);
+        index
+            .insert_with_id(&mut txn, ids[0], "vector", data, None, &arena)
+            .unwrap();
+    }
+    let result = index.insert_with_id(&mut txn, ids[0], "other", &moved, None, &arena);
+    assert!(matches!(result, Err(VectorError::LabelMismatch { .. })));
+    txn.commit().unwrap();
+
+    let txn = env.read_txn().unwrap();
+    let arena = Bump::new();
+    let results = index
+        .search::<Filter>(&txn, &moved, 200, "vector", None, false, &arena)
+        .unwrap();
+    assert_eq!(results[0].id, ids[0]);
+    let mut found = results.iter().map(|v| v.id).collect::<Vec<_>>();
+    found.sort_unstable();
+    found.dedup();
+    assert_eq!(found.len(), results.len());
+}
@@ -0,0 +1,295 @@
+use std::collections::{HashMap, HashSet};
+
+use bumpalo::Bump;
+use heed3::{Env, EnvOpenOptions, RoTxn};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use tempfile::TempDir;
+
+use crate::helix_engine::{
+    traversal_core::config::IvfFlatConfig,
+    types::VectorError,
+    vector_core::{
+        hnsw::HNSW,
+        ivf_flat,
+        vector::HVector,
+        vector_core::{HNSWConfig, VectorCore},
+        vector_distance::cosine_similarity,
+        vector_index::VectorIndexKind,
+    },
+};
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+const DIMENSIONS: usize = 16;
+
+fn setup_index(nlist: usize, nprobe: usize) -> (Env, TempDir, VectorCore) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        EnvOpenOptions::new()
+            .map_size(512 * 1024 * 1024)
+            .max_dbs(32)
+            .open(temp_dir.path())
+            .unwrap()
+    };
+    let mut txn = env.write_txn().unwrap();
+    let config = IvfFlatConfig {
+        nlist: Some(nlist),
+        nprobe: Some(nprobe),
+    };
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+    index
+        .configure_ivf_flat(&mut txn, &HashMap::from([("doc".to_string(), config)]))
+        .unwrap();
+    txn.commit().unwrap();
+    (env, temp_dir, index)
+}
+
+fn random_vectors(n: usize, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            (0..DIMENSIONS)
+                .map(|_| rng.random_range(-1.0..1.0))
+                .collect()
+        })
+        .collect()
+}
+
+fn insert_all(env: &Env, index: &VectorCore, label: &str, vectors: &[Vec<f64>]) -> Vec<u128> {
+    let mut txn = env.write_txn().unwrap();
+    let arena = Bump::new();
+    let ids = vectors
+        .iter()
+        .map(|vector| {
+            let data = arena.alloc_slice_copy(vector);
+            index
+                .insert::<Filter>(&mut txn, label, data, None, &arena)
+                .unwrap()
+                .id
+        })
+        .collect();
+    txn.commit().unwrap();
+    ids
+}
+
+fn search(env: &Env, index: &VectorCore, label: &str, query: &[f64], k: usize) -> Vec<u128> {
+    let txn = env.read_txn().unwrap();
+    let arena = Bump::new();
+    let query = arena.alloc_slice_copy(query);
+    index
+        .search::<Filter>(&txn, query, k, label, None, false, &arena)
+        .unwrap()
+        .iter()
+        .map(|vector| vector.id)
+        .collect()
+}
+
+fn brute_force(ids: &[u128], vectors: &[Vec<f64>], query: &[f64], k: usize) -> Vec<u128> {
+    let mut scored: Vec<(f64, u128)> = ids
+        .iter()
+        .zip(vectors)
+        .map(|(id, vector)| (1.0 - cosine_similarity(query, vector).unwrap(), *id))
+        .collect();
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+    scored.into_iter().take(k).map(|(_, id)| id).collect()
+}
+
+#[test]
+fn test_ivf_flat_probing_every_list_is_exact() {
+    // 1000 vectors over 8 lists retrains the centroids twice and moves vectors between lists
+    let (env, _temp_dir, index) = setup_index(8, 8);
+    let vectors = random_vectors(1000, 1);
+    let ids = insert_all(&env, &index, "doc", &vectors);
+
+    for query in random_vectors(10, 2) {
+        assert_eq!(
+            search(&env, &index, "doc", &query, 10),
+            brute_force(&ids, &vectors, &query, 10)
+        );
+    }
+}
+
+#[test]
+fn test_ivf_flat_recall_with_few_probes() {
+    let (env, _temp_dir, index) = setup_index(16, 6);
+    let vectors = random_vectors(2000, 3);
+    let ids = insert_all(&env, &index, "doc", &vectors);
+
+    let (mut found, mut total) = (0, 0);
+    for query in random_vectors(20, 4) {
+        let results: HashSet<u128> = search(&env, &index, "doc", &query, 10)
+            .into_iter()
+            .collect();
+        let expected = brute_force(&ids, &vectors, &query, 10);
+        found += expected.iter().filter(|id| results.contains(id)).count();
+        total += expected.len();
+    }
+    let recall = found as f64 / total as f64;
+    assert!(recall >= 0.7, "recall {recall} too low");
+}
+
+#[test]
+fn test_ivf_flat_delete_removes_from_search() {
+    let (env, _temp_dir, index) = setup_index(4, 4);
+    let vectors = random_vectors(50, 5);
+    let ids = insert_all(&env, &index, "doc", &vectors);
+
+    let target = ids[7];
+    assert_eq!(search(&env, &index, "doc", &vectors[7], 1), vec![target]);
+
+    let mut txn = env.write_txn().unwrap();
+    index.delete(&mut txn, target, &Bump::new()).unwrap();
+    assert!(!ivf_flat::remove(index.ivf_flat_db, &mut txn, target).unwrap());
+    txn.commit().unwrap();
+
+    let results = search(&env, &index, "doc", &vectors[7], 10);
+    assert_eq!(results.len(), 10);
+    assert!(!results.contains(&target));
+}
+
+#[test]
+fn test_ivf_flat_reinsert_replaces_vector() {
+    let (env, _temp_dir, index) = setup_index(4, 4);
+    let vectors = random_vectors(20, 6);
+    let ids = insert_all(&env, &index, "doc", &vectors);
+
+    let mut txn = env.write_txn().unwrap();
+    let arena = Bump::new();
+    let data = arena.alloc_slice_copy(&vectors[0]);
+    index
+        .insert_with_id(&mut txn, ids[3], "doc", data, None, &arena)
+        .unwrap();
+    txn.commit().unwrap();
+
+    // Both the vector now under ids[3] and the original match exactly, and ids[3] is only
+    // returned once
+    let results = search(&env, &index, "doc", &vectors[0], 3);
+    assert_eq!(
+        results[..2].iter().collect::<HashSet<_>>(),
+        HashSet::from([&ids[0], &ids[3]])
+    );
+    assert!(!results[2..].contains(&ids[3]));
+}
+
+#[test]
+fn test_ivf_flat_and_hnsw_types_are_separate() {
+    let (env, _temp_dir, index) = setup_index(4, 4);
+    let docs = random_vectors(30, 7);
+    let images = random_vectors(30, 8);
+    let doc_ids = insert_all(&env, &index, "doc", &docs);
+    let image_ids = insert_all(&env, &index, "image", &images);
+
+    let txn = env.read_txn().unwrap();
+    assert!(index.edges_db.len(&txn).unwrap() > 0);
+    drop(txn);
+
+    let results = search(&env, &index, "doc", &images[0], 10);
+    assert!(results.iter().all(|id| doc_ids.contains(id)));
+    let results = search(&env, &index, "image", &docs[0], 10);
+    assert!(results.iter().all(|id| image_ids.contains(id)));
+    assert_eq!(
+        search(&env, &index, "image", &images[5], 1),
+        vec![image_ids[5]]
+    );
+}
+
+#[test]
+fn test_ivf_flat_search_empty_type() {
+    let (env, _temp_dir, index) = setup_index(4, 4);
+    let txn = env.read_txn().unwrap();
+    let arena = Bump::new();
+    let query = arena.alloc_slice_copy(&[0.5; DIMENSIONS]);
+    let result = index.search::<Filter>(&txn, query, 5, "doc", None, false, &arena);
+    assert!(matches!(result, Err(VectorError::EntryPointNotFound)));
+}
+
+#[test]
+fn test_ivf_flat_config_defaults() {
+    let config = IvfFlatConfig::default();
+    assert_eq!((config.nlist(), config.nprobe()), (1024, 32));
+
+    let config = IvfFlatConfig {
+        nlist: Some(8),
+        nprobe: Some(100),
+    };
+    assert_eq!(config.nprobe(), 8);
+}
+
+#[test]
+fn test_ivf_flat_index_kind_follows_config() {
+    let (env, _temp_dir, index) = setup_index(4, 2);
+    let txn = env.read_txn().unwrap();
+    match index.index_kind(&txn, "doc").unwrap() {
+        VectorIndexKind::IvfFlat(ivf_flat) => assert_eq!(ivf_flat.config().nprobe(), 2),
+        other => panic!("expected IVF-Flat, got {other:?}"),
+    }
+    assert!(matches!(
+        index.index_kind(&txn, "image").unwrap(),
+        VectorIndexKind::Hnsw(_)
+    ));
+    drop(txn);
+
+    // Reconfiguring drops the settings of types no longer listed
+    let mut txn = env.write_txn().unwrap();
+    index.configure_ivf_flat(&mut txn, &HashMap::new()).unwrap();
+    assert!(matches!(
+        index.index_kind(&txn, "doc").unwrap(),
+        VectorIndexKind::Hnsw(_)
+    ));
+}
+
+#[test]
+fn test_ivf_flat_refuses_switch_of_type_with_hnsw_vectors() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        EnvOpenOptions::new()
+            .map_size(512 * 1024 * 1024)
+            .max_dbs(32)
+            .open(temp_dir.path())
+            .unwrap()
+    };
+    let mut txn = env.write_txn().unwrap();
+    let index = VectorCore::new(&env, &mut txn, HNSWConfig::new(None, None, None)).unwrap();
+    txn.commit().unwrap();
+    insert_all(&env, &index, "doc", &random_vectors(10, 7));
+
+    let config = IvfFlatConfig {
+        nlist: Some(4),
+        nprobe: Some(2),
+    };
+    let mut txn = env.write_txn().unwrap();
+    let result = index.configure_ivf_flat(&mut txn, &HashMap::from([("doc".to_string(), config)]));
+    assert!(matches!(result, Err(VectorError::IndexSwitch(label)) if label == "doc"));
+    assert!(matches!(
+        index.index_kind(&txn, "doc").unwrap(),
+        VectorIndexKind::Hnsw(_)
+    ));
+
+    // A type without vectors yet can still be switched
+    index
+        .configure_ivf_flat(&mut txn, &HashMap::from([("image".to_string(), config)]))
+        .unwrap();
+    assert!(matches!(
+        index.index_kind(&txn, "image").unwrap(),
+        VectorIndexKind::IvfFlat(_)
+    ));
+}
+
+#[test]
+fn test_ivf_flat_reconfigures_type_with_its_own_vectors() {
+    let (env, _temp_dir, index) = setup_index(4, 2);
+    insert_all(&env, &index, "doc", &random_vectors(10, 8));
+
+    let config = IvfFlatConfig {
+        nlist: Some(4),
+        nprobe: Some(4),
+    };
+    let mut txn = env.write_txn().unwrap();
+    index
+        .configure_ivf_flat(&mut txn, &HashMap::from([("doc".to_string(), config)]))
+        .unwrap();
+    match index.index_kind(&txn, "doc").unwrap() {
+        VectorIndexKind::IvfFlat(ivf_flat) => assert_eq!(ivf_flat.config().nprobe(), 4),
+        other => panic!("expected IVF-Flat, got {other:?}"),
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::helix_engine::{
+    graph::random_walk::{WalkConfig, co_occurrences, random_walks},
+    storage_core::HelixGraphStorage,
+    traversal_core::{
+        config::Config,
+        ops::{
+            g::G,
+            source::{add_e::AddEAdapter, add_n::AddNAdapter},
+        },
+    },
+};
+
+/// A chain `a - b - c` of `link` edges, with `b` also `blocks` a lone `d` and an unlinked post
+struct Graph {
+    _temp_dir: TempDir,
+    storage: HelixGraphStorage,
+    a: u128,
+    b: u128,
+    c: u128,
+    d: u128,
+    post: u128,
+}
+
+fn setup() -> Graph {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = HelixGraphStorage::new(
+        temp_dir.path().to_str().unwrap(),
+        Config::default(),
+        Default::default(),
+    )
+    .unwrap();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let mut add = |label: &str| {
+        G::new_mut(&storage, &arena, &mut txn)
+            .add_n(label, None, None)
+            .collect_to_obj()
+            .unwrap()
+            .id()
+    };
+    let [a, b, c, d] = ["user"; 4].map(&mut add);
+    let post = add("post");
+    for (label, from, to) in [("link", a, b), ("link", c, b), ("blocks", b, d)] {
+        G::new_mut(&storage, &arena, &mut txn)
+            .add_edge(label, None, from, to, false, false)
+            .collect_to_obj()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    Graph {
+        _temp_dir: temp_dir,
+        storage,
+        a,
+        b,
+        c,
+        d,
+        post,
+    }
+}
+
+fn walks(graph: &Graph, config: &WalkConfig) -> Vec<Vec<u128>> {
+    let txn = graph.storage.graph_env.read_txn().unwrap();
+    random_walks(&graph.storage, &txn, config).unwrap()
+}
+
+#[test]
+fn test_walks_start_from_every_node_and_follow_edges_either_way() {
+    let graph = setup();
+    let config = WalkConfig {
+        walk_length: 6,
+        walks_per_node: 3,
+        seed: Some(7),
+        ..Default::default()
+    };
+    let all = walks(&graph, &config);
+    assert_eq!(all.len(), 15);
+
+    let linked = [(graph.a, graph.b), (graph.c, graph.b), (graph.b, graph.d)];
+    let edges: HashSet<(u128, u128)> = linked
+        .into_iter()
+        .flat_map(|(from, to)| [(from, to), (to, from)])
+        .collect();
+    for walk in &all {
+        assert!(
+            walk.windows(2)
+                .all(|step| edges.contains(&(step[0], step[1])))
+        );
+        // Only the post, which has no edges, stops early
+        match walk[0] == graph.post {
+            true => assert_eq!(walk, &[graph.post]),
+            false => assert_eq!(walk.len(), 6),
+        }
+    }
+    let starts: HashSet<u128> = all.iter().map(|walk| walk[0]).collect();
+    assert_eq!(starts.len(), 5);
+
+    // The same seed walks the same way
+    assert_eq!(walks(&graph, &config), all);
+}
+
+#[test]
+fn test_walks_keep_to_the_label_and_edge_labels() {
+    let graph = setup();
+    let all = walks(
+        &graph,
+        &WalkConfig {
+            walk_length: 5,
+            walks_per_node: 2,
+            label: Some("user".to_string()),
+            edge_labels: vec!["link".to_string()],
+            ..Default::default()
+        },
+    );
+    assert_eq!(all.len(), 8);
+    assert!(all.iter().all(|walk| walk[0] != graph.post));
+    // d is only reached by a `blocks` edge
+    for walk in &all {
+        match walk[0] == graph.d {
+            true => assert_eq!(walk, &[graph.d]),
+            false => assert!(!walk.contains(&graph.d)),
+        }
+    }
+}
+
+#[test]
+fn test_low_return_parameter_walks_back_and_forth() {
+    let graph = setup();
+    let config = WalkConfig {
+        walk_length: 9,
+        walks_per_node: 20,
+        p: 1e-9,
+        q: 1e9,
+        label: Some("user".to_string()),
+        edge_labels: vec!["link".to_string()],
+        seed: Some(1),
+    };
+    for walk in walks(&graph, &config)
+        .iter()
+        .filter(|walk| walk[0] == graph.a)
+    {
+        let expected = [graph.a, graph.b].repeat(5)[..9].to_vec();
+        assert_eq!(walk, &expected);
+    }
+
+    // A low in-out parameter always moves on from b instead of stepping back
+    let outward = WalkConfig {
+        p: 1e9,
+        q: 1e-9,
+        ..config
+    };
+    for walk in walks(&graph, &outward)
+        .iter()
+        .filter(|walk| walk[0] == graph.a)
+    {
+        assert_eq!(&walk[..3], &[graph.a, graph.b, graph.c]);
+    }
+}
+
+#[test]
+fn test_walk_config_is_checked() {
+    let graph = setup();
+    let txn = graph.storage.graph_env.read_txn().unwrap();
+    for config in [
+        WalkConfig {
+            walk_length: 0,
+            ..Default::default()
+        },
+        WalkConfig {
+            q: 0.0,
+            ..Default::default()
+        },
+    ] {
+        assert!(random_walks(&graph.storage, &txn, &config).is_err());
+    }
+}
+
+#[test]
+fn test_co_occurrences_pair_nodes_within_the_window() {
+    let walks = vec![vec![1, 2, 3, 4], vec![5]];
+    assert_eq!(
+        co_occurrences(&walks, 1),
+        [(1, 2), (2, 1), (2, 3), (3, 2), (3, 4), (4, 3)]
+    );
+    assert_eq!(co_occurrences(&walks, 2).len(), 10);
+    assert!(co_occurrences(&walks, 0).is_empty());
+}
@@ -892,3 +892,43 @@ fn test_drop_one_edge_preserves_other_edges() {
     assert!(storage.get_edge(&txn, &edge1.id, &arena).is_err());
     assert!(storage.get_edge(&txn, &edge2.id, &arena).is_ok());
 }
+
+// ============================================================================
+// Pinned Snapshot Tests
+// ============================================================================
+
+use crate::helix_engine::storage_core::snapshot::{PinnedSnapshot, ReadTxn};
+
+#[test]
+fn test_pinned_snapshot_hides_later_writes_on_its_thread() {
+    let (storage, _temp_dir) = setup_test_storage();
+    let arena = Bump::new();
+    insert_node(&storage, &create_test_node(&arena, 20001, "Before"));
+
+    let snapshot = PinnedSnapshot::pin(&storage).unwrap();
+    insert_node(&storage, &create_test_node(&arena, 20002, "After"));
+
+    // Every read on this thread shares the snapshot taken before the second write
+    let txn = storage.read_txn().unwrap();
+    assert!(matches!(txn, ReadTxn::Pinned(_)));
+    assert_eq!(txn.id(), snapshot.txn_id());
+    assert!(storage.get_node(&txn, &20001, &arena).is_ok());
+    assert!(storage.get_node(&txn, &20002, &arena).is_err());
+    let again = storage.read_txn().unwrap();
+    assert!(storage.get_node(&again, &20002, &arena).is_err());
+
+    // Other threads read the latest commit
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            let arena = Bump::new();
+            let txn = storage.read_txn().unwrap();
+            assert!(matches!(txn, ReadTxn::Fresh(_)));
+            assert!(storage.get_node(&txn, &20002, &arena).is_ok());
+        });
+    });
+
+    drop((txn, again, snapshot));
+    let txn = storage.read_txn().unwrap();
+    assert!(matches!(txn, ReadTxn::Fresh(_)));
+    assert!(storage.get_node(&txn, &20002, &arena).is_ok());
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use tempfile::TempDir;
+
+use crate::helix_engine::{
+    storage_core::HelixGraphStorage,
+    traversal_core::{
+        config::Config,
+        ops::{
+            g::G,
+            source::{add_e::AddEAdapter, add_n::AddNAdapter},
+            util::upsert::UpsertAdapter,
+        },
+        traversal_value::TraversalValue,
+    },
+    types::GraphError,
+};
+
+fn open(path: &str) -> Arc<HelixGraphStorage> {
+    let mut config = Config::default();
+    config.graph_config.as_mut().unwrap().acyclic_edges = Some(vec!["depends_on".to_string()]);
+    Arc::new(HelixGraphStorage::new(path, config, Default::default()).unwrap())
+}
+
+fn add_task<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_n("task", None, None)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn add_edge<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    label: &'static str,
+    from: u128,
+    to: u128,
+) -> Result<(), GraphError> {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_edge(label, None, from, to, false, false)
+        .collect_to_obj()
+        .map(|_| ())
+}
+
+#[test]
+fn test_edges_closing_a_cycle_are_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = add_task(&storage, &mut txn);
+    let b = add_task(&storage, &mut txn);
+    let c = add_task(&storage, &mut txn);
+    add_edge(&storage, &mut txn, "depends_on", a, b).unwrap();
+    add_edge(&storage, &mut txn, "depends_on", b, c).unwrap();
+    // Two paths to the same task aren't a cycle
+    add_edge(&storage, &mut txn, "depends_on", a, c).unwrap();
+
+    assert!(matches!(
+        add_edge(&storage, &mut txn, "depends_on", c, a),
+        Err(GraphError::CycleDetected(_))
+    ));
+    assert!(matches!(
+        add_edge(&storage, &mut txn, "depends_on", b, b),
+        Err(GraphError::CycleDetected(_))
+    ));
+    // Only edges of the acyclic type are followed
+    add_edge(&storage, &mut txn, "blocks", c, a).unwrap();
+    add_edge(&storage, &mut txn, "blocks", a, a).unwrap();
+}
+
+#[test]
+fn test_edges_added_in_earlier_writes_are_followed() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = add_task(&storage, &mut txn);
+    let b = add_task(&storage, &mut txn);
+    add_edge(&storage, &mut txn, "depends_on", a, b).unwrap();
+    txn.commit().unwrap();
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let error = add_edge(&storage, &mut txn, "depends_on", b, a).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Cycle detected: depends_on edge from {} to {} would close a cycle",
+            uuid::Uuid::from_u128(b),
+            uuid::Uuid::from_u128(a)
+        )
+    );
+}
+
+#[test]
+fn test_upserted_edges_closing_a_cycle_are_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = add_task(&storage, &mut txn);
+    let b = add_task(&storage, &mut txn);
+    add_edge(&storage, &mut txn, "depends_on", a, b).unwrap();
+
+    let arena = Bump::new();
+    let result = G::new_mut_from_iter(
+        &storage,
+        &mut txn,
+        std::iter::empty::<TraversalValue>(),
+        &arena,
+    )
+    .upsert_e("depends_on", b, a, &[])
+    .collect::<Result<Vec<_>, _>>();
+    assert!(matches!(result, Err(GraphError::CycleDetected(_))));
+}
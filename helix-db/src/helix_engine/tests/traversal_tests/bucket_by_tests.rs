@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use chrono::{DateTime, Utc};
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{add_n::AddNAdapter, n_from_type::NFromTypeAdapter},
+                util::bucket_by::BucketByAdapter,
+            },
+        },
+    },
+    props,
+    protocol::value::Value,
+    utils::bucket::{Buckets, Interval, IntervalUnit},
+};
+
+const POSTED: [&str; 5] = [
+    "2024-03-04T10:00:00Z",
+    "2024-03-04T23:30:00Z",
+    "2024-03-05T08:00:00Z",
+    "2024-03-10T12:00:00Z",
+    "2024-04-01T00:00:00Z",
+];
+
+fn date(time: &str) -> Value {
+    Value::String(time.to_string())
+}
+
+/// Posts created at each of `POSTED`, and one without a creation time
+fn setup_posts() -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = Arc::new(
+        HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            Config::default(),
+            Default::default(),
+        )
+        .unwrap(),
+    );
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    for time in POSTED {
+        G::new_mut(&storage, &arena, &mut txn)
+            .add_n(
+                "post",
+                props_option(&arena, props!("created_at" => date(time))),
+                None,
+            )
+            .collect_to_obj()
+            .unwrap();
+    }
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_n("post", None, None)
+        .collect_to_obj()
+        .unwrap();
+    txn.commit().unwrap();
+    (temp_dir, storage)
+}
+
+fn counts(storage: &HelixGraphStorage, interval: &str, timezone: &str) -> Vec<(String, usize)> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let buckets = G::new(storage, &txn, &arena)
+        .n_from_type("post")
+        .bucket_by("created_at", interval, timezone, true)
+        .unwrap();
+    buckets
+        .0
+        .into_iter()
+        .map(|bucket| {
+            assert!(bucket.items.is_none());
+            (bucket.start, bucket.count)
+        })
+        .collect()
+}
+
+fn expected(buckets: &[(&str, usize)]) -> Vec<(String, usize)> {
+    buckets
+        .iter()
+        .map(|(start, count)| (start.to_string(), *count))
+        .collect()
+}
+
+#[test]
+fn test_bucket_by_counts_per_calendar_bucket() {
+    let (_temp_dir, storage) = setup_posts();
+
+    assert_eq!(
+        counts(&storage, "1d", "UTC"),
+        expected(&[
+            ("2024-03-04T00:00:00+00:00", 2),
+            ("2024-03-05T00:00:00+00:00", 1),
+            ("2024-03-10T00:00:00+00:00", 1),
+            ("2024-04-01T00:00:00+00:00", 1),
+        ])
+    );
+    // Weeks start on Monday, so Sunday the 10th falls in the week of the 4th
+    assert_eq!(
+        counts(&storage, "1w", "UTC"),
+        expected(&[
+            ("2024-03-04T00:00:00+00:00", 4),
+            ("2024-04-01T00:00:00+00:00", 1),
+        ])
+    );
+    assert_eq!(
+        counts(&storage, "1mo", "UTC"),
+        expected(&[
+            ("2024-03-01T00:00:00+00:00", 4),
+            ("2024-04-01T00:00:00+00:00", 1),
+        ])
+    );
+    assert_eq!(
+        counts(&storage, "1y", "UTC"),
+        expected(&[("2024-01-01T00:00:00+00:00", 5)])
+    );
+    assert_eq!(
+        counts(&storage, "12h", "UTC")[..2],
+        expected(&[
+            ("2024-03-04T00:00:00+00:00", 1),
+            ("2024-03-04T12:00:00+00:00", 1),
+        ])
+    );
+}
+
+#[test]
+fn test_bucket_by_follows_the_time_zone() {
+    let (_temp_dir, storage) = setup_posts();
+
+    // 23:30 UTC on the 4th is already the 5th in Berlin, and April is in summer time
+    assert_eq!(
+        counts(&storage, "1d", "Europe/Berlin"),
+        expected(&[
+            ("2024-03-04T00:00:00+01:00", 1),
+            ("2024-03-05T00:00:00+01:00", 2),
+            ("2024-03-10T00:00:00+01:00", 1),
+            ("2024-04-01T00:00:00+02:00", 1),
+        ])
+    );
+    // Midnight UTC on April 1st is still March in New York
+    assert_eq!(
+        counts(&storage, "1mo", "America/New_York"),
+        expected(&[("2024-03-01T00:00:00-05:00", 5)])
+    );
+}
+
+#[test]
+fn test_bucket_by_keeps_items_unless_counting() {
+    let (_temp_dir, storage) = setup_posts();
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let Buckets(buckets) = G::new(&storage, &txn, &arena)
+        .n_from_type("post")
+        .bucket_by("created_at", "1mo", "UTC", false)
+        .unwrap();
+
+    let sizes: Vec<usize> = buckets
+        .iter()
+        .map(|bucket| bucket.items.as_ref().unwrap().len())
+        .collect();
+    assert_eq!(sizes, [4, 1]);
+    assert_eq!(buckets[1].count, 1);
+    assert_eq!(
+        buckets[1].items.as_ref().unwrap()[0].get_property("created_at"),
+        Some(&date(POSTED[4]))
+    );
+}
+
+#[test]
+fn test_bucket_by_rejects_bad_intervals_and_time_zones() {
+    let (_temp_dir, storage) = setup_posts();
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    for (interval, timezone) in [
+        ("0d", "UTC"),
+        ("1x", "UTC"),
+        ("d", "UTC"),
+        ("1d", "Mars/Base"),
+    ] {
+        let result = G::new(&storage, &txn, &arena)
+            .n_from_type("post")
+            .bucket_by("created_at", interval, timezone, true);
+        assert!(result.is_err(), "{interval} {timezone}");
+    }
+}
+
+#[test]
+fn test_interval_buckets_across_daylight_saving_changes() {
+    let day: Interval = "1d".parse().unwrap();
+    assert_eq!(
+        day,
+        Interval {
+            count: 1,
+            unit: IntervalUnit::Day
+        }
+    );
+    assert_eq!("3mo".parse::<Interval>().unwrap().to_string(), "3mo");
+
+    let tz = chrono_tz::America::New_York;
+    let start = |time: &str| {
+        day.bucket_start(time.parse::<DateTime<Utc>>().unwrap(), tz)
+            .to_rfc3339()
+    };
+    // New York moved its clocks forward at 2am on March 10th, 2024
+    assert_eq!(start("2024-03-10T12:00:00Z"), "2024-03-10T00:00:00-05:00");
+    assert_eq!(start("2024-03-11T12:00:00Z"), "2024-03-11T00:00:00-04:00");
+
+    // Santiago skipped midnight on September 8th, 2024, so that day starts at 1am
+    let tz = chrono_tz::America::Santiago;
+    let time = "2024-09-08T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    assert_eq!(
+        day.bucket_start(time, tz).to_rfc3339(),
+        "2024-09-08T01:00:00-03:00"
+    );
+}
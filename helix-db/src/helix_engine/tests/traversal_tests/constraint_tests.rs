@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use tempfile::TempDir;
+
+use crate::helix_engine::{
+    storage_core::{
+        HelixGraphStorage,
+        fsck::{self, IssueKind},
+        storage_methods::StorageMethods,
+    },
+    traversal_core::{
+        config::Config,
+        ops::{
+            g::G,
+            source::{add_e::AddEAdapter, add_n::AddNAdapter},
+        },
+    },
+    types::{EdgeConstraint, GraphError},
+};
+
+/// `E::authored_by { From: comment [1], To: user [0..2] }`
+fn authored_by() -> Vec<EdgeConstraint> {
+    vec![
+        EdgeConstraint {
+            node_label: "comment".to_string(),
+            edge_label: "authored_by".to_string(),
+            incoming: false,
+            min: 1,
+            max: Some(1),
+        },
+        EdgeConstraint {
+            node_label: "user".to_string(),
+            edge_label: "authored_by".to_string(),
+            incoming: true,
+            min: 0,
+            max: Some(2),
+        },
+    ]
+}
+
+fn open(path: &str, constraints: Option<Vec<EdgeConstraint>>) -> Arc<HelixGraphStorage> {
+    let mut config = Config::default();
+    config.graph_config.as_mut().unwrap().edge_constraints = constraints;
+    Arc::new(HelixGraphStorage::new(path, config, Default::default()).unwrap())
+}
+
+fn add_node<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    label: &'static str,
+) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_n(label, None, None)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn author<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    comment: u128,
+    user: u128,
+) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_edge("authored_by", None, comment, user, false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn violation(result: Result<(), GraphError>) -> String {
+    match result {
+        Err(GraphError::ConstraintViolation(violation)) => violation.requirement(),
+        other => panic!("expected a constraint violation, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_node_can_get_its_required_edges_in_the_same_write() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap(), Some(authored_by()));
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let user = add_node(&storage, &mut txn, "user");
+    // Users need no edges
+    storage.check_constraints(&txn).unwrap();
+    let comment = add_node(&storage, &mut txn, "comment");
+    author(&storage, &mut txn, comment, user);
+    storage.check_constraints(&txn).unwrap();
+    txn.commit().unwrap();
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    add_node(&storage, &mut txn, "comment");
+    assert_eq!(
+        violation(storage.check_constraints(&txn)),
+        "must have exactly 1 outgoing authored_by edge, it has 0"
+    );
+}
+
+#[test]
+fn test_edges_beyond_the_upper_bound_break_constraints() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap(), Some(authored_by()));
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let user = add_node(&storage, &mut txn, "user");
+    let other = add_node(&storage, &mut txn, "user");
+    let comment = add_node(&storage, &mut txn, "comment");
+    author(&storage, &mut txn, comment, user);
+    author(&storage, &mut txn, comment, other);
+    assert_eq!(
+        violation(storage.check_constraints(&txn)),
+        "must have exactly 1 outgoing authored_by edge, it has 2"
+    );
+    txn.abort();
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let user = add_node(&storage, &mut txn, "user");
+    for _ in 0..3 {
+        let comment = add_node(&storage, &mut txn, "comment");
+        author(&storage, &mut txn, comment, user);
+    }
+    assert_eq!(
+        violation(storage.check_constraints(&txn)),
+        "must have at most 2 incoming authored_by edges, it has 3"
+    );
+}
+
+#[test]
+fn test_dropping_required_edges_breaks_constraints() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap(), Some(authored_by()));
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let user = add_node(&storage, &mut txn, "user");
+    let comment = add_node(&storage, &mut txn, "comment");
+    let edge = author(&storage, &mut txn, comment, user);
+    storage.check_constraints(&txn).unwrap();
+    txn.commit().unwrap();
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.drop_edge(&mut txn, &edge).unwrap();
+    assert!(storage.check_constraints(&txn).is_err());
+    txn.abort();
+
+    // Dropping the comment takes its edge with it
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.drop_node(&mut txn, &comment).unwrap();
+    storage.check_constraints(&txn).unwrap();
+}
+
+#[test]
+fn test_fsck_reports_nodes_written_before_constraints() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().to_str().unwrap();
+    let comment = {
+        let storage = open(path, None);
+        let mut txn = storage.graph_env.write_txn().unwrap();
+        let comment = add_node(&storage, &mut txn, "comment");
+        storage.check_constraints(&txn).unwrap();
+        txn.commit().unwrap();
+        comment
+    };
+
+    let storage = open(path, Some(authored_by()));
+    let report = fsck::check(&storage).unwrap();
+
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    let issue = &report.issues[0];
+    assert_eq!(issue.kind, IssueKind::EdgeConstraintViolation);
+    assert_eq!(issue.id.as_u128(), comment);
+    assert_eq!(
+        issue.detail,
+        "comment must have exactly 1 outgoing authored_by edge, it has 0"
+    );
+    assert!(!issue.repairable);
+}
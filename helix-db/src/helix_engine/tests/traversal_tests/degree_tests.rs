@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, degrees::DegreeDirection},
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                in_::in_e::InEdgesAdapter,
+                source::{
+                    add_e::AddEAdapter, add_n::AddNAdapter, e_from_id::EFromIdAdapter,
+                    n_from_id::NFromIdAdapter, n_from_type::NFromTypeAdapter,
+                },
+                util::{degree::DegreeAdapter, drop::Drop},
+            },
+        },
+    },
+    protocol::value::Value,
+};
+
+fn open(path: &str) -> Arc<HelixGraphStorage> {
+    Arc::new(HelixGraphStorage::new(path, Config::default(), Default::default()).unwrap())
+}
+
+fn add_user(storage: &HelixGraphStorage) -> u128 {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let id = G::new_mut(storage, &arena, &mut txn)
+        .add_n("user", None, None)
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    txn.commit().unwrap();
+    id
+}
+
+fn link(storage: &HelixGraphStorage, label: &str, from: u128, to: u128) -> u128 {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let id = G::new_mut(storage, &arena, &mut txn)
+        .add_edge(label, None, from, to, false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    txn.commit().unwrap();
+    id
+}
+
+fn degree(storage: &HelixGraphStorage, id: u128, direction: DegreeDirection) -> Value {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_id(&id)
+        .degree("follows", direction)
+}
+
+fn neighbor_count(storage: &HelixGraphStorage, id: u128) -> Value {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_id(&id)
+        .neighbor_count()
+}
+
+#[test]
+fn test_degree_follows_added_and_dropped_edges() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+    let alice = add_user(&storage);
+    let bob = add_user(&storage);
+    let carol = add_user(&storage);
+
+    let bob_follows_alice = link(&storage, "follows", bob, alice);
+    link(&storage, "follows", carol, alice);
+    link(&storage, "follows", alice, bob);
+    link(&storage, "blocks", alice, carol);
+    assert_eq!(degree(&storage, alice, DegreeDirection::In), Value::U64(2));
+    assert_eq!(degree(&storage, alice, DegreeDirection::Out), Value::U64(1));
+    assert_eq!(
+        degree(&storage, alice, DegreeDirection::Both),
+        Value::U64(3)
+    );
+    assert_eq!(neighbor_count(&storage, alice), Value::U64(4));
+    assert_eq!(degree(&storage, carol, DegreeDirection::In), Value::U64(0));
+    assert_eq!(neighbor_count(&storage, carol), Value::U64(2));
+
+    // The same number as counting the edges
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let walked = G::new(&storage, &txn, &arena)
+        .n_from_id(&alice)
+        .in_e("follows")
+        .count();
+    drop(txn);
+    assert_eq!(
+        degree(&storage, alice, DegreeDirection::In),
+        Value::from(walked)
+    );
+
+    // Summed over every node in the traversal
+    let txn = storage.graph_env.read_txn().unwrap();
+    let total = G::new(&storage, &txn, &arena)
+        .n_from_type("user")
+        .degree("follows", DegreeDirection::Out);
+    drop(txn);
+    assert_eq!(total, Value::U64(3));
+
+    let txn = storage.graph_env.read_txn().unwrap();
+    let edge = G::new(&storage, &txn, &arena)
+        .e_from_id(&bob_follows_alice)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    drop(txn);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    Drop::drop_traversal(edge.into_iter().map(Ok), storage.as_ref(), &mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(degree(&storage, alice, DegreeDirection::In), Value::U64(1));
+    assert_eq!(degree(&storage, bob, DegreeDirection::Out), Value::U64(0));
+
+    // Dropping a node uncounts its edges from the nodes at their other end
+    let txn = storage.graph_env.read_txn().unwrap();
+    let node = G::new(&storage, &txn, &arena)
+        .n_from_id(&alice)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    drop(txn);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    Drop::drop_traversal(node.into_iter().map(Ok), storage.as_ref(), &mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(neighbor_count(&storage, bob), Value::U64(0));
+    assert_eq!(neighbor_count(&storage, carol), Value::U64(0));
+}
+
+#[test]
+fn test_degrees_are_counted_for_storages_written_before_them() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().to_str().unwrap();
+    let storage = open(path);
+    let alice = add_user(&storage);
+    let bob = add_user(&storage);
+    let carol = add_user(&storage);
+    link(&storage, "follows", bob, alice);
+    link(&storage, "follows", carol, alice);
+    link(&storage, "follows", alice, alice);
+
+    // Forget the counts as a storage from before they were kept
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.degrees_db.clear(&mut txn).unwrap();
+    storage
+        .metadata_db
+        .delete(&mut txn, b"degrees_counted")
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(degree(&storage, alice, DegreeDirection::In), Value::U64(0));
+    drop(storage);
+
+    let storage = open(path);
+    assert_eq!(degree(&storage, alice, DegreeDirection::In), Value::U64(3));
+    assert_eq!(
+        degree(&storage, alice, DegreeDirection::Both),
+        Value::U64(4)
+    );
+    assert_eq!(neighbor_count(&storage, bob), Value::U64(1));
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{
+                    add_e::AddEAdapter, add_n::AddNAdapter, e_from_type::EFromTypeAdapter,
+                    n_from_type::NFromTypeAdapter,
+                },
+                util::{delete_where::DeleteWhere, filter_ref::FilterRefAdapter},
+            },
+            traversal_value::TraversalValue,
+        },
+        types::GraphError,
+    },
+    props,
+    protocol::value::Value,
+};
+
+/// Logs with timestamps 0 to 29, each linked to the next
+fn setup_logs() -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = Arc::new(
+        HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            Config::default(),
+            Default::default(),
+        )
+        .unwrap(),
+    );
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let mut previous = None;
+    for ts in 0..30 {
+        let log = G::new_mut(&storage, &arena, &mut txn)
+            .add_n("Log", props_option(&arena, props!("ts" => ts)), None)
+            .collect_to_obj()
+            .unwrap();
+        if let Some(previous) = previous {
+            G::new_mut(&storage, &arena, &mut txn)
+                .add_edge("Next", None, previous, log.id(), false, false)
+                .collect_to_obj()
+                .unwrap();
+        }
+        previous = Some(log.id());
+    }
+    txn.commit().unwrap();
+    (temp_dir, storage)
+}
+
+fn count(storage: &HelixGraphStorage, label: &str, edges: bool) -> usize {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    match edges {
+        true => G::new(storage, &txn, &arena).e_from_type(label).count(),
+        false => G::new(storage, &txn, &arena).n_from_type(label).count(),
+    }
+}
+
+fn before(cutoff: i64) -> impl Fn(&Result<TraversalValue, GraphError>) -> bool {
+    move |val| match val {
+        Ok(val) => val
+            .get_property("ts")
+            .is_some_and(|ts| *ts < Value::I64(cutoff)),
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn test_delete_where_commits_batches_until_nothing_matches() {
+    let (_temp_dir, storage) = setup_logs();
+    let txn = storage.graph_env.write_txn().unwrap();
+
+    let mut calls = 0;
+    let (deleted, txn) = DeleteWhere::delete_in_batches(&storage, txn, 10, |txn, arena, batch| {
+        calls += 1;
+        let old = before(25);
+        G::new(&storage, txn, arena)
+            .n_from_type("Log")
+            .filter_ref(move |val, _| Ok(old(val)))
+            .take(batch)
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .unwrap();
+    assert_eq!(deleted, 25);
+    assert_eq!(calls, 3);
+
+    // The first two batches are committed, the last is left to the query to commit
+    assert_eq!(count(&storage, "Log", false), 10);
+    txn.commit().unwrap();
+    assert_eq!(count(&storage, "Log", false), 5);
+    // Only the links between the logs that are left remain
+    assert_eq!(count(&storage, "Next", true), 4);
+}
+
+#[test]
+fn test_delete_where_deletes_edges_and_keeps_committed_batches_on_error() {
+    let (_temp_dir, storage) = setup_logs();
+    let txn = storage.graph_env.write_txn().unwrap();
+
+    let mut calls = 0;
+    let result = DeleteWhere::delete_in_batches(&storage, txn, 10, |txn, arena, batch| {
+        calls += 1;
+        if calls == 3 {
+            return Err(GraphError::New("selection failed".to_string()));
+        }
+        G::new(&storage, txn, arena)
+            .e_from_type("Next")
+            .take(batch)
+            .collect::<Result<Vec<_>, _>>()
+    });
+    assert!(matches!(result, Err(GraphError::New(_))));
+    assert_eq!(count(&storage, "Next", true), 9);
+    assert_eq!(count(&storage, "Log", false), 30);
+
+    let txn = storage.graph_env.write_txn().unwrap();
+    let (deleted, txn) = DeleteWhere::delete_where(&storage, txn, |txn, arena, batch| {
+        G::new(&storage, txn, arena)
+            .e_from_type("Next")
+            .take(batch)
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(deleted, 9);
+    assert_eq!(count(&storage, "Next", true), 0);
+}
@@ -0,0 +1,205 @@
+use std::{collections::HashSet, sync::Arc};
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::{
+    helix_engine::{
+        graph::ego_cache::EgoCache,
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        traversal_core::{
+            config::{Config, EgoCacheConfig, GatewayConfig},
+            ops::{
+                g::G,
+                source::{add_e::AddEAdapter, add_n::AddNAdapter, n_from_id::NFromIdAdapter},
+                util::ego::EgoAdapter,
+            },
+            traversal_value::TraversalValue,
+        },
+    },
+    utils::label_hash::hash_label,
+};
+
+fn knows_cache(max_entries: usize) -> EgoCacheConfig {
+    EgoCacheConfig {
+        edge: "knows".to_string(),
+        max_depth: None,
+        max_entries: Some(max_entries),
+    }
+}
+
+fn setup_storage(ego_cache: Option<Vec<EgoCacheConfig>>) -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        gateway_config: Some(GatewayConfig {
+            ego_cache,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let storage = HelixGraphStorage::new(
+        temp_dir.path().to_str().unwrap(),
+        config,
+        Default::default(),
+    )
+    .unwrap();
+    (temp_dir, Arc::new(storage))
+}
+
+/// `a` knows `b` and `e`, `b` knows `c`, `c` knows `a` back, and `b` blocks `d`
+fn add_people(storage: &HelixGraphStorage) -> [u128; 5] {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let people = [(); 5].map(|_| {
+        G::new_mut(storage, &arena, &mut txn)
+            .add_n("person", None, None)
+            .collect_to_obj()
+            .unwrap()
+            .id()
+    });
+    let [a, b, c, d, e] = people;
+    for (label, from, to) in [
+        ("knows", a, b),
+        ("knows", a, e),
+        ("knows", b, c),
+        ("knows", c, a),
+        ("blocks", b, d),
+    ] {
+        G::new_mut(storage, &arena, &mut txn)
+            .add_edge(label, None, from, to, false, false)
+            .collect_to_obj()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    people
+}
+
+fn ego_ids(storage: &HelixGraphStorage, start: u128, depth: i32) -> HashSet<u128> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_id(&start)
+        .ego("knows", depth)
+        .map(|item| item.unwrap().id())
+        .collect()
+}
+
+#[test]
+fn test_ego_reaches_nodes_within_depth_along_the_edge_type() {
+    let (_temp_dir, storage) = setup_storage(None);
+    let [a, b, c, _, e] = add_people(&storage);
+
+    assert_eq!(ego_ids(&storage, a, 1), HashSet::from([b, e]));
+    // c leads back to a, which is left out, and d is only reached by a `blocks` edge
+    assert_eq!(ego_ids(&storage, a, 2), HashSet::from([b, c, e]));
+    assert_eq!(ego_ids(&storage, a, 5), HashSet::from([b, c, e]));
+    assert!(ego_ids(&storage, a, 0).is_empty());
+    assert!(ego_ids(&storage, e, 3).is_empty());
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let err = G::new(&storage, &txn, &arena)
+        .n_from_id(&a)
+        .ego("knows", -1)
+        .next()
+        .unwrap();
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_ego_returns_each_node_once_across_starts() {
+    let (_temp_dir, storage) = setup_storage(None);
+    let [a, b, c, _, e] = add_people(&storage);
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let starts =
+        [a, b].map(|id| TraversalValue::Node(storage.get_node(&txn, &id, &arena).unwrap()));
+    let ids: Vec<u128> = G::from_iter(&storage, &txn, starts.into_iter(), &arena)
+        .ego("knows", 2)
+        .map(|item| item.unwrap().id())
+        .collect();
+    // b's neighborhood c and a overlaps a's b, c and e
+    assert_eq!(ids.len(), 4);
+    assert_eq!(
+        ids.into_iter().collect::<HashSet<_>>(),
+        HashSet::from([a, b, c, e])
+    );
+}
+
+#[test]
+fn test_ego_cache_serves_repeats_until_invalidated() {
+    let (_temp_dir, storage) = setup_storage(Some(vec![knows_cache(100)]));
+    let [a, b, c, d, e] = add_people(&storage);
+    let cache = &storage.ego_cache;
+
+    assert_eq!(ego_ids(&storage, a, 2), HashSet::from([b, c, e]));
+    assert_eq!(ego_ids(&storage, a, 2), HashSet::from([b, c, e]));
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+
+    // Deeper than the cached depth walks the graph every time
+    ego_ids(&storage, a, 3);
+    assert_eq!(cache.len(), 1);
+
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_edge("knows", None, a, d, false, false)
+        .collect_to_obj()
+        .unwrap();
+    txn.commit().unwrap();
+
+    // Invalidating another edge type keeps serving the neighborhood from before the write
+    let txn_id = storage.graph_env.read_txn().unwrap().id();
+    cache.invalidate(Some(&["blocks".to_string()]), txn_id);
+    assert_eq!(ego_ids(&storage, a, 2), HashSet::from([b, c, e]));
+
+    cache.invalidate(Some(&["knows".to_string()]), txn_id);
+    assert!(cache.is_empty());
+    assert_eq!(ego_ids(&storage, a, 2), HashSet::from([b, c, d, e]));
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_ego_cache_ignores_reads_from_before_an_invalidation() {
+    let cache = EgoCache::from_config(&[knows_cache(100)]);
+    let knows = hash_label("knows", None);
+    let nodes: Arc<[u128]> = Arc::from([1, 2]);
+
+    cache.insert(&knows, 7, 2, 5, Arc::clone(&nodes));
+    assert!(cache.get(&knows, 7, 2, 5).is_some());
+
+    cache.invalidate(None, 10);
+    // A read that began before the write can neither store nor see a neighborhood
+    cache.insert(&knows, 7, 2, 9, Arc::clone(&nodes));
+    assert!(cache.is_empty());
+    cache.insert(&knows, 7, 2, 10, Arc::clone(&nodes));
+    assert!(cache.get(&knows, 7, 2, 9).is_none());
+    assert!(cache.get(&knows, 7, 2, 10).is_some());
+
+    // Only configured edge types and depths are kept
+    let blocks = hash_label("blocks", None);
+    cache.insert(&blocks, 7, 2, 10, Arc::clone(&nodes));
+    cache.insert(&knows, 7, 3, 10, nodes);
+    assert_eq!(cache.len(), 1);
+    assert!(!cache.caches(&blocks, 1));
+    assert!(!cache.caches(&knows, 3));
+}
+
+#[test]
+fn test_ego_cache_drops_the_least_recently_used() {
+    let cache = EgoCache::from_config(&[knows_cache(2)]);
+    let knows = hash_label("knows", None);
+    let nodes: Arc<[u128]> = Arc::from([1]);
+
+    cache.insert(&knows, 1, 1, 0, Arc::clone(&nodes));
+    cache.insert(&knows, 2, 1, 0, Arc::clone(&nodes));
+    assert!(cache.get(&knows, 1, 1, 0).is_some());
+    cache.insert(&knows, 3, 1, 0, nodes);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&knows, 1, 1, 0).is_some());
+    assert!(cache.get(&knows, 2, 1, 0).is_none());
+    assert!(cache.get(&knows, 3, 1, 0).is_some());
+    assert!(!EgoCache::from_config(&[]).is_enabled());
+}
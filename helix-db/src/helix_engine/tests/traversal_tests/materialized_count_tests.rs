@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{
+                    add_e::AddEAdapter, add_n::AddNAdapter, e_from_id::EFromIdAdapter,
+                    n_from_id::NFromIdAdapter,
+                },
+                util::drop::Drop,
+            },
+        },
+        types::MaterializedCount,
+    },
+    protocol::value::Value,
+};
+
+fn follows_counts() -> Vec<MaterializedCount> {
+    vec![
+        MaterializedCount {
+            node_label: "user".to_string(),
+            field: "follower_count".to_string(),
+            edge_label: "follows".to_string(),
+            incoming: true,
+        },
+        MaterializedCount {
+            node_label: "user".to_string(),
+            field: "following_count".to_string(),
+            edge_label: "follows".to_string(),
+            incoming: false,
+        },
+    ]
+}
+
+fn open(path: &str, counts: Option<Vec<MaterializedCount>>) -> Arc<HelixGraphStorage> {
+    let mut config = Config::default();
+    config.graph_config.as_mut().unwrap().materialized_counts = counts;
+    Arc::new(HelixGraphStorage::new(path, config, Default::default()).unwrap())
+}
+
+fn add_user(storage: &HelixGraphStorage) -> u128 {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let id = G::new_mut(storage, &arena, &mut txn)
+        .add_n("user", None, None)
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    txn.commit().unwrap();
+    id
+}
+
+fn follow(storage: &HelixGraphStorage, from: u128, to: u128) -> u128 {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let id = G::new_mut(storage, &arena, &mut txn)
+        .add_edge("follows", None, from, to, false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    txn.commit().unwrap();
+    id
+}
+
+fn count(storage: &HelixGraphStorage, id: u128, field: &str) -> Option<Value> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let node = storage.get_node(&txn, &id, &arena).unwrap();
+    node.get_property(field).cloned()
+}
+
+#[test]
+fn test_counts_follow_added_and_dropped_edges() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap(), Some(follows_counts()));
+    let alice = add_user(&storage);
+    let bob = add_user(&storage);
+    let carol = add_user(&storage);
+
+    let bob_follows_alice = follow(&storage, bob, alice);
+    follow(&storage, carol, alice);
+    follow(&storage, alice, bob);
+    assert_eq!(
+        count(&storage, alice, "follower_count"),
+        Some(Value::I64(2))
+    );
+    assert_eq!(
+        count(&storage, alice, "following_count"),
+        Some(Value::I64(1))
+    );
+    assert_eq!(count(&storage, bob, "follower_count"), Some(Value::I64(1)));
+    assert_eq!(
+        count(&storage, carol, "following_count"),
+        Some(Value::I64(1))
+    );
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let edge = G::new(&storage, &txn, &arena)
+        .e_from_id(&bob_follows_alice)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    drop(txn);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    Drop::drop_traversal(edge.into_iter().map(Ok), storage.as_ref(), &mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        count(&storage, alice, "follower_count"),
+        Some(Value::I64(1))
+    );
+    assert_eq!(count(&storage, bob, "following_count"), Some(Value::I64(0)));
+
+    // Dropping a node uncounts its edges from the nodes at their other end
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let node = G::new(&storage, &txn, &arena)
+        .n_from_id(&alice)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    drop(txn);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    Drop::drop_traversal(node.into_iter().map(Ok), storage.as_ref(), &mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(count(&storage, bob, "follower_count"), Some(Value::I64(0)));
+    assert_eq!(
+        count(&storage, carol, "following_count"),
+        Some(Value::I64(0))
+    );
+}
+
+#[test]
+fn test_counts_are_computed_for_nodes_written_before_them() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().to_str().unwrap();
+    let storage = open(path, None);
+    let alice = add_user(&storage);
+    let bob = add_user(&storage);
+    let carol = add_user(&storage);
+    follow(&storage, bob, alice);
+    follow(&storage, carol, alice);
+    assert_eq!(count(&storage, alice, "follower_count"), None);
+    drop(storage);
+
+    let storage = open(path, Some(follows_counts()));
+    assert_eq!(
+        count(&storage, alice, "follower_count"),
+        Some(Value::I64(2))
+    );
+    assert_eq!(
+        count(&storage, alice, "following_count"),
+        Some(Value::I64(0))
+    );
+    assert_eq!(count(&storage, bob, "following_count"), Some(Value::I64(1)));
+
+    follow(&storage, alice, carol);
+    drop(storage);
+    // Counts keep up with writes once computed
+    let storage = open(path, Some(follows_counts()));
+    assert_eq!(
+        count(&storage, carol, "follower_count"),
+        Some(Value::I64(1))
+    );
+}
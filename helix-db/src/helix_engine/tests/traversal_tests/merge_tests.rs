@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        bm25::bm25::BM25,
+        storage_core::{HelixGraphStorage, merge::ConflictPolicy, storage_methods::StorageMethods},
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                in_::in_::InAdapter,
+                out::out::OutAdapter,
+                source::{
+                    add_e::AddEAdapter, add_n::AddNAdapter, n_from_id::NFromIdAdapter,
+                    n_from_index::NFromIndexAdapter,
+                },
+                util::merge::MergeNodesAdapter,
+            },
+        },
+        types::{GraphError, SecondaryIndex},
+    },
+    props,
+    protocol::value::Value,
+    utils::items::Node,
+};
+
+fn open(path: &str) -> Arc<HelixGraphStorage> {
+    let mut config = Config::default();
+    config.graph_config.as_mut().unwrap().secondary_indices =
+        Some(vec![SecondaryIndex::Unique("email".to_string())]);
+    Arc::new(HelixGraphStorage::new(path, config, Default::default()).unwrap())
+}
+
+fn add_person<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    props: Vec<(String, Value)>,
+) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_n("person", props_option(&arena, props), Some(&["email"]))
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn knows<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>, from: u128, to: u128) {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_edge("knows", None, from, to, false, false)
+        .collect_to_obj()
+        .unwrap();
+}
+
+fn pair<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>) -> (u128, u128) {
+    (
+        add_person(storage, txn, props! { "name" => "Ada", "age" => 36 }),
+        add_person(
+            storage,
+            txn,
+            props! { "name" => "Ada L.", "city" => "London" },
+        ),
+    )
+}
+
+fn merge<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    keep: u128,
+    remove: u128,
+    conflict: ConflictPolicy,
+) -> Result<Vec<(String, Value)>, GraphError> {
+    let arena = Bump::new();
+    let merged = G::new_mut(storage, &arena, txn)
+        .merge_nodes(keep, remove, conflict)
+        .collect_to_obj()?;
+    let node: Node = storage.get_node(txn, &merged.id(), &arena)?;
+    let mut properties: Vec<(String, Value)> = node
+        .properties
+        .iter()
+        .flat_map(|properties| properties.iter())
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(properties)
+}
+
+#[test]
+fn test_merge_repoints_edges_and_drops_the_removed_node() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let keep = add_person(&storage, &mut txn, props! { "name" => "Ada" });
+    let remove = add_person(&storage, &mut txn, props! { "name" => "Ada L." });
+    let friend = add_person(&storage, &mut txn, props! { "name" => "Charles" });
+    knows(&storage, &mut txn, remove, friend);
+    knows(&storage, &mut txn, friend, remove);
+    knows(&storage, &mut txn, keep, remove);
+    merge(&storage, &mut txn, keep, remove, ConflictPolicy::Keep).unwrap();
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let mut out: Vec<u128> = G::new(&storage, &txn, &arena)
+        .n_from_id(&keep)
+        .out_node("knows")
+        .map(|node| node.unwrap().id())
+        .collect();
+    out.sort();
+    let mut expected = vec![friend, keep];
+    expected.sort();
+    assert_eq!(out, expected);
+    let into_friend: Vec<u128> = G::new(&storage, &txn, &arena)
+        .n_from_id(&friend)
+        .in_node("knows")
+        .map(|node| node.unwrap().id())
+        .collect();
+    assert_eq!(into_friend, vec![keep]);
+
+    assert!(matches!(
+        storage.get_node(&txn, &remove, &arena),
+        Err(GraphError::NodeNotFound)
+    ));
+    assert_eq!(storage.merged_into(&txn, remove).unwrap(), Some(keep));
+    assert_eq!(storage.merged_into(&txn, keep).unwrap(), None);
+}
+
+#[test]
+fn test_merge_resolves_conflicting_properties_by_policy() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let (keep, remove) = pair(&storage, &mut txn);
+    assert_eq!(
+        merge(&storage, &mut txn, keep, remove, ConflictPolicy::Keep).unwrap(),
+        props! { "age" => 36, "city" => "London", "name" => "Ada" }
+    );
+    let (keep, remove) = pair(&storage, &mut txn);
+    assert_eq!(
+        merge(&storage, &mut txn, keep, remove, ConflictPolicy::Overwrite).unwrap(),
+        props! { "age" => 36, "city" => "London", "name" => "Ada L." }
+    );
+    let (keep, remove) = pair(&storage, &mut txn);
+    let error = merge(&storage, &mut txn, keep, remove, ConflictPolicy::Fail).unwrap_err();
+    assert!(matches!(error, GraphError::MergeConflict(_)), "{error}");
+
+    assert!(merge(&storage, &mut txn, keep, keep, ConflictPolicy::Keep).is_err());
+}
+
+#[test]
+fn test_merge_updates_secondary_and_text_indices() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let keep = add_person(&storage, &mut txn, props! { "name" => "Ada" });
+    let remove = add_person(
+        &storage,
+        &mut txn,
+        props! { "name" => "Ada L.", "email" => "ada@example.com", "bio" => "analyst" },
+    );
+    merge(&storage, &mut txn, keep, remove, ConflictPolicy::Keep).unwrap();
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let by_email: Vec<u128> = G::new(&storage, &txn, &arena)
+        .n_from_index("person", "email", &"ada@example.com".to_string())
+        .map(|node| node.unwrap().id())
+        .collect();
+    assert_eq!(by_email, vec![keep]);
+
+    let bm25 = storage.bm25.as_ref().unwrap();
+    let hits = bm25.search(&txn, "analyst", 10, &arena).unwrap();
+    assert_eq!(
+        hits.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![keep]
+    );
+}
@@ -1,14 +1,28 @@
+pub mod acyclic_tests;
+pub mod bucket_by_tests;
+pub mod constraint_tests;
 pub mod count_tests;
+pub mod degree_tests;
+pub mod delete_where_tests;
 pub mod drop_tests;
+pub mod ego_tests;
 pub mod edge_traversal_tests;
 pub mod filter_tests;
+pub mod materialized_count_tests;
+pub mod merge_tests;
 pub mod node_traversal_tests;
 pub mod ppr_tests;
 pub mod range_tests;
+pub mod sample_tests;
 pub mod secondary_index_tests;
 pub mod shortest_path_tests;
+pub mod stats_tests;
+pub mod subgraph_tests;
 pub mod test_utils;
+pub mod trigger_tests;
 pub mod update_tests;
 pub mod upsert_tests;
 pub mod util_tests;
 pub mod vector_traversal_tests;
+pub mod view_tests;
+pub mod walk_paths_tests;
@@ -0,0 +1,102 @@
+use std::{collections::HashSet, sync::Arc};
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::helix_engine::{
+    storage_core::HelixGraphStorage,
+    traversal_core::{
+        config::Config,
+        ops::{
+            g::G,
+            source::{add_n::AddNAdapter, n_from_type::NFromTypeAdapter},
+            util::sample::SampleAdapter,
+        },
+    },
+};
+
+fn setup_people(count: usize) -> (TempDir, Arc<HelixGraphStorage>, Vec<u128>) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = Arc::new(
+        HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            Config::default(),
+            Default::default(),
+        )
+        .unwrap(),
+    );
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let ids = (0..count)
+        .map(|_| {
+            G::new_mut(&storage, &arena, &mut txn)
+                .add_n("person", None, None)
+                .collect_to_obj()
+                .unwrap()
+                .id()
+        })
+        .collect();
+    txn.commit().unwrap();
+    (temp_dir, storage, ids)
+}
+
+fn sample_ids(storage: &HelixGraphStorage, n: usize) -> Vec<u128> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_type("person")
+        .sample(n)
+        .map(|item| item.unwrap().id())
+        .collect()
+}
+
+#[test]
+fn test_sample_keeps_n_distinct_items_in_traversal_order() {
+    let (_temp_dir, storage, ids) = setup_people(50);
+    let all = sample_ids(&storage, 50);
+    assert_eq!(all.len(), 50);
+
+    let sampled = sample_ids(&storage, 10);
+    assert_eq!(sampled.len(), 10);
+    assert_eq!(sampled.iter().collect::<HashSet<_>>().len(), 10);
+    assert!(sampled.iter().all(|id| ids.contains(id)));
+    // Kept in the order the traversal returned them
+    let positions = sampled
+        .iter()
+        .map(|id| all.iter().position(|other| other == id).unwrap())
+        .collect::<Vec<_>>();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    assert_eq!(sample_ids(&storage, 100).len(), 50);
+    assert!(sample_ids(&storage, 0).is_empty());
+}
+
+#[test]
+fn test_sample_reaches_every_item() {
+    let (_temp_dir, storage, ids) = setup_people(20);
+    // Each item has a 1 in 4 chance per draw, so 200 draws miss one with negligible odds
+    let mut seen = HashSet::new();
+    for _ in 0..200 {
+        seen.extend(sample_ids(&storage, 5));
+    }
+    assert_eq!(seen.len(), ids.len());
+}
+
+#[test]
+fn test_sample_fraction_keeps_about_that_share() {
+    let (_temp_dir, storage, _) = setup_people(1000);
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let count = |fraction: f64| {
+        G::new(&storage, &txn, &arena)
+            .n_from_type("person")
+            .sample_fraction(fraction)
+            .count()
+    };
+
+    assert_eq!(count(0.0), 0);
+    assert_eq!(count(1.0), 1000);
+    // Binomial(1000, 0.1) stays within 50..150 with overwhelming probability
+    let kept = count(0.1);
+    assert!((50..150).contains(&kept), "kept {kept}");
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{add_n::AddNAdapter, n_from_type::NFromTypeAdapter},
+                util::stats::StatsAdapter,
+            },
+        },
+    },
+    props,
+    protocol::value::Value,
+    utils::stats::{EXACT_LIMIT, TDigest, Variance},
+};
+
+/// Requests with latencies 1 to 100 as integers, one with a float, one with a string and one
+/// without a latency
+fn setup_requests() -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = Arc::new(
+        HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            Config::default(),
+            Default::default(),
+        )
+        .unwrap(),
+    );
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let latencies = (1..=99)
+        .map(Value::I64)
+        .chain([Value::F64(100.0), Value::String("slow".to_string())]);
+    for latency in latencies {
+        G::new_mut(&storage, &arena, &mut txn)
+            .add_n(
+                "request",
+                props_option(&arena, props!("latency" => latency)),
+                None,
+            )
+            .collect_to_obj()
+            .unwrap();
+    }
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_n("request", None, None)
+        .collect_to_obj()
+        .unwrap();
+    txn.commit().unwrap();
+    (temp_dir, storage)
+}
+
+fn percentile(storage: &HelixGraphStorage, label: &str, q: f64) -> Value {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_type(label)
+        .percentile("latency", q)
+        .unwrap()
+}
+
+#[test]
+fn test_percentiles_are_exact_for_small_sets() {
+    let (_temp_dir, storage) = setup_requests();
+
+    // Only the 100 numeric latencies count, interpolating between neighbours
+    assert_eq!(percentile(&storage, "request", 0.5), Value::F64(50.5));
+    assert_eq!(percentile(&storage, "request", 0.0), Value::F64(1.0));
+    assert_eq!(percentile(&storage, "request", 1.0), Value::F64(100.0));
+    let Value::F64(p99) = percentile(&storage, "request", 0.99) else {
+        panic!("expected a float");
+    };
+    assert!((p99 - 99.01).abs() < 1e-9, "{p99}");
+
+    assert_eq!(percentile(&storage, "missing", 0.5), Value::Empty);
+}
+
+#[test]
+fn test_stddev_is_the_sample_standard_deviation() {
+    let (_temp_dir, storage) = setup_requests();
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+
+    let Value::F64(stddev) = G::new(&storage, &txn, &arena)
+        .n_from_type("request")
+        .stddev("latency")
+        .unwrap()
+    else {
+        panic!("expected a float");
+    };
+    // The sample variance of 1..=100 is 100 * 101 / 12
+    assert!((stddev - (100.0_f64 * 101.0 / 12.0).sqrt()).abs() < 1e-9);
+
+    let mut single = Variance::default();
+    single.add(4.0);
+    assert_eq!(single.stddev(), None);
+    single.add(f64::NAN);
+    single.add(6.0);
+    assert_eq!(single.stddev(), Some(2.0_f64.sqrt()));
+}
+
+#[test]
+fn test_tdigest_estimates_quantiles_of_large_sets() {
+    let mut digest = TDigest::default();
+    assert_eq!(digest.quantile(0.5), None);
+
+    // A shuffled 0..100_000, so values don't arrive in order
+    let n = 100_000_u64;
+    for i in 0..n {
+        digest.add((i * 7_919 % n) as f64);
+    }
+    assert!(!digest.is_exact());
+    assert_eq!(digest.count(), n as usize);
+
+    for (q, tolerance) in [(0.5, 0.01), (0.95, 0.005), (0.99, 0.002), (0.999, 0.0005)] {
+        let estimate = digest.quantile(q).unwrap();
+        let expected = q * (n - 1) as f64;
+        assert!(
+            (estimate - expected).abs() <= tolerance * n as f64,
+            "q {q}: {estimate} vs {expected}"
+        );
+    }
+    assert_eq!(digest.quantile(0.0), Some(0.0));
+    assert_eq!(digest.quantile(1.0), Some((n - 1) as f64));
+
+    let mut small = TDigest::default();
+    for i in 0..EXACT_LIMIT - 1 {
+        small.add(i as f64);
+    }
+    assert!(small.is_exact());
+    assert_eq!(small.quantile(0.5), Some((EXACT_LIMIT - 2) as f64 / 2.0));
+}
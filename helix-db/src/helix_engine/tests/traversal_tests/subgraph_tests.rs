@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                source::{add_e::AddEAdapter, add_n::AddNAdapter, n_from_id::NFromIdAdapter},
+                util::subgraph::SubgraphAdapter,
+            },
+            traversal_value::TraversalValue,
+        },
+    },
+    utils::id::ID,
+};
+
+fn open(path: &str) -> Arc<HelixGraphStorage> {
+    Arc::new(HelixGraphStorage::new(path, Config::default(), Default::default()).unwrap())
+}
+
+fn add_person<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_n("person", None, None)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn knows<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>, from: u128, to: u128) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_edge("knows", None, from, to, false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+/// The ids of the nodes and edges of the subgraph `depth` edges around `from`, sorted
+fn subgraph(storage: &HelixGraphStorage, from: &[u128], depth: usize) -> (Vec<u128>, Vec<u128>) {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let seeds = from.iter().map(|id| {
+        G::new(storage, &txn, &arena)
+            .n_from_id(id)
+            .collect_to_obj()
+            .unwrap()
+    });
+    let subgraph = G::from_iter(storage, &txn, seeds.collect::<Vec<_>>().into_iter(), &arena)
+        .subgraph(depth)
+        .collect_to_obj()
+        .unwrap();
+    let TraversalValue::Subgraph { nodes, edges } = subgraph else {
+        panic!("expected a subgraph, got {subgraph:?}");
+    };
+    let mut nodes: Vec<u128> = nodes.iter().map(|node| node.id).collect();
+    let mut edges: Vec<u128> = edges.iter().map(|edge| edge.id).collect();
+    nodes.sort();
+    edges.sort();
+    (nodes, edges)
+}
+
+fn sorted(mut ids: Vec<u128>) -> Vec<u128> {
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_subgraph_follows_edges_both_ways_up_to_depth() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    // a -> b -> c -> d, and e -> a
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let [a, b, c, d, e] = std::array::from_fn(|_| add_person(&storage, &mut txn));
+    let ab = knows(&storage, &mut txn, a, b);
+    let bc = knows(&storage, &mut txn, b, c);
+    let cd = knows(&storage, &mut txn, c, d);
+    let ea = knows(&storage, &mut txn, e, a);
+    txn.commit().unwrap();
+
+    assert_eq!(subgraph(&storage, &[b], 0), (vec![b], vec![]));
+    assert_eq!(
+        subgraph(&storage, &[b], 1),
+        (sorted(vec![a, b, c]), sorted(vec![ab, bc]))
+    );
+    assert_eq!(
+        subgraph(&storage, &[b], 2),
+        (sorted(vec![a, b, c, d, e]), sorted(vec![ab, bc, cd, ea]))
+    );
+    // The subgraphs around several nodes are merged into one
+    assert_eq!(
+        subgraph(&storage, &[d, e], 1),
+        (sorted(vec![a, c, d, e]), sorted(vec![cd, ea]))
+    );
+}
+
+#[test]
+fn test_subgraph_includes_edges_between_the_outermost_nodes() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    // a -> b, a -> c, b -> c
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let [a, b, c] = std::array::from_fn(|_| add_person(&storage, &mut txn));
+    let ab = knows(&storage, &mut txn, a, b);
+    let ac = knows(&storage, &mut txn, a, c);
+    let bc = knows(&storage, &mut txn, b, c);
+    txn.commit().unwrap();
+
+    assert_eq!(
+        subgraph(&storage, &[a], 1),
+        (sorted(vec![a, b, c]), sorted(vec![ab, ac, bc]))
+    );
+}
+
+#[test]
+fn test_subgraph_serializes_as_nodes_and_edges() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = add_person(&storage, &mut txn);
+    let b = add_person(&storage, &mut txn);
+    let ab = knows(&storage, &mut txn, a, b);
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let subgraph = G::new(&storage, &txn, &arena)
+        .n_from_id(&a)
+        .subgraph(1)
+        .collect_to_obj()
+        .unwrap();
+    let json = sonic_rs::to_value(&subgraph).unwrap();
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(
+        nodes[0]["id"].as_str(),
+        Some(ID::from(a).stringify().as_str())
+    );
+    assert_eq!(nodes[0]["label"].as_str(), Some("person"));
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(
+        edges[0]["id"].as_str(),
+        Some(ID::from(ab).stringify().as_str())
+    );
+    assert_eq!(edges[0]["label"].as_str(), Some("knows"));
+    assert_eq!(
+        edges[0]["from_node"].as_str(),
+        Some(ID::from(a).stringify().as_str())
+    );
+    assert_eq!(
+        edges[0]["to_node"].as_str(),
+        Some(ID::from(b).stringify().as_str())
+    );
+}
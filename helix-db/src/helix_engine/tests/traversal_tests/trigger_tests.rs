@@ -0,0 +1,249 @@
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Arc,
+};
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::{
+            HelixGraphStorage,
+            triggers::{MAX_TRIGGER_DEPTH, Trigger, TriggerEvent, TriggerSubmission},
+        },
+        traversal_core::{
+            ops::{
+                g::G,
+                out::out::OutAdapter,
+                source::{
+                    add_e::AddEAdapter, add_n::AddNAdapter, e_from_id::EFromIdAdapter,
+                    n_from_id::NFromIdAdapter, n_from_type::NFromTypeAdapter,
+                },
+                util::upsert::UpsertAdapter,
+            },
+            traversal_value::TraversalValue,
+        },
+        types::GraphError,
+    },
+    props,
+    protocol::value::Value,
+};
+
+// Written the way the compiler generates triggers, so the generated form keeps compiling
+
+/// `ON AddN<trigger_order> AS order DO { audit <- AddN<trigger_audit> AddE<audited>::From(order)::To(audit) }`
+fn trigger_add_n_order<'db>(
+    db: &'db HelixGraphStorage,
+    mut txn: &mut RwTxn<'db>,
+    id: u128,
+) -> Result<(), GraphError> {
+    let arena = Bump::new();
+    let order = G::new(&db, &txn, &arena).n_from_id(&id).collect_to_obj()?;
+    let audit = G::new_mut(&db, &arena, &mut txn)
+        .add_n("trigger_audit", None, None)
+        .collect_to_obj()?;
+    G::new_mut(&db, &arena, &mut txn)
+        .add_edge("audited", None, order.id(), audit.id(), false, false)
+        .collect_to_obj()?;
+    Ok(())
+}
+
+/// `ON AddE<trigger_follows> AS follows DO { AddN<trigger_audit>({note: follows::{note}}) }`
+fn trigger_add_e_follows<'db>(
+    db: &'db HelixGraphStorage,
+    mut txn: &mut RwTxn<'db>,
+    id: u128,
+) -> Result<(), GraphError> {
+    let arena = Bump::new();
+    let follows = G::new(&db, &txn, &arena).e_from_id(&id).collect_to_obj()?;
+    let note = follows.get_property("note").cloned().unwrap_or_default();
+    G::new_mut(&db, &arena, &mut txn)
+        .add_n(
+            "trigger_audit",
+            props_option(&arena, props! { "note" => note }),
+            None,
+        )
+        .collect_to_obj()?;
+    Ok(())
+}
+
+/// `ON AddN<trigger_loop> DO { AddN<trigger_loop> }`, which the analyzer would reject
+fn trigger_add_n_loop<'db>(
+    db: &'db HelixGraphStorage,
+    mut txn: &mut RwTxn<'db>,
+    _id: u128,
+) -> Result<(), GraphError> {
+    let arena = Bump::new();
+    G::new_mut(&db, &arena, &mut txn)
+        .add_n("trigger_loop", None, None)
+        .collect_to_obj()?;
+    Ok(())
+}
+
+/// A trigger whose generated code panics, e.g. on a bug in an expression it evaluates
+fn trigger_add_n_panic<'db>(
+    _db: &'db HelixGraphStorage,
+    _txn: &mut RwTxn<'db>,
+    _id: u128,
+) -> Result<(), GraphError> {
+    panic!("trigger_panic")
+}
+
+inventory::submit! {
+    TriggerSubmission(Trigger::new(TriggerEvent::AddN, "trigger_order", trigger_add_n_order))
+}
+inventory::submit! {
+    TriggerSubmission(Trigger::new(TriggerEvent::AddN, "trigger_panic", trigger_add_n_panic))
+}
+inventory::submit! {
+    TriggerSubmission(Trigger::new(TriggerEvent::AddE, "trigger_follows", trigger_add_e_follows))
+}
+inventory::submit! {
+    TriggerSubmission(Trigger::new(TriggerEvent::AddN, "trigger_loop", trigger_add_n_loop))
+}
+
+fn setup_test_db() -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+    let storage = HelixGraphStorage::new(
+        db_path,
+        crate::helix_engine::traversal_core::config::Config::default(),
+        Default::default(),
+    )
+    .unwrap();
+    (temp_dir, Arc::new(storage))
+}
+
+fn audits(storage: &HelixGraphStorage) -> usize {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    G::new(storage, &txn, &arena)
+        .n_from_type("trigger_audit")
+        .count()
+}
+
+#[test]
+fn test_add_n_runs_trigger_in_the_same_transaction() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let order = G::new_mut(&storage, &arena, &mut txn)
+        .add_n("trigger_order", None, None)
+        .collect_to_obj()
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = storage.graph_env.read_txn().unwrap();
+    let audited = G::new(&storage, &txn, &arena)
+        .n_from_id(&order.id())
+        .out_node("audited")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(audited.len(), 1);
+    assert_eq!(audited[0].label(), "trigger_audit");
+}
+
+#[test]
+fn test_aborted_write_discards_trigger_writes() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_n("trigger_order", None, None)
+        .collect_to_obj()
+        .unwrap();
+    txn.abort();
+
+    assert_eq!(audits(&storage), 0);
+}
+
+#[test]
+fn test_add_edge_and_upsert_run_triggers() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = G::new_mut(&storage, &arena, &mut txn)
+        .add_n("person", None, None)
+        .collect_to_obj()
+        .unwrap();
+    let b = G::new_mut(&storage, &arena, &mut txn)
+        .add_n("person", None, None)
+        .collect_to_obj()
+        .unwrap();
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_edge(
+            "trigger_follows",
+            props_option(&arena, props! { "note" => "hello" }),
+            a.id(),
+            b.id(),
+            false,
+            false,
+        )
+        .collect_to_obj()
+        .unwrap();
+    // Upserting creates the order, since none exists yet
+    G::new_mut_from_iter(
+        &storage,
+        &mut txn,
+        std::iter::empty::<TraversalValue>(),
+        &arena,
+    )
+    .upsert_n("trigger_order", &[])
+    .collect_to_obj()
+    .unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(audits(&storage), 2);
+    let txn = storage.graph_env.read_txn().unwrap();
+    let notes = G::new(&storage, &txn, &arena)
+        .n_from_type("trigger_audit")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .iter()
+        .filter_map(|audit| audit.get_property("note").cloned())
+        .collect::<Vec<_>>();
+    assert_eq!(notes, [Value::from("hello")]);
+}
+
+#[test]
+fn test_triggers_running_each_other_fail_the_write() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let result = G::new_mut(&storage, &arena, &mut txn)
+        .add_n("trigger_loop", None, None)
+        .collect_to_obj();
+
+    assert!(matches!(
+        result,
+        Err(GraphError::New(message)) if message.contains("nested more than")
+    ));
+}
+
+#[test]
+fn test_panicking_trigger_does_not_leave_depth_raised() {
+    let (_temp_dir, storage) = setup_test_db();
+    // More panics than the nesting limit, all on this thread
+    for _ in 0..=MAX_TRIGGER_DEPTH {
+        let panicked = catch_unwind(AssertUnwindSafe(|| {
+            let arena = Bump::new();
+            let mut txn = storage.graph_env.write_txn().unwrap();
+            let _ = G::new_mut(&storage, &arena, &mut txn)
+                .add_n("trigger_panic", None, None)
+                .collect_to_obj();
+        }));
+        assert!(panicked.is_err());
+    }
+
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    G::new_mut(&storage, &arena, &mut txn)
+        .add_n("trigger_order", None, None)
+        .collect_to_obj()
+        .unwrap();
+    txn.commit().unwrap();
+    assert_eq!(audits(&storage), 1);
+}
@@ -994,6 +994,127 @@ fn test_upsert_v_new_vector_is_searchable() {
     );
 }
 
+// ============================================================================
+// Embedded Vector Upsert Tests (upsert_v_embedded)
+// ============================================================================
+
+#[test]
+fn test_upsert_v_embedded_skips_unchanged_content() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+
+    let created = G::new_mut_from_iter(
+        &storage,
+        &mut txn,
+        std::iter::empty::<TraversalValue>(),
+        &arena,
+    )
+    .upsert_v_embedded(
+        &[0.1, 0.2, 0.3],
+        "hello world",
+        "doc",
+        &[("content", Value::from("hello world"))],
+    )
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    assert!(created[0].reembedded());
+
+    // Same content: the stored embedding is kept even if a different one is passed
+    let result = G::new_mut_from_iter(&storage, &mut txn, created.into_iter(), &arena)
+        .upsert_v_embedded(
+            &[0.9, 0.8, 0.7],
+            "hello world",
+            "doc",
+            &[("views", Value::from(1))],
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(result.len(), 1);
+    if let TraversalValue::Vector(vector) = &result[0] {
+        assert!(!vector.reembedded);
+        assert_eq!(vector.data, &[0.1, 0.2, 0.3]);
+        assert_eq!(vector.get_property("views").unwrap(), &Value::from(1));
+        assert_eq!(
+            vector.get_property("content").unwrap(),
+            &Value::from("hello world")
+        );
+    } else {
+        panic!("Expected vector");
+    }
+    txn.commit().unwrap();
+
+    let txn = storage.graph_env.read_txn().unwrap();
+    let stored = storage
+        .vectors
+        .get_full_vector(&txn, result[0].id(), &arena)
+        .unwrap();
+    assert_eq!(stored.data, &[0.1, 0.2, 0.3]);
+}
+
+#[test]
+fn test_upsert_v_embedded_reembeds_changed_content() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+
+    let created = G::new_mut_from_iter(
+        &storage,
+        &mut txn,
+        std::iter::empty::<TraversalValue>(),
+        &arena,
+    )
+    .upsert_v_embedded(&[0.1, 0.2, 0.3], "first draft", "doc", &[])
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let id = created[0].id();
+
+    let result = G::new_mut_from_iter(&storage, &mut txn, created.into_iter(), &arena)
+        .upsert_v_embedded(&[0.3, 0.2, 0.1], "second draft", "doc", &[])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(result[0].id(), id);
+    assert!(result[0].reembedded());
+    assert_eq!(result[0].data(), &[0.3, 0.2, 0.1]);
+    txn.commit().unwrap();
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let search_results = G::new(&storage, &txn, &arena)
+        .search_v::<Filter, _>(&[0.3, 0.2, 0.1], 1, "doc", None)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(search_results[0].id(), id);
+    assert_eq!(search_results[0].data(), &[0.3, 0.2, 0.1]);
+}
+
+#[test]
+fn test_stored_embedding_found_by_content_and_type() {
+    let (_temp_dir, storage) = setup_test_db();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+
+    G::new_mut_from_iter(
+        &storage,
+        &mut txn,
+        std::iter::empty::<TraversalValue>(),
+        &arena,
+    )
+    .upsert_v_embedded(&[0.4, 0.5, 0.6], "shared text", "doc", &[])
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(
+        storage.stored_embedding("doc", "shared text"),
+        Some(vec![0.4, 0.5, 0.6])
+    );
+    assert_eq!(storage.stored_embedding("doc", "other text"), None);
+    assert_eq!(storage.stored_embedding("note", "shared text"), None);
+}
+
 // ============================================================================
 // Regression Tests - Property Update and Revert
 // ============================================================================
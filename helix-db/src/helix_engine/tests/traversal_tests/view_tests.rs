@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RoTxn;
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::{
+            HelixGraphStorage,
+            views::{View, ViewSubmission},
+        },
+        traversal_core::{
+            ops::{
+                g::G,
+                source::{
+                    add_n::AddNAdapter, n_from_id::NFromIdAdapter, n_from_type::NFromTypeAdapter,
+                    n_from_view::NFromViewAdapter,
+                },
+                util::drop::Drop,
+            },
+            traversal_value::TraversalValue,
+        },
+        types::GraphError,
+    },
+    props,
+};
+
+/// Posts in reverse insertion order, so reads show the view's order rather than the label's
+fn latest_posts<'db>(
+    db: &'db HelixGraphStorage,
+    txn: &RoTxn<'db>,
+) -> Result<Vec<u128>, GraphError> {
+    let arena = Bump::new();
+    let posts = G::new(db, txn, &arena)
+        .n_from_type("post")
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(posts.iter().rev().map(|post| post.id()).collect())
+}
+
+static LATEST_POSTS: View = View::new("LatestPosts", latest_posts).with_labels(&["post"]);
+
+inventory::submit! {
+    ViewSubmission(View::new("LatestPostsInline", latest_posts))
+}
+
+fn setup_test_db() -> (TempDir, Arc<HelixGraphStorage>) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+    let storage = HelixGraphStorage::new(
+        db_path,
+        crate::helix_engine::traversal_core::config::Config::default(),
+        Default::default(),
+    )
+    .unwrap();
+    (temp_dir, Arc::new(storage))
+}
+
+fn add_posts(storage: &HelixGraphStorage, titles: &[&str]) -> Vec<u128> {
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let ids = titles
+        .iter()
+        .map(|title| {
+            G::new_mut(storage, &arena, &mut txn)
+                .add_n(
+                    "post",
+                    props_option(&arena, props! { "title" => *title }),
+                    None,
+                )
+                .collect_to_obj()
+                .unwrap()
+                .id()
+        })
+        .collect();
+    txn.commit().unwrap();
+    ids
+}
+
+fn read_view(storage: &HelixGraphStorage, name: &str) -> Result<Vec<u128>, GraphError> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let nodes = G::new(storage, &txn, &arena)
+        .n_from_view(name)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(nodes.iter().map(|node| node.id()).collect())
+}
+
+#[test]
+fn test_view_reads_nodes_in_refreshed_order() {
+    let (_temp_dir, storage) = setup_test_db();
+    let ids = add_posts(&storage, &["first", "second", "third"]);
+
+    assert_eq!(storage.refresh_view(&LATEST_POSTS).unwrap(), 3);
+    let read = read_view(&storage, "LatestPosts").unwrap();
+    assert_eq!(read, ids.iter().rev().copied().collect::<Vec<_>>());
+
+    // Nodes added since the last refresh are not in the view until it refreshes again
+    let added = add_posts(&storage, &["fourth"]);
+    assert_eq!(read_view(&storage, "LatestPosts").unwrap().len(), 3);
+    storage.refresh_view(&LATEST_POSTS).unwrap();
+    assert_eq!(read_view(&storage, "LatestPosts").unwrap()[0], added[0]);
+}
+
+#[test]
+fn test_view_skips_dropped_nodes() {
+    let (_temp_dir, storage) = setup_test_db();
+    let ids = add_posts(&storage, &["first", "second", "third"]);
+    storage.refresh_view(&LATEST_POSTS).unwrap();
+
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let second = G::new(&storage, &txn, &arena)
+        .n_from_id(&ids[1])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    drop(txn);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    Drop::drop_traversal(
+        second.into_iter().map(Ok::<TraversalValue, GraphError>),
+        storage.as_ref(),
+        &mut txn,
+    )
+    .unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(
+        read_view(&storage, "LatestPosts").unwrap(),
+        [ids[2], ids[0]]
+    );
+}
+
+#[test]
+fn test_view_never_refreshed_runs_its_traversal() {
+    let (_temp_dir, storage) = setup_test_db();
+    let ids = add_posts(&storage, &["first", "second"]);
+
+    assert_eq!(
+        read_view(&storage, "LatestPostsInline").unwrap(),
+        [ids[1], ids[0]]
+    );
+
+    let txn = storage.graph_env.read_txn().unwrap();
+    assert!(
+        storage
+            .view_ids(&txn, "LatestPostsInline")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_undeclared_view_errors() {
+    let (_temp_dir, storage) = setup_test_db();
+    add_posts(&storage, &["first"]);
+
+    assert!(matches!(
+        read_view(&storage, "Missing"),
+        Err(GraphError::New(message)) if message.contains("Missing")
+    ));
+}
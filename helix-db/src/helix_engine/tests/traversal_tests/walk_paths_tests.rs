@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use heed3::RwTxn;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+use super::test_utils::props_option;
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage,
+        traversal_core::{
+            config::Config,
+            ops::{
+                g::G,
+                out::out::OutAdapter,
+                source::{add_e::AddEAdapter, add_n::AddNAdapter, n_from_id::NFromIdAdapter},
+                util::walk_paths::{PathHop, PathsAdapter},
+            },
+            traversal_value::TraversalValue,
+        },
+    },
+    props,
+    protocol::value::{Path, Value},
+    utils::id::ID,
+};
+
+fn open(path: &str) -> Arc<HelixGraphStorage> {
+    Arc::new(HelixGraphStorage::new(path, Config::default(), Default::default()).unwrap())
+}
+
+fn add_person<'db>(storage: &'db HelixGraphStorage, txn: &mut RwTxn<'db>, name: &str) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_n(
+            "person",
+            props_option(&arena, props! { "name" => name }),
+            None,
+        )
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn follows<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    from: u128,
+    to: u128,
+) -> u128 {
+    let arena = Bump::new();
+    G::new_mut(storage, &arena, txn)
+        .add_edge("follows", None, from, to, false, false)
+        .collect_to_obj()
+        .unwrap()
+        .id()
+}
+
+fn paths(storage: &HelixGraphStorage, from: u128, hops: &[PathHop]) -> Vec<Path> {
+    let arena = Bump::new();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let mut paths: Vec<Path> = G::new(storage, &txn, &arena)
+        .n_from_id(&from)
+        .paths(hops)
+        .map(|item| match item.unwrap() {
+            TraversalValue::Value(Value::Path(path)) => path,
+            other => panic!("expected a path, got {other:?}"),
+        })
+        .collect();
+    paths.sort_by_key(|path| path.nodes.iter().map(|node| node.id).collect::<Vec<_>>());
+    paths
+}
+
+fn node_ids(path: &Path) -> Vec<u128> {
+    path.nodes.iter().map(|node| node.id).collect()
+}
+
+fn edge_ids(path: &Path) -> Vec<u128> {
+    path.edges.iter().map(|edge| edge.id).collect()
+}
+
+#[test]
+fn test_paths_follow_out_hops_to_each_node_the_traversal_reaches() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    // a -> b -> c, a -> d -> c and e -> a
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let [a, b, c, d, e] =
+        ["a", "b", "c", "d", "e"].map(|name| add_person(&storage, &mut txn, name));
+    let ab = follows(&storage, &mut txn, a, b);
+    let bc = follows(&storage, &mut txn, b, c);
+    let ad = follows(&storage, &mut txn, a, d);
+    let dc = follows(&storage, &mut txn, d, c);
+    follows(&storage, &mut txn, e, a);
+    txn.commit().unwrap();
+
+    let found = paths(
+        &storage,
+        a,
+        &[PathHop::Out("follows"), PathHop::Out("follows")],
+    );
+    let mut expected = vec![(vec![a, b, c], vec![ab, bc]), (vec![a, d, c], vec![ad, dc])];
+    expected.sort();
+    let mut walked = found
+        .iter()
+        .map(|path| (node_ids(path), edge_ids(path)))
+        .collect::<Vec<_>>();
+    walked.sort();
+    assert_eq!(walked, expected);
+    assert_eq!(
+        found[0].nodes[0].properties.get("name"),
+        Some(&Value::String("a".to_string()))
+    );
+
+    // One path per node the same steps without ::PATHS reach
+    let reached = {
+        let arena = Bump::new();
+        let txn = storage.graph_env.read_txn().unwrap();
+        G::new(&storage, &txn, &arena)
+            .n_from_id(&a)
+            .out_node("follows")
+            .out_node("follows")
+            .count()
+    };
+    assert_eq!(found.len(), reached);
+
+    assert!(paths(&storage, c, &[PathHop::Out("follows")]).is_empty());
+    let empty = paths(&storage, c, &[]);
+    assert_eq!(empty.len(), 1);
+    assert_eq!(node_ids(&empty[0]), vec![c]);
+}
+
+#[test]
+fn test_paths_follow_in_hops_against_the_edges() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let [a, b, c] = ["a", "b", "c"].map(|name| add_person(&storage, &mut txn, name));
+    let ab = follows(&storage, &mut txn, a, b);
+    let cb = follows(&storage, &mut txn, c, b);
+    txn.commit().unwrap();
+
+    // a follows b, who c also follows
+    let found = paths(
+        &storage,
+        a,
+        &[PathHop::Out("follows"), PathHop::In("follows")],
+    );
+    let walked = found.iter().map(node_ids).collect::<Vec<_>>();
+    let mut expected = vec![vec![a, b, a], vec![a, b, c]];
+    expected.sort();
+    let mut sorted = walked.clone();
+    sorted.sort();
+    assert_eq!(sorted, expected);
+
+    let to_c = found.iter().find(|path| path.nodes[2].id == c).unwrap();
+    assert_eq!(edge_ids(to_c), vec![ab, cb]);
+    // The edge walked backwards keeps its own direction
+    assert_eq!((to_c.edges[1].from_node, to_c.edges[1].to_node), (c, b));
+}
+
+#[test]
+fn test_path_serializes_with_uuid_ids() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = open(temp_dir.path().to_str().unwrap());
+
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let a = add_person(&storage, &mut txn, "a");
+    let b = add_person(&storage, &mut txn, "b");
+    let ab = follows(&storage, &mut txn, a, b);
+    txn.commit().unwrap();
+
+    let path = paths(&storage, a, &[PathHop::Out("follows")]).remove(0);
+    let json = sonic_rs::to_value(&Value::Path(path.clone())).unwrap();
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(
+        nodes[0]["id"].as_str(),
+        Some(ID::from(a).stringify().as_str())
+    );
+    assert_eq!(nodes[1]["name"].as_str(), Some("b"));
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(
+        edges[0]["id"].as_str(),
+        Some(ID::from(ab).stringify().as_str())
+    );
+    assert_eq!(edges[0]["label"].as_str(), Some("follows"));
+    assert_eq!(
+        edges[0]["to_node"].as_str(),
+        Some(ID::from(b).stringify().as_str())
+    );
+
+    let bytes = bincode::serialize(&Value::Path(path.clone())).unwrap();
+    let decoded: Value = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, Value::Path(path));
+}
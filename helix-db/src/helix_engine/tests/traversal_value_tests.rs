@@ -1,6 +1,7 @@
 //! Unit tests for TraversalValue enum and its methods.
 
-use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::reranker::reranker::update_score;
+use crate::helix_engine::traversal_core::traversal_value::{ScoreBreakdown, TraversalValue};
 use crate::helix_engine::vector_core::vector::HVector;
 use crate::helix_engine::vector_core::vector_without_data::VectorWithoutData;
 use crate::protocol::value::Value;
@@ -94,6 +95,8 @@ fn create_test_vector<'a>(arena: &'a Bump, id: u128, label: &str, data: &[f64])
         deleted: false,
         level: 0,
         distance: Some(0.5),
+        search_distance: None,
+        reembedded: false,
         data: arena.alloc_slice_copy(data),
         properties: None,
     }
@@ -120,6 +123,8 @@ fn create_test_vector_with_props<'a>(
         deleted: false,
         level: 0,
         distance: Some(0.5),
+        search_distance: None,
+        reembedded: false,
         data: arena.alloc_slice_copy(data),
         properties: Some(properties),
     }
@@ -209,7 +214,11 @@ fn test_id_vector_without_data_returns_correct_id() {
 fn test_id_node_with_score_returns_correct_id() {
     let arena = Bump::new();
     let node = create_test_node(&arena, 33333, "TestNode");
-    let tv = TraversalValue::NodeWithScore { node, score: 0.95 };
+    let tv = TraversalValue::NodeWithScore {
+        node,
+        score: 0.95,
+        search_score: None,
+    };
     assert_eq!(tv.id(), 33333);
 }
 
@@ -271,7 +280,11 @@ fn test_label_vector_without_data_returns_correct_label() {
 fn test_label_node_with_score_returns_correct_label() {
     let arena = Bump::new();
     let node = create_test_node(&arena, 1, "Document");
-    let tv = TraversalValue::NodeWithScore { node, score: 0.9 };
+    let tv = TraversalValue::NodeWithScore {
+        node,
+        score: 0.9,
+        search_score: None,
+    };
     assert_eq!(tv.label(), "Document");
 }
 
@@ -389,6 +402,7 @@ fn test_score_node_with_score_returns_stored_score() {
     let tv = TraversalValue::NodeWithScore {
         node,
         score: 0.123456,
+        search_score: None,
     };
     assert!((tv.score() - 0.123456).abs() < f64::EPSILON);
 }
@@ -402,6 +416,69 @@ fn test_score_node_panics() {
     let _ = tv.score();
 }
 
+// ============================================================================
+// Score Breakdown Tests
+// ============================================================================
+
+#[test]
+fn test_score_breakdown_of_vector_search_result() {
+    let arena = Bump::new();
+    let mut vector = create_test_vector(&arena, 1, "Vec", &[1.0]);
+    vector.distance = Some(0.25);
+    let tv = TraversalValue::Vector(vector);
+    assert_eq!(
+        tv.score_breakdown(),
+        ScoreBreakdown {
+            score: 0.25,
+            bm25_score: None,
+            vector_distance: Some(0.25),
+            rerank_score: None,
+        }
+    );
+}
+
+#[test]
+fn test_score_breakdown_keeps_search_scores_after_reranking() {
+    let arena = Bump::new();
+    let mut vector = create_test_vector(&arena, 1, "Vec", &[1.0]);
+    vector.distance = Some(0.25);
+    let mut tv = TraversalValue::Vector(vector);
+    update_score(&mut tv, 0.5).unwrap();
+    update_score(&mut tv, 0.75).unwrap();
+    assert_eq!(tv.vector_distance(), Some(0.25));
+    assert_eq!(tv.rerank_score(), Some(0.75));
+    assert!((tv.score() - 0.75).abs() < f64::EPSILON);
+
+    let node = create_test_node(&arena, 2, "Doc");
+    let mut tv = TraversalValue::NodeWithScore {
+        node,
+        score: 3.5,
+        search_score: None,
+    };
+    assert_eq!(tv.bm25_score(), Some(3.5));
+    assert_eq!(tv.rerank_score(), None);
+    update_score(&mut tv, 0.1).unwrap();
+    assert_eq!(tv.bm25_score(), Some(3.5));
+    assert_eq!(tv.rerank_score(), Some(0.1));
+    assert_eq!(tv.vector_distance(), None);
+}
+
+#[test]
+fn test_score_breakdown_converts_to_object() {
+    let breakdown = ScoreBreakdown {
+        score: 0.1,
+        bm25_score: Some(3.5),
+        vector_distance: None,
+        rerank_score: Some(0.1),
+    };
+    let Value::Object(fields) = Value::from(breakdown) else {
+        panic!("expected an object");
+    };
+    assert_eq!(fields.get("bm25_score"), Some(&Value::F64(3.5)));
+    assert_eq!(fields.get("vector_distance"), Some(&Value::Empty));
+    assert_eq!(fields.len(), 4);
+}
+
 // ============================================================================
 // get_property() Method Tests
 // ============================================================================
@@ -486,7 +563,11 @@ fn test_get_property_node_with_score_existing_property() {
         "Doc",
         vec![("title", Value::String("Report".to_string()))],
     );
-    let tv = TraversalValue::NodeWithScore { node, score: 0.9 };
+    let tv = TraversalValue::NodeWithScore {
+        node,
+        score: 0.9,
+        search_score: None,
+    };
     let prop = tv.get_property("title");
     assert!(prop.is_some());
     assert_eq!(prop.unwrap(), &Value::String("Report".to_string()));
@@ -623,10 +704,12 @@ fn test_eq_node_with_score_same_id() {
     let tv1 = TraversalValue::NodeWithScore {
         node: node1,
         score: 0.5,
+        search_score: None,
     };
     let tv2 = TraversalValue::NodeWithScore {
         node: node2,
         score: 0.9,
+        search_score: None,
     };
     assert_eq!(tv1, tv2);
 }
@@ -716,7 +799,11 @@ fn test_vector_with_large_data() {
 fn test_node_with_score_zero_score() {
     let arena = Bump::new();
     let node = create_test_node(&arena, 1, "Test");
-    let tv = TraversalValue::NodeWithScore { node, score: 0.0 };
+    let tv = TraversalValue::NodeWithScore {
+        node,
+        score: 0.0,
+        search_score: None,
+    };
     assert!((tv.score() - 0.0).abs() < f64::EPSILON);
 }
 
@@ -724,7 +811,11 @@ fn test_node_with_score_zero_score() {
 fn test_node_with_score_negative_score() {
     let arena = Bump::new();
     let node = create_test_node(&arena, 1, "Test");
-    let tv = TraversalValue::NodeWithScore { node, score: -1.5 };
+    let tv = TraversalValue::NodeWithScore {
+        node,
+        score: -1.5,
+        search_score: None,
+    };
     assert!((tv.score() - (-1.5)).abs() < f64::EPSILON);
 }
 
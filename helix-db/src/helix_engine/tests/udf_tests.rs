@@ -0,0 +1,191 @@
+use std::path::PathBuf;
+
+use crate::helix_engine::{
+    storage_core::{HelixGraphStorage, version_info::VersionInfo},
+    traversal_core::config::{Config, GatewayConfig, UdfConfig},
+    udf::{UdfError, Udfs},
+};
+use crate::protocol::value::Value;
+use tempfile::TempDir;
+
+const SCORING: &str = r#"
+(module
+  (func (export "boost") (param f64 i32) (result f64)
+    local.get 0
+    local.get 1
+    f64.convert_i32_s
+    f64.mul)
+  (func (export "spin") (result i32)
+    (loop (br 0))
+    i32.const 0)
+  (memory 1)
+  (func (export "grow") (param i32) (result i32)
+    local.get 0
+    memory.grow))
+"#;
+
+fn config(name: &str, export: Option<&str>) -> UdfConfig {
+    UdfConfig {
+        name: name.to_string(),
+        path: PathBuf::from("scoring.wasm"),
+        export: export.map(str::to_string),
+        fuel: None,
+        max_memory_bytes: None,
+    }
+}
+
+fn scoring_udfs() -> Udfs {
+    let mut udfs = Udfs::default();
+    for (name, export) in [("boost", None), ("spin", None), ("grow", None)] {
+        udfs.load(&config(name, export), SCORING.as_bytes())
+            .unwrap();
+    }
+    udfs
+}
+
+#[test]
+fn test_call_converts_numbers() {
+    let udfs = scoring_udfs();
+    let result = udfs
+        .call("boost", &[Value::F64(1.5), Value::I64(3)])
+        .unwrap();
+    assert!(matches!(result, Value::F64(v) if v == 4.5));
+
+    // Integer arguments widen to float parameters
+    let result = udfs.call("boost", &[Value::U8(2), Value::I32(2)]).unwrap();
+    assert!(matches!(result, Value::F64(v) if v == 4.0));
+}
+
+#[test]
+fn test_call_rejects_bad_arguments() {
+    let udfs = scoring_udfs();
+    assert!(matches!(
+        udfs.call("boost", &[Value::F64(1.0)]),
+        Err(UdfError::InvalidArguments { .. })
+    ));
+    assert!(matches!(
+        udfs.call("boost", &[Value::F64(1.0), Value::from("two")]),
+        Err(UdfError::InvalidArguments { .. })
+    ));
+    // Floats aren't truncated into integer parameters
+    assert!(matches!(
+        udfs.call("boost", &[Value::F64(1.0), Value::F64(2.5)]),
+        Err(UdfError::InvalidArguments { .. })
+    ));
+    assert!(matches!(
+        udfs.call("missing", &[]),
+        Err(UdfError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_calls_are_limited() {
+    let udfs = scoring_udfs();
+    assert!(matches!(
+        udfs.call("spin", &[]),
+        Err(UdfError::OutOfFuel(_))
+    ));
+    assert!(matches!(
+        udfs.eval("spin", &[]),
+        Err(UdfError::OutOfFuel(_))
+    ));
+
+    // Growing past the memory limit fails inside the module rather than allocating
+    let result = udfs.call("grow", &[Value::I32(1)]).unwrap();
+    assert!(matches!(result, Value::I32(1)));
+    let result = udfs.call("grow", &[Value::I32(1024)]).unwrap();
+    assert!(matches!(result, Value::I32(-1)));
+}
+
+#[test]
+fn test_eval_widens_results_to_f64() {
+    let udfs = scoring_udfs();
+    let result = udfs.eval("boost", &[Value::F64(1.5), Value::I64(3)]);
+    assert!(matches!(result, Ok(v) if v == 4.5));
+    let result = udfs.eval("grow", &[Value::I32(1)]);
+    assert!(matches!(result, Ok(v) if v == 1.0));
+    assert!(matches!(
+        udfs.eval("boost", &[Value::F64(1.0)]),
+        Err(UdfError::InvalidArguments { .. })
+    ));
+}
+
+#[test]
+fn test_load_rejects_imports_and_non_numeric_signatures() {
+    let mut udfs = Udfs::default();
+    let imports = r#"
+        (module
+          (import "env" "read_file" (func $read (param i32) (result i32)))
+          (func (export "f") (result i32) i32.const 0 call $read))
+    "#;
+    assert!(matches!(
+        udfs.load(&config("f", None), imports.as_bytes()),
+        Err(UdfError::Load { .. })
+    ));
+
+    let externref = r#"(module (func (export "f") (param externref)))"#;
+    assert!(matches!(
+        udfs.load(&config("f", None), externref.as_bytes()),
+        Err(UdfError::Load { .. })
+    ));
+
+    let no_result = r#"(module (func (export "f") (param i32)))"#;
+    assert!(matches!(
+        udfs.load(&config("f", None), no_result.as_bytes()),
+        Err(UdfError::Load { .. })
+    ));
+
+    assert!(matches!(
+        udfs.load(&config("f", Some("absent")), SCORING.as_bytes()),
+        Err(UdfError::Load { .. })
+    ));
+    assert!(udfs.is_empty());
+
+    // A function can be registered under a different name than its export
+    udfs.load(&config("score", Some("boost")), SCORING.as_bytes())
+        .unwrap();
+    assert!(udfs.contains("score"));
+}
+
+#[test]
+fn test_storage_loads_configured_udfs() {
+    let temp_dir = TempDir::new().unwrap();
+    let module_path = temp_dir.path().join("scoring.wat");
+    std::fs::write(&module_path, SCORING).unwrap();
+    let mut udf = config("boost", None);
+    udf.path = module_path;
+    let config = Config {
+        db_max_size_gb: Some(0),
+        gateway_config: Some(GatewayConfig {
+            udfs: Some(vec![udf.clone()]),
+            ..Default::default()
+        }),
+        ..Config::default()
+    };
+
+    let db_path = temp_dir.path().join("db");
+    let storage = HelixGraphStorage::new(
+        db_path.to_str().unwrap(),
+        config.clone(),
+        VersionInfo::default(),
+    )
+    .unwrap();
+    let result = storage
+        .udfs
+        .eval("boost", &[Value::F64(2.0), Value::I32(2)]);
+    assert!(matches!(result, Ok(v) if v == 4.0));
+    drop(storage);
+
+    // A module that can't be loaded stops the instance from starting
+    udf.path = temp_dir.path().join("missing.wasm");
+    let config = Config {
+        gateway_config: Some(GatewayConfig {
+            udfs: Some(vec![udf]),
+            ..Default::default()
+        }),
+        ..config
+    };
+    assert!(
+        HelixGraphStorage::new(db_path.to_str().unwrap(), config, VersionInfo::default()).is_err()
+    );
+}
@@ -99,3 +99,65 @@ fn test_hvector_cosine_similarity() {
     let similarity = v1.distance_to(&v2).unwrap();
     assert!((similarity - (1.0 - 0.9746318461970762)).abs() < 1e-9);
 }
+
+#[test]
+fn test_simd_kernels_match_scalar() {
+    use crate::helix_engine::vector_core::simd::{available, scalar};
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    // Lengths around each kernel's lane count exercise their remainders
+    for len in [0, 1, 3, 4, 7, 8, 9, 15, 16, 17, 384, 1537] {
+        let a: Vec<f64> = (0..len).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let b: Vec<f64> = (0..len).map(|_| rng.random_range(-1.0..1.0)).collect();
+        let dot = scalar::dot(&a, &b);
+        let sums = scalar::dot_and_norms(&a, &b);
+        let l2 = scalar::l2_squared(&a, &b);
+        for kernels in available() {
+            let close = |x: f64, y: f64| (x - y).abs() <= 1e-9 * (1.0 + y.abs());
+            assert!(close((kernels.dot)(&a, &b), dot), "{} dot", kernels.name);
+            let simd_sums = (kernels.dot_and_norms)(&a, &b);
+            assert!(close(simd_sums.dot, sums.dot), "{} dot", kernels.name);
+            assert!(
+                close(simd_sums.norm_a_sq, sums.norm_a_sq),
+                "{}",
+                kernels.name
+            );
+            assert!(
+                close(simd_sums.norm_b_sq, sums.norm_b_sq),
+                "{}",
+                kernels.name
+            );
+            assert!(
+                close((kernels.l2_squared)(&a, &b), l2),
+                "{} l2",
+                kernels.name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_simd_kernels_scalar_is_last() {
+    use crate::helix_engine::vector_core::simd::{available, kernels};
+
+    let available = available();
+    assert_eq!(available.last().unwrap().name, "scalar");
+    assert_eq!(kernels().name, available[0].name);
+}
+
+#[test]
+fn test_euclidean_distance() {
+    use crate::helix_engine::vector_core::vector_distance::euclidean_distance;
+
+    assert_eq!(euclidean_distance(&[0.0, 0.0], &[3.0, 4.0]).unwrap(), 5.0);
+    assert_eq!(euclidean_distance(&[1.0, 2.0], &[1.0, 2.0]).unwrap(), 0.0);
+    assert!(euclidean_distance(&[1.0], &[1.0, 2.0]).is_err());
+}
+
+#[test]
+fn test_cosine_similarity_zero_vector() {
+    use crate::helix_engine::vector_core::vector_distance::cosine_similarity;
+
+    assert_eq!(cosine_similarity(&[0.0; 9], &[1.0; 9]).unwrap(), -1.0);
+}
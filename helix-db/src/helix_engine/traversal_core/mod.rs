@@ -1,13 +1,15 @@
 pub mod config;
 pub mod ops;
+pub mod step_profile;
 pub mod traversal_iter;
 pub mod traversal_value;
 
-use crate::helix_engine::storage_core::{HelixGraphStorage, version_info::VersionInfo};
-use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::storage_core::{HelixGraphStorage, version_info::VersionInfo, warm_up};
+use crate::helix_engine::traversal_core::config::{Config, WarmUpConfig, WarmUpMode};
 use crate::helix_engine::types::GraphError;
 use crate::helix_gateway::mcp::mcp::{McpBackend, McpConnections};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
+use tracing::warn;
 
 pub const LMDB_STRING_HEADER_LENGTH: usize = 8;
 
@@ -35,15 +37,22 @@ pub struct HelixGraphEngineOpts {
 impl HelixGraphEngine {
     pub fn new(opts: HelixGraphEngineOpts) -> Result<HelixGraphEngine, GraphError> {
         let should_use_mcp = opts.config.mcp;
+        let mcp_rate_limit = opts.config.gateway_config().mcp_rate_limit;
+        let warm_up_config = opts.config.gateway_config().warm_up();
         let storage =
             match HelixGraphStorage::new(opts.path.as_str(), opts.config, opts.version_info) {
                 Ok(db) => Arc::new(db),
                 Err(err) => return Err(err),
             };
 
+        Self::warm_up(&storage, warm_up_config);
+        Self::merge_bm25(&storage);
+
         let (mcp_backend, mcp_connections) = if should_use_mcp.unwrap_or(false) {
             let mcp_backend = Arc::new(McpBackend::new(storage.clone()));
-            let mcp_connections = Arc::new(Mutex::new(McpConnections::new()));
+            let mcp_connections = Arc::new(Mutex::new(
+                McpConnections::new().with_rate_limit(mcp_rate_limit),
+            ));
             (Some(mcp_backend), Some(mcp_connections))
         } else {
             (None, None)
@@ -55,4 +64,72 @@ impl HelixGraphEngine {
             mcp_connections,
         })
     }
+
+    /// Warm up the vector index before returning, or on a background thread when deferred.
+    /// A failed warm-up only costs speed, so it's logged rather than failing the start.
+    fn warm_up(storage: &Arc<HelixGraphStorage>, config: WarmUpConfig) {
+        let mode = config.mode();
+        let run = move |storage: &HelixGraphStorage| {
+            if let Err(e) = warm_up::warm_up(storage, &config) {
+                warn!(error = %e, "Vector index warm-up failed");
+            }
+        };
+        match mode {
+            WarmUpMode::Eager => run(storage),
+            WarmUpMode::Deferred => {
+                let storage = storage.clone();
+                if let Err(e) = std::thread::Builder::new()
+                    .name("helix-warm-up".to_string())
+                    .spawn(move || run(&storage))
+                {
+                    warn!(error = %e, "Could not start the vector index warm-up");
+                }
+            }
+            WarmUpMode::Off => {}
+        }
+    }
+
+    /// Merge queued BM25 terms on a background thread, until the storage is dropped
+    fn merge_bm25(storage: &Arc<HelixGraphStorage>) {
+        let Some(interval) = storage
+            .bm25
+            .as_ref()
+            .and_then(|bm25| bm25.merge_policy().interval())
+        else {
+            return;
+        };
+        let storage: Weak<HelixGraphStorage> = Arc::downgrade(storage);
+        let merge = move || {
+            loop {
+                std::thread::sleep(interval);
+                let Some(storage) = storage.upgrade() else {
+                    return;
+                };
+                if let Err(e) = Self::merge_queued_bm25(&storage) {
+                    warn!(error = %e, "BM25 merge failed");
+                }
+            }
+        };
+        if let Err(e) = std::thread::Builder::new()
+            .name("helix-bm25-merge".to_string())
+            .spawn(merge)
+        {
+            warn!(error = %e, "Could not start BM25 merges");
+        }
+    }
+
+    fn merge_queued_bm25(storage: &HelixGraphStorage) -> Result<(), GraphError> {
+        let Some(bm25) = &storage.bm25 else {
+            return Ok(());
+        };
+        let txn = storage.graph_env.read_txn()?;
+        if !bm25.has_queued_merges(&txn)? {
+            return Ok(());
+        }
+        drop(txn);
+        let mut txn = storage.graph_env.write_txn()?;
+        bm25.merge_queued(&mut txn, bm25.merge_policy().max_terms_per_merge())?;
+        txn.commit()?;
+        Ok(())
+    }
 }
@@ -1,6 +1,10 @@
 use crate::{
     helix_engine::{
-        storage_core::HelixGraphStorage,
+        storage_core::{
+            HelixGraphStorage,
+            triggers::{self, TriggerEvent},
+            write_log,
+        },
         traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
         types::GraphError,
     },
@@ -75,20 +79,25 @@ impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, Gr
             to_node,
         };
 
-        let mut result: Result<TraversalValue, GraphError> = Ok(TraversalValue::Empty);
+        let mut result: Result<TraversalValue, GraphError> = self
+            .storage
+            .check_acyclic(self.txn, label, from_node, to_node)
+            .map(|_| TraversalValue::Empty);
 
-        match edge.to_bincode_bytes() {
-            Ok(bytes) => {
-                if let Err(e) = self.storage.edges_db.put_with_flags(
-                    self.txn,
-                    PutFlags::APPEND,
-                    HelixGraphStorage::edge_key(&edge.id),
-                    &bytes,
-                ) {
-                    result = Err(GraphError::from(e));
+        if result.is_ok() {
+            match edge.to_bincode_bytes() {
+                Ok(bytes) => {
+                    if let Err(e) = self.storage.edges_db.put_with_flags(
+                        self.txn,
+                        PutFlags::APPEND,
+                        HelixGraphStorage::edge_key(&edge.id),
+                        &bytes,
+                    ) {
+                        result = Err(GraphError::from(e));
+                    }
                 }
+                Err(e) => result = Err(GraphError::from(e)),
             }
-            Err(e) => result = Err(GraphError::from(e)),
         }
 
         // Skip remaining operations if edge insertion failed
@@ -135,12 +144,28 @@ impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, Gr
                     }
                 }
             }
+
+            if result.is_ok()
+                && let Err(e) =
+                    self.storage
+                        .count_edge(self.txn, &label_hash, from_node, to_node, 1)
+            {
+                result = Err(e);
+            }
+
+            if result.is_ok()
+                && let Err(e) =
+                    triggers::fire(self.storage, self.txn, TriggerEvent::AddE, label, edge.id)
+            {
+                result = Err(e);
+            }
         }
 
         let result = match result {
             Ok(_) => Ok(TraversalValue::Edge(edge)),
             Err(e) => Err(e),
         };
+        write_log::record_value(&result);
 
         RwTraversalIterator {
             arena: self.arena,
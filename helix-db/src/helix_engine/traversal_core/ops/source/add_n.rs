@@ -1,7 +1,11 @@
 use crate::{
     helix_engine::{
         bm25::bm25::{BM25, BM25Flatten},
-        storage_core::HelixGraphStorage,
+        storage_core::{
+            HelixGraphStorage,
+            triggers::{self, TriggerEvent},
+            write_log,
+        },
         traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
         types::GraphError,
     },
@@ -148,10 +152,19 @@ impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, Gr
             }
         }
 
+        if result.is_ok()
+            && let Err(e) =
+                triggers::fire(self.storage, self.txn, TriggerEvent::AddN, label, node.id)
+        {
+            result = Err(e);
+        }
+
         if result.is_ok() {
+            self.storage.constrain_node(label, node.id);
             result = Ok(TraversalValue::Node(node));
         }
         // Preserve original error - don't overwrite with generic message
+        write_log::record_value(&result);
 
         RwTraversalIterator {
             storage: self.storage,
@@ -1,5 +1,6 @@
 use crate::{
     helix_engine::{
+        storage_core::scan_counter,
         traversal_core::{
             LMDB_STRING_HEADER_LENGTH, traversal_iter::RoTraversalIterator,
             traversal_value::TraversalValue,
@@ -27,6 +28,7 @@ impl<'arena, 'txn, 's> Iterator for EFromType<'arena, 'txn, 's> {
 
     fn next(&mut self) -> Option<Self::Item> {
         for value in self.iter.by_ref() {
+            scan_counter::record(1);
             let (id, value) = value.unwrap();
 
             match value.decode() {
@@ -6,5 +6,6 @@ pub mod e_from_type;
 pub mod n_from_id;
 pub mod n_from_index;
 pub mod n_from_type;
+pub mod n_from_view;
 pub mod v_from_id;
 pub mod v_from_type;
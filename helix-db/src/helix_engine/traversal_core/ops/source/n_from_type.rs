@@ -1,5 +1,6 @@
 use crate::{
     helix_engine::{
+        storage_core::scan_counter,
         traversal_core::{
             LMDB_STRING_HEADER_LENGTH, traversal_iter::RoTraversalIterator,
             traversal_value::TraversalValue,
@@ -51,6 +52,7 @@ impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, Gr
     > {
         let label_as_bytes = label.as_bytes();
         let iter = self.storage.nodes_db.iter(self.txn).unwrap().filter_map(move |item| {
+            scan_counter::record(1);
             if let Ok((id, value)) = item {
                 assert!(
                     value.len() >= LMDB_STRING_HEADER_LENGTH,
@@ -0,0 +1,70 @@
+use crate::helix_engine::{
+    storage_core::{storage_methods::StorageMethods, views},
+    traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+    types::GraphError,
+};
+
+pub trait NFromViewAdapter<'db, 'arena, 'txn, 's>:
+    Iterator<Item = Result<TraversalValue<'arena>, GraphError>>
+{
+    /// Returns an iterator containing the nodes of the view named `name`, in the order its
+    /// traversal returned them when the view was last refreshed.
+    ///
+    /// Nodes dropped since then are skipped. A view that was never refreshed runs its
+    /// traversal instead.
+    fn n_from_view(
+        self,
+        name: &'s str,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >;
+}
+
+impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    NFromViewAdapter<'db, 'arena, 'txn, 's> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    #[inline]
+    fn n_from_view(
+        self,
+        name: &'s str,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    > {
+        let ids = match self.storage.view_ids(self.txn, name) {
+            Ok(Some(ids)) => Ok(ids),
+            Ok(None) => match views::find(name) {
+                Some(view) => (view.func)(self.storage, self.txn),
+                None => Err(GraphError::New(format!("View `{name}` is not declared"))),
+            },
+            Err(e) => Err(e),
+        };
+        let (ids, error) = match ids {
+            Ok(ids) => (ids, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+
+        let storage = self.storage;
+        let txn = self.txn;
+        let arena = self.arena;
+        let nodes = ids.into_iter().filter_map(move |id| {
+            match storage.get_node(txn, &id, arena) {
+                Ok(node) => Some(Ok(TraversalValue::Node(node))),
+                Err(GraphError::NodeNotFound) => None,
+                Err(e) => Some(Err(e)),
+            }
+        });
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: error.map(Err).into_iter().chain(nodes),
+        }
+    }
+}
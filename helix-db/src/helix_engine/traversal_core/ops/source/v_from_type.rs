@@ -1,4 +1,5 @@
 use crate::helix_engine::{
+    storage_core::scan_counter,
     traversal_core::{
         LMDB_STRING_HEADER_LENGTH, traversal_iter::RoTraversalIterator,
         traversal_value::TraversalValue,
@@ -47,6 +48,7 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             .iter(self.txn)
             .unwrap()
             .filter_map(move |item| {
+                scan_counter::record(1);
                 if let Ok((id, value)) = item {
 
 
@@ -0,0 +1,62 @@
+use chrono_tz::Tz;
+
+use crate::{
+    helix_engine::{
+        traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+        types::GraphError,
+    },
+    utils::bucket::{Buckets, Interval, time_of},
+};
+
+pub trait BucketByAdapter<'arena>: Iterator {
+    /// BucketBy groups the current items by the time bucket a date property falls in, such as
+    /// the day in a time zone. Items without the property, or with one that isn't a date, are
+    /// left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The property holding each item's time
+    /// * `interval` - How wide the buckets are, like `15m`, `1d` or `1mo`
+    /// * `timezone` - The IANA time zone buckets follow, like `UTC` or `Europe/Berlin`
+    /// * `should_count` - Whether to only count the items of each bucket
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let buckets = G::new(storage, &txn).n_from_type("Post").bucket_by("created_at", "1d", "UTC", true)?;
+    /// ```
+    fn bucket_by<T: AsRef<str>>(
+        self,
+        property: &str,
+        interval: &str,
+        timezone: T,
+        should_count: bool,
+    ) -> Result<Buckets<'arena>, GraphError>;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    BucketByAdapter<'arena> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn bucket_by<T: AsRef<str>>(
+        self,
+        property: &str,
+        interval: &str,
+        timezone: T,
+        should_count: bool,
+    ) -> Result<Buckets<'arena>, GraphError> {
+        let interval: Interval = interval.parse().map_err(GraphError::New)?;
+        let timezone = timezone.as_ref();
+        let tz: Tz = timezone
+            .parse()
+            .map_err(|_| GraphError::New(format!("unknown time zone `{timezone}`")))?;
+
+        let mut items = Vec::new();
+        for item in self.inner {
+            let item = item?;
+            if let Some(time) = item.get_property(property).and_then(time_of) {
+                items.push((time, item));
+            }
+        }
+        Ok(Buckets::new(items, interval, tz, should_count))
+    }
+}
@@ -0,0 +1,96 @@
+use heed3::RoTxn;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, degrees::DegreeDirection},
+        traversal_core::{
+            traversal_iter::{RoTraversalIterator, RwTraversalIterator},
+            traversal_value::TraversalValue,
+        },
+        types::GraphError,
+    },
+    protocol::value::Value,
+    utils::label_hash::hash_label,
+};
+
+pub trait DegreeAdapter<'arena>: Iterator {
+    /// Degree counts the edges with the label at each of the current nodes in `direction`,
+    /// summed over the nodes. The counts are kept as edges are written, so no edges are read.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the edges to count
+    /// * `direction` - Whether to count edges from the nodes, to them or both
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let followers = G::new(storage, &txn)
+    ///     .n_from_id(&id)
+    ///     .degree("follows", DegreeDirection::In);
+    /// ```
+    fn degree(self, label: &str, direction: DegreeDirection) -> Value;
+
+    /// NeighborCount counts the edges of any label to or from each of the current nodes,
+    /// summed over the nodes. Like [`DegreeAdapter::degree`], no edges are read.
+    fn neighbor_count(self) -> Value;
+}
+
+/// Sum `count` over the nodes and vectors in `inner`
+fn sum_over<'arena>(
+    inner: impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    count: impl Fn(u128) -> Result<u64, GraphError>,
+) -> Value {
+    let total: u64 = inner
+        .filter_map(|item| match item {
+            Ok(
+                value @ (TraversalValue::Node(_)
+                | TraversalValue::NodeWithScore { .. }
+                | TraversalValue::Vector(_)
+                | TraversalValue::VectorNodeWithoutVectorData(_)),
+            ) => count(value.id()).ok(),
+            _ => None,
+        })
+        .sum();
+    Value::from(total)
+}
+
+fn degree_of(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    label: &str,
+    direction: DegreeDirection,
+) -> impl Fn(u128) -> Result<u64, GraphError> {
+    let label = hash_label(label, None);
+    move |id| storage.degree(txn, id, &label, direction)
+}
+
+impl<'db, 'arena: 'txn, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    DegreeAdapter<'arena> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn degree(self, label: &str, direction: DegreeDirection) -> Value {
+        sum_over(
+            self.inner,
+            degree_of(self.storage, self.txn, label, direction),
+        )
+    }
+
+    fn neighbor_count(self) -> Value {
+        sum_over(self.inner, |id| self.storage.neighbor_count(self.txn, id))
+    }
+}
+
+impl<'db, 'arena: 'txn, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    DegreeAdapter<'arena> for RwTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn degree(self, label: &str, direction: DegreeDirection) -> Value {
+        sum_over(
+            self.inner,
+            degree_of(self.storage, self.txn, label, direction),
+        )
+    }
+
+    fn neighbor_count(self) -> Value {
+        sum_over(self.inner, |id| self.storage.neighbor_count(self.txn, id))
+    }
+}
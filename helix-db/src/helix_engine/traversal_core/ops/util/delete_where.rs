@@ -0,0 +1,130 @@
+use std::time::Instant;
+
+use bumpalo::Bump;
+use heed3::{RoTxn, RwTxn};
+use tracing::info;
+
+use crate::helix_engine::{
+    bm25::bm25::BM25,
+    storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+    traversal_core::traversal_value::TraversalValue,
+    types::GraphError,
+};
+
+/// Items deleted per write transaction by `::DELETE`
+pub const DELETE_BATCH_SIZE: usize = 10_000;
+
+pub struct DeleteWhere;
+
+impl DeleteWhere {
+    /// Deletes everything `select` matches in batches of `DELETE_BATCH_SIZE`, committing each
+    /// batch and reporting progress before selecting the next, so deleting millions of items
+    /// neither times out nor holds the write lock throughout. Batches committed before a failure
+    /// stay deleted.
+    ///
+    /// `select` runs the traversal on the transaction it's given and returns at most `batch`
+    /// of the items it finds. Returns how many items were deleted and the transaction to carry
+    /// on the query with.
+    pub fn delete_where<'db, F>(
+        storage: &'db HelixGraphStorage,
+        txn: RwTxn<'db>,
+        select: F,
+    ) -> Result<(u64, RwTxn<'db>), GraphError>
+    where
+        F: for<'a> FnMut(
+            &'a RoTxn<'db>,
+            &'a Bump,
+            usize,
+        ) -> Result<Vec<TraversalValue<'a>>, GraphError>,
+    {
+        Self::delete_in_batches(storage, txn, DELETE_BATCH_SIZE, select)
+    }
+
+    pub(crate) fn delete_in_batches<'db, F>(
+        storage: &'db HelixGraphStorage,
+        mut txn: RwTxn<'db>,
+        batch_size: usize,
+        mut select: F,
+    ) -> Result<(u64, RwTxn<'db>), GraphError>
+    where
+        F: for<'a> FnMut(
+            &'a RoTxn<'db>,
+            &'a Bump,
+            usize,
+        ) -> Result<Vec<TraversalValue<'a>>, GraphError>,
+    {
+        let started = Instant::now();
+        let mut arena = Bump::new();
+        let mut deleted = 0_u64;
+        let mut batches = 0_u64;
+        loop {
+            // Ids are copied out so the batch stops borrowing the transaction it came from
+            let batch = select(&txn, &arena, batch_size)?
+                .into_iter()
+                .map(Target::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            let last = batch.len() < batch_size;
+            for target in batch {
+                match target {
+                    Target::Node(id) => {
+                        storage.drop_node(&mut txn, &id)?;
+                        if let Some(bm25) = &storage.bm25 {
+                            bm25.delete_doc(&mut txn, id)?;
+                        }
+                    }
+                    Target::Edge(id) => storage.drop_edge(&mut txn, &id)?,
+                    Target::Vector(id) => storage.drop_vector(&mut txn, &id)?,
+                    Target::Empty => continue,
+                }
+                deleted += 1;
+            }
+            if last {
+                break;
+            }
+
+            txn.commit()?;
+            batches += 1;
+            info!(
+                batches,
+                deleted,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Deleted batch"
+            );
+            arena.reset();
+            txn = storage.graph_env.write_txn()?;
+        }
+        if batches > 0 {
+            info!(
+                batches = batches + 1,
+                deleted,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Deleted all matching items"
+            );
+        }
+        Ok((deleted, txn))
+    }
+}
+
+enum Target {
+    Node(u128),
+    Edge(u128),
+    Vector(u128),
+    Empty,
+}
+
+impl TryFrom<TraversalValue<'_>> for Target {
+    type Error = GraphError;
+
+    fn try_from(item: TraversalValue<'_>) -> Result<Self, Self::Error> {
+        match item {
+            TraversalValue::Node(node) => Ok(Target::Node(node.id)),
+            TraversalValue::Edge(edge) => Ok(Target::Edge(edge.id)),
+            TraversalValue::Vector(vector) => Ok(Target::Vector(vector.id)),
+            TraversalValue::VectorNodeWithoutVectorData(vector) => Ok(Target::Vector(vector.id)),
+            TraversalValue::Empty => Ok(Target::Empty),
+            other => Err(GraphError::ConversionError(format!(
+                "Incorrect Type: {other:?}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::{collections::HashSet, sync::Arc};
+
+use heed3::RoTxn;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+        types::GraphError,
+    },
+    utils::label_hash::hash_label,
+};
+
+pub trait EgoAdapter<'db, 'arena, 'txn>: Iterator {
+    /// Ego returns the nodes within `depth` hops of each of the current nodes along edges with
+    /// the label, following them outwards and leaving out the node itself. Each node is
+    /// returned once. Neighborhoods of edge types in `gateway_config.ego_cache` are cached
+    /// across queries.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the edges to follow
+    /// * `depth` - How many edges away from the current nodes to go
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let traversal = G::new(storage, &txn).n_from_id(&id).ego("follows", 2);
+    /// ```
+    fn ego<N>(
+        self,
+        label: &str,
+        depth: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    EgoAdapter<'db, 'arena, 'txn> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn ego<N>(
+        self,
+        label: &str,
+        depth: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>,
+    {
+        let label = hash_label(label, None);
+        let depth = depth.try_into().ok();
+        let (storage, txn, arena) = (self.storage, self.txn, self.arena);
+        let mut seen = HashSet::new();
+
+        let inner = self.inner.flat_map(move |item| {
+            let neighborhood = item.and_then(|item| {
+                let depth = depth.ok_or_else(|| {
+                    GraphError::New("ego depth must be a non-negative number".to_string())
+                })?;
+                neighborhood(storage, txn, &label, item.id(), depth)
+            });
+            let nodes = match neighborhood {
+                Ok(nodes) => nodes,
+                Err(e) => return vec![Err(e)],
+            };
+            nodes
+                .iter()
+                .filter(|id| seen.insert(**id))
+                .filter_map(|id| match storage.get_node(txn, id, arena) {
+                    Ok(node) => Some(Ok(TraversalValue::Node(node))),
+                    Err(GraphError::NodeNotFound) => None,
+                    Err(e) => Some(Err(e)),
+                })
+                .collect()
+        });
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner,
+        }
+    }
+}
+
+/// The neighborhood of `node`, from the ego cache when its edge type is cached
+fn neighborhood(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    label: &[u8; 4],
+    node: u128,
+    depth: usize,
+) -> Result<Arc<[u128]>, GraphError> {
+    let cache = &storage.ego_cache;
+    if !cache.caches(label, depth) {
+        return walk(storage, txn, label, node, depth).map(Arc::from);
+    }
+    let txn_id = txn.id();
+    if let Some(nodes) = cache.get(label, node, depth, txn_id) {
+        return Ok(nodes);
+    }
+    let nodes: Arc<[u128]> = walk(storage, txn, label, node, depth)?.into();
+    cache.insert(label, node, depth, txn_id, Arc::clone(&nodes));
+    Ok(nodes)
+}
+
+/// The ids of the nodes within `depth` hops of `node` along its outgoing `label` edges, other
+/// than `node`, in the order they're reached
+fn walk(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    label: &[u8; 4],
+    node: u128,
+    depth: usize,
+) -> Result<Vec<u128>, GraphError> {
+    let mut seen = HashSet::from([node]);
+    let mut reached = Vec::new();
+    let mut frontier = vec![node];
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for from in frontier {
+            let key = HelixGraphStorage::out_edge_key(&from, label);
+            if let Some(edges) = storage.out_edges_db.get_duplicates(txn, &key)? {
+                for result in edges {
+                    let (_, to) = HelixGraphStorage::unpack_adj_edge_data(result?.1)?;
+                    if seen.insert(to) {
+                        next.push(to);
+                    }
+                }
+            }
+        }
+        reached.extend(&next);
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    Ok(reached)
+}
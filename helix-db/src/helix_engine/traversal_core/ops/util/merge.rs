@@ -0,0 +1,51 @@
+use crate::helix_engine::{
+    storage_core::{merge::ConflictPolicy, write_log},
+    traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
+    types::GraphError,
+};
+
+pub trait MergeNodesAdapter<'db, 'arena, 'txn>:
+    Iterator<Item = Result<TraversalValue<'arena>, GraphError>>
+{
+    /// Merge the node `remove` into the node `keep`, yielding the kept node as merged
+    fn merge_nodes(
+        self,
+        keep: u128,
+        remove: u128,
+        conflict: ConflictPolicy,
+    ) -> RwTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    MergeNodesAdapter<'db, 'arena, 'txn> for RwTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn merge_nodes(
+        self,
+        keep: u128,
+        remove: u128,
+        conflict: ConflictPolicy,
+    ) -> RwTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    > {
+        let result = self
+            .storage
+            .merge_nodes(self.txn, self.arena, keep, remove, conflict)
+            .map(TraversalValue::Node);
+        write_log::record_value(&result);
+
+        RwTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: std::iter::once(result),
+        }
+    }
+}
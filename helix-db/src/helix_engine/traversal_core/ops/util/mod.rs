@@ -1,14 +1,23 @@
 pub mod aggregate;
+pub mod bucket_by;
 pub mod count;
+pub mod degree;
 pub mod dedup;
+pub mod delete_where;
 pub mod drop;
+pub mod ego;
 pub mod exist;
 pub mod filter_mut;
 pub mod filter_ref;
 pub mod group_by;
 pub mod map;
+pub mod merge;
 pub mod order;
 pub mod paths;
 pub mod range;
+pub mod sample;
+pub mod stats;
+pub mod subgraph;
 pub mod update;
 pub mod upsert;
+pub mod walk_paths;
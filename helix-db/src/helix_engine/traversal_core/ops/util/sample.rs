@@ -0,0 +1,138 @@
+use rand::Rng;
+
+use crate::helix_engine::{
+    traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+    types::GraphError,
+};
+
+pub trait SampleAdapter<'db, 'arena, 'txn>: Iterator {
+    /// Sample keeps `n` of the current items chosen uniformly at random (all of them when there
+    /// are fewer), in the order the traversal returned them
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of items to keep
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let traversal = G::new(storage, &txn).n_from_type("user").sample(100);
+    /// ```
+    fn sample<N>(
+        self,
+        n: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>,
+        N::Error: std::fmt::Debug;
+
+    /// SampleFraction keeps each of the current items with probability `fraction`, so about
+    /// that share of them is kept without waiting for the traversal to finish
+    ///
+    /// # Arguments
+    ///
+    /// * `fraction` - The chance of keeping each item, between 0 and 1
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let traversal = G::new(storage, &txn).n_from_type("user").sample_fraction(0.01);
+    /// ```
+    fn sample_fraction<F>(
+        self,
+        fraction: F,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        F: Into<f64>;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    SampleAdapter<'db, 'arena, 'txn> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn sample<N>(
+        self,
+        n: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>,
+        N::Error: std::fmt::Debug,
+    {
+        let n = n
+            .try_into()
+            .expect("Sample size must be non-negative and fit in usize");
+
+        // Reservoir sampling: the i-th item replaces a random kept one with probability n / i
+        let mut rng = rand::rng();
+        let mut errors = Vec::new();
+        let mut reservoir: Vec<(usize, TraversalValue<'arena>)> = Vec::with_capacity(n);
+        let mut seen = 0;
+        for item in self.inner {
+            let item = match item {
+                Ok(item) => item,
+                Err(e) => {
+                    errors.push(Err(e));
+                    continue;
+                }
+            };
+            if reservoir.len() < n {
+                reservoir.push((seen, item));
+            } else {
+                let slot = rng.random_range(0..=seen);
+                if slot < n {
+                    reservoir[slot] = (seen, item);
+                }
+            }
+            seen += 1;
+        }
+        reservoir.sort_unstable_by_key(|(position, _)| *position);
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: errors
+                .into_iter()
+                .chain(reservoir.into_iter().map(|(_, item)| Ok(item))),
+        }
+    }
+
+    fn sample_fraction<F>(
+        self,
+        fraction: F,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        F: Into<f64>,
+    {
+        let fraction = fraction.into().clamp(0.0, 1.0);
+        let mut rng = rand::rng();
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: self
+                .inner
+                .filter(move |item| item.is_err() || rng.random_bool(fraction)),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{
+    helix_engine::{
+        traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+        types::GraphError,
+    },
+    protocol::value::Value,
+    utils::stats::{TDigest, Variance},
+};
+
+pub trait StatsAdapter: Iterator {
+    /// Percentile returns the value of a numeric property below which a `q` share of the
+    /// current items fall, exact for small sets and estimated with a t-digest for large ones.
+    /// Items without the property, or with one that isn't a number, are left out, and the result
+    /// is empty when none are left.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let p95 = G::new(storage, &txn).n_from_type("Request").percentile("latency", 0.95)?;
+    /// ```
+    fn percentile(self, property: &str, q: f64) -> Result<Value, GraphError>;
+
+    /// Stddev returns the sample standard deviation of a numeric property over the current
+    /// items, leaving out the ones without a number, or empty for fewer than two values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let spread = G::new(storage, &txn).n_from_type("Request").stddev("latency")?;
+    /// ```
+    fn stddev(self, property: &str) -> Result<Value, GraphError>;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>> StatsAdapter
+    for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn percentile(self, property: &str, q: f64) -> Result<Value, GraphError> {
+        let mut digest = TDigest::default();
+        for item in self.inner {
+            if let Some(value) = item?.get_property(property).and_then(Value::to_f64) {
+                digest.add(value);
+            }
+        }
+        Ok(digest.quantile(q).map_or(Value::Empty, Value::F64))
+    }
+
+    fn stddev(self, property: &str) -> Result<Value, GraphError> {
+        let mut variance = Variance::default();
+        for item in self.inner {
+            if let Some(value) = item?.get_property(property).and_then(Value::to_f64) {
+                variance.add(value);
+            }
+        }
+        Ok(variance.stddev().map_or(Value::Empty, Value::F64))
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+        types::GraphError,
+    },
+    utils::items::{Edge, Node},
+};
+
+pub trait SubgraphAdapter<'db, 'arena, 'txn>: Iterator {
+    /// Subgraph collects the nodes within `depth` edges of the current nodes, following edges
+    /// either way, with every edge between those nodes, into a single value
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - How many edges away from the current nodes to go
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let traversal = G::new(storage, &txn).n_from_id(&id).subgraph(2);
+    /// ```
+    fn subgraph<N>(
+        self,
+        depth: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>;
+}
+
+impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    SubgraphAdapter<'db, 'arena, 'txn> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn subgraph<N>(
+        self,
+        depth: N,
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >
+    where
+        N: TryInto<usize>,
+    {
+        let result = depth
+            .try_into()
+            .map_err(|_| {
+                GraphError::New("subgraph depth must be a non-negative number".to_string())
+            })
+            .and_then(|depth| {
+                let seeds = self
+                    .inner
+                    .filter_map(|item| match item {
+                        Ok(TraversalValue::Node(node)) => Some(Ok(node)),
+                        Ok(_) => None,
+                        Err(e) => Some(Err(e)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (nodes, edges) =
+                    collect_subgraph(self.storage, self.txn, self.arena, seeds, depth)?;
+                Ok(TraversalValue::Subgraph { nodes, edges })
+            });
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: std::iter::once(result),
+        }
+    }
+}
+
+/// The nodes within `depth` edges of `seeds`, in the order they're reached, and the edges
+/// between them. Edges to vectors are left out, as a vector isn't a node of the subgraph.
+fn collect_subgraph<'arena>(
+    storage: &HelixGraphStorage,
+    txn: &heed3::RoTxn,
+    arena: &'arena bumpalo::Bump,
+    seeds: Vec<Node<'arena>>,
+    depth: usize,
+) -> Result<(Vec<Node<'arena>>, Vec<Edge<'arena>>), GraphError> {
+    let mut seen: HashSet<u128> = HashSet::new();
+    let mut nodes = Vec::new();
+    for node in seeds {
+        if seen.insert(node.id) {
+            nodes.push(node);
+        }
+    }
+
+    let mut frontier = 0..nodes.len();
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for node in &nodes[frontier.clone()] {
+            for (_, neighbour) in adjacent(storage, txn, node.id)? {
+                if seen.insert(neighbour) {
+                    next.push(neighbour);
+                }
+            }
+        }
+        frontier = nodes.len()..nodes.len();
+        for id in next {
+            match storage.get_node(txn, &id, arena) {
+                Ok(node) => nodes.push(node),
+                Err(GraphError::NodeNotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        frontier.end = nodes.len();
+        if frontier.is_empty() {
+            break;
+        }
+    }
+
+    let in_subgraph: HashSet<u128> = nodes.iter().map(|node| node.id).collect();
+    let mut edge_ids = HashSet::new();
+    let mut edges = Vec::new();
+    for node in &nodes {
+        for (edge_id, neighbour) in adjacent(storage, txn, node.id)? {
+            if in_subgraph.contains(&neighbour) && edge_ids.insert(edge_id) {
+                edges.push(storage.get_edge(txn, &edge_id, arena)?);
+            }
+        }
+    }
+    Ok((nodes, edges))
+}
+
+/// The `(edge id, node id)` of every edge into or out of `node`
+fn adjacent(
+    storage: &HelixGraphStorage,
+    txn: &heed3::RoTxn,
+    node: u128,
+) -> Result<Vec<(u128, u128)>, GraphError> {
+    let mut adjacent = Vec::new();
+    for db in [&storage.out_edges_db, &storage.in_edges_db] {
+        for result in db.prefix_iter(txn, &node.to_be_bytes())? {
+            let (_, value) = result?;
+            adjacent.push(HelixGraphStorage::unpack_adj_edge_data(value)?);
+        }
+    }
+    Ok(adjacent)
+}
@@ -3,6 +3,7 @@ use itertools::Itertools;
 
 use crate::{
     helix_engine::{
+        storage_core::write_log,
         traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
         types::GraphError,
     },
@@ -28,7 +29,7 @@ where
 pub trait UpdateAdapter<'db, 'arena, 'txn>: Iterator {
     fn update(
         self,
-        props: &[(&'static str, Value)],
+        props: &[(&'arena str, Value)],
     ) -> RwTraversalIterator<
         'db,
         'arena,
@@ -42,7 +43,7 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
 {
     fn update(
         self,
-        props: &[(&'static str, Value)],
+        props: &[(&'arena str, Value)],
     ) -> RwTraversalIterator<
         'db,
         'arena,
@@ -184,7 +185,10 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
                                     &node.id,
                                     &serialized_node,
                                 ) {
-                                    Ok(_) => results.push(Ok(TraversalValue::Node(node))),
+                                    Ok(_) => {
+                                        write_log::record(node.id);
+                                        results.push(Ok(TraversalValue::Node(node)))
+                                    }
                                     Err(e) => results.push(Err(GraphError::from(e))),
                                 }
                             }
@@ -242,7 +246,10 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
                                     &edge.id,
                                     &serialized_edge,
                                 ) {
-                                    Ok(_) => results.push(Ok(TraversalValue::Edge(edge))),
+                                    Ok(_) => {
+                                        write_log::record(edge.id);
+                                        results.push(Ok(TraversalValue::Edge(edge)))
+                                    }
                                     Err(e) => results.push(Err(GraphError::from(e))),
                                 }
                             }
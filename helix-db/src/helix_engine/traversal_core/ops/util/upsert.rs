@@ -4,10 +4,14 @@ use itertools::Itertools;
 use crate::{
     helix_engine::{
         bm25::bm25::{BM25, BM25Flatten},
-        storage_core::HelixGraphStorage,
+        storage_core::{
+            HelixGraphStorage,
+            triggers::{self, TriggerEvent},
+            write_log,
+        },
         traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
         types::GraphError,
-        vector_core::{hnsw::HNSW, vector::HVector},
+        vector_core::{content_hash, hnsw::HNSW, vector::HVector},
     },
     protocol::value::Value,
     utils::{
@@ -54,6 +58,24 @@ pub trait UpsertAdapter<'db, 'arena, 'txn>: Iterator {
         'txn,
         impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
     >;
+
+    /// Upsert a vector embedded from `content`
+    ///
+    /// An existing vector last upserted from the same content keeps its stored embedding and
+    /// its place in the index, only its properties are updated. Otherwise it's moved to `query`.
+    /// The returned vector's `reembedded` tells which happened.
+    fn upsert_v_embedded(
+        self,
+        query: &'arena [f64],
+        content: &str,
+        label: &'arena str,
+        props: &[(&'static str, Value)],
+    ) -> RwTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >;
 }
 
 impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
@@ -329,7 +351,20 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
                     }
                 }
 
+                if result.is_ok()
+                    && let Err(e) = triggers::fire(
+                        self.storage,
+                        self.txn,
+                        TriggerEvent::AddN,
+                        label,
+                        node.id,
+                    )
+                {
+                    result = Err(e);
+                }
+
                 if result.is_ok() {
+                    self.storage.constrain_node(label, node.id);
                     result = Ok(TraversalValue::Node(node));
                 }
                 // Don't overwrite existing errors with a generic message
@@ -342,6 +377,7 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             }
         }
 
+        write_log::record_value(&result);
         RwTraversalIterator {
             storage: self.storage,
             arena: self.arena,
@@ -422,73 +458,97 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             Some(Err(e)) => {
                 result = Err(e);
             }
-            None => {
-                // Create new edge
-                let version = self.storage.version_info.get_latest(label);
-                let properties = if props.is_empty() {
-                    None
-                } else {
-                    Some(ImmutablePropertiesMap::new(
-                        props.len(),
-                        props.iter().map(|(k, v)| (*k, v.clone())),
-                        self.arena,
-                    ))
-                };
+            None => match self.storage.check_acyclic(self.txn, label, from_node, to_node) {
+                Err(e) => result = Err(e),
+                Ok(()) => {
+                    // Create new edge
+                    let version = self.storage.version_info.get_latest(label);
+                    let properties = if props.is_empty() {
+                        None
+                    } else {
+                        Some(ImmutablePropertiesMap::new(
+                            props.len(),
+                            props.iter().map(|(k, v)| (*k, v.clone())),
+                            self.arena,
+                        ))
+                    };
 
-                let edge = Edge {
-                    id: v6_uuid(),
-                    label,
-                    version,
-                    properties,
-                    from_node,
-                    to_node,
-                };
+                    let edge = Edge {
+                        id: v6_uuid(),
+                        label,
+                        version,
+                        properties,
+                        from_node,
+                        to_node,
+                    };
 
-                // Insert into edges_db
-                match edge.to_bincode_bytes() {
-                    Ok(bytes) => {
-                        if let Err(e) = self.storage.edges_db.put_with_flags(
-                            self.txn,
-                            PutFlags::APPEND,
-                            HelixGraphStorage::edge_key(&edge.id),
-                            &bytes,
-                        ) {
-                            result = Err(GraphError::from(e));
+                    // Insert into edges_db
+                    match edge.to_bincode_bytes() {
+                        Ok(bytes) => {
+                            if let Err(e) = self.storage.edges_db.put_with_flags(
+                                self.txn,
+                                PutFlags::APPEND,
+                                HelixGraphStorage::edge_key(&edge.id),
+                                &bytes,
+                            ) {
+                                result = Err(GraphError::from(e));
+                            }
                         }
+                        Err(e) => result = Err(GraphError::from(e)),
                     }
-                    Err(e) => result = Err(GraphError::from(e)),
-                }
 
-                // Insert into out_edges_db
-                let label_hash = hash_label(edge.label, None);
-                if let Err(e) = self.storage.out_edges_db.put_with_flags(
-                    self.txn,
-                    PutFlags::APPEND_DUP,
-                    &HelixGraphStorage::out_edge_key(&from_node, &label_hash),
-                    &HelixGraphStorage::pack_edge_data(&edge.id, &to_node),
-                ) {
-                    result = Err(GraphError::from(e));
-                }
+                    // Insert into out_edges_db
+                    let label_hash = hash_label(edge.label, None);
+                    if let Err(e) = self.storage.out_edges_db.put_with_flags(
+                        self.txn,
+                        PutFlags::APPEND_DUP,
+                        &HelixGraphStorage::out_edge_key(&from_node, &label_hash),
+                        &HelixGraphStorage::pack_edge_data(&edge.id, &to_node),
+                    ) {
+                        result = Err(GraphError::from(e));
+                    }
 
-                // Insert into in_edges_db
-                if let Err(e) = self.storage.in_edges_db.put_with_flags(
-                    self.txn,
-                    PutFlags::APPEND_DUP,
-                    &HelixGraphStorage::in_edge_key(&to_node, &label_hash),
-                    &HelixGraphStorage::pack_edge_data(&edge.id, &from_node),
-                ) {
-                    result = Err(GraphError::from(e));
-                }
+                    // Insert into in_edges_db
+                    if let Err(e) = self.storage.in_edges_db.put_with_flags(
+                        self.txn,
+                        PutFlags::APPEND_DUP,
+                        &HelixGraphStorage::in_edge_key(&to_node, &label_hash),
+                        &HelixGraphStorage::pack_edge_data(&edge.id, &from_node),
+                    ) {
+                        result = Err(GraphError::from(e));
+                    }
 
-                if result.is_ok() {
-                    result = Ok(TraversalValue::Edge(edge));
+                    if result.is_ok()
+                        && let Err(e) =
+                            self.storage
+                                .count_edge(self.txn, &label_hash, from_node, to_node, 1)
+                    {
+                        result = Err(e);
+                    }
+
+                    if result.is_ok()
+                        && let Err(e) = triggers::fire(
+                            self.storage,
+                            self.txn,
+                            TriggerEvent::AddE,
+                            label,
+                            edge.id,
+                        )
+                    {
+                        result = Err(e);
+                    }
+
+                    if result.is_ok() {
+                        result = Ok(TraversalValue::Edge(edge));
+                    }
                 }
-            }
+            },
             Some(Ok(_)) => {
                 // Non-edge value in iterator - ignore
             }
         }
 
+        write_log::record_value(&result);
         RwTraversalIterator {
             storage: self.storage,
             arena: self.arena,
@@ -954,6 +1014,7 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             }
         }
 
+        write_log::record_value(&result);
         RwTraversalIterator {
             storage: self.storage,
             arena: self.arena,
@@ -961,4 +1022,75 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             inner: std::iter::once(result),
         }
     }
+
+    fn upsert_v_embedded(
+        mut self,
+        query: &'arena [f64],
+        content: &str,
+        label: &'arena str,
+        props: &[(&'static str, Value)],
+    ) -> RwTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    > {
+        let hash = content_hash::hash(content);
+        let vectors = &self.storage.vectors;
+
+        // Resolve the existing vector and whether its content changed before merging properties
+        let mut reembedded = true;
+        let existing = match self.inner.next() {
+            Some(Ok(TraversalValue::Vector(vector))) => Ok(vector),
+            Some(Ok(TraversalValue::VectorNodeWithoutVectorData(vector))) => Ok(vector.into()),
+            other => Err(other),
+        };
+        let item = match existing {
+            Ok(mut vector) => Some(match vectors.content_hash(self.txn, vector.id) {
+                Ok(Some(stored)) if stored == hash => {
+                    reembedded = false;
+                    if vector.data.is_empty() {
+                        vectors
+                            .get_full_vector(self.txn, vector.id, self.arena)
+                            .map(|full| {
+                                vector.data = full.data;
+                                TraversalValue::Vector(vector)
+                            })
+                            .map_err(GraphError::from)
+                    } else {
+                        Ok(TraversalValue::Vector(vector))
+                    }
+                }
+                Ok(_) => vectors
+                    .reembed(self.txn, &mut vector, query, self.arena)
+                    .map(|_| TraversalValue::Vector(vector))
+                    .map_err(GraphError::from),
+                Err(e) => Err(GraphError::from(e)),
+            }),
+            Err(other) => other,
+        };
+
+        let mut upserted = RwTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: item.into_iter(),
+        }
+        .upsert_v(query, label, props);
+
+        let mut result = upserted.inner.next().unwrap_or(Ok(TraversalValue::Empty));
+        if let Ok(TraversalValue::Vector(ref mut vector)) = result {
+            vector.reembedded = reembedded;
+            if reembedded && let Err(e) = vectors.set_content_hash(upserted.txn, vector, hash) {
+                result = Err(GraphError::from(e));
+            }
+        }
+
+        RwTraversalIterator {
+            storage: upserted.storage,
+            arena: upserted.arena,
+            txn: upserted.txn,
+            inner: std::iter::once(result),
+        }
+    }
 }
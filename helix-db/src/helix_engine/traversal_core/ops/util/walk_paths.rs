@@ -0,0 +1,167 @@
+use crate::{
+    helix_engine::{
+        storage_core::{HelixGraphStorage, storage_methods::StorageMethods},
+        traversal_core::{traversal_iter::RoTraversalIterator, traversal_value::TraversalValue},
+        types::GraphError,
+    },
+    protocol::value::{Path, PathEdge, PathNode, Value},
+    utils::{
+        items::{Edge, Node},
+        label_hash::hash_label,
+    },
+};
+
+/// One step of the walk [`PathsAdapter::paths`] follows from each node
+#[derive(Debug, Clone, Copy)]
+pub enum PathHop<'s> {
+    /// Follow an outgoing edge with the label to the node at its other end
+    Out(&'s str),
+    /// Follow an incoming edge with the label back to the node it comes from
+    In(&'s str),
+}
+
+pub trait PathsAdapter<'db, 'arena, 'txn, 's>: Iterator {
+    /// Paths follows `hops` from each of the current nodes, like chaining their `Out` and `In`
+    /// steps, but returns the whole path to each node reached instead of only the node
+    ///
+    /// # Arguments
+    ///
+    /// * `hops` - The edges to follow from each node, in order
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let traversal = G::new(storage, &txn)
+    ///     .n_from_id(&id)
+    ///     .paths(&[PathHop::Out("follows"), PathHop::Out("follows")]);
+    /// ```
+    fn paths(
+        self,
+        hops: &'s [PathHop<'s>],
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    >;
+}
+
+impl<'db, 'arena, 'txn, 's, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
+    PathsAdapter<'db, 'arena, 'txn, 's> for RoTraversalIterator<'db, 'arena, 'txn, I>
+{
+    fn paths(
+        self,
+        hops: &'s [PathHop<'s>],
+    ) -> RoTraversalIterator<
+        'db,
+        'arena,
+        'txn,
+        impl Iterator<Item = Result<TraversalValue<'arena>, GraphError>>,
+    > {
+        let storage = self.storage;
+        let txn = self.txn;
+        let arena = self.arena;
+        let iter = self.inner.flat_map(move |item| {
+            let start = match item {
+                Ok(TraversalValue::Node(node)) => node,
+                Ok(_) => return vec![],
+                Err(e) => return vec![Err(e)],
+            };
+            let mut path = Path {
+                nodes: vec![path_node(&start)],
+                edges: vec![],
+            };
+            let mut paths = Vec::new();
+            let walked = walk(storage, txn, arena, start.id, hops, &mut path, &mut paths);
+            let mut results = paths
+                .into_iter()
+                .map(|path| Ok(TraversalValue::Value(Value::Path(path))))
+                .collect::<Vec<_>>();
+            if let Err(e) = walked {
+                results.push(Err(e));
+            }
+            results
+        });
+
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: iter,
+        }
+    }
+}
+
+/// Follow `hops` from `node`, which `path` ends at, adding a copy of `path` to `paths` for
+/// each node the last hop reaches. Edges to vectors aren't followed.
+fn walk(
+    storage: &HelixGraphStorage,
+    txn: &heed3::RoTxn,
+    arena: &bumpalo::Bump,
+    node: u128,
+    hops: &[PathHop],
+    path: &mut Path,
+    paths: &mut Vec<Path>,
+) -> Result<(), GraphError> {
+    let Some((hop, rest)) = hops.split_first() else {
+        paths.push(path.clone());
+        return Ok(());
+    };
+    let (db, key) = match hop {
+        PathHop::Out(label) => (
+            &storage.out_edges_db,
+            HelixGraphStorage::out_edge_key(&node, &hash_label(label, None)),
+        ),
+        PathHop::In(label) => (
+            &storage.in_edges_db,
+            HelixGraphStorage::in_edge_key(&node, &hash_label(label, None)),
+        ),
+    };
+    let Some(adjacent) = db.get_duplicates(txn, &key)? else {
+        return Ok(());
+    };
+    for entry in adjacent {
+        let (_, value) = entry?;
+        let (edge_id, next) = HelixGraphStorage::unpack_adj_edge_data(value)?;
+        let next_node = match storage.get_node(txn, &next, arena) {
+            Ok(next_node) => next_node,
+            Err(GraphError::NodeNotFound) => continue,
+            Err(e) => return Err(e),
+        };
+        let edge = storage.get_edge(txn, &edge_id, arena)?;
+        path.nodes.push(path_node(&next_node));
+        path.edges.push(path_edge(&edge));
+        walk(storage, txn, arena, next, rest, path, paths)?;
+        path.nodes.pop();
+        path.edges.pop();
+    }
+    Ok(())
+}
+
+fn path_node(node: &Node<'_>) -> PathNode {
+    PathNode {
+        id: node.id,
+        label: node.label.to_string(),
+        properties: node
+            .properties
+            .iter()
+            .flat_map(|properties| properties.iter())
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+    }
+}
+
+fn path_edge(edge: &Edge<'_>) -> PathEdge {
+    PathEdge {
+        id: edge.id,
+        label: edge.label.to_string(),
+        from_node: edge.from_node,
+        to_node: edge.to_node,
+        properties: edge
+            .properties
+            .iter()
+            .flat_map(|properties| properties.iter())
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     helix_engine::{
+        storage_core::write_log,
         traversal_core::{traversal_iter::RwTraversalIterator, traversal_value::TraversalValue},
         types::GraphError,
         vector_core::{hnsw::HNSW, vector::HVector},
@@ -52,6 +53,7 @@ impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphE
             Ok(vector) => Ok(TraversalValue::Vector(vector)),
             Err(e) => Err(GraphError::from(e)),
         };
+        write_log::record_value(&result);
 
         RwTraversalIterator {
             inner: std::iter::once(result),
@@ -0,0 +1,64 @@
+//! Per-thread timings of the steps of the running query.
+//!
+//! Generated handlers wrap each top-level statement of a query, such as one traversal
+//! assigned to a variable, in [`start`] and [`record`]. The worker turns profiling on
+//! before a handler starts when slow queries are logged and takes the steps afterwards,
+//! so a slow query log can say which step was slow and how much it read.
+
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+use crate::helix_engine::storage_core::scan_counter;
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static STEPS: RefCell<Vec<StepTiming>> = const { RefCell::new(Vec::new()) };
+}
+
+/// How long one step of a query took and how many records it read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepTiming {
+    pub step: &'static str,
+    pub elapsed: Duration,
+    pub items_scanned: u64,
+}
+
+/// Where a step started, taken by [`start`]
+pub struct StepStart {
+    at: Instant,
+    scanned: u64,
+}
+
+/// Clear the current thread's steps and turn profiling on or off for the next handler
+pub fn reset(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+    STEPS.with_borrow_mut(Vec::clear);
+}
+
+/// Start timing a step, if profiling is on
+#[inline]
+pub fn start() -> Option<StepStart> {
+    ENABLED.with(Cell::get).then(|| StepStart {
+        at: Instant::now(),
+        scanned: scan_counter::current(),
+    })
+}
+
+/// Record the step `step` begun at `start`
+#[inline]
+pub fn record(step: &'static str, start: Option<StepStart>) {
+    if let Some(start) = start {
+        let timing = StepTiming {
+            step,
+            elapsed: start.at.elapsed(),
+            items_scanned: scan_counter::current().saturating_sub(start.scanned),
+        };
+        STEPS.with_borrow_mut(|steps| steps.push(timing));
+    }
+}
+
+/// Take the current thread's steps and turn profiling off
+pub fn take() -> Vec<StepTiming> {
+    ENABLED.with(|e| e.set(false));
+    STEPS.with_borrow_mut(std::mem::take)
+}
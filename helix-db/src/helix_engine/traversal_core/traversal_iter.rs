@@ -31,9 +31,31 @@ where
     }
 }
 
+/// A read traversal whose iterator type is erased
+pub type BoxedRoTraversalIterator<'db, 'arena, 'txn> = RoTraversalIterator<
+    'db,
+    'arena,
+    'txn,
+    Box<dyn Iterator<Item = Result<TraversalValue<'arena>, GraphError>> + 'txn>,
+>;
+
 impl<'db, 'arena, 'txn, I: Iterator<Item = Result<TraversalValue<'arena>, GraphError>>>
     RoTraversalIterator<'db, 'arena, 'txn, I>
 {
+    /// Erases the iterator's type, so the steps after it are compiled once for every traversal
+    /// rather than once for each chain of steps, at the cost of a dynamic call per item
+    pub fn boxed(self) -> BoxedRoTraversalIterator<'db, 'arena, 'txn>
+    where
+        I: 'txn,
+    {
+        RoTraversalIterator {
+            storage: self.storage,
+            arena: self.arena,
+            txn: self.txn,
+            inner: Box::new(self.inner),
+        }
+    }
+
     pub fn take_and_collect_to<B: FromIterator<TraversalValue<'arena>>>(self, n: usize) -> B {
         self.inner
             .filter_map(|item| item.ok())
@@ -3,7 +3,10 @@ use serde::Serialize;
 use crate::{
     helix_engine::vector_core::{vector::HVector, vector_without_data::VectorWithoutData},
     protocol::value::Value,
-    utils::items::{Edge, Node},
+    utils::{
+        id::uuid_str_from_buf,
+        items::{Edge, Node},
+    },
 };
 use std::{borrow::Cow, hash::Hash};
 
@@ -23,11 +26,23 @@ pub enum TraversalValue<'arena> {
     /// A count of the number of items
     /// A path between two nodes in the graph
     Path((Vec<Node<'arena>>, Vec<Edge<'arena>>)),
+    /// The nodes around a traversal's nodes, with the edges between them
+    Subgraph {
+        nodes: Vec<Node<'arena>>,
+        #[serde(serialize_with = "serialize_subgraph_edges")]
+        edges: Vec<Edge<'arena>>,
+    },
     /// A value in the graph
     Value(Value),
 
     /// Item With Score
-    NodeWithScore { node: Node<'arena>, score: f64 },
+    NodeWithScore {
+        node: Node<'arena>,
+        score: f64,
+        /// The score the search gave the node, kept once a reranker replaces `score`
+        #[serde(skip)]
+        search_score: Option<f64>,
+    },
     /// An empty traversal value
     #[default]
     Empty,
@@ -91,6 +106,56 @@ impl<'arena> TraversalValue<'arena> {
         }
     }
 
+    /// The BM25 score a text search gave the item, even after it was reranked
+    pub fn bm25_score(&self) -> Option<f64> {
+        match self {
+            TraversalValue::NodeWithScore {
+                score,
+                search_score,
+                ..
+            } => Some(search_score.unwrap_or(*score)),
+            _ => None,
+        }
+    }
+
+    /// The distance a vector search gave the item, even after it was reranked
+    pub fn vector_distance(&self) -> Option<f64> {
+        match self {
+            TraversalValue::Vector(vector) => vector.search_distance.or(vector.distance),
+            _ => None,
+        }
+    }
+
+    /// The score the last reranker gave the item, if one reranked it
+    pub fn rerank_score(&self) -> Option<f64> {
+        match self {
+            TraversalValue::Vector(vector) => vector.search_distance.and(vector.distance),
+            TraversalValue::NodeWithScore {
+                score,
+                search_score,
+                ..
+            } => search_score.map(|_| *score),
+            _ => None,
+        }
+    }
+
+    /// The item's score along with the search and rerank scores it was computed from
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
+        ScoreBreakdown {
+            score: self.score(),
+            bm25_score: self.bm25_score(),
+            vector_distance: self.vector_distance(),
+            rerank_score: self.rerank_score(),
+        }
+    }
+
+    pub fn reembedded(&self) -> bool {
+        match self {
+            TraversalValue::Vector(vector) => vector.reembedded(),
+            _ => false,
+        }
+    }
+
     pub fn label_arena(&self) -> &'arena str {
         match self {
             TraversalValue::Node(node) => node.label,
@@ -116,6 +181,38 @@ impl<'arena> TraversalValue<'arena> {
     }
 }
 
+/// How a search result's score came about, for debugging relevance
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScoreBreakdown {
+    /// The score the item is ranked by
+    pub score: f64,
+    /// The BM25 score of a text search result
+    pub bm25_score: Option<f64>,
+    /// The distance of a vector search result
+    pub vector_distance: Option<f64>,
+    /// The score of the last reranker, if the item was reranked
+    pub rerank_score: Option<f64>,
+}
+
+impl From<ScoreBreakdown> for Value {
+    fn from(breakdown: ScoreBreakdown) -> Self {
+        let score = |score: Option<f64>| score.map(Value::F64).unwrap_or(Value::Empty);
+        Value::Object(
+            [
+                ("score".to_string(), Value::F64(breakdown.score)),
+                ("bm25_score".to_string(), score(breakdown.bm25_score)),
+                (
+                    "vector_distance".to_string(),
+                    score(breakdown.vector_distance),
+                ),
+                ("rerank_score".to_string(), score(breakdown.rerank_score)),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+}
+
 impl Hash for TraversalValue<'_> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
@@ -160,3 +257,44 @@ impl PartialEq for TraversalValue<'_> {
         }
     }
 }
+
+/// Serializes the edges of a subgraph with their endpoints as uuid strings, so they can be
+/// matched against the ids of the subgraph's nodes
+fn serialize_subgraph_edges<S>(edges: &[Edge<'_>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::{SerializeMap, SerializeSeq};
+
+    struct SubgraphEdge<'a, 'arena>(&'a Edge<'arena>);
+
+    impl Serialize for SubgraphEdge<'_, '_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let edge = self.0;
+            let mut buffer = [0u8; 36];
+            let mut state = serializer.serialize_map(Some(
+                5 + edge.properties.as_ref().map(|p| p.len()).unwrap_or(0),
+            ))?;
+            state.serialize_entry("id", uuid_str_from_buf(edge.id, &mut buffer))?;
+            state.serialize_entry("label", edge.label)?;
+            state.serialize_entry("version", &edge.version)?;
+            state.serialize_entry("from_node", uuid_str_from_buf(edge.from_node, &mut buffer))?;
+            state.serialize_entry("to_node", uuid_str_from_buf(edge.to_node, &mut buffer))?;
+            if let Some(properties) = &edge.properties {
+                for (key, value) in properties.iter() {
+                    state.serialize_entry(key, value)?;
+                }
+            }
+            state.end()
+        }
+    }
+
+    let mut seq = serializer.serialize_seq(Some(edges.len()))?;
+    for edge in edges {
+        seq.serialize_element(&SubgraphEdge(edge))?;
+    }
+    seq.end()
+}
@@ -1,15 +1,18 @@
 use crate::{
+    helix_engine::udf::UdfError,
     helix_gateway::router::router::IoContFn,
     helixc::parser::{
         errors::ParserError,
-        types::{Field, FieldPrefix},
+        types::{EdgeSchema, Field, FieldPrefix, GraphStepType, StepType},
     },
+    protocol::params::ParamError,
 };
 use core::fmt;
 use heed3::{Error as HeedError, MdbError};
 use serde::{Deserialize, Serialize};
 use sonic_rs::Error as SonicError;
 use std::{fmt::Display, net::AddrParseError, str::Utf8Error, string::FromUtf8Error};
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub enum GraphError {
@@ -38,6 +41,11 @@ pub enum GraphError {
     IoNeeded(IoContFn),
     RerankerError(String),
     DuplicateKey(String),
+    UdfError(String),
+    ConstraintViolation(ConstraintViolation),
+    CycleDetected(String),
+    MergeConflict(String),
+    InvalidParams(Vec<ParamError>),
 }
 
 impl std::error::Error for GraphError {}
@@ -78,6 +86,16 @@ impl fmt::Display for GraphError {
             GraphError::DuplicateKey(msg) => {
                 write!(f, "Duplicate key on unique index: {msg}")
             }
+            GraphError::UdfError(msg) => write!(f, "User-defined function error: {msg}"),
+            GraphError::ConstraintViolation(violation) => {
+                write!(f, "Constraint violated: {violation}")
+            }
+            GraphError::CycleDetected(msg) => write!(f, "Cycle detected: {msg}"),
+            GraphError::MergeConflict(msg) => write!(f, "Merge conflict: {msg}"),
+            GraphError::InvalidParams(errors) => {
+                let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "Invalid parameters: {}", errors.join("; "))
+            }
         }
     }
 }
@@ -157,6 +175,12 @@ impl From<VectorError> for GraphError {
     }
 }
 
+impl From<UdfError> for GraphError {
+    fn from(error: UdfError) -> Self {
+        GraphError::UdfError(error.to_string())
+    }
+}
+
 #[derive(Debug)]
 pub enum VectorError {
     VectorNotFound(String),
@@ -167,6 +191,8 @@ pub enum VectorError {
     ConversionError(String),
     VectorCoreError(String),
     VectorAlreadyDeleted(String),
+    LabelMismatch { id: String, label: String },
+    IndexSwitch(String),
 }
 
 impl std::error::Error for VectorError {}
@@ -182,6 +208,14 @@ impl fmt::Display for VectorError {
             VectorError::ConversionError(msg) => write!(f, "Conversion error: {msg}"),
             VectorError::VectorCoreError(msg) => write!(f, "Vector core error: {msg}"),
             VectorError::VectorAlreadyDeleted(id) => write!(f, "Vector already deleted: {id}"),
+            VectorError::LabelMismatch { id, label } => {
+                write!(f, "Vector {id} is already stored as a {label}")
+            }
+            VectorError::IndexSwitch(label) => write!(
+                f,
+                "Vector type {label} already has vectors in HNSW and can't be switched to IVF-Flat; \
+                 configure IVF-Flat before storing any {label}"
+            ),
         }
     }
 }
@@ -242,3 +276,146 @@ impl SecondaryIndex {
         }
     }
 }
+
+/// A node property kept as the number of the node's edges of a type, declared in the schema
+/// as e.g. `follower_count: COUNT(_::In<Follows>) @materialized`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MaterializedCount {
+    pub node_label: String,
+    pub field: String,
+    pub edge_label: String,
+    /// Counts the edges going to the node rather than those leaving it
+    pub incoming: bool,
+}
+
+impl MaterializedCount {
+    /// The count of a node's materialized computed field, when it counts a single `In`, `Out`,
+    /// `InE` or `OutE` step of an edge type
+    pub fn from_field(node_label: &str, field: &Field) -> Option<Self> {
+        let computed = field.computed.as_ref().filter(|c| c.materialized)?;
+        let [step] = computed.traversal.steps.as_slice() else {
+            return None;
+        };
+        let (edge_label, incoming) = match &step.step {
+            StepType::Node(graph_step) | StepType::Edge(graph_step) => match &graph_step.step {
+                GraphStepType::In(label) | GraphStepType::InE(label) => (label, true),
+                GraphStepType::Out(label) | GraphStepType::OutE(label) => (label, false),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        (!edge_label.is_empty()).then(|| MaterializedCount {
+            node_label: node_label.to_string(),
+            field: field.name.clone(),
+            edge_label: edge_label.clone(),
+            incoming,
+        })
+    }
+}
+
+impl Display for MaterializedCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "MaterializedCount {{ node_label: \"{}\".to_string(), field: \"{}\".to_string(), edge_label: \"{}\".to_string(), incoming: {} }}",
+            self.node_label, self.field, self.edge_label, self.incoming
+        )
+    }
+}
+
+/// Bounds on the number of edges of a type each node at one of its ends has, declared in the
+/// schema after the end's node type, e.g. `E::AuthoredBy { From: Comment [1], To: User }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EdgeConstraint {
+    pub node_label: String,
+    pub edge_label: String,
+    /// Bounds the edges going to the node rather than those leaving it
+    pub incoming: bool,
+    pub min: u32,
+    /// `None` for no upper bound
+    pub max: Option<u32>,
+}
+
+impl EdgeConstraint {
+    /// The constraints declared on the ends of an edge type
+    pub fn from_schema(edge: &EdgeSchema) -> Vec<Self> {
+        [
+            (&edge.from.1, &edge.from_cardinality, false),
+            (&edge.to.1, &edge.to_cardinality, true),
+        ]
+        .into_iter()
+        .filter_map(|(node_label, cardinality, incoming)| {
+            let cardinality = cardinality.as_ref()?;
+            Some(EdgeConstraint {
+                node_label: node_label.clone(),
+                edge_label: edge.name.1.clone(),
+                incoming,
+                min: cardinality.min,
+                max: cardinality.max,
+            })
+        })
+        .collect()
+    }
+
+    /// Whether a node with `edges` of the edges meets the constraint
+    pub fn allows(&self, edges: u64) -> bool {
+        edges >= self.min as u64 && self.max.is_none_or(|max| edges <= max as u64)
+    }
+}
+
+impl Display for EdgeConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "EdgeConstraint {{ node_label: \"{}\".to_string(), edge_label: \"{}\".to_string(), incoming: {}, min: {}, max: {:?} }}",
+            self.node_label, self.edge_label, self.incoming, self.min, self.max
+        )
+    }
+}
+
+/// A node whose edges of a type are outside the bounds of an [`EdgeConstraint`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    pub node: Uuid,
+    pub constraint: EdgeConstraint,
+    /// The number of the edges the node has
+    pub edges: u64,
+}
+
+impl ConstraintViolation {
+    /// What the node's edges should be, e.g. `must have exactly 1 outgoing AuthoredBy edge,
+    /// it has 0`
+    pub fn requirement(&self) -> String {
+        let EdgeConstraint {
+            edge_label,
+            incoming,
+            min,
+            max,
+            ..
+        } = &self.constraint;
+        let (bounds, most) = match (min, max) {
+            (min, Some(max)) if min == max => (format!("exactly {max}"), *max),
+            (0, Some(max)) => (format!("at most {max}"), *max),
+            (min, Some(max)) => (format!("between {min} and {max}"), *max),
+            (min, None) => (format!("at least {min}"), *min),
+        };
+        format!(
+            "must have {bounds} {} {edge_label} edge{}, it has {}",
+            if *incoming { "incoming" } else { "outgoing" },
+            if most == 1 { "" } else { "s" },
+            self.edges
+        )
+    }
+}
+
+impl Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.constraint.node_label,
+            self.node,
+            self.requirement()
+        )
+    }
+}
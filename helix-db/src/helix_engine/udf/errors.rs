@@ -0,0 +1,23 @@
+//! Error types for user-defined functions.
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UdfError {
+    #[error("Unknown user-defined function `{0}`")]
+    NotFound(String),
+
+    #[error("Failed to load user-defined function `{name}`: {message}")]
+    Load { name: String, message: String },
+
+    #[error("Invalid arguments to `{name}`: {message}")]
+    InvalidArguments { name: String, message: String },
+
+    #[error("`{0}` ran out of fuel")]
+    OutOfFuel(String),
+
+    #[error("`{name}` trapped: {message}")]
+    Trap { name: String, message: String },
+}
+
+pub type UdfResult<T> = Result<T, UdfError>;
@@ -0,0 +1,14 @@
+//! User-defined functions loaded from WebAssembly modules.
+//!
+//! Instances register modules in `GatewayConfig.udfs`, and HQL calls them with
+//! `CALL name(args)`, e.g. in a projection `::{score: CALL boost(_::{rating}, 2)}`.
+//! Modules run in a sandbox: they can't import host functions, each call gets a fresh
+//! instance, and calls are limited by fuel and linear memory. Arguments and results are
+//! WebAssembly numbers (i32, i64, f32, f64), and queries see every result as an F64. A call
+//! that fails fails the query.
+
+pub mod errors;
+pub mod udfs;
+
+pub use errors::{UdfError, UdfResult};
+pub use udfs::Udfs;
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use wasmi::{
+    Engine, F32, F64, FuncType, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, TrapCode,
+    Val, ValType,
+};
+
+use crate::{
+    helix_engine::{
+        traversal_core::config::UdfConfig,
+        udf::errors::{UdfError, UdfResult},
+    },
+    protocol::value::Value,
+};
+
+/// User-defined functions an instance loaded, by the name queries call them by
+pub struct Udfs {
+    engine: Engine,
+    functions: HashMap<String, Udf>,
+}
+
+struct Udf {
+    module: Module,
+    export: String,
+    ty: FuncType,
+    fuel: u64,
+    max_memory_bytes: usize,
+}
+
+impl Default for Udfs {
+    fn default() -> Self {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        Udfs {
+            engine: Engine::new(&config),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+impl Udfs {
+    /// Load the modules `configs` point to
+    pub fn from_config(configs: &[UdfConfig]) -> UdfResult<Self> {
+        let mut udfs = Udfs::default();
+        for config in configs {
+            let wasm = std::fs::read(&config.path).map_err(|e| UdfError::Load {
+                name: config.name.clone(),
+                message: format!("{}: {e}", config.path.display()),
+            })?;
+            udfs.load(config, &wasm)?;
+        }
+        Ok(udfs)
+    }
+
+    /// Compile `wasm` and register its export as `config.name`. The module may not import
+    /// anything, and the function must take only numbers and return one.
+    pub fn load(&mut self, config: &UdfConfig, wasm: &[u8]) -> UdfResult<()> {
+        let load_error = |message: String| UdfError::Load {
+            name: config.name.clone(),
+            message,
+        };
+        let module = Module::new(&self.engine, wasm).map_err(|e| load_error(e.to_string()))?;
+        if let Some(import) = module.imports().next() {
+            return Err(load_error(format!(
+                "modules can't import anything, but it imports `{}::{}`",
+                import.module(),
+                import.name()
+            )));
+        }
+        let ty = module
+            .get_export(config.export())
+            .and_then(|export| export.func().cloned())
+            .ok_or_else(|| load_error(format!("no exported function `{}`", config.export())))?;
+        if !ty.params().iter().chain(ty.results()).all(is_number) || ty.results().len() != 1 {
+            return Err(load_error(format!(
+                "`{}` must take only i32, i64, f32 or f64 values and return one",
+                config.export()
+            )));
+        }
+        self.functions.insert(
+            config.name.clone(),
+            Udf {
+                module,
+                export: config.export().to_string(),
+                ty,
+                fuel: config.fuel(),
+                max_memory_bytes: config.max_memory_bytes(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Call `name` in a fresh instance, limited to the function's fuel and memory
+    pub fn call(&self, name: &str, args: &[Value]) -> UdfResult<Value> {
+        let udf = self
+            .functions
+            .get(name)
+            .ok_or_else(|| UdfError::NotFound(name.to_string()))?;
+        if args.len() != udf.ty.params().len() {
+            return Err(UdfError::InvalidArguments {
+                name: name.to_string(),
+                message: format!(
+                    "expected {} argument(s), got {}",
+                    udf.ty.params().len(),
+                    args.len()
+                ),
+            });
+        }
+        let inputs = udf
+            .ty
+            .params()
+            .iter()
+            .zip(args)
+            .map(|(ty, arg)| {
+                to_val(*ty, arg).ok_or_else(|| UdfError::InvalidArguments {
+                    name: name.to_string(),
+                    message: format!("can't pass {arg:?} as {ty:?}"),
+                })
+            })
+            .collect::<UdfResult<Vec<_>>>()?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(udf.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store
+            .set_fuel(udf.fuel)
+            .expect("fuel metering should be enabled on the engine");
+        let trap = |e: wasmi::Error| match e.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => UdfError::OutOfFuel(name.to_string()),
+            _ => UdfError::Trap {
+                name: name.to_string(),
+                message: e.to_string(),
+            },
+        };
+        let instance = Linker::new(&self.engine)
+            .instantiate_and_start(&mut store, &udf.module)
+            .map_err(trap)?;
+        let func = instance
+            .get_func(&store, &udf.export)
+            .expect("export should have been checked when the module was loaded");
+        let mut outputs = udf
+            .ty
+            .results()
+            .iter()
+            .map(|ty| Val::default(*ty))
+            .collect::<Vec<_>>();
+        func.call(&mut store, &inputs, &mut outputs).map_err(trap)?;
+        Ok(outputs.first().map_or(Value::Empty, from_val))
+    }
+
+    /// Call `name` from a query, which sees the result as an F64 whatever number type the
+    /// function returns. Failures fail the query.
+    pub fn eval(&self, name: &str, args: &[Value]) -> UdfResult<f64> {
+        match self.call(name, args)? {
+            Value::I32(v) => Ok(f64::from(v)),
+            Value::I64(v) => Ok(v as f64),
+            Value::F32(v) => Ok(f64::from(v)),
+            Value::F64(v) => Ok(v),
+            other => Err(UdfError::Trap {
+                name: name.to_string(),
+                message: format!("returned {other:?} rather than a number"),
+            }),
+        }
+    }
+}
+
+fn is_number(ty: &ValType) -> bool {
+    matches!(
+        ty,
+        ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64
+    )
+}
+
+fn to_val(ty: ValType, value: &Value) -> Option<Val> {
+    let integer = match *value {
+        Value::I8(v) => Some(i64::from(v)),
+        Value::I16(v) => Some(i64::from(v)),
+        Value::I32(v) => Some(i64::from(v)),
+        Value::I64(v) => Some(v),
+        Value::U8(v) => Some(i64::from(v)),
+        Value::U16(v) => Some(i64::from(v)),
+        Value::U32(v) => Some(i64::from(v)),
+        Value::U64(v) => i64::try_from(v).ok(),
+        _ => None,
+    };
+    let float = match *value {
+        Value::F32(v) => Some(f64::from(v)),
+        Value::F64(v) => Some(v),
+        _ => integer.map(|v| v as f64),
+    };
+    match ty {
+        ValType::I32 => integer.and_then(|v| i32::try_from(v).ok()).map(Val::I32),
+        ValType::I64 => integer.map(Val::I64),
+        ValType::F32 => float.map(|v| Val::F32(F32::from_float(v as f32))),
+        ValType::F64 => float.map(|v| Val::F64(F64::from_float(v))),
+        _ => None,
+    }
+}
+
+fn from_val(val: &Val) -> Value {
+    match val {
+        Val::I32(v) => Value::I32(*v),
+        Val::I64(v) => Value::I64(*v),
+        Val::F32(v) => Value::F32(v.to_float()),
+        Val::F64(v) => Value::F64(v.to_float()),
+        _ => Value::Empty,
+    }
+}
@@ -0,0 +1,90 @@
+//! Hashes of the content vectors were embedded from.
+//!
+//! A vector upserted from text records the hash of that text. Upserting it again with the same
+//! text keeps the stored embedding: the embedding model isn't called and the vector isn't moved
+//! in its index. The hash is also looked up by type, so text already embedded for another vector
+//! of the type reuses that embedding instead of calling the model.
+//!
+//! Keys in the `vector_content_hashes` table:
+//! - `i` + id: the content hash of the vector
+//! - `h` + label hash + content hash: the id of the last vector of the type embedded from it
+
+use crate::{helix_engine::types::VectorError, utils::label_hash::hash_label};
+use heed3::{Database, RoTxn, RwTxn, types::Bytes};
+
+const ID_PREFIX: u8 = b'i';
+const HASH_PREFIX: u8 = b'h';
+
+/// The hash of the content a vector is embedded from
+#[inline]
+pub fn hash(content: &str) -> u64 {
+    twox_hash::XxHash3_64::oneshot(content.as_bytes())
+}
+
+#[inline(always)]
+fn id_key(id: u128) -> [u8; 17] {
+    let mut key = [0u8; 17];
+    key[0] = ID_PREFIX;
+    key[1..].copy_from_slice(&id.to_be_bytes());
+    key
+}
+
+#[inline(always)]
+fn hash_key(label: &str, hash: u64) -> [u8; 13] {
+    let mut key = [0u8; 13];
+    key[0] = HASH_PREFIX;
+    key[1..5].copy_from_slice(&hash_label(label, None));
+    key[5..].copy_from_slice(&hash.to_be_bytes());
+    key
+}
+
+/// The content hash recorded for the vector `id`
+pub fn get(db: Database<Bytes, Bytes>, txn: &RoTxn, id: u128) -> Result<Option<u64>, VectorError> {
+    Ok(db
+        .get(txn, &id_key(id))?
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap())))
+}
+
+/// The last vector of `label` recorded with the content hash
+pub fn find(
+    db: Database<Bytes, Bytes>,
+    txn: &RoTxn,
+    label: &str,
+    hash: u64,
+) -> Result<Option<u128>, VectorError> {
+    Ok(db
+        .get(txn, &hash_key(label, hash))?
+        .map(|bytes| u128::from_be_bytes(bytes.try_into().unwrap())))
+}
+
+/// Record the content hash of the vector `id`, replacing the one recorded before
+pub fn put(
+    db: Database<Bytes, Bytes>,
+    txn: &mut RwTxn,
+    id: u128,
+    label: &str,
+    hash: u64,
+) -> Result<(), VectorError> {
+    remove(db, txn, id, label)?;
+    db.put(txn, &id_key(id), &hash.to_be_bytes())?;
+    db.put(txn, &hash_key(label, hash), &id.to_be_bytes())?;
+    Ok(())
+}
+
+/// Forget the content hash of the vector `id`
+pub fn remove(
+    db: Database<Bytes, Bytes>,
+    txn: &mut RwTxn,
+    id: u128,
+    label: &str,
+) -> Result<(), VectorError> {
+    let Some(old) = get(db, txn, id)? else {
+        return Ok(());
+    };
+    db.delete(txn, &id_key(id))?;
+    // Another vector embedded from the same content may have taken the lookup over since
+    if find(db, txn, label, old)? == Some(id) {
+        db.delete(txn, &hash_key(label, old))?;
+    }
+    Ok(())
+}
@@ -30,6 +30,30 @@ pub trait HNSW {
         'db: 'arena,
         'arena: 'txn;
 
+    /// Search for the k nearest neighbors of each of many query vectors
+    ///
+    /// The queries share the reads of the index, so a batch is much cheaper than searching
+    /// each query on its own.
+    ///
+    /// # Returns
+    ///
+    /// The nearest neighbors of each query, in query order
+    #[allow(clippy::too_many_arguments)]
+    fn search_batch<'db, 'arena, 'txn, F>(
+        &'db self,
+        txn: &'txn RoTxn<'db>,
+        queries: &[&'arena [f64]],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Vec<bumpalo::collections::Vec<'arena, HVector<'arena>>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn;
+
     /// Insert a new vector into the index
     ///
     /// # Arguments
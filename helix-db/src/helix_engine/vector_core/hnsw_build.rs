@@ -0,0 +1,300 @@
+//! Parallel construction of the HNSW graph, for bulk loads and index rebuilds.
+//!
+//! The graph is built in memory: the vectors already in the index are read once with their
+//! neighbor lists, then the vectors being added are linked on the rayon pool, each neighbor list
+//! behind its own lock so any number of vectors are linked at once. The lists that changed are
+//! written back in the caller's write transaction, so a build commits or rolls back with it.
+//!
+//! Like single inserts, a build keeps every vector in the graph at level 0 and links each to its
+//! `m` nearest, which link back to it while they have fewer than `m_max_0` neighbors, or replace
+//! their furthest neighbor when it is further than the new vector.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+use heed3::{RoTxn, RwTxn};
+use rayon::prelude::*;
+
+use crate::helix_engine::{
+    types::VectorError,
+    vector_core::{
+        vector::HVector,
+        vector_core::{ENTRY_POINT_KEY, VectorCore},
+        vector_distance::{MAX_DISTANCE, cosine_distance_with_norms, norm},
+        vector_index::VectorIndexKind,
+        vector_without_data::VectorWithoutData,
+    },
+};
+
+/// Vectors linked one at a time before the rest are linked in parallel, so the first vectors,
+/// which every later one is linked through, are linked to each other as closely as a sequential
+/// build would link them
+const SEQUENTIAL_SEED: usize = 256;
+
+/// A vector of the graph and its distance to the vector being linked
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f64, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// The level 0 HNSW graph held in memory while it is built
+pub struct Graph<'arena> {
+    ids: Vec<u128>,
+    data: Vec<&'arena [f64]>,
+    norms: Vec<f64>,
+    neighbors: Vec<Mutex<Vec<u32>>>,
+    changed: Vec<AtomicBool>,
+    entry: Option<u32>,
+    m: usize,
+    m_max_0: usize,
+    ef_construct: usize,
+}
+
+impl<'arena> Graph<'arena> {
+    fn new(vectors: &VectorCore) -> Self {
+        Self {
+            ids: Vec::new(),
+            data: Vec::new(),
+            norms: Vec::new(),
+            neighbors: Vec::new(),
+            changed: Vec::new(),
+            entry: None,
+            m: vectors.config.m,
+            m_max_0: vectors.config.m_max_0,
+            ef_construct: vectors.config.ef_construct,
+        }
+    }
+
+    /// The graph of the vectors in HNSW indices. With `links`, the stored neighbor lists are
+    /// read too and deleted vectors are kept, as they are in the index; without, deleted
+    /// vectors are left out and nothing is linked.
+    pub fn load(
+        vectors: &VectorCore,
+        txn: &RoTxn,
+        links: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Self, VectorError> {
+        let mut graph = Self::new(vectors);
+        let mut in_hnsw: HashMap<String, bool> = HashMap::new();
+        for item in vectors.vector_properties_db.iter(txn)? {
+            let (id, bytes) = item?;
+            let vector = VectorWithoutData::from_bincode_bytes(arena, bytes, id)?;
+            if vector.deleted && !links {
+                continue;
+            }
+            let hnsw = match in_hnsw.get(vector.label) {
+                Some(&hnsw) => hnsw,
+                None => {
+                    let hnsw = matches!(
+                        vectors.index_kind(txn, vector.label)?,
+                        VectorIndexKind::Hnsw(_)
+                    );
+                    in_hnsw.insert(vector.label.to_string(), hnsw);
+                    hnsw
+                }
+            };
+            if !hnsw {
+                continue;
+            }
+            // Records without vector data aren't in the graph; fsck reports them
+            let Some(bytes) = vectors
+                .vectors_db
+                .get(txn, &VectorCore::vector_key(id, 0))?
+            else {
+                continue;
+            };
+            let data = HVector::cast_raw_vector_data(arena, bytes);
+            graph.push(id, data);
+        }
+        for changed in &mut graph.changed {
+            *changed.get_mut() = false;
+        }
+        if !links {
+            return Ok(graph);
+        }
+
+        let index_of: HashMap<u128, u32> = graph
+            .ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index as u32))
+            .collect();
+        for item in vectors.edges_db.lazily_decode_data().iter(txn)? {
+            let (key, _) = item?;
+            // Keys are source id, level and sink id; builds only link level 0
+            if key.len() != 40 || key[16..24] != 0usize.to_be_bytes() {
+                continue;
+            }
+            let source = u128::from_be_bytes(key[..16].try_into().unwrap());
+            let sink = u128::from_be_bytes(key[24..].try_into().unwrap());
+            if let (Some(&source), Some(&sink)) = (index_of.get(&source), index_of.get(&sink))
+                && source != sink
+            {
+                graph.neighbors[source as usize]
+                    .get_mut()
+                    .unwrap()
+                    .push(sink);
+            }
+        }
+        if let Some(entry) = vectors.vectors_db.get(txn, ENTRY_POINT_KEY)?
+            && let Ok(entry) = <[u8; 16]>::try_from(entry)
+            && let Some(&entry) = index_of.get(&u128::from_be_bytes(entry))
+        {
+            graph.entry = Some(entry);
+        }
+        Ok(graph)
+    }
+
+    /// Add a vector to the graph, unlinked
+    pub fn push(&mut self, id: u128, data: &'arena [f64]) {
+        self.entry.get_or_insert(self.ids.len() as u32);
+        self.ids.push(id);
+        self.norms.push(norm(data));
+        self.data.push(data);
+        self.neighbors.push(Mutex::new(Vec::new()));
+        self.changed.push(AtomicBool::new(true));
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    #[inline]
+    fn distance(&self, a: u32, b: u32) -> f64 {
+        let (a, b) = (a as usize, b as usize);
+        cosine_distance_with_norms(self.data[a], self.norms[a], self.data[b], self.norms[b])
+            .unwrap_or(MAX_DISTANCE)
+    }
+
+    fn neighbors_of(&self, index: u32) -> Vec<u32> {
+        self.neighbors[index as usize].lock().unwrap().clone()
+    }
+
+    /// The `ef_construct` vectors nearest to `query` found from the entry point, nearest first
+    fn search(&self, query: u32, entry: u32) -> Vec<Scored> {
+        let mut visited = HashSet::from([query, entry]);
+        let start = Scored(self.distance(query, entry), entry);
+        let mut candidates = BinaryHeap::from([Reverse(start)]);
+        let mut nearest = BinaryHeap::from([start]);
+
+        while let Some(Reverse(candidate)) = candidates.pop() {
+            let furthest = nearest.peek().map_or(f64::MAX, |s| s.0);
+            if nearest.len() >= self.ef_construct && candidate.0 > furthest {
+                break;
+            }
+            for neighbor in self.neighbors_of(candidate.1) {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let scored = Scored(self.distance(query, neighbor), neighbor);
+                let furthest = nearest.peek().map_or(f64::MAX, |s| s.0);
+                if nearest.len() < self.ef_construct || scored.0 < furthest {
+                    candidates.push(Reverse(scored));
+                    nearest.push(scored);
+                    if nearest.len() > self.ef_construct {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+        nearest.into_sorted_vec()
+    }
+
+    /// Link a vector to its nearest vectors of the graph, and them back to it
+    fn link(&self, index: u32) {
+        let Some(entry) = self.entry.filter(|&entry| entry != index) else {
+            return;
+        };
+        let selected: Vec<u32> = self
+            .search(index, entry)
+            .into_iter()
+            .filter(|scored| scored.1 != index)
+            .take(self.m)
+            .map(|scored| scored.1)
+            .collect();
+        *self.neighbors[index as usize].lock().unwrap() = selected.clone();
+        self.changed[index as usize].store(true, AtomicOrdering::Relaxed);
+
+        for neighbor in selected {
+            let mut list = self.neighbors[neighbor as usize].lock().unwrap();
+            if list.contains(&index) {
+                continue;
+            }
+            list.push(index);
+            if list.len() > self.m_max_0 {
+                let mut scored: Vec<Scored> = list
+                    .iter()
+                    .map(|&other| Scored(self.distance(neighbor, other), other))
+                    .collect();
+                scored.sort_unstable();
+                scored.truncate(self.m_max_0);
+                *list = scored.into_iter().map(|scored| scored.1).collect();
+            }
+            self.changed[neighbor as usize].store(true, AtomicOrdering::Relaxed);
+        }
+    }
+
+    /// Link the vectors from `from` on, the first of them one at a time and the rest in
+    /// parallel on the rayon pool
+    pub fn link_from(&self, from: usize) {
+        let seeded = self.len().min(from.max(SEQUENTIAL_SEED));
+        for index in from..seeded {
+            self.link(index as u32);
+        }
+        (seeded..self.len())
+            .into_par_iter()
+            .for_each(|index| self.link(index as u32));
+    }
+
+    /// Write the neighbor lists that changed, and the entry point
+    pub fn write(&self, vectors: &VectorCore, txn: &mut RwTxn) -> Result<(), VectorError> {
+        for (index, changed) in self.changed.iter().enumerate() {
+            if !changed.load(AtomicOrdering::Relaxed) {
+                continue;
+            }
+            let id = self.ids[index];
+            let prefix = VectorCore::out_edges_key(id, 0, None);
+            let stale: Vec<Vec<u8>> = vectors
+                .edges_db
+                .prefix_iter(txn, &prefix)?
+                .map(|item| item.map(|(key, _)| key.to_vec()))
+                .collect::<Result<_, _>>()?;
+            for key in stale {
+                vectors.edges_db.delete(txn, &key)?;
+            }
+            for &neighbor in self.neighbors[index].lock().unwrap().iter() {
+                let key = VectorCore::out_edges_key(id, 0, Some(self.ids[neighbor as usize]));
+                vectors.edges_db.put(txn, &key, &())?;
+            }
+        }
+        match self.entry {
+            Some(entry) => vectors.vectors_db.put(
+                txn,
+                ENTRY_POINT_KEY,
+                &self.ids[entry as usize].to_be_bytes(),
+            )?,
+            None => {
+                vectors.vectors_db.delete(txn, ENTRY_POINT_KEY)?;
+            }
+        }
+        Ok(())
+    }
+}
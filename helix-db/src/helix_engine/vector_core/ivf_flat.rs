@@ -0,0 +1,487 @@
+//! IVF-Flat index, kept on disk, for vector types too large to keep an HNSW graph of in memory
+//! or re-indexed in bulk too often to rebuild one.
+//!
+//! Each vector type configured in `ivf_flat` is split into `nlist` clusters around centroids. A
+//! vector's data is written to the posting list of its nearest centroid, keyed so a list is one
+//! contiguous range of the table, and a search scans only the lists of the `nprobe` centroids
+//! nearest the query. Only the centroids are read for every query; the postings are read
+//! sequentially straight from the data file, so the working set stays small however many
+//! vectors there are, at the cost of scanning a few thousand vectors per search. Adding a vector
+//! compares it to the centroids only, so indexing is far cheaper than an HNSW insert.
+//!
+//! The first `nlist` vectors of a type seed the centroids. The centroids are then retrained
+//! with spherical k-means over a sample of the postings each time the type grows fourfold,
+//! moving every vector to its new nearest list, until the type holds `RETRAIN_PER_LIST`
+//! vectors per list. Past that the clusters reflect the data well and stay fixed.
+//!
+//! The settings of each configured type are recorded in the index table when the storage opens.
+//! A type can only be switched to IVF-Flat while it has no vectors outside its IVF-Flat index:
+//! vectors already linked into HNSW would never be assigned to a list, so opening the storage
+//! with such a type newly configured fails instead.
+
+use crate::{
+    helix_engine::{
+        traversal_core::config::IvfFlatConfig,
+        types::VectorError,
+        vector_core::{
+            binary_heap::BinaryHeap,
+            utils::VectorFilter,
+            vector::HVector,
+            vector_core::VectorCore,
+            vector_distance::{MAX_DISTANCE, cosine_similarity},
+            vector_index::VectorIndex,
+        },
+    },
+    utils::label_hash::hash_label,
+};
+use heed3::{Database, RoTxn, RwTxn, types::Bytes};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap, mem};
+
+const CONFIG_PREFIX: u8 = b'k';
+const META_PREFIX: u8 = b'm';
+const CENTROID_PREFIX: u8 = b'c';
+const POSTING_PREFIX: u8 = b'p';
+const ASSIGNMENT_PREFIX: u8 = b'a';
+
+/// Centroids are retrained each time a type grows by this factor
+const RETRAIN_GROWTH: u64 = 4;
+/// Centroids stop being retrained once a type holds this many vectors per list
+const RETRAIN_PER_LIST: u64 = 64;
+/// Vectors per list sampled to train the centroids
+const TRAIN_SAMPLE_PER_LIST: u64 = 32;
+/// k-means iterations per training
+const TRAIN_ITERATIONS: usize = 6;
+
+/// Per type bookkeeping
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Meta {
+    /// Centroids stored so far, up to the configured nlist
+    lists: u32,
+    /// Vectors in the index
+    vectors: u64,
+    /// Vectors in the index when the centroids were last trained
+    trained_at: u64,
+}
+
+/// A vector scanned by a search, ordered by distance so the heap pops the furthest first
+#[derive(Debug, Clone, Copy)]
+struct Scored {
+    distance: f64,
+    id: u128,
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+/// Record which types are kept in IVF-Flat indices, replacing the previous settings
+pub fn configure(
+    db: Database<Bytes, Bytes>,
+    txn: &mut RwTxn,
+    labels: &HashMap<String, IvfFlatConfig>,
+) -> Result<(), VectorError> {
+    let stale: Vec<Vec<u8>> = db
+        .prefix_iter(txn, &[CONFIG_PREFIX])?
+        .map(|entry| Ok(entry?.0.to_vec()))
+        .collect::<Result<_, VectorError>>()?;
+    for key in stale {
+        db.delete(txn, &key)?;
+    }
+    for (label, config) in labels {
+        db.put(
+            txn,
+            &IvfFlat::config_key(hash_label(label, None)),
+            &bincode::serialize(config)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// Whether the vector `id` is in an IVF-Flat index
+pub fn contains(db: Database<Bytes, Bytes>, txn: &RoTxn, id: u128) -> Result<bool, VectorError> {
+    Ok(db.get(txn, &IvfFlat::assignment_key(id))?.is_some())
+}
+
+/// Drop a vector from the IVF-Flat index it's in, returning whether it was in one
+pub fn remove(db: Database<Bytes, Bytes>, txn: &mut RwTxn, id: u128) -> Result<bool, VectorError> {
+    let Some(assignment) = db.get(txn, &IvfFlat::assignment_key(id))? else {
+        return Ok(false);
+    };
+    let label: [u8; 4] = assignment[..4].try_into().unwrap();
+    let list = u32::from_be_bytes(assignment[4..8].try_into().unwrap());
+    let index = IvfFlat {
+        db,
+        label,
+        config: IvfFlatConfig::default(),
+    };
+    db.delete(txn, &IvfFlat::posting_key(label, list, Some(id)))?;
+    db.delete(txn, &IvfFlat::assignment_key(id))?;
+    if let Some(mut meta) = index.get_meta(txn)? {
+        meta.vectors = meta.vectors.saturating_sub(1);
+        index.put_meta(txn, &meta)?;
+    }
+    Ok(true)
+}
+
+/// The IVF-Flat index of one vector type
+#[derive(Debug, Clone, Copy)]
+pub struct IvfFlat {
+    db: Database<Bytes, Bytes>,
+    label: [u8; 4],
+    config: IvfFlatConfig,
+}
+
+impl IvfFlat {
+    /// The IVF-Flat index of `label`, if it's configured to have one
+    pub fn open(
+        db: Database<Bytes, Bytes>,
+        txn: &RoTxn,
+        label: &str,
+    ) -> Result<Option<Self>, VectorError> {
+        let label = hash_label(label, None);
+        match db.get(txn, &Self::config_key(label))? {
+            Some(bytes) => Ok(Some(Self {
+                db,
+                label,
+                config: bincode::deserialize(bytes)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub fn config(&self) -> &IvfFlatConfig {
+        &self.config
+    }
+
+    /// Key of the settings of a type: [k, label]
+    #[inline(always)]
+    fn config_key(label: [u8; 4]) -> [u8; 5] {
+        let mut key = [CONFIG_PREFIX; 5];
+        key[1..].copy_from_slice(&label);
+        key
+    }
+
+    /// Key of the bookkeeping of a type: [m, label]
+    #[inline(always)]
+    fn meta_key(label: [u8; 4]) -> [u8; 5] {
+        let mut key = [META_PREFIX; 5];
+        key[1..].copy_from_slice(&label);
+        key
+    }
+
+    /// Key of a centroid: [c, label, list]
+    #[inline(always)]
+    fn centroid_key(label: [u8; 4], list: u32) -> [u8; 9] {
+        let mut key = [CENTROID_PREFIX; 9];
+        key[1..5].copy_from_slice(&label);
+        key[5..].copy_from_slice(&list.to_be_bytes());
+        key
+    }
+
+    /// Key of a posting: [p, label, list, id]. Without the id it's the prefix of the list.
+    #[inline(always)]
+    fn posting_key(label: [u8; 4], list: u32, id: Option<u128>) -> Vec<u8> {
+        let mut key = Vec::with_capacity(25);
+        key.push(POSTING_PREFIX);
+        key.extend_from_slice(&label);
+        key.extend_from_slice(&list.to_be_bytes());
+        if let Some(id) = id {
+            key.extend_from_slice(&id.to_be_bytes());
+        }
+        key
+    }
+
+    /// Key of the list a vector is in: [a, id] -> [label, list]
+    #[inline(always)]
+    fn assignment_key(id: u128) -> [u8; 17] {
+        let mut key = [ASSIGNMENT_PREFIX; 17];
+        key[1..].copy_from_slice(&id.to_be_bytes());
+        key
+    }
+
+    fn get_meta(&self, txn: &RoTxn) -> Result<Option<Meta>, VectorError> {
+        match self.db.get(txn, &Self::meta_key(self.label))? {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put_meta(&self, txn: &mut RwTxn, meta: &Meta) -> Result<(), VectorError> {
+        self.db
+            .put(txn, &Self::meta_key(self.label), &bincode::serialize(meta)?)?;
+        Ok(())
+    }
+
+    /// The centroids of the type, in list order
+    fn centroids(&self, txn: &RoTxn) -> Result<Vec<Vec<f64>>, VectorError> {
+        let prefix = [
+            CENTROID_PREFIX,
+            self.label[0],
+            self.label[1],
+            self.label[2],
+            self.label[3],
+        ];
+        self.db
+            .prefix_iter(txn, &prefix)?
+            .map(|entry| Ok(decode(entry?.1)))
+            .collect()
+    }
+
+    /// The lists of the `n` centroids nearest `data`
+    fn nearest_lists(centroids: &[Vec<f64>], data: &[f64], n: usize) -> Vec<u32> {
+        let mut lists: Vec<(f64, u32)> = centroids
+            .iter()
+            .enumerate()
+            .map(|(list, centroid)| (distance(centroid, data), list as u32))
+            .collect();
+        lists.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        lists.truncate(n);
+        lists.into_iter().map(|(_, list)| list).collect()
+    }
+
+    fn put_posting(
+        &self,
+        txn: &mut RwTxn,
+        list: u32,
+        id: u128,
+        data: &[u8],
+    ) -> Result<(), VectorError> {
+        self.db
+            .put(txn, &Self::posting_key(self.label, list, Some(id)), data)?;
+        let mut assignment = [0u8; 8];
+        assignment[..4].copy_from_slice(&self.label);
+        assignment[4..].copy_from_slice(&list.to_be_bytes());
+        self.db.put(txn, &Self::assignment_key(id), &assignment)?;
+        Ok(())
+    }
+
+    /// Retrain the centroids with spherical k-means over a sample of the type's vectors, then
+    /// move every vector whose nearest centroid changed
+    fn train(&self, txn: &mut RwTxn, meta: &mut Meta) -> Result<(), VectorError> {
+        let mut centroids = self.centroids(txn)?;
+        let prefix = [
+            POSTING_PREFIX,
+            self.label[0],
+            self.label[1],
+            self.label[2],
+            self.label[3],
+        ];
+        let stride = meta
+            .vectors
+            .div_ceil(centroids.len() as u64 * TRAIN_SAMPLE_PER_LIST)
+            .max(1) as usize;
+        let sample: Vec<Vec<f64>> = self
+            .db
+            .prefix_iter(txn, &prefix)?
+            .step_by(stride)
+            .map(|entry| Ok(normalized(decode(entry?.1))))
+            .collect::<Result<_, VectorError>>()?;
+
+        for _ in 0..TRAIN_ITERATIONS {
+            let mut sums = vec![vec![0.0; centroids[0].len()]; centroids.len()];
+            let mut counts = vec![0usize; centroids.len()];
+            for vector in &sample {
+                let list = Self::nearest_lists(&centroids, vector, 1)[0] as usize;
+                counts[list] += 1;
+                sums[list]
+                    .iter_mut()
+                    .zip(vector)
+                    .for_each(|(sum, v)| *sum += v);
+            }
+            // An empty list keeps its centroid
+            for (centroid, (sum, count)) in centroids.iter_mut().zip(sums.into_iter().zip(counts)) {
+                if count > 0 {
+                    *centroid = normalized(sum);
+                }
+            }
+        }
+        for (list, centroid) in centroids.iter().enumerate() {
+            self.db.put(
+                txn,
+                &Self::centroid_key(self.label, list as u32),
+                bytemuck::cast_slice(centroid),
+            )?;
+        }
+
+        let mut moves = Vec::new();
+        for entry in self.db.prefix_iter(txn, &prefix)? {
+            let (key, value) = entry?;
+            let list = u32::from_be_bytes(key[5..9].try_into().unwrap());
+            let nearest = Self::nearest_lists(&centroids, &decode(value), 1)[0];
+            if nearest != list {
+                let id = u128::from_be_bytes(key[9..25].try_into().unwrap());
+                moves.push((id, list, nearest, value.to_vec()));
+            }
+        }
+        for (id, from, to, data) in moves {
+            self.db
+                .delete(txn, &Self::posting_key(self.label, from, Some(id)))?;
+            self.put_posting(txn, to, id, &data)?;
+        }
+
+        meta.trained_at = meta.vectors;
+        Ok(())
+    }
+}
+
+impl VectorIndex for IvfFlat {
+    /// Add the vector to the list of its nearest centroid, replacing it if it's already in
+    /// there
+    fn add<'db, 'arena, 'txn>(
+        &self,
+        vectors: &'db VectorCore,
+        txn: &'txn mut RwTxn<'db>,
+        vector: &mut HVector<'arena>,
+        _arena: &'arena bumpalo::Bump,
+    ) -> Result<(), VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        self.remove(vectors, txn, vector.id)?;
+
+        let data = vector.data;
+        let mut meta = self.get_meta(txn)?.unwrap_or_default();
+        let list = match (meta.lists as usize) < self.config.nlist() {
+            // Seeding: each of the first vectors starts a list of its own
+            true => {
+                let list = meta.lists;
+                self.db.put(
+                    txn,
+                    &Self::centroid_key(self.label, list),
+                    bytemuck::cast_slice(data),
+                )?;
+                meta.lists += 1;
+                list
+            }
+            false => Self::nearest_lists(&self.centroids(txn)?, data, 1)[0],
+        };
+        self.put_posting(txn, list, vector.id, bytemuck::cast_slice(data))?;
+        meta.vectors += 1;
+
+        let nlist = self.config.nlist() as u64;
+        if meta.lists as u64 == nlist
+            && meta.vectors >= meta.trained_at.max(nlist) * RETRAIN_GROWTH
+            && meta.vectors <= nlist * RETRAIN_PER_LIST
+        {
+            self.train(txn, &mut meta)?;
+        }
+        self.put_meta(txn, &meta)
+    }
+
+    fn remove(&self, _vectors: &VectorCore, txn: &mut RwTxn, id: u128) -> Result<(), VectorError> {
+        remove(self.db, txn, id)?;
+        Ok(())
+    }
+
+    /// Scan the lists of the `nprobe` centroids nearest the query, keeping the `ef` nearest
+    /// vectors for the filters to pick from. Filters only apply to the results.
+    fn search<'db, 'arena, 'txn, F>(
+        &self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        query: &'arena [f64],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        _should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<bumpalo::collections::Vec<'arena, HVector<'arena>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        if self.get_meta(txn)?.is_none_or(|meta| meta.vectors == 0) {
+            return Err(VectorError::EntryPointNotFound);
+        }
+
+        let centroids = self.centroids(txn)?;
+        let candidates = vectors.config.ef.max(k);
+        let mut nearest = std::collections::BinaryHeap::with_capacity(candidates + 1);
+        let mut data = Vec::with_capacity(query.len());
+        for list in Self::nearest_lists(&centroids, query, self.config.nprobe()) {
+            let prefix = Self::posting_key(self.label, list, None);
+            for entry in self.db.prefix_iter(txn, &prefix)? {
+                let (key, value) = entry?;
+                decode_into(value, &mut data);
+                let distance = cosine_similarity(query, &data).map(|sim| 1.0 - sim)?;
+                if nearest.len() < candidates
+                    || nearest
+                        .peek()
+                        .is_some_and(|furthest: &Scored| distance < furthest.distance)
+                {
+                    let id = u128::from_be_bytes(key[9..25].try_into().unwrap());
+                    nearest.push(Scored { distance, id });
+                    if nearest.len() > candidates {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+
+        let mut results = BinaryHeap::with_capacity(arena, nearest.len());
+        for Scored { distance, id } in nearest {
+            let mut vector = vectors.get_raw_vector_data(txn, id, label, arena)?;
+            vector.set_distance(distance);
+            results.push(vector);
+        }
+        results.to_vec_with_filter::<F, true>(
+            k,
+            filter,
+            label,
+            txn,
+            vectors.vector_properties_db,
+            arena,
+        )
+    }
+}
+
+/// Cosine distance, as the HNSW index measures it
+#[inline]
+fn distance(from: &[f64], to: &[f64]) -> f64 {
+    cosine_similarity(from, to).map_or(MAX_DISTANCE, |sim| 1.0 - sim)
+}
+
+/// Copy stored vector data into `data`. Values in LMDB aren't aligned for `f64`.
+#[inline]
+fn decode_into(bytes: &[u8], data: &mut Vec<f64>) {
+    data.clear();
+    data.extend(
+        bytes
+            .chunks_exact(mem::size_of::<f64>())
+            .map(|chunk| f64::from_ne_bytes(chunk.try_into().unwrap())),
+    );
+}
+
+#[inline]
+fn decode(bytes: &[u8]) -> Vec<f64> {
+    let mut data = Vec::with_capacity(bytes.len() / mem::size_of::<f64>());
+    decode_into(bytes, &mut data);
+    data
+}
+
+fn normalized(mut vector: Vec<f64>) -> Vec<f64> {
+    let norm = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
@@ -1,7 +1,12 @@
 pub mod binary_heap;
+pub mod content_hash;
 pub mod hnsw;
+pub mod hnsw_build;
+pub mod ivf_flat;
+pub mod simd;
 pub mod utils;
 pub mod vector;
 pub mod vector_core;
 pub mod vector_distance;
+pub mod vector_index;
 pub mod vector_without_data;
@@ -0,0 +1,324 @@
+//! Distance kernels specialized for the SIMD extensions of the CPU, picked when first used.
+//!
+//! On x86_64 the AVX-512 kernels run when the CPU has AVX-512F, then the AVX2 ones when it has
+//! AVX2 and FMA. On aarch64 the NEON kernels run. Other CPUs and targets use the scalar kernels.
+//! The kernels agree up to floating point rounding, as each one sums its lanes in its own order.
+//!
+//! Kernels read the first `min(a.len(), b.len())` elements; callers check lengths first.
+
+use std::sync::LazyLock;
+
+/// The dot product of two vectors and their squared norms, summed in one pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DotAndNorms {
+    pub dot: f64,
+    pub norm_a_sq: f64,
+    pub norm_b_sq: f64,
+}
+
+/// A set of distance kernels for one instruction set
+#[derive(Clone, Copy)]
+pub struct Kernels {
+    /// Instruction set the kernels use, e.g. `avx2`
+    pub name: &'static str,
+    pub dot: fn(&[f64], &[f64]) -> f64,
+    pub dot_and_norms: fn(&[f64], &[f64]) -> DotAndNorms,
+    /// Squared euclidean distance
+    pub l2_squared: fn(&[f64], &[f64]) -> f64,
+}
+
+static KERNELS: LazyLock<Kernels> = LazyLock::new(|| available()[0]);
+
+/// The fastest kernels the CPU supports
+#[inline]
+pub fn kernels() -> &'static Kernels {
+    &KERNELS
+}
+
+/// Every set of kernels the CPU supports, fastest first. The last is always the scalar one.
+pub fn available() -> Vec<Kernels> {
+    let mut kernels = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::arch::is_x86_feature_detected!("avx512f") {
+            kernels.push(x86::AVX512);
+        }
+        if std::arch::is_x86_feature_detected!("avx2") && std::arch::is_x86_feature_detected!("fma")
+        {
+            kernels.push(x86::AVX2);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            kernels.push(neon::NEON);
+        }
+    }
+    kernels.push(scalar::SCALAR);
+    kernels
+}
+
+pub mod scalar {
+    use super::{DotAndNorms, Kernels};
+
+    pub const SCALAR: Kernels = Kernels {
+        name: "scalar",
+        dot,
+        dot_and_norms,
+        l2_squared,
+    };
+
+    /// Independent sums, so the compiler can keep them in registers and vectorize them
+    const LANES: usize = 8;
+
+    pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+        let mut sums = [0.0; LANES];
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (a_chunk, b_chunk) in (&mut a_chunks).zip(&mut b_chunks) {
+            for ((sum, x), y) in sums.iter_mut().zip(a_chunk).zip(b_chunk) {
+                *sum += x * y;
+            }
+        }
+        let remainder: f64 = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| x * y)
+            .sum();
+        sums.iter().sum::<f64>() + remainder
+    }
+
+    pub fn dot_and_norms(a: &[f64], b: &[f64]) -> DotAndNorms {
+        let mut dot = [0.0; LANES];
+        let mut norm_a = [0.0; LANES];
+        let mut norm_b = [0.0; LANES];
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (a_chunk, b_chunk) in (&mut a_chunks).zip(&mut b_chunks) {
+            for (i, (&x, &y)) in a_chunk.iter().zip(b_chunk).enumerate() {
+                dot[i] += x * y;
+                norm_a[i] += x * x;
+                norm_b[i] += y * y;
+            }
+        }
+        let mut sums = DotAndNorms {
+            dot: dot.iter().sum(),
+            norm_a_sq: norm_a.iter().sum(),
+            norm_b_sq: norm_b.iter().sum(),
+        };
+        for (x, y) in a_chunks.remainder().iter().zip(b_chunks.remainder()) {
+            sums.dot += x * y;
+            sums.norm_a_sq += x * x;
+            sums.norm_b_sq += y * y;
+        }
+        sums
+    }
+
+    pub fn l2_squared(a: &[f64], b: &[f64]) -> f64 {
+        let mut sums = [0.0; LANES];
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (a_chunk, b_chunk) in (&mut a_chunks).zip(&mut b_chunks) {
+            for ((sum, x), y) in sums.iter_mut().zip(a_chunk).zip(b_chunk) {
+                let diff = x - y;
+                *sum += diff * diff;
+            }
+        }
+        let remainder: f64 = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum();
+        sums.iter().sum::<f64>() + remainder
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::{DotAndNorms, Kernels, scalar};
+    use std::arch::x86_64::*;
+
+    // Only listed by `available` once the CPU is known to support their target features
+    pub const AVX2: Kernels = Kernels {
+        name: "avx2",
+        dot: |a, b| unsafe { dot_avx2(a, b) },
+        dot_and_norms: |a, b| unsafe { dot_and_norms_avx2(a, b) },
+        l2_squared: |a, b| unsafe { l2_squared_avx2(a, b) },
+    };
+
+    pub const AVX512: Kernels = Kernels {
+        name: "avx512",
+        dot: |a, b| unsafe { dot_avx512(a, b) },
+        dot_and_norms: |a, b| unsafe { dot_and_norms_avx512(a, b) },
+        l2_squared: |a, b| unsafe { l2_squared_avx512(a, b) },
+    };
+
+    #[target_feature(enable = "avx2")]
+    fn sum_avx2(v: __m256d) -> f64 {
+        let pair = _mm_add_pd(_mm256_castpd256_pd128(v), _mm256_extractf128_pd(v, 1));
+        _mm_cvtsd_f64(_mm_add_sd(pair, _mm_unpackhi_pd(pair, pair)))
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    fn dot_avx2(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = _mm256_setzero_pd();
+        let mut a_chunks = a.chunks_exact(4);
+        let mut b_chunks = b.chunks_exact(4);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 4 doubles loaded
+            let (x, y) = unsafe { (_mm256_loadu_pd(x.as_ptr()), _mm256_loadu_pd(y.as_ptr())) };
+            acc = _mm256_fmadd_pd(x, y, acc);
+        }
+        sum_avx2(acc) + scalar::dot(a_chunks.remainder(), b_chunks.remainder())
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    fn dot_and_norms_avx2(a: &[f64], b: &[f64]) -> DotAndNorms {
+        let mut dot = _mm256_setzero_pd();
+        let mut norm_a = _mm256_setzero_pd();
+        let mut norm_b = _mm256_setzero_pd();
+        let mut a_chunks = a.chunks_exact(4);
+        let mut b_chunks = b.chunks_exact(4);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 4 doubles loaded
+            let (x, y) = unsafe { (_mm256_loadu_pd(x.as_ptr()), _mm256_loadu_pd(y.as_ptr())) };
+            dot = _mm256_fmadd_pd(x, y, dot);
+            norm_a = _mm256_fmadd_pd(x, x, norm_a);
+            norm_b = _mm256_fmadd_pd(y, y, norm_b);
+        }
+        let rest = scalar::dot_and_norms(a_chunks.remainder(), b_chunks.remainder());
+        DotAndNorms {
+            dot: sum_avx2(dot) + rest.dot,
+            norm_a_sq: sum_avx2(norm_a) + rest.norm_a_sq,
+            norm_b_sq: sum_avx2(norm_b) + rest.norm_b_sq,
+        }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    fn l2_squared_avx2(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = _mm256_setzero_pd();
+        let mut a_chunks = a.chunks_exact(4);
+        let mut b_chunks = b.chunks_exact(4);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 4 doubles loaded
+            let (x, y) = unsafe { (_mm256_loadu_pd(x.as_ptr()), _mm256_loadu_pd(y.as_ptr())) };
+            let diff = _mm256_sub_pd(x, y);
+            acc = _mm256_fmadd_pd(diff, diff, acc);
+        }
+        sum_avx2(acc) + scalar::l2_squared(a_chunks.remainder(), b_chunks.remainder())
+    }
+
+    #[target_feature(enable = "avx512f")]
+    fn dot_avx512(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = _mm512_setzero_pd();
+        let mut a_chunks = a.chunks_exact(8);
+        let mut b_chunks = b.chunks_exact(8);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 8 doubles loaded
+            let (x, y) = unsafe { (_mm512_loadu_pd(x.as_ptr()), _mm512_loadu_pd(y.as_ptr())) };
+            acc = _mm512_fmadd_pd(x, y, acc);
+        }
+        _mm512_reduce_add_pd(acc) + scalar::dot(a_chunks.remainder(), b_chunks.remainder())
+    }
+
+    #[target_feature(enable = "avx512f")]
+    fn dot_and_norms_avx512(a: &[f64], b: &[f64]) -> DotAndNorms {
+        let mut dot = _mm512_setzero_pd();
+        let mut norm_a = _mm512_setzero_pd();
+        let mut norm_b = _mm512_setzero_pd();
+        let mut a_chunks = a.chunks_exact(8);
+        let mut b_chunks = b.chunks_exact(8);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 8 doubles loaded
+            let (x, y) = unsafe { (_mm512_loadu_pd(x.as_ptr()), _mm512_loadu_pd(y.as_ptr())) };
+            dot = _mm512_fmadd_pd(x, y, dot);
+            norm_a = _mm512_fmadd_pd(x, x, norm_a);
+            norm_b = _mm512_fmadd_pd(y, y, norm_b);
+        }
+        let rest = scalar::dot_and_norms(a_chunks.remainder(), b_chunks.remainder());
+        DotAndNorms {
+            dot: _mm512_reduce_add_pd(dot) + rest.dot,
+            norm_a_sq: _mm512_reduce_add_pd(norm_a) + rest.norm_a_sq,
+            norm_b_sq: _mm512_reduce_add_pd(norm_b) + rest.norm_b_sq,
+        }
+    }
+
+    #[target_feature(enable = "avx512f")]
+    fn l2_squared_avx512(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = _mm512_setzero_pd();
+        let mut a_chunks = a.chunks_exact(8);
+        let mut b_chunks = b.chunks_exact(8);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 8 doubles loaded
+            let (x, y) = unsafe { (_mm512_loadu_pd(x.as_ptr()), _mm512_loadu_pd(y.as_ptr())) };
+            let diff = _mm512_sub_pd(x, y);
+            acc = _mm512_fmadd_pd(diff, diff, acc);
+        }
+        _mm512_reduce_add_pd(acc) + scalar::l2_squared(a_chunks.remainder(), b_chunks.remainder())
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::{DotAndNorms, Kernels, scalar};
+    use std::arch::aarch64::*;
+
+    // Only listed by `available` once the CPU is known to support NEON
+    pub const NEON: Kernels = Kernels {
+        name: "neon",
+        dot: |a, b| unsafe { dot_neon(a, b) },
+        dot_and_norms: |a, b| unsafe { dot_and_norms_neon(a, b) },
+        l2_squared: |a, b| unsafe { l2_squared_neon(a, b) },
+    };
+
+    #[target_feature(enable = "neon")]
+    fn dot_neon(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = vdupq_n_f64(0.0);
+        let mut a_chunks = a.chunks_exact(2);
+        let mut b_chunks = b.chunks_exact(2);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 2 doubles loaded
+            let (x, y) = unsafe { (vld1q_f64(x.as_ptr()), vld1q_f64(y.as_ptr())) };
+            acc = vfmaq_f64(acc, x, y);
+        }
+        vaddvq_f64(acc) + scalar::dot(a_chunks.remainder(), b_chunks.remainder())
+    }
+
+    #[target_feature(enable = "neon")]
+    fn dot_and_norms_neon(a: &[f64], b: &[f64]) -> DotAndNorms {
+        let mut dot = vdupq_n_f64(0.0);
+        let mut norm_a = vdupq_n_f64(0.0);
+        let mut norm_b = vdupq_n_f64(0.0);
+        let mut a_chunks = a.chunks_exact(2);
+        let mut b_chunks = b.chunks_exact(2);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 2 doubles loaded
+            let (x, y) = unsafe { (vld1q_f64(x.as_ptr()), vld1q_f64(y.as_ptr())) };
+            dot = vfmaq_f64(dot, x, y);
+            norm_a = vfmaq_f64(norm_a, x, x);
+            norm_b = vfmaq_f64(norm_b, y, y);
+        }
+        let rest = scalar::dot_and_norms(a_chunks.remainder(), b_chunks.remainder());
+        DotAndNorms {
+            dot: vaddvq_f64(dot) + rest.dot,
+            norm_a_sq: vaddvq_f64(norm_a) + rest.norm_a_sq,
+            norm_b_sq: vaddvq_f64(norm_b) + rest.norm_b_sq,
+        }
+    }
+
+    #[target_feature(enable = "neon")]
+    fn l2_squared_neon(a: &[f64], b: &[f64]) -> f64 {
+        let mut acc = vdupq_n_f64(0.0);
+        let mut a_chunks = a.chunks_exact(2);
+        let mut b_chunks = b.chunks_exact(2);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: chunks hold the 2 doubles loaded
+            let (x, y) = unsafe { (vld1q_f64(x.as_ptr()), vld1q_f64(y.as_ptr())) };
+            let diff = vsubq_f64(x, y);
+            acc = vfmaq_f64(acc, diff, diff);
+        }
+        vaddvq_f64(acc) + scalar::l2_squared(a_chunks.remainder(), b_chunks.remainder())
+    }
+}
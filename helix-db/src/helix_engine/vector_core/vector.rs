@@ -33,6 +33,11 @@ pub struct HVector<'arena> {
     pub level: usize,
     /// The distance of the HVector
     pub distance: Option<f64>,
+    /// The distance the search gave the HVector, kept once a reranker replaces `distance`
+    /// with its score, not stored
+    pub search_distance: Option<f64>,
+    /// Whether the upsert that returned the HVector embedded it again, not stored
+    pub reembedded: bool,
     /// The actual vector
     pub data: &'arena [f64],
     /// The properties of the HVector
@@ -121,6 +126,8 @@ impl<'arena> HVector<'arena> {
             label,
             data,
             distance: None,
+            search_distance: None,
+            reembedded: false,
             properties: None,
             deleted: false,
         }
@@ -226,6 +233,8 @@ impl<'arena> HVector<'arena> {
             version: 1,
             level: 0,
             distance: None,
+            search_distance: None,
+            reembedded: false,
             properties: None,
             deleted: false,
         })
@@ -281,6 +290,10 @@ impl<'arena> HVector<'arena> {
         self.distance.unwrap_or(2.0)
     }
 
+    pub fn reembedded(&self) -> bool {
+        self.reembedded
+    }
+
     pub fn expand_from_vector_without_data(&mut self, vector: VectorWithoutData<'arena>) {
         self.label = vector.label;
         self.version = vector.version;
@@ -297,6 +310,8 @@ impl<'arena> From<VectorWithoutData<'arena>> for HVector<'arena> {
             version: value.version,
             level: value.level,
             distance: None,
+            search_distance: None,
+            reembedded: false,
             data: &[],
             properties: value.properties,
             deleted: value.deleted,
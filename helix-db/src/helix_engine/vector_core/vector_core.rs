@@ -2,15 +2,25 @@ use super::binary_heap::BinaryHeap;
 use crate::{
     debug_println,
     helix_engine::{
+        storage_core::scan_counter,
+        traversal_core::config::IvfFlatConfig,
         types::VectorError,
         vector_core::{
+            content_hash,
             hnsw::HNSW,
+            hnsw_build::Graph,
+            ivf_flat::{self, IvfFlat},
             utils::{Candidate, HeapOps, VectorFilter},
             vector::HVector,
+            vector_distance::{cosine_distance_with_norms, norm},
+            vector_index::{HnswIndex, VectorIndex, VectorIndexKind},
             vector_without_data::VectorWithoutData,
         },
     },
-    utils::{id::uuid_str, properties::ImmutablePropertiesMap},
+    utils::{
+        id::{uuid_str, v6_uuid},
+        properties::ImmutablePropertiesMap,
+    },
 };
 use heed3::{
     Database, Env, RoTxn, RwTxn,
@@ -19,12 +29,17 @@ use heed3::{
 };
 use rand::prelude::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+/// Filter type of the searches run while linking a vector, which don't filter
+type NoFilter = fn(&HVector, &RoTxn) -> bool;
 
 const DB_VECTORS: &str = "vectors"; // for vector data (v:)
 const DB_VECTOR_DATA: &str = "vector_data"; // for vector data (v:)
 const DB_HNSW_EDGES: &str = "hnsw_out_nodes"; // for hnsw out node data
-const VECTOR_PREFIX: &[u8] = b"v:";
+const DB_IVF_FLAT: &str = "ivf_flat"; // for ivf-flat settings, centroids and postings
+const DB_CONTENT_HASHES: &str = "vector_content_hashes"; // for hashes of embedded content
+pub const VECTOR_PREFIX: &[u8] = b"v:";
 pub const ENTRY_POINT_KEY: &[u8] = b"entry_point";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,11 +76,65 @@ impl HNSWConfig {
     }
 }
 
+/// Neighbor lists read by the queries of a batch search, kept so each is read once per batch
+struct SharedNeighbors<'arena> {
+    neighbors: HashMap<(u128, usize), &'arena [HVector<'arena>]>,
+    norms: HashMap<u128, f64>,
+}
+
+impl<'arena> SharedNeighbors<'arena> {
+    fn new(entry_point: &HVector<'arena>) -> Self {
+        Self {
+            neighbors: HashMap::new(),
+            norms: HashMap::from([(entry_point.id, norm(entry_point.data))]),
+        }
+    }
+
+    fn norm(&self, vector: &HVector) -> f64 {
+        self.norms
+            .get(&vector.id)
+            .copied()
+            .unwrap_or_else(|| norm(vector.data))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn get<'db: 'arena, 'txn, F>(
+        &mut self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        label: &'arena str,
+        id: u128,
+        level: usize,
+        filter: Option<&[F]>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<&'arena [HVector<'arena>], VectorError>
+    where
+        'arena: 'txn,
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+    {
+        if let Some(neighbors) = self.neighbors.get(&(id, level)) {
+            return Ok(neighbors);
+        }
+        let neighbors = vectors
+            .get_neighbors(txn, label, id, level, filter, arena)?
+            .into_bump_slice();
+        for neighbor in neighbors {
+            self.norms
+                .entry(neighbor.id)
+                .or_insert_with(|| norm(neighbor.data));
+        }
+        self.neighbors.insert((id, level), neighbors);
+        Ok(neighbors)
+    }
+}
+
 pub struct VectorCore {
     pub vectors_db: Database<Bytes, Bytes>,
     pub vector_properties_db: Database<U128<BE>, Bytes>,
     pub edges_db: Database<Bytes, Unit>,
     pub config: HNSWConfig,
+    pub ivf_flat_db: Database<Bytes, Bytes>,
+    pub content_hashes_db: Database<Bytes, Bytes>,
 }
 
 impl VectorCore {
@@ -77,15 +146,118 @@ impl VectorCore {
             .name(DB_VECTOR_DATA)
             .create(txn)?;
         let edges_db = env.create_database(txn, Some(DB_HNSW_EDGES))?;
+        let ivf_flat_db = env.create_database(txn, Some(DB_IVF_FLAT))?;
+        let content_hashes_db = env.create_database(txn, Some(DB_CONTENT_HASHES))?;
 
         Ok(Self {
             vectors_db,
             vector_properties_db,
             edges_db,
             config,
+            ivf_flat_db,
+            content_hashes_db,
         })
     }
 
+    /// Keep the vectors of the given types in IVF-Flat indices instead of HNSW. Fails with
+    /// [`VectorError::IndexSwitch`] if a type not kept in IVF-Flat so far already has vectors
+    /// outside it, since those would never be found by its searches.
+    pub fn configure_ivf_flat(
+        &self,
+        txn: &mut RwTxn,
+        labels: &HashMap<String, IvfFlatConfig>,
+    ) -> Result<(), VectorError> {
+        let mut switched = HashSet::new();
+        for label in labels.keys() {
+            if IvfFlat::open(self.ivf_flat_db, txn, label)?.is_none() {
+                switched.insert(label.as_str());
+            }
+        }
+        if !switched.is_empty() {
+            let arena = bumpalo::Bump::new();
+            for entry in self.vector_properties_db.iter(txn)? {
+                let (id, bytes) = entry?;
+                let vector = VectorWithoutData::from_bincode_bytes(&arena, bytes, id)?;
+                if !vector.deleted
+                    && switched.contains(vector.label)
+                    && !ivf_flat::contains(self.ivf_flat_db, txn, id)?
+                {
+                    return Err(VectorError::IndexSwitch(vector.label.to_string()));
+                }
+            }
+        }
+        ivf_flat::configure(self.ivf_flat_db, txn, labels)
+    }
+
+    /// The index the vectors of `label` are kept in
+    pub fn index_kind(&self, txn: &RoTxn, label: &str) -> Result<VectorIndexKind, VectorError> {
+        Ok(match IvfFlat::open(self.ivf_flat_db, txn, label)? {
+            Some(index) => VectorIndexKind::IvfFlat(index),
+            None => VectorIndexKind::Hnsw(HnswIndex),
+        })
+    }
+
+    /// The hash of the content the vector `id` was embedded from, if it was upserted from content
+    pub fn content_hash(&self, txn: &RoTxn, id: u128) -> Result<Option<u64>, VectorError> {
+        content_hash::get(self.content_hashes_db, txn, id)
+    }
+
+    /// Record the hash of the content `vector` was embedded from
+    pub fn set_content_hash(
+        &self,
+        txn: &mut RwTxn,
+        vector: &HVector,
+        hash: u64,
+    ) -> Result<(), VectorError> {
+        content_hash::put(self.content_hashes_db, txn, vector.id, vector.label, hash)
+    }
+
+    /// The stored embedding of a vector of `label` embedded from `content`, if there is one
+    pub fn embedding_for_content(
+        &self,
+        txn: &RoTxn,
+        label: &str,
+        content: &str,
+    ) -> Result<Option<Vec<f64>>, VectorError> {
+        let Some(id) = content_hash::find(
+            self.content_hashes_db,
+            txn,
+            label,
+            content_hash::hash(content),
+        )?
+        else {
+            return Ok(None);
+        };
+        let arena = bumpalo::Bump::new();
+        match self.get_raw_vector_data(txn, id, label, &arena) {
+            Ok(vector) => Ok(Some(vector.data.to_vec())),
+            Err(VectorError::EntryPointNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the data of a stored vector with a new embedding, moving it in its index
+    pub fn reembed<'db, 'arena, 'txn>(
+        &'db self,
+        txn: &'txn mut RwTxn<'db>,
+        vector: &mut HVector<'arena>,
+        data: &'arena [f64],
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<(), VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        match self.index_kind(txn, vector.label)? {
+            VectorIndexKind::Hnsw(index) => index.remove(self, txn, vector.id)?,
+            VectorIndexKind::IvfFlat(index) => index.remove(self, txn, vector.id)?,
+        }
+        let reembedded =
+            self.insert_with_id(txn, vector.id, vector.label, data, vector.properties, arena)?;
+        vector.data = reembedded.data;
+        Ok(())
+    }
+
     /// Vector key: [v, id, ]
     #[inline(always)]
     pub fn vector_key(id: u128, level: usize) -> Vec<u8> {
@@ -144,6 +316,47 @@ impl VectorCore {
         Ok(())
     }
 
+    /// Drop the edges of `id` in both directions, handing the entry point to its neighbour on
+    /// the highest level if it was `id`
+    fn unlink(&self, txn: &mut RwTxn, id: u128) -> Result<(), VectorError> {
+        let prefix = id.to_be_bytes();
+        let keys = self
+            .edges_db
+            .prefix_iter(txn, &prefix)?
+            .map(|result| result.map(|(key, _)| key.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut successor: Option<(usize, u128)> = None;
+        for key in keys {
+            let mut level = [0u8; 8];
+            level.copy_from_slice(&key[16..24]);
+            let level = usize::from_be_bytes(level);
+            let mut sink = [0u8; 16];
+            sink.copy_from_slice(&key[24..40]);
+            let sink = u128::from_be_bytes(sink);
+
+            self.edges_db.delete(txn, &key)?;
+            self.edges_db
+                .delete(txn, &Self::out_edges_key(sink, level, Some(id)))?;
+            if sink != id && successor.is_none_or(|(top, _)| level > top) {
+                successor = Some((level, sink));
+            }
+        }
+
+        let is_entry_point = self.vectors_db.get(txn, ENTRY_POINT_KEY)? == Some(&prefix[..]);
+        match successor {
+            Some((_, sink)) if is_entry_point => {
+                self.vectors_db
+                    .put(txn, ENTRY_POINT_KEY, &sink.to_be_bytes())?;
+            }
+            None if is_entry_point => {
+                self.vectors_db.delete(txn, ENTRY_POINT_KEY)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn put_vector<'arena>(
         &self,
@@ -380,6 +593,90 @@ impl VectorCore {
         Ok(results)
     }
 
+    /// [`Self::search_level`] for one query of a batch, reading the graph through `shared`
+    #[allow(clippy::too_many_arguments)]
+    fn search_level_shared<'db: 'arena, 'arena: 'txn, 'txn, F>(
+        &self,
+        txn: &'txn RoTxn<'db>,
+        label: &'arena str,
+        query: &[f64],
+        query_norm: f64,
+        shared: &mut SharedNeighbors<'arena>,
+        mut entry_point: HVector<'arena>,
+        ef: usize,
+        level: usize,
+        filter: Option<&[F]>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<BinaryHeap<'arena, HVector<'arena>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+    {
+        let mut visited: HashSet<u128> = HashSet::new();
+        let mut candidates: BinaryHeap<'arena, Candidate> =
+            BinaryHeap::with_capacity(arena, self.config.ef_construct);
+        let mut results: BinaryHeap<'arena, HVector<'arena>> = BinaryHeap::new(arena);
+
+        entry_point.set_distance(cosine_distance_with_norms(
+            query,
+            query_norm,
+            entry_point.data,
+            shared.norm(&entry_point),
+        )?);
+        candidates.push(Candidate {
+            id: entry_point.id,
+            distance: entry_point.get_distance(),
+        });
+        results.push(entry_point);
+        visited.insert(entry_point.id);
+
+        while let Some(curr_cand) = candidates.pop() {
+            if results.len() >= ef
+                && results
+                    .get_max()
+                    .is_none_or(|f| curr_cand.distance > f.get_distance())
+            {
+                break;
+            }
+
+            let max_distance = if results.len() >= ef {
+                results.get_max().map(|f| f.get_distance())
+            } else {
+                None
+            };
+
+            let neighbors = shared.get(self, txn, label, curr_cand.id, level, filter, arena)?;
+            for neighbor in neighbors {
+                if !visited.insert(neighbor.id) {
+                    continue;
+                }
+                let Ok(distance) = cosine_distance_with_norms(
+                    query,
+                    query_norm,
+                    neighbor.data,
+                    shared.norm(neighbor),
+                ) else {
+                    continue;
+                };
+
+                if max_distance.is_none_or(|max| distance < max) {
+                    let mut neighbor = *neighbor;
+                    neighbor.set_distance(distance);
+                    candidates.push(Candidate {
+                        id: neighbor.id,
+                        distance,
+                    });
+
+                    results.push(neighbor);
+
+                    if results.len() > ef {
+                        results = results.take_inord(ef);
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
     pub fn num_inserted_vectors(&self, txn: &RoTxn) -> Result<u64, VectorError> {
         Ok(self.vectors_db.len(txn)?)
     }
@@ -391,6 +688,7 @@ impl VectorCore {
         id: u128,
         arena: &'arena bumpalo::Bump,
     ) -> Result<Option<VectorWithoutData<'arena>>, VectorError> {
+        scan_counter::record(1);
         let vector: Option<VectorWithoutData<'arena>> =
             match self.vector_properties_db.get(txn, &id)? {
                 Some(bytes) => Some(VectorWithoutData::from_bincode_bytes(arena, bytes, id)?),
@@ -413,6 +711,7 @@ impl VectorCore {
         id: u128,
         arena: &'arena bumpalo::Bump,
     ) -> Result<HVector<'arena>, VectorError> {
+        scan_counter::record(1);
         let vector_data_bytes = self
             .vectors_db
             .get(txn, &Self::vector_key(id, 0))?
@@ -442,6 +741,110 @@ impl VectorCore {
         HVector::from_raw_vector_data(arena, vector_data_bytes, label, id)
     }
 
+    /// The label of the vector stored under `id`, deleted or not
+    pub fn stored_label<'arena>(
+        &self,
+        txn: &RoTxn,
+        id: u128,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Option<&'arena str>, VectorError> {
+        match self.vector_properties_db.get(txn, &id)? {
+            Some(bytes) => Ok(Some(
+                VectorWithoutData::from_bincode_bytes(arena, bytes, id)?.label,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Insert a vector under `id`, replacing the data and properties of a vector of the same
+    /// label already stored under it
+    pub fn insert_with_id<'db, 'arena, 'txn>(
+        &'db self,
+        txn: &'txn mut RwTxn<'db>,
+        id: u128,
+        label: &'arena str,
+        data: &'arena [f64],
+        properties: Option<ImmutablePropertiesMap<'arena>>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<HVector<'arena>, VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        if let Some(existing) = self.stored_label(txn, id, arena)?
+            && existing != label
+        {
+            return Err(VectorError::LabelMismatch {
+                id: uuid_str(id, arena).to_string(),
+                label: existing.to_string(),
+            });
+        }
+
+        let mut query = HVector::from_slice(label, 0, data);
+        query.id = id;
+        query.properties = properties;
+        self.put_vector(txn, &query)?;
+
+        match self.index_kind(txn, label)? {
+            VectorIndexKind::Hnsw(index) => index.add(self, txn, &mut query, arena)?,
+            VectorIndexKind::IvfFlat(index) => index.add(self, txn, &mut query, arena)?,
+        }
+
+        debug_println!("vector inserted with id {}", query.id);
+        Ok(query)
+    }
+
+    /// Insert many vectors of `label` at once. In an HNSW index they are linked into the graph
+    /// in parallel on all cores after reading the rest of the graph once, so a load that is
+    /// large next to the index is much faster than inserting its vectors one at a time.
+    pub fn bulk_load<'db, 'arena, 'txn>(
+        &'db self,
+        txn: &'txn mut RwTxn<'db>,
+        label: &'arena str,
+        items: Vec<(&'arena [f64], Option<ImmutablePropertiesMap<'arena>>)>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<bumpalo::collections::Vec<'arena, HVector<'arena>>, VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        let mut inserted = bumpalo::collections::Vec::with_capacity_in(items.len(), arena);
+        if let VectorIndexKind::IvfFlat(_) = self.index_kind(txn, label)? {
+            for (data, properties) in items {
+                let vector = self.insert_with_id(txn, v6_uuid(), label, data, properties, arena)?;
+                inserted.push(vector);
+            }
+            return Ok(inserted);
+        }
+
+        let mut graph = Graph::load(self, txn, true, arena)?;
+        let from = graph.len();
+        for (data, properties) in items {
+            let mut vector = HVector::from_slice(label, 0, data);
+            vector.id = v6_uuid();
+            vector.properties = properties;
+            self.put_vector(txn, &vector)?;
+            graph.push(vector.id, vector.data);
+            inserted.push(vector);
+        }
+        graph.link_from(from);
+        graph.write(self, txn)?;
+
+        debug_println!("bulk loaded {} vectors", inserted.len());
+        Ok(inserted)
+    }
+
+    /// Relink the HNSW graph from scratch on all cores, dropping deleted vectors from it.
+    /// Returns the number of vectors in the rebuilt graph.
+    pub fn rebuild_hnsw(&self, txn: &mut RwTxn) -> Result<usize, VectorError> {
+        let arena = bumpalo::Bump::new();
+        let graph = Graph::load(self, txn, false, &arena)?;
+        self.edges_db.clear(txn)?;
+        graph.link_from(0);
+        graph.write(self, txn)?;
+        Ok(graph.len())
+    }
+
     /// Get all vectors from the database, optionally filtered by level
     pub fn get_all_vectors<'db: 'arena, 'arena: 'txn, 'txn>(
         &self,
@@ -505,57 +908,52 @@ impl HNSW for VectorCore {
         'db: 'arena,
         'arena: 'txn,
     {
-        let query = HVector::from_slice(label, 0, query);
-        // let temp_arena = bumpalo::Bump::new();
+        #[cfg(feature = "gateway")]
+        let start = std::time::Instant::now();
+        let results = match self.index_kind(txn, label)? {
+            VectorIndexKind::Hnsw(index) => {
+                index.search(self, txn, query, k, label, filter, should_trickle, arena)?
+            }
+            VectorIndexKind::IvfFlat(index) => {
+                index.search(self, txn, query, k, label, filter, should_trickle, arena)?
+            }
+        };
 
-        let mut entry_point = self.get_entry_point(txn, label, arena)?;
+        debug_println!("vector search found {} results", results.len());
+        #[cfg(feature = "gateway")]
+        helix_metrics::prometheus::observe_hnsw_search(start.elapsed());
+        Ok(results)
+    }
 
-        let ef = self.config.ef;
-        let curr_level = entry_point.level;
-        // println!("curr_level: {curr_level}");
-        for level in (1..=curr_level).rev() {
-            let mut nearest = self.search_level(
-                txn,
-                label,
-                &query,
-                &mut entry_point,
-                ef,
-                level,
-                match should_trickle {
-                    true => filter,
-                    false => None,
-                },
-                arena,
-            )?;
-            if let Some(closest) = nearest.pop() {
-                entry_point = closest;
+    fn search_batch<'db, 'arena, 'txn, F>(
+        &'db self,
+        txn: &'txn RoTxn<'db>,
+        queries: &[&'arena [f64]],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Vec<bumpalo::collections::Vec<'arena, HVector<'arena>>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        #[cfg(feature = "gateway")]
+        let start = std::time::Instant::now();
+        let results = match self.index_kind(txn, label)? {
+            VectorIndexKind::Hnsw(index) => {
+                index.search_batch(self, txn, queries, k, label, filter, should_trickle, arena)?
             }
-        }
-        // println!("entry_point: {entry_point:?}");
-        let candidates = self.search_level(
-            txn,
-            label,
-            &query,
-            &mut entry_point,
-            ef,
-            0,
-            match should_trickle {
-                true => filter,
-                false => None,
-            },
-            arena,
-        )?;
-        // println!("candidates");
-        let results = candidates.to_vec_with_filter::<F, true>(
-            k,
-            filter,
-            label,
-            txn,
-            self.vector_properties_db,
-            arena,
-        )?;
+            VectorIndexKind::IvfFlat(index) => {
+                index.search_batch(self, txn, queries, k, label, filter, should_trickle, arena)?
+            }
+        };
 
-        debug_println!("vector search found {} results", results.len());
+        debug_println!("batch vector search of {} queries", results.len());
+        #[cfg(feature = "gateway")]
+        helix_metrics::prometheus::observe_hnsw_search(start.elapsed());
         Ok(results)
     }
 
@@ -572,42 +970,92 @@ impl HNSW for VectorCore {
         'db: 'arena,
         'arena: 'txn,
     {
-        let new_level = self.get_new_level();
+        self.insert_with_id(txn, v6_uuid(), label, data, properties, arena)
+    }
 
-        let mut query = HVector::from_slice(label, 0, data);
-        query.properties = properties;
-        self.put_vector(txn, &query)?;
+    fn delete(&self, txn: &mut RwTxn, id: u128, arena: &bumpalo::Bump) -> Result<(), VectorError> {
+        match self.get_vector_properties(txn, id, arena)? {
+            Some(mut properties) => {
+                debug_println!("properties: {properties:?}");
+                if properties.deleted {
+                    return Err(VectorError::VectorAlreadyDeleted(id.to_string()));
+                }
 
-        query.level = new_level;
+                properties.deleted = true;
+                self.vector_properties_db.put(
+                    txn,
+                    &id,
+                    bincode::serialize(&properties)?.as_ref(),
+                )?;
+                match self.index_kind(txn, properties.label)? {
+                    VectorIndexKind::Hnsw(index) => index.remove(self, txn, id)?,
+                    VectorIndexKind::IvfFlat(index) => index.remove(self, txn, id)?,
+                }
+                content_hash::remove(self.content_hashes_db, txn, id, properties.label)?;
+                debug_println!("vector deleted with id {}", &id);
+                Ok(())
+            }
+            None => Err(VectorError::VectorNotFound(id.to_string())),
+        }
+    }
+}
+
+impl VectorIndex for HnswIndex {
+    /// Link the vector into the graph, first unlinking it if it's already in there
+    fn add<'db, 'arena, 'txn>(
+        &self,
+        vectors: &'db VectorCore,
+        txn: &'txn mut RwTxn<'db>,
+        vector: &mut HVector<'arena>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<(), VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        vectors.unlink(txn, vector.id)?;
 
-        let entry_point = match self.get_entry_point(txn, label, arena) {
+        let label = vector.label;
+        let new_level = vectors.get_new_level();
+
+        vector.level = new_level;
+
+        let entry_point = match vectors.get_entry_point(txn, label, arena) {
             Ok(ep) => ep,
             Err(_) => {
                 // TODO: use proper error handling
-                self.set_entry_point(txn, &query)?;
-                query.set_distance(0.0);
+                vectors.set_entry_point(txn, vector)?;
+                vector.set_distance(0.0);
 
-                return Ok(query);
+                return Ok(());
             }
         };
 
         let l = entry_point.level;
         let mut curr_ep = entry_point;
         for level in (new_level + 1..=l).rev() {
-            let mut nearest =
-                self.search_level::<F>(txn, label, &query, &mut curr_ep, 1, level, None, arena)?;
+            let mut nearest = vectors.search_level::<NoFilter>(
+                txn,
+                label,
+                vector,
+                &mut curr_ep,
+                1,
+                level,
+                None,
+                arena,
+            )?;
             curr_ep = nearest.pop().ok_or(VectorError::VectorCoreError(
                 "emtpy search result".to_string(),
             ))?;
         }
 
         for level in (0..=l.min(new_level)).rev() {
-            let nearest = self.search_level::<F>(
+            let nearest = vectors.search_level::<NoFilter>(
                 txn,
                 label,
-                &query,
+                vector,
                 &mut curr_ep,
-                self.config.ef_construct,
+                vectors.config.ef_construct,
                 level,
                 None,
                 arena,
@@ -616,48 +1064,177 @@ impl HNSW for VectorCore {
                 "emtpy search result".to_string(),
             ))?;
 
-            let neighbors =
-                self.select_neighbors::<F>(txn, label, &query, nearest, level, true, None, arena)?;
-            self.set_neighbours(txn, query.id, &neighbors, level)?;
+            let neighbors = vectors.select_neighbors::<NoFilter>(
+                txn, label, vector, nearest, level, true, None, arena,
+            )?;
+            vectors.set_neighbours(txn, vector.id, &neighbors, level)?;
 
             for e in neighbors {
                 let id = e.id;
                 let e_conns = BinaryHeap::from(
                     arena,
-                    self.get_neighbors::<F>(txn, label, id, level, None, arena)?,
+                    vectors.get_neighbors::<NoFilter>(txn, label, id, level, None, arena)?,
                 );
-                let e_new_conn = self
-                    .select_neighbors::<F>(txn, label, &query, e_conns, level, true, None, arena)?;
-                self.set_neighbours(txn, id, &e_new_conn, level)?;
+                let e_new_conn = vectors.select_neighbors::<NoFilter>(
+                    txn, label, vector, e_conns, level, true, None, arena,
+                )?;
+                vectors.set_neighbours(txn, id, &e_new_conn, level)?;
             }
         }
 
         if new_level > l {
-            self.set_entry_point(txn, &query)?;
+            vectors.set_entry_point(txn, vector)?;
         }
 
-        debug_println!("vector inserted with id {}", query.id);
-        Ok(query)
+        Ok(())
     }
 
-    fn delete(&self, txn: &mut RwTxn, id: u128, arena: &bumpalo::Bump) -> Result<(), VectorError> {
-        match self.get_vector_properties(txn, id, arena)? {
-            Some(mut properties) => {
-                debug_println!("properties: {properties:?}");
-                if properties.deleted {
-                    return Err(VectorError::VectorAlreadyDeleted(id.to_string()));
-                }
+    /// Deleted vectors stay in the graph to keep it connected, searches skip them
+    fn remove(
+        &self,
+        _vectors: &VectorCore,
+        _txn: &mut RwTxn,
+        _id: u128,
+    ) -> Result<(), VectorError> {
+        Ok(())
+    }
 
-                properties.deleted = true;
-                self.vector_properties_db.put(
-                    txn,
-                    &id,
-                    bincode::serialize(&properties)?.as_ref(),
-                )?;
-                debug_println!("vector deleted with id {}", &id);
-                Ok(())
+    fn search<'db, 'arena, 'txn, F>(
+        &self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        query: &'arena [f64],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<bumpalo::collections::Vec<'arena, HVector<'arena>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        let query = HVector::from_slice(label, 0, query);
+
+        let mut entry_point = vectors.get_entry_point(txn, label, arena)?;
+
+        let ef = vectors.config.ef;
+        let curr_level = entry_point.level;
+        // println!("curr_level: {curr_level}");
+        for level in (1..=curr_level).rev() {
+            let mut nearest = vectors.search_level(
+                txn,
+                label,
+                &query,
+                &mut entry_point,
+                ef,
+                level,
+                match should_trickle {
+                    true => filter,
+                    false => None,
+                },
+                arena,
+            )?;
+            if let Some(closest) = nearest.pop() {
+                entry_point = closest;
             }
-            None => Err(VectorError::VectorNotFound(id.to_string())),
         }
+        // println!("entry_point: {entry_point:?}");
+        let candidates = vectors.search_level(
+            txn,
+            label,
+            &query,
+            &mut entry_point,
+            ef,
+            0,
+            match should_trickle {
+                true => filter,
+                false => None,
+            },
+            arena,
+        )?;
+        // println!("candidates");
+        candidates.to_vec_with_filter::<F, true>(
+            k,
+            filter,
+            label,
+            txn,
+            vectors.vector_properties_db,
+            arena,
+        )
+    }
+
+    /// Every query starts from the same entry point and nearby queries walk the same part of
+    /// the graph, so the neighbor lists read and the norms of their vectors are shared by the
+    /// whole batch. Each distance is then a single dot product.
+    fn search_batch<'db, 'arena, 'txn, F>(
+        &self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        queries: &[&'arena [f64]],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Vec<bumpalo::collections::Vec<'arena, HVector<'arena>>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        let entry_point = vectors.get_entry_point(txn, label, arena)?;
+        let mut shared = SharedNeighbors::new(&entry_point);
+        let level_filter = match should_trickle {
+            true => filter,
+            false => None,
+        };
+
+        let ef = vectors.config.ef;
+        queries
+            .iter()
+            .map(|query| {
+                let query_norm = norm(query);
+                let mut curr_ep = entry_point;
+                for level in (1..=entry_point.level).rev() {
+                    let mut nearest = vectors.search_level_shared(
+                        txn,
+                        label,
+                        query,
+                        query_norm,
+                        &mut shared,
+                        curr_ep,
+                        ef,
+                        level,
+                        level_filter,
+                        arena,
+                    )?;
+                    if let Some(closest) = nearest.pop() {
+                        curr_ep = closest;
+                    }
+                }
+                let candidates = vectors.search_level_shared(
+                    txn,
+                    label,
+                    query,
+                    query_norm,
+                    &mut shared,
+                    curr_ep,
+                    ef,
+                    0,
+                    level_filter,
+                    arena,
+                )?;
+                candidates.to_vec_with_filter::<F, true>(
+                    k,
+                    filter,
+                    label,
+                    txn,
+                    vectors.vector_properties_db,
+                    arena,
+                )
+            })
+            .collect()
     }
 }
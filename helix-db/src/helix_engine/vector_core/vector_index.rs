@@ -0,0 +1,105 @@
+use crate::helix_engine::{
+    types::VectorError,
+    vector_core::{ivf_flat::IvfFlat, vector::HVector, vector_core::VectorCore},
+};
+
+use heed3::{RoTxn, RwTxn};
+
+/// An index over the vectors of one vector type.
+///
+/// The vector data and properties are stored by the [`VectorCore`] whatever the index; an index
+/// only keeps what it needs to find the nearest of them. Each vector type uses the index its
+/// config names, see [`VectorIndexKind`].
+pub trait VectorIndex {
+    /// Add a vector, already stored, to the index
+    ///
+    /// # Arguments
+    ///
+    /// * `vectors` - The vector storage the index belongs to
+    /// * `txn` - The transaction to use
+    /// * `vector` - The stored vector
+    fn add<'db, 'arena, 'txn>(
+        &self,
+        vectors: &'db VectorCore,
+        txn: &'txn mut RwTxn<'db>,
+        vector: &mut HVector<'arena>,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<(), VectorError>
+    where
+        'db: 'arena,
+        'arena: 'txn;
+
+    /// Remove a deleted vector from the index
+    ///
+    /// # Arguments
+    ///
+    /// * `vectors` - The vector storage the index belongs to
+    /// * `txn` - The transaction to use
+    /// * `id` - The id of the vector
+    fn remove(&self, vectors: &VectorCore, txn: &mut RwTxn, id: u128) -> Result<(), VectorError>;
+
+    /// Search for the k nearest neighbors of a query vector that pass the filters
+    ///
+    /// # Arguments
+    ///
+    /// * `vectors` - The vector storage the index belongs to
+    /// * `txn` - The transaction to use
+    /// * `query` - The query vector
+    /// * `k` - The number of nearest neighbors to search for
+    /// * `should_trickle` - Whether to apply the filters while searching, not only to the results
+    #[allow(clippy::too_many_arguments)]
+    fn search<'db, 'arena, 'txn, F>(
+        &self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        query: &'arena [f64],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<bumpalo::collections::Vec<'arena, HVector<'arena>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn;
+
+    /// Search for the k nearest neighbors of each of many query vectors, in query order
+    ///
+    /// By default each query is searched on its own; an index can share work between them.
+    #[allow(clippy::too_many_arguments)]
+    fn search_batch<'db, 'arena, 'txn, F>(
+        &self,
+        vectors: &VectorCore,
+        txn: &'txn RoTxn<'db>,
+        queries: &[&'arena [f64]],
+        k: usize,
+        label: &'arena str,
+        filter: Option<&'arena [F]>,
+        should_trickle: bool,
+        arena: &'arena bumpalo::Bump,
+    ) -> Result<Vec<bumpalo::collections::Vec<'arena, HVector<'arena>>>, VectorError>
+    where
+        F: Fn(&HVector<'arena>, &RoTxn<'db>) -> bool,
+        'db: 'arena,
+        'arena: 'txn,
+    {
+        queries
+            .iter()
+            .map(|query| self.search(vectors, txn, query, k, label, filter, should_trickle, arena))
+            .collect()
+    }
+}
+
+/// The index a vector type is kept in
+#[derive(Debug, Clone, Copy)]
+pub enum VectorIndexKind {
+    /// The HNSW graph shared by every type without an index of its own (the default)
+    Hnsw(HnswIndex),
+    /// An IVF-Flat index of the type's own, set in `vector_config.ivf_flat`
+    IvfFlat(IvfFlat),
+}
+
+/// The HNSW graph, stored in the `edges_db` of the [`VectorCore`] and tuned by its `config`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HnswIndex;
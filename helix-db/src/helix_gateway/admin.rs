@@ -0,0 +1,499 @@
+//! The `/admin` API: the routes an instance serves, the configuration it runs with, live
+//! stats, full node and edge tables as Parquet, random walks for node embeddings, consistency
+//! checks, HNSW rebuilds, BM25 index stats and reloads of the tunables. This is what the
+//! dashboard, `helix status --detailed`, `helix export`, `helix fsck` and `helix reload` use.
+//!
+//! Once API keys or JWT auth are configured, callers need an `admin` scoped API key or a
+//! token with the `admin` role.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Instant;
+
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use bumpalo::Bump;
+use helix_metrics::prometheus::{RouteCounters, route_counters};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::helix_engine::bm25::bm25::Bm25Stats;
+use crate::helix_engine::graph::random_walk::{WalkConfig, co_occurrences, random_walks};
+use crate::helix_engine::storage_core::{HelixGraphStorage, fsck};
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, Config};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::mcp::resources::stored_label;
+use crate::helix_gateway::reload::Tunables;
+use crate::helix_gateway::worker_pool::WorkerPoolStats;
+use crate::protocol::HelixError;
+use crate::protocol::parquet::{self, PARQUET_MEDIA_TYPE};
+use crate::protocol::value::Value;
+use crate::utils::items::{Edge, Node};
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// JWT role granting access to the admin API
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Stands in for secrets in the reported configuration
+pub const REDACTED: &str = "<redacted>";
+
+/// Whether the caller holds an `admin` scoped API key or a token with the `admin` role
+pub(crate) fn is_admin(caller: &Caller) -> bool {
+    match caller {
+        Caller::ApiKey(key) => key.scope == ApiKeyScope::Admin,
+        Caller::Jwt(principal) => principal.roles.iter().any(|r| r == ADMIN_ROLE),
+    }
+}
+
+/// Rejects callers that may not use the admin API
+pub struct AdminAuth;
+
+impl FromRequestParts<Arc<AppState>> for AdminAuth {
+    type Rejection = HelixError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let api_key = parts
+                .headers
+                .get("x-api-key")
+                .and_then(|v| v.to_str().ok())
+                .ok_or(HelixError::InvalidApiKey)?;
+            verify_key(api_key)?;
+        }
+
+        let BearerAuth(caller) = BearerAuth::from_request_parts(parts, state).await?;
+        match caller {
+            Some(caller) if !is_admin(&caller) => Err(HelixError::NotAdmin),
+            _ => Ok(AdminAuth),
+        }
+    }
+}
+
+/// What the admin API reports beyond the app state
+pub struct AdminInfo {
+    config: RwLock<Config>,
+    started: Instant,
+}
+
+impl AdminInfo {
+    /// `config` is reported with gateway defaults filled in, without the schema (served by
+    /// `/introspect`) and with API key hashes and connection string passwords redacted
+    pub fn new(config: &Config, workers_per_core: usize) -> Self {
+        let mut gateway_config = config.gateway_config().effective();
+        gateway_config.workers_per_core = Some(workers_per_core);
+        for key in gateway_config.api_keys.iter_mut().flatten() {
+            key.key_hash = REDACTED.to_string();
+        }
+        if let Some(sync) = gateway_config.postgres_sync.as_mut() {
+            sync.url = redact_password(&sync.url);
+        }
+        if let Some(standby) = gateway_config.standby.as_mut() {
+            standby.leader = redact_password(&standby.leader);
+        }
+        if let Some(jwt) = gateway_config.jwt.as_mut() {
+            jwt.jwks_url = redact_password(&jwt.jwks_url);
+        }
+        if let Some(directory_url) = gateway_config
+            .tls
+            .as_mut()
+            .and_then(|tls| tls.acme.as_mut())
+            .and_then(|acme| acme.directory_url.as_mut())
+        {
+            *directory_url = redact_password(directory_url);
+        }
+        AdminInfo {
+            config: RwLock::new(Config {
+                schema: None,
+                gateway_config: Some(gateway_config),
+                ..config.clone()
+            }),
+            started: Instant::now(),
+        }
+    }
+
+    /// Report the tunables the instance was reloaded with
+    pub fn set_tunables(&self, tunables: &Tunables) {
+        let mut config = self.config.write().unwrap_or_else(PoisonError::into_inner);
+        tunables.apply_to(config.gateway_config.get_or_insert_with(Default::default));
+    }
+}
+
+/// `connection` with the password of a URL's `user:password@` and the values of `password=`
+/// parameters, as in libpq key-value strings and URL query strings, redacted
+fn redact_password(connection: &str) -> String {
+    let mut connection = connection.to_string();
+    if let Some(scheme_end) = connection.find("://") {
+        let start = scheme_end + "://".len();
+        let end = connection[start..]
+            .find(['/', '?', '#'])
+            .map_or(connection.len(), |i| start + i);
+        if let Some(at) = connection[start..end].rfind('@')
+            && let Some(colon) = connection[start..start + at].find(':')
+        {
+            connection.replace_range(start + colon + 1..start + at, REDACTED);
+        }
+    }
+    connection
+        .split_inclusive([' ', '?', '&'])
+        .map(|part| match part.strip_prefix("password=") {
+            Some(value) => {
+                let separator = value.len() - usize::from(value.ends_with([' ', '?', '&']));
+                format!("password={REDACTED}{}", &value[separator..])
+            }
+            None => part.to_string(),
+        })
+        .collect()
+}
+
+/// A query route and how the gateway treats it
+#[derive(Debug, Serialize)]
+pub struct RouteInfo {
+    pub name: String,
+    pub write: bool,
+    /// Scheduled on the batch lane by default
+    pub batch: bool,
+    pub cache_ttl_ms: Option<u64>,
+    pub roles: Vec<String>,
+    pub labels: Option<Vec<String>>,
+    /// Most requests of the route run at once
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminRoutes {
+    pub routes: Vec<RouteInfo>,
+    pub mcp_routes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminStats {
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    /// Latest schema version of each versioned node, edge and vector type
+    pub schema_versions: BTreeMap<String, u8>,
+    pub workers: WorkerPoolStats,
+    /// Size of the LMDB data file on disk
+    pub data_size_bytes: Option<u64>,
+    /// Requests handled per route since the instance started
+    pub routes: Vec<RouteCounters>,
+}
+
+/// Lists the query and MCP routes, sorted by name
+pub async fn admin_routes_handler(_: AdminAuth, State(state): State<Arc<AppState>>) -> Response {
+    let router = state.worker_pool.routes();
+    let mut routes: Vec<RouteInfo> = router
+        .routes
+        .keys()
+        .map(|name| RouteInfo {
+            name: name.clone(),
+            write: router.is_write_route(name),
+            batch: router.batch_routes.contains(name),
+            cache_ttl_ms: router
+                .cache_routes
+                .get(name)
+                .map(|ttl| ttl.as_millis() as u64),
+            roles: router.route_roles(name).to_vec(),
+            labels: router.route_labels(name).map(<[String]>::to_vec),
+            max_concurrency: router.concurrency_limit(name).map(|limit| limit.limit),
+        })
+        .collect();
+    routes.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut mcp_routes: Vec<String> = router.mcp_routes.keys().cloned().collect();
+    mcp_routes.sort();
+    json_response(&AdminRoutes { routes, mcp_routes })
+}
+
+/// The configuration the instance runs with
+pub async fn admin_config_handler(
+    _: AdminAuth,
+    Extension(info): Extension<Arc<AdminInfo>>,
+) -> Response {
+    json_response(&*info.config.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Uptime, schema versions, worker pool load, data size and per-route counters
+pub async fn admin_stats_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Extension(info): Extension<Arc<AdminInfo>>,
+) -> Response {
+    let schema_versions = state
+        .worker_pool
+        .graph()
+        .storage
+        .version_info
+        .0
+        .iter()
+        .map(|(label, item)| (label.to_string(), item.latest))
+        .collect();
+    json_response(&AdminStats {
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs: info.started.elapsed().as_secs(),
+        schema_versions,
+        workers: state.worker_pool.stats(),
+        data_size_bytes: state
+            .worker_pool
+            .graph()
+            .storage
+            .graph_env
+            .real_disk_size()
+            .ok(),
+        routes: route_counters(),
+    })
+}
+
+/// Every node (`/admin/export/nodes/{label}`) or edge (`/admin/export/edges/{label}`) with a
+/// label as a Parquet table: a row per item with its id, `from_node` and `to_node` for edges,
+/// then its properties. The table is built in memory, so export large graphs with the CLI.
+pub async fn admin_export_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Path((kind, label)): Path<(String, String)>,
+) -> Response {
+    let edges = match kind.as_str() {
+        "nodes" => false,
+        "edges" => true,
+        _ => return (StatusCode::NOT_FOUND, "Export nodes or edges").into_response(),
+    };
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let scan_label = label.clone();
+    let rows = tokio::task::spawn_blocking(move || label_rows(&storage, edges, &scan_label)).await;
+    let rows = match rows {
+        Ok(Ok(rows)) => rows,
+        Ok(Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if rows.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No {kind} are labeled {label}"),
+        )
+            .into_response();
+    }
+    match parquet::encode_rows(&rows) {
+        Ok(body) => Response::builder()
+            .header("Content-Type", PARQUET_MEDIA_TYPE)
+            .header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{label}.parquet\""),
+            )
+            .body(Body::from(body))
+            .expect("should be able to make response from an export"),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// What to walk for `/admin/walks`: the [`WalkConfig`] fields, and a `window` to answer with
+/// the co-occurring pairs of nodes instead of the walks
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct WalksRequest {
+    #[serde(flatten)]
+    pub walk: WalkConfig,
+    pub window: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum WalksResponse {
+    Walks(Vec<Vec<String>>),
+    Pairs(Vec<[String; 2]>),
+}
+
+/// Node2Vec style random walks over the whole graph as `{"walks": [[id, ...], ...]}`, or
+/// `{"pairs": [[id, id], ...]}` when the body sets a `window`. Like the Parquet export, the
+/// answer is built in memory.
+pub async fn admin_walks_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    body: Bytes,
+) -> Response {
+    let request: WalksRequest = match body.is_empty() {
+        true => WalksRequest::default(),
+        false => match sonic_rs::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        },
+    };
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let walks = tokio::task::spawn_blocking(move || {
+        let txn = storage.graph_env.read_txn()?;
+        random_walks(&storage, &txn, &request.walk).map(|walks| (walks, request.window))
+    })
+    .await;
+    let uuid = |id: u128| Uuid::from_u128(id).to_string();
+    match walks {
+        Ok(Ok((walks, None))) => json_response(&WalksResponse::Walks(
+            walks
+                .into_iter()
+                .map(|walk| walk.into_iter().map(uuid).collect())
+                .collect(),
+        )),
+        Ok(Ok((walks, Some(window)))) => json_response(&WalksResponse::Pairs(
+            co_occurrences(&walks, window)
+                .into_iter()
+                .map(|(node, other)| [uuid(node), uuid(other)])
+                .collect(),
+        )),
+        Ok(Err(e @ GraphError::New(_))) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Check that the tables of the stored graph agree with each other
+pub async fn admin_fsck_handler(_: AdminAuth, State(state): State<Arc<AppState>>) -> Response {
+    fsck_response(&state, false).await
+}
+
+/// Check the stored graph and fix what can be fixed without losing data. Writes wait until
+/// the repair commits.
+pub async fn admin_fsck_repair_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    fsck_response(&state, true).await
+}
+
+async fn fsck_response(state: &AppState, repair: bool) -> Response {
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let report = tokio::task::spawn_blocking(move || match repair {
+        true => fsck::repair(&storage),
+        false => fsck::check(&storage),
+    })
+    .await;
+    match report {
+        Ok(Ok(report)) => json_response(&report),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Serialize)]
+struct RebuildReport {
+    vectors: usize,
+    duration_ms: u128,
+}
+
+/// Relink the HNSW graph from scratch on all cores, dropping deleted vectors from it. Writes
+/// wait until the rebuild commits.
+pub async fn admin_rebuild_vectors_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let rebuilt = tokio::task::spawn_blocking(move || -> Result<RebuildReport, GraphError> {
+        let start = Instant::now();
+        let mut txn = storage.graph_env.write_txn()?;
+        let vectors = storage.vectors.rebuild_hnsw(&mut txn)?;
+        txn.commit()?;
+        Ok(RebuildReport {
+            vectors,
+            duration_ms: start.elapsed().as_millis(),
+        })
+    })
+    .await;
+    match rebuilt {
+        Ok(Ok(report)) => json_response(&report),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Segments, postings and merges of the BM25 index. 404s when BM25 is off.
+pub async fn admin_bm25_handler(_: AdminAuth, State(state): State<Arc<AppState>>) -> Response {
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let stats = tokio::task::spawn_blocking(move || -> Result<Option<Bm25Stats>, GraphError> {
+        let Some(bm25) = &storage.bm25 else {
+            return Ok(None);
+        };
+        let txn = storage.graph_env.read_txn()?;
+        bm25.stats(&txn).map(Some)
+    })
+    .await;
+    match stats {
+        Ok(Ok(Some(stats))) => json_response(&stats),
+        Ok(Ok(None)) => (StatusCode::NOT_FOUND, "BM25 is not enabled").into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Rows of the nodes or edges with `label`, skipping the decoding of any other item
+fn label_rows(
+    storage: &HelixGraphStorage,
+    edges: bool,
+    label: &str,
+) -> Result<Vec<Vec<(String, Value)>>, GraphError> {
+    let txn = storage.graph_env.read_txn()?;
+    let db = match edges {
+        true => storage.edges_db,
+        false => storage.nodes_db,
+    };
+    let mut rows = Vec::new();
+    let mut arena = Bump::new();
+    for item in db.iter(&txn)? {
+        let (id, bytes) = item?;
+        if stored_label(bytes)? != label {
+            continue;
+        }
+        let mut row = vec![(
+            "id".to_string(),
+            Value::String(Uuid::from_u128(id).to_string()),
+        )];
+        let properties = match edges {
+            true => {
+                let edge = Edge::from_bincode_bytes(id, bytes, &arena)?;
+                for (column, node) in [("from_node", edge.from_node), ("to_node", edge.to_node)] {
+                    row.push((
+                        column.to_string(),
+                        Value::String(Uuid::from_u128(node).to_string()),
+                    ));
+                }
+                edge.properties
+            }
+            false => Node::from_bincode_bytes(id, bytes, &arena)?.properties,
+        };
+        row.extend(owned(properties));
+        rows.push(row);
+        arena.reset();
+    }
+    Ok(rows)
+}
+
+fn owned(properties: Option<ImmutablePropertiesMap<'_>>) -> Vec<(String, Value)> {
+    properties
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn json_response(body: &impl Serialize) -> Response {
+    match sonic_rs::to_vec(body) {
+        Ok(body) => Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("should be able to make response from admin info"),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize admin info",
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,102 @@
+//! API keys for query routes.
+//!
+//! Keys are provisioned with `helix auth create-key`, which stores only their SHA-256 hash in
+//! the instance's gateway config. A read-only key is rejected on write routes.
+
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+use crate::helix_engine::traversal_core::config::{ApiKeyConfig, ApiKeyScope};
+
+/// Prefix of generated keys, so they are recognisable in configs and secret scanners
+pub const KEY_PREFIX: &str = "hx_";
+
+/// Generate a new random API key
+pub fn generate_api_key() -> String {
+    let bytes: [u8; 32] = rand::random();
+    format!("{KEY_PREFIX}{}", to_hex(&bytes))
+}
+
+/// Hex encoded SHA-256 hash of a key, as stored in the instance config
+pub fn hash_api_key(key: &str) -> String {
+    to_hex(&Sha256::digest(key.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    for (byte, chunk) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let chunk = std::str::from_utf8(chunk).ok()?;
+        *byte = u8::from_str_radix(chunk, 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// The key a request was authenticated with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizedKey {
+    pub name: String,
+    pub scope: ApiKeyScope,
+}
+
+impl AuthorizedKey {
+    pub fn can_call(&self, is_write: bool) -> bool {
+        !is_write || self.scope != ApiKeyScope::ReadOnly
+    }
+}
+
+struct ProvisionedKey {
+    hash: [u8; 32],
+    key: AuthorizedKey,
+}
+
+/// The API keys an instance accepts
+#[derive(Default)]
+pub struct ApiKeys(Vec<ProvisionedKey>);
+
+impl ApiKeys {
+    /// Keys with a malformed hash are skipped with a warning
+    pub fn from_config(keys: &[ApiKeyConfig]) -> Self {
+        let keys = keys
+            .iter()
+            .filter_map(|config| match from_hex(&config.key_hash) {
+                Some(hash) => Some(ProvisionedKey {
+                    hash,
+                    key: AuthorizedKey {
+                        name: config.name.clone(),
+                        scope: config.scope,
+                    },
+                }),
+                None => {
+                    warn!(name = %config.name, "Ignoring API key with malformed key_hash");
+                    None
+                }
+            })
+            .collect();
+        ApiKeys(keys)
+    }
+
+    /// Whether query routes require a key
+    pub fn is_enabled(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    /// The key matching `token`. Every key is compared so timing doesn't reveal which matched.
+    pub fn verify(&self, token: &str) -> Option<AuthorizedKey> {
+        let provided = Sha256::digest(token.as_bytes());
+        let mut matched = None;
+        for provisioned in &self.0 {
+            if bool::from(provided.ct_eq(&provisioned.hash[..])) {
+                matched = Some(&provisioned.key);
+            }
+        }
+        matched.cloned()
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::helix_engine::storage_core::audit_log::{self, AuditEntry, AuditFilter};
+use crate::helix_gateway::admin::AdminAuth;
+use crate::helix_gateway::gateway::AppState;
+use crate::protocol::Request;
+
+/// Identify a caller by a fingerprint of their API key, never the key itself
+pub fn api_key_fingerprint(api_key: &str) -> String {
+    let digest = Sha256::digest(api_key.as_bytes());
+    let hex: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+    format!("key:{hex}")
+}
+
+/// Audit the transactions the writer thread commits while running `request`
+pub(crate) fn begin(request: &Request) {
+    audit_log::begin(
+        request.name.clone(),
+        request.api_key.as_deref().map(api_key_fingerprint),
+    );
+}
+
+#[derive(Serialize)]
+struct AuditLogResponse {
+    entries: Vec<AuditEntry>,
+}
+
+/// Lists audit log entries, newest first, filtered by the query string
+/// (`since_ms`, `until_ms`, `query`, `actor`, `item_id`, `limit`).
+pub async fn audit_log_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<AuditFilter>,
+) -> axum::response::Response {
+    let entries = match state.worker_pool.graph().storage.audit_entries(&filter) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!(?e, "Failed to read audit log");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not read audit log",
+            )
+                .into_response();
+        }
+    };
+
+    match sonic_rs::to_vec(&AuditLogResponse { entries }) {
+        Ok(body) => axum::response::Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("should be able to make response from audit entries"),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize audit log",
+        )
+            .into_response(),
+    }
+}
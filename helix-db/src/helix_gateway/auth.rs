@@ -0,0 +1,111 @@
+//! Authentication of query requests by `Authorization: Bearer` token.
+//!
+//! A token is either an API key provisioned for the instance or a JWT from the configured
+//! identity provider. API keys are limited by their scope; JWT callers by the roles a route
+//! declares with `#[roles(...)]`.
+
+use std::sync::Arc;
+
+use axum::extract::FromRequestParts;
+use axum::http::{header::AUTHORIZATION, request::Parts};
+
+use crate::helix_gateway::api_keys::AuthorizedKey;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::jwt::JwtPrincipal;
+use crate::protocol::HelixError;
+
+/// Who made a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Caller {
+    ApiKey(AuthorizedKey),
+    Jwt(JwtPrincipal),
+}
+
+impl Caller {
+    /// Check the caller may call `route`. API keys are service credentials and aren't
+    /// subject to route roles.
+    pub fn authorize(
+        &self,
+        route: &str,
+        is_write: bool,
+        roles: &[String],
+    ) -> Result<(), HelixError> {
+        match self {
+            Caller::ApiKey(key) if !key.can_call(is_write) => Err(HelixError::ReadOnlyApiKey {
+                route: route.to_string(),
+            }),
+            Caller::Jwt(principal) if !principal.has_any_role(roles) => {
+                Err(HelixError::MissingRole {
+                    route: route.to_string(),
+                    required: roles.to_vec(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Identifies the caller across requests, keeping key names and JWT subjects apart
+    pub fn id(&self) -> String {
+        match self {
+            Caller::ApiKey(key) => format!("key:{}", key.name),
+            Caller::Jwt(principal) => {
+                format!("jwt:{}", principal.subject.as_deref().unwrap_or_default())
+            }
+        }
+    }
+
+    /// Name of the caller for logs
+    pub fn name(&self) -> &str {
+        match self {
+            Caller::ApiKey(key) => &key.name,
+            Caller::Jwt(principal) => principal.subject.as_deref().unwrap_or("<no subject>"),
+        }
+    }
+}
+
+/// Token of an `Authorization: Bearer <token>` header value
+pub(crate) fn bearer_token(value: &str) -> Option<&str> {
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Extracts the caller a request was made by. Holds `None` when the instance has neither
+/// API keys nor JWT auth configured; otherwise a missing or invalid token rejects the request.
+pub struct BearerAuth(pub Option<Caller>);
+
+impl FromRequestParts<Arc<AppState>> for BearerAuth {
+    type Rejection = HelixError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(bearer_token);
+        authenticate(state, token).await.map(BearerAuth)
+    }
+}
+
+/// Identify the caller presenting `token`, an API key or a JWT. `None` when the instance has
+/// neither configured; otherwise a missing or invalid token is an error.
+pub(crate) async fn authenticate(
+    state: &AppState,
+    token: Option<&str>,
+) -> Result<Option<Caller>, HelixError> {
+    if !state.api_keys.is_enabled() && state.jwt.is_none() {
+        return Ok(None);
+    }
+    let token = token.ok_or(HelixError::MissingBearerToken)?;
+
+    if let Some(key) = state.api_keys.verify(token) {
+        return Ok(Some(Caller::ApiKey(key)));
+    }
+    match &state.jwt {
+        Some(jwt) => Ok(Some(Caller::Jwt(jwt.verify(token).await?))),
+        None => Err(HelixError::InvalidApiKey),
+    }
+}
@@ -0,0 +1,206 @@
+//! Several queries in one HTTP call, for clients that would otherwise pay a round trip per query.
+//!
+//! `POST /batch` takes a JSON array of `{"name": ..., "params": {...}}` and answers with an
+//! array holding, at the same index, either `{"status": 200, "result": ...}` or the error of
+//! that query. Consecutive reads run in parallel across the readers. A write waits for the
+//! reads before it and runs to completion before anything after it starts, so later queries
+//! see its effects.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use sonic_rs::LazyValue;
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError, error::ErrorBody};
+
+/// Largest number of queries accepted in one batch
+pub const MAX_BATCH_QUERIES: usize = 100;
+
+#[derive(Deserialize)]
+struct BatchQuery<'a> {
+    name: String,
+    /// Passed to the query as its request body; omitted for queries without parameters
+    #[serde(borrow, default)]
+    params: Option<LazyValue<'a>>,
+}
+
+#[derive(Serialize)]
+struct BatchError {
+    status: u16,
+    #[serde(flatten)]
+    body: ErrorBody,
+}
+
+pub async fn batch_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
+    body: Bytes,
+) -> Response {
+    let api_key = {
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+                return (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "Missing x-api-key header",
+                )
+                    .into_response();
+            };
+            if let Err(e) = verify_key(api_key) {
+                info!(?e, "Invalid API key");
+                return e.into_response();
+            }
+            Some(api_key.to_string())
+        }
+        #[cfg(not(feature = "api-key"))]
+        None::<String>
+    };
+
+    let queries: Vec<BatchQuery> = match sonic_rs::from_slice(&body) {
+        Ok(queries) => queries,
+        Err(e) => return HelixError::InvalidBatch(e.to_string()).into_response(),
+    };
+    if queries.len() > MAX_BATCH_QUERIES {
+        return HelixError::BatchTooLarge {
+            len: queries.len(),
+            max: MAX_BATCH_QUERIES,
+        }
+        .into_response();
+    }
+
+    let hints = RequestHints::from_headers(&headers);
+    let span = info_span!("helix.batch", queries = queries.len(), otel.kind = "server",);
+    otel::set_remote_parent(&span, &headers);
+
+    let ctx = BatchContext {
+        state: &state,
+        caller: auth.0.as_ref(),
+        addr,
+        headers: &headers,
+        hints: &hints,
+    };
+    let results = async {
+        let mut results = Vec::with_capacity(queries.len());
+        let mut reads = Vec::new();
+        for query in queries {
+            let body = query
+                .params
+                .map(|params| Bytes::copy_from_slice(params.as_raw_str().as_bytes()))
+                .unwrap_or_default();
+            let req = Request {
+                name: query.name,
+                req_type: RequestType::Query,
+                api_key: api_key.clone(),
+                body,
+                in_fmt: Format::Json,
+                out_fmt: Format::Json,
+            };
+            if state.worker_pool.is_write_route(&req.name) {
+                results.extend(join_all(reads.drain(..)).await);
+                results.push(ctx.run(req).await);
+            } else {
+                reads.push(ctx.run(req));
+            }
+        }
+        results.extend(join_all(reads).await);
+        results
+    }
+    .instrument(span)
+    .await;
+
+    encode_results(results)
+}
+
+struct BatchContext<'a> {
+    state: &'a AppState,
+    caller: Option<&'a Caller>,
+    addr: Option<IpAddr>,
+    headers: &'a HeaderMap,
+    hints: &'a RequestHints,
+}
+
+impl BatchContext<'_> {
+    /// Authorize, rate limit and execute one query of the batch
+    async fn run(&self, req: Request) -> Result<Vec<u8>, HelixError> {
+        let start_time = Instant::now();
+        let pool = &self.state.worker_pool;
+        if let Some(caller) = self.caller {
+            caller.authorize(
+                &req.name,
+                pool.is_write_route(&req.name),
+                &pool.route_roles(&req.name),
+            )?;
+        }
+        let limiter = &self.state.rate_limiter;
+        if limiter.is_enabled() {
+            let client = limiter.client_id(self.caller, self.addr, self.headers);
+            limiter.check(&req.name, &client)?;
+        }
+
+        let query_name = req.name.clone();
+        let span = info_span!("helix.request", route = %query_name);
+        let res = pool
+            .process_with(req, self.hints.clone())
+            .instrument(span)
+            .await;
+        if !matches!(res, Err(HelixError::NotFound { .. })) {
+            helix_metrics::prometheus::observe_request(
+                &query_name,
+                res.is_ok(),
+                start_time.elapsed(),
+            );
+        }
+        if let Err(e) = &res {
+            info!(query = %query_name, error = ?e, "Error response in batch");
+        }
+        res.map(|r| r.body)
+    }
+}
+
+/// JSON array of the results, in the order the queries were given
+fn encode_results(results: Vec<Result<Vec<u8>, HelixError>>) -> Response {
+    let mut out = Vec::from(b"[".as_slice());
+    for (i, result) in results.into_iter().enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        match result {
+            Ok(body) => {
+                out.extend_from_slice(br#"{"status":200,"result":"#);
+                out.extend_from_slice(if body.is_empty() { b"null" } else { &body });
+                out.push(b'}');
+            }
+            Err(e) => {
+                let error = BatchError {
+                    status: e.status().as_u16(),
+                    body: e.body(),
+                };
+                out.extend(sonic_rs::to_vec(&error).expect("batch error should always serialize"));
+            }
+        }
+    }
+    out.push(b']');
+
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(out))
+        .expect("should be able to make response from batch results")
+}
@@ -0,0 +1,38 @@
+//! Limits on the size of request bodies.
+//!
+//! Bodies declaring a `Content-Length` above `max_request_bytes` are rejected with 413 before
+//! any of them is read, and bodies sent without a length are cut off once they pass it. Imports
+//! at `/import/{query}` are added after the limit so it doesn't wrap them; they're streamed and
+//! hold each line to the limit instead (see `helix_gateway::import`).
+
+use axum::Router;
+use axum::extract::{DefaultBodyLimit, Request};
+use axum::http::header::CONTENT_LENGTH;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+
+use crate::protocol::HelixError;
+
+/// Hold the bodies of every route added to `router` so far to `max` bytes
+pub fn limit_request_bodies<S>(router: Router<S>, max: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(DefaultBodyLimit::max(max))
+        .layer(middleware::from_fn(move |req: Request, next: Next| {
+            reject_oversized(max, req, next)
+        }))
+}
+
+async fn reject_oversized(max: usize, req: Request, next: Next) -> Response {
+    let declared = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max as u64) {
+        return HelixError::BodyTooLarge { max }.into_response();
+    }
+    next.run(req).await
+}
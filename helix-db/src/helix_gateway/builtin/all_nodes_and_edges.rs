@@ -67,7 +67,7 @@ pub async fn nodes_edges_handler(
 
 pub fn nodes_edges_inner(input: HandlerInput) -> Result<protocol::Response, GraphError> {
     let db = Arc::clone(&input.graph.storage);
-    let txn = db.graph_env.read_txn().map_err(GraphError::from)?;
+    let txn = db.read_txn().map_err(GraphError::from)?;
     let arena = bumpalo::Bump::new();
 
     let (limit, node_label) = if !input.request.body.is_empty() {
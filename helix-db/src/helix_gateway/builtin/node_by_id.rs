@@ -54,7 +54,7 @@ pub async fn node_details_handler(
 
 pub fn node_details_inner(input: HandlerInput) -> Result<protocol::Response, GraphError> {
     let db = Arc::clone(&input.graph.storage);
-    let txn = db.graph_env.read_txn().map_err(GraphError::from)?;
+    let txn = db.read_txn().map_err(GraphError::from)?;
     let arena = bumpalo::Bump::new();
 
     let node_id_str = if !input.request.body.is_empty() {
@@ -56,7 +56,7 @@ pub async fn nodes_by_label_handler(
 
 pub fn nodes_by_label_inner(input: HandlerInput) -> Result<protocol::Response, GraphError> {
     let db = Arc::clone(&input.graph.storage);
-    let txn = db.graph_env.read_txn().map_err(GraphError::from)?;
+    let txn = db.read_txn().map_err(GraphError::from)?;
     let arena = bumpalo::Bump::new();
 
     let (label, limit) = if !input.request.body.is_empty() {
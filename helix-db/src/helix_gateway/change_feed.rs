@@ -0,0 +1,67 @@
+//! Notifications of committed writes, for consumers that react to data changing.
+//!
+//! A write is described by the labels its route touches, the same granularity the result
+//! cache invalidates at. Publishing is skipped while nobody listens.
+
+use tokio::sync::broadcast;
+
+/// Changes buffered per listener before it starts missing them
+const CHANGE_FEED_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A write committed. `labels` is `None` when the route's labels are unknown, so the
+    /// write may have touched anything.
+    Write { labels: Option<Vec<String>> },
+    /// The gateway stopped accepting requests
+    ShuttingDown,
+}
+
+impl Change {
+    /// Whether data read through `read_labels` may have changed. `None` means the reader's
+    /// labels are unknown.
+    pub fn affects(&self, read_labels: Option<&[String]>) -> bool {
+        match (self, read_labels) {
+            (
+                Change::Write {
+                    labels: Some(written),
+                },
+                Some(read),
+            ) => written.iter().any(|label| read.contains(label)),
+            (Change::Write { .. }, _) => true,
+            (Change::ShuttingDown, _) => false,
+        }
+    }
+}
+
+pub struct ChangeFeed {
+    tx: broadcast::Sender<Change>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        ChangeFeed {
+            tx: broadcast::channel(CHANGE_FEED_CAPACITY).0,
+        }
+    }
+}
+
+impl ChangeFeed {
+    /// Announce a committed write that touched `labels`
+    pub fn publish_write(&self, labels: Option<&[String]>) {
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(Change::Write {
+                labels: labels.map(<[String]>::to_vec),
+            });
+        }
+    }
+
+    pub fn publish_shutdown(&self) {
+        let _ = self.tx.send(Change::ShuttingDown);
+    }
+
+    /// Receive changes published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.tx.subscribe()
+    }
+}
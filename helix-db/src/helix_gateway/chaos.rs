@@ -0,0 +1,280 @@
+//! Fault injection for testing clients and the engine's error paths.
+//!
+//! Only built with the `chaos` feature. Faults are configured with `HELIX_CHAOS`, a comma
+//! separated list of `fault=rate` pairs, where a rate is the chance in `[0, 1]` that a request
+//! hits the fault. Faults that slow things down take how long after a colon:
+//!
+//! ```text
+//! HELIX_CHAOS="io_error=0.05,map_full=0.01,continuation_delay=0.1:250ms,writer_stall=0.02:1s"
+//! ```
+//!
+//! - `io_error` fails a request with an IO error before its handler runs
+//! - `map_full` fails a write as if the LMDB map were full
+//! - `continuation_delay` holds back an IO continuation before it is sent to a worker
+//! - `writer_stall` blocks a writer thread before it picks up a write
+//!
+//! `seed=N` makes the faults hit the same requests on every run, and `routes=a|b` only
+//! injects faults into those routes. Injected faults are logged under [`CHAOS_TARGET`].
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use heed3::{Error as HeedError, MdbError};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tracing::{error, warn};
+
+use crate::helix_engine::types::GraphError;
+
+/// Log target of injected faults
+pub const CHAOS_TARGET: &str = "helix_chaos";
+
+/// Environment variable the faults are read from
+pub const CHAOS_ENV: &str = "HELIX_CHAOS";
+
+/// A fault that slows requests down instead of failing them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delay {
+    pub rate: f64,
+    pub duration: Duration,
+}
+
+/// Which faults to inject, and how often
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChaosConfig {
+    pub io_error: f64,
+    pub map_full: f64,
+    pub continuation_delay: Option<Delay>,
+    pub writer_stall: Option<Delay>,
+    pub seed: Option<u64>,
+    /// Routes faults are injected into, or every route when empty
+    pub routes: Vec<String>,
+}
+
+impl ChaosConfig {
+    /// Parse a `HELIX_CHAOS` spec such as `io_error=0.05,writer_stall=0.02:1s`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut config = ChaosConfig::default();
+        for pair in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected fault=rate, got '{pair}'"))?;
+            match key.trim() {
+                "io_error" => config.io_error = parse_rate(value)?,
+                "map_full" => config.map_full = parse_rate(value)?,
+                "continuation_delay" => config.continuation_delay = Some(parse_delay(value)?),
+                "writer_stall" => config.writer_stall = Some(parse_delay(value)?),
+                "seed" => {
+                    config.seed = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|_| format!("invalid seed '{value}'"))?,
+                    )
+                }
+                "routes" => {
+                    config.routes = value
+                        .split('|')
+                        .map(str::trim)
+                        .filter(|route| !route.is_empty())
+                        .map(String::from)
+                        .collect()
+                }
+                other => return Err(format!("unknown fault '{other}'")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!(
+            "invalid rate '{value}', expected a number from 0 to 1"
+        )),
+    }
+}
+
+fn parse_delay(value: &str) -> Result<Delay, String> {
+    let (rate, duration) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected rate:duration, got '{value}'"))?;
+    let duration = duration.trim();
+    let (amount, unit_ms) = if let Some(ms) = duration.strip_suffix("ms") {
+        (ms, 1)
+    } else if let Some(secs) = duration.strip_suffix('s') {
+        (secs, 1000)
+    } else {
+        (duration, 1)
+    };
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{duration}', expected e.g. 250ms or 2s"))?;
+    Ok(Delay {
+        rate: parse_rate(rate)?,
+        duration: Duration::from_millis(amount * unit_ms),
+    })
+}
+
+/// How many faults have been injected since chaos was configured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    pub io_errors: u64,
+    pub map_full: u64,
+    pub delayed_continuations: u64,
+    pub writer_stalls: u64,
+}
+
+/// Injects the faults of a [`ChaosConfig`]
+pub struct Chaos {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+    io_errors: AtomicU64,
+    map_full: AtomicU64,
+    delayed_continuations: AtomicU64,
+    writer_stalls: AtomicU64,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        Chaos {
+            config,
+            rng: Mutex::new(rng),
+            io_errors: AtomicU64::new(0),
+            map_full: AtomicU64::new(0),
+            delayed_continuations: AtomicU64::new(0),
+            writer_stalls: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            io_errors: self.io_errors.load(Ordering::Relaxed),
+            map_full: self.map_full.load(Ordering::Relaxed),
+            delayed_continuations: self.delayed_continuations.load(Ordering::Relaxed),
+            writer_stalls: self.writer_stalls.load(Ordering::Relaxed),
+        }
+    }
+
+    fn targets(&self, route: &str) -> bool {
+        self.config.routes.is_empty() || self.config.routes.iter().any(|r| r == route)
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0
+            && self
+                .rng
+                .lock()
+                .expect("chaos rng lock poisoned")
+                .random_bool(rate)
+    }
+
+    /// The error to fail a request with instead of running its handler, if any
+    pub fn request_fault(&self, route: &str, is_write: bool) -> Option<GraphError> {
+        if !self.targets(route) {
+            return None;
+        }
+        if self.roll(self.config.io_error) {
+            self.io_errors.fetch_add(1, Ordering::Relaxed);
+            warn!(target: CHAOS_TARGET, route, fault = "io_error", "Injecting fault");
+            return Some(GraphError::Io(std::io::Error::other("injected IO error")));
+        }
+        if is_write && self.roll(self.config.map_full) {
+            self.map_full.fetch_add(1, Ordering::Relaxed);
+            warn!(target: CHAOS_TARGET, route, fault = "map_full", "Injecting fault");
+            return Some(HeedError::Mdb(MdbError::MapFull).into());
+        }
+        None
+    }
+
+    /// How long to hold back an IO continuation, if at all
+    pub fn continuation_delay(&self, route: &str) -> Option<Duration> {
+        let delay = self.config.continuation_delay?;
+        if !self.targets(route) || !self.roll(delay.rate) {
+            return None;
+        }
+        self.delayed_continuations.fetch_add(1, Ordering::Relaxed);
+        warn!(target: CHAOS_TARGET, route, fault = "continuation_delay", delay_ms = delay.duration.as_millis() as u64, "Injecting fault");
+        Some(delay.duration)
+    }
+
+    /// How long to block a writer before it runs a write, if at all
+    pub fn writer_stall(&self, route: &str) -> Option<Duration> {
+        let stall = self.config.writer_stall?;
+        if !self.targets(route) || !self.roll(stall.rate) {
+            return None;
+        }
+        self.writer_stalls.fetch_add(1, Ordering::Relaxed);
+        warn!(target: CHAOS_TARGET, route, fault = "writer_stall", stall_ms = stall.duration.as_millis() as u64, "Injecting fault");
+        Some(stall.duration)
+    }
+}
+
+static CHAOS: LazyLock<RwLock<Option<Arc<Chaos>>>> = LazyLock::new(|| {
+    let chaos = match std::env::var(CHAOS_ENV) {
+        Ok(spec) => match ChaosConfig::parse(&spec) {
+            Ok(config) => {
+                warn!(target: CHAOS_TARGET, spec, "Fault injection is enabled");
+                Some(Arc::new(Chaos::new(config)))
+            }
+            Err(e) => {
+                error!(target: CHAOS_TARGET, "Ignoring invalid {CHAOS_ENV}: {e}");
+                None
+            }
+        },
+        Err(_) => None,
+    };
+    RwLock::new(chaos)
+});
+
+/// The faults being injected, read from `HELIX_CHAOS` on first use
+pub fn current() -> Option<Arc<Chaos>> {
+    CHAOS.read().expect("chaos lock poisoned").clone()
+}
+
+/// Replace the faults being injected, or stop injecting them with `None`
+pub fn set(config: Option<ChaosConfig>) -> Option<Arc<Chaos>> {
+    let chaos = config.map(|config| Arc::new(Chaos::new(config)));
+    *CHAOS.write().expect("chaos lock poisoned") = chaos.clone();
+    chaos
+}
+
+pub(crate) fn request_fault(route: &str, is_write: bool) -> Option<GraphError> {
+    current()?.request_fault(route, is_write)
+}
+
+/// Block the calling writer thread if it should stall
+pub(crate) fn stall_writer(route: &str) {
+    if let Some(stall) = current().and_then(|chaos| chaos.writer_stall(route)) {
+        std::thread::sleep(stall);
+    }
+}
+
+/// Wrap an IO continuation future so it may be held back before it runs
+pub(crate) fn delay_continuation<F: Future>(
+    route: &str,
+    fut: F,
+) -> impl Future<Output = F::Output> + use<F> {
+    let delay = current().and_then(|chaos| chaos.continuation_delay(route));
+    async move {
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        fut.await
+    }
+}
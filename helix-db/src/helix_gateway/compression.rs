@@ -0,0 +1,30 @@
+//! Response compression negotiated by `Accept-Encoding`.
+
+use tower_http::compression::CompressionLayer;
+use tower_http::compression::predicate::{And, NotForContentType, Predicate, SizeAbove};
+
+use crate::helix_engine::traversal_core::config::{CompressionAlgorithm, CompressionConfig};
+
+/// Responses worth compressing: large enough, and not already compressed or streamed
+pub type CompressionPredicate =
+    And<And<And<SizeAbove, NotForContentType>, NotForContentType>, NotForContentType>;
+
+/// Build the compression layer applied to every gateway route, if enabled
+pub fn compression_layer(
+    config: &CompressionConfig,
+) -> Option<CompressionLayer<CompressionPredicate>> {
+    if !config.enabled() {
+        return None;
+    }
+    let predicate = SizeAbove::new(config.min_size_bytes())
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    Some(
+        CompressionLayer::new()
+            .gzip(config.allows(CompressionAlgorithm::Gzip))
+            .br(config.allows(CompressionAlgorithm::Br))
+            .zstd(config.allows(CompressionAlgorithm::Zstd))
+            .compress_when(predicate),
+    )
+}
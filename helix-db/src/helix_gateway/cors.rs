@@ -0,0 +1,124 @@
+//! CORS policy for browser apps calling the gateway from another origin.
+
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderName, HeaderValue, Method};
+use thiserror::Error;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer, ExposeHeaders};
+
+use crate::helix_engine::traversal_core::config::CorsConfig;
+use crate::protocol::error::{RATE_LIMIT_LIMIT, RATE_LIMIT_REMAINING, RATE_LIMIT_RESET};
+use crate::protocol::request::{PRIORITY_HEADER, READ_YOUR_WRITES_HEADER};
+
+/// Allows any value in a list
+const WILDCARD: &str = "*";
+
+const DEFAULT_METHODS: [Method; 2] = [Method::GET, Method::POST];
+
+#[derive(Debug, Error)]
+pub enum CorsError {
+    #[error("CORS `allowed_origins` is empty")]
+    NoOrigins,
+    #[error("Invalid CORS origin `{0}`")]
+    Origin(String),
+    #[error("Invalid CORS method `{0}`")]
+    Method(String),
+    #[error("Invalid CORS header `{0}`")]
+    Header(String),
+    #[error("CORS `allow_credentials` can't be combined with `*` in `{0}`")]
+    WildcardWithCredentials(&'static str),
+}
+
+/// Headers a browser app may send, besides the CORS-safelisted ones
+fn default_allowed_headers() -> Vec<HeaderName> {
+    vec![
+        CONTENT_TYPE,
+        AUTHORIZATION,
+        HeaderName::from_static("x-api-key"),
+        HeaderName::from_static(PRIORITY_HEADER),
+        HeaderName::from_static(READ_YOUR_WRITES_HEADER),
+        HeaderName::from_static("traceparent"),
+        HeaderName::from_static("tracestate"),
+    ]
+}
+
+/// Headers of gateway responses a browser app may read
+fn default_expose_headers() -> Vec<HeaderName> {
+    vec![
+        RETRY_AFTER,
+        HeaderName::from_static(RATE_LIMIT_LIMIT),
+        HeaderName::from_static(RATE_LIMIT_REMAINING),
+        HeaderName::from_static(RATE_LIMIT_RESET),
+    ]
+}
+
+/// Build the CORS layer applied to every gateway route
+pub fn cors_layer(config: &CorsConfig) -> Result<CorsLayer, CorsError> {
+    if config.allowed_origins.is_empty() {
+        return Err(CorsError::NoOrigins);
+    }
+    let credentials = config.allow_credentials();
+    if credentials {
+        let lists = [
+            ("allowed_origins", Some(&config.allowed_origins)),
+            ("allowed_methods", config.allowed_methods.as_ref()),
+            ("allowed_headers", config.allowed_headers.as_ref()),
+            ("expose_headers", config.expose_headers.as_ref()),
+        ];
+        if let Some((field, _)) = lists
+            .iter()
+            .find(|(_, list)| list.is_some_and(|list| is_wildcard(list)))
+        {
+            return Err(CorsError::WildcardWithCredentials(field));
+        }
+    }
+
+    let origins = if is_wildcard(&config.allowed_origins) {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(parse_all(&config.allowed_origins, |origin| {
+            HeaderValue::from_str(origin.trim_end_matches('/'))
+                .map_err(|_| CorsError::Origin(origin.to_string()))
+        })?)
+    };
+    let methods = match &config.allowed_methods {
+        Some(methods) if is_wildcard(methods) => AllowMethods::from(Any),
+        Some(methods) => AllowMethods::list(parse_all(methods, |method| {
+            Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .map_err(|_| CorsError::Method(method.to_string()))
+        })?),
+        None => AllowMethods::list(DEFAULT_METHODS),
+    };
+    let headers = match &config.allowed_headers {
+        Some(headers) if is_wildcard(headers) => AllowHeaders::from(Any),
+        Some(headers) => AllowHeaders::list(parse_all(headers, parse_header)?),
+        None => AllowHeaders::list(default_allowed_headers()),
+    };
+    let expose = match &config.expose_headers {
+        Some(headers) if is_wildcard(headers) => ExposeHeaders::from(Any),
+        Some(headers) => ExposeHeaders::list(parse_all(headers, parse_header)?),
+        None => ExposeHeaders::list(default_expose_headers()),
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(expose)
+        .allow_credentials(credentials)
+        .max_age(config.max_age()))
+}
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|value| value == WILDCARD)
+}
+
+fn parse_all<T>(
+    values: &[String],
+    parse: impl Fn(&str) -> Result<T, CorsError>,
+) -> Result<Vec<T>, CorsError> {
+    values.iter().map(|value| parse(value)).collect()
+}
+
+fn parse_header(name: &str) -> Result<HeaderName, CorsError> {
+    HeaderName::from_bytes(name.as_bytes()).map_err(|_| CorsError::Header(name.to_string()))
+}
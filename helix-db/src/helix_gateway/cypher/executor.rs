@@ -0,0 +1,940 @@
+//! Runs a parsed [`Statement`] against storage.
+//!
+//! Clauses turn a list of rows, each binding variables to nodes and relationships, into the
+//! next list, starting from a single empty row. `MATCH` walks the adjacency databases from
+//! the candidates of each pattern's first node, so patterns starting with a labelled node
+//! read only that label and an unlabelled one reads every node. `CREATE` inserts through the
+//! same traversal steps queries use, so secondary indices, BM25 and the write log see its
+//! writes. It keeps to the schema the container was compiled with: nodes and relationships
+//! need a type of the schema, relationships must join the node types theirs does, and
+//! properties must be declared and hold values of their types. Vectors aren't nodes here and
+//! are skipped when a relationship leads to one.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use bumpalo::Bump;
+use heed3::{RoTxn, RwTxn};
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::scan_counter;
+use crate::helix_engine::storage_core::storage_methods::StorageMethods;
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::add_e::AddEAdapter;
+use crate::helix_engine::traversal_core::ops::source::add_n::AddNAdapter;
+use crate::helix_engine::traversal_core::ops::source::n_from_type::NFromTypeAdapter;
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::cypher::CypherError;
+use crate::helix_gateway::cypher::parser::{
+    Clause, CompareOp, Count, Direction, Expr, Function, NodePattern, Pattern, RelPattern, Return,
+    Statement,
+};
+use crate::protocol::value::Value;
+use crate::utils::id::uuid_str;
+use crate::utils::items::{Edge, Node};
+use crate::utils::label_hash::hash_label;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// Variables bound so far, in the order they were bound
+type Row<'arena> = IndexMap<String, TraversalValue<'arena>>;
+
+/// The transaction a statement runs in; only statements that create need a write transaction
+pub enum Txn<'t, 'db> {
+    Read(&'t RoTxn<'db>),
+    Write(&'t mut RwTxn<'db>),
+}
+
+impl<'db> Txn<'_, 'db> {
+    fn ro(&self) -> &RoTxn<'db> {
+        match self {
+            Txn::Read(txn) => txn,
+            Txn::Write(txn) => txn,
+        }
+    }
+}
+
+/// Columns and rows a statement returned
+#[derive(Debug, Default)]
+pub struct StatementResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<sonic_rs::Value>>,
+}
+
+/// The node and relationship types `CREATE` keeps to, from the schema JSON the compiler
+/// embeds in the container
+#[derive(Deserialize, Default)]
+pub struct Schema {
+    #[serde(default)]
+    nodes: Vec<NodeType>,
+    #[serde(default)]
+    edges: Vec<EdgeType>,
+}
+
+#[derive(Deserialize)]
+struct SchemaJson {
+    #[serde(default)]
+    schema: Schema,
+}
+
+#[derive(Deserialize)]
+struct NodeType {
+    name: String,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct EdgeType {
+    name: String,
+    from: String,
+    to: String,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+impl Schema {
+    pub fn from_json(schema_json: &str) -> Result<Self, GraphError> {
+        let SchemaJson { schema } = sonic_rs::from_str(schema_json)
+            .map_err(|e| GraphError::New(format!("compiled schema is invalid: {e}")))?;
+        Ok(schema)
+    }
+}
+
+pub struct Executor<'db, 'arena, 'p> {
+    storage: &'db HelixGraphStorage,
+    arena: &'arena Bump,
+    params: &'p HashMap<String, Value>,
+    /// Unchecked without one, as for an instance run without a compiled schema
+    schema: Option<&'p Schema>,
+}
+
+impl<'db: 'arena, 'arena, 'p> Executor<'db, 'arena, 'p> {
+    pub fn new(
+        storage: &'db HelixGraphStorage,
+        arena: &'arena Bump,
+        params: &'p HashMap<String, Value>,
+        schema: Option<&'p Schema>,
+    ) -> Self {
+        Self {
+            storage,
+            arena,
+            params,
+            schema,
+        }
+    }
+
+    pub fn execute(
+        &self,
+        txn: &mut Txn<'_, 'db>,
+        statement: &Statement,
+    ) -> Result<StatementResult, CypherError> {
+        let mut rows = vec![Row::new()];
+        for clause in &statement.clauses {
+            rows = match clause {
+                Clause::Match { patterns, filter } => {
+                    let mut matched = Vec::new();
+                    for row in rows {
+                        let mut partial = vec![row];
+                        for pattern in patterns {
+                            let mut next = Vec::new();
+                            for row in &partial {
+                                self.match_pattern(txn.ro(), pattern, row, &mut next)?;
+                            }
+                            partial = next;
+                        }
+                        matched.extend(partial);
+                    }
+                    match filter {
+                        Some(filter) => {
+                            let mut kept = Vec::with_capacity(matched.len());
+                            for row in matched {
+                                if self.truth(filter, &row)? == Some(true) {
+                                    kept.push(row);
+                                }
+                            }
+                            kept
+                        }
+                        None => matched,
+                    }
+                }
+                Clause::Create(patterns) => match txn {
+                    Txn::Write(txn) => {
+                        let mut created = Vec::with_capacity(rows.len());
+                        for mut row in rows {
+                            for pattern in patterns {
+                                self.create_pattern(txn, pattern, &mut row)?;
+                            }
+                            created.push(row);
+                        }
+                        created
+                    }
+                    Txn::Read(_) => {
+                        return Err(CypherError::Invalid(
+                            "CREATE needs a write transaction".to_string(),
+                        ));
+                    }
+                },
+            };
+        }
+
+        match &statement.ret {
+            Some(ret) => self.project(statement, ret, rows),
+            None => Ok(StatementResult::default()),
+        }
+    }
+
+    fn match_pattern(
+        &self,
+        txn: &RoTxn<'db>,
+        pattern: &Pattern,
+        row: &Row<'arena>,
+        out: &mut Vec<Row<'arena>>,
+    ) -> Result<(), CypherError> {
+        for node in self.node_candidates(txn, &pattern.start, row)? {
+            if !self.node_matches(&node, &pattern.start, row)? {
+                continue;
+            }
+            let mut row = row.clone();
+            let from = node.id;
+            bind(&mut row, &pattern.start.var, TraversalValue::Node(node));
+            self.match_hops(txn, &pattern.hops, from, row, &mut Vec::new(), out)?;
+        }
+        Ok(())
+    }
+
+    fn match_hops(
+        &self,
+        txn: &RoTxn<'db>,
+        hops: &[(RelPattern, NodePattern)],
+        from: u128,
+        row: Row<'arena>,
+        used: &mut Vec<u128>,
+        out: &mut Vec<Row<'arena>>,
+    ) -> Result<(), CypherError> {
+        let Some(((rel, node_pattern), rest)) = hops.split_first() else {
+            out.push(row);
+            return Ok(());
+        };
+
+        for (edge, other) in self.expand(txn, from, rel)? {
+            // A relationship is matched at most once within a pattern
+            if used.contains(&edge.id) || !self.edge_matches(&edge, rel, &row)? {
+                continue;
+            }
+            let node = match bound_node(&row, &node_pattern.var) {
+                Some(node) if node.id == other => *node,
+                Some(_) => continue,
+                None => match self.storage.get_node(txn, &other, self.arena) {
+                    Ok(node) => node,
+                    Err(GraphError::NodeNotFound) => continue,
+                    Err(e) => return Err(e.into()),
+                },
+            };
+            if !self.node_matches(&node, node_pattern, &row)? {
+                continue;
+            }
+
+            let mut next = row.clone();
+            used.push(edge.id);
+            bind(&mut next, &rel.var, TraversalValue::Edge(edge));
+            bind(&mut next, &node_pattern.var, TraversalValue::Node(node));
+            self.match_hops(txn, rest, other, next, used, out)?;
+            used.pop();
+        }
+        Ok(())
+    }
+
+    /// Nodes the first node of a pattern can be: the one already bound to its variable, the
+    /// nodes with its label, or every node
+    fn node_candidates(
+        &self,
+        txn: &RoTxn<'db>,
+        pattern: &NodePattern,
+        row: &Row<'arena>,
+    ) -> Result<Vec<Node<'arena>>, CypherError> {
+        if let Some(node) = bound_node(row, &pattern.var) {
+            return Ok(vec![*node]);
+        }
+
+        let mut nodes = Vec::new();
+        match &pattern.label {
+            Some(label) => {
+                for item in G::new(self.storage, txn, self.arena).n_from_type(label) {
+                    if let TraversalValue::Node(node) = item? {
+                        nodes.push(node);
+                    }
+                }
+            }
+            None => {
+                for item in self.storage.nodes_db.iter(txn).map_err(GraphError::from)? {
+                    let (id, bytes) = item.map_err(GraphError::from)?;
+                    scan_counter::record(1);
+                    let node = Node::from_bincode_bytes(id, bytes, self.arena)
+                        .map_err(|e| GraphError::ConversionError(e.to_string()))?;
+                    nodes.push(node);
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Relationships leaving `from` in the pattern's direction, with the node at their other end
+    fn expand(
+        &self,
+        txn: &RoTxn<'db>,
+        from: u128,
+        rel: &RelPattern,
+    ) -> Result<Vec<(Edge<'arena>, u128)>, CypherError> {
+        let directions: &[Direction] = match rel.direction {
+            Direction::Out => &[Direction::Out],
+            Direction::In => &[Direction::In],
+            Direction::Both => &[Direction::Out, Direction::In],
+        };
+
+        let mut adjacent = Vec::new();
+        for direction in directions {
+            let db = match direction {
+                Direction::In => &self.storage.in_edges_db,
+                _ => &self.storage.out_edges_db,
+            };
+            match &rel.label {
+                Some(label) => {
+                    let hash = hash_label(label, None);
+                    let key = match direction {
+                        Direction::In => HelixGraphStorage::in_edge_key(&from, &hash),
+                        _ => HelixGraphStorage::out_edge_key(&from, &hash),
+                    };
+                    if let Some(duplicates) =
+                        db.get_duplicates(txn, &key).map_err(GraphError::from)?
+                    {
+                        for item in duplicates {
+                            let (_, data) = item.map_err(GraphError::from)?;
+                            adjacent.push(HelixGraphStorage::unpack_adj_edge_data(data)?);
+                        }
+                    }
+                }
+                None => {
+                    let prefix = from.to_be_bytes();
+                    for item in db.prefix_iter(txn, &prefix).map_err(GraphError::from)? {
+                        let (_, data) = item.map_err(GraphError::from)?;
+                        adjacent.push(HelixGraphStorage::unpack_adj_edge_data(data)?);
+                    }
+                }
+            }
+        }
+
+        let mut edges = Vec::with_capacity(adjacent.len());
+        for (edge_id, other) in adjacent {
+            scan_counter::record(1);
+            edges.push((self.storage.get_edge(txn, &edge_id, self.arena)?, other));
+        }
+        Ok(edges)
+    }
+
+    fn node_matches(
+        &self,
+        node: &Node<'arena>,
+        pattern: &NodePattern,
+        row: &Row<'arena>,
+    ) -> Result<bool, CypherError> {
+        if pattern
+            .label
+            .as_deref()
+            .is_some_and(|label| label != node.label)
+        {
+            return Ok(false);
+        }
+        self.properties_match(|key| node.get_property(key), &pattern.properties, row)
+    }
+
+    fn edge_matches(
+        &self,
+        edge: &Edge<'arena>,
+        pattern: &RelPattern,
+        row: &Row<'arena>,
+    ) -> Result<bool, CypherError> {
+        match row_value(row, &pattern.var) {
+            Some(TraversalValue::Edge(bound)) if bound.id != edge.id => return Ok(false),
+            _ => {}
+        }
+        self.properties_match(|key| edge.get_property(key), &pattern.properties, row)
+    }
+
+    fn properties_match<'v>(
+        &self,
+        property: impl Fn(&str) -> Option<&'v Value>,
+        expected: &[(String, Expr)],
+        row: &Row<'arena>,
+    ) -> Result<bool, CypherError> {
+        for (key, expr) in expected {
+            let expected = self.value(expr, row)?;
+            if property(key).is_none_or(|actual| *actual != expected) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn create_pattern(
+        &self,
+        txn: &mut RwTxn<'db>,
+        pattern: &Pattern,
+        row: &mut Row<'arena>,
+    ) -> Result<(), CypherError> {
+        let mut nodes = Vec::with_capacity(pattern.hops.len() + 1);
+        for node in pattern.nodes() {
+            if let Some(existing) = bound_node(row, &node.var) {
+                nodes.push((existing.id, existing.label));
+                continue;
+            }
+            let label = node.label.as_deref().ok_or_else(|| {
+                CypherError::Invalid("Nodes created with CREATE need a label".to_string())
+            })?;
+            let declared = match self.schema {
+                Some(schema) => {
+                    let ty = schema.nodes.iter().find(|ty| ty.name == label);
+                    let ty = ty.ok_or_else(|| {
+                        CypherError::Invalid(format!("`{label}` isn't a node type of the schema"))
+                    })?;
+                    Some(&ty.properties)
+                }
+                None => None,
+            };
+            let properties = self.properties(label, &node.properties, declared, row)?;
+            let indices: Vec<&str> = node
+                .properties
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .filter(|key| self.storage.secondary_indices.contains_key(*key))
+                .collect();
+            let created = G::new_mut(self.storage, self.arena, txn)
+                .add_n(self.arena.alloc_str(label), properties, Some(&indices))
+                .collect_to_obj()?;
+            let label = self.arena.alloc_str(label);
+            nodes.push((created.id(), label));
+            bind(row, &node.var, created);
+        }
+
+        for (i, (rel, _)) in pattern.hops.iter().enumerate() {
+            let ((from, from_label), (to, to_label)) = match rel.direction {
+                Direction::In => (nodes[i + 1], nodes[i]),
+                _ => (nodes[i], nodes[i + 1]),
+            };
+            let label = rel.label.as_deref().ok_or_else(|| {
+                CypherError::Invalid("Relationships created with CREATE need a type".to_string())
+            })?;
+            let declared = match self.schema {
+                Some(schema) => {
+                    let ty = schema.edges.iter().find(|ty| ty.name == label);
+                    let ty = ty.ok_or_else(|| {
+                        CypherError::Invalid(format!(
+                            "`{label}` isn't a relationship type of the schema"
+                        ))
+                    })?;
+                    if ty.from != from_label || ty.to != to_label {
+                        return Err(CypherError::Invalid(format!(
+                            "`{label}` relationships go from `{}` to `{}`, not from `{from_label}` \
+                             to `{to_label}`",
+                            ty.from, ty.to
+                        )));
+                    }
+                    Some(&ty.properties)
+                }
+                None => None,
+            };
+            let properties = self.properties(label, &rel.properties, declared, row)?;
+            let created = G::new_mut(self.storage, self.arena, txn)
+                .add_edge(
+                    self.arena.alloc_str(label),
+                    properties,
+                    from,
+                    to,
+                    false,
+                    false,
+                )
+                .collect_to_obj()?;
+            bind(row, &rel.var, created);
+        }
+        Ok(())
+    }
+
+    /// Properties to create an item of type `label` with. Null properties are left out, as in
+    /// Neo4j. The others must be among the `declared` properties and fit their types.
+    fn properties(
+        &self,
+        label: &str,
+        properties: &[(String, Expr)],
+        declared: Option<&BTreeMap<String, String>>,
+        row: &Row<'arena>,
+    ) -> Result<Option<ImmutablePropertiesMap<'arena>>, CypherError> {
+        let mut values = Vec::with_capacity(properties.len());
+        for (key, expr) in properties {
+            let value = self.value(expr, row)?;
+            if value == Value::Empty {
+                continue;
+            }
+            if let Some(declared) = declared {
+                match declared.get(key) {
+                    Some(ty) if !matches!(key.as_str(), "id" | "label") => {
+                        if !fits(&value, ty) {
+                            return Err(CypherError::Type(format!(
+                                "`{label}.{key}` holds {ty}, not {}",
+                                value.to_variant_string()
+                            )));
+                        }
+                    }
+                    _ => {
+                        return Err(CypherError::Invalid(format!(
+                            "`{label}` has no property `{key}`"
+                        )));
+                    }
+                }
+            }
+            values.push((self.arena.alloc_str(key) as &str, value));
+        }
+        Ok((!values.is_empty())
+            .then(|| ImmutablePropertiesMap::new(values.len(), values.into_iter(), self.arena)))
+    }
+
+    fn project(
+        &self,
+        statement: &Statement,
+        ret: &Return,
+        rows: Vec<Row<'arena>>,
+    ) -> Result<StatementResult, CypherError> {
+        let star;
+        let items: Vec<(&str, &Expr)> = match &ret.items {
+            Some(items) => items
+                .iter()
+                .map(|item| (item.name.as_str(), &item.expr))
+                .collect(),
+            None => {
+                star = variables(statement)
+                    .into_iter()
+                    .map(|var| (var, Expr::Var(var.to_string())))
+                    .collect::<Vec<_>>();
+                star.iter().map(|(name, expr)| (*name, expr)).collect()
+            }
+        };
+        let aggregating = items.iter().any(|(_, expr)| expr.is_aggregate());
+
+        // Each output row with the row it came from, which ORDER BY can still read
+        let mut records: Vec<(Vec<TraversalValue<'arena>>, Row<'arena>)> = Vec::new();
+        if aggregating {
+            let mut groups: BTreeMap<Vec<Value>, usize> = BTreeMap::new();
+            for row in rows {
+                let mut key = Vec::new();
+                for (_, expr) in items.iter().filter(|(_, expr)| !expr.is_aggregate()) {
+                    key.push(to_value(&self.eval(expr, &row)?));
+                }
+                let index = *groups.entry(key).or_insert_with(|| {
+                    records.push((vec![count_value(0); items.len()], row.clone()));
+                    records.len() - 1
+                });
+                let (cells, first) = &mut records[index];
+                for (cell, (_, expr)) in cells.iter_mut().zip(&items) {
+                    match expr {
+                        Expr::Call(Function::Count(arg)) => {
+                            let counted = match arg {
+                                Some(arg) => !is_null(&self.eval(arg, &row)?),
+                                None => true,
+                            };
+                            if counted {
+                                *cell = count_value(count_of(cell) + 1);
+                            }
+                        }
+                        expr => *cell = self.eval(expr, first)?,
+                    }
+                }
+            }
+            // Counting over nothing still answers, with zero
+            if records.is_empty() && items.iter().all(|(_, expr)| expr.is_aggregate()) {
+                records.push((vec![count_value(0); items.len()], Row::new()));
+            }
+        } else {
+            for row in rows {
+                let cells = items
+                    .iter()
+                    .map(|(_, expr)| self.eval(expr, &row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                records.push((cells, row));
+            }
+        }
+
+        if ret.distinct {
+            let mut seen = BTreeSet::new();
+            records
+                .retain(|(cells, _)| seen.insert(cells.iter().map(to_value).collect::<Vec<_>>()));
+        }
+
+        if !ret.order_by.is_empty() {
+            let mut keyed = Vec::with_capacity(records.len());
+            for (cells, mut row) in records {
+                for ((name, _), cell) in items.iter().zip(&cells) {
+                    row.entry(name.to_string()).or_insert_with(|| cell.clone());
+                }
+                let keys = ret
+                    .order_by
+                    .iter()
+                    .map(|sort| self.eval(&sort.expr, &row).map(|v| to_value(&v)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                keyed.push((keys, cells, row));
+            }
+            keyed.sort_by(|(a, ..), (b, ..)| {
+                a.iter()
+                    .zip(b)
+                    .zip(&ret.order_by)
+                    .map(|((a, b), sort)| {
+                        let ordering = sort_order(a, b);
+                        if sort.descending {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            records = keyed
+                .into_iter()
+                .map(|(_, cells, row)| (cells, row))
+                .collect();
+        }
+
+        let skip = ret
+            .skip
+            .as_ref()
+            .map(|count| self.count(count))
+            .transpose()?
+            .unwrap_or(0);
+        let limit = ret
+            .limit
+            .as_ref()
+            .map(|count| self.count(count))
+            .transpose()?
+            .unwrap_or(usize::MAX);
+
+        let rows = records
+            .into_iter()
+            .skip(skip)
+            .take(limit)
+            .map(|(cells, _)| {
+                cells
+                    .iter()
+                    .map(sonic_rs::to_value)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| CypherError::Graph(GraphError::New(e.to_string())))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(StatementResult {
+            columns: items.iter().map(|(name, _)| name.to_string()).collect(),
+            rows,
+        })
+    }
+
+    fn count(&self, count: &Count) -> Result<usize, CypherError> {
+        let name = match count {
+            Count::Literal(n) => return Ok(*n),
+            Count::Param(name) => name,
+        };
+        let n = match self.param(name)? {
+            Value::I8(n) => usize::try_from(n).ok(),
+            Value::I16(n) => usize::try_from(n).ok(),
+            Value::I32(n) => usize::try_from(n).ok(),
+            Value::I64(n) => usize::try_from(n).ok(),
+            Value::U8(n) => Some(n as usize),
+            Value::U16(n) => Some(n as usize),
+            Value::U32(n) => Some(n as usize),
+            Value::U64(n) => usize::try_from(n).ok(),
+            _ => None,
+        };
+        n.ok_or_else(|| CypherError::Type(format!("${name} must be a non-negative integer")))
+    }
+
+    fn param(&self, name: &str) -> Result<Value, CypherError> {
+        self.params
+            .get(name)
+            .cloned()
+            .ok_or_else(|| CypherError::MissingParameter(name.to_string()))
+    }
+
+    /// An expression that has to be a value, with nodes and relationships standing for their ids
+    fn value(&self, expr: &Expr, row: &Row<'arena>) -> Result<Value, CypherError> {
+        Ok(to_value(&self.eval(expr, row)?))
+    }
+
+    /// An expression's truth: `None` when it's null
+    fn truth(&self, expr: &Expr, row: &Row<'arena>) -> Result<Option<bool>, CypherError> {
+        match self.value(expr, row)? {
+            Value::Boolean(b) => Ok(Some(b)),
+            Value::Empty => Ok(None),
+            other => Err(CypherError::Type(format!(
+                "Expected a boolean, got {}",
+                other.inner_stringify()
+            ))),
+        }
+    }
+
+    fn eval(&self, expr: &Expr, row: &Row<'arena>) -> Result<TraversalValue<'arena>, CypherError> {
+        let value = match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Param(name) => self.param(name)?,
+            Expr::Var(var) => {
+                return Ok(row.get(var).cloned().unwrap_or(null()));
+            }
+            Expr::Property(var, key) => match row.get(var) {
+                Some(TraversalValue::Node(node)) => node.get_property(key).cloned(),
+                Some(TraversalValue::Edge(edge)) => edge.get_property(key).cloned(),
+                Some(TraversalValue::Value(Value::Object(map))) => map.get(key).cloned(),
+                _ => None,
+            }
+            .unwrap_or(Value::Empty),
+            Expr::List(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.value(item, row))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::Not(e) => truth_value(self.truth(e, row)?.map(|b| !b)),
+            Expr::And(a, b) => truth_value(match (self.truth(a, row)?, self.truth(b, row)?) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }),
+            Expr::Or(a, b) => truth_value(match (self.truth(a, row)?, self.truth(b, row)?) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }),
+            Expr::Xor(a, b) => truth_value(match (self.truth(a, row)?, self.truth(b, row)?) {
+                (Some(a), Some(b)) => Some(a != b),
+                _ => None,
+            }),
+            Expr::Compare(a, op, b) => compare(self.value(a, row)?, *op, self.value(b, row)?)?,
+            Expr::IsNull { expr, negated } => {
+                Value::Boolean(is_null(&self.eval(expr, row)?) != *negated)
+            }
+            Expr::Call(function) => self.call(function, row)?,
+        };
+        Ok(TraversalValue::Value(value))
+    }
+
+    fn call(&self, function: &Function, row: &Row<'arena>) -> Result<Value, CypherError> {
+        let (arg, name) = match function {
+            Function::Id(arg) => (arg, "id"),
+            Function::Labels(arg) => (arg, "labels"),
+            Function::Type(arg) => (arg, "type"),
+            Function::Count(_) => {
+                return Err(CypherError::Invalid(
+                    "count() can only be a returned column".to_string(),
+                ));
+            }
+        };
+        Ok(match (function, self.eval(arg, row)?) {
+            (_, value) if is_null(&value) => Value::Empty,
+            (Function::Id(_), TraversalValue::Node(Node { id, .. }))
+            | (Function::Id(_), TraversalValue::Edge(Edge { id, .. })) => {
+                Value::String(uuid_str(id, self.arena).to_string())
+            }
+            (Function::Labels(_), TraversalValue::Node(node)) => {
+                Value::Array(vec![Value::String(node.label.to_string())])
+            }
+            (Function::Type(_), TraversalValue::Edge(edge)) => {
+                Value::String(edge.label.to_string())
+            }
+            _ => {
+                return Err(CypherError::Type(format!(
+                    "{name}() got an argument of the wrong kind"
+                )));
+            }
+        })
+    }
+}
+
+fn bind<'arena>(row: &mut Row<'arena>, var: &Option<String>, value: TraversalValue<'arena>) {
+    if let Some(var) = var {
+        row.insert(var.clone(), value);
+    }
+}
+
+fn row_value<'r, 'arena>(
+    row: &'r Row<'arena>,
+    var: &Option<String>,
+) -> Option<&'r TraversalValue<'arena>> {
+    var.as_ref().and_then(|var| row.get(var))
+}
+
+fn bound_node<'r, 'arena>(row: &'r Row<'arena>, var: &Option<String>) -> Option<&'r Node<'arena>> {
+    match row_value(row, var) {
+        Some(TraversalValue::Node(node)) => Some(node),
+        _ => None,
+    }
+}
+
+/// Variables `RETURN *` returns, in the order the statement binds them
+fn variables(statement: &Statement) -> Vec<&str> {
+    let mut vars: Vec<&str> = Vec::new();
+    for clause in &statement.clauses {
+        let patterns = match clause {
+            Clause::Match { patterns, .. } | Clause::Create(patterns) => patterns,
+        };
+        for pattern in patterns {
+            let hops = pattern.hops.iter();
+            let names = std::iter::once(&pattern.start.var)
+                .chain(hops.flat_map(|(rel, node)| [&rel.var, &node.var]))
+                .flatten();
+            for name in names {
+                if !vars.contains(&name.as_str()) {
+                    vars.push(name);
+                }
+            }
+        }
+    }
+    vars
+}
+
+fn null<'arena>() -> TraversalValue<'arena> {
+    TraversalValue::Value(Value::Empty)
+}
+
+fn is_null(value: &TraversalValue) -> bool {
+    matches!(value, TraversalValue::Value(Value::Empty))
+}
+
+fn truth_value(truth: Option<bool>) -> Value {
+    truth.map_or(Value::Empty, Value::Boolean)
+}
+
+fn count_value<'arena>(count: i64) -> TraversalValue<'arena> {
+    TraversalValue::Value(Value::I64(count))
+}
+
+fn count_of(cell: &TraversalValue) -> i64 {
+    match cell {
+        TraversalValue::Value(Value::I64(count)) => *count,
+        _ => 0,
+    }
+}
+
+/// A value to compare, group or sort by. Nodes and relationships compare by id.
+fn to_value(value: &TraversalValue) -> Value {
+    match value {
+        TraversalValue::Value(value) => value.clone(),
+        TraversalValue::Node(node) => Value::U128(node.id),
+        TraversalValue::Edge(edge) => Value::U128(edge.id),
+        _ => Value::Empty,
+    }
+}
+
+fn is_numeric(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::I8(_)
+            | Value::I16(_)
+            | Value::I32(_)
+            | Value::I64(_)
+            | Value::U8(_)
+            | Value::U16(_)
+            | Value::U32(_)
+            | Value::U64(_)
+            | Value::U128(_)
+            | Value::F32(_)
+            | Value::F64(_)
+    )
+}
+
+/// Whether a value can be stored in a property of the schema type `ty`. Integers fit float
+/// properties; object types aren't checked.
+fn fits(value: &Value, ty: &str) -> bool {
+    match ty {
+        "String" => matches!(value, Value::String(_)),
+        "Boolean" => matches!(value, Value::Boolean(_)),
+        "F32" | "F64" => is_numeric(value),
+        "I8" | "I16" | "I32" | "I64" | "U8" | "U16" | "U32" | "U64" | "U128" => {
+            is_numeric(value) && !matches!(value, Value::F32(_) | Value::F64(_))
+        }
+        "ID" => matches!(value, Value::Id(_) | Value::String(_)),
+        "Date" => matches!(value, Value::Date(_) | Value::String(_)),
+        _ => match ty.strip_prefix("Array(") {
+            Some(inner) => match value {
+                Value::Array(items) => {
+                    let inner = inner.strip_suffix(')').unwrap_or(inner);
+                    items.iter().all(|item| fits(item, inner))
+                }
+                _ => false,
+            },
+            None => true,
+        },
+    }
+}
+
+/// Whether two values can be ordered against each other
+fn comparable(a: &Value, b: &Value) -> bool {
+    (is_numeric(a) && is_numeric(b))
+        || matches!(
+            (a, b),
+            (Value::String(_), Value::String(_))
+                | (Value::Boolean(_), Value::Boolean(_))
+                | (Value::Date(_), Value::Date(_))
+        )
+}
+
+/// Comparisons with null, or ordering values of different kinds, are null
+fn compare(a: Value, op: CompareOp, b: Value) -> Result<Value, CypherError> {
+    if a == Value::Empty || b == Value::Empty {
+        return Ok(Value::Empty);
+    }
+    let ordered = |accept: fn(Ordering) -> bool| {
+        if comparable(&a, &b) {
+            Value::Boolean(accept(order(&a, &b)))
+        } else {
+            Value::Empty
+        }
+    };
+    let strings = |test: fn(&str, &str) -> bool| match (&a, &b) {
+        (Value::String(a), Value::String(b)) => Value::Boolean(test(a, b)),
+        _ => Value::Empty,
+    };
+    Ok(match op {
+        CompareOp::Eq => Value::Boolean(a == b),
+        CompareOp::Neq => Value::Boolean(a != b),
+        CompareOp::Lt => ordered(Ordering::is_lt),
+        CompareOp::Lte => ordered(Ordering::is_le),
+        CompareOp::Gt => ordered(Ordering::is_gt),
+        CompareOp::Gte => ordered(Ordering::is_ge),
+        CompareOp::StartsWith => strings(|a, b| a.starts_with(b)),
+        CompareOp::EndsWith => strings(|a, b| a.ends_with(b)),
+        CompareOp::Contains => strings(|a, b| a.contains(b)),
+        CompareOp::In => match &b {
+            Value::Array(items) => Value::Boolean(items.contains(&a)),
+            _ => {
+                return Err(CypherError::Type(
+                    "IN needs a list on its right".to_string(),
+                ));
+            }
+        },
+    })
+}
+
+/// Sort order of ORDER BY: nulls last when ascending, values of different kinds by kind
+fn sort_order(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Empty, Value::Empty) => Ordering::Equal,
+        (Value::Empty, _) => Ordering::Greater,
+        (_, Value::Empty) => Ordering::Less,
+        _ => order(a, b),
+    }
+}
+
+/// [`Value`]'s ordering, except integers and floats are compared as floats rather than equal
+fn order(a: &Value, b: &Value) -> Ordering {
+    let is_float = |value: &Value| matches!(value, Value::F32(_) | Value::F64(_));
+    if is_numeric(a) && is_numeric(b) && (is_float(a) || is_float(b)) {
+        f64::from(a.clone()).total_cmp(&f64::from(b.clone()))
+    } else {
+        a.cmp(b)
+    }
+}
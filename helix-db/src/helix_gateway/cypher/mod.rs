@@ -0,0 +1,319 @@
+//! A practical subset of openCypher at `/cypher`, easing migration from Neo4j.
+//!
+//! The endpoint takes the body of Neo4j's transactional HTTP endpoint,
+//! `{"statements": [{"statement": ..., "parameters": {...}}]}`, and answers in its format,
+//! `{"results": [{"columns": [...], "data": [{"row": [...]}]}], "errors": []}`. Statements
+//! support `MATCH` with `WHERE`, `CREATE` and `RETURN` with `DISTINCT`, `ORDER BY`, `SKIP`,
+//! `LIMIT` and `count()`. Nodes and relationships are returned as the JSON queries return
+//! them.
+//!
+//! Statements are parsed by the gateway, so malformed ones are rejected before they queue.
+//! The request then runs on the worker pool as the `cypher` route, or as the `cypher_write`
+//! write route when any statement creates, so all statements of a request share one
+//! transaction and writes reach the change feed and invalidate the result cache like other
+//! writes. Statements reach every node and edge past the schema's queries, so once API keys
+//! or JWT auth are configured, callers need the access the admin API takes: an `admin`
+//! scoped API key or a token with the `admin` role.
+
+pub mod executor;
+pub mod parser;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use bumpalo::Bump;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::admin::is_admin;
+use crate::helix_gateway::auth::BearerAuth;
+use crate::helix_gateway::cypher::executor::{Executor, Schema, StatementResult, Txn};
+use crate::helix_gateway::cypher::parser::{Count, Expr, Statement};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::value::Value;
+use crate::protocol::{Format, HelixError, Response};
+
+/// Route read-only requests run as
+pub const CYPHER_READ_ROUTE: &str = "cypher";
+/// Route requests with a `CREATE` run as, on the writer
+pub const CYPHER_WRITE_ROUTE: &str = "cypher_write";
+
+#[derive(Debug, Error)]
+pub enum CypherError {
+    #[error("Invalid Cypher syntax: {0}")]
+    Syntax(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Missing parameter ${0}")]
+    MissingParameter(String),
+    #[error("Type error: {0}")]
+    Type(String),
+    #[error("Invalid request body: {0}")]
+    Body(#[from] sonic_rs::Error),
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+}
+
+impl From<CypherError> for GraphError {
+    fn from(error: CypherError) -> Self {
+        match error {
+            CypherError::Graph(e) => e,
+            e => GraphError::New(e.to_string()),
+        }
+    }
+}
+
+/// A statement of a request with its parameters
+type ParsedStatement = (Statement, HashMap<String, Value>);
+
+#[derive(Deserialize)]
+struct CypherRequest {
+    statements: Vec<CypherStatement>,
+}
+
+#[derive(Deserialize)]
+struct CypherStatement {
+    statement: String,
+    #[serde(default)]
+    parameters: HashMap<String, Value>,
+}
+
+#[derive(Serialize)]
+struct CypherResponse {
+    results: Vec<ResultSet>,
+    /// Always empty: failed requests are answered with an error status instead
+    errors: Vec<()>,
+}
+
+#[derive(Serialize)]
+struct ResultSet {
+    columns: Vec<String>,
+    data: Vec<ResultRow>,
+}
+
+#[derive(Serialize)]
+struct ResultRow {
+    row: Vec<sonic_rs::Value>,
+}
+
+impl From<StatementResult> for ResultSet {
+    fn from(result: StatementResult) -> Self {
+        ResultSet {
+            columns: result.columns,
+            data: result
+                .rows
+                .into_iter()
+                .map(|row| ResultRow { row })
+                .collect(),
+        }
+    }
+}
+
+/// Parse a request body into its statements and their parameters, checking every parameter
+/// a statement uses was given
+fn parse_request(body: &[u8]) -> Result<Vec<ParsedStatement>, CypherError> {
+    let request: CypherRequest = sonic_rs::from_slice(body)?;
+    request
+        .statements
+        .into_iter()
+        .map(|s| {
+            let statement = Statement::parse(&s.statement)?;
+            let missing = parameters(&statement).find(|p| !s.parameters.contains_key(*p));
+            if let Some(missing) = missing.map(str::to_string) {
+                return Err(CypherError::MissingParameter(missing));
+            }
+            Ok((statement, s.parameters))
+        })
+        .collect()
+}
+
+/// Names of the parameters a statement reads
+fn parameters(statement: &Statement) -> impl Iterator<Item = &str> {
+    use crate::helix_gateway::cypher::parser::{Clause, Function};
+
+    fn walk<'a>(expr: &'a Expr, out: &mut Vec<&'a str>) {
+        match expr {
+            Expr::Param(name) => out.push(name),
+            Expr::Literal(_) | Expr::Var(_) | Expr::Property(..) => {}
+            Expr::List(items) => items.iter().for_each(|e| walk(e, out)),
+            Expr::Not(e) | Expr::IsNull { expr: e, .. } => walk(e, out),
+            Expr::And(a, b) | Expr::Or(a, b) | Expr::Xor(a, b) | Expr::Compare(a, _, b) => {
+                walk(a, out);
+                walk(b, out);
+            }
+            Expr::Call(Function::Id(e) | Function::Labels(e) | Function::Type(e)) => walk(e, out),
+            Expr::Call(Function::Count(e)) => e.iter().for_each(|e| walk(e, out)),
+        }
+    }
+
+    let mut names = Vec::new();
+    for clause in &statement.clauses {
+        let (patterns, filter) = match clause {
+            Clause::Match { patterns, filter } => (patterns, filter.as_ref()),
+            Clause::Create(patterns) => (patterns, None),
+        };
+        for pattern in patterns {
+            let nodes = pattern.nodes().flat_map(|n| &n.properties);
+            let rels = pattern.hops.iter().flat_map(|(r, _)| &r.properties);
+            nodes.chain(rels).for_each(|(_, e)| walk(e, &mut names));
+        }
+        filter.into_iter().for_each(|e| walk(e, &mut names));
+    }
+    if let Some(ret) = &statement.ret {
+        ret.items
+            .iter()
+            .flatten()
+            .for_each(|i| walk(&i.expr, &mut names));
+        ret.order_by.iter().for_each(|s| walk(&s.expr, &mut names));
+        for count in [&ret.skip, &ret.limit].into_iter().flatten() {
+            if let Count::Param(name) = count {
+                names.push(name);
+            }
+        }
+    }
+    names.into_iter()
+}
+
+/// Handler of the `cypher` route
+pub fn cypher_read(input: HandlerInput) -> Result<Response, GraphError> {
+    let statements = parse_request(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let arena = Bump::new();
+    let txn = storage.read_txn()?;
+    let mut results = Vec::with_capacity(statements.len());
+    for (statement, params) in &statements {
+        let executor = Executor::new(storage, &arena, params, None);
+        results.push(executor.execute(&mut Txn::Read(&txn), statement)?.into());
+    }
+    respond(results)
+}
+
+/// Handler of the `cypher_write` route. Statements run in one transaction, committed only
+/// once all of them succeed.
+pub fn cypher_write(input: HandlerInput) -> Result<Response, GraphError> {
+    let statements = parse_request(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let schema = match &storage.storage_config.schema {
+        Some(schema_json) => Some(Schema::from_json(schema_json)?),
+        None => None,
+    };
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn()?;
+    let mut results = Vec::with_capacity(statements.len());
+    for (statement, params) in &statements {
+        let executor = Executor::new(storage, &arena, params, schema.as_ref());
+        results.push(
+            executor
+                .execute(&mut Txn::Write(&mut txn), statement)?
+                .into(),
+        );
+    }
+    storage.check_constraints(&txn)?;
+    storage.audit_write(&mut txn)?;
+    txn.commit()?;
+    respond(results)
+}
+
+fn respond(results: Vec<ResultSet>) -> Result<Response, GraphError> {
+    let response = CypherResponse {
+        results,
+        errors: Vec::new(),
+    };
+    Ok(Response {
+        body: sonic_rs::to_vec(&response).map_err(|e| GraphError::New(e.to_string()))?,
+        fmt: Format::Json,
+    })
+}
+
+pub async fn cypher_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let api_key = {
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+                return (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "Missing x-api-key header",
+                )
+                    .into_response();
+            };
+            if let Err(e) = verify_key(api_key) {
+                info!(?e, "Invalid API key");
+                return e.into_response();
+            }
+            Some(api_key.to_string())
+        }
+        #[cfg(not(feature = "api-key"))]
+        None::<String>
+    };
+
+    let statements = match parse_request(&body) {
+        Ok(statements) => statements,
+        Err(e) => return HelixError::InvalidCypher(e.to_string()).into_response(),
+    };
+    let is_write = statements.iter().any(|(statement, _)| statement.is_write());
+    let route = if is_write {
+        CYPHER_WRITE_ROUTE
+    } else {
+        CYPHER_READ_ROUTE
+    };
+
+    if let BearerAuth(Some(caller)) = &auth
+        && !is_admin(caller)
+    {
+        info!(caller = %caller.name(), route, "Unauthorized Cypher request");
+        return HelixError::NotAdmin.into_response();
+    }
+    let limiter = &state.rate_limiter;
+    if limiter.is_enabled() {
+        let client = limiter.client_id(auth.0.as_ref(), addr, &headers);
+        if let Err(e) = limiter.check(route, &client) {
+            info!(?client, route, "Rate limited");
+            return e.into_response();
+        }
+    }
+
+    let req = Request {
+        name: route.to_string(),
+        req_type: RequestType::Query,
+        api_key,
+        body,
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    };
+    let span = info_span!("helix.request", route, otel.kind = "server");
+    otel::set_remote_parent(&span, &headers);
+    let start_time = Instant::now();
+    let res = state
+        .worker_pool
+        .process_with(req, RequestHints::from_headers(&headers))
+        .instrument(span)
+        .await;
+    helix_metrics::prometheus::observe_request(route, res.is_ok(), start_time.elapsed());
+
+    match res {
+        Ok(response) => response.into_response(),
+        Err(e) => {
+            info!(route, error = ?e, "Error response");
+            e.into_response()
+        }
+    }
+}
@@ -0,0 +1,771 @@
+//! Parses a statement into the [`Statement`] AST and checks it can run: variables are bound
+//! before they are read, and everything `CREATE` makes has a label (and a direction for
+//! relationships), since nodes and edges in Helix always do.
+
+use std::collections::HashMap;
+
+use pest::Parser as PestParser;
+use pest::iterators::Pair;
+use pest_derive::Parser;
+
+use crate::helix_gateway::cypher::CypherError;
+use crate::protocol::value::Value;
+
+#[derive(Parser)]
+#[grammar = "helix_gateway/cypher/cypher.pest"]
+struct CypherParser;
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub clauses: Vec<Clause>,
+    pub ret: Option<Return>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Clause {
+    Match {
+        patterns: Vec<Pattern>,
+        filter: Option<Expr>,
+    },
+    Create(Vec<Pattern>),
+}
+
+/// A node followed by any number of relationship/node hops
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub start: NodePattern,
+    pub hops: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NodePattern {
+    pub var: Option<String>,
+    pub label: Option<String>,
+    pub properties: Vec<(String, Expr)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelPattern {
+    pub var: Option<String>,
+    pub label: Option<String>,
+    pub properties: Vec<(String, Expr)>,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// `-[]->`
+    Out,
+    /// `<-[]-`
+    In,
+    /// `-[]-`, either way
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Literal(Value),
+    Param(String),
+    Var(String),
+    Property(String, String),
+    List(Vec<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    IsNull { expr: Box<Expr>, negated: bool },
+    Call(Function),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    StartsWith,
+    EndsWith,
+    Contains,
+    In,
+}
+
+/// The functions the subset supports
+#[derive(Debug, Clone)]
+pub enum Function {
+    /// `id(x)`, the UUID of a node or relationship
+    Id(Box<Expr>),
+    /// `labels(n)`, a one element list holding the node's label
+    Labels(Box<Expr>),
+    /// `type(r)`, the relationship's label
+    Type(Box<Expr>),
+    /// `count(*)` when `None`, otherwise the number of non-null values
+    Count(Option<Box<Expr>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Return {
+    pub distinct: bool,
+    /// `None` for `RETURN *`
+    pub items: Option<Vec<ReturnItem>>,
+    pub order_by: Vec<SortItem>,
+    pub skip: Option<Count>,
+    pub limit: Option<Count>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnItem {
+    pub expr: Expr,
+    /// Column name: the alias, or the expression as written
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SortItem {
+    pub expr: Expr,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Count {
+    Literal(usize),
+    Param(String),
+}
+
+impl Statement {
+    pub fn parse(input: &str) -> Result<Self, CypherError> {
+        let pair = CypherParser::parse(Rule::statement, input)
+            .map_err(|e| CypherError::Syntax(e.to_string()))?
+            .next()
+            .ok_or_else(|| CypherError::Syntax("Empty statement".to_string()))?;
+
+        let mut clauses = Vec::new();
+        let mut ret = None;
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::match_clause => {
+                    let mut patterns = Vec::new();
+                    let mut filter = None;
+                    for pair in pair.into_inner() {
+                        match pair.as_rule() {
+                            Rule::pattern_list => patterns = parse_patterns(pair)?,
+                            Rule::where_clause => filter = Some(parse_expr(last_inner(pair)?)?),
+                            _ => {}
+                        }
+                    }
+                    clauses.push(Clause::Match { patterns, filter });
+                }
+                Rule::create_clause => {
+                    let patterns = parse_patterns(last_inner(pair)?)?;
+                    clauses.push(Clause::Create(patterns));
+                }
+                Rule::return_clause => ret = Some(parse_return(pair)?),
+                _ => {}
+            }
+        }
+
+        let statement = Statement { clauses, ret };
+        statement.validate()?;
+        Ok(statement)
+    }
+
+    /// Whether the statement creates anything, so has to run on the writer
+    pub fn is_write(&self) -> bool {
+        self.clauses
+            .iter()
+            .any(|clause| matches!(clause, Clause::Create(_)))
+    }
+
+    fn validate(&self) -> Result<(), CypherError> {
+        if self.ret.is_none() && !self.is_write() {
+            return Err(CypherError::Invalid(
+                "A statement that only matches must end with RETURN".to_string(),
+            ));
+        }
+
+        let mut bound: HashMap<&str, Kind> = HashMap::new();
+        for clause in &self.clauses {
+            match clause {
+                Clause::Match { patterns, filter } => {
+                    for pattern in patterns {
+                        for node in pattern.nodes() {
+                            check_properties(&node.properties, &bound)?;
+                            bind(&mut bound, node.var.as_deref(), Kind::Node)?;
+                        }
+                        for rel in pattern.hops.iter().map(|(rel, _)| rel) {
+                            check_properties(&rel.properties, &bound)?;
+                            bind(&mut bound, rel.var.as_deref(), Kind::Relationship)?;
+                        }
+                    }
+                    if let Some(filter) = filter {
+                        check_expr(filter, &bound, false)?;
+                    }
+                }
+                Clause::Create(patterns) => {
+                    for pattern in patterns {
+                        for node in pattern.nodes() {
+                            check_properties(&node.properties, &bound)?;
+                            let existing = node.var.as_deref().and_then(|v| bound.get(v));
+                            match existing {
+                                Some(Kind::Node) => {
+                                    if node.label.is_some() || !node.properties.is_empty() {
+                                        return Err(CypherError::Invalid(format!(
+                                            "`{}` already exists, it can't be given a label or properties in CREATE",
+                                            node.var.as_deref().unwrap_or_default()
+                                        )));
+                                    }
+                                }
+                                Some(_) => {
+                                    return Err(CypherError::Invalid(format!(
+                                        "`{}` is a relationship, not a node",
+                                        node.var.as_deref().unwrap_or_default()
+                                    )));
+                                }
+                                None if node.label.is_none() => {
+                                    return Err(CypherError::Invalid(
+                                        "Nodes created with CREATE need a label".to_string(),
+                                    ));
+                                }
+                                None => bind(&mut bound, node.var.as_deref(), Kind::Node)?,
+                            }
+                        }
+                        for rel in pattern.hops.iter().map(|(rel, _)| rel) {
+                            check_properties(&rel.properties, &bound)?;
+                            if rel.label.is_none() || rel.direction == Direction::Both {
+                                return Err(CypherError::Invalid(
+                                    "Relationships created with CREATE need a type and a direction"
+                                        .to_string(),
+                                ));
+                            }
+                            if let Some(var) = &rel.var
+                                && bound.contains_key(var.as_str())
+                            {
+                                return Err(CypherError::Invalid(format!(
+                                    "`{var}` is already bound, CREATE needs a new relationship"
+                                )));
+                            }
+                            bind(&mut bound, rel.var.as_deref(), Kind::Relationship)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(ret) = &self.ret {
+            let mut visible = bound.clone();
+            for item in ret.items.iter().flatten() {
+                check_expr(&item.expr, &bound, true)?;
+                visible.entry(item.name.as_str()).or_insert(Kind::Value);
+            }
+            // ORDER BY may also use the returned columns, which is how it sorts by a count
+            for sort in &ret.order_by {
+                check_expr(&sort.expr, &visible, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Pattern {
+    pub fn nodes(&self) -> impl Iterator<Item = &NodePattern> {
+        std::iter::once(&self.start).chain(self.hops.iter().map(|(_, node)| node))
+    }
+}
+
+impl Expr {
+    /// Whether the expression aggregates over rows
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self, Expr::Call(Function::Count(_)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Node,
+    Relationship,
+    /// A returned column, usable in ORDER BY
+    Value,
+}
+
+fn bind<'a>(
+    bound: &mut HashMap<&'a str, Kind>,
+    var: Option<&'a str>,
+    kind: Kind,
+) -> Result<(), CypherError> {
+    let Some(var) = var else {
+        return Ok(());
+    };
+    match bound.insert(var, kind) {
+        Some(existing) if existing != kind => Err(CypherError::Invalid(format!(
+            "`{var}` is used as both a node and a relationship"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_properties(
+    properties: &[(String, Expr)],
+    bound: &HashMap<&str, Kind>,
+) -> Result<(), CypherError> {
+    properties
+        .iter()
+        .try_for_each(|(_, expr)| check_expr(expr, bound, false))
+}
+
+fn check_expr(
+    expr: &Expr,
+    bound: &HashMap<&str, Kind>,
+    allow_aggregate: bool,
+) -> Result<(), CypherError> {
+    let check = |e: &Expr| check_expr(e, bound, false);
+    match expr {
+        Expr::Literal(_) | Expr::Param(_) => Ok(()),
+        Expr::Var(var) | Expr::Property(var, _) => {
+            if bound.contains_key(var.as_str()) {
+                Ok(())
+            } else {
+                Err(CypherError::Invalid(format!(
+                    "Variable `{var}` is not defined"
+                )))
+            }
+        }
+        Expr::List(items) => items.iter().try_for_each(check),
+        Expr::Not(e) | Expr::IsNull { expr: e, .. } => check(e),
+        Expr::And(a, b) | Expr::Or(a, b) | Expr::Xor(a, b) | Expr::Compare(a, _, b) => {
+            check(a)?;
+            check(b)
+        }
+        Expr::Call(Function::Count(arg)) => {
+            if !allow_aggregate {
+                return Err(CypherError::Invalid(
+                    "count() can only be a returned column, sort by its alias instead".to_string(),
+                ));
+            }
+            arg.as_deref().map_or(Ok(()), check)
+        }
+        Expr::Call(Function::Id(e) | Function::Labels(e) | Function::Type(e)) => check(e),
+    }
+}
+
+fn last_inner(pair: Pair<Rule>) -> Result<Pair<Rule>, CypherError> {
+    let rule = pair.as_rule();
+    pair.into_inner()
+        .last()
+        .ok_or_else(|| CypherError::Syntax(format!("Empty {rule:?}")))
+}
+
+fn parse_patterns(pair: Pair<Rule>) -> Result<Vec<Pattern>, CypherError> {
+    pair.into_inner().map(parse_pattern).collect()
+}
+
+fn parse_pattern(pair: Pair<Rule>) -> Result<Pattern, CypherError> {
+    let mut pairs = pair.into_inner();
+    let start = parse_node(
+        pairs
+            .next()
+            .ok_or_else(|| CypherError::Syntax("Empty pattern".to_string()))?,
+    )?;
+    let mut hops = Vec::new();
+    while let (Some(rel), Some(node)) = (pairs.next(), pairs.next()) {
+        hops.push((parse_relationship(rel)?, parse_node(node)?));
+    }
+    Ok(Pattern { start, hops })
+}
+
+fn parse_node(pair: Pair<Rule>) -> Result<NodePattern, CypherError> {
+    let mut node = NodePattern::default();
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::identifier => node.var = Some(identifier(pair)),
+            Rule::label => node.label = Some(identifier(last_inner(pair)?)),
+            Rule::properties => node.properties = parse_properties(pair)?,
+            _ => {}
+        }
+    }
+    Ok(node)
+}
+
+fn parse_relationship(pair: Pair<Rule>) -> Result<RelPattern, CypherError> {
+    let mut rel = RelPattern {
+        var: None,
+        label: None,
+        properties: Vec::new(),
+        direction: Direction::Both,
+    };
+    let (mut left, mut right) = (false, false);
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::left_arrow => left = true,
+            Rule::right_arrow => right = true,
+            Rule::identifier => rel.var = Some(identifier(pair)),
+            Rule::label => rel.label = Some(identifier(last_inner(pair)?)),
+            Rule::properties => rel.properties = parse_properties(pair)?,
+            _ => {}
+        }
+    }
+    rel.direction = match (left, right) {
+        (false, true) => Direction::Out,
+        (true, false) => Direction::In,
+        (false, false) => Direction::Both,
+        (true, true) => {
+            return Err(CypherError::Invalid(
+                "A relationship can't point both ways".to_string(),
+            ));
+        }
+    };
+    Ok(rel)
+}
+
+fn parse_properties(pair: Pair<Rule>) -> Result<Vec<(String, Expr)>, CypherError> {
+    pair.into_inner()
+        .map(|property| {
+            let mut inner = property.into_inner();
+            match (inner.next(), inner.next()) {
+                (Some(key), Some(value)) => Ok((identifier(key), parse_expr(value)?)),
+                _ => Err(CypherError::Syntax("Malformed property".to_string())),
+            }
+        })
+        .collect()
+}
+
+fn parse_return(pair: Pair<Rule>) -> Result<Return, CypherError> {
+    let mut ret = Return {
+        distinct: false,
+        items: None,
+        order_by: Vec::new(),
+        skip: None,
+        limit: None,
+    };
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::distinct => ret.distinct = true,
+            Rule::return_items => {
+                let mut items = Vec::new();
+                for item in pair.into_inner() {
+                    if item.as_rule() != Rule::return_item {
+                        continue;
+                    }
+                    let mut inner = item.into_inner().filter(|p| p.as_rule() != Rule::AS);
+                    let expr_pair = inner
+                        .next()
+                        .ok_or_else(|| CypherError::Syntax("Empty RETURN item".to_string()))?;
+                    let name = match inner.next() {
+                        Some(alias) => identifier(alias),
+                        None => expr_pair.as_str().trim().to_string(),
+                    };
+                    items.push(ReturnItem {
+                        expr: parse_expr(expr_pair)?,
+                        name,
+                    });
+                }
+                if !items.is_empty() {
+                    ret.items = Some(items);
+                }
+            }
+            Rule::order_by => {
+                for sort in pair.into_inner() {
+                    if sort.as_rule() != Rule::sort_item {
+                        continue;
+                    }
+                    let mut inner = sort.into_inner();
+                    let expr = parse_expr(
+                        inner
+                            .next()
+                            .ok_or_else(|| CypherError::Syntax("Empty ORDER BY".to_string()))?,
+                    )?;
+                    let descending = inner
+                        .next()
+                        .is_some_and(|p| p.as_rule() == Rule::descending);
+                    ret.order_by.push(SortItem { expr, descending });
+                }
+            }
+            Rule::skip => ret.skip = Some(parse_count(last_inner(pair)?)?),
+            Rule::limit => ret.limit = Some(parse_count(last_inner(pair)?)?),
+            _ => {}
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_count(pair: Pair<Rule>) -> Result<Count, CypherError> {
+    match pair.as_rule() {
+        Rule::parameter => Ok(Count::Param(identifier(last_inner(pair)?))),
+        _ => pair
+            .as_str()
+            .parse()
+            .map(Count::Literal)
+            .map_err(|_| CypherError::Invalid(format!("`{}` is not a count", pair.as_str()))),
+    }
+}
+
+fn parse_expr(pair: Pair<Rule>) -> Result<Expr, CypherError> {
+    match pair.as_rule() {
+        Rule::expr | Rule::atom => parse_expr(last_inner(pair)?),
+        Rule::or_expr => fold_binary(pair, Expr::Or),
+        Rule::xor_expr => fold_binary(pair, Expr::Xor),
+        Rule::and_expr => fold_binary(pair, Expr::And),
+        Rule::not_expr => {
+            let mut negations = 0;
+            let mut inner = None;
+            for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::not => negations += 1,
+                    _ => inner = Some(parse_expr(pair)?),
+                }
+            }
+            let mut expr = inner.ok_or_else(|| CypherError::Syntax("Empty NOT".to_string()))?;
+            for _ in 0..negations {
+                expr = Expr::Not(Box::new(expr));
+            }
+            Ok(expr)
+        }
+        Rule::comparison => {
+            let mut inner = pair.into_inner();
+            let left = parse_expr(
+                inner
+                    .next()
+                    .ok_or_else(|| CypherError::Syntax("Empty comparison".to_string()))?,
+            )?;
+            match (inner.next(), inner.next()) {
+                (Some(op), Some(right)) => Ok(Expr::Compare(
+                    Box::new(left),
+                    compare_op(op)?,
+                    Box::new(parse_expr(right)?),
+                )),
+                _ => Ok(left),
+            }
+        }
+        Rule::postfix => {
+            let mut inner = pair.into_inner();
+            let expr = parse_expr(
+                inner
+                    .next()
+                    .ok_or_else(|| CypherError::Syntax("Empty expression".to_string()))?,
+            )?;
+            match inner.next() {
+                Some(null_check) => Ok(Expr::IsNull {
+                    expr: Box::new(expr),
+                    negated: null_check.into_inner().any(|p| p.as_rule() == Rule::not),
+                }),
+                None => Ok(expr),
+            }
+        }
+        Rule::literal => parse_literal(last_inner(pair)?),
+        Rule::parameter => Ok(Expr::Param(identifier(last_inner(pair)?))),
+        Rule::variable => Ok(Expr::Var(identifier(last_inner(pair)?))),
+        Rule::property_access => {
+            let mut inner = pair.into_inner();
+            match (inner.next(), inner.next()) {
+                (Some(var), Some(key)) => Ok(Expr::Property(identifier(var), identifier(key))),
+                _ => Err(CypherError::Syntax("Malformed property access".to_string())),
+            }
+        }
+        Rule::list => Ok(Expr::List(
+            pair.into_inner()
+                .map(parse_expr)
+                .collect::<Result<_, _>>()?,
+        )),
+        Rule::function_call => parse_function(pair),
+        rule => Err(CypherError::Syntax(format!("Unexpected {rule:?}"))),
+    }
+}
+
+fn fold_binary(
+    pair: Pair<Rule>,
+    combine: fn(Box<Expr>, Box<Expr>) -> Expr,
+) -> Result<Expr, CypherError> {
+    let mut operands = pair
+        .into_inner()
+        .filter(|p| !matches!(p.as_rule(), Rule::OR | Rule::XOR | Rule::AND))
+        .map(parse_expr);
+    let first = operands
+        .next()
+        .ok_or_else(|| CypherError::Syntax("Empty expression".to_string()))??;
+    operands.try_fold(first, |acc, next| {
+        Ok(combine(Box::new(acc), Box::new(next?)))
+    })
+}
+
+fn compare_op(pair: Pair<Rule>) -> Result<CompareOp, CypherError> {
+    let op = last_inner(pair)?;
+    Ok(match op.as_rule() {
+        Rule::eq => CompareOp::Eq,
+        Rule::neq => CompareOp::Neq,
+        Rule::lt => CompareOp::Lt,
+        Rule::lte => CompareOp::Lte,
+        Rule::gt => CompareOp::Gt,
+        Rule::gte => CompareOp::Gte,
+        Rule::starts_with => CompareOp::StartsWith,
+        Rule::ends_with => CompareOp::EndsWith,
+        Rule::contains => CompareOp::Contains,
+        Rule::in_list => CompareOp::In,
+        rule => return Err(CypherError::Syntax(format!("Unexpected {rule:?}"))),
+    })
+}
+
+fn parse_literal(pair: Pair<Rule>) -> Result<Expr, CypherError> {
+    let text = pair.as_str();
+    let value = match pair.as_rule() {
+        Rule::integer => Value::I64(
+            text.parse()
+                .map_err(|_| CypherError::Invalid(format!("Integer `{text}` is out of range")))?,
+        ),
+        Rule::float => Value::F64(
+            text.parse()
+                .map_err(|_| CypherError::Invalid(format!("Invalid float `{text}`")))?,
+        ),
+        Rule::string => Value::String(unescape(last_inner(pair)?.as_str())),
+        Rule::boolean => Value::Boolean(text.eq_ignore_ascii_case("true")),
+        _ => Value::Empty,
+    };
+    Ok(Expr::Literal(value))
+}
+
+fn parse_function(pair: Pair<Rule>) -> Result<Expr, CypherError> {
+    let mut inner = pair.into_inner();
+    let name = identifier(
+        inner
+            .next()
+            .ok_or_else(|| CypherError::Syntax("Empty function call".to_string()))?,
+    );
+    let args: Vec<Pair<Rule>> = inner.collect();
+    let star = args.first().is_some_and(|p| p.as_rule() == Rule::star);
+    let single = |args: Vec<Pair<Rule>>| -> Result<Box<Expr>, CypherError> {
+        match <[_; 1]>::try_from(args) {
+            Ok([arg]) if arg.as_rule() != Rule::star => Ok(Box::new(parse_expr(arg)?)),
+            _ => Err(CypherError::Invalid(format!(
+                "{name}() takes exactly one argument"
+            ))),
+        }
+    };
+    let function = match name.to_ascii_lowercase().as_str() {
+        "id" => Function::Id(single(args)?),
+        "labels" => Function::Labels(single(args)?),
+        "type" => Function::Type(single(args)?),
+        "count" if star => Function::Count(None),
+        "count" => Function::Count(Some(single(args)?)),
+        _ => {
+            return Err(CypherError::Invalid(format!(
+                "Unsupported function `{name}`"
+            )));
+        }
+    };
+    Ok(Expr::Call(function))
+}
+
+/// An identifier, without the backticks of a quoted one
+fn identifier(pair: Pair<Rule>) -> String {
+    let text = pair.as_str();
+    text.strip_prefix('`')
+        .and_then(|t| t.strip_suffix('`'))
+        .unwrap_or(text)
+        .to_string()
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_match_where_return() {
+        let statement = Statement::parse(
+            "MATCH (a:Person {name: 'alice'})-[r:KNOWS]->(b:Person) \
+             WHERE b.age >= 18 AND NOT b.name STARTS WITH \"x\" \
+             RETURN a.name AS name, b, count(*) ORDER BY name DESC SKIP 1 LIMIT $limit",
+        )
+        .unwrap();
+        assert!(!statement.is_write());
+
+        let Clause::Match { patterns, filter } = &statement.clauses[0] else {
+            panic!("expected MATCH");
+        };
+        let pattern = &patterns[0];
+        assert_eq!(pattern.start.var.as_deref(), Some("a"));
+        assert_eq!(pattern.start.label.as_deref(), Some("Person"));
+        assert_eq!(pattern.start.properties[0].0, "name");
+        let (rel, node) = &pattern.hops[0];
+        assert_eq!(rel.label.as_deref(), Some("KNOWS"));
+        assert_eq!(rel.direction, Direction::Out);
+        assert_eq!(node.var.as_deref(), Some("b"));
+        assert!(matches!(filter, Some(Expr::And(_, _))));
+
+        let ret = statement.ret.unwrap();
+        let items = ret.items.unwrap();
+        let names: Vec<&str> = items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["name", "b", "count(*)"]);
+        assert!(items[2].expr.is_aggregate());
+        assert!(ret.order_by[0].descending);
+        assert!(matches!(ret.skip, Some(Count::Literal(1))));
+        assert!(matches!(ret.limit, Some(Count::Param(ref p)) if p == "limit"));
+    }
+
+    #[test]
+    fn test_parses_create_and_directions() {
+        let statement = Statement::parse(
+            "match (a:Person), (b:Person) create (a)<-[:FOLLOWS {since: 2020}]-(b), (c:Tag {n: [1, 2.5]})",
+        )
+        .unwrap();
+        assert!(statement.is_write());
+        let Clause::Create(patterns) = &statement.clauses[1] else {
+            panic!("expected CREATE");
+        };
+        assert_eq!(patterns[0].hops[0].0.direction, Direction::In);
+        assert_eq!(patterns.len(), 2);
+    }
+
+    #[test]
+    fn test_keywords_are_not_identifiers() {
+        // `order` and `asc` start identifiers here rather than being keywords
+        assert!(Statement::parse("MATCH (orders:Order) RETURN orders.ascii").is_ok());
+        assert!(matches!(
+            Statement::parse("MATCH (match) RETURN match"),
+            Err(CypherError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_statements() {
+        let invalid = [
+            "MATCH (n)",
+            "MATCH (n) RETURN m",
+            "MATCH (n) WHERE count(*) > 1 RETURN n",
+            "MATCH (n) RETURN n.name, count(*) ORDER BY count(*)",
+            "CREATE (n)",
+            "CREATE (a:A)-[:R]-(b:B)",
+            "CREATE (a:A)-->(b:B)",
+            "MATCH (n)-[n]->(m) RETURN n",
+            "MATCH (n) RETURN upper(n.name)",
+        ];
+        for statement in invalid {
+            assert!(
+                matches!(Statement::parse(statement), Err(CypherError::Invalid(_))),
+                "{statement}"
+            );
+        }
+        assert!(matches!(
+            Statement::parse("MATCH (n RETURN n"),
+            Err(CypherError::Syntax(_))
+        ));
+    }
+}
@@ -0,0 +1,35 @@
+//! Keeps the `::EGO` neighborhood cache fresh by dropping an edge type's neighborhoods after
+//! every committed write touching it, as reported by the change feed.
+
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use crate::helix_gateway::change_feed::Change;
+use crate::helix_gateway::gateway::AppState;
+
+/// Drop cached neighborhoods as writes commit, until the gateway shuts down
+pub async fn invalidate_on_writes(state: Arc<AppState>) {
+    let pool = &state.worker_pool;
+    let storage = &pool.graph().storage;
+    let mut changes = pool.changes().subscribe();
+    loop {
+        let labels = match changes.recv().await {
+            Ok(Change::ShuttingDown) | Err(RecvError::Closed) => return,
+            Ok(Change::Write { labels }) => labels,
+            // Missed writes may have touched any edge type
+            Err(RecvError::Lagged(_)) => None,
+        };
+        // A transaction begun now sees the write, so reads from before it can't be cached
+        let txn_id = match storage.graph_env.read_txn() {
+            Ok(txn) => txn.id(),
+            Err(e) => {
+                warn!(error = %e, "Failed to open a read transaction, dropping all neighborhoods");
+                storage.ego_cache.invalidate(None, 0);
+                continue;
+            }
+        };
+        storage.ego_cache.invalidate(labels.as_deref(), txn_id);
+    }
+}
@@ -1,11 +1,15 @@
 use crate::helix_engine::types::GraphError;
+#[cfg(feature = "reqwest")]
 use reqwest::Client;
+#[cfg(feature = "reqwest")]
 use sonic_rs::JsonValueTrait;
+#[cfg(feature = "reqwest")]
 use sonic_rs::{JsonContainerTrait, json};
 use std::env;
 use url::Url;
 
 /// Parse an API error response and return a descriptive GraphError
+#[cfg(feature = "reqwest")]
 fn parse_api_error(provider: &str, status: u16, body: &str) -> GraphError {
     // Try to extract error message from JSON response
     if let Ok(json) = sonic_rs::from_str::<sonic_rs::Value>(body)
@@ -48,9 +52,11 @@ pub enum EmbeddingProvider {
     Local,
 }
 
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
 pub struct EmbeddingModelImpl {
     pub(crate) provider: EmbeddingProvider,
     api_key: Option<String>,
+    #[cfg(feature = "reqwest")]
     client: Client,
     pub(crate) model: String,
     pub(crate) url: Option<String>,
@@ -100,6 +106,7 @@ impl EmbeddingModelImpl {
         Ok(EmbeddingModelImpl {
             provider,
             api_key,
+            #[cfg(feature = "reqwest")]
             client: Client::new(),
             model: model_name,
             url,
@@ -169,6 +176,7 @@ impl EmbeddingModelImpl {
     }
 }
 
+#[cfg(feature = "reqwest")]
 impl EmbeddingModel for EmbeddingModelImpl {
     /// Must be called with an active tokio context
     fn fetch_embedding(&self, text: &str) -> Result<Vec<f64>, GraphError> {
@@ -429,6 +437,20 @@ impl EmbeddingModel for EmbeddingModelImpl {
     }
 }
 
+/// Builds without `reqwest` have no HTTP client to reach a provider with
+#[cfg(not(feature = "reqwest"))]
+impl EmbeddingModel for EmbeddingModelImpl {
+    fn fetch_embedding(&self, _text: &str) -> Result<Vec<f64>, GraphError> {
+        Err(GraphError::EmbeddingError(
+            "Embedding providers need helix-db's `reqwest` feature".to_string(),
+        ))
+    }
+
+    async fn fetch_embedding_async(&self, text: &str) -> Result<Vec<f64>, GraphError> {
+        self.fetch_embedding(text)
+    }
+}
+
 /// Creates embedding based on provider.
 pub fn get_embedding_model(
     api_key: Option<&str>,
@@ -0,0 +1,253 @@
+//! Arrow Flight access to read queries.
+//!
+//! `DoGet` runs the query a ticket names and streams its result as Arrow record batches,
+//! laid out as a table the way `Accept: application/vnd.apache.arrow.stream` responses are
+//! (see `protocol::arrow`). A ticket is the JSON `{"query": "<name>", "params": {...}}`;
+//! `GetFlightInfo` on a command descriptor holding the same JSON returns one endpoint with
+//! it as the ticket, for clients that plan before fetching. Calls are authenticated and rate
+//! limited like gRPC calls, with the bearer token or API key in the call metadata.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaResult, Ticket,
+};
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::response::Response;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use tonic::codegen::Service;
+use tonic::{Status, Streaming};
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::grpc::{api_key, check_access};
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::protocol::arrow;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError};
+
+/// Route the Flight rpcs are served on, with the rpc name as the `method` parameter
+pub const FLIGHT_ROUTE: &str = "/arrow.flight.protocol.FlightService/{method}";
+
+/// Query a ticket names
+#[derive(Debug, Deserialize)]
+struct QueryTicket {
+    query: String,
+    #[serde(default)]
+    params: Option<sonic_rs::Value>,
+}
+
+impl QueryTicket {
+    fn parse(ticket: &[u8]) -> Result<Self, Status> {
+        sonic_rs::from_slice(ticket)
+            .map_err(|e| Status::invalid_argument(format!("Ticket is not a query: {e}")))
+    }
+}
+
+/// Who is calling, taken from the HTTP request before it's handed to the Flight service
+#[derive(Clone, Default)]
+struct FlightClient {
+    caller: Option<Caller>,
+    addr: Option<IpAddr>,
+}
+
+pub async fn flight_handler(
+    State(state): State<Arc<AppState>>,
+    auth: Result<BearerAuth, HelixError>,
+    ClientAddr(addr): ClientAddr,
+    mut req: axum::extract::Request,
+) -> Response {
+    let caller = match auth {
+        Ok(BearerAuth(caller)) => caller,
+        Err(e) => return Status::from(e).into_http(),
+    };
+    req.extensions_mut().insert(FlightClient { caller, addr });
+    let mut server = FlightServiceServer::new(HelixFlight { state });
+    match server.call(req).await {
+        Ok(response) => response.map(Body::new),
+        Err(never) => match never {},
+    }
+}
+
+struct HelixFlight {
+    state: Arc<AppState>,
+}
+
+impl HelixFlight {
+    /// Run the ticket's query and lay its JSON result out as a record batch
+    async fn execute(
+        &self,
+        ticket: QueryTicket,
+        client: FlightClient,
+        headers: &axum::http::HeaderMap,
+    ) -> Result<arrow_array::RecordBatch, Status> {
+        let name = ticket.query;
+        let api_key = api_key(headers)?;
+        if let Err(e) = check_access(
+            &self.state,
+            &name,
+            client.caller.as_ref(),
+            client.addr,
+            headers,
+        ) {
+            info!(query = %name, error = %e, "Rejected Flight call");
+            return Err(e.into());
+        }
+        if self.state.worker_pool.is_write_route(&name) {
+            return Err(Status::invalid_argument(format!(
+                "`{name}` is a write query; only read queries are served over Flight"
+            )));
+        }
+
+        let span = info_span!("helix.request", route = %name, rpc = "DoGet", otel.kind = "server");
+        otel::set_remote_parent(&span, headers);
+        let params = ticket.params.unwrap_or_else(sonic_rs::Value::new_object);
+        let req = Request {
+            name: name.clone(),
+            req_type: RequestType::Query,
+            api_key,
+            body: Bytes::from(sonic_rs::to_vec(&params).expect("JSON value should serialize")),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+
+        let start_time = Instant::now();
+        let res = self
+            .state
+            .worker_pool
+            .process_with(req, RequestHints::from_headers(headers))
+            .instrument(span)
+            .await;
+        if !matches!(res, Err(HelixError::NotFound { .. })) {
+            helix_metrics::prometheus::observe_request(&name, res.is_ok(), start_time.elapsed());
+        }
+        let body = match res {
+            Ok(response) => response.body,
+            Err(e) => {
+                info!(query = %name, error = ?e, "Error response over Flight");
+                return Err(e.into());
+            }
+        };
+        let result = if body.is_empty() {
+            sonic_rs::Value::new()
+        } else {
+            sonic_rs::from_slice(&body)
+                .map_err(|e| Status::internal(format!("Result is not JSON: {e}")))?
+        };
+        arrow::to_record_batch(&result).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for HelixFlight {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+
+    async fn do_get(
+        &self,
+        request: tonic::Request<Ticket>,
+    ) -> Result<tonic::Response<Self::DoGetStream>, Status> {
+        let client = request
+            .extensions()
+            .get::<FlightClient>()
+            .cloned()
+            .unwrap_or_default();
+        let headers = request.metadata().clone().into_headers();
+        let ticket = QueryTicket::parse(&request.get_ref().ticket)?;
+        let batch = self.execute(ticket, client, &headers).await?;
+        let stream = FlightDataEncoderBuilder::new()
+            .build(stream::once(async move { Ok(batch) }))
+            .map_err(Status::from);
+        Ok(tonic::Response::new(stream.boxed()))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: tonic::Request<FlightDescriptor>,
+    ) -> Result<tonic::Response<FlightInfo>, Status> {
+        let descriptor = request.into_inner();
+        // Checked here so a bad command fails at planning rather than on fetch
+        QueryTicket::parse(&descriptor.cmd)?;
+        let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(descriptor.cmd.clone()));
+        Ok(tonic::Response::new(
+            FlightInfo::new()
+                .with_descriptor(descriptor)
+                .with_endpoint(endpoint),
+        ))
+    }
+
+    async fn handshake(
+        &self,
+        _request: tonic::Request<Streaming<HandshakeRequest>>,
+    ) -> Result<tonic::Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented(
+            "Handshake is not needed; send the bearer token with each call",
+        ))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: tonic::Request<Criteria>,
+    ) -> Result<tonic::Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("ListFlights is not supported"))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: tonic::Request<FlightDescriptor>,
+    ) -> Result<tonic::Response<PollInfo>, Status> {
+        Err(Status::unimplemented("PollFlightInfo is not supported"))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: tonic::Request<FlightDescriptor>,
+    ) -> Result<tonic::Response<SchemaResult>, Status> {
+        Err(Status::unimplemented(
+            "A result's schema is only known once its query has run",
+        ))
+    }
+
+    async fn do_put(
+        &self,
+        _request: tonic::Request<Streaming<FlightData>>,
+    ) -> Result<tonic::Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("DoPut is not supported"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: tonic::Request<Streaming<FlightData>>,
+    ) -> Result<tonic::Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("DoExchange is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: tonic::Request<Action>,
+    ) -> Result<tonic::Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("DoAction is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: tonic::Request<Empty>,
+    ) -> Result<tonic::Response<Self::ListActionsStream>, Status> {
+        Ok(tonic::Response::new(stream::empty().boxed()))
+    }
+}
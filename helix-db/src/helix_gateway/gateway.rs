@@ -1,16 +1,29 @@
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::atomic::{self, AtomicUsize};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, sync::Arc};
 
+use axum::Extension;
 use axum::body::Body;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post};
 use core_affinity::CoreId;
-use tracing::{info, trace, warn};
+use tracing::{Instrument, info, info_span, trace, warn};
 
-use super::router::router::{HandlerFn, HelixRouter};
+use super::router::router::{ConcurrencyLimit, HandlerFn, HelixRouter};
+use crate::helix_gateway::admin::{
+    AdminInfo, admin_bm25_handler, admin_config_handler, admin_export_handler, admin_fsck_handler,
+    admin_fsck_repair_handler, admin_rebuild_vectors_handler, admin_routes_handler,
+    admin_stats_handler, admin_walks_handler,
+};
+use crate::helix_gateway::api_keys::ApiKeys;
+use crate::helix_gateway::audit::audit_log_handler;
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::batch::batch_handler;
+use crate::helix_gateway::body_limit::limit_request_bodies;
 #[cfg(feature = "dev-instance")]
 use crate::helix_gateway::builtin::all_nodes_and_edges::nodes_edges_handler;
 #[cfg(feature = "dev-instance")]
@@ -19,9 +32,40 @@ use crate::helix_gateway::builtin::node_by_id::node_details_handler;
 use crate::helix_gateway::builtin::node_connections::node_connections_handler;
 #[cfg(feature = "dev-instance")]
 use crate::helix_gateway::builtin::nodes_by_label::nodes_by_label_handler;
+use crate::helix_gateway::compression::compression_layer;
+use crate::helix_gateway::cors::cors_layer;
+use crate::helix_gateway::ego_cache;
+#[cfg(feature = "cypher")]
+use crate::helix_gateway::cypher;
+use crate::helix_gateway::flight::{FLIGHT_ROUTE, flight_handler};
+use crate::helix_gateway::graphql::{GraphqlSchema, graphql_handler, graphql_sdl_handler};
+use crate::helix_gateway::grpc::{GrpcSchema, grpc_handler};
+use crate::helix_gateway::health::{healthz_handler, readyz_handler};
+use crate::helix_gateway::import::{MaxLineBytes, import_handler};
 use crate::helix_gateway::introspect_schema::introspect_schema_handler;
-use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::helix_gateway::jwt::JwtVerifier;
+use crate::helix_gateway::kafka::{self, KafkaConnector};
+use crate::helix_gateway::otel;
+use crate::helix_gateway::postgres_sync::{self, PostgresSync};
+use crate::helix_gateway::prometheus_metrics::prometheus_metrics_handler;
+use crate::helix_gateway::qdrant;
+use crate::helix_gateway::rate_limit::{ClientAddr, RateLimiter};
+use crate::helix_gateway::reload::{LogLevel, Reloader, admin_reload_handler};
+use crate::helix_gateway::replication::{
+    SNAPSHOT_ROUTE, Standby, leader_handler, snapshot_handler, standby_routes,
+};
+use crate::helix_gateway::scheduler::{Scheduler, schedules_handler};
+use crate::helix_gateway::sessions::{close_session_handler, open_session_handler};
+use crate::helix_gateway::views::ViewRefresher;
+use crate::helix_gateway::subscriptions::subscribe_handler;
+use crate::helix_gateway::text_search;
+use crate::helix_gateway::tls::TlsServer;
+use crate::helix_gateway::vector_search;
+use crate::helix_gateway::worker_pool::{WorkerPool, open_partitions};
+use crate::helix_gateway::worker_stats::worker_stats_handler;
 use crate::protocol;
+use crate::protocol::HelixError;
+use crate::protocol::request::RequestHints;
 use crate::{
     helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts},
     helix_gateway::mcp::mcp::MCPHandlerFn,
@@ -31,6 +75,8 @@ pub struct GatewayOpts {}
 
 impl GatewayOpts {
     pub const DEFAULT_WORKERS_PER_CORE: usize = 8;
+    /// How long in-flight requests get to finish after a shutdown signal
+    pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
 }
 
 pub struct HelixGateway {
@@ -40,6 +86,9 @@ pub struct HelixGateway {
     pub(crate) router: Arc<HelixRouter>,
     pub(crate) opts: Option<HelixGraphEngineOpts>,
     pub(crate) cluster_id: Option<String>,
+    pub(crate) shutdown_timeout: Duration,
+    pub(crate) grpc_proto: Option<String>,
+    pub(crate) log_level: Option<LogLevel>,
 }
 
 impl HelixGateway {
@@ -54,6 +103,12 @@ impl HelixGateway {
     ) -> HelixGateway {
         let router = Arc::new(HelixRouter::new(routes, mcp_routes, write_routes));
         let cluster_id = std::env::var("HELIX_CLUSTER_ID").ok();
+        let shutdown_timeout = Duration::from_secs(
+            std::env::var("HELIX_SHUTDOWN_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(GatewayOpts::DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+        );
         HelixGateway {
             address: address.to_string(),
             graph_access,
@@ -61,10 +116,75 @@ impl HelixGateway {
             workers_per_core,
             opts,
             cluster_id,
+            shutdown_timeout,
+            grpc_proto: None,
+            log_level: None,
         }
     }
 
-    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Routes whose reads are scheduled on the batch lane by default
+    pub fn with_batch_routes(mut self, batch_routes: HashSet<String>) -> Self {
+        self.router_mut().batch_routes = batch_routes;
+        self
+    }
+
+    /// Read routes whose results are cached, with their time to live
+    pub fn with_cache_routes(mut self, cache_routes: HashMap<String, Duration>) -> Self {
+        self.router_mut().cache_routes = cache_routes;
+        self
+    }
+
+    /// Labels touched by each route, used to invalidate cached results on writes
+    pub fn with_route_labels(mut self, route_labels: HashMap<String, Vec<String>>) -> Self {
+        self.router_mut().route_labels = route_labels;
+        self
+    }
+
+    /// Roles required to call each route, enforced for JWT callers
+    pub fn with_route_roles(mut self, route_roles: HashMap<String, Vec<String>>) -> Self {
+        self.router_mut().route_roles = route_roles;
+        self
+    }
+
+    /// Most requests of each route run at once, as declared with `#[max_concurrency(...)]`
+    pub fn with_route_concurrency(mut self, route_concurrency: HashMap<String, usize>) -> Self {
+        self.router_mut().concurrency_limits = route_concurrency
+            .into_iter()
+            .map(|(route, limit)| (route, ConcurrencyLimit::new(limit)))
+            .collect();
+        self
+    }
+
+    /// MCP tools that perform write operations
+    pub fn with_mcp_write_routes(mut self, mcp_write_routes: HashSet<String>) -> Self {
+        self.router_mut().mcp_write_routes = mcp_write_routes;
+        self
+    }
+
+    /// Roles required to call each MCP tool, enforced for JWT callers
+    pub fn with_mcp_route_roles(mut self, mcp_route_roles: HashMap<String, Vec<String>>) -> Self {
+        self.router_mut().mcp_route_roles = mcp_route_roles;
+        self
+    }
+
+    /// Serve the queries over gRPC as described by the compiler's `.proto` output. An empty
+    /// definition leaves gRPC off.
+    pub fn with_grpc_proto(mut self, proto: &str) -> Self {
+        self.grpc_proto = (!proto.is_empty()).then(|| proto.to_string());
+        self
+    }
+
+    /// The filter of what the process logs, so reloads can change it
+    pub fn with_log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+
+    fn router_mut(&mut self) -> &mut HelixRouter {
+        Arc::get_mut(&mut self.router).expect("router should not be shared before the gateway runs")
+    }
+
+    pub fn run(mut self) -> Result<(), Box<dyn std::error::Error>> {
         trace!("Starting Helix Gateway");
 
         let all_core_ids = core_affinity::get_core_ids().expect("unable to get core IDs");
@@ -88,12 +208,104 @@ impl HelixGateway {
             Err(_) => all_core_ids,
         };
 
+        let gateway_config = self
+            .opts
+            .as_ref()
+            .map(|o| o.config.gateway_config())
+            .unwrap_or_default();
+
+        let partitions = match &self.opts {
+            Some(opts) => open_partitions(opts, gateway_config.partitions())?,
+            None => Vec::new(),
+        };
+
         info!(
-            "Worker pool initialized: {} cores, {} worker threads, 1 writer thread",
+            "Worker pool initialized: {} cores, {} worker threads, {} writer thread(s), queue capacity {}",
             all_core_ids.len(),
-            all_core_ids.len() * self.workers_per_core
+            all_core_ids.len() * self.workers_per_core,
+            partitions.len() + 1,
+            gateway_config.queue_capacity()
         );
 
+        if gateway_config.jwt.is_none() && !self.router.route_roles.is_empty() {
+            warn!(
+                "Routes declare #[roles(...)] but JWT auth is not configured; roles are not enforced"
+            );
+        }
+
+        #[cfg(feature = "cypher")]
+        if gateway_config.cypher() {
+            let router = self.router_mut();
+            router.add_route(cypher::CYPHER_READ_ROUTE, cypher::cypher_read, false);
+            router.add_route(cypher::CYPHER_WRITE_ROUTE, cypher::cypher_write, true);
+        }
+        if gateway_config.qdrant() {
+            let router = self.router_mut();
+            router.add_route(qdrant::QDRANT_READ_ROUTE, qdrant::qdrant_read, false);
+            router.add_route(qdrant::QDRANT_WRITE_ROUTE, qdrant::qdrant_write, true);
+        }
+        self.router_mut().add_route(
+            vector_search::SEARCH_BATCH_ROUTE,
+            vector_search::search_vectors_batch,
+            false,
+        );
+        self.router_mut()
+            .add_route(text_search::SEARCH_TEXT_ROUTE, text_search::search_text, false);
+
+        let kafka = gateway_config
+            .kafka
+            .as_ref()
+            .map(|config| KafkaConnector::from_config(config, gateway_config.audit_log()))
+            .transpose()?;
+        if kafka.as_ref().is_some_and(KafkaConnector::ingests) {
+            self.router_mut()
+                .add_route(kafka::KAFKA_INGEST_ROUTE, kafka::kafka_ingest, true);
+        }
+        let postgres_sync = gateway_config
+            .postgres_sync
+            .as_ref()
+            .map(PostgresSync::from_config)
+            .transpose()?;
+        if postgres_sync.is_some() {
+            self.router_mut().add_route(
+                postgres_sync::POSTGRES_SYNC_ROUTE,
+                postgres_sync::postgres_sync,
+                true,
+            );
+        }
+
+        let standby = gateway_config
+            .standby
+            .as_ref()
+            .map(|config| Standby::new(config, self.graph_access.storage.graph_env.path()));
+
+        let tls = gateway_config
+            .tls
+            .as_ref()
+            .map(TlsServer::from_config)
+            .transpose()?;
+
+        let rate_limiter = RateLimiter::from_config(gateway_config.rate_limit.as_ref());
+        for route in rate_limiter.limited_routes() {
+            if !self.router.routes.contains_key(&route) {
+                warn!(
+                    route,
+                    "Rate limit configured for a route that does not exist"
+                );
+            }
+        }
+
+        let scheduler =
+            Scheduler::from_config(gateway_config.schedules.as_deref().unwrap_or_default())?;
+        for query in scheduler.queries() {
+            if !self.router.routes.contains_key(query) {
+                warn!(query, "Schedule configured for a query that does not exist");
+            }
+        }
+        let scheduler = (!scheduler.is_empty()).then(|| Arc::new(scheduler));
+        let views = ViewRefresher::from_registered()?;
+        let views = (!views.is_empty()).then(|| Arc::new(views));
+
         let tokio_core_ids = all_core_ids.clone();
         let tokio_core_setter = Arc::new(CoreSetter::new(tokio_core_ids, 1));
 
@@ -108,18 +320,95 @@ impl HelixGateway {
         let worker_core_ids = all_core_ids.clone();
         let worker_core_setter = Arc::new(CoreSetter::new(worker_core_ids, self.workers_per_core));
 
-        let worker_pool = WorkerPool::new(
+        let worker_pool = WorkerPool::with_partitions(
             worker_core_setter,
             Arc::clone(&self.graph_access),
             Arc::clone(&self.router),
             Arc::clone(&rt),
+            &gateway_config,
+            partitions.clone(),
         );
 
         let mut axum_app = axum::Router::new();
 
         axum_app = axum_app
             .route("/{*path}", post(post_handler))
-            .route("/introspect", get(introspect_schema_handler));
+            .route("/batch", post(batch_handler))
+            .route("/introspect", get(introspect_schema_handler))
+            .route("/worker-stats", get(worker_stats_handler))
+            .route("/metrics", get(prometheus_metrics_handler))
+            .route("/audit-log", get(audit_log_handler))
+            .route("/healthz", get(healthz_handler))
+            .route("/readyz", get(readyz_handler))
+            .route("/leader", get(leader_handler))
+            .route("/subscribe", get(subscribe_handler));
+
+        let config = self
+            .opts
+            .as_ref()
+            .map(|o| o.config.clone())
+            .unwrap_or_default();
+        let admin_info = Arc::new(AdminInfo::new(&config, self.workers_per_core));
+        let reloader = Arc::new(Reloader::new(self.log_level.take(), Arc::clone(&admin_info)));
+        axum_app = axum_app
+            .route("/admin/routes", get(admin_routes_handler))
+            .route("/admin/config", get(admin_config_handler))
+            .route("/admin/stats", get(admin_stats_handler))
+            .route("/admin/export/{kind}/{label}", get(admin_export_handler))
+            .route("/admin/walks", post(admin_walks_handler))
+            .route("/admin/fsck", get(admin_fsck_handler))
+            .route("/admin/fsck/repair", post(admin_fsck_repair_handler))
+            .route("/admin/vectors/rebuild", post(admin_rebuild_vectors_handler))
+            .route("/admin/bm25", get(admin_bm25_handler))
+            .route("/admin/reload", post(admin_reload_handler))
+            .route(SNAPSHOT_ROUTE, get(snapshot_handler))
+            .layer(Extension(Arc::clone(&admin_info)))
+            .layer(Extension(Arc::clone(&reloader)));
+
+        if let Some(proto) = &self.grpc_proto {
+            let schema = GrpcSchema::from_proto(proto)?;
+            axum_app = axum_app
+                .route(&GrpcSchema::route(), post(grpc_handler))
+                .layer(Extension(Arc::new(schema)));
+        }
+
+        if let Some(scheduler) = &scheduler {
+            axum_app = axum_app
+                .route("/schedules", get(schedules_handler))
+                .layer(Extension(Arc::clone(scheduler)));
+        }
+
+        if gateway_config.sessions.is_some() {
+            axum_app = axum_app
+                .route("/session", post(open_session_handler))
+                .route("/session/{token}", delete(close_session_handler));
+        }
+
+        if gateway_config.arrow_flight() {
+            axum_app = axum_app.route(FLIGHT_ROUTE, post(flight_handler));
+        }
+
+        if gateway_config.graphql() {
+            let schema_json = self.opts.as_ref().and_then(|o| o.config.schema.as_deref());
+            match schema_json {
+                Some(schema_json) => {
+                    let schema = GraphqlSchema::from_schema_json(schema_json)?;
+                    axum_app = axum_app
+                        .route("/graphql", post(graphql_handler).get(graphql_sdl_handler))
+                        .layer(Extension(Arc::new(schema)));
+                }
+                None => warn!("GraphQL is enabled but the container has no schema; not serving it"),
+            }
+        }
+
+        #[cfg(feature = "cypher")]
+        if gateway_config.cypher() {
+            axum_app = axum_app.route("/cypher", post(cypher::cypher_handler));
+        }
+
+        if gateway_config.qdrant() {
+            axum_app = axum_app.merge(qdrant::router());
+        }
 
         #[cfg(feature = "dev-instance")]
         {
@@ -130,29 +419,159 @@ impl HelixGateway {
                 .route("/node-details", get(node_details_handler));
         }
 
-        let axum_app = axum_app.with_state(Arc::new(AppState {
+        // Imports are streamed, so they're added after the body limit and hold each line to it
+        let max_request_bytes = gateway_config.max_request_bytes();
+        axum_app = limit_request_bodies(axum_app, max_request_bytes).route(
+            "/import/{query}",
+            post(import_handler).layer(Extension(MaxLineBytes(max_request_bytes))),
+        );
+
+        // A standby only copies its leader, so it serves none of the routes above and nothing
+        // else may write to it
+        let (scheduler, views, kafka, postgres_sync) = match &standby {
+            Some(standby) => {
+                info!(leader = standby.leader(), "Running as a standby");
+                axum_app = standby_routes(standby.status());
+                (None, None, None, None)
+            }
+            None => (scheduler, views, kafka, postgres_sync),
+        };
+
+        // Applied after all routes are added so they wrap every one of them
+        if let Some(compression) = compression_layer(&gateway_config.compression()) {
+            axum_app = axum_app.layer(compression);
+        }
+        if let Some(cors) = &gateway_config.cors {
+            axum_app = axum_app.layer(cors_layer(cors)?);
+        }
+
+        let state = Arc::new(AppState {
             worker_pool,
             schema_json: self.opts.and_then(|o| o.config.schema),
             cluster_id: self.cluster_id,
-        }));
-
-        rt.block_on(async move {
+            api_keys: ApiKeys::from_config(gateway_config.api_keys()),
+            jwt: gateway_config.jwt.clone().map(JwtVerifier::new),
+            rate_limiter,
+        });
+        let axum_app = axum_app.with_state(Arc::clone(&state));
+
+        let scheduler_task = scheduler.map(|scheduler| rt.spawn(scheduler.run(Arc::clone(&state))));
+        let views_task = views.map(|views| rt.spawn(views.run(Arc::clone(&state))));
+        let kafka_task = kafka.map(|kafka| rt.spawn(kafka.run(Arc::clone(&state))));
+        let postgres_sync_task = postgres_sync.map(|sync| rt.spawn(sync.run(Arc::clone(&state))));
+        let standby_task = standby.map(|standby| rt.spawn(standby.run()));
+        #[cfg(unix)]
+        let sighup_task = Some(rt.spawn(
+            Arc::clone(&reloader).reload_on_sighup(Arc::clone(&state)),
+        ));
+        // Without SIGHUP, tunables are only reloaded over HTTP
+        #[cfg(not(unix))]
+        let sighup_task = None;
+        let ego_cache_task = state
+            .worker_pool
+            .graph()
+            .storage
+            .ego_cache
+            .is_enabled()
+            .then(|| rt.spawn(ego_cache::invalidate_on_writes(Arc::clone(&state))));
+
+        let shutdown_timeout = self.shutdown_timeout;
+        let shutdown_started = Arc::new(tokio::sync::Notify::new());
+
+        let shutdown_deadline = rt.block_on(async {
             // Initialize metrics system
             helix_metrics::init_metrics_system();
 
             let listener = tokio::net::TcpListener::bind(self.address)
                 .await
                 .expect("Failed to bind listener");
-            info!("Listener has been bound, starting server");
-            axum::serve(listener, axum_app)
-                .with_graceful_shutdown(shutdown_signal())
-                .await
-                .expect("Failed to serve");
+            info!(
+                https = tls.is_some(),
+                "Listener has been bound, starting server"
+            );
+
+            // Stop accepting connections on signal, then give open connections and the
+            // requests in flight in the worker pool up to `shutdown_timeout`, together,
+            // to complete before giving up on them.
+            let signal_state = Arc::clone(&state);
+            let signal_notify = Arc::clone(&shutdown_started);
+            let shutdown = async move {
+                shutdown_signal().await;
+                signal_state.worker_pool.begin_shutdown();
+                signal_notify.notify_one();
+            };
+            let app = axum_app.into_make_service_with_connect_info::<SocketAddr>();
+            let server = async move {
+                match tls {
+                    Some(tls) => tls.serve(listener, app, shutdown).await,
+                    None => {
+                        axum::serve(listener, app)
+                            .with_graceful_shutdown(shutdown)
+                            .await
+                    }
+                }
+            };
 
+            tokio::pin!(server);
+            let signalled = tokio::select! {
+                res = &mut server => {
+                    res.expect("Failed to serve");
+                    false
+                }
+                _ = shutdown_started.notified() => true,
+            };
+            let deadline = Instant::now() + shutdown_timeout;
+            if signalled
+                && tokio::time::timeout_at(deadline.into(), server)
+                    .await
+                    .is_err()
+            {
+                warn!("Shutdown deadline exceeded, abandoning open connections");
+            }
+
+            info!("Draining worker pool...");
+            if state.worker_pool.drain(deadline).await {
+                info!("Worker pool drained");
+            } else {
+                warn!(
+                    in_flight = state.worker_pool.in_flight(),
+                    "Shutdown deadline exceeded, abandoning in-flight requests"
+                );
+            }
+            deadline
+        });
+
+        for task in [
+            scheduler_task,
+            views_task,
+            kafka_task,
+            postgres_sync_task,
+            ego_cache_task,
+            standby_task,
+            sighup_task,
+        ]
+            .into_iter()
+            .flatten()
+        {
+            task.abort();
+            let _ = rt.block_on(task);
+        }
+
+        // Join the worker threads so their thread-local metrics buffers are flushed
+        match Arc::try_unwrap(state) {
+            Ok(state) => {
+                if state.worker_pool.shutdown(shutdown_deadline) {
+                    info!("Worker pool stopped");
+                }
+            }
+            Err(_) => warn!("Connections still hold the worker pool, skipping worker join"),
+        }
+
+        rt.block_on(async {
             // Shutdown metrics system to flush all pending events
             info!("Shutting down metrics system...");
             let shutdown_result = tokio::time::timeout(
-                std::time::Duration::from_secs(5),
+                Duration::from_secs(5),
                 helix_metrics::shutdown_metrics_system(),
             )
             .await;
@@ -163,10 +582,29 @@ impl HelixGateway {
             }
         });
 
+        close_storage(self.graph_access);
+        partitions.into_iter().for_each(close_storage);
+
         Ok(())
     }
 }
 
+/// Sync LMDB to disk and wait for the environment to close once the last
+/// reference to the graph engine is dropped.
+fn close_storage(graph_access: Arc<HelixGraphEngine>) {
+    let env = &graph_access.storage.graph_env;
+    if let Err(e) = env.force_sync() {
+        warn!(?e, "Failed to sync LMDB environment on shutdown");
+    }
+    let closing = env.clone().prepare_for_closing();
+    drop(graph_access);
+    if closing.wait_timeout(Duration::from_secs(5)) {
+        info!("LMDB environment closed");
+    } else {
+        warn!("LMDB environment still referenced at exit, relying on OS cleanup");
+    }
+}
+
 async fn shutdown_signal() {
     // Respond to either Ctrl-C (SIGINT) or SIGTERM (e.g. `kill` or systemd stop)
     #[cfg(unix)]
@@ -196,9 +634,31 @@ async fn sigterm() {
 
 async fn post_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
     req: protocol::request::Request,
 ) -> axum::http::Response<Body> {
     let start_time = Instant::now();
+    if let BearerAuth(Some(caller)) = &auth
+        && let Err(e) = caller.authorize(
+            &req.name,
+            state.worker_pool.is_write_request(req.req_type, &req.name),
+            &state.worker_pool.request_roles(req.req_type, &req.name),
+        )
+    {
+        info!(caller = %caller.name(), query = %req.name, error = %e, "Unauthorized query");
+        return e.into_response();
+    }
+    if state.rate_limiter.is_enabled() {
+        let client = state
+            .rate_limiter
+            .client_id(auth.0.as_ref(), addr, &headers);
+        if let Err(e) = state.rate_limiter.check(&req.name, &client) {
+            info!(?client, query = %req.name, "Rate limited");
+            return e.into_response();
+        }
+    }
     #[cfg(feature = "api-key")]
     {
         use crate::helix_gateway::key_verification::verify_key;
@@ -216,7 +676,30 @@ async fn post_handler(
     }
     let body = req.body.to_vec();
     let query_name = req.name.clone();
-    let res = state.worker_pool.process(req).await;
+    let hints = RequestHints {
+        caller: auth.0.as_ref().map(Caller::id),
+        ..RequestHints::from_headers(&headers)
+    };
+    let span = info_span!(
+        "helix.request",
+        route = %query_name,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+    );
+    otel::set_remote_parent(&span, &headers);
+    let res = state
+        .worker_pool
+        .process_with(req, hints)
+        .instrument(span.clone())
+        .await;
+    if res.is_err() {
+        span.record("otel.status_code", "ERROR");
+    }
+
+    // Unknown routes are not recorded so arbitrary paths can't grow the series set
+    if !matches!(res, Err(HelixError::NotFound { .. })) {
+        helix_metrics::prometheus::observe_request(&query_name, res.is_ok(), start_time.elapsed());
+    }
 
     match res {
         Ok(r) => {
@@ -262,6 +745,9 @@ pub struct AppState {
     pub worker_pool: WorkerPool,
     pub schema_json: Option<String>,
     pub cluster_id: Option<String>,
+    pub api_keys: ApiKeys,
+    pub jwt: Option<JwtVerifier>,
+    pub rate_limiter: RateLimiter,
 }
 
 pub struct CoreSetter {
@@ -0,0 +1,552 @@
+//! Read-only GraphQL over the schema, for clients with GraphQL tooling that only need simple
+//! reads.
+//!
+//! The GraphQL schema is generated at startup from the schema the container was compiled with.
+//! Every node and vector type becomes an object with `id`, `label` and its properties, plus a
+//! field per edge type it starts (`out<Edge>`) or ends (`in<Edge>`) listing the items at the
+//! other end. The root query has `<type>(id)` and `<type>List(limit, offset)` for each type,
+//! and `search<Vector>(vector, k)` running a vector search. `POST /graphql` executes a request
+//! and `GET /graphql` returns the SDL. Each field resolves in its own read transaction, so a
+//! request doesn't see a single snapshot when writes land while it runs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema,
+    SchemaError, TypeRef,
+};
+use async_graphql::{Name, Value};
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use bumpalo::Bump;
+use heed3::RoTxn;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::storage_methods::StorageMethods;
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::in_::in_::InAdapter;
+use crate::helix_engine::traversal_core::ops::out::out::OutAdapter;
+use crate::helix_engine::traversal_core::ops::source::n_from_type::NFromTypeAdapter;
+use crate::helix_engine::traversal_core::ops::source::v_from_type::VFromTypeAdapter;
+use crate::helix_engine::traversal_core::ops::util::range::RangeAdapter;
+use crate::helix_engine::traversal_core::ops::vectors::search::SearchVAdapter;
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::types::GraphError;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::protocol::HelixError;
+
+/// Route name GraphQL calls are authorized and rate limited as
+pub const GRAPHQL_ROUTE: &str = "graphql";
+/// Results returned by `search<Vector>` when `k` isn't given
+pub const DEFAULT_SEARCH_K: i32 = 10;
+/// Items returned by `<type>List` when `limit` isn't given
+pub const DEFAULT_LIST_LIMIT: i32 = 100;
+/// Deepest selection accepted, so relation fields can't fan out without bound
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// Integers that don't fit GraphQL's 32 bit `Int`
+const LONG_SCALAR: &str = "Long";
+/// Properties without a GraphQL counterpart, returned as they are stored
+const JSON_SCALAR: &str = "JSON";
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+#[derive(Debug, Error)]
+pub enum GraphqlError {
+    #[error("Invalid schema JSON: {0}")]
+    SchemaJson(#[from] sonic_rs::Error),
+    #[error("Invalid GraphQL schema: {0}")]
+    Schema(#[from] SchemaError),
+}
+
+/// The schema JSON the compiler embeds in the container, as far as GraphQL needs it
+#[derive(Deserialize)]
+struct SchemaJson {
+    schema: SchemaData,
+}
+
+#[derive(Deserialize)]
+struct SchemaData {
+    #[serde(default)]
+    nodes: Vec<TypeData>,
+    #[serde(default)]
+    vectors: Vec<TypeData>,
+    #[serde(default)]
+    edges: Vec<EdgeData>,
+}
+
+#[derive(Deserialize)]
+struct TypeData {
+    name: String,
+    #[serde(default)]
+    properties: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct EdgeData {
+    name: String,
+    from: String,
+    to: String,
+}
+
+/// A node or vector, as the JSON object queries return for it
+struct Item(async_graphql::indexmap::IndexMap<Name, Value>);
+
+impl Item {
+    fn new(value: &TraversalValue) -> async_graphql::Result<Self> {
+        match async_graphql::to_value(value)? {
+            Value::Object(mut fields) => {
+                if let TraversalValue::Vector(vector) = value
+                    && let Some(distance) = vector.distance
+                {
+                    fields.insert(Name::new("distance"), Value::from(distance));
+                }
+                Ok(Item(fields))
+            }
+            _ => Err("Traversal returned something other than a node or vector".into()),
+        }
+    }
+
+    fn id(&self) -> async_graphql::Result<u128> {
+        match self.0.get("id") {
+            Some(Value::String(id)) => parse_id(id),
+            _ => Err("Item has no id".into()),
+        }
+    }
+}
+
+/// The generated GraphQL schema
+#[derive(Clone)]
+pub struct GraphqlSchema(Schema);
+
+impl GraphqlSchema {
+    /// Generate the GraphQL schema from the schema JSON the gateway serves at `/introspect`
+    pub fn from_schema_json(schema_json: &str) -> Result<Self, GraphqlError> {
+        let SchemaJson { schema } = sonic_rs::from_str(schema_json)?;
+        let vector_types: Vec<&str> = schema.vectors.iter().map(|v| v.name.as_str()).collect();
+        let known = |name: &str| {
+            vector_types.contains(&name) || schema.nodes.iter().any(|n| n.name == name)
+        };
+
+        let mut query = Object::new("Query");
+        let mut builder = Schema::build("Query", None, None)
+            .register(Scalar::new(LONG_SCALAR).description("Integer wider than 32 bits"))
+            .register(Scalar::new(JSON_SCALAR).description("Any JSON value"))
+            .limit_depth(MAX_QUERY_DEPTH);
+
+        let types = schema.nodes.iter().map(|t| (t, false));
+        for (ty, is_vector) in types.chain(schema.vectors.iter().map(|t| (t, true))) {
+            let mut object = item_object(ty, is_vector);
+            for edge in &schema.edges {
+                if edge.from == ty.name && known(&edge.to) {
+                    let to_vector = vector_types.contains(&edge.to.as_str());
+                    object = object.field(relation_field(edge, Direction::Out, to_vector));
+                }
+                if edge.to == ty.name && known(&edge.from) {
+                    let from_vector = vector_types.contains(&edge.from.as_str());
+                    object = object.field(relation_field(edge, Direction::In, from_vector));
+                }
+            }
+            builder = builder.register(object);
+
+            query = query
+                .field(by_id_field(&ty.name, is_vector))
+                .field(list_field(&ty.name, is_vector));
+            if is_vector {
+                query = query.field(search_field(&ty.name));
+            }
+        }
+
+        Ok(GraphqlSchema(builder.register(query).finish()?))
+    }
+
+    pub fn sdl(&self) -> String {
+        self.0.sdl()
+    }
+
+    /// Run a request against `storage`, blocking a thread of the runtime's blocking pool
+    /// while the fields resolve
+    pub async fn execute(
+        &self,
+        storage: Arc<HelixGraphStorage>,
+        request: async_graphql::Request,
+    ) -> async_graphql::Response {
+        let schema = self.0.clone();
+        let handle = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || handle.block_on(schema.execute(request.data(storage))))
+            .await
+            .unwrap_or_else(|e| {
+                async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(
+                    format!("GraphQL execution failed: {e}"),
+                    None,
+                )])
+            })
+    }
+}
+
+fn item_object(ty: &TypeData, is_vector: bool) -> Object {
+    let mut object = Object::new(&ty.name)
+        .field(item_field("id", TypeRef::named_nn(TypeRef::ID)))
+        .field(item_field("label", TypeRef::named_nn(TypeRef::STRING)));
+    if is_vector {
+        object = object.field(
+            item_field("distance", TypeRef::named(TypeRef::FLOAT))
+                .description("Distance to the query vector, set on search results"),
+        );
+    }
+    // Sorted so the SDL doesn't change between builds of the same schema
+    let properties: BTreeMap<&String, &String> = ty.properties.iter().collect();
+    for (name, field_type) in properties {
+        if matches!(name.as_str(), "id" | "label" | "distance") {
+            continue;
+        }
+        object = object.field(item_field(name, property_type(field_type)));
+    }
+    object
+}
+
+/// GraphQL type of a property, from the name the compiler gives its field type
+fn property_type(field_type: &str) -> TypeRef {
+    if let Some(inner) = field_type
+        .strip_prefix("Array(")
+        .and_then(|t| t.strip_suffix(')'))
+    {
+        return TypeRef::List(Box::new(property_type(inner)));
+    }
+    let name = match field_type {
+        "String" | "Date" => TypeRef::STRING,
+        "Boolean" => TypeRef::BOOLEAN,
+        "ID" => TypeRef::ID,
+        "F32" | "F64" => TypeRef::FLOAT,
+        "I8" | "I16" | "I32" | "U8" | "U16" => TypeRef::INT,
+        "I64" | "U32" | "U64" | "U128" => LONG_SCALAR,
+        _ => JSON_SCALAR,
+    };
+    TypeRef::named(name)
+}
+
+/// A field read from the parent item's JSON
+fn item_field(name: &str, ty: TypeRef) -> Field {
+    let key = name.to_string();
+    Field::new(name, ty, move |ctx| {
+        let value = ctx
+            .parent_value
+            .downcast_ref::<Item>()
+            .and_then(|item| item.0.get(key.as_str()))
+            .cloned();
+        FieldFuture::from_value(value)
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Out,
+    In,
+}
+
+fn relation_field(edge: &EdgeData, direction: Direction, target_is_vector: bool) -> Field {
+    let (prefix, target) = match direction {
+        Direction::Out => ("out", &edge.to),
+        Direction::In => ("in", &edge.from),
+    };
+    let label = edge.name.clone();
+    Field::new(
+        format!("{prefix}{}", upper_first(&edge.name)),
+        TypeRef::named_nn_list_nn(target),
+        move |ctx| {
+            let label = label.clone();
+            FieldFuture::new(async move {
+                let parent = ctx.parent_value.try_downcast_ref::<Item>()?;
+                let id = parent.id()?;
+                let limit = optional_count(&ctx, "limit")?;
+                let items = read(&ctx, |storage, txn, arena| {
+                    let Some(source) = source(storage, txn, arena, id)? else {
+                        return Ok(Vec::new());
+                    };
+                    let g = G::from_iter(storage, txn, std::iter::once(source), arena);
+                    let limit = limit.unwrap_or(usize::MAX);
+                    let values = match (direction, target_is_vector) {
+                        (Direction::Out, false) => g.out_node(&label).range(0, limit).collect(),
+                        (Direction::Out, true) => {
+                            g.out_vec(&label, false).range(0, limit).collect()
+                        }
+                        (Direction::In, false) => g.in_node(&label).range(0, limit).collect(),
+                        (Direction::In, true) => g.in_vec(&label, false).range(0, limit).collect(),
+                    };
+                    items(values)
+                })?;
+                Ok(Some(FieldValue::list(
+                    items.into_iter().map(FieldValue::owned_any),
+                )))
+            })
+        },
+    )
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+}
+
+fn by_id_field(type_name: &str, is_vector: bool) -> Field {
+    let label = type_name.to_string();
+    Field::new(
+        lower_first(type_name),
+        TypeRef::named(type_name),
+        move |ctx| {
+            let label = label.clone();
+            FieldFuture::new(async move {
+                let id = parse_id(ctx.args.try_get("id")?.string()?)?;
+                let item = read(&ctx, |storage, txn, arena| {
+                    let value = if is_vector {
+                        storage
+                            .vectors
+                            .get_vector_properties(txn, id, arena)
+                            .map_err(GraphError::from)?
+                            .filter(|v| !v.deleted)
+                            .map(TraversalValue::VectorNodeWithoutVectorData)
+                    } else {
+                        match storage.get_node(txn, &id, arena) {
+                            Ok(node) => Some(TraversalValue::Node(node)),
+                            Err(GraphError::NodeNotFound) => None,
+                            Err(e) => return Err(e.into()),
+                        }
+                    };
+                    // An id of another type isn't one of this type
+                    value
+                        .filter(|v| v.label() == label)
+                        .map(|v| Item::new(&v))
+                        .transpose()
+                })?;
+                Ok(item.map(FieldValue::owned_any))
+            })
+        },
+    )
+    .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID)))
+}
+
+fn list_field(type_name: &str, is_vector: bool) -> Field {
+    let label = type_name.to_string();
+    Field::new(
+        format!("{}List", lower_first(type_name)),
+        TypeRef::named_nn_list_nn(type_name),
+        move |ctx| {
+            let label = label.clone();
+            FieldFuture::new(async move {
+                let limit = optional_count(&ctx, "limit")?.unwrap_or(DEFAULT_LIST_LIMIT as usize);
+                let offset = optional_count(&ctx, "offset")?.unwrap_or(0);
+                let end = offset.saturating_add(limit);
+                let items = read(&ctx, |storage, txn, arena| {
+                    let g = G::new(storage, txn, arena);
+                    let values = if is_vector {
+                        let label = arena.alloc_str(&label);
+                        g.v_from_type(label, false).range(offset, end).collect()
+                    } else {
+                        g.n_from_type(&label).range(offset, end).collect()
+                    };
+                    items(values)
+                })?;
+                Ok(Some(FieldValue::list(
+                    items.into_iter().map(FieldValue::owned_any),
+                )))
+            })
+        },
+    )
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+    .argument(InputValue::new("offset", TypeRef::named(TypeRef::INT)))
+}
+
+fn search_field(type_name: &str) -> Field {
+    let label = type_name.to_string();
+    Field::new(
+        format!("search{}", upper_first(type_name)),
+        TypeRef::named_nn_list_nn(type_name),
+        move |ctx| {
+            let label = label.clone();
+            FieldFuture::new(async move {
+                let vector = ctx
+                    .args
+                    .try_get("vector")?
+                    .list()?
+                    .iter()
+                    .map(|v| v.f64())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let k = optional_count(&ctx, "k")?.unwrap_or(DEFAULT_SEARCH_K as usize);
+                let items = read(&ctx, |storage, txn, arena| {
+                    let vector = arena.alloc_slice_copy(&vector);
+                    let label = arena.alloc_str(&label);
+                    let values = G::new(storage, txn, arena)
+                        .search_v::<Filter, _>(vector, k, label, None)
+                        .collect();
+                    items(values)
+                })?;
+                Ok(Some(FieldValue::list(
+                    items.into_iter().map(FieldValue::owned_any),
+                )))
+            })
+        },
+    )
+    .argument(InputValue::new(
+        "vector",
+        TypeRef::named_nn_list_nn(TypeRef::FLOAT),
+    ))
+    .argument(InputValue::new("k", TypeRef::named(TypeRef::INT)).default_value(DEFAULT_SEARCH_K))
+}
+
+/// Run `f` in a read transaction on the storage the request was given
+fn read<T>(
+    ctx: &ResolverContext<'_>,
+    f: impl for<'a> FnOnce(&'a HelixGraphStorage, &'a RoTxn<'a>, &'a Bump) -> async_graphql::Result<T>,
+) -> async_graphql::Result<T> {
+    let storage = ctx.data::<Arc<HelixGraphStorage>>()?;
+    let txn = storage.graph_env.read_txn().map_err(GraphError::from)?;
+    let arena = Bump::new();
+    f(storage, &txn, &arena)
+}
+
+/// The node or vector `id` refers to, to start a traversal from
+fn source<'a>(
+    storage: &'a HelixGraphStorage,
+    txn: &'a RoTxn<'a>,
+    arena: &'a Bump,
+    id: u128,
+) -> async_graphql::Result<Option<TraversalValue<'a>>> {
+    match storage.get_node(txn, &id, arena) {
+        Ok(node) => return Ok(Some(TraversalValue::Node(node))),
+        Err(GraphError::NodeNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+    let vector = storage
+        .vectors
+        .get_vector_properties(txn, id, arena)
+        .map_err(GraphError::from)?;
+    Ok(vector
+        .filter(|v| !v.deleted)
+        .map(TraversalValue::VectorNodeWithoutVectorData))
+}
+
+fn items(values: Result<Vec<TraversalValue>, GraphError>) -> async_graphql::Result<Vec<Item>> {
+    values?.iter().map(Item::new).collect()
+}
+
+/// A non-negative integer argument, if given
+fn optional_count(ctx: &ResolverContext<'_>, name: &str) -> async_graphql::Result<Option<usize>> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => {
+            let count = value.i64()?;
+            usize::try_from(count)
+                .map(Some)
+                .map_err(|_| format!("`{name}` must not be negative").into())
+        }
+        _ => Ok(None),
+    }
+}
+
+fn parse_id(id: &str) -> async_graphql::Result<u128> {
+    uuid::Uuid::parse_str(id)
+        .map(|uuid| uuid.as_u128())
+        .map_err(|e| format!("Invalid id `{id}`: {e}").into())
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_lowercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn upper_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(schema): Extension<Arc<GraphqlSchema>>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
+    body: Bytes,
+) -> Response {
+    #[cfg(feature = "api-key")]
+    {
+        use crate::helix_gateway::key_verification::verify_key;
+
+        let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                "Missing x-api-key header",
+            )
+                .into_response();
+        };
+        if let Err(e) = verify_key(api_key) {
+            info!(?e, "Invalid API key");
+            return e.into_response();
+        }
+    }
+
+    if let Err(e) = check_access(&state, auth.0.as_ref(), addr, &headers) {
+        info!(error = %e, "Rejected GraphQL request");
+        return e.into_response();
+    }
+    let request: async_graphql::Request = match sonic_rs::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return HelixError::InvalidGraphql(e.to_string()).into_response(),
+    };
+
+    let span = info_span!("helix.request", route = GRAPHQL_ROUTE, otel.kind = "server");
+    otel::set_remote_parent(&span, &headers);
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    let start_time = Instant::now();
+    let response = schema.execute(storage, request).instrument(span).await;
+    helix_metrics::prometheus::observe_request(
+        GRAPHQL_ROUTE,
+        response.is_ok(),
+        start_time.elapsed(),
+    );
+
+    // Field errors are part of a GraphQL response, which is sent with 200 like any other
+    match sonic_rs::to_vec(&response) {
+        Ok(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => HelixError::Graph(GraphError::New(e.to_string())).into_response(),
+    }
+}
+
+/// The generated schema in SDL, for GraphQL tooling
+pub async fn graphql_sdl_handler(Extension(schema): Extension<Arc<GraphqlSchema>>) -> Response {
+    Response::builder()
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(schema.sdl()))
+        .expect("should be able to make response from string")
+}
+
+fn check_access(
+    state: &AppState,
+    caller: Option<&Caller>,
+    addr: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> Result<(), HelixError> {
+    if let Some(caller) = caller {
+        caller.authorize(GRAPHQL_ROUTE, false, &[])?;
+    }
+    let limiter = &state.rate_limiter;
+    if limiter.is_enabled() {
+        let client = limiter.client_id(caller, addr, headers);
+        limiter.check(GRAPHQL_ROUTE, &client)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,414 @@
+//! gRPC access to the compiled queries.
+//!
+//! The compiler describes the queries in a `.proto` file (see `helixc::generator::proto`)
+//! whose text the container hands to the gateway. The gateway parses it at startup and serves
+//! the service at `/helix.queries.HelixQueries/<rpc>`, translating with the parsed descriptors:
+//! a request message becomes the JSON body the query takes over HTTP, and the JSON result
+//! becomes a `google.protobuf.Struct`, or one `ResultItem` per returned element for the
+//! `<name>Stream` rpcs. gRPC needs HTTP/2, over TLS or as cleartext with prior knowledge.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use futures_util::future::BoxFuture;
+use futures_util::stream;
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor, SerializeOptions, ServiceDescriptor};
+use protox::Compiler;
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+use serde::Serialize;
+use thiserror::Error;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::metadata::MetadataValue;
+use tonic::server::{Grpc, ServerStreamingService, UnaryService};
+use tonic::{Code, Status};
+use tracing::{Instrument, Span, info, info_span};
+
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+pub use crate::protocol::request::{GRPC_PACKAGE, GRPC_SERVICE, RESULT_ITEM_MESSAGE};
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError};
+
+/// Metadata key of the error code HTTP error responses carry in their body
+pub const ERROR_CODE_METADATA: &str = "x-helix-error-code";
+
+/// Name the service definition is compiled under
+const PROTO_FILE_NAME: &str = "queries.proto";
+
+/// Requests keep the parameter names and 64 bit numbers the query's input struct expects
+const REQUEST_JSON_OPTIONS: SerializeOptions = SerializeOptions::new()
+    .use_proto_field_name(true)
+    .stringify_64_bit_integers(false)
+    .skip_default_fields(false);
+
+#[derive(Debug, Error)]
+pub enum GrpcError {
+    #[error("Invalid gRPC service definition: {0}")]
+    Proto(#[from] protox::Error),
+    #[error("gRPC service definition has no `{0}`")]
+    Missing(String),
+}
+
+/// The parsed service definition
+pub struct GrpcSchema {
+    service: ServiceDescriptor,
+    /// `google.protobuf.Struct`, returned by the unary rpcs
+    result: MessageDescriptor,
+    item: MessageDescriptor,
+}
+
+impl GrpcSchema {
+    pub fn from_proto(proto: &str) -> Result<Self, GrpcError> {
+        let mut resolver = ChainFileResolver::new();
+        resolver.add(SourceResolver(proto.to_string()));
+        resolver.add(GoogleFileResolver::new());
+        let mut compiler = Compiler::with_file_resolver(resolver);
+        compiler.open_file(PROTO_FILE_NAME)?;
+        let pool = compiler.descriptor_pool();
+
+        let service_name = format!("{GRPC_PACKAGE}.{GRPC_SERVICE}");
+        let item_name = format!("{GRPC_PACKAGE}.{RESULT_ITEM_MESSAGE}");
+        let result_name = "google.protobuf.Struct";
+        Ok(GrpcSchema {
+            service: pool
+                .get_service_by_name(&service_name)
+                .ok_or(GrpcError::Missing(service_name))?,
+            result: pool
+                .get_message_by_name(result_name)
+                .ok_or_else(|| GrpcError::Missing(result_name.to_string()))?,
+            item: pool
+                .get_message_by_name(&item_name)
+                .ok_or(GrpcError::Missing(item_name))?,
+        })
+    }
+
+    pub fn service(&self) -> &ServiceDescriptor {
+        &self.service
+    }
+
+    /// Route the service's rpcs are served on, with the rpc name as the `method` parameter
+    pub fn route() -> String {
+        format!("/{GRPC_PACKAGE}.{GRPC_SERVICE}/{{method}}")
+    }
+}
+
+/// Serves the service definition from memory, leaving imports to the other resolvers
+struct SourceResolver(String);
+
+impl FileResolver for SourceResolver {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        if name == PROTO_FILE_NAME {
+            File::from_source(name, &self.0)
+        } else {
+            Err(protox::Error::file_not_found(name))
+        }
+    }
+}
+
+pub async fn grpc_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(schema): Extension<Arc<GrpcSchema>>,
+    Path(method): Path<String>,
+    auth: Result<BearerAuth, HelixError>,
+    ClientAddr(addr): ClientAddr,
+    req: axum::extract::Request,
+) -> Response {
+    let Some(method) = schema.service.methods().find(|m| m.name() == method) else {
+        return Status::unimplemented(format!("Unknown method `{method}`")).into_http();
+    };
+    // Both rpcs of a query take `<query>Request`
+    let request = method.input();
+    let name = request
+        .name()
+        .strip_suffix("Request")
+        .unwrap_or(request.name())
+        .to_string();
+
+    let caller = match auth {
+        Ok(BearerAuth(caller)) => caller,
+        Err(e) => return Status::from(e).into_http(),
+    };
+    let api_key = match api_key(req.headers()) {
+        Ok(api_key) => api_key,
+        Err(status) => return status.into_http(),
+    };
+    if let Err(e) = check_access(&state, &name, caller.as_ref(), addr, req.headers()) {
+        info!(query = %name, error = %e, "Rejected gRPC call");
+        return Status::from(e).into_http();
+    }
+
+    let span = info_span!(
+        "helix.request",
+        route = %name,
+        rpc = %method.name(),
+        otel.kind = "server",
+    );
+    otel::set_remote_parent(&span, req.headers());
+    let call = QueryCall {
+        state: Arc::clone(&state),
+        hints: RequestHints::from_headers(req.headers()),
+        name,
+        api_key,
+        result: schema.result.clone(),
+        item: schema.item.clone(),
+        span,
+    };
+
+    let mut grpc = Grpc::new(DynamicCodec { request });
+    let response = if method.is_server_streaming() {
+        grpc.server_streaming(call, req).await
+    } else {
+        grpc.unary(call, req).await
+    };
+    response.map(Body::new)
+}
+
+/// The `x-api-key` metadata, verified when the instance requires API keys
+pub(crate) fn api_key(headers: &HeaderMap) -> Result<Option<String>, Status> {
+    #[cfg(feature = "api-key")]
+    {
+        use crate::helix_gateway::key_verification::verify_key;
+
+        let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+            return Err(Status::unauthenticated("Missing x-api-key metadata"));
+        };
+        if let Err(e) = verify_key(api_key) {
+            info!(?e, "Invalid API key");
+            return Err(Status::from(e));
+        }
+        Ok(Some(api_key.to_string()))
+    }
+    #[cfg(not(feature = "api-key"))]
+    {
+        let _ = headers;
+        Ok(None)
+    }
+}
+
+pub(crate) fn check_access(
+    state: &AppState,
+    name: &str,
+    caller: Option<&Caller>,
+    addr: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> Result<(), HelixError> {
+    let pool = &state.worker_pool;
+    if let Some(caller) = caller {
+        caller.authorize(name, pool.is_write_route(name), &pool.route_roles(name))?;
+    }
+    let limiter = &state.rate_limiter;
+    if limiter.is_enabled() {
+        let client = limiter.client_id(caller, addr, headers);
+        limiter.check(name, &client)?;
+    }
+    Ok(())
+}
+
+impl From<HelixError> for Status {
+    fn from(e: HelixError) -> Self {
+        let code = match e.status() {
+            StatusCode::NOT_FOUND => Code::NotFound,
+            StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+            StatusCode::FORBIDDEN => Code::PermissionDenied,
+            StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+            StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+            status if status.is_client_error() => Code::InvalidArgument,
+            _ => Code::Internal,
+        };
+        let mut status = Status::new(code, e.to_string());
+        status.metadata_mut().insert(
+            ERROR_CODE_METADATA,
+            MetadataValue::from_static(e.code().as_str()),
+        );
+        status
+    }
+}
+
+/// One call of a query, translating between its messages and the JSON the query speaks.
+/// `Grpc` takes the service by value, so the call is cloned into the future it returns.
+#[derive(Clone)]
+struct QueryCall {
+    state: Arc<AppState>,
+    name: String,
+    api_key: Option<String>,
+    hints: RequestHints,
+    result: MessageDescriptor,
+    item: MessageDescriptor,
+    span: Span,
+}
+
+impl QueryCall {
+    async fn execute(self, params: DynamicMessage) -> Result<Vec<u8>, Status> {
+        let mut body = Vec::new();
+        params
+            .serialize_with_options(
+                &mut sonic_rs::Serializer::new(&mut body),
+                &REQUEST_JSON_OPTIONS,
+            )
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let req = Request {
+            name: self.name.clone(),
+            req_type: RequestType::Query,
+            api_key: self.api_key,
+            body: Bytes::from(body),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+
+        let start_time = Instant::now();
+        let res = self
+            .state
+            .worker_pool
+            .process_with(req, self.hints)
+            .instrument(self.span)
+            .await;
+        if !matches!(res, Err(HelixError::NotFound { .. })) {
+            helix_metrics::prometheus::observe_request(
+                &self.name,
+                res.is_ok(),
+                start_time.elapsed(),
+            );
+        }
+        match res {
+            Ok(response) => Ok(response.body),
+            Err(e) => {
+                info!(query = %self.name, error = ?e, "Error response over gRPC");
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl UnaryService<DynamicMessage> for QueryCall {
+    type Response = DynamicMessage;
+    type Future = BoxFuture<'static, Result<tonic::Response<DynamicMessage>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+        let call = self.clone();
+        Box::pin(async move {
+            let result = call.result.clone();
+            let body = call.execute(request.into_inner()).await?;
+            let message = if body.is_empty() {
+                DynamicMessage::new(result)
+            } else {
+                let mut de = sonic_rs::Deserializer::from_slice(&body);
+                DynamicMessage::deserialize(result, &mut de)
+                    .map_err(|e| Status::internal(format!("Result is not a JSON object: {e}")))?
+            };
+            Ok(tonic::Response::new(message))
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ResultItem<'a> {
+    field: &'a str,
+    value: &'a sonic_rs::Value,
+}
+
+impl ServerStreamingService<DynamicMessage> for QueryCall {
+    type Response = DynamicMessage;
+    type ResponseStream = stream::Iter<std::vec::IntoIter<Result<DynamicMessage, Status>>>;
+    type Future = BoxFuture<'static, Result<tonic::Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+        let call = self.clone();
+        Box::pin(async move {
+            let item = call.item.clone();
+            let body = call.execute(request.into_inner()).await?;
+            let items = if body.is_empty() {
+                Vec::new()
+            } else {
+                let result: sonic_rs::Value = sonic_rs::from_slice(&body)
+                    .map_err(|e| Status::internal(format!("Result is not JSON: {e}")))?;
+                result_items(&item, &result)?
+            };
+            Ok(tonic::Response::new(stream::iter(
+                items.into_iter().map(Ok).collect::<Vec<_>>(),
+            )))
+        })
+    }
+}
+
+/// A `ResultItem` per element of each returned array, and per other returned value
+fn result_items(
+    item: &MessageDescriptor,
+    result: &sonic_rs::Value,
+) -> Result<Vec<DynamicMessage>, Status> {
+    use sonic_rs::JsonContainerTrait;
+
+    let Some(fields) = result.as_object() else {
+        return Err(Status::internal("Result is not a JSON object"));
+    };
+    let mut items = Vec::new();
+    for (field, value) in fields.iter() {
+        let values = match value.as_array() {
+            Some(values) => values.iter().collect::<Vec<_>>(),
+            None => vec![value],
+        };
+        for value in values {
+            let json = sonic_rs::to_vec(&ResultItem { field, value })
+                .map_err(|e| Status::internal(e.to_string()))?;
+            let mut de = sonic_rs::Deserializer::from_slice(&json);
+            items.push(
+                DynamicMessage::deserialize(item.clone(), &mut de)
+                    .map_err(|e| Status::internal(e.to_string()))?,
+            );
+        }
+    }
+    Ok(items)
+}
+
+/// Encodes and decodes messages described at runtime
+struct DynamicCodec {
+    request: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.request.clone())
+    }
+}
+
+struct DynamicEncoder;
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: DynamicMessage, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+struct DynamicDecoder(MessageDescriptor);
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<DynamicMessage>, Status> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+}
@@ -0,0 +1,80 @@
+//! Liveness and readiness probes for orchestrators and `helix wait`.
+//!
+//! `/healthz` answers as long as the process can serve HTTP. `/readyz` also checks that the
+//! instance can execute queries, and starts failing once a shutdown begins so load balancers
+//! stop routing to it while in-flight requests drain. It also fails, listing the reasons, while
+//! the stored data is incompatible with the build and writes are refused.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use crate::helix_engine::storage_core::compatibility::SchemaMismatch;
+use crate::helix_gateway::gateway::AppState;
+
+/// Outcome of each readiness check
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    /// A read transaction can be opened on the LMDB environment
+    pub storage: bool,
+    /// Every worker thread is running and the pool accepts requests
+    pub workers: bool,
+    /// The schema the queries were compiled against is loaded
+    pub schema: bool,
+    /// Why the stored data is incompatible with this build, which refuses writes until fixed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_mismatch: Option<SchemaMismatch>,
+}
+
+impl Readiness {
+    pub fn check(state: &AppState) -> Self {
+        let pool = &state.worker_pool;
+        let storage = pool.graph().storage.graph_env.read_txn().is_ok();
+        let workers = !pool.is_shutting_down() && pool.stopped_workers() == 0;
+        let schema = state.schema_json.is_some();
+        let schema_mismatch = pool.graph().storage.schema_mismatch.clone();
+        Readiness {
+            ready: storage && workers && schema && schema_mismatch.is_none(),
+            storage,
+            workers,
+            schema,
+            schema_mismatch,
+        }
+    }
+}
+
+/// Always succeeds; a failed request means the process is down or wedged
+pub async fn healthz_handler() -> Response {
+    json_response(StatusCode::OK, br#"{"status":"ok"}"#.to_vec())
+}
+
+/// 200 when the instance can serve queries, 503 with the failing checks otherwise
+pub async fn readyz_handler(State(state): State<Arc<AppState>>) -> Response {
+    let readiness = Readiness::check(&state);
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    match sonic_rs::to_vec(&readiness) {
+        Ok(body) => json_response(status, body),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize readiness",
+        )
+            .into_response(),
+    }
+}
+
+fn json_response(status: StatusCode, body: Vec<u8>) -> Response {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .expect("should be able to make response from readiness")
+}
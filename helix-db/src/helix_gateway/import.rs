@@ -0,0 +1,205 @@
+//! Bulk imports streamed through a query.
+//!
+//! `POST /import/{query}` takes newline-delimited JSON and runs `query` once per line, with the
+//! line as its parameters, in order and as the body arrives, so an import of any size is never
+//! held in memory. Each line is held to `max_request_bytes` and blank lines are skipped. A
+//! finished import answers `{"imported": n}`. The first failing line stops the import, which
+//! answers with that line's error, its 1-based `line` number and how many lines were `imported`
+//! before it; those stay committed.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use bytes::BytesMut;
+use futures_util::StreamExt;
+use serde::Serialize;
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_gateway::auth::BearerAuth;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::grpc::check_access;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError, error::ErrorBody};
+
+/// Longest line an import accepts, the gateway's `max_request_bytes`
+#[derive(Debug, Clone, Copy)]
+pub struct MaxLineBytes(pub usize);
+
+#[derive(Serialize)]
+struct ImportDone {
+    imported: usize,
+}
+
+#[derive(Serialize)]
+struct ImportError {
+    line: usize,
+    imported: usize,
+    #[serde(flatten)]
+    body: ErrorBody,
+}
+
+pub async fn import_handler(
+    State(state): State<Arc<AppState>>,
+    Path(query): Path<String>,
+    Extension(MaxLineBytes(max)): Extension<MaxLineBytes>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
+    body: Body,
+) -> Response {
+    let api_key = {
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+                return (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "Missing x-api-key header",
+                )
+                    .into_response();
+            };
+            if let Err(e) = verify_key(api_key) {
+                info!(?e, "Invalid API key");
+                return e.into_response();
+            }
+            Some(api_key.to_string())
+        }
+        #[cfg(not(feature = "api-key"))]
+        None::<String>
+    };
+
+    if !state.worker_pool.routes().routes.contains_key(&query) {
+        return HelixError::NotFound {
+            ty: RequestType::Query,
+            name: query,
+        }
+        .into_response();
+    }
+    if let Err(e) = check_access(&state, &query, auth.0.as_ref(), addr, &headers) {
+        info!(query = %query, error = %e, "Import rejected");
+        return e.into_response();
+    }
+
+    let span = info_span!("helix.import", route = %query, otel.kind = "server");
+    otel::set_remote_parent(&span, &headers);
+    let import = Import {
+        state: &state,
+        query: &query,
+        api_key,
+        hints: RequestHints::from_headers(&headers),
+        imported: 0,
+    };
+    let res = import.run(body, max).instrument(span).await;
+
+    let (status, body) = match res {
+        Ok(imported) => {
+            info!(query = %query, imported, "Import finished");
+            (
+                axum::http::StatusCode::OK,
+                sonic_rs::to_vec(&ImportDone { imported }),
+            )
+        }
+        Err((line, imported, e)) => {
+            info!(query = %query, line, imported, error = ?e, "Import stopped");
+            let error = ImportError {
+                line,
+                imported,
+                body: e.body(),
+            };
+            (e.status(), sonic_rs::to_vec(&error))
+        }
+    };
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            body.expect("import result should always serialize"),
+        ))
+        .expect("should be able to make response from import result")
+}
+
+struct Import<'a> {
+    state: &'a AppState,
+    query: &'a str,
+    api_key: Option<String>,
+    hints: RequestHints,
+    imported: usize,
+}
+
+impl Import<'_> {
+    /// Run the query on each line of `body` as it's read, returning the number of lines imported
+    /// or the number and error of the line that failed with the number imported before it
+    async fn run(mut self, body: Body, max: usize) -> Result<usize, (usize, usize, HelixError)> {
+        let mut stream = body.into_data_stream();
+        let mut buf = BytesMut::new();
+        let mut line = 0;
+        // Bytes of `buf` already known not to hold a newline
+        let mut searched = 0;
+        loop {
+            while let Some(end) = buf[searched..].iter().position(|&b| b == b'\n') {
+                let end = searched + end;
+                line += 1;
+                if end > max {
+                    return Err((line, self.imported, HelixError::BodyTooLarge { max }));
+                }
+                let row = buf.split_to(end + 1).freeze();
+                searched = 0;
+                self.run_line(row.slice(..end))
+                    .await
+                    .map_err(|e| (line, self.imported, e))?;
+            }
+            if buf.len() > max {
+                return Err((line + 1, self.imported, HelixError::BodyTooLarge { max }));
+            }
+            searched = buf.len();
+            match stream.next().await {
+                Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    let e = HelixError::InvalidImport(e.to_string());
+                    return Err((line + 1, self.imported, e));
+                }
+                None => break,
+            }
+        }
+        if !buf.is_empty() {
+            line += 1;
+            self.run_line(buf.freeze())
+                .await
+                .map_err(|e| (line, self.imported, e))?;
+        }
+        Ok(self.imported)
+    }
+
+    async fn run_line(&mut self, row: Bytes) -> Result<(), HelixError> {
+        if row.trim_ascii().is_empty() {
+            return Ok(());
+        }
+        let start_time = Instant::now();
+        let req = Request {
+            name: self.query.to_string(),
+            req_type: RequestType::Query,
+            api_key: self.api_key.clone(),
+            body: row,
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+        let res = self
+            .state
+            .worker_pool
+            .process_with(req, self.hints.clone())
+            .await;
+        helix_metrics::prometheus::observe_request(self.query, res.is_ok(), start_time.elapsed());
+        res?;
+        self.imported += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//! JWT bearer token verification against an identity provider's JSON Web Key Set.
+//!
+//! Signing keys are fetched from the configured JWKS URL and cached. A token signed with a
+//! key id the cache doesn't know triggers a refetch, at most once per
+//! [`MIN_REFETCH_INTERVAL`], so rotated keys are picked up without a restart.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::helix_engine::traversal_core::config::JwtConfig;
+use crate::protocol::HelixError;
+
+/// Shortest time between two fetches of the key set
+pub const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(10);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A claim value, keeping only the shapes roles can be read from
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Claim {
+    Text(String),
+    List(Vec<Claim>),
+    Object(HashMap<String, Claim>),
+    Other(IgnoredAny),
+}
+
+/// The caller a verified token was issued to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtPrincipal {
+    /// The `sub` claim
+    pub subject: Option<String>,
+    pub roles: Vec<String>,
+}
+
+impl JwtPrincipal {
+    /// Whether the caller holds one of `required`. Routes requiring no roles are open.
+    pub fn has_any_role(&self, required: &[String]) -> bool {
+        required.is_empty() || required.iter().any(|role| self.roles.contains(role))
+    }
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+pub struct JwtVerifier {
+    config: JwtConfig,
+    client: reqwest::Client,
+    keys: RwLock<Option<CachedKeys>>,
+}
+
+impl JwtVerifier {
+    pub fn new(config: JwtConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .expect("should be able to build JWKS client");
+        JwtVerifier {
+            config,
+            client,
+            keys: RwLock::new(None),
+        }
+    }
+
+    /// Verifier starting from an already fetched key set
+    pub fn with_keys(config: JwtConfig, keys: JwkSet) -> Self {
+        let verifier = Self::new(config);
+        *verifier
+            .keys
+            .try_write()
+            .expect("verifier was just created") = Some(CachedKeys {
+            keys,
+            fetched_at: Instant::now(),
+        });
+        verifier
+    }
+
+    /// Check the token's signature, expiry, issuer and audience, and read the caller's roles
+    pub async fn verify(&self, token: &str) -> Result<JwtPrincipal, HelixError> {
+        let header = decode_header(token).map_err(|e| HelixError::InvalidToken(e.to_string()))?;
+        // Keys from a JWKS are public; accepting HMAC would let the public key act as a secret
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(HelixError::InvalidToken(
+                "symmetric signing algorithms are not accepted".to_string(),
+            ));
+        }
+
+        let jwk = self.signing_key(header.kid.as_deref()).await?;
+        if let Some(key_alg) = jwk.common.key_algorithm
+            && key_alg.to_string().parse::<Algorithm>().ok() != Some(header.alg)
+        {
+            return Err(HelixError::InvalidToken(
+                "token algorithm does not match its signing key".to_string(),
+            ));
+        }
+        let key =
+            DecodingKey::from_jwk(&jwk).map_err(|e| HelixError::InvalidToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = decode::<HashMap<String, Claim>>(token, &key, &validation)
+            .map_err(|e| HelixError::InvalidToken(e.to_string()))?
+            .claims;
+
+        Ok(JwtPrincipal {
+            subject: match claims.get("sub") {
+                Some(Claim::Text(subject)) => Some(subject.clone()),
+                _ => None,
+            },
+            roles: roles_from_claims(&claims, self.config.roles_claim()),
+        })
+    }
+
+    async fn signing_key(&self, kid: Option<&str>) -> Result<Jwk, HelixError> {
+        {
+            let cached = self.keys.read().await;
+            if let Some(cached) = cached.as_ref()
+                && cached.fetched_at.elapsed() < self.config.jwks_refresh()
+                && let Some(jwk) = find_key(&cached.keys, kid)
+            {
+                return Ok(jwk.clone());
+            }
+        }
+
+        let mut cached = self.keys.write().await;
+        // Another request may have refetched while this one waited for the lock
+        let recently_fetched = cached
+            .as_ref()
+            .is_some_and(|c| c.fetched_at.elapsed() < MIN_REFETCH_INTERVAL);
+        if !recently_fetched {
+            match self.fetch_keys().await {
+                Ok(keys) => {
+                    *cached = Some(CachedKeys {
+                        keys,
+                        fetched_at: Instant::now(),
+                    })
+                }
+                Err(e) => {
+                    warn!(?e, url = %self.config.jwks_url, "Failed to fetch JWKS");
+                    // Keep verifying with the previous keys until the provider is reachable
+                    match cached.as_mut() {
+                        Some(stale) => stale.fetched_at = Instant::now(),
+                        None => {
+                            return Err(HelixError::InvalidToken(
+                                "signing keys are unavailable".to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        cached
+            .as_ref()
+            .and_then(|c| find_key(&c.keys, kid))
+            .cloned()
+            .ok_or_else(|| HelixError::InvalidToken("unknown signing key".to_string()))
+    }
+
+    async fn fetch_keys(&self) -> Result<JwkSet, reqwest::Error> {
+        self.client
+            .get(&self.config.jwks_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// The key with id `kid`, or the only key of the set when the token names none
+fn find_key<'a>(keys: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }
+}
+
+/// Roles held in the claim at the dot separated `path`, either an array of strings or a
+/// space separated string like OAuth's `scope`
+pub fn roles_from_claims(claims: &HashMap<String, Claim>, path: &str) -> Vec<String> {
+    let mut parts = path.split('.');
+    let mut claim = parts.next().and_then(|first| claims.get(first));
+    for part in parts {
+        claim = match claim {
+            Some(Claim::Object(nested)) => nested.get(part),
+            _ => None,
+        };
+    }
+    match claim {
+        Some(Claim::Text(roles)) => roles.split_whitespace().map(str::to_string).collect(),
+        Some(Claim::List(roles)) => roles
+            .iter()
+            .filter_map(|role| match role {
+                Claim::Text(role) => Some(role.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
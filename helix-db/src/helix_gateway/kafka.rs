@@ -0,0 +1,471 @@
+//! Kafka connector configured in `GatewayConfig.kafka`, so event-driven systems can feed and
+//! follow an instance without glue services.
+//!
+//! Ingest consumes partitions of a topic of JSON mutation events, one per record. The records
+//! of each fetch run as one request to the `kafka_ingest` write route, which stores the offset
+//! the partition resumes from in the same transaction, so after a restart consumption picks
+//! up where the last committed batch ended and mutations are applied exactly once. `query`
+//! events run their route as a request of their own and may run again if the instance stops
+//! right after them. Events that don't parse or fail to apply are logged and skipped.
+//!
+//! CDC follows the audit log and produces each entry to a topic, keyed by the entry id,
+//! resuming after the last entry it published.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bumpalo::Bump;
+use futures_util::future::{join, join_all};
+use rskafka::chrono::{DateTime, Utc};
+use rskafka::client::error::{Error as ClientError, ProtocolError};
+use rskafka::client::partition::{Compression, OffsetAt, UnknownTopicHandling};
+use rskafka::client::{Client, ClientBuilder};
+use rskafka::record::{Record, RecordAndOffset};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::audit_log::AuditEntry;
+use crate::helix_engine::traversal_core::config::{
+    KafkaCdcConfig, KafkaConfig, KafkaIngestConfig, KafkaStartOffset,
+};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::mutation::{self, Mutation, Outcome, apply, send};
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+
+/// Route batches of mutation events run as, on the writer
+pub const KAFKA_INGEST_ROUTE: &str = "kafka_ingest";
+
+/// Most record bytes a single fetch returns
+const FETCH_MAX_BYTES: i32 = 1 << 20;
+/// How long a fetch waits at the end of a partition for new records
+const FETCH_MAX_WAIT_MS: i32 = 500;
+/// Audit log entries produced per request
+const CDC_BATCH_SIZE: usize = 500;
+/// How often CDC reads the audit log when no write notifications arrive
+const CDC_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Wait before retrying a failed broker request
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Prefix of the metadata keys ingest offsets are stored under
+const OFFSET_KEY_PREFIX: &str = "kafka_offset:";
+/// Prefix of the metadata keys the last published audit entry is stored under
+const CDC_CURSOR_KEY_PREFIX: &str = "kafka_cdc_cursor:";
+
+#[derive(Error, Debug)]
+pub enum KafkaError {
+    #[error("Kafka is configured without brokers")]
+    NoBrokers,
+
+    #[error("Kafka is configured without an `ingest` or `cdc` topic")]
+    NoTopics,
+
+    #[error("Kafka ingest is configured without partitions")]
+    NoPartitions,
+
+    #[error("Kafka CDC publishes the audit log, so it needs `audit_log = true`")]
+    CdcWithoutAuditLog,
+}
+
+/// Body of a `kafka_ingest` request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestBatch {
+    pub mutations: Vec<Mutation>,
+    /// Offset to store for a partition once the mutations commit
+    pub resume: Option<PartitionOffset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionOffset {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// Handler of the `kafka_ingest` route
+pub fn kafka_ingest(input: HandlerInput) -> Result<Response, GraphError> {
+    let batch: IngestBatch = sonic_rs::from_slice(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn()?;
+    for mutation in &batch.mutations {
+        apply(storage, &mut txn, mutation, &arena)?;
+    }
+    if let Some(resume) = &batch.resume {
+        storage.metadata_db.put(
+            &mut txn,
+            &offset_key(&resume.topic, resume.partition),
+            &resume.offset.to_be_bytes(),
+        )?;
+    }
+    storage.check_constraints(&txn)?;
+    storage.audit_write(&mut txn)?;
+    txn.commit()?;
+    Ok(Response {
+        body: sonic_rs::to_vec(&sonic_rs::json!({ "applied": batch.mutations.len() }))?,
+        fmt: Format::Json,
+    })
+}
+
+fn offset_key(topic: &str, partition: i32) -> Vec<u8> {
+    format!("{OFFSET_KEY_PREFIX}{topic}:{partition}").into_bytes()
+}
+
+fn cdc_cursor_key(topic: &str, partition: i32) -> Vec<u8> {
+    format!("{CDC_CURSOR_KEY_PREFIX}{topic}:{partition}").into_bytes()
+}
+
+/// Offset consumption of a partition resumes from, once a batch of it has been applied
+pub fn stored_offset(
+    storage: &HelixGraphStorage,
+    topic: &str,
+    partition: i32,
+) -> Result<Option<i64>, GraphError> {
+    let txn = storage.graph_env.read_txn()?;
+    let offset = storage
+        .metadata_db
+        .get(&txn, &offset_key(topic, partition))?;
+    Ok(offset
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(i64::from_be_bytes))
+}
+
+/// Id of the last audit entry published to a partition
+pub fn stored_cdc_cursor(
+    storage: &HelixGraphStorage,
+    topic: &str,
+    partition: i32,
+) -> Result<Option<Uuid>, GraphError> {
+    let txn = storage.graph_env.read_txn()?;
+    let cursor = storage
+        .metadata_db
+        .get(&txn, &cdc_cursor_key(topic, partition))?;
+    Ok(cursor.and_then(|bytes| Uuid::from_slice(bytes).ok()))
+}
+
+fn store_cdc_cursor(
+    storage: &HelixGraphStorage,
+    topic: &str,
+    partition: i32,
+    cursor: Uuid,
+) -> Result<(), GraphError> {
+    let mut txn = storage.graph_env.write_txn()?;
+    storage.metadata_db.put(
+        &mut txn,
+        &cdc_cursor_key(topic, partition),
+        cursor.as_bytes(),
+    )?;
+    txn.commit()?;
+    Ok(())
+}
+
+/// The record an audit entry is published as
+pub fn change_record(entry: &AuditEntry) -> Record {
+    Record {
+        key: Some(entry.id.to_string().into_bytes()),
+        value: Some(sonic_rs::to_vec(entry).expect("audit entries should always serialize")),
+        headers: BTreeMap::new(),
+        timestamp: DateTime::from_timestamp_millis(entry.timestamp_ms as i64)
+            .unwrap_or_else(Utc::now),
+    }
+}
+
+/// Apply fetched records of a partition in order and store the offset after the last one.
+/// Returns false when the worker pool shut down before they were all applied.
+pub async fn ingest_records(
+    pool: &WorkerPool,
+    topic: &str,
+    partition: i32,
+    records: &[RecordAndOffset],
+) -> bool {
+    let Some(last) = records.last() else {
+        return true;
+    };
+    let mut batch: Vec<(i64, Mutation)> = Vec::new();
+    for RecordAndOffset { record, offset } in records {
+        // Records without a value, such as tombstones, carry no event
+        let Some(value) = &record.value else {
+            continue;
+        };
+        let mutation = match sonic_rs::from_slice::<Mutation>(value) {
+            Ok(mutation) => mutation,
+            Err(e) => {
+                warn!(topic, partition, offset, error = %e, "Skipping record that isn't a mutation event");
+                continue;
+            }
+        };
+        let Mutation::Query { name, params } = mutation else {
+            batch.push((*offset, mutation));
+            continue;
+        };
+        if !batch.is_empty()
+            && !apply_batch(pool, topic, partition, std::mem::take(&mut batch), *offset).await
+        {
+            return false;
+        }
+        let body = match params {
+            Some(params) => {
+                sonic_rs::to_vec(&params).expect("event values should always serialize")
+            }
+            None => b"{}".to_vec(),
+        };
+        match send(pool, &name, body).await {
+            Outcome::Applied => {}
+            Outcome::Failed(e) => {
+                warn!(topic, partition, offset, query = %name, error = %e, "Skipping query event that failed")
+            }
+            Outcome::Stopped => return false,
+        }
+    }
+    apply_batch(pool, topic, partition, batch, last.offset + 1).await
+}
+
+/// Apply mutations of a partition, each paired with the offset after it, in one request that
+/// stores `resume` as the partition's offset
+async fn apply_batch(
+    pool: &WorkerPool,
+    topic: &str,
+    partition: i32,
+    batch: Vec<(i64, Mutation)>,
+    resume: i64,
+) -> bool {
+    let position = |offset| PartitionOffset {
+        topic: topic.to_string(),
+        partition,
+        offset,
+    };
+    let batch = batch
+        .into_iter()
+        .map(|(offset, mutation)| (mutation, position(offset + 1)))
+        .collect();
+    mutation::apply_in_order(
+        pool,
+        KAFKA_INGEST_ROUTE,
+        batch,
+        position(resume),
+        |mutations, resume| {
+            let batch = IngestBatch {
+                mutations,
+                resume: Some(resume),
+            };
+            sonic_rs::to_vec(&batch).expect("mutation events should always serialize")
+        },
+    )
+    .await
+}
+
+/// Retry a broker request until it succeeds, or give up with `None` once the worker pool
+/// shuts down
+async fn retrying<T, F, Fut>(pool: &WorkerPool, action: &str, mut request: F) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    loop {
+        match request().await {
+            Ok(value) => return Some(value),
+            Err(e) => warn!(error = %e, "Failed to {action}, retrying"),
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+        if pool.is_shutting_down() {
+            return None;
+        }
+    }
+}
+
+/// Ingest and CDC of an instance, checked against the gateway config it runs with
+pub struct KafkaConnector {
+    config: KafkaConfig,
+}
+
+impl KafkaConnector {
+    pub fn from_config(config: &KafkaConfig, audit_log: bool) -> Result<Self, KafkaError> {
+        if config.brokers.is_empty() {
+            return Err(KafkaError::NoBrokers);
+        }
+        if config.ingest.is_none() && config.cdc.is_none() {
+            return Err(KafkaError::NoTopics);
+        }
+        if config
+            .ingest
+            .as_ref()
+            .is_some_and(|ingest| ingest.partitions().is_empty())
+        {
+            return Err(KafkaError::NoPartitions);
+        }
+        if config.cdc.is_some() && !audit_log {
+            return Err(KafkaError::CdcWithoutAuditLog);
+        }
+        Ok(KafkaConnector {
+            config: config.clone(),
+        })
+    }
+
+    /// Whether events are consumed, which needs the `kafka_ingest` route
+    pub fn ingests(&self) -> bool {
+        self.config.ingest.is_some()
+    }
+
+    /// Consume and publish until the worker pool starts shutting down
+    pub async fn run(self, state: Arc<AppState>) {
+        let pool = &state.worker_pool;
+        let Some(client) = retrying(pool, "connect to Kafka", || {
+            ClientBuilder::new(self.config.brokers.clone())
+                .client_id(self.config.client_id())
+                .build()
+        })
+        .await
+        else {
+            return;
+        };
+        info!(brokers = ?self.config.brokers, "Connected to Kafka");
+
+        let ingest = join_all(self.config.ingest.iter().flat_map(|ingest| {
+            ingest
+                .partitions()
+                .iter()
+                .map(|&partition| consume(&client, ingest, partition, pool))
+        }));
+        let cdc = async {
+            if let Some(cdc) = &self.config.cdc {
+                publish(&client, cdc, pool).await;
+            }
+        };
+        join(ingest, cdc).await;
+    }
+}
+
+async fn consume(client: &Client, config: &KafkaIngestConfig, partition: i32, pool: &WorkerPool) {
+    let topic = config.topic.as_str();
+    let Some(consumer) = retrying(pool, "connect to an ingest partition", || {
+        client.partition_client(topic, partition, UnknownTopicHandling::Retry)
+    })
+    .await
+    else {
+        return;
+    };
+    let start = match config.start_offset() {
+        KafkaStartOffset::Earliest => OffsetAt::Earliest,
+        KafkaStartOffset::Latest => OffsetAt::Latest,
+    };
+    let mut offset = match stored_offset(&pool.graph().storage, topic, partition) {
+        Ok(Some(offset)) => offset,
+        Ok(None) => {
+            match retrying(pool, "read the start offset", || consumer.get_offset(start)).await {
+                Some(offset) => offset,
+                None => return,
+            }
+        }
+        Err(e) => {
+            error!(topic, partition, error = %e, "Failed to read the stored Kafka offset");
+            return;
+        }
+    };
+    info!(topic, partition, offset, "Consuming Kafka partition");
+
+    while !pool.is_shutting_down() {
+        match consumer
+            .fetch_records(offset, 1..FETCH_MAX_BYTES, FETCH_MAX_WAIT_MS)
+            .await
+        {
+            Ok((records, _)) => {
+                let Some(next) = records.last().map(|last| last.offset + 1) else {
+                    continue;
+                };
+                if !ingest_records(pool, topic, partition, &records).await {
+                    return;
+                }
+                offset = next;
+            }
+            Err(ClientError::ServerError {
+                protocol_error: ProtocolError::OffsetOutOfRange,
+                ..
+            }) => {
+                warn!(
+                    topic,
+                    partition, offset, "Kafka offset is out of range, resuming from the earliest"
+                );
+                match retrying(pool, "read the earliest offset", || {
+                    consumer.get_offset(OffsetAt::Earliest)
+                })
+                .await
+                {
+                    Some(earliest) => offset = earliest,
+                    None => return,
+                }
+            }
+            Err(e) => {
+                warn!(topic, partition, error = %e, "Failed to fetch Kafka records, retrying");
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+async fn publish(client: &Client, config: &KafkaCdcConfig, pool: &WorkerPool) {
+    let (topic, partition) = (config.topic.as_str(), config.partition());
+    let Some(producer) = retrying(pool, "connect to the CDC partition", || {
+        client.partition_client(topic, partition, UnknownTopicHandling::Retry)
+    })
+    .await
+    else {
+        return;
+    };
+    let storage = Arc::clone(&pool.graph().storage);
+    // Subscribed before the first read so no write between the two goes unnoticed
+    let mut changes = pool.changes().subscribe();
+    let mut cursor = match stored_cdc_cursor(&storage, topic, partition) {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            error!(topic, error = %e, "Failed to read the stored CDC position");
+            return;
+        }
+    };
+    info!(topic, partition, "Publishing changes to Kafka");
+
+    loop {
+        let entries = match storage.audit_entries_after(cursor, CDC_BATCH_SIZE) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(error = %e, "Failed to read the audit log for CDC, retrying");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let Some(last) = entries.last().map(|entry| entry.id) else {
+            if pool.is_shutting_down() {
+                return;
+            }
+            // Notifications are only a hint, the log is read again either way
+            let _ = tokio::time::timeout(CDC_POLL_INTERVAL, changes.recv()).await;
+            continue;
+        };
+        let records: Vec<Record> = entries.iter().map(change_record).collect();
+        if retrying(pool, "publish changes", || {
+            producer.produce(records.clone(), Compression::NoCompression)
+        })
+        .await
+        .is_none()
+        {
+            return;
+        }
+        cursor = Some(last);
+        let storage = Arc::clone(&storage);
+        let (topic_owned, stored) = (topic.to_string(), last);
+        let result = tokio::task::spawn_blocking(move || {
+            store_cdc_cursor(&storage, &topic_owned, partition, stored)
+        })
+        .await
+        .unwrap_or_else(|e| Err(GraphError::New(e.to_string())));
+        if let Err(e) = result {
+            warn!(error = %e, "Failed to store the CDC position");
+        }
+    }
+}
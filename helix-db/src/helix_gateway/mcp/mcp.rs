@@ -2,39 +2,87 @@ use crate::{
     helix_engine::{
         storage_core::HelixGraphStorage,
         traversal_core::{
+            config::RateLimit,
             ops::util::{aggregate::AggregateAdapter, group_by::GroupByAdapter},
             traversal_value::TraversalValue,
         },
         types::GraphError,
     },
-    helix_gateway::mcp::tools::{EdgeType, FilterTraversal, Order, ToolArgs, execute_query_chain},
-    protocol::{Format, Request, Response},
+    helix_gateway::{
+        mcp::tools::{EdgeType, FilterTraversal, Order, ToolArgs, execute_query_chain},
+        rate_limit::Bucket,
+    },
+    protocol::{Format, HelixError, Request, Response},
     utils::id::v6_uuid,
 };
 use bumpalo::Bump;
 use helix_macros::mcp_handler;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sonic_rs::JsonValueTrait;
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 pub type QueryStep = ToolArgs;
 
 pub struct McpConnections {
     pub connections: HashMap<String, MCPConnection>,
+    /// Calls each connection opened with `init` may make
+    pub rate_limit: Option<RateLimit>,
 }
 
 impl McpConnections {
     pub fn new() -> Self {
         Self {
             connections: HashMap::new(),
+            rate_limit: None,
         }
     }
 
     pub fn new_with_max_connections(max_connections: usize) -> Self {
         Self {
             connections: HashMap::with_capacity(max_connections),
+            rate_limit: None,
+        }
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Open a connection for `owner`, limited to the configured rate
+    pub fn open(&mut self, connection_id: String, owner: Option<String>) {
+        let mut connection = MCPConnection::new(connection_id);
+        connection.owner = owner;
+        connection.rate_limit = self
+            .rate_limit
+            .map(|limit| Bucket::new(limit, Instant::now()));
+        self.add_connection(connection);
+    }
+
+    /// Check the connection a request names was opened by `caller`, and take from its rate
+    /// budget. `init` names no connection; every other tool must name one `caller` owns.
+    /// Which tools a caller may call is checked by the gateway, from its key scope or roles.
+    pub fn admit(&mut self, request: &Request, caller: Option<&str>) -> Result<(), HelixError> {
+        if request.name == "init" {
+            return Ok(());
+        }
+        let connection_id = sonic_rs::get(&request.body, &["connection_id"])
+            .ok()
+            .and_then(|id| id.as_str().map(str::to_string))
+            .unwrap_or_default();
+        match self.connections.get_mut(&connection_id) {
+            // Another caller's connection is reported as missing, so ids can't be probed
+            Some(connection) if connection.owner.as_deref() == caller => {
+                match &mut connection.rate_limit {
+                    Some(bucket) => bucket.take(Instant::now()),
+                    None => Ok(()),
+                }
+            }
+            _ => Err(HelixError::McpConnectionNotFound(connection_id)),
         }
     }
 
@@ -89,6 +137,10 @@ pub struct MCPConnection {
     pub connection_id: String,
     pub query_chain: Vec<QueryStep>,
     pub current_position: usize,
+    /// The caller that opened the connection, as `Caller::id`; `None` when the instance
+    /// doesn't authenticate callers
+    pub owner: Option<String>,
+    rate_limit: Option<Bucket>,
 }
 
 impl MCPConnection {
@@ -97,6 +149,8 @@ impl MCPConnection {
             connection_id,
             query_chain: Vec::new(),
             current_position: 0,
+            owner: None,
+            rate_limit: None,
         }
     }
 
@@ -139,6 +193,9 @@ pub struct MCPToolInput {
     pub mcp_backend: Arc<McpBackend>,
     pub mcp_connections: Arc<Mutex<McpConnections>>,
     pub schema: Option<String>,
+    /// Who made the request, as `Caller::id`; `None` when the instance doesn't authenticate
+    /// callers
+    pub caller: Option<String>,
 }
 
 pub type BasicMCPHandlerFn = for<'a> fn(&'a mut MCPToolInput) -> Result<Response, GraphError>;
@@ -153,11 +210,26 @@ pub struct MCPHandlerSubmission(pub MCPHandler);
 pub struct MCPHandler {
     pub name: &'static str,
     pub func: BasicMCPHandlerFn,
+    /// Tools of `#[mcp]` write queries. Read-only API keys can't call them.
+    pub is_write: bool,
+    /// Roles a JWT caller needs at least one of. Empty means any authenticated caller.
+    pub roles: &'static [&'static str],
 }
 
 impl MCPHandler {
-    pub const fn new(name: &'static str, func: BasicMCPHandlerFn) -> Self {
-        Self { name, func }
+    pub const fn new(name: &'static str, func: BasicMCPHandlerFn, is_write: bool) -> Self {
+        Self {
+            name,
+            func,
+            is_write,
+            roles: &[],
+        }
+    }
+
+    /// Require JWT callers to hold one of `roles`
+    pub const fn with_roles(mut self, roles: &'static [&'static str]) -> Self {
+        self.roles = roles;
+        self
     }
 }
 
@@ -266,11 +338,12 @@ pub struct InitRequest {
     pub connection_port: u16,
 }
 
+/// Opens a connection that only the calling key or JWT subject can use
 #[mcp_handler]
 pub fn init(input: &mut MCPToolInput) -> Result<Response, GraphError> {
     let connection_id = uuid::Uuid::from_u128(v6_uuid()).to_string();
     let mut connections = input.mcp_connections.lock().unwrap();
-    connections.add_connection(MCPConnection::new(connection_id.clone()));
+    connections.open(connection_id.clone(), input.caller.clone());
     drop(connections);
     Ok(Format::Json.create_response(&connection_id))
 }
@@ -377,6 +450,104 @@ pub fn next(input: &mut MCPToolInput) -> Result<Response, GraphError> {
     Ok(Format::Json.create_response(&next_value))
 }
 
+/// Batch size of `stream` when the request doesn't set one
+pub const DEFAULT_STREAM_BATCH_SIZE: usize = 10;
+
+#[derive(Deserialize)]
+pub struct StreamRequest {
+    pub connection_id: String,
+    pub batch_size: Option<usize>,
+    /// Return the items found so far once this many milliseconds have passed
+    pub max_millis: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct StreamBatch<'arena> {
+    pub items: Vec<TraversalValue<'arena>>,
+    pub progress: StreamProgress,
+}
+
+#[derive(Serialize)]
+pub struct StreamProgress {
+    /// Items returned by `next` and `stream` so far, this batch included
+    pub position: usize,
+    /// Whether the results are exhausted. The batch that finds this out may be empty.
+    pub done: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Returns the next batch of results, resuming where the last `next` or `stream` call stopped.
+/// A batch is cut short once `max_millis` have passed and it holds at least one item, so a slow
+/// traversal shows partial hits early. Clients call again until `progress.done`.
+#[mcp_handler]
+pub fn stream(input: &mut MCPToolInput) -> Result<Response, GraphError> {
+    let data: StreamRequest = sonic_rs::from_slice(&input.request.body)?;
+    let started = Instant::now();
+    let deadline = data
+        .max_millis
+        .map(|millis| started + Duration::from_millis(millis));
+    let batch_size = data.batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE).max(1);
+
+    // Clone necessary data while holding the lock
+    let (query_chain, start) = {
+        let connections = input.mcp_connections.lock().unwrap();
+        let connection = connections
+            .get_connection(&data.connection_id)
+            .ok_or_else(|| {
+                GraphError::StorageError(format!("Connection not found: {}", data.connection_id))
+            })?;
+        (connection.query_chain.clone(), connection.current_position)
+    };
+
+    // Execute long-running operation without holding the lock
+    let arena = Bump::new();
+    let storage = input.mcp_backend.db.as_ref();
+    let txn = storage.graph_env.read_txn()?;
+    let stream = execute_query_chain(&query_chain, storage, &txn, &arena)?;
+    let mut iter = stream.into_inner_iter().skip(start);
+
+    let mut items = Vec::new();
+    let mut done = false;
+    while items.len() < batch_size
+        && (items.is_empty() || deadline.is_none_or(|deadline| Instant::now() < deadline))
+    {
+        match iter.next() {
+            Some(item) => items.push(item?),
+            None => {
+                done = true;
+                break;
+            }
+        }
+    }
+
+    let position = start + items.len();
+    {
+        let mut connections = input.mcp_connections.lock().unwrap();
+        let connection = connections
+            .get_connection_mut(&data.connection_id)
+            .ok_or_else(|| {
+                GraphError::StorageError(format!("Connection not found: {}", data.connection_id))
+            })?;
+        connection.current_position = position;
+    }
+
+    tracing::debug!(
+        "[STREAM] Returning {} items for connection {}, position {}, done: {}",
+        items.len(),
+        data.connection_id,
+        position,
+        done
+    );
+    Ok(Format::Json.create_response(&StreamBatch {
+        items,
+        progress: StreamProgress {
+            position,
+            done,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        },
+    }))
+}
+
 #[derive(Deserialize)]
 pub struct Range {
     pub start: usize,
@@ -1,2 +1,3 @@
 pub mod mcp;
+pub mod resources;
 pub mod tools;
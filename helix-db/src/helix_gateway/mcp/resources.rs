@@ -0,0 +1,531 @@
+//! MCP resources and prompt templates, so LLM clients can ground themselves in the schema,
+//! the compiled queries and the data's shape before they start calling tools.
+//!
+//! The handlers answer the MCP `resources/list`, `resources/read`, `prompts/list` and
+//! `prompts/get` methods, with bodies shaped as the protocol expects them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use bumpalo::Bump;
+use helix_macros::mcp_handler;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helix_engine::{
+        storage_core::HelixGraphStorage, traversal_core::LMDB_STRING_HEADER_LENGTH,
+        types::GraphError, vector_core::vector_without_data::VectorWithoutData,
+    },
+    helix_gateway::mcp::mcp::{MCPHandler, MCPHandlerSubmission, MCPToolInput},
+    protocol::{Format, Response},
+};
+
+pub const SCHEMA_URI: &str = "helix://schema";
+pub const SCHEMA_DOCS_URI: &str = "helix://schema/docs";
+pub const QUERIES_URI: &str = "helix://queries";
+pub const STATS_URI: &str = "helix://stats";
+
+/// Results `semantic_search` asks for when the client doesn't say
+const DEFAULT_SEARCH_K: &str = "10";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub mime_type: &'static str,
+}
+
+pub const RESOURCES: [Resource; 4] = [
+    Resource {
+        uri: SCHEMA_URI,
+        name: "Schema",
+        description: "The node, edge and vector types and the compiled queries, as JSON",
+        mime_type: "application/json",
+    },
+    Resource {
+        uri: SCHEMA_DOCS_URI,
+        name: "Schema documentation",
+        description: "Every node, edge and vector type with its properties",
+        mime_type: "text/markdown",
+    },
+    Resource {
+        uri: QUERIES_URI,
+        name: "Sample queries",
+        description: "The compiled queries with their parameters and an example request body",
+        mime_type: "text/markdown",
+    },
+    Resource {
+        uri: STATS_URI,
+        name: "Label statistics",
+        description: "How many nodes, edges and vectors are stored under each label",
+        mime_type: "application/json",
+    },
+];
+
+#[derive(Debug, Serialize)]
+pub struct PromptArgument {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Prompt {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub arguments: &'static [PromptArgument],
+}
+
+pub const PROMPTS: [Prompt; 3] = [
+    Prompt {
+        name: "explore_graph",
+        description: "Get to know the graph: its types, how they connect and how much data there is",
+        arguments: &[],
+    },
+    Prompt {
+        name: "find_nodes",
+        description: "Find nodes of one type that match a description",
+        arguments: &[
+            PromptArgument {
+                name: "label",
+                description: "The node type to look for",
+                required: true,
+            },
+            PromptArgument {
+                name: "description",
+                description: "What the nodes should match, in plain words",
+                required: true,
+            },
+        ],
+    },
+    Prompt {
+        name: "semantic_search",
+        description: "Search a vector type by meaning and explain the results",
+        arguments: &[
+            PromptArgument {
+                name: "label",
+                description: "The vector type to search",
+                required: true,
+            },
+            PromptArgument {
+                name: "query",
+                description: "The text to search for",
+                required: true,
+            },
+            PromptArgument {
+                name: "k",
+                description: "How many results to fetch, 10 by default",
+                required: false,
+            },
+        ],
+    },
+];
+
+#[derive(Debug, Serialize)]
+struct ResourceList {
+    resources: &'static [Resource],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    pub mime_type: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ResourceRead {
+    contents: Vec<ResourceContents>,
+}
+
+#[derive(Debug, Serialize)]
+struct PromptList {
+    prompts: &'static [Prompt],
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptContent {
+    #[serde(rename = "type")]
+    pub content_type: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptMessage {
+    pub role: &'static str,
+    pub content: PromptContent,
+}
+
+#[derive(Debug, Serialize)]
+struct PromptResult {
+    description: &'static str,
+    messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListRequest {
+    pub connection_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadResourceRequest {
+    pub connection_id: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetPromptRequest {
+    pub connection_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+/// Items stored under each label
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct LabelStats {
+    pub nodes: BTreeMap<String, u64>,
+    pub edges: BTreeMap<String, u64>,
+    /// Vectors that have not been deleted
+    pub vectors: BTreeMap<String, u64>,
+}
+
+impl LabelStats {
+    /// Counts every stored item, reading only the label of nodes and edges
+    pub fn collect(storage: &HelixGraphStorage) -> Result<Self, GraphError> {
+        let txn = storage.graph_env.read_txn()?;
+        let mut stats = LabelStats::default();
+        for item in storage.nodes_db.iter(&txn)? {
+            let (_, value) = item?;
+            count(&mut stats.nodes, stored_label(value)?);
+        }
+        for item in storage.edges_db.iter(&txn)? {
+            let (_, value) = item?;
+            count(&mut stats.edges, stored_label(value)?);
+        }
+        let mut arena = Bump::new();
+        for item in storage.vectors.vector_properties_db.iter(&txn)? {
+            let (id, value) = item?;
+            let vector = VectorWithoutData::from_bincode_bytes(&arena, value, id)?;
+            if !vector.deleted {
+                count(&mut stats.vectors, vector.label);
+            }
+            arena.reset();
+        }
+        Ok(stats)
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, u64>, label: &str) {
+    match counts.get_mut(label) {
+        Some(count) => *count += 1,
+        None => {
+            counts.insert(label.to_string(), 1);
+        }
+    }
+}
+
+/// The label bincode stores ahead of a node's or edge's other fields
+pub(crate) fn stored_label(value: &[u8]) -> Result<&str, GraphError> {
+    let label = value
+        .get(..LMDB_STRING_HEADER_LENGTH)
+        .and_then(|header| {
+            let len = u64::from_le_bytes(header.try_into().ok()?) as usize;
+            value.get(LMDB_STRING_HEADER_LENGTH..LMDB_STRING_HEADER_LENGTH + len)
+        })
+        .ok_or_else(|| GraphError::ConversionError("Stored item has no label".to_string()))?;
+    Ok(std::str::from_utf8(label)?)
+}
+
+/// The schema JSON the compiler embeds in the container, as far as the resources need it
+#[derive(Deserialize, Default)]
+struct SchemaJson {
+    #[serde(default)]
+    schema: SchemaData,
+    #[serde(default)]
+    queries: Vec<QueryData>,
+}
+
+#[derive(Deserialize, Default)]
+struct SchemaData {
+    #[serde(default)]
+    nodes: Vec<TypeData>,
+    #[serde(default)]
+    vectors: Vec<TypeData>,
+    #[serde(default)]
+    edges: Vec<EdgeData>,
+}
+
+#[derive(Deserialize)]
+struct TypeData {
+    name: String,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct EdgeData {
+    name: String,
+    from: String,
+    to: String,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct QueryData {
+    name: String,
+    #[serde(default)]
+    parameters: BTreeMap<String, String>,
+    #[serde(default)]
+    returns: Vec<String>,
+}
+
+fn parse_schema(schema: Option<&str>) -> Result<SchemaJson, GraphError> {
+    match schema {
+        Some(schema) => Ok(sonic_rs::from_str(schema)?),
+        None => Ok(SchemaJson::default()),
+    }
+}
+
+/// Markdown describing every node, edge and vector type
+pub fn schema_docs(schema: Option<&str>) -> Result<String, GraphError> {
+    let schema = parse_schema(schema)?.schema;
+    let mut docs = String::from("# Schema\n");
+    for (heading, types) in [("Nodes", &schema.nodes), ("Vectors", &schema.vectors)] {
+        if types.is_empty() {
+            continue;
+        }
+        let _ = write!(docs, "\n## {heading}\n");
+        for item in types {
+            let _ = write!(docs, "\n### {}\n", item.name);
+            write_properties(&mut docs, &item.properties);
+        }
+    }
+    if !schema.edges.is_empty() {
+        docs.push_str("\n## Edges\n");
+        for edge in &schema.edges {
+            let _ = write!(
+                docs,
+                "\n### {}\n\nFrom `{}` to `{}`.\n",
+                edge.name, edge.from, edge.to
+            );
+            write_properties(&mut docs, &edge.properties);
+        }
+    }
+    Ok(docs)
+}
+
+fn write_properties(docs: &mut String, properties: &BTreeMap<String, String>) {
+    if properties.is_empty() {
+        return;
+    }
+    docs.push('\n');
+    for (name, field_type) in properties {
+        let _ = writeln!(docs, "- `{name}`: {field_type}");
+    }
+}
+
+/// Markdown listing the compiled queries, each with an example request body
+pub fn sample_queries(schema: Option<&str>) -> Result<String, GraphError> {
+    let queries = parse_schema(schema)?.queries;
+    let mut docs = String::from(
+        "# Queries\n\nEach query is called with a JSON body on `POST /<query name>`.\n",
+    );
+    for query in &queries {
+        let _ = write!(docs, "\n## {}\n\n", query.name);
+        if !query.returns.is_empty() {
+            let _ = writeln!(docs, "Returns `{}`.\n", query.returns.join("`, `"));
+        }
+        let body: BTreeMap<&str, sonic_rs::Value> = query
+            .parameters
+            .iter()
+            .map(|(name, param_type)| (name.as_str(), sample_value(param_type)))
+            .collect();
+        let body = sonic_rs::to_string_pretty(&body)?;
+        let _ = writeln!(docs, "```json\n{body}\n```");
+    }
+    Ok(docs)
+}
+
+/// A placeholder value of a parameter type, as the analyzer prints it
+fn sample_value(param_type: &str) -> sonic_rs::Value {
+    match param_type {
+        "String" => sonic_rs::json!("text"),
+        "F32" | "F64" => sonic_rs::json!(0.0),
+        "I8" | "I16" | "I32" | "I64" | "U8" | "U16" | "U32" | "U64" | "U128" => {
+            sonic_rs::json!(0)
+        }
+        "Boolean" => sonic_rs::json!(false),
+        "ID" => sonic_rs::json!("00000000-0000-0000-0000-000000000000"),
+        "Date" => sonic_rs::json!("2024-01-01T00:00:00Z"),
+        _ => match param_type
+            .strip_prefix("Array(")
+            .and_then(|inner| inner.strip_suffix(')'))
+        {
+            Some(inner) => sonic_rs::json!([sample_value(inner)]),
+            None => sonic_rs::Value::new(),
+        },
+    }
+}
+
+/// The messages of a prompt, with its arguments filled in
+pub fn render_prompt(
+    name: &str,
+    arguments: &HashMap<String, String>,
+    schema: Option<&str>,
+    storage: &HelixGraphStorage,
+) -> Result<Vec<PromptMessage>, GraphError> {
+    let prompt = PROMPTS
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| GraphError::New(format!("Unknown prompt: {name}")))?;
+    for argument in prompt.arguments.iter().filter(|a| a.required) {
+        if !arguments.contains_key(argument.name) {
+            return Err(GraphError::New(format!(
+                "Prompt `{name}` needs the `{}` argument",
+                argument.name
+            )));
+        }
+    }
+    let arg = |key: &str| arguments.get(key).map(String::as_str).unwrap_or_default();
+
+    let text = match prompt.name {
+        "explore_graph" => {
+            let stats = sonic_rs::to_string_pretty(&LabelStats::collect(storage)?)?;
+            format!(
+                "You are connected to a HelixDB graph. Here is its schema:\n\n{}\n\
+                 These are the item counts per label:\n\n```json\n{stats}\n```\n\n\
+                 Summarise what the graph holds and how its types connect. Then suggest a few \
+                 questions it can answer, naming the tools (`n_from_type`, `out_step`, \
+                 `in_step`, `filter_items`, `collect`) that would answer each one.",
+                schema_docs(schema)?
+            )
+        }
+        "find_nodes" => {
+            let label = arg("label");
+            let schema = parse_schema(schema)?.schema;
+            let properties = schema
+                .nodes
+                .iter()
+                .find(|node| node.name == label)
+                .map(|node| {
+                    node.properties
+                        .iter()
+                        .map(|(name, field_type)| format!("`{name}` ({field_type})"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .filter(|properties| !properties.is_empty())
+                .unwrap_or_else(|| "none declared".to_string());
+            format!(
+                "Find `{label}` nodes matching this description: {}\n\n\
+                 Start with `n_from_type` for `{label}`, narrow the results with \
+                 `filter_items` on its properties ({properties}), and fetch them with \
+                 `collect`. Explain which filters you used and why.",
+                arg("description")
+            )
+        }
+        "semantic_search" => {
+            let k = arguments
+                .get("k")
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_SEARCH_K);
+            format!(
+                "Search the `{}` vectors for: {}\n\n\
+                 Call `search_vector_text` with k = {k}, then use `next` to page through the \
+                 results. Summarise what the closest matches have in common and how well they \
+                 answer the query.",
+                arg("label"),
+                arg("query")
+            )
+        }
+        _ => unreachable!("every prompt in PROMPTS is rendered"),
+    };
+
+    Ok(vec![PromptMessage {
+        role: "user",
+        content: PromptContent {
+            content_type: "text",
+            text,
+        },
+    }])
+}
+
+fn check_connection(input: &MCPToolInput, connection_id: &str) -> Result<(), GraphError> {
+    let connections = input.mcp_connections.lock().unwrap();
+    match connections.get_connection(connection_id) {
+        Some(_) => Ok(()),
+        None => Err(GraphError::StorageError(format!(
+            "Connection not found: {connection_id}"
+        ))),
+    }
+}
+
+#[mcp_handler]
+pub fn list_resources(input: &mut MCPToolInput) -> Result<Response, GraphError> {
+    let data: ListRequest = sonic_rs::from_slice(&input.request.body)?;
+    check_connection(input, &data.connection_id)?;
+    Ok(Format::Json.create_response(&ResourceList {
+        resources: &RESOURCES,
+    }))
+}
+
+#[mcp_handler]
+pub fn read_resource(input: &mut MCPToolInput) -> Result<Response, GraphError> {
+    let data: ReadResourceRequest = sonic_rs::from_slice(&input.request.body)?;
+    check_connection(input, &data.connection_id)?;
+
+    let schema = input.schema.as_deref();
+    let text = match data.uri.as_str() {
+        SCHEMA_URI => schema.unwrap_or("{}").to_string(),
+        SCHEMA_DOCS_URI => schema_docs(schema)?,
+        QUERIES_URI => sample_queries(schema)?,
+        STATS_URI => sonic_rs::to_string(&LabelStats::collect(&input.mcp_backend.db)?)?,
+        uri => return Err(GraphError::New(format!("Unknown resource: {uri}"))),
+    };
+    let mime_type = RESOURCES
+        .iter()
+        .find(|r| r.uri == data.uri)
+        .map(|r| r.mime_type)
+        .expect("every readable resource is listed");
+    Ok(Format::Json.create_response(&ResourceRead {
+        contents: vec![ResourceContents {
+            uri: data.uri,
+            mime_type,
+            text,
+        }],
+    }))
+}
+
+#[mcp_handler]
+pub fn list_prompts(input: &mut MCPToolInput) -> Result<Response, GraphError> {
+    let data: ListRequest = sonic_rs::from_slice(&input.request.body)?;
+    check_connection(input, &data.connection_id)?;
+    Ok(Format::Json.create_response(&PromptList { prompts: &PROMPTS }))
+}
+
+#[mcp_handler]
+pub fn get_prompt(input: &mut MCPToolInput) -> Result<Response, GraphError> {
+    let data: GetPromptRequest = sonic_rs::from_slice(&input.request.body)?;
+    check_connection(input, &data.connection_id)?;
+
+    let messages = render_prompt(
+        &data.name,
+        &data.arguments,
+        input.schema.as_deref(),
+        &input.mcp_backend.db,
+    )?;
+    let description = PROMPTS
+        .iter()
+        .find(|p| p.name == data.name)
+        .map(|p| p.description)
+        .unwrap_or_default();
+    Ok(Format::Json.create_response(&PromptResult {
+        description,
+        messages,
+    }))
+}
@@ -1,12 +1,88 @@
-#[cfg(feature = "dev-instance")]
+#[cfg(feature = "gateway")]
+pub mod admin;
+#[cfg(feature = "gateway")]
+pub mod api_keys;
+#[cfg(feature = "gateway")]
+pub mod audit;
+#[cfg(feature = "gateway")]
+pub mod auth;
+#[cfg(feature = "gateway")]
+pub mod batch;
+#[cfg(feature = "gateway")]
+pub mod body_limit;
+#[cfg(all(feature = "dev-instance", feature = "gateway"))]
 pub mod builtin;
+#[cfg(feature = "gateway")]
+pub mod change_feed;
+#[cfg(all(feature = "chaos", feature = "gateway"))]
+pub mod chaos;
+#[cfg(feature = "gateway")]
+pub mod compression;
+#[cfg(feature = "gateway")]
+pub mod cors;
+#[cfg(all(feature = "cypher", feature = "gateway"))]
+pub mod cypher;
 pub mod embedding_providers;
+#[cfg(feature = "gateway")]
+pub mod ego_cache;
+#[cfg(feature = "gateway")]
+pub mod flight;
+#[cfg(feature = "gateway")]
 pub mod gateway;
+#[cfg(feature = "gateway")]
+pub mod graphql;
+#[cfg(feature = "gateway")]
+pub mod grpc;
+#[cfg(feature = "gateway")]
+pub mod health;
+#[cfg(feature = "gateway")]
+pub mod import;
+#[cfg(feature = "gateway")]
 pub mod introspect_schema;
-#[cfg(feature = "api-key")]
+#[cfg(feature = "gateway")]
+pub mod jwt;
+#[cfg(feature = "gateway")]
+pub mod kafka;
+#[cfg(all(feature = "api-key", feature = "gateway"))]
 pub mod key_verification;
 pub mod mcp;
+#[cfg(feature = "gateway")]
+pub mod mutation;
+#[cfg(feature = "gateway")]
+pub mod otel;
+#[cfg(feature = "gateway")]
+pub mod postgres_sync;
+#[cfg(feature = "gateway")]
+pub mod prometheus_metrics;
+#[cfg(feature = "gateway")]
+pub mod qdrant;
+pub mod rate_limit;
+#[cfg(feature = "gateway")]
+pub mod reload;
+#[cfg(feature = "gateway")]
+pub mod replication;
+#[cfg(feature = "gateway")]
+pub mod result_cache;
 pub mod router;
-#[cfg(test)]
+#[cfg(feature = "gateway")]
+pub mod scheduler;
+#[cfg(feature = "gateway")]
+pub mod sessions;
+#[cfg(feature = "gateway")]
+pub mod slow_query;
+#[cfg(feature = "gateway")]
+pub mod subscriptions;
+#[cfg(all(test, feature = "gateway"))]
 pub mod tests;
+#[cfg(feature = "gateway")]
+pub mod text_search;
+#[cfg(feature = "gateway")]
+pub mod tls;
+#[cfg(feature = "gateway")]
+pub mod vector_search;
+#[cfg(feature = "gateway")]
+pub mod views;
+#[cfg(feature = "gateway")]
 pub mod worker_pool;
+#[cfg(feature = "gateway")]
+pub mod worker_stats;
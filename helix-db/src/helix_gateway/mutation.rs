@@ -0,0 +1,294 @@
+//! Mutation events ingest sources such as Kafka and Postgres sync apply, and the requests
+//! they run as on the writer.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use bumpalo::Bump;
+use heed3::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::storage_methods::StorageMethods;
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::add_e::AddEAdapter;
+use crate::helix_engine::traversal_core::ops::source::add_n::AddNAdapter;
+use crate::helix_engine::traversal_core::ops::source::n_from_index::NFromIndexAdapter;
+use crate::helix_engine::traversal_core::ops::source::n_from_type::NFromTypeAdapter;
+use crate::helix_engine::traversal_core::ops::util::update::UpdateAdapter;
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::qdrant::points::JsonValue;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::{Request, RequestType};
+use crate::protocol::value::Value;
+use crate::protocol::{Format, HelixError};
+use crate::utils::items::Node;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// A mutation event, such as the JSON value of one Kafka record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Mutation {
+    AddNode {
+        label: String,
+        #[serde(default)]
+        properties: Properties,
+    },
+    /// Add an edge between two existing nodes
+    AddEdge {
+        label: String,
+        from: NodeRef,
+        to: NodeRef,
+        #[serde(default)]
+        properties: Properties,
+    },
+    /// Set `properties` on the node of `label` whose `key` property has the value given in
+    /// `properties`, adding the node when there is none
+    UpsertNode {
+        label: String,
+        key: String,
+        properties: Properties,
+    },
+    /// Run a route with `params` as its JSON body
+    Query {
+        name: String,
+        #[serde(default)]
+        params: Option<JsonValue>,
+    },
+}
+
+/// Properties of an event; nulls are left out
+pub type Properties = HashMap<String, JsonValue>;
+
+/// An edge endpoint, by id or by the value of a key property like upserts find nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NodeRef {
+    Id(Uuid),
+    Key {
+        label: String,
+        key: String,
+        value: JsonValue,
+    },
+}
+
+/// Apply a mutation in a write transaction; `query` events run as requests of their own
+pub(crate) fn apply<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    mutation: &Mutation,
+    arena: &Bump,
+) -> Result<(), GraphError> {
+    match mutation {
+        Mutation::AddNode { label, properties } => {
+            add_node(storage, txn, label, properties, arena)?;
+        }
+        Mutation::AddEdge {
+            label,
+            from,
+            to,
+            properties,
+        } => {
+            let from = resolve(storage, txn, from, arena)?;
+            let to = resolve(storage, txn, to, arena)?;
+            G::new_mut(storage, arena, txn)
+                .add_edge(
+                    arena.alloc_str(label),
+                    property_map(properties, arena),
+                    from,
+                    to,
+                    false,
+                    false,
+                )
+                .collect_to_obj()?;
+        }
+        Mutation::UpsertNode {
+            label,
+            key,
+            properties,
+        } => {
+            let value = properties.get(key).ok_or_else(|| {
+                GraphError::New(format!("Upsert of `{label}` is missing its key `{key}`"))
+            })?;
+            match find_node(storage, txn, label, key, &value.0, arena)? {
+                Some(node) => {
+                    let properties: Vec<(&str, Value)> = properties
+                        .iter()
+                        .filter(|(_, value)| value.0 != Value::Empty)
+                        .map(|(key, value)| (&*arena.alloc_str(key), value.0.clone()))
+                        .collect();
+                    G::new_mut_from(storage, txn, TraversalValue::Node(node), arena)
+                        .update(&properties)
+                        .collect_to_obj()?;
+                }
+                None => add_node(storage, txn, label, properties, arena)?,
+            }
+        }
+        Mutation::Query { .. } => {
+            return Err(GraphError::New(
+                "`query` events run as requests of their own".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn add_node<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    label: &str,
+    properties: &Properties,
+    arena: &Bump,
+) -> Result<(), GraphError> {
+    let indices: Vec<&str> = properties
+        .keys()
+        .map(String::as_str)
+        .filter(|key| storage.secondary_indices.contains_key(*key))
+        .collect();
+    G::new_mut(storage, arena, txn)
+        .add_n(
+            arena.alloc_str(label),
+            property_map(properties, arena),
+            Some(&indices),
+        )
+        .collect_to_obj()?;
+    Ok(())
+}
+
+fn property_map<'arena>(
+    properties: &Properties,
+    arena: &'arena Bump,
+) -> Option<ImmutablePropertiesMap<'arena>> {
+    let values: Vec<_> = properties
+        .iter()
+        .filter(|(_, value)| value.0 != Value::Empty)
+        .map(|(key, value)| (&*arena.alloc_str(key), value.0.clone()))
+        .collect();
+    (!values.is_empty())
+        .then(|| ImmutablePropertiesMap::new(values.len(), values.into_iter(), arena))
+}
+
+/// Id of the node an edge endpoint refers to
+fn resolve<'db: 'arena, 'arena: 'txn, 'txn>(
+    storage: &'db HelixGraphStorage,
+    txn: &'txn RoTxn<'db>,
+    node: &NodeRef,
+    arena: &'arena Bump,
+) -> Result<u128, GraphError> {
+    match node {
+        NodeRef::Id(id) => Ok(storage.get_node(txn, &id.as_u128(), arena)?.id),
+        NodeRef::Key { label, key, value } => find_node(storage, txn, label, key, &value.0, arena)?
+            .map(|node| node.id)
+            .ok_or(GraphError::NodeNotFound),
+    }
+}
+
+/// The node of `label` whose `key` property is `value`, found through the secondary index on
+/// `key` when there is one
+fn find_node<'db: 'arena, 'arena: 'txn, 'txn>(
+    storage: &'db HelixGraphStorage,
+    txn: &'txn RoTxn<'db>,
+    label: &str,
+    key: &str,
+    value: &Value,
+    arena: &'arena Bump,
+) -> Result<Option<Node<'arena>>, GraphError> {
+    let matches = |item: Result<TraversalValue<'arena>, GraphError>| match item {
+        Ok(TraversalValue::Node(node)) if node.get_property(key) == Some(value) => Some(Ok(node)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    };
+    let found = if storage.secondary_indices.contains_key(key) {
+        G::new(storage, txn, arena)
+            .n_from_index(label, key, value)
+            .find_map(matches)
+    } else {
+        G::new(storage, txn, arena)
+            .n_from_type(label)
+            .find_map(matches)
+    };
+    found.transpose()
+}
+
+pub(crate) enum Outcome {
+    Applied,
+    Failed(HelixError),
+    /// The worker pool is shutting down
+    Stopped,
+}
+
+/// Run a request, waiting out overload
+pub(crate) async fn send(pool: &WorkerPool, route: &str, body: Vec<u8>) -> Outcome {
+    let body = Bytes::from(body);
+    loop {
+        let request = Request {
+            name: route.to_string(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: body.clone(),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+        match pool.process(request).await {
+            Ok(_) => return Outcome::Applied,
+            Err(HelixError::ShuttingDown) => return Outcome::Stopped,
+            Err(HelixError::Overloaded { retry_after_secs }) => {
+                tokio::time::sleep(Duration::from_secs(retry_after_secs)).await;
+                if pool.is_shutting_down() {
+                    return Outcome::Stopped;
+                }
+            }
+            Err(e) => return Outcome::Failed(e),
+        }
+    }
+}
+
+/// Apply mutations in order through a write `route`, each paired with the position of the
+/// source once it has applied, with `body` building the request that applies mutations and
+/// stores a position in the same transaction. They run as one request and, when that fails,
+/// one at a time so that only the failing ones are skipped. `end` is stored last. Returns
+/// false when the worker pool shut down before they were all applied.
+pub(crate) async fn apply_in_order<P, F>(
+    pool: &WorkerPool,
+    route: &str,
+    batch: Vec<(Mutation, P)>,
+    end: P,
+    body: F,
+) -> bool
+where
+    P: Clone + PartialEq + Debug,
+    F: Fn(Vec<Mutation>, P) -> Vec<u8>,
+{
+    let mutations = batch.iter().map(|(m, _)| m.clone()).collect();
+    match send(pool, route, body(mutations, end.clone())).await {
+        Outcome::Applied => return true,
+        Outcome::Stopped => return false,
+        Outcome::Failed(_) => {}
+    }
+    let last = batch.last().map(|(_, position)| position.clone());
+    for (mutation, position) in batch {
+        match send(pool, route, body(vec![mutation], position.clone())).await {
+            Outcome::Applied => continue,
+            Outcome::Stopped => return false,
+            Outcome::Failed(e) => {
+                warn!(route, ?position, error = %e, "Skipping mutation event that failed to apply")
+            }
+        }
+        // Move past the skipped event
+        if let Outcome::Stopped = send(pool, route, body(Vec::new(), position)).await {
+            return false;
+        }
+    }
+    if last.as_ref() == Some(&end) {
+        return true;
+    }
+    !matches!(
+        send(pool, route, body(Vec::new(), end)).await,
+        Outcome::Stopped
+    )
+}
@@ -0,0 +1,106 @@
+//! OpenTelemetry span export and W3C trace context propagation.
+//!
+//! The gateway records `tracing` spans for each request. When an OTLP collector is
+//! configured they are exported through [`layer`], and a caller's `traceparent` header
+//! makes the request span a child of the caller's trace.
+
+use axum::http::HeaderMap;
+use opentelemetry::{
+    propagation::{Extractor, TextMapPropagator},
+    trace::TracerProvider,
+};
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use tracing::{Span, Subscriber, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{Layer, filter::filter_fn, registry::LookupSpan};
+
+use crate::helix_engine::traversal_core::config::GatewayConfig;
+
+/// Environment variables the OTLP exporter reads its endpoint from
+const OTLP_ENDPOINT_VARS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+];
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// Make `span` a child of the trace described by the request's `traceparent` and
+/// `tracestate` headers. Requests without a valid `traceparent` start a new trace.
+pub fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+    let cx = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    // Only fails when no OpenTelemetry layer is installed, in which case there is nothing to link
+    let _ = span.set_parent(cx);
+}
+
+/// Signal specific OTLP/HTTP path for a collector base URL
+pub(crate) fn traces_endpoint(base: &str) -> String {
+    let base = base.trim_end_matches('/');
+    match base.ends_with("/v1/traces") {
+        true => base.to_string(),
+        false => format!("{base}/v1/traces"),
+    }
+}
+
+/// Owns the tracer provider; flushes buffered spans when dropped
+pub struct OtelGuard(SdkTracerProvider);
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            warn!(?e, "Failed to flush OpenTelemetry spans");
+        }
+    }
+}
+
+/// Build a layer exporting spans to the instance's OTLP collector.
+/// Returns `None` when no collector is configured.
+pub fn layer<S>(
+    config: &GatewayConfig,
+) -> Result<Option<(impl Layer<S> + use<S>, OtelGuard)>, ExporterBuildError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let exporter = match &config.otlp_endpoint {
+        Some(endpoint) => SpanExporter::builder()
+            .with_http()
+            .with_endpoint(traces_endpoint(endpoint))
+            .build()?,
+        None if OTLP_ENDPOINT_VARS
+            .iter()
+            .any(|var| std::env::var(var).is_ok_and(|v| !v.is_empty())) =>
+        {
+            SpanExporter::builder().with_http().build()?
+        }
+        None => return Ok(None),
+    };
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name().to_string())
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("helix-db");
+    Ok(Some((
+        // Only Helix's own spans are exported, not those of the HTTP stack or the exporter
+        tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(filter_fn(|metadata| {
+                metadata.target().starts_with("helix_db")
+            })),
+        OtelGuard(provider),
+    )))
+}
@@ -0,0 +1,320 @@
+//! Postgres change-data sync configured in `GatewayConfig.postgres_sync`, so an instance can
+//! serve as the graph and vector index over an existing relational source of truth.
+//!
+//! Each synced table is polled for rows whose `updated_at` column moved past the last row
+//! applied, ordered by `updated_at` and then the key column. Rows are upserted as nodes of the
+//! table's label, matched on the key property, through the `postgres_sync` write route, which
+//! stores the table's cursor in the same transaction so a restart resumes after the last row
+//! applied. Deleted rows aren't synced, nor are rows without an `updated_at` or key. Rows
+//! committed with an `updated_at` older than ones already synced, as long transactions can,
+//! are missed until they change again.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bumpalo::Bump;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio_postgres::{Client, NoTls};
+use tracing::{error, info, warn};
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::traversal_core::config::{PostgresSyncConfig, PostgresTableConfig};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::mutation::{self, Mutation, Properties, apply};
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+
+/// Route batches of synced rows run as, on the writer
+pub const POSTGRES_SYNC_ROUTE: &str = "postgres_sync";
+
+/// Rows read per query
+const BATCH_SIZE: usize = 500;
+/// Wait before reconnecting or retrying a failed query
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Prefix of the metadata keys table cursors are stored under
+const CURSOR_KEY_PREFIX: &str = "postgres_sync_cursor:";
+
+#[derive(Error, Debug)]
+pub enum PostgresSyncError {
+    #[error("Postgres sync is configured without tables")]
+    NoTables,
+
+    #[error("Postgres sync has an invalid connection string: {0}")]
+    InvalidUrl(String),
+
+    #[error("Postgres table `{0}` is synced more than once")]
+    DuplicateTable(String),
+}
+
+/// How far a table has been synced: the `updated_at` and key of the last row applied, as text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    pub table: String,
+    pub updated_at: String,
+    pub key: String,
+}
+
+/// Body of a `postgres_sync` request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncBatch {
+    pub mutations: Vec<Mutation>,
+    /// Cursor to store for the table once the mutations commit
+    pub cursor: SyncCursor,
+}
+
+/// A row read from a synced table
+#[derive(Debug, Clone)]
+pub struct ChangedRow {
+    /// The row as a JSON object of its columns
+    pub row: String,
+    pub updated_at: String,
+    pub key: String,
+}
+
+/// Handler of the `postgres_sync` route
+pub fn postgres_sync(input: HandlerInput) -> Result<Response, GraphError> {
+    let batch: SyncBatch = sonic_rs::from_slice(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn()?;
+    for mutation in &batch.mutations {
+        apply(storage, &mut txn, mutation, &arena)?;
+    }
+    storage.metadata_db.put(
+        &mut txn,
+        &cursor_key(&batch.cursor.table),
+        &sonic_rs::to_vec(&batch.cursor)?,
+    )?;
+    storage.check_constraints(&txn)?;
+    storage.audit_write(&mut txn)?;
+    txn.commit()?;
+    Ok(Response {
+        body: sonic_rs::to_vec(&sonic_rs::json!({ "applied": batch.mutations.len() }))?,
+        fmt: Format::Json,
+    })
+}
+
+fn cursor_key(table: &str) -> Vec<u8> {
+    format!("{CURSOR_KEY_PREFIX}{table}").into_bytes()
+}
+
+/// Cursor polling of a table resumes after, once rows of it have been applied
+pub fn stored_cursor(
+    storage: &HelixGraphStorage,
+    table: &str,
+) -> Result<Option<SyncCursor>, GraphError> {
+    let txn = storage.graph_env.read_txn()?;
+    let cursor = storage.metadata_db.get(&txn, &cursor_key(table))?;
+    Ok(cursor.and_then(|bytes| sonic_rs::from_slice(bytes).ok()))
+}
+
+/// A Postgres identifier, quoted so any name is taken as is
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Query for the next rows of a table after `cursor`, returning each row as JSON along with
+/// its `updated_at` and key as text. The cursor is compared as literals, so Postgres reads
+/// them as values of the columns' types.
+pub fn select_changes(config: &PostgresTableConfig, cursor: Option<&SyncCursor>) -> String {
+    let table = config
+        .table
+        .split('.')
+        .map(quote_ident)
+        .collect::<Vec<_>>()
+        .join(".");
+    let updated_at = format!("t.{}", quote_ident(config.updated_at()));
+    let key = format!("t.{}", quote_ident(config.key()));
+    let mut sql = format!(
+        "SELECT to_jsonb(t)::text, {updated_at}::text, {key}::text FROM {table} t \
+         WHERE {updated_at} IS NOT NULL AND {key} IS NOT NULL"
+    );
+    if let Some(cursor) = cursor {
+        sql.push_str(&format!(
+            " AND ({updated_at}, {key}) > ({}, {})",
+            quote_literal(&cursor.updated_at),
+            quote_literal(&cursor.key)
+        ));
+    }
+    sql.push_str(&format!(" ORDER BY {updated_at}, {key} LIMIT {BATCH_SIZE}"));
+    sql
+}
+
+/// The upsert a row is applied as, with the columns the table copies
+fn row_mutation(config: &PostgresTableConfig, row: &str) -> Result<Mutation, sonic_rs::Error> {
+    let mut properties: Properties = sonic_rs::from_str(row)?;
+    if let Some(columns) = &config.columns {
+        properties
+            .retain(|column, _| column == config.key() || columns.iter().any(|c| c == column));
+    }
+    Ok(Mutation::UpsertNode {
+        label: config.label.clone(),
+        key: config.key().to_string(),
+        properties,
+    })
+}
+
+/// Upsert rows read from a table in order and store the cursor of the last one. Returns false
+/// when the worker pool shut down before they were all applied.
+pub async fn apply_rows(
+    pool: &WorkerPool,
+    config: &PostgresTableConfig,
+    rows: &[ChangedRow],
+) -> bool {
+    let cursor = |row: &ChangedRow| SyncCursor {
+        table: config.table.clone(),
+        updated_at: row.updated_at.clone(),
+        key: row.key.clone(),
+    };
+    let Some(end) = rows.last().map(cursor) else {
+        return true;
+    };
+    let mut batch = Vec::with_capacity(rows.len());
+    for row in rows {
+        match row_mutation(config, &row.row) {
+            Ok(mutation) => batch.push((mutation, cursor(row))),
+            Err(e) => {
+                warn!(table = config.table, key = row.key, error = %e, "Skipping row that isn't a JSON object")
+            }
+        }
+    }
+    mutation::apply_in_order(
+        pool,
+        POSTGRES_SYNC_ROUTE,
+        batch,
+        end,
+        |mutations, cursor| {
+            let batch = SyncBatch { mutations, cursor };
+            sonic_rs::to_vec(&batch).expect("synced rows should always serialize")
+        },
+    )
+    .await
+}
+
+/// Tables of a Postgres database kept synced, checked against the gateway config
+pub struct PostgresSync {
+    config: PostgresSyncConfig,
+    connection: tokio_postgres::Config,
+}
+
+impl PostgresSync {
+    pub fn from_config(config: &PostgresSyncConfig) -> Result<Self, PostgresSyncError> {
+        if config.tables.is_empty() {
+            return Err(PostgresSyncError::NoTables);
+        }
+        let mut tables = HashSet::new();
+        for table in &config.tables {
+            if !tables.insert(table.table.as_str()) {
+                return Err(PostgresSyncError::DuplicateTable(table.table.clone()));
+            }
+        }
+        let connection = config
+            .url
+            .parse()
+            .map_err(|e: tokio_postgres::Error| PostgresSyncError::InvalidUrl(e.to_string()))?;
+        Ok(PostgresSync {
+            config: config.clone(),
+            connection,
+        })
+    }
+
+    /// Poll the tables, reconnecting whenever the connection drops, until the worker pool
+    /// starts shutting down
+    pub async fn run(self, state: Arc<AppState>) {
+        let pool = &state.worker_pool;
+        let interval = Duration::from_secs(self.config.poll_interval_secs());
+        while !pool.is_shutting_down() {
+            let (client, connection) = match self.connection.connect(NoTls).await {
+                Ok(connected) => connected,
+                Err(e) => {
+                    warn!(error = %e, "Failed to connect to Postgres, retrying");
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let connection = tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    warn!(error = %e, "Postgres connection closed");
+                }
+            });
+            info!("Connected to Postgres for sync");
+
+            let synced = join_all(
+                self.config
+                    .tables
+                    .iter()
+                    .map(|table| sync_table(&client, table, pool, interval)),
+            )
+            .await;
+            connection.abort();
+            if synced.contains(&false) {
+                return;
+            }
+        }
+    }
+}
+
+/// Poll a table until the connection closes, returning false when it should stop for good
+async fn sync_table(
+    client: &Client,
+    config: &PostgresTableConfig,
+    pool: &WorkerPool,
+    interval: Duration,
+) -> bool {
+    let table = config.table.as_str();
+    let mut cursor = match stored_cursor(&pool.graph().storage, table) {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            error!(table, error = %e, "Failed to read the stored Postgres sync cursor");
+            return false;
+        }
+    };
+    info!(table, label = config.label, "Syncing Postgres table");
+
+    while !pool.is_shutting_down() {
+        let rows = match client
+            .query(&select_changes(config, cursor.as_ref()), &[])
+            .await
+        {
+            Ok(rows) => rows,
+            Err(_) if client.is_closed() => return true,
+            Err(e) => {
+                warn!(table, error = %e, "Failed to read Postgres changes, retrying");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let caught_up = rows.len() < BATCH_SIZE;
+        let rows: Vec<ChangedRow> = rows
+            .iter()
+            .map(|row| ChangedRow {
+                row: row.get(0),
+                updated_at: row.get(1),
+                key: row.get(2),
+            })
+            .collect();
+        if !apply_rows(pool, config, &rows).await {
+            return false;
+        }
+        if let Some(last) = rows.last() {
+            cursor = Some(SyncCursor {
+                table: table.to_string(),
+                updated_at: last.updated_at.clone(),
+                key: last.key.clone(),
+            });
+        }
+        if caught_up {
+            tokio::time::sleep(interval).await;
+        }
+    }
+    false
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use helix_metrics::prometheus::{render, write_counter, write_gauge};
+
+use crate::helix_gateway::admin::AdminAuth;
+use crate::helix_gateway::gateway::AppState;
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves request, worker pool, result cache, LMDB and vector search metrics
+/// in the Prometheus text format.
+pub async fn prometheus_metrics_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> axum::response::Response {
+    axum::response::Response::builder()
+        .header("Content-Type", PROMETHEUS_CONTENT_TYPE)
+        .body(Body::from(render_metrics(&state)))
+        .expect("should be able to make response from metrics")
+}
+
+pub(crate) fn render_metrics(state: &AppState) -> String {
+    let mut out = render();
+
+    let stats = state.worker_pool.stats();
+    write_gauge(
+        &mut out,
+        "helix_worker_threads",
+        "Reader worker threads",
+        stats.workers,
+    );
+    write_gauge(
+        &mut out,
+        "helix_queue_capacity",
+        "Capacity of each worker queue",
+        stats.queue_capacity,
+    );
+    write_gauge(
+        &mut out,
+        "helix_read_queue_depth",
+        "Interactive read requests waiting for a worker",
+        stats.read_queue_depth,
+    );
+    write_gauge(
+        &mut out,
+        "helix_batch_queue_depth",
+        "Batch read requests waiting for a worker",
+        stats.batch_queue_depth,
+    );
+    write_gauge(
+        &mut out,
+        "helix_write_queue_depth",
+        "Write requests waiting for a writer",
+        stats.write_queue_depth,
+    );
+    write_gauge(
+        &mut out,
+        "helix_in_flight_requests",
+        "Requests queued, executing or awaiting IO",
+        stats.in_flight,
+    );
+    write_counter(
+        &mut out,
+        "helix_shed_requests_total",
+        "Requests rejected because a queue was full",
+        stats.shed_total,
+    );
+    write_counter(
+        &mut out,
+        "helix_rate_limited_requests_total",
+        "Requests rejected because the client exceeded its rate limit",
+        state.rate_limiter.rejected_total(),
+    );
+    write_gauge(
+        &mut out,
+        "helix_cache_entries",
+        "Entries in the result cache",
+        stats.cache_entries,
+    );
+    write_counter(
+        &mut out,
+        "helix_cache_hits_total",
+        "Reads served from the result cache",
+        stats.cache_hits,
+    );
+    write_counter(
+        &mut out,
+        "helix_cache_misses_total",
+        "Cacheable reads that had to execute",
+        stats.cache_misses,
+    );
+
+    let env = &state.worker_pool.graph().storage.graph_env;
+    let info = env.info();
+    write_gauge(
+        &mut out,
+        "helix_lmdb_map_size_bytes",
+        "Size of the LMDB memory map",
+        info.map_size,
+    );
+    write_gauge(
+        &mut out,
+        "helix_lmdb_last_page_number",
+        "ID of the last used LMDB page",
+        info.last_page_number,
+    );
+    write_gauge(
+        &mut out,
+        "helix_lmdb_last_txn_id",
+        "ID of the last committed LMDB transaction",
+        info.last_txn_id,
+    );
+    write_gauge(
+        &mut out,
+        "helix_lmdb_readers",
+        "LMDB reader slots in use",
+        info.number_of_readers,
+    );
+    write_gauge(
+        &mut out,
+        "helix_lmdb_max_readers",
+        "Maximum LMDB reader slots",
+        info.maximum_number_of_readers,
+    );
+    if let Ok(size) = env.real_disk_size() {
+        write_gauge(
+            &mut out,
+            "helix_lmdb_disk_size_bytes",
+            "Size of the LMDB data file on disk",
+            size,
+        );
+    }
+
+    out
+}
@@ -0,0 +1,145 @@
+//! Collections are vector labels. A label is a collection once it has live vectors or was
+//! created through the API, which records its vector size in the metadata database.
+
+use heed3::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::storage_core::{HelixGraphStorage, write_log};
+use crate::helix_engine::types::GraphError;
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector_without_data::VectorWithoutData;
+
+/// Prefix of the metadata keys collection configs are stored under
+const CONFIG_KEY_PREFIX: &str = "qdrant_collection:";
+
+/// Vector parameters of a collection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionConfig {
+    pub size: usize,
+    /// Name of the collection's single vector when it was created with a named one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector_name: Option<String>,
+}
+
+fn config_key(collection: &str) -> Vec<u8> {
+    format!("{CONFIG_KEY_PREFIX}{collection}").into_bytes()
+}
+
+/// Config the collection was created with
+pub fn stored_config(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    collection: &str,
+) -> Result<Option<CollectionConfig>, GraphError> {
+    match storage.metadata_db.get(txn, &config_key(collection))? {
+        Some(bytes) => Ok(Some(sonic_rs::from_slice(bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Config of a collection, taking the size of a label that wasn't created through the API
+/// from its first live vector. `None` when the collection doesn't exist.
+pub fn config(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    collection: &str,
+) -> Result<Option<CollectionConfig>, GraphError> {
+    if let Some(config) = stored_config(storage, txn, collection)? {
+        return Ok(Some(config));
+    }
+    let arena = bumpalo::Bump::new();
+    let Some(first) = live_vectors(storage, txn, collection, None, &arena).next() else {
+        return Ok(None);
+    };
+    let vector = storage.vectors.get_full_vector(txn, first?.id, &arena)?;
+    Ok(Some(CollectionConfig {
+        size: vector.data.len(),
+        vector_name: None,
+    }))
+}
+
+pub fn save_config(
+    storage: &HelixGraphStorage,
+    txn: &mut RwTxn,
+    collection: &str,
+    config: &CollectionConfig,
+) -> Result<(), GraphError> {
+    let bytes = sonic_rs::to_vec(config)?;
+    storage
+        .metadata_db
+        .put(txn, &config_key(collection), &bytes)?;
+    Ok(())
+}
+
+/// Delete the vectors and config of a collection. False when it didn't exist.
+pub fn delete(
+    storage: &HelixGraphStorage,
+    txn: &mut RwTxn,
+    collection: &str,
+) -> Result<bool, GraphError> {
+    let arena = bumpalo::Bump::new();
+    let ids = live_vectors(storage, txn, collection, None, &arena)
+        .map(|v| v.map(|v| v.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    for id in &ids {
+        storage.vectors.delete(txn, *id, &arena)?;
+        write_log::record(*id);
+    }
+    let had_config = storage.metadata_db.delete(txn, &config_key(collection))?;
+    Ok(had_config || !ids.is_empty())
+}
+
+/// Names of all collections
+pub fn list(storage: &HelixGraphStorage, txn: &RoTxn) -> Result<Vec<String>, GraphError> {
+    let mut names = Vec::new();
+    for entry in storage
+        .metadata_db
+        .prefix_iter(txn, CONFIG_KEY_PREFIX.as_bytes())?
+    {
+        let (key, _) = entry?;
+        let name = String::from_utf8_lossy(&key[CONFIG_KEY_PREFIX.len()..]);
+        names.push(name.into_owned());
+    }
+
+    let arena = bumpalo::Bump::new();
+    for entry in storage.vectors.vector_properties_db.iter(txn)? {
+        let (id, bytes) = entry?;
+        let vector = VectorWithoutData::from_bincode_bytes(&arena, bytes, id)?;
+        if !vector.deleted && !names.iter().any(|n| n == vector.label) {
+            names.push(vector.label.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Live vectors of a collection in id order, starting at `from`
+pub fn live_vectors<'a>(
+    storage: &HelixGraphStorage,
+    txn: &'a RoTxn,
+    collection: &'a str,
+    from: Option<u128>,
+    arena: &'a bumpalo::Bump,
+) -> impl Iterator<Item = Result<VectorWithoutData<'a>, GraphError>> + 'a {
+    let range = from.unwrap_or(0)..;
+    let iter = storage
+        .vectors
+        .vector_properties_db
+        .range(txn, &range)
+        .map_err(GraphError::from);
+    let (iter, error) = match iter {
+        Ok(iter) => (Some(iter), None),
+        Err(e) => (None, Some(Err(e))),
+    };
+    error
+        .into_iter()
+        .chain(iter.into_iter().flatten().filter_map(move |entry| {
+            let vector = entry.map_err(GraphError::from).and_then(|(id, bytes)| {
+                Ok(VectorWithoutData::from_bincode_bytes(arena, bytes, id)?)
+            });
+            match vector {
+                Ok(v) if v.deleted || v.label != collection => None,
+                result => Some(result),
+            }
+        }))
+}
@@ -0,0 +1,287 @@
+//! Payload filters: `must`, `should` and `must_not` clauses of `match`, `range`, `has_id`,
+//! `is_empty` and `is_null` conditions, nested to any depth.
+
+use serde::{Deserialize, Serialize};
+
+use crate::helix_gateway::qdrant::points::{JsonValue, PointId};
+use crate::protocol::value::Value;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// One condition or a list of them, as Qdrant accepts both in filter clauses. Lists are tried
+/// first, as serde would otherwise read one as a filter's fields by position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(T),
+}
+
+impl<T> OneOrMany<T> {
+    fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(item) => std::slice::from_ref(item),
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Filter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub must: Option<OneOrMany<Condition>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub should: Option<OneOrMany<Condition>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub must_not: Option<OneOrMany<Condition>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    Field(FieldCondition),
+    HasId { has_id: Vec<PointId> },
+    IsEmpty { is_empty: KeyRef },
+    IsNull { is_null: KeyRef },
+    Filter(Box<Filter>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldCondition {
+    pub key: String,
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Match>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRef {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Match {
+    Value { value: JsonValue },
+    Any { any: Vec<JsonValue> },
+    Except { except: Vec<JsonValue> },
+    Text { text: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Range {
+    pub gt: Option<f64>,
+    pub gte: Option<f64>,
+    pub lt: Option<f64>,
+    pub lte: Option<f64>,
+}
+
+impl Filter {
+    /// Check every field condition tests something, as unsupported conditions such as geo
+    /// filters would otherwise match every point
+    pub fn validate(&self) -> Result<(), String> {
+        self.conditions().try_for_each(|condition| match condition {
+            Condition::Field(FieldCondition {
+                key,
+                matches: None,
+                range: None,
+            }) => Err(format!("Unsupported condition on field `{key}`")),
+            Condition::Filter(filter) => filter.validate(),
+            _ => Ok(()),
+        })
+    }
+
+    fn conditions(&self) -> impl Iterator<Item = &Condition> {
+        [&self.must, &self.should, &self.must_not]
+            .into_iter()
+            .flatten()
+            .flat_map(OneOrMany::as_slice)
+    }
+
+    /// Whether the point `id` with `payload` passes the filter
+    pub fn matches(&self, id: u128, payload: Option<&ImmutablePropertiesMap>) -> bool {
+        let test = |c: &Condition| c.matches(id, payload);
+        self.must
+            .as_ref()
+            .is_none_or(|must| must.as_slice().iter().all(test))
+            && self
+                .should
+                .as_ref()
+                .is_none_or(|should| should.as_slice().iter().any(test))
+            && self
+                .must_not
+                .as_ref()
+                .is_none_or(|must_not| !must_not.as_slice().iter().any(test))
+    }
+}
+
+impl Condition {
+    fn matches(&self, id: u128, payload: Option<&ImmutablePropertiesMap>) -> bool {
+        match self {
+            Condition::Field(field) => {
+                let values = lookup(payload, &field.key);
+                field.matches.as_ref().is_none_or(|m| m.matches(&values))
+                    && field.range.as_ref().is_none_or(|r| r.matches(&values))
+            }
+            Condition::HasId { has_id } => has_id.iter().any(|p| p.as_u128() == id),
+            Condition::IsEmpty { is_empty } => lookup(payload, &is_empty.key)
+                .iter()
+                .all(|v| matches!(v, Value::Empty)),
+            Condition::IsNull { is_null } => {
+                let values = lookup(payload, &is_null.key);
+                !values.is_empty() && values.iter().all(|v| matches!(v, Value::Empty))
+            }
+            Condition::Filter(filter) => filter.matches(id, payload),
+        }
+    }
+}
+
+impl Match {
+    fn matches(&self, values: &[&Value]) -> bool {
+        match self {
+            Match::Value { value } => values.iter().any(|v| **v == value.0),
+            Match::Any { any } => values.iter().any(|v| any.iter().any(|a| **v == a.0)),
+            Match::Except { except } => {
+                !values.is_empty() && values.iter().all(|v| except.iter().all(|e| **v != e.0))
+            }
+            Match::Text { text } => values
+                .iter()
+                .any(|v| matches!(v, Value::String(s) if s.contains(text.as_str()))),
+        }
+    }
+}
+
+impl Range {
+    fn matches(&self, values: &[&Value]) -> bool {
+        values.iter().filter_map(|v| as_f64(v)).any(|x| {
+            self.gt.is_none_or(|b| x > b)
+                && self.gte.is_none_or(|b| x >= b)
+                && self.lt.is_none_or(|b| x < b)
+                && self.lte.is_none_or(|b| x <= b)
+        })
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    Some(match value {
+        Value::F32(v) => *v as f64,
+        Value::F64(v) => *v,
+        Value::I8(v) => *v as f64,
+        Value::I16(v) => *v as f64,
+        Value::I32(v) => *v as f64,
+        Value::I64(v) => *v as f64,
+        Value::U8(v) => *v as f64,
+        Value::U16(v) => *v as f64,
+        Value::U32(v) => *v as f64,
+        Value::U64(v) => *v as f64,
+        Value::U128(v) => *v as f64,
+        _ => return None,
+    })
+}
+
+/// Values at a dotted payload key such as `metadata.tags`. Arrays along the path are
+/// flattened, so a condition matches when any of their elements does.
+fn lookup<'a>(payload: Option<&ImmutablePropertiesMap<'a>>, key: &str) -> Vec<&'a Value> {
+    let mut parts = key.split('.').map(|part| part.trim_end_matches("[]"));
+    let Some(root) = parts.next().and_then(|first| payload?.get(first)) else {
+        return Vec::new();
+    };
+    let mut values = flatten(root);
+    for part in parts {
+        values = values
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::Object(object) => object.get(part),
+                _ => None,
+            })
+            .flat_map(flatten)
+            .collect();
+    }
+    values
+}
+
+fn flatten(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(flatten).collect(),
+        value => vec![value],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(arena: &bumpalo::Bump) -> ImmutablePropertiesMap<'_> {
+        let json: JsonValue = sonic_rs::from_str(
+            r#"{"source": "wiki", "year": 2021, "metadata": {"tags": ["a", "b"], "page": null}}"#,
+        )
+        .unwrap();
+        let JsonValue(Value::Object(object)) = json else {
+            panic!("payload should be an object");
+        };
+        let len = object.len();
+        ImmutablePropertiesMap::new(
+            len,
+            object.into_iter().map(|(k, v)| (&*arena.alloc_str(&k), v)),
+            arena,
+        )
+    }
+
+    fn filter(json: &str) -> Filter {
+        let filter: Filter = sonic_rs::from_str(json).unwrap();
+        filter.validate().unwrap();
+        filter
+    }
+
+    #[test]
+    fn test_filter_clauses() {
+        let arena = bumpalo::Bump::new();
+        let payload = payload(&arena);
+        let cases = [
+            (
+                r#"{"must": [{"key": "source", "match": {"value": "wiki"}}]}"#,
+                true,
+            ),
+            (
+                r#"{"must": {"key": "year", "range": {"gte": 2020, "lt": 2022}}}"#,
+                true,
+            ),
+            (
+                r#"{"must": [{"key": "metadata.tags", "match": {"any": ["b", "c"]}}]}"#,
+                true,
+            ),
+            (
+                r#"{"must": [{"key": "metadata.tags[]", "match": {"value": "z"}}]}"#,
+                false,
+            ),
+            (
+                r#"{"must_not": [{"key": "source", "match": {"except": ["web"]}}]}"#,
+                false,
+            ),
+            (
+                r#"{"should": [{"key": "year", "match": {"value": 1}}, {"has_id": [7]}]}"#,
+                true,
+            ),
+            (
+                r#"{"must": [{"is_null": {"key": "metadata.page"}}, {"is_empty": {"key": "x"}}]}"#,
+                true,
+            ),
+            (
+                r#"{"must": [{"must_not": [{"key": "source", "match": {"text": "ik"}}]}]}"#,
+                false,
+            ),
+        ];
+        for (json, expected) in cases {
+            assert_eq!(filter(json).matches(7, Some(&payload)), expected, "{json}");
+        }
+    }
+
+    #[test]
+    fn test_unsupported_conditions_are_rejected() {
+        let json = r#"{"must": [{"key": "location", "geo_radius": {"radius": 1.0}}]}"#;
+        let parsed = sonic_rs::from_str::<Filter>(json);
+        assert!(parsed.is_err() || parsed.unwrap().validate().is_err());
+    }
+}
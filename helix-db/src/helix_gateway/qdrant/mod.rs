@@ -0,0 +1,1047 @@
+//! The commonly used subset of Qdrant's REST API, so RAG frameworks built on Qdrant clients
+//! can use an instance unchanged.
+//!
+//! Collections are vector labels: creating one records its vector size, and labels that
+//! already have vectors, such as the schema's vector types, are collections too. Points are
+//! the label's vectors, with integer or UUID ids stored as the vector id and the payload stored
+//! as its properties. Only the `Cosine` distance is supported, matching the HNSW index.
+//!
+//! Requests are checked by the gateway against the collection, so a missing collection or a
+//! vector of the wrong size is answered with Qdrant's error before it queues. They then run
+//! on the worker pool as the `qdrant` route, or the `qdrant_write` write route for creates,
+//! upserts and deletes, so writes reach the change feed and invalidate the result cache like
+//! other writes. Calls are authorized as reads or writes of those routes, taking the key from
+//! the `api-key` header Qdrant clients send or an `Authorization: Bearer` token.
+
+pub mod collections;
+pub mod filter;
+pub mod points;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequestParts, Path};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use bumpalo::Bump;
+use serde::{Deserialize, Serialize};
+use tracing::{Instrument, info, info_span};
+
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::auth::{Caller, authenticate, bearer_token};
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::otel;
+use crate::helix_gateway::qdrant::collections::CollectionConfig;
+use crate::helix_gateway::qdrant::filter::Filter;
+use crate::helix_gateway::qdrant::points::{
+    JsonValue, NewPoint, PointId, Scroll, Search, WithPayload,
+};
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError, Response};
+
+/// Route reads run as
+pub const QDRANT_READ_ROUTE: &str = "qdrant";
+/// Route creates, upserts and deletes run as, on the writer
+pub const QDRANT_WRITE_ROUTE: &str = "qdrant_write";
+
+/// Header Qdrant clients send their API key in
+const API_KEY_HEADER: &str = "api-key";
+
+/// Results of searches and scrolls that don't set `limit`
+const DEFAULT_LIMIT: usize = 10;
+
+/// An API call, sent to the worker pool as the route's body
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Operation {
+    ListCollections,
+    GetCollection {
+        collection: String,
+    },
+    CollectionExists {
+        collection: String,
+    },
+    CreateCollection {
+        collection: String,
+        config: CollectionConfig,
+    },
+    DeleteCollection {
+        collection: String,
+    },
+    Upsert {
+        collection: String,
+        points: Vec<NewPoint>,
+    },
+    Retrieve {
+        collection: String,
+        ids: Vec<PointId>,
+        with_payload: WithPayload,
+        with_vector: bool,
+    },
+    Delete {
+        collection: String,
+        ids: Option<Vec<PointId>>,
+        filter: Option<Filter>,
+    },
+    Search {
+        collection: String,
+        search: Search,
+    },
+    Query {
+        collection: String,
+        search: Search,
+    },
+    Scroll {
+        collection: String,
+        scroll: Scroll,
+    },
+    Count {
+        collection: String,
+        filter: Option<Filter>,
+    },
+}
+
+impl Operation {
+    fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Operation::CreateCollection { .. }
+                | Operation::DeleteCollection { .. }
+                | Operation::Upsert { .. }
+                | Operation::Delete { .. }
+        )
+    }
+
+    /// Collection the call needs to exist
+    fn existing_collection(&self) -> Option<&str> {
+        match self {
+            Operation::ListCollections
+            | Operation::CollectionExists { .. }
+            | Operation::CreateCollection { .. }
+            | Operation::DeleteCollection { .. } => None,
+            Operation::GetCollection { collection }
+            | Operation::Upsert { collection, .. }
+            | Operation::Retrieve { collection, .. }
+            | Operation::Delete { collection, .. }
+            | Operation::Search { collection, .. }
+            | Operation::Query { collection, .. }
+            | Operation::Scroll { collection, .. }
+            | Operation::Count { collection, .. } => Some(collection),
+        }
+    }
+
+    /// Vectors the call passes, which must have the collection's size
+    fn vectors(&self) -> Vec<&[f64]> {
+        match self {
+            Operation::Upsert { points, .. } => points.iter().map(|p| &p.vector[..]).collect(),
+            Operation::Search { search, .. } | Operation::Query { search, .. } => {
+                vec![&search.vector]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// An error answered in Qdrant's format
+struct QdrantError {
+    status: StatusCode,
+    message: String,
+}
+
+impl QdrantError {
+    fn bad_request(message: impl std::fmt::Display) -> Self {
+        QdrantError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("Wrong input: {message}"),
+        }
+    }
+
+    fn collection_not_found(collection: &str) -> Self {
+        QdrantError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Not found: Collection `{collection}` doesn't exist!"),
+        }
+    }
+
+    fn into_response(self, start: Instant) -> axum::http::Response<Body> {
+        let body = sonic_rs::json!({
+            "status": { "error": self.message },
+            "time": start.elapsed().as_secs_f64(),
+        });
+        json_response(self.status, body.to_string().into_bytes())
+    }
+}
+
+impl From<sonic_rs::Error> for QdrantError {
+    fn from(error: sonic_rs::Error) -> Self {
+        QdrantError {
+            status: StatusCode::BAD_REQUEST,
+            message: format!("Format error in JSON body: {error}"),
+        }
+    }
+}
+
+fn json_response(status: StatusCode, body: Vec<u8>) -> axum::http::Response<Body> {
+    axum::http::Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .expect("should be able to make response from Qdrant result")
+}
+
+fn json(value: &impl Serialize) -> Result<Vec<u8>, GraphError> {
+    Ok(sonic_rs::to_vec(value)?)
+}
+
+/// Handler of the `qdrant` route
+pub fn qdrant_read(input: HandlerInput) -> Result<Response, GraphError> {
+    let operation: Operation = sonic_rs::from_slice(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let txn = storage.read_txn()?;
+    let arena = Bump::new();
+    let existing = |collection: &str| {
+        collections::config(storage, &txn, collection)?.ok_or(GraphError::LabelNotFound)
+    };
+
+    let body = match &operation {
+        Operation::ListCollections => {
+            let names = collections::list(storage, &txn)?;
+            let collections: Vec<_> = names
+                .iter()
+                .map(|name| sonic_rs::json!({ "name": name }))
+                .collect();
+            json(&sonic_rs::json!({ "collections": collections }))?
+        }
+        Operation::GetCollection { collection } => {
+            let config = existing(collection)?;
+            let count = points::count(storage, &txn, collection, None)?;
+            json(&collection_info(&config, count, storage))?
+        }
+        Operation::CollectionExists { collection } => {
+            let exists = collections::config(storage, &txn, collection)?.is_some();
+            json(&sonic_rs::json!({ "exists": exists }))?
+        }
+        Operation::Retrieve {
+            collection,
+            ids,
+            with_payload,
+            with_vector,
+        } => {
+            existing(collection)?;
+            let records = points::retrieve(
+                storage,
+                &txn,
+                collection,
+                ids,
+                with_payload,
+                *with_vector,
+                &arena,
+            )?;
+            json(&records)?
+        }
+        Operation::Search { collection, search } => {
+            existing(collection)?;
+            json(&points::search(storage, &txn, collection, search, &arena)?)?
+        }
+        Operation::Query { collection, search } => {
+            existing(collection)?;
+            let found = points::search(storage, &txn, collection, search, &arena)?;
+            json(&sonic_rs::json!({ "points": found }))?
+        }
+        Operation::Scroll { collection, scroll } => {
+            existing(collection)?;
+            json(&points::scroll(storage, &txn, collection, scroll, &arena)?)?
+        }
+        Operation::Count { collection, filter } => {
+            existing(collection)?;
+            let count = points::count(storage, &txn, collection, filter.as_ref())?;
+            json(&sonic_rs::json!({ "count": count }))?
+        }
+        _ => {
+            return Err(GraphError::New(format!(
+                "Writes run on the `{QDRANT_WRITE_ROUTE}` route"
+            )));
+        }
+    };
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+/// Handler of the `qdrant_write` route
+pub fn qdrant_write(input: HandlerInput) -> Result<Response, GraphError> {
+    let operation: Operation = sonic_rs::from_slice(&input.request.body)?;
+    let storage = input.graph.storage.as_ref();
+    let mut txn = storage.graph_env.write_txn()?;
+    let completed = sonic_rs::json!({ "operation_id": 0, "status": "completed" });
+
+    let body = match &operation {
+        Operation::CreateCollection { collection, config } => {
+            if collections::config(storage, &txn, collection)?.is_some() {
+                return Err(GraphError::New(format!(
+                    "Collection `{collection}` already exists"
+                )));
+            }
+            collections::save_config(storage, &mut txn, collection, config)?;
+            json(&true)?
+        }
+        Operation::DeleteCollection { collection } => {
+            json(&collections::delete(storage, &mut txn, collection)?)?
+        }
+        Operation::Upsert { collection, points } => {
+            let config =
+                collections::config(storage, &txn, collection)?.ok_or(GraphError::LabelNotFound)?;
+            if let Some(point) = points.iter().find(|p| p.vector.len() != config.size) {
+                return Err(GraphError::New(dimension_error(&config, &point.vector)));
+            }
+            // Labels that weren't created keep the size of their first vector
+            if collections::stored_config(storage, &txn, collection)?.is_none() {
+                collections::save_config(storage, &mut txn, collection, &config)?;
+            }
+            points::upsert(storage, &mut txn, collection, points)?;
+            json(&completed)?
+        }
+        Operation::Delete {
+            collection,
+            ids,
+            filter,
+        } => {
+            if collections::config(storage, &txn, collection)?.is_none() {
+                return Err(GraphError::LabelNotFound);
+            }
+            points::delete(
+                storage,
+                &mut txn,
+                collection,
+                ids.as_deref(),
+                filter.as_ref(),
+            )?;
+            json(&completed)?
+        }
+        _ => {
+            return Err(GraphError::New(format!(
+                "Reads run on the `{QDRANT_READ_ROUTE}` route"
+            )));
+        }
+    };
+    storage.check_constraints(&txn)?;
+    storage.audit_write(&mut txn)?;
+    txn.commit()?;
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+fn dimension_error(config: &CollectionConfig, vector: &[f64]) -> String {
+    format!(
+        "Vector dimension error: expected dim: {}, got {}",
+        config.size,
+        vector.len()
+    )
+}
+
+/// Collection info in the shape Qdrant clients parse. Storage settings without a Helix
+/// equivalent report Qdrant's defaults.
+fn collection_info(
+    config: &CollectionConfig,
+    count: usize,
+    storage: &crate::helix_engine::storage_core::HelixGraphStorage,
+) -> sonic_rs::Value {
+    let params = sonic_rs::json!({ "size": config.size, "distance": "Cosine" });
+    let vectors = match &config.vector_name {
+        Some(name) => sonic_rs::to_value(&HashMap::from([(name, params)]))
+            .expect("named vector params should serialize"),
+        None => params,
+    };
+    let hnsw = &storage.vectors.config;
+    sonic_rs::json!({
+        "status": "green",
+        "optimizer_status": "ok",
+        "indexed_vectors_count": count,
+        "points_count": count,
+        "segments_count": 1,
+        "config": {
+            "params": {
+                "vectors": vectors,
+                "shard_number": 1,
+                "replication_factor": 1,
+                "write_consistency_factor": 1,
+                "on_disk_payload": true,
+            },
+            "hnsw_config": {
+                "m": hnsw.m,
+                "ef_construct": hnsw.ef_construct,
+                "full_scan_threshold": 10000,
+                "max_indexing_threads": 0,
+                "on_disk": false,
+            },
+            "optimizer_config": {
+                "deleted_threshold": 0.2,
+                "vacuum_min_vector_number": 1000,
+                "default_segment_number": 0,
+                "max_segment_size": null,
+                "memmap_threshold": null,
+                "indexing_threshold": 20000,
+                "flush_interval_sec": 5,
+                "max_optimization_threads": null,
+            },
+            "wal_config": { "wal_capacity_mb": 32, "wal_segments_ahead": 0 },
+            "quantization_config": null,
+        },
+        "payload_schema": {},
+    })
+}
+
+/// The caller and connection of an API call
+pub struct QdrantCall {
+    pub state: Arc<AppState>,
+    pub headers: HeaderMap,
+    pub caller: Option<Caller>,
+    pub addr: ClientAddr,
+}
+
+impl FromRequestParts<Arc<AppState>> for QdrantCall {
+    type Rejection = axum::http::Response<Body>;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let header = |name| parts.headers.get(name).and_then(|v| v.to_str().ok());
+
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let Some(api_key) = header("x-api-key").or(header(API_KEY_HEADER)) else {
+                return Err((StatusCode::BAD_REQUEST, "Missing x-api-key header").into_response());
+            };
+            if let Err(e) = verify_key(api_key) {
+                info!(?e, "Invalid API key");
+                return Err(e.into_response());
+            }
+        }
+
+        let token =
+            header(API_KEY_HEADER).or(header(AUTHORIZATION.as_str()).and_then(bearer_token));
+        let caller = authenticate(state, token)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let Ok(addr) = ClientAddr::from_request_parts(parts, state).await;
+        Ok(QdrantCall {
+            state: Arc::clone(state),
+            headers: parts.headers.clone(),
+            caller,
+            addr,
+        })
+    }
+}
+
+impl QdrantCall {
+    async fn run(self, operation: Result<Operation, QdrantError>) -> axum::http::Response<Body> {
+        self.run_inner(operation, None).await
+    }
+
+    /// Run a retrieve of one point, answering with the point rather than a list
+    async fn run_point(self, operation: Operation, id: PointId) -> axum::http::Response<Body> {
+        self.run_inner(Ok(operation), Some(id)).await
+    }
+
+    async fn run_inner(
+        self,
+        operation: Result<Operation, QdrantError>,
+        point: Option<PointId>,
+    ) -> axum::http::Response<Body> {
+        let start = Instant::now();
+        let operation = match operation.and_then(|op| self.check(op)) {
+            Ok(operation) => operation,
+            Err(e) => return e.into_response(start),
+        };
+        let is_write = operation.is_write();
+        let route = if is_write {
+            QDRANT_WRITE_ROUTE
+        } else {
+            QDRANT_READ_ROUTE
+        };
+
+        let api_key = {
+            #[cfg(feature = "api-key")]
+            {
+                let header = |name| self.headers.get(name).and_then(|v| v.to_str().ok());
+                header("x-api-key")
+                    .or(header(API_KEY_HEADER))
+                    .map(str::to_string)
+            }
+            #[cfg(not(feature = "api-key"))]
+            None::<String>
+        };
+        if let Some(caller) = &self.caller
+            && let Err(e) = caller.authorize(route, is_write, &[])
+        {
+            info!(caller = %caller.name(), route, error = %e, "Unauthorized Qdrant request");
+            return e.into_response();
+        }
+        let limiter = &self.state.rate_limiter;
+        if limiter.is_enabled() {
+            let client = limiter.client_id(self.caller.as_ref(), self.addr.0, &self.headers);
+            if let Err(e) = limiter.check(route, &client) {
+                info!(?client, route, "Rate limited");
+                return e.into_response();
+            }
+        }
+
+        let body = match sonic_rs::to_vec(&operation) {
+            Ok(body) => body,
+            Err(e) => return HelixError::from(GraphError::from(e)).into_response(),
+        };
+        let req = Request {
+            name: route.to_string(),
+            req_type: RequestType::Query,
+            api_key,
+            body: Bytes::from(body),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+        let span = info_span!("helix.request", route, otel.kind = "server");
+        otel::set_remote_parent(&span, &self.headers);
+        let res = self
+            .state
+            .worker_pool
+            .process_with(req, RequestHints::from_headers(&self.headers))
+            .instrument(span)
+            .await;
+        helix_metrics::prometheus::observe_request(route, res.is_ok(), start.elapsed());
+
+        let result = match res {
+            Ok(response) => response.body,
+            Err(e) => {
+                info!(route, error = ?e, "Error response");
+                let status = e.status();
+                let message = match (status, operation.existing_collection()) {
+                    (StatusCode::NOT_FOUND, Some(collection)) => {
+                        return QdrantError::collection_not_found(collection).into_response(start);
+                    }
+                    _ => e.to_string(),
+                };
+                return QdrantError { status, message }.into_response(start);
+            }
+        };
+        let result = match point {
+            Some(id) => match sonic_rs::from_slice::<Vec<sonic_rs::Value>>(&result) {
+                Ok(records) if !records.is_empty() => records[0].to_string().into_bytes(),
+                _ => {
+                    let message = format!("Not found: No point with id {id} found");
+                    let status = StatusCode::NOT_FOUND;
+                    return QdrantError { status, message }.into_response(start);
+                }
+            },
+            None => result,
+        };
+
+        let mut body = Vec::with_capacity(result.len() + 48);
+        body.extend_from_slice(b"{\"result\":");
+        body.extend_from_slice(&result);
+        let time = start.elapsed().as_secs_f64();
+        body.extend_from_slice(format!(",\"status\":\"ok\",\"time\":{time}}}").as_bytes());
+        json_response(StatusCode::OK, body)
+    }
+
+    /// Check the collection exists, or doesn't for a create, and the vectors have its size
+    fn check(&self, operation: Operation) -> Result<Operation, QdrantError> {
+        let storage = &self.state.worker_pool.graph().storage;
+        let internal = |e: GraphError| QdrantError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: e.to_string(),
+        };
+        let txn = storage
+            .graph_env
+            .read_txn()
+            .map_err(|e| internal(e.into()))?;
+        if let Operation::CreateCollection { collection, .. } = &operation {
+            if collections::config(storage, &txn, collection)
+                .map_err(internal)?
+                .is_some()
+            {
+                let message = format!("Collection `{collection}` already exists!");
+                return Err(QdrantError::bad_request(message));
+            }
+            return Ok(operation);
+        }
+        let Some(collection) = operation.existing_collection() else {
+            return Ok(operation);
+        };
+        let Some(config) = collections::config(storage, &txn, collection).map_err(internal)? else {
+            return Err(QdrantError::collection_not_found(collection));
+        };
+        if let Some(vector) = operation.vectors().iter().find(|v| v.len() != config.size) {
+            return Err(QdrantError::bad_request(dimension_error(&config, vector)));
+        }
+        if let Operation::Upsert { points, .. } = &operation {
+            let arena = bumpalo::Bump::new();
+            for point in points {
+                let label = storage
+                    .vectors
+                    .stored_label(&txn, point.id.as_u128(), &arena)
+                    .map_err(|e| internal(e.into()))?;
+                if let Some(label) = label
+                    && label != collection
+                {
+                    let message = format!("Point {} belongs to collection `{label}`", point.id);
+                    return Err(QdrantError::bad_request(message));
+                }
+            }
+        }
+        Ok(operation)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VectorsConfig {
+    Single(VectorParams),
+    Named(HashMap<String, VectorParams>),
+}
+
+#[derive(Deserialize)]
+struct VectorParams {
+    size: usize,
+    distance: String,
+}
+
+#[derive(Deserialize)]
+struct CreateCollection {
+    vectors: VectorsConfig,
+}
+
+/// A vector given either bare or as the collection's one named vector
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VectorInput {
+    Dense(Vec<f64>),
+    Named(HashMap<String, Vec<f64>>),
+    NamedStruct { vector: Vec<f64> },
+}
+
+impl VectorInput {
+    fn dense(self) -> Result<Vec<f64>, QdrantError> {
+        match self {
+            VectorInput::Dense(vector) | VectorInput::NamedStruct { vector } => Ok(vector),
+            VectorInput::Named(named) if named.len() == 1 => {
+                Ok(named.into_values().next().expect("map has one vector"))
+            }
+            VectorInput::Named(_) => Err(QdrantError::bad_request(
+                "Only collections with a single vector are supported",
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PointStruct {
+    id: PointId,
+    vector: VectorInput,
+    #[serde(default)]
+    payload: Option<JsonValue>,
+}
+
+#[derive(Deserialize)]
+struct Batch {
+    ids: Vec<PointId>,
+    vectors: Vec<VectorInput>,
+    #[serde(default)]
+    payloads: Option<Vec<Option<JsonValue>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Upsert {
+    Points { points: Vec<PointStruct> },
+    Batch { batch: Batch },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WithVector {
+    All(bool),
+    Names(Vec<String>),
+}
+
+impl WithVector {
+    fn requested(with_vector: Option<WithVector>) -> bool {
+        match with_vector {
+            Some(WithVector::All(all)) => all,
+            Some(WithVector::Names(names)) => !names.is_empty(),
+            None => false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Retrieve {
+    ids: Vec<PointId>,
+    with_payload: Option<WithPayload>,
+    with_vector: Option<WithVector>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DeleteSelector {
+    Points { points: Vec<PointId> },
+    Filter { filter: Box<Filter> },
+}
+
+#[derive(Deserialize)]
+struct SearchRequest {
+    vector: VectorInput,
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+    filter: Option<Filter>,
+    score_threshold: Option<f64>,
+    with_payload: Option<WithPayload>,
+    with_vector: Option<WithVector>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QueryInput {
+    Nearest { nearest: VectorInput },
+    Vector(VectorInput),
+}
+
+#[derive(Deserialize)]
+struct QueryRequest {
+    query: Option<QueryInput>,
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+    filter: Option<Filter>,
+    score_threshold: Option<f64>,
+    with_payload: Option<WithPayload>,
+    with_vector: Option<WithVector>,
+}
+
+#[derive(Deserialize, Default)]
+struct ScrollRequest {
+    offset: Option<PointId>,
+    limit: Option<usize>,
+    filter: Option<Filter>,
+    with_payload: Option<WithPayload>,
+    with_vector: Option<WithVector>,
+}
+
+#[derive(Deserialize, Default)]
+struct CountRequest {
+    filter: Option<Filter>,
+}
+
+/// Parse a request body, treating an empty one as `{}` for requests whose fields are optional
+fn parse<'a, T: Deserialize<'a> + Default>(body: &'a [u8]) -> Result<T, QdrantError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    Ok(sonic_rs::from_slice(body)?)
+}
+
+fn validate(filter: Option<Filter>) -> Result<Option<Filter>, QdrantError> {
+    if let Some(filter) = &filter {
+        filter.validate().map_err(QdrantError::bad_request)?;
+    }
+    Ok(filter)
+}
+
+fn point_id(id: &str) -> Result<PointId, QdrantError> {
+    match id.parse::<u64>() {
+        Ok(n) => Ok(PointId::Num(n)),
+        Err(_) => uuid::Uuid::parse_str(id)
+            .map(PointId::Uuid)
+            .map_err(|_| QdrantError::bad_request(format!("Invalid point id `{id}`"))),
+    }
+}
+
+pub async fn list_collections(call: QdrantCall) -> axum::http::Response<Body> {
+    call.run(Ok(Operation::ListCollections)).await
+}
+
+pub async fn get_collection(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+) -> axum::http::Response<Body> {
+    call.run(Ok(Operation::GetCollection { collection })).await
+}
+
+pub async fn collection_exists(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+) -> axum::http::Response<Body> {
+    call.run(Ok(Operation::CollectionExists { collection }))
+        .await
+}
+
+pub async fn create_collection(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = sonic_rs::from_slice::<CreateCollection>(&body)
+        .map_err(QdrantError::from)
+        .and_then(|request| {
+            let (vector_name, params) = match request.vectors {
+                VectorsConfig::Single(params) => (None, params),
+                VectorsConfig::Named(named) if named.len() == 1 => {
+                    let (name, params) = named.into_iter().next().expect("map has one vector");
+                    (Some(name), params)
+                }
+                VectorsConfig::Named(_) => {
+                    return Err(QdrantError::bad_request(
+                        "Only collections with a single vector are supported",
+                    ));
+                }
+            };
+            if !params.distance.eq_ignore_ascii_case("cosine") {
+                return Err(QdrantError::bad_request(format!(
+                    "Distance `{}` is not supported, only `Cosine` is",
+                    params.distance
+                )));
+            }
+            let config = CollectionConfig {
+                size: params.size,
+                vector_name,
+            };
+            Ok(Operation::CreateCollection { collection, config })
+        });
+    call.run(operation).await
+}
+
+pub async fn delete_collection(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+) -> axum::http::Response<Body> {
+    call.run(Ok(Operation::DeleteCollection { collection }))
+        .await
+}
+
+pub async fn upsert_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = (|| -> Result<Operation, QdrantError> {
+        let points = match sonic_rs::from_slice::<Upsert>(&body)? {
+            Upsert::Points { points } => points
+                .into_iter()
+                .map(|p| {
+                    Ok(NewPoint {
+                        id: p.id,
+                        vector: p.vector.dense()?,
+                        payload: p.payload,
+                    })
+                })
+                .collect::<Result<Vec<_>, QdrantError>>()?,
+            Upsert::Batch { batch } => {
+                if batch.ids.len() != batch.vectors.len() {
+                    return Err(QdrantError::bad_request(
+                        "Batch ids and vectors differ in length",
+                    ));
+                }
+                let mut payloads = batch.payloads.unwrap_or_default().into_iter();
+                batch
+                    .ids
+                    .into_iter()
+                    .zip(batch.vectors)
+                    .map(|(id, vector)| {
+                        Ok(NewPoint {
+                            id,
+                            vector: vector.dense()?,
+                            payload: payloads.next().flatten(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, QdrantError>>()?
+            }
+        };
+        Ok(Operation::Upsert { collection, points })
+    })();
+    call.run(operation).await
+}
+
+pub async fn retrieve_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = sonic_rs::from_slice::<Retrieve>(&body)
+        .map_err(QdrantError::from)
+        .map(|request| Operation::Retrieve {
+            collection,
+            ids: request.ids,
+            with_payload: request.with_payload.unwrap_or(WithPayload::All(true)),
+            with_vector: WithVector::requested(request.with_vector),
+        });
+    call.run(operation).await
+}
+
+pub async fn get_point(
+    call: QdrantCall,
+    Path((collection, id)): Path<(String, String)>,
+) -> axum::http::Response<Body> {
+    let id = match point_id(&id) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(Instant::now()),
+    };
+    let operation = Operation::Retrieve {
+        collection,
+        ids: vec![id],
+        with_payload: WithPayload::All(true),
+        with_vector: true,
+    };
+    call.run_point(operation, id).await
+}
+
+pub async fn delete_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = sonic_rs::from_slice::<DeleteSelector>(&body)
+        .map_err(QdrantError::from)
+        .and_then(|selector| {
+            Ok(match selector {
+                DeleteSelector::Points { points } => Operation::Delete {
+                    collection,
+                    ids: Some(points),
+                    filter: None,
+                },
+                DeleteSelector::Filter { filter } => Operation::Delete {
+                    collection,
+                    ids: None,
+                    filter: validate(Some(*filter))?,
+                },
+            })
+        });
+    call.run(operation).await
+}
+
+pub async fn search_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = (|| -> Result<Operation, QdrantError> {
+        let request: SearchRequest = sonic_rs::from_slice(&body)?;
+        let search = Search {
+            vector: request.vector.dense()?,
+            limit: request.limit.unwrap_or(DEFAULT_LIMIT),
+            offset: request.offset,
+            filter: validate(request.filter)?,
+            score_threshold: request.score_threshold,
+            with_payload: request.with_payload.unwrap_or(WithPayload::All(false)),
+            with_vector: WithVector::requested(request.with_vector),
+        };
+        Ok(Operation::Search { collection, search })
+    })();
+    call.run(operation).await
+}
+
+pub async fn query_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = (|| -> Result<Operation, QdrantError> {
+        let request: QueryRequest = sonic_rs::from_slice(&body)?;
+        let vector = match request.query {
+            Some(QueryInput::Nearest { nearest: vector } | QueryInput::Vector(vector)) => {
+                vector.dense()?
+            }
+            None => {
+                return Err(QdrantError::bad_request(
+                    "Only nearest queries are supported",
+                ));
+            }
+        };
+        let search = Search {
+            vector,
+            limit: request.limit.unwrap_or(DEFAULT_LIMIT),
+            offset: request.offset,
+            filter: validate(request.filter)?,
+            score_threshold: request.score_threshold,
+            with_payload: request.with_payload.unwrap_or(WithPayload::All(false)),
+            with_vector: WithVector::requested(request.with_vector),
+        };
+        Ok(Operation::Query { collection, search })
+    })();
+    call.run(operation).await
+}
+
+pub async fn scroll_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = parse::<ScrollRequest>(&body).and_then(|request| {
+        let scroll = Scroll {
+            offset: request.offset,
+            limit: request.limit.unwrap_or(DEFAULT_LIMIT),
+            filter: validate(request.filter)?,
+            with_payload: request.with_payload.unwrap_or(WithPayload::All(true)),
+            with_vector: WithVector::requested(request.with_vector),
+        };
+        Ok(Operation::Scroll { collection, scroll })
+    });
+    call.run(operation).await
+}
+
+pub async fn count_points(
+    call: QdrantCall,
+    Path(collection): Path<String>,
+    body: Bytes,
+) -> axum::http::Response<Body> {
+    let operation = parse::<CountRequest>(&body).and_then(|request| {
+        Ok(Operation::Count {
+            collection,
+            filter: validate(request.filter)?,
+        })
+    });
+    call.run(operation).await
+}
+
+/// Routes of the API, served from the root like Qdrant's
+pub fn router() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route("/collections", get(list_collections))
+        .route(
+            "/collections/{collection}",
+            get(get_collection)
+                .put(create_collection)
+                .delete(delete_collection),
+        )
+        .route("/collections/{collection}/exists", get(collection_exists))
+        .route(
+            "/collections/{collection}/points",
+            post(retrieve_points).put(upsert_points),
+        )
+        .route("/collections/{collection}/points/{id}", get(get_point))
+        .route(
+            "/collections/{collection}/points/delete",
+            post(delete_points),
+        )
+        .route(
+            "/collections/{collection}/points/search",
+            post(search_points),
+        )
+        .route("/collections/{collection}/points/query", post(query_points))
+        .route(
+            "/collections/{collection}/points/scroll",
+            post(scroll_points),
+        )
+        .route("/collections/{collection}/points/count", post(count_points))
+}
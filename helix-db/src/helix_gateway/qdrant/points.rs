@@ -0,0 +1,407 @@
+//! Points are the vectors of a collection's label, their payload the vector's properties.
+
+use std::collections::HashMap;
+
+use heed3::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::storage_core::{HelixGraphStorage, write_log};
+use crate::helix_engine::types::{GraphError, VectorError};
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::qdrant::collections;
+use crate::helix_gateway::qdrant::filter::Filter;
+use crate::protocol::value::Value;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+type NoFilter = fn(&HVector, &RoTxn) -> bool;
+
+/// Id of a point: an unsigned integer or a UUID, both stored as the vector's `u128` id.
+/// Ids that fit in 64 bits are answered as integers, others as UUIDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PointId {
+    Num(u64),
+    Uuid(uuid::Uuid),
+}
+
+impl PointId {
+    pub fn as_u128(&self) -> u128 {
+        match self {
+            PointId::Num(n) => *n as u128,
+            PointId::Uuid(uuid) => uuid.as_u128(),
+        }
+    }
+
+    pub fn from_u128(id: u128) -> Self {
+        match u64::try_from(id) {
+            Ok(n) => PointId::Num(n),
+            Err(_) => PointId::Uuid(uuid::Uuid::from_u128(id)),
+        }
+    }
+}
+
+impl std::fmt::Display for PointId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointId::Num(n) => write!(f, "{n}"),
+            PointId::Uuid(uuid) => write!(f, "{uuid}"),
+        }
+    }
+}
+
+/// Which payload fields to return
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WithPayload {
+    All(bool),
+    Keys(Vec<String>),
+    Include { include: Vec<String> },
+    Exclude { exclude: Vec<String> },
+}
+
+impl WithPayload {
+    fn select<'a>(&self, properties: Option<&ImmutablePropertiesMap<'a>>) -> Option<Payload<'a>> {
+        let fields = properties.into_iter().flat_map(|p| p.iter());
+        let fields: Payload = match self {
+            WithPayload::All(false) => return None,
+            WithPayload::All(true) => fields.collect(),
+            WithPayload::Keys(keys) | WithPayload::Include { include: keys } => fields
+                .filter(|(k, _)| keys.iter().any(|key| key == k))
+                .collect(),
+            WithPayload::Exclude { exclude } => fields
+                .filter(|(k, _)| !exclude.iter().any(|key| key == k))
+                .collect(),
+        };
+        Some(fields)
+    }
+}
+
+type Payload<'a> = HashMap<&'a str, &'a Value>;
+
+/// A point to upsert, its vector already checked against the collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPoint {
+    pub id: PointId,
+    pub vector: Vec<f64>,
+    #[serde(default)]
+    pub payload: Option<JsonValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub id: PointId,
+    pub payload: Option<Payload<'a>>,
+    pub vector: Option<&'a [f64]>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoredPoint<'a> {
+    pub id: PointId,
+    /// Points aren't versioned, so this is always 0
+    pub version: u64,
+    pub score: f64,
+    pub payload: Option<Payload<'a>>,
+    pub vector: Option<&'a [f64]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Search {
+    pub vector: Vec<f64>,
+    pub limit: usize,
+    pub offset: usize,
+    pub filter: Option<Filter>,
+    pub score_threshold: Option<f64>,
+    pub with_payload: WithPayload,
+    pub with_vector: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scroll {
+    pub offset: Option<PointId>,
+    pub limit: usize,
+    pub filter: Option<Filter>,
+    pub with_payload: WithPayload,
+    pub with_vector: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScrollPage<'a> {
+    pub points: Vec<Record<'a>>,
+    pub next_page_offset: Option<PointId>,
+}
+
+/// A payload value, read from any JSON including `null`s, which are kept as empty values
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct JsonValue(pub Value);
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JsonVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for JsonVisitor {
+            type Value = JsonValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a JSON value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::Boolean(v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::I64(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::U64(v)))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::F64(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::String(v.to_string())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::String(v)))
+            }
+
+            fn visit_unit<E>(self) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::Empty))
+            }
+
+            fn visit_none<E>(self) -> Result<JsonValue, E> {
+                Ok(JsonValue(Value::Empty))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<JsonValue, A::Error> {
+                let mut items = Vec::new();
+                while let Some(JsonValue(item)) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(JsonValue(Value::Array(items)))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<JsonValue, A::Error> {
+                let mut object = HashMap::new();
+                while let Some((key, JsonValue(value))) = map.next_entry::<String, JsonValue>()? {
+                    object.insert(key, value);
+                }
+                Ok(JsonValue(Value::Object(object)))
+            }
+        }
+
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+fn properties<'a>(
+    payload: Option<&JsonValue>,
+    arena: &'a bumpalo::Bump,
+) -> Option<ImmutablePropertiesMap<'a>> {
+    let Some(JsonValue(Value::Object(object))) = payload.cloned() else {
+        return None;
+    };
+    let len = object.len();
+    let items = object.into_iter().map(|(k, v)| (&*arena.alloc_str(&k), v));
+    Some(ImmutablePropertiesMap::new(len, items, arena))
+}
+
+/// Insert the points, replacing those with the same ids
+pub fn upsert<'db>(
+    storage: &'db HelixGraphStorage,
+    txn: &mut RwTxn<'db>,
+    collection: &str,
+    points: &[NewPoint],
+) -> Result<(), GraphError> {
+    let arena = bumpalo::Bump::new();
+    let label = arena.alloc_str(collection);
+    for point in points {
+        let data = arena.alloc_slice_copy(&point.vector);
+        let props = properties(point.payload.as_ref(), &arena);
+        let vector =
+            storage
+                .vectors
+                .insert_with_id(txn, point.id.as_u128(), label, data, props, &arena)?;
+        write_log::record(vector.id);
+    }
+    Ok(())
+}
+
+/// The points of the collection with the given ids, skipping missing ones
+pub fn retrieve<'a>(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    collection: &str,
+    ids: &[PointId],
+    with_payload: &WithPayload,
+    with_vector: bool,
+    arena: &'a bumpalo::Bump,
+) -> Result<Vec<Record<'a>>, GraphError> {
+    let mut records = Vec::with_capacity(ids.len());
+    for id in ids {
+        let vector = match storage.vectors.get_full_vector(txn, id.as_u128(), arena) {
+            Ok(vector) if vector.label == collection => vector,
+            Ok(_) | Err(VectorError::VectorNotFound(_) | VectorError::VectorDeleted) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        records.push(Record {
+            id: *id,
+            payload: with_payload.select(vector.properties.as_ref()),
+            vector: with_vector.then_some(vector.data),
+        });
+    }
+    Ok(records)
+}
+
+/// Delete the points with the given ids, or those matching a filter
+pub fn delete(
+    storage: &HelixGraphStorage,
+    txn: &mut RwTxn,
+    collection: &str,
+    ids: Option<&[PointId]>,
+    filter: Option<&Filter>,
+) -> Result<(), GraphError> {
+    let arena = bumpalo::Bump::new();
+    let ids: Vec<u128> = match ids {
+        Some(ids) => {
+            let mut live = Vec::with_capacity(ids.len());
+            for id in ids.iter().map(PointId::as_u128) {
+                match storage.vectors.get_vector_properties(txn, id, &arena) {
+                    Ok(Some(vector)) if vector.label == collection => live.push(id),
+                    Ok(_) | Err(VectorError::VectorDeleted) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            live
+        }
+        None => collections::live_vectors(storage, txn, collection, None, &arena)
+            .filter(|v| {
+                v.as_ref().map_or(true, |v| {
+                    filter.is_none_or(|f| f.matches(v.id, v.properties.as_ref()))
+                })
+            })
+            .map(|v| v.map(|v| v.id))
+            .collect::<Result<_, _>>()?,
+    };
+    for id in ids {
+        storage.vectors.delete(txn, id, &arena)?;
+        write_log::record(id);
+    }
+    Ok(())
+}
+
+/// Nearest points to a vector. With a filter, the candidates the index visits are filtered,
+/// so fewer than `limit` points may be found when few of them match.
+pub fn search<'a>(
+    storage: &'a HelixGraphStorage,
+    txn: &'a RoTxn,
+    collection: &str,
+    search: &Search,
+    arena: &'a bumpalo::Bump,
+) -> Result<Vec<ScoredPoint<'a>>, GraphError> {
+    let wanted = search.limit + search.offset;
+    let k = match search.filter {
+        Some(_) => wanted.max(storage.vectors.config.ef),
+        None => wanted,
+    };
+    let query = arena.alloc_slice_copy(&search.vector);
+    let label = arena.alloc_str(collection);
+    let found = match storage
+        .vectors
+        .search::<NoFilter>(txn, query, k, label, None, false, arena)
+    {
+        Ok(found) => found,
+        Err(VectorError::EntryPointNotFound) => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(found
+        .into_iter()
+        .filter(|v| {
+            search
+                .filter
+                .as_ref()
+                .is_none_or(|f| f.matches(v.id, v.properties.as_ref()))
+        })
+        .map(|v| ScoredPoint {
+            id: PointId::from_u128(v.id),
+            version: 0,
+            score: 1.0 - v.get_distance(),
+            payload: search.with_payload.select(v.properties.as_ref()),
+            vector: search.with_vector.then_some(v.data),
+        })
+        .filter(|p| search.score_threshold.is_none_or(|t| p.score >= t))
+        .skip(search.offset)
+        .take(search.limit)
+        .collect())
+}
+
+/// A page of points in id order
+pub fn scroll<'a>(
+    storage: &HelixGraphStorage,
+    txn: &'a RoTxn,
+    collection: &'a str,
+    scroll: &Scroll,
+    arena: &'a bumpalo::Bump,
+) -> Result<ScrollPage<'a>, GraphError> {
+    let from = scroll.offset.as_ref().map(PointId::as_u128);
+    let mut points = Vec::with_capacity(scroll.limit);
+    let mut next_page_offset = None;
+    for vector in collections::live_vectors(storage, txn, collection, from, arena) {
+        let vector = vector?;
+        let properties = vector.properties.as_ref();
+        if scroll
+            .filter
+            .as_ref()
+            .is_some_and(|f| !f.matches(vector.id, properties))
+        {
+            continue;
+        }
+        if points.len() == scroll.limit {
+            next_page_offset = Some(PointId::from_u128(vector.id));
+            break;
+        }
+        let data = match scroll.with_vector {
+            true => Some(storage.vectors.get_full_vector(txn, vector.id, arena)?.data),
+            false => None,
+        };
+        points.push(Record {
+            id: PointId::from_u128(vector.id),
+            payload: scroll.with_payload.select(properties),
+            vector: data,
+        });
+    }
+    Ok(ScrollPage {
+        points,
+        next_page_offset,
+    })
+}
+
+/// Number of points matching a filter
+pub fn count(
+    storage: &HelixGraphStorage,
+    txn: &RoTxn,
+    collection: &str,
+    filter: Option<&Filter>,
+) -> Result<usize, GraphError> {
+    let arena = bumpalo::Bump::new();
+    let mut count = 0;
+    for vector in collections::live_vectors(storage, txn, collection, None, &arena) {
+        let vector = vector?;
+        if filter.is_none_or(|f| f.matches(vector.id, vector.properties.as_ref())) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
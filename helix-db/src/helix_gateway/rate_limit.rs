@@ -0,0 +1,258 @@
+//! Token-bucket rate limiting of query requests per client.
+//!
+//! Every client gets a bucket for the global limit, shared by all routes without an override,
+//! and one per overridden route. Clients are identified by the API key or JWT subject they
+//! authenticated with, falling back to their IP address.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+#[cfg(feature = "gateway")]
+use axum::extract::{ConnectInfo, FromRequestParts};
+#[cfg(feature = "gateway")]
+use axum::http::{HeaderMap, request::Parts};
+#[cfg(feature = "gateway")]
+use std::{convert::Infallible, net::SocketAddr};
+
+use crate::helix_engine::traversal_core::config::{RateLimit, RateLimitConfig};
+#[cfg(feature = "gateway")]
+use crate::helix_gateway::auth::Caller;
+use crate::protocol::HelixError;
+
+/// Header a reverse proxy passes the original client address in
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Buckets kept before idle ones are dropped. A full bucket is indistinguishable from a
+/// missing one, so dropping those loses nothing.
+const MAX_TRACKED_BUCKETS: usize = 100_000;
+
+/// Who a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    ApiKey(String),
+    Subject(String),
+    Ip(IpAddr),
+    /// Neither authenticated nor connected over TCP; such requests share one bucket
+    Unknown,
+}
+
+/// Peer address of the connection a request arrived on, if known
+#[cfg(feature = "gateway")]
+pub struct ClientAddr(pub Option<IpAddr>);
+
+#[cfg(feature = "gateway")]
+impl<S: Send + Sync> FromRequestParts<S> for ClientAddr {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientAddr(
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    /// `None` for the global bucket
+    route: Option<String>,
+    client: ClientId,
+}
+
+pub(crate) struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    pub(crate) fn new(limit: RateLimit, now: Instant) -> Self {
+        Bucket {
+            limit,
+            tokens: limit.burst() as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.requests_per_sec() as f64)
+            .min(self.limit.burst() as f64);
+        self.updated = now;
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * self.limit.requests_per_sec() as f64 >= self.limit.burst() as f64
+    }
+
+    /// Take a token, or say how long until one is available
+    pub(crate) fn take(&mut self, now: Instant) -> Result<(), HelixError> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait = (1.0 - self.tokens) / self.limit.requests_per_sec() as f64;
+        Err(HelixError::RateLimited {
+            limit: self.limit.burst(),
+            retry_after_secs: (wait.ceil() as u64).max(1),
+        })
+    }
+}
+
+/// The rate limits of an instance and each client's remaining budget
+#[derive(Default)]
+pub struct RateLimiter {
+    limits: RwLock<Limits>,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+    rejected_total: AtomicU64,
+}
+
+#[derive(Default)]
+struct Limits {
+    global: Option<RateLimit>,
+    routes: HashMap<String, RateLimit>,
+    #[cfg_attr(not(feature = "gateway"), allow(dead_code))]
+    trust_forwarded_for: bool,
+}
+
+impl Limits {
+    fn from_config(config: Option<&RateLimitConfig>) -> Self {
+        let Some(config) = config else {
+            return Limits::default();
+        };
+        Limits {
+            global: config.global,
+            routes: config.routes.clone().unwrap_or_default(),
+            trust_forwarded_for: config.trust_forwarded_for(),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn from_config(config: Option<&RateLimitConfig>) -> Self {
+        RateLimiter {
+            limits: RwLock::new(Limits::from_config(config)),
+            ..RateLimiter::default()
+        }
+    }
+
+    /// Replace the limits. Every client starts again with a full bucket.
+    pub fn reload(&self, config: Option<&RateLimitConfig>) {
+        let mut limits = self.limits.write().expect("rate limits poisoned");
+        *limits = Limits::from_config(config);
+        self.buckets
+            .lock()
+            .expect("rate limit buckets poisoned")
+            .clear();
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        let limits = self.limits.read().expect("rate limits poisoned");
+        limits.global.is_some() || !limits.routes.is_empty()
+    }
+
+    /// Routes with their own limit
+    pub fn limited_routes(&self) -> Vec<String> {
+        let limits = self.limits.read().expect("rate limits poisoned");
+        limits.routes.keys().cloned().collect()
+    }
+
+    /// Identify the client of a request by its credentials, else by its address
+    #[cfg(feature = "gateway")]
+    pub fn client_id(
+        &self,
+        caller: Option<&Caller>,
+        addr: Option<IpAddr>,
+        headers: &HeaderMap,
+    ) -> ClientId {
+        match caller {
+            Some(Caller::ApiKey(key)) => return ClientId::ApiKey(key.name.clone()),
+            Some(Caller::Jwt(principal)) => {
+                if let Some(subject) = &principal.subject {
+                    return ClientId::Subject(subject.clone());
+                }
+            }
+            None => {}
+        }
+        let trust_forwarded_for = self
+            .limits
+            .read()
+            .expect("rate limits poisoned")
+            .trust_forwarded_for;
+        let forwarded = trust_forwarded_for
+            .then(|| forwarded_for(headers))
+            .flatten();
+        forwarded.or(addr).map_or(ClientId::Unknown, ClientId::Ip)
+    }
+
+    /// Take a token from the client's bucket for `route`
+    pub fn check(&self, route: &str, client: &ClientId) -> Result<(), HelixError> {
+        self.check_at(route, client, Instant::now())
+    }
+
+    pub(crate) fn check_at(
+        &self,
+        route: &str,
+        client: &ClientId,
+        now: Instant,
+    ) -> Result<(), HelixError> {
+        let limits = self.limits.read().expect("rate limits poisoned");
+        let (key, limit) = match limits.routes.get(route) {
+            Some(limit) => (
+                BucketKey {
+                    route: Some(route.to_string()),
+                    client: client.clone(),
+                },
+                *limit,
+            ),
+            None => match limits.global {
+                Some(limit) => (
+                    BucketKey {
+                        route: None,
+                        client: client.clone(),
+                    },
+                    limit,
+                ),
+                None => return Ok(()),
+            },
+        };
+
+        let mut buckets = self.buckets.lock().expect("rate limit buckets poisoned");
+        if buckets.len() >= MAX_TRACKED_BUCKETS && !buckets.contains_key(&key) {
+            buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+        let bucket = buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(limit, now));
+        bucket.take(now).inspect_err(|_| {
+            self.rejected_total.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+
+    /// Requests rejected since startup
+    pub fn rejected_total(&self) -> u64 {
+        self.rejected_total.load(Ordering::Relaxed)
+    }
+}
+
+/// The client address appended by the proxy in front of the gateway. Earlier entries come
+/// from the client and can't be trusted.
+#[cfg(feature = "gateway")]
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get(FORWARDED_FOR_HEADER)?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
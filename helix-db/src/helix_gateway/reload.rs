@@ -0,0 +1,195 @@
+//! Reloading the tunables of a running instance: what it logs, the slow query threshold, the
+//! size of the result cache and the rate limits. They're replaced through `POST /admin/reload`
+//! or, on unix, by sending the process SIGHUP, which re-reads them from the JSON file named by
+//! `HELIX_TUNABLES_FILE`. Everything else in the configuration still takes a restart.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use tracing_subscriber::filter::{ParseError, Targets};
+use tracing_subscriber::{Registry, reload};
+
+use crate::helix_engine::traversal_core::config::{GatewayConfig, RateLimitConfig};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::admin::{AdminAuth, AdminInfo};
+use crate::helix_gateway::gateway::AppState;
+
+/// Environment variable naming the JSON file of [`Tunables`] read on SIGHUP
+pub const TUNABLES_FILE_VAR: &str = "HELIX_TUNABLES_FILE";
+
+/// The gateway settings that take effect without a restart. Unset ones go back to their
+/// defaults, as they would on a restart with the same configuration.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Tunables {
+    pub log_level: Option<String>,
+    pub slow_query_ms: Option<u64>,
+    pub cache_max_entries: Option<usize>,
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+impl Tunables {
+    pub fn from_config(config: &GatewayConfig) -> Self {
+        Tunables {
+            log_level: config.log_level.clone(),
+            slow_query_ms: config.slow_query_ms,
+            cache_max_entries: config.cache_max_entries,
+            rate_limit: config.rate_limit.clone(),
+        }
+    }
+
+    /// Replace the tunables of `config` with these
+    pub fn apply_to(&self, config: &mut GatewayConfig) {
+        config.log_level = self.log_level.clone();
+        config.slow_query_ms = self.slow_query_ms;
+        config.cache_max_entries = self.cache_max_entries;
+        config.rate_limit = self.rate_limit.clone();
+    }
+}
+
+/// Filter of what the process logs, changeable while it runs
+#[derive(Clone)]
+pub struct LogLevel(reload::Handle<Targets, Registry>);
+
+impl LogLevel {
+    /// A filter layer to add first to the registry, logging what `directives` select, and
+    /// the handle changing it
+    pub fn layer(
+        directives: &str,
+    ) -> Result<(reload::Layer<Targets, Registry>, LogLevel), ParseError> {
+        let (layer, handle) = reload::Layer::new(directives.parse::<Targets>()?);
+        Ok((layer, LogLevel(handle)))
+    }
+
+    pub fn set(&self, directives: &str) -> Result<(), GraphError> {
+        let targets = directives
+            .parse::<Targets>()
+            .map_err(|e| GraphError::New(format!("invalid log level `{directives}`: {e}")))?;
+        self.0
+            .reload(targets)
+            .map_err(|e| GraphError::New(format!("could not change the log level: {e}")))
+    }
+}
+
+/// Applies reloaded tunables to the running instance
+pub struct Reloader {
+    /// `None` when the process logs through a subscriber the gateway can't change
+    log_level: Option<LogLevel>,
+    info: Arc<AdminInfo>,
+}
+
+impl Reloader {
+    pub fn new(log_level: Option<LogLevel>, info: Arc<AdminInfo>) -> Self {
+        Reloader { log_level, info }
+    }
+
+    /// Replace the tunables of the instance, answering with their effective values. Nothing
+    /// changes when the log level is invalid.
+    pub fn reload(&self, state: &AppState, tunables: &Tunables) -> Result<Tunables, GraphError> {
+        let mut config = GatewayConfig::default();
+        tunables.apply_to(&mut config);
+
+        match &self.log_level {
+            Some(log_level) => log_level.set(config.log_level())?,
+            None if tunables.log_level.is_some() => {
+                warn!("The log level can't be changed in this process, leaving it as it is")
+            }
+            None => {}
+        }
+
+        let worker_pool = &state.worker_pool;
+        worker_pool.set_slow_query_threshold(config.slow_query_threshold());
+        worker_pool.set_cache_max_entries(config.cache_max_entries());
+
+        state.rate_limiter.reload(config.rate_limit.as_ref());
+        let routes = worker_pool.routes();
+        for route in state.rate_limiter.limited_routes() {
+            if !routes.routes.contains_key(&route) {
+                warn!(
+                    route,
+                    "Rate limit configured for a route that does not exist"
+                );
+            }
+        }
+
+        let effective = Tunables {
+            log_level: Some(config.log_level().to_string()),
+            slow_query_ms: worker_pool
+                .slow_query_threshold()
+                .map(|threshold| threshold.as_millis() as u64),
+            cache_max_entries: Some(config.cache_max_entries()),
+            rate_limit: config.rate_limit,
+        };
+        self.info.set_tunables(&effective);
+        info!(tunables = ?effective, "Reloaded tunables");
+        Ok(effective)
+    }
+
+    /// Reload the tunables from the file named by `HELIX_TUNABLES_FILE` whenever the process
+    /// receives SIGHUP
+    #[cfg(unix)]
+    pub async fn reload_on_sighup(self: Arc<Self>, state: Arc<AppState>) {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                warn!(
+                    ?e,
+                    "Could not install the SIGHUP handler, tunables reload only over HTTP"
+                );
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            let Some(path) = std::env::var_os(TUNABLES_FILE_VAR).map(PathBuf::from) else {
+                warn!("Received SIGHUP but {TUNABLES_FILE_VAR} is not set, nothing to reload");
+                continue;
+            };
+            let reloaded = read_tunables(&path).and_then(|tunables| self.reload(&state, &tunables));
+            if let Err(e) = reloaded {
+                warn!(path = %path.display(), error = %e, "Failed to reload tunables on SIGHUP");
+            }
+        }
+    }
+}
+
+/// Tunables from a JSON file
+pub fn read_tunables(path: &Path) -> Result<Tunables, GraphError> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| GraphError::New(format!("could not read {}: {e}", path.display())))?;
+    sonic_rs::from_slice(&bytes)
+        .map_err(|e| GraphError::New(format!("invalid tunables in {}: {e}", path.display())))
+}
+
+/// Replace the tunables with those in the JSON body, answering with their effective values
+pub async fn admin_reload_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Extension(reloader): Extension<Arc<Reloader>>,
+    body: Bytes,
+) -> Response {
+    let tunables: Tunables = match body.is_empty() {
+        true => Tunables::default(),
+        false => match sonic_rs::from_slice(&body) {
+            Ok(tunables) => tunables,
+            Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        },
+    };
+    match reloader.reload(&state, &tunables) {
+        Ok(effective) => match sonic_rs::to_vec(&effective) {
+            Ok(body) => Response::builder()
+                .header("Content-Type", "application/json")
+                .body(Body::from(body))
+                .expect("should be able to make response from tunables"),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,372 @@
+//! Warm standbys of a leader, for basic failover of self-hosted instances.
+//!
+//! A leader serves a consistent, compacted copy of its LMDB environment at
+//! `/admin/replication/snapshot`, tagged with the id of the last transaction it holds. An
+//! instance configured with `GatewayConfig.standby` asks its leader for a copy every
+//! `sync_interval_secs`, is answered 304 while nothing was committed since the copy it has, and
+//! keeps the latest copy in `standby/` next to its own data. A standby refuses queries with
+//! R505 and fails `/readyz`, so load balancers leave it out.
+//!
+//! Copies aren't incremental: every sync after a commit copies the whole compacted environment,
+//! costing the leader a read transaction held for the copy, the disk IO of writing it to a
+//! temporary file in `HELIX_SNAPSHOT_DIR` (the system temp dir by default, outside the data
+//! directory) and the bandwidth of sending it. The sync interval bounds how often that happens.
+//!
+//! Restarting a standby without the standby config promotes it: before the environment opens,
+//! the last copy replaces its data (see [`promote_synced_snapshot`]). Every instance answers
+//! `/leader` with its role, so clients can find where to send requests after a failover.
+
+use std::fs;
+use std::io::{self, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Query, State};
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Router};
+use futures_util::stream;
+use heed3::CompactionOption;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+
+use crate::helix_engine::traversal_core::config::StandbyConfig;
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::admin::AdminAuth;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::health::healthz_handler;
+use crate::protocol::HelixError;
+
+/// Route a leader serves copies of its data at
+pub const SNAPSHOT_ROUTE: &str = "/admin/replication/snapshot";
+/// Header carrying the id of the last transaction a copy holds
+pub const POSITION_HEADER: &str = "x-helix-position";
+/// Variable holding the API key a standby sends to its leader, when the leader needs one
+pub const LEADER_API_KEY_VAR: &str = "HELIX_LEADER_API_KEY";
+/// Variable naming the directory a leader writes copies to before sending them
+pub const SNAPSHOT_DIR_VAR: &str = "HELIX_SNAPSHOT_DIR";
+
+/// Directory next to the environment's data a standby keeps its copy in
+const STANDBY_DIR: &str = "standby";
+const DATA_FILE: &str = "data.mdb";
+const PARTIAL_FILE: &str = "data.mdb.partial";
+const POSITION_FILE: &str = "position";
+/// Data an instance held before its last promotion, kept until the next one
+const PRE_PROMOTION_FILE: &str = "data.mdb.pre-promotion";
+/// Bytes of a copy read per chunk of the response
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Longest a request to the leader may take, including the whole copy
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// What an instance answers at `/leader`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum Role {
+    /// Serves queries; `position` is the id of its last committed transaction
+    Leader { position: u64 },
+    /// Copies `leader`'s data and refuses queries. `position` is that of the last copy it
+    /// synced, and `error` why its last sync failed, if it did.
+    Standby {
+        leader: String,
+        position: Option<u64>,
+        synced_at_ms: Option<u64>,
+        error: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+pub struct SnapshotParams {
+    /// Position of the copy the caller has; answered with 304 if nothing was committed since
+    after: Option<u64>,
+}
+
+/// Copy of the environment as of now, or 304 when it's still at the caller's position
+pub async fn snapshot_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SnapshotParams>,
+) -> Result<Response, HelixError> {
+    let env = state.worker_pool.graph().storage.graph_env.clone();
+    // Read before copying, so the copy holds at least this transaction
+    let position = env.info().last_txn_id as u64;
+    if params.after == Some(position) {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(POSITION_HEADER, position.to_string())],
+        )
+            .into_response());
+    }
+
+    let file = tokio::task::spawn_blocking(move || -> Result<fs::File, GraphError> {
+        // Unnamed, so the copy is removed once the response is sent or dropped, and outside the
+        // data directory, so copies don't take up the space the data needs
+        let mut file = tempfile::tempfile_in(snapshot_dir())?;
+        env.copy_to_file(&mut file, CompactionOption::Enabled)?;
+        file.rewind()?;
+        Ok(file)
+    })
+    .await
+    .map_err(|e| GraphError::New(format!("Snapshot task failed: {e}")))??;
+    let len = file.metadata().map_err(GraphError::from)?.len();
+    info!(position, bytes = len, "Serving a snapshot to a standby");
+
+    let chunks = stream::unfold(Some(tokio::fs::File::from_std(file)), |file| async move {
+        let mut file = file?;
+        let mut buf = vec![0; CHUNK_SIZE];
+        match file.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(Bytes::from(buf)), Some(file)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/octet-stream")
+        .header(CONTENT_LENGTH, len)
+        .header(POSITION_HEADER, position)
+        .body(Body::from_stream(chunks))
+        .expect("should be able to make response from snapshot"))
+}
+
+/// Directory a leader writes copies to before sending them
+fn snapshot_dir() -> PathBuf {
+    std::env::var_os(SNAPSHOT_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// The role of a leader
+pub async fn leader_handler(State(state): State<Arc<AppState>>) -> Response {
+    let position = state
+        .worker_pool
+        .graph()
+        .storage
+        .graph_env
+        .info()
+        .last_txn_id as u64;
+    json_response(&Role::Leader { position })
+}
+
+/// The role of a standby, with where its leader is
+async fn standby_leader_handler(Extension(status): Extension<Arc<StandbyStatus>>) -> Response {
+    json_response(&status.role())
+}
+
+/// Routes a standby serves instead of the gateway's: `/healthz`, `/leader`, and R505 for
+/// everything else, `/readyz` included
+pub fn standby_routes(status: Arc<StandbyStatus>) -> Router<Arc<AppState>> {
+    let leader = status.leader.clone();
+    Router::new()
+        .route("/healthz", get(healthz_handler))
+        .route("/leader", get(standby_leader_handler))
+        .fallback(move || {
+            let leader = leader.clone();
+            async move { HelixError::Standby { leader }.into_response() }
+        })
+        .layer(Extension(status))
+}
+
+fn json_response(role: &Role) -> Response {
+    match sonic_rs::to_vec(role) {
+        Ok(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize role",
+        )
+            .into_response(),
+    }
+}
+
+/// How far a standby is, shared between its sync task and `/leader`
+pub struct StandbyStatus {
+    leader: String,
+    synced: Mutex<Synced>,
+}
+
+#[derive(Default)]
+struct Synced {
+    position: Option<u64>,
+    synced_at_ms: Option<u64>,
+    error: Option<String>,
+}
+
+impl StandbyStatus {
+    pub fn role(&self) -> Role {
+        let synced = self.synced.lock().unwrap();
+        Role::Standby {
+            leader: self.leader.clone(),
+            position: synced.position,
+            synced_at_ms: synced.synced_at_ms,
+            error: synced.error.clone(),
+        }
+    }
+
+    pub fn position(&self) -> Option<u64> {
+        self.synced.lock().unwrap().position
+    }
+
+    fn record(&self, position: Option<u64>, error: Option<String>) {
+        let mut synced = self.synced.lock().unwrap();
+        if error.is_none() {
+            synced.synced_at_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_millis() as u64);
+        }
+        if position.is_some() {
+            synced.position = position;
+        }
+        synced.error = error;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SyncError {
+    #[error("request to the leader failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("leader answered {0}")]
+    Status(reqwest::StatusCode),
+    #[error("leader sent a snapshot without a valid `{POSITION_HEADER}` header")]
+    MissingPosition,
+    #[error("could not store the snapshot: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Keeps a copy of the leader's data next to the environment at `data_dir`
+pub struct Standby {
+    base_url: String,
+    interval: Duration,
+    dir: PathBuf,
+    api_key: Option<String>,
+    client: reqwest::Client,
+    status: Arc<StandbyStatus>,
+}
+
+impl Standby {
+    pub fn new(config: &StandbyConfig, data_dir: &Path) -> Self {
+        let dir = data_dir.join(STANDBY_DIR);
+        // Resume from the copy synced before a restart rather than fetching it again
+        let position = fs::read_to_string(dir.join(POSITION_FILE))
+            .ok()
+            .and_then(|p| p.trim().parse().ok())
+            .filter(|_| dir.join(DATA_FILE).exists());
+        let status = Arc::new(StandbyStatus {
+            leader: config.leader.clone(),
+            synced: Mutex::new(Synced {
+                position,
+                ..Default::default()
+            }),
+        });
+        Standby {
+            base_url: config.leader.trim_end_matches('/').to_string(),
+            interval: config.sync_interval(),
+            dir,
+            api_key: std::env::var(LEADER_API_KEY_VAR).ok(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("reqwest client should build"),
+            status,
+        }
+    }
+
+    pub fn status(&self) -> Arc<StandbyStatus> {
+        Arc::clone(&self.status)
+    }
+
+    pub fn leader(&self) -> &str {
+        &self.status.leader
+    }
+
+    /// Sync from the leader every `sync_interval`, until the gateway shuts down
+    pub async fn run(self) {
+        loop {
+            match self.sync().await {
+                Ok(Some(position)) => info!(position, "Synced a snapshot from the leader"),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(leader = %self.base_url, error = %e, "Standby sync failed");
+                    self.status.record(None, Some(e.to_string()));
+                }
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    /// Fetch a copy of the leader's data if it committed since the last one, returning the
+    /// position of the new copy
+    pub async fn sync(&self) -> Result<Option<u64>, SyncError> {
+        let mut req = self
+            .client
+            .get(format!("{}{SNAPSHOT_ROUTE}", self.base_url));
+        if let Some(position) = self.status.position() {
+            req = req.query(&[("after", position)]);
+        }
+        if let Some(key) = &self.api_key {
+            req = req.header("x-api-key", key).bearer_auth(key);
+        }
+        let mut res = req.send().await?;
+        if res.status() == reqwest::StatusCode::NOT_MODIFIED {
+            self.status.record(None, None);
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(SyncError::Status(res.status()));
+        }
+        let position = parse_position(res.headers()).ok_or(SyncError::MissingPosition)?;
+
+        // Written aside and renamed, so a promotion never finds half a copy
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let partial = self.dir.join(PARTIAL_FILE);
+        let mut file = tokio::fs::File::create(&partial).await?;
+        while let Some(chunk) = res.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&partial, self.dir.join(DATA_FILE)).await?;
+        tokio::fs::write(self.dir.join(POSITION_FILE), position.to_string()).await?;
+
+        self.status.record(Some(position), None);
+        Ok(Some(position))
+    }
+}
+
+fn parse_position(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(POSITION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// Replace the data of the environment at `path` with the last copy a standby synced into it,
+/// keeping the replaced data as `data.mdb.pre-promotion`. Must run before the environment is
+/// opened. Returns whether there was a copy to promote.
+pub fn promote_synced_snapshot(path: &Path) -> io::Result<bool> {
+    let dir = path.join(STANDBY_DIR);
+    let snapshot = dir.join(DATA_FILE);
+    if !snapshot.exists() {
+        return Ok(false);
+    }
+    let position = fs::read_to_string(dir.join(POSITION_FILE)).unwrap_or_default();
+
+    let data = path.join(DATA_FILE);
+    if data.exists() {
+        fs::rename(&data, path.join(PRE_PROMOTION_FILE))?;
+    }
+    fs::rename(&snapshot, &data)?;
+    fs::remove_dir_all(&dir)?;
+    info!(
+        position = position.trim(),
+        "Promoted to leader with the last snapshot synced as a standby"
+    );
+    Ok(true)
+}
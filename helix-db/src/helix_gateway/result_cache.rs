@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+
+use crate::protocol::{Format, Response};
+
+/// Cache of read route responses keyed on route name and request body.
+///
+/// Entries are invalidated by label: every committed write bumps an epoch for each label it
+/// touches, and a cached entry is only served while the epochs it was computed under are
+/// unchanged. Writes whose labels are unknown invalidate every entry.
+pub struct ResultCache {
+    max_entries: AtomicUsize,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    label_epochs: HashMap<String, u64>,
+    /// Bumped by writes with unknown labels
+    global_epoch: u64,
+    /// Bumped by every write, for cached routes with unknown labels
+    write_epoch: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    route: String,
+    body: Bytes,
+    out_fmt: Format,
+}
+
+struct CacheEntry {
+    body: Vec<u8>,
+    fmt: Format,
+    expires_at: Instant,
+    epochs: Epochs,
+}
+
+/// The epochs a read observed before it executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epochs {
+    global: u64,
+    labels: LabelEpochs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LabelEpochs {
+    /// Epoch of each label the route touches
+    Known(Vec<u64>),
+    /// The route's labels are unknown, so any write invalidates it
+    Unknown(u64),
+}
+
+impl CacheState {
+    fn epochs(&self, labels: Option<&[String]>) -> Epochs {
+        Epochs {
+            global: self.global_epoch,
+            labels: match labels {
+                Some(labels) => LabelEpochs::Known(
+                    labels
+                        .iter()
+                        .map(|l| self.label_epochs.get(l).copied().unwrap_or(0))
+                        .collect(),
+                ),
+                None => LabelEpochs::Unknown(self.write_epoch),
+            },
+        }
+    }
+}
+
+impl ResultCache {
+    pub fn new(max_entries: usize) -> Self {
+        ResultCache {
+            max_entries: AtomicUsize::new(max_entries),
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_entries() > 0
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries.load(Ordering::Relaxed)
+    }
+
+    /// Resize the cache, dropping every entry when it shrinks. 0 disables it.
+    pub fn set_max_entries(&self, max_entries: usize) {
+        let previous = self.max_entries.swap(max_entries, Ordering::Relaxed);
+        if max_entries < previous {
+            self.state().entries.clear();
+        }
+    }
+
+    /// Look up a fresh response for the request
+    pub fn get(
+        &self,
+        route: &str,
+        body: &Bytes,
+        out_fmt: Format,
+        labels: Option<&[String]>,
+    ) -> Option<Response> {
+        let key = CacheKey {
+            route: route.to_string(),
+            body: body.clone(),
+            out_fmt,
+        };
+        let mut state = self.state();
+        let current = state.epochs(labels);
+        let hit = match state.entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() && entry.epochs == current => {
+                Some(Response {
+                    body: entry.body.clone(),
+                    fmt: entry.fmt,
+                })
+            }
+            Some(_) => {
+                state.entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+        drop(state);
+
+        match hit {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        hit
+    }
+
+    /// Epochs to record with a result. Must be taken before the read executes so a write
+    /// committing mid-read leaves the stored entry stale.
+    pub fn epochs(&self, labels: Option<&[String]>) -> Epochs {
+        self.state().epochs(labels)
+    }
+
+    /// Store a response computed under `epochs`
+    pub fn insert(
+        &self,
+        route: &str,
+        body: Bytes,
+        out_fmt: Format,
+        response: &Response,
+        ttl: Duration,
+        epochs: Epochs,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = Instant::now();
+        let max_entries = self.max_entries();
+        let mut state = self.state();
+        if state.entries.len() >= max_entries {
+            state.entries.retain(|_, entry| entry.expires_at > now);
+            if state.entries.len() >= max_entries {
+                return;
+            }
+        }
+        state.entries.insert(
+            CacheKey {
+                route: route.to_string(),
+                body,
+                out_fmt,
+            },
+            CacheEntry {
+                body: response.body.clone(),
+                fmt: response.fmt,
+                expires_at: now + ttl,
+                epochs,
+            },
+        );
+    }
+
+    /// Invalidate entries that depend on `labels` after a write commits.
+    /// `None` invalidates everything.
+    pub fn invalidate(&self, labels: Option<&[String]>) {
+        let mut state = self.state();
+        state.write_epoch += 1;
+        match labels {
+            Some(labels) => {
+                for label in labels {
+                    *state.label_epochs.entry(label.clone()).or_default() += 1;
+                }
+            }
+            None => {
+                state.global_epoch += 1;
+                state.entries.clear();
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
@@ -10,10 +10,18 @@
 use crate::{
     helix_engine::{traversal_core::HelixGraphEngine, types::GraphError},
     helix_gateway::mcp::mcp::MCPHandlerFn,
-    protocol::request::RetChan,
+    protocol::request::{Priority, RequestType, RetChan},
 };
 use core::fmt;
-use std::{collections::HashMap, fmt::Debug, future::Future, pin::Pin, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
 
 use crate::protocol::{Request, Response};
 
@@ -61,6 +69,15 @@ pub struct Handler {
     pub name: &'static str,
     pub func: BasicHandlerFn,
     pub is_write: bool,
+    pub priority: Priority,
+    /// How long successful responses of this read route may be served from the result cache
+    pub cache_ttl_ms: Option<u64>,
+    /// Node, edge and vector labels the route reads or writes. `None` means unknown.
+    pub labels: Option<&'static [&'static str]>,
+    /// Roles a JWT caller needs at least one of. Empty means any authenticated caller.
+    pub roles: &'static [&'static str],
+    /// Most requests of this route run at once; later ones wait for one to finish
+    pub max_concurrency: Option<usize>,
 }
 
 impl Handler {
@@ -69,12 +86,63 @@ impl Handler {
             name,
             func,
             is_write,
+            priority: Priority::Interactive,
+            cache_ttl_ms: None,
+            labels: None,
+            roles: &[],
+            max_concurrency: None,
         }
     }
+
+    /// Set the default scheduling lane for this route
+    pub const fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Cache successful responses of this route for `ttl_ms` milliseconds
+    pub const fn with_cache_ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.cache_ttl_ms = Some(ttl_ms);
+        self
+    }
+
+    /// Declare the labels this route touches, used to invalidate cached results
+    pub const fn with_labels(mut self, labels: &'static [&'static str]) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Require JWT callers to hold one of `roles`
+    pub const fn with_roles(mut self, roles: &'static [&'static str]) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    /// Run at most `limit` requests of this route at once
+    pub const fn with_max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = Some(limit);
+        self
+    }
 }
 
 inventory::collect!(HandlerSubmission);
 
+/// How many requests of a route may run at once, and the permits they hold while running
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    pub limit: usize,
+    pub permits: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            permits: Arc::new(Semaphore::new(limit)),
+        }
+    }
+}
+
 /// Router for handling requests and MCP requests
 ///
 /// Standard Routes and MCP Routes are stored in a HashMap with the method and path as the key
@@ -84,6 +152,20 @@ pub struct HelixRouter {
     pub mcp_routes: HashMap<String, MCPHandlerFn>,
     /// Set of route names that perform write operations
     pub write_routes: std::collections::HashSet<String>,
+    /// Set of read routes scheduled on the batch lane unless the request says otherwise
+    pub batch_routes: std::collections::HashSet<String>,
+    /// Read routes whose results are cached, with their time to live
+    pub cache_routes: HashMap<String, Duration>,
+    /// Labels touched by each route. Writes without an entry invalidate the whole cache.
+    pub route_labels: HashMap<String, Vec<String>>,
+    /// Roles required by each route. Routes without an entry are open to any caller.
+    pub route_roles: HashMap<String, Vec<String>>,
+    /// Permits of routes limited with `#[max_concurrency(...)]`, one held by each running request
+    pub concurrency_limits: HashMap<String, ConcurrencyLimit>,
+    /// MCP tools that perform write operations
+    pub mcp_write_routes: std::collections::HashSet<String>,
+    /// Roles required by each MCP tool. Tools without an entry are open to any caller.
+    pub mcp_route_roles: HashMap<String, Vec<String>>,
 }
 
 impl HelixRouter {
@@ -100,6 +182,13 @@ impl HelixRouter {
             routes: rts,
             mcp_routes: mcp_rts,
             write_routes: write_rts,
+            batch_routes: Default::default(),
+            cache_routes: Default::default(),
+            route_labels: Default::default(),
+            route_roles: Default::default(),
+            concurrency_limits: Default::default(),
+            mcp_write_routes: Default::default(),
+            mcp_route_roles: Default::default(),
         }
     }
 
@@ -108,6 +197,52 @@ impl HelixRouter {
         self.write_routes.contains(name)
     }
 
+    /// Default scheduling lane for a route
+    pub fn route_priority(&self, name: &str) -> Priority {
+        if self.batch_routes.contains(name) {
+            Priority::Batch
+        } else {
+            Priority::Interactive
+        }
+    }
+
+    /// Time to live for cached results of a route, if it is cached
+    pub fn cache_ttl(&self, name: &str) -> Option<Duration> {
+        self.cache_routes.get(name).copied()
+    }
+
+    /// Labels a route touches, if known
+    pub fn route_labels(&self, name: &str) -> Option<&[String]> {
+        self.route_labels.get(name).map(Vec::as_slice)
+    }
+
+    /// Roles a caller needs one of to call a route; empty if the route declares none
+    pub fn route_roles(&self, name: &str) -> &[String] {
+        self.route_roles.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Permits of a route that limits how many of its requests run at once
+    pub fn concurrency_limit(&self, name: &str) -> Option<&ConcurrencyLimit> {
+        self.concurrency_limits.get(name)
+    }
+
+    /// Check if a request writes, whether it calls a query or an MCP tool
+    pub fn is_write_request(&self, req_type: RequestType, name: &str) -> bool {
+        match req_type {
+            RequestType::Query => self.is_write_route(name),
+            RequestType::MCP => self.mcp_write_routes.contains(name),
+        }
+    }
+
+    /// Roles a caller needs one of to make a request; empty if the route or tool declares none
+    pub fn request_roles(&self, req_type: RequestType, name: &str) -> &[String] {
+        let roles = match req_type {
+            RequestType::Query => &self.route_roles,
+            RequestType::MCP => &self.mcp_route_roles,
+        };
+        roles.get(name).map_or(&[], Vec::as_slice)
+    }
+
     /// Add a route to the router
     pub fn add_route(&mut self, name: &str, handler: BasicHandlerFn, is_write: bool) {
         self.routes.insert(name.to_string(), Arc::new(handler));
@@ -117,6 +252,27 @@ impl HelixRouter {
     }
 }
 
+/// The router workers dispatch through, replaceable while the pool is running.
+/// Requests load the current router once, so a request already dispatched finishes on the
+/// router it started with.
+pub struct Routes(RwLock<Arc<HelixRouter>>);
+
+impl Routes {
+    pub fn new(router: Arc<HelixRouter>) -> Self {
+        Routes(RwLock::new(router))
+    }
+
+    /// The router new requests are dispatched through
+    pub fn load(&self) -> Arc<HelixRouter> {
+        Arc::clone(&self.0.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Dispatch new requests through `router`
+    pub fn store(&self, router: Arc<HelixRouter>) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = router;
+    }
+}
+
 #[derive(Debug)]
 pub enum RouterError {
     Io(std::io::Error),
@@ -226,6 +382,29 @@ mod tests {
         assert!(!router.is_write_route("nonexistent"));
     }
 
+    #[test]
+    fn test_router_mcp_tools_are_checked_separately() {
+        let mut router = HelixRouter::new(None, None, Some(HashSet::from(["addUser".to_string()])));
+        router.mcp_write_routes.insert("addUserMcp".to_string());
+        router
+            .mcp_route_roles
+            .insert("addUserMcp".to_string(), vec!["editor".to_string()]);
+
+        assert!(router.is_write_request(RequestType::Query, "addUser"));
+        assert!(!router.is_write_request(RequestType::MCP, "addUser"));
+        assert!(router.is_write_request(RequestType::MCP, "addUserMcp"));
+        assert!(!router.is_write_request(RequestType::Query, "addUserMcp"));
+        assert_eq!(
+            router.request_roles(RequestType::MCP, "addUserMcp"),
+            ["editor".to_string()]
+        );
+        assert!(
+            router
+                .request_roles(RequestType::Query, "addUserMcp")
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_router_add_route_basic() {
         let mut router = HelixRouter::new(None, None, None);
@@ -264,6 +443,97 @@ mod tests {
         assert!(router.is_write_route("test"));
     }
 
+    #[test]
+    fn test_router_route_priority() {
+        let mut router = HelixRouter::new(None, None, None);
+        router.add_route("lookup", dummy_handler, false);
+        router.add_route("report", another_handler, false);
+        router.batch_routes.insert("report".to_string());
+
+        assert_eq!(router.route_priority("lookup"), Priority::Interactive);
+        assert_eq!(router.route_priority("report"), Priority::Batch);
+        assert_eq!(router.route_priority("missing"), Priority::Interactive);
+    }
+
+    #[test]
+    fn test_handler_with_priority() {
+        let handler = Handler::new("report", dummy_handler, false);
+        assert_eq!(handler.priority, Priority::Interactive);
+
+        let handler = handler.with_priority(Priority::Batch);
+        assert_eq!(handler.priority, Priority::Batch);
+    }
+
+    #[test]
+    fn test_handler_with_cache_and_labels() {
+        const HANDLER: Handler = Handler::new("dashboard", dummy_handler, false)
+            .with_cache_ttl_ms(30_000)
+            .with_labels(&["User", "Follows"]);
+
+        assert_eq!(HANDLER.cache_ttl_ms, Some(30_000));
+        assert_eq!(HANDLER.labels, Some(&["User", "Follows"][..]));
+    }
+
+    #[test]
+    fn test_handler_with_roles() {
+        const HANDLER: Handler =
+            Handler::new("delete_user", dummy_handler, true).with_roles(&["admin", "support"]);
+
+        assert_eq!(HANDLER.roles, &["admin", "support"]);
+        assert!(
+            Handler::new("get_user", dummy_handler, false)
+                .roles
+                .is_empty()
+        );
+
+        let mut router = HelixRouter::new(None, None, None);
+        router
+            .route_roles
+            .insert("delete_user".to_string(), vec!["admin".to_string()]);
+        assert_eq!(router.route_roles("delete_user"), &["admin".to_string()]);
+        assert!(router.route_roles("get_user").is_empty());
+    }
+
+    #[test]
+    fn test_handler_with_max_concurrency() {
+        const HANDLER: Handler =
+            Handler::new("report", dummy_handler, false).with_max_concurrency(2);
+
+        assert_eq!(HANDLER.max_concurrency, Some(2));
+        assert_eq!(
+            Handler::new("get_user", dummy_handler, false).max_concurrency,
+            None
+        );
+
+        let mut router = HelixRouter::new(None, None, None);
+        router
+            .concurrency_limits
+            .insert("report".to_string(), ConcurrencyLimit::new(2));
+        let limit = router.concurrency_limit("report").unwrap();
+        assert_eq!(limit.limit, 2);
+        assert_eq!(limit.permits.available_permits(), 2);
+        assert!(router.concurrency_limit("get_user").is_none());
+    }
+
+    #[test]
+    fn test_router_cache_ttl_and_labels() {
+        let mut router = HelixRouter::new(None, None, None);
+        router
+            .cache_routes
+            .insert("dashboard".to_string(), Duration::from_secs(30));
+        router
+            .route_labels
+            .insert("dashboard".to_string(), vec!["User".to_string()]);
+
+        assert_eq!(router.cache_ttl("dashboard"), Some(Duration::from_secs(30)));
+        assert_eq!(router.cache_ttl("missing"), None);
+        assert_eq!(
+            router.route_labels("dashboard"),
+            Some(&["User".to_string()][..])
+        );
+        assert_eq!(router.route_labels("missing"), None);
+    }
+
     // ============================================================================
     // RouterError Tests
     // ============================================================================
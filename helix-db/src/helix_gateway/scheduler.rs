@@ -0,0 +1,239 @@
+//! Queries the instance runs itself on cron schedules, configured in
+//! `GatewayConfig.schedules`. Each schedule waits for its next fire time, runs its query
+//! through the worker pool like any other request, and keeps a short history of runs that
+//! `/schedules` reports.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use axum::Extension;
+use axum::body::{Body, Bytes};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::helix_engine::traversal_core::config::ScheduleConfig;
+use crate::helix_gateway::admin::AdminAuth;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::Format;
+use crate::protocol::request::{Request, RequestType};
+use crate::utils::cron::parse_cron;
+
+/// Runs kept per schedule, newest first
+pub const MAX_RUN_HISTORY: usize = 20;
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("Invalid cron expression `{cron}` for schedule `{name}`: {message}")]
+    InvalidCron {
+        name: String,
+        cron: String,
+        message: String,
+    },
+
+    #[error("Schedule `{0}` is declared more than once")]
+    DuplicateName(String),
+}
+
+/// One run of a scheduled query
+#[derive(Serialize, Clone, Debug)]
+pub struct ScheduleRun {
+    pub started_at_ms: i64,
+    pub duration_ms: u64,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// A schedule as reported by `/schedules`
+#[derive(Serialize, Debug)]
+pub struct ScheduleStatus {
+    pub name: String,
+    pub query: String,
+    pub cron: String,
+    /// When the next run is due, once the scheduler has started
+    pub next_run_ms: Option<i64>,
+    /// Recent runs, newest first
+    pub runs: Vec<ScheduleRun>,
+}
+
+#[derive(Serialize)]
+struct SchedulesResponse {
+    schedules: Vec<ScheduleStatus>,
+}
+
+pub struct Scheduler {
+    schedules: Vec<Schedule>,
+}
+
+struct Schedule {
+    name: String,
+    query: String,
+    cron: String,
+    schedule: cron::Schedule,
+    body: Bytes,
+    state: Mutex<ScheduleState>,
+}
+
+#[derive(Default)]
+struct ScheduleState {
+    next_run: Option<DateTime<Utc>>,
+    runs: VecDeque<ScheduleRun>,
+}
+
+impl Scheduler {
+    pub fn from_config(configs: &[ScheduleConfig]) -> Result<Self, ScheduleError> {
+        let mut schedules: Vec<Schedule> = Vec::with_capacity(configs.len());
+        for config in configs {
+            if schedules.iter().any(|s| s.name == config.name) {
+                return Err(ScheduleError::DuplicateName(config.name.clone()));
+            }
+            let schedule = parse_cron(&config.cron).map_err(|e| ScheduleError::InvalidCron {
+                name: config.name.clone(),
+                cron: config.cron.clone(),
+                message: e.to_string(),
+            })?;
+            let body = match &config.params {
+                Some(params) => {
+                    sonic_rs::to_vec(params).expect("JSON values should always serialize")
+                }
+                None => b"{}".to_vec(),
+            };
+            schedules.push(Schedule {
+                name: config.name.clone(),
+                query: config.query.clone(),
+                cron: config.cron.clone(),
+                schedule,
+                body: Bytes::from(body),
+                state: Mutex::default(),
+            });
+        }
+        Ok(Scheduler { schedules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    /// Query routes the schedules run
+    pub fn queries(&self) -> impl Iterator<Item = &str> {
+        self.schedules.iter().map(|s| s.query.as_str())
+    }
+
+    pub fn statuses(&self) -> Vec<ScheduleStatus> {
+        self.schedules
+            .iter()
+            .map(|schedule| {
+                let state = schedule.lock();
+                ScheduleStatus {
+                    name: schedule.name.clone(),
+                    query: schedule.query.clone(),
+                    cron: schedule.cron.clone(),
+                    next_run_ms: state.next_run.map(|t| t.timestamp_millis()),
+                    runs: state.runs.iter().cloned().collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Run every schedule until the worker pool starts shutting down. Runs of one schedule
+    /// never overlap: fire times missed while a run is still going are skipped.
+    pub async fn run(self: Arc<Self>, state: Arc<AppState>) {
+        join_all(
+            self.schedules
+                .iter()
+                .map(|schedule| schedule.run(&state.worker_pool)),
+        )
+        .await;
+    }
+}
+
+impl Schedule {
+    fn lock(&self) -> MutexGuard<'_, ScheduleState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn run(&self, pool: &WorkerPool) {
+        let mut last_run: Option<DateTime<Utc>> = None;
+        loop {
+            // Never fire the same time twice, even if the clock reads slightly early on waking
+            let after = last_run.map_or_else(Utc::now, |last| last.max(Utc::now()));
+            let Some(next_run) = self.schedule.after(&after).next() else {
+                info!(schedule = %self.name, "Schedule has no more runs");
+                self.lock().next_run = None;
+                return;
+            };
+            self.lock().next_run = Some(next_run);
+            let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            if pool.is_shutting_down() {
+                return;
+            }
+            last_run = Some(next_run);
+            self.run_once(pool).await;
+        }
+    }
+
+    async fn run_once(&self, pool: &WorkerPool) {
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let request = Request {
+            name: self.query.clone(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: self.body.clone(),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+        let error = pool.process(request).await.err().map(|e| e.to_string());
+        let run = ScheduleRun {
+            started_at_ms: started_at.timestamp_millis(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            ok: error.is_none(),
+            error,
+        };
+        match &run.error {
+            Some(error) => warn!(
+                schedule = %self.name,
+                query = %self.query,
+                error,
+                "Scheduled query failed"
+            ),
+            None => info!(
+                schedule = %self.name,
+                query = %self.query,
+                duration_ms = run.duration_ms,
+                "Scheduled query ran"
+            ),
+        }
+        let mut state = self.lock();
+        state.runs.push_front(run);
+        state.runs.truncate(MAX_RUN_HISTORY);
+    }
+}
+
+/// Lists the configured schedules with their next run and recent run history
+pub async fn schedules_handler(
+    _: AdminAuth,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+) -> axum::response::Response {
+    let response = SchedulesResponse {
+        schedules: scheduler.statuses(),
+    };
+    match sonic_rs::to_vec(&response) {
+        Ok(body) => axum::response::Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("should be able to make response from schedules"),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize schedules",
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Path, State};
+#[cfg(feature = "api-key")]
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use tracing::info;
+
+use crate::helix_gateway::auth::BearerAuth;
+use crate::helix_gateway::gateway::AppState;
+use crate::protocol::HelixError;
+
+/// Opens a read session on a snapshot of the graph as of now. Queries sent with the returned
+/// token in the `x-helix-session` header all read that snapshot.
+pub async fn open_session_handler(
+    State(state): State<Arc<AppState>>,
+    #[cfg(feature = "api-key")] headers: HeaderMap,
+    _auth: BearerAuth,
+) -> axum::response::Response {
+    #[cfg(feature = "api-key")]
+    if let Err(e) = verify_api_key(&headers) {
+        return e;
+    }
+
+    let session = match state.worker_pool.sessions().open() {
+        Ok(session) => session,
+        Err(e) => return e.into_response(),
+    };
+    info!(session = %session.session, txn_id = session.txn_id, "Opened read session");
+    match sonic_rs::to_vec(&session) {
+        Ok(body) => axum::response::Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("should be able to make response from session"),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize session",
+        )
+            .into_response(),
+    }
+}
+
+/// Closes a read session, releasing its snapshot once the queries already sent to it finish
+pub async fn close_session_handler(
+    State(state): State<Arc<AppState>>,
+    #[cfg(feature = "api-key")] headers: HeaderMap,
+    _auth: BearerAuth,
+    Path(token): Path<String>,
+) -> axum::response::Response {
+    #[cfg(feature = "api-key")]
+    if let Err(e) = verify_api_key(&headers) {
+        return e;
+    }
+
+    if state.worker_pool.sessions().close(&token) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        HelixError::SessionNotFound(token).into_response()
+    }
+}
+
+#[cfg(feature = "api-key")]
+fn verify_api_key(headers: &HeaderMap) -> Result<(), axum::response::Response> {
+    use crate::helix_gateway::key_verification::verify_key;
+
+    let api_key = match headers.get("x-api-key") {
+        Some(v) => match v.to_str() {
+            Ok(s) => s,
+            Err(_) => {
+                return Err((StatusCode::BAD_REQUEST, "Invalid x-api-key header").into_response());
+            }
+        },
+        None => {
+            return Err((StatusCode::BAD_REQUEST, "Missing x-api-key header").into_response());
+        }
+    };
+    verify_key(api_key).map_err(IntoResponse::into_response)
+}
@@ -0,0 +1,331 @@
+//! Live query results over WebSocket.
+//!
+//! A client connects to `/subscribe` and sends
+//! `{"type": "subscribe", "id": ..., "name": ..., "params": {...}}` for each read route it
+//! wants to watch. The result is sent right away as `{"type": "result", "id": ..., "result": ...}`
+//! and again whenever a committed write touches one of the route's labels and the result
+//! differs from the last one sent. `{"type": "unsubscribe", "id": ...}` stops a subscription.
+//! Failures are sent as `{"type": "error", "id": ..., "status": ...}` along with the fields of
+//! the error body every other response uses (`protocol::error::ErrorBody`).
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code};
+use axum::http::HeaderMap;
+#[cfg(feature = "api-key")]
+use axum::response::IntoResponse;
+use axum::response::Response;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tracing::debug;
+
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::change_feed::Change;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::rate_limit::ClientAddr;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError, error::ErrorBody};
+
+/// Largest number of queries one connection may watch
+pub const MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 64;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        id: String,
+        name: String,
+        #[serde(default)]
+        params: Option<sonic_rs::Value>,
+    },
+    Unsubscribe {
+        id: String,
+    },
+}
+
+#[derive(Serialize)]
+struct ErrorMessage<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: Option<&'a str>,
+    status: u16,
+    #[serde(flatten)]
+    body: ErrorBody,
+}
+
+struct Subscription {
+    name: String,
+    params: Bytes,
+    labels: Option<Vec<String>>,
+    /// Result last sent, so unchanged results are not resent
+    last: Vec<u8>,
+}
+
+pub async fn subscribe_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    auth: BearerAuth,
+    ClientAddr(addr): ClientAddr,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let api_key = {
+        #[cfg(feature = "api-key")]
+        {
+            use crate::helix_gateway::key_verification::verify_key;
+
+            let Some(api_key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) else {
+                return (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "Missing x-api-key header",
+                )
+                    .into_response();
+            };
+            if let Err(e) = verify_key(api_key) {
+                tracing::info!(?e, "Invalid API key");
+                return e.into_response();
+            }
+            Some(api_key.to_string())
+        }
+        #[cfg(not(feature = "api-key"))]
+        None::<String>
+    };
+
+    let connection = Connection {
+        state,
+        caller: auth.0,
+        addr,
+        headers,
+        api_key,
+        subscriptions: HashMap::new(),
+    };
+    ws.on_upgrade(move |socket| connection.run(socket))
+}
+
+struct Connection {
+    state: Arc<AppState>,
+    caller: Option<Caller>,
+    addr: Option<IpAddr>,
+    headers: HeaderMap,
+    api_key: Option<String>,
+    subscriptions: HashMap<String, Subscription>,
+}
+
+impl Connection {
+    async fn run(mut self, mut socket: WebSocket) {
+        let mut changes = self.state.worker_pool.changes().subscribe();
+        if self.state.worker_pool.is_shutting_down() {
+            close(&mut socket).await;
+            return;
+        }
+        loop {
+            tokio::select! {
+                msg = socket.recv() => {
+                    let reply = match msg {
+                        Some(Ok(Message::Text(text))) => self.handle_message(text.as_bytes()).await,
+                        Some(Ok(Message::Binary(bytes))) => self.handle_message(&bytes).await,
+                        Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                        Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    };
+                    if let Some(reply) = reply
+                        && socket.send(Message::Text(reply.into())).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                change = changes.recv() => {
+                    // A lagging connection refreshes everything rather than tracking what it missed
+                    let mut pending = match change {
+                        Ok(Change::ShuttingDown) | Err(RecvError::Closed) => {
+                            close(&mut socket).await;
+                            break;
+                        }
+                        Ok(change) => vec![change],
+                        Err(RecvError::Lagged(_)) => vec![Change::Write { labels: None }],
+                    };
+                    // Writes committed back to back are served by a single refresh
+                    loop {
+                        match changes.try_recv() {
+                            Ok(change) => pending.push(change),
+                            Err(TryRecvError::Lagged(_)) => pending.push(Change::Write { labels: None }),
+                            Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                        }
+                    }
+                    if pending.contains(&Change::ShuttingDown) {
+                        close(&mut socket).await;
+                        break;
+                    }
+                    let mut sent = Ok(());
+                    for update in self.refresh(&pending).await {
+                        sent = socket.send(Message::Text(update.into())).await;
+                        if sent.is_err() {
+                            break;
+                        }
+                    }
+                    if sent.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        debug!(
+            subscriptions = self.subscriptions.len(),
+            "Subscription connection closed"
+        );
+    }
+
+    async fn handle_message(&mut self, msg: &[u8]) -> Option<String> {
+        match sonic_rs::from_slice::<ClientMessage>(msg) {
+            Ok(ClientMessage::Subscribe { id, name, params }) => {
+                let params = params
+                    .map(|params| Bytes::from(sonic_rs::to_vec(&params).unwrap_or_default()))
+                    .unwrap_or_default();
+                Some(match self.subscribe(&id, name, params).await {
+                    Ok(result) => result_message(&id, &result),
+                    Err(e) => error_message(Some(&id), &e),
+                })
+            }
+            Ok(ClientMessage::Unsubscribe { id }) => {
+                self.subscriptions.remove(&id);
+                None
+            }
+            Err(e) => Some(error_message(
+                None,
+                &HelixError::InvalidSubscription(e.to_string()),
+            )),
+        }
+    }
+
+    /// Authorize and run the query, keeping it as a subscription if it succeeds
+    async fn subscribe(
+        &mut self,
+        id: &str,
+        name: String,
+        params: Bytes,
+    ) -> Result<Vec<u8>, HelixError> {
+        let pool = &self.state.worker_pool;
+        if !self.subscriptions.contains_key(id)
+            && self.subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_CONNECTION
+        {
+            return Err(HelixError::InvalidSubscription(format!(
+                "a connection can hold at most {MAX_SUBSCRIPTIONS_PER_CONNECTION} subscriptions"
+            )));
+        }
+        if pool.is_write_route(&name) {
+            return Err(HelixError::InvalidSubscription(format!(
+                "`{name}` is a write route"
+            )));
+        }
+        if let Some(caller) = &self.caller {
+            caller.authorize(&name, false, &pool.route_roles(&name))?;
+        }
+        let limiter = &self.state.rate_limiter;
+        if limiter.is_enabled() {
+            let client = limiter.client_id(self.caller.as_ref(), self.addr, &self.headers);
+            limiter.check(&name, &client)?;
+        }
+
+        let result = self.execute(&name, params.clone()).await?;
+        self.subscriptions.insert(
+            id.to_string(),
+            Subscription {
+                labels: pool.route_labels(&name),
+                name,
+                params,
+                last: result.clone(),
+            },
+        );
+        Ok(result)
+    }
+
+    /// Rerun the subscriptions `changes` may affect, returning messages for those whose
+    /// result changed
+    async fn refresh(&mut self, changes: &[Change]) -> Vec<String> {
+        let this = &*self;
+        let affected: Vec<&String> = this
+            .subscriptions
+            .iter()
+            .filter(|(_, sub)| {
+                changes
+                    .iter()
+                    .any(|change| change.affects(sub.labels.as_deref()))
+            })
+            .map(|(id, _)| id)
+            .collect();
+        let results = join_all(affected.into_iter().map(|id| async move {
+            let sub = &this.subscriptions[id];
+            (
+                id.clone(),
+                this.execute(&sub.name, sub.params.clone()).await,
+            )
+        }))
+        .await;
+
+        let mut updates = Vec::new();
+        for (id, result) in results {
+            match result {
+                Ok(result) => {
+                    let sub = self
+                        .subscriptions
+                        .get_mut(&id)
+                        .expect("refreshed subscription should still exist");
+                    if sub.last != result {
+                        updates.push(result_message(&id, &result));
+                        sub.last = result;
+                    }
+                }
+                Err(e) => updates.push(error_message(Some(&id), &e)),
+            }
+        }
+        updates
+    }
+
+    async fn execute(&self, name: &str, params: Bytes) -> Result<Vec<u8>, HelixError> {
+        let req = Request {
+            name: name.to_string(),
+            req_type: RequestType::Query,
+            api_key: self.api_key.clone(),
+            body: params,
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        };
+        self.state
+            .worker_pool
+            .process_with(req, RequestHints::default())
+            .await
+            .map(|r| r.body)
+    }
+}
+
+async fn close(socket: &mut WebSocket) {
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::AWAY,
+            reason: "Server is shutting down".into(),
+        })))
+        .await;
+}
+
+fn result_message(id: &str, result: &[u8]) -> String {
+    let id = sonic_rs::to_string(id).expect("string should always serialize");
+    let result = if result.is_empty() {
+        "null".into()
+    } else {
+        String::from_utf8_lossy(result)
+    };
+    format!(r#"{{"type":"result","id":{id},"result":{result}}}"#)
+}
+
+fn error_message(id: Option<&str>, e: &HelixError) -> String {
+    sonic_rs::to_string(&ErrorMessage {
+        kind: "error",
+        id,
+        status: e.status().as_u16(),
+        body: e.body(),
+    })
+    .expect("subscription error should always serialize")
+}
@@ -0,0 +1,172 @@
+use crate::helix_engine::traversal_core::config::{ApiKeyConfig, ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::api_keys::{ApiKeys, KEY_PREFIX, generate_api_key, hash_api_key};
+use crate::helix_gateway::auth::{BearerAuth, Caller, bearer_token};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::HelixError;
+use axum::extract::FromRequestParts;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn key_config(name: &str, key: &str, scope: ApiKeyScope) -> ApiKeyConfig {
+    ApiKeyConfig {
+        name: name.to_string(),
+        key_hash: hash_api_key(key),
+        scope,
+    }
+}
+
+fn create_test_state(api_keys: ApiKeys) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        db_max_size_gb: Some(0),
+        ..Default::default()
+    };
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config,
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let router = Arc::new(HelixRouter::new(None, None, None));
+    let state = Arc::new(AppState {
+        worker_pool: WorkerPool::new(core_setter, graph, router, rt),
+        schema_json: None,
+        cluster_id: None,
+        api_keys,
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn extract(
+    state: &Arc<AppState>,
+    authorization: Option<&str>,
+) -> Result<BearerAuth, HelixError> {
+    let mut builder = axum::http::Request::builder().uri("/query");
+    if let Some(value) = authorization {
+        builder = builder.header("authorization", value);
+    }
+    let (mut parts, _) = builder.body(()).unwrap().into_parts();
+    BearerAuth::from_request_parts(&mut parts, state).await
+}
+
+#[test]
+fn test_generated_keys_are_unique() {
+    let key = generate_api_key();
+    assert!(key.starts_with(KEY_PREFIX));
+    assert_eq!(key.len(), KEY_PREFIX.len() + 64);
+    assert_ne!(key, generate_api_key());
+    assert_eq!(hash_api_key(&key).len(), 64);
+    assert_ne!(hash_api_key(&key), key);
+}
+
+#[test]
+fn test_verify_matches_key_and_scope() {
+    let keys = ApiKeys::from_config(&[
+        key_config("writer", "write-secret", ApiKeyScope::ReadWrite),
+        key_config("reader", "read-secret", ApiKeyScope::ReadOnly),
+    ]);
+    assert!(keys.is_enabled());
+
+    let writer = keys.verify("write-secret").unwrap();
+    assert_eq!(writer.name, "writer");
+    assert!(writer.can_call(true));
+    assert!(writer.can_call(false));
+
+    let reader = keys.verify("read-secret").unwrap();
+    assert_eq!(reader.name, "reader");
+    assert!(!reader.can_call(true));
+    assert!(reader.can_call(false));
+
+    assert!(keys.verify("wrong-secret").is_none());
+    assert!(keys.verify("").is_none());
+}
+
+#[test]
+fn test_malformed_hash_is_skipped() {
+    let keys = ApiKeys::from_config(&[ApiKeyConfig {
+        name: "broken".to_string(),
+        key_hash: "not-a-hash".to_string(),
+        scope: ApiKeyScope::ReadWrite,
+    }]);
+    assert!(!keys.is_enabled());
+    assert!(keys.verify("not-a-hash").is_none());
+}
+
+#[test]
+fn test_api_key_scope_defaults_to_read_write() {
+    let config: ApiKeyConfig = sonic_rs::from_str(r#"{"name":"ci","key_hash":"00"}"#).unwrap();
+    assert_eq!(config.scope, ApiKeyScope::ReadWrite);
+    let config: ApiKeyConfig =
+        sonic_rs::from_str(r#"{"name":"ci","key_hash":"00","scope":"read_only"}"#).unwrap();
+    assert_eq!(config.scope, ApiKeyScope::ReadOnly);
+}
+
+#[test]
+fn test_bearer_token_parsing() {
+    assert_eq!(bearer_token("Bearer abc"), Some("abc"));
+    assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+    assert_eq!(bearer_token("Basic abc"), None);
+    assert_eq!(bearer_token("Bearer "), None);
+    assert_eq!(bearer_token("abc"), None);
+}
+
+#[tokio::test]
+async fn test_bearer_auth_open_without_keys() {
+    let (state, _temp_dir) = create_test_state(ApiKeys::default());
+    let auth = extract(&state, None).await.unwrap();
+    assert!(auth.0.is_none());
+}
+
+#[tokio::test]
+async fn test_bearer_auth_rejects_missing_and_unknown_keys() {
+    let keys = ApiKeys::from_config(&[key_config("ci", "secret", ApiKeyScope::ReadOnly)]);
+    let (state, _temp_dir) = create_test_state(keys);
+
+    assert!(matches!(
+        extract(&state, None).await,
+        Err(HelixError::MissingBearerToken)
+    ));
+    assert!(matches!(
+        extract(&state, Some("Basic secret")).await,
+        Err(HelixError::MissingBearerToken)
+    ));
+    assert!(matches!(
+        extract(&state, Some("Bearer wrong")).await,
+        Err(HelixError::InvalidApiKey)
+    ));
+
+    let auth = extract(&state, Some("Bearer secret")).await.unwrap();
+    let Some(Caller::ApiKey(key)) = auth.0 else {
+        panic!("expected an API key caller");
+    };
+    assert_eq!(key.name, "ci");
+    assert_eq!(key.scope, ApiKeyScope::ReadOnly);
+}
+
+#[test]
+fn test_auth_error_status_codes() {
+    use axum::response::IntoResponse;
+
+    let response = HelixError::MissingBearerToken.into_response();
+    assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+    let response = HelixError::ReadOnlyApiKey {
+        route: "add_user".to_string(),
+    }
+    .into_response();
+    assert_eq!(response.status(), axum::http::StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,170 @@
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::audit_log::{self, AuditEntry, AuditFilter};
+use crate::helix_engine::storage_core::write_log;
+use crate::helix_engine::traversal_core::config::{Config, GatewayConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::audit::api_key_fingerprint;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+fn create_test_graph() -> (HelixGraphEngine, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        db_max_size_gb: Some(0),
+        ..Default::default()
+    };
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config,
+        version_info: Default::default(),
+    };
+    (HelixGraphEngine::new(opts).unwrap(), temp_dir)
+}
+
+fn append(storage: &HelixGraphStorage, entry: &AuditEntry) {
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.append_audit_entry(&mut txn, entry).unwrap();
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_write_log_take_dedups_in_order() {
+    write_log::reset();
+    write_log::record(3);
+    write_log::record(1);
+    write_log::record(3);
+    assert_eq!(write_log::take(), vec![3, 1]);
+    assert!(write_log::take().is_empty());
+}
+
+#[test]
+fn test_api_key_fingerprint_hides_key() {
+    let fingerprint = api_key_fingerprint("secret-key");
+    assert!(fingerprint.starts_with("key:"));
+    assert_eq!(fingerprint.len(), "key:".len() + 16);
+    assert!(!fingerprint.contains("secret-key"));
+    assert_eq!(fingerprint, api_key_fingerprint("secret-key"));
+    assert_ne!(fingerprint, api_key_fingerprint("other-key"));
+}
+
+#[test]
+fn test_audit_entries_newest_first() {
+    let (graph, _temp_dir) = create_test_graph();
+    let storage = &graph.storage;
+    for query in ["first", "second", "third"] {
+        append(storage, &AuditEntry::new(query.to_string(), None, vec![]));
+    }
+
+    let entries = storage.audit_entries(&AuditFilter::default()).unwrap();
+    let queries: Vec<_> = entries.iter().map(|e| e.query.as_str()).collect();
+    assert_eq!(queries, ["third", "second", "first"]);
+
+    let filter = AuditFilter {
+        limit: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(storage.audit_entries(&filter).unwrap().len(), 2);
+}
+
+#[test]
+fn test_audit_entries_filtered() {
+    let (graph, _temp_dir) = create_test_graph();
+    let storage = &graph.storage;
+    append(
+        storage,
+        &AuditEntry::new(
+            "add_user".to_string(),
+            Some("key:a".to_string()),
+            vec![1, 2],
+        ),
+    );
+    append(
+        storage,
+        &AuditEntry::new("drop_user".to_string(), Some("key:b".to_string()), vec![2]),
+    );
+
+    let by_query = AuditFilter {
+        query: Some("add_user".to_string()),
+        ..Default::default()
+    };
+    let entries = storage.audit_entries(&by_query).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].item_ids,
+        vec![Uuid::from_u128(1), Uuid::from_u128(2)]
+    );
+
+    let by_actor = AuditFilter {
+        actor: Some("key:b".to_string()),
+        ..Default::default()
+    };
+    let entries = storage.audit_entries(&by_actor).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].query, "drop_user");
+
+    let by_item = AuditFilter {
+        item_id: Some(Uuid::from_u128(2)),
+        ..Default::default()
+    };
+    assert_eq!(storage.audit_entries(&by_item).unwrap().len(), 2);
+
+    let future = AuditFilter {
+        since_ms: Some(u64::MAX),
+        ..Default::default()
+    };
+    assert!(storage.audit_entries(&future).unwrap().is_empty());
+}
+
+#[test]
+fn test_audit_write_commits_with_the_write() {
+    let (graph, _temp_dir) = create_test_graph();
+    let storage = &graph.storage;
+
+    // Outside an audited write nothing is appended
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.audit_write(&mut txn).unwrap();
+    txn.commit().unwrap();
+    assert!(
+        storage
+            .audit_entries(&AuditFilter::default())
+            .unwrap()
+            .is_empty()
+    );
+
+    audit_log::begin("add_user".to_string(), Some("key:a".to_string()));
+
+    // An aborted write leaves no entry behind
+    write_log::reset();
+    write_log::record(1);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.audit_write(&mut txn).unwrap();
+    txn.abort();
+    assert!(
+        storage
+            .audit_entries(&AuditFilter::default())
+            .unwrap()
+            .is_empty()
+    );
+
+    write_log::record(2);
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    storage.audit_write(&mut txn).unwrap();
+    txn.commit().unwrap();
+    audit_log::end();
+
+    let entries = storage.audit_entries(&AuditFilter::default()).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].query, "add_user");
+    assert_eq!(entries[0].actor.as_deref(), Some("key:a"));
+    assert_eq!(entries[0].item_ids, vec![Uuid::from_u128(2)]);
+}
+
+#[test]
+fn test_audit_log_config_default_off() {
+    assert!(!GatewayConfig::default().audit_log());
+    let config = GatewayConfig {
+        audit_log: Some(true),
+        ..Default::default()
+    };
+    assert!(config.audit_log());
+}
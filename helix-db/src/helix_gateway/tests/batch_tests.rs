@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::api_keys::AuthorizedKey;
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::batch::{MAX_BATCH_QUERIES, batch_handler};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::{ClientAddr, RateLimiter};
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+fn echo(input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: input.request.body.to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn failing(_input: HandlerInput) -> Result<Response, GraphError> {
+    Err(GraphError::New("handler error".to_string()))
+}
+
+fn count_response(count: usize) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: count.to_string().into_bytes(),
+        fmt: Format::Json,
+    })
+}
+
+/// App state with `echo`, `failing`, a `count` read and an `increment` write route
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let read_counter = Arc::clone(&counter);
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert("echo".to_string(), Arc::new(echo));
+    routes.insert("failing".to_string(), Arc::new(failing));
+    routes.insert(
+        "count".to_string(),
+        Arc::new(move |_| count_response(read_counter.load(Ordering::SeqCst))),
+    );
+    routes.insert(
+        "increment".to_string(),
+        Arc::new(move |_| count_response(counter.fetch_add(1, Ordering::SeqCst) + 1)),
+    );
+    let write_routes = HashSet::from(["increment".to_string()]);
+    let router = Arc::new(HelixRouter::new(Some(routes), None, Some(write_routes)));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn run_batch(
+    state: Arc<AppState>,
+    caller: Option<Caller>,
+    body: &str,
+) -> (StatusCode, sonic_rs::Value) {
+    let response = batch_handler(
+        State(state),
+        HeaderMap::new(),
+        BearerAuth(caller),
+        ClientAddr(None),
+        Bytes::from(body.to_string()),
+    )
+    .await;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_results_in_request_order() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_batch(
+        state,
+        None,
+        r#"[
+            {"name": "echo", "params": {"id": 1}},
+            {"name": "echo", "params": [2, "two"]},
+            {"name": "echo"}
+        ]"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let results = body.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    for result in results {
+        assert_eq!(result["status"].as_u64(), Some(200));
+    }
+    assert_eq!(results[0]["result"]["id"].as_u64(), Some(1));
+    assert_eq!(results[1]["result"][1].as_str(), Some("two"));
+    assert!(results[2]["result"].is_null());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_writes_are_ordered_with_reads() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_batch(
+        state,
+        None,
+        r#"[
+            {"name": "count"},
+            {"name": "increment"},
+            {"name": "count"},
+            {"name": "count"},
+            {"name": "increment"},
+            {"name": "increment"},
+            {"name": "count"}
+        ]"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let counts: Vec<u64> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["result"].as_u64().unwrap())
+        .collect();
+    assert_eq!(counts, [0, 1, 1, 1, 2, 3, 3]);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_reports_errors_per_query() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_batch(
+        state,
+        None,
+        r#"[{"name": "missing"}, {"name": "failing"}, {"name": "echo", "params": 7}]"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let results = body.as_array().unwrap();
+    assert_eq!(results[0]["status"].as_u64(), Some(404));
+    assert_eq!(results[0]["code"].as_str(), Some("R301"));
+    assert_eq!(results[1]["status"].as_u64(), Some(500));
+    assert_eq!(results[1]["code"].as_str(), Some("R901"));
+    assert!(
+        results[1]["message"]
+            .as_str()
+            .unwrap()
+            .contains("handler error")
+    );
+    assert_eq!(results[2]["result"].as_u64(), Some(7));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_authorizes_each_query() {
+    let (state, _dir) = create_test_app_state();
+    let read_only = Caller::ApiKey(AuthorizedKey {
+        name: "dashboard".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+    });
+    let (status, body) = run_batch(
+        state,
+        Some(read_only),
+        r#"[{"name": "increment"}, {"name": "count"}]"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let results = body.as_array().unwrap();
+    assert_eq!(results[0]["status"].as_u64(), Some(403));
+    assert_eq!(results[0]["code"].as_str(), Some("R204"));
+    // The rejected write never ran
+    assert_eq!(results[1]["result"].as_u64(), Some(0));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_rejects_malformed_body() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_batch(Arc::clone(&state), None, r#"{"name": "echo"}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"].as_str(), Some("R102"));
+
+    let (status, _) = run_batch(Arc::clone(&state), None, r#"[{"params": {}}]"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = run_batch(state, None, "[]").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.as_array().unwrap().is_empty());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_batch_size_is_limited() {
+    let (state, _dir) = create_test_app_state();
+    let queries = vec![r#"{"name": "echo"}"#; MAX_BATCH_QUERIES + 1].join(",");
+    let (status, body) = run_batch(state, None, &format!("[{queries}]")).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"].as_str(), Some("R103"));
+}
@@ -0,0 +1,60 @@
+use crate::helix_gateway::body_limit::limit_request_bodies;
+use axum::body::{Body, Bytes};
+use axum::http::{Request, StatusCode, header};
+use axum::routing::post;
+use futures_util::stream;
+use sonic_rs::JsonValueTrait;
+use tower::ServiceExt;
+
+const MAX: usize = 16;
+
+fn app() -> axum::Router {
+    let router = axum::Router::new().route(
+        "/{*path}",
+        post(|body: Bytes| async move { body.len().to_string() }),
+    );
+    limit_request_bodies(router, MAX)
+}
+
+fn request(body: Body) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/addUser")
+        .body(body)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_body_within_limit_is_passed_on() {
+    let response = app()
+        .oneshot(request(Body::from(vec![b'a'; MAX])))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_declared_length_over_limit_is_rejected_before_reading() {
+    let mut req = request(Body::empty());
+    req.headers_mut()
+        .insert(header::CONTENT_LENGTH, (MAX + 1).into());
+    let response = app().oneshot(req).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: sonic_rs::Value = sonic_rs::from_slice(&body).unwrap();
+    assert_eq!(body["code"].as_str(), Some("R108"));
+    assert_eq!(body["details"]["max"].as_u64(), Some(MAX as u64));
+}
+
+#[tokio::test]
+async fn test_body_without_length_is_cut_off_at_limit() {
+    let chunks = vec![Ok::<_, std::io::Error>(vec![b'a'; MAX]), Ok(vec![b'a'; 1])];
+    let response = app()
+        .oneshot(request(Body::from_stream(stream::iter(chunks))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,252 @@
+use crate::helix_engine::traversal_core::HelixGraphEngineOpts;
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::{traversal_core::HelixGraphEngine, types::GraphError};
+use crate::helix_gateway::chaos::{self, ChaosConfig, Delay};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::helix_gateway::{
+    gateway::CoreSetter,
+    router::router::{HandlerInput, HelixRouter, IoContFn},
+};
+use crate::protocol::Format;
+use crate::protocol::{HelixError, Request, request::RequestType, response::Response};
+use axum::body::Bytes;
+use serial_test::serial;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn create_test_graph() -> (Arc<HelixGraphEngine>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.db_max_size_gb = Some(0);
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config,
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    (graph, temp_dir)
+}
+
+fn ok_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: b"ok".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn io_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    Err(IoContFn::create_err(move |cont_tx, ret_chan| {
+        Box::pin(async move {
+            cont_tx
+                .send_async((
+                    ret_chan,
+                    Box::new(|| {
+                        Ok(Response {
+                            body: b"ok".to_vec(),
+                            fmt: Format::Json,
+                        })
+                    }),
+                ))
+                .await
+                .expect("continuation channel should be open");
+        })
+    }))
+}
+
+/// A pool serving `chaos_read`, `chaos_io` and the write route `chaos_write`
+fn create_pool() -> (WorkerPool, TempDir) {
+    let (graph, temp_dir) = create_test_graph();
+    let mut routes = HashMap::new();
+    routes.insert("chaos_read".to_string(), Arc::new(ok_handler) as Arc<_>);
+    routes.insert("chaos_write".to_string(), Arc::new(ok_handler) as Arc<_>);
+    routes.insert("chaos_io".to_string(), Arc::new(io_handler) as Arc<_>);
+    let write_routes = HashSet::from(["chaos_write".to_string()]);
+    let router = Arc::new(HelixRouter::new(Some(routes), None, Some(write_routes)));
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let core_setter = Arc::new(CoreSetter::new(vec![core_affinity::CoreId { id: 0 }], 2));
+    (WorkerPool::new(core_setter, graph, router, rt), temp_dir)
+}
+
+fn request(name: &str) -> Request {
+    Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    }
+}
+
+// ============================================================================
+// Config Tests
+// ============================================================================
+
+#[test]
+fn test_parse_chaos_config() {
+    let config = ChaosConfig::parse(
+        "io_error=0.05, map_full=0.01,continuation_delay=0.1:250ms,writer_stall=1:2s,seed=7,routes=a|b",
+    )
+    .unwrap();
+
+    assert_eq!(config.io_error, 0.05);
+    assert_eq!(config.map_full, 0.01);
+    assert_eq!(
+        config.continuation_delay,
+        Some(Delay {
+            rate: 0.1,
+            duration: Duration::from_millis(250)
+        })
+    );
+    assert_eq!(
+        config.writer_stall,
+        Some(Delay {
+            rate: 1.0,
+            duration: Duration::from_secs(2)
+        })
+    );
+    assert_eq!(config.seed, Some(7));
+    assert_eq!(config.routes, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(ChaosConfig::parse("").unwrap(), ChaosConfig::default());
+}
+
+#[test]
+fn test_parse_chaos_config_rejects_invalid_specs() {
+    assert!(ChaosConfig::parse("io_error=1.5").is_err());
+    assert!(ChaosConfig::parse("io_error").is_err());
+    assert!(ChaosConfig::parse("disk_on_fire=0.5").is_err());
+    assert!(ChaosConfig::parse("writer_stall=0.5").is_err());
+    assert!(ChaosConfig::parse("writer_stall=0.5:soon").is_err());
+}
+
+// ============================================================================
+// Injection Tests
+// ============================================================================
+
+#[tokio::test]
+#[serial(chaos)]
+async fn test_io_error_fails_targeted_routes_only() {
+    let (pool, _temp_dir) = create_pool();
+    let chaos = chaos::set(Some(ChaosConfig {
+        io_error: 1.0,
+        routes: vec!["chaos_read".to_string()],
+        ..ChaosConfig::default()
+    }))
+    .unwrap();
+
+    let failed = pool.process(request("chaos_read")).await;
+    let untouched = pool.process(request("chaos_write")).await;
+    chaos::set(None);
+
+    assert!(matches!(failed, Err(HelixError::Graph(GraphError::Io(_)))));
+    assert!(untouched.is_ok());
+    assert_eq!(chaos.stats().io_errors, 1);
+}
+
+#[tokio::test]
+#[serial(chaos)]
+async fn test_map_full_only_fails_writes() {
+    let (pool, _temp_dir) = create_pool();
+    let chaos = chaos::set(Some(ChaosConfig {
+        map_full: 1.0,
+        routes: vec!["chaos_read".to_string(), "chaos_write".to_string()],
+        ..ChaosConfig::default()
+    }))
+    .unwrap();
+
+    let read = pool.process(request("chaos_read")).await;
+    let write = pool.process(request("chaos_write")).await;
+    chaos::set(None);
+
+    assert!(read.is_ok());
+    match write {
+        Err(HelixError::Graph(GraphError::StorageError(msg))) => {
+            assert!(msg.contains("MDB_MAP_FULL"), "unexpected error: {msg}")
+        }
+        other => panic!("expected a map full error, got {other:?}"),
+    }
+    assert_eq!(chaos.stats().map_full, 1);
+}
+
+#[tokio::test]
+#[serial(chaos)]
+async fn test_continuation_delay_holds_back_io_continuations() {
+    let (pool, _temp_dir) = create_pool();
+    let chaos = chaos::set(Some(ChaosConfig {
+        continuation_delay: Some(Delay {
+            rate: 1.0,
+            duration: Duration::from_millis(200),
+        }),
+        routes: vec!["chaos_io".to_string()],
+        ..ChaosConfig::default()
+    }))
+    .unwrap();
+
+    let started = Instant::now();
+    let result = pool.process(request("chaos_io")).await;
+    chaos::set(None);
+
+    assert_eq!(result.unwrap().body, b"ok");
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(chaos.stats().delayed_continuations, 1);
+}
+
+#[tokio::test]
+#[serial(chaos)]
+async fn test_writer_stall_delays_writes_but_not_reads() {
+    let (pool, _temp_dir) = create_pool();
+    let chaos = chaos::set(Some(ChaosConfig {
+        writer_stall: Some(Delay {
+            rate: 1.0,
+            duration: Duration::from_millis(200),
+        }),
+        routes: vec!["chaos_read".to_string(), "chaos_write".to_string()],
+        ..ChaosConfig::default()
+    }))
+    .unwrap();
+
+    let started = Instant::now();
+    let write = pool.process(request("chaos_write")).await;
+    let write_time = started.elapsed();
+    let read = pool.process(request("chaos_read")).await;
+    chaos::set(None);
+
+    assert!(write.is_ok());
+    assert!(read.is_ok());
+    assert!(write_time >= Duration::from_millis(200));
+    assert_eq!(chaos.stats().writer_stalls, 1);
+}
+
+#[tokio::test]
+#[serial(chaos)]
+async fn test_seeded_chaos_injects_the_same_faults() {
+    let (pool, _temp_dir) = create_pool();
+    let config = ChaosConfig {
+        io_error: 0.5,
+        seed: Some(42),
+        routes: vec!["chaos_read".to_string()],
+        ..ChaosConfig::default()
+    };
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        chaos::set(Some(config.clone()));
+        let mut outcomes = Vec::new();
+        for _ in 0..32 {
+            outcomes.push(pool.process(request("chaos_read")).await.is_ok());
+        }
+        runs.push(outcomes);
+    }
+    chaos::set(None);
+
+    assert_eq!(runs[0], runs[1]);
+    assert!(runs[0].contains(&true) && runs[0].contains(&false));
+}
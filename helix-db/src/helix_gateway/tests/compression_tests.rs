@@ -0,0 +1,88 @@
+use crate::helix_engine::traversal_core::config::{CompressionAlgorithm, CompressionConfig};
+use crate::helix_gateway::compression::compression_layer;
+use axum::body::Body;
+use axum::http::{Request, header};
+use axum::routing::post;
+use tower::ServiceExt;
+
+/// Serves a JSON body of `size` bytes at every path
+fn app(config: &CompressionConfig, size: usize) -> axum::Router {
+    let body = format!("\"{}\"", "a".repeat(size - 2));
+    axum::Router::new()
+        .route(
+            "/{*path}",
+            post(move || async move { ([(header::CONTENT_TYPE, "application/json")], body) }),
+        )
+        .layer(compression_layer(config).unwrap())
+}
+
+/// The `Content-Encoding` and body length of a response to `accept_encoding`
+async fn request(app: axum::Router, accept_encoding: &str) -> (Option<String>, usize) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/getUsers")
+        .header(header::ACCEPT_ENCODING, accept_encoding)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let encoding = response
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (encoding, body.len())
+}
+
+#[tokio::test]
+async fn test_large_responses_are_compressed() {
+    let config = CompressionConfig::default();
+    for encoding in ["gzip", "br", "zstd"] {
+        let (used, len) = request(app(&config, 64 * 1024), encoding).await;
+        assert_eq!(used.as_deref(), Some(encoding));
+        assert!(len < 64 * 1024);
+    }
+}
+
+#[tokio::test]
+async fn test_client_preference_is_respected() {
+    let config = CompressionConfig::default();
+    let (used, _) = request(app(&config, 4096), "gzip;q=0.5, zstd;q=1.0").await;
+    assert_eq!(used.as_deref(), Some("zstd"));
+}
+
+#[tokio::test]
+async fn test_small_or_unaccepted_responses_are_not_compressed() {
+    let config = CompressionConfig::default();
+    let (used, len) = request(app(&config, 512), "gzip").await;
+    assert_eq!(used, None);
+    assert_eq!(len, 512);
+
+    let (used, len) = request(app(&config, 4096), "identity").await;
+    assert_eq!(used, None);
+    assert_eq!(len, 4096);
+}
+
+#[tokio::test]
+async fn test_min_size_and_algorithms_are_configurable() {
+    let config = CompressionConfig {
+        min_size_bytes: Some(100),
+        algorithms: Some(vec![CompressionAlgorithm::Zstd]),
+        ..Default::default()
+    };
+    let (used, _) = request(app(&config, 512), "gzip, zstd").await;
+    assert_eq!(used.as_deref(), Some("zstd"));
+
+    let (used, _) = request(app(&config, 512), "gzip").await;
+    assert_eq!(used, None);
+}
+
+#[test]
+fn test_compression_can_be_disabled() {
+    let config = CompressionConfig {
+        enabled: Some(false),
+        ..Default::default()
+    };
+    assert!(compression_layer(&config).is_none());
+}
@@ -0,0 +1,165 @@
+use crate::helix_engine::traversal_core::config::CorsConfig;
+use crate::helix_gateway::cors::{CorsError, cors_layer};
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode, header};
+use axum::routing::post;
+use tower::ServiceExt;
+
+const APP_ORIGIN: &str = "https://app.example.com";
+
+fn config(origins: &[&str]) -> CorsConfig {
+    CorsConfig {
+        allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn app(config: &CorsConfig) -> axum::Router {
+    axum::Router::new()
+        .route("/{*path}", post(|| async { "ok" }))
+        .layer(cors_layer(config).unwrap())
+}
+
+fn preflight(origin: &str, request_headers: &str) -> Request<Body> {
+    Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/getUser")
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, request_headers)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn header_value(response: &axum::response::Response, name: header::HeaderName) -> &str {
+    response
+        .headers()
+        .get(name)
+        .map(|v| v.to_str().unwrap())
+        .unwrap_or("")
+}
+
+#[tokio::test]
+async fn test_preflight_for_allowed_origin() {
+    let response = app(&config(&[APP_ORIGIN]))
+        .oneshot(preflight(APP_ORIGIN, "authorization,content-type"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        APP_ORIGIN
+    );
+    let methods = header_value(&response, header::ACCESS_CONTROL_ALLOW_METHODS);
+    assert!(methods.contains("POST"));
+    let headers = header_value(&response, header::ACCESS_CONTROL_ALLOW_HEADERS);
+    assert!(headers.contains("authorization"));
+    assert!(headers.contains("x-helix-priority"));
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_MAX_AGE),
+        "600"
+    );
+}
+
+#[tokio::test]
+async fn test_other_origins_get_no_cors_headers() {
+    let response = app(&config(&[APP_ORIGIN]))
+        .oneshot(preflight("https://evil.example.com", "content-type"))
+        .await
+        .unwrap();
+    assert!(
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_simple_request_exposes_rate_limit_headers() {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/getUser")
+        .header(header::ORIGIN, APP_ORIGIN)
+        .body(Body::empty())
+        .unwrap();
+    let response = app(&config(&[&format!("{APP_ORIGIN}/")]))
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // A trailing slash in the configured origin is ignored
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        APP_ORIGIN
+    );
+    let exposed = header_value(&response, header::ACCESS_CONTROL_EXPOSE_HEADERS);
+    assert!(exposed.contains("retry-after"));
+    assert!(exposed.contains("ratelimit-remaining"));
+}
+
+#[tokio::test]
+async fn test_wildcard_origin_and_credentials() {
+    let response = app(&config(&["*"]))
+        .oneshot(preflight("https://anywhere.example.com", "content-type"))
+        .await
+        .unwrap();
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        "*"
+    );
+
+    let with_credentials = CorsConfig {
+        allow_credentials: Some(true),
+        ..config(&[APP_ORIGIN])
+    };
+    let response = app(&with_credentials)
+        .oneshot(preflight(APP_ORIGIN, "content-type"))
+        .await
+        .unwrap();
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        "true"
+    );
+}
+
+#[test]
+fn test_invalid_cors_config_is_rejected() {
+    assert!(matches!(
+        cors_layer(&config(&[])),
+        Err(CorsError::NoOrigins)
+    ));
+    assert!(matches!(
+        cors_layer(&config(&["https://bad\norigin"])),
+        Err(CorsError::Origin(_))
+    ));
+    assert!(matches!(
+        cors_layer(&CorsConfig {
+            allowed_methods: Some(vec!["GE T".to_string()]),
+            ..config(&[APP_ORIGIN])
+        }),
+        Err(CorsError::Method(_))
+    ));
+    assert!(matches!(
+        cors_layer(&CorsConfig {
+            allowed_headers: Some(vec!["bad header".to_string()]),
+            ..config(&[APP_ORIGIN])
+        }),
+        Err(CorsError::Header(_))
+    ));
+    assert!(matches!(
+        cors_layer(&CorsConfig {
+            allow_credentials: Some(true),
+            ..config(&["*"])
+        }),
+        Err(CorsError::WildcardWithCredentials("allowed_origins"))
+    ));
+    assert!(matches!(
+        cors_layer(&CorsConfig {
+            allow_credentials: Some(true),
+            expose_headers: Some(vec!["*".to_string()]),
+            ..config(&[APP_ORIGIN])
+        }),
+        Err(CorsError::WildcardWithCredentials("expose_headers"))
+    ));
+}
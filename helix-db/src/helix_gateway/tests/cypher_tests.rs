@@ -0,0 +1,413 @@
+use std::sync::Arc;
+
+use crate::helix_engine::storage_core::audit_log::AuditFilter;
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, Config, GatewayConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::api_keys::{ApiKeys, AuthorizedKey};
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::cypher::{
+    CYPHER_READ_ROUTE, CYPHER_WRITE_ROUTE, cypher_handler, cypher_read, cypher_write,
+};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::jwt::JwtPrincipal;
+use crate::helix_gateway::rate_limit::{ClientAddr, RateLimiter};
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+/// People who know each other and write docs, in the schema JSON the compiler embeds
+const SCHEMA: &str = r#"{
+    "schema": {
+        "nodes": [
+            {
+                "name": "Person",
+                "properties": { "id": "ID", "label": "String", "name": "String", "age": "I64" }
+            },
+            { "name": "Doc", "properties": { "id": "ID", "label": "String", "title": "String" } }
+        ],
+        "vectors": [],
+        "edges": [
+            { "name": "KNOWS", "from": "Person", "to": "Person", "properties": { "since": "I64" } },
+            { "name": "WROTE", "from": "Person", "to": "Doc", "properties": {} }
+        ]
+    },
+    "queries": []
+}"#;
+
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    create_test_app_state_with(&GatewayConfig::default())
+}
+
+fn create_test_app_state_with(config: &GatewayConfig) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            schema: Some(SCHEMA.to_string()),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route(CYPHER_READ_ROUTE, cypher_read, false);
+    router.add_route(CYPHER_WRITE_ROUTE, cypher_write, true);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::with_config(core_setter, graph, Arc::new(router), rt, config);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: ApiKeys::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn run_cypher(
+    state: Arc<AppState>,
+    caller: Option<Caller>,
+    body: &str,
+) -> (StatusCode, sonic_rs::Value) {
+    let response = cypher_handler(
+        State(state),
+        HeaderMap::new(),
+        BearerAuth(caller),
+        ClientAddr(None),
+        Bytes::from(body.to_string()),
+    )
+    .await;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+/// Request body running `statement` with `parameters`
+fn statement(statement: &str, parameters: sonic_rs::Value) -> String {
+    sonic_rs::to_string(&sonic_rs::json!({
+        "statements": [{ "statement": statement, "parameters": parameters }]
+    }))
+    .unwrap()
+}
+
+/// Rows of the first result, each as its list of columns
+fn result_rows(body: &sonic_rs::Value) -> Vec<sonic_rs::Value> {
+    body["results"][0]["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["row"].clone())
+        .collect()
+}
+
+/// Alice and Bob know each other's friend Carol; Alice wrote a doc
+const POPULATE: &str = "\
+    CREATE (a:Person {name: 'alice', age: 30})-[:KNOWS {since: 2020}]->(c:Person {name: 'carol', age: 41}), \
+           (b:Person {name: 'bob', age: 25})-[:KNOWS]->(c), \
+           (a)-[:WROTE]->(:Doc {title: 'notes'})";
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_create_then_match() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(POPULATE, sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    assert!(body["errors"].as_array().unwrap().is_empty());
+
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(
+            "MATCH (p:Person)-[k:KNOWS]->(friend:Person {name: $name}) \
+             WHERE p.age > 20 AND NOT p.name STARTS WITH 'z' \
+             RETURN p.name AS name, type(k), friend.age ORDER BY name",
+            sonic_rs::json!({ "name": "carol" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    let columns = body["results"][0]["columns"].as_array().unwrap();
+    let columns: Vec<&str> = columns.iter().map(|c| c.as_str().unwrap()).collect();
+    assert_eq!(columns, ["name", "type(k)", "friend.age"]);
+    let rows = result_rows(&body);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][0].as_str(), Some("alice"));
+    assert_eq!(rows[0][1].as_str(), Some("KNOWS"));
+    assert_eq!(rows[0][2].as_i64(), Some(41));
+    assert_eq!(rows[1][0].as_str(), Some("bob"));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_writes_are_audited() {
+    let (state, _dir) = create_test_app_state_with(&GatewayConfig {
+        audit_log: Some(true),
+        ..Default::default()
+    });
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(POPULATE, sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement("MATCH (p:Person) RETURN p.name", sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+
+    let entries = state
+        .worker_pool
+        .graph()
+        .storage
+        .audit_entries(&AuditFilter::default())
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].query, CYPHER_WRITE_ROUTE);
+    // Four nodes and three relationships
+    assert_eq!(entries[0].item_ids.len(), 7);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_returns_nodes_aggregates_and_pages() {
+    let (state, _dir) = create_test_app_state();
+    run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(POPULATE, sonic_rs::json!({})),
+    )
+    .await;
+
+    // Undirected relationships match both ways; counts group by the other columns
+    let (_, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(
+            "MATCH (p:Person)-[:KNOWS]-(other) \
+             RETURN p.name AS name, count(*) AS friends ORDER BY friends DESC, name LIMIT $n",
+            sonic_rs::json!({ "n": 2 }),
+        ),
+    )
+    .await;
+    let rows = result_rows(&body);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0][0].as_str(), Some("carol"));
+    assert_eq!(rows[0][1].as_i64(), Some(2));
+    assert_eq!(rows[1][0].as_str(), Some("alice"));
+    assert_eq!(rows[1][1].as_i64(), Some(1));
+
+    let (_, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(
+            "MATCH (d:Doc)<-[:WROTE]-(author) RETURN d, labels(author), id(author) IS NULL",
+            sonic_rs::json!({}),
+        ),
+    )
+    .await;
+    let rows = result_rows(&body);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0]["label"].as_str(), Some("Doc"));
+    assert_eq!(rows[0][0]["title"].as_str(), Some("notes"));
+    assert!(rows[0][0]["id"].as_str().is_some());
+    assert_eq!(rows[0][1][0].as_str(), Some("Person"));
+    assert_eq!(rows[0][2].as_bool(), Some(false));
+
+    let (_, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(
+            "MATCH (n) RETURN DISTINCT labels(n) AS label ORDER BY label SKIP 1",
+            sonic_rs::json!({}),
+        ),
+    )
+    .await;
+    let rows = result_rows(&body);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0][0].as_str(), Some("Person"));
+
+    let (_, body) = run_cypher(
+        state,
+        None,
+        &statement("MATCH (n:Missing) RETURN count(n)", sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(result_rows(&body)[0][0].as_i64(), Some(0));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_match_then_create_links_existing_nodes() {
+    let (state, _dir) = create_test_app_state();
+    run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(POPULATE, sonic_rs::json!({})),
+    )
+    .await;
+
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(
+            "MATCH (a:Person {name: 'alice'}), (b:Person {name: 'bob'}) \
+             CREATE (b)-[r:KNOWS]->(a) RETURN type(r)",
+            sonic_rs::json!({}),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    assert_eq!(result_rows(&body)[0][0].as_str(), Some("KNOWS"));
+
+    let (_, body) = run_cypher(
+        state,
+        None,
+        &statement(
+            "MATCH (:Person {name: 'bob'})-[:KNOWS]->(p) RETURN p.name ORDER BY p.name",
+            sonic_rs::json!({}),
+        ),
+    )
+    .await;
+    let names: Vec<String> = result_rows(&body)
+        .iter()
+        .map(|row| row[0].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["alice", "carol"]);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_invalid_requests_are_rejected() {
+    let (state, _dir) = create_test_app_state();
+    for body in [
+        "not json".to_string(),
+        statement("MATCH (n RETURN n", sonic_rs::json!({})),
+        statement("MATCH (n) RETURN m", sonic_rs::json!({})),
+        statement("MATCH (n) WHERE n.age > $min RETURN n", sonic_rs::json!({})),
+    ] {
+        let (status, body) = run_cypher(Arc::clone(&state), None, &body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body:?}");
+        assert_eq!(body["code"].as_str(), Some("R106"));
+    }
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_creates_keep_to_the_schema() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_cypher(
+        Arc::clone(&state),
+        None,
+        &statement(POPULATE, sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+
+    for (create, error) in [
+        ("CREATE (:Robot {name: 'hal'})", "`Robot` isn't a node type"),
+        (
+            "CREATE (:Person {nickname: 'al'})",
+            "`Person` has no property `nickname`",
+        ),
+        (
+            "CREATE (:Person {id: 'x'})",
+            "`Person` has no property `id`",
+        ),
+        (
+            "CREATE (:Person {age: 'old'})",
+            "`Person.age` holds I64, not String",
+        ),
+        (
+            "MATCH (p:Person {name: 'alice'}) CREATE (p)-[:LIKES]->(p)",
+            "`LIKES` isn't a relationship type",
+        ),
+        (
+            "MATCH (d:Doc) CREATE (d)-[:WROTE]->(:Person {name: 'dan'})",
+            "`WROTE` relationships go from `Person` to `Doc`, not from `Doc` to `Person`",
+        ),
+        (
+            "MATCH (p:Person {name: 'bob'}) CREATE (p)-[:KNOWS {since: 'ages'}]->(p)",
+            "`KNOWS.since` holds I64, not String",
+        ),
+    ] {
+        let (status, body) = run_cypher(
+            Arc::clone(&state),
+            None,
+            &statement(create, sonic_rs::json!({})),
+        )
+        .await;
+        assert_ne!(status, StatusCode::OK, "{create}");
+        let message = body["message"].as_str().unwrap_or_default();
+        assert!(message.contains(error), "{create}: {body:?}");
+    }
+
+    let (_, body) = run_cypher(
+        state,
+        None,
+        &statement("MATCH (n) RETURN count(*)", sonic_rs::json!({})),
+    )
+    .await;
+    assert_eq!(result_rows(&body)[0][0].as_i64(), Some(4));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_only_admins_may_call() {
+    let (state, _dir) = create_test_app_state();
+    let key = |scope| {
+        Some(Caller::ApiKey(AuthorizedKey {
+            name: "dashboard".to_string(),
+            scope,
+        }))
+    };
+    let token = |roles: &[&str]| {
+        Some(Caller::Jwt(JwtPrincipal {
+            subject: Some("ada".to_string()),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+        }))
+    };
+    let count = statement("MATCH (p:Person) RETURN count(*)", sonic_rs::json!({}));
+
+    for caller in [
+        key(ApiKeyScope::ReadOnly),
+        key(ApiKeyScope::ReadWrite),
+        token(&["writer"]),
+    ] {
+        let (status, body) = run_cypher(Arc::clone(&state), caller, &count).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{body:?}");
+        assert_eq!(body["code"].as_str(), Some("R206"));
+    }
+
+    for caller in [key(ApiKeyScope::Admin), token(&["admin"])] {
+        let (status, body) = run_cypher(Arc::clone(&state), caller, &count).await;
+        assert_eq!(status, StatusCode::OK, "{body:?}");
+        assert_eq!(result_rows(&body)[0][0].as_i64(), Some(0));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::helix_engine::traversal_core::config::{Config, EgoCacheConfig, GatewayConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::ego_cache::invalidate_on_writes;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::{Request, RequestType};
+use crate::protocol::{Format, Response};
+use crate::utils::label_hash::hash_label;
+use axum::body::Bytes;
+use tempfile::TempDir;
+
+/// App state caching `Knows` neighborhoods, with a `follow` write on the `Knows` label and a
+/// `touch_other` write on the `Other` label
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            gateway_config: Some(GatewayConfig {
+                ego_cache: Some(vec![EgoCacheConfig {
+                    edge: "Knows".to_string(),
+                    max_depth: None,
+                    max_entries: None,
+                }]),
+                ..Default::default()
+            }),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    for name in ["follow", "touch_other"] {
+        routes.insert(
+            name.to_string(),
+            Arc::new(|_| {
+                Ok(Response {
+                    body: b"null".to_vec(),
+                    fmt: Format::Json,
+                })
+            }),
+        );
+    }
+    let write_routes = HashSet::from(["follow".to_string(), "touch_other".to_string()]);
+    let mut router = HelixRouter::new(Some(routes), None, Some(write_routes));
+    router.route_labels = HashMap::from([
+        ("follow".to_string(), vec!["Knows".to_string()]),
+        ("touch_other".to_string(), vec!["Other".to_string()]),
+    ]);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, Arc::new(router), rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn write(state: &AppState, name: &str) {
+    let req = Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    };
+    state.worker_pool.process(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_writes_to_a_cached_edge_type_drop_its_neighborhoods() {
+    let (state, _dir) = create_test_app_state();
+    let storage = Arc::clone(&state.worker_pool.graph().storage);
+    assert!(storage.ego_cache.is_enabled());
+    let run = tokio::spawn(invalidate_on_writes(Arc::clone(&state)));
+    // Let the task subscribe before writing
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let knows = hash_label("Knows", None);
+    let txn_id = storage.graph_env.read_txn().unwrap().id();
+    storage
+        .ego_cache
+        .insert(&knows, 1, 2, txn_id, Arc::from([2, 3]));
+
+    write(&state, "touch_other").await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(storage.ego_cache.len(), 1);
+
+    write(&state, "follow").await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !storage.ego_cache.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("neighborhoods should have been dropped");
+
+    state.worker_pool.begin_shutdown();
+    tokio::time::timeout(Duration::from_secs(5), run)
+        .await
+        .expect("invalidation should stop once the pool is shutting down")
+        .unwrap();
+}
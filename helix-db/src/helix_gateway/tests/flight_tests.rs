@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::{ApiKeyConfig, ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::api_keys::{ApiKeys, hash_api_key};
+use crate::helix_gateway::flight::{FLIGHT_ROUTE, flight_handler};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+use arrow_array::RecordBatch;
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int64Type;
+use arrow_flight::error::FlightError;
+use arrow_flight::{FlightClient, FlightDescriptor, Ticket};
+use arrow_schema::DataType;
+use axum::routing::post;
+use futures_util::TryStreamExt;
+use tempfile::TempDir;
+use tonic::Code;
+use tonic::transport::Channel;
+
+/// Echoes its parameters back under `params`
+fn echo(input: HandlerInput) -> Result<Response, GraphError> {
+    let mut body = br#"{"params":"#.to_vec();
+    body.extend_from_slice(&input.request.body);
+    body.push(b'}');
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+fn list(_input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: br#"{"users":[{"name":"alice","age":31},{"name":"bob","age":27}]}"#.to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn failing(_input: HandlerInput) -> Result<Response, GraphError> {
+    Err(GraphError::New("handler error".to_string()))
+}
+
+fn create_test_app_state(api_keys: ApiKeys) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert("echo".to_string(), Arc::new(echo));
+    routes.insert("list".to_string(), Arc::new(list));
+    routes.insert("failing".to_string(), Arc::new(failing));
+    routes.insert("remove".to_string(), Arc::new(list));
+    let write_routes = HashSet::from(["remove".to_string()]);
+    let router = Arc::new(HelixRouter::new(Some(routes), None, Some(write_routes)));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys,
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+/// Serve the Flight route on a local port and connect a client to it
+async fn client(state: Arc<AppState>) -> FlightClient {
+    let app = axum::Router::new()
+        .route(FLIGHT_ROUTE, post(flight_handler))
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    FlightClient::new(channel)
+}
+
+async fn do_get(client: &mut FlightClient, ticket: &str) -> Result<Vec<RecordBatch>, FlightError> {
+    client
+        .do_get(Ticket::new(ticket.to_string()))
+        .await?
+        .try_collect()
+        .await
+}
+
+fn status_code(result: Result<Vec<RecordBatch>, FlightError>) -> Code {
+    match result {
+        Err(FlightError::Tonic(status)) => status.code(),
+        other => panic!("expected a gRPC error, got {other:?}"),
+    }
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_do_get_streams_result_as_record_batches() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let mut client = client(state).await;
+
+    let batches = do_get(&mut client, r#"{"query": "list"}"#).await.unwrap();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.schema().field(0).name(), "name");
+    assert_eq!(batch.schema().field(1).data_type(), &DataType::Int64);
+    let names = batch.column(0).as_string::<i32>();
+    assert_eq!(names.value(1), "bob");
+    assert_eq!(
+        batch.column(1).as_primitive::<Int64Type>().values(),
+        &[31, 27]
+    );
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_flight_info_endpoint_runs_the_command() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let mut client = client(state).await;
+
+    let command = r#"{"query": "echo", "params": {"limit": 5}}"#;
+    let info = client
+        .get_flight_info(FlightDescriptor::new_cmd(command))
+        .await
+        .unwrap();
+    let ticket = info.endpoint[0].ticket.clone().unwrap();
+    let batches: Vec<RecordBatch> = client
+        .do_get(ticket)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let params = batches[0].column(0).as_string::<i32>();
+    assert_eq!(params.value(0), r#"{"limit":5}"#);
+
+    let invalid = client
+        .get_flight_info(FlightDescriptor::new_cmd("not json"))
+        .await;
+    assert!(matches!(invalid, Err(FlightError::Tonic(s)) if s.code() == Code::InvalidArgument));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_do_get_errors_map_to_grpc_status() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let mut client = client(state).await;
+
+    let result = do_get(&mut client, r#"{"query": "failing"}"#).await;
+    assert_eq!(status_code(result), Code::Internal);
+    let result = do_get(&mut client, r#"{"query": "missing"}"#).await;
+    assert_eq!(status_code(result), Code::NotFound);
+    let result = do_get(&mut client, r#"{"query": "remove"}"#).await;
+    assert_eq!(status_code(result), Code::InvalidArgument);
+    let result = do_get(&mut client, r#"{"params": {}}"#).await;
+    assert_eq!(status_code(result), Code::InvalidArgument);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_do_get_is_authorized() {
+    let keys = ApiKeys::from_config(&[ApiKeyConfig {
+        name: "notebook".to_string(),
+        key_hash: hash_api_key("secret"),
+        scope: ApiKeyScope::ReadOnly,
+    }]);
+    let (state, _dir) = create_test_app_state(keys);
+    let mut client = client(state).await;
+
+    let result = do_get(&mut client, r#"{"query": "list"}"#).await;
+    assert_eq!(status_code(result), Code::Unauthenticated);
+
+    client.add_header("authorization", "Bearer secret").unwrap();
+    let batches = do_get(&mut client, r#"{"query": "list"}"#).await.unwrap();
+    assert_eq!(batches[0].num_rows(), 2);
+}
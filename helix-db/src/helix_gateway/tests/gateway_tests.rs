@@ -1,12 +1,15 @@
 use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
 use crate::helix_gateway::gateway::{AppState, CoreSetter, GatewayOpts, HelixGateway};
+use crate::helix_gateway::otel::{set_remote_parent, traces_endpoint};
+use crate::helix_gateway::prometheus_metrics::render_metrics;
+use crate::helix_gateway::rate_limit::RateLimiter;
 use crate::helix_gateway::router::router::HelixRouter;
 use crate::helix_gateway::worker_pool::WorkerPool;
 use core_affinity::CoreId;
 use std::sync::atomic;
 use std::{collections::HashMap, sync::Arc};
 
-use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::config::{Config, GatewayConfig};
 use tempfile::TempDir;
 
 fn create_test_graph() -> (Arc<HelixGraphEngine>, TempDir) {
@@ -155,6 +158,9 @@ fn test_app_state_creation() {
         worker_pool,
         schema_json: None,
         cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
     };
 
     assert!(state.schema_json.is_none());
@@ -181,6 +187,9 @@ fn test_app_state_with_schema() {
         worker_pool,
         schema_json: Some("{\"schema\": \"test\"}".to_string()),
         cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
     };
 
     assert!(state.schema_json.is_some());
@@ -207,12 +216,105 @@ fn test_app_state_with_cluster_id() {
         worker_pool,
         schema_json: None,
         cluster_id: Some("cluster-456".to_string()),
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
     };
 
     assert!(state.cluster_id.is_some());
     assert_eq!(state.cluster_id.unwrap(), "cluster-456");
 }
 
+#[test]
+fn test_prometheus_metrics_render() {
+    let (graph, _temp_dir) = create_test_graph();
+    let router = Arc::new(HelixRouter::new(None, None, None));
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+
+    let cores = core_affinity::get_core_ids().unwrap_or_default();
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+    let state = AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    };
+
+    let out = render_metrics(&state);
+    assert!(out.contains("# TYPE helix_read_queue_depth gauge\nhelix_read_queue_depth 0\n"));
+    assert!(out.contains("# TYPE helix_cache_hits_total counter\n"));
+    assert!(out.contains("\nhelix_lmdb_map_size_bytes "));
+    assert!(out.contains("# TYPE helix_hnsw_search_duration_seconds histogram\n"));
+}
+
+// ============================================================================
+// Tracing Tests
+// ============================================================================
+
+#[test]
+fn test_otlp_traces_endpoint() {
+    assert_eq!(
+        traces_endpoint("http://collector:4318"),
+        "http://collector:4318/v1/traces"
+    );
+    assert_eq!(
+        traces_endpoint("http://collector:4318/"),
+        "http://collector:4318/v1/traces"
+    );
+    assert_eq!(
+        traces_endpoint("http://collector:4318/v1/traces"),
+        "http://collector:4318/v1/traces"
+    );
+}
+
+#[test]
+fn test_traceparent_becomes_span_parent() {
+    use opentelemetry::trace::{TraceContextExt, TracerProvider};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+    let mut headers = axum::http::HeaderMap::new();
+    headers.insert(
+        "traceparent",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+            .parse()
+            .unwrap(),
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("helix.request");
+        set_remote_parent(&span, &headers);
+        let cx = span.context();
+        let span_context = cx.span().span_context().clone();
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_ne!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+
+        // Without a traceparent the request starts its own trace
+        let fresh = tracing::info_span!("helix.request");
+        set_remote_parent(&fresh, &axum::http::HeaderMap::new());
+        assert_ne!(
+            fresh.context().span().span_context().trace_id(),
+            span_context.trace_id()
+        );
+    });
+}
+
 // ============================================================================
 // CoreSetter Tests
 // ============================================================================
@@ -339,6 +441,62 @@ fn test_gateway_opts_default_workers_per_core() {
     assert_eq!(GatewayOpts::DEFAULT_WORKERS_PER_CORE, 8);
 }
 
+#[test]
+fn test_gateway_config_defaults() {
+    let config = Config::default().gateway_config();
+    assert_eq!(
+        config.queue_capacity(),
+        GatewayConfig::DEFAULT_QUEUE_CAPACITY
+    );
+    assert!(config.shed_on_overload());
+    assert_eq!(
+        config.retry_after_secs(),
+        GatewayConfig::DEFAULT_RETRY_AFTER_SECS
+    );
+}
+
+#[test]
+fn test_gateway_config_parsed_from_instance_config() {
+    let config: Config =
+        sonic_rs::from_str(r#"{"gateway_config": {"workers_per_core": 4, "queue_capacity": 0}}"#)
+            .unwrap();
+    let gateway_config = config.gateway_config();
+
+    assert_eq!(gateway_config.workers_per_core, Some(4));
+    // A zero-capacity queue would reject everything, so it is clamped
+    assert_eq!(gateway_config.queue_capacity(), 1);
+}
+
+#[test]
+fn test_gateway_config_partitions() {
+    assert_eq!(GatewayConfig::default().partitions(), 1);
+    let gateway_config = GatewayConfig {
+        partitions: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(gateway_config.partitions(), 1);
+}
+
+#[test]
+fn test_gateway_config_embedded_in_generated_config() {
+    let config = Config {
+        gateway_config: Some(GatewayConfig {
+            queue_capacity: Some(64),
+            ..Default::default()
+        }),
+        ..Config::default()
+    };
+    let generated = config.to_string();
+
+    assert!(generated.contains(r#"gateway_config: sonic_rs::from_str(r#"{"#));
+    assert!(generated.contains(r#""queue_capacity":64"#));
+    assert!(
+        Config::default()
+            .to_string()
+            .contains("gateway_config: None,")
+    );
+}
+
 // ============================================================================
 // API Key Verification Integration Tests
 // ============================================================================
@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::{add_e::AddEAdapter, add_n::AddNAdapter};
+use crate::helix_engine::traversal_core::ops::vectors::insert::InsertVAdapter;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::api_keys::{ApiKeys, AuthorizedKey};
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::graphql::{GraphqlSchema, graphql_handler};
+use crate::helix_gateway::rate_limit::{ClientAddr, RateLimiter};
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::value::Value;
+use crate::utils::id::uuid_str;
+use crate::utils::properties::ImmutablePropertiesMap;
+use axum::Extension;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use bumpalo::Bump;
+use heed3::RoTxn;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+/// Schema JSON as the compiler embeds it in the container
+const SCHEMA_JSON: &str = r#"{
+    "schema": {
+        "nodes": [
+            {"name": "Person", "properties": {"name": "String", "age": "U32", "tags": "Array(String)"}},
+            {"name": "Doc", "properties": {"title": "String"}}
+        ],
+        "vectors": [
+            {"name": "Embedding", "properties": {"source": "String"}}
+        ],
+        "edges": [
+            {"name": "Knows", "from": "Person", "to": "Person", "properties": {}},
+            {"name": "Wrote", "from": "Person", "to": "Doc", "properties": {}},
+            {"name": "EmbeddingOf", "from": "Doc", "to": "Embedding", "properties": {}}
+        ]
+    },
+    "queries": []
+}"#;
+
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let router = Arc::new(HelixRouter::new(None, None, None));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: Some(SCHEMA_JSON.to_string()),
+        cluster_id: None,
+        api_keys: ApiKeys::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+struct Ids {
+    alice: String,
+    bob: String,
+    doc: String,
+}
+
+/// Alice knows Bob and wrote a doc, which has an embedding next to an unrelated one
+fn populate(state: &AppState) -> Ids {
+    let storage = state.worker_pool.graph().storage.as_ref();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+
+    let person = |name: &'static str, age: u32, txn: &mut _| {
+        G::new_mut(storage, &arena, txn)
+            .add_n(
+                "Person",
+                Some(ImmutablePropertiesMap::new(
+                    3,
+                    [
+                        ("name", Value::from(name)),
+                        ("age", Value::from(age)),
+                        ("tags", Value::Array(vec![Value::from("admin")])),
+                    ]
+                    .into_iter(),
+                    &arena,
+                )),
+                None,
+            )
+            .collect_to_obj()
+            .unwrap()
+            .id()
+    };
+    let alice = person("alice", 30, &mut txn);
+    let bob = person("bob", 25, &mut txn);
+    let doc = G::new_mut(storage, &arena, &mut txn)
+        .add_n(
+            "Doc",
+            Some(ImmutablePropertiesMap::new(
+                1,
+                [("title", Value::from("notes"))].into_iter(),
+                &arena,
+            )),
+            None,
+        )
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    let embedding = G::new_mut(storage, &arena, &mut txn)
+        .insert_v::<Filter>(
+            &[1.0, 0.0, 0.0],
+            "Embedding",
+            Some(ImmutablePropertiesMap::new(
+                1,
+                [("source", Value::from("notes"))].into_iter(),
+                &arena,
+            )),
+        )
+        .collect_to_obj()
+        .unwrap()
+        .id();
+    G::new_mut(storage, &arena, &mut txn)
+        .insert_v::<Filter>(&[0.0, 1.0, 0.0], "Embedding", None)
+        .collect_to_obj()
+        .unwrap();
+
+    for (label, from, to) in [
+        ("Knows", alice, bob),
+        ("Wrote", alice, doc),
+        ("EmbeddingOf", doc, embedding),
+    ] {
+        G::new_mut(storage, &arena, &mut txn)
+            .add_edge(label, None, from, to, false, false)
+            .collect_to_obj()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    Ids {
+        alice: uuid_str(alice, &arena).to_string(),
+        bob: uuid_str(bob, &arena).to_string(),
+        doc: uuid_str(doc, &arena).to_string(),
+    }
+}
+
+async fn run_graphql(
+    state: Arc<AppState>,
+    caller: Option<Caller>,
+    body: &str,
+) -> (StatusCode, sonic_rs::Value) {
+    let schema = Arc::new(GraphqlSchema::from_schema_json(SCHEMA_JSON).unwrap());
+    let response = graphql_handler(
+        State(state),
+        Extension(schema),
+        HeaderMap::new(),
+        BearerAuth(caller),
+        ClientAddr(None),
+        Bytes::from(body.to_string()),
+    )
+    .await;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+fn query(query: &str) -> String {
+    sonic_rs::to_string(&sonic_rs::json!({ "query": query })).unwrap()
+}
+
+#[test]
+fn test_schema_is_generated_from_types() {
+    let sdl = GraphqlSchema::from_schema_json(SCHEMA_JSON).unwrap().sdl();
+
+    assert!(sdl.contains("type Person {"), "{sdl}");
+    assert!(sdl.contains("id: ID!"));
+    assert!(sdl.contains("age: Long"));
+    assert!(sdl.contains("tags: [String]"));
+    assert!(sdl.contains("outKnows(limit: Int): [Person!]!"));
+    assert!(sdl.contains("inKnows(limit: Int): [Person!]!"));
+    assert!(sdl.contains("outWrote(limit: Int): [Doc!]!"));
+    assert!(sdl.contains("inWrote(limit: Int): [Person!]!"));
+    assert!(sdl.contains("outEmbeddingOf(limit: Int): [Embedding!]!"));
+    assert!(sdl.contains("distance: Float"));
+    assert!(sdl.contains("person(id: ID!): Person"));
+    assert!(sdl.contains("personList(limit: Int, offset: Int): [Person!]!"));
+    assert!(sdl.contains("searchEmbedding(vector: [Float!]!, k: Int = 10): [Embedding!]!"));
+    // Only vector types can be searched
+    assert!(!sdl.contains("searchPerson"));
+}
+
+#[test]
+fn test_invalid_schema_json_is_rejected() {
+    assert!(GraphqlSchema::from_schema_json("{").is_err());
+    assert!(GraphqlSchema::from_schema_json(r#"{"queries": []}"#).is_err());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_reads_nodes_and_relations() {
+    let (state, _dir) = create_test_app_state();
+    let ids = populate(&state);
+
+    let body = query(&format!(
+        r#"{{
+            person(id: "{}") {{
+                id name age tags
+                outKnows {{ name inKnows {{ name }} }}
+                outWrote {{ title inWrote {{ id }} outEmbeddingOf {{ source }} }}
+            }}
+        }}"#,
+        ids.alice
+    ));
+    let (status, body) = run_graphql(Arc::clone(&state), None, &body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("errors").is_none(), "{body:?}");
+
+    let alice = &body["data"]["person"];
+    assert_eq!(alice["id"].as_str(), Some(ids.alice.as_str()));
+    assert_eq!(alice["name"].as_str(), Some("alice"));
+    assert_eq!(alice["age"].as_u64(), Some(30));
+    assert_eq!(alice["tags"][0].as_str(), Some("admin"));
+    assert_eq!(alice["outKnows"][0]["name"].as_str(), Some("bob"));
+    assert_eq!(
+        alice["outKnows"][0]["inKnows"][0]["name"].as_str(),
+        Some("alice")
+    );
+    let doc = &alice["outWrote"][0];
+    assert_eq!(doc["title"].as_str(), Some("notes"));
+    assert_eq!(doc["inWrote"][0]["id"].as_str(), Some(ids.alice.as_str()));
+    assert_eq!(doc["outEmbeddingOf"][0]["source"].as_str(), Some("notes"));
+
+    let body = query(&format!(
+        r#"{{
+            doc(id: "{}") {{ title }}
+            notADoc: doc(id: "{}") {{ title }}
+            personList(limit: 5) {{ name }}
+            skipped: personList(offset: 1) {{ name }}
+        }}"#,
+        ids.doc, ids.bob
+    ));
+    let (_, body) = run_graphql(state, None, &body).await;
+    assert!(body.get("errors").is_none(), "{body:?}");
+    assert_eq!(body["data"]["doc"]["title"].as_str(), Some("notes"));
+    // An id of another type isn't found
+    assert!(body["data"]["notADoc"].is_null());
+    assert_eq!(body["data"]["personList"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["skipped"].as_array().unwrap().len(), 1);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_searches_vectors() {
+    let (state, _dir) = create_test_app_state();
+    populate(&state);
+
+    let body = query(r#"{ searchEmbedding(vector: [0.9, 0.1, 0.0], k: 1) { source distance } }"#);
+    let (status, body) = run_graphql(state, None, &body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("errors").is_none(), "{body:?}");
+
+    let results = body["data"]["searchEmbedding"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["source"].as_str(), Some("notes"));
+    assert!(results[0]["distance"].as_f64().is_some());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_field_errors_are_reported_in_the_response() {
+    let (state, _dir) = create_test_app_state();
+    let (status, body) = run_graphql(
+        Arc::clone(&state),
+        None,
+        &query(r#"{ person(id: "not-an-id") { name } }"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["data"]["person"].is_null());
+    let message = body["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("Invalid id"), "{message}");
+
+    let (_, body) = run_graphql(Arc::clone(&state), None, &query("{ missing }")).await;
+    assert!(body["errors"].as_array().is_some_and(|e| !e.is_empty()));
+
+    let (status, body) = run_graphql(state, None, "not json").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"].as_str(), Some("R105"));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_read_only_keys_can_query() {
+    let (state, _dir) = create_test_app_state();
+    let read_only = Caller::ApiKey(AuthorizedKey {
+        name: "dashboard".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+    });
+    let (status, body) =
+        run_graphql(state, Some(read_only), &query("{ personList { name } }")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("errors").is_none(), "{body:?}");
+    assert!(body["data"]["personList"].as_array().unwrap().is_empty());
+}
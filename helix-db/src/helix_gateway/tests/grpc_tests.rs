@@ -0,0 +1,310 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::{ApiKeyConfig, ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::api_keys::{ApiKeys, hash_api_key};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::grpc::{ERROR_CODE_METADATA, GrpcSchema, grpc_handler};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::helixc::generator::proto::ProtoFile;
+use crate::helixc::generator::queries::{Parameter, Query};
+use crate::helixc::generator::utils::{GeneratedType, RustType};
+use crate::protocol::{Format, Response};
+use axum::Extension;
+use axum::body::Body;
+use axum::http::Request;
+use axum::routing::post;
+use http_body_util::BodyExt;
+use prost::Message;
+use prost_reflect::{DynamicMessage, MessageDescriptor, Value};
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+use tonic::{Code, Status};
+use tower::ServiceExt;
+
+/// Echoes its parameters back under `params`
+fn echo(input: HandlerInput) -> Result<Response, GraphError> {
+    let mut body = br#"{"params":"#.to_vec();
+    body.extend_from_slice(&input.request.body);
+    body.push(b'}');
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+fn list(_input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: br#"{"items":[{"n":1},{"n":2}],"total":2}"#.to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn failing(_input: HandlerInput) -> Result<Response, GraphError> {
+    Err(GraphError::New("handler error".to_string()))
+}
+
+fn parameter(name: &str, field_type: GeneratedType, is_optional: bool) -> Parameter {
+    Parameter {
+        name: name.to_string(),
+        field_type,
+        is_optional,
+    }
+}
+
+/// Service definition as the compiler would emit it for the test routes
+fn schema() -> GrpcSchema {
+    let queries = vec![
+        Query {
+            name: "echo".to_string(),
+            parameters: vec![
+                parameter("name", GeneratedType::RustType(RustType::String), false),
+                parameter("age", GeneratedType::RustType(RustType::U32), false),
+                parameter("big", GeneratedType::RustType(RustType::I64), false),
+                parameter("nickname", GeneratedType::RustType(RustType::String), true),
+                parameter(
+                    "tags",
+                    GeneratedType::Vec(Box::new(GeneratedType::RustType(RustType::String))),
+                    false,
+                ),
+            ],
+            ..Query::default()
+        },
+        Query {
+            name: "list".to_string(),
+            ..Query::default()
+        },
+        Query {
+            name: "failing".to_string(),
+            ..Query::default()
+        },
+        Query {
+            name: "remove".to_string(),
+            ..Query::default()
+        },
+    ];
+    GrpcSchema::from_proto(&ProtoFile { queries: &queries }.to_string()).unwrap()
+}
+
+fn create_test_app_state(api_keys: ApiKeys) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert("echo".to_string(), Arc::new(echo));
+    routes.insert("list".to_string(), Arc::new(list));
+    routes.insert("failing".to_string(), Arc::new(failing));
+    routes.insert("remove".to_string(), Arc::new(list));
+    let write_routes = HashSet::from(["remove".to_string()]);
+    let router = Arc::new(HelixRouter::new(Some(routes), None, Some(write_routes)));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys,
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+struct GrpcReply {
+    status: Status,
+    messages: Vec<DynamicMessage>,
+}
+
+/// Call `method` through the gateway route, framing the request the way gRPC clients do
+async fn call(
+    state: Arc<AppState>,
+    schema: &Arc<GrpcSchema>,
+    method: &str,
+    params: DynamicMessage,
+    token: Option<&str>,
+) -> GrpcReply {
+    let method_desc = schema.service().methods().find(|m| m.name() == method);
+    let output = method_desc.map(|m| m.output());
+
+    let payload = params.encode_to_vec();
+    let mut body = vec![0];
+    body.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    body.extend_from_slice(&payload);
+    let mut request = Request::post(format!("/helix.queries.HelixQueries/{method}"))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    let request = request.body(Body::from(body)).unwrap();
+
+    let app = axum::Router::new()
+        .route(&GrpcSchema::route(), post(grpc_handler))
+        .layer(Extension(Arc::clone(schema)))
+        .with_state(state);
+    let response = app.oneshot(request).await.unwrap();
+    let headers = response.headers().clone();
+    let collected = response.into_body().collect().await.unwrap();
+    // Errors before any message are sent as headers only, the rest in trailers
+    let status_headers = collected.trailers().cloned().unwrap_or(headers);
+    let data = collected.to_bytes();
+
+    let mut messages = Vec::new();
+    let mut rest = &data[..];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest[1..5].try_into().unwrap()) as usize;
+        let output: MessageDescriptor = output.clone().expect("messages need a known method");
+        messages.push(DynamicMessage::decode(output, &rest[5..5 + len]).unwrap());
+        rest = &rest[5 + len..];
+    }
+    let status =
+        Status::from_header_map(&status_headers).expect("response should carry a grpc-status");
+    GrpcReply { status, messages }
+}
+
+fn request(schema: &GrpcSchema, method: &str) -> DynamicMessage {
+    let method = schema
+        .service()
+        .methods()
+        .find(|m| m.name() == method)
+        .unwrap();
+    DynamicMessage::new(method.input())
+}
+
+fn error_code(status: &Status) -> Option<&str> {
+    status
+        .metadata()
+        .get(ERROR_CODE_METADATA)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// A returned `google.protobuf.Struct` as JSON
+fn json(message: &DynamicMessage) -> sonic_rs::Value {
+    sonic_rs::from_str(&sonic_rs::to_string(message).unwrap()).unwrap()
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_unary_call_passes_parameters_as_json() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let schema = Arc::new(schema());
+    let mut params = request(&schema, "echo");
+    params.set_field_by_name("name", Value::String("alice".to_string()));
+    params.set_field_by_name("age", Value::U32(30));
+    params.set_field_by_name("big", Value::I64(1 << 40));
+    params.set_field_by_name("tags", Value::List(vec![Value::String("a".to_string())]));
+
+    let reply = call(state, &schema, "echo", params, None).await;
+    assert_eq!(reply.status.code(), Code::Ok, "{}", reply.status.message());
+    assert_eq!(reply.messages.len(), 1);
+
+    let result = json(&reply.messages[0]);
+    let params = &result["params"];
+    assert_eq!(params["name"].as_str(), Some("alice"));
+    // Struct numbers are doubles
+    assert_eq!(params["age"].as_f64(), Some(30.0));
+    assert_eq!(params["big"].as_f64(), Some((1u64 << 40) as f64));
+    assert_eq!(params["tags"][0].as_str(), Some("a"));
+    // Unset optional parameters are left out rather than sent as defaults
+    assert!(params.get("nickname").is_none());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_stream_call_sends_returned_elements() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let schema = Arc::new(schema());
+    let params = request(&schema, "listStream");
+
+    let reply = call(state, &schema, "listStream", params, None).await;
+    assert_eq!(reply.status.code(), Code::Ok, "{}", reply.status.message());
+    let items: Vec<(String, sonic_rs::Value)> = reply
+        .messages
+        .iter()
+        .map(|item| {
+            let item = json(item);
+            (
+                item["field"].as_str().unwrap().to_string(),
+                item["value"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].0, "items");
+    assert_eq!(items[0].1["n"].as_f64(), Some(1.0));
+    assert_eq!(items[1].0, "items");
+    assert_eq!(items[1].1["n"].as_f64(), Some(2.0));
+    assert_eq!(items[2].0, "total");
+    assert_eq!(items[2].1.as_f64(), Some(2.0));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_errors_map_to_grpc_status() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let schema = Arc::new(schema());
+
+    let params = request(&schema, "failing");
+    let reply = call(Arc::clone(&state), &schema, "failing", params, None).await;
+    assert_eq!(reply.status.code(), Code::Internal);
+    assert!(reply.status.message().contains("handler error"));
+    assert_eq!(error_code(&reply.status), Some("R901"));
+
+    let params = request(&schema, "list");
+    let reply = call(state, &schema, "missing", params, None).await;
+    assert_eq!(reply.status.code(), Code::Unimplemented);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_calls_are_authorized() {
+    let keys = ApiKeys::from_config(&[ApiKeyConfig {
+        name: "dashboard".to_string(),
+        key_hash: hash_api_key("secret"),
+        scope: ApiKeyScope::ReadOnly,
+    }]);
+    let (state, _dir) = create_test_app_state(keys);
+    let schema = Arc::new(schema());
+
+    let params = request(&schema, "list");
+    let reply = call(Arc::clone(&state), &schema, "list", params.clone(), None).await;
+    assert_eq!(reply.status.code(), Code::Unauthenticated);
+    assert_eq!(error_code(&reply.status), Some("R202"));
+
+    let reply = call(Arc::clone(&state), &schema, "list", params, Some("secret")).await;
+    assert_eq!(reply.status.code(), Code::Ok, "{}", reply.status.message());
+
+    let params = request(&schema, "remove");
+    let reply = call(state, &schema, "remove", params, Some("secret")).await;
+    assert_eq!(reply.status.code(), Code::PermissionDenied);
+    assert_eq!(error_code(&reply.status), Some("R204"));
+}
+
+#[test]
+fn test_invalid_proto_is_rejected() {
+    assert!(GrpcSchema::from_proto("syntax = \"proto3\"; message {").is_err());
+    // Valid protobuf, but not a definition the compiler emits
+    assert!(GrpcSchema::from_proto("syntax = \"proto3\"; package helix.queries;").is_err());
+}
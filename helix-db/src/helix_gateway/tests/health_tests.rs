@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::version_info::VersionInfo;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts, config::Config};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::health::{Readiness, healthz_handler, readyz_handler};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::RequestType;
+use crate::protocol::{Format, HelixError, Request};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::Response;
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+
+fn create_test_app_state(schema_json: Option<String>) -> (Arc<AppState>, TempDir) {
+    create_app_state_in(
+        TempDir::new().unwrap(),
+        schema_json,
+        HelixRouter::new(None, None, None),
+    )
+}
+
+fn create_app_state_in(
+    temp_dir: TempDir,
+    schema_json: Option<String>,
+    router: HelixRouter,
+) -> (Arc<AppState>, TempDir) {
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            schema: schema_json.clone(),
+            ..Config::default()
+        },
+        version_info: VersionInfo::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let router = Arc::new(router);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = core_affinity::get_core_ids().unwrap_or_default();
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn body_json(response: Response) -> sonic_rs::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    sonic_rs::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_healthz_is_ok() {
+    let response = healthz_handler().await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["Content-Type"], "application/json");
+    let body = body_json(response).await;
+    assert_eq!(body["status"].as_str(), Some("ok"));
+}
+
+#[tokio::test]
+async fn test_readyz_ready() {
+    let (state, _dir) = create_test_app_state(Some("{}".to_string()));
+    let response = readyz_handler(State(state)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = body_json(response).await;
+    for check in ["ready", "storage", "workers", "schema"] {
+        assert_eq!(body[check].as_bool(), Some(true), "{check}");
+    }
+}
+
+#[tokio::test]
+async fn test_readyz_without_schema() {
+    let (state, _dir) = create_test_app_state(None);
+    let response = readyz_handler(State(state)).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = body_json(response).await;
+    assert_eq!(body["ready"].as_bool(), Some(false));
+    assert_eq!(body["schema"].as_bool(), Some(false));
+    assert_eq!(body["storage"].as_bool(), Some(true));
+}
+
+#[tokio::test]
+async fn test_readyz_fails_once_shutdown_begins() {
+    let (state, _dir) = create_test_app_state(Some("{}".to_string()));
+    assert!(Readiness::check(&state).ready);
+
+    state.worker_pool.begin_shutdown();
+    let readiness = Readiness::check(&state);
+    assert!(!readiness.ready);
+    assert!(!readiness.workers);
+    assert!(readiness.storage);
+}
+
+#[test]
+fn test_worker_pool_reports_no_stopped_workers() {
+    let (state, _dir) = create_test_app_state(None);
+    assert_eq!(state.worker_pool.stopped_workers(), 0);
+}
+
+fn user_schema(age_type: &str) -> String {
+    format!(
+        r#"{{"schema": {{"nodes": [{{"name": "User", "properties": {{"age": "{age_type}"}}}}]}}}}"#
+    )
+}
+
+fn ok_handler(_input: HandlerInput) -> Result<crate::protocol::response::Response, GraphError> {
+    Ok(crate::protocol::response::Response {
+        body: b"{}".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+/// An instance whose data was written with `age` as an I32, now built with it as a String
+fn incompatible_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let config = Config {
+        schema: Some(user_schema("I32")),
+        ..Config::default()
+    };
+    drop(
+        HelixGraphStorage::new(
+            temp_dir.path().to_str().unwrap(),
+            config,
+            VersionInfo::default(),
+        )
+        .unwrap(),
+    );
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("add_user", ok_handler, true);
+    router.add_route("get_user", ok_handler, false);
+    create_app_state_in(temp_dir, Some(user_schema("String")), router)
+}
+
+fn request(name: &str) -> Request {
+    Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: axum::body::Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    }
+}
+
+#[tokio::test]
+async fn test_readyz_reports_incompatible_data() {
+    let (state, _dir) = incompatible_app_state();
+    let response = readyz_handler(State(state)).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = body_json(response).await;
+    assert_eq!(body["ready"].as_bool(), Some(false));
+    assert_eq!(body["schema"].as_bool(), Some(true));
+    assert_eq!(
+        body["schema_mismatch"]["reasons"][0].as_str(),
+        Some("property `age` of `User` changed from I32 to String without a migration")
+    );
+}
+
+#[tokio::test]
+async fn test_incompatible_data_refuses_writes_but_serves_reads() {
+    let (state, _dir) = incompatible_app_state();
+    let pool = &state.worker_pool;
+
+    let err = pool.process(request("add_user")).await.unwrap_err();
+    assert!(
+        matches!(err, HelixError::IncompatibleSchema { .. }),
+        "{err:?}"
+    );
+    assert_eq!(err.code().as_str(), "R404");
+    assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    assert!(pool.process(request("get_user")).await.is_ok());
+}
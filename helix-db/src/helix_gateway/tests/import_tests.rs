@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::auth::BearerAuth;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::import::{MaxLineBytes, import_handler};
+use crate::helix_gateway::rate_limit::{ClientAddr, RateLimiter};
+use crate::helix_gateway::router::router::{HandlerFn, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+use axum::Extension;
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use futures_util::stream;
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+
+const MAX_LINE: usize = 64;
+
+/// App state with an `addRow` write route recording each body it's given, failing on
+/// bodies containing `bad`
+fn create_test_app_state() -> (Arc<AppState>, Arc<Mutex<Vec<String>>>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let rows = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&rows);
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert(
+        "addRow".to_string(),
+        Arc::new(move |input| {
+            let row = String::from_utf8(input.request.body.to_vec()).unwrap();
+            if row.contains("bad") {
+                return Err(GraphError::New("bad row".to_string()));
+            }
+            recorded.lock().unwrap().push(row);
+            Ok(Response {
+                body: b"{}".to_vec(),
+                fmt: Format::Json,
+            })
+        }),
+    );
+    let write_routes = HashSet::from(["addRow".to_string()]);
+    let router = Arc::new(HelixRouter::new(Some(routes), None, Some(write_routes)));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, rows, temp_dir)
+}
+
+async fn run_import(
+    state: Arc<AppState>,
+    query: &str,
+    body: Body,
+) -> (StatusCode, sonic_rs::Value) {
+    let response = import_handler(
+        State(state),
+        Path(query.to_string()),
+        Extension(MaxLineBytes(MAX_LINE)),
+        HeaderMap::new(),
+        BearerAuth(None),
+        ClientAddr(None),
+        body,
+    )
+    .await;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_import_runs_query_per_line_in_order() {
+    let (state, rows, _dir) = create_test_app_state();
+    let body = Body::from("{\"n\": 1}\n\n{\"n\": 2}\r\n{\"n\": 3}");
+    let (status, body) = run_import(state, "addRow", body).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["imported"].as_u64(), Some(3));
+    assert_eq!(
+        *rows.lock().unwrap(),
+        vec![r#"{"n": 1}"#, "{\"n\": 2}\r", r#"{"n": 3}"#]
+    );
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_import_joins_lines_split_across_chunks() {
+    let (state, rows, _dir) = create_test_app_state();
+    let chunks = ["{\"n\"", ": 1}\n{", "\"n\": 2}\n"]
+        .map(|chunk| Ok::<_, std::io::Error>(chunk.as_bytes().to_vec()));
+    let body = Body::from_stream(stream::iter(chunks));
+    let (status, body) = run_import(state, "addRow", body).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["imported"].as_u64(), Some(2));
+    assert_eq!(*rows.lock().unwrap(), vec![r#"{"n": 1}"#, r#"{"n": 2}"#]);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_import_stops_at_first_failing_line() {
+    let (state, rows, _dir) = create_test_app_state();
+    let body = Body::from("{\"n\": 1}\n{\"n\": \"bad\"}\n{\"n\": 3}\n");
+    let (status, body) = run_import(state, "addRow", body).await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["line"].as_u64(), Some(2));
+    assert_eq!(body["imported"].as_u64(), Some(1));
+    assert_eq!(body["code"].as_str(), Some("R901"));
+    assert_eq!(*rows.lock().unwrap(), vec![r#"{"n": 1}"#]);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_import_rejects_line_over_limit() {
+    let (state, rows, _dir) = create_test_app_state();
+    let long = format!("{{\"n\": \"{}\"}}", "a".repeat(MAX_LINE));
+    let body = Body::from(format!("{{\"n\": 1}}\n{long}\n{{\"n\": 3}}\n"));
+    let (status, body) = run_import(state, "addRow", body).await;
+
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"].as_str(), Some("R108"));
+    assert_eq!(body["line"].as_u64(), Some(2));
+    assert_eq!(body["imported"].as_u64(), Some(1));
+    assert_eq!(rows.lock().unwrap().len(), 1);
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_import_of_unknown_query_is_not_found() {
+    let (state, _rows, _dir) = create_test_app_state();
+    let (status, body) = run_import(state, "missing", Body::from("{}\n")).await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"].as_str(), Some("R301"));
+}
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use crate::helix_gateway::{
-    gateway::CoreSetter, router::router::HelixRouter, worker_pool::WorkerPool,
+    gateway::CoreSetter, rate_limit::RateLimiter, router::router::HelixRouter,
+    worker_pool::WorkerPool,
 };
 use crate::{
     helix_engine::{
@@ -42,6 +43,9 @@ fn create_test_app_state(schema_json: Option<String>) -> Arc<AppState> {
         worker_pool,
         schema_json,
         cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
     })
 }
 
@@ -0,0 +1,313 @@
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, Config, JwtConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::api_keys::{ApiKeys, AuthorizedKey};
+use crate::helix_gateway::auth::{BearerAuth, Caller};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::jwt::{Claim, JwtPrincipal, JwtVerifier, roles_from_claims};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::HelixError;
+use axum::extract::FromRequestParts;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode, jwk::JwkSet};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::Serialize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+const ISSUER: &str = "https://id.example.com/";
+const AUDIENCE: &str = "helix";
+
+struct SigningKey {
+    kid: &'static str,
+    pkcs8: Vec<u8>,
+    public: Vec<u8>,
+}
+
+impl SigningKey {
+    fn generate(kid: &'static str) -> Self {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let public = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .unwrap()
+            .public_key()
+            .as_ref()
+            .to_vec();
+        SigningKey {
+            kid,
+            pkcs8: pkcs8.as_ref().to_vec(),
+            public,
+        }
+    }
+
+    fn jwks_json(&self) -> String {
+        format!(
+            r#"{{"keys":[{{"kty":"OKP","crv":"Ed25519","alg":"EdDSA","kid":"{}","x":"{}"}}]}}"#,
+            self.kid,
+            URL_SAFE_NO_PAD.encode(&self.public)
+        )
+    }
+
+    fn jwks(&self) -> JwkSet {
+        sonic_rs::from_str(&self.jwks_json()).unwrap()
+    }
+
+    fn sign(&self, claims: &Claims) -> String {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(self.kid.to_string());
+        encode(&header, claims, &EncodingKey::from_ed_der(&self.pkcs8)).unwrap()
+    }
+}
+
+#[derive(Serialize)]
+struct Claims {
+    sub: String,
+    iss: String,
+    aud: String,
+    exp: u64,
+    roles: Vec<String>,
+}
+
+fn now_secs() -> u64 {
+    jsonwebtoken::get_current_timestamp()
+}
+
+fn claims(roles: &[&str]) -> Claims {
+    Claims {
+        sub: "user-1".to_string(),
+        iss: ISSUER.to_string(),
+        aud: AUDIENCE.to_string(),
+        exp: now_secs() + 600,
+        roles: roles.iter().map(|r| r.to_string()).collect(),
+    }
+}
+
+fn jwt_config(jwks_url: &str) -> JwtConfig {
+    JwtConfig {
+        jwks_url: jwks_url.to_string(),
+        issuer: Some(ISSUER.to_string()),
+        audience: Some(AUDIENCE.to_string()),
+        roles_claim: None,
+        jwks_refresh_secs: None,
+    }
+}
+
+fn verifier(key: &SigningKey) -> JwtVerifier {
+    JwtVerifier::with_keys(jwt_config("http://127.0.0.1:1/jwks"), key.jwks())
+}
+
+#[tokio::test]
+async fn test_valid_token_yields_subject_and_roles() {
+    let key = SigningKey::generate("k1");
+    let principal = verifier(&key)
+        .verify(&key.sign(&claims(&["admin", "support"])))
+        .await
+        .unwrap();
+    assert_eq!(principal.subject.as_deref(), Some("user-1"));
+    assert_eq!(principal.roles, vec!["admin", "support"]);
+}
+
+#[tokio::test]
+async fn test_expired_token_rejected() {
+    let key = SigningKey::generate("k1");
+    let mut expired = claims(&["admin"]);
+    expired.exp = now_secs() - 600;
+    let result = verifier(&key).verify(&key.sign(&expired)).await;
+    assert!(matches!(result, Err(HelixError::InvalidToken(_))));
+}
+
+#[tokio::test]
+async fn test_wrong_issuer_or_audience_rejected() {
+    let key = SigningKey::generate("k1");
+    let verifier = verifier(&key);
+
+    let mut other_issuer = claims(&[]);
+    other_issuer.iss = "https://evil.example.com/".to_string();
+    assert!(matches!(
+        verifier.verify(&key.sign(&other_issuer)).await,
+        Err(HelixError::InvalidToken(_))
+    ));
+
+    let mut other_audience = claims(&[]);
+    other_audience.aud = "someone-else".to_string();
+    assert!(matches!(
+        verifier.verify(&key.sign(&other_audience)).await,
+        Err(HelixError::InvalidToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_token_from_other_key_rejected() {
+    let trusted = SigningKey::generate("k1");
+    let impostor = SigningKey::generate("k1");
+    let result = verifier(&trusted)
+        .verify(&impostor.sign(&claims(&["admin"])))
+        .await;
+    assert!(matches!(result, Err(HelixError::InvalidToken(_))));
+}
+
+#[tokio::test]
+async fn test_hmac_token_rejected() {
+    let key = SigningKey::generate("k1");
+    let token = encode(
+        &Header::new(Algorithm::HS256),
+        &claims(&["admin"]),
+        &EncodingKey::from_secret(&key.public),
+    )
+    .unwrap();
+    let result = verifier(&key).verify(&token).await;
+    assert!(matches!(result, Err(HelixError::InvalidToken(_))));
+    assert!(matches!(
+        verifier(&key).verify("not-a-jwt").await,
+        Err(HelixError::InvalidToken(_))
+    ));
+}
+
+#[test]
+fn test_roles_from_claims_paths() {
+    let claims: std::collections::HashMap<String, Claim> = sonic_rs::from_str(
+        r#"{"realm_access":{"roles":["admin","viewer"]},"scope":"read write","roles":7}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        roles_from_claims(&claims, "realm_access.roles"),
+        vec!["admin", "viewer"]
+    );
+    assert_eq!(roles_from_claims(&claims, "scope"), vec!["read", "write"]);
+    assert!(roles_from_claims(&claims, "roles").is_empty());
+    assert!(roles_from_claims(&claims, "missing.path").is_empty());
+}
+
+#[test]
+fn test_caller_authorize() {
+    let required = vec!["admin".to_string()];
+    let admin = Caller::Jwt(JwtPrincipal {
+        subject: Some("a".to_string()),
+        roles: vec!["admin".to_string()],
+    });
+    let viewer = Caller::Jwt(JwtPrincipal {
+        subject: Some("v".to_string()),
+        roles: vec!["viewer".to_string()],
+    });
+    assert!(admin.authorize("delete_user", true, &required).is_ok());
+    assert!(matches!(
+        viewer.authorize("delete_user", true, &required),
+        Err(HelixError::MissingRole { .. })
+    ));
+    assert!(viewer.authorize("get_user", true, &[]).is_ok());
+
+    let read_only = Caller::ApiKey(AuthorizedKey {
+        name: "ci".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+    });
+    assert!(read_only.authorize("get_user", false, &required).is_ok());
+    assert!(matches!(
+        read_only.authorize("delete_user", true, &[]),
+        Err(HelixError::ReadOnlyApiKey { .. })
+    ));
+}
+
+/// Serve `jwks` on a local port, counting how often it is fetched
+async fn serve_jwks(jwks: String) -> (String, Arc<AtomicUsize>) {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&fetches);
+    let app = axum::Router::new().route(
+        "/jwks",
+        axum::routing::get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let jwks = jwks.clone();
+            async move { jwks }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (format!("http://{addr}/jwks"), fetches)
+}
+
+#[tokio::test]
+async fn test_jwks_fetched_once_and_cached() {
+    let key = SigningKey::generate("k1");
+    let (url, fetches) = serve_jwks(key.jwks_json()).await;
+    let verifier = JwtVerifier::new(jwt_config(&url));
+
+    for _ in 0..3 {
+        verifier.verify(&key.sign(&claims(&[]))).await.unwrap();
+    }
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    // An unknown key id right after a fetch doesn't hammer the provider
+    let unknown = SigningKey::generate("k2");
+    assert!(matches!(
+        verifier.verify(&unknown.sign(&claims(&[]))).await,
+        Err(HelixError::InvalidToken(_))
+    ));
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_unreachable_jwks_rejects_tokens() {
+    let key = SigningKey::generate("k1");
+    let verifier = JwtVerifier::new(jwt_config("http://127.0.0.1:1/jwks"));
+    assert!(matches!(
+        verifier.verify(&key.sign(&claims(&[]))).await,
+        Err(HelixError::InvalidToken(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_bearer_auth_accepts_jwt() {
+    let key = SigningKey::generate("k1");
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Default::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let core_setter = Arc::new(CoreSetter::new(vec![core_affinity::CoreId { id: 0 }], 2));
+    let router = Arc::new(HelixRouter::new(None, None, None));
+    let state = Arc::new(AppState {
+        worker_pool: WorkerPool::new(core_setter, graph, router, rt),
+        schema_json: None,
+        cluster_id: None,
+        api_keys: ApiKeys::default(),
+        jwt: Some(verifier(&key)),
+        rate_limiter: RateLimiter::default(),
+    });
+
+    let token = key.sign(&claims(&["admin"]));
+    let (mut parts, _) = axum::http::Request::builder()
+        .header("authorization", format!("Bearer {token}"))
+        .body(())
+        .unwrap()
+        .into_parts();
+    let auth = BearerAuth::from_request_parts(&mut parts, &state)
+        .await
+        .unwrap();
+    let Some(Caller::Jwt(principal)) = auth.0 else {
+        panic!("expected a JWT caller");
+    };
+    assert_eq!(principal.roles, vec!["admin"]);
+
+    let (mut parts, _) = axum::http::Request::builder()
+        .body(())
+        .unwrap()
+        .into_parts();
+    assert!(matches!(
+        BearerAuth::from_request_parts(&mut parts, &state).await,
+        Err(HelixError::MissingBearerToken)
+    ));
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bumpalo::Bump;
+use rskafka::chrono::Utc;
+use rskafka::record::{Record, RecordAndOffset};
+use tempfile::TempDir;
+
+use crate::helix_engine::storage_core::audit_log::AuditEntry;
+use crate::helix_engine::traversal_core::config::{
+    Config, KafkaCdcConfig, KafkaConfig, KafkaIngestConfig,
+};
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::n_from_type::NFromTypeAdapter;
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::gateway::CoreSetter;
+use crate::helix_gateway::kafka::{
+    KAFKA_INGEST_ROUTE, KafkaConnector, KafkaError, change_record, ingest_records, kafka_ingest,
+    stored_offset,
+};
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::value::Value;
+use crate::protocol::{Format, Response};
+
+/// A worker pool with the ingest route and a `touch` route counting its calls
+fn create_test_pool(calls: Arc<AtomicUsize>) -> (WorkerPool, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert(
+        "touch".to_string(),
+        Arc::new(move |input: HandlerInput| {
+            assert_eq!(input.request.body.as_ref(), br#"{"by":"kafka"}"#);
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Response {
+                body: b"null".to_vec(),
+                fmt: Format::Json,
+            })
+        }),
+    );
+    let mut router = HelixRouter::new(Some(routes), None, None);
+    router.add_route(KAFKA_INGEST_ROUTE, kafka_ingest, true);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    (
+        WorkerPool::new(core_setter, graph, Arc::new(router), rt),
+        temp_dir,
+    )
+}
+
+fn records(values: &[&str]) -> Vec<RecordAndOffset> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| RecordAndOffset {
+            record: Record {
+                key: None,
+                value: Some(value.as_bytes().to_vec()),
+                headers: BTreeMap::new(),
+                timestamp: Utc::now(),
+            },
+            offset: 40 + i as i64,
+        })
+        .collect()
+}
+
+/// Properties of the nodes of `label`, by their `name`
+fn nodes(pool: &WorkerPool, label: &str) -> HashMap<String, Vec<(String, Value)>> {
+    let storage = pool.graph().storage.as_ref();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let arena = Bump::new();
+    G::new(storage, &txn, &arena)
+        .n_from_type(label)
+        .map(|item| match item.unwrap() {
+            TraversalValue::Node(node) => {
+                let properties = node
+                    .properties
+                    .map(|props| {
+                        props
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let name = node.get_property("name").unwrap().inner_stringify();
+                (name, properties)
+            }
+            other => panic!("expected a node, got {other:?}"),
+        })
+        .collect()
+}
+
+fn edge_count(pool: &WorkerPool) -> usize {
+    let storage = pool.graph().storage.as_ref();
+    let txn = storage.graph_env.read_txn().unwrap();
+    storage.edges_db.len(&txn).unwrap() as usize
+}
+
+#[tokio::test]
+async fn test_ingest_records_applies_events_and_stores_offset() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (pool, _dir) = create_test_pool(Arc::clone(&calls));
+    let batch = records(&[
+        r#"{"op":"add_node","label":"User","properties":{"name":"ada","age":36}}"#,
+        r#"{"op":"upsert_node","label":"User","key":"name","properties":{"name":"grace"}}"#,
+        "not json",
+        r#"{"op":"query","name":"touch","params":{"by":"kafka"}}"#,
+        r#"{"op":"upsert_node","label":"User","key":"name","properties":{"name":"ada","age":37}}"#,
+        r#"{"op":"add_edge","label":"Follows",
+            "from":{"label":"User","key":"name","value":"grace"},
+            "to":{"label":"User","key":"name","value":"ada"}}"#,
+    ]);
+
+    assert!(ingest_records(&pool, "mutations", 0, &batch).await);
+
+    let users = nodes(&pool, "User");
+    assert_eq!(users.len(), 2);
+    assert!(users["ada"].contains(&("age".to_string(), Value::I64(37))));
+    assert_eq!(edge_count(&pool), 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let storage = &pool.graph().storage;
+    assert_eq!(stored_offset(storage, "mutations", 0).unwrap(), Some(46));
+    assert_eq!(stored_offset(storage, "mutations", 1).unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_ingest_records_skips_failing_events() {
+    let (pool, _dir) = create_test_pool(Arc::new(AtomicUsize::new(0)));
+    let batch = records(&[
+        r#"{"op":"add_node","label":"User","properties":{"name":"ada"}}"#,
+        r#"{"op":"add_edge","label":"Follows",
+            "from":"00000000-0000-0000-0000-000000000001",
+            "to":{"label":"User","key":"name","value":"ada"}}"#,
+        r#"{"op":"upsert_node","label":"User","key":"name","properties":{"age":3}}"#,
+        r#"{"op":"add_node","label":"User","properties":{"name":"grace"}}"#,
+    ]);
+
+    assert!(ingest_records(&pool, "mutations", 0, &batch).await);
+
+    let users = nodes(&pool, "User");
+    let mut names = users.keys().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["ada", "grace"]);
+    assert_eq!(edge_count(&pool), 0);
+    assert_eq!(
+        stored_offset(&pool.graph().storage, "mutations", 0).unwrap(),
+        Some(44)
+    );
+}
+
+#[tokio::test]
+async fn test_ingest_records_stops_on_shutdown() {
+    let (pool, _dir) = create_test_pool(Arc::new(AtomicUsize::new(0)));
+    pool.begin_shutdown();
+    let batch = records(&[r#"{"op":"add_node","label":"User","properties":{"name":"ada"}}"#]);
+
+    assert!(!ingest_records(&pool, "mutations", 0, &batch).await);
+    assert_eq!(
+        stored_offset(&pool.graph().storage, "mutations", 0).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_audit_entries_after_follow_the_log() {
+    let (pool, _dir) = create_test_pool(Arc::new(AtomicUsize::new(0)));
+    let storage = &pool.graph().storage;
+    let entries: Vec<AuditEntry> = (0..3)
+        .map(|i| AuditEntry::new(format!("write{i}"), None, vec![i]))
+        .collect();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    for entry in &entries {
+        storage.append_audit_entry(&mut txn, entry).unwrap();
+    }
+    txn.commit().unwrap();
+
+    assert_eq!(storage.audit_entries_after(None, 10).unwrap(), entries);
+    assert_eq!(
+        storage.audit_entries_after(Some(entries[0].id), 1).unwrap(),
+        entries[1..2]
+    );
+    assert!(
+        storage
+            .audit_entries_after(Some(entries[2].id), 10)
+            .unwrap()
+            .is_empty()
+    );
+
+    let record = change_record(&entries[0]);
+    assert_eq!(record.key, Some(entries[0].id.to_string().into_bytes()));
+    let published: AuditEntry = sonic_rs::from_slice(&record.value.unwrap()).unwrap();
+    assert_eq!(published, entries[0]);
+    assert_eq!(
+        record.timestamp.timestamp_millis(),
+        entries[0].timestamp_ms as i64
+    );
+}
+
+#[test]
+fn test_kafka_config_is_validated() {
+    let ingest = KafkaIngestConfig {
+        topic: "mutations".to_string(),
+        partitions: None,
+        start_offset: None,
+    };
+    let cdc = KafkaCdcConfig {
+        topic: "changes".to_string(),
+        partition: None,
+    };
+    let config = KafkaConfig {
+        brokers: vec!["localhost:9092".to_string()],
+        client_id: None,
+        ingest: Some(ingest.clone()),
+        cdc: None,
+    };
+    assert!(
+        KafkaConnector::from_config(&config, false)
+            .unwrap()
+            .ingests()
+    );
+
+    let no_brokers = KafkaConfig {
+        brokers: Vec::new(),
+        ..config.clone()
+    };
+    assert!(matches!(
+        KafkaConnector::from_config(&no_brokers, false),
+        Err(KafkaError::NoBrokers)
+    ));
+    let no_topics = KafkaConfig {
+        ingest: None,
+        ..config.clone()
+    };
+    assert!(matches!(
+        KafkaConnector::from_config(&no_topics, false),
+        Err(KafkaError::NoTopics)
+    ));
+    let no_partitions = KafkaConfig {
+        ingest: Some(KafkaIngestConfig {
+            partitions: Some(Vec::new()),
+            ..ingest
+        }),
+        ..config.clone()
+    };
+    assert!(matches!(
+        KafkaConnector::from_config(&no_partitions, false),
+        Err(KafkaError::NoPartitions)
+    ));
+
+    let cdc_only = KafkaConfig {
+        ingest: None,
+        cdc: Some(cdc),
+        ..config
+    };
+    assert!(matches!(
+        KafkaConnector::from_config(&cdc_only, false),
+        Err(KafkaError::CdcWithoutAuditLog)
+    ));
+    assert!(
+        !KafkaConnector::from_config(&cdc_only, true)
+            .unwrap()
+            .ingests()
+    );
+}
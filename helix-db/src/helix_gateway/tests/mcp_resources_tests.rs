@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use bumpalo::Bump;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+use crate::helix_engine::storage_core::version_info::VersionInfo;
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::{add_e::AddEAdapter, add_n::AddNAdapter};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::mcp::mcp::{
+    MCPConnection, MCPHandlerSubmission, MCPToolInput, McpBackend, McpConnections,
+};
+use crate::helix_gateway::mcp::resources::{
+    LabelStats, get_prompt, list_prompts, list_resources, read_resource, render_prompt,
+    sample_queries, schema_docs,
+};
+use crate::protocol::{Format, Request, Response, request::RequestType};
+
+const SCHEMA: &str = r#"{
+    "schema": {
+        "nodes": [{"name": "User", "properties": {"name": "String", "age": "U32"}}],
+        "vectors": [{"name": "Doc", "properties": {"text": "String"}}],
+        "edges": [{"name": "Follows", "from": "User", "to": "User", "properties": {"since": "Date"}}]
+    },
+    "queries": [
+        {"name": "getUser", "parameters": {"id": "ID"}, "returns": ["user"]},
+        {"name": "tagUsers", "parameters": {"tags": "Array(String)", "limit": "I64"}, "returns": []}
+    ]
+}"#;
+
+/// An engine holding two users following each other, and an open `conn` connection
+fn setup() -> (Arc<McpBackend>, Arc<Mutex<McpConnections>>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config::default(),
+        version_info: VersionInfo::default(),
+    };
+    let engine = HelixGraphEngine::new(opts).unwrap();
+
+    let arena = Bump::new();
+    let mut txn = engine.storage.graph_env.write_txn().unwrap();
+    let alice = G::new_mut(engine.storage.as_ref(), &arena, &mut txn)
+        .add_n("User", None, None)
+        .collect_to_obj()
+        .unwrap();
+    let bob = G::new_mut(engine.storage.as_ref(), &arena, &mut txn)
+        .add_n("User", None, None)
+        .collect_to_obj()
+        .unwrap();
+    for (from, to) in [(&alice, &bob), (&bob, &alice)] {
+        G::new_mut(engine.storage.as_ref(), &arena, &mut txn)
+            .add_edge("Follows", None, from.id(), to.id(), false, false)
+            .collect_to_obj()
+            .unwrap();
+    }
+    txn.commit().unwrap();
+
+    let connections = Arc::new(Mutex::new(McpConnections::new()));
+    connections
+        .lock()
+        .unwrap()
+        .add_connection(MCPConnection::new("conn".to_string()));
+    let backend = Arc::new(McpBackend::new(Arc::clone(&engine.storage)));
+    (backend, connections, temp_dir)
+}
+
+fn call(
+    handler: fn(&mut MCPToolInput) -> Result<Response, GraphError>,
+    backend: &Arc<McpBackend>,
+    connections: &Arc<Mutex<McpConnections>>,
+    body: &str,
+) -> Result<sonic_rs::Value, GraphError> {
+    let mut input = MCPToolInput {
+        request: Request {
+            name: "resources".to_string(),
+            req_type: RequestType::MCP,
+            body: Bytes::from(body.to_string()),
+            api_key: None,
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        },
+        mcp_backend: Arc::clone(backend),
+        mcp_connections: Arc::clone(connections),
+        schema: Some(SCHEMA.to_string()),
+        caller: None,
+    };
+    let response = handler(&mut input)?;
+    Ok(sonic_rs::from_slice(&response.body).unwrap())
+}
+
+#[test]
+fn test_resource_and_prompt_handlers_are_registered() {
+    let handler_names: Vec<&str> = inventory::iter::<MCPHandlerSubmission>
+        .into_iter()
+        .map(|submission| submission.0.name)
+        .collect();
+    for name in [
+        "list_resources",
+        "read_resource",
+        "list_prompts",
+        "get_prompt",
+    ] {
+        assert!(handler_names.contains(&name), "{name} is not registered");
+    }
+}
+
+#[test]
+fn test_schema_docs_lists_types_and_properties() {
+    let docs = schema_docs(Some(SCHEMA)).unwrap();
+    assert!(docs.contains("## Nodes\n\n### User\n\n- `age`: U32\n- `name`: String\n"));
+    assert!(docs.contains("## Vectors\n\n### Doc\n\n- `text`: String\n"));
+    assert!(docs.contains("### Follows\n\nFrom `User` to `User`.\n\n- `since`: Date\n"));
+
+    assert_eq!(schema_docs(None).unwrap(), "# Schema\n");
+}
+
+#[test]
+fn test_sample_queries_have_example_bodies() {
+    let docs = sample_queries(Some(SCHEMA)).unwrap();
+    assert!(docs.contains("## getUser\n\nReturns `user`.\n"));
+    assert!(docs.contains(r#""id": "00000000-0000-0000-0000-000000000000""#));
+    assert!(docs.contains("## tagUsers\n\n```json\n"));
+    assert!(docs.contains(r#""limit": 0"#));
+    assert!(docs.contains(r#""tags": ["#));
+}
+
+#[test]
+fn test_label_stats_counts_items_per_label() {
+    let (backend, _connections, _dir) = setup();
+    let stats = LabelStats::collect(&backend.db).unwrap();
+    assert_eq!(stats.nodes.get("User"), Some(&2));
+    assert_eq!(stats.edges.get("Follows"), Some(&2));
+    assert!(stats.vectors.is_empty());
+}
+
+#[test]
+fn test_list_and_read_resources() {
+    let (backend, connections, _dir) = setup();
+
+    let list = call(
+        list_resources,
+        &backend,
+        &connections,
+        r#"{"connection_id":"conn"}"#,
+    )
+    .unwrap();
+    let uris: Vec<&str> = list["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        uris,
+        [
+            "helix://schema",
+            "helix://schema/docs",
+            "helix://queries",
+            "helix://stats"
+        ]
+    );
+    assert_eq!(
+        list["resources"][1]["mimeType"].as_str(),
+        Some("text/markdown")
+    );
+
+    let read = call(
+        read_resource,
+        &backend,
+        &connections,
+        r#"{"connection_id":"conn","uri":"helix://stats"}"#,
+    )
+    .unwrap();
+    let contents = &read["contents"][0];
+    assert_eq!(contents["uri"].as_str(), Some("helix://stats"));
+    assert_eq!(contents["mimeType"].as_str(), Some("application/json"));
+    let stats: sonic_rs::Value = sonic_rs::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(stats["nodes"]["User"].as_u64(), Some(2));
+
+    let read = call(
+        read_resource,
+        &backend,
+        &connections,
+        r#"{"connection_id":"conn","uri":"helix://schema"}"#,
+    )
+    .unwrap();
+    assert_eq!(read["contents"][0]["text"].as_str(), Some(SCHEMA));
+
+    assert!(
+        call(
+            read_resource,
+            &backend,
+            &connections,
+            r#"{"connection_id":"conn","uri":"helix://nope"}"#,
+        )
+        .is_err()
+    );
+    assert!(
+        call(
+            list_resources,
+            &backend,
+            &connections,
+            r#"{"connection_id":"other"}"#
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_prompts_fill_in_arguments() {
+    let (backend, connections, _dir) = setup();
+
+    let list = call(
+        list_prompts,
+        &backend,
+        &connections,
+        r#"{"connection_id":"conn"}"#,
+    )
+    .unwrap();
+    let prompts = list["prompts"].as_array().unwrap();
+    assert_eq!(prompts.len(), 3);
+    assert_eq!(prompts[1]["name"].as_str(), Some("find_nodes"));
+    assert_eq!(prompts[1]["arguments"][0]["required"].as_bool(), Some(true));
+
+    let prompt = call(
+        get_prompt,
+        &backend,
+        &connections,
+        r#"{"connection_id":"conn","name":"find_nodes","arguments":{"label":"User","description":"adults"}}"#,
+    )
+    .unwrap();
+    let message = &prompt["messages"][0];
+    assert_eq!(message["role"].as_str(), Some("user"));
+    assert_eq!(message["content"]["type"].as_str(), Some("text"));
+    let text = message["content"]["text"].as_str().unwrap();
+    assert!(text.starts_with("Find `User` nodes matching this description: adults"));
+    assert!(text.contains("`age` (U32), `name` (String)"));
+
+    let messages =
+        render_prompt("explore_graph", &HashMap::new(), Some(SCHEMA), &backend.db).unwrap();
+    assert!(messages[0].content.text.contains("### Follows"));
+    assert!(messages[0].content.text.contains(r#""User": 2"#));
+
+    let arguments = HashMap::from([("label".to_string(), "Doc".to_string())]);
+    assert!(matches!(
+        render_prompt("semantic_search", &arguments, Some(SCHEMA), &backend.db),
+        Err(GraphError::New(message)) if message.contains("`query`")
+    ));
+    assert!(render_prompt("unknown", &arguments, Some(SCHEMA), &backend.db).is_err());
+}
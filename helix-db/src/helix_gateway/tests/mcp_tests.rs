@@ -4,6 +4,7 @@ mod mcp_tests {
 
     use axum::body::Bytes;
     use bumpalo::Bump;
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait};
     use tempfile::TempDir;
 
     use crate::{
@@ -185,6 +186,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = collect(&mut input).unwrap();
@@ -196,6 +198,86 @@ mod mcp_tests {
         assert_eq!(label_count, 2);
     }
 
+    #[test]
+    fn stream_handler_returns_batches_with_progress() {
+        use crate::helix_gateway::mcp::mcp::stream;
+
+        let (engine, _temp_dir) = setup_engine();
+        let mut txn = engine.storage.graph_env.write_txn().unwrap();
+        let arena = Bump::new();
+        for _ in 0..5 {
+            let _ = G::new_mut(engine.storage.as_ref(), &arena, &mut txn)
+                .add_n("person", None, None)
+                .collect_to_obj()
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let backend = Arc::new(McpBackend::new(Arc::clone(&engine.storage)));
+        let connections = Arc::new(Mutex::new(McpConnections::new()));
+        let mut connection = MCPConnection::new("conn".to_string());
+        connection.add_query_step(ToolArgs::NFromType {
+            node_type: "person".to_string(),
+        });
+        connections.lock().unwrap().add_connection(connection);
+
+        let call = |body: &str| -> sonic_rs::Value {
+            let mut input = MCPToolInput {
+                request: Request {
+                    name: "stream".to_string(),
+                    req_type: RequestType::MCP,
+                    body: Bytes::from(body.to_string()),
+                    api_key: None,
+                    in_fmt: Format::Json,
+                    out_fmt: Format::Json,
+                },
+                mcp_backend: Arc::clone(&backend),
+                mcp_connections: Arc::clone(&connections),
+                schema: None,
+                caller: None,
+            };
+            sonic_rs::from_slice(&stream(&mut input).unwrap().body).unwrap()
+        };
+        let body = r#"{"connection_id":"conn","batch_size":2}"#;
+
+        let mut seen = Vec::new();
+        for (len, position, done) in [(2, 2, false), (2, 4, false), (1, 5, true)] {
+            let batch = call(body);
+            let items = batch["items"].as_array().unwrap();
+            assert_eq!(items.len(), len);
+            seen.extend(
+                items
+                    .iter()
+                    .map(|item| item["id"].as_str().unwrap().to_string()),
+            );
+            assert_eq!(batch["progress"]["position"].as_u64(), Some(position));
+            assert_eq!(batch["progress"]["done"].as_bool(), Some(done));
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+        assert_eq!(
+            connections
+                .lock()
+                .unwrap()
+                .get_connection("conn")
+                .unwrap()
+                .current_position,
+            5
+        );
+
+        // A spent time budget still returns one item so the client makes progress
+        connections
+            .lock()
+            .unwrap()
+            .get_connection_mut("conn")
+            .unwrap()
+            .reset_position();
+        let batch = call(r#"{"connection_id":"conn","batch_size":10,"max_millis":0}"#);
+        assert_eq!(batch["items"].as_array().unwrap().len(), 1);
+        assert_eq!(batch["progress"]["done"].as_bool(), Some(false));
+    }
+
     // ============================================================================
     // MCP Handler Registration Tests
     // ============================================================================
@@ -213,6 +295,7 @@ mod mcp_tests {
         assert!(handler_names.contains(&"init"));
         assert!(handler_names.contains(&"tool_call"));
         assert!(handler_names.contains(&"next"));
+        assert!(handler_names.contains(&"stream"));
         assert!(handler_names.contains(&"collect"));
         assert!(handler_names.contains(&"aggregate_by"));
         assert!(handler_names.contains(&"group_by"));
@@ -336,6 +419,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = out_step(&mut input).unwrap();
@@ -396,6 +480,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = in_step(&mut input).unwrap();
@@ -454,6 +539,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = out_e_step(&mut input).unwrap();
@@ -513,6 +599,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = in_e_step(&mut input).unwrap();
@@ -559,6 +646,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = n_from_type(&mut input).unwrap();
@@ -614,6 +702,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = e_from_type(&mut input).unwrap();
@@ -685,6 +774,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = filter_items(&mut input).unwrap();
@@ -755,6 +845,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = order_by(&mut input).unwrap();
@@ -1266,6 +1357,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         // Note: search_keyword may return Empty if BM25 index is not initialized
@@ -1304,6 +1396,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_keyword(&mut input);
@@ -1346,6 +1439,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_keyword(&mut input);
@@ -1417,6 +1511,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector(&mut input);
@@ -1453,6 +1548,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector(&mut input);
@@ -1496,6 +1592,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector(&mut input);
@@ -1533,6 +1630,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector_text(&mut input);
@@ -1570,6 +1668,7 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector_text(&mut input);
@@ -1613,9 +1712,140 @@ mod mcp_tests {
             mcp_backend: backend,
             mcp_connections: Arc::clone(&connections),
             schema: None,
+            caller: None,
         };
 
         let response = search_vector_text(&mut input);
         assert!(response.is_err());
     }
+
+    // ============================================================================
+    // Connection Access and Rate Limit Tests
+    // ============================================================================
+
+    fn mcp_request(name: &str, body: &str) -> Request {
+        Request {
+            name: name.to_string(),
+            req_type: RequestType::MCP,
+            body: Bytes::from(body.to_string()),
+            api_key: None,
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        }
+    }
+
+    #[test]
+    fn init_opens_connection_owned_by_caller() {
+        use crate::helix_gateway::mcp::mcp::init;
+        use crate::protocol::HelixError;
+
+        let (engine, _temp_dir) = setup_engine();
+        let connections = Arc::new(Mutex::new(McpConnections::new()));
+        let mut input = MCPToolInput {
+            request: mcp_request("init", ""),
+            mcp_backend: Arc::new(McpBackend::new(Arc::clone(&engine.storage))),
+            mcp_connections: Arc::clone(&connections),
+            schema: None,
+            caller: Some("key:agent".to_string()),
+        };
+        let response = init(&mut input).unwrap();
+        let connection_id: String = sonic_rs::from_slice(&response.body).unwrap();
+
+        let mut connections = connections.lock().unwrap();
+        assert_eq!(
+            connections.get_connection(&connection_id).unwrap().owner,
+            Some("key:agent".to_string())
+        );
+        let call = mcp_request(
+            "collect",
+            &format!(r#"{{"connection_id":"{connection_id}"}}"#),
+        );
+        assert!(connections.admit(&call, Some("key:agent")).is_ok());
+        for caller in [Some("key:other"), Some("jwt:agent"), None] {
+            assert!(matches!(
+                connections.admit(&call, caller),
+                Err(HelixError::McpConnectionNotFound(id)) if id == connection_id
+            ));
+        }
+
+        // Tools naming no open connection are refused before they run
+        assert!(matches!(
+            connections.admit(
+                &mcp_request("collect", r#"{"connection_id":"missing"}"#),
+                Some("key:agent")
+            ),
+            Err(HelixError::McpConnectionNotFound(id)) if id == "missing"
+        ));
+        assert!(matches!(
+            connections.admit(&mcp_request("collect", "{}"), Some("key:agent")),
+            Err(HelixError::McpConnectionNotFound(_))
+        ));
+        assert!(
+            connections
+                .admit(&mcp_request("init", ""), Some("key:other"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn init_without_auth_opens_unowned_connection() {
+        use crate::helix_gateway::mcp::mcp::init;
+
+        let (engine, _temp_dir) = setup_engine();
+        let connections = Arc::new(Mutex::new(McpConnections::new()));
+        let mut input = MCPToolInput {
+            request: mcp_request("init", ""),
+            mcp_backend: Arc::new(McpBackend::new(Arc::clone(&engine.storage))),
+            mcp_connections: Arc::clone(&connections),
+            schema: None,
+            caller: None,
+        };
+        let response = init(&mut input).unwrap();
+        let connection_id: String = sonic_rs::from_slice(&response.body).unwrap();
+
+        let mut connections = connections.lock().unwrap();
+        assert_eq!(
+            connections.get_connection(&connection_id).unwrap().owner,
+            None
+        );
+        let call = mcp_request(
+            "collect",
+            &format!(r#"{{"connection_id":"{connection_id}"}}"#),
+        );
+        assert!(connections.admit(&call, None).is_ok());
+    }
+
+    #[test]
+    fn connection_rate_limit_is_per_connection() {
+        use crate::helix_engine::traversal_core::config::RateLimit;
+        use crate::protocol::HelixError;
+
+        let mut connections = McpConnections::new().with_rate_limit(Some(RateLimit {
+            requests_per_sec: 1,
+            burst: Some(2),
+        }));
+        connections.open("a".to_string(), None);
+        connections.open("b".to_string(), None);
+
+        let call_a = mcp_request("collect", r#"{"connection_id":"a"}"#);
+        assert!(connections.admit(&call_a, None).is_ok());
+        assert!(connections.admit(&call_a, None).is_ok());
+        assert!(matches!(
+            connections.admit(&call_a, None),
+            Err(HelixError::RateLimited {
+                limit: 2,
+                retry_after_secs: 1
+            })
+        ));
+
+        let call_b = mcp_request("collect", r#"{"connection_id":"b"}"#);
+        assert!(connections.admit(&call_b, None).is_ok());
+
+        // Connections added directly, as tools re-add the ones they take out, aren't limited
+        connections.add_connection(MCPConnection::new("c".to_string()));
+        let call_c = mcp_request("collect", r#"{"connection_id":"c"}"#);
+        for _ in 0..3 {
+            assert!(connections.admit(&call_c, None).is_ok());
+        }
+    }
 }
@@ -1,8 +1,42 @@
+pub mod admin_tests;
+pub mod api_keys_tests;
+pub mod audit_log_tests;
+pub mod batch_tests;
+pub mod body_limit_tests;
+#[cfg(feature = "chaos")]
+pub mod chaos_tests;
+pub mod compression_tests;
+pub mod cors_tests;
+#[cfg(feature = "cypher")]
+pub mod cypher_tests;
+pub mod ego_cache_tests;
 pub mod embedding_providers;
+pub mod flight_tests;
 pub mod gateway_loom_tests;
 pub mod gateway_tests;
+pub mod graphql_tests;
+pub mod grpc_tests;
+pub mod health_tests;
+pub mod import_tests;
 pub mod introspect_schema_tests;
+pub mod jwt_tests;
+pub mod kafka_tests;
+pub mod mcp_resources_tests;
 pub mod mcp_tests;
+pub mod postgres_sync_tests;
+pub mod qdrant_tests;
+pub mod rate_limit_tests;
+pub mod reload_tests;
+pub mod replication_tests;
+pub mod result_cache_tests;
 pub mod router_tests;
+pub mod scheduler_tests;
+pub mod session_tests;
+pub mod slow_query_tests;
+pub mod subscription_tests;
+pub mod text_search_tests;
+pub mod tls_tests;
+pub mod vector_search_tests;
+pub mod view_tests;
 pub mod worker_pool_concurrency_tests;
 pub mod worker_pool_tests;
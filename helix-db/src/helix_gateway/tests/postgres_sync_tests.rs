@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bumpalo::Bump;
+use tempfile::TempDir;
+
+use crate::helix_engine::traversal_core::config::{
+    Config, PostgresSyncConfig, PostgresTableConfig,
+};
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::n_from_type::NFromTypeAdapter;
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::gateway::CoreSetter;
+use crate::helix_gateway::postgres_sync::{
+    ChangedRow, POSTGRES_SYNC_ROUTE, PostgresSync, PostgresSyncError, SyncCursor, apply_rows,
+    postgres_sync, quote_ident, select_changes, stored_cursor,
+};
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::value::Value;
+
+fn create_test_pool() -> (WorkerPool, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route(POSTGRES_SYNC_ROUTE, postgres_sync, true);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    (
+        WorkerPool::new(core_setter, graph, Arc::new(router), rt),
+        temp_dir,
+    )
+}
+
+fn users_table() -> PostgresTableConfig {
+    PostgresTableConfig {
+        table: "public.users".to_string(),
+        label: "User".to_string(),
+        key: None,
+        updated_at: None,
+        columns: Some(vec!["name".to_string()]),
+    }
+}
+
+fn row(row: &str, updated_at: &str, key: &str) -> ChangedRow {
+    ChangedRow {
+        row: row.to_string(),
+        updated_at: updated_at.to_string(),
+        key: key.to_string(),
+    }
+}
+
+/// Properties of the nodes of `label`, by their `id`
+fn nodes(pool: &WorkerPool, label: &str) -> HashMap<String, HashMap<String, Value>> {
+    let storage = pool.graph().storage.as_ref();
+    let txn = storage.graph_env.read_txn().unwrap();
+    let arena = Bump::new();
+    G::new(storage, &txn, &arena)
+        .n_from_type(label)
+        .map(|item| match item.unwrap() {
+            TraversalValue::Node(node) => {
+                let properties: HashMap<String, Value> = node
+                    .properties
+                    .map(|props| {
+                        props
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (properties["id"].inner_stringify(), properties)
+            }
+            other => panic!("expected a node, got {other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_select_changes_quotes_and_resumes_after_cursor() {
+    let config = PostgresTableConfig {
+        table: "app.order\"s".to_string(),
+        label: "Order".to_string(),
+        key: Some("order_id".to_string()),
+        updated_at: Some("modified".to_string()),
+        columns: None,
+    };
+    assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+    assert_eq!(
+        select_changes(&config, None),
+        "SELECT to_jsonb(t)::text, t.\"modified\"::text, t.\"order_id\"::text \
+         FROM \"app\".\"order\"\"s\" t \
+         WHERE t.\"modified\" IS NOT NULL AND t.\"order_id\" IS NOT NULL \
+         ORDER BY t.\"modified\", t.\"order_id\" LIMIT 500"
+    );
+
+    let cursor = SyncCursor {
+        table: config.table.clone(),
+        updated_at: "2026-01-02 03:04:05+00".to_string(),
+        key: "o'7".to_string(),
+    };
+    let sql = select_changes(&config, Some(&cursor));
+    assert!(sql.contains(
+        "AND (t.\"modified\", t.\"order_id\") > ('2026-01-02 03:04:05+00', 'o''7') ORDER BY"
+    ));
+}
+
+#[tokio::test]
+async fn test_apply_rows_upserts_nodes_and_stores_cursor() {
+    let (pool, _dir) = create_test_pool();
+    let config = users_table();
+    let rows = [
+        row(r#"{"id":1,"name":"ada","password":"x"}"#, "2026-01-01", "1"),
+        row(r#"{"id":2,"name":"grace"}"#, "2026-01-02", "2"),
+        row(r#"{"id":1,"name":"ada lovelace"}"#, "2026-01-03", "1"),
+    ];
+
+    assert!(apply_rows(&pool, &config, &rows).await);
+
+    let users = nodes(&pool, "User");
+    assert_eq!(users.len(), 2);
+    assert_eq!(
+        users["1"]["name"],
+        Value::String("ada lovelace".to_string())
+    );
+    assert!(!users["1"].contains_key("password"));
+    assert_eq!(
+        stored_cursor(&pool.graph().storage, "public.users").unwrap(),
+        Some(SyncCursor {
+            table: "public.users".to_string(),
+            updated_at: "2026-01-03".to_string(),
+            key: "1".to_string(),
+        })
+    );
+    assert_eq!(
+        stored_cursor(&pool.graph().storage, "orders").unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_apply_rows_skips_failing_rows() {
+    let (pool, _dir) = create_test_pool();
+    let config = PostgresTableConfig {
+        key: Some("email".to_string()),
+        columns: None,
+        ..users_table()
+    };
+    let rows = [
+        row(r#"{"id":1,"email":"ada@example.com"}"#, "2026-01-01", "a"),
+        row(r#"{"id":2}"#, "2026-01-02", "b"),
+        row("[1]", "2026-01-03", "c"),
+    ];
+
+    assert!(apply_rows(&pool, &config, &rows).await);
+
+    let users = nodes(&pool, "User");
+    assert_eq!(users.keys().collect::<Vec<_>>(), ["1"]);
+    let cursor = stored_cursor(&pool.graph().storage, "public.users").unwrap();
+    assert_eq!(cursor.map(|cursor| cursor.key), Some("c".to_string()));
+}
+
+#[test]
+fn test_postgres_sync_config_is_validated() {
+    let config = PostgresSyncConfig {
+        url: "postgres://helix@localhost/app".to_string(),
+        poll_interval_secs: None,
+        tables: vec![users_table()],
+    };
+    assert!(PostgresSync::from_config(&config).is_ok());
+
+    let no_tables = PostgresSyncConfig {
+        tables: Vec::new(),
+        ..config.clone()
+    };
+    assert!(matches!(
+        PostgresSync::from_config(&no_tables),
+        Err(PostgresSyncError::NoTables)
+    ));
+    let duplicate = PostgresSyncConfig {
+        tables: vec![users_table(), users_table()],
+        ..config.clone()
+    };
+    assert!(matches!(
+        PostgresSync::from_config(&duplicate),
+        Err(PostgresSyncError::DuplicateTable(table)) if table == "public.users"
+    ));
+    let invalid_url = PostgresSyncConfig {
+        url: "postgres://helix@localhost:port/app".to_string(),
+        ..config
+    };
+    assert!(matches!(
+        PostgresSync::from_config(&invalid_url),
+        Err(PostgresSyncError::InvalidUrl(_))
+    ));
+}
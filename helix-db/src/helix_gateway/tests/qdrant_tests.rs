@@ -0,0 +1,471 @@
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::{ApiKeyConfig, ApiKeyScope, Config};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::api_keys::{ApiKeys, hash_api_key};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::qdrant::{
+    QDRANT_READ_ROUTE, QDRANT_WRITE_ROUTE, qdrant_read, qdrant_write, router,
+};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use axum::body::Body;
+use axum::http::{Method, StatusCode};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn create_test_app_state(api_keys: ApiKeys) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route(QDRANT_READ_ROUTE, qdrant_read, false);
+    router.add_route(QDRANT_WRITE_ROUTE, qdrant_write, true);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, Arc::new(router), rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys,
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn call(
+    state: &Arc<AppState>,
+    method: Method,
+    path: &str,
+    body: Option<sonic_rs::Value>,
+    api_key: Option<&str>,
+) -> (StatusCode, sonic_rs::Value) {
+    let mut request = axum::http::Request::builder().method(method).uri(path);
+    if let Some(key) = api_key {
+        request = request.header("api-key", key);
+    }
+    let body = body.map_or(Body::empty(), |b| Body::from(b.to_string()));
+    let response = router()
+        .with_state(Arc::clone(state))
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+/// A collection of 3-dimensional vectors holding four documents
+async fn populate(state: &Arc<AppState>) {
+    let (status, body) = call(
+        state,
+        Method::PUT,
+        "/collections/docs",
+        Some(sonic_rs::json!({ "vectors": { "size": 3, "distance": "Cosine" } })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    assert_eq!(body["result"].as_bool(), Some(true));
+
+    let (status, body) = call(
+        state,
+        Method::PUT,
+        "/collections/docs/points?wait=true",
+        Some(sonic_rs::json!({ "points": [
+            { "id": 1, "vector": [1.0, 0.0, 0.0], "payload": { "source": "wiki", "year": 2020 } },
+            { "id": 2, "vector": [0.9, 0.1, 0.0], "payload": { "source": "blog", "year": 2023 } },
+            { "id": 3, "vector": [0.0, 1.0, 0.0], "payload": { "source": "wiki", "year": 2021 } },
+            {
+                "id": "5c56c793-69f3-4fbf-87e6-c4bf54c28c26",
+                "vector": [0.0, 0.0, 1.0],
+                "payload": { "source": "wiki", "meta": { "tags": ["a", "b"] } }
+            },
+        ] })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    assert_eq!(body["result"]["status"].as_str(), Some("completed"));
+    assert_eq!(body["status"].as_str(), Some("ok"));
+}
+
+fn ids(points: &sonic_rs::Value) -> Vec<String> {
+    points
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].to_string())
+        .collect()
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_collections_lifecycle() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let (_, body) = call(&state, Method::GET, "/collections/docs/exists", None, None).await;
+    assert_eq!(body["result"]["exists"].as_bool(), Some(false));
+
+    populate(&state).await;
+    let (_, body) = call(&state, Method::GET, "/collections", None, None).await;
+    assert_eq!(
+        body["result"]["collections"][0]["name"].as_str(),
+        Some("docs")
+    );
+
+    let (status, body) = call(&state, Method::GET, "/collections/docs", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    let info = &body["result"];
+    assert_eq!(info["status"].as_str(), Some("green"));
+    assert_eq!(info["points_count"].as_u64(), Some(4));
+    let vectors = &info["config"]["params"]["vectors"];
+    assert_eq!(vectors["size"].as_u64(), Some(3));
+    assert_eq!(vectors["distance"].as_str(), Some("Cosine"));
+
+    let (status, body) = call(&state, Method::DELETE, "/collections/docs", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"].as_bool(), Some(true));
+    let (_, body) = call(&state, Method::GET, "/collections/docs/exists", None, None).await;
+    assert_eq!(body["result"]["exists"].as_bool(), Some(false));
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_search_filters_and_scores() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    populate(&state).await;
+
+    let (status, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/search",
+        Some(sonic_rs::json!({ "vector": [1.0, 0.0, 0.0], "limit": 2, "with_payload": true })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    let found = &body["result"];
+    assert_eq!(ids(found), ["1", "2"]);
+    assert!((found[0]["score"].as_f64().unwrap() - 1.0).abs() < 1e-9);
+    assert_eq!(found[0]["payload"]["source"].as_str(), Some("wiki"));
+    assert!(found[0]["vector"].is_null());
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/query",
+        Some(sonic_rs::json!({
+            "query": [1.0, 0.0, 0.0],
+            "limit": 3,
+            "filter": { "must": [{ "key": "source", "match": { "value": "wiki" } }] },
+        })),
+        None,
+    )
+    .await;
+    let points = &body["result"]["points"];
+    assert_eq!(ids(points)[0], "1");
+    assert!(!ids(points).contains(&"2".to_string()));
+    assert!(points[0]["payload"].is_null());
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/search",
+        Some(sonic_rs::json!({
+            "vector": [0.0, 0.0, 1.0],
+            "limit": 5,
+            "score_threshold": 0.5,
+            "filter": { "should": [{ "key": "meta.tags", "match": { "any": ["b"] } }] },
+        })),
+        None,
+    )
+    .await;
+    assert_eq!(
+        ids(&body["result"]),
+        ["\"5c56c793-69f3-4fbf-87e6-c4bf54c28c26\""]
+    );
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_upsert_replaces_points() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    populate(&state).await;
+
+    for _ in 0..2 {
+        let (status, body) = call(
+            &state,
+            Method::PUT,
+            "/collections/docs/points?wait=true",
+            Some(sonic_rs::json!({ "points": [{ "id": 1, "vector": [0.0, 1.0, 0.1] }] })),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body:?}");
+    }
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/search",
+        Some(sonic_rs::json!({ "vector": [0.0, 1.0, 0.0], "limit": 10 })),
+        None,
+    )
+    .await;
+    let found = ids(&body["result"]);
+    assert_eq!(found.len(), 4, "{found:?}");
+    assert_eq!(found[..2], ["3", "1"]);
+
+    let (status, _) = call(
+        &state,
+        Method::PUT,
+        "/collections/notes",
+        Some(sonic_rs::json!({ "vectors": { "size": 3, "distance": "Cosine" } })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(
+        &state,
+        Method::PUT,
+        "/collections/notes/points",
+        Some(sonic_rs::json!({ "points": [{ "id": 1, "vector": [1.0, 0.0, 0.0] }] })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body:?}");
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_retrieve_scroll_count_and_delete() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    populate(&state).await;
+
+    let (status, body) = call(
+        &state,
+        Method::GET,
+        "/collections/docs/points/2",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body:?}");
+    assert_eq!(body["result"]["payload"]["year"].as_i64(), Some(2023));
+    assert_eq!(body["result"]["vector"].as_array().unwrap().len(), 3);
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points",
+        Some(sonic_rs::json!({ "ids": [3, 9], "with_payload": ["year"] })),
+        None,
+    )
+    .await;
+    assert_eq!(ids(&body["result"]), ["3"]);
+    assert!(body["result"][0]["payload"]["source"].is_null());
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/scroll",
+        Some(sonic_rs::json!({ "limit": 2 })),
+        None,
+    )
+    .await;
+    assert_eq!(ids(&body["result"]["points"]), ["1", "2"]);
+    assert_eq!(body["result"]["next_page_offset"].as_u64(), Some(3));
+
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/count",
+        Some(sonic_rs::json!({
+            "filter": { "must": { "key": "year", "range": { "gte": 2021 } } }
+        })),
+        None,
+    )
+    .await;
+    assert_eq!(body["result"]["count"].as_u64(), Some(2));
+
+    for selector in [
+        sonic_rs::json!({ "points": [1] }),
+        sonic_rs::json!({ "filter": { "must": [{ "key": "source", "match": { "value": "blog" } }] } }),
+    ] {
+        let (status, body) = call(
+            &state,
+            Method::POST,
+            "/collections/docs/points/delete",
+            Some(selector),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body:?}");
+    }
+    let (_, body) = call(
+        &state,
+        Method::POST,
+        "/collections/docs/points/count",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(body["result"]["count"].as_u64(), Some(2));
+    let (status, body) = call(
+        &state,
+        Method::GET,
+        "/collections/docs/points/1",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(
+        body["status"]["error"]
+            .as_str()
+            .unwrap()
+            .contains("No point")
+    );
+
+    // Upserting an existing id replaces the point
+    call(
+        &state,
+        Method::PUT,
+        "/collections/docs/points",
+        Some(sonic_rs::json!({
+            "batch": { "ids": [3], "vectors": [[1.0, 0.0, 0.0]], "payloads": [{ "source": "new" }] }
+        })),
+        None,
+    )
+    .await;
+    let (_, body) = call(
+        &state,
+        Method::GET,
+        "/collections/docs/points/3",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(body["result"]["payload"]["source"].as_str(), Some("new"));
+    assert!(body["result"]["payload"]["year"].is_null());
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_invalid_requests_are_rejected() {
+    let (state, _dir) = create_test_app_state(ApiKeys::default());
+    let (status, body) = call(
+        &state,
+        Method::POST,
+        "/collections/missing/points/search",
+        Some(sonic_rs::json!({ "vector": [1.0], "limit": 1 })),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body["status"]["error"].as_str(),
+        Some("Not found: Collection `missing` doesn't exist!")
+    );
+
+    populate(&state).await;
+    for (method, path, body) in [
+        (
+            Method::PUT,
+            "/collections/docs",
+            sonic_rs::json!({ "vectors": { "size": 3, "distance": "Cosine" } }),
+        ),
+        (
+            Method::PUT,
+            "/collections/euclid",
+            sonic_rs::json!({ "vectors": { "size": 3, "distance": "Euclid" } }),
+        ),
+        (
+            Method::PUT,
+            "/collections/docs/points",
+            sonic_rs::json!({ "points": [{ "id": 9, "vector": [1.0, 2.0] }] }),
+        ),
+        (
+            Method::POST,
+            "/collections/docs/points/search",
+            sonic_rs::json!({ "vector": [1.0, 0.0, 0.0], "filter": { "must": [{ "key": "x" }] } }),
+        ),
+        (
+            Method::POST,
+            "/collections/docs/points",
+            sonic_rs::json!({ "ids": [-1] }),
+        ),
+    ] {
+        let (status, response) = call(&state, method, path, Some(body.clone()), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body:?}: {response:?}");
+        assert!(response["status"]["error"].as_str().is_some());
+    }
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_api_key_header_is_checked() {
+    let keys = ApiKeys::from_config(&[
+        ApiKeyConfig {
+            name: "app".to_string(),
+            key_hash: hash_api_key("writer"),
+            scope: ApiKeyScope::ReadWrite,
+        },
+        ApiKeyConfig {
+            name: "dashboard".to_string(),
+            key_hash: hash_api_key("reader"),
+            scope: ApiKeyScope::ReadOnly,
+        },
+    ]);
+    let (state, _dir) = create_test_app_state(keys);
+    let create = || Some(sonic_rs::json!({ "vectors": { "size": 3, "distance": "Cosine" } }));
+
+    let (status, _) = call(&state, Method::PUT, "/collections/docs", create(), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &state,
+        Method::PUT,
+        "/collections/docs",
+        create(),
+        Some("reader"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = call(
+        &state,
+        Method::PUT,
+        "/collections/docs",
+        create(),
+        Some("writer"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(&state, Method::GET, "/collections", None, Some("reader")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["result"]["collections"][0]["name"].as_str(),
+        Some("docs")
+    );
+}
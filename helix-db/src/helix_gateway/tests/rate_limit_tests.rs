@@ -0,0 +1,260 @@
+use crate::helix_engine::traversal_core::config::{ApiKeyScope, RateLimit, RateLimitConfig};
+use crate::helix_gateway::api_keys::AuthorizedKey;
+use crate::helix_gateway::auth::Caller;
+use crate::helix_gateway::jwt::JwtPrincipal;
+use crate::helix_gateway::rate_limit::{ClientAddr, ClientId, FORWARDED_FOR_HEADER, RateLimiter};
+use crate::protocol::HelixError;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+fn limit(requests_per_sec: u32, burst: Option<u32>) -> RateLimit {
+    RateLimit {
+        requests_per_sec,
+        burst,
+    }
+}
+
+fn limiter(global: Option<RateLimit>, routes: &[(&str, RateLimit)]) -> RateLimiter {
+    RateLimiter::from_config(Some(&RateLimitConfig {
+        global,
+        routes: Some(
+            routes
+                .iter()
+                .map(|(route, limit)| (route.to_string(), *limit))
+                .collect::<HashMap<_, _>>(),
+        ),
+        trust_forwarded_for: None,
+    }))
+}
+
+fn ip(last: u8) -> ClientId {
+    ClientId::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)))
+}
+
+#[test]
+fn test_disabled_without_config() {
+    let limiter = RateLimiter::from_config(None);
+    assert!(!limiter.is_enabled());
+    for _ in 0..100 {
+        assert!(limiter.check("getUser", &ip(1)).is_ok());
+    }
+}
+
+#[test]
+fn test_burst_then_rejected_with_retry_after() {
+    let limiter = limiter(Some(limit(1, Some(3))), &[]);
+    let now = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    }
+    match limiter.check_at("getUser", &ip(1), now) {
+        Err(HelixError::RateLimited {
+            limit,
+            retry_after_secs,
+        }) => {
+            assert_eq!(limit, 3);
+            assert_eq!(retry_after_secs, 1);
+        }
+        other => panic!("expected RateLimited, got {other:?}"),
+    }
+    assert_eq!(limiter.rejected_total(), 1);
+}
+
+#[test]
+fn test_tokens_refill_over_time() {
+    let limiter = limiter(Some(limit(2, Some(2))), &[]);
+    let now = Instant::now();
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_err());
+
+    // Half a second refills one token at 2 requests per second
+    let later = now + Duration::from_millis(500);
+    assert!(limiter.check_at("getUser", &ip(1), later).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), later).is_err());
+
+    // Refilling stops at the burst size
+    let much_later = later + Duration::from_secs(60);
+    assert!(limiter.check_at("getUser", &ip(1), much_later).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), much_later).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), much_later).is_err());
+}
+
+#[test]
+fn test_clients_have_separate_buckets() {
+    let limiter = limiter(Some(limit(1, Some(1))), &[]);
+    let now = Instant::now();
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_err());
+    assert!(limiter.check_at("getUser", &ip(2), now).is_ok());
+    assert!(
+        limiter
+            .check_at("getUser", &ClientId::ApiKey("ci".to_string()), now)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_global_limit_is_shared_across_routes() {
+    let limiter = limiter(Some(limit(1, Some(2))), &[]);
+    let now = Instant::now();
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("listPosts", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_err());
+    assert!(limiter.check_at("listPosts", &ip(1), now).is_err());
+}
+
+#[test]
+fn test_route_override_replaces_global_limit() {
+    let limiter = limiter(
+        Some(limit(1, Some(1))),
+        &[("bulkImport", limit(1, Some(3)))],
+    );
+    let now = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.check_at("bulkImport", &ip(1), now).is_ok());
+    }
+    assert!(limiter.check_at("bulkImport", &ip(1), now).is_err());
+    // The override has its own bucket, so the global budget is untouched
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_err());
+}
+
+#[test]
+fn test_route_override_without_global_limit() {
+    let limiter = limiter(None, &[("bulkImport", limit(1, None))]);
+    assert!(limiter.is_enabled());
+    let now = Instant::now();
+    assert!(limiter.check_at("bulkImport", &ip(1), now).is_ok());
+    assert!(limiter.check_at("bulkImport", &ip(1), now).is_err());
+    for _ in 0..10 {
+        assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    }
+}
+
+#[test]
+fn test_zero_rate_is_treated_as_one() {
+    let limit = limit(0, None);
+    assert_eq!(limit.requests_per_sec(), 1);
+    assert_eq!(limit.burst(), 1);
+}
+
+#[test]
+fn test_client_id_prefers_credentials() {
+    let limiter = RateLimiter::default();
+    let addr = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    let headers = HeaderMap::new();
+
+    let key = Caller::ApiKey(AuthorizedKey {
+        name: "ci".to_string(),
+        scope: ApiKeyScope::ReadOnly,
+    });
+    assert_eq!(
+        limiter.client_id(Some(&key), addr, &headers),
+        ClientId::ApiKey("ci".to_string())
+    );
+
+    let user = Caller::Jwt(JwtPrincipal {
+        subject: Some("user-1".to_string()),
+        roles: vec![],
+    });
+    assert_eq!(
+        limiter.client_id(Some(&user), addr, &headers),
+        ClientId::Subject("user-1".to_string())
+    );
+
+    let anonymous = Caller::Jwt(JwtPrincipal {
+        subject: None,
+        roles: vec![],
+    });
+    assert_eq!(
+        limiter.client_id(Some(&anonymous), addr, &headers),
+        ClientId::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    );
+    assert_eq!(limiter.client_id(None, None, &headers), ClientId::Unknown);
+}
+
+#[test]
+fn test_forwarded_for_only_when_trusted() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        FORWARDED_FOR_HEADER,
+        "203.0.113.9, 198.51.100.7".parse().unwrap(),
+    );
+    let addr = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+
+    let untrusted = RateLimiter::default();
+    assert_eq!(untrusted.client_id(None, addr, &headers), ip(1));
+
+    let trusted = RateLimiter::from_config(Some(&RateLimitConfig {
+        trust_forwarded_for: Some(true),
+        ..Default::default()
+    }));
+    // The last entry is the one the proxy appended
+    assert_eq!(
+        trusted.client_id(None, addr, &headers),
+        ClientId::Ip("198.51.100.7".parse().unwrap())
+    );
+
+    headers.insert(FORWARDED_FOR_HEADER, "garbage".parse().unwrap());
+    assert_eq!(trusted.client_id(None, addr, &headers), ip(1));
+}
+
+#[tokio::test]
+async fn test_client_addr_from_connect_info() {
+    let (mut parts, _) = axum::http::Request::builder()
+        .uri("/query")
+        .body(())
+        .unwrap()
+        .into_parts();
+    let ClientAddr(addr) = ClientAddr::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(addr, None);
+
+    parts
+        .extensions
+        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4321))));
+    let ClientAddr(addr) = ClientAddr::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(addr, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+}
+
+#[test]
+fn test_rate_limit_config_parses() {
+    let config: RateLimitConfig = sonic_rs::from_str(
+        r#"{"global":{"requests_per_sec":50},"routes":{"bulkImport":{"requests_per_sec":1,"burst":5}}}"#,
+    )
+    .unwrap();
+    assert_eq!(config.global, Some(limit(50, None)));
+    assert_eq!(config.global.unwrap().burst(), 50);
+    assert!(!config.trust_forwarded_for());
+    assert_eq!(config.routes.unwrap()["bulkImport"], limit(1, Some(5)));
+}
+
+#[test]
+fn test_reload_replaces_limits_and_refills_buckets() {
+    let limiter = limiter(Some(limit(1, Some(1))), &[]);
+    let now = Instant::now();
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("getUser", &ip(1), now).is_err());
+
+    limiter.reload(Some(&RateLimitConfig {
+        global: None,
+        routes: Some(HashMap::from([("addUser".to_string(), limit(1, Some(2)))])),
+        trust_forwarded_for: None,
+    }));
+    assert_eq!(limiter.limited_routes(), vec!["addUser".to_string()]);
+    assert!(limiter.check_at("getUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("addUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("addUser", &ip(1), now).is_ok());
+    assert!(limiter.check_at("addUser", &ip(1), now).is_err());
+
+    limiter.reload(None);
+    assert!(!limiter.is_enabled());
+    assert!(limiter.check_at("addUser", &ip(1), now).is_ok());
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::helix_engine::traversal_core::config::{Config, GatewayConfig, RateLimit};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::admin::{AdminAuth, AdminInfo, admin_config_handler};
+use crate::helix_gateway::api_keys::ApiKeys;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::{ClientId, RateLimiter};
+use crate::helix_gateway::reload::{
+    LogLevel, Reloader, Tunables, admin_reload_handler, read_tunables,
+};
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+use axum::Extension;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+
+fn noop(_input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: b"null".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+/// App state serving `getUser`, with a gateway config setting every tunable
+fn create_test_state() -> (Arc<AppState>, Arc<AdminInfo>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let gateway_config = GatewayConfig {
+        slow_query_ms: Some(100),
+        cache_max_entries: Some(10),
+        ..Default::default()
+    };
+    let config = Config {
+        db_max_size_gb: Some(0),
+        gateway_config: Some(gateway_config.clone()),
+        ..Default::default()
+    };
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: config.clone(),
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert("getUser".to_string(), Arc::new(noop));
+    let router = HelixRouter::new(Some(routes), None, None);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let state = Arc::new(AppState {
+        worker_pool: WorkerPool::with_config(
+            core_setter,
+            graph,
+            Arc::new(router),
+            rt,
+            &gateway_config,
+        ),
+        schema_json: None,
+        cluster_id: None,
+        api_keys: ApiKeys::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    let info = Arc::new(AdminInfo::new(&config, 8));
+    (state, info, temp_dir)
+}
+
+async fn reload(
+    state: &Arc<AppState>,
+    reloader: &Arc<Reloader>,
+    body: &str,
+) -> axum::response::Response {
+    admin_reload_handler(
+        AdminAuth,
+        State(Arc::clone(state)),
+        Extension(Arc::clone(reloader)),
+        Bytes::from(body.to_string()),
+    )
+    .await
+}
+
+async fn json_body(response: axum::response::Response) -> sonic_rs::Value {
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    sonic_rs::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_reload_applies_tunables() {
+    let (state, info, _dir) = create_test_state();
+    let reloader = Arc::new(Reloader::new(None, Arc::clone(&info)));
+    assert_eq!(
+        state.worker_pool.slow_query_threshold(),
+        Some(Duration::from_millis(100))
+    );
+
+    let body = json_body(
+        reload(
+            &state,
+            &reloader,
+            r#"{"slow_query_ms": 5, "rate_limit": {"global": {"requests_per_sec": 1, "burst": 1}}}"#,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(body["slow_query_ms"].as_u64(), Some(5));
+    assert_eq!(
+        body["cache_max_entries"].as_u64(),
+        Some(GatewayConfig::DEFAULT_CACHE_MAX_ENTRIES as u64)
+    );
+    assert_eq!(
+        body["log_level"].as_str(),
+        Some(GatewayConfig::DEFAULT_LOG_LEVEL)
+    );
+
+    assert_eq!(
+        state.worker_pool.slow_query_threshold(),
+        Some(Duration::from_millis(5))
+    );
+    let now = Instant::now();
+    assert!(
+        state
+            .rate_limiter
+            .check_at("getUser", &ClientId::Unknown, now)
+            .is_ok()
+    );
+    assert!(
+        state
+            .rate_limiter
+            .check_at("getUser", &ClientId::Unknown, now)
+            .is_err()
+    );
+
+    // Unset tunables go back to their defaults
+    json_body(reload(&state, &reloader, "{}").await).await;
+    assert_eq!(state.worker_pool.slow_query_threshold(), None);
+    assert!(!state.rate_limiter.is_enabled());
+}
+
+#[tokio::test]
+async fn test_reload_is_reported_in_admin_config() {
+    let (state, info, _dir) = create_test_state();
+    let reloader = Arc::new(Reloader::new(None, Arc::clone(&info)));
+    json_body(reload(&state, &reloader, r#"{"cache_max_entries": 0}"#).await).await;
+
+    let config = json_body(admin_config_handler(AdminAuth, Extension(info)).await).await;
+    let gateway = &config["gateway_config"];
+    assert_eq!(gateway["cache_max_entries"].as_u64(), Some(0));
+    assert!(gateway["slow_query_ms"].is_null());
+    assert_eq!(gateway["workers_per_core"].as_u64(), Some(8));
+    assert_eq!(state.worker_pool.stats().cache_entries, 0);
+}
+
+#[tokio::test]
+async fn test_reload_rejects_settings_that_need_a_restart() {
+    let (state, info, _dir) = create_test_state();
+    let reloader = Arc::new(Reloader::new(None, info));
+    let response = reload(
+        &state,
+        &reloader,
+        r#"{"slow_query_ms": 5, "queue_capacity": 4}"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        state.worker_pool.slow_query_threshold(),
+        Some(Duration::from_millis(100))
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_log_level_changes_nothing() {
+    let (state, info, _dir) = create_test_state();
+    let (_layer, log_level) = LogLevel::layer("info").unwrap();
+    let reloader = Arc::new(Reloader::new(Some(log_level), info));
+    let response = reload(
+        &state,
+        &reloader,
+        r#"{"log_level": "helix_db=loud", "slow_query_ms": 5}"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        state.worker_pool.slow_query_threshold(),
+        Some(Duration::from_millis(100))
+    );
+}
+
+#[test]
+fn test_log_level_changes_what_is_logged() {
+    let (layer, log_level) = LogLevel::layer("info").unwrap();
+    let subscriber = tracing_subscriber::registry().with(layer);
+    tracing::subscriber::with_default(subscriber, || {
+        assert!(tracing::enabled!(Level::INFO));
+        assert!(!tracing::enabled!(Level::DEBUG));
+
+        log_level.set("warn,helix_db=debug").unwrap();
+        assert!(!tracing::enabled!(target: "other", Level::INFO));
+        assert!(tracing::enabled!(target: "helix_db::gateway", Level::DEBUG));
+
+        assert!(log_level.set("helix_db=loud").is_err());
+        assert!(tracing::enabled!(target: "helix_db::gateway", Level::DEBUG));
+    });
+}
+
+#[test]
+fn test_tunables_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("tunables.json");
+    std::fs::write(
+        &path,
+        r#"{"log_level": "info", "rate_limit": {"routes": {"getUser": {"requests_per_sec": 10}}}}"#,
+    )
+    .unwrap();
+    let tunables = read_tunables(&path).unwrap();
+    assert_eq!(tunables.log_level.as_deref(), Some("info"));
+    assert_eq!(
+        tunables.rate_limit.unwrap().routes.unwrap()["getUser"],
+        RateLimit {
+            requests_per_sec: 10,
+            burst: None,
+        }
+    );
+
+    std::fs::write(&path, r#"{"queue_capacity": 10}"#).unwrap();
+    assert!(read_tunables(&path).is_err());
+    assert!(read_tunables(&dir.path().join("missing.json")).is_err());
+}
+
+#[test]
+fn test_tunables_round_trip_through_gateway_config() {
+    let config = GatewayConfig {
+        log_level: Some("debug".to_string()),
+        slow_query_ms: Some(250),
+        queue_capacity: Some(4),
+        ..Default::default()
+    };
+    let tunables = Tunables::from_config(&config);
+    assert_eq!(tunables.slow_query_ms, Some(250));
+
+    let mut reloaded = GatewayConfig {
+        cache_max_entries: Some(5),
+        queue_capacity: Some(4),
+        ..Default::default()
+    };
+    tunables.apply_to(&mut reloaded);
+    assert_eq!(reloaded, config);
+}
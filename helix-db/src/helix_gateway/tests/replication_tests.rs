@@ -0,0 +1,195 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::{Config, StandbyConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::replication::{
+    Role, SNAPSHOT_ROUTE, Standby, leader_handler, promote_synced_snapshot, snapshot_handler,
+    standby_routes,
+};
+use crate::helix_gateway::router::router::HelixRouter;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use heed3::types::Str;
+use heed3::{Database, EnvOpenOptions};
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+const TEST_DB: &str = "replication_test";
+
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let router = Arc::new(HelixRouter::new(None, None, None));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+/// Commit `key` to the leader's environment
+fn put(state: &AppState, key: &str, value: &str) {
+    let env = &state.worker_pool.graph().storage.graph_env;
+    let mut wtxn = env.write_txn().unwrap();
+    let db: Database<Str, Str> = env.create_database(&mut wtxn, Some(TEST_DB)).unwrap();
+    db.put(&mut wtxn, key, value).unwrap();
+    wtxn.commit().unwrap();
+}
+
+/// Values of `keys` in the environment at `path`
+fn read(path: &Path, keys: &[&str]) -> Vec<Option<String>> {
+    let env = unsafe {
+        EnvOpenOptions::new()
+            .map_size(1024 * 1024 * 1024)
+            .max_dbs(200)
+            .open(path)
+            .unwrap()
+    };
+    let rtxn = env.read_txn().unwrap();
+    let db: Database<Str, Str> = env.open_database(&rtxn, Some(TEST_DB)).unwrap().unwrap();
+    keys.iter()
+        .map(|key| db.get(&rtxn, key).unwrap().map(str::to_string))
+        .collect()
+}
+
+/// Serve a leader's `/leader` and snapshots on a local port
+async fn serve(state: &Arc<AppState>) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new()
+        .route("/leader", get(leader_handler))
+        .route(SNAPSHOT_ROUTE, get(snapshot_handler))
+        .with_state(Arc::clone(state))
+        .into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    addr
+}
+
+async fn get_json(app: axum::Router, uri: &str) -> (StatusCode, sonic_rs::Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, sonic_rs::from_slice(&body).unwrap())
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_standby_syncs_leader_commits_and_promotes_last_copy() {
+    let (state, _leader_dir) = create_test_app_state();
+    put(&state, "a", "1");
+    let addr = serve(&state).await;
+
+    let standby_dir = TempDir::new().unwrap();
+    let config = StandbyConfig {
+        leader: format!("http://{addr}/"),
+        ..Default::default()
+    };
+    let standby = Standby::new(&config, standby_dir.path());
+    let first = standby.sync().await.unwrap().expect("first sync copies");
+    // Nothing committed since the copy
+    assert_eq!(standby.sync().await.unwrap(), None);
+
+    put(&state, "b", "2");
+    let second = standby.sync().await.unwrap().expect("commit is copied");
+    assert!(second > first);
+    assert!(matches!(
+        standby.status().role(),
+        Role::Standby { position: Some(p), error: None, .. } if p == second
+    ));
+
+    // A restarted standby resumes from the copy it has
+    let restarted = Standby::new(&config, standby_dir.path());
+    assert_eq!(restarted.sync().await.unwrap(), None);
+
+    fs::write(standby_dir.path().join("data.mdb"), b"standby data").unwrap();
+    assert!(promote_synced_snapshot(standby_dir.path()).unwrap());
+    assert_eq!(
+        fs::read(standby_dir.path().join("data.mdb.pre-promotion")).unwrap(),
+        b"standby data"
+    );
+    assert!(!standby_dir.path().join("standby").exists());
+    assert_eq!(
+        read(standby_dir.path(), &["a", "b"]),
+        vec![Some("1".to_string()), Some("2".to_string())]
+    );
+
+    // Nothing left to promote on the next start
+    assert!(!promote_synced_snapshot(standby_dir.path()).unwrap());
+}
+
+#[tokio::test]
+async fn test_standby_refuses_requests_and_points_at_leader() {
+    let (state, _dir) = create_test_app_state();
+    let standby_dir = TempDir::new().unwrap();
+    let config = StandbyConfig {
+        leader: "http://10.0.0.5:6969".to_string(),
+        ..Default::default()
+    };
+    let standby = Standby::new(&config, standby_dir.path());
+    let app = standby_routes(standby.status()).with_state(state);
+
+    for uri in ["/getUser", "/readyz", "/admin/stats"] {
+        let (status, body) = get_json(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["code"].as_str(), Some("R505"));
+        assert_eq!(
+            body["details"]["leader"].as_str(),
+            Some("http://10.0.0.5:6969")
+        );
+    }
+
+    let (status, body) = get_json(app, "/leader").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["role"].as_str(), Some("standby"));
+    assert_eq!(body["leader"].as_str(), Some("http://10.0.0.5:6969"));
+    assert!(body["position"].is_null());
+}
+
+#[tokio::test]
+async fn test_leader_reports_its_position() {
+    let (state, _dir) = create_test_app_state();
+    let app = axum::Router::new()
+        .route("/leader", get(leader_handler))
+        .with_state(Arc::clone(&state));
+
+    let (_, before) = get_json(app.clone(), "/leader").await;
+    assert_eq!(before["role"].as_str(), Some("leader"));
+    put(&state, "a", "1");
+    let (_, after) = get_json(app, "/leader").await;
+    assert!(after["position"].as_u64().unwrap() > before["position"].as_u64().unwrap());
+}
@@ -0,0 +1,175 @@
+use crate::helix_gateway::result_cache::ResultCache;
+use crate::protocol::{Format, Response};
+use axum::body::Bytes;
+use std::time::Duration;
+
+fn response(body: &[u8]) -> Response {
+    Response {
+        body: body.to_vec(),
+        fmt: Format::Json,
+    }
+}
+
+fn labels(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn cache_entry(cache: &ResultCache, route: &str, route_labels: Option<&[String]>) {
+    let epochs = cache.epochs(route_labels);
+    cache.insert(
+        route,
+        Bytes::new(),
+        Format::Json,
+        &response(b"cached"),
+        Duration::from_secs(60),
+        epochs,
+    );
+}
+
+#[test]
+fn test_cache_hit_and_miss() {
+    let cache = ResultCache::new(10);
+    let person = labels(&["Person"]);
+
+    assert!(
+        cache
+            .get("q", &Bytes::new(), Format::Json, Some(&person))
+            .is_none()
+    );
+    cache_entry(&cache, "q", Some(&person));
+
+    let hit = cache.get("q", &Bytes::new(), Format::Json, Some(&person));
+    assert_eq!(hit.unwrap().body, b"cached");
+    // Different parameters are a different entry
+    assert!(
+        cache
+            .get("q", &Bytes::from_static(b"{}"), Format::Json, Some(&person))
+            .is_none()
+    );
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(cache.misses(), 2);
+}
+
+#[test]
+fn test_cache_entry_expires() {
+    let cache = ResultCache::new(10);
+    let epochs = cache.epochs(None);
+    cache.insert(
+        "q",
+        Bytes::new(),
+        Format::Json,
+        &response(b"cached"),
+        Duration::from_millis(10),
+        epochs,
+    );
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(cache.get("q", &Bytes::new(), Format::Json, None).is_none());
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_write_invalidates_only_matching_labels() {
+    let cache = ResultCache::new(10);
+    let person = labels(&["Person"]);
+    let company = labels(&["Company"]);
+    cache_entry(&cache, "people", Some(&person));
+    cache_entry(&cache, "companies", Some(&company));
+
+    cache.invalidate(Some(&person));
+
+    assert!(
+        cache
+            .get("people", &Bytes::new(), Format::Json, Some(&person))
+            .is_none()
+    );
+    assert!(
+        cache
+            .get("companies", &Bytes::new(), Format::Json, Some(&company))
+            .is_some()
+    );
+}
+
+#[test]
+fn test_unknown_labels_invalidate_conservatively() {
+    let cache = ResultCache::new(10);
+    let person = labels(&["Person"]);
+
+    // A write with unknown labels clears everything
+    cache_entry(&cache, "people", Some(&person));
+    cache.invalidate(None);
+    assert!(cache.is_empty());
+
+    // A read with unknown labels is invalidated by any write
+    cache_entry(&cache, "anything", None);
+    cache.invalidate(Some(&labels(&["Company"])));
+    assert!(
+        cache
+            .get("anything", &Bytes::new(), Format::Json, None)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_write_during_read_leaves_result_stale() {
+    let cache = ResultCache::new(10);
+    let person = labels(&["Person"]);
+
+    // Epochs are captured before the read runs; a write commits before the insert
+    let epochs = cache.epochs(Some(&person));
+    cache.invalidate(Some(&person));
+    cache.insert(
+        "people",
+        Bytes::new(),
+        Format::Json,
+        &response(b"stale"),
+        Duration::from_secs(60),
+        epochs,
+    );
+
+    assert!(
+        cache
+            .get("people", &Bytes::new(), Format::Json, Some(&person))
+            .is_none()
+    );
+}
+
+#[test]
+fn test_cache_respects_max_entries() {
+    let cache = ResultCache::new(1);
+    cache_entry(&cache, "first", None);
+    cache_entry(&cache, "second", None);
+
+    assert_eq!(cache.len(), 1);
+    assert!(
+        cache
+            .get("first", &Bytes::new(), Format::Json, None)
+            .is_some()
+    );
+
+    let disabled = ResultCache::new(0);
+    assert!(!disabled.is_enabled());
+    cache_entry(&disabled, "first", None);
+    assert!(disabled.is_empty());
+}
+
+#[test]
+fn test_shrinking_cache_drops_entries() {
+    let cache = ResultCache::new(10);
+    cache_entry(&cache, "a", None);
+    cache_entry(&cache, "b", None);
+
+    cache.set_max_entries(20);
+    assert_eq!(cache.len(), 2);
+
+    cache.set_max_entries(1);
+    assert!(cache.is_empty());
+    cache_entry(&cache, "a", None);
+    cache_entry(&cache, "b", None);
+    assert_eq!(cache.len(), 1);
+
+    cache.set_max_entries(0);
+    assert!(!cache.is_enabled());
+    cache_entry(&cache, "a", None);
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::helix_engine::traversal_core::config::{Config, ScheduleConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HandlerInput, HelixRouter};
+use crate::helix_gateway::scheduler::{ScheduleError, Scheduler};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::{Format, Response};
+use tempfile::TempDir;
+
+fn schedule(name: &str, query: &str, cron: &str) -> ScheduleConfig {
+    ScheduleConfig {
+        name: name.to_string(),
+        query: query.to_string(),
+        cron: cron.to_string(),
+        params: None,
+    }
+}
+
+/// App state with a `sweep` route counting its calls and a `failing` route
+fn create_test_app_state(calls: Arc<AtomicUsize>) -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert(
+        "sweep".to_string(),
+        Arc::new(move |input: HandlerInput| {
+            assert_eq!(input.request.body.as_ref(), br#"{"older_than_days":30}"#);
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Response {
+                body: b"null".to_vec(),
+                fmt: Format::Json,
+            })
+        }),
+    );
+    routes.insert(
+        "failing".to_string(),
+        Arc::new(|_| Err(GraphError::New("sweep failed".to_string()))),
+    );
+    let router = Arc::new(HelixRouter::new(Some(routes), None, None));
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, router, rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+#[test]
+fn test_from_config_accepts_standard_and_extended_cron() {
+    let scheduler = Scheduler::from_config(&[
+        schedule("nightly", "rollup", "0 3 * * *"),
+        schedule("every-ten-seconds", "sweep", "*/10 * * * * *"),
+        schedule("daily", "sweep", "@daily"),
+    ])
+    .unwrap();
+    assert_eq!(
+        scheduler.queries().collect::<Vec<_>>(),
+        ["rollup", "sweep", "sweep"]
+    );
+    let statuses = scheduler.statuses();
+    assert_eq!(statuses[0].cron, "0 3 * * *");
+    assert!(
+        statuses
+            .iter()
+            .all(|s| s.next_run_ms.is_none() && s.runs.is_empty())
+    );
+}
+
+#[test]
+fn test_from_config_rejects_bad_schedules() {
+    assert!(matches!(
+        Scheduler::from_config(&[schedule("nightly", "rollup", "at 3am")]),
+        Err(ScheduleError::InvalidCron { name, .. }) if name == "nightly"
+    ));
+    assert!(matches!(
+        Scheduler::from_config(&[
+            schedule("nightly", "rollup", "@daily"),
+            schedule("nightly", "sweep", "@hourly"),
+        ]),
+        Err(ScheduleError::DuplicateName(name)) if name == "nightly"
+    ));
+}
+
+#[tokio::test]
+async fn test_scheduler_runs_queries_and_records_history() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (state, _dir) = create_test_app_state(Arc::clone(&calls));
+    let mut sweep = schedule("sweep", "sweep", "* * * * * *");
+    sweep.params = Some(sonic_rs::from_str(r#"{"older_than_days": 30}"#).unwrap());
+    let scheduler = Arc::new(
+        Scheduler::from_config(&[sweep, schedule("failing", "failing", "* * * * * *")]).unwrap(),
+    );
+
+    let run = Arc::clone(&scheduler).run(Arc::clone(&state));
+    assert!(
+        tokio::time::timeout(Duration::from_millis(2500), run)
+            .await
+            .is_err()
+    );
+
+    assert!(calls.load(Ordering::SeqCst) >= 1);
+    let statuses = scheduler.statuses();
+    let sweep = &statuses[0];
+    assert_eq!(sweep.runs.len(), calls.load(Ordering::SeqCst));
+    assert!(sweep.runs.iter().all(|run| run.ok && run.error.is_none()));
+    assert!(sweep.next_run_ms.is_some());
+    if let [newest, older, ..] = sweep.runs.as_slice() {
+        assert!(newest.started_at_ms > older.started_at_ms);
+    }
+
+    let failing = &statuses[1];
+    assert!(!failing.runs.is_empty());
+    assert!(failing.runs.iter().all(|run| !run.ok));
+    assert!(
+        failing.runs[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("sweep failed")
+    );
+}
+
+#[tokio::test]
+async fn test_scheduler_stops_on_shutdown() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (state, _dir) = create_test_app_state(Arc::clone(&calls));
+    let scheduler =
+        Arc::new(Scheduler::from_config(&[schedule("sweep", "sweep", "* * * * * *")]).unwrap());
+
+    state.worker_pool.begin_shutdown();
+    tokio::time::timeout(Duration::from_secs(3), scheduler.run(state))
+        .await
+        .expect("scheduler should stop once the pool is shutting down");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_schedules_handler_lists_schedules() {
+    use crate::helix_gateway::admin::AdminAuth;
+    use crate::helix_gateway::scheduler::schedules_handler;
+    use axum::Extension;
+    use axum::http::StatusCode;
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+
+    let scheduler =
+        Arc::new(Scheduler::from_config(&[schedule("nightly", "rollup", "0 3 * * *")]).unwrap());
+    let response = schedules_handler(AdminAuth, Extension(scheduler)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: sonic_rs::Value = sonic_rs::from_slice(&body).unwrap();
+    let schedules = body["schedules"].as_array().unwrap();
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0]["name"].as_str(), Some("nightly"));
+    assert_eq!(schedules[0]["query"].as_str(), Some("rollup"));
+    assert!(schedules[0]["runs"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use tempfile::TempDir;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::traversal_core::config::{Config, GatewayConfig, SessionConfig};
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::CoreSetter;
+use crate::helix_gateway::router::router::{HandlerInput, HelixRouter};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::{Request, RequestHints, RequestType};
+use crate::protocol::{Format, HelixError, Response};
+use crate::utils::items::Node;
+
+/// Number of nodes, as seen by the request's read transaction
+fn count_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let storage = &input.graph.storage;
+    let txn = storage.read_txn()?;
+    Ok(Response {
+        body: storage.nodes_db.len(&txn)?.to_string().into_bytes(),
+        fmt: Format::Json,
+    })
+}
+
+fn add_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let storage = &input.graph.storage;
+    let mut txn = storage.graph_env.write_txn()?;
+    let id = uuid::Uuid::new_v4().as_u128();
+    let node = Node {
+        id,
+        label: "Item",
+        version: 1,
+        properties: None,
+    };
+    storage.nodes_db.put(
+        &mut txn,
+        HelixGraphStorage::node_key(&id),
+        &node.to_bincode_bytes()?,
+    )?;
+    txn.commit()?;
+    Ok(Response {
+        body: b"null".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn session_test_pool(sessions: Option<SessionConfig>) -> (Arc<WorkerPool>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("count", count_handler, false);
+    router.add_route("add", add_handler, true);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let config = GatewayConfig {
+        sessions,
+        ..Default::default()
+    };
+    let pool = WorkerPool::with_config(core_setter, graph, Arc::new(router), rt, &config);
+    (Arc::new(pool), temp_dir)
+}
+
+async fn run(pool: &WorkerPool, name: &str, session: Option<&str>) -> Result<String, HelixError> {
+    let req = Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    };
+    let hints = RequestHints {
+        session: session.map(str::to_string),
+        ..Default::default()
+    };
+    let res = pool.process_with(req, hints).await?;
+    Ok(String::from_utf8(res.body).unwrap())
+}
+
+#[tokio::test]
+async fn test_session_reads_the_snapshot_it_was_opened_on() {
+    let (pool, _temp_dir) = session_test_pool(Some(SessionConfig::default()));
+    run(&pool, "add", None).await.unwrap();
+
+    let session = pool.sessions().open().unwrap();
+    assert_eq!(
+        session.idle_timeout_secs,
+        SessionConfig::DEFAULT_IDLE_TIMEOUT_SECS
+    );
+    run(&pool, "add", None).await.unwrap();
+
+    assert_eq!(
+        run(&pool, "count", Some(&session.session)).await.unwrap(),
+        "1"
+    );
+    assert_eq!(run(&pool, "count", None).await.unwrap(), "2");
+    run(&pool, "add", None).await.unwrap();
+    assert_eq!(
+        run(&pool, "count", Some(&session.session)).await.unwrap(),
+        "1"
+    );
+
+    // A session opened later sees the writes before it
+    let later = pool.sessions().open().unwrap();
+    assert!(later.txn_id > session.txn_id);
+    assert_eq!(
+        run(&pool, "count", Some(&later.session)).await.unwrap(),
+        "3"
+    );
+    assert_eq!(pool.sessions().len(), 2);
+
+    assert!(pool.sessions().close(&session.session));
+    assert!(!pool.sessions().close(&session.session));
+    assert!(matches!(
+        run(&pool, "count", Some(&session.session)).await,
+        Err(HelixError::SessionNotFound(token)) if token == session.session
+    ));
+}
+
+#[tokio::test]
+async fn test_sessions_reject_writes_and_unknown_tokens() {
+    let (pool, _temp_dir) = session_test_pool(Some(SessionConfig::default()));
+    let session = pool.sessions().open().unwrap();
+
+    assert!(matches!(
+        run(&pool, "add", Some(&session.session)).await,
+        Err(HelixError::InvalidSession(_))
+    ));
+    assert!(matches!(
+        run(&pool, "count", Some("missing")).await,
+        Err(HelixError::SessionNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sessions_are_capped_and_off_by_default() {
+    let (pool, _temp_dir) = session_test_pool(Some(SessionConfig {
+        max_sessions: Some(2),
+        idle_timeout_secs: None,
+    }));
+    let first = pool.sessions().open().unwrap();
+    pool.sessions().open().unwrap();
+    assert!(matches!(
+        pool.sessions().open(),
+        Err(HelixError::TooManySessions { max: 2 })
+    ));
+    pool.sessions().close(&first.session);
+    pool.sessions().open().unwrap();
+
+    let (pool, _temp_dir) = session_test_pool(None);
+    assert!(!pool.sessions().is_enabled());
+    assert!(matches!(
+        pool.sessions().open(),
+        Err(HelixError::InvalidSession(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_idle_sessions_expire() {
+    let (pool, _temp_dir) = session_test_pool(Some(SessionConfig {
+        max_sessions: Some(1),
+        idle_timeout_secs: Some(1),
+    }));
+    let session = pool.sessions().open().unwrap();
+    run(&pool, "count", Some(&session.session)).await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !pool.sessions().is_empty() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("session should have expired");
+    assert!(matches!(
+        run(&pool, "count", Some(&session.session)).await,
+        Err(HelixError::SessionNotFound(_))
+    ));
+    // The expired session no longer counts towards the cap
+    pool.sessions().open().unwrap();
+}
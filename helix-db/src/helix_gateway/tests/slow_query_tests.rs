@@ -0,0 +1,93 @@
+use crate::helix_engine::storage_core::scan_counter;
+use crate::helix_engine::traversal_core::config::GatewayConfig;
+use crate::helix_engine::traversal_core::step_profile::{self, StepTiming};
+use crate::helix_gateway::slow_query::{format_steps, redact_params};
+use std::time::Duration;
+
+#[test]
+fn test_redact_params_hides_values() {
+    let body = br#"{"name":"alice","age":42,"admin":true,"nickname":null}"#;
+    assert_eq!(
+        redact_params(body),
+        "{name: <string>, age: <number>, admin: <bool>, nickname: null}"
+    );
+}
+
+#[test]
+fn test_redact_params_collapses_arrays() {
+    let body = br#"{"vector":[0.1,0.2,0.3],"filter":{"ids":["a","b"]}}"#;
+    assert_eq!(
+        redact_params(body),
+        "{vector: <array len=3>, filter: {ids: <array len=2>}}"
+    );
+}
+
+#[test]
+fn test_redact_params_empty_and_invalid() {
+    assert_eq!(redact_params(b""), "{}");
+    assert_eq!(redact_params(b"not json"), "<unparseable>");
+}
+
+#[test]
+fn test_scan_counter_take_resets() {
+    scan_counter::reset();
+    scan_counter::record(2);
+    scan_counter::record(3);
+    assert_eq!(scan_counter::take(), 5);
+    assert_eq!(scan_counter::take(), 0);
+}
+
+#[test]
+fn test_step_profile_only_records_when_enabled() {
+    step_profile::reset(false);
+    let started = step_profile::start();
+    step_profile::record("skipped", started);
+    assert!(step_profile::take().is_empty());
+
+    step_profile::reset(true);
+    let started = step_profile::start();
+    scan_counter::record(4);
+    step_profile::record("users", started);
+    let steps = step_profile::take();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].step, "users");
+    assert_eq!(steps[0].items_scanned, 4);
+
+    // Taking the steps turns profiling off for the next handler
+    step_profile::record("after", step_profile::start());
+    assert!(step_profile::take().is_empty());
+}
+
+#[test]
+fn test_format_steps_slowest_first() {
+    let steps = [
+        StepTiming {
+            step: "user",
+            elapsed: Duration::from_micros(200),
+            items_scanned: 1,
+        },
+        StepTiming {
+            step: "posts",
+            elapsed: Duration::from_micros(12_500),
+            items_scanned: 340,
+        },
+    ];
+    assert_eq!(
+        format_steps(&steps),
+        "posts 12.50ms (340 scanned), user 0.20ms (1 scanned)"
+    );
+    assert_eq!(format_steps(&[]), "");
+}
+
+#[test]
+fn test_slow_query_threshold_config() {
+    assert_eq!(GatewayConfig::default().slow_query_threshold(), None);
+    let config = GatewayConfig {
+        slow_query_ms: Some(250),
+        ..Default::default()
+    };
+    assert_eq!(
+        config.slow_query_threshold(),
+        Some(Duration::from_millis(250))
+    );
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::change_feed::{Change, ChangeFeed};
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HelixRouter};
+use crate::helix_gateway::subscriptions::subscribe_handler;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::{Request, RequestType};
+use crate::protocol::{Format, Response};
+use axum::body::Bytes;
+use axum::routing::get;
+use futures_util::{SinkExt, StreamExt};
+use sonic_rs::JsonValueTrait;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn count_response(count: usize) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: count.to_string().into_bytes(),
+        fmt: Format::Json,
+    })
+}
+
+/// App state with a `count` read and an `increment` write on the `Counter` label, and a
+/// `touch_other` write on the `Other` label
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let read_counter = Arc::clone(&counter);
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    routes.insert(
+        "count".to_string(),
+        Arc::new(move |_| count_response(read_counter.load(Ordering::SeqCst))),
+    );
+    routes.insert(
+        "increment".to_string(),
+        Arc::new(move |_| count_response(counter.fetch_add(1, Ordering::SeqCst) + 1)),
+    );
+    routes.insert("touch_other".to_string(), Arc::new(|_| count_response(0)));
+    let write_routes = HashSet::from(["increment".to_string(), "touch_other".to_string()]);
+    let mut router = HelixRouter::new(Some(routes), None, Some(write_routes));
+    router.route_labels = HashMap::from([
+        ("count".to_string(), vec!["Counter".to_string()]),
+        ("increment".to_string(), vec!["Counter".to_string()]),
+        ("touch_other".to_string(), vec!["Other".to_string()]),
+    ]);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, Arc::new(router), rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+/// Serve `/subscribe` on a local port and connect to it
+async fn connect(state: &Arc<AppState>) -> Client {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new()
+        .route("/subscribe", get(subscribe_handler))
+        .with_state(Arc::clone(state))
+        .into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let (client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/subscribe"))
+        .await
+        .unwrap();
+    client
+}
+
+async fn send(client: &mut Client, msg: &str) {
+    client.send(Message::text(msg)).await.unwrap();
+}
+
+async fn next_json(client: &mut Client) -> sonic_rs::Value {
+    let msg = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("expected a message")
+        .unwrap()
+        .unwrap();
+    sonic_rs::from_str(msg.to_text().unwrap()).unwrap()
+}
+
+async fn assert_silent(client: &mut Client) {
+    let msg = tokio::time::timeout(Duration::from_millis(200), client.next()).await;
+    assert!(msg.is_err(), "unexpected message: {msg:?}");
+}
+
+async fn write(state: &AppState, name: &str) {
+    let req = Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    };
+    state.worker_pool.process(req).await.unwrap();
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_subscription_receives_updates_after_writes() {
+    let (state, _dir) = create_test_app_state();
+    let mut client = connect(&state).await;
+
+    send(
+        &mut client,
+        r#"{"type":"subscribe","id":"c","name":"count"}"#,
+    )
+    .await;
+    let msg = next_json(&mut client).await;
+    assert_eq!(msg["type"].as_str(), Some("result"));
+    assert_eq!(msg["id"].as_str(), Some("c"));
+    assert_eq!(msg["result"].as_u64(), Some(0));
+
+    write(&state, "increment").await;
+    let msg = next_json(&mut client).await;
+    assert_eq!(msg["id"].as_str(), Some("c"));
+    assert_eq!(msg["result"].as_u64(), Some(1));
+
+    // Writes to unrelated labels don't produce updates
+    write(&state, "touch_other").await;
+    assert_silent(&mut client).await;
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_unsubscribe_stops_updates() {
+    let (state, _dir) = create_test_app_state();
+    let mut client = connect(&state).await;
+
+    send(
+        &mut client,
+        r#"{"type":"subscribe","id":"c","name":"count"}"#,
+    )
+    .await;
+    next_json(&mut client).await;
+    send(&mut client, r#"{"type":"unsubscribe","id":"c"}"#).await;
+    // Unsubscribing is not acknowledged, so wait until the server has handled it
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    write(&state, "increment").await;
+    assert_silent(&mut client).await;
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_subscription_errors() {
+    let (state, _dir) = create_test_app_state();
+    let mut client = connect(&state).await;
+
+    send(
+        &mut client,
+        r#"{"type":"subscribe","id":"w","name":"increment"}"#,
+    )
+    .await;
+    let msg = next_json(&mut client).await;
+    assert_eq!(msg["type"].as_str(), Some("error"));
+    assert_eq!(msg["id"].as_str(), Some("w"));
+    assert_eq!(msg["code"].as_str(), Some("R104"));
+
+    send(
+        &mut client,
+        r#"{"type":"subscribe","id":"m","name":"missing"}"#,
+    )
+    .await;
+    let msg = next_json(&mut client).await;
+    assert_eq!(msg["status"].as_u64(), Some(404));
+    assert_eq!(msg["code"].as_str(), Some("R301"));
+
+    send(&mut client, r#"{"type":"watch"}"#).await;
+    let msg = next_json(&mut client).await;
+    assert!(msg["id"].is_null());
+    assert_eq!(msg["code"].as_str(), Some("R104"));
+
+    // Failed subscriptions are not kept
+    write(&state, "increment").await;
+    assert_silent(&mut client).await;
+}
+
+#[cfg(not(feature = "api-key"))]
+#[tokio::test]
+async fn test_subscriptions_closed_on_shutdown() {
+    let (state, _dir) = create_test_app_state();
+    let mut client = connect(&state).await;
+    send(
+        &mut client,
+        r#"{"type":"subscribe","id":"c","name":"count"}"#,
+    )
+    .await;
+    next_json(&mut client).await;
+
+    state.worker_pool.begin_shutdown();
+    let msg = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::Close(Some(_))));
+}
+
+#[test]
+fn test_change_affects_overlapping_labels() {
+    let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+    let write = Change::Write {
+        labels: Some(labels(&["User", "Follows"])),
+    };
+    assert!(write.affects(Some(&labels(&["Post", "User"]))));
+    assert!(!write.affects(Some(&labels(&["Post"]))));
+    // Unknown labels on either side could overlap
+    assert!(write.affects(None));
+    assert!(Change::Write { labels: None }.affects(Some(&labels(&["Post"]))));
+    assert!(!Change::ShuttingDown.affects(None));
+}
+
+#[tokio::test]
+async fn test_change_feed_publishes_to_listeners() {
+    let feed = ChangeFeed::default();
+    // Nobody listens, so nothing is buffered
+    feed.publish_write(None);
+
+    let mut changes = feed.subscribe();
+    feed.publish_write(Some(&["User".to_string()]));
+    feed.publish_shutdown();
+    assert_eq!(
+        changes.recv().await.unwrap(),
+        Change::Write {
+            labels: Some(vec!["User".to_string()])
+        }
+    );
+    assert_eq!(changes.recv().await.unwrap(), Change::ShuttingDown);
+    assert!(changes.try_recv().is_err());
+}
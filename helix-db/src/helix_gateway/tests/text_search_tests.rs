@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use crate::helix_engine::bm25::bm25::BM25;
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::ops::g::G;
+use crate::helix_engine::traversal_core::ops::source::add_n::AddNAdapter;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::helix_gateway::text_search::{SEARCH_TEXT_ROUTE, search_text};
+use crate::protocol::request::RequestType;
+use crate::protocol::value::Value;
+use crate::protocol::{Format, Request};
+use crate::utils::properties::ImmutablePropertiesMap;
+use axum::body::Bytes;
+use bumpalo::Bump;
+use heed3::RoTxn;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+fn create_test_graph() -> (Arc<HelixGraphEngine>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config::default(),
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    (graph, temp_dir)
+}
+
+/// Adds an `Article` node per title and body, returning their ids
+fn add_articles(graph: &HelixGraphEngine, articles: &[(&str, &str)]) -> Vec<u128> {
+    let storage = graph.storage.as_ref();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let ids = articles
+        .iter()
+        .map(|(title, body)| {
+            let properties = ImmutablePropertiesMap::new(
+                3,
+                [
+                    ("title", Value::String(title.to_string())),
+                    ("body", Value::String(body.to_string())),
+                    ("views", Value::I32(10)),
+                ]
+                .into_iter(),
+                &arena,
+            );
+            G::new_mut(storage, &arena, &mut txn)
+                .add_n("Article", Some(properties), None)
+                .collect_to_obj()
+                .unwrap()
+                .id()
+        })
+        .collect();
+    txn.commit().unwrap();
+    ids
+}
+
+fn search(graph: &Arc<HelixGraphEngine>, body: sonic_rs::Value) -> sonic_rs::Value {
+    let input = HandlerInput {
+        request: Request {
+            name: SEARCH_TEXT_ROUTE.to_string(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: Bytes::from(body.to_string()),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        },
+        graph: Arc::clone(graph),
+    };
+    let response = search_text(input).unwrap();
+    sonic_rs::from_slice(&response.body).unwrap()
+}
+
+#[test]
+fn test_search_text_highlights_matching_properties() {
+    let (graph, _temp_dir) = create_test_graph();
+    add_articles(
+        &graph,
+        &[
+            (
+                "Graph databases",
+                "Graph databases store relationships next to the data they join.",
+            ),
+            ("Cooking", "Slow roasted vegetables with rosemary."),
+        ],
+    );
+
+    let body = search(
+        &graph,
+        sonic_rs::json!({
+            "label": "Article",
+            "query": "relationships graph",
+            "highlight": {},
+            "properties": false,
+        }),
+    );
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    let hit = &results[0];
+    assert!(hit.get("properties").is_none());
+    assert_eq!(
+        hit["highlights"]["title"][0]["text"].as_str(),
+        Some("<em>Graph</em> databases")
+    );
+    assert_eq!(
+        hit["highlights"]["body"][0]["text"].as_str(),
+        Some("<em>Graph</em> databases store <em>relationships</em> next to the data they join.")
+    );
+    let offsets = hit["highlights"]["body"][0]["matches"][1]
+        .as_array()
+        .unwrap();
+    assert_eq!(offsets[0].as_u64(), Some(22));
+    assert_eq!(offsets[1].as_u64(), Some(35));
+}
+
+#[test]
+fn test_search_text_without_highlight_returns_properties() {
+    let (graph, _temp_dir) = create_test_graph();
+    add_articles(&graph, &[("Rust", "Ownership and borrowing explained.")]);
+
+    let body = search(
+        &graph,
+        sonic_rs::json!({"label": "Article", "query": "borrowing"}),
+    );
+    let hit = &body["results"][0];
+    assert!(hit.get("highlights").is_none());
+    assert_eq!(hit["properties"]["title"].as_str(), Some("Rust"));
+    assert!(hit["score"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn test_search_text_hybrid_highlights_vectors() {
+    let (graph, _temp_dir) = create_test_graph();
+    let storage = graph.storage.as_ref();
+    let arena = Bump::new();
+    let mut txn = storage.graph_env.write_txn().unwrap();
+    let mut ids = Vec::new();
+    for (vector, text) in [
+        ([1.0, 0.0, 0.0], "quarterly revenue grew"),
+        ([0.0, 1.0, 0.0], "revenue fell sharply"),
+    ] {
+        let properties = ImmutablePropertiesMap::new(
+            1,
+            [("text", Value::String(text.to_string()))].into_iter(),
+            &arena,
+        );
+        let inserted = storage
+            .vectors
+            .insert::<fn(&HVector, &RoTxn) -> bool>(
+                &mut txn,
+                "Chunk",
+                &vector,
+                Some(properties),
+                &arena,
+            )
+            .unwrap();
+        storage
+            .bm25
+            .as_ref()
+            .unwrap()
+            .insert_doc(&mut txn, inserted.id, text)
+            .unwrap();
+        ids.push(inserted.id);
+    }
+    txn.commit().unwrap();
+
+    let body = search(
+        &graph,
+        sonic_rs::json!({
+            "label": "Chunk",
+            "query": "revenue",
+            "vector": [0.0, 1.0, 0.0],
+            "alpha": 0.1,
+            "highlight": {"pre_tag": "[", "post_tag": "]"},
+        }),
+    );
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0]["id"].as_str(),
+        Some(uuid::Uuid::from_u128(ids[1]).to_string().as_str())
+    );
+    assert_eq!(
+        results[0]["highlights"]["text"][0]["text"].as_str(),
+        Some("[revenue] fell sharply")
+    );
+}
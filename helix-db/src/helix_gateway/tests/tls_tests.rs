@@ -0,0 +1,218 @@
+use crate::helix_engine::traversal_core::config::{AcmeConfig, TlsConfig};
+use crate::helix_gateway::tls::{TlsError, TlsServer};
+use axum::routing::get;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair,
+};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+struct TestCa {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+impl TestCa {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "Helix test CA");
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        TestCa { cert, key }
+    }
+
+    /// Certificate and key PEMs for `name`, signed by this CA
+    fn issue(&self, name: &str, usage: ExtendedKeyUsagePurpose) -> (String, String) {
+        let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.extended_key_usages = vec![usage];
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+}
+
+fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// A TLS config serving a `localhost` certificate issued by `ca`
+fn files_config(dir: &Path, ca: &TestCa) -> TlsConfig {
+    let (cert, key) = ca.issue("localhost", ExtendedKeyUsagePurpose::ServerAuth);
+    TlsConfig {
+        cert_path: Some(write(dir, "cert.pem", &cert)),
+        key_path: Some(write(dir, "key.pem", &key)),
+        ..Default::default()
+    }
+}
+
+fn acme_config(dir: &Path) -> AcmeConfig {
+    AcmeConfig {
+        domains: vec!["db.example.com".to_string()],
+        contact: None,
+        cache_dir: dir.join("acme"),
+        directory_url: None,
+    }
+}
+
+/// Serve a `/ping` route with `config` on a local port
+async fn serve(config: &TlsConfig) -> (SocketAddr, tokio::sync::oneshot::Sender<()>) {
+    let server = TlsServer::from_config(config).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new()
+        .route("/ping", get(|| async { "pong" }))
+        .into_make_service_with_connect_info::<SocketAddr>();
+    let (stop, stopped) = tokio::sync::oneshot::channel();
+    tokio::spawn(server.serve(listener, app, async {
+        let _ = stopped.await;
+    }));
+    (addr, stop)
+}
+
+fn client(ca: &TestCa) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(ca.cert.pem().as_bytes()).unwrap())
+}
+
+#[test]
+fn test_tls_requires_a_certificate_source() {
+    let result = TlsServer::from_config(&TlsConfig::default());
+    assert!(matches!(result, Err(TlsError::MissingCertificate)));
+
+    let result = TlsServer::from_config(&TlsConfig {
+        cert_path: Some(PathBuf::from("cert.pem")),
+        ..Default::default()
+    });
+    assert!(matches!(result, Err(TlsError::MissingCertificate)));
+}
+
+#[test]
+fn test_tls_files_and_acme_are_exclusive() {
+    let dir = TempDir::new().unwrap();
+    let config = TlsConfig {
+        acme: Some(acme_config(dir.path())),
+        ..files_config(dir.path(), &TestCa::new())
+    };
+    assert!(matches!(
+        TlsServer::from_config(&config),
+        Err(TlsError::ConflictingCertificate)
+    ));
+}
+
+#[test]
+fn test_tls_reports_unreadable_files() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing.pem");
+    let config = TlsConfig {
+        cert_path: Some(missing.clone()),
+        key_path: Some(missing.clone()),
+        ..Default::default()
+    };
+    match TlsServer::from_config(&config) {
+        Err(TlsError::Pem { path, .. }) => assert_eq!(path, missing),
+        _ => panic!("expected a PEM error"),
+    }
+
+    let empty = write(dir.path(), "empty.pem", "");
+    let config = TlsConfig {
+        cert_path: Some(empty.clone()),
+        ..files_config(dir.path(), &TestCa::new())
+    };
+    match TlsServer::from_config(&config) {
+        Err(TlsError::Pem { path, .. }) => assert_eq!(path, empty),
+        _ => panic!("expected a PEM error"),
+    }
+}
+
+#[test]
+fn test_tls_acme_needs_domains() {
+    let dir = TempDir::new().unwrap();
+    let config = TlsConfig {
+        acme: Some(AcmeConfig {
+            domains: vec![],
+            ..acme_config(dir.path())
+        }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        TlsServer::from_config(&config),
+        Err(TlsError::NoAcmeDomains)
+    ));
+
+    let config = TlsConfig {
+        acme: Some(acme_config(dir.path())),
+        ..Default::default()
+    };
+    assert!(matches!(
+        TlsServer::from_config(&config),
+        Ok(TlsServer::Acme { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_tls_serves_https() {
+    let dir = TempDir::new().unwrap();
+    let ca = TestCa::new();
+    let (addr, _stop) = serve(&files_config(dir.path(), &ca)).await;
+
+    let response = client(&ca)
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/ping", addr.port()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), "pong");
+
+    // Plain HTTP is not served on a TLS listener
+    let plain = reqwest::get(format!("http://localhost:{}/ping", addr.port())).await;
+    assert!(plain.is_err());
+}
+
+#[tokio::test]
+async fn test_mtls_requires_client_certificate() {
+    let dir = TempDir::new().unwrap();
+    let server_ca = TestCa::new();
+    let client_ca = TestCa::new();
+    let config = TlsConfig {
+        client_ca_path: Some(write(dir.path(), "clients.pem", &client_ca.cert.pem())),
+        ..files_config(dir.path(), &server_ca)
+    };
+    let (addr, _stop) = serve(&config).await;
+    let url = format!("https://localhost:{}/ping", addr.port());
+
+    let anonymous = client(&server_ca).build().unwrap().get(&url).send().await;
+    assert!(anonymous.is_err());
+
+    let (cert, key) = client_ca.issue("service-a", ExtendedKeyUsagePurpose::ClientAuth);
+    let identity = reqwest::Identity::from_pkcs8_pem(cert.as_bytes(), key.as_bytes()).unwrap();
+    let response = client(&server_ca)
+        .identity(identity)
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.text().await.unwrap(), "pong");
+
+    // A certificate from another CA is refused
+    let (cert, key) = server_ca.issue("intruder", ExtendedKeyUsagePurpose::ClientAuth);
+    let identity = reqwest::Identity::from_pkcs8_pem(cert.as_bytes(), key.as_bytes()).unwrap();
+    let intruder = client(&server_ca)
+        .identity(identity)
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await;
+    assert!(intruder.is_err());
+}
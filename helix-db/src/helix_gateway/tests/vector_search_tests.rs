@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::helix_gateway::vector_search::{SEARCH_BATCH_ROUTE, search_vectors_batch};
+use crate::protocol::request::RequestType;
+use crate::protocol::{Format, Request};
+use axum::body::Bytes;
+use bumpalo::Bump;
+use heed3::RoTxn;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use tempfile::TempDir;
+
+type Filter = fn(&HVector, &RoTxn) -> bool;
+
+fn create_test_graph() -> (Arc<HelixGraphEngine>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config::default(),
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+    (graph, temp_dir)
+}
+
+/// Inserts `vectors` as `Doc` vectors, returning their ids
+fn insert_docs(graph: &HelixGraphEngine, vectors: &[[f64; 3]]) -> Vec<u128> {
+    let mut txn = graph.storage.graph_env.write_txn().unwrap();
+    let ids = vectors
+        .iter()
+        .map(|vector| {
+            let arena = Bump::new();
+            graph
+                .storage
+                .vectors
+                .insert::<Filter>(&mut txn, "Doc", vector, None, &arena)
+                .unwrap()
+                .id
+        })
+        .collect();
+    txn.commit().unwrap();
+    ids
+}
+
+fn search(graph: &Arc<HelixGraphEngine>, body: sonic_rs::Value) -> sonic_rs::Value {
+    let input = HandlerInput {
+        request: Request {
+            name: SEARCH_BATCH_ROUTE.to_string(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: Bytes::from(body.to_string()),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        },
+        graph: Arc::clone(graph),
+    };
+    let response = search_vectors_batch(input).unwrap();
+    sonic_rs::from_slice(&response.body).unwrap()
+}
+
+#[test]
+fn test_search_batch_returns_k_neighbors_per_query() {
+    let (graph, _temp_dir) = create_test_graph();
+    let ids = insert_docs(
+        &graph,
+        &[
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 1.0, 0.0],
+        ],
+    );
+
+    let body = search(
+        &graph,
+        sonic_rs::json!({
+            "label": "Doc",
+            "vectors": [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            "k": 2
+        }),
+    );
+
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    for result in results.iter() {
+        assert_eq!(result.as_array().unwrap().len(), 2);
+    }
+    let nearest = |query: usize| results[query][0]["id"].as_str().unwrap().to_string();
+    assert_eq!(nearest(0), uuid::Uuid::from_u128(ids[0]).to_string());
+    assert_eq!(nearest(1), uuid::Uuid::from_u128(ids[2]).to_string());
+    assert_eq!(results[0][0]["label"].as_str(), Some("Doc"));
+    assert!(results[0][0]["distance"].as_f64().unwrap() < 1e-9);
+}
+
+#[test]
+fn test_search_batch_of_type_without_vectors_is_empty() {
+    let (graph, _temp_dir) = create_test_graph();
+
+    let body = search(
+        &graph,
+        sonic_rs::json!({ "label": "Doc", "vectors": [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] }),
+    );
+
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.as_array().unwrap().is_empty()));
+}
+
+#[test]
+fn test_search_batch_without_queries_is_rejected() {
+    let (graph, _temp_dir) = create_test_graph();
+    let input = HandlerInput {
+        request: Request {
+            name: SEARCH_BATCH_ROUTE.to_string(),
+            req_type: RequestType::Query,
+            api_key: None,
+            body: Bytes::from(r#"{"label":"Doc","vectors":[]}"#),
+            in_fmt: Format::Json,
+            out_fmt: Format::Json,
+        },
+        graph,
+    };
+    assert!(search_vectors_batch(input).is_err());
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::views::View;
+use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::{HelixGraphEngine, HelixGraphEngineOpts};
+use crate::helix_engine::types::GraphError;
+use crate::helix_gateway::gateway::{AppState, CoreSetter};
+use crate::helix_gateway::rate_limit::RateLimiter;
+use crate::helix_gateway::router::router::{HandlerFn, HelixRouter};
+use crate::helix_gateway::views::{ViewError, ViewRefresher};
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::protocol::request::{Request, RequestType};
+use crate::protocol::{Format, Response};
+use axum::body::Bytes;
+use heed3::RoTxn;
+use tempfile::TempDir;
+
+static REFRESHES: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_view<'db>(
+    _db: &'db HelixGraphStorage,
+    _txn: &RoTxn<'db>,
+) -> Result<Vec<u128>, GraphError> {
+    REFRESHES.fetch_add(1, Ordering::SeqCst);
+    Ok(Vec::new())
+}
+
+fn empty_view<'db>(
+    _db: &'db HelixGraphStorage,
+    _txn: &RoTxn<'db>,
+) -> Result<Vec<u128>, GraphError> {
+    Ok(Vec::new())
+}
+
+static COUNTERS: View = View::new("Counters", counted_view).with_labels(&["Counter"]);
+static BAD_CRON: View = View::new("BadCron", empty_view).with_refresh_cron("every minute");
+static HOURLY: View = View::new("Hourly", empty_view).with_refresh_cron("0 * * * *");
+
+/// App state with an `increment` write on the `Counter` label and a `touch_other` write on
+/// the `Other` label
+fn create_test_app_state() -> (Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts).unwrap());
+
+    let mut routes: HashMap<String, HandlerFn> = HashMap::new();
+    for name in ["increment", "touch_other"] {
+        routes.insert(
+            name.to_string(),
+            Arc::new(|_| {
+                Ok(Response {
+                    body: b"null".to_vec(),
+                    fmt: Format::Json,
+                })
+            }),
+        );
+    }
+    let write_routes = HashSet::from(["increment".to_string(), "touch_other".to_string()]);
+    let mut router = HelixRouter::new(Some(routes), None, Some(write_routes));
+    router.route_labels = HashMap::from([
+        ("increment".to_string(), vec!["Counter".to_string()]),
+        ("touch_other".to_string(), vec!["Other".to_string()]),
+    ]);
+
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let worker_pool = WorkerPool::new(core_setter, graph, Arc::new(router), rt);
+
+    let state = Arc::new(AppState {
+        worker_pool,
+        schema_json: None,
+        cluster_id: None,
+        api_keys: Default::default(),
+        jwt: None,
+        rate_limiter: RateLimiter::default(),
+    });
+    (state, temp_dir)
+}
+
+async fn write(state: &AppState, name: &str) {
+    let req = Request {
+        name: name.to_string(),
+        req_type: RequestType::Query,
+        api_key: None,
+        body: Bytes::new(),
+        in_fmt: Format::Json,
+        out_fmt: Format::Json,
+    };
+    state.worker_pool.process(req).await.unwrap();
+}
+
+async fn wait_for_refreshes(count: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while REFRESHES.load(Ordering::SeqCst) < count {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("view should have refreshed");
+}
+
+#[test]
+fn test_refresher_rejects_bad_cron() {
+    assert!(ViewRefresher::new([&HOURLY]).is_ok());
+    assert!(matches!(
+        ViewRefresher::new([&HOURLY, &BAD_CRON]),
+        Err(ViewError::InvalidCron { view, .. }) if view == "BadCron"
+    ));
+}
+
+#[tokio::test]
+async fn test_view_refreshes_after_writes_to_its_labels() {
+    let (state, _dir) = create_test_app_state();
+    let refresher = Arc::new(ViewRefresher::new([&COUNTERS]).unwrap());
+    let run = tokio::spawn(refresher.run(Arc::clone(&state)));
+
+    // Refreshed once at startup
+    wait_for_refreshes(1).await;
+
+    write(&state, "increment").await;
+    wait_for_refreshes(2).await;
+
+    write(&state, "touch_other").await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(REFRESHES.load(Ordering::SeqCst), 2);
+
+    state.worker_pool.begin_shutdown();
+    tokio::time::timeout(Duration::from_secs(5), run)
+        .await
+        .expect("refresher should stop once the pool is shutting down")
+        .unwrap();
+}
@@ -1,13 +1,17 @@
 use crate::helix_engine::traversal_core::HelixGraphEngineOpts;
-use crate::helix_engine::traversal_core::config::Config;
+use crate::helix_engine::traversal_core::config::{Config, GatewayConfig};
 use crate::helix_engine::{traversal_core::HelixGraphEngine, types::GraphError};
-use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::helix_gateway::worker_pool::{ReadLanes, WorkerPool};
 use crate::helix_gateway::{
     gateway::CoreSetter,
     router::router::{HandlerInput, HelixRouter, IoContFn},
 };
 use crate::protocol::Format;
-use crate::protocol::{HelixError, Request, request::RequestType, response::Response};
+use crate::protocol::{
+    HelixError, Request,
+    request::{Priority, ReqMsg, RequestHints, RequestTrace, RequestType},
+    response::Response,
+};
 use axum::body::Bytes;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -2603,3 +2607,1017 @@ async fn test_stress_parallel_writes_with_continuations() {
         "All stress test continuations should have completed"
     );
 }
+
+// ============================================================================
+// Graceful Shutdown Tests
+// ============================================================================
+
+fn shutdown_test_pool(router: HelixRouter) -> (WorkerPool, TempDir) {
+    let (graph, temp_dir) = create_test_graph();
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let pool = WorkerPool::new(core_setter, graph, Arc::new(router), rt);
+    (pool, temp_dir)
+}
+
+#[tokio::test]
+async fn test_process_rejected_after_begin_shutdown() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("test_query", test_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    pool.begin_shutdown();
+    assert!(pool.is_shutting_down());
+
+    let result = pool
+        .process(create_test_request("test_query", RequestType::Query))
+        .await;
+    assert!(matches!(result, Err(HelixError::ShuttingDown)));
+    assert_eq!(pool.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_drain_waits_for_io_continuation() {
+    fn slow_io_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+        Err(IoContFn::create_err(move |cont_tx, ret_chan| {
+            Box::pin(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                cont_tx
+                    .send_async((
+                        ret_chan,
+                        Box::new(move || {
+                            Ok(Response {
+                                body: b"drained".to_vec(),
+                                fmt: Format::Json,
+                            })
+                        })
+                            as Box<dyn FnOnce() -> Result<Response, GraphError> + Send + Sync>,
+                    ))
+                    .await
+                    .expect("cont channel should be alive");
+            })
+        }))
+    }
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("slow_io", slow_io_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+    let pool = Arc::new(pool);
+
+    let pool_clone = Arc::clone(&pool);
+    let handle = tokio::spawn(async move {
+        pool_clone
+            .process(create_test_request("slow_io", RequestType::Query))
+            .await
+    });
+
+    // Give the request time to reach a worker
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(pool.in_flight(), 1);
+
+    pool.begin_shutdown();
+    assert!(
+        pool.drain(std::time::Instant::now() + std::time::Duration::from_secs(5))
+            .await
+    );
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.body, b"drained");
+    assert_eq!(pool.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_drain_times_out() {
+    fn stuck_io_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+        Err(IoContFn::create_err(move |cont_tx, ret_chan| {
+            Box::pin(async move {
+                // Hold the channels so the request stays in flight
+                let _held = (cont_tx, ret_chan);
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            })
+        }))
+    }
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("stuck", stuck_io_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+    let pool = Arc::new(pool);
+
+    let pool_clone = Arc::clone(&pool);
+    tokio::spawn(async move {
+        let _ = pool_clone
+            .process(create_test_request("stuck", RequestType::Query))
+            .await;
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    assert!(
+        !pool
+            .drain(std::time::Instant::now() + std::time::Duration::from_millis(50))
+            .await
+    );
+    assert_eq!(pool.in_flight(), 1);
+}
+
+#[test]
+fn test_shutdown_joins_idle_workers() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("test_query", test_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    assert!(pool.shutdown(std::time::Instant::now() + std::time::Duration::from_secs(5)));
+}
+
+#[tokio::test]
+async fn test_shutdown_after_processing_requests() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("read", test_handler, false);
+    router.add_route("write", test_handler, true);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    for name in ["read", "write", "read"] {
+        let result = pool
+            .process(create_test_request(name, RequestType::Query))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    let joined = tokio::task::spawn_blocking(move || {
+        pool.shutdown(std::time::Instant::now() + std::time::Duration::from_secs(5))
+    })
+    .await
+    .unwrap();
+    assert!(joined);
+}
+
+// ============================================================================
+// Overload Shedding Tests
+// ============================================================================
+
+fn slow_write_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    Ok(Response {
+        body: b"written".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+fn overload_test_pool(config: &GatewayConfig) -> (Arc<WorkerPool>, TempDir) {
+    let (graph, temp_dir) = create_test_graph();
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("slow_write", slow_write_handler, true);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let pool = WorkerPool::with_config(core_setter, graph, Arc::new(router), rt, config);
+    (Arc::new(pool), temp_dir)
+}
+
+#[tokio::test]
+async fn test_full_queue_sheds_with_retry_after() {
+    let config = GatewayConfig {
+        queue_capacity: Some(1),
+        retry_after_secs: Some(7),
+        ..Default::default()
+    };
+    let (pool, _temp_dir) = overload_test_pool(&config);
+
+    // First write occupies the writer, second fills the single queue slot
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let pool_clone = Arc::clone(&pool);
+        handles.push(tokio::spawn(async move {
+            pool_clone
+                .process(create_test_request("slow_write", RequestType::Query))
+                .await
+        }));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+
+    let result = pool
+        .process(create_test_request("slow_write", RequestType::Query))
+        .await;
+    assert!(matches!(
+        result,
+        Err(HelixError::Overloaded {
+            retry_after_secs: 7
+        })
+    ));
+
+    let stats = pool.stats();
+    assert_eq!(stats.queue_capacity, 1);
+    assert_eq!(stats.write_queue_depth, 1);
+    assert_eq!(stats.shed_total, 1);
+
+    for handle in handles {
+        assert!(handle.await.unwrap().is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_full_queue_waits_when_shedding_disabled() {
+    let config = GatewayConfig {
+        queue_capacity: Some(1),
+        shed_on_overload: Some(false),
+        ..Default::default()
+    };
+    let (pool, _temp_dir) = overload_test_pool(&config);
+
+    let mut handles = Vec::new();
+    for _ in 0..3 {
+        let pool_clone = Arc::clone(&pool);
+        handles.push(tokio::spawn(async move {
+            pool_clone
+                .process(create_test_request("slow_write", RequestType::Query))
+                .await
+        }));
+    }
+
+    for handle in handles {
+        assert!(handle.await.unwrap().is_ok());
+    }
+    assert_eq!(pool.stats().shed_total, 0);
+}
+
+#[test]
+fn test_worker_pool_stats_defaults() {
+    let (pool, _temp_dir) = overload_test_pool(&GatewayConfig::default());
+    let stats = pool.stats();
+
+    assert_eq!(stats.workers, 2);
+    assert_eq!(stats.queue_capacity, GatewayConfig::DEFAULT_QUEUE_CAPACITY);
+    assert_eq!(stats.read_queue_depth, 0);
+    assert_eq!(stats.write_queue_depth, 0);
+    assert_eq!(stats.in_flight, 0);
+    assert_eq!(stats.shed_total, 0);
+}
+
+// ============================================================================
+// Priority Lane Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_batch_route_is_served() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("batch_query", test_handler, false);
+    router.batch_routes.insert("batch_query".to_string());
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    let result = pool
+        .process(create_test_request("batch_query", RequestType::Query))
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(pool.stats().batch_queue_depth, 0);
+}
+
+#[tokio::test]
+async fn test_priority_override_is_served() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("test_query", test_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    let result = pool
+        .process_with(
+            create_test_request("test_query", RequestType::Query),
+            RequestHints {
+                priority: Some(Priority::Batch),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_read_lanes_prefer_interactive_without_starving_batch() {
+    let (interactive_tx, interactive_rx) = flume::unbounded::<ReqMsg>();
+    let (batch_tx, batch_rx) = flume::unbounded::<ReqMsg>();
+    let mut lanes = ReadLanes::new(interactive_rx, batch_rx);
+
+    for _ in 0..6 {
+        let (ret_tx, _) = tokio::sync::oneshot::channel();
+        interactive_tx
+            .send((
+                create_test_request("i", RequestType::Query),
+                ret_tx,
+                RequestTrace::default(),
+            ))
+            .unwrap();
+    }
+    for _ in 0..2 {
+        let (ret_tx, _) = tokio::sync::oneshot::channel();
+        batch_tx
+            .send((
+                create_test_request("b", RequestType::Query),
+                ret_tx,
+                RequestTrace::default(),
+            ))
+            .unwrap();
+    }
+
+    let order: String = std::iter::from_fn(|| lanes.try_recv().ok())
+        .map(|(req, _, _)| req.name)
+        .collect();
+    assert_eq!(order, "iiiibiib");
+}
+
+#[test]
+fn test_read_lanes_disconnect_only_when_both_closed() {
+    let (interactive_tx, interactive_rx) = flume::unbounded::<ReqMsg>();
+    let (batch_tx, batch_rx) = flume::unbounded::<ReqMsg>();
+    let mut lanes = ReadLanes::new(interactive_rx, batch_rx);
+
+    drop(interactive_tx);
+    let (ret_tx, _) = tokio::sync::oneshot::channel();
+    batch_tx
+        .send((
+            create_test_request("b", RequestType::Query),
+            ret_tx,
+            RequestTrace::default(),
+        ))
+        .unwrap();
+    assert_eq!(lanes.recv().unwrap().0.name, "b");
+
+    drop(batch_tx);
+    assert!(lanes.recv().is_err());
+}
+
+// ============================================================================
+// Result Cache Tests
+// ============================================================================
+
+static CACHED_READ_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn counting_read_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    CACHED_READ_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    Ok(Response {
+        body: b"people".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+#[tokio::test]
+async fn test_cached_route_served_until_write_invalidates() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("people", counting_read_handler, false);
+    router.add_route("add_company", test_handler, true);
+    router.add_route("add_person", test_handler, true);
+    router
+        .cache_routes
+        .insert("people".to_string(), std::time::Duration::from_secs(60));
+    for (route, label) in [
+        ("people", "Person"),
+        ("add_company", "Company"),
+        ("add_person", "Person"),
+    ] {
+        router
+            .route_labels
+            .insert(route.to_string(), vec![label.to_string()]);
+    }
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+    let calls = || CACHED_READ_CALLS.load(std::sync::atomic::Ordering::SeqCst);
+
+    for _ in 0..3 {
+        let result = pool
+            .process(create_test_request("people", RequestType::Query))
+            .await;
+        assert_eq!(result.unwrap().body, b"people");
+    }
+    assert_eq!(calls(), 1);
+
+    // Writes to unrelated labels keep the entry
+    pool.process(create_test_request("add_company", RequestType::Query))
+        .await
+        .unwrap();
+    pool.process(create_test_request("people", RequestType::Query))
+        .await
+        .unwrap();
+    assert_eq!(calls(), 1);
+
+    pool.process(create_test_request("add_person", RequestType::Query))
+        .await
+        .unwrap();
+    pool.process(create_test_request("people", RequestType::Query))
+        .await
+        .unwrap();
+    assert_eq!(calls(), 2);
+
+    let stats = pool.stats();
+    assert_eq!(stats.cache_hits, 3);
+    assert_eq!(stats.cache_misses, 2);
+    assert_eq!(stats.cache_entries, 1);
+}
+
+#[tokio::test]
+async fn test_failed_reads_are_not_cached() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("failing", error_handler, false);
+    router
+        .cache_routes
+        .insert("failing".to_string(), std::time::Duration::from_secs(60));
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    for _ in 0..2 {
+        let result = pool
+            .process(create_test_request("failing", RequestType::Query))
+            .await;
+        assert!(result.is_err());
+    }
+    assert_eq!(pool.stats().cache_entries, 0);
+    assert_eq!(pool.stats().cache_hits, 0);
+}
+
+// ============================================================================
+// Read-Your-Writes Tests
+// ============================================================================
+
+static ORDER_WRITTEN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn slow_order_write_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    ORDER_WRITTEN.store(true, std::sync::atomic::Ordering::SeqCst);
+    test_handler(input)
+}
+
+fn order_read_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    let body = match ORDER_WRITTEN.load(std::sync::atomic::Ordering::SeqCst) {
+        true => b"written".to_vec(),
+        false => b"missing".to_vec(),
+    };
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+#[tokio::test]
+async fn test_read_your_writes_runs_after_queued_write() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("add_order", slow_order_write_handler, true);
+    router.add_route("get_order", order_read_handler, false);
+    router
+        .cache_routes
+        .insert("get_order".to_string(), std::time::Duration::from_secs(60));
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+    let pool = Arc::new(pool);
+
+    // Cache the read from before the write
+    let before = pool
+        .process(create_test_request("get_order", RequestType::Query))
+        .await
+        .unwrap();
+    assert_eq!(before.body, b"missing");
+
+    let writer = Arc::clone(&pool);
+    let write = tokio::spawn(async move {
+        writer
+            .process(create_test_request("add_order", RequestType::Query))
+            .await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    // Sent while the write is still running, it queues behind it and skips the cache
+    let read = pool
+        .process_with(
+            create_test_request("get_order", RequestType::Query),
+            RequestHints {
+                read_your_writes: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(read.body, b"written");
+    assert!(write.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_read_your_writes_rejected_in_session() {
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("test_query", test_handler, false);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    let result = pool
+        .process_with(
+            create_test_request("test_query", RequestType::Query),
+            RequestHints {
+                session: Some("token".to_string()),
+                read_your_writes: true,
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(HelixError::InvalidSession(_))));
+}
+
+// ============================================================================
+// Concurrency Limit Tests
+// ============================================================================
+
+static REPORTS_RUNNING: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static MOST_REPORTS_RUNNING: std::sync::atomic::AtomicUsize =
+    std::sync::atomic::AtomicUsize::new(0);
+
+fn slow_report_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    use std::sync::atomic::Ordering;
+
+    let running = REPORTS_RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+    MOST_REPORTS_RUNNING.fetch_max(running, Ordering::SeqCst);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    REPORTS_RUNNING.fetch_sub(1, Ordering::SeqCst);
+    Ok(Response {
+        body: b"report".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+#[tokio::test]
+async fn test_route_concurrency_limit_holds_back_extra_requests() {
+    use crate::helix_gateway::router::router::ConcurrencyLimit;
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("report", slow_report_handler, false);
+    router
+        .concurrency_limits
+        .insert("report".to_string(), ConcurrencyLimit::new(1));
+    // Two readers, so without the limit two reports would run together
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    let results = futures_util::future::join_all(
+        (0..4).map(|_| pool.process(create_test_request("report", RequestType::Query))),
+    )
+    .await;
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(
+        MOST_REPORTS_RUNNING.load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+    let limit = pool.routes();
+    let limit = limit.concurrency_limit("report").unwrap();
+    assert_eq!(limit.permits.available_permits(), 1);
+}
+
+// ============================================================================
+// Route Replacement Tests
+// ============================================================================
+
+fn replaced_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    Ok(Response {
+        body: b"replaced".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+#[tokio::test]
+async fn test_replace_routes_serves_new_handlers() {
+    let cached = |router: &mut HelixRouter| {
+        router
+            .cache_routes
+            .insert("report".to_string(), std::time::Duration::from_secs(60));
+    };
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("report", test_handler, false);
+    router.add_route("retired", test_handler, false);
+    cached(&mut router);
+    let (pool, _temp_dir) = shutdown_test_pool(router);
+
+    let result = pool
+        .process(create_test_request("report", RequestType::Query))
+        .await;
+    assert_eq!(result.unwrap().body, b"test response");
+    assert_eq!(pool.stats().cache_entries, 1);
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("report", replaced_handler, false);
+    router.add_route("add_report", test_handler, true);
+    cached(&mut router);
+    pool.replace_routes(router);
+
+    // The cached result of the previous handler is not served
+    let result = pool
+        .process(create_test_request("report", RequestType::Query))
+        .await;
+    assert_eq!(result.unwrap().body, b"replaced");
+    assert!(pool.is_write_route("add_report"));
+    let result = pool
+        .process(create_test_request("retired", RequestType::Query))
+        .await;
+    assert!(matches!(result, Err(HelixError::NotFound { .. })));
+}
+
+// ============================================================================
+// Slow Query Log Tests
+// ============================================================================
+
+fn scanning_handler(_input: HandlerInput) -> Result<Response, GraphError> {
+    use crate::helix_engine::traversal_core::step_profile;
+
+    let step_started = step_profile::start();
+    crate::helix_engine::storage_core::scan_counter::record(3);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    step_profile::record("users", step_started);
+    Ok(Response {
+        body: b"scanned".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn slow_query_test_pool(config: &GatewayConfig) -> (WorkerPool, TempDir) {
+    let (graph, temp_dir) = create_test_graph();
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("scan_users", scanning_handler, false);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let pool = WorkerPool::with_config(core_setter, graph, Arc::new(router), rt, config);
+    (pool, temp_dir)
+}
+
+async fn captured_slow_query_logs(slow_query_ms: u64) -> String {
+    let config = GatewayConfig {
+        slow_query_ms: Some(slow_query_ms),
+        ..Default::default()
+    };
+    let (pool, _temp_dir) = slow_query_test_pool(&config);
+
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut request = create_test_request("scan_users", RequestType::Query);
+    request.body = Bytes::from_static(br#"{"email":"alice@example.com","limit":10}"#);
+    assert!(pool.process(request).await.is_ok());
+
+    String::from_utf8(logs.0.lock().unwrap().clone()).unwrap()
+}
+
+#[tokio::test]
+async fn test_slow_query_logged_with_redacted_params() {
+    let logs = captured_slow_query_logs(10).await;
+    assert!(logs.contains("helix_slow_query"), "{logs}");
+    assert!(logs.contains("query=scan_users"), "{logs}");
+    assert!(logs.contains("items_scanned=3"), "{logs}");
+    assert!(logs.contains("execute_ms="), "{logs}");
+    assert!(logs.contains("steps=users "), "{logs}");
+    assert!(logs.contains("ms (3 scanned)"), "{logs}");
+    assert!(
+        logs.contains("params={email: <string>, limit: <number>}"),
+        "{logs}"
+    );
+    assert!(!logs.contains("alice@example.com"), "{logs}");
+}
+
+#[tokio::test]
+async fn test_fast_query_not_logged() {
+    let logs = captured_slow_query_logs(10_000).await;
+    assert!(!logs.contains("helix_slow_query"), "{logs}");
+}
+
+// ============================================================================
+// Audit Log Tests
+// ============================================================================
+
+// Commits like a generated write handler, appending its audit entry in the write's txn
+fn auditing_write_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let db = &input.graph.storage;
+    let mut txn = db.graph_env.write_txn()?;
+    crate::helix_engine::storage_core::write_log::record(42);
+    db.audit_write(&mut txn)?;
+    txn.commit()?;
+    Ok(Response {
+        body: b"written".to_vec(),
+        fmt: Format::Json,
+    })
+}
+
+// Fails after auditing, so the entry is dropped with the aborted txn
+fn failing_write_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let db = &input.graph.storage;
+    let mut txn = db.graph_env.write_txn()?;
+    crate::helix_engine::storage_core::write_log::record(7);
+    db.audit_write(&mut txn)?;
+    Err(GraphError::New("write failed".to_string()))
+}
+
+fn audit_test_pool(audit_log: bool) -> (WorkerPool, TempDir) {
+    let (graph, temp_dir) = create_test_graph();
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("add_user", auditing_write_handler, true);
+    router.add_route("bad_write", failing_write_handler, true);
+    router.add_route("read_user", test_handler, false);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let config = GatewayConfig {
+        audit_log: Some(audit_log),
+        ..Default::default()
+    };
+    let pool = WorkerPool::with_config(core_setter, graph, Arc::new(router), rt, &config);
+    (pool, temp_dir)
+}
+
+#[tokio::test]
+async fn test_successful_write_is_audited() {
+    use crate::helix_engine::storage_core::audit_log::AuditFilter;
+
+    let (pool, _temp_dir) = audit_test_pool(true);
+    let mut request = create_test_request("add_user", RequestType::Query);
+    request.api_key = Some("secret-key".to_string());
+    assert!(pool.process(request).await.is_ok());
+    assert!(
+        pool.process(create_test_request("read_user", RequestType::Query))
+            .await
+            .is_ok()
+    );
+    // Reads run on the writer to see their own writes aren't writes
+    let read_your_writes = RequestHints {
+        read_your_writes: true,
+        ..Default::default()
+    };
+    assert!(
+        pool.process_with(
+            create_test_request("read_user", RequestType::Query),
+            read_your_writes
+        )
+        .await
+        .is_ok()
+    );
+    assert!(
+        pool.process(create_test_request("bad_write", RequestType::Query))
+            .await
+            .is_err()
+    );
+
+    let entries = pool
+        .graph()
+        .storage
+        .audit_entries(&AuditFilter::default())
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].query, "add_user");
+    assert_eq!(entries[0].item_ids, vec![uuid::Uuid::from_u128(42)]);
+    assert_eq!(
+        entries[0].actor.as_deref(),
+        Some(crate::helix_gateway::audit::api_key_fingerprint("secret-key").as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_writes_not_audited_when_disabled() {
+    use crate::helix_engine::storage_core::audit_log::AuditFilter;
+
+    let (pool, _temp_dir) = audit_test_pool(false);
+    assert!(
+        pool.process(create_test_request("add_user", RequestType::Query))
+            .await
+            .is_ok()
+    );
+    let entries = pool
+        .graph()
+        .storage
+        .audit_entries(&AuditFilter::default())
+        .unwrap();
+    assert!(entries.is_empty());
+}
+
+// ============================================================================
+// Partition Tests
+// ============================================================================
+
+const TENANT_NOTE_KEY: &[u8] = b"tenant_note";
+
+fn tenant_note_write_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let db = &input.graph.storage;
+    let mut txn = db.graph_env.write_txn()?;
+    db.metadata_db
+        .put(&mut txn, TENANT_NOTE_KEY, &input.request.body)?;
+    txn.commit()?;
+    test_handler(input)
+}
+
+fn tenant_note_read_handler(input: HandlerInput) -> Result<Response, GraphError> {
+    let db = &input.graph.storage;
+    let txn = db.graph_env.read_txn()?;
+    let body = match db.metadata_db.get(&txn, TENANT_NOTE_KEY)? {
+        Some(note) => note.to_vec(),
+        None => b"missing".to_vec(),
+    };
+    Ok(Response {
+        body,
+        fmt: Format::Json,
+    })
+}
+
+fn partition_test_pool(partitions: usize) -> (WorkerPool, Vec<Arc<HelixGraphEngine>>, TempDir) {
+    use crate::helix_gateway::worker_pool::open_partitions;
+
+    let temp_dir = TempDir::new().unwrap();
+    let opts = HelixGraphEngineOpts {
+        path: temp_dir.path().to_str().unwrap().to_string(),
+        config: Config {
+            db_max_size_gb: Some(0),
+            ..Config::default()
+        },
+        version_info: Default::default(),
+    };
+    let graph = Arc::new(HelixGraphEngine::new(opts.clone()).unwrap());
+    let partition_graphs = open_partitions(&opts, partitions).unwrap();
+
+    let mut router = HelixRouter::new(None, None, None);
+    router.add_route("add_note", tenant_note_write_handler, true);
+    router.add_route("get_note", tenant_note_read_handler, false);
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let cores = vec![core_affinity::CoreId { id: 0 }];
+    let core_setter = Arc::new(CoreSetter::new(cores, 2));
+    let pool = WorkerPool::with_partitions(
+        core_setter,
+        graph,
+        Arc::new(router),
+        rt,
+        &GatewayConfig::default(),
+        partition_graphs.clone(),
+    );
+    (pool, partition_graphs, temp_dir)
+}
+
+/// A key whose requests run on partition `index` of `count`
+fn tenant_on_partition(index: usize, count: usize) -> String {
+    use crate::helix_gateway::worker_pool::partition_of;
+
+    (0..)
+        .map(|i| format!("tenant-{i}"))
+        .find(|key| partition_of(key, count) == index)
+        .unwrap()
+}
+
+fn stored_note(graph: &HelixGraphEngine) -> Option<Vec<u8>> {
+    let db = &graph.storage;
+    let txn = db.graph_env.read_txn().unwrap();
+    db.metadata_db
+        .get(&txn, TENANT_NOTE_KEY)
+        .unwrap()
+        .map(<[u8]>::to_vec)
+}
+
+async fn process_for_tenant(
+    pool: &WorkerPool,
+    name: &str,
+    body: &'static [u8],
+    tenant: &str,
+) -> Result<Response, HelixError> {
+    let mut request = create_test_request(name, RequestType::Query);
+    request.body = Bytes::from_static(body);
+    pool.process_with(
+        request,
+        RequestHints {
+            partition: Some(tenant.to_string()),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+#[test]
+fn test_partition_of_is_stable_and_in_range() {
+    use crate::helix_gateway::worker_pool::partition_of;
+
+    assert_eq!(partition_of("tenant-1", 1), 0);
+    assert_eq!(partition_of("tenant-1", 0), 0);
+    for count in 2..8 {
+        let index = partition_of("tenant-1", count);
+        assert!(index < count);
+        assert_eq!(partition_of("tenant-1", count), index);
+    }
+}
+
+#[tokio::test]
+async fn test_partitioned_write_lands_in_its_own_environment() {
+    let (pool, partitions, temp_dir) = partition_test_pool(2);
+    assert_eq!(pool.partition_count(), 2);
+    assert_ne!(
+        partitions[0].storage.graph_env.path(),
+        pool.graph().storage.graph_env.path()
+    );
+    assert!(
+        partitions[0]
+            .storage
+            .graph_env
+            .path()
+            .starts_with(temp_dir.path().join("partitions"))
+    );
+
+    let partitioned = tenant_on_partition(1, 2);
+    let main = tenant_on_partition(0, 2);
+    process_for_tenant(&pool, "add_note", b"partitioned", &partitioned)
+        .await
+        .unwrap();
+
+    // Only the partition's environment holds the write, and reads with its key find it there
+    assert_eq!(
+        stored_note(&partitions[0]).as_deref(),
+        Some(&b"partitioned"[..])
+    );
+    assert_eq!(stored_note(pool.graph()), None);
+    let read = process_for_tenant(&pool, "get_note", b"", &partitioned)
+        .await
+        .unwrap();
+    assert_eq!(read.body, b"partitioned");
+    let read = process_for_tenant(&pool, "get_note", b"", &main)
+        .await
+        .unwrap();
+    assert_eq!(read.body, b"missing");
+
+    // Keys of the main database, and requests without a key, run on the main database
+    process_for_tenant(&pool, "add_note", b"main", &main)
+        .await
+        .unwrap();
+    let read = pool
+        .process(create_test_request("get_note", RequestType::Query))
+        .await
+        .unwrap();
+    assert_eq!(read.body, b"main");
+    assert_eq!(
+        stored_note(&partitions[0]).as_deref(),
+        Some(&b"partitioned"[..])
+    );
+    assert!(pool.shutdown(std::time::Instant::now() + std::time::Duration::from_secs(5)));
+}
+
+#[tokio::test]
+async fn test_partition_key_ignored_without_partitions() {
+    let (pool, partitions, _temp_dir) = partition_test_pool(1);
+    assert!(partitions.is_empty());
+    assert_eq!(pool.partition_count(), 1);
+
+    process_for_tenant(&pool, "add_note", b"main", "tenant-1")
+        .await
+        .unwrap();
+    assert_eq!(stored_note(pool.graph()).as_deref(), Some(&b"main"[..]));
+}
+
+#[tokio::test]
+async fn test_partition_rejected_in_session() {
+    let (pool, _partitions, _temp_dir) = partition_test_pool(2);
+
+    let result = pool
+        .process_with(
+            create_test_request("get_note", RequestType::Query),
+            RequestHints {
+                session: Some("token".to_string()),
+                partition: Some(tenant_on_partition(1, 2)),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(HelixError::InvalidSession(_))));
+}
@@ -0,0 +1,169 @@
+//! Text search returning highlighted snippets: a BM25 search of one label, or a hybrid search
+//! when a query vector is given, with each result's matching string properties cut into
+//! fragments around the query's terms. Search UIs can show why a document matched without
+//! fetching it whole.
+//!
+//! Matches are found when the results are read, so snippets always reflect the stored
+//! properties and cost nothing at write time.
+
+use std::collections::BTreeMap;
+
+use bumpalo::Bump;
+use heed3::RoTxn;
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::bm25::bm25::{BM25, combine_hybrid_scores};
+use crate::helix_engine::bm25::highlight::{HighlightOptions, Snippet, highlight};
+use crate::helix_engine::storage_core::HelixGraphStorage;
+use crate::helix_engine::storage_core::storage_methods::StorageMethods;
+use crate::helix_engine::types::{GraphError, VectorError};
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::protocol::Response;
+use crate::protocol::value::Value;
+use crate::utils::id::uuid_str;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// Route text searches run as
+pub const SEARCH_TEXT_ROUTE: &str = "search_text";
+
+/// Results returned when the request doesn't say
+const DEFAULT_K: usize = 10;
+
+/// Weight of the BM25 score in hybrid searches when the request doesn't say
+const DEFAULT_ALPHA: f32 = 0.5;
+
+type NoFilter = fn(&HVector, &RoTxn) -> bool;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchText {
+    /// The node or vector type searched
+    pub label: String,
+    pub query: String,
+    /// Results returned
+    #[serde(default)]
+    pub k: Option<usize>,
+    /// Makes the search hybrid, ranking by BM25 score and similarity to this vector
+    #[serde(default)]
+    pub vector: Option<Vec<f64>>,
+    /// Weight of the BM25 score in hybrid searches, from 0 to 1 (default: 0.5)
+    #[serde(default)]
+    pub alpha: Option<f32>,
+    /// Return snippets of the properties matching the query
+    #[serde(default)]
+    pub highlight: Option<HighlightOptions>,
+    /// Return the results' properties (default: true)
+    #[serde(default)]
+    pub properties: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct Hit<'a> {
+    id: &'a str,
+    label: &'a str,
+    score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<&'a ImmutablePropertiesMap<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlights: Option<BTreeMap<&'a str, Vec<Snippet>>>,
+}
+
+#[derive(Serialize)]
+struct SearchTextResults<'a> {
+    results: Vec<Hit<'a>>,
+}
+
+/// The label and properties of a stored node or vector with `label`
+fn find<'db: 'arena, 'arena: 'txn, 'txn>(
+    storage: &HelixGraphStorage,
+    txn: &'txn RoTxn<'db>,
+    id: u128,
+    label: &str,
+    arena: &'arena Bump,
+) -> Result<Option<(&'arena str, Option<ImmutablePropertiesMap<'arena>>)>, GraphError> {
+    match storage.get_node(txn, &id, arena) {
+        Ok(node) => return Ok((node.label == label).then_some((node.label, node.properties))),
+        Err(GraphError::NodeNotFound) => {}
+        Err(e) => return Err(e),
+    }
+    match storage.vectors.get_vector_properties(txn, id, arena) {
+        Ok(Some(vector)) if vector.label == label => Ok(Some((vector.label, vector.properties))),
+        Ok(_) | Err(VectorError::VectorDeleted) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler of the `search_text` route
+pub fn search_text(input: HandlerInput) -> Result<Response, GraphError> {
+    let request: SearchText = sonic_rs::from_slice(&input.request.body)?;
+    let k = request.k.unwrap_or(DEFAULT_K);
+    let storage = input.graph.storage.as_ref();
+    let bm25 = storage
+        .bm25
+        .as_ref()
+        .ok_or_else(|| GraphError::from("BM25 not enabled!"))?;
+    let txn = storage.read_txn()?;
+    let arena = Bump::new();
+
+    let scored = match &request.vector {
+        None => bm25.search(&txn, &request.query, k, &arena)?,
+        Some(vector) => {
+            let alpha = request.alpha.unwrap_or(DEFAULT_ALPHA).clamp(0.0, 1.0);
+            let bm25_results = bm25.search(&txn, &request.query, k * 2, &arena)?;
+            let label = arena.alloc_str(&request.label);
+            let query = arena.alloc_slice_copy(vector);
+            let vector_results = match storage.vectors.search::<NoFilter>(
+                &txn,
+                query,
+                k * 2,
+                label,
+                None,
+                false,
+                &arena,
+            ) {
+                Ok(found) => found
+                    .into_iter()
+                    .map(|vector| (vector.id, vector.distance.unwrap_or(0.0)))
+                    .collect(),
+                Err(VectorError::EntryPointNotFound) => Vec::new(),
+                Err(e) => return Err(e.into()),
+            };
+            combine_hybrid_scores(bm25_results, vector_results, alpha, k)
+        }
+    };
+
+    let terms = bm25.query_terms(&request.query);
+    let with_properties = request.properties.unwrap_or(true);
+    let mut results = Vec::with_capacity(scored.len());
+    for (id, score) in scored {
+        let Some((label, properties)) = find(storage, &txn, id, &request.label, &arena)? else {
+            continue;
+        };
+        let properties = properties.map(|properties| &*arena.alloc(properties));
+        let highlights = request.highlight.as_ref().map(|options| {
+            properties
+                .into_iter()
+                .flat_map(|properties| properties.iter())
+                .filter_map(|(key, value)| match value {
+                    Value::String(text) => {
+                        let snippets = highlight(text, &terms, options);
+                        (!snippets.is_empty()).then_some((key, snippets))
+                    }
+                    _ => None,
+                })
+                .collect()
+        });
+        results.push(Hit {
+            id: uuid_str(id, &arena),
+            label,
+            score,
+            properties: properties.filter(|_| with_properties),
+            highlights,
+        });
+    }
+    Ok(input
+        .request
+        .out_fmt
+        .create_response(&SearchTextResults { results }))
+}
@@ -0,0 +1,179 @@
+//! HTTPS termination in the gateway.
+//!
+//! The certificate is loaded from PEM files or provisioned from an ACME certificate authority,
+//! whose TLS-ALPN-01 challenge is answered on the gateway's own port. When a client CA bundle
+//! is configured, connections must present a certificate signed by one of its CAs.
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum_server::Handle;
+use axum_server::tls_rustls::RustlsConfig;
+use futures_util::StreamExt;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::{self, PemObject};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{VerifierBuilderError, WebPkiClientVerifier};
+use rustls::{ConfigBuilder, RootCertStore, ServerConfig, server::WantsServerCert};
+use rustls_acme::axum::AxumAcceptor;
+use rustls_acme::caches::DirCache;
+use rustls_acme::{AcmeConfig as AcmeClientConfig, AcmeState};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::helix_engine::traversal_core::config::{AcmeConfig, TlsConfig};
+
+/// Protocols offered during the handshake, most preferred first
+const ALPN_PROTOCOLS: [&[u8]; 2] = [b"h2", b"http/1.1"];
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("TLS needs either `cert_path` and `key_path`, or `acme`")]
+    MissingCertificate,
+    #[error("TLS `cert_path`/`key_path` and `acme` are mutually exclusive")]
+    ConflictingCertificate,
+    #[error("ACME needs at least one domain")]
+    NoAcmeDomains,
+    #[error("Couldn't load {}: {source}", path.display())]
+    Pem { path: PathBuf, source: pem::Error },
+    #[error("Invalid client CA bundle: {0}")]
+    ClientCa(#[from] VerifierBuilderError),
+    #[error("{0}")]
+    Rustls(#[from] rustls::Error),
+}
+
+/// A TLS listener ready to serve the gateway
+pub enum TlsServer {
+    Files(RustlsConfig),
+    Acme {
+        state: AcmeState<io::Error>,
+        acceptor: AxumAcceptor,
+    },
+}
+
+impl TlsServer {
+    /// Load the certificate and client CAs, failing on a misconfiguration before the
+    /// gateway starts listening
+    pub fn from_config(config: &TlsConfig) -> Result<Self, TlsError> {
+        match (&config.cert_path, &config.key_path, &config.acme) {
+            (None, None, Some(acme)) => Self::acme(config, acme),
+            (Some(cert_path), Some(key_path), None) => {
+                let certs = load_certs(cert_path)?;
+                let key =
+                    PrivateKeyDer::from_pem_file(key_path).map_err(|source| TlsError::Pem {
+                        path: key_path.clone(),
+                        source,
+                    })?;
+                let mut server_config = builder(config)?.with_single_cert(certs, key)?;
+                server_config.alpn_protocols = alpn_protocols();
+                Ok(TlsServer::Files(RustlsConfig::from_config(Arc::new(
+                    server_config,
+                ))))
+            }
+            (_, _, Some(_)) => Err(TlsError::ConflictingCertificate),
+            _ => Err(TlsError::MissingCertificate),
+        }
+    }
+
+    fn acme(config: &TlsConfig, acme: &AcmeConfig) -> Result<Self, TlsError> {
+        if acme.domains.is_empty() {
+            return Err(TlsError::NoAcmeDomains);
+        }
+        let state = AcmeClientConfig::new(&acme.domains)
+            .contact(acme.contact.iter().flatten())
+            .directory(acme.directory_url())
+            .cache(DirCache::new(acme.cache_dir.clone()))
+            .state();
+        let mut server_config = builder(config)?.with_cert_resolver(state.resolver());
+        server_config.alpn_protocols = alpn_protocols();
+        let acceptor = state.axum_acceptor(Arc::new(server_config));
+        Ok(TlsServer::Acme { state, acceptor })
+    }
+
+    /// Serve `app` over TLS until `shutdown` completes, then wait for open connections
+    pub async fn serve(
+        self,
+        listener: tokio::net::TcpListener,
+        app: IntoMakeServiceWithConnectInfo<axum::Router, SocketAddr>,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> io::Result<()> {
+        let listener = listener.into_std()?;
+        let handle = Handle::new();
+        let shutdown_handle = handle.clone();
+        tokio::spawn(async move {
+            shutdown.await;
+            shutdown_handle.graceful_shutdown(None);
+        });
+
+        match self {
+            TlsServer::Files(config) => {
+                axum_server::from_tcp_rustls(listener, config)
+                    .handle(handle)
+                    .serve(app)
+                    .await
+            }
+            TlsServer::Acme {
+                mut state,
+                acceptor,
+            } => {
+                // Polling the state orders, renews and caches the certificate
+                tokio::spawn(async move {
+                    while let Some(event) = state.next().await {
+                        match event {
+                            Ok(event) => info!(?event, "ACME certificate event"),
+                            Err(e) => warn!(error = %e, "ACME certificate provisioning failed"),
+                        }
+                    }
+                });
+                axum_server::from_tcp(listener)
+                    .acceptor(acceptor)
+                    .handle(handle)
+                    .serve(app)
+                    .await
+            }
+        }
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn alpn_protocols() -> Vec<Vec<u8>> {
+    ALPN_PROTOCOLS.iter().map(|p| p.to_vec()).collect()
+}
+
+/// Server config builder requiring client certificates when a client CA bundle is set
+fn builder(config: &TlsConfig) -> Result<ConfigBuilder<ServerConfig, WantsServerCert>, TlsError> {
+    let builder =
+        ServerConfig::builder_with_provider(provider()).with_safe_default_protocol_versions()?;
+    let Some(client_ca_path) = &config.client_ca_path else {
+        return Ok(builder.with_no_client_auth());
+    };
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(client_ca_path)? {
+        roots.add(cert)?;
+    }
+    let verifier =
+        WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider()).build()?;
+    Ok(builder.with_client_cert_verifier(verifier))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let pem_error = |source| TlsError::Pem {
+        path: path.to_path_buf(),
+        source,
+    };
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(pem_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pem_error)?;
+    if certs.is_empty() {
+        return Err(pem_error(pem::Error::NoItemsFound));
+    }
+    Ok(certs)
+}
@@ -0,0 +1,100 @@
+//! Batch nearest-neighbor search: many query vectors against one vector type in one call.
+//!
+//! The queries are searched together, sharing the neighbor lists read from the index and the
+//! norms of the vectors visited, so a batch costs much less than a request per query. Results
+//! are returned per query, in query order, nearest first.
+
+use bumpalo::Bump;
+use heed3::RoTxn;
+use serde::{Deserialize, Serialize};
+
+use crate::helix_engine::types::{GraphError, VectorError};
+use crate::helix_engine::vector_core::hnsw::HNSW;
+use crate::helix_engine::vector_core::vector::HVector;
+use crate::helix_gateway::router::router::HandlerInput;
+use crate::protocol::Response;
+use crate::utils::id::uuid_str;
+use crate::utils::properties::ImmutablePropertiesMap;
+
+/// Route batch searches run as
+pub const SEARCH_BATCH_ROUTE: &str = "search_vectors_batch";
+
+/// Neighbors returned per query when the request doesn't say
+const DEFAULT_K: usize = 10;
+
+type NoFilter = fn(&HVector, &RoTxn) -> bool;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchBatch {
+    /// The vector type searched
+    pub label: String,
+    /// The query vectors
+    pub vectors: Vec<Vec<f64>>,
+    /// Neighbors returned per query
+    #[serde(default)]
+    pub k: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct Neighbor<'a> {
+    id: &'a str,
+    label: &'a str,
+    distance: f64,
+    properties: Option<&'a ImmutablePropertiesMap<'a>>,
+}
+
+#[derive(Serialize)]
+struct SearchBatchResults<'a> {
+    results: Vec<Vec<Neighbor<'a>>>,
+}
+
+/// Handler of the `search_vectors_batch` route
+pub fn search_vectors_batch(input: HandlerInput) -> Result<Response, GraphError> {
+    let request: SearchBatch = sonic_rs::from_slice(&input.request.body)?;
+    if request.vectors.is_empty() {
+        return Err(GraphError::New(
+            "search_vectors_batch needs at least one query vector".to_string(),
+        ));
+    }
+    let k = request.k.unwrap_or(DEFAULT_K);
+    let storage = input.graph.storage.as_ref();
+    let txn = storage.read_txn()?;
+    let arena = Bump::new();
+
+    let label = arena.alloc_str(&request.label);
+    let queries: Vec<&[f64]> = request
+        .vectors
+        .iter()
+        .map(|vector| &*arena.alloc_slice_copy(vector))
+        .collect();
+    let found = match storage
+        .vectors
+        .search_batch::<NoFilter>(&txn, &queries, k, label, None, false, &arena)
+    {
+        Ok(found) => found,
+        Err(VectorError::EntryPointNotFound) => queries
+            .iter()
+            .map(|_| bumpalo::collections::Vec::new_in(&arena))
+            .collect(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let results = found
+        .iter()
+        .map(|neighbors| {
+            neighbors
+                .iter()
+                .map(|v| Neighbor {
+                    id: uuid_str(v.id, &arena),
+                    label: v.label,
+                    distance: v.get_distance(),
+                    properties: v.properties.as_ref(),
+                })
+                .collect()
+        })
+        .collect();
+    Ok(input
+        .request
+        .out_fmt
+        .create_response(&SearchBatchResults { results }))
+}
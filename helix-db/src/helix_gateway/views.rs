@@ -0,0 +1,156 @@
+//! Keeps materialized views fresh. Each view is refreshed once at startup, then after every
+//! committed write to a label it reads, or on its `#[refresh(cron: ...)]` schedule instead.
+//! Writes arriving while a view refreshes are folded into a single refresh after it.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use thiserror::Error;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tracing::{debug, info, warn};
+
+use crate::helix_engine::storage_core::views::{self, View};
+use crate::helix_gateway::change_feed::Change;
+use crate::helix_gateway::gateway::AppState;
+use crate::helix_gateway::worker_pool::WorkerPool;
+use crate::utils::cron::parse_cron;
+
+#[derive(Error, Debug)]
+pub enum ViewError {
+    #[error("Invalid refresh cron expression `{cron}` for view `{view}`: {message}")]
+    InvalidCron {
+        view: String,
+        cron: String,
+        message: String,
+    },
+}
+
+pub struct ViewRefresher {
+    views: Vec<RefreshedView>,
+}
+
+struct RefreshedView {
+    view: &'static View,
+    labels: Option<Vec<String>>,
+    schedule: Option<cron::Schedule>,
+}
+
+impl ViewRefresher {
+    /// Refresh the views declared by the compiled queries
+    pub fn from_registered() -> Result<Self, ViewError> {
+        Self::new(views::registered())
+    }
+
+    pub fn new(views: impl IntoIterator<Item = &'static View>) -> Result<Self, ViewError> {
+        let views = views
+            .into_iter()
+            .map(|view| {
+                let schedule = view
+                    .refresh_cron
+                    .map(|cron| {
+                        parse_cron(cron).map_err(|e| ViewError::InvalidCron {
+                            view: view.name.to_string(),
+                            cron: cron.to_string(),
+                            message: e.to_string(),
+                        })
+                    })
+                    .transpose()?;
+                Ok(RefreshedView {
+                    view,
+                    labels: view
+                        .labels
+                        .map(|labels| labels.iter().map(|label| label.to_string()).collect()),
+                    schedule,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ViewRefresher { views })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    /// Refresh every view until the worker pool starts shutting down
+    pub async fn run(self: Arc<Self>, state: Arc<AppState>) {
+        join_all(self.views.iter().map(|view| view.run(&state.worker_pool))).await;
+    }
+}
+
+impl RefreshedView {
+    async fn run(&self, pool: &WorkerPool) {
+        match &self.schedule {
+            Some(schedule) => self.run_on_schedule(pool, schedule).await,
+            None => self.run_on_writes(pool).await,
+        }
+    }
+
+    async fn run_on_writes(&self, pool: &WorkerPool) {
+        // Subscribe before the first refresh so no write lands between the two unnoticed
+        let mut changes = pool.changes().subscribe();
+        self.refresh(pool).await;
+        loop {
+            let mut stale = match changes.recv().await {
+                Ok(Change::ShuttingDown) | Err(RecvError::Closed) => return,
+                Ok(change) => change.affects(self.labels.as_deref()),
+                Err(RecvError::Lagged(_)) => true,
+            };
+            loop {
+                match changes.try_recv() {
+                    Ok(Change::ShuttingDown) | Err(TryRecvError::Closed) => return,
+                    Ok(change) => stale |= change.affects(self.labels.as_deref()),
+                    Err(TryRecvError::Lagged(_)) => stale = true,
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+            if stale {
+                self.refresh(pool).await;
+            }
+        }
+    }
+
+    async fn run_on_schedule(&self, pool: &WorkerPool, schedule: &cron::Schedule) {
+        self.refresh(pool).await;
+        let mut last_run: Option<DateTime<Utc>> = None;
+        loop {
+            let after = last_run.map_or_else(Utc::now, |last| last.max(Utc::now()));
+            let Some(next_run) = schedule.after(&after).next() else {
+                info!(
+                    view = self.view.name,
+                    "View refresh schedule has no more runs"
+                );
+                return;
+            };
+            let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            if pool.is_shutting_down() {
+                return;
+            }
+            last_run = Some(next_run);
+            self.refresh(pool).await;
+        }
+    }
+
+    async fn refresh(&self, pool: &WorkerPool) {
+        let start = Instant::now();
+        let storage = Arc::clone(&pool.graph().storage);
+        let view = self.view;
+        let result = tokio::task::spawn_blocking(move || storage.refresh_view(view)).await;
+        match result {
+            Ok(Ok(nodes)) => {
+                // Cached reads of the view hold its previous contents
+                pool.invalidate_cached(self.labels.as_deref());
+                debug!(
+                    view = view.name,
+                    nodes,
+                    duration_ms = start.elapsed().as_millis() as u64,
+                    "View refreshed"
+                );
+            }
+            Ok(Err(e)) => warn!(view = view.name, error = %e, "View refresh failed"),
+            Err(e) => warn!(view = view.name, error = %e, "View refresh panicked"),
+        }
+    }
+}
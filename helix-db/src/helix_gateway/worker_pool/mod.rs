@@ -1,29 +1,86 @@
-use crate::helix_engine::{traversal_core::HelixGraphEngine, types::GraphError};
+use crate::helix_engine::{
+    storage_core::{audit_log, constraints, scan_counter, write_log},
+    traversal_core::{HelixGraphEngine, config::GatewayConfig, step_profile},
+    types::GraphError,
+};
 use crate::helix_gateway::{
+    audit,
+    change_feed::ChangeFeed,
     gateway::CoreSetter,
     mcp::mcp::MCPToolInput,
-    router::router::{ContChan, ContMsg, HandlerInput, HelixRouter},
+    result_cache::ResultCache,
+    router::router::{ContChan, ContMsg, HandlerInput, HelixRouter, Routes},
+    slow_query::log_slow_query,
 };
 use crate::protocol::{
     HelixError, Request,
-    request::{ReqMsg, RequestType, RetChan},
+    request::{Priority, ReqMsg, RequestHints, RequestTrace, RequestType, RetChan},
     response::Response,
 };
-use flume::{Receiver, Sender};
+use flume::{Receiver, RecvError, Selector, Sender, TryRecvError, TrySendError, WeakSender};
+use serde::Serialize;
 use std::iter;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tokio::sync::oneshot;
-use tracing::{error, trace};
+use tracing::{Instrument, error, info_span, trace, warn};
+
+mod partitions;
+mod sessions;
+
+use partitions::Partition;
+pub use partitions::{PARTITIONS_DIR, open_partitions, partition_of};
+pub use sessions::{SessionInfo, Sessions};
+
+/// Stored as the slow query threshold when slow queries aren't logged
+const SLOW_QUERY_OFF: u64 = u64::MAX;
 
 /// A Thread Pool of workers to execute Database operations
 pub struct WorkerPool {
     tx: Sender<ReqMsg>,
+    /// Lower priority read queue, drained by the same readers as `tx`
+    batch_tx: Sender<ReqMsg>,
     write_tx: Sender<ReqMsg>,
-    router: Arc<HelixRouter>,
-    _workers: Vec<Worker>,
-    _writer_worker: Worker,
+    /// The only strong handle to the continuation channel outside of in-flight IO futures.
+    /// Workers hold weak handles so the channel disconnects once the pool is shut down
+    /// and every pending continuation has been delivered.
+    cont_tx: ContChan,
+    graph_access: Arc<HelixGraphEngine>,
+    router: Arc<Routes>,
+    in_flight: AtomicUsize,
+    shutting_down: AtomicBool,
+    num_workers: usize,
+    queue_capacity: usize,
+    shed_on_overload: bool,
+    retry_after_secs: u64,
+    shed_total: AtomicU64,
+    cache: ResultCache,
+    changes: ChangeFeed,
+    /// Milliseconds, or [`SLOW_QUERY_OFF`]
+    slow_query_ms: AtomicU64,
+    sessions: Sessions,
+    workers: Vec<Worker>,
+    writer_worker: Worker,
+    /// Partitions 1 and up; partition 0 is `graph_access`, served by the workers above
+    partitions: Vec<Partition>,
+}
+
+/// Point-in-time gauges describing worker pool load
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerPoolStats {
+    pub workers: usize,
+    pub queue_capacity: usize,
+    pub read_queue_depth: usize,
+    pub batch_queue_depth: usize,
+    pub write_queue_depth: usize,
+    pub in_flight: usize,
+    pub shed_total: u64,
+    pub cache_entries: usize,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
 }
 
 impl WorkerPool {
@@ -33,11 +90,52 @@ impl WorkerPool {
         router: Arc<HelixRouter>,
         io_rt: Arc<Runtime>,
     ) -> WorkerPool {
-        let (req_tx, req_rx) = flume::bounded::<ReqMsg>(1000);
-        let (cont_tx, cont_rx) = flume::bounded::<ContMsg>(1000);
+        Self::with_config(
+            workers_core_setter,
+            graph_access,
+            router,
+            io_rt,
+            &GatewayConfig::default(),
+        )
+    }
+
+    /// Create a worker pool using the queue limits and shed behaviour from `config`
+    pub fn with_config(
+        workers_core_setter: Arc<CoreSetter>,
+        graph_access: Arc<HelixGraphEngine>,
+        router: Arc<HelixRouter>,
+        io_rt: Arc<Runtime>,
+        config: &GatewayConfig,
+    ) -> WorkerPool {
+        Self::with_partitions(
+            workers_core_setter,
+            graph_access,
+            router,
+            io_rt,
+            config,
+            Vec::new(),
+        )
+    }
+
+    /// Create a worker pool that also serves `partitions`, as partitions 1 and up after the
+    /// main database, each with a writer thread of its own
+    pub fn with_partitions(
+        workers_core_setter: Arc<CoreSetter>,
+        graph_access: Arc<HelixGraphEngine>,
+        router: Arc<HelixRouter>,
+        io_rt: Arc<Runtime>,
+        config: &GatewayConfig,
+        partitions: Vec<Arc<HelixGraphEngine>>,
+    ) -> WorkerPool {
+        let queue_capacity = config.queue_capacity();
+        let (req_tx, req_rx) = flume::bounded::<ReqMsg>(queue_capacity);
+        let (batch_tx, batch_rx) = flume::bounded::<ReqMsg>(queue_capacity);
+        let (cont_tx, cont_rx) = flume::bounded::<ContMsg>(queue_capacity);
 
         // Dedicated channel for write operations - single writer thread
-        let (write_tx, write_rx) = flume::bounded::<ReqMsg>(1000);
+        let (write_tx, write_rx) = flume::bounded::<ReqMsg>(queue_capacity);
+
+        let router = Arc::new(Routes::new(router));
 
         let num_workers = workers_core_setter.num_threads();
         if num_workers < 2 {
@@ -52,12 +150,12 @@ impl WorkerPool {
             .enumerate()
             .map(|(i, setter)| {
                 Worker::start(
-                    req_rx.clone(),
+                    ReadLanes::new(req_rx.clone(), batch_rx.clone()),
                     setter,
                     Arc::clone(&graph_access),
                     Arc::clone(&router),
                     Arc::clone(&io_rt),
-                    (cont_tx.clone(), cont_rx.clone()),
+                    (cont_tx.downgrade(), cont_rx.clone()),
                     i % 2 == 0,
                 )
             })
@@ -69,58 +167,555 @@ impl WorkerPool {
             Arc::clone(&graph_access),
             Arc::clone(&router),
             Arc::clone(&io_rt),
+            config.audit_log(),
+        );
+
+        let partitions = partitions
+            .into_iter()
+            .map(|graph| {
+                Partition::start(
+                    graph,
+                    Arc::clone(&router),
+                    Arc::clone(&io_rt),
+                    queue_capacity,
+                    config.audit_log(),
+                )
+            })
+            .collect();
+
+        let sessions = Sessions::new(
+            config.sessions.clone(),
+            Arc::clone(&graph_access),
+            Arc::clone(&router),
+            &io_rt,
+            queue_capacity,
         );
 
         WorkerPool {
             tx: req_tx,
+            batch_tx,
             write_tx,
+            cont_tx,
+            graph_access,
             router,
-            _workers: workers,
-            _writer_worker: writer_worker,
+            in_flight: AtomicUsize::new(0),
+            shutting_down: AtomicBool::new(false),
+            num_workers,
+            queue_capacity,
+            shed_on_overload: config.shed_on_overload(),
+            retry_after_secs: config.retry_after_secs(),
+            shed_total: AtomicU64::new(0),
+            cache: ResultCache::new(config.cache_max_entries()),
+            changes: ChangeFeed::default(),
+            slow_query_ms: AtomicU64::new(config.slow_query_ms.unwrap_or(SLOW_QUERY_OFF)),
+            sessions,
+            workers,
+            writer_worker,
+            partitions,
+        }
+    }
+
+    /// Current queue depths and load counters, for autoscaling and monitoring
+    pub fn stats(&self) -> WorkerPoolStats {
+        WorkerPoolStats {
+            workers: self.num_workers,
+            queue_capacity: self.queue_capacity,
+            read_queue_depth: self.tx.len(),
+            batch_queue_depth: self.batch_tx.len(),
+            write_queue_depth: self.write_tx.len(),
+            in_flight: self.in_flight(),
+            shed_total: self.shed_total.load(Ordering::Relaxed),
+            cache_entries: self.cache.len(),
+            cache_hits: self.cache.hits(),
+            cache_misses: self.cache.misses(),
+        }
+    }
+
+    /// The graph engine the workers execute against
+    pub fn graph(&self) -> &Arc<HelixGraphEngine> {
+        &self.graph_access
+    }
+
+    /// The router requests are dispatched through
+    pub fn routes(&self) -> Arc<HelixRouter> {
+        self.router.load()
+    }
+
+    /// Whether `name` is routed to the writer
+    pub fn is_write_route(&self, name: &str) -> bool {
+        self.router.load().is_write_route(name)
+    }
+
+    /// Labels `name` touches, if known
+    pub fn route_labels(&self, name: &str) -> Option<Vec<String>> {
+        self.router
+            .load()
+            .route_labels(name)
+            .map(<[String]>::to_vec)
+    }
+
+    /// Roles a JWT caller needs one of to call `name`
+    pub fn route_roles(&self, name: &str) -> Vec<String> {
+        self.router.load().route_roles(name).to_vec()
+    }
+
+    /// Whether a request is routed to the writer
+    pub fn is_write_request(&self, req_type: RequestType, name: &str) -> bool {
+        self.router.load().is_write_request(req_type, name)
+    }
+
+    /// Roles a JWT caller needs one of to make a request
+    pub fn request_roles(&self, req_type: RequestType, name: &str) -> Vec<String> {
+        self.router.load().request_roles(req_type, name).to_vec()
+    }
+
+    /// Dispatch requests through `router` from now on, without restarting the workers.
+    /// Requests already dispatched finish on the previous router, and cached results are
+    /// dropped since the new handlers may compute them differently.
+    pub fn replace_routes(&self, router: HelixRouter) {
+        self.router.store(Arc::new(router));
+        self.cache.invalidate(None);
+    }
+
+    /// Drop cached results that read `labels`, e.g. after data they depend on changed
+    /// outside a request. `None` drops every cached result.
+    pub fn invalidate_cached(&self, labels: Option<&[String]>) {
+        self.cache.invalidate(labels);
+    }
+
+    /// Resize the result cache. Shrinking it drops every cached result, and 0 disables it.
+    pub fn set_cache_max_entries(&self, max_entries: usize) {
+        self.cache.set_max_entries(max_entries);
+    }
+
+    /// Queries taking at least this long are logged with their timings
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        match self.slow_query_ms.load(Ordering::Relaxed) {
+            SLOW_QUERY_OFF => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// Log queries taking at least `threshold` from now on, or none when `None`
+    pub fn set_slow_query_threshold(&self, threshold: Option<Duration>) {
+        let ms = threshold.map_or(SLOW_QUERY_OFF, |threshold| {
+            (threshold.as_millis() as u64).min(SLOW_QUERY_OFF - 1)
+        });
+        self.slow_query_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Committed writes and shutdown, as they happen
+    pub fn changes(&self) -> &ChangeFeed {
+        &self.changes
+    }
+
+    /// Read sessions, each pinned to one snapshot of the graph
+    pub fn sessions(&self) -> &Sessions {
+        &self.sessions
+    }
+
+    /// Number of requests currently being processed (queued, executing or awaiting IO)
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Stop accepting new requests. Requests already submitted keep running.
+    pub fn begin_shutdown(&self) {
+        if !self.shutting_down.swap(true, Ordering::AcqRel) {
+            self.changes.publish_shutdown();
         }
     }
 
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+
+    /// Number of partitions requests are routed to by their partition key, counting the main
+    /// database
+    pub fn partition_count(&self) -> usize {
+        self.partitions.len() + 1
+    }
+
+    /// The partition other than the main database a request with this partition key runs on
+    fn partition(&self, key: Option<&str>) -> Option<&Partition> {
+        let index = partition_of(key?, self.partition_count());
+        index.checked_sub(1).map(|i| &self.partitions[i])
+    }
+
+    /// Worker and writer threads that have exited, e.g. after a handler panicked
+    pub fn stopped_workers(&self) -> usize {
+        self.workers
+            .iter()
+            .chain(iter::once(&self.writer_worker))
+            .chain(self.partitions.iter().map(|p| &p.worker))
+            .filter(|w| w.handle.is_finished())
+            .count()
+    }
+
+    /// Wait until every in-flight request has completed or the deadline passes.
+    /// Returns `true` if the pool drained in time.
+    pub async fn drain(&self, deadline: Instant) -> bool {
+        while self.in_flight() > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        true
+    }
+
+    /// Close all channels and join the worker threads.
+    ///
+    /// Workers finish whatever is still queued (including pending IO continuations)
+    /// before exiting. Returns `true` if every thread exited before the deadline;
+    /// threads still running after it are detached.
+    pub fn shutdown(self, deadline: Instant) -> bool {
+        self.begin_shutdown();
+        let WorkerPool {
+            tx,
+            batch_tx,
+            write_tx,
+            cont_tx,
+            workers,
+            writer_worker,
+            partitions,
+            ..
+        } = self;
+        drop(tx);
+        drop(batch_tx);
+        drop(write_tx);
+        drop(cont_tx);
+
+        let mut pending: Vec<Worker> = workers;
+        pending.push(writer_worker);
+        pending.extend(partitions.into_iter().map(|p| p.worker));
+        while !pending.is_empty() && Instant::now() < deadline {
+            pending.retain(|w| !w.handle.is_finished());
+            if !pending.is_empty() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        if !pending.is_empty() {
+            warn!(
+                "{} worker thread(s) still running after shutdown deadline",
+                pending.len()
+            );
+            return false;
+        }
+        true
+    }
+
     /// Process a request on the Worker Pool
     /// Write operations are routed to a dedicated writer thread to ensure proper LMDB locking
     pub async fn process(&self, req: Request) -> Result<Response, HelixError> {
+        self.process_with(req, RequestHints::default()).await
+    }
+
+    /// Process a request using the scheduling hints supplied by the client.
+    /// Priority only affects reads; writes always go through the single writer thread in order.
+    /// Requests whose partition key hashes to a partition other than the main database run on
+    /// that partition's writer instead, reads included.
+    pub async fn process_with(
+        &self,
+        req: Request,
+        hints: RequestHints,
+    ) -> Result<Response, HelixError> {
+        if self.is_shutting_down() {
+            return Err(HelixError::ShuttingDown);
+        }
+        let start = Instant::now();
+
+        let router = self.router.load();
+        let is_write = router.is_write_request(req.req_type, &req.name);
+        let is_query = req.req_type == RequestType::Query;
+        let labels = router.route_labels(&req.name);
+        let partition = self.partition(hints.partition.as_deref());
+        let graph = partition.map_or(&self.graph_access, |p| &p.graph_access);
+
+        // Data this build can't read correctly must not be rewritten by it
+        if is_write && let Some(mismatch) = &graph.storage.schema_mismatch {
+            return Err(HelixError::IncompatibleSchema {
+                reasons: mismatch.reasons.clone(),
+            });
+        }
+
+        // Sessions only read, so every query they run sees the same snapshot
+        let session = match &hints.session {
+            Some(_) if is_write || !is_query => {
+                return Err(HelixError::InvalidSession(
+                    "sessions are read-only".to_string(),
+                ));
+            }
+            Some(_) if hints.read_your_writes => {
+                return Err(HelixError::InvalidSession(
+                    "sessions read a fixed snapshot, not their own writes".to_string(),
+                ));
+            }
+            Some(_) if hints.partition.is_some() && !self.partitions.is_empty() => {
+                return Err(HelixError::InvalidSession(
+                    "sessions read the main database, not a partition".to_string(),
+                ));
+            }
+            Some(token) => Some(self.sessions.sender(token)?),
+            None => None,
+        };
+
+        // Reads that must see the caller's writes queue behind them on the writer
+        let on_writer = is_write || (is_query && hints.read_your_writes);
+
+        // Serve cached reads without touching the queues
+        let cacheable = match router.cache_ttl(&req.name) {
+            Some(ttl)
+                if is_query
+                    && !on_writer
+                    && partition.is_none()
+                    && session.is_none()
+                    && self.cache.is_enabled() =>
+            {
+                if let Some(res) = self.cache.get(&req.name, &req.body, req.out_fmt, labels) {
+                    return Ok(res);
+                }
+                Some((
+                    ttl,
+                    req.body.clone(),
+                    req.out_fmt,
+                    self.cache.epochs(labels),
+                ))
+            }
+            _ => None,
+        };
+
+        // Requests past a route's limit wait here rather than taking up a worker
+        let _permit = match router.concurrency_limit(&req.name) {
+            Some(limit) => Some(
+                Arc::clone(&limit.permits)
+                    .acquire_owned()
+                    .await
+                    .expect("route concurrency limits are never closed"),
+            ),
+            None => None,
+        };
+
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let _guard = InFlightGuard(&self.in_flight);
+
         let (ret_tx, ret_rx) = oneshot::channel();
         let req_name = req.name.clone();
 
         // Route to dedicated writer thread or reader worker pool
-        let channel = if self.router.is_write_route(&req.name) {
-            &self.write_tx
-        } else {
-            &self.tx
-        };
+        let (channel, lane) =
+            info_span!("helix.route", route = %req.name, is_write).in_scope(|| {
+                if let Some(session) = &session {
+                    (session, "session")
+                } else if let Some(partition) = partition {
+                    (&partition.tx, "partition")
+                } else if on_writer {
+                    (&self.write_tx, "write")
+                } else {
+                    match hints
+                        .priority
+                        .unwrap_or_else(|| router.route_priority(&req.name))
+                    {
+                        Priority::Interactive => (&self.tx, "interactive"),
+                        Priority::Batch => (&self.batch_tx, "batch"),
+                    }
+                }
+            });
+        let mut trace = RequestTrace::queued(lane);
+        trace.caller = hints.caller.clone();
+        let profile = Arc::clone(&trace.profile);
+        let slow_query_threshold = self.slow_query_threshold();
+        let params = slow_query_threshold.map(|_| req.body.clone());
+        if slow_query_threshold.is_some() {
+            profile.profile_steps();
+        }
 
-        channel.send_async((req, ret_tx)).await.map_err(|_| {
-            error!("WorkerPool channel closed for request '{req_name}'");
-            HelixError::Graph(GraphError::New("Server is shutting down".into()))
-        })?;
+        if self.shed_on_overload {
+            // Fail fast rather than letting queueing latency grow without bound
+            match channel.try_send((req, ret_tx, trace)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    self.shed_total.fetch_add(1, Ordering::Relaxed);
+                    warn!("Worker queue full, shedding request '{req_name}'");
+                    return Err(HelixError::Overloaded {
+                        retry_after_secs: self.retry_after_secs,
+                    });
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    return Err(closed_channel_error(&req_name, hints.session));
+                }
+            }
+        } else {
+            channel
+                .send_async((req, ret_tx, trace))
+                .await
+                .map_err(|_| closed_channel_error(&req_name, hints.session))?;
+        }
 
         // Handle the case where the worker might have dropped the sender
         // (e.g., worker thread panicked or client disconnected)
-        ret_rx.await.unwrap_or_else(|_| {
+        let res = ret_rx.await.unwrap_or_else(|_| {
             error!("Worker dropped sender without reply for request '{req_name}'");
             Err(HelixError::Graph(GraphError::New(
                 "Internal server error: worker failed to respond".into(),
             )))
-        })
+        });
+
+        if let (Some(threshold), Some(params)) = (slow_query_threshold, params) {
+            let elapsed = start.elapsed();
+            if elapsed >= threshold {
+                log_slow_query(&req_name, &params, elapsed, &profile);
+            }
+        }
+
+        // The cache and change feed only cover the main database
+        if let Ok(response) = &res
+            && partition.is_none()
+        {
+            if is_write && is_query {
+                self.cache.invalidate(labels);
+                self.changes.publish_write(labels);
+            } else if let Some((ttl, body, out_fmt, epochs)) = cacheable {
+                self.cache
+                    .insert(&req_name, body, out_fmt, response, ttl, epochs);
+            }
+        }
+        res
+    }
+}
+
+/// A session's queue closes when it expires; any other queue only closes on shutdown
+fn closed_channel_error(req_name: &str, session: Option<String>) -> HelixError {
+    match session {
+        Some(token) => HelixError::SessionNotFound(token),
+        None => {
+            error!("WorkerPool channel closed for request '{req_name}'");
+            HelixError::ShuttingDown
+        }
+    }
+}
+
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Number of consecutive interactive requests a reader may take while batch work is waiting
+const INTERACTIVE_WEIGHT: usize = 4;
+
+/// The interactive and batch read queues as seen by a single reader.
+///
+/// Interactive requests are preferred, but after `INTERACTIVE_WEIGHT` interactive requests
+/// in a row the batch lane is checked first, so background traffic still makes progress.
+pub(crate) struct ReadLanes {
+    interactive: Receiver<ReqMsg>,
+    batch: Receiver<ReqMsg>,
+    interactive_streak: usize,
+}
+
+impl ReadLanes {
+    pub(crate) fn new(interactive: Receiver<ReqMsg>, batch: Receiver<ReqMsg>) -> Self {
+        ReadLanes {
+            interactive,
+            batch,
+            interactive_streak: 0,
+        }
+    }
+
+    fn lane(&self, priority: Priority) -> &Receiver<ReqMsg> {
+        match priority {
+            Priority::Interactive => &self.interactive,
+            Priority::Batch => &self.batch,
+        }
+    }
+
+    fn record(&mut self, priority: Priority) {
+        match priority {
+            Priority::Interactive => self.interactive_streak += 1,
+            Priority::Batch => self.interactive_streak = 0,
+        }
+    }
+
+    /// Take the next request without blocking. Only reports `Disconnected` once both lanes are.
+    pub(crate) fn try_recv(&mut self) -> Result<ReqMsg, TryRecvError> {
+        let order = if self.interactive_streak >= INTERACTIVE_WEIGHT {
+            [Priority::Batch, Priority::Interactive]
+        } else {
+            [Priority::Interactive, Priority::Batch]
+        };
+
+        let mut disconnected = 0;
+        for priority in order {
+            match self.lane(priority).try_recv() {
+                Ok(msg) => {
+                    self.record(priority);
+                    return Ok(msg);
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => disconnected += 1,
+            }
+        }
+
+        if disconnected == order.len() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Block until a request arrives on either lane.
+    pub(crate) fn recv(&mut self) -> Result<ReqMsg, RecvError> {
+        match self.try_recv() {
+            Ok(msg) => return Ok(msg),
+            Err(TryRecvError::Disconnected) => return Err(RecvError::Disconnected),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let selected = Selector::new()
+            .recv(&self.interactive, |r| r.map(|m| (Priority::Interactive, m)))
+            .recv(&self.batch, |r| r.map(|m| (Priority::Batch, m)))
+            .wait();
+
+        match selected {
+            Ok((priority, msg)) => {
+                self.record(priority);
+                Ok(msg)
+            }
+            // One lane closed; keep serving the other until it closes too
+            Err(RecvError::Disconnected) => match self.try_recv() {
+                Ok(msg) => Ok(msg),
+                Err(TryRecvError::Disconnected) => Err(RecvError::Disconnected),
+                Err(TryRecvError::Empty) => {
+                    let priority = if self.interactive.is_disconnected() {
+                        Priority::Batch
+                    } else {
+                        Priority::Interactive
+                    };
+                    let msg = self.lane(priority).recv()?;
+                    self.record(priority);
+                    Ok(msg)
+                }
+            },
+        }
     }
 }
 
 struct Worker {
-    _handle: JoinHandle<()>,
+    handle: JoinHandle<()>,
 }
 
 impl Worker {
     pub fn start(
-        rx: Receiver<ReqMsg>,
+        mut rx: ReadLanes,
         core_setter: Arc<CoreSetter>,
         graph_access: Arc<HelixGraphEngine>,
-        router: Arc<HelixRouter>,
+        router: Arc<Routes>,
         io_rt: Arc<Runtime>,
-        (cont_tx, cont_rx): (ContChan, Receiver<ContMsg>),
+        (cont_tx, cont_rx): (WeakSender<ContMsg>, Receiver<ContMsg>),
         parity: bool,
     ) -> Worker {
         let handle = std::thread::spawn(move || {
@@ -157,11 +752,12 @@ impl Worker {
                         }
 
                         match rx.recv() {
-                            Ok((req, ret_chan)) => request_mapper(
+                            Ok((req, ret_chan, trace)) => request_mapper(
                                 req,
                                 ret_chan,
+                                trace,
                                 graph_access.clone(),
-                                &router,
+                                &router.load(),
                                 &io_rt,
                                 &cont_tx,
                             ),
@@ -177,11 +773,12 @@ impl Worker {
                         // rx.try_recv() then cont_rx.recv()
 
                         match rx.try_recv() {
-                            Ok((req, ret_chan)) => request_mapper(
+                            Ok((req, ret_chan, trace)) => request_mapper(
                                 req,
                                 ret_chan,
+                                trace,
                                 graph_access.clone(),
-                                &router,
+                                &router.load(),
                                 &io_rt,
                                 &cont_tx,
                             ),
@@ -209,8 +806,11 @@ impl Worker {
                     }
                 }
             }
+
+            // Hand any buffered metrics to the global sender before the thread exits
+            helix_metrics::flush_thread_local();
         });
-        Worker { _handle: handle }
+        Worker { handle }
     }
 
     /// Start a dedicated writer worker thread
@@ -219,8 +819,9 @@ impl Worker {
     pub fn start_writer(
         rx: Receiver<ReqMsg>,
         graph_access: Arc<HelixGraphEngine>,
-        router: Arc<HelixRouter>,
+        router: Arc<Routes>,
         io_rt: Arc<Runtime>,
+        audit_writes: bool,
     ) -> Worker {
         let handle = std::thread::spawn(move || {
             // Initialize thread-local metrics buffer
@@ -233,7 +834,15 @@ impl Worker {
             // any continuations to complete before moving to the next request.
             loop {
                 match rx.recv() {
-                    Ok((req, ret_chan)) => {
+                    Ok((req, ret_chan, trace)) => {
+                        #[cfg(feature = "chaos")]
+                        crate::helix_gateway::chaos::stall_writer(&req.name);
+
+                        // Reads sent here to see their own writes aren't audited
+                        if audit_writes && router.load().is_write_request(req.req_type, &req.name) {
+                            audit::begin(&req);
+                        }
+
                         // Create a per-request continuation channel
                         let (cont_tx, cont_rx) = flume::bounded::<ContMsg>(1);
 
@@ -241,10 +850,11 @@ impl Worker {
                         request_mapper(
                             req,
                             ret_chan,
+                            trace,
                             graph_access.clone(),
-                            &router,
+                            &router.load(),
                             &io_rt,
-                            &cont_tx,
+                            &cont_tx.downgrade(),
                         );
 
                         // Drop our sender so the channel disconnects when the async future
@@ -253,6 +863,8 @@ impl Worker {
 
                         // Poll continuation channel until sender is dropped.
                         while let Ok((ret_chan, cfn)) = cont_rx.recv() {
+                            write_log::reset();
+                            constraints::reset();
                             let result = cfn().map_err(Into::into);
                             if ret_chan.send(result).is_err() {
                                 trace!(
@@ -260,6 +872,7 @@ impl Worker {
                                 );
                             }
                         }
+                        audit_log::end();
                     }
                     Err(_) => {
                         trace!("Writer request channel was dropped, shutting down");
@@ -267,22 +880,47 @@ impl Worker {
                     }
                 }
             }
+
+            helix_metrics::flush_thread_local();
         });
-        Worker { _handle: handle }
+        Worker { handle }
     }
 }
 
 fn request_mapper(
     request: Request,
     ret_chan: RetChan,
+    trace: RequestTrace,
     graph_access: Arc<HelixGraphEngine>,
     router: &HelixRouter,
     io_rt: &Runtime,
-    cont_tx: &ContChan,
+    cont_tx: &WeakSender<ContMsg>,
 ) {
     let req_name = request.name.clone();
     let req_type = request.req_type;
 
+    let RequestTrace {
+        request: request_span,
+        queue_wait,
+        enqueued_at,
+        profile,
+        caller,
+    } = trace;
+    drop(queue_wait);
+    profile.record_queue_wait(enqueued_at.elapsed());
+    let _execute = info_span!(parent: &request_span, "helix.execute", route = %req_name).entered();
+
+    #[cfg(feature = "chaos")]
+    if router.routes.contains_key(&req_name) || router.mcp_routes.contains_key(&req_name) {
+        let is_write = router.is_write_request(req_type, &req_name);
+        if let Some(fault) = crate::helix_gateway::chaos::request_fault(&req_name, is_write) {
+            if ret_chan.send(Err(fault.into())).is_err() {
+                trace!("Client disconnected before injected fault could be sent");
+            }
+            return;
+        }
+    }
+
     let res = match request.req_type {
         RequestType::Query => {
             if let Some(handler) = router.routes.get(&request.name) {
@@ -291,12 +929,31 @@ fn request_mapper(
                     graph: graph_access,
                 };
 
-                match handler(input) {
-                    Err(GraphError::IoNeeded(cont_closure)) => {
-                        let fut = cont_closure.0(cont_tx.clone(), ret_chan);
-                        io_rt.spawn(fut);
-                        return;
-                    }
+                scan_counter::reset();
+                step_profile::reset(profile.steps_profiled());
+                write_log::reset();
+                // Nodes noted by a write that failed before checking them
+                constraints::reset();
+                let started = Instant::now();
+                let res = handler(input);
+                profile.record_execution(started.elapsed(), scan_counter::take());
+                profile.record_steps(step_profile::take());
+
+                match res {
+                    Err(GraphError::IoNeeded(cont_closure)) => match cont_tx.upgrade() {
+                        Some(cont_tx) => {
+                            let fut = cont_closure.0(cont_tx, ret_chan).instrument(info_span!(
+                                parent: &request_span,
+                                "helix.io_continuation",
+                                route = %req_name
+                            ));
+                            #[cfg(feature = "chaos")]
+                            let fut = crate::helix_gateway::chaos::delay_continuation(&req_name, fut);
+                            io_rt.spawn(fut);
+                            return;
+                        }
+                        None => Some(Err(HelixError::ShuttingDown)),
+                    },
                     res => Some(res.map_err(Into::into)),
                 }
             } else {
@@ -305,23 +962,34 @@ fn request_mapper(
         }
         RequestType::MCP => {
             if let Some(mcp_handler) = router.mcp_routes.get(&request.name) {
-                let mut mcp_input = MCPToolInput {
-                    request,
-                    mcp_backend: Arc::clone(
-                        graph_access
-                            .mcp_backend
-                            .as_ref()
-                            .expect("MCP backend not found"),
-                    ),
-                    mcp_connections: Arc::clone(
-                        graph_access
-                            .mcp_connections
-                            .as_ref()
-                            .expect("MCP connections not found"),
-                    ),
-                    schema: graph_access.storage.storage_config.schema.clone(),
-                };
-                Some(mcp_handler(&mut mcp_input).map_err(Into::into))
+                let mcp_connections = Arc::clone(
+                    graph_access
+                        .mcp_connections
+                        .as_ref()
+                        .expect("MCP connections not found"),
+                );
+                let admitted = mcp_connections
+                    .lock()
+                    .expect("MCP connections lock poisoned")
+                    .admit(&request, caller.as_deref());
+                match admitted {
+                    Ok(()) => {
+                        let mut mcp_input = MCPToolInput {
+                            request,
+                            mcp_backend: Arc::clone(
+                                graph_access
+                                    .mcp_backend
+                                    .as_ref()
+                                    .expect("MCP backend not found"),
+                            ),
+                            mcp_connections,
+                            schema: graph_access.storage.storage_config.schema.clone(),
+                            caller,
+                        };
+                        Some(mcp_handler(&mut mcp_input).map_err(Into::into))
+                    }
+                    Err(e) => Some(Err(e)),
+                }
             } else {
                 None
             }
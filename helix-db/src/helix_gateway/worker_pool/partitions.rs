@@ -0,0 +1,80 @@
+//! Tenant partitions: databases of their own, each in a separate LMDB environment with its own
+//! writer thread, so writes of different tenants commit in parallel instead of queueing for the
+//! one write lock of the main database.
+//!
+//! A request sent with a key in the `x-helix-partition` header runs on the partition its key
+//! hashes to. Partition 0 is the main database; the others are stored under
+//! `<data dir>/partitions/<n>`. A partition's writer also serves its reads, in the order they
+//! were sent, so they see every write sent before them.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use flume::Sender;
+use tokio::runtime::Runtime;
+
+use super::Worker;
+use crate::helix_engine::{
+    traversal_core::{HelixGraphEngine, HelixGraphEngineOpts},
+    types::GraphError,
+};
+use crate::helix_gateway::router::router::Routes;
+use crate::protocol::request::ReqMsg;
+
+/// Directory under the data directory holding the partitions other than the main database
+pub const PARTITIONS_DIR: &str = "partitions";
+
+/// Index of the partition a key's requests run on, out of `count`. Stable across restarts
+/// and builds, so a key keeps finding its data as long as `count` doesn't change.
+pub fn partition_of(key: &str, count: usize) -> usize {
+    // FNV-1a
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    (hash % count.max(1) as u64) as usize
+}
+
+/// Open the `count - 1` partitions besides the main database stored at `opts.path`, with the
+/// main database's config
+pub fn open_partitions(
+    opts: &HelixGraphEngineOpts,
+    count: usize,
+) -> Result<Vec<Arc<HelixGraphEngine>>, GraphError> {
+    (1..count)
+        .map(|index| {
+            let path = Path::new(&opts.path)
+                .join(PARTITIONS_DIR)
+                .join(index.to_string());
+            HelixGraphEngine::new(HelixGraphEngineOpts {
+                path: path.to_string_lossy().into_owned(),
+                ..opts.clone()
+            })
+            .map(Arc::new)
+        })
+        .collect()
+}
+
+pub(super) struct Partition {
+    pub(super) graph_access: Arc<HelixGraphEngine>,
+    pub(super) tx: Sender<ReqMsg>,
+    pub(super) worker: Worker,
+}
+
+impl Partition {
+    pub(super) fn start(
+        graph_access: Arc<HelixGraphEngine>,
+        router: Arc<Routes>,
+        io_rt: Arc<Runtime>,
+        queue_capacity: usize,
+        audit_writes: bool,
+    ) -> Self {
+        let (tx, rx) = flume::bounded::<ReqMsg>(queue_capacity);
+        let worker =
+            Worker::start_writer(rx, Arc::clone(&graph_access), router, io_rt, audit_writes);
+        Partition {
+            graph_access,
+            tx,
+            worker,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Read sessions: several read queries answered from one snapshot of the graph, so pages of a
+//! listing or the queries of a report agree with each other while writes commit in between.
+//!
+//! `POST /session` opens a session and answers with its token. Queries sent with the token in
+//! the `x-helix-session` header run on the session's snapshot, one at a time, on a thread of
+//! the session's own, as LMDB read transactions can't move between threads. The session
+//! closes on `DELETE /session/{token}`, or once no query used it for the idle timeout.
+
+use std::collections::HashMap;
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use flume::{Receiver, Sender};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+use tracing::trace;
+use uuid::Uuid;
+
+use super::request_mapper;
+use crate::helix_engine::{
+    storage_core::snapshot::PinnedSnapshot,
+    traversal_core::{HelixGraphEngine, config::SessionConfig},
+    types::GraphError,
+};
+use crate::helix_gateway::router::router::{ContMsg, Routes};
+use crate::protocol::{HelixError, request::ReqMsg};
+
+/// A session as reported to the client that opened it
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    /// Token to send in the `x-helix-session` header
+    pub session: String,
+    /// Id of the transaction whose snapshot the session reads
+    pub txn_id: usize,
+    pub idle_timeout_secs: u64,
+}
+
+struct Session {
+    tx: Sender<ReqMsg>,
+    handle: JoinHandle<()>,
+}
+
+pub struct Sessions {
+    /// `None` when sessions are off
+    config: Option<SessionConfig>,
+    graph_access: Arc<HelixGraphEngine>,
+    router: Arc<Routes>,
+    /// Weak so the pool doesn't keep the runtime alive; only session threads hold it
+    io_rt: Weak<Runtime>,
+    queue_capacity: usize,
+    open: Mutex<HashMap<String, Session>>,
+}
+
+impl Sessions {
+    pub(super) fn new(
+        config: Option<SessionConfig>,
+        graph_access: Arc<HelixGraphEngine>,
+        router: Arc<Routes>,
+        io_rt: &Arc<Runtime>,
+        queue_capacity: usize,
+    ) -> Self {
+        Sessions {
+            config,
+            graph_access,
+            router,
+            io_rt: Arc::downgrade(io_rt),
+            queue_capacity,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    /// Number of sessions open, leaving out the ones that timed out
+    pub fn len(&self) -> usize {
+        let mut open = self.open.lock().expect("sessions lock poisoned");
+        open.retain(|_, session| !session.handle.is_finished());
+        open.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Open a session on a snapshot of the graph as of now
+    pub fn open(&self) -> Result<SessionInfo, HelixError> {
+        let Some(config) = &self.config else {
+            return Err(HelixError::InvalidSession(
+                "sessions are off on this instance".to_string(),
+            ));
+        };
+        let mut open = self.open.lock().expect("sessions lock poisoned");
+        open.retain(|_, session| !session.handle.is_finished());
+        if open.len() >= config.max_sessions() {
+            return Err(HelixError::TooManySessions {
+                max: config.max_sessions(),
+            });
+        }
+
+        let (tx, rx) = flume::bounded::<ReqMsg>(self.queue_capacity);
+        let (ready_tx, ready_rx) = sync_channel(1);
+        let graph_access = Arc::clone(&self.graph_access);
+        let router = Arc::clone(&self.router);
+        let io_rt = self.io_rt.upgrade().ok_or(HelixError::ShuttingDown)?;
+        let idle_timeout = config.idle_timeout();
+        let handle = std::thread::spawn(move || {
+            let snapshot = match PinnedSnapshot::pin(&graph_access.storage) {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            if ready_tx.send(Ok(snapshot.txn_id())).is_ok() {
+                serve(rx, graph_access, &router, &io_rt, idle_timeout);
+            }
+        });
+        let txn_id = ready_rx
+            .recv()
+            .map_err(|_| GraphError::New("session thread exited before opening".to_string()))?
+            .map_err(GraphError::from)?;
+
+        let token = Uuid::new_v4().simple().to_string();
+        open.insert(token.clone(), Session { tx, handle });
+        Ok(SessionInfo {
+            session: token,
+            txn_id,
+            idle_timeout_secs: idle_timeout.as_secs(),
+        })
+    }
+
+    /// Close a session once the queries already sent to it finish. Returns whether it was open.
+    pub fn close(&self, token: &str) -> bool {
+        let mut open = self.open.lock().expect("sessions lock poisoned");
+        open.remove(token)
+            .is_some_and(|session| !session.handle.is_finished())
+    }
+
+    /// Queue of the session `token`
+    pub(super) fn sender(&self, token: &str) -> Result<Sender<ReqMsg>, HelixError> {
+        let open = self.open.lock().expect("sessions lock poisoned");
+        match open.get(token) {
+            Some(session) if !session.handle.is_finished() => Ok(session.tx.clone()),
+            _ => Err(HelixError::SessionNotFound(token.to_string())),
+        }
+    }
+}
+
+/// Run the session's queries on the snapshot pinned to this thread until the session is closed
+/// or stays idle, waiting for IO continuations like the writer does so they read the snapshot
+/// too
+fn serve(
+    rx: Receiver<ReqMsg>,
+    graph_access: Arc<HelixGraphEngine>,
+    router: &Routes,
+    io_rt: &Runtime,
+    idle_timeout: Duration,
+) {
+    helix_metrics::init_thread_local();
+    let _io_guard = io_rt.enter();
+
+    while let Ok((req, ret_chan, trace)) = rx.recv_timeout(idle_timeout) {
+        let (cont_tx, cont_rx) = flume::bounded::<ContMsg>(1);
+        request_mapper(
+            req,
+            ret_chan,
+            trace,
+            Arc::clone(&graph_access),
+            &router.load(),
+            io_rt,
+            &cont_tx.downgrade(),
+        );
+        drop(cont_tx);
+
+        while let Ok((ret_chan, cfn)) = cont_rx.recv() {
+            let result = cfn().map_err(Into::into);
+            if ret_chan.send(result).is_err() {
+                trace!("Client disconnected before continuation response could be sent");
+            }
+        }
+    }
+
+    helix_metrics::flush_thread_local();
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::StatusCode;
+
+use crate::helix_gateway::admin::AdminAuth;
+use crate::helix_gateway::gateway::AppState;
+use axum::response::IntoResponse;
+
+/// Reports worker pool queue depths and shed counts so external autoscalers
+/// can react to load before requests start getting rejected.
+pub async fn worker_stats_handler(
+    _: AdminAuth,
+    State(state): State<Arc<AppState>>,
+) -> axum::response::Response {
+    match sonic_rs::to_vec(&state.worker_pool.stats()) {
+        Ok(body) => axum::response::Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("should be able to make response from stats"),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not serialize stats",
+        )
+            .into_response(),
+    }
+}
@@ -24,6 +24,14 @@ pub enum ErrorCode {
     E109,
     /// `E110` – `schema item name is a reserved type name`
     E110,
+    /// `E111` – `invalid computed field`
+    E111,
+    /// `E112` – `invalid view`
+    E112,
+    /// `E113` – `invalid trigger`
+    E113,
+    /// `E114` – `invalid edge constraint`
+    E114,
 
     // TYPE ERRORS
     /// `E201` – `item type not in schema`
@@ -46,6 +54,10 @@ pub enum ErrorCode {
     E209,
     /// `E210` – `identifier was expected to be of type ID, but got {}`
     E210,
+    /// `E211` – `computed field cannot be written`
+    E211,
+    /// `E212` – `parameter check doesn't fit the parameter`
+    E212,
     // QUERY ERRORS
     /// `E301` – `variable not in scope`
     E301,
@@ -59,6 +71,8 @@ pub enum ErrorCode {
     E305,
     /// `E306` – `expression is not a boolean`
     E306,
+    /// `E307` – `query exceeds the complexity budget`
+    E307,
 
     // MCP ERRORS
     /// `E401` – `MCP query must return a single value`
@@ -99,6 +113,10 @@ pub enum ErrorCode {
     E627,
     /// `E628` - `DROP can only be applied to traversals`
     E628,
+    /// `E629` - `invalid MERGE_NODES`
+    E629,
+    /// `E630` - `PATHS can only follow Out and In steps between nodes`
+    E630,
 
     /// `E631` - `range must have a start and end`
     E631,
@@ -106,6 +124,20 @@ pub enum ErrorCode {
     E632,
     /// `E633` - `index of range must be an integer`
     E633,
+    /// `E634` - `DEGREE and NEIGHBOR_COUNT can only be applied to nodes and vectors`
+    E634,
+    /// `E635` - `SAMPLE takes a number of items or a fraction between 0 and 1`
+    E635,
+    /// `E636` - `BUCKET_BY takes a date property, a bucket width and a time zone`
+    E636,
+    /// `E637` - `statistics take a numeric property`
+    E637,
+    /// `E638` - `DELETE applies to nodes, edges or vectors selected from the graph`
+    E638,
+    /// `E639` - `rerankers take scored items`
+    E639,
+    /// `E640` - `scores are only set on search results`
+    E640,
 
     /// `E641` - `closure is only valid as the last step in a traversal`
     E641,
@@ -119,6 +151,10 @@ pub enum ErrorCode {
     E645,
     /// `E646` - `field value is empty`
     E646,
+    /// `E647` - `key shadows the relevance score`
+    E647,
+    /// `E648` - `UDF call outside a projection or weight`
+    E648,
 
     /// `E651` - `in variable is not iterable`
     E651,
@@ -139,6 +175,8 @@ pub enum ErrorCode {
 
     /// `W101` - `query has no return`
     W101,
+    /// `W102` - `cache annotation ignored on write query`
+    W102,
 }
 impl ErrorCode {
     /// Returns a short human-readable description of the error (e.g. "unknown edge type").
@@ -155,6 +193,10 @@ impl ErrorCode {
             ErrorCode::E108 => "invalid schema version",
             ErrorCode::E109 => "duplicate field name in schema",
             ErrorCode::E110 => "schema item name is a reserved type name",
+            ErrorCode::E111 => "invalid computed field",
+            ErrorCode::E112 => "invalid view",
+            ErrorCode::E113 => "invalid trigger",
+            ErrorCode::E114 => "invalid edge constraint",
             // Type errors
             ErrorCode::E201 => "item type not in schema",
             ErrorCode::E202 => "invalid field for item type",
@@ -166,6 +208,8 @@ impl ErrorCode {
             ErrorCode::E208 => "field has not been indexed",
             ErrorCode::E209 => "unknown type for parameter",
             ErrorCode::E210 => "expected ID type",
+            ErrorCode::E211 => "computed field cannot be written",
+            ErrorCode::E212 => "parameter check doesn't fit the parameter",
             // Query errors
             ErrorCode::E301 => "variable not in scope",
             ErrorCode::E302 => "variable previously declared",
@@ -173,6 +217,7 @@ impl ErrorCode {
             ErrorCode::E304 => "missing item type",
             ErrorCode::E305 => "missing parameter",
             ErrorCode::E306 => "expression is not a boolean",
+            ErrorCode::E307 => "query exceeds the complexity budget",
             // MCP errors
             ErrorCode::E401 => "MCP query must return a single value",
             // Conversion errors
@@ -192,10 +237,19 @@ impl ErrorCode {
             ErrorCode::E626 => "edge type does not have a vector type as its To source",
             ErrorCode::E627 => "shortest path requires from or to parameter",
             ErrorCode::E628 => "DROP can only be applied to traversals",
+            ErrorCode::E629 => "invalid MERGE_NODES",
+            ErrorCode::E630 => "PATHS can only follow Out and In steps between nodes",
             // Range errors
             ErrorCode::E631 => "range must have a start and end",
             ErrorCode::E632 => "range start must be less than range end",
             ErrorCode::E633 => "index of range must be an integer",
+            ErrorCode::E634 => "DEGREE and NEIGHBOR_COUNT can only be applied to nodes and vectors",
+            ErrorCode::E635 => "SAMPLE takes a number of items or a fraction between 0 and 1",
+            ErrorCode::E636 => "BUCKET_BY takes a date property, a bucket width and a time zone",
+            ErrorCode::E637 => "statistics take a numeric property",
+            ErrorCode::E638 => "DELETE applies to nodes, edges or vectors selected from the graph",
+            ErrorCode::E639 => "rerankers take scored items",
+            ErrorCode::E640 => "scores are only set on search results",
             // Object remapping errors
             ErrorCode::E641 => "closure is only valid as the last step in a traversal",
             ErrorCode::E642 => "object remapping is only valid as the last step in a traversal",
@@ -203,6 +257,8 @@ impl ErrorCode {
             ErrorCode::E644 => "exclude is only valid as the last step in a traversal",
             ErrorCode::E645 => "object remapping must have at least one field",
             ErrorCode::E646 => "field value is empty",
+            ErrorCode::E647 => "key shadows the relevance score",
+            ErrorCode::E648 => "UDF call outside a projection or weight",
             // For loop errors
             ErrorCode::E651 => "in variable is not iterable",
             ErrorCode::E652 => "variable is not a field of the inner type",
@@ -214,6 +270,7 @@ impl ErrorCode {
             ErrorCode::E658 => "field not found in object type",
             // Warnings
             ErrorCode::W101 => "query has no return",
+            ErrorCode::W102 => "cache annotation ignored on write query",
         }
     }
 }
@@ -231,6 +288,10 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::E108 => write!(f, "E108"),
             ErrorCode::E109 => write!(f, "E109"),
             ErrorCode::E110 => write!(f, "E110"),
+            ErrorCode::E111 => write!(f, "E111"),
+            ErrorCode::E112 => write!(f, "E112"),
+            ErrorCode::E113 => write!(f, "E113"),
+            ErrorCode::E114 => write!(f, "E114"),
             ErrorCode::E201 => write!(f, "E201"),
             ErrorCode::E202 => write!(f, "E202"),
             ErrorCode::E203 => write!(f, "E203"),
@@ -241,12 +302,15 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::E208 => write!(f, "E208"),
             ErrorCode::E209 => write!(f, "E209"),
             ErrorCode::E210 => write!(f, "E210"),
+            ErrorCode::E211 => write!(f, "E211"),
+            ErrorCode::E212 => write!(f, "E212"),
             ErrorCode::E301 => write!(f, "E301"),
             ErrorCode::E302 => write!(f, "E302"),
             ErrorCode::E303 => write!(f, "E303"),
             ErrorCode::E304 => write!(f, "E304"),
             ErrorCode::E305 => write!(f, "E305"),
             ErrorCode::E306 => write!(f, "E306"),
+            ErrorCode::E307 => write!(f, "E307"),
             ErrorCode::E401 => write!(f, "E401"),
             ErrorCode::E501 => write!(f, "E501"),
             ErrorCode::E601 => write!(f, "E601"),
@@ -263,15 +327,26 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::E626 => write!(f, "E626"),
             ErrorCode::E627 => write!(f, "E627"),
             ErrorCode::E628 => write!(f, "E628"),
+            ErrorCode::E629 => write!(f, "E629"),
+            ErrorCode::E630 => write!(f, "E630"),
             ErrorCode::E631 => write!(f, "E631"),
             ErrorCode::E632 => write!(f, "E632"),
             ErrorCode::E633 => write!(f, "E633"),
+            ErrorCode::E634 => write!(f, "E634"),
+            ErrorCode::E635 => write!(f, "E635"),
+            ErrorCode::E636 => write!(f, "E636"),
+            ErrorCode::E637 => write!(f, "E637"),
+            ErrorCode::E638 => write!(f, "E638"),
+            ErrorCode::E639 => write!(f, "E639"),
+            ErrorCode::E640 => write!(f, "E640"),
             ErrorCode::E641 => write!(f, "E641"),
             ErrorCode::E642 => write!(f, "E642"),
             ErrorCode::E643 => write!(f, "E643"),
             ErrorCode::E644 => write!(f, "E644"),
             ErrorCode::E645 => write!(f, "E645"),
             ErrorCode::E646 => write!(f, "E646"),
+            ErrorCode::E647 => write!(f, "E647"),
+            ErrorCode::E648 => write!(f, "E648"),
             ErrorCode::E651 => write!(f, "E651"),
             ErrorCode::E652 => write!(f, "E652"),
             ErrorCode::E653 => write!(f, "E653"),
@@ -281,6 +356,7 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::E657 => write!(f, "E657"),
             ErrorCode::E658 => write!(f, "E658"),
             ErrorCode::W101 => write!(f, "W101"),
+            ErrorCode::W102 => write!(f, "W102"),
         }
     }
 }
@@ -315,6 +391,10 @@ implement_error_code!(E106, "use of undeclared node or vector type `{}` in schem
 implement_error_code!(E107, "duplicate {} definition `{}`" => { schema_type, name }, "rename the {} or remove the duplicate definition" => { schema_type });
 implement_error_code!(E109, "duplicate field `{}` in {} `{}`" => { field_name, schema_type, schema_name }, "rename the field or remove the duplicate" => {});
 implement_error_code!(E110, "`{}` is a reserved type name and cannot be used as a {} name" => { name, schema_type }, "rename the {} to something else" => { schema_type });
+implement_error_code!(E111, "invalid computed field `{}`: {}" => { field_name, reason }, "{}" => { fix });
+implement_error_code!(E112, "invalid view `{}`: {}" => { view_name, reason }, "{}" => { fix });
+implement_error_code!(E113, "invalid trigger `{}`: {}" => { trigger_name, reason }, "{}" => { fix });
+implement_error_code!(E114, "invalid edge constraint on `{}`: {}" => { edge_name, reason }, "{}" => { fix });
 
 // Type errors
 implement_error_code!(E201, "item type not in schema `{}`" => { item_type }, "check the schema field names" => {});
@@ -331,6 +411,8 @@ implement_error_code!(E207, "edge type `{}` exists but it is not a valid edge ty
 implement_error_code!(E208, "field `{}` has not been indexed for node type `{}`" => { field_name, node_type }, "use a field that has been indexed with `INDEX` in the schema for node type `{}`" => { node_type });
 implement_error_code!(E209, "unknown type `{}` for parameter `{}`" => { parameter_type, parameter_name }, "declare or use a matching schema object or use a primitive type" => {});
 implement_error_code!(E210, "identifier `{}` was expected to be of type ID, but got {}" => { identifier, value_type_name }, "ensure the identifier is of type ID" => {});
+implement_error_code!(E211, "field `{}` of {} type `{}` is computed and cannot be written" => { field_name, item_type, item_type_name }, "remove the field; it is kept up to date from the graph" => {});
+implement_error_code!(E212, "`@{}` can't be used on parameter `{}`: {}" => { check, param_name, reason }, "use `@range` on numbers, `@length` on strings or lists and `@one_of` on strings" => {});
 
 // Query errors
 implement_error_code!(E301, "variable `{}` not in scope" => { variable }, "check the variable" => {});
@@ -338,6 +420,7 @@ implement_error_code!(E302, "variable `{}` previously declared" => { variable },
 implement_error_code!(E304, "missing {} type" => { item_type }, "add an {} type" => { item_type });
 implement_error_code!(E305, "missing parameter `{}` for method `{}`" => { parameter_name, method_name }, "add the parameter `{}`" => { parameter_name });
 implement_error_code!(E306, "expression should result in a boolean, instead got `{}`" => { expression_type }, "ensure the expression is a boolean" => {});
+implement_error_code!(E307, "worst case of about {} items exceeds the complexity budget of {}: {}" => { items, budget, factors }, "bound the traversal with `RANGE`, start it from ids or an index, or raise `complexity_budget` in helix.toml" => {});
 
 // MCP errors
 implement_error_code!(E401, "MCP query must return a single value, but got `{}`" => { number_of_values }, "return a single value" => {});
@@ -364,11 +447,20 @@ implement_error_code!(E625, "edge type `{}` does not have a vector type as its `
 implement_error_code!(E626, "edge type `{}` does not have a vector type as its `To` source" => { edge_type }, "set the `To` type of the edge to a vector type" => {});
 implement_error_code!(E627, "`{}` requires either a `from` or `to` parameter" => { step_name }, "add a `from` or `to` parameter to the step" => {});
 implement_error_code!(E628, "`DROP` can only be applied to traversals, but got `{}`" => { expression_type }, "ensure the expression is a traversal" => {});
+implement_error_code!(E629, "invalid `MERGE_NODES`: {}" => { reason }, "{}" => { fix });
+implement_error_code!(E630, "`::PATHS` can't follow `{}`" => { step }, "start from nodes and only step through `Out<Edge>` and `In<Edge>` before `::PATHS`" => {});
 
 // Range errors
 implement_error_code!(E631, "range must have a start and end, missing the `{}` value" => { start_or_end }, "add a `{}` value to the range" => { start_or_end });
 implement_error_code!(E632, "range start must be less than range end, got `{}` which is larger than `{}`" => { start, end }, "change the range start to be less than the range end" => {});
 implement_error_code!(E633, "index of range must be an integer, got `{}` which is of type `{}`" => { index, index_type }, "change {} to be an integer" => { index_type });
+implement_error_code!(E634, "`{}` can't be applied to `{}`" => { step, item_type }, "count the edges of nodes or vectors" => {});
+implement_error_code!(E635, "`SAMPLE` can't take `{}`" => { size }, "pass a number of items like `SAMPLE(100)` or a fraction like `SAMPLE(0.01)`" => {});
+implement_error_code!(E636, "`BUCKET_BY` can't take `{}`: {}" => { arg, reason }, "bucket by a date property with a width like `1d` and a time zone like `\"Europe/Berlin\"`" => {});
+implement_error_code!(E637, "`{}` can't take `{}`: {}" => { step, arg, reason }, "take statistics of a numeric property of nodes, edges or vectors" => {});
+implement_error_code!(E638, "`DELETE` can't be applied to `{}`: {}" => { item_type, reason }, "delete nodes, edges or vectors selected like `N<Log>::WHERE(...)::DELETE`, or use `DROP`" => {});
+implement_error_code!(E639, "`{}` can't rerank `{}`: they have no relevance scores" => { step, items }, "rerank the results of `SearchV`, `SearchBM25` or `SearchHybrid`, before any step that replaces them" => {});
+implement_error_code!(E640, "`{}` can't be read from `{}`: they have no relevance scores" => { property, items }, "read it from the results of `SearchV`, `SearchBM25` or `SearchHybrid`, or of a reranker after them" => {});
 
 // Object remapping errors
 implement_error_code!(E641, "closure is only valid as the last step in a traversal" => {}, "move the closure to the end of the traversal" => {});
@@ -377,6 +469,8 @@ implement_error_code!(E643, "field `{}` previously excluded" => { field_name },
 implement_error_code!(E644, "`exclude` is only valid as the last step in a traversal, or as the step before an object remapping or closure" => {}, "move the `exclude` step to the end of the traversal or before the object remapping or closure" => {});
 implement_error_code!(E645, "object remapping must have at least one field" => {}, "add at least one field to the object remapping" => {});
 implement_error_code!(E646, "field value is empty" => {}, "field value must be a literal, identifier, traversal,or object" => {});
+implement_error_code!(E647, "`{}` is the relevance score of these `{}` and can't hold another value" => { key, items }, "give the field another name, or select the score as `{}`" => { key });
+implement_error_code!(E648, "`CALL {}` can only compute a field of a projection or a `ShortestPathDijkstras` weight" => { name }, "select it in a projection, like `::{{value: CALL {}(_::{{property}})}}`" => { name });
 
 // For loop errors
 implement_error_code!(E651, "`IN` variable `{}` is not iterable" => { in_variable }, "ensure the `in` variable is iterable" => {});
@@ -0,0 +1,529 @@
+//! Estimates how far each query can expand in the worst case and fails the ones over a
+//! complexity budget, catching accidental O(n²) queries before they're deployed.
+//!
+//! The estimate is the most items any step of a query handles. An unbounded scan like
+//! `N<User>` counts as [`SCAN_ITEMS`] items and an `Out`/`In` hop multiplies by [`FANOUT`],
+//! unless the edge's cardinality caps it. A traversal nested in a `WHERE`, closure, object
+//! remapping or `FOR` loop runs once per item it's nested in, so it multiplies by their number.
+//! Ids, `RANGE`, `FIRST`, `COUNT` and searches with a `k` bound the items again.
+
+use crate::helixc::{
+    analyzer::{Ctx, error_codes::ErrorCode, errors::push_query_err},
+    parser::{location::Loc, types::*},
+};
+use std::collections::HashMap;
+
+/// Budget `helix check` applies when helix.toml doesn't set `complexity_budget`. Allows a scan
+/// followed by two unbounded hops, but not a scan nested in another scan.
+pub const DEFAULT_COMPLEXITY_BUDGET: u64 = 1_000_000_000;
+
+/// Items assumed for an unbounded scan of a node, edge or vector type
+const SCAN_ITEMS: u64 = 100_000;
+
+/// Items assumed to be reached from each item by an unbounded hop
+const FANOUT: u64 = 100;
+
+/// Items assumed for bounds that are only known at runtime, like `RANGE(0, limit)` or the
+/// elements of an array parameter
+const BOUNDED_ITEMS: u64 = 100;
+
+/// A number of items, and the unbounded steps that multiplied up to it
+#[derive(Debug, Clone)]
+struct Items {
+    count: u64,
+    factors: Vec<String>,
+}
+
+impl Items {
+    fn one() -> Self {
+        Items {
+            count: 1,
+            factors: Vec::new(),
+        }
+    }
+
+    /// Each item expanding into `n`
+    fn times(&self, n: u64, factor: impl FnOnce() -> String) -> Self {
+        let mut factors = self.factors.clone();
+        if n > 1 {
+            factors.push(factor());
+        }
+        Items {
+            count: self.count.saturating_mul(n),
+            factors,
+        }
+    }
+
+    /// The items of `inner`, once per item of `self`
+    fn nest(&self, inner: &Items) -> Self {
+        Items {
+            count: self.count.saturating_mul(inner.count),
+            factors: self.factors.iter().chain(&inner.factors).cloned().collect(),
+        }
+    }
+
+    /// At most `n` items
+    fn bound(&self, n: u64, bound: &str) -> Self {
+        if n >= self.count {
+            return self.clone();
+        }
+        Items {
+            count: n,
+            factors: if n > 1 {
+                vec![bound.to_string()]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+struct Estimator<'a> {
+    edges: &'a HashMap<&'a str, &'a EdgeSchema>,
+    /// Items each variable holds, per run of the scope it was assigned in
+    variables: HashMap<String, Items>,
+    /// Most items any step handled, and where
+    peak: (Items, Option<Loc>),
+}
+
+impl Estimator<'_> {
+    fn record(&mut self, items: Items, loc: &Loc) {
+        if items.count > self.peak.0.count {
+            self.peak = (items, Some(loc.clone()));
+        }
+    }
+
+    /// Estimate statements run once per item of `outer`
+    fn statements(&mut self, statements: &[Statement], outer: &Items) {
+        for statement in statements {
+            match &statement.statement {
+                StatementType::Assignment(assignment) => {
+                    let items = self.expression(&assignment.value, outer);
+                    self.variables.insert(assignment.variable.clone(), items);
+                }
+                StatementType::Expression(expr) | StatementType::Drop(expr) => {
+                    self.expression(expr, outer);
+                }
+                StatementType::ForLoop(for_loop) => {
+                    let (_, in_variable) = &for_loop.in_variable;
+                    let iterations = self.variable(in_variable);
+                    match &for_loop.variable {
+                        ForLoopVars::Identifier { name, .. }
+                        | ForLoopVars::ObjectAccess { name, .. } => {
+                            self.variables.insert(name.clone(), Items::one());
+                        }
+                        ForLoopVars::ObjectDestructuring { fields, .. } => {
+                            for (_, name) in fields {
+                                self.variables.insert(name.clone(), Items::one());
+                            }
+                        }
+                    }
+                    let outer = outer.nest(&iterations);
+                    self.record(outer.clone(), &for_loop.loc);
+                    self.statements(&for_loop.statements, &outer);
+                }
+            }
+        }
+    }
+
+    fn variable(&self, name: &str) -> Items {
+        self.variables.get(name).cloned().unwrap_or_else(Items::one)
+    }
+
+    /// Estimate an expression run once per item of `outer`, answering the items it
+    /// evaluates to each time
+    fn expression(&mut self, expr: &Expression, outer: &Items) -> Items {
+        match &expr.expr {
+            ExpressionType::Traversal(traversal) => self.traversal(traversal, outer),
+            ExpressionType::Identifier(name) => self.variable(name),
+            ExpressionType::ArrayLiteral(items) => {
+                for item in items {
+                    self.expression(item, outer);
+                }
+                Items::one().times(items.len() as u64, || "array".to_string())
+            }
+            ExpressionType::Exists(exists) => {
+                self.expression(&exists.expr, outer);
+                Items::one()
+            }
+            ExpressionType::Not(expr) => {
+                self.expression(expr, outer);
+                Items::one()
+            }
+            ExpressionType::And(exprs) | ExpressionType::Or(exprs) => {
+                for expr in exprs {
+                    self.expression(expr, outer);
+                }
+                Items::one()
+            }
+            ExpressionType::SearchVector(search) => {
+                Items::one().times(bound_of(search.k.as_ref()), || "`SearchV`".to_string())
+            }
+            ExpressionType::SearchHybrid(search) => {
+                Items::one().times(bound_of(search.k.as_ref()), || "`SearchHybrid`".to_string())
+            }
+            ExpressionType::BM25Search(search) => {
+                Items::one().times(bound_of(search.k.as_ref()), || "`SearchBM25`".to_string())
+            }
+            ExpressionType::PPR(ppr) => {
+                Items::one().times(bound_of(ppr.limit.as_ref()), || "`PPR`".to_string())
+            }
+            _ => Items::one(),
+        }
+    }
+
+    /// Estimate a traversal run once per item of `outer`, answering the items it ends with
+    /// each time
+    fn traversal(&mut self, traversal: &Traversal, outer: &Items) -> Items {
+        let mut items = match &traversal.start {
+            StartNode::Node { node_type, ids } => self.start(ids, || format!("`N<{node_type}>`")),
+            StartNode::Edge { edge_type, ids } => self.start(ids, || format!("`E<{edge_type}>`")),
+            StartNode::Vector { vector_type, ids } => {
+                self.start(ids, || format!("`V<{vector_type}>`"))
+            }
+            StartNode::SearchVector(search) => {
+                Items::one().times(bound_of(search.k.as_ref()), || "`SearchV`".to_string())
+            }
+            StartNode::SearchHybrid(search) => {
+                Items::one().times(bound_of(search.k.as_ref()), || "`SearchHybrid`".to_string())
+            }
+            StartNode::PPR(ppr) => {
+                Items::one().times(bound_of(ppr.limit.as_ref()), || "`PPR`".to_string())
+            }
+            StartNode::Identifier(name) => self.variable(name),
+            StartNode::Anonymous => Items::one(),
+        };
+        self.record(outer.nest(&items), &traversal.loc);
+
+        for step in &traversal.steps {
+            items = match &step.step {
+                StepType::Node(graph_step) | StepType::Edge(graph_step) => {
+                    self.graph_step(graph_step, &items)
+                }
+                StepType::Where(expr) => {
+                    self.expression(expr, &outer.nest(&items));
+                    items
+                }
+                StepType::BooleanOperation(op) => {
+                    let per_item = outer.nest(&items);
+                    match &op.op {
+                        BooleanOpType::And(exprs) | BooleanOpType::Or(exprs) => {
+                            for expr in exprs {
+                                self.expression(expr, &per_item);
+                            }
+                        }
+                        BooleanOpType::GreaterThan(expr)
+                        | BooleanOpType::GreaterThanOrEqual(expr)
+                        | BooleanOpType::LessThan(expr)
+                        | BooleanOpType::LessThanOrEqual(expr)
+                        | BooleanOpType::Equal(expr)
+                        | BooleanOpType::NotEqual(expr)
+                        | BooleanOpType::Contains(expr)
+                        | BooleanOpType::IsIn(expr) => {
+                            self.expression(expr, &per_item);
+                        }
+                    }
+                    items
+                }
+                StepType::Object(object) => {
+                    self.fields(&object.fields, &outer.nest(&items));
+                    items
+                }
+                StepType::Closure(closure) => {
+                    self.variables
+                        .insert(closure.identifier.clone(), Items::one());
+                    self.fields(&closure.object.fields, &outer.nest(&items));
+                    items
+                }
+                StepType::Update(update) => {
+                    self.fields(&update.fields, &outer.nest(&items));
+                    items
+                }
+                StepType::OrderBy(order_by) => {
+                    self.expression(&order_by.expression, &outer.nest(&items));
+                    items
+                }
+                StepType::Range((start, end)) => {
+                    let n = match (literal(start), literal(end)) {
+                        (Some(start), Some(end)) => end.saturating_sub(start),
+                        _ => BOUNDED_ITEMS,
+                    };
+                    items.bound(n, "`RANGE`")
+                }
+                StepType::Sample(sample) => match number(&sample.size) {
+                    Some(Number::Int(n)) => items.bound(n, "`SAMPLE`"),
+                    _ => items,
+                },
+                StepType::First | StepType::Count | StepType::Stat(_) | StepType::Upsert(_) => {
+                    items.bound(1, "")
+                }
+                _ => items,
+            };
+            self.record(outer.nest(&items), &step.loc);
+        }
+        items
+    }
+
+    /// Items a scan starts with: one per id, or all of the type without ids
+    fn start(&self, ids: &Option<Vec<IdType>>, label: impl FnOnce() -> String) -> Items {
+        let Some(ids) = ids else {
+            return Items::one().times(SCAN_ITEMS, || format!("{} scan", label()));
+        };
+        let per_id = ids
+            .iter()
+            .map(|id| match id {
+                IdType::Identifier { value, .. } => self.variable(value),
+                _ => Items::one(),
+            })
+            .collect::<Vec<_>>();
+        let count = per_id
+            .iter()
+            .fold(0u64, |count, items| count.saturating_add(items.count));
+        let factors = per_id
+            .into_iter()
+            .max_by_key(|items| items.count)
+            .map_or_else(Vec::new, |items| items.factors);
+        Items { count, factors }
+    }
+
+    fn graph_step(&mut self, graph_step: &GraphStep, items: &Items) -> Items {
+        let (edge, outgoing) = match &graph_step.step {
+            GraphStepType::Out(edge) | GraphStepType::OutE(edge) => (edge, true),
+            GraphStepType::In(edge) | GraphStepType::InE(edge) => (edge, false),
+            GraphStepType::Ego(_) => return items.times(FANOUT, || "`EGO`".to_string()),
+            GraphStepType::Subgraph(_) => return items.times(FANOUT, || "`SUBGRAPH`".to_string()),
+            GraphStepType::SearchVector(search) => {
+                return items.times(bound_of(search.k.as_ref()), || "`SearchV`".to_string());
+            }
+            _ => return items.clone(),
+        };
+        let cardinality = self
+            .edges
+            .get(edge.as_str())
+            .and_then(|schema| match outgoing {
+                true => schema.from_cardinality.as_ref(),
+                false => schema.to_cardinality.as_ref(),
+            });
+        let fanout = cardinality
+            .and_then(|cardinality| cardinality.max)
+            .map_or(FANOUT, u64::from);
+        let direction = if outgoing { "Out" } else { "In" };
+        items.times(fanout, || format!("`{direction}<{edge}>`"))
+    }
+
+    fn return_value(&mut self, return_value: &ReturnType) {
+        match return_value {
+            ReturnType::Expression(expr) => {
+                self.expression(expr, &Items::one());
+            }
+            ReturnType::Array(values) => values.iter().for_each(|v| self.return_value(v)),
+            ReturnType::Object(values) => values.values().for_each(|v| self.return_value(v)),
+            ReturnType::Empty => {}
+        }
+    }
+
+    /// Estimate the traversals of object fields, run once per item of `outer`
+    fn fields(&mut self, fields: &[FieldAddition], outer: &Items) {
+        for field in fields {
+            match &field.value.value {
+                FieldValueType::Traversal(traversal) => {
+                    self.traversal(traversal, outer);
+                }
+                FieldValueType::Expression(expr) => {
+                    self.expression(expr, outer);
+                }
+                FieldValueType::Fields(fields) => self.fields(fields, outer),
+                _ => {}
+            }
+        }
+    }
+}
+
+enum Number {
+    Int(u64),
+    Float,
+}
+
+fn number(value: &EvaluatesToNumber) -> Option<Number> {
+    let n = match value.value {
+        EvaluatesToNumberType::I8(n) => n as i128,
+        EvaluatesToNumberType::I16(n) => n as i128,
+        EvaluatesToNumberType::I32(n) => n as i128,
+        EvaluatesToNumberType::I64(n) => n as i128,
+        EvaluatesToNumberType::U8(n) => n as i128,
+        EvaluatesToNumberType::U16(n) => n as i128,
+        EvaluatesToNumberType::U32(n) => n as i128,
+        EvaluatesToNumberType::U64(n) => n as i128,
+        EvaluatesToNumberType::U128(n) => n.min(u64::MAX as u128) as i128,
+        EvaluatesToNumberType::F32(_) | EvaluatesToNumberType::F64(_) => {
+            return Some(Number::Float);
+        }
+        EvaluatesToNumberType::Identifier(_) => return None,
+    };
+    Some(Number::Int(n.clamp(0, u64::MAX as i128) as u64))
+}
+
+/// Items a `k` or limit bounds a search to
+fn bound_of(value: Option<&EvaluatesToNumber>) -> u64 {
+    match value.and_then(number) {
+        Some(Number::Int(n)) => n,
+        _ => BOUNDED_ITEMS,
+    }
+}
+
+fn literal(expr: &Expression) -> Option<u64> {
+    match expr.expr {
+        ExpressionType::IntegerLiteral(n) => Some(n.max(0) as u64),
+        _ => None,
+    }
+}
+
+/// The most items any step of `query` handles in the worst case, the unbounded steps that
+/// multiplied up to them and where
+pub(crate) fn estimate_query(
+    edges: &HashMap<&str, &EdgeSchema>,
+    query: &Query,
+) -> (u64, Vec<String>, Loc) {
+    let mut estimator = Estimator {
+        edges,
+        variables: HashMap::new(),
+        peak: (Items::one(), None),
+    };
+    for parameter in &query.parameters {
+        if let FieldType::Array(_) = parameter.param_type.1 {
+            let name = &parameter.name.1;
+            let items = Items::one().times(BOUNDED_ITEMS, || format!("`{name}`"));
+            estimator.variables.insert(name.clone(), items);
+        }
+    }
+    estimator.statements(&query.statements, &Items::one());
+    for return_value in &query.return_values {
+        estimator.return_value(return_value);
+    }
+    let (items, loc) = estimator.peak;
+    (
+        items.count,
+        items.factors,
+        loc.unwrap_or_else(|| query.loc.clone()),
+    )
+}
+
+/// Fail the queries whose worst-case expansion exceeds `budget`
+pub(crate) fn check_query_complexity(ctx: &mut Ctx, budget: u64) {
+    for query in &ctx.src.queries {
+        let (items, factors, loc) = estimate_query(&ctx.edge_map, query);
+        if items <= budget {
+            continue;
+        }
+        let factors = match factors.is_empty() {
+            true => "unbounded items".to_string(),
+            false => factors.join(" × "),
+        };
+        push_query_err(
+            ctx,
+            query,
+            loc,
+            ErrorCode::E307,
+            ErrorCode::E307_message(&items.to_string(), &budget.to_string(), &factors),
+            ErrorCode::E307_hint(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helixc::{
+        analyzer::{
+            DEFAULT_COMPLEXITY_BUDGET, analyze_with_complexity_budget, diagnostic::Diagnostic,
+            error_codes::ErrorCode,
+        },
+        parser::{HelixParser, write_to_temp_file},
+    };
+
+    const SCHEMA: &str = r#"
+        N::User { name: String }
+        N::Post { title: String }
+        E::Follows { From: User, To: User }
+        E::Wrote { From: Post [1], To: User }
+    "#;
+
+    fn check(source: &str) -> Vec<Diagnostic> {
+        let content = write_to_temp_file(vec![SCHEMA, source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, _) =
+            analyze_with_complexity_budget(&parsed, Some(DEFAULT_COMPLEXITY_BUDGET)).unwrap();
+        diagnostics
+    }
+
+    fn over_budget(diagnostics: &[Diagnostic]) -> bool {
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.error_code == ErrorCode::E307)
+    }
+
+    #[test]
+    fn test_scan_nested_in_closure_over_scan_exceeds_budget() {
+        let diagnostics = check(
+            r#"
+            QUERY everyone() =>
+                users <- N<User>::{
+                    name,
+                    others: N<User>::WHERE(_::{name}::EQ("x"))
+                }
+                RETURN users
+        "#,
+        );
+
+        assert!(over_budget(&diagnostics), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn test_range_bounds_outer_scan() {
+        let diagnostics = check(
+            r#"
+            QUERY some() =>
+                users <- N<User>::RANGE(0, 10)::{
+                    name,
+                    others: N<User>::WHERE(_::{name}::EQ("x"))
+                }
+                RETURN users
+        "#,
+        );
+
+        assert!(!over_budget(&diagnostics), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn test_hops_from_id_stay_within_budget() {
+        let diagnostics = check(
+            r#"
+            QUERY friends(id: ID) =>
+                friends <- N<User>(id)::Out<Follows>::Out<Follows>::Out<Follows>
+                RETURN friends
+        "#,
+        );
+
+        assert!(!over_budget(&diagnostics), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn test_edge_cardinality_bounds_hop() {
+        let capped = check(
+            r#"
+            QUERY authors() =>
+                authors <- N<Post>::Out<Wrote>::Out<Follows>
+                RETURN authors
+        "#,
+        );
+        let unbounded = check(
+            r#"
+            QUERY followers() =>
+                followers <- N<User>::Out<Follows>::Out<Follows>::Out<Follows>
+                RETURN followers
+        "#,
+        );
+
+        assert!(!over_budget(&capped), "{capped:?}");
+        assert!(over_budget(&unbounded), "{unbounded:?}");
+    }
+}
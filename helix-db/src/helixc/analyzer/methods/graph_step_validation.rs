@@ -640,6 +640,67 @@ pub(crate) fn apply_graph_step<'a>(
             traversal.should_collect = ShouldCollect::ToVec;
             Some(Type::Unknown)
         }
+        (Subgraph(sg), Type::Nodes(_) | Type::Node(_)) => {
+            let depth = match &sg.depth.value {
+                EvaluatesToNumberType::Identifier(i) => {
+                    is_valid_identifier(ctx, original_query, sg.loc.clone(), i.as_str());
+                    type_in_scope(ctx, original_query, sg.loc.clone(), scope, i.as_str());
+                    gen_identifier_or_param(original_query, i, false, false)
+                }
+                EvaluatesToNumberType::I32(i) => {
+                    GeneratedValue::Primitive(GenRef::Std(i.to_string()))
+                }
+                _ => GeneratedValue::Unknown,
+            };
+            traversal
+                .steps
+                .push(Separator::Period(GeneratedStep::Subgraph(depth)));
+            traversal.should_collect = ShouldCollect::ToObj;
+            Some(Type::Subgraph)
+        }
+        (Ego(ego), Type::Nodes(Some(node_label)) | Type::Node(Some(node_label))) => {
+            let edge = match ctx.edge_map.get(ego.label.as_str()) {
+                Some(edge) => edge,
+                None => {
+                    generate_error!(ctx, original_query, gs.loc.clone(), E102, ego.label.as_str());
+                    return None;
+                }
+            };
+            if edge.from.1 != *node_label {
+                generate_error!(
+                    ctx,
+                    original_query,
+                    gs.loc.clone(),
+                    E207,
+                    ego.label.as_str(),
+                    "node",
+                    node_label.as_str()
+                );
+                return None;
+            }
+            if !ctx.node_set.contains(edge.to.1.as_str()) {
+                generate_error!(ctx, original_query, gs.loc.clone(), E102, ego.label.as_str());
+                return None;
+            }
+            let to = edge.to.1.clone();
+            let depth = match &ego.depth.value {
+                EvaluatesToNumberType::Identifier(i) => {
+                    is_valid_identifier(ctx, original_query, ego.loc.clone(), i.as_str());
+                    type_in_scope(ctx, original_query, ego.loc.clone(), scope, i.as_str());
+                    gen_identifier_or_param(original_query, i, false, false)
+                }
+                EvaluatesToNumberType::I32(i) => {
+                    GeneratedValue::Primitive(GenRef::Std(i.to_string()))
+                }
+                _ => GeneratedValue::Unknown,
+            };
+            traversal.steps.push(Separator::Period(GeneratedStep::Ego(
+                GenRef::Literal(ego.label.clone()),
+                depth,
+            )));
+            traversal.should_collect = ShouldCollect::ToVec;
+            Some(Type::Nodes(Some(to)))
+        }
         (SearchVector(sv), Type::Vectors(Some(vector_ty)) | Type::Vector(Some(vector_ty))) => {
             if !(matches!(cur_ty, Type::Vector(_)) || matches!(cur_ty, Type::Vectors(_))) {
                 generate_error!(
@@ -674,14 +735,21 @@ pub(crate) fn apply_graph_step<'a>(
                     VecData::Standard(value)
                 }
                 Some(VectorData::Embed(e)) => {
+                    let model_name = ctx.embedding_model(
+                        e,
+                        &gen_query.embedding_model_to_use,
+                        sv.vector_type.as_deref(),
+                    );
                     let embed_data = match &e.value {
                         EvaluatesToString::Identifier(i) => EmbedData {
                             data: gen_identifier_or_param(original_query, i.as_str(), true, false),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name: model_name.clone(),
+                            reuse_stored: None,
                         },
                         EvaluatesToString::StringLiteral(s) => EmbedData {
                             data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name,
+                            reuse_stored: None,
                         },
                     };
                     let name = gen_query.add_hoisted_embed(embed_data);
@@ -1054,4 +1122,100 @@ mod tests {
         let (diagnostics, _) = result.unwrap();
         assert!(diagnostics.is_empty());
     }
+
+    #[test]
+    fn test_subgraph_around_nodes() {
+        let source = r#"
+            N::Person { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY test(id: ID, depth: I64) =>
+                around <- N<Person>(id)::SUBGRAPH(depth: 2)
+                wider <- N<Person>::SUBGRAPH(depth: depth)
+                RETURN around, wider
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains(".subgraph(2)"), "{output}");
+        assert!(output.contains(".subgraph(data.depth.clone())"), "{output}");
+        assert!(output.contains("\"around\": around"), "{output}");
+    }
+
+    #[test]
+    fn test_ego_network_along_an_edge_type() {
+        let source = r#"
+            N::Person { name: String }
+            N::Post { title: String }
+            E::Knows { From: Person, To: Person }
+            E::Wrote { From: Person, To: Post }
+
+            QUERY test(id: ID, depth: I64) =>
+                friends <- N<Person>(id)::EGO<Knows>(depth: 2)
+                posts <- N<Person>::EGO<Wrote>(depth: depth)::{title}
+                RETURN friends, posts
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains(".ego(\"Knows\", 2)"), "{output}");
+        assert!(output.contains(".ego(\"Wrote\", data.depth.clone())"), "{output}");
+    }
+
+    #[test]
+    fn test_ego_needs_an_edge_from_the_current_node() {
+        let source = r#"
+            N::Person { name: String }
+            N::Post { title: String }
+            E::Wrote { From: Person, To: Post }
+
+            QUERY test(id: ID) =>
+                posts <- N<Post>(id)::EGO<Wrote>(depth: 2)
+                missing <- N<Person>(id)::EGO<Likes>(depth: 2)
+                RETURN posts, missing
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E207));
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E102));
+    }
+
+    #[test]
+    fn test_dijkstra_weight_calls_udf() {
+        let source = r#"
+            N::City { name: String }
+            E::Road { From: City, To: City, Properties: { distance: F64 } }
+
+            QUERY test(from: ID, to: ID) =>
+                path <- N<City>(from)::ShortestPathDijkstras<Road>(CALL toll(_::{distance}, 2))::To(to)
+                RETURN path
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains("db.udfs.eval(\"toll\""), "{output}");
+    }
 }
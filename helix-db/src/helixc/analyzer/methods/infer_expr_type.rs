@@ -1,7 +1,7 @@
 //! Semantic analyzer for Helix‑QL.
 use crate::helixc::analyzer::error_codes::ErrorCode;
 use crate::helixc::analyzer::utils::{
-    DEFAULT_VAR_NAME, VariableInfo, is_in_scope, is_param, validate_id_type,
+    DEFAULT_VAR_NAME, VariableInfo, is_computed_field, is_in_scope, is_param, validate_id_type,
 };
 use crate::helixc::generator::utils::EmbedData;
 use crate::{
@@ -20,9 +20,9 @@ use crate::{
             bool_ops::BoExp,
             queries::Query as GeneratedQuery,
             source_steps::{
-                AddE, AddN, AddV, PPR as GeneratedPPR, SearchBM25,
-                SearchHybrid as GeneratedSearchHybrid, SearchVector as GeneratedSearchVector,
-                SourceStep,
+                AddE, AddN, AddV, MergeNodes as GeneratedMergeNodes, PPR as GeneratedPPR,
+                SearchBM25, SearchHybrid as GeneratedSearchHybrid,
+                SearchVector as GeneratedSearchVector, SourceStep,
             },
             statements::Statement as GeneratedStatement,
             traversal_steps::{
@@ -198,6 +198,20 @@ pub(crate) fn infer_expr_type<'a>(
                                         "node",
                                         ty.as_str()
                                     );
+                                } else if is_computed_field(
+                                    ctx,
+                                    &Type::Node(Some(ty.to_string())),
+                                    field_name,
+                                ) {
+                                    generate_error!(
+                                        ctx,
+                                        original_query,
+                                        add.loc.clone(),
+                                        E211,
+                                        field_name.as_str(),
+                                        "node",
+                                        ty.as_str()
+                                    );
                                 }
                                 match field_value {
                                     ValueType::Identifier { value, loc } => {
@@ -1063,6 +1077,11 @@ pub(crate) fn infer_expr_type<'a>(
                             VecData::Standard(id)
                         }
                         VectorData::Embed(e) => {
+                            let model_name = ctx.embedding_model(
+                                e,
+                                &gen_query.embedding_model_to_use,
+                                add.vector_type.as_deref(),
+                            );
                             let embed_data = match &e.value {
                                 EvaluatesToString::Identifier(i) => {
                                     type_in_scope(
@@ -1079,12 +1098,14 @@ pub(crate) fn infer_expr_type<'a>(
                                             true,
                                             false,
                                         ),
-                                        model_name: gen_query.embedding_model_to_use.clone(),
+                                        model_name: model_name.clone(),
+                                        reuse_stored: None,
                                     }
                                 }
                                 EvaluatesToString::StringLiteral(s) => EmbedData {
                                     data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                                    model_name: gen_query.embedding_model_to_use.clone(),
+                                    model_name,
+                                    reuse_stored: None,
                                 },
                             };
 
@@ -1176,6 +1197,11 @@ pub(crate) fn infer_expr_type<'a>(
                     ))
                 }
                 Some(VectorData::Embed(e)) => {
+                    let model_name = ctx.embedding_model(
+                        e,
+                        &gen_query.embedding_model_to_use,
+                        sv.vector_type.as_deref(),
+                    );
                     let embed_data = match &e.value {
                         EvaluatesToString::Identifier(i) => {
                             type_in_scope(ctx, original_query, sv.loc.clone(), scope, i.as_str());
@@ -1186,12 +1212,14 @@ pub(crate) fn infer_expr_type<'a>(
                                     true,
                                     false,
                                 ),
-                                model_name: gen_query.embedding_model_to_use.clone(),
+                                model_name: model_name.clone(),
+                                reuse_stored: None,
                             }
                         }
                         EvaluatesToString::StringLiteral(s) => EmbedData {
                             data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name,
+                            reuse_stored: None,
                         },
                     };
 
@@ -1366,6 +1394,11 @@ pub(crate) fn infer_expr_type<'a>(
                     ))
                 }
                 Some(VectorData::Embed(e)) => {
+                    let model_name = ctx.embedding_model(
+                        e,
+                        &gen_query.embedding_model_to_use,
+                        sh.vector_type.as_deref(),
+                    );
                     let embed_data = match &e.value {
                         EvaluatesToString::Identifier(i) => {
                             type_in_scope(ctx, original_query, sh.loc.clone(), scope, i.as_str());
@@ -1376,12 +1409,14 @@ pub(crate) fn infer_expr_type<'a>(
                                     true,
                                     false,
                                 ),
-                                model_name: gen_query.embedding_model_to_use.clone(),
+                                model_name: model_name.clone(),
+                                reuse_stored: None,
                             }
                         }
                         EvaluatesToString::StringLiteral(s) => EmbedData {
                             data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name,
+                            reuse_stored: None,
                         },
                     };
                     VecData::Hoisted(gen_query.add_hoisted_embed(embed_data))
@@ -1888,7 +1923,79 @@ pub(crate) fn infer_expr_type<'a>(
                 None, // Will be handled by generator
             )
         }
+        UdfCall(udf_call) => {
+            // UDF calls are only generated as projection fields and weights, which don't come
+            // through here
+            generate_error!(
+                ctx,
+                original_query,
+                udf_call.loc.clone(),
+                E648,
+                [udf_call.name.as_str()],
+                [udf_call.name.as_str()]
+            );
+            (Type::Unknown, None)
+        }
         Empty => (Type::Unknown, Some(GeneratedStatement::Empty)),
+        MergeNodes(merge) => {
+            let mut labels = Vec::new();
+            for name in [merge.keep.as_str(), merge.remove.as_str()] {
+                is_valid_identifier(ctx, original_query, merge.loc.clone(), name);
+                if is_param(original_query, name).is_some() {
+                    validate_id_type(ctx, original_query, merge.loc.clone(), scope, name);
+                    continue;
+                }
+                match scope.get(name) {
+                    None => {
+                        generate_error!(ctx, original_query, merge.loc.clone(), E301, name);
+                    }
+                    Some(var_info) => match &var_info.ty {
+                        Type::Node(label) if var_info.is_single => labels.extend(label.clone()),
+                        Type::Scalar(FieldType::Uuid) => {}
+                        ty => {
+                            generate_error!(
+                                ctx,
+                                original_query,
+                                merge.loc.clone(),
+                                E629,
+                                [&format!("`{name}` is {}, not a single node", ty.kind_str())],
+                                ["pass a node, or the ID of one, as each argument"]
+                            );
+                        }
+                    },
+                }
+            }
+            if let [keep, remove] = labels.as_slice()
+                && keep != remove
+            {
+                generate_error!(
+                    ctx,
+                    original_query,
+                    merge.loc.clone(),
+                    E629,
+                    [&format!(
+                        "can't merge a `{remove}` node into a `{keep}` node"
+                    )],
+                    ["merge nodes of the same type"]
+                );
+            }
+
+            gen_query.is_mut = true;
+            (
+                Type::Node(labels.into_iter().next()),
+                Some(GeneratedStatement::Traversal(GeneratedTraversal {
+                    traversal_type: TraversalType::Mut,
+                    steps: vec![],
+                    should_collect: ShouldCollect::ToObj,
+                    source_step: Separator::Period(SourceStep::MergeNodes(GeneratedMergeNodes {
+                        keep: gen_id_access_or_param(original_query, &merge.keep),
+                        remove: gen_id_access_or_param(original_query, &merge.remove),
+                        conflict: merge.conflict,
+                    })),
+                    ..Default::default()
+                })),
+            )
+        }
         BM25Search(bm25_search) => {
             if let Some(ref ty) = bm25_search.type_arg
                 && !ctx.node_set.contains(ty.as_str())
@@ -2367,4 +2474,79 @@ mod tests {
         let (diagnostics, _) = result.unwrap();
         assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E202));
     }
+
+    // ============================================================================
+    // MergeNodes Expression Tests
+    // ============================================================================
+
+    #[test]
+    fn test_merge_nodes_valid() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test(id1: ID, id2: ID) =>
+                person <- N<Person>(id1)
+                merged <- MERGE_NODES(person, id2, conflict: OVERWRITE)
+                RETURN merged
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty());
+        let output = generated.queries[0].to_string();
+        assert!(output.contains("merge_nodes(person.id(), *data.id2, ConflictPolicy::Overwrite)"));
+    }
+
+    #[test]
+    fn test_merge_nodes_of_different_types() {
+        let source = r#"
+            N::Person { name: String }
+            N::Company { name: String }
+
+            QUERY test(id1: ID, id2: ID) =>
+                person <- N<Person>(id1)
+                company <- N<Company>(id2)
+                MERGE_NODES(person, company)
+                people <- N<Person>
+                MERGE_NODES(person, people)
+                RETURN person
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E629)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_udf_call_outside_a_projection() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test() =>
+                boosted <- CALL boost(1, 2)
+                RETURN boosted
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E648));
+    }
 }
@@ -1,3 +1,4 @@
+pub(super) mod complexity_validation;
 pub(super) mod exclude_validation;
 pub(super) mod graph_step_validation;
 pub(super) mod infer_expr_type;
@@ -7,3 +8,5 @@ pub(super) mod query_validation;
 pub(super) mod schema_methods;
 pub(super) mod statement_validation;
 pub(super) mod traversal_validation;
+pub(super) mod trigger_validation;
+pub(super) mod view_validation;
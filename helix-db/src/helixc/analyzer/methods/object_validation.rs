@@ -5,6 +5,7 @@ use crate::helixc::analyzer::{
 };
 use crate::helixc::generator::source_steps::SourceStep;
 use crate::helixc::parser::errors::ParserError;
+use crate::helixc::parser::location::Loc;
 use crate::{
     generate_error,
     helixc::{
@@ -12,8 +13,8 @@ use crate::{
             Ctx,
             types::Type,
             utils::{
-                gen_property_access, is_valid_identifier,
-                validate_field_name_existence_for_item_type,
+                VariableInfo, gen_property_access, is_score_property, is_valid_identifier,
+                validate_field_name_existence_for_item_type, yields_scored_items,
             },
         },
         generator::{
@@ -208,6 +209,60 @@ fn extract_fields_from_object<'a>(
     data_field_accessed
 }
 
+/// Checks a relevance score is read from search results, the only items that have one.
+/// Anonymous traversals are checked where the traversal they filter or order is validated.
+fn check_scored_property<'a>(
+    ctx: &mut Ctx<'a>,
+    original_query: &'a Query,
+    loc: &Loc,
+    cur_ty: &Type,
+    property: &str,
+    gen_traversal: &GeneratedTraversal,
+    scope: &std::collections::HashMap<&'a str, VariableInfo>,
+) {
+    if is_score_property(ctx, cur_ty, property)
+        && !matches!(gen_traversal.source_step.inner(), SourceStep::Anonymous)
+        && !yields_scored_items(gen_traversal, scope)
+    {
+        generate_error!(
+            ctx,
+            original_query,
+            loc.clone(),
+            E640,
+            property,
+            &cur_ty.get_type_name()
+        );
+    }
+}
+
+/// Checks a key named after a relevance score holds that score on search results, whose
+/// returned items already carry it under that name
+fn check_score_key<'a>(
+    ctx: &mut Ctx<'a>,
+    original_query: &'a Query,
+    field: &FieldAddition,
+    cur_ty: &Type,
+    gen_traversal: &GeneratedTraversal,
+    scope: &std::collections::HashMap<&'a str, VariableInfo>,
+) {
+    let selects_score =
+        matches!(&field.value.value, FieldValueType::Identifier(id) if *id == field.key);
+    if !selects_score
+        && is_score_property(ctx, cur_ty, &field.key)
+        && !matches!(gen_traversal.source_step.inner(), SourceStep::Anonymous)
+        && yields_scored_items(gen_traversal, scope)
+    {
+        generate_error!(
+            ctx,
+            original_query,
+            field.loc.clone(),
+            E647,
+            [&field.key, &cur_ty.get_type_name()],
+            [&field.key]
+        );
+    }
+}
+
 /// Validates the property access
 ///
 /// # Arguments
@@ -252,6 +307,15 @@ fn validate_property_access<'a>(
                             cur_ty,
                             lit.as_str(),
                         );
+                        check_scored_property(
+                            ctx,
+                            original_query,
+                            &obj.fields[0].value.loc,
+                            cur_ty,
+                            lit.as_str(),
+                            gen_traversal,
+                            scope,
+                        );
                         // Check if we're accessing the 'data' field on a Vector type
                         // If so, we need to mark vector traversal steps to fetch the data
                         if lit.as_str() == "data"
@@ -262,7 +326,11 @@ fn validate_property_access<'a>(
 
                         gen_traversal
                             .steps
-                            .push(Separator::Period(gen_property_access(lit.as_str())));
+                            .push(Separator::Period(gen_property_access(
+                                ctx,
+                                cur_ty,
+                                lit.as_str(),
+                            )));
 
                         // Store the field name so nested traversal code generation can access it
                         gen_traversal.object_fields.push(lit.as_str().to_string());
@@ -319,8 +387,25 @@ fn validate_property_access<'a>(
 
                 // Collect field names and nested traversals
                 for field_addition in &obj.fields {
+                    check_score_key(
+                        ctx,
+                        original_query,
+                        field_addition,
+                        cur_ty,
+                        gen_traversal,
+                        scope,
+                    );
                     match &field_addition.value.value {
                         FieldValueType::Identifier(id) => {
+                            check_scored_property(
+                                ctx,
+                                original_query,
+                                &field_addition.value.loc,
+                                cur_ty,
+                                id.as_str(),
+                                gen_traversal,
+                                scope,
+                            );
                             // Use the key (output field name), not the id (source property name)
                             gen_traversal.object_fields.push(field_addition.key.clone());
                             // Track the mapping from output name to source property name
@@ -408,8 +493,10 @@ fn validate_property_access<'a>(
                             use crate::helixc::generator::traversal_steps::{ComputedExpressionInfo, NestedTraversalInfo};
                             use crate::helixc::parser::types::ExpressionType;
 
-                            if let ExpressionType::MathFunctionCall(_) = &expr.expr {
-                                // Math function call - store as computed expression
+                            if let ExpressionType::MathFunctionCall(_)
+                            | ExpressionType::UdfCall(_) = &expr.expr
+                            {
+                                // Math or user-defined function call - store as computed expression
                                 gen_traversal.computed_expressions.insert(
                                     field_addition.key.clone(),
                                     ComputedExpressionInfo {
@@ -608,4 +695,29 @@ mod tests {
         let (diagnostics, _) = result.unwrap();
         assert!(diagnostics.is_empty());
     }
+
+    #[test]
+    fn test_computed_field_calls_udf() {
+        let source = r#"
+            N::Person { name: String, rating: F64 }
+
+            QUERY test() =>
+                people <- N<Person>
+                RETURN people::{name, score: CALL boost(_::{rating}, 2)}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty());
+        let output = generated.to_string();
+        assert!(output.contains("db.udfs.eval(\"boost\""), "{output}");
+        assert!(
+            output.contains(".collect::<Result<Vec<_>, GraphError>>()?"),
+            "{output}"
+        );
+    }
 }
@@ -8,12 +8,18 @@ use crate::helixc::{
     analyzer::{
         Ctx,
         errors::{push_query_err, push_query_warn},
-        methods::{infer_expr_type::infer_expr_type, statement_validation::validate_statements},
+        methods::{
+            infer_expr_type::infer_expr_type, statement_validation::validate_statements,
+            trigger_validation::added_items,
+        },
         types::Type,
-        utils::{VariableInfo, is_valid_identifier},
+        utils::{VariableInfo, is_score_property, is_valid_identifier},
     },
     generator::{
-        queries::{Parameter as GeneratedParameter, Query as GeneratedQuery},
+        queries::{
+            ParamRule as GeneratedParamRule, Parameter as GeneratedParameter,
+            Query as GeneratedQuery,
+        },
         return_values::{
             ReturnFieldInfo, ReturnFieldSource, ReturnFieldType, ReturnValue, ReturnValueStruct,
         },
@@ -129,19 +135,24 @@ fn build_return_fields(
         // Helper to find which output field name maps to a given property
         // e.g., for property "id", might return Some("file_id") if there's a mapping file_id -> ID
         let find_output_for_property = |property: &str| -> Option<String> {
-            // First check if any object_field maps to this property via field_name_mappings
-            for output_name in &traversal.object_fields {
-                if let Some(prop) = traversal.field_name_mappings.get(output_name)
-                    && prop.to_lowercase() == property.to_lowercase()
-                {
-                    return Some(output_name.clone());
-                }
-                // Also check if the output_name itself matches (identity mapping)
-                if output_name.to_lowercase() == property.to_lowercase() {
-                    return Some(output_name.clone());
-                }
-            }
-            None
+            traversal
+                .object_fields
+                .iter()
+                .find(|output_name| {
+                    // Keys holding a nested traversal or computed value don't select a property
+                    if traversal.nested_traversals.contains_key(*output_name)
+                        || traversal.computed_expressions.contains_key(*output_name)
+                    {
+                        return false;
+                    }
+                    // A remapped key selects the property it maps to, not the one it is named after
+                    let selected = traversal
+                        .field_name_mappings
+                        .get(*output_name)
+                        .unwrap_or(output_name);
+                    selected.to_lowercase() == property.to_lowercase()
+                })
+                .cloned()
         };
 
         // If has_object_step, only add implicit fields if they're explicitly selected OR has_spread
@@ -293,6 +304,62 @@ fn build_return_fields(
                     ));
                 }
             }
+            // Set by UpsertV, so only returned when selected
+            if let Some(output_name) = find_output_for_property("reembedded")
+                && !traversal
+                    .excluded_fields
+                    .contains(&"reembedded".to_string())
+            {
+                let rust_type = RustFieldType::Primitive(GenRef::Std(RustType::Bool));
+                if output_name != "reembedded" {
+                    fields.push(ReturnFieldInfo::new_implicit_with_property(
+                        output_name,
+                        "reembedded".to_string(),
+                        rust_type,
+                    ));
+                } else {
+                    fields.push(ReturnFieldInfo::new_implicit(
+                        "reembedded".to_string(),
+                        rust_type,
+                    ));
+                }
+            }
+        }
+
+        // Relevance scores of search results, only returned when selected. A vector's `score`
+        // is returned with its other implicit fields above
+        for property in [
+            "score",
+            "bm25_score",
+            "vector_distance",
+            "rerank_score",
+            "scores",
+        ] {
+            if (item_type == "vector" && property == "score")
+                || !is_score_property(ctx, inferred_type, property)
+                || traversal.excluded_fields.contains(&property.to_string())
+            {
+                continue;
+            }
+            if let Some(output_name) = find_output_for_property(property) {
+                let rust_type = if property == "score" {
+                    RustFieldType::Primitive(GenRef::Std(RustType::F64))
+                } else {
+                    RustFieldType::Value
+                };
+                if output_name != property {
+                    fields.push(ReturnFieldInfo::new_implicit_with_property(
+                        output_name,
+                        property.to_string(),
+                        rust_type,
+                    ));
+                } else {
+                    fields.push(ReturnFieldInfo::new_implicit(
+                        property.to_string(),
+                        rust_type,
+                    ));
+                }
+            }
         }
 
         // Step 2: Add schema fields based on projection mode
@@ -308,8 +375,8 @@ fn build_return_fields(
             let lower = prop.to_lowercase();
             matches!(
                 lower.as_str(),
-                "id" | "label" | "from_node" | "to_node" | "data" | "score"
-            )
+                "id" | "label" | "from_node" | "to_node" | "data" | "reembedded"
+            ) || is_score_property(ctx, inferred_type, &lower)
         };
 
         if let Some(schema_fields) = schema_fields {
@@ -478,6 +545,10 @@ fn build_return_fields(
                                     | "to_node"
                                     | "data"
                                     | "score"
+                                    | "bm25_score"
+                                    | "vector_distance"
+                                    | "rerank_score"
+                                    | "scores"
                                     | "ID"
                                     | "Label" // Also check capitalized versions
                             )
@@ -578,6 +649,9 @@ fn build_return_fields(
                                 Some("score") => {
                                     RustFieldType::Primitive(GenRef::Std(RustType::F64))
                                 }
+                                Some(
+                                    "bm25_score" | "vector_distance" | "rerank_score" | "scores",
+                                ) => RustFieldType::Value,
                                 Some("id") | Some("ID") | Some("label") | Some("Label")
                                 | Some("from_node") | Some("to_node") | None => {
                                     RustFieldType::Primitive(GenRef::RefLT("a", RustType::Str))
@@ -1154,15 +1228,71 @@ fn type_to_rust_string_and_fields(
     }
 }
 
+/// Checks that each `@range`, `@length` and `@one_of` on a parameter fits its type. On a list,
+/// the length is that of the list and the other checks apply to each of its items.
+fn validate_param_checks<'a>(ctx: &mut Ctx<'a>, original_query: &'a Query, param: &Parameter) {
+    let (item_type, is_list) = match &param.param_type.1 {
+        FieldType::Array(item_type) => (item_type.as_ref(), true),
+        field_type => (field_type, false),
+    };
+    for (loc, check) in &param.checks {
+        let reason = match check {
+            ParamCheck::Range(min, max) if min > max => {
+                Some(format!("the minimum {min} is above the maximum {max}"))
+            }
+            ParamCheck::Length(min, max) if min > max => {
+                Some(format!("the minimum {min} is above the maximum {max}"))
+            }
+            ParamCheck::Range(..) if !Type::Scalar(item_type.clone()).is_numeric() => {
+                Some(format!("`{}` isn't a number", param.param_type.1))
+            }
+            ParamCheck::Length(..) if !is_list && *item_type != FieldType::String => {
+                Some(format!("`{}` isn't a string or a list", param.param_type.1))
+            }
+            ParamCheck::OneOf(_) if *item_type != FieldType::String => {
+                Some(format!("`{}` isn't a string", param.param_type.1))
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            generate_error!(
+                ctx,
+                original_query,
+                loc.clone(),
+                E212,
+                check.name(),
+                &param.name.1,
+                &reason
+            );
+        }
+    }
+}
+
 pub(crate) fn validate_query<'a>(ctx: &mut Ctx<'a>, original_query: &'a Query) {
     let mut query = GeneratedQuery {
         name: original_query.name.clone(),
+        doc: original_query.doc.clone(),
         ..Default::default()
     };
 
-    if let Some(BuiltInMacro::Model(model_name)) = &original_query.built_in_macro {
-        // handle model macro
-        query.embedding_model_to_use = Some(model_name.clone());
+    for built_in_macro in &original_query.built_in_macros {
+        match built_in_macro {
+            // handle model macro
+            BuiltInMacro::Model(model_name) => {
+                query.embedding_model_to_use = Some(model_name.clone());
+            }
+            BuiltInMacro::Priority(priority) => query.priority = *priority,
+            BuiltInMacro::Cache { ttl_ms } => query.cache_ttl_ms = Some(*ttl_ms),
+            BuiltInMacro::Roles(roles) => {
+                for role in roles {
+                    if !query.roles.contains(role) {
+                        query.roles.push(role.clone());
+                    }
+                }
+            }
+            BuiltInMacro::MaxConcurrency(limit) => query.max_concurrency = Some(*limit),
+            BuiltInMacro::MCP => {}
+        }
     }
 
     // -------------------------------------------------
@@ -1184,6 +1314,17 @@ pub(crate) fn validate_query<'a>(ctx: &mut Ctx<'a>, original_query: &'a Query) {
                 &param.name.1
             );
         }
+        validate_param_checks(ctx, original_query, param);
+        query.param_rules.push(GeneratedParamRule {
+            name: param.name.1.clone(),
+            field_type: param.param_type.1.clone(),
+            is_optional: param.is_optional,
+            checks: param
+                .checks
+                .iter()
+                .map(|(_, check)| check.clone())
+                .collect(),
+        });
         // constructs parameters and sub‑parameters for generator
         GeneratedParameter::unwrap_param(
             &original_query.name,
@@ -1243,7 +1384,11 @@ pub(crate) fn validate_query<'a>(ctx: &mut Ctx<'a>, original_query: &'a Query) {
         analyze_return_expr(ctx, original_query, &mut scope, &mut query, ret);
     }
 
-    if let Some(BuiltInMacro::MCP) = &original_query.built_in_macro {
+    if original_query
+        .built_in_macros
+        .iter()
+        .any(|m| matches!(m, BuiltInMacro::MCP))
+    {
         if query.return_values.len() != 1 {
             generate_error!(
                 ctx,
@@ -1258,9 +1403,78 @@ pub(crate) fn validate_query<'a>(ctx: &mut Ctx<'a>, original_query: &'a Query) {
         }
     }
 
+    if query.is_mut && query.cache_ttl_ms.take().is_some() {
+        push_query_warn(
+            ctx,
+            original_query,
+            original_query.loc.clone(),
+            ErrorCode::W102,
+            "results of write queries are never cached".to_string(),
+            "remove `#[cache(...)]` from this query",
+            None,
+        );
+    }
+    query.labels = query_labels(ctx, original_query);
+
     ctx.output.queries.push(query);
 }
 
+/// Schema labels mentioned anywhere in the query, used to invalidate cached results. Views
+/// count as the labels they read, adds as the labels of the triggers they run, and edges also
+/// as the node types at their ends, whose writes change what a traversal over them reaches.
+///
+/// Returns `None` when the query may touch items of any type: untyped `N`, `E` or `V`
+/// sources, or `DROP`, which also removes connected edges.
+pub(crate) fn query_labels(ctx: &Ctx, original_query: &Query) -> Option<Vec<String>> {
+    let src = original_query.original_query.as_str();
+    let mut labels = std::collections::BTreeSet::new();
+    for (kind, item) in added_items(src) {
+        if let Some(trigger) = ctx.triggers.get(&(kind, item)) {
+            labels.extend(query_labels(ctx, &trigger.query)?);
+        }
+    }
+    let mut rest = src;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        let token_len = rest[start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + token_len];
+        rest = &rest[start + token_len..];
+
+        match token {
+            "N" | "E" | "V" if !rest.trim_start().starts_with('<') => return None,
+            "DROP" => return None,
+            // Upserts that create an item run its trigger, whichever type the upsert reads
+            "UpsertN" | "UpsertE" => {
+                let kind = match token {
+                    "UpsertN" => TriggerKind::AddN,
+                    _ => TriggerKind::AddE,
+                };
+                for ((trigger_kind, _), trigger) in &ctx.triggers {
+                    if *trigger_kind == kind {
+                        labels.extend(query_labels(ctx, &trigger.query)?);
+                    }
+                }
+            }
+            _ if ctx.node_set.contains(token) || ctx.vector_set.contains(token) => {
+                labels.insert(token.to_string());
+            }
+            _ if ctx.edge_map.contains_key(token) => {
+                let edge = ctx.edge_map[token];
+                labels.insert(token.to_string());
+                labels.insert(edge.from.1.clone());
+                labels.insert(edge.to.1.clone());
+            }
+            _ if ctx.views.contains_key(token) => match &ctx.views[token].labels {
+                Some(view_labels) => labels.extend(view_labels.iter().cloned()),
+                None => return None,
+            },
+            _ => {}
+        }
+    }
+    Some(labels.into_iter().collect())
+}
+
 fn analyze_return_expr<'a>(
     ctx: &mut Ctx<'a>,
     original_query: &'a Query,
@@ -1360,7 +1574,12 @@ fn analyze_return_expr<'a>(
                             // New unified approach
                             if matches!(
                                 inferred_type,
-                                Type::Boolean | Type::Scalar(_) | Type::Count
+                                Type::Boolean
+                                    | Type::Scalar(_)
+                                    | Type::Count
+                                    | Type::Subgraph
+                                    | Type::Paths
+                                    | Type::Buckets
                             ) {
                                 // Primitive types: emit variable directly, no struct needed
                                 let mut prim_struct = ReturnValueStruct::new(field_name.clone());
@@ -1440,7 +1659,12 @@ fn analyze_return_expr<'a>(
                             // New unified approach
                             if matches!(
                                 inferred_type,
-                                Type::Boolean | Type::Scalar(_) | Type::Count
+                                Type::Boolean
+                                    | Type::Scalar(_)
+                                    | Type::Count
+                                    | Type::Subgraph
+                                    | Type::Paths
+                                    | Type::Buckets
                             ) {
                                 let mut prim_struct = ReturnValueStruct::new(field_name.clone());
                                 prim_struct.source_variable = field_name.clone();
@@ -1535,7 +1759,12 @@ fn analyze_return_expr<'a>(
                     // New unified approach
                     if matches!(
                         identifier_end_type,
-                        Type::Boolean | Type::Scalar(_) | Type::Count
+                        Type::Boolean
+                            | Type::Scalar(_)
+                            | Type::Count
+                            | Type::Subgraph
+                            | Type::Paths
+                            | Type::Buckets
                     ) {
                         // Primitive types: emit variable directly, no struct needed
                         let mut prim_struct = ReturnValueStruct::new(field_name.clone());
@@ -1604,7 +1833,7 @@ fn analyze_return_expr<'a>(
 
                     // Note: Map closures are no longer injected here.
                     // Mapping will happen at response construction time instead.
-                }  // end GeneratedStatement::Identifier
+                } // end GeneratedStatement::Identifier
                 GeneratedStatement::Literal(l) => {
                     let field_name = "data".to_string();
                     let rust_type = "Value".to_string();
@@ -1692,6 +1921,7 @@ fn analyze_return_expr<'a>(
 mod tests {
     use super::*;
     use crate::helixc::parser::{HelixParser, write_to_temp_file};
+    use crate::protocol::request::Priority;
 
     // ============================================================================
     // Parameter Validation Tests
@@ -1741,6 +1971,78 @@ mod tests {
         assert!(!diagnostics.iter().any(|d| d.error_code == ErrorCode::E209));
     }
 
+    #[test]
+    fn test_parameter_checks_become_param_rules() {
+        let source = r#"
+            N::Person { name: String, age: U8 }
+
+            QUERY findPeople(name: String @length(1, 64), age?: U8 @range(18, 120), role: String @one_of("admin", "user"), tags: [String] @length(0, 5) @one_of("a", "b")) =>
+                p <- N<Person>::WHERE(_::{name}::EQ(name))
+                RETURN p
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+        let output = generated.queries[0].to_string();
+        assert!(output.contains("const FINDPEOPLE_PARAMS: &[ParamRule] = &["));
+        assert!(output.contains(r#"ParamRule::new("name", ParamKind::String).length(1, 64)"#));
+        assert!(
+            output
+                .contains(r#"ParamRule::new("age", ParamKind::U8).optional().range(18.0, 120.0)"#)
+        );
+        assert!(output.contains(r#".one_of(&["admin", "user"])"#));
+        assert!(output.contains(
+            r#"ParamRule::new("tags", ParamKind::Array(&ParamKind::String)).length(0, 5).one_of(&["a", "b"])"#
+        ));
+        assert!(output.contains(
+            "input.request.in_fmt.deserialize_params::<findPeopleInput>(&input.request.body, FINDPEOPLE_PARAMS)?"
+        ));
+    }
+
+    #[test]
+    fn test_parameter_checks_must_fit_the_parameter_type() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test(name: String @range(1, 2), age: U8 @one_of("old"), ids: [ID] @length(1, 3), score: F64 @length(1, 2), limit: I64 @range(10, 1)) =>
+                p <- N<Person>
+                RETURN p
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, _) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        let messages = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E212)
+            .map(|d| d.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages.len(), 4, "{messages:?}");
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("`@range`") && m.contains("`name`"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("`@one_of`") && m.contains("`age`"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("`@length`") && m.contains("`score`"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("the minimum 10 is above the maximum 1"))
+        );
+    }
+
     // ============================================================================
     // Variable Scope Tests
     // ============================================================================
@@ -1977,6 +2279,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_embed_model_argument_and_vector_default() {
+        let source = r#"
+            #[model("small")]
+            V::Title { text: String }
+            V::Body { text: String }
+
+            QUERY addDoc(title: String, body: String) =>
+                t <- AddV<Title>(Embed(title), {text: title})
+                b <- AddV<Body>(Embed(body, model: "large"), {text: body})
+                plain <- AddV<Body>(Embed(body), {text: body})
+                RETURN t
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+        let models: Vec<_> = generated.queries[0]
+            .hoisted_embedding_calls
+            .iter()
+            .map(|embed| embed.model_name.as_deref())
+            .collect();
+        assert_eq!(models, [Some("\"small\""), Some("\"large\""), None]);
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains("embed_async!(db, &data.body, \"large\")")
+        );
+    }
+
+    #[test]
+    fn test_model_macro_overrides_vector_default_but_not_embed_argument() {
+        let source = r#"
+            #[model("small")]
+            V::Title { text: String }
+
+            #[model("query")]
+            QUERY searchTitles(text: String) =>
+                macro_model <- SearchV<Title>(Embed(text), 5)
+                own_model <- SearchV<Title>(Embed(text, model: "large"), 5)
+                RETURN macro_model, own_model
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+        let models: Vec<_> = generated.queries[0]
+            .hoisted_embedding_calls
+            .iter()
+            .map(|embed| embed.model_name.as_deref())
+            .collect();
+        assert_eq!(models, [Some("\"query\""), Some("\"large\"")]);
+    }
+
+    #[test]
+    fn test_upsert_v_embed_reuses_stored_embedding() {
+        let source = r#"
+            V::Document { content: String }
+
+            QUERY upsertDoc(text: String) =>
+                existing <- V<Document>::WHERE(_::{content}::EQ(text))
+                doc <- existing::UpsertV(Embed(text), {content: text})
+                RETURN doc::{content, reembedded}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+        let output = generated.queries[0].to_string();
+        assert!(output.contains("db.stored_embedding(\"Document\", &data.text)"));
+        assert!(
+            output
+                .contains(".upsert_v_embedded(&__internal_embed_data_0, &data.text, \"Document\"")
+        );
+        assert!(output.contains("reembedded: doc.reembedded()"));
+    }
+
     // ============================================================================
     // Complex Query Tests
     // ============================================================================
@@ -2045,4 +2433,272 @@ mod tests {
         let (diagnostics, _) = result.unwrap();
         assert!(!diagnostics.iter().any(|d| d.error_code == ErrorCode::E301));
     }
+
+    #[test]
+    fn test_priority_macro_sets_batch_handler() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[priority(batch)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(generated.queries[0].priority, Priority::Batch);
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains("#[handler(priority = batch)]")
+        );
+    }
+
+    #[test]
+    fn test_cache_macro_emits_ttl_and_labels() {
+        let source = r#"
+            N::Person { name: String }
+            N::Company { name: String }
+
+            #[cache(ttl: 30s)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(generated.queries[0].cache_ttl_ms, Some(30_000));
+        assert_eq!(
+            generated.queries[0].labels,
+            Some(vec!["Person".to_string()])
+        );
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains(r#"#[handler(cache_ttl_ms = 30000, labels = ["Person"])]"#)
+        );
+    }
+
+    #[test]
+    fn test_write_query_emits_labels() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY addPerson(name: String) =>
+                person <- AddN<Person>({name: name})
+                RETURN person
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (_, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains(r#"#[handler(is_write, labels = ["Person"])]"#)
+        );
+    }
+
+    #[test]
+    fn test_edge_labels_include_endpoint_node_types() {
+        let source = r#"
+            N::Person { name: String }
+            N::Company { name: String }
+            E::WorksAt { From: Person, To: Company }
+
+            #[cache(ttl: 30s)]
+            QUERY employers(id: ID) =>
+                companies <- N<Person>(id)::Out<WorksAt>
+                RETURN companies
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (_, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        // Adding or updating a Company changes what the traversal reaches
+        assert_eq!(
+            generated.queries[0].labels,
+            Some(vec![
+                "Company".to_string(),
+                "Person".to_string(),
+                "WorksAt".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_drop_has_unknown_labels() {
+        let source = r#"
+            N::Person { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY removeFriends(id: ID) =>
+                person <- N<Person>(id)
+                DROP person::Out<Knows>
+                RETURN person
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (_, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        // Dropping items can remove connected edges of any label
+        assert_eq!(generated.queries[0].labels, None);
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains("#[handler(is_write)]")
+        );
+    }
+
+    #[test]
+    fn test_roles_macro_emits_roles() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[roles(admin)]
+            #[roles(support, admin)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(generated.queries[0].roles, vec!["admin", "support"]);
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains(r#"#[handler(roles = ["admin", "support"])]"#)
+        );
+    }
+
+    #[test]
+    fn test_max_concurrency_macro_emits_limit() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[max_concurrency(2)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(generated.queries[0].max_concurrency, Some(2));
+        assert!(
+            generated.queries[0]
+                .to_string()
+                .contains("#[handler(max_concurrency = 2)]")
+        );
+    }
+
+    #[test]
+    fn test_mcp_write_query_emits_write_tool() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[mcp]
+            #[roles(admin)]
+            QUERY addPerson(name: String) =>
+                person <- AddN<Person>({name: name})
+                RETURN person
+
+            #[mcp]
+            QUERY getPerson(id: ID) =>
+                person <- N<Person>(id)
+                RETURN person
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty());
+        // Queries aren't generated in source order
+        let output = generated
+            .queries
+            .iter()
+            .map(|query| query.to_string())
+            .collect::<String>();
+        assert!(
+            output.contains("#[mcp_handler(is_write, roles = [\"admin\"])]\npub fn addPersonMcp(")
+        );
+        assert!(output.contains("#[mcp_handler]\npub fn getPersonMcp("));
+    }
+
+    #[test]
+    fn test_rust_client_types_query_returns() {
+        let source = r#"
+            N::Person { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY getPerson(id: ID) =>
+                person <- N<Person>(id)
+                friends <- person::Out<Knows>
+                RETURN person, friends
+
+            QUERY countPeople() =>
+                count <- N<Person>::COUNT
+                RETURN count
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let output = generated.to_rust_client();
+        assert!(output.contains("pub struct getPersonInput {\n    pub id: String,\n}"));
+        assert!(output.contains(
+            "pub struct GetPersonFriendsReturnType {\n    pub id: String,\n    pub label: String,\n    pub name: Option<serde_json::Value>,\n}"
+        ));
+        assert!(output.contains(
+            "pub struct getPersonOutput {\n    pub person: GetPersonPersonReturnType,\n    pub friends: Vec<GetPersonFriendsReturnType>,\n}"
+        ));
+        assert!(
+            output.contains("pub struct countPeopleOutput {\n    pub count: serde_json::Value,\n}")
+        );
+        assert!(
+            output.contains(
+                "pub async fn countPeople(&self) -> Result<countPeopleOutput, ClientError>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_cache_macro_on_write_query_warns() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[cache(ttl: 30s)]
+            QUERY addPerson(name: String) =>
+                person <- AddN<Person>({name: name})
+                RETURN person
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::W102));
+        assert_eq!(generated.queries[0].cache_ttl_ms, None);
+    }
 }
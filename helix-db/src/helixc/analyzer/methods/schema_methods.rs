@@ -2,12 +2,15 @@ use std::{borrow::Cow, collections::HashMap};
 
 use indexmap::IndexMap;
 
-use crate::helixc::{
-    analyzer::{error_codes::ErrorCode, errors::push_schema_err, Ctx},
-    parser::{
-        errors::ParserError,
-        location::Loc,
-        types::{Field, FieldPrefix, FieldType, Source},
+use crate::{
+    helix_engine::types::{EdgeConstraint, MaterializedCount},
+    helixc::{
+        analyzer::{error_codes::ErrorCode, errors::push_schema_err, Ctx},
+        parser::{
+            errors::ParserError,
+            location::Loc,
+            types::{EdgeSchema, Field, FieldPrefix, FieldType, Source},
+        },
     },
 };
 
@@ -45,6 +48,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "id".to_string(),
                                 field_type: FieldType::Uuid,
                                 loc: Loc::empty(),
@@ -55,6 +59,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "label".to_string(),
                                 field_type: FieldType::String,
                                 loc: Loc::empty(),
@@ -77,6 +82,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "id".to_string(),
                                 field_type: FieldType::Uuid,
                                 loc: Loc::empty(),
@@ -87,6 +93,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "label".to_string(),
                                 field_type: FieldType::String,
                                 loc: Loc::empty(),
@@ -97,6 +104,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "from_node".to_string(),
                                 field_type: FieldType::Uuid,
                                 loc: Loc::empty(),
@@ -107,6 +115,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "to_node".to_string(),
                                 field_type: FieldType::Uuid,
                                 loc: Loc::empty(),
@@ -132,6 +141,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "id".to_string(),
                                 field_type: FieldType::Uuid,
                                 loc: Loc::empty(),
@@ -142,6 +152,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "label".to_string(),
                                 field_type: FieldType::String,
                                 loc: Loc::empty(),
@@ -152,6 +163,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "data".to_string(),
                                 field_type: FieldType::Array(Box::new(FieldType::F64)),
                                 loc: Loc::empty(),
@@ -162,6 +174,7 @@ pub(crate) fn build_field_lookups<'a>(src: &'a Source) -> SchemaVersionMap<'a> {
                             Cow::Owned(Field {
                                 prefix: FieldPrefix::Empty,
                                 defaults: None,
+                                computed: None,
                                 name: "score".to_string(),
                                 field_type: FieldType::F64,
                                 loc: Loc::empty(),
@@ -361,6 +374,39 @@ pub(crate) fn check_schema(ctx: &mut Ctx) -> Result<(), ParserError> {
                         Some("use built-in types only (String, U32, etc.)".to_string()),
                     );
                 }
+                if f.computed.is_some() {
+                    push_computed_field_err(
+                        ctx,
+                        f,
+                        "computed fields are only supported on nodes",
+                        "move the field to a node type",
+                    );
+                }
+            }
+        }
+        if check_edge_constraints(ctx, edge) {
+            ctx.output
+                .edge_constraints
+                .extend(EdgeConstraint::from_schema(edge));
+        }
+        if edge.acyclic {
+            if edge.from.1 == edge.to.1 {
+                ctx.output.acyclic_edges.push(edge.name.1.clone());
+            } else {
+                // Only edges between nodes of one type can lead back to where they started
+                push_schema_err(
+                    ctx,
+                    edge.name.0.clone(),
+                    ErrorCode::E114,
+                    ErrorCode::E114_message(
+                        &edge.name.1,
+                        &format!(
+                            "`@acyclic` edges from `{}` to `{}` can't form a cycle",
+                            edge.from.1, edge.to.1
+                        ),
+                    ),
+                    Some(ErrorCode::E114_hint("remove `@acyclic`")),
+                );
             }
         }
         ctx.output.edges.push(edge.clone().into());
@@ -397,6 +443,9 @@ pub(crate) fn check_schema(ctx: &mut Ctx) -> Result<(), ParserError> {
                     Some("use built-in types only (String, U32, etc.)".to_string()),
                 );
             }
+            if let Some(count) = check_computed_field(ctx, &node.name.1, f) {
+                ctx.output.materialized_counts.push(count);
+            }
         }
         ctx.output.nodes.push(node.clone().into());
     }
@@ -432,12 +481,133 @@ pub(crate) fn check_schema(ctx: &mut Ctx) -> Result<(), ParserError> {
                     Some("use built-in types only (String, U32, etc.)".to_string()),
                 );
             }
+            if f.computed.is_some() {
+                push_computed_field_err(
+                    ctx,
+                    f,
+                    "computed fields are only supported on nodes",
+                    "move the field to a node type",
+                );
+            }
         }
         ctx.output.vectors.push(vector.clone().into());
     }
     Ok(())
 }
 
+fn push_computed_field_err(ctx: &mut Ctx, field: &Field, reason: &str, fix: &str) {
+    push_schema_err(
+        ctx,
+        field.loc.clone(),
+        ErrorCode::E111,
+        ErrorCode::E111_message(&field.name, reason),
+        Some(ErrorCode::E111_hint(fix)),
+    );
+}
+
+/// Checks a node's computed field, giving the count the engine keeps for it. Only counts of
+/// the node's edges of one type can be maintained by writes, so `COUNT` takes a single `In`,
+/// `Out`, `InE` or `OutE` step.
+fn check_computed_field(ctx: &mut Ctx, node: &str, field: &Field) -> Option<MaterializedCount> {
+    let computed = field.computed.as_ref()?;
+    if !computed.materialized {
+        push_computed_field_err(
+            ctx,
+            field,
+            "computed fields must be materialized",
+            "add `@materialized` after the `COUNT`",
+        );
+        return None;
+    }
+    if field.is_indexed() {
+        push_computed_field_err(
+            ctx,
+            field,
+            "computed fields cannot be indexed",
+            "remove `INDEX` from the field",
+        );
+        return None;
+    }
+    let Some(count) = MaterializedCount::from_field(node, field) else {
+        push_computed_field_err(
+            ctx,
+            field,
+            "`COUNT` must count a single `In`, `Out`, `InE` or `OutE` step of an edge type",
+            "count the node's edges of one type, e.g. `COUNT(_::In<Follows>)`",
+        );
+        return None;
+    };
+    let edge_label = &count.edge_label;
+    let Some(edge) = ctx.edge_map.get(edge_label.as_str()) else {
+        push_computed_field_err(
+            ctx,
+            field,
+            &format!("unknown edge type `{edge_label}`"),
+            "declare the edge type in the schema",
+        );
+        return None;
+    };
+    let (end, direction) = match count.incoming {
+        true => (&edge.to.1, "to"),
+        false => (&edge.from.1, "from"),
+    };
+    if end != node {
+        push_computed_field_err(
+            ctx,
+            field,
+            &format!("edge type `{edge_label}` does not go {direction} `{node}`"),
+            &format!("count edges that go {direction} `{node}`"),
+        );
+        return None;
+    }
+    Some(count)
+}
+
+/// Checks the bounds declared on an edge's ends, returning whether they can be enforced
+fn check_edge_constraints(ctx: &mut Ctx, edge: &EdgeSchema) -> bool {
+    let mut valid = true;
+    for (end, cardinality) in [
+        (&edge.from, &edge.from_cardinality),
+        (&edge.to, &edge.to_cardinality),
+    ] {
+        let Some(cardinality) = cardinality else {
+            continue;
+        };
+        let (reason, fix) = if ctx.vector_set.contains(end.1.as_str()) {
+            (
+                format!("`{}` is a vector type, edges are only bounded on nodes", end.1),
+                format!("remove `{cardinality}` from `{}`", end.1),
+            )
+        } else if cardinality.max.is_some_and(|max| max < cardinality.min) {
+            (
+                format!("`{cardinality}` has an upper bound below its lower bound"),
+                "write the lower bound first, e.g. `[1..3]`".to_string(),
+            )
+        } else if cardinality.max == Some(0) {
+            (
+                format!("`{cardinality}` allows no edges"),
+                "remove the edge type if nodes can't have it".to_string(),
+            )
+        } else if cardinality.min == 0 && cardinality.max.is_none() {
+            (
+                format!("`{cardinality}` allows any number of edges"),
+                format!("remove `{cardinality}` from `{}`", end.1),
+            )
+        } else {
+            continue;
+        };
+        push_schema_err(
+            ctx,
+            cardinality.loc.clone(),
+            ErrorCode::E114,
+            ErrorCode::E114_message(&edge.name.1, &reason),
+            Some(ErrorCode::E114_hint(&fix)),
+        );
+        valid = false;
+    }
+    valid
+}
+
 fn is_valid_schema_field_type(ft: &FieldType) -> bool {
     match ft {
         FieldType::Identifier(_) => false,
@@ -447,10 +617,30 @@ fn is_valid_schema_field_type(ft: &FieldType) -> bool {
     }
 }
 
-const NODE_RESERVED_FIELD_NAMES: &[&str] = &["id", "label", "type", "version"];
+const NODE_RESERVED_FIELD_NAMES: &[&str] = &[
+    "id",
+    "label",
+    "type",
+    "version",
+    "bm25_score",
+    "vector_distance",
+    "rerank_score",
+    "scores",
+];
 const EDGE_RESERVED_FIELD_NAMES: &[&str] =
     &["id", "label", "to_node", "from_node", "type", "version"];
-const VEC_RESERVED_FIELD_NAMES: &[&str] = &["id", "label", "data", "score", "type", "version"];
+const VEC_RESERVED_FIELD_NAMES: &[&str] = &[
+    "id",
+    "label",
+    "data",
+    "score",
+    "type",
+    "version",
+    "bm25_score",
+    "vector_distance",
+    "rerank_score",
+    "scores",
+];
 
 /// Reserved type names that cannot be used as schema item names (node, edge, vector).
 /// These names conflict with built-in helix-db types and imports.
@@ -1197,4 +1387,232 @@ mod tests {
         // Should not have any E110 errors for valid names
         assert!(!diagnostics.iter().any(|d| d.error_code == ErrorCode::E110));
     }
+
+    // ============================================================================
+    // Computed Field Tests
+    // ============================================================================
+
+    #[test]
+    fn test_materialized_count_fields() {
+        let source = r#"
+            N::User {
+                name: String,
+                follower_count: COUNT(_::In<Follows>) @materialized,
+                following_count: COUNT(_::OutE<Follows>) @materialized,
+            }
+            E::Follows { From: User, To: User }
+
+            QUERY getUser(id: ID) =>
+                user <- N<User>(id)::WHERE(_::{follower_count}::GT(10))
+                RETURN user::{name, follower_count}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert_eq!(
+            generated.materialized_counts,
+            vec![
+                MaterializedCount {
+                    node_label: "User".to_string(),
+                    field: "follower_count".to_string(),
+                    edge_label: "Follows".to_string(),
+                    incoming: true,
+                },
+                MaterializedCount {
+                    node_label: "User".to_string(),
+                    field: "following_count".to_string(),
+                    edge_label: "Follows".to_string(),
+                    incoming: false,
+                },
+            ]
+        );
+        assert!(generated
+            .to_string()
+            .contains("materialized_counts: Some(vec![MaterializedCount {"));
+    }
+
+    #[test]
+    fn test_computed_field_must_be_materialized() {
+        let source = r#"
+            N::User { follower_count: COUNT(_::In<Follows>) }
+            E::Follows { From: User, To: User }
+
+            QUERY test() =>
+                u <- N<User>
+                RETURN u
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.error_code == ErrorCode::E111 && d.message.contains("materialized")));
+        assert!(generated.materialized_counts.is_empty());
+    }
+
+    #[test]
+    fn test_computed_field_counts_edges_of_the_node() {
+        let source = r#"
+            N::User { name: String }
+            N::Post { like_count: COUNT(_::Out<Likes>) @materialized }
+            E::Likes { From: User, To: Post }
+
+            QUERY test() =>
+                p <- N<Post>
+                RETURN p
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, _) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E111
+            && d.message.contains("does not go from `Post`")));
+    }
+
+    #[test]
+    fn test_computed_field_counts_a_single_step() {
+        let source = r#"
+            N::User { friend_of_friend_count: COUNT(_::Out<Knows>::Out<Knows>) @materialized }
+            E::Knows { From: User, To: User }
+
+            QUERY test() =>
+                u <- N<User>
+                RETURN u
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, _) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E111));
+    }
+
+    #[test]
+    fn test_computed_field_cannot_be_written() {
+        let source = r#"
+            N::User { name: String, follower_count: COUNT(_::In<Follows>) @materialized }
+            E::Follows { From: User, To: User }
+
+            QUERY addUser(name: String) =>
+                u <- AddN<User>({name: name, follower_count: 5})
+                RETURN u
+
+            QUERY resetFollowers(id: ID) =>
+                u <- N<User>(id)::UPDATE({follower_count: 0})
+                RETURN u
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, _) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E211)
+                .count(),
+            2
+        );
+    }
+
+    // ============================================================================
+    // Edge Constraint Tests
+    // ============================================================================
+
+    #[test]
+    fn test_edge_constraints() {
+        let source = r#"
+            N::Comment { body: String }
+            N::User { name: String }
+            E::AuthoredBy { From: Comment [1], To: User }
+
+            QUERY addComment(user: ID, body: String) =>
+                comment <- AddN<Comment>({body: body})
+                AddE<AuthoredBy>::From(comment)::To(user)
+                RETURN comment
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert_eq!(
+            generated.edge_constraints,
+            vec![EdgeConstraint {
+                node_label: "Comment".to_string(),
+                edge_label: "AuthoredBy".to_string(),
+                incoming: false,
+                min: 1,
+                max: Some(1),
+            }]
+        );
+        let generated = generated.to_string();
+        assert!(generated.contains("edge_constraints: Some(vec![EdgeConstraint {"));
+        assert!(generated.contains("db.check_constraints(&txn)?;\ndb.audit_write(&mut txn)?;"));
+    }
+
+    #[test]
+    fn test_acyclic_edges() {
+        let source = r#"
+            N::Task { name: String }
+            N::User { name: String }
+            E::DependsOn @acyclic { From: Task, To: Task }
+            E::Owns @acyclic { From: User, To: Task }
+
+            QUERY addDependency(task: ID, dependency: ID) =>
+                edge <- AddE<DependsOn>::From(task)::To(dependency)
+                RETURN edge
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].error_code, ErrorCode::E114);
+        assert!(diagnostics[0].message.contains("`Owns`"));
+        assert_eq!(generated.acyclic_edges, vec!["DependsOn".to_string()]);
+        assert!(
+            generated
+                .to_string()
+                .contains("acyclic_edges: Some(vec![\"DependsOn\".to_string()]),")
+        );
+    }
+
+    #[test]
+    fn test_edge_constraints_must_bound_node_edges() {
+        let source = r#"
+            N::User { name: String }
+            V::Doc { text: String }
+            E::Wrote { From: User [3..1], To: Doc [1] }
+            E::Knows { From: User [0..*], To: User [0] }
+
+            QUERY test() =>
+                u <- N<User>
+                RETURN u
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+
+        let errors: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E114)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("below its lower bound")));
+        assert!(errors.iter().any(|e| e.contains("`Doc` is a vector type")));
+        assert!(errors.iter().any(|e| e.contains("any number of edges")));
+        assert!(errors.iter().any(|e| e.contains("allows no edges")));
+        assert!(generated.edge_constraints.is_empty());
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
             errors::push_query_err,
             methods::infer_expr_type::infer_expr_type,
             types::Type,
-            utils::{VariableInfo, is_valid_identifier},
+            utils::{VariableInfo, is_valid_identifier, yields_scored_items},
         },
         generator::{
             queries::Query as GeneratedQuery,
@@ -85,6 +85,7 @@ pub(crate) fn validate_statements<'a>(
             // Store projection metadata from the traversal if available
             if let Some(GeneratedStatement::Traversal(ref tr)) = stmt {
                 var_info.store_projection_metadata(tr);
+                var_info.scored = yields_scored_items(tr, scope);
             }
             scope.insert(
                 assign.variable.as_str(),
@@ -1,12 +1,14 @@
+use crate::helix_engine::storage_core::degrees::DegreeDirection;
 use crate::helixc::analyzer::error_codes::*;
 use crate::helixc::analyzer::utils::{
-    DEFAULT_VAR_NAME, VariableInfo, check_identifier_is_fieldtype,
+    DEFAULT_VAR_NAME, FieldLookup, VariableInfo, check_identifier_is_fieldtype, yields_scored_items,
 };
 use crate::helixc::generator::bool_ops::{Contains, IsIn, PropertyEq, PropertyNeq};
 use crate::helixc::generator::source_steps::{
     PPR as GeneratedPPR, SearchHybrid as GeneratedSearchHybrid, SearchVector, VFromID, VFromType,
 };
-use crate::helixc::generator::traversal_steps::{AggregateBy, GroupBy};
+use crate::helixc::generator::traversal_steps::{AggregateBy, BucketBy, GroupBy};
+use crate::utils::bucket::Interval;
 use crate::helixc::generator::utils::{EmbedData, VecData};
 use crate::{
     generate_error,
@@ -27,15 +29,18 @@ use crate::{
         generator::{
             bool_ops::{BoExp, BoolOp, Eq, Gt, Gte, Lt, Lte, Neq},
             queries::Query as GeneratedQuery,
-            source_steps::{EFromID, EFromType, NFromID, NFromIndex, NFromType, SourceStep},
+            source_steps::{
+                EFromID, EFromType, NFromID, NFromIndex, NFromType, NFromView, SourceStep,
+            },
             statements::Statement as GeneratedStatement,
             traversal_steps::{
-                OrderBy, Range, ShouldCollect, Step as GeneratedStep,
+                Degree as GeneratedDegree, EdgeType, OrderBy, PathHop, Range,
+                Sample as GeneratedSample, ShouldCollect, Stat as GeneratedStat, Step as GeneratedStep,
                 Traversal as GeneratedTraversal, TraversalType, Where, WhereRef,
             },
             utils::{GenRef, GeneratedValue, Order, Separator},
         },
-        parser::{location::Loc, types::*},
+        parser::{location::Loc, pretty::print_step, types::*},
     },
     protocol::value::Value,
 };
@@ -43,6 +48,29 @@ use indexmap::IndexMap;
 use paste::paste;
 use std::collections::HashMap;
 
+/// Report a reranking step whose input has no relevance scores to rerank by, such as nodes
+/// reached by stepping away from search results
+fn check_scored_input<'a>(
+    ctx: &mut Ctx<'a>,
+    original_query: &'a Query,
+    step: &Step,
+    step_name: &str,
+    cur_ty: &Type,
+    gen_traversal: &GeneratedTraversal,
+    scope: &HashMap<&'a str, VariableInfo>,
+) {
+    if !yields_scored_items(gen_traversal, scope) {
+        generate_error!(
+            ctx,
+            original_query,
+            step.loc.clone(),
+            E639,
+            step_name,
+            &cur_ty.get_type_name()
+        );
+    }
+}
+
 /// Check if a property name is a reserved property and return its expected type
 fn get_reserved_property_type(prop_name: &str, item_type: &Type) -> Option<FieldType> {
     match prop_name {
@@ -93,6 +121,15 @@ fn get_reserved_property_type(prop_name: &str, item_type: &Type) -> Option<Field
                 _ => None,
             }
         }
+        "score" | "bm25_score" | "vector_distance" | "rerank_score" => {
+            // Only valid for search results, which are nodes or vectors
+            match item_type {
+                Type::Node(_) | Type::Nodes(_) | Type::Vector(_) | Type::Vectors(_) => {
+                    Some(FieldType::F64)
+                }
+                _ => None,
+            }
+        }
         _ => None,
     }
 }
@@ -155,6 +192,27 @@ pub(crate) fn validate_traversal<'a>(
 ) -> Option<Type> {
     let mut previous_step = None;
     let mut cur_ty = match &tr.start {
+        StartNode::Node { node_type, ids } if ctx.views.contains_key(node_type.as_str()) => {
+            if ids.is_some() {
+                generate_error!(
+                    ctx,
+                    original_query,
+                    tr.loc.clone(),
+                    E112,
+                    [
+                        node_type,
+                        "views are read whole, not looked up by id or index"
+                    ],
+                    ["read the view and filter it with `WHERE`"]
+                );
+                return None;
+            }
+            gen_traversal.source_step = Separator::Period(SourceStep::NFromView(NFromView {
+                name: GenRef::Literal(node_type.clone()),
+            }));
+            gen_traversal.traversal_type = TraversalType::Ref;
+            Type::Nodes(Some(ctx.views[node_type.as_str()].node_type.clone()))
+        }
         StartNode::Node { node_type, ids } => {
             if !ctx.node_set.contains(node_type.as_str()) {
                 generate_error!(ctx, original_query, tr.loc.clone(), E101, node_type);
@@ -548,6 +606,11 @@ pub(crate) fn validate_traversal<'a>(
                     ))
                 }
                 Some(VectorData::Embed(e)) => {
+                    let model_name = ctx.embedding_model(
+                        e,
+                        &gen_query.embedding_model_to_use,
+                        sv.vector_type.as_deref(),
+                    );
                     let embed_data = match &e.value {
                         EvaluatesToString::Identifier(i) => {
                             type_in_scope(ctx, original_query, sv.loc.clone(), scope, i.as_str());
@@ -558,12 +621,14 @@ pub(crate) fn validate_traversal<'a>(
                                     true,
                                     false,
                                 ),
-                                model_name: gen_query.embedding_model_to_use.clone(),
+                                model_name: model_name.clone(),
+                                reuse_stored: None,
                             }
                         }
                         EvaluatesToString::StringLiteral(s) => EmbedData {
                             data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name,
+                            reuse_stored: None,
                         },
                     };
 
@@ -739,6 +804,11 @@ pub(crate) fn validate_traversal<'a>(
                     ))
                 }
                 Some(VectorData::Embed(e)) => {
+                    let model_name = ctx.embedding_model(
+                        e,
+                        &gen_query.embedding_model_to_use,
+                        sh.vector_type.as_deref(),
+                    );
                     let embed_data = match &e.value {
                         EvaluatesToString::Identifier(i) => {
                             type_in_scope(ctx, original_query, sh.loc.clone(), scope, i.as_str());
@@ -749,12 +819,14 @@ pub(crate) fn validate_traversal<'a>(
                                     true,
                                     false,
                                 ),
-                                model_name: gen_query.embedding_model_to_use.clone(),
+                                model_name: model_name.clone(),
+                                reuse_stored: None,
                             }
                         }
                         EvaluatesToString::StringLiteral(s) => EmbedData {
                             data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                            model_name: gen_query.embedding_model_to_use.clone(),
+                            model_name,
+                            reuse_stored: None,
                         },
                     };
                     VecData::Hoisted(gen_query.add_hoisted_embed(embed_data))
@@ -1088,6 +1160,8 @@ pub(crate) fn validate_traversal<'a>(
         }
     };
 
+    let start_ty = cur_ty.clone();
+
     // Track excluded fields for property validation
     let mut excluded: HashMap<&str, Loc> = HashMap::new();
 
@@ -1123,6 +1197,13 @@ pub(crate) fn validate_traversal<'a>(
                 gen_traversal.should_collect = ShouldCollect::ToObj;
             }
 
+            StepType::Count if cur_ty == Type::Buckets => {
+                if let Some(Separator::Period(GeneratedStep::BucketBy(bucket_by))) =
+                    gen_traversal.steps.last_mut()
+                {
+                    bucket_by.should_count = true;
+                }
+            }
             StepType::Count => {
                 cur_ty = Type::Count;
                 excluded.clear();
@@ -1132,6 +1213,155 @@ pub(crate) fn validate_traversal<'a>(
                 gen_traversal.should_collect = ShouldCollect::No;
             }
 
+            StepType::Degree(degree) => {
+                match cur_ty.base() {
+                    Type::Node(Some(item_label))
+                    | Type::Nodes(Some(item_label))
+                    | Type::Vector(Some(item_label))
+                    | Type::Vectors(Some(item_label)) => {
+                        match ctx.edge_map.get(degree.label.as_str()) {
+                            Some(edge) => {
+                                let connects = match degree.direction {
+                                    DegreeDirection::Out => edge.from.1 == *item_label,
+                                    DegreeDirection::In => edge.to.1 == *item_label,
+                                    DegreeDirection::Both => {
+                                        edge.from.1 == *item_label || edge.to.1 == *item_label
+                                    }
+                                };
+                                if !connects {
+                                    let item_type = match cur_ty.base() {
+                                        Type::Node(_) | Type::Nodes(_) => "node",
+                                        _ => "vector",
+                                    };
+                                    generate_error!(
+                                        ctx,
+                                        original_query,
+                                        degree.loc.clone(),
+                                        E207,
+                                        degree.label.as_str(),
+                                        item_type,
+                                        item_label.as_str()
+                                    );
+                                }
+                            }
+                            None => {
+                                generate_error!(
+                                    ctx,
+                                    original_query,
+                                    degree.loc.clone(),
+                                    E102,
+                                    degree.label.as_str()
+                                );
+                            }
+                        }
+                    }
+                    _ => {
+                        generate_error!(
+                            ctx,
+                            original_query,
+                            degree.loc.clone(),
+                            E634,
+                            &print_step(step),
+                            cur_ty.kind_str()
+                        );
+                    }
+                }
+                cur_ty = Type::Count;
+                excluded.clear();
+                gen_traversal
+                    .steps
+                    .push(Separator::Period(GeneratedStep::Degree(GeneratedDegree {
+                        label: GenRef::Literal(degree.label.clone()),
+                        direction: degree.direction,
+                    })));
+                gen_traversal.should_collect = ShouldCollect::No;
+            }
+
+            StepType::NeighborCount => {
+                if !matches!(
+                    cur_ty.base(),
+                    Type::Node(_) | Type::Nodes(_) | Type::Vector(_) | Type::Vectors(_)
+                ) {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        graph_step.loc.clone(),
+                        E634,
+                        &print_step(step),
+                        cur_ty.kind_str()
+                    );
+                }
+                cur_ty = Type::Count;
+                excluded.clear();
+                gen_traversal
+                    .steps
+                    .push(Separator::Period(GeneratedStep::NeighborCount));
+                gen_traversal.should_collect = ShouldCollect::No;
+            }
+
+            StepType::Paths => {
+                // PATHS replays the Out/In hops before it, so only a plain walk between nodes
+                // can be turned into paths
+                if !matches!(start_ty, Type::Node(_) | Type::Nodes(_)) {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        graph_step.loc.clone(),
+                        E630,
+                        start_ty.kind_str()
+                    );
+                } else if let Some(prior) = tr.steps[..i].iter().find(|prior| {
+                    !matches!(
+                        &prior.step,
+                        StepType::Node(GraphStep {
+                            step: GraphStepType::Out(_) | GraphStepType::In(_),
+                            ..
+                        })
+                    )
+                }) {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        graph_step.loc.clone(),
+                        E630,
+                        &print_step(&prior.step)
+                    );
+                } else {
+                    let hops = gen_traversal
+                        .steps
+                        .iter()
+                        .filter_map(|step| match step {
+                            Separator::Period(GeneratedStep::Out(out))
+                                if matches!(out.edge_type, EdgeType::Node) =>
+                            {
+                                Some(PathHop::Out(out.label.clone()))
+                            }
+                            Separator::Period(GeneratedStep::In(in_))
+                                if matches!(in_.edge_type, EdgeType::Node) =>
+                            {
+                                Some(PathHop::In(in_.label.clone()))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    if hops.len() == gen_traversal.steps.len() {
+                        gen_traversal.steps = vec![Separator::Period(GeneratedStep::Paths(hops))];
+                    } else {
+                        // One of the hops reached vectors rather than nodes
+                        generate_error!(
+                            ctx,
+                            original_query,
+                            graph_step.loc.clone(),
+                            E630,
+                            &print_step(&tr.steps[i - 1].step)
+                        );
+                    }
+                }
+                cur_ty = Type::Paths;
+                excluded.clear();
+                gen_traversal.should_collect = ShouldCollect::ToVec;
+            }
+
             StepType::Exclude(ex) => {
                 // checks if exclude is either the last step or the step before an object remapping or closure
                 // i.e. you cant have `N<Type>::!{field1}::Out<Label>`
@@ -1343,9 +1573,16 @@ pub(crate) fn validate_traversal<'a>(
                             }
                         }
                         Type::Nodes(Some(node_ty)) | Type::Node(Some(node_ty)) => {
-                            // Check if this is a reserved property first
+                            // Check if this is a reserved property first, unless the node
+                            // type declares a field of that name
                             if let Some(reserved_type) =
-                                get_reserved_property_type(field_name.as_str(), &cur_ty)
+                                get_reserved_property_type(field_name.as_str(), &cur_ty).filter(
+                                    |_| {
+                                        !ctx.node_fields.get(node_ty.as_str()).is_some_and(
+                                            |fields| fields.contains_key(field_name.as_str()),
+                                        )
+                                    },
+                                )
                             {
                                 // Validate the type matches
                                 if let FieldType::Array(inner_type) = &property_type {
@@ -2047,6 +2284,194 @@ pub(crate) fn validate_traversal<'a>(
                         should_count,
                     })))
             }
+            StepType::BucketBy(bb) => {
+                if !matches!(
+                    cur_ty.base(),
+                    Type::Node(Some(_))
+                        | Type::Nodes(Some(_))
+                        | Type::Edge(Some(_))
+                        | Type::Edges(Some(_))
+                        | Type::Vector(Some(_))
+                        | Type::Vectors(Some(_))
+                ) {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        bb.loc.clone(),
+                        E636,
+                        &cur_ty.get_type_name(),
+                        "only nodes, edges and vectors can be bucketed"
+                    );
+                    return Some(cur_ty.clone());
+                }
+                field_exists_on_item_type(
+                    ctx,
+                    original_query,
+                    cur_ty.base().clone(),
+                    vec![(bb.property.as_str(), &bb.loc)],
+                );
+                if let Some(field_type) = cur_ty
+                    .base()
+                    .get_field_type_from_item_fields(ctx, &bb.property)
+                    && !matches!(
+                        field_type,
+                        FieldType::Date | FieldType::String | FieldType::I64 | FieldType::U64
+                    )
+                {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        bb.loc.clone(),
+                        E636,
+                        &bb.property,
+                        &format!("it holds a {field_type}, not a date")
+                    );
+                }
+                if let Err(reason) = bb.interval.parse::<Interval>() {
+                    generate_error!(ctx, original_query, bb.loc.clone(), E636, &bb.interval, &reason);
+                }
+                let timezone = match &bb.timezone {
+                    None => GeneratedValue::Literal(GenRef::Literal("UTC".to_string())),
+                    Some(EvaluatesToString::StringLiteral(tz)) => {
+                        if tz.parse::<chrono_tz::Tz>().is_err() {
+                            generate_error!(
+                                ctx,
+                                original_query,
+                                bb.loc.clone(),
+                                E636,
+                                tz,
+                                "it isn't an IANA time zone"
+                            );
+                        }
+                        GeneratedValue::Literal(GenRef::Literal(tz.clone()))
+                    }
+                    Some(EvaluatesToString::Identifier(i)) => {
+                        is_valid_identifier(ctx, original_query, bb.loc.clone(), i.as_str());
+                        if let Some(ty) = type_in_scope(ctx, original_query, bb.loc.clone(), scope, i)
+                            && ty != Type::Scalar(FieldType::String)
+                        {
+                            generate_error!(
+                                ctx,
+                                original_query,
+                                bb.loc.clone(),
+                                E636,
+                                i,
+                                "the time zone must be a String"
+                            );
+                        }
+                        gen_identifier_or_param(original_query, i, true, false)
+                    }
+                };
+
+                // A following `::COUNT` turns this into counts only
+                cur_ty = Type::Buckets;
+                gen_traversal.should_collect = ShouldCollect::Try;
+                gen_traversal
+                    .steps
+                    .push(Separator::Period(GeneratedStep::BucketBy(BucketBy {
+                        should_count: false,
+                        property: GenRef::Literal(bb.property.clone()),
+                        interval: GenRef::Literal(bb.interval.clone()),
+                        timezone,
+                    })))
+            }
+            StepType::Stat(stat) => {
+                let name = stat.function.name();
+                if !matches!(
+                    cur_ty.base(),
+                    Type::Node(Some(_))
+                        | Type::Nodes(Some(_))
+                        | Type::Edge(Some(_))
+                        | Type::Edges(Some(_))
+                        | Type::Vector(Some(_))
+                        | Type::Vectors(Some(_))
+                ) {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        stat.loc.clone(),
+                        E637,
+                        name,
+                        &cur_ty.get_type_name(),
+                        "only properties of nodes, edges and vectors have statistics"
+                    );
+                    return Some(cur_ty.clone());
+                }
+                field_exists_on_item_type(
+                    ctx,
+                    original_query,
+                    cur_ty.base().clone(),
+                    vec![(stat.property.as_str(), &stat.loc)],
+                );
+                if let Some(field_type) = cur_ty
+                    .base()
+                    .get_field_type_from_item_fields(ctx, &stat.property)
+                    && !Type::Scalar(field_type.clone()).is_numeric()
+                {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        stat.loc.clone(),
+                        E637,
+                        name,
+                        &stat.property,
+                        &format!("it holds a {field_type}, not a number")
+                    );
+                }
+
+                let property = GenRef::Literal(stat.property.clone());
+                cur_ty = Type::Scalar(FieldType::F64);
+                excluded.clear();
+                gen_traversal.should_collect = ShouldCollect::Try;
+                gen_traversal
+                    .steps
+                    .push(Separator::Period(GeneratedStep::Stat(
+                        match stat.function.quantile() {
+                            Some(q) => GeneratedStat::Percentile(property, q),
+                            None => GeneratedStat::Stddev(property),
+                        },
+                    )));
+            }
+            StepType::Delete => {
+                // Each batch runs the traversal again, so it has to start from the graph
+                // rather than from items already held in a variable
+                let reason = match (&cur_ty, &gen_traversal.traversal_type) {
+                    (
+                        Type::Node(_)
+                        | Type::Nodes(_)
+                        | Type::Edge(_)
+                        | Type::Edges(_)
+                        | Type::Vector(_)
+                        | Type::Vectors(_),
+                        TraversalType::Ref,
+                    ) => None,
+                    (
+                        Type::Node(_)
+                        | Type::Nodes(_)
+                        | Type::Edge(_)
+                        | Type::Edges(_)
+                        | Type::Vector(_)
+                        | Type::Vectors(_),
+                        _,
+                    ) => Some("it has to start from `N`, `E` or `V` rather than a variable"),
+                    _ => Some("it deletes nodes, edges or vectors"),
+                };
+                if let Some(reason) = reason {
+                    generate_error!(
+                        ctx,
+                        original_query,
+                        graph_step.loc.clone(),
+                        E638,
+                        &cur_ty.get_type_name(),
+                        reason
+                    );
+                    return Some(cur_ty.clone());
+                }
+                cur_ty = Type::Count;
+                excluded.clear();
+                gen_traversal.traversal_type = TraversalType::Delete;
+                gen_traversal.should_collect = ShouldCollect::No;
+            }
             StepType::Update(update) => {
                 // if type == node, edge, vector then update is valid
                 // otherwise it is invalid
@@ -2731,7 +3156,8 @@ pub(crate) fn validate_traversal<'a>(
                     }
                 };
 
-                // Parse vector data
+                // Parse vector data, keeping the text of embedded vectors to detect unchanged content
+                let mut content = None;
                 let vec_data = match &upsert.data {
                     Some(VectorData::Identifier(id)) => {
                         is_valid_identifier(ctx, original_query, upsert.loc.clone(), id.as_str());
@@ -2781,6 +3207,11 @@ pub(crate) fn validate_traversal<'a>(
                         ))))
                     }
                     Some(VectorData::Embed(embed)) => {
+                        let model_name = ctx.embedding_model(
+                            embed,
+                            &gen_query.embedding_model_to_use,
+                            Some(label.as_str()),
+                        );
                         let embed_data = match &embed.value {
                             EvaluatesToString::Identifier(id) => {
                                 is_valid_identifier(
@@ -2803,14 +3234,17 @@ pub(crate) fn validate_traversal<'a>(
                                         true,
                                         false,
                                     ),
-                                    model_name: gen_query.embedding_model_to_use.clone(),
+                                    model_name: model_name.clone(),
+                                    reuse_stored: Some(label.clone()),
                                 }
                             }
                             EvaluatesToString::StringLiteral(s) => EmbedData {
                                 data: GeneratedValue::Literal(GenRef::Ref(s.clone())),
-                                model_name: gen_query.embedding_model_to_use.clone(),
+                                model_name,
+                                reuse_stored: Some(label.clone()),
                             },
                         };
+                        content = Some(embed_data.data.clone());
                         Some(VecData::Hoisted(gen_query.add_hoisted_embed(embed_data)))
                     }
                     None => None,
@@ -2821,6 +3255,7 @@ pub(crate) fn validate_traversal<'a>(
                     source,
                     source_is_plural,
                     label,
+                    content,
                     properties: Some(
                         upsert
                             .fields
@@ -2943,6 +3378,57 @@ pub(crate) fn validate_traversal<'a>(
                 excluded.clear();
             }
 
+            StepType::Sample(sample) => {
+                let size = match &sample.size.value {
+                    EvaluatesToNumberType::I64(n) => Some(GeneratedSample::Count(
+                        GeneratedValue::Primitive(GenRef::Std(n.to_string())),
+                    )),
+                    EvaluatesToNumberType::F64(f) if (0.0..=1.0).contains(f) => {
+                        Some(GeneratedSample::Fraction(GeneratedValue::Primitive(
+                            GenRef::Std(format!("{f:?}")),
+                        )))
+                    }
+                    EvaluatesToNumberType::Identifier(i) => {
+                        is_valid_identifier(ctx, original_query, sample.loc.clone(), i.as_str());
+                        match type_in_scope(ctx, original_query, sample.loc.clone(), scope, i) {
+                            Some(ty) if ty.is_integer() => Some(GeneratedSample::Count(
+                                gen_identifier_or_param(original_query, i, false, true),
+                            )),
+                            Some(ty) if ty.is_numeric() => Some(GeneratedSample::Fraction(
+                                gen_identifier_or_param(original_query, i, false, true),
+                            )),
+                            Some(_) => {
+                                generate_error!(
+                                    ctx,
+                                    original_query,
+                                    sample.size.loc.clone(),
+                                    E635,
+                                    &sample.size.loc.span
+                                );
+                                None
+                            }
+                            // Already reported as not in scope
+                            None => None,
+                        }
+                    }
+                    _ => {
+                        generate_error!(
+                            ctx,
+                            original_query,
+                            sample.size.loc.clone(),
+                            E635,
+                            &sample.size.loc.span
+                        );
+                        None
+                    }
+                };
+                if let Some(size) = size {
+                    gen_traversal
+                        .steps
+                        .push(Separator::Period(GeneratedStep::Sample(size)));
+                }
+            }
+
             StepType::Range((start, end)) => {
                 let (start, end) = match (&start.expr, &end.expr) {
                     (ExpressionType::Identifier(i), ExpressionType::Identifier(j)) => {
@@ -3157,14 +3643,13 @@ pub(crate) fn validate_traversal<'a>(
                 };
 
                 // Closure parameters are always singular (they represent individual items during iteration)
-                scope.insert(
-                    cl.identifier.as_str(),
-                    VariableInfo::new_with_source(
-                        closure_param_type.clone(),
-                        true,
-                        closure_source_var.clone(),
-                    ),
+                let mut closure_param_info = VariableInfo::new_with_source(
+                    closure_param_type.clone(),
+                    true,
+                    closure_source_var.clone(),
                 );
+                closure_param_info.scored = yields_scored_items(gen_traversal, scope);
+                scope.insert(cl.identifier.as_str(), closure_param_info);
                 let obj = &cl.object;
                 let mut fields_out = vec![];
                 // Pass the singular type to validate_object so nested traversals use the correct type
@@ -3206,6 +3691,15 @@ pub(crate) fn validate_traversal<'a>(
                 scope.remove(cl.identifier.as_str());
             }
             StepType::RerankRRF(rerank_rrf) => {
+                check_scored_input(
+                    ctx,
+                    original_query,
+                    graph_step,
+                    "RerankRRF",
+                    &cur_ty,
+                    gen_traversal,
+                    scope,
+                );
                 // Generate k parameter if provided
                 let k = rerank_rrf.k.as_ref().map(|k_expr| match &k_expr.expr {
                     ExpressionType::Identifier(id) => {
@@ -3238,6 +3732,15 @@ pub(crate) fn validate_traversal<'a>(
                     )));
             }
             StepType::RerankMMR(rerank_mmr) => {
+                check_scored_input(
+                    ctx,
+                    original_query,
+                    graph_step,
+                    "RerankMMR",
+                    &cur_ty,
+                    gen_traversal,
+                    scope,
+                );
                 // Generate lambda parameter
                 let lambda = match &rerank_mmr.lambda.expr {
                     ExpressionType::Identifier(id) => {
@@ -3321,11 +3824,61 @@ pub(crate) fn validate_traversal<'a>(
                         crate::helixc::generator::traversal_steps::RerankMMR { lambda, distance },
                     )));
             }
+            StepType::Boost(boost) => {
+                check_scored_input(
+                    ctx,
+                    original_query,
+                    graph_step,
+                    "BOOST",
+                    &cur_ty,
+                    gen_traversal,
+                    scope,
+                );
+                let half_life = boost.half_life.as_ref().map(|days| match &days.expr {
+                    ExpressionType::Identifier(id) => {
+                        is_valid_identifier(ctx, original_query, days.loc.clone(), id.as_str());
+                        type_in_scope(ctx, original_query, days.loc.clone(), scope, id.as_str());
+                        gen_identifier_or_param(original_query, id.as_str(), false, true)
+                    }
+                    ExpressionType::IntegerLiteral(val) => {
+                        GeneratedValue::Primitive(GenRef::Std(val.to_string()))
+                    }
+                    ExpressionType::FloatLiteral(val) => {
+                        GeneratedValue::Primitive(GenRef::Std(val.to_string()))
+                    }
+                    _ => {
+                        generate_error!(
+                            ctx,
+                            original_query,
+                            days.loc.clone(),
+                            E206,
+                            &days.expr.to_string()
+                        );
+                        GeneratedValue::Unknown
+                    }
+                });
+                // Listing no signals boosts by all of them
+                let boosts = |signal| boost.signals.is_empty() || boost.signals.contains(&signal);
+
+                gen_traversal
+                    .steps
+                    .push(Separator::Period(GeneratedStep::Boost(
+                        crate::helixc::generator::traversal_steps::Boost {
+                            salience: boosts(BoostSignal::Salience),
+                            confidence: boosts(BoostSignal::Confidence),
+                            recency: boosts(BoostSignal::Recency),
+                            half_life,
+                        },
+                    )));
+            }
         }
         previous_step = Some(step.clone());
     }
     match gen_traversal.traversal_type {
-        TraversalType::Mut | TraversalType::Update(_) | TraversalType::Upsert { .. } => {
+        TraversalType::Mut
+        | TraversalType::Update(_)
+        | TraversalType::Upsert { .. }
+        | TraversalType::Delete => {
             gen_query.is_mut = true;
         }
         _ => {}
@@ -3637,6 +4190,567 @@ mod tests {
         assert!(diagnostics.is_empty());
     }
 
+    #[test]
+    fn test_paths_replaces_hops_with_paths_step() {
+        let source = r#"
+            N::Person { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY test(id: ID) =>
+                walked <- N<Person>(id)::Out<Knows>::In<Knows>::PATHS
+                RETURN walked
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(
+            output.contains(r#".paths(&[PathHop::Out("Knows"), PathHop::In("Knows")])"#),
+            "{output}"
+        );
+        assert!(!output.contains("out_node"), "{output}");
+        assert!(output.contains("\"walked\": walked"), "{output}");
+    }
+
+    #[test]
+    fn test_paths_after_other_steps_is_rejected() {
+        let source = r#"
+            N::Person { name: String, age: U32 }
+            E::Knows { From: Person, To: Person }
+
+            QUERY test(id: ID) =>
+                edges <- N<Person>(id)::OutE<Knows>::PATHS
+                older <- N<Person>(id)::Out<Knows>::WHERE(_::{age}::GT(30))::PATHS
+                links <- E<Knows>::PATHS
+                RETURN edges, older, links
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E630)
+                .count(),
+            3,
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_degree_and_neighbor_count_read_kept_counts() {
+        let source = r#"
+            N::Person { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY test(id: ID) =>
+                known_by <- N<Person>(id)::DEGREE<Knows>(in)
+                linked <- N<Person>(id)::NEIGHBOR_COUNT
+                popular <- N<Person>::WHERE(_::DEGREE<Knows>(both)::GT(10))
+                RETURN known_by, linked, popular
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(
+            output.contains(r#".degree("Knows", DegreeDirection::In)"#),
+            "{output}"
+        );
+        assert!(
+            output.contains(r#".degree("Knows", DegreeDirection::Both)"#),
+            "{output}"
+        );
+        assert!(output.contains(".neighbor_count()"), "{output}");
+        assert!(output.contains("\"known_by\": known_by"), "{output}");
+    }
+
+    #[test]
+    fn test_degree_checks_edge_type_and_item() {
+        let source = r#"
+            N::Person { name: String }
+            N::Company { name: String }
+            E::Knows { From: Person, To: Person }
+
+            QUERY test(id: ID) =>
+                unknown <- N<Person>(id)::DEGREE<Likes>(out)
+                wrong <- N<Company>(id)::DEGREE<Knows>(in)
+                edges <- E<Knows>::NEIGHBOR_COUNT
+                RETURN unknown, wrong, edges
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        for code in [ErrorCode::E102, ErrorCode::E207, ErrorCode::E634] {
+            assert!(
+                diagnostics.iter().any(|d| d.error_code == code),
+                "{diagnostics:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_by_count_or_fraction() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test(n: I64, share: F64) =>
+                few <- N<Person>::SAMPLE(100)
+                some <- N<Person>::SAMPLE(0.01)
+                asked <- N<Person>::SAMPLE(n)
+                portion <- N<Person>::SAMPLE(share)
+                RETURN few, some, asked, portion
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains(".sample(100)"), "{output}");
+        assert!(output.contains(".sample_fraction(0.01)"), "{output}");
+        assert!(output.contains(".sample(data.n.clone())"), "{output}");
+        assert!(
+            output.contains(".sample_fraction(data.share.clone())"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_sample_rejects_fractions_above_one_and_non_numbers() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test(name: String) =>
+                most <- N<Person>::SAMPLE(1.5)
+                named <- N<Person>::SAMPLE(name)
+                RETURN most, named
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E635)
+                .count(),
+            2,
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_bucket_by_counts_per_bucket_in_a_time_zone() {
+        let source = r#"
+            N::Post { title: String, created_at: Date DEFAULT NOW }
+
+            QUERY test(tz: String) =>
+                daily <- N<Post>::BUCKET_BY(created_at, 1d)::COUNT
+                weekly <- N<Post>::BUCKET_BY(created_at, 1w, "Europe/Berlin")::COUNT
+                local <- N<Post>::BUCKET_BY(created_at, 15m, tz)
+                RETURN daily, weekly, local
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(
+            output.contains(".bucket_by(\"created_at\", \"1d\", \"UTC\", true)"),
+            "{output}"
+        );
+        assert!(
+            output.contains(".bucket_by(\"created_at\", \"1w\", \"Europe/Berlin\", true)"),
+            "{output}"
+        );
+        assert!(
+            output.contains(".bucket_by(\"created_at\", \"15m\", &data.tz, false)"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_bucket_by_rejects_bad_widths_zones_and_fields() {
+        let source = r#"
+            N::Post { title: String, created_at: Date DEFAULT NOW }
+
+            QUERY test() =>
+                never <- N<Post>::BUCKET_BY(created_at, 0d)
+                nowhere <- N<Post>::BUCKET_BY(created_at, 1d, "Mars/Base")
+                titled <- N<Post>::BUCKET_BY(title, 1d)
+                RETURN never, nowhere, titled
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E636)
+                .count(),
+            2,
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_stats_of_numeric_properties() {
+        let source = r#"
+            N::Request { path: String, latency: F64 }
+
+            QUERY test() =>
+                typical <- N<Request>::MEDIAN(latency)
+                slow <- N<Request>::P95(latency)
+                slowest <- N<Request>::P99(latency)
+                spread <- N<Request>::STDDEV(latency)
+                RETURN typical, slow, slowest, spread
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains(".percentile(\"latency\", 0.5)?"), "{output}");
+        assert!(output.contains(".percentile(\"latency\", 0.95)?"), "{output}");
+        assert!(output.contains(".percentile(\"latency\", 0.99)?"), "{output}");
+        assert!(output.contains(".stddev(\"latency\")?"), "{output}");
+    }
+
+    #[test]
+    fn test_stats_reject_non_numeric_properties() {
+        let source = r#"
+            N::Request { path: String, latency: F64 }
+
+            QUERY test() =>
+                paths <- N<Request>::P50(path)
+                counted <- N<Request>::COUNT::STDDEV(latency)
+                RETURN paths, counted
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E637)
+                .count(),
+            2,
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_delete_runs_in_batches_on_a_write_transaction() {
+        let source = r#"
+            N::Log { ts: I64 }
+
+            QUERY test(cutoff: I64) =>
+                deleted <- N<Log>::WHERE(_::{ts}::LT(cutoff))::DELETE
+                RETURN deleted
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert!(generated.queries[0].is_mut);
+        let output = generated.queries[0].to_string();
+        assert!(
+            output.contains("DeleteWhere::delete_where(&db, txn, |txn, arena, batch|"),
+            "{output}"
+        );
+        assert!(output.contains(".take(batch)"), "{output}");
+        assert!(output.contains("txn = next_txn;"), "{output}");
+    }
+
+    #[test]
+    fn test_delete_rejects_variables_and_non_items() {
+        let source = r#"
+            N::Log { ts: I64 }
+
+            QUERY test() =>
+                logs <- N<Log>
+                from_variable <- logs::DELETE
+                counted <- N<Log>::COUNT::DELETE
+                RETURN from_variable, counted
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.error_code == ErrorCode::E638)
+                .count(),
+            2,
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_reranker_pipeline_composes_stages() {
+        let source = r#"
+            N::Doc { text: String }
+            V::Chunk { text: String }
+
+            QUERY test(vec: [F64], text: String) =>
+                chunks <- SearchV<Chunk>(vec, 50)::RERANK_RRF(60)::BOOST(salience, half_life: 7)::RERANK_MMR(0.7)
+                docs <- SearchBM25<Doc>(text, 50)
+                top <- docs::WHERE(_::{text}::CONTAINS("a"))::BOOST::RANGE(0, 10)
+                RETURN chunks, top
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        let stages = [
+            ".rerank(RRFReranker::with_k(60 as f64).unwrap(), None)",
+            ".rerank(SignalBoostConfig::new().with_salience(true).with_confidence(false).with_recency(false).with_half_life_days(7 as f64), None)",
+            ".rerank(MMRReranker::new(0.7).unwrap(), None)",
+        ]
+        .map(|stage| output.find(stage));
+        assert!(stages.iter().all(Option::is_some), "{output}");
+        assert!(stages.is_sorted(), "{output}");
+        assert!(
+            output.contains(".rerank(SignalBoostConfig::new(), None)"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_rerankers_reject_unscored_items() {
+        let source = r#"
+            N::Doc { text: String }
+            V::Chunk { text: String }
+            E::Cites { From: Doc, To: Doc }
+
+            QUERY test(vec: [F64], text: String) =>
+                all_docs <- N<Doc>::RerankRRF
+                cited <- SearchBM25<Doc>(text, 10)
+                neighbours <- cited::Out<Cites>::BOOST
+                chunks <- SearchV<Chunk>(vec, 10)::RerankMMR(lambda: 0.5)
+                kept <- chunks::RANGE(0, 5)::RerankRRF
+                RETURN all_docs, neighbours, kept
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        let rejected: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E639)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                "`RerankRRF` can't rerank `Doc`: they have no relevance scores (in QUERY named `test`)",
+                "`BOOST` can't rerank `Doc`: they have no relevance scores (in QUERY named `test`)",
+            ],
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_score_properties_of_search_results() {
+        let source = r#"
+            N::Doc { text: String }
+            V::Chunk { text: String }
+
+            QUERY test(vec: [F64], text: String) =>
+                hits <- SearchBM25<Doc>(text, 10)
+                docs <- hits::RerankRRF
+                chunks <- SearchV<Chunk>(vec, 10)
+                close <- chunks::WHERE(_::{vector_distance}::LT(0.5))
+                RETURN docs::{text, score, bm25_score, rerank_score, scores}, close::{text, vector_distance}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        for access in [
+            ".score()",
+            ".bm25_score().map(Value::from).unwrap_or_default()",
+            ".rerank_score().map(Value::from).unwrap_or_default()",
+            "Value::from(doc.score_breakdown())",
+            "val.vector_distance().map(Value::from).unwrap_or_default() < 0.5",
+            "close.vector_distance().map(Value::from).unwrap_or_default()",
+        ] {
+            assert!(output.contains(access), "{access} not in {output}");
+        }
+    }
+
+    #[test]
+    fn test_node_score_field_is_not_a_search_score() {
+        let source = r#"
+            N::Player { name: String, score: I64 }
+
+            QUERY test() =>
+                players <- N<Player>::WHERE(_::{score}::GT(10))
+                RETURN players::{name, score}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert!(output.contains("get_property(\"score\")"), "{output}");
+        assert!(!output.contains(".score()"), "{output}");
+    }
+
+    #[test]
+    fn test_score_key_remapping_another_property() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY test() =>
+                people <- N<Person>
+                RETURN people::{score: name}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, generated) = result.unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        let output = generated.queries[0].to_string();
+        assert_eq!(output.matches("pub score:").count(), 1, "{output}");
+        assert!(output.contains("get_property(\"name\")"), "{output}");
+        assert!(!output.contains(".score()"), "{output}");
+    }
+
+    #[test]
+    fn test_score_key_rejected_on_search_results() {
+        let source = r#"
+            N::Doc { text: String }
+
+            QUERY test(text: String) =>
+                docs <- SearchBM25<Doc>(text, 10)
+                RETURN docs::{score: text, bm25_score}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        let rejected: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E647)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                "`score` is the relevance score of these `Doc` and can't hold another value (in QUERY named `test`)"
+            ],
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_score_properties_reject_unscored_items() {
+        let source = r#"
+            N::Doc { text: String }
+            V::Chunk { text: String }
+            E::Cites { From: Doc, To: Doc }
+
+            QUERY test(text: String) =>
+                docs <- SearchBM25<Doc>(text, 10)
+                cited <- docs::Out<Cites>
+                chunks <- V<Chunk>
+                RETURN docs::{text, bm25_score}, cited::{score}, chunks::{text, scores}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let result = crate::helixc::analyzer::analyze(&parsed);
+
+        assert!(result.is_ok());
+        let (diagnostics, _) = result.unwrap();
+        let rejected: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E640)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                "`score` can't be read from `Doc`: they have no relevance scores (in QUERY named `test`)",
+                "`scores` can't be read from `Chunk`: they have no relevance scores (in QUERY named `test`)",
+            ],
+            "{diagnostics:?}"
+        );
+    }
+
     // ============================================================================
     // Complex Query Tests
     // ============================================================================
@@ -0,0 +1,354 @@
+//! Semantic analyzer for triggers.
+
+use crate::{
+    generate_error,
+    helixc::{
+        analyzer::{
+            Ctx,
+            error_codes::ErrorCode,
+            errors::push_query_err,
+            methods::statement_validation::validate_statements,
+            types::Type,
+            utils::{VariableInfo, is_valid_identifier},
+        },
+        generator::{queries::Query as GeneratedQuery, triggers::Trigger as GeneratedTrigger},
+        parser::{location::Loc, types::*},
+    },
+};
+use paste::paste;
+use std::collections::{HashMap, HashSet};
+
+/// The node and edge types HQL source adds with `AddN<...>` and `AddE<...>`, which run the
+/// triggers declared on them
+pub(crate) fn added_items(src: &str) -> Vec<(TriggerKind, &str)> {
+    let mut added = Vec::new();
+    let mut rest = src;
+    while let Some(start) = rest.find("Add") {
+        let kind = match rest[start + 3..].chars().next() {
+            Some('N') => TriggerKind::AddN,
+            Some('E') => TriggerKind::AddE,
+            _ => {
+                rest = &rest[start + 3..];
+                continue;
+            }
+        };
+        rest = &rest[start + 4..];
+        if let Some(item) = rest.trim_start().strip_prefix('<')
+            && let Some(end) = item.find('>')
+        {
+            added.push((kind, item[..end].trim()));
+        }
+    }
+    added
+}
+
+fn push_trigger_err(ctx: &mut Ctx, trigger: &Trigger, loc: Loc, reason: &str, fix: &str) {
+    push_query_err(
+        ctx,
+        &trigger.query,
+        loc,
+        ErrorCode::E113,
+        ErrorCode::E113_message(&trigger.name(), reason),
+        ErrorCode::E113_hint(fix),
+    );
+}
+
+/// Check every trigger and generate the functions running them. Only one trigger may run on
+/// each write, and triggers can't run themselves again, directly or through other triggers.
+pub(crate) fn validate_triggers<'a>(ctx: &mut Ctx<'a>) {
+    let mut seen = HashSet::new();
+    let mut valid = Vec::new();
+    for trigger in &ctx.src.triggers {
+        if !seen.insert((trigger.kind, trigger.item_type.1.as_str())) {
+            push_trigger_err(
+                ctx,
+                trigger,
+                trigger.loc.clone(),
+                &format!(
+                    "another trigger already runs on {}<{}>",
+                    trigger.kind, trigger.item_type.1
+                ),
+                "merge the statements of both triggers into one",
+            );
+            continue;
+        }
+        if let Some(generated) = validate_trigger(ctx, trigger) {
+            valid.push((trigger, generated));
+        }
+    }
+
+    let declared: HashMap<(TriggerKind, &str), &Trigger> = valid
+        .iter()
+        .map(|(trigger, _)| ((trigger.kind, trigger.item_type.1.as_str()), *trigger))
+        .collect();
+    for (trigger, generated) in valid {
+        if let Some(cycle) = find_cycle(&declared, trigger) {
+            push_trigger_err(
+                ctx,
+                trigger,
+                trigger.loc.clone(),
+                &format!("it runs itself again through {}", cycle.join(" -> ")),
+                "remove the write that runs the trigger again",
+            );
+            continue;
+        }
+        ctx.triggers
+            .insert((trigger.kind, trigger.item_type.1.as_str()), trigger);
+        ctx.output.triggers.push(generated);
+    }
+}
+
+/// The chain of writes through which `trigger` ends up running itself, if it does
+fn find_cycle(
+    declared: &HashMap<(TriggerKind, &str), &Trigger>,
+    trigger: &Trigger,
+) -> Option<Vec<String>> {
+    fn visit<'a>(
+        declared: &HashMap<(TriggerKind, &str), &'a Trigger>,
+        target: (TriggerKind, &str),
+        current: &'a Trigger,
+        path: &mut Vec<String>,
+        visited: &mut HashSet<(TriggerKind, &'a str)>,
+    ) -> bool {
+        for (kind, item) in added_items(&current.query.original_query) {
+            path.push(format!("{kind}<{item}>"));
+            if (kind, item) == target {
+                return true;
+            }
+            if let Some(next) = declared.get(&(kind, item))
+                && visited.insert((next.kind, next.item_type.1.as_str()))
+                && visit(declared, target, next, path, visited)
+            {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let target = (trigger.kind, trigger.item_type.1.as_str());
+    let mut path = vec![format!("{}<{}>", trigger.kind, trigger.item_type.1)];
+    visit(declared, target, trigger, &mut path, &mut HashSet::new()).then_some(path)
+}
+
+fn validate_trigger<'a>(ctx: &mut Ctx<'a>, trigger: &'a Trigger) -> Option<GeneratedTrigger> {
+    let query = &trigger.query;
+    let (item_type, loc) = (trigger.item_type.1.as_str(), trigger.item_type.0.clone());
+    let ty = match trigger.kind {
+        TriggerKind::AddN if ctx.node_set.contains(item_type) => {
+            Type::Node(Some(item_type.to_string()))
+        }
+        TriggerKind::AddE if ctx.edge_map.contains_key(item_type) => {
+            Type::Edge(Some(item_type.to_string()))
+        }
+        TriggerKind::AddN => {
+            generate_error!(ctx, query, loc, E101, item_type);
+            return None;
+        }
+        TriggerKind::AddE => {
+            generate_error!(ctx, query, loc, E102, item_type);
+            return None;
+        }
+    };
+
+    let mut scope: HashMap<&str, VariableInfo> = HashMap::new();
+    if let Some((loc, binding)) = &trigger.binding {
+        if !is_valid_identifier(ctx, query, loc.clone(), binding) {
+            return None;
+        }
+        scope.insert(binding.as_str(), VariableInfo::new(ty, true));
+    }
+
+    let errors = ctx.diagnostics.len();
+    let mut gen_query = GeneratedQuery {
+        name: query.name.clone(),
+        ..Default::default()
+    };
+    let mut statements = Vec::new();
+    for stmt in &query.statements {
+        statements.push(validate_statements(
+            ctx,
+            &mut scope,
+            query,
+            &mut gen_query,
+            stmt,
+        )?);
+    }
+    if ctx.diagnostics.len() > errors {
+        return None;
+    }
+    if !gen_query.hoisted_embedding_calls.is_empty() {
+        push_trigger_err(
+            ctx,
+            trigger,
+            trigger.loc.clone(),
+            "triggers run inside the write and cannot call `Embed`",
+            "embed the text in the query that writes the item",
+        );
+        return None;
+    }
+
+    Some(GeneratedTrigger {
+        kind: trigger.kind,
+        item_type: item_type.to_string(),
+        binding: trigger.binding.as_ref().map(|(_, name)| name.clone()),
+        statements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::added_items;
+    use crate::helixc::{
+        analyzer::error_codes::ErrorCode,
+        parser::{HelixParser, types::TriggerKind, write_to_temp_file},
+    };
+
+    const SCHEMA: &str = r#"
+        N::Order { total: F64 }
+        N::Customer { email: String }
+        N::Audit { note: String }
+        E::PlacedBy { From: Order, To: Customer }
+    "#;
+
+    fn analyze(source: &str) -> (Vec<crate::helixc::analyzer::diagnostic::Diagnostic>, String) {
+        let content = write_to_temp_file(vec![SCHEMA, source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        (diagnostics, generated.to_string())
+    }
+
+    fn trigger_errors(
+        diagnostics: &[crate::helixc::analyzer::diagnostic::Diagnostic],
+    ) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E113)
+            .map(|d| d.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_added_items() {
+        assert_eq!(
+            added_items(
+                "o <- AddN<Order>({total: 1.0}) AddE < PlacedBy >::From(o)::To(c) N<Order>"
+            ),
+            [
+                (TriggerKind::AddN, "Order"),
+                (TriggerKind::AddE, "PlacedBy")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trigger_generates_function_run_after_the_write() {
+        let (diagnostics, generated) = analyze(
+            r#"
+            ON AddN<Order> AS order DO {
+                customer <- N<Customer>::WHERE(_::{email}::EQ("walk-in@example.com"))::FIRST
+                AddE<PlacedBy>::From(order)::To(customer)
+            }
+
+            QUERY place(total: F64) =>
+                order <- AddN<Order>({total: total})
+                RETURN order
+        "#,
+        );
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert!(generated.contains("#[trigger(AddN, Order)]"));
+        assert!(generated.contains("pub fn trigger_AddN_Order<'db>"));
+        assert!(
+            generated.contains(
+                "let order = G::new(&db, &txn, &arena).n_from_id(&id).collect_to_obj()?;"
+            )
+        );
+        assert!(generated.contains(".add_edge("));
+        // Writes that run the trigger invalidate what the trigger writes too
+        assert!(
+            generated
+                .contains(r#"#[handler(is_write, labels = ["Customer", "Order", "PlacedBy"])]"#)
+        );
+    }
+
+    #[test]
+    fn test_trigger_item_type_must_exist() {
+        let (diagnostics, _) = analyze(
+            r#"
+            ON AddN<Invoice> DO {
+                AddN<Audit>({note: "invoice"})
+            }
+            ON AddE<Customer> DO {
+                AddN<Audit>({note: "customer"})
+            }
+        "#,
+        );
+
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E101));
+        assert!(diagnostics.iter().any(|d| d.error_code == ErrorCode::E102));
+    }
+
+    #[test]
+    fn test_one_trigger_per_write() {
+        let (diagnostics, _) = analyze(
+            r#"
+            ON AddN<Order> DO {
+                AddN<Audit>({note: "first"})
+            }
+            ON AddN<Order> DO {
+                AddN<Audit>({note: "second"})
+            }
+        "#,
+        );
+
+        let errors = trigger_errors(&diagnostics);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].contains("already runs on AddN<Order>"));
+    }
+
+    #[test]
+    fn test_trigger_cannot_run_itself() {
+        let (diagnostics, generated) = analyze(
+            r#"
+            ON AddN<Order> DO {
+                AddN<Audit>({note: "order"})
+            }
+            ON AddN<Audit> DO {
+                AddN<Order>({total: 0.0})
+            }
+            ON AddN<Customer> DO {
+                AddN<Customer>({email: "again"})
+            }
+        "#,
+        );
+
+        let errors = trigger_errors(&diagnostics);
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("AddN<Order> -> AddN<Audit> -> AddN<Order>"))
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("AddN<Customer> -> AddN<Customer>"))
+        );
+        assert!(!generated.contains("#[trigger("));
+    }
+
+    #[test]
+    fn test_trigger_binding_is_typed() {
+        let (diagnostics, _) = analyze(
+            r#"
+            ON AddE<PlacedBy> AS placed DO {
+                AddN<Audit>({note: placed::{missing}})
+            }
+        "#,
+        );
+
+        assert!(!diagnostics.is_empty());
+        assert!(trigger_errors(&diagnostics).is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! Semantic analyzer for materialized views.
+
+use crate::{
+    helixc::{
+        analyzer::{
+            Ctx,
+            error_codes::ErrorCode,
+            errors::push_query_err,
+            methods::{query_validation::query_labels, traversal_validation::validate_traversal},
+            types::Type,
+        },
+        generator::{
+            queries::Query as GeneratedQuery,
+            traversal_steps::{ShouldCollect, Traversal as GeneratedTraversal},
+            views::View as GeneratedView,
+        },
+        parser::{location::Loc, types::*},
+    },
+    utils::cron::parse_cron,
+};
+use std::collections::HashMap;
+
+/// What queries reading a view need to know about it
+pub(crate) struct ViewInfo {
+    /// Type of the nodes in the view
+    pub(crate) node_type: String,
+    /// Labels the view reads, `None` if it may read any label
+    pub(crate) labels: Option<Vec<String>>,
+}
+
+fn push_view_err(ctx: &mut Ctx, view: &View, loc: Loc, reason: &str, fix: &str) {
+    push_query_err(
+        ctx,
+        &view.query,
+        loc,
+        ErrorCode::E112,
+        ErrorCode::E112_message(&view.name.1, reason),
+        ErrorCode::E112_hint(fix),
+    );
+}
+
+/// Check a view and generate the function refreshing it. Returns what queries reading the
+/// view need to know, unless the view is invalid.
+pub(crate) fn validate_view<'a>(ctx: &mut Ctx<'a>, view: &'a View) -> Option<ViewInfo> {
+    let name = view.name.1.as_str();
+    if ctx.node_set.contains(name) || ctx.edge_map.contains_key(name) || ctx.vector_set.contains(name)
+    {
+        push_view_err(
+            ctx,
+            view,
+            view.name.0.clone(),
+            "the name is already used by a schema item",
+            "rename the view",
+        );
+        return None;
+    }
+    if ctx.views.contains_key(name) {
+        push_view_err(
+            ctx,
+            view,
+            view.name.0.clone(),
+            "another view has the same name",
+            "rename the view or remove the duplicate",
+        );
+        return None;
+    }
+    if let Some((loc, cron)) = &view.refresh_cron
+        && let Err(e) = parse_cron(cron)
+    {
+        push_view_err(
+            ctx,
+            view,
+            loc.clone(),
+            &format!("invalid refresh cron expression `{cron}`: {e}"),
+            "use a cron expression such as `*/5 * * * *`",
+        );
+        return None;
+    }
+    if let StartNode::Node { node_type, .. } = &view.traversal.start
+        && !ctx.node_set.contains(node_type.as_str())
+        && ctx.src.views.iter().any(|other| other.name.1 == *node_type)
+    {
+        push_view_err(
+            ctx,
+            view,
+            view.traversal.loc.clone(),
+            "views cannot read other views",
+            "start the view from a node type",
+        );
+        return None;
+    }
+
+    let errors = ctx.diagnostics.len();
+    let mut gen_query = GeneratedQuery {
+        name: view.name.1.clone(),
+        ..Default::default()
+    };
+    let mut gen_traversal = GeneratedTraversal::default();
+    let ty = validate_traversal(
+        ctx,
+        &view.traversal,
+        &mut HashMap::new(),
+        &view.query,
+        None,
+        &mut gen_traversal,
+        &mut gen_query,
+    )?;
+    if ctx.diagnostics.len() > errors {
+        return None;
+    }
+    if gen_query.is_mut {
+        push_view_err(
+            ctx,
+            view,
+            view.traversal.loc.clone(),
+            "views cannot write",
+            "remove the steps adding, updating or dropping items",
+        );
+        return None;
+    }
+    let Type::Nodes(Some(node_type)) = ty else {
+        push_view_err(
+            ctx,
+            view,
+            view.traversal.loc.clone(),
+            &format!("views hold nodes, but this traversal returns {}", ty.kind_str()),
+            "end the traversal on the nodes the view should hold",
+        );
+        return None;
+    };
+    gen_traversal.should_collect = ShouldCollect::ToVec;
+
+    let labels = query_labels(ctx, &view.query);
+    ctx.output.views.push(GeneratedView {
+        name: view.name.1.clone(),
+        traversal: gen_traversal,
+        labels: labels.clone(),
+        refresh_cron: view.refresh_cron.as_ref().map(|(_, cron)| cron.clone()),
+    });
+    Some(ViewInfo { node_type, labels })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helixc::{
+        analyzer::error_codes::ErrorCode,
+        parser::{HelixParser, write_to_temp_file},
+    };
+
+    const SCHEMA: &str = r#"
+        N::Post { title: String, likes: I64 }
+        N::User { name: String }
+        E::Wrote { From: User, To: Post }
+    "#;
+
+    fn analyze(source: &str) -> (Vec<crate::helixc::analyzer::diagnostic::Diagnostic>, String) {
+        let content = write_to_temp_file(vec![SCHEMA, source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, generated) = crate::helixc::analyzer::analyze(&parsed).unwrap();
+        (diagnostics, generated.to_string())
+    }
+
+    #[test]
+    fn test_view_generates_refresh_function_and_source_step() {
+        let (diagnostics, generated) = analyze(
+            r#"
+            VIEW PopularPosts AS N<Post>::WHERE(_::{likes}::GT(100))::ORDER<Desc>(_::{likes})
+
+            #[cache(ttl: 1m)]
+            QUERY popular() =>
+                posts <- N<PopularPosts>::RANGE(0, 10)
+                RETURN posts
+        "#,
+        );
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert!(generated.contains(r#"#[view(PopularPosts, labels = ["Post"])]"#));
+        assert!(generated.contains("pub fn view_PopularPosts<'db>"));
+        assert!(generated.contains(r#".n_from_view("PopularPosts")"#));
+        // Cached reads of the view are invalidated with the labels it reads
+        assert!(generated.contains(r#"#[handler(cache_ttl_ms = 60000, labels = ["Post"])]"#));
+    }
+
+    #[test]
+    fn test_view_refresh_schedule() {
+        let (diagnostics, generated) = analyze(
+            r#"
+            #[refresh(cron: "*/5 * * * *")]
+            VIEW Authors AS N<Post>::In<Wrote>
+        "#,
+        );
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert!(generated.contains(
+            r#"#[view(Authors, labels = ["Post", "User", "Wrote"], refresh = "*/5 * * * *")]"#
+        ));
+
+        let (diagnostics, _) = analyze(
+            r#"
+            #[refresh(cron: "every five minutes")]
+            VIEW Authors AS N<Post>::In<Wrote>
+        "#,
+        );
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.error_code == ErrorCode::E112 && d.message.contains("cron")));
+    }
+
+    #[test]
+    fn test_view_must_hold_nodes() {
+        let (diagnostics, _) = analyze(
+            r#"
+            VIEW PostCount AS N<Post>::COUNT
+        "#,
+        );
+
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.error_code == ErrorCode::E112 && d.message.contains("hold nodes")));
+    }
+
+    #[test]
+    fn test_view_name_must_be_unique() {
+        let (diagnostics, _) = analyze(
+            r#"
+            VIEW Post AS N<Post>
+            VIEW Recent AS N<Post>
+            VIEW Recent AS N<Post>::RANGE(0, 10)
+        "#,
+        );
+
+        let reasons: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E112)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(reasons.len(), 2, "{reasons:?}");
+        assert!(reasons[0].contains("schema item"));
+        assert!(reasons[1].contains("another view"));
+    }
+
+    #[test]
+    fn test_view_cannot_be_read_by_id_or_from_another_view() {
+        let (diagnostics, _) = analyze(
+            r#"
+            VIEW Recent AS N<Post>::RANGE(0, 10)
+            VIEW RecentAuthors AS N<Recent>::In<Wrote>
+
+            QUERY get(id: ID) =>
+                post <- N<Recent>(id)
+                RETURN post
+        "#,
+        );
+
+        let reasons: Vec<&str> = diagnostics
+            .iter()
+            .filter(|d| d.error_code == ErrorCode::E112)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(reasons.len(), 2, "{reasons:?}");
+        assert!(reasons.iter().any(|r| r.contains("other views")));
+        assert!(reasons.iter().any(|r| r.contains("read whole")));
+    }
+}
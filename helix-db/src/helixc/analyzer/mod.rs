@@ -12,16 +12,22 @@ use crate::{
         analyzer::{
             diagnostic::Diagnostic,
             methods::{
+                complexity_validation::check_query_complexity,
                 migration_validation::validate_migration,
                 query_validation::validate_query,
                 schema_methods::{SchemaVersionMap, build_field_lookups, check_schema},
+                trigger_validation::validate_triggers,
+                view_validation::{ViewInfo, validate_view},
             },
             types::Type,
         },
         generator::Source as GeneratedSource,
         parser::{
             errors::ParserError,
-            types::{EdgeSchema, ExpressionType, Field, Query, ReturnType, Source},
+            types::{
+                EdgeSchema, Embed, ExpressionType, Field, Query, ReturnType, Source, Trigger,
+                TriggerKind,
+            },
         },
     },
 };
@@ -33,11 +39,26 @@ use std::{
     collections::{HashMap, HashSet},
 };
 
+pub use methods::complexity_validation::DEFAULT_COMPLEXITY_BUDGET;
+
 pub fn analyze(src: &Source) -> Result<(Vec<Diagnostic>, GeneratedSource), ParserError> {
+    analyze_with_complexity_budget(src, None)
+}
+
+/// [`analyze`], also failing queries whose estimated worst-case expansion exceeds `budget`
+pub fn analyze_with_complexity_budget(
+    src: &Source,
+    budget: Option<u64>,
+) -> Result<(Vec<Diagnostic>, GeneratedSource), ParserError> {
     let mut ctx = Ctx::new(src)?;
     ctx.check_schema()?;
     ctx.check_schema_migrations();
+    ctx.check_views();
+    ctx.check_triggers();
     ctx.check_queries();
+    if let Some(budget) = budget {
+        check_query_complexity(&mut ctx, budget);
+    }
     Ok((ctx.diagnostics, ctx.output))
 }
 
@@ -60,7 +81,13 @@ pub(crate) struct Ctx<'a> {
     pub(super) node_fields: IndexMap<&'a str, IndexMap<&'a str, Cow<'a, Field>>>,
     pub(super) edge_fields: IndexMap<&'a str, IndexMap<&'a str, Cow<'a, Field>>>,
     pub(super) vector_fields: IndexMap<&'a str, IndexMap<&'a str, Cow<'a, Field>>>,
+    /// Default embedding model of each vector type declared with `#[model(...)]`
+    pub(super) vector_models: HashMap<&'a str, &'a str>,
     pub(super) all_schemas: SchemaVersionMap<'a>,
+    /// Views that passed analysis, which queries read like node types
+    pub(super) views: HashMap<&'a str, ViewInfo>,
+    /// Triggers that passed analysis, by the write they run after
+    pub(super) triggers: HashMap<(TriggerKind, &'a str), &'a Trigger>,
     pub(super) diagnostics: Vec<Diagnostic>,
     pub(super) output: GeneratedSource,
 }
@@ -95,6 +122,12 @@ impl<'a> Ctx<'a> {
                 .iter()
                 .map(|v| v.name.as_str())
                 .collect(),
+            vector_models: src
+                .get_latest_schema()?
+                .vector_schemas
+                .iter()
+                .filter_map(|v| Some((v.name.as_str(), v.model.as_deref()?)))
+                .collect(),
             edge_map: src
                 .get_latest_schema()?
                 .edge_schemas
@@ -105,6 +138,8 @@ impl<'a> Ctx<'a> {
             edge_fields,
             vector_fields,
             all_schemas,
+            views: HashMap::new(),
+            triggers: HashMap::new(),
             src,
             diagnostics: Vec::new(),
             output: GeneratedSource {
@@ -142,6 +177,25 @@ impl<'a> Ctx<'a> {
         }
     }
 
+    /// Model an `Embed` into `vector_type` calls: the one it names, else its query's
+    /// `#[model(...)]`, else the vector type's default. `None` leaves the instance's model.
+    pub(super) fn embedding_model(
+        &self,
+        embed: &Embed,
+        query_model: &Option<String>,
+        vector_type: Option<&str>,
+    ) -> Option<String> {
+        embed
+            .model
+            .clone()
+            .or_else(|| query_model.clone())
+            .or_else(|| {
+                vector_type
+                    .and_then(|ty| self.vector_models.get(ty))
+                    .map(|model| model.to_string())
+            })
+    }
+
     // ---------- Pass #1: schema --------------------------
     /// Validate that every edge references declared node types.
     pub(super) fn check_schema(&mut self) -> Result<(), ParserError> {
@@ -155,6 +209,20 @@ impl<'a> Ctx<'a> {
         }
     }
 
+    // ---------- Pass #1.75: views --------------------------
+    pub(super) fn check_views(&mut self) {
+        for v in &self.src.views {
+            if let Some(info) = validate_view(self, v) {
+                self.views.insert(v.name.1.as_str(), info);
+            }
+        }
+    }
+
+    // ---------- Pass #1.8: triggers --------------------------
+    pub(super) fn check_triggers(&mut self) {
+        validate_triggers(self);
+    }
+
     // ---------- Pass #2: queries -------------------------
     pub(super) fn check_queries(&mut self) {
         for q in &self.src.queries {
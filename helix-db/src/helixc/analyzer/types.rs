@@ -260,6 +260,9 @@ pub enum Type {
     Anonymous(Box<Type>),
     Count,
     Boolean,
+    Subgraph,
+    Paths,
+    Buckets,
     Unknown,
 }
 
@@ -278,6 +281,9 @@ impl Type {
             Type::Array(_) => "array",
             Type::Count => "count",
             Type::Boolean => "boolean",
+            Type::Subgraph => "subgraph",
+            Type::Paths => "paths",
+            Type::Buckets => "buckets",
             Type::Unknown => "unknown",
             Type::Anonymous(ty) => ty.kind_str(),
         }
@@ -297,6 +303,9 @@ impl Type {
             Type::Array(ty) => ty.get_type_name(),
             Type::Count => "count".to_string(),
             Type::Boolean => "boolean".to_string(),
+            Type::Subgraph => "subgraph".to_string(),
+            Type::Paths => "paths".to_string(),
+            Type::Buckets => "buckets".to_string(),
             Type::Unknown => "unknown".to_string(),
             Type::Object(fields) => {
                 let field_names = fields.keys().cloned().collect::<Vec<_>>();
@@ -328,7 +337,6 @@ impl Type {
         }
     }
 
-    #[allow(dead_code)]
     pub fn is_numeric(&self) -> bool {
         matches!(
             self,
@@ -371,6 +379,9 @@ impl Type {
             Type::Object(fields) => Type::Object(fields),
             Type::Count => Type::Count,
             Type::Boolean => Type::Boolean,
+            Type::Subgraph => Type::Subgraph,
+            Type::Paths => Type::Paths,
+            Type::Buckets => Type::Buckets,
             Type::Unknown => Type::Unknown,
             Type::Anonymous(inner) => Type::Anonymous(Box::new(inner.into_single())),
             Type::Aggregate(info) => Type::Aggregate(info),
@@ -395,6 +406,9 @@ impl PartialEq for Type {
             (Type::Scalar(ft), Type::Scalar(other_ft)) => ft == other_ft,
             (Type::Object(fields), Type::Object(other_fields)) => fields == other_fields,
             (Type::Boolean, Type::Boolean) => true,
+            (Type::Subgraph, Type::Subgraph) => true,
+            (Type::Paths, Type::Paths) => true,
+            (Type::Buckets, Type::Buckets) => true,
             (Type::Unknown, Type::Unknown) => true,
             (Type::Anonymous(inner), Type::Anonymous(other_inner)) => inner == other_inner,
             (Type::Node(name), Type::Node(other_name)) => name == other_name,
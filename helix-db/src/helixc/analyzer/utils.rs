@@ -172,6 +172,7 @@ pub(super) fn type_in_scope(
     }
 }
 
+/// Checks the fields written to an item exist and aren't computed
 pub(super) fn field_exists_on_item_type(
     ctx: &mut Ctx,
     original_query: &Query,
@@ -179,7 +180,8 @@ pub(super) fn field_exists_on_item_type(
     fields: Vec<(&str, &Loc)>,
 ) {
     for (key, loc) in fields {
-        if !item_type.item_fields_contains_key(ctx, key) {
+        if !item_type.item_fields_contains_key(ctx, key) || is_score_property(ctx, &item_type, key)
+        {
             generate_error!(
                 ctx,
                 original_query,
@@ -189,10 +191,32 @@ pub(super) fn field_exists_on_item_type(
                 item_type.kind_str(),
                 &item_type.get_type_name()
             );
+        } else if is_computed_field(ctx, &item_type, key) {
+            generate_error!(
+                ctx,
+                original_query,
+                loc.clone(),
+                E211,
+                key,
+                item_type.kind_str(),
+                &item_type.get_type_name()
+            );
         }
     }
 }
 
+/// Whether a field of a node type is computed from the graph
+pub(super) fn is_computed_field(ctx: &Ctx, item_type: &Type, key: &str) -> bool {
+    match item_type {
+        Type::Node(Some(node_type)) | Type::Nodes(Some(node_type)) => ctx
+            .node_fields
+            .get(node_type.as_str())
+            .and_then(|fields| fields.get(key))
+            .is_some_and(|field| field.computed.is_some()),
+        _ => false,
+    }
+}
+
 #[allow(unused)]
 pub(super) fn get_singular_type(ty: Type) -> Type {
     match ty {
@@ -241,10 +265,13 @@ pub(super) fn get_field_type_from_item_fields(
     item_type.get_field_type_from_item_fields(ctx, name)
 }
 
-pub(super) fn gen_property_access(name: &str) -> Step {
+pub(super) fn gen_property_access(ctx: &Ctx, item_type: &Type, name: &str) -> Step {
     match name {
         "id" | "ID" | "Id" => Step::ReservedPropertyAccess(ReservedProp::Id),
         "label" | "Label" => Step::ReservedPropertyAccess(ReservedProp::Label),
+        n if is_score_property(ctx, item_type, n) => {
+            Step::ReservedPropertyAccess(ReservedProp::from_score_property(n))
+        }
         // "version" | "Version" => Step::ReservedPropertyAccess(ReservedProp::Version),
         // "from_node" | "fromNode" | "FromNode" => Step::ReservedPropertyAccess(ReservedProp::FromNode),
         // "to_node" | "toNode" | "ToNode" => Step::ReservedPropertyAccess(ReservedProp::ToNode),
@@ -285,6 +312,8 @@ pub(super) struct VariableInfo {
     pub excluded_fields: Vec<String>,
     pub has_spread: bool,
     pub nested_traversals: std::collections::HashMap<String, crate::helixc::generator::traversal_steps::NestedTraversalInfo>,
+    /// Whether the items carry relevance scores that rerankers can order by
+    pub scored: bool,
 }
 
 impl VariableInfo {
@@ -301,6 +330,7 @@ impl VariableInfo {
             excluded_fields: Vec::new(),
             has_spread: false,
             nested_traversals: std::collections::HashMap::new(),
+            scored: false,
         }
     }
 
@@ -317,6 +347,7 @@ impl VariableInfo {
             excluded_fields: Vec::new(),
             has_spread: false,
             nested_traversals: std::collections::HashMap::new(),
+            scored: false,
         }
     }
 
@@ -333,6 +364,7 @@ impl VariableInfo {
             excluded_fields: Vec::new(),
             has_spread: false,
             nested_traversals: std::collections::HashMap::new(),
+            scored: false,
         }
     }
 
@@ -351,6 +383,59 @@ impl VariableInfo {
     }
 }
 
+/// The type of `name` if it is one of the reserved properties holding the relevance scores of
+/// search results
+pub(super) fn score_property_type(name: &str) -> Option<FieldType> {
+    match name {
+        "score" | "bm25_score" | "vector_distance" | "rerank_score" => Some(FieldType::F64),
+        "scores" => Some(FieldType::Object(HashMap::from(
+            ["score", "bm25_score", "vector_distance", "rerank_score"]
+                .map(|score| (score.to_string(), FieldType::F64)),
+        ))),
+        _ => None,
+    }
+}
+
+/// Whether `name` reads a relevance score of `item_type`'s items rather than one of their
+/// fields: nodes that declare a field called `score` keep it
+pub(super) fn is_score_property(ctx: &Ctx, item_type: &Type, name: &str) -> bool {
+    score_property_type(name).is_some()
+        && match item_type {
+            Type::Node(Some(node_type)) | Type::Nodes(Some(node_type)) => !ctx
+                .node_fields
+                .get(node_type.as_str())
+                .is_some_and(|fields| fields.contains_key(name)),
+            Type::Vector(_) | Type::Vectors(_) => true,
+            _ => false,
+        }
+}
+
+/// Whether the items `traversal` yields have relevance scores: search results, directly or
+/// through a variable, followed only by steps that pass them on with their scores
+pub(super) fn yields_scored_items(
+    traversal: &crate::helixc::generator::traversal_steps::Traversal,
+    scope: &HashMap<&str, VariableInfo>,
+) -> bool {
+    use crate::helixc::generator::source_steps::SourceStep;
+
+    let mut scored = match traversal.source_step.inner() {
+        SourceStep::SearchVector(_) | SourceStep::SearchBM25(_) | SourceStep::SearchHybrid(_) => {
+            true
+        }
+        SourceStep::Identifier(name) => scope
+            .get(name.inner().as_str())
+            .is_some_and(|var_info| var_info.scored),
+        _ => false,
+    };
+    for step in &traversal.steps {
+        scored = match step.inner() {
+            Step::SearchVector(_) => true,
+            step => scored && step.keeps_scores(),
+        };
+    }
+    scored
+}
+
 #[allow(unused)]
 pub(super) trait VariableAccess {
     fn get_variable_name(&self) -> String;
@@ -388,7 +473,7 @@ impl FieldLookup for Type {
                 .get(node_type.as_str())
                 .map(|fields| match key {
                     "id" | "ID" | "label" => true,
-                    _ => fields.contains_key(key),
+                    _ => fields.contains_key(key) || score_property_type(key).is_some(),
                 })
                 .unwrap_or(true),
             Type::Edge(Some(edge_type)) | Type::Edges(Some(edge_type)) => ctx
@@ -403,8 +488,8 @@ impl FieldLookup for Type {
                 .vector_fields
                 .get(vector_type.as_str())
                 .map(|fields| match key {
-                    "id" | "ID" | "label" | "data" | "score" => true,
-                    _ => fields.contains_key(key),
+                    "id" | "ID" | "label" | "data" | "reembedded" => true,
+                    _ => fields.contains_key(key) || score_property_type(key).is_some(),
                 })
                 .unwrap_or(true),
             _ => {
@@ -425,7 +510,7 @@ impl FieldLookup for Type {
                     .get(node_type.as_str())
                     .map(|fields| match key {
                         "id" | "ID" | "label" => true,
-                        _ => fields.contains_key(key),
+                        _ => fields.contains_key(key) || score_property_type(key).is_some(),
                     })
                     .unwrap_or(true),
                 node_type.as_str(),
@@ -444,8 +529,8 @@ impl FieldLookup for Type {
                 ctx.vector_fields
                     .get(vector_type.as_str())
                     .map(|fields| match key {
-                        "id" | "ID" | "label" | "data" | "score" => true,
-                        _ => fields.contains_key(key),
+                        "id" | "ID" | "label" | "data" | "reembedded" => true,
+                        _ => fields.contains_key(key) || score_property_type(key).is_some(),
                     })
                     .unwrap_or(true),
                 vector_type.as_str(),
@@ -474,7 +559,7 @@ impl FieldLookup for Type {
                     _ => fields
                         .get(key)
                         .map(|field| Some(field.field_type.clone()))
-                        .unwrap_or(None),
+                        .unwrap_or_else(|| score_property_type(key)),
                 })
                 .unwrap_or(None),
             Type::Edge(Some(edge_type)) | Type::Edges(Some(edge_type)) => ctx
@@ -498,11 +583,9 @@ impl FieldLookup for Type {
                     "id" | "ID" => Some(FieldType::Uuid),
                     "label" => Some(FieldType::String),
                     "data" => Some(FieldType::Array(Box::new(FieldType::F64))),
-                    "score" => Some(FieldType::F64),
-                    _ => fields
-                        .get(key)
-                        .map(|field| Some(field.field_type.clone()))
-                        .unwrap_or(None),
+                    "reembedded" => Some(FieldType::Boolean),
+                    _ => score_property_type(key)
+                        .or_else(|| fields.get(key).map(|field| field.field_type.clone())),
                 })
                 .unwrap_or(None),
             _ => {
@@ -0,0 +1,58 @@
+//! Query parameter and return types as clients of the gateway see them, shared by the
+//! generators of client bindings and API descriptions.
+
+use std::collections::HashSet;
+
+use crate::helixc::generator::{
+    return_values::RustFieldType,
+    utils::{GenRef, GeneratedType, RustType},
+};
+
+/// Spells the types of query parameters and returns for a client. IDs travel as UUID strings
+/// and dates as RFC 3339 strings, as in JSON requests. Properties are returned untyped by the
+/// gateway, so they, counts, aggregates and types the generator doesn't describe are any JSON
+/// value.
+pub trait ClientTypes {
+    type Type;
+
+    fn scalar(&self, t: &RustType) -> Self::Type;
+
+    fn list(&self, element: Self::Type) -> Self::Type;
+
+    /// A parameter object the generator describes, by name
+    fn object(&self, name: String) -> Self::Type;
+
+    fn any(&self) -> Self::Type;
+
+    /// A returned value that may be missing. Most clients' any value already allows null.
+    fn optional(&self, value: Self::Type) -> Self::Type {
+        value
+    }
+
+    /// Type of a parameter, where `objects` are the parameter objects the generator describes
+    fn parameter(&self, field_type: &GeneratedType, objects: &HashSet<&str>) -> Self::Type {
+        match field_type {
+            GeneratedType::RustType(t) => self.scalar(t),
+            GeneratedType::Vec(inner) => self.list(self.parameter(inner, objects)),
+            GeneratedType::Object(name) | GeneratedType::Variable(name) => {
+                let name = name.to_string();
+                match objects.contains(name.as_str()) {
+                    true => self.object(name),
+                    false => self.any(),
+                }
+            }
+        }
+    }
+
+    /// Type of a returned field that isn't a nested struct
+    fn returned(&self, field_type: &RustFieldType) -> Self::Type {
+        match field_type {
+            RustFieldType::OptionValue => self.optional(self.any()),
+            RustFieldType::Value | RustFieldType::TraversalValue => self.any(),
+            RustFieldType::Vec(inner) => self.list(self.returned(inner)),
+            RustFieldType::RefArray(ty) => self.list(self.scalar(ty)),
+            RustFieldType::Primitive(GenRef::Unknown | GenRef::Id(_)) => self.any(),
+            RustFieldType::Primitive(ty) => self.scalar(ty.inner()),
+        }
+    }
+}
@@ -4,13 +4,24 @@
 //! Code generation for computed expression fields in RETURN statements.
 //!
 //! Handles expressions like `ADD(_::Out<HasRailwayCluster>::COUNT, _::Out<HasObjectCluster>::COUNT)`
-//! in object projections.
+//! in object projections, and user-defined function calls like `CALL boost(_::{rating})`,
+//! which the instance's `Udfs` evaluate. A failing call fails the query, so items with such
+//! fields are built in closures returning `Result`.
 //!
 //! Since `Value` implements standard math ops (`Add`, `Sub`, `Mul`, `Div`), the generated
 //! code can directly use operators on `Value` types without conversion.
 
 use crate::helixc::parser::types::{Expression, ExpressionType, MathFunction};
 
+/// Whether the expression calls a user-defined function
+pub fn calls_udf(expression: &Expression) -> bool {
+    match &expression.expr {
+        ExpressionType::UdfCall(_) => true,
+        ExpressionType::MathFunctionCall(call) => call.args.iter().any(calls_udf),
+        _ => false,
+    }
+}
+
 /// Generate Rust code for a computed expression field.
 ///
 /// This handles math function calls (ADD, SUB, MUL, etc.) where arguments
@@ -107,6 +118,18 @@ pub fn generate_computed_expression(expression: &Expression, item_var: &str) ->
                 _ => "Value::Empty /* unsupported math function */".to_string(),
             }
         }
+        ExpressionType::UdfCall(call) => {
+            let args: Vec<String> = call
+                .args
+                .iter()
+                .map(|arg| generate_computed_expression(arg, item_var))
+                .collect();
+            format!(
+                "Value::from(db.udfs.eval(\"{}\", &[{}])?)",
+                call.name,
+                args.join(", ")
+            )
+        }
         ExpressionType::Traversal(traversal) => {
             // Generate traversal code that returns a Value directly
             generate_traversal_value(traversal, item_var)
@@ -188,6 +211,17 @@ fn generate_traversal_value(
                 steps.push_str(".count_to_val()");
                 ends_with_count = true;
             }
+            StepType::Degree(degree) => {
+                steps.push_str(&format!(
+                    ".degree(\"{}\", DegreeDirection::{:?})",
+                    degree.label, degree.direction
+                ));
+                ends_with_count = true;
+            }
+            StepType::NeighborCount => {
+                steps.push_str(".neighbor_count()");
+                ends_with_count = true;
+            }
             StepType::Object(obj) => {
                 // Property access
                 if let Some(field) = obj.fields.first() {
@@ -206,7 +240,7 @@ fn generate_traversal_value(
     }
 
     if ends_with_count {
-        // COUNT traversals return Value directly
+        // COUNT, DEGREE and NEIGHBOR_COUNT traversals return Value directly
         format!(
             "G::from_iter(&db, &txn, std::iter::once({}.clone()), &arena){}",
             source_var, steps
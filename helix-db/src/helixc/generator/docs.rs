@@ -0,0 +1,497 @@
+//! Documentation of the compiled queries, for teams exposing an instance as an internal API.
+//!
+//! Every query gets a page with its doc comment, route, parameters, response and an example
+//! `curl` call, and an index page lists the queries. Parameter and response types come from
+//! the queries' JSON Schema documents, so the pages describe the same shapes as `helix
+//! generate json-schema`.
+
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::helixc::generator::{
+    Source,
+    json_schema::{JsonSchema, SchemaDocument, input_schema, output_schema},
+    queries::Query,
+};
+
+/// Markup the pages are written in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    #[default]
+    Markdown,
+    /// Standalone pages, for serving without a Markdown renderer
+    Html,
+}
+
+impl DocsFormat {
+    /// Extension of the page files
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocsFormat::Markdown => "md",
+            DocsFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for DocsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(DocsFormat::Markdown),
+            "html" => Ok(DocsFormat::Html),
+            _ => Err(format!(
+                "unknown docs format '{s}', expected 'markdown' or 'html'"
+            )),
+        }
+    }
+}
+
+impl Display for DocsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocsFormat::Markdown => write!(f, "markdown"),
+            DocsFormat::Html => write!(f, "html"),
+        }
+    }
+}
+
+/// A page of the documentation
+#[derive(Debug, Clone)]
+pub struct DocsPage {
+    /// File name, `index.<ext>` or `<query>.<ext>`
+    pub file_name: String,
+    pub content: String,
+}
+
+impl Source {
+    /// The index page, listing the queries by name, followed by a page per query. Example calls
+    /// are made against `base_url`.
+    pub fn to_docs(&self, format: DocsFormat, base_url: &str) -> Vec<DocsPage> {
+        let base_url = base_url.trim_end_matches('/');
+        let index = DocsPage {
+            file_name: format!("index.{}", format.extension()),
+            content: render(format, "Queries", &index_blocks(&self.queries, format)),
+        };
+        let pages = self.queries.iter().map(|query| DocsPage {
+            file_name: format!("{}.{}", query.name, format.extension()),
+            content: render(format, &query.name, &query_blocks(query, base_url)),
+        });
+        std::iter::once(index).chain(pages).collect()
+    }
+}
+
+/// Markup-independent content of a page
+enum Block {
+    Heading(u8, String),
+    Paragraph(String),
+    /// A paragraph set as code
+    Route(String),
+    Code(String),
+    Table {
+        headers: &'static [&'static str],
+        /// Leading columns set as code
+        code_columns: usize,
+        rows: Vec<Vec<String>>,
+    },
+    /// Queries linked to their pages, with their summaries
+    Links(Vec<(String, String, String)>),
+}
+
+fn index_blocks(queries: &[Query], format: DocsFormat) -> Vec<Block> {
+    let mut queries: Vec<_> = queries.iter().collect();
+    queries.sort_by(|a, b| a.name.cmp(&b.name));
+    let links = queries
+        .into_iter()
+        .map(|query| {
+            let summary = query
+                .doc
+                .as_deref()
+                .and_then(|doc| doc.lines().next())
+                .unwrap_or_default();
+            (
+                query.name.clone(),
+                format!("{}.{}", query.name, format.extension()),
+                summary.to_string(),
+            )
+        })
+        .collect();
+    vec![
+        Block::Heading(1, "Queries".to_string()),
+        Block::Links(links),
+    ]
+}
+
+fn query_blocks(query: &Query, base_url: &str) -> Vec<Block> {
+    let mut blocks = vec![Block::Heading(1, query.name.clone())];
+    if let Some(doc) = &query.doc {
+        blocks.extend(doc.split("\n\n").map(|p| Block::Paragraph(p.to_string())));
+    }
+    blocks.push(Block::Route(format!("POST {base_url}/{}", query.name)));
+    let kind = match query.is_mut {
+        true => "Writes to the graph.",
+        false => "Reads the graph.",
+    };
+    let mut facts = vec![kind.to_string()];
+    if !query.roles.is_empty() {
+        facts.push(format!(
+            "Callers need one of the roles {}.",
+            query.roles.join(", ")
+        ));
+    }
+    if let Some(ttl_ms) = query.cache_ttl_ms {
+        facts.push(format!("Results are cached for {ttl_ms} ms."));
+    }
+    blocks.push(Block::Paragraph(facts.join(" ")));
+
+    let input = input_schema(query);
+    blocks.push(Block::Heading(2, "Parameters".to_string()));
+    match object_rows(&input.schema, true) {
+        Some(rows) if !rows.is_empty() => blocks.push(Block::Table {
+            headers: &["Name", "Type", "Required"],
+            code_columns: 2,
+            rows,
+        }),
+        _ => blocks.push(Block::Paragraph("None.".to_string())),
+    }
+    def_blocks(&mut blocks, &input, true);
+
+    let output = output_schema(query);
+    blocks.push(Block::Heading(2, "Response".to_string()));
+    match object_rows(&output.schema, false) {
+        Some(rows) if !rows.is_empty() => blocks.push(Block::Table {
+            headers: &["Field", "Type"],
+            code_columns: 2,
+            rows,
+        }),
+        Some(_) => blocks.push(Block::Paragraph("An empty object.".to_string())),
+        None => blocks.push(Block::Paragraph("Any JSON value.".to_string())),
+    }
+    def_blocks(&mut blocks, &output, false);
+
+    blocks.push(Block::Heading(2, "Example".to_string()));
+    let mut curl = format!(
+        "curl -X POST {base_url}/{} \\\n  -H 'Content-Type: application/json'",
+        query.name
+    );
+    if !query.parameters.is_empty() {
+        let body = example_value(&input.schema, &input.defs, 0);
+        let _ = write!(curl, " \\\n  -d '{body}'");
+    }
+    blocks.push(Block::Code(curl));
+    blocks
+}
+
+/// A heading and table for each object the document defines
+fn def_blocks(blocks: &mut Vec<Block>, document: &SchemaDocument, with_required: bool) {
+    for (name, schema) in &document.defs {
+        blocks.push(Block::Heading(3, name.clone()));
+        blocks.push(Block::Table {
+            headers: match with_required {
+                true => &["Name", "Type", "Required"],
+                false => &["Field", "Type"],
+            },
+            code_columns: 2,
+            rows: object_rows(schema, with_required).unwrap_or_default(),
+        });
+    }
+}
+
+/// The name, type and, when asked for, whether it's required of each property of an object
+/// schema, or `None` when the schema isn't an object
+fn object_rows(schema: &JsonSchema, with_required: bool) -> Option<Vec<Vec<String>>> {
+    let properties = schema.properties.as_ref()?;
+    let required = schema.required.as_deref().unwrap_or_default();
+    let rows = properties
+        .iter()
+        .map(|(name, property)| {
+            let mut row = vec![name.clone(), type_name(property)];
+            if with_required {
+                let required = required.contains(name);
+                row.push(if required { "yes" } else { "no" }.to_string());
+            }
+            row
+        })
+        .collect();
+    Some(rows)
+}
+
+fn type_name(schema: &JsonSchema) -> String {
+    if let Some(reference) = &schema.reference {
+        return reference.trim_start_matches("#/$defs/").to_string();
+    }
+    match (schema.schema_type, schema.format) {
+        (Some("array"), _) => {
+            let items = schema.items.as_deref().map_or("any".to_string(), type_name);
+            format!("{items}[]")
+        }
+        (Some(schema_type), Some(format)) => format!("{schema_type} ({format})"),
+        (Some(schema_type), None) => schema_type.to_string(),
+        (None, _) => "any".to_string(),
+    }
+}
+
+/// Compact JSON of a value the schema accepts, stopping at objects nested `depth` deep in
+/// case they refer to each other
+fn example_value(schema: &JsonSchema, defs: &IndexMap<String, JsonSchema>, depth: usize) -> String {
+    if let Some(reference) = &schema.reference {
+        let name = reference.trim_start_matches("#/$defs/");
+        return match defs.get(name) {
+            Some(def) if depth < 4 => example_value(def, defs, depth + 1),
+            _ => "{}".to_string(),
+        };
+    }
+    match (schema.schema_type, schema.format) {
+        (Some("string"), Some("uuid")) => "\"00000000-0000-0000-0000-000000000000\"".to_string(),
+        (Some("string"), Some("date-time")) => "\"2025-01-01T00:00:00Z\"".to_string(),
+        (Some("string"), _) => "\"string\"".to_string(),
+        (Some("integer"), _) => schema.minimum.unwrap_or(0).max(0).to_string(),
+        (Some("number"), _) => "0.0".to_string(),
+        (Some("boolean"), _) => "false".to_string(),
+        (Some("array"), _) => match &schema.items {
+            Some(items) => format!("[{}]", example_value(items, defs, depth)),
+            None => "[]".to_string(),
+        },
+        (Some("object"), _) => {
+            let fields = schema
+                .properties
+                .iter()
+                .flatten()
+                .map(|(name, property)| {
+                    format!("\"{name}\":{}", example_value(property, defs, depth))
+                })
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(","))
+        }
+        _ => "null".to_string(),
+    }
+}
+
+fn render(format: DocsFormat, title: &str, blocks: &[Block]) -> String {
+    match format {
+        DocsFormat::Markdown => render_markdown(blocks),
+        DocsFormat::Html => render_html(title, blocks),
+    }
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let cell = |text: &str, code: bool| match code {
+        true => format!("`{text}`"),
+        false => text.replace('|', "\\|"),
+    };
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "{} {text}", "#".repeat(*level as usize));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "{text}");
+            }
+            Block::Route(text) => {
+                let _ = writeln!(out, "`{text}`");
+            }
+            Block::Code(text) => {
+                let _ = writeln!(out, "```sh\n{text}\n```");
+            }
+            Block::Table {
+                headers,
+                code_columns,
+                rows,
+            } => {
+                let _ = writeln!(out, "| {} |", headers.join(" | "));
+                let _ = writeln!(out, "|{}", " --- |".repeat(headers.len()));
+                for row in rows {
+                    let cells: Vec<_> = row
+                        .iter()
+                        .enumerate()
+                        .map(|(i, text)| cell(text, i < *code_columns))
+                        .collect();
+                    let _ = writeln!(out, "| {} |", cells.join(" | "));
+                }
+            }
+            Block::Links(links) => {
+                for (name, target, summary) in links {
+                    match summary.is_empty() {
+                        true => {
+                            let _ = writeln!(out, "- [{name}]({target})");
+                        }
+                        false => {
+                            let _ = writeln!(out, "- [{name}]({target}): {summary}");
+                        }
+                    }
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html>");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(
+        out,
+        "<style>body{{font-family:sans-serif;max-width:60em;margin:2em auto}}\
+         table{{border-collapse:collapse}}th,td{{border:1px solid #ccc;padding:.3em .6em}}\
+         pre{{background:#f4f4f4;padding:1em}}</style>"
+    );
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "<h{level}>{}</h{level}>", escape(text));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "<p>{}</p>", escape(text));
+            }
+            Block::Route(text) => {
+                let _ = writeln!(out, "<p><code>{}</code></p>", escape(text));
+            }
+            Block::Code(text) => {
+                let _ = writeln!(out, "<pre><code>{}</code></pre>", escape(text));
+            }
+            Block::Table {
+                headers,
+                code_columns,
+                rows,
+            } => {
+                let _ = writeln!(out, "<table>");
+                let headers: String = headers.iter().map(|h| format!("<th>{h}</th>")).collect();
+                let _ = writeln!(out, "<tr>{headers}</tr>");
+                for row in rows {
+                    let cells: String = row
+                        .iter()
+                        .enumerate()
+                        .map(|(i, text)| match i < *code_columns {
+                            true => format!("<td><code>{}</code></td>", escape(text)),
+                            false => format!("<td>{}</td>", escape(text)),
+                        })
+                        .collect();
+                    let _ = writeln!(out, "<tr>{cells}</tr>");
+                }
+                let _ = writeln!(out, "</table>");
+            }
+            Block::Links(links) => {
+                let _ = writeln!(out, "<ul>");
+                for (name, target, summary) in links {
+                    let link = format!("<a href=\"{}\">{}</a>", escape(target), escape(name));
+                    match summary.is_empty() {
+                        true => {
+                            let _ = writeln!(out, "<li>{link}</li>");
+                        }
+                        false => {
+                            let _ = writeln!(out, "<li>{link}: {}</li>", escape(summary));
+                        }
+                    }
+                }
+                let _ = writeln!(out, "</ul>");
+            }
+        }
+    }
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::{
+        analyzer::analyze,
+        parser::{HelixParser, write_to_temp_file},
+    };
+
+    fn source() -> Source {
+        let content = write_to_temp_file(vec![
+            r#"
+            N::User { name: String, age: U8 }
+
+            // Looks a user up by id.
+            //
+            // Returns nothing for unknown ids.
+            QUERY GetUser(id: ID) =>
+                user <- N<User>(id)
+                RETURN user
+
+            QUERY AddUser(name: String, age: U8) =>
+                user <- AddN<User>({ name: name, age: age })
+                RETURN user
+            "#,
+        ]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, source) = analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        source
+    }
+
+    fn page<'a>(pages: &'a [DocsPage], file_name: &str) -> &'a str {
+        &pages
+            .iter()
+            .find(|page| page.file_name == file_name)
+            .unwrap()
+            .content
+    }
+
+    #[test]
+    fn test_markdown_pages_document_queries() {
+        let pages = source().to_docs(DocsFormat::Markdown, "http://localhost:6969/");
+
+        assert_eq!(pages.len(), 3);
+        let index = page(&pages, "index.md");
+        assert!(
+            index.contains(
+                "- [AddUser](AddUser.md)\n- [GetUser](GetUser.md): Looks a user up by id."
+            )
+        );
+
+        let get_user = page(&pages, "GetUser.md");
+        assert!(get_user.starts_with("# GetUser\n\nLooks a user up by id.\n\n"));
+        assert!(get_user.contains("Returns nothing for unknown ids."));
+        assert!(get_user.contains("`POST http://localhost:6969/GetUser`"));
+        assert!(get_user.contains("Reads the graph."));
+        assert!(get_user.contains("| `id` | `string (uuid)` | yes |"));
+        assert!(get_user.contains("-d '{\"id\":\"00000000-0000-0000-0000-000000000000\"}'"));
+
+        let add_user = page(&pages, "AddUser.md");
+        assert!(add_user.contains("Writes to the graph."));
+        assert!(add_user.contains("| `age` | `integer` | yes |"));
+        assert!(add_user.contains("-d '{\"name\":\"string\",\"age\":0}'"));
+    }
+
+    #[test]
+    fn test_html_pages_are_escaped() {
+        let pages = source().to_docs(DocsFormat::Html, "http://localhost:6969");
+
+        let get_user = page(&pages, "GetUser.html");
+        assert!(get_user.starts_with("<!DOCTYPE html>"));
+        assert!(get_user.contains("<h1>GetUser</h1>"));
+        assert!(get_user.contains("<td><code>id</code></td>"));
+        assert!(get_user.contains("-d '{&quot;id&quot;:"));
+        assert!(page(&pages, "index.html").contains("<a href=\"GetUser.html\">GetUser</a>"));
+    }
+
+    #[test]
+    fn test_docs_format_parses_flag_values() {
+        assert_eq!("html".parse::<DocsFormat>(), Ok(DocsFormat::Html));
+        assert_eq!("markdown".parse::<DocsFormat>(), Ok(DocsFormat::Markdown));
+        assert!("pdf".parse::<DocsFormat>().is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! JSON Schema documents of the compiled queries, for API gateways, form builders and
+//! validation middleware.
+//!
+//! Every query gets a `<name>Input` document describing its JSON request body and a
+//! `<name>Output` document describing its response, in the 2020-12 dialect with the objects
+//! they share under `$defs`. Values without a client type, as [`ClientTypes`] describes, are
+//! described as any JSON value.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::helixc::generator::{
+    Source,
+    client_types::ClientTypes,
+    queries::{Parameter, Query},
+    return_values::{ReturnFieldType, ReturnValueField, ReturnValueStruct},
+    utils::RustType,
+};
+
+/// Dialect the documents declare in `$schema`
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A schema, of the keywords the generator uses. The default schema accepts any JSON value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct JsonSchema {
+    #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<JsonSchema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<IndexMap<String, JsonSchema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<Vec<String>>,
+}
+
+impl JsonSchema {
+    fn of_type(schema_type: &'static str) -> Self {
+        Self {
+            schema_type: Some(schema_type),
+            ..Self::default()
+        }
+    }
+
+    fn string(format: &'static str) -> Self {
+        Self {
+            format: Some(format),
+            ..Self::of_type("string")
+        }
+    }
+
+    fn integer(minimum: Option<i64>, maximum: Option<i64>) -> Self {
+        Self {
+            minimum,
+            maximum,
+            ..Self::of_type("integer")
+        }
+    }
+
+    fn array(items: JsonSchema) -> Self {
+        Self {
+            items: Some(Box::new(items)),
+            ..Self::of_type("array")
+        }
+    }
+
+    fn reference(name: &str) -> Self {
+        Self {
+            reference: Some(format!("#/$defs/{name}")),
+            ..Self::default()
+        }
+    }
+
+    fn object(properties: IndexMap<String, JsonSchema>, required: Vec<String>) -> Self {
+        Self {
+            properties: Some(properties),
+            required: Some(required),
+            ..Self::of_type("object")
+        }
+    }
+}
+
+/// A standalone schema document, with the definitions its schema refers to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaDocument {
+    #[serde(rename = "$schema")]
+    pub dialect: &'static str,
+    pub title: String,
+    #[serde(flatten)]
+    pub schema: JsonSchema,
+    #[serde(rename = "$defs", skip_serializing_if = "IndexMap::is_empty")]
+    pub defs: IndexMap<String, JsonSchema>,
+}
+
+impl SchemaDocument {
+    fn new(title: String, schema: JsonSchema, defs: IndexMap<String, JsonSchema>) -> Self {
+        Self {
+            dialect: JSON_SCHEMA_DIALECT,
+            title,
+            schema,
+            defs,
+        }
+    }
+}
+
+/// The schema documents of one query
+#[derive(Debug, Clone)]
+pub struct QuerySchemas {
+    pub query: String,
+    /// Schema of the request body
+    pub input: SchemaDocument,
+    /// Schema of the response
+    pub output: SchemaDocument,
+}
+
+impl Source {
+    /// JSON Schema documents of this source's queries, in query order
+    pub fn to_json_schemas(&self) -> Vec<QuerySchemas> {
+        self.queries
+            .iter()
+            .map(|query| QuerySchemas {
+                query: query.name.clone(),
+                input: input_schema(query),
+                output: output_schema(query),
+            })
+            .collect()
+    }
+}
+
+pub fn input_schema(query: &Query) -> SchemaDocument {
+    let objects: HashSet<&str> = query
+        .sub_parameters
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let defs = query
+        .sub_parameters
+        .iter()
+        .map(|(name, parameters)| (name.clone(), parameters_schema(parameters, &objects)))
+        .collect();
+    SchemaDocument::new(
+        format!("{}Input", query.name),
+        parameters_schema(&query.parameters, &objects),
+        defs,
+    )
+}
+
+pub fn output_schema(query: &Query) -> SchemaDocument {
+    let title = format!("{}Output", query.name);
+    let mut defs = IndexMap::new();
+    if !query.use_struct_returns || query.return_structs.is_empty() {
+        return SchemaDocument::new(title, JsonSchema::default(), defs);
+    }
+
+    let mut properties = IndexMap::new();
+    for struct_def in &query.return_structs {
+        if struct_def.is_primitive || struct_def.is_aggregate {
+            properties.insert(struct_def.source_variable.clone(), JsonSchema::default());
+        } else if struct_def.source_variable.is_empty() {
+            // Object literals are returned field by field
+            properties.extend(struct_fields(struct_def));
+        } else {
+            add_struct_def(&mut defs, struct_def);
+            let schema = JsonSchema::reference(&struct_def.name);
+            let schema = match struct_def.is_collection {
+                true => JsonSchema::array(schema),
+                false => schema,
+            };
+            properties.insert(struct_def.source_variable.clone(), schema);
+        }
+    }
+    SchemaDocument::new(title, returned_object(properties), defs)
+}
+
+/// A returned object, which always has every field
+fn returned_object(properties: IndexMap<String, JsonSchema>) -> JsonSchema {
+    let required = properties.keys().cloned().collect();
+    JsonSchema::object(properties, required)
+}
+
+fn parameters_schema(parameters: &[Parameter], objects: &HashSet<&str>) -> JsonSchema {
+    let properties = parameters
+        .iter()
+        .map(|parameter| {
+            (
+                parameter.name.clone(),
+                JsonSchemaTypes.parameter(&parameter.field_type, objects),
+            )
+        })
+        .collect();
+    let required = parameters
+        .iter()
+        .filter(|parameter| !parameter.is_optional)
+        .map(|parameter| parameter.name.clone())
+        .collect();
+    JsonSchema::object(properties, required)
+}
+
+fn add_struct_def(defs: &mut IndexMap<String, JsonSchema>, struct_def: &ReturnValueStruct) {
+    if defs.contains_key(&struct_def.name) {
+        return;
+    }
+    for nested in struct_def.nested_structs() {
+        add_struct_def(defs, &nested);
+    }
+    let schema = returned_object(struct_fields(struct_def).into_iter().collect());
+    defs.insert(struct_def.name.clone(), schema);
+}
+
+/// Names and schemas of a return struct's fields
+fn struct_fields(struct_def: &ReturnValueStruct) -> Vec<(String, JsonSchema)> {
+    struct_def
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let schema = match struct_def.field_infos.get(i).map(|info| &info.field_type) {
+                Some(ReturnFieldType::Simple(ty)) => JsonSchemaTypes.returned(ty),
+                Some(ReturnFieldType::Nested(_)) => nested_schema(field),
+                None => JsonSchema::default(),
+            };
+            (field.name.clone(), schema)
+        })
+        .collect()
+}
+
+/// A nested traversal's struct, or a list of them unless the traversal takes the first
+fn nested_schema(field: &ReturnValueField) -> JsonSchema {
+    let field_type = field.field_type.replace("<'a>", "");
+    match field_type
+        .strip_prefix("Vec<")
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        Some(inner) => JsonSchema::array(JsonSchema::reference(inner)),
+        None => JsonSchema::reference(&field_type),
+    }
+}
+
+/// Schemas of parameter and returned values
+struct JsonSchemaTypes;
+
+impl ClientTypes for JsonSchemaTypes {
+    type Type = JsonSchema;
+
+    fn scalar(&self, t: &RustType) -> JsonSchema {
+        let bounded =
+            |minimum: i64, maximum: i64| JsonSchema::integer(Some(minimum), Some(maximum));
+        match t {
+            RustType::Str | RustType::String => JsonSchema::of_type("string"),
+            RustType::Uuid => JsonSchema::string("uuid"),
+            RustType::Date => JsonSchema::string("date-time"),
+            RustType::Bool => JsonSchema::of_type("boolean"),
+            RustType::I8 => bounded(i8::MIN.into(), i8::MAX.into()),
+            RustType::I16 => bounded(i16::MIN.into(), i16::MAX.into()),
+            RustType::I32 => bounded(i32::MIN.into(), i32::MAX.into()),
+            RustType::U8 => bounded(0, u8::MAX.into()),
+            RustType::U16 => bounded(0, u16::MAX.into()),
+            RustType::U32 => bounded(0, u32::MAX.into()),
+            RustType::I64 => JsonSchema::integer(None, None),
+            RustType::U64 | RustType::Usize | RustType::U128 => JsonSchema::integer(Some(0), None),
+            RustType::F32 | RustType::F64 => JsonSchema::of_type("number"),
+        }
+    }
+
+    fn list(&self, element: JsonSchema) -> JsonSchema {
+        JsonSchema::array(element)
+    }
+
+    fn object(&self, name: String) -> JsonSchema {
+        JsonSchema::reference(&name)
+    }
+
+    fn any(&self) -> JsonSchema {
+        JsonSchema::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::generator::return_values::{ReturnFieldInfo, RustFieldType};
+    use crate::helixc::generator::utils::{GenRef, GeneratedType};
+    use sonic_rs::json;
+
+    fn parameter(name: &str, field_type: GeneratedType, is_optional: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            field_type,
+            is_optional,
+        }
+    }
+
+    fn query(name: &str) -> Query {
+        Query {
+            name: name.to_string(),
+            ..Query::default()
+        }
+    }
+
+    #[test]
+    fn test_input_schema_maps_parameter_types() {
+        let mut q = query("search");
+        q.parameters = vec![
+            parameter("id", GeneratedType::RustType(RustType::Uuid), false),
+            parameter("limit", GeneratedType::RustType(RustType::U8), true),
+            parameter(
+                "tags",
+                GeneratedType::Vec(Box::new(GeneratedType::RustType(RustType::String))),
+                false,
+            ),
+            parameter(
+                "filter",
+                GeneratedType::Variable(GenRef::Std("searchFilterData".to_string())),
+                false,
+            ),
+            parameter(
+                "extra",
+                GeneratedType::Variable(GenRef::Std("Value".to_string())),
+                true,
+            ),
+        ];
+        q.sub_parameters = vec![(
+            "searchFilterData".to_string(),
+            vec![parameter(
+                "since",
+                GeneratedType::RustType(RustType::Date),
+                false,
+            )],
+        )];
+
+        let expected = json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "title": "searchInput",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string", "format": "uuid" },
+                "limit": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "filter": { "$ref": "#/$defs/searchFilterData" },
+                "extra": {}
+            },
+            "required": ["id", "tags", "filter"],
+            "$defs": {
+                "searchFilterData": {
+                    "type": "object",
+                    "properties": { "since": { "type": "string", "format": "date-time" } },
+                    "required": ["since"]
+                }
+            }
+        });
+        assert_eq!(sonic_rs::to_value(&input_schema(&q)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_output_schema_describes_returned_structs() {
+        let mut user = ReturnValueStruct::new("User".to_string());
+        user.source_variable = "users".to_string();
+        user.is_collection = true;
+        user.fields = vec![
+            ReturnValueField::new("id".to_string(), "&'a str".to_string()),
+            ReturnValueField::new("name".to_string(), "Option<&'a Value>".to_string()),
+        ];
+        user.field_infos = vec![
+            ReturnFieldInfo::new_user_defined(
+                "id".to_string(),
+                RustFieldType::Primitive(GenRef::Std(RustType::String)),
+            ),
+            ReturnFieldInfo::new_user_defined("name".to_string(), RustFieldType::OptionValue),
+        ];
+        let mut count = ReturnValueStruct::new("Count".to_string());
+        count.source_variable = "total".to_string();
+        count.is_primitive = true;
+
+        let mut q = query("listUsers");
+        q.use_struct_returns = true;
+        q.return_structs = vec![user, count];
+
+        let expected = json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "title": "listUsersOutput",
+            "type": "object",
+            "properties": {
+                "users": { "type": "array", "items": { "$ref": "#/$defs/User" } },
+                "total": {}
+            },
+            "required": ["users", "total"],
+            "$defs": {
+                "User": {
+                    "type": "object",
+                    "properties": { "id": { "type": "string" }, "name": {} },
+                    "required": ["id", "name"]
+                }
+            }
+        });
+        assert_eq!(sonic_rs::to_value(&output_schema(&q)).unwrap(), expected);
+
+        let untyped = query("raw");
+        assert_eq!(
+            sonic_rs::to_value(&output_schema(&untyped)).unwrap(),
+            json!({ "$schema": JSON_SCHEMA_DIALECT, "title": "rawOutput" })
+        );
+    }
+}
@@ -12,6 +12,7 @@ pub enum MathExpr {
     NumericLiteral(NumericLiteral),
     PropertyAccess(PropertyAccess),
     Identifier(String),
+    UdfCall(UdfCallGen),
 }
 
 /// Context for property access in weight calculations
@@ -29,6 +30,13 @@ pub struct MathFunctionCallGen {
     pub args: Vec<MathExpr>,
 }
 
+/// A user-defined function call, evaluated by the instance's `Udfs`
+#[derive(Debug, Clone)]
+pub struct UdfCallGen {
+    pub name: String,
+    pub args: Vec<MathExpr>,
+}
+
 #[derive(Debug, Clone)]
 pub struct NumericLiteral {
     pub value: f64,
@@ -47,6 +55,7 @@ impl Display for MathExpr {
             MathExpr::NumericLiteral(n) => write!(f, "{}", n),
             MathExpr::PropertyAccess(prop) => write!(f, "{}", prop),
             MathExpr::Identifier(id) => write!(f, "{}", id),
+            MathExpr::UdfCall(call) => write!(f, "{}", call),
         }
     }
 }
@@ -62,6 +71,18 @@ impl Display for NumericLiteral {
     }
 }
 
+impl Display for UdfCallGen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| format!("Value::from({arg})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "db.udfs.eval(\"{}\", &[{}])?", self.name, args)
+    }
+}
+
 impl Display for PropertyAccess {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.context {
@@ -292,6 +313,18 @@ pub fn generate_math_expr(
             Ok(MathExpr::NumericLiteral(NumericLiteral { value: *f }))
         }
         ExpressionType::Identifier(id) => Ok(MathExpr::Identifier(id.clone())),
+        ExpressionType::UdfCall(call) => {
+            let args = call
+                .args
+                .iter()
+                .map(|arg| generate_math_expr(arg, context))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(MathExpr::UdfCall(UdfCallGen {
+                name: call.name.clone(),
+                args,
+            }))
+        }
         ExpressionType::Traversal(traversal) => {
             // Parse property access from traversal
             // This is where we'd handle _::{prop}, _::From::{prop}, _::To::{prop}
@@ -742,4 +775,22 @@ mod tests {
         let expr = MathExpr::Identifier("custom_var".to_string());
         assert_eq!(expr.to_string(), "custom_var");
     }
+
+    #[test]
+    fn test_udf_call() {
+        let call = UdfCallGen {
+            name: "boost".to_string(),
+            args: vec![
+                MathExpr::PropertyAccess(PropertyAccess {
+                    context: PropertyContext::Edge,
+                    property: GenRef::Literal("distance".to_string()),
+                }),
+                MathExpr::NumericLiteral(NumericLiteral { value: 2.0 }),
+            ],
+        };
+        assert_eq!(
+            call.to_string(),
+            "db.udfs.eval(\"boost\", &[Value::from((edge.get_property(\"distance\").ok_or(GraphError::Default)?.as_f64())), Value::from(2_f64)])?"
+        );
+    }
 }
@@ -6,52 +6,191 @@
 //! File names should be self-explanatory as to what is included in the file.
 
 use crate::{
-    helix_engine::{traversal_core::config::Config, types::SecondaryIndex},
+    helix_engine::{
+        traversal_core::config::Config,
+        types::{EdgeConstraint, MaterializedCount, SecondaryIndex},
+    },
     helixc::{
         analyzer::IntrospectionData,
         generator::{
             migrations::GeneratedMigration,
             queries::Query,
             schemas::{EdgeSchema, NodeSchema, VectorSchema},
+            triggers::Trigger,
             utils::write_headers,
+            views::View,
         },
     },
 };
 use core::fmt;
-use std::io::Write;
-use std::{fmt::Display, fs::File, io::Result, path::Path};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::str::FromStr;
+use std::{
+    fmt::Display,
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
 
 pub mod bool_ops;
+pub mod client_types;
 pub mod computed_expr;
+pub mod docs;
+pub mod json_schema;
 pub mod math_functions;
 pub mod migrations;
+pub mod proto;
 pub mod queries;
 pub mod return_values;
+pub mod rust_client;
 pub mod schemas;
 pub mod source_steps;
 pub mod statements;
 pub mod traversal_steps;
+pub mod triggers;
 pub mod tsdisplay;
 pub mod utils;
+pub mod views;
+
+/// First line of every generated query module, so stale modules can be told apart from user files
+const QUERY_MODULE_MARKER: &str = "// Generated from the project's queries, do not edit";
 
 /// Source is analyzed source
 /// Path is directory to place the generated files
+///
+/// Each query gets its own module under `queries/`, declared by `queries.rs`, so cargo only
+/// recompiles the queries that changed. Files whose content didn't change aren't rewritten.
 pub fn generate(source: Source, path: &Path) -> Result<()> {
-    let mut file = File::create(path.join("queries.rs"))?;
-    write!(file, "{source}")?;
+    let modules_dir = path.join("queries");
+    fs::create_dir_all(&modules_dir)?;
+    let files = source.to_files();
+    for (file, content) in &files {
+        write_if_changed(&path.join(file), content)?;
+    }
+    for entry in fs::read_dir(&modules_dir)? {
+        let entry_path = entry?.path();
+        let is_stale = entry_path.extension().is_some_and(|ext| ext == "rs")
+            && !files.iter().any(|(file, _)| path.join(file) == entry_path)
+            && fs::read_to_string(&entry_path)
+                .is_ok_and(|content| content.starts_with(QUERY_MODULE_MARKER));
+        if is_stale {
+            fs::remove_file(&entry_path)?;
+        }
+    }
+    write_if_changed(&path.join("queries.proto"), &source.to_proto())?;
     Ok(())
 }
 
+/// Leaves the file untouched when it already has the content, keeping its mtime for cargo
+fn write_if_changed(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    fs::write(path, content)
+}
+
+/// Snake case module names for the queries, in order, suffixed where two names collide
+///
+/// Collisions are settled in order of query name rather than parse order, so a query keeps its
+/// module between builds.
+fn query_module_names(queries: &[Query]) -> Vec<String> {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+        "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+        "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self",
+        "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized",
+        "use", "virtual", "where", "while", "yield",
+    ];
+    let mut by_name: Vec<_> = queries.iter().enumerate().collect();
+    by_name.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    let mut names = vec![String::new(); queries.len()];
+    for (index, query) in by_name {
+        let mut base = String::new();
+        let mut prev_lower = false;
+        for c in query.name.chars() {
+            if c.is_ascii_uppercase() && prev_lower {
+                base.push('_');
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            base.push(c.to_ascii_lowercase());
+        }
+        if KEYWORDS.contains(&base.as_str()) {
+            base.push_str("_query");
+        }
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.contains(&name) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        names[index] = name;
+    }
+    names
+}
+
+/// How the generated `queries.rs` trades build time against speed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodegenProfile {
+    /// Traversals monomorphized step by step, with a generated struct for each remapped result
+    #[default]
+    MaxPerf,
+    /// Read traversals boxed after each step and remapped results built as JSON maps, so large
+    /// projects build much faster at the cost of a dynamic call per item and step
+    FastCompile,
+}
+
+impl FromStr for CodegenProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "max-perf" => Ok(CodegenProfile::MaxPerf),
+            "fast-compile" => Ok(CodegenProfile::FastCompile),
+            _ => Err(format!(
+                "unknown codegen profile '{s}', expected 'fast-compile' or 'max-perf'"
+            )),
+        }
+    }
+}
+
+impl Display for CodegenProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenProfile::MaxPerf => write!(f, "max-perf"),
+            CodegenProfile::FastCompile => write!(f, "fast-compile"),
+        }
+    }
+}
+
+thread_local! {
+    /// Profile of the source being written, for the `Display` impls that depend on it
+    static CODEGEN_PROFILE: Cell<CodegenProfile> = const { Cell::new(CodegenProfile::MaxPerf) };
+}
+
+/// Profile of the source being written on this thread, [`CodegenProfile::MaxPerf`] outside of
+/// writing a [`Source`]
+pub fn codegen_profile() -> CodegenProfile {
+    CODEGEN_PROFILE.get()
+}
+
 pub struct Source {
     pub nodes: Vec<NodeSchema>,
     pub edges: Vec<EdgeSchema>,
     pub vectors: Vec<VectorSchema>,
+    pub views: Vec<View>,
+    pub triggers: Vec<Trigger>,
     pub queries: Vec<Query>,
     pub config: Config,
     pub src: String,
     pub migrations: Vec<GeneratedMigration>,
     pub introspection_data: Option<IntrospectionData>,
     pub secondary_indices: Vec<SecondaryIndex>,
+    pub materialized_counts: Vec<MaterializedCount>,
+    pub edge_constraints: Vec<EdgeConstraint>,
+    pub acyclic_edges: Vec<String>,
+    pub codegen_profile: CodegenProfile,
 }
 impl Default for Source {
     fn default() -> Self {
@@ -59,22 +198,70 @@ impl Default for Source {
             nodes: vec![],
             edges: vec![],
             vectors: vec![],
+            views: vec![],
+            triggers: vec![],
             queries: vec![],
             config: Config::default(),
             src: "".to_string(),
             migrations: vec![],
             introspection_data: None,
             secondary_indices: vec![],
+            materialized_counts: vec![],
+            edge_constraints: vec![],
+            acyclic_edges: vec![],
+            codegen_profile: CodegenProfile::default(),
         }
     }
 }
 impl Display for Source {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_profile(|| self.write_source(f, None))
+    }
+}
+impl Source {
+    /// The generated code as files relative to the output directory: `queries.rs`, declaring a
+    /// module per query, and the query modules under `queries/`
+    pub fn to_files(&self) -> Vec<(PathBuf, String)> {
+        let module_names = query_module_names(&self.queries);
+        self.with_profile(|| {
+            let aggregator = Aggregator {
+                source: self,
+                module_names: &module_names,
+            };
+            let mut files = vec![(PathBuf::from("queries.rs"), aggregator.to_string())];
+            for (query, name) in self.queries.iter().zip(&module_names) {
+                files.push((
+                    Path::new("queries").join(format!("{name}.rs")),
+                    format!("{QUERY_MODULE_MARKER}\nuse super::*;\n\n{query}\n"),
+                ));
+            }
+            files
+        })
+    }
+
+    /// Runs `write` with this source's profile set for the `Display` impls that depend on it
+    fn with_profile<T>(&self, write: impl FnOnce() -> T) -> T {
+        let previous = CODEGEN_PROFILE.replace(self.codegen_profile);
+        let result = write();
+        CODEGEN_PROFILE.set(previous);
+        result
+    }
+
+    /// Writes the whole source, with the queries inlined or, given their module names, declared
+    /// as modules
+    fn write_source(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        module_names: Option<&[String]>,
+    ) -> fmt::Result {
         writeln!(f, "{}", write_headers())?;
         self.config.fmt_with_schema(
             f,
             self.introspection_data.as_ref(),
             &self.secondary_indices,
+            &self.materialized_counts,
+            &self.edge_constraints,
+            &self.acyclic_edges,
         )?;
         write!(
             f,
@@ -109,13 +296,40 @@ impl Display for Source {
         write!(
             f,
             "{}",
-            self.queries
+            self.views
                 .iter()
-                .map(|q| format!("{q}"))
+                .map(|v| format!("{v}"))
                 .collect::<Vec<_>>()
                 .join("\n")
         )?;
         writeln!(f)?;
+        write!(
+            f,
+            "{}",
+            self.triggers
+                .iter()
+                .map(|t| format!("{t}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )?;
+        writeln!(f)?;
+        write!(
+            f,
+            "{}",
+            match module_names {
+                Some(names) => {
+                    let mut names = names.to_vec();
+                    names.sort();
+                    names
+                        .iter()
+                        .map(|name| format!("mod {name};"))
+                        .collect::<Vec<_>>()
+                }
+                None => self.queries.iter().map(|q| format!("{q}")).collect(),
+            }
+            .join("\n")
+        )?;
+        writeln!(f)?;
         writeln!(
             f,
             "{}",
@@ -125,6 +339,139 @@ impl Display for Source {
                 .collect::<Vec<_>>()
                 .join("\n")
         )?;
+        writeln!(
+            f,
+            "pub const GRPC_PROTO: &str = r#\"{}\"#;",
+            self.to_proto()
+        )?;
         Ok(())
     }
 }
+
+/// `queries.rs` of a split source, declaring the query modules instead of inlining them
+struct Aggregator<'a> {
+    source: &'a Source,
+    module_names: &'a [String],
+}
+impl Display for Aggregator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.write_source(f, Some(self.module_names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::{
+        analyzer::analyze,
+        parser::{HelixParser, write_to_temp_file},
+    };
+
+    fn generate_with(profile: CodegenProfile) -> String {
+        let content = write_to_temp_file(vec![
+            r#"
+            N::User { name: String }
+            E::Follows { From: User, To: User }
+
+            QUERY followers(id: ID) =>
+                followers <- N<User>(id)::Out<Follows>::WHERE(_::{name}::EQ("x"))
+                RETURN followers::{ username: name }
+            "#,
+        ]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (diagnostics, mut source) = analyze(&parsed).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        source.codegen_profile = profile;
+        source.to_string()
+    }
+
+    #[test]
+    fn test_max_perf_generates_structs_and_unboxed_traversals() {
+        let output = generate_with(CodegenProfile::MaxPerf);
+
+        assert!(output.contains("ReturnType"), "{output}");
+        assert!(!output.contains(".boxed()"));
+    }
+
+    #[test]
+    fn test_fast_compile_boxes_steps_and_remaps_to_maps() {
+        let output = generate_with(CodegenProfile::FastCompile);
+
+        assert!(output.contains(".out_node(\"Follows\")\n.boxed()"));
+        assert!(output.contains("json!({"));
+        assert!(!output.contains("ReturnType"));
+        // Writing the source doesn't change the profile of later output on the thread
+        assert_eq!(codegen_profile(), CodegenProfile::MaxPerf);
+    }
+
+    #[test]
+    fn test_statements_are_timed_as_steps() {
+        let output = generate_with(CodegenProfile::MaxPerf);
+
+        assert!(
+            output
+                .contains("let step_started = step_profile::start();\n    let followers = G::new("),
+            "{output}"
+        );
+        assert!(output.contains("step_profile::record(\"followers\", step_started);"));
+    }
+
+    #[test]
+    fn test_to_files_splits_queries_into_modules() {
+        let content = write_to_temp_file(vec![
+            r#"
+            N::User { name: String }
+
+            QUERY GetUser(id: ID) =>
+                user <- N<User>(id)
+                RETURN user
+
+            QUERY get_user(id: ID) =>
+                user <- N<User>(id)
+                RETURN user
+
+            QUERY type(id: ID) =>
+                user <- N<User>(id)
+                RETURN user
+            "#,
+        ]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let (_, source) = analyze(&parsed).unwrap();
+        let files = source.to_files();
+
+        let mut paths: Vec<_> = files.iter().map(|(path, _)| path.clone()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("queries/get_user.rs"),
+                PathBuf::from("queries/get_user_2.rs"),
+                PathBuf::from("queries/type_query.rs"),
+                PathBuf::from("queries.rs"),
+            ]
+        );
+        let file = |path: &str| &files.iter().find(|(p, _)| p == Path::new(path)).unwrap().1;
+        let aggregator = file("queries.rs");
+        assert!(aggregator.contains("mod get_user;\nmod get_user_2;\nmod type_query;"));
+        assert!(aggregator.contains("pub const GRPC_PROTO"));
+        assert!(!aggregator.contains("pub fn GetUser"));
+        let module = file("queries/get_user.rs");
+        assert!(module.starts_with(QUERY_MODULE_MARKER));
+        assert!(module.contains("use super::*;"));
+        assert!(module.contains("pub fn GetUser"));
+    }
+
+    #[test]
+    fn test_codegen_profile_parses_flag_values() {
+        assert_eq!(
+            "fast-compile".parse::<CodegenProfile>(),
+            Ok(CodegenProfile::FastCompile)
+        );
+        assert_eq!(
+            "max-perf".parse::<CodegenProfile>(),
+            Ok(CodegenProfile::MaxPerf)
+        );
+        assert!("fast".parse::<CodegenProfile>().is_err());
+        assert_eq!(CodegenProfile::FastCompile.to_string(), "fast-compile");
+    }
+}
@@ -0,0 +1,244 @@
+//! Protobuf service definition of the compiled queries, served by the gateway over gRPC.
+//!
+//! Every query becomes two rpcs taking a `<name>Request` message built from its parameters:
+//! `<name>` returns the whole result as a `google.protobuf.Struct`, and `<name>Stream` sends
+//! the elements of each returned collection as separate `ResultItem` messages. Field numbers
+//! follow parameter order, so reordering parameters breaks existing clients.
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Write};
+
+use crate::helixc::generator::{
+    Source,
+    client_types::ClientTypes,
+    queries::{Parameter, Query},
+    utils::{GeneratedType, RustType},
+};
+use crate::protocol::request::{GRPC_PACKAGE, GRPC_SERVICE, RESULT_ITEM_MESSAGE};
+
+impl Source {
+    /// The `.proto` file describing the gRPC service for this source's queries
+    pub fn to_proto(&self) -> String {
+        ProtoFile {
+            queries: &self.queries,
+        }
+        .to_string()
+    }
+}
+
+pub struct ProtoFile<'a> {
+    pub queries: &'a [Query],
+}
+
+impl Display for ProtoFile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "// Generated from the project's queries, do not edit")?;
+        writeln!(f, "syntax = \"proto3\";")?;
+        writeln!(f)?;
+        writeln!(f, "package {GRPC_PACKAGE};")?;
+        writeln!(f)?;
+        writeln!(f, "import \"google/protobuf/struct.proto\";")?;
+        writeln!(f)?;
+
+        writeln!(f, "service {GRPC_SERVICE} {{")?;
+        for query in self.queries {
+            let name = &query.name;
+            writeln!(
+                f,
+                "  rpc {name}({name}Request) returns (google.protobuf.Struct);"
+            )?;
+            writeln!(
+                f,
+                "  rpc {name}Stream({name}Request) returns (stream {RESULT_ITEM_MESSAGE});"
+            )?;
+        }
+        writeln!(f, "}}")?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "// One element of a returned collection, or a returned value that is not one"
+        )?;
+        writeln!(f, "message {RESULT_ITEM_MESSAGE} {{")?;
+        writeln!(f, "  // Name the value is returned under")?;
+        writeln!(f, "  string field = 1;")?;
+        writeln!(f, "  google.protobuf.Value value = 2;")?;
+        writeln!(f, "}}")?;
+
+        for query in self.queries {
+            let messages: HashSet<&str> = query
+                .sub_parameters
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            writeln!(f)?;
+            write_message(
+                f,
+                &format!("{}Request", query.name),
+                &query.parameters,
+                &messages,
+            )?;
+            for (name, parameters) in &query.sub_parameters {
+                writeln!(f)?;
+                write_message(f, name, parameters, &messages)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_message(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    parameters: &[Parameter],
+    messages: &HashSet<&str>,
+) -> fmt::Result {
+    writeln!(f, "message {name} {{")?;
+    for (i, parameter) in parameters.iter().enumerate() {
+        writeln!(
+            f,
+            "  {} {} = {};",
+            field_type(parameter, messages),
+            parameter.name,
+            i + 1
+        )?;
+    }
+    writeln!(f, "}}")
+}
+
+/// Protobuf type of a parameter, with its `repeated` or `optional` label
+fn field_type(parameter: &Parameter, messages: &HashSet<&str>) -> String {
+    let mut out = String::new();
+    match &parameter.field_type {
+        GeneratedType::Vec(inner) => {
+            let _ = write!(out, "repeated {}", ProtoTypes.parameter(inner, messages));
+        }
+        field_type => {
+            if parameter.is_optional {
+                out.push_str("optional ");
+            }
+            out.push_str(&ProtoTypes.parameter(field_type, messages));
+        }
+    }
+    out
+}
+
+/// Protobuf types of message fields, without their labels
+struct ProtoTypes;
+
+impl ClientTypes for ProtoTypes {
+    type Type = String;
+
+    fn scalar(&self, t: &RustType) -> String {
+        match t {
+            RustType::Str | RustType::String | RustType::Uuid | RustType::Date => "string",
+            RustType::Bool => "bool",
+            RustType::I8 | RustType::I16 | RustType::I32 => "int32",
+            RustType::I64 => "int64",
+            RustType::U8 | RustType::U16 | RustType::U32 => "uint32",
+            // Protobuf has no 128 bit integers
+            RustType::U64 | RustType::Usize | RustType::U128 => "uint64",
+            RustType::F32 => "float",
+            RustType::F64 => "double",
+        }
+        .to_string()
+    }
+
+    // Nested lists can't be repeated fields, so they are passed as JSON arrays
+    fn list(&self, _element: String) -> String {
+        "google.protobuf.ListValue".to_string()
+    }
+
+    fn object(&self, name: String) -> String {
+        name
+    }
+
+    fn any(&self) -> String {
+        "google.protobuf.Value".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::generator::utils::GenRef;
+
+    fn parameter(name: &str, field_type: GeneratedType, is_optional: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            field_type,
+            is_optional,
+        }
+    }
+
+    fn query(name: &str) -> Query {
+        Query {
+            name: name.to_string(),
+            ..Query::default()
+        }
+    }
+
+    #[test]
+    fn test_proto_declares_service_per_query() {
+        let queries = vec![query("getUser"), query("addUser")];
+        let output = ProtoFile { queries: &queries }.to_string();
+
+        assert!(output.contains("syntax = \"proto3\";"));
+        assert!(output.contains("package helix.queries;"));
+        assert!(output.contains("service HelixQueries {"));
+        assert!(output.contains("rpc getUser(getUserRequest) returns (google.protobuf.Struct);"));
+        assert!(output.contains("rpc addUserStream(addUserRequest) returns (stream ResultItem);"));
+        assert!(output.contains("message getUserRequest {\n}"));
+    }
+
+    #[test]
+    fn test_proto_maps_parameter_types() {
+        let mut q = query("search");
+        q.parameters = vec![
+            parameter("id", GeneratedType::RustType(RustType::Uuid), false),
+            parameter("limit", GeneratedType::RustType(RustType::U32), true),
+            parameter("score", GeneratedType::RustType(RustType::F64), false),
+            parameter(
+                "tags",
+                GeneratedType::Vec(Box::new(GeneratedType::RustType(RustType::String))),
+                false,
+            ),
+            parameter(
+                "grid",
+                GeneratedType::Vec(Box::new(GeneratedType::Vec(Box::new(
+                    GeneratedType::RustType(RustType::I64),
+                )))),
+                false,
+            ),
+            parameter(
+                "filter",
+                GeneratedType::Variable(GenRef::Std("searchFilterData".to_string())),
+                true,
+            ),
+            parameter(
+                "extra",
+                GeneratedType::Variable(GenRef::Std("Value".to_string())),
+                false,
+            ),
+        ];
+        q.sub_parameters = vec![(
+            "searchFilterData".to_string(),
+            vec![parameter(
+                "since",
+                GeneratedType::RustType(RustType::Date),
+                false,
+            )],
+        )];
+        let queries = vec![q];
+        let output = ProtoFile { queries: &queries }.to_string();
+
+        assert!(output.contains("  string id = 1;"));
+        assert!(output.contains("  optional uint32 limit = 2;"));
+        assert!(output.contains("  double score = 3;"));
+        assert!(output.contains("  repeated string tags = 4;"));
+        assert!(output.contains("  repeated google.protobuf.ListValue grid = 5;"));
+        assert!(output.contains("  optional searchFilterData filter = 6;"));
+        assert!(output.contains("  google.protobuf.Value extra = 7;"));
+        assert!(output.contains("message searchFilterData {\n  string since = 1;\n}"));
+    }
+}
@@ -1,31 +1,112 @@
 use std::fmt::{self, Display};
 
 use crate::helixc::generator::{
+    CodegenProfile, codegen_profile,
     return_values::{ReturnValue, ReturnValueStruct, RustFieldType},
     statements::Statement,
+    traversal_steps::ReservedProp,
     utils::{EmbedData, GeneratedType},
 };
+use crate::helixc::parser::types::{FieldType, ParamCheck};
+use crate::protocol::request::Priority;
+use itertools::Itertools;
+
+/// Reads the relevance score `name` of the search result `var`, if it is one
+fn score_property_value(var: &str, name: &str) -> Option<String> {
+    match name {
+        "score" => Some(format!("{var}.score()")),
+        "bm25_score" | "vector_distance" | "rerank_score" | "scores" => {
+            Some(ReservedProp::from_score_property(name).value_of(var))
+        }
+        _ => None,
+    }
+}
+
+/// Opens a remapped result: its generated struct, or a JSON map for
+/// [`CodegenProfile::FastCompile`]
+fn remap_open(struct_name: &str) -> String {
+    match codegen_profile() {
+        CodegenProfile::MaxPerf => format!("{struct_name} {{"),
+        CodegenProfile::FastCompile => "json!({".to_string(),
+    }
+}
+
+/// A field of a result opened with [`remap_open`]
+fn remap_field(name: &str, value: &str) -> String {
+    match codegen_profile() {
+        CodegenProfile::MaxPerf => format!("{name}: {value},"),
+        // Parenthesized so commas in generic arguments don't end the value
+        CodegenProfile::FastCompile => format!("\"{name}\": ({value}),"),
+    }
+}
+
+/// Closes a result opened with [`remap_open`]
+fn remap_close() -> &'static str {
+    match codegen_profile() {
+        CodegenProfile::MaxPerf => "}",
+        CodegenProfile::FastCompile => "})",
+    }
+}
 
 pub struct Query {
     pub embedding_model_to_use: Option<String>,
     pub mcp_handler: Option<String>,
     pub name: String,
+    /// The query's doc comment from the source, for generated documentation
+    pub doc: Option<String>,
     pub statements: Vec<Statement>,
     pub parameters: Vec<Parameter>, // iterate through and print each one
     pub sub_parameters: Vec<(String, Vec<Parameter>)>,
+    /// What the gateway checks each parameter against before the query runs
+    pub param_rules: Vec<ParamRule>,
     pub return_values: Vec<(String, ReturnValue)>, // Legacy approach
     pub return_structs: Vec<ReturnValueStruct>,    // New struct-based approach
     pub use_struct_returns: bool,                  // Flag to use new vs old approach
     pub is_mut: bool,
+    /// Scheduling lane for reads, set with `#[priority(...)]`
+    pub priority: Priority,
+    /// Result cache time to live, set with `#[cache(ttl: ...)]` on read queries
+    pub cache_ttl_ms: Option<u64>,
+    /// Schema labels the query touches, `None` if it may touch any label
+    pub labels: Option<Vec<String>>,
+    /// Roles a JWT caller needs one of, set with `#[roles(...)]`
+    pub roles: Vec<String>,
+    /// Most calls the gateway runs at once, set with `#[max_concurrency(...)]`
+    pub max_concurrency: Option<usize>,
     pub hoisted_embedding_calls: Vec<EmbedData>,
 }
 
 impl Query {
     fn print_handler(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = Vec::new();
         if self.is_mut {
-            writeln!(f, "#[handler(is_write)]")
-        } else {
+            args.push("is_write".to_string());
+        }
+        if self.priority == Priority::Batch {
+            args.push("priority = batch".to_string());
+        }
+        if let Some(ttl_ms) = self.cache_ttl_ms {
+            args.push(format!("cache_ttl_ms = {ttl_ms}"));
+        }
+        // Labels are only needed to invalidate cached results
+        if let Some(labels) = &self.labels
+            && (self.is_mut || self.cache_ttl_ms.is_some())
+        {
+            let labels = labels.iter().map(|l| format!("\"{l}\"")).join(", ");
+            args.push(format!("labels = [{labels}]"));
+        }
+        if !self.roles.is_empty() {
+            let roles = self.roles.iter().map(|r| format!("\"{r}\"")).join(", ");
+            args.push(format!("roles = [{roles}]"));
+        }
+        if let Some(limit) = self.max_concurrency {
+            args.push(format!("max_concurrency = {limit}"));
+        }
+
+        if args.is_empty() {
             writeln!(f, "#[handler]")
+        } else {
+            writeln!(f, "#[handler({})]", args.join(", "))
         }
     }
 
@@ -48,6 +129,14 @@ impl Query {
                 if struct_def.is_primitive {
                     continue; // Primitive types don't need struct definitions
                 }
+                // Fast compile builds the results as maps, only nested results need a struct
+                if codegen_profile() == CodegenProfile::FastCompile {
+                    for nested_struct in struct_def.nested_structs() {
+                        write!(f, "{}", nested_struct.generate_all_struct_defs())?;
+                        writeln!(f)?;
+                    }
+                    continue;
+                }
                 write!(f, "{}", struct_def.generate_all_struct_defs())?;
                 writeln!(f)?;
             }
@@ -72,6 +161,31 @@ impl Query {
         Ok(())
     }
 
+    fn param_rules_name(&self) -> String {
+        format!("{}_PARAMS", self.name.to_uppercase())
+    }
+
+    /// Prints the table of rules the parameters are checked against, with a table of its own for
+    /// each object parameter
+    fn print_param_rules(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.param_rules_name();
+        let mut tables = Vec::new();
+        let rules = self
+            .param_rules
+            .iter()
+            .map(|rule| rule.print(&name, &mut tables))
+            .collect::<Vec<_>>();
+        tables.push((name, rules));
+        for (name, rules) in tables {
+            writeln!(
+                f,
+                "const {name}: &[ParamRule] = &[\n{}\n];",
+                rules.join(",\n")
+            )?;
+        }
+        Ok(())
+    }
+
     fn print_hoisted_embedding_calls(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if !self.hoisted_embedding_calls.is_empty() {
             writeln!(
@@ -100,6 +214,11 @@ impl Query {
     }
 
     fn print_txn_commit(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_mut {
+            // Edge constraints may only hold once the whole write is done
+            writeln!(f, "db.check_constraints(&txn)?;")?;
+            writeln!(f, "db.audit_write(&mut txn)?;")?;
+        }
         writeln!(
             f,
             "txn.commit().map_err(|e| GraphError::New(format!(\"Failed to commit transaction: {{:?}}\", e)))?;"
@@ -111,6 +230,7 @@ impl Query {
         if !self.parameters.is_empty() {
             self.print_input_struct(f)?;
             self.print_parameters(f)?;
+            self.print_param_rules(f)?;
         }
         if !self.return_values.is_empty() {
             self.print_return_values(f)?;
@@ -126,18 +246,12 @@ impl Query {
         // print the db boilerplate
         writeln!(f, "let db = Arc::clone(&input.graph.storage);")?;
         if !self.parameters.is_empty() {
-            match self.hoisted_embedding_calls.is_empty() {
-                true => writeln!(
-                    f,
-                    "let data = input.request.in_fmt.deserialize::<{}Input>(&input.request.body)?;",
-                    self.name
-                )?,
-                false => writeln!(
-                    f,
-                    "let data = input.request.in_fmt.deserialize::<{}Input>(&input.request.body)?.into_owned();",
-                    self.name
-                )?,
-            }
+            writeln!(
+                f,
+                "let data = input.request.in_fmt.deserialize_params::<{}Input>(&input.request.body, {})?;",
+                self.name,
+                self.param_rules_name()
+            )?;
         }
 
         // print embedding calls
@@ -151,13 +265,13 @@ impl Query {
             )?,
             false => writeln!(
                 f,
-                "let txn = db.graph_env.read_txn().map_err(|e| GraphError::New(format!(\"Failed to start read transaction: {{:?}}\", e)))?;"
+                "let txn = db.read_txn().map_err(|e| GraphError::New(format!(\"Failed to start read transaction: {{:?}}\", e)))?;"
             )?,
         }
 
-        // prints each statement
-        for statement in &self.statements {
-            writeln!(f, "    {statement};")?;
+        // prints each statement, timed as a step of the query
+        for (i, statement) in self.statements.iter().enumerate() {
+            writeln!(f, "    {};", statement.profiled(i + 1))?;
         }
 
         // Generate return value
@@ -305,26 +419,26 @@ impl Query {
                         .closure_param_name
                         .as_deref()
                         .unwrap_or_else(|| struct_def.source_variable.trim_end_matches('s'));
-                    // Check if any field is a nested traversal (needs Result handling)
-                    let has_nested = struct_def.fields.iter().any(|f| f.is_nested_traversal);
+                    // Check if building an item can fail (needs Result handling)
+                    let is_fallible = struct_def.is_fallible();
 
-                    if has_nested {
+                    if is_fallible {
                         writeln!(
                             f,
-                            "    \"{}\": {}.iter().map(|{}| Ok::<_, GraphError>({} {{",
+                            "    \"{}\": {}.iter().map(|{}| Ok::<_, GraphError>({}",
                             struct_def.source_variable,
                             struct_def.source_variable,
                             singular_var,
-                            struct_def.name
+                            remap_open(&struct_def.name)
                         )?;
                     } else {
                         writeln!(
                             f,
-                            "    \"{}\": {}.iter().map(|{}| {} {{",
+                            "    \"{}\": {}.iter().map(|{}| {}",
                             struct_def.source_variable,
                             struct_def.source_variable,
                             singular_var,
-                            struct_def.name
+                            remap_open(&struct_def.name)
                         )?;
                     }
 
@@ -625,6 +739,8 @@ impl Query {
                                             format!("uuid_str({}.from_node(), &arena)", access_var)
                                         } else if accessed_field_name == "to_node" {
                                             format!("uuid_str({}.to_node(), &arena)", access_var)
+                                        } else if let Some(value) = score_property_value(access_var, accessed_field_name).filter(|_| accessed_field_name != "score") {
+                                            value
                                         } else {
                                             format!("{}.get_property(\"{}\")", access_var, accessed_field_name)
                                         }
@@ -664,8 +780,10 @@ impl Query {
                                             format!("uuid_str({}.to_node(), &arena)", var_name)
                                         } else if nf.name == "data" {
                                             format!("{}.data()", var_name)
-                                        } else if nf.name == "score" {
-                                            format!("{}.score()", var_name)
+                                        } else if let Some(value) = score_property_value(var_name, &nf.name) {
+                                            value
+                                        } else if nf.name == "reembedded" {
+                                            format!("{}.reembedded()", var_name)
                                         } else {
                                             format!("{}.get_property(\"{}\")", var_name, nf.name)
                                         };
@@ -716,23 +834,31 @@ impl Query {
                                     format!("uuid_str({}.to_node(), &arena)", singular_var)
                                 } else if property_name == "data" {
                                     format!("{}.data()", singular_var)
-                                } else if property_name == "score" {
-                                    format!("{}.score()", singular_var)
+                                } else if let Some(value) = score_property_value(singular_var, property_name)
+                                    .filter(|_| !matches!(field_info.source, crate::helixc::generator::return_values::ReturnFieldSource::SchemaField { .. }))
+                                {
+                                    value
+                                } else if property_name == "reembedded" {
+                                    format!("{}.reembedded()", singular_var)
                                 } else {
                                     // Regular schema field - use property_name for get_property
                                     format!("{}.get_property(\"{}\")", singular_var, property_name)
                                 }
                             }
                         };
-                        writeln!(f, "        {}: {},", field.name, field_value)?;
+                        writeln!(f, "        {}", remap_field(&field.name, &field_value))?;
                     }
 
-                    // Check if any field is a nested traversal (needs Result handling)
-                    let has_nested = struct_def.fields.iter().any(|f| f.is_nested_traversal);
-                    if has_nested {
-                        write!(f, "    }})).collect::<Result<Vec<_>, GraphError>>()?")
+                    // Check if building an item can fail (needs Result handling)
+                    let is_fallible = struct_def.is_fallible();
+                    if is_fallible {
+                        write!(
+                            f,
+                            "    {})).collect::<Result<Vec<_>, GraphError>>()?",
+                            remap_close()
+                        )
                     } else {
-                        write!(f, "    }}).collect::<Vec<_>>()")
+                        write!(f, "    {}).collect::<Vec<_>>()", remap_close())
                     }?;
                 } else {
                     // Single item - direct struct construction
@@ -741,8 +867,9 @@ impl Query {
 
                     writeln!(
                         f,
-                        "    \"{}\": {} {{",
-                        struct_def.source_variable, struct_def.name
+                        "    \"{}\": {}",
+                        struct_def.source_variable,
+                        remap_open(&struct_def.name)
                     )?;
 
                     for (field_idx, field) in struct_def.fields.iter().enumerate() {
@@ -1036,6 +1163,8 @@ impl Query {
                                             format!("uuid_str({}.from_node(), &arena)", access_var)
                                         } else if accessed_field_name == "to_node" {
                                             format!("uuid_str({}.to_node(), &arena)", access_var)
+                                        } else if let Some(value) = score_property_value(access_var, accessed_field_name).filter(|_| accessed_field_name != "score") {
+                                            value
                                         } else {
                                             format!("{}.get_property(\"{}\")", access_var, accessed_field_name)
                                         }
@@ -1075,8 +1204,10 @@ impl Query {
                                             format!("uuid_str({}.to_node(), &arena)", var_name)
                                         } else if nf.name == "data" {
                                             format!("{}.data()", var_name)
-                                        } else if nf.name == "score" {
-                                            format!("{}.score()", var_name)
+                                        } else if let Some(value) = score_property_value(var_name, &nf.name) {
+                                            value
+                                        } else if nf.name == "reembedded" {
+                                            format!("{}.reembedded()", var_name)
                                         } else {
                                             format!("{}.get_property(\"{}\")", var_name, nf.name)
                                         };
@@ -1133,8 +1264,12 @@ impl Query {
                                     )
                                 } else if property_name == "data" {
                                     format!("{}.data()", struct_def.source_variable)
-                                } else if property_name == "score" {
-                                    format!("{}.score()", struct_def.source_variable)
+                                } else if let Some(value) = score_property_value(&struct_def.source_variable, property_name)
+                                    .filter(|_| !matches!(field_info.source, crate::helixc::generator::return_values::ReturnFieldSource::SchemaField { .. }))
+                                {
+                                    value
+                                } else if property_name == "reembedded" {
+                                    format!("{}.reembedded()", struct_def.source_variable)
                                 } else {
                                     // Regular schema field - use property_name for get_property
                                     format!(
@@ -1144,10 +1279,10 @@ impl Query {
                                 }
                             }
                         };
-                        writeln!(f, "        {}: {},", field.name, field_value)?;
+                        writeln!(f, "        {}", remap_field(&field.name, &field_value))?;
                     }
 
-                    write!(f, "    }}")?;
+                    write!(f, "    {}", remap_close())?;
                 }
             }
             writeln!(f)?;
@@ -1235,7 +1370,19 @@ impl Query {
         }
         writeln!(f, "}}")?;
 
-        writeln!(f, "#[mcp_handler]")?;
+        let mut args = Vec::new();
+        if self.is_mut {
+            args.push("is_write".to_string());
+        }
+        if !self.roles.is_empty() {
+            let roles = self.roles.iter().map(|r| format!("\"{r}\"")).join(", ");
+            args.push(format!("roles = [{roles}]"));
+        }
+        if args.is_empty() {
+            writeln!(f, "#[mcp_handler]")?;
+        } else {
+            writeln!(f, "#[mcp_handler({})]", args.join(", "))?;
+        }
         writeln!(
             f,
             "pub fn {mcp_function_name}(input: &mut MCPToolInput) -> Result<Response, GraphError> {{"
@@ -1287,8 +1434,8 @@ impl Query {
             )?,
         }
 
-        for statement in &self.statements {
-            writeln!(f, "    {statement};")?;
+        for (i, statement) in self.statements.iter().enumerate() {
+            writeln!(f, "    {};", statement.profiled(i + 1))?;
         }
 
         // Generate return value - same logic as regular handler
@@ -1315,26 +1462,26 @@ impl Query {
                         .closure_param_name
                         .as_deref()
                         .unwrap_or_else(|| struct_def.source_variable.trim_end_matches('s'));
-                    // Check if any field is a nested traversal (needs Result handling)
-                    let has_nested = struct_def.fields.iter().any(|f| f.is_nested_traversal);
+                    // Check if building an item can fail (needs Result handling)
+                    let is_fallible = struct_def.is_fallible();
 
-                    if has_nested {
+                    if is_fallible {
                         writeln!(
                             f,
-                            "    \"{}\": {}.iter().map(|{}| Ok::<_, GraphError>({} {{",
+                            "    \"{}\": {}.iter().map(|{}| Ok::<_, GraphError>({}",
                             struct_def.source_variable,
                             struct_def.source_variable,
                             singular_var,
-                            struct_def.name
+                            remap_open(&struct_def.name)
                         )?;
                     } else {
                         writeln!(
                             f,
-                            "    \"{}\": {}.iter().map(|{}| {} {{",
+                            "    \"{}\": {}.iter().map(|{}| {}",
                             struct_def.source_variable,
                             struct_def.source_variable,
                             singular_var,
-                            struct_def.name
+                            remap_open(&struct_def.name)
                         )?;
                     }
 
@@ -1554,6 +1701,8 @@ impl Query {
                                             format!("uuid_str({}.from_node(), &arena)", access_var)
                                         } else if accessed_field_name == "to_node" {
                                             format!("uuid_str({}.to_node(), &arena)", access_var)
+                                        } else if let Some(value) = score_property_value(access_var, accessed_field_name).filter(|_| accessed_field_name != "score") {
+                                            value
                                         } else {
                                             format!("{}.get_property(\"{}\")", access_var, accessed_field_name)
                                         }
@@ -1593,8 +1742,10 @@ impl Query {
                                             format!("uuid_str({}.to_node(), &arena)", var_name)
                                         } else if nf.name == "data" {
                                             format!("{}.data()", var_name)
-                                        } else if nf.name == "score" {
-                                            format!("{}.score()", var_name)
+                                        } else if let Some(value) = score_property_value(var_name, &nf.name) {
+                                            value
+                                        } else if nf.name == "reembedded" {
+                                            format!("{}.reembedded()", var_name)
                                         } else {
                                             format!("{}.get_property(\"{}\")", var_name, nf.name)
                                         };
@@ -1639,22 +1790,30 @@ impl Query {
                                 format!("uuid_str({}.to_node(), &arena)", singular_var)
                             } else if property_name == "data" {
                                 format!("{}.data()", singular_var)
-                            } else if property_name == "score" {
-                                format!("{}.score()", singular_var)
+                            } else if let Some(value) = score_property_value(singular_var, property_name)
+                                .filter(|_| !matches!(field_info.source, crate::helixc::generator::return_values::ReturnFieldSource::SchemaField { .. }))
+                            {
+                                value
+                            } else if property_name == "reembedded" {
+                                format!("{}.reembedded()", singular_var)
                             } else {
                                 // Regular schema field - use property_name for get_property
                                 format!("{}.get_property(\"{}\")", singular_var, property_name)
                             }
                         };
-                        writeln!(f, "        {}: {},", field.name, field_value)?;
+                        writeln!(f, "        {}", remap_field(&field.name, &field_value))?;
                     }
 
-                    // Check if any field is a nested traversal (needs Result handling)
-                    let has_nested = struct_def.fields.iter().any(|f| f.is_nested_traversal);
-                    if has_nested {
-                        write!(f, "    }})).collect::<Vec<_>>()")
+                    // Check if building an item can fail (needs Result handling)
+                    let is_fallible = struct_def.is_fallible();
+                    if is_fallible {
+                        write!(
+                            f,
+                            "    {})).collect::<Result<Vec<_>, GraphError>>()?",
+                            remap_close()
+                        )
                     } else {
-                        write!(f, "    }}).collect::<Vec<_>>()")
+                        write!(f, "    {}).collect::<Vec<_>>()", remap_close())
                     }?;
                 } else {
                     // Single item - direct struct construction
@@ -1663,8 +1822,9 @@ impl Query {
 
                     writeln!(
                         f,
-                        "    \"{}\": {} {{",
-                        struct_def.source_variable, struct_def.name
+                        "    \"{}\": {}",
+                        struct_def.source_variable,
+                        remap_open(&struct_def.name)
                     )?;
 
                     for (field_idx, field) in struct_def.fields.iter().enumerate() {
@@ -1838,6 +1998,8 @@ impl Query {
                                             format!("uuid_str({}.from_node(), &arena)", access_var)
                                         } else if accessed_field_name == "to_node" {
                                             format!("uuid_str({}.to_node(), &arena)", access_var)
+                                        } else if let Some(value) = score_property_value(access_var, accessed_field_name).filter(|_| accessed_field_name != "score") {
+                                            value
                                         } else {
                                             format!("{}.get_property(\"{}\")", access_var, accessed_field_name)
                                         }
@@ -1877,8 +2039,10 @@ impl Query {
                                             format!("uuid_str({}.to_node(), &arena)", var_name)
                                         } else if nf.name == "data" {
                                             format!("{}.data()", var_name)
-                                        } else if nf.name == "score" {
-                                            format!("{}.score()", var_name)
+                                        } else if let Some(value) = score_property_value(var_name, &nf.name) {
+                                            value
+                                        } else if nf.name == "reembedded" {
+                                            format!("{}.reembedded()", var_name)
                                         } else {
                                             format!("{}.get_property(\"{}\")", var_name, nf.name)
                                         };
@@ -1935,8 +2099,12 @@ impl Query {
                                     )
                                 } else if property_name == "data" {
                                     format!("{}.data()", struct_def.source_variable)
-                                } else if property_name == "score" {
-                                    format!("{}.score()", struct_def.source_variable)
+                                } else if let Some(value) = score_property_value(&struct_def.source_variable, property_name)
+                                    .filter(|_| !matches!(field_info.source, crate::helixc::generator::return_values::ReturnFieldSource::SchemaField { .. }))
+                                {
+                                    value
+                                } else if property_name == "reembedded" {
+                                    format!("{}.reembedded()", struct_def.source_variable)
                                 } else {
                                     // Regular schema field - use property_name for get_property
                                     format!(
@@ -1946,10 +2114,10 @@ impl Query {
                                 }
                             }
                         };
-                        writeln!(f, "        {}: {},", field.name, field_value)?;
+                        writeln!(f, "        {}", remap_field(&field.name, &field_value))?;
                     }
 
-                    write!(f, "    }}")?;
+                    write!(f, "    {}", remap_close())?;
                 }
             }
             writeln!(f)?;
@@ -2048,13 +2216,20 @@ impl Default for Query {
             embedding_model_to_use: None,
             mcp_handler: None,
             name: "".to_string(),
+            doc: None,
             statements: vec![],
             parameters: vec![],
             sub_parameters: vec![],
+            param_rules: vec![],
             return_values: vec![],
             return_structs: vec![],
             use_struct_returns: true, // Enable new struct-based returns
             is_mut: false,
+            priority: Priority::default(),
+            cache_ttl_ms: None,
+            labels: None,
+            roles: vec![],
+            max_concurrency: None,
             hoisted_embedding_calls: vec![],
         }
     }
@@ -2068,6 +2243,95 @@ impl Query {
     }
 }
 
+/// A parameter and the checks on it, printed as a `protocol::params::ParamRule`
+pub struct ParamRule {
+    pub name: String,
+    pub field_type: FieldType,
+    pub is_optional: bool,
+    pub checks: Vec<ParamCheck>,
+}
+
+impl ParamRule {
+    /// Prints the rule, adding the tables of any objects in it to `tables`
+    fn print(&self, table: &str, tables: &mut Vec<(String, Vec<String>)>) -> String {
+        let mut rule = format!(
+            "ParamRule::new(\"{}\", {})",
+            self.name,
+            Self::print_kind(
+                &self.field_type,
+                &format!(
+                    "{}_{}",
+                    table.trim_end_matches("_PARAMS"),
+                    self.name.to_uppercase()
+                ),
+                tables
+            )
+        );
+        if self.is_optional {
+            rule.push_str(".optional()");
+        }
+        for check in &self.checks {
+            match check {
+                ParamCheck::Range(min, max) => rule.push_str(&format!(".range({min:?}, {max:?})")),
+                ParamCheck::Length(min, max) => rule.push_str(&format!(".length({min}, {max})")),
+                ParamCheck::OneOf(values) => {
+                    let values = values.iter().map(|v| format!("{v:?}")).join(", ");
+                    rule.push_str(&format!(".one_of(&[{values}])"));
+                }
+            }
+        }
+        rule
+    }
+
+    fn print_kind(
+        field_type: &FieldType,
+        path: &str,
+        tables: &mut Vec<(String, Vec<String>)>,
+    ) -> String {
+        let kind = match field_type {
+            FieldType::String => "String",
+            FieldType::Boolean => "Bool",
+            FieldType::I8 => "I8",
+            FieldType::I16 => "I16",
+            FieldType::I32 => "I32",
+            FieldType::I64 => "I64",
+            FieldType::U8 => "U8",
+            FieldType::U16 => "U16",
+            FieldType::U32 => "U32",
+            FieldType::U64 => "U64",
+            FieldType::U128 => "U128",
+            FieldType::F32 => "F32",
+            FieldType::F64 => "F64",
+            FieldType::Uuid => "Id",
+            FieldType::Date => "Date",
+            FieldType::Identifier(_) => "Any",
+            FieldType::Array(inner) => {
+                return format!(
+                    "ParamKind::Array(&{})",
+                    Self::print_kind(inner, path, tables)
+                );
+            }
+            FieldType::Object(fields) => {
+                let table = format!("{path}_PARAMS");
+                let rules = fields
+                    .iter()
+                    .sorted_by_key(|(name, _)| name.as_str())
+                    .map(|(name, field_type)| ParamRule {
+                        name: name.clone(),
+                        field_type: field_type.clone(),
+                        is_optional: false,
+                        checks: vec![],
+                    })
+                    .map(|rule| rule.print(&table, tables))
+                    .collect();
+                tables.push((table.clone(), rules));
+                return format!("ParamKind::Object({table})");
+            }
+        };
+        format!("ParamKind::{kind}")
+    }
+}
+
 pub struct Parameter {
     pub name: String,
     pub field_type: GeneratedType,
@@ -1,6 +1,7 @@
 use core::fmt;
 use std::fmt::Display;
 
+use crate::helixc::generator::computed_expr::calls_udf;
 use crate::helixc::generator::utils::RustType;
 
 use super::utils::GenRef;
@@ -54,11 +55,23 @@ pub struct ReturnValueStruct {
     pub aggregate_properties: Vec<String>, // Properties to group by (for closure-style aggregates)
     pub is_count_aggregate: bool,   // True for COUNT mode aggregates
     pub closure_param_name: Option<String>, // HQL closure parameter name (e.g., "e" from entries::|e|)
-    pub is_primitive: bool,                 // True for Count/Boolean/Scalar - emit variable directly
+    pub is_primitive: bool, // True for Count/Boolean/Scalar - emit variable directly
     pub primitive_literal_value: Option<GenRef<String>>, // For primitives with field access (e.g., user::ID)
 }
 
 impl ReturnValueStruct {
+    /// Whether building an item can fail: nested traversals and user-defined functions
+    /// return `Result`s
+    pub fn is_fallible(&self) -> bool {
+        self.fields.iter().any(|f| f.is_nested_traversal)
+            || self.field_infos.iter().any(|info| {
+                matches!(
+                    &info.source,
+                    ReturnFieldSource::ComputedExpression { expression } if calls_udf(expression)
+                )
+            })
+    }
+
     pub fn new(name: String) -> Self {
         Self {
             name,
@@ -158,6 +171,8 @@ impl ReturnValueStruct {
                 format!("{}.data()", singular_var)
             } else if field.name == "score" {
                 format!("{}.score()", singular_var)
+            } else if field.name == "reembedded" {
+                format!("{}.reembedded()", singular_var)
             } else if field.is_nested_traversal {
                 // Nested traversal - will be populated by nested G::new() call
                 "/* nested traversal */".to_string()
@@ -326,13 +341,14 @@ impl ReturnValueStruct {
         struct_def
     }
 
-    /// Recursively generate all struct definitions (including nested ones)
-    pub fn generate_all_struct_defs(&self) -> String {
-        let mut output = String::new();
-
-        // First, generate nested struct definitions
-        for field_info in &self.field_infos {
-            if let ReturnFieldType::Nested(nested_fields) = &field_info.field_type {
+    /// The structs of this struct's nested fields, one level deep
+    pub fn nested_structs(&self) -> Vec<ReturnValueStruct> {
+        self.field_infos
+            .iter()
+            .filter_map(|field_info| {
+                let ReturnFieldType::Nested(nested_fields) = &field_info.field_type else {
+                    return None;
+                };
                 // Use the nested_struct_name from the source if available, otherwise fall back to field name
                 let nested_name = if let ReturnFieldSource::NestedTraversal {
                     nested_struct_name: Some(name),
@@ -343,7 +359,7 @@ impl ReturnValueStruct {
                 } else {
                     format!("{}ReturnType", capitalize_first(&field_info.name))
                 };
-                let nested_struct = ReturnValueStruct::from_return_fields(
+                Some(ReturnValueStruct::from_return_fields(
                     nested_name,
                     nested_fields.clone(),
                     "item".to_string(), // Placeholder - actual value comes from traversal
@@ -354,11 +370,20 @@ impl ReturnValueStruct {
                     Vec::new(),         // No aggregate properties for nested structs
                     false,              // Not count aggregate
                     None,               // Nested structs don't have their own closure param
-                );
-                // Recursively generate nested struct defs
-                output.push_str(&nested_struct.generate_all_struct_defs());
-                output.push_str("\n\n");
-            }
+                ))
+            })
+            .collect()
+    }
+
+    /// Recursively generate all struct definitions (including nested ones)
+    pub fn generate_all_struct_defs(&self) -> String {
+        let mut output = String::new();
+
+        // First, generate nested struct definitions
+        for nested_struct in self.nested_structs() {
+            // Recursively generate nested struct defs
+            output.push_str(&nested_struct.generate_all_struct_defs());
+            output.push_str("\n\n");
         }
 
         // Then generate this struct's definition
@@ -399,6 +424,8 @@ pub fn generate_map_closure_body(
             "val.data()".to_string()
         } else if field.name == "score" {
             "val.score()".to_string()
+        } else if field.name == "reembedded" {
+            "val.reembedded()".to_string()
         } else if field.is_nested_traversal {
             // Nested traversal - will be populated by nested G::new() call
             "/* TODO: nested traversal */".to_string()
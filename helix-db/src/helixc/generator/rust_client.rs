@@ -0,0 +1,329 @@
+//! Typed Rust bindings for the compiled queries, built on the `helix-client` crate.
+//!
+//! Every query gets a `<name>Input` struct of its parameters, a `<name>Output` struct of what it
+//! returns and an async method on `Queries`. Values without a client type, as [`ClientTypes`]
+//! describes, come back as `serde_json::Value`.
+
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+use crate::helixc::generator::{
+    Source,
+    client_types::ClientTypes,
+    queries::{Parameter, Query},
+    return_values::{ReturnFieldType, ReturnValueStruct},
+    utils::RustType,
+};
+
+impl Source {
+    /// A Rust module of typed bindings for this source's queries
+    pub fn to_rust_client(&self) -> String {
+        RustClientFile {
+            queries: &self.queries,
+        }
+        .to_string()
+    }
+}
+
+pub struct RustClientFile<'a> {
+    pub queries: &'a [Query],
+}
+
+impl Display for RustClientFile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "// Generated from the project's queries, do not edit")?;
+        writeln!(
+            f,
+            "#![allow(non_camel_case_types, non_snake_case, dead_code)]"
+        )?;
+        writeln!(f)?;
+        writeln!(f, "use helix_client::{{ClientError, HelixClient}};")?;
+        writeln!(f, "use serde::{{Deserialize, Serialize}};")?;
+
+        // Return structs are emitted once even when queries share one
+        let mut written = HashSet::new();
+        for query in self.queries {
+            write_input_structs(f, query)?;
+            write_output_structs(f, query, &mut written)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "/// The project's queries, called through `client`")?;
+        writeln!(f, "#[derive(Clone, Debug)]")?;
+        writeln!(f, "pub struct Queries {{")?;
+        writeln!(f, "    pub client: HelixClient,")?;
+        writeln!(f, "}}")?;
+        writeln!(f)?;
+        writeln!(f, "impl Queries {{")?;
+        writeln!(f, "    pub fn new(client: HelixClient) -> Self {{")?;
+        writeln!(f, "        Self {{ client }}")?;
+        writeln!(f, "    }}")?;
+        for query in self.queries {
+            let name = &query.name;
+            writeln!(f)?;
+            match query.parameters.is_empty() {
+                true => {
+                    writeln!(
+                        f,
+                        "    pub async fn {name}(&self) -> Result<{name}Output, ClientError> {{"
+                    )?;
+                    writeln!(f, "        self.client.query(\"{name}\", &()).await")?;
+                }
+                false => {
+                    writeln!(
+                        f,
+                        "    pub async fn {name}(&self, input: &{name}Input) -> Result<{name}Output, ClientError> {{"
+                    )?;
+                    writeln!(f, "        self.client.query(\"{name}\", input).await")?;
+                }
+            }
+            writeln!(f, "    }}")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn write_input_structs(f: &mut fmt::Formatter<'_>, query: &Query) -> fmt::Result {
+    if query.parameters.is_empty() {
+        return Ok(());
+    }
+    let objects: HashSet<&str> = query
+        .sub_parameters
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    writeln!(f)?;
+    write_parameter_struct(
+        f,
+        &format!("{}Input", query.name),
+        &query.parameters,
+        &objects,
+    )?;
+    for (name, parameters) in &query.sub_parameters {
+        writeln!(f)?;
+        write_parameter_struct(f, name, parameters, &objects)?;
+    }
+    Ok(())
+}
+
+fn write_parameter_struct(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    parameters: &[Parameter],
+    objects: &HashSet<&str>,
+) -> fmt::Result {
+    writeln!(f, "#[derive(Serialize, Deserialize, Clone, Debug)]")?;
+    writeln!(f, "pub struct {name} {{")?;
+    for parameter in parameters {
+        let field_type = RustTypes.parameter(&parameter.field_type, objects);
+        match parameter.is_optional {
+            true => writeln!(f, "    pub {}: Option<{field_type}>,", parameter.name)?,
+            false => writeln!(f, "    pub {}: {field_type},", parameter.name)?,
+        }
+    }
+    writeln!(f, "}}")
+}
+
+fn write_output_structs(
+    f: &mut fmt::Formatter<'_>,
+    query: &Query,
+    written: &mut HashSet<String>,
+) -> fmt::Result {
+    let name = &query.name;
+    if !query.use_struct_returns || query.return_structs.is_empty() {
+        writeln!(f)?;
+        return writeln!(f, "pub type {name}Output = serde_json::Value;");
+    }
+
+    let mut fields = Vec::new();
+    for struct_def in &query.return_structs {
+        if struct_def.is_primitive || struct_def.is_aggregate {
+            fields.push((
+                struct_def.source_variable.clone(),
+                "serde_json::Value".to_string(),
+            ));
+        } else if struct_def.source_variable.is_empty() {
+            // Object literals are returned field by field
+            fields.extend(struct_fields(struct_def));
+        } else {
+            write_return_struct(f, struct_def, written)?;
+            let field_type = match struct_def.is_collection {
+                true => format!("Vec<{}>", struct_def.name),
+                false => struct_def.name.clone(),
+            };
+            fields.push((struct_def.source_variable.clone(), field_type));
+        }
+    }
+
+    writeln!(f)?;
+    writeln!(f, "#[derive(Deserialize, Clone, Debug)]")?;
+    writeln!(f, "pub struct {name}Output {{")?;
+    for (field, field_type) in fields {
+        writeln!(f, "    pub {field}: {field_type},")?;
+    }
+    writeln!(f, "}}")
+}
+
+fn write_return_struct(
+    f: &mut fmt::Formatter<'_>,
+    struct_def: &ReturnValueStruct,
+    written: &mut HashSet<String>,
+) -> fmt::Result {
+    if !written.insert(struct_def.name.clone()) {
+        return Ok(());
+    }
+    for nested in struct_def.nested_structs() {
+        write_return_struct(f, &nested, written)?;
+    }
+    writeln!(f)?;
+    writeln!(f, "#[derive(Deserialize, Clone, Debug)]")?;
+    writeln!(f, "pub struct {} {{", struct_def.name)?;
+    for (field, field_type) in struct_fields(struct_def) {
+        writeln!(f, "    pub {field}: {field_type},")?;
+    }
+    writeln!(f, "}}")
+}
+
+/// Names and client types of a return struct's fields
+fn struct_fields(struct_def: &ReturnValueStruct) -> Vec<(String, String)> {
+    struct_def
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let field_type = match struct_def.field_infos.get(i).map(|info| &info.field_type) {
+                Some(ReturnFieldType::Simple(ty)) => RustTypes.returned(ty),
+                // Nested struct names match the server's, without the lifetime
+                Some(ReturnFieldType::Nested(_)) => field.field_type.replace("<'a>", ""),
+                None => RustTypes.any(),
+            };
+            (field.name.clone(), field_type)
+        })
+        .collect()
+}
+
+/// Rust types of the bindings
+struct RustTypes;
+
+impl ClientTypes for RustTypes {
+    type Type = String;
+
+    fn scalar(&self, t: &RustType) -> String {
+        match t {
+            RustType::Str | RustType::String | RustType::Uuid | RustType::Date => "String",
+            RustType::Bool => "bool",
+            RustType::I8 => "i8",
+            RustType::I16 => "i16",
+            RustType::I32 => "i32",
+            RustType::I64 => "i64",
+            RustType::U8 => "u8",
+            RustType::U16 => "u16",
+            RustType::U32 => "u32",
+            RustType::U64 | RustType::Usize => "u64",
+            RustType::U128 => "u128",
+            RustType::F32 => "f32",
+            RustType::F64 => "f64",
+        }
+        .to_string()
+    }
+
+    fn list(&self, element: String) -> String {
+        format!("Vec<{element}>")
+    }
+
+    fn object(&self, name: String) -> String {
+        name
+    }
+
+    fn any(&self) -> String {
+        "serde_json::Value".to_string()
+    }
+
+    fn optional(&self, value: String) -> String {
+        format!("Option<{value}>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::generator::utils::{GenRef, GeneratedType};
+
+    fn parameter(name: &str, field_type: GeneratedType, is_optional: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            field_type,
+            is_optional,
+        }
+    }
+
+    fn query(name: &str) -> Query {
+        Query {
+            name: name.to_string(),
+            ..Query::default()
+        }
+    }
+
+    #[test]
+    fn test_rust_client_has_method_per_query() {
+        let mut search = query("search");
+        search.parameters = vec![parameter(
+            "limit",
+            GeneratedType::RustType(RustType::U32),
+            false,
+        )];
+        let queries = vec![query("all"), search];
+        let output = RustClientFile { queries: &queries }.to_string();
+
+        assert!(output.contains("use helix_client::{ClientError, HelixClient};"));
+        assert!(output.contains(
+            "    pub async fn all(&self) -> Result<allOutput, ClientError> {\n        self.client.query(\"all\", &()).await\n    }"
+        ));
+        assert!(output.contains(
+            "    pub async fn search(&self, input: &searchInput) -> Result<searchOutput, ClientError> {\n        self.client.query(\"search\", input).await\n    }"
+        ));
+        assert!(output.contains("pub type allOutput = serde_json::Value;"));
+        assert!(!output.contains("allInput"));
+    }
+
+    #[test]
+    fn test_rust_client_maps_parameter_types() {
+        let mut q = query("search");
+        q.parameters = vec![
+            parameter("id", GeneratedType::RustType(RustType::Uuid), false),
+            parameter("limit", GeneratedType::RustType(RustType::Usize), true),
+            parameter(
+                "grid",
+                GeneratedType::Vec(Box::new(GeneratedType::Vec(Box::new(
+                    GeneratedType::RustType(RustType::F64),
+                )))),
+                false,
+            ),
+            parameter(
+                "filter",
+                GeneratedType::Variable(GenRef::Std("searchFilterData".to_string())),
+                false,
+            ),
+            parameter(
+                "extra",
+                GeneratedType::Variable(GenRef::Std("Value".to_string())),
+                false,
+            ),
+        ];
+        q.sub_parameters = vec![(
+            "searchFilterData".to_string(),
+            vec![parameter(
+                "since",
+                GeneratedType::RustType(RustType::Date),
+                false,
+            )],
+        )];
+        let queries = vec![q];
+        let output = RustClientFile { queries: &queries }.to_string();
+
+        assert!(output.contains(
+            "#[derive(Serialize, Deserialize, Clone, Debug)]\npub struct searchInput {\n    pub id: String,\n    pub limit: Option<u64>,\n    pub grid: Vec<Vec<f64>>,\n    pub filter: searchFilterData,\n    pub extra: serde_json::Value,\n}"
+        ));
+        assert!(output.contains("pub struct searchFilterData {\n    pub since: String,\n}"));
+    }
+}
@@ -1,8 +1,11 @@
 use core::fmt;
 use std::fmt::Display;
 
-use crate::helixc::generator::utils::{
-    VecData, write_properties, write_properties_slice, write_secondary_indices,
+use crate::{
+    helix_engine::storage_core::merge::ConflictPolicy,
+    helixc::generator::utils::{
+        VecData, write_properties, write_properties_slice, write_secondary_indices,
+    },
 };
 
 use super::{
@@ -40,6 +43,8 @@ pub enum SourceStep {
     NFromIndex(NFromIndex),
     /// Lookup a node by type
     NFromType(NFromType),
+    /// Lookup the nodes of a view
+    NFromView(NFromView),
     /// Lookup an edge by ID
     EFromID(EFromID),
     /// Lookup an edge by type
@@ -56,6 +61,8 @@ pub enum SourceStep {
     SearchHybrid(SearchHybrid),
     /// Personalized PageRank
     PPR(PPR),
+    /// Merge one node into another
+    MergeNodes(MergeNodes),
     Upsert(Upsert),
     /// Traversal starts from an anonymous node
     Anonymous,
@@ -327,6 +334,17 @@ impl Display for NFromType {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct NFromView {
+    /// Name of the view
+    pub name: GenRef<String>,
+}
+impl Display for NFromView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n_from_view({})", self.name)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EFromID {
     /// ID of edge
@@ -404,6 +422,26 @@ impl Display for SearchBM25 {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct MergeNodes {
+    /// ID of the node kept
+    pub keep: GeneratedValue,
+    /// ID of the node merged into it
+    pub remove: GeneratedValue,
+    /// Which value conflicting properties take
+    pub conflict: ConflictPolicy,
+}
+
+impl Display for MergeNodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "merge_nodes({}, {}, ConflictPolicy::{:?})",
+            self.keep, self.remove, self.conflict
+        )
+    }
+}
+
 impl Display for SourceStep {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -414,12 +452,14 @@ impl Display for SourceStep {
             SourceStep::NFromID(n_from_id) => write!(f, "{n_from_id}"),
             SourceStep::NFromIndex(n_from_index) => write!(f, "{n_from_index}"),
             SourceStep::NFromType(n_from_type) => write!(f, "{n_from_type}"),
+            SourceStep::NFromView(n_from_view) => write!(f, "{n_from_view}"),
             SourceStep::EFromID(e_from_id) => write!(f, "{e_from_id}"),
             SourceStep::EFromType(e_from_type) => write!(f, "{e_from_type}"),
             SourceStep::SearchVector(search_vector) => write!(f, "{search_vector}"),
             SourceStep::SearchBM25(search_bm25) => write!(f, "{search_bm25}"),
             SourceStep::SearchHybrid(search_hybrid) => write!(f, "{search_hybrid}"),
             SourceStep::PPR(ppr) => write!(f, "{ppr}"),
+            SourceStep::MergeNodes(merge_nodes) => write!(f, "{merge_nodes}"),
             SourceStep::Upsert(upsert) => write!(f, "upsert({:?})", upsert),
             SourceStep::Anonymous => write!(f, ""),
             SourceStep::Empty => {
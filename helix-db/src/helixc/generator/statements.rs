@@ -39,6 +39,30 @@ impl Display for Statement {
     }
 }
 
+impl Statement {
+    /// Name of the statement in step timings: the variable it assigns, or its kind and
+    /// position among the query's statements. `None` for statements that do no work.
+    pub fn step_name(&self, index: usize) -> Option<String> {
+        match self {
+            Statement::Assignment(assignment) => Some(assignment.variable.inner().clone()),
+            Statement::Drop(_) => Some(format!("DROP#{index}")),
+            Statement::Traversal(_) => Some(format!("traversal#{index}")),
+            Statement::ForEach(_) => Some(format!("FOR#{index}")),
+            _ => None,
+        }
+    }
+
+    /// The statement timed as a step of the query when its steps are profiled
+    pub fn profiled(&self, index: usize) -> String {
+        match self.step_name(index) {
+            Some(step) => format!(
+                "let step_started = step_profile::start();\n    {self};\n    step_profile::record(\"{step}\", step_started)"
+            ),
+            None => self.to_string(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum IdentifierType {
     Primitive,
@@ -1,10 +1,13 @@
+use crate::helix_engine::storage_core::degrees::DegreeDirection;
 use crate::helixc::{
     analyzer::types::Type,
     generator::utils::{VecData, write_properties_slice},
 };
 
 use super::{
+    CodegenProfile,
     bool_ops::{BoExp, BoolOp},
+    codegen_profile,
     source_steps::SourceStep,
     utils::{GenRef, GeneratedValue, Order, Separator},
 };
@@ -66,11 +69,16 @@ pub enum TraversalType {
         source: Option<GenRef<String>>,
         source_is_plural: bool,
         label: String,
+        /// Text the vector is embedded from, to skip re-embedding when it's unchanged
+        content: Option<GeneratedValue>,
         properties: Option<Vec<(String, GeneratedValue)>>,
         vec_data: Option<VecData>,
     },
     /// Standalone - no G::new wrapper, just the source step (used for plural AddE)
     Standalone,
+    /// Delete - reruns the traversal per batch, committing each batch and carrying on the
+    /// query in a new transaction
+    Delete,
 }
 impl Debug for TraversalType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -141,6 +149,21 @@ pub struct Traversal {
     pub computed_expressions: std::collections::HashMap<String, ComputedExpressionInfo>,
 }
 
+impl Traversal {
+    /// Writes the steps of a read traversal. For [`CodegenProfile::FastCompile`] the iterator is
+    /// boxed after each step that yields one, so the next step is compiled once for all chains.
+    fn write_read_steps(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let boxed = codegen_profile() == CodegenProfile::FastCompile;
+        for step in &self.steps {
+            write!(f, "\n{step}")?;
+            if boxed && step.inner().yields_traversal() {
+                write!(f, "\n.boxed()")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Display for Traversal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.traversal_type {
@@ -150,23 +173,17 @@ impl Display for Traversal {
                     "G::from_iter(&db, &txn, std::iter::once({var}.clone()), &arena)"
                 )?;
                 write!(f, "{}", self.source_step)?;
-                for step in &self.steps {
-                    write!(f, "\n{step}")?;
-                }
+                self.write_read_steps(f)?;
             }
             TraversalType::FromIter(var) => {
                 write!(f, "G::from_iter(&db, &txn, {var}.iter().cloned(), &arena)")?;
                 write!(f, "{}", self.source_step)?;
-                for step in &self.steps {
-                    write!(f, "\n{step}")?;
-                }
+                self.write_read_steps(f)?;
             }
             TraversalType::Ref => {
                 write!(f, "G::new(&db, &txn, &arena)")?;
                 write!(f, "{}", self.source_step)?;
-                for step in &self.steps {
-                    write!(f, "\n{step}")?;
-                }
+                self.write_read_steps(f)?;
             }
 
             TraversalType::Mut => {
@@ -189,6 +206,24 @@ impl Display for Traversal {
                 debug_assert!(false, "TraversalType::Empty should not reach generator");
                 write!(f, "/* ERROR: empty traversal type */")?;
             }
+            TraversalType::Delete => {
+                write!(f, "{{")?;
+                write!(
+                    f,
+                    "let (deleted, next_txn) = DeleteWhere::delete_where(&db, txn, |txn, arena, batch| {{"
+                )?;
+                write!(f, "G::new(&db, &txn, &arena)")?;
+                write!(f, "{}", self.source_step)?;
+                for step in &self.steps {
+                    write!(f, "\n{step}")?;
+                }
+                write!(f, "\n    .take(batch)")?;
+                write!(f, "\n    .collect::<Result<Vec<_>, _>>()")?;
+                write!(f, "}})?;")?;
+                write!(f, "txn = next_txn;")?;
+                write!(f, "Value::from(deleted)")?;
+                write!(f, "}}")?;
+            }
             TraversalType::Update(properties) => {
                 write!(f, "{{")?;
                 write!(f, "let update_tr = G::new(&db, &txn, &arena)")?;
@@ -343,6 +378,7 @@ impl Display for Traversal {
                 source,
                 source_is_plural,
                 label,
+                content,
                 properties,
                 vec_data,
             } => {
@@ -374,8 +410,18 @@ impl Display for Traversal {
                         )?;
                     }
                 }
-                match vec_data {
-                    Some(vd) => {
+                match (vec_data, content) {
+                    (Some(vd), Some(content)) => {
+                        write!(
+                            f,
+                            "\n    .upsert_v_embedded({}, {}, \"{}\", {})",
+                            vd,
+                            content,
+                            label,
+                            write_properties_slice(properties)
+                        )?;
+                    }
+                    (Some(vd), None) => {
                         write!(
                             f,
                             "\n    .upsert_v({}, \"{}\", {})",
@@ -384,7 +430,7 @@ impl Display for Traversal {
                             write_properties_slice(properties)
                         )?;
                     }
-                    None => {
+                    (None, _) => {
                         write!(
                             f,
                             "\n    .upsert_v(&[], \"{}\", {})",
@@ -485,11 +531,16 @@ impl Traversal {
                     | Step::FromV(_)
                     | Step::ToV(_)
                     | Step::Count
+                    | Step::Degree(_)
+                    | Step::NeighborCount
                     | Step::SearchVector(_)
                     | Step::ShortestPath(_)
                     | Step::ShortestPathDijkstras(_)
                     | Step::ShortestPathBFS(_)
                     | Step::ShortestPathAStar(_)
+                    | Step::Subgraph(_)
+                    | Step::Ego(_, _)
+                    | Step::Paths(_)
             )
         })
     }
@@ -500,6 +551,11 @@ impl Traversal {
 pub enum ReservedProp {
     Id,
     Label,
+    Score,
+    Bm25Score,
+    VectorDistance,
+    RerankScore,
+    Scores,
     // Version,
     // FromNode,
     // ToNode,
@@ -509,6 +565,38 @@ pub enum ReservedProp {
     // Data,
 }
 
+impl ReservedProp {
+    /// The reserved property reading the relevance score `name` of search results
+    pub fn from_score_property(name: &str) -> Self {
+        match name {
+            "bm25_score" => ReservedProp::Bm25Score,
+            "vector_distance" => ReservedProp::VectorDistance,
+            "rerank_score" => ReservedProp::RerankScore,
+            "scores" => ReservedProp::Scores,
+            _ => ReservedProp::Score,
+        }
+    }
+
+    /// The `Value` of the property of the item `var`
+    pub fn value_of(&self, var: &str) -> String {
+        match self {
+            ReservedProp::Id => format!("Value::Id(ID::from({var}.id()))"),
+            ReservedProp::Label => format!("Value::from({var}.label())"),
+            ReservedProp::Score => format!("Value::from({var}.score())"),
+            ReservedProp::Bm25Score => {
+                format!("{var}.bm25_score().map(Value::from).unwrap_or_default()")
+            }
+            ReservedProp::VectorDistance => {
+                format!("{var}.vector_distance().map(Value::from).unwrap_or_default()")
+            }
+            ReservedProp::RerankScore => {
+                format!("{var}.rerank_score().map(Value::from).unwrap_or_default()")
+            }
+            ReservedProp::Scores => format!("Value::from({var}.score_breakdown())"),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum Step {
     // graph steps
@@ -523,9 +611,12 @@ pub enum Step {
 
     // utils
     Count,
+    Degree(Degree),
+    NeighborCount,
 
     Where(Where),
     Range(Range),
+    Sample(Sample),
     OrderBy(OrderBy),
     Dedup,
 
@@ -545,6 +636,15 @@ pub enum Step {
     ShortestPathBFS(ShortestPathBFS),
     ShortestPathAStar(ShortestPathAStar),
 
+    // subgraph
+    Subgraph(GeneratedValue),
+
+    // ego network, by edge label and depth
+    Ego(GenRef<String>, GeneratedValue),
+
+    // paths
+    Paths(Vec<PathHop>),
+
     // search vector
     SearchVector(SearchVectorStep),
 
@@ -552,14 +652,56 @@ pub enum Step {
 
     AggregateBy(AggregateBy),
 
+    BucketBy(BucketBy),
+    Stat(Stat),
+
     // rerankers
     RerankRRF(RerankRRF),
     RerankMMR(RerankMMR),
+    Boost(Boost),
+}
+impl Step {
+    /// Whether the step yields a read traversal that further steps can follow
+    fn yields_traversal(&self) -> bool {
+        matches!(
+            self,
+            Step::Out(_)
+                | Step::In(_)
+                | Step::OutE(_)
+                | Step::InE(_)
+                | Step::FromN
+                | Step::ToN
+                | Step::FromV(_)
+                | Step::ToV(_)
+                | Step::Where(_)
+                | Step::Range(_)
+                | Step::Dedup
+        )
+    }
+
+    /// Whether the step passes its input items on with their relevance scores, so rerankers
+    /// after it still have scores to work with
+    pub fn keeps_scores(&self) -> bool {
+        matches!(
+            self,
+            Step::Where(_)
+                | Step::Range(_)
+                | Step::Sample(_)
+                | Step::OrderBy(_)
+                | Step::Dedup
+                | Step::RerankRRF(_)
+                | Step::RerankMMR(_)
+                | Step::Boost(_)
+        )
+    }
 }
+
 impl Display for Step {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Step::Count => write!(f, "count_to_val()"),
+            Step::Degree(degree) => write!(f, "{degree}"),
+            Step::NeighborCount => write!(f, "neighbor_count()"),
             Step::Dedup => write!(f, "dedup()"),
             Step::FromN => write!(f, "from_n()"),
             Step::FromV(from_v) => write!(f, "{from_v}"),
@@ -573,13 +715,15 @@ impl Display for Step {
                 ),
                 ReservedProp::Label => {
                     write!(f, "map(|item| item.map(|v| Value::from(v.label())))")
-                } // ReservedProp::Version => write!(f, "map(|item| Ok(Value::from(item.version)))"),
-                  // ReservedProp::FromNode => write!(f, "map(|item| Ok(Value::from(uuid_str(item.from_node, &arena))))"),
-                  // ReservedProp::ToNode => write!(f, "map(|item| Ok(Value::from(uuid_str(item.to_node, &arena))))"),
-                  // ReservedProp::Deleted => write!(f, "map(|item| Ok(Value::from(item.deleted)))"),
-                  // ReservedProp::Level => write!(f, "map(|item| Ok(Value::from(item.level)))"),
-                  // ReservedProp::Distance => write!(f, "map(|item| Ok(item.distance.map(Value::from).unwrap_or(Value::Empty)))"),
-                  // ReservedProp::Data => write!(f, "map(|item| Ok(Value::from(item.data)))"),
+                }
+                prop => write!(f, "map(|item| item.map(|v| {}))", prop.value_of("v")),
+                // ReservedProp::Version => write!(f, "map(|item| Ok(Value::from(item.version)))"),
+                // ReservedProp::FromNode => write!(f, "map(|item| Ok(Value::from(uuid_str(item.from_node, &arena))))"),
+                // ReservedProp::ToNode => write!(f, "map(|item| Ok(Value::from(uuid_str(item.to_node, &arena))))"),
+                // ReservedProp::Deleted => write!(f, "map(|item| Ok(Value::from(item.deleted)))"),
+                // ReservedProp::Level => write!(f, "map(|item| Ok(Value::from(item.level)))"),
+                // ReservedProp::Distance => write!(f, "map(|item| Ok(item.distance.map(Value::from).unwrap_or(Value::Empty)))"),
+                // ReservedProp::Data => write!(f, "map(|item| Ok(Value::from(item.data)))"),
             },
 
             Step::Out(out) => write!(f, "{out}"),
@@ -588,6 +732,7 @@ impl Display for Step {
             Step::InE(in_e) => write!(f, "{in_e}"),
             Step::Where(where_) => write!(f, "{where_}"),
             Step::Range(range) => write!(f, "{range}"),
+            Step::Sample(sample) => write!(f, "{sample}"),
             Step::OrderBy(order_by) => write!(f, "{order_by}"),
             Step::BoolOp(bool_op) => write!(f, "{bool_op}"),
             Step::ShortestPath(shortest_path) => write!(f, "{shortest_path}"),
@@ -596,11 +741,24 @@ impl Display for Step {
             }
             Step::ShortestPathBFS(shortest_path_bfs) => write!(f, "{shortest_path_bfs}"),
             Step::ShortestPathAStar(shortest_path_astar) => write!(f, "{shortest_path_astar}"),
+            Step::Subgraph(depth) => write!(f, "subgraph({depth})"),
+            Step::Ego(label, depth) => write!(f, "ego({label}, {depth})"),
+            Step::Paths(hops) => write!(
+                f,
+                "paths(&[{}])",
+                hops.iter()
+                    .map(|hop| hop.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Step::SearchVector(search_vector) => write!(f, "{search_vector}"),
             Step::GroupBy(group_by) => write!(f, "{group_by}"),
             Step::AggregateBy(aggregate_by) => write!(f, "{aggregate_by}"),
+            Step::BucketBy(bucket_by) => write!(f, "{bucket_by}"),
+            Step::Stat(stat) => write!(f, "{stat}"),
             Step::RerankRRF(rerank_rrf) => write!(f, "{rerank_rrf}"),
             Step::RerankMMR(rerank_mmr) => write!(f, "{rerank_mmr}"),
+            Step::Boost(boost) => write!(f, "{boost}"),
         }
     }
 }
@@ -608,6 +766,8 @@ impl Debug for Step {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Step::Count => write!(f, "Count"),
+            Step::Degree(_) => write!(f, "Degree"),
+            Step::NeighborCount => write!(f, "NeighborCount"),
             Step::Dedup => write!(f, "Dedup"),
             Step::FromN => write!(f, "FromN"),
             Step::ToN => write!(f, "ToN"),
@@ -621,17 +781,24 @@ impl Debug for Step {
             Step::InE(_) => write!(f, "InE"),
             Step::Where(_) => write!(f, "Where"),
             Step::Range(_) => write!(f, "Range"),
+            Step::Sample(_) => write!(f, "Sample"),
             Step::OrderBy(_) => write!(f, "OrderBy"),
             Step::BoolOp(_) => write!(f, "Bool"),
             Step::ShortestPath(_) => write!(f, "ShortestPath"),
             Step::ShortestPathDijkstras(_) => write!(f, "ShortestPathDijkstras"),
             Step::ShortestPathBFS(_) => write!(f, "ShortestPathBFS"),
             Step::ShortestPathAStar(_) => write!(f, "ShortestPathAStar"),
+            Step::Subgraph(_) => write!(f, "Subgraph"),
+            Step::Ego(_, _) => write!(f, "Ego"),
+            Step::Paths(_) => write!(f, "Paths"),
             Step::SearchVector(_) => write!(f, "SearchVector"),
             Step::GroupBy(_) => write!(f, "GroupBy"),
             Step::AggregateBy(_) => write!(f, "AggregateBy"),
+            Step::BucketBy(_) => write!(f, "BucketBy"),
+            Step::Stat(_) => write!(f, "Stat"),
             Step::RerankRRF(_) => write!(f, "RerankRRF"),
             Step::RerankMMR(_) => write!(f, "RerankMMR"),
+            Step::Boost(_) => write!(f, "Boost"),
         }
     }
 }
@@ -701,6 +868,49 @@ impl Display for In {
     }
 }
 
+#[derive(Clone)]
+pub enum Sample {
+    Count(GeneratedValue),
+    Fraction(GeneratedValue),
+}
+impl Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sample::Count(n) => write!(f, "sample({n})"),
+            Sample::Fraction(fraction) => write!(f, "sample_fraction({fraction})"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Degree {
+    pub label: GenRef<String>,
+    pub direction: DegreeDirection,
+}
+impl Display for Degree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "degree({}, DegreeDirection::{:?})",
+            self.label, self.direction
+        )
+    }
+}
+
+#[derive(Clone)]
+pub enum PathHop {
+    Out(GenRef<String>),
+    In(GenRef<String>),
+}
+impl Display for PathHop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathHop::Out(label) => write!(f, "PathHop::Out({label})"),
+            PathHop::In(label) => write!(f, "PathHop::In({label})"),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct OutE {
     pub label: GenRef<String>,
@@ -770,10 +980,7 @@ impl Display for WhereRef {
 
                 // Handle ReservedPropertyAccess with BoolOp - generate direct field access
                 if let (Some(reserved_prop), Some(bool_op)) = (reserved_prop, bool_op) {
-                    let value_expr = match reserved_prop {
-                        ReservedProp::Id => "Value::Id(ID::from(val.id()))".to_string(),
-                        ReservedProp::Label => "Value::from(val.label())".to_string(),
-                    };
+                    let value_expr = reserved_prop.value_of("val");
                     let bool_expr = match bool_op {
                         BoolOp::Gt(gt) => format!("{} > {}", value_expr, gt.right),
                         BoolOp::Gte(gte) => format!("{} >= {}", value_expr, gte.right),
@@ -902,13 +1109,7 @@ impl OrderBy {
                 "val.get_property({}).cloned().unwrap_or(Value::Empty)",
                 prop
             )),
-            Step::ReservedPropertyAccess(reserved_prop) => {
-                let value_expr = match reserved_prop {
-                    ReservedProp::Id => "Value::Id(ID::from(val.id()))".to_string(),
-                    ReservedProp::Label => "Value::from(val.label())".to_string(),
-                };
-                Some(value_expr)
-            }
+            Step::ReservedPropertyAccess(reserved_prop) => Some(reserved_prop.value_of("val")),
             _ => None,
         }
     }
@@ -948,6 +1149,37 @@ impl Display for GroupBy {
     }
 }
 
+#[derive(Clone)]
+pub struct BucketBy {
+    pub should_count: bool,
+    pub property: GenRef<String>,
+    pub interval: GenRef<String>,
+    pub timezone: GeneratedValue,
+}
+impl Display for BucketBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bucket_by({}, {}, {}, {})",
+            self.property, self.interval, self.timezone, self.should_count
+        )
+    }
+}
+
+#[derive(Clone)]
+pub enum Stat {
+    Percentile(GenRef<String>, f64),
+    Stddev(GenRef<String>),
+}
+impl Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stat::Percentile(property, q) => write!(f, "percentile({property}, {q:?})"),
+            Stat::Stddev(property) => write!(f, "stddev({property})"),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AggregateBy {
     pub should_count: bool,
@@ -1246,3 +1478,27 @@ impl Display for RerankMMR {
         }
     }
 }
+
+#[derive(Clone)]
+pub struct Boost {
+    pub salience: bool,
+    pub confidence: bool,
+    pub recency: bool,
+    pub half_life: Option<GeneratedValue>,
+}
+impl Display for Boost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rerank(SignalBoostConfig::new()")?;
+        if !(self.salience && self.confidence && self.recency) {
+            write!(
+                f,
+                ".with_salience({}).with_confidence({}).with_recency({})",
+                self.salience, self.confidence, self.recency
+            )?;
+        }
+        if let Some(half_life) = &self.half_life {
+            write!(f, ".with_half_life_days({half_life} as f64)")?;
+        }
+        write!(f, ", None)")
+    }
+}
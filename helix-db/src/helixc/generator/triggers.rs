@@ -0,0 +1,43 @@
+use std::fmt::{self, Display};
+
+use crate::helixc::{generator::statements::Statement, parser::types::TriggerKind};
+
+/// A trigger, generated as the function running its statements
+pub struct Trigger {
+    pub kind: TriggerKind,
+    pub item_type: String,
+    /// Variable the written item is bound to, set with `AS`
+    pub binding: Option<String>,
+    pub statements: Vec<Statement>,
+}
+
+impl Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = match self.binding {
+            Some(_) => "id",
+            None => "_id",
+        };
+        writeln!(f, "#[trigger({}, {})]", self.kind, self.item_type)?;
+        writeln!(
+            f,
+            "pub fn trigger_{}_{}<'db>(db: &'db HelixGraphStorage, mut txn: &mut RwTxn<'db>, {id}: u128) -> Result<(), GraphError> {{",
+            self.kind, self.item_type
+        )?;
+        writeln!(f, "let arena = Bump::new();")?;
+        if let Some(binding) = &self.binding {
+            let source = match self.kind {
+                TriggerKind::AddN => "n_from_id",
+                TriggerKind::AddE => "e_from_id",
+            };
+            writeln!(
+                f,
+                "let {binding} = G::new(&db, &txn, &arena).{source}(&id).collect_to_obj()?;"
+            )?;
+        }
+        for statement in &self.statements {
+            writeln!(f, "    {statement};")?;
+        }
+        writeln!(f, "Ok(())")?;
+        writeln!(f, "}}")
+    }
+}
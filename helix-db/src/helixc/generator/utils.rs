@@ -158,6 +158,8 @@ impl Display for VecData {
 pub struct EmbedData {
     pub data: GeneratedValue,
     pub model_name: Option<String>,
+    /// Vector type whose stored embedding of the same text is reused instead of calling the model
+    pub reuse_stored: Option<String>,
 }
 
 impl EmbedData {
@@ -168,11 +170,25 @@ impl EmbedData {
 
 impl Display for EmbedData {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let EmbedData { data, model_name } = self;
+        let EmbedData {
+            data,
+            model_name,
+            reuse_stored,
+        } = self;
+        if let Some(label) = reuse_stored {
+            write!(
+                f,
+                "match db.stored_embedding(\"{label}\", {data}) {{ Some(embedding) => Ok(embedding), None => "
+            )?;
+        }
         match model_name {
-            Some(model) => write!(f, "embed_async!(db, {data}, {model})"),
-            None => write!(f, "embed_async!(db, {data})"),
+            Some(model) => write!(f, "embed_async!(db, {data}, {model})")?,
+            None => write!(f, "embed_async!(db, {data})")?,
+        }
+        if reuse_stored.is_some() {
+            write!(f, " }}")?;
         }
+        Ok(())
     }
 }
 
@@ -445,14 +461,15 @@ pub fn write_headers() -> String {
 
 
 use bumpalo::Bump;
-use heed3::RoTxn;
-use helix_macros::{handler, tool_call, mcp_handler, migration};
+use heed3::{RoTxn, RwTxn};
+use helix_macros::{handler, tool_call, mcp_handler, migration, trigger, view};
 use helix_db::{
     helix_engine::{
         reranker::{
             RerankAdapter,
-            fusion::{RRFReranker, MMRReranker, DistanceMethod},
+            fusion::{RRFReranker, MMRReranker, DistanceMethod, SignalBoostConfig},
         },
+        storage_core::{HelixGraphStorage, degrees::DegreeDirection, merge::ConflictPolicy},
         traversal_core::{
             config::{Config, GraphConfig, VectorConfig},
             ops::{
@@ -470,24 +487,27 @@ use helix_db::{
                     n_from_id::NFromIdAdapter,
                     n_from_index::NFromIndexAdapter,
                     n_from_type::NFromTypeAdapter,
+                    n_from_view::NFromViewAdapter,
                     v_from_id::VFromIdAdapter,
                     v_from_type::VFromTypeAdapter
                 },
                 util::{
-                    dedup::DedupAdapter, drop::Drop, exist::Exist, filter_mut::FilterMut,
+                    dedup::DedupAdapter, delete_where::DeleteWhere, drop::Drop, exist::Exist, filter_mut::FilterMut,
                     filter_ref::FilterRefAdapter, map::MapAdapter, paths::{PathAlgorithm, ShortestPathAdapter},
-                    range::RangeAdapter, update::UpdateAdapter, order::OrderByAdapter,
-                    aggregate::AggregateAdapter, group_by::GroupByAdapter, count::CountAdapter,
-                    upsert::UpsertAdapter,
+                    range::RangeAdapter, sample::SampleAdapter, update::UpdateAdapter, order::OrderByAdapter,
+                    aggregate::AggregateAdapter, group_by::GroupByAdapter, count::CountAdapter, degree::DegreeAdapter,
+                    upsert::UpsertAdapter, merge::MergeNodesAdapter, subgraph::SubgraphAdapter, ego::EgoAdapter,
+                    walk_paths::{PathHop, PathsAdapter}, bucket_by::BucketByAdapter, stats::StatsAdapter,
                 },
                 vectors::{
                     brute_force_search::BruteForceSearchVAdapter, insert::InsertVAdapter,
                     search::SearchVAdapter,
                 },
             },
+            step_profile,
             traversal_value::TraversalValue,
         },
-        types::{GraphError, SecondaryIndex},
+        types::{EdgeConstraint, GraphError, MaterializedCount, SecondaryIndex},
         vector_core::vector::HVector,
     },
     helix_gateway::{
@@ -501,6 +521,7 @@ use helix_db::{
         response::Response,
         value::{casting::{cast, CastType}, Value},
         format::Format,
+        params::{ParamKind, ParamRule},
     },
     utils::{
         id::{ID, uuid_str},
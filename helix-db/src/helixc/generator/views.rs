@@ -0,0 +1,38 @@
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+
+use crate::helixc::generator::traversal_steps::Traversal;
+
+/// A materialized view, generated as the function refreshing it
+pub struct View {
+    pub name: String,
+    pub traversal: Traversal,
+    /// Schema labels the view reads, `None` if it may read any label
+    pub labels: Option<Vec<String>>,
+    /// Set with `#[refresh(cron: ...)]`
+    pub refresh_cron: Option<String>,
+}
+
+impl Display for View {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = vec![self.name.clone()];
+        if let Some(labels) = &self.labels {
+            let labels = labels.iter().map(|l| format!("\"{l}\"")).join(", ");
+            args.push(format!("labels = [{labels}]"));
+        }
+        if let Some(cron) = &self.refresh_cron {
+            args.push(format!("refresh = \"{cron}\""));
+        }
+        writeln!(f, "#[view({})]", args.join(", "))?;
+        writeln!(
+            f,
+            "pub fn view_{}<'db>(db: &'db HelixGraphStorage, txn: &RoTxn<'db>) -> Result<Vec<u128>, GraphError> {{",
+            self.name
+        )?;
+        writeln!(f, "let arena = Bump::new();")?;
+        writeln!(f, "let items = {};", self.traversal)?;
+        writeln!(f, "Ok(items.iter().map(|item| item.id()).collect())")?;
+        writeln!(f, "}}")
+    }
+}
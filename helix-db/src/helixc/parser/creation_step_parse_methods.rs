@@ -1,7 +1,7 @@
 use crate::helixc::parser::{
     HelixParser, ParserError, Rule,
     location::HasLoc,
-    types::{AddEdge, AddNode, AddVector, Embed, EvaluatesToString, VectorData},
+    types::{AddEdge, AddNode, AddVector, VectorData},
     utils::PairTools,
 };
 use pest::iterators::Pair;
@@ -27,25 +27,7 @@ impl HelixParser {
                             data = Some(VectorData::Vector(self.parse_vec_literal(p)?));
                         }
                         Rule::embed_method => {
-                            let inner = vector_data.clone().try_inner_next()?;
-                            data = Some(VectorData::Embed(Embed {
-                                loc: vector_data.loc(),
-                                value: match inner.as_rule() {
-                                    Rule::identifier => {
-                                        EvaluatesToString::Identifier(inner.as_str().to_string())
-                                    }
-                                    Rule::string_literal => {
-                                        EvaluatesToString::StringLiteral(inner.as_str().to_string())
-                                    }
-                                    _ => {
-                                        return Err(ParserError::from(format!(
-                                            "Unexpected rule in SearchV: {:?} => {:?}",
-                                            inner.as_rule(),
-                                            inner,
-                                        )));
-                                    }
-                                },
-                            }));
+                            data = Some(VectorData::Embed(self.parse_embed(vector_data)?));
                         }
                         _ => {
                             return Err(ParserError::from(format!(
@@ -1,11 +1,12 @@
 use crate::{
+    helix_engine::storage_core::merge::ConflictPolicy,
     helixc::parser::{
         HelixParser, ParserError, Rule,
         location::{HasLoc, Loc},
         types::{
-            Assignment, BM25Search, Embed, EvaluatesToNumber, EvaluatesToNumberType,
-            EvaluatesToString, ExistsExpression, Expression, ExpressionType, ForLoop, ForLoopVars,
-            MathFunction, MathFunctionCall, PPR, SearchHybrid, SearchVector, ValueType, VectorData,
+            Assignment, BM25Search, EvaluatesToNumber, EvaluatesToNumberType, ExistsExpression,
+            Expression, ExpressionType, ForLoop, ForLoopVars, MathFunction, MathFunctionCall,
+            MergeNodes, PPR, SearchHybrid, SearchVector, UdfCall, ValueType, VectorData,
         },
         utils::{PairTools, PairsTools},
     },
@@ -151,10 +152,18 @@ impl HelixParser {
                 loc: pair.loc(),
                 expr: ExpressionType::BM25Search(self.parse_bm25_search(pair)?),
             }),
+            Rule::merge_nodes => Ok(Expression {
+                loc: pair.loc(),
+                expr: ExpressionType::MergeNodes(self.parse_merge_nodes(pair)?),
+            }),
             Rule::math_function_call => Ok(Expression {
                 loc: pair.loc(),
                 expr: ExpressionType::MathFunctionCall(self.parse_math_function_call(pair)?),
             }),
+            Rule::udf_call => Ok(Expression {
+                loc: pair.loc(),
+                expr: ExpressionType::UdfCall(self.parse_udf_call(pair)?),
+            }),
             _ => Err(ParserError::from(format!(
                 "Unexpected expression type: {:?}",
                 pair.as_rule()
@@ -304,6 +313,24 @@ impl HelixParser {
         Ok(expressions)
     }
 
+    pub(super) fn parse_merge_nodes(&self, pair: Pair<Rule>) -> Result<MergeNodes, ParserError> {
+        let loc = pair.loc();
+        let mut pairs = pair.into_inner();
+        let keep = pairs.try_next()?.as_str().to_string();
+        let remove = pairs.try_next()?.as_str().to_string();
+        let conflict = match pairs.next().map(|policy| policy.as_str()) {
+            None | Some("KEEP") => ConflictPolicy::Keep,
+            Some("OVERWRITE") => ConflictPolicy::Overwrite,
+            Some(_) => ConflictPolicy::Fail,
+        };
+        Ok(MergeNodes {
+            loc,
+            keep,
+            remove,
+            conflict,
+        })
+    }
+
     pub(super) fn parse_bm25_search(&self, pair: Pair<Rule>) -> Result<BM25Search, ParserError> {
         let mut pairs = pair.clone().into_inner();
         let vector_type = pairs.try_next()?.as_str().to_string();
@@ -452,26 +479,7 @@ impl HelixParser {
                             data = Some(VectorData::Vector(self.parse_vec_literal(p)?));
                         }
                         Rule::embed_method => {
-                            let loc = vector_data.loc();
-                            let inner = vector_data.try_inner_next()?;
-                            data = Some(VectorData::Embed(Embed {
-                                loc,
-                                value: match inner.as_rule() {
-                                    Rule::identifier => {
-                                        EvaluatesToString::Identifier(inner.as_str().to_string())
-                                    }
-                                    Rule::string_literal => {
-                                        EvaluatesToString::StringLiteral(inner.as_str().to_string())
-                                    }
-                                    _ => {
-                                        return Err(ParserError::from(format!(
-                                            "Unexpected rule in SearchV: {:?} => {:?}",
-                                            inner.as_rule(),
-                                            inner,
-                                        )));
-                                    }
-                                },
-                            }));
+                            data = Some(VectorData::Embed(self.parse_embed(vector_data)?));
                         }
                         _ => {
                             return Err(ParserError::from(format!(
@@ -546,26 +554,7 @@ impl HelixParser {
                             vec_data = Some(VectorData::Vector(self.parse_vec_literal(p)?));
                         }
                         Rule::embed_method => {
-                            let loc = vector_data.loc();
-                            let inner = vector_data.try_inner_next()?;
-                            vec_data = Some(VectorData::Embed(Embed {
-                                loc,
-                                value: match inner.as_rule() {
-                                    Rule::identifier => {
-                                        EvaluatesToString::Identifier(inner.as_str().to_string())
-                                    }
-                                    Rule::string_literal => {
-                                        EvaluatesToString::StringLiteral(inner.as_str().to_string())
-                                    }
-                                    _ => {
-                                        return Err(ParserError::from(format!(
-                                            "Unexpected rule in SearchHybrid: {:?} => {:?}",
-                                            inner.as_rule(),
-                                            inner,
-                                        )));
-                                    }
-                                },
-                            }));
+                            vec_data = Some(VectorData::Embed(self.parse_embed(vector_data)?));
                         }
                         _ => {
                             return Err(ParserError::from(format!(
@@ -863,6 +852,20 @@ impl HelixParser {
         })
     }
 
+    pub(super) fn parse_udf_call(&self, pair: Pair<Rule>) -> Result<UdfCall, ParserError> {
+        let loc = pair.loc();
+        let mut inner = pair.into_inner();
+        let name = inner.try_next()?.as_str().to_string();
+        let args = match inner.next() {
+            Some(args_pair) => args_pair
+                .into_inner()
+                .map(|arg_pair| self.parse_math_expression(arg_pair))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        Ok(UdfCall { name, args, loc })
+    }
+
     pub(super) fn parse_math_expression(
         &self,
         pair: Pair<Rule>,
@@ -875,6 +878,10 @@ impl HelixParser {
                 loc: inner.loc(),
                 expr: ExpressionType::MathFunctionCall(self.parse_math_function_call(inner)?),
             }),
+            Rule::udf_call => Ok(Expression {
+                loc: inner.loc(),
+                expr: ExpressionType::UdfCall(self.parse_udf_call(inner)?),
+            }),
             Rule::evaluates_to_number => {
                 // evaluates_to_number is a compound rule, unwrap and parse its contents
                 let inner_inner = inner.try_inner_next()?;
@@ -885,6 +892,10 @@ impl HelixParser {
                             self.parse_math_function_call(inner_inner)?,
                         ),
                     }),
+                    Rule::udf_call => Ok(Expression {
+                        loc: inner_inner.loc(),
+                        expr: ExpressionType::UdfCall(self.parse_udf_call(inner_inner)?),
+                    }),
                     Rule::float => inner_inner
                         .as_str()
                         .parse()
@@ -965,7 +976,14 @@ impl HelixParser {
 
 #[cfg(test)]
 mod tests {
-    use crate::helixc::parser::{HelixParser, write_to_temp_file};
+    use crate::{
+        helix_engine::storage_core::merge::ConflictPolicy,
+        helixc::parser::{
+            HelixParser,
+            types::{Assignment, Expression, ExpressionType, StatementType},
+            write_to_temp_file,
+        },
+    };
 
     // ============================================================================
     // Literal Expression Tests
@@ -1501,4 +1519,58 @@ mod tests {
         let result = HelixParser::parse_source(&content);
         assert!(result.is_ok());
     }
+
+    // ============================================================================
+    // MergeNodes Tests
+    // ============================================================================
+
+    #[test]
+    fn test_parse_merge_nodes() {
+        let source = r#"
+            N::Person { name: String }
+
+            QUERY dedupe(keep: ID, remove: ID) =>
+                merged <- MERGE_NODES(keep, remove)
+                MERGE_NODES(keep, remove, conflict: FAIL)
+                RETURN merged
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let conflicts: Vec<_> = parsed.queries[0]
+            .statements
+            .iter()
+            .filter_map(|statement| match &statement.statement {
+                StatementType::Assignment(Assignment {
+                    value:
+                        Expression {
+                            expr: ExpressionType::MergeNodes(merge),
+                            ..
+                        },
+                    ..
+                })
+                | StatementType::Expression(Expression {
+                    expr: ExpressionType::MergeNodes(merge),
+                    ..
+                }) => Some(merge.conflict),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(conflicts, vec![ConflictPolicy::Keep, ConflictPolicy::Fail]);
+    }
+
+    #[test]
+    fn test_parse_udf_call() {
+        let source = r#"
+            N::Person { name: String, rating: F64 }
+
+            QUERY scored() =>
+                people <- N<Person>
+                RETURN people::{name, score: CALL boost(_::{rating}, 2), total: ADD(CALL boost(_::{rating}, 2), 1)}
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let result = HelixParser::parse_source(&content);
+        assert!(result.is_ok());
+    }
 }
@@ -1,12 +1,14 @@
+use crate::helix_engine::storage_core::degrees::DegreeDirection;
 use crate::helixc::parser::{
     HelixParser, ParserError, Rule,
     location::HasLoc,
     types::{
-        Aggregate, BooleanOp, BooleanOpType, Closure, Embed, EvaluatesToString, Exclude,
-        Expression, ExpressionType, FieldAddition, FieldValue, FieldValueType, GraphStep,
-        GraphStepType, GroupBy, IdType, MMRDistance, Object, OrderBy, OrderByType, RerankMMR,
-        RerankRRF, ShortestPath, ShortestPathAStar, ShortestPathBFS, ShortestPathDijkstras, Step,
-        StepType, Update, UpsertE, UpsertN, UpsertV, VectorData,
+        Aggregate, BooleanOp, BooleanOpType, Boost, BoostSignal, BucketBy, Closure, Degree, Ego,
+        EvaluatesToNumber, EvaluatesToNumberType, EvaluatesToString, Exclude, Expression,
+        ExpressionType, FieldAddition, FieldValue, FieldValueType, GraphStep, GraphStepType,
+        GroupBy, IdType, MMRDistance, Object, OrderBy, OrderByType, RerankMMR, RerankRRF, Sample,
+        ShortestPath, ShortestPathAStar, ShortestPathBFS, ShortestPathDijkstras, Stat,
+        StatFunction, Step, StepType, Subgraph, Update, UpsertE, UpsertN, UpsertV, VectorData,
     },
     utils::{PairTools, PairsTools},
 };
@@ -40,6 +42,60 @@ impl HelixParser {
         })
     }
 
+    /// Parses a sample step
+    ///
+    /// #### Example
+    /// ```rs
+    /// ::SAMPLE(100)
+    /// ::SAMPLE(0.01)
+    /// ```
+    pub(super) fn parse_sample(&self, pair: Pair<Rule>) -> Result<Sample, ParserError> {
+        let loc = pair.loc();
+        let size = pair.try_inner_next()?;
+        let value = match size.as_rule() {
+            Rule::integer => EvaluatesToNumberType::I64(
+                size.as_str()
+                    .parse::<i64>()
+                    .map_err(|_| ParserError::from("Invalid integer value"))?,
+            ),
+            Rule::float => EvaluatesToNumberType::F64(
+                size.as_str()
+                    .parse::<f64>()
+                    .map_err(|_| ParserError::from("Invalid float value"))?,
+            ),
+            _ => EvaluatesToNumberType::Identifier(size.as_str().to_string()),
+        };
+        Ok(Sample {
+            loc,
+            size: EvaluatesToNumber {
+                loc: size.loc(),
+                value,
+            },
+        })
+    }
+
+    /// Parses a degree step
+    ///
+    /// #### Example
+    /// ```rs
+    /// ::DEGREE<Follows>(in)
+    /// ```
+    pub(super) fn parse_degree(&self, pair: Pair<Rule>) -> Result<Degree, ParserError> {
+        let loc = pair.loc();
+        let mut inner = pair.into_inner();
+        let label = inner.try_next()?.as_str().to_string();
+        let direction = match inner.try_next()?.as_str() {
+            "in" => DegreeDirection::In,
+            "out" => DegreeDirection::Out,
+            _ => DegreeDirection::Both,
+        };
+        Ok(Degree {
+            loc,
+            label,
+            direction,
+        })
+    }
+
     /// Parses a range step
     ///
     /// #### Example
@@ -199,24 +255,7 @@ impl HelixParser {
                             data = Some(VectorData::Vector(self.parse_vec_literal(p)?));
                         }
                         Rule::embed_method => {
-                            let inner = vector_data.clone().try_inner_next()?;
-                            data = Some(VectorData::Embed(Embed {
-                                loc: vector_data.loc(),
-                                value: match inner.as_rule() {
-                                    Rule::identifier => {
-                                        EvaluatesToString::Identifier(inner.as_str().to_string())
-                                    }
-                                    Rule::string_literal => {
-                                        EvaluatesToString::StringLiteral(inner.as_str().to_string())
-                                    }
-                                    _ => {
-                                        return Err(ParserError::from(format!(
-                                            "Unexpected rule in UpsertV vector_data: {:?}",
-                                            inner.as_rule()
-                                        )));
-                                    }
-                                },
-                            }));
+                            data = Some(VectorData::Embed(self.parse_embed(vector_data)?));
                         }
                         _ => {
                             return Err(ParserError::from(format!(
@@ -385,6 +424,56 @@ impl HelixParser {
         })
     }
 
+    /// Parses a bucket by step
+    ///
+    /// #### Example
+    /// ```rs
+    /// ::BUCKET_BY(created_at, 1d, "Europe/Berlin")
+    /// ```
+    pub(super) fn parse_bucket_by(&self, pair: Pair<Rule>) -> Result<BucketBy, ParserError> {
+        let loc = pair.loc();
+        let mut inner = pair.into_inner();
+        let property = inner.try_next()?.as_str().to_string();
+        let interval = inner.try_next()?.as_str().to_string();
+        let timezone = inner.next().map(|timezone| match timezone.as_rule() {
+            Rule::string_literal => {
+                EvaluatesToString::StringLiteral(timezone.as_str().trim_matches('"').to_string())
+            }
+            _ => EvaluatesToString::Identifier(timezone.as_str().to_string()),
+        });
+        Ok(BucketBy {
+            loc,
+            property,
+            interval,
+            timezone,
+        })
+    }
+
+    /// Parses a statistic step
+    ///
+    /// #### Example
+    /// ```rs
+    /// ::P95(latency)
+    /// ::STDDEV(latency)
+    /// ```
+    pub(super) fn parse_stat(&self, pair: Pair<Rule>) -> Result<Stat, ParserError> {
+        let loc = pair.loc();
+        let mut inner = pair.into_inner();
+        let function = match inner.try_next()?.as_str() {
+            "P50" => StatFunction::P50,
+            "P95" => StatFunction::P95,
+            "P99" => StatFunction::P99,
+            "MEDIAN" => StatFunction::Median,
+            _ => StatFunction::Stddev,
+        };
+        let property = inner.try_next()?.as_str().to_string();
+        Ok(Stat {
+            loc,
+            function,
+            property,
+        })
+    }
+
     pub(super) fn parse_step(&self, pair: Pair<Rule>) -> Result<Step, ParserError> {
         let step_pair = pair.clone().try_inner_next()?;
         match step_pair.as_rule() {
@@ -404,6 +493,10 @@ impl HelixParser {
                 loc: step_pair.loc(),
                 step: StepType::Where(Box::new(self.parse_expression(step_pair)?)),
             }),
+            Rule::sample_step => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Sample(self.parse_sample(step_pair)?),
+            }),
             Rule::range_step => Ok(Step {
                 loc: step_pair.loc(),
                 step: StepType::Range(self.parse_range(step_pair)?),
@@ -417,6 +510,18 @@ impl HelixParser {
                 loc: step_pair.loc(),
                 step: StepType::Count,
             }),
+            Rule::paths => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Paths,
+            }),
+            Rule::degree => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Degree(self.parse_degree(step_pair)?),
+            }),
+            Rule::neighbor_count => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::NeighborCount,
+            }),
             Rule::ID => Ok(Step {
                 loc: step_pair.loc(),
                 step: StepType::Object(Object {
@@ -468,10 +573,22 @@ impl HelixParser {
                 loc: step_pair.loc(),
                 step: StepType::GroupBy(self.parse_group_by(step_pair)?),
             }),
+            Rule::bucket_by => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::BucketBy(self.parse_bucket_by(step_pair)?),
+            }),
+            Rule::stat_step => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Stat(self.parse_stat(step_pair)?),
+            }),
             Rule::first => Ok(Step {
                 loc: step_pair.loc(),
                 step: StepType::First,
             }),
+            Rule::delete => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Delete,
+            }),
             Rule::rerank_rrf => Ok(Step {
                 loc: step_pair.loc(),
                 step: StepType::RerankRRF(self.parse_rerank_rrf(step_pair)?),
@@ -480,6 +597,10 @@ impl HelixParser {
                 loc: step_pair.loc(),
                 step: StepType::RerankMMR(self.parse_rerank_mmr(step_pair)?),
             }),
+            Rule::boost => Ok(Step {
+                loc: step_pair.loc(),
+                step: StepType::Boost(self.parse_boost(step_pair)?),
+            }),
             _ => Err(ParserError::from(format!(
                 "Unexpected step type: {:?}",
                 step_pair.as_rule()
@@ -816,6 +937,59 @@ impl HelixParser {
                     }),
                 }
             }
+            Rule::subgraph => {
+                let depth = pair.clone().try_inner_next()?;
+                let value = match depth.as_rule() {
+                    Rule::integer => EvaluatesToNumberType::I32(
+                        depth
+                            .as_str()
+                            .parse::<i32>()
+                            .map_err(|_| ParserError::from("Invalid integer value"))?,
+                    ),
+                    _ => EvaluatesToNumberType::Identifier(depth.as_str().to_string()),
+                };
+                GraphStep {
+                    loc: pair.loc(),
+                    step: GraphStepType::Subgraph(Subgraph {
+                        loc: pair.loc(),
+                        depth: EvaluatesToNumber {
+                            loc: depth.loc(),
+                            value,
+                        },
+                    }),
+                }
+            }
+            Rule::ego => {
+                let mut inner = pair.clone().into_inner();
+                let label = inner
+                    .next()
+                    .ok_or_else(|| ParserError::from("Expected edge type for EGO"))?
+                    .as_str()
+                    .to_string();
+                let depth = inner
+                    .next()
+                    .ok_or_else(|| ParserError::from("Expected depth for EGO"))?;
+                let value = match depth.as_rule() {
+                    Rule::integer => EvaluatesToNumberType::I32(
+                        depth
+                            .as_str()
+                            .parse::<i32>()
+                            .map_err(|_| ParserError::from("Invalid integer value"))?,
+                    ),
+                    _ => EvaluatesToNumberType::Identifier(depth.as_str().to_string()),
+                };
+                GraphStep {
+                    loc: pair.loc(),
+                    step: GraphStepType::Ego(Ego {
+                        loc: pair.loc(),
+                        label,
+                        depth: EvaluatesToNumber {
+                            loc: depth.loc(),
+                            value,
+                        },
+                    }),
+                }
+            }
 
             Rule::search_vector => GraphStep {
                 loc: pair.loc(),
@@ -837,6 +1011,7 @@ impl HelixParser {
     /// ```rs
     /// ::RerankRRF(k: 60)
     /// ::RerankRRF()
+    /// ::RERANK_RRF(60)
     /// ```
     pub(super) fn parse_rerank_rrf(&self, pair: Pair<Rule>) -> Result<RerankRRF, ParserError> {
         let loc = pair.loc();
@@ -858,6 +1033,7 @@ impl HelixParser {
     /// ```rs
     /// ::RerankMMR(lambda: 0.7)
     /// ::RerankMMR(lambda: 0.5, distance: "euclidean")
+    /// ::RERANK_MMR(0.7)
     /// ```
     pub(super) fn parse_rerank_mmr(&self, pair: Pair<Rule>) -> Result<RerankMMR, ParserError> {
         let loc = pair.loc();
@@ -901,4 +1077,55 @@ impl HelixParser {
             distance,
         })
     }
+
+    /// Parses a BOOST step
+    ///
+    /// #### Example
+    /// ```rs
+    /// ::BOOST
+    /// ::BOOST(salience, recency, half_life: 7)
+    /// ```
+    pub(super) fn parse_boost(&self, pair: Pair<Rule>) -> Result<Boost, ParserError> {
+        let loc = pair.loc();
+        let mut signals = Vec::new();
+        let mut half_life = None;
+
+        for arg in pair.into_inner() {
+            let arg = arg.try_inner_next()?;
+            match arg.as_rule() {
+                Rule::boost_signal => {
+                    let signal = match arg.as_str() {
+                        "salience" => BoostSignal::Salience,
+                        "confidence" => BoostSignal::Confidence,
+                        _ => BoostSignal::Recency,
+                    };
+                    if signals.contains(&signal) {
+                        return Err(ParserError::from(format!(
+                            "BOOST lists `{}` more than once",
+                            signal.as_str()
+                        )));
+                    }
+                    signals.push(signal);
+                }
+                Rule::boost_half_life => {
+                    if half_life.is_some() {
+                        return Err(ParserError::from("BOOST sets `half_life` more than once"));
+                    }
+                    half_life = Some(self.parse_expression(arg.try_inner_next()?)?);
+                }
+                _ => {
+                    return Err(ParserError::from(format!(
+                        "Unexpected rule in BOOST: {:?}",
+                        arg.as_rule()
+                    )));
+                }
+            }
+        }
+
+        Ok(Boost {
+            loc,
+            signals,
+            half_life,
+        })
+    }
 }
@@ -22,12 +22,18 @@ pub mod expression_parse_methods;
 pub mod graph_step_parse_methods;
 pub mod location;
 pub mod object_parse_methods;
+pub mod pretty;
 pub mod query_parse_methods;
 pub mod return_value_parse_methods;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod roundtrip;
+pub mod schema_diagram;
 pub mod schema_parse_methods;
 pub mod traversal_parse_methods;
+pub mod trigger_parse_methods;
 pub mod types;
 pub mod utils;
+pub mod view_parse_methods;
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
@@ -41,6 +47,8 @@ impl HelixParser {
             source: String::new(),
             schema: HashMap::new(),
             migrations: Vec::new(),
+            views: Vec::new(),
+            triggers: Vec::new(),
             queries: Vec::new(),
         };
 
@@ -155,6 +163,18 @@ impl HelixParser {
                     Rule::migration_def => {
                         remaining_migrations.insert(pair);
                     }
+                    Rule::view_def => {
+                        parser
+                            .source
+                            .views
+                            .push(parser.parse_view_def(pair, file.name.clone())?);
+                    }
+                    Rule::trigger_def => {
+                        parser
+                            .source
+                            .triggers
+                            .push(parser.parse_trigger_def(pair, file.name.clone())?);
+                    }
                     Rule::query_def => {
                         remaining_queries.insert(pair);
                     }
@@ -187,6 +207,8 @@ impl HelixParser {
                     })
                     .or_insert(new_schema);
             }
+            source.views.extend(parser.source.views);
+            source.triggers.extend(parser.source.triggers);
             source.queries.extend(parser.source.queries);
             source.migrations.extend(parser.source.migrations);
             Ok(())
@@ -0,0 +1,948 @@
+// Copyright 2025 HelixDB Inc.
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Pretty-printer turning parsed HelixQL back into source.
+//!
+//! Parsing printed source gives back the schemas, migrations, views, triggers and queries it
+//! was printed from, so tools can rewrite HQL through the AST. The output is canonical: items come out in
+//! source order, map keys are sorted and every construct is laid out one way, so printing a
+//! re-parsed source gives the same text again. Comments aren't part of the AST and are dropped.
+
+use crate::{
+    helix_engine::storage_core::{degrees::DegreeDirection, merge::ConflictPolicy},
+    helixc::parser::{
+        location::Loc,
+        types::{
+            AddEdge, AddNode, AddVector, BM25Search, BooleanOpType, BuiltInMacro, Cardinality,
+            DefaultValue, EdgeConnection, EdgeSchema, EvaluatesToNumber, EvaluatesToNumberType,
+            EvaluatesToString, Expression, ExpressionType, Field, FieldAddition, FieldPrefix,
+            FieldType, FieldValue, FieldValueType, ForLoopVars, GraphStep, GraphStepType, IdType,
+            MMRDistance, MergeNodes, Migration, MigrationItem, MigrationItemMapping, NodeSchema, Object,
+            OrderByType, PPR, ParamCheck, Query, ReturnType, Schema, SearchHybrid, SearchVector, Source,
+            StartNode, Statement, StatementType, StepType, Traversal, ValueType, VectorData,
+            Trigger, VectorSchema, View, WeightExpression,
+        },
+    },
+    protocol::{request::Priority, value::Value},
+};
+use std::{cmp::Ordering, collections::HashMap};
+
+const INDENT: &str = "    ";
+
+/// Print every schema, migration, view, trigger and query of a source, separated by blank lines
+pub fn print_source(source: &Source) -> String {
+    let mut migrations = source.migrations.iter().collect::<Vec<_>>();
+    migrations.sort_by(|a, b| source_order(&a.loc, &b.loc));
+    let mut views = source.views.iter().collect::<Vec<_>>();
+    views.sort_by(|a, b| source_order(&a.loc, &b.loc));
+    let mut triggers = source.triggers.iter().collect::<Vec<_>>();
+    triggers.sort_by(|a, b| source_order(&a.loc, &b.loc));
+    let mut queries = source.queries.iter().collect::<Vec<_>>();
+    queries.sort_by(|a, b| source_order(&a.loc, &b.loc));
+
+    let items = source
+        .get_schemas_in_order()
+        .into_iter()
+        .map(print_schema)
+        .chain(migrations.into_iter().map(print_migration))
+        .chain(views.into_iter().map(print_view))
+        .chain(triggers.into_iter().map(print_trigger))
+        .chain(queries.into_iter().map(print_query))
+        .collect::<Vec<_>>();
+    items.join("\n")
+}
+
+/// Print a schema's definitions, in a `schema::N` block unless it is the first version
+pub fn print_schema(schema: &Schema) -> String {
+    let versioned = schema.version.1 != 1;
+    let depth = usize::from(versioned);
+    let definitions = schema
+        .node_schemas
+        .iter()
+        .map(|node| print_node_schema(node, depth))
+        .chain(
+            schema
+                .edge_schemas
+                .iter()
+                .map(|edge| print_edge_schema(edge, depth)),
+        )
+        .chain(
+            schema
+                .vector_schemas
+                .iter()
+                .map(|vector| print_vector_schema(vector, depth)),
+        )
+        .collect::<Vec<_>>()
+        .join("\n");
+    match versioned {
+        true => format!("schema::{} {{\n{definitions}}}\n", schema.version.1),
+        false => definitions,
+    }
+}
+
+/// Print a migration between two schema versions
+pub fn print_migration(migration: &Migration) -> String {
+    let mut out = format!(
+        "MIGRATION schema::{} => schema::{} {{\n",
+        migration.from_version.1, migration.to_version.1
+    );
+    for mapping in &migration.body {
+        print_item_mapping(&mut out, mapping);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Print a view with its refresh schedule
+pub fn print_view(view: &View) -> String {
+    let refresh = match &view.refresh_cron {
+        Some((_, cron)) => format!("#[refresh(cron: {})]\n", quoted(cron)),
+        None => String::new(),
+    };
+    format!(
+        "{refresh}VIEW {} AS {}\n",
+        view.name.1,
+        print_traversal(&view.traversal)
+    )
+}
+
+/// Print a trigger with its event, binding and statements
+pub fn print_trigger(trigger: &Trigger) -> String {
+    let binding = match &trigger.binding {
+        Some((_, name)) => format!(" AS {name}"),
+        None => String::new(),
+    };
+    let mut out = format!(
+        "ON {}<{}>{binding} DO {{\n",
+        trigger.kind, trigger.item_type.1
+    );
+    print_statements(&mut out, &trigger.query.statements, 1);
+    out.push_str("}\n");
+    out
+}
+
+/// Print a query with its macros, parameters, body and return values
+pub fn print_query(query: &Query) -> String {
+    let mut out = String::new();
+    for built_in_macro in &query.built_in_macros {
+        out.push_str(&print_macro(built_in_macro));
+        out.push('\n');
+    }
+    let parameters = query
+        .parameters
+        .iter()
+        .map(|param| {
+            let optional = if param.is_optional { "?" } else { "" };
+            let checks = param
+                .checks
+                .iter()
+                .map(|(_, check)| format!(" {}", print_param_check(check)))
+                .collect::<String>();
+            format!(
+                "{}{optional}: {}{checks}",
+                param.name.1,
+                print_field_type(&param.param_type.1)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    out.push_str(&format!("QUERY {}({parameters}) =>\n", query.name));
+    print_statements(&mut out, &query.statements, 1);
+    let return_values = query
+        .return_values
+        .iter()
+        .map(print_return_value)
+        .collect::<Vec<_>>()
+        .join(", ");
+    out.push_str(&format!("{INDENT}RETURN {return_values}\n"));
+    out
+}
+
+/// Print a check on a query parameter's value
+pub fn print_param_check(check: &ParamCheck) -> String {
+    match check {
+        ParamCheck::Range(min, max) => format!("@range({min}, {max})"),
+        ParamCheck::Length(min, max) => format!("@length({min}, {max})"),
+        ParamCheck::OneOf(values) => {
+            let values = values
+                .iter()
+                .map(|value| format!("\"{value}\""))
+                .collect::<Vec<_>>();
+            format!("@one_of({})", values.join(", "))
+        }
+    }
+}
+
+/// Print a type as written in schemas and query parameters
+pub fn print_field_type(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Array(inner) => format!("[{}]", print_field_type(inner)),
+        FieldType::Object(fields) => {
+            let fields = sorted(fields)
+                .into_iter()
+                .map(|(name, field_type)| format!("{name}: {}", print_field_type(field_type)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Print an expression as it would appear on the right of an assignment
+pub fn print_expression(expression: &Expression) -> String {
+    match &expression.expr {
+        ExpressionType::Traversal(traversal) => print_traversal(traversal),
+        ExpressionType::Identifier(name) => name.clone(),
+        ExpressionType::StringLiteral(s) => quoted(s),
+        ExpressionType::IntegerLiteral(i) => i.to_string(),
+        ExpressionType::FloatLiteral(f) => float(f.to_string()),
+        ExpressionType::BooleanLiteral(b) => b.to_string(),
+        ExpressionType::ArrayLiteral(items) => format!("[{}]", expressions(items)),
+        ExpressionType::Exists(exists) => format!("EXISTS({})", print_expression(&exists.expr)),
+        ExpressionType::AddVector(add) => print_add_vector(add),
+        ExpressionType::AddNode(add) => print_add_node(add),
+        ExpressionType::AddEdge(add) => print_add_edge(add),
+        ExpressionType::Not(inner) => format!("!{}", print_expression(inner)),
+        ExpressionType::And(exprs) => format!("AND({})", expressions(exprs)),
+        ExpressionType::Or(exprs) => format!("OR({})", expressions(exprs)),
+        ExpressionType::SearchVector(search) => print_search_vector(search),
+        ExpressionType::SearchHybrid(search) => print_search_hybrid(search),
+        ExpressionType::PPR(ppr) => print_ppr(ppr),
+        ExpressionType::BM25Search(search) => print_bm25_search(search),
+        ExpressionType::MergeNodes(merge) => print_merge_nodes(merge),
+        ExpressionType::MathFunctionCall(call) => {
+            format!("{}({})", call.function.name(), expressions(&call.args))
+        }
+        ExpressionType::UdfCall(call) => format!("CALL {}({})", call.name, expressions(&call.args)),
+        ExpressionType::Empty => "NONE".to_string(),
+    }
+}
+
+/// Print a traversal from its start through every step
+pub fn print_traversal(traversal: &Traversal) -> String {
+    let mut out = match &traversal.start {
+        StartNode::Node { node_type, ids } => format!("N{}{}", type_arg(node_type), ids_arg(ids)),
+        StartNode::Edge { edge_type, ids } => format!("E{}{}", type_arg(edge_type), ids_arg(ids)),
+        StartNode::Vector { vector_type, ids } => {
+            format!("V{}{}", type_arg(vector_type), ids_arg(ids))
+        }
+        StartNode::SearchVector(search) => print_search_vector(search),
+        StartNode::SearchHybrid(search) => print_search_hybrid(search),
+        StartNode::PPR(ppr) => print_ppr(ppr),
+        StartNode::Identifier(name) => name.clone(),
+        StartNode::Anonymous => "_".to_string(),
+    };
+    for step in &traversal.steps {
+        out.push_str("::");
+        out.push_str(&print_step(&step.step));
+    }
+    out
+}
+
+fn source_order(a: &Loc, b: &Loc) -> Ordering {
+    (&a.filepath, a.start.byte_offset).cmp(&(&b.filepath, b.start.byte_offset))
+}
+
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// A string literal, whether or not the parser kept its quotes
+fn quoted(s: &str) -> String {
+    match s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        true => s.to_string(),
+        false => format!("\"{s}\""),
+    }
+}
+
+/// A float that can't be read back as an integer
+fn float(printed: String) -> String {
+    match printed.contains(['.', 'e', 'N', 'i']) {
+        true => printed,
+        false => format!("{printed}.0"),
+    }
+}
+
+fn expressions(exprs: &[Expression]) -> String {
+    exprs
+        .iter()
+        .map(print_expression)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_fields(out: &mut String, fields: &[Field], depth: usize) {
+    let pad = INDENT.repeat(depth);
+    for field in fields {
+        let prefix = match field.prefix {
+            FieldPrefix::Index => "INDEX ",
+            FieldPrefix::UniqueIndex => "UNIQUE INDEX ",
+            FieldPrefix::Optional | FieldPrefix::Empty => "",
+        };
+        if let Some(computed) = &field.computed {
+            let materialized = if computed.materialized { " @materialized" } else { "" };
+            out.push_str(&format!(
+                "{pad}{prefix}{}: COUNT({}){materialized},\n",
+                field.name,
+                print_traversal(&computed.traversal)
+            ));
+            continue;
+        }
+        let default = match &field.defaults {
+            Some(DefaultValue::Empty) | None => String::new(),
+            Some(default) => format!(" DEFAULT {}", print_default(default)),
+        };
+        out.push_str(&format!(
+            "{pad}{prefix}{}: {}{default},\n",
+            field.name,
+            print_field_type(&field.field_type)
+        ));
+    }
+}
+
+fn print_default(default: &DefaultValue) -> String {
+    match default {
+        DefaultValue::Now => "NOW".to_string(),
+        DefaultValue::String(s) => quoted(s),
+        DefaultValue::F32(f) => float(f.to_string()),
+        DefaultValue::F64(f) => float(f.to_string()),
+        DefaultValue::I8(i) => i.to_string(),
+        DefaultValue::I16(i) => i.to_string(),
+        DefaultValue::I32(i) => i.to_string(),
+        DefaultValue::I64(i) => i.to_string(),
+        DefaultValue::U8(u) => u.to_string(),
+        DefaultValue::U16(u) => u.to_string(),
+        DefaultValue::U32(u) => u.to_string(),
+        DefaultValue::U64(u) => u.to_string(),
+        DefaultValue::U128(u) => u.to_string(),
+        DefaultValue::Boolean(b) => b.to_string(),
+        DefaultValue::Empty => String::new(),
+    }
+}
+
+fn print_node_schema(node: &NodeSchema, depth: usize) -> String {
+    let pad = INDENT.repeat(depth);
+    let mut out = format!("{pad}N::{} {{\n", node.name.1);
+    print_fields(&mut out, &node.fields, depth + 1);
+    out.push_str(&format!("{pad}}}\n"));
+    out
+}
+
+fn print_vector_schema(vector: &VectorSchema, depth: usize) -> String {
+    let pad = INDENT.repeat(depth);
+    let mut out = match &vector.model {
+        Some(model) => format!("{pad}#[model({model})]\n"),
+        None => String::new(),
+    };
+    out.push_str(&format!("{pad}V::{} {{\n", vector.name));
+    print_fields(&mut out, &vector.fields, depth + 1);
+    out.push_str(&format!("{pad}}}\n"));
+    out
+}
+
+fn print_edge_schema(edge: &EdgeSchema, depth: usize) -> String {
+    let pad = INDENT.repeat(depth);
+    let unique = if edge.unique { " UNIQUE" } else { "" };
+    let acyclic = if edge.acyclic { " @acyclic" } else { "" };
+    let mut out = format!("{pad}E::{}{unique}{acyclic} {{\n", edge.name.1);
+    let cardinality = |cardinality: &Option<Cardinality>| match cardinality {
+        Some(cardinality) => format!(" {cardinality}"),
+        None => String::new(),
+    };
+    out.push_str(&format!(
+        "{pad}{INDENT}From: {}{},\n",
+        edge.from.1,
+        cardinality(&edge.from_cardinality)
+    ));
+    out.push_str(&format!(
+        "{pad}{INDENT}To: {}{},\n",
+        edge.to.1,
+        cardinality(&edge.to_cardinality)
+    ));
+    if let Some(properties) = &edge.properties {
+        out.push_str(&format!("{pad}{INDENT}Properties: {{\n"));
+        print_fields(&mut out, properties, depth + 2);
+        out.push_str(&format!("{pad}{INDENT}}}\n"));
+    }
+    out.push_str(&format!("{pad}}}\n"));
+    out
+}
+
+fn print_migration_item(item: &MigrationItem) -> String {
+    match item {
+        MigrationItem::Node(name) => format!("N::{name}"),
+        MigrationItem::Edge(name) => format!("E::{name}"),
+        MigrationItem::Vector(name) => format!("V::{name}"),
+    }
+}
+
+fn print_item_mapping(out: &mut String, mapping: &MigrationItemMapping) {
+    out.push_str(&format!(
+        "{INDENT}{} => {} {{\n",
+        print_migration_item(&mapping.from_item.1),
+        print_migration_item(&mapping.to_item.1)
+    ));
+    // Edge properties are nested the way they are in the edge's schema
+    let is_edge = matches!(mapping.from_item.1, MigrationItem::Edge(_));
+    let depth = if is_edge { 3 } else { 2 };
+    if is_edge {
+        out.push_str(&format!("{INDENT}{INDENT}Properties: {{\n"));
+    }
+    let pad = INDENT.repeat(depth);
+    for remapping in &mapping.remappings {
+        let cast = match &remapping.cast {
+            Some(cast) => format!(" AS {}", print_field_type(&cast.cast_to)),
+            None => String::new(),
+        };
+        out.push_str(&format!(
+            "{pad}{}: {}{cast},\n",
+            remapping.property_name.1,
+            print_field_value(&remapping.property_value)
+        ));
+    }
+    if is_edge {
+        out.push_str(&format!("{INDENT}{INDENT}}}\n"));
+    }
+    out.push_str(&format!("{INDENT}}}\n"));
+}
+
+fn print_macro(built_in_macro: &BuiltInMacro) -> String {
+    match built_in_macro {
+        BuiltInMacro::MCP => "#[mcp]".to_string(),
+        BuiltInMacro::Model(name) => format!("#[model({name})]"),
+        BuiltInMacro::Priority(Priority::Interactive) => "#[priority(interactive)]".to_string(),
+        BuiltInMacro::Priority(Priority::Batch) => "#[priority(batch)]".to_string(),
+        BuiltInMacro::Cache { ttl_ms } => {
+            let ttl = [(3_600_000, "h"), (60_000, "m"), (1_000, "s")]
+                .into_iter()
+                .find(|(unit_ms, _)| *ttl_ms > 0 && ttl_ms % unit_ms == 0)
+                .map(|(unit_ms, unit)| format!("{}{unit}", ttl_ms / unit_ms))
+                .unwrap_or_else(|| format!("{ttl_ms}ms"));
+            format!("#[cache(ttl: {ttl})]")
+        }
+        BuiltInMacro::Roles(roles) => {
+            let roles = roles.iter().map(|role| quoted(role)).collect::<Vec<_>>();
+            format!("#[roles({})]", roles.join(", "))
+        }
+        BuiltInMacro::MaxConcurrency(limit) => format!("#[max_concurrency({limit})]"),
+    }
+}
+
+fn print_statements(out: &mut String, statements: &[Statement], depth: usize) {
+    let pad = INDENT.repeat(depth);
+    for statement in statements {
+        match &statement.statement {
+            StatementType::Assignment(assignment) => out.push_str(&format!(
+                "{pad}{} <- {}\n",
+                assignment.variable,
+                print_expression(&assignment.value)
+            )),
+            StatementType::Expression(expr) => {
+                out.push_str(&format!("{pad}{}\n", print_expression(expr)))
+            }
+            StatementType::Drop(expr) => {
+                out.push_str(&format!("{pad}DROP {}\n", print_expression(expr)))
+            }
+            StatementType::ForLoop(for_loop) => {
+                let variable = match &for_loop.variable {
+                    ForLoopVars::Identifier { name, .. } => name.clone(),
+                    ForLoopVars::ObjectAccess { name, field, .. } => format!("{name}.{field}"),
+                    ForLoopVars::ObjectDestructuring { fields, .. } => {
+                        let fields = fields
+                            .iter()
+                            .map(|(_, name)| name.as_str())
+                            .collect::<Vec<_>>();
+                        format!("{{{}}}", fields.join(", "))
+                    }
+                };
+                out.push_str(&format!(
+                    "{pad}FOR {variable} IN {} {{\n",
+                    for_loop.in_variable.1
+                ));
+                print_statements(out, &for_loop.statements, depth + 1);
+                out.push_str(&format!("{pad}}}\n"));
+            }
+        }
+    }
+}
+
+fn print_return_value(return_value: &ReturnType) -> String {
+    match return_value {
+        ReturnType::Array(items) => {
+            let items = items.iter().map(print_return_value).collect::<Vec<_>>();
+            format!("[{}]", items.join(", "))
+        }
+        ReturnType::Object(fields) => {
+            let fields = sorted(fields)
+                .into_iter()
+                .map(|(key, value)| format!("{key}: {}", print_return_value(value)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(", "))
+        }
+        ReturnType::Expression(expr) => print_expression(expr),
+        ReturnType::Empty => "NONE".to_string(),
+    }
+}
+
+fn type_arg(name: &str) -> String {
+    match name.is_empty() {
+        true => String::new(),
+        false => format!("<{name}>"),
+    }
+}
+
+fn ids_arg(ids: &Option<Vec<IdType>>) -> String {
+    match ids {
+        Some(ids) if !ids.is_empty() => {
+            let ids = ids.iter().map(print_id).collect::<Vec<_>>();
+            format!("({})", ids.join(", "))
+        }
+        _ => String::new(),
+    }
+}
+
+fn print_id(id: &IdType) -> String {
+    match id {
+        IdType::Literal { value, .. } => quoted(value),
+        IdType::Identifier { value, .. } => value.clone(),
+        IdType::ByIndex { index, value, .. } => {
+            format!("{{{}: {}}}", print_id(index), print_value_type(value))
+        }
+    }
+}
+
+fn print_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => quoted(s),
+        Value::F32(f) => float(f.to_string()),
+        Value::F64(f) => float(f.to_string()),
+        Value::Array(items) => {
+            let items = items.iter().map(print_value).collect::<Vec<_>>();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(fields) => {
+            let fields = sorted(fields)
+                .into_iter()
+                .map(|(key, value)| format!("{key}: {}", print_value(value)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(", "))
+        }
+        Value::Empty => "NONE".to_string(),
+        other => other.inner_stringify(),
+    }
+}
+
+fn print_value_type(value: &ValueType) -> String {
+    match value {
+        ValueType::Literal { value, .. } => print_value(value),
+        ValueType::Identifier { value, .. } => value.clone(),
+        ValueType::Object { fields, .. } => print_properties(fields),
+    }
+}
+
+/// Properties given to `AddN`, `AddE` and `AddV`
+fn print_properties(fields: &HashMap<String, ValueType>) -> String {
+    let fields = sorted(fields)
+        .into_iter()
+        .map(|(key, value)| format!("{key}: {}", print_value_type(value)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(", "))
+}
+
+fn print_field_value(value: &FieldValue) -> String {
+    match &value.value {
+        FieldValueType::Traversal(traversal) => print_traversal(traversal),
+        FieldValueType::Expression(expr) => print_expression(expr),
+        FieldValueType::Fields(fields) => print_object_fields(fields, false),
+        FieldValueType::Literal(value) => print_value(value),
+        FieldValueType::Identifier(name) => name.clone(),
+        FieldValueType::Empty => "NONE".to_string(),
+    }
+}
+
+/// Fields of an `UPDATE` or upsert
+fn print_field_additions(fields: &[FieldAddition]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| format!("{}: {}", field.key, print_field_value(&field.value)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Fields of an object remapping, where `{name}` is short for `{name: name}`
+fn print_object_fields(fields: &[FieldAddition], should_spread: bool) -> String {
+    let mut printed = fields
+        .iter()
+        .map(|field| match &field.value.value {
+            FieldValueType::Identifier(name) if *name == field.key => name.clone(),
+            _ => format!("{}: {}", field.key, print_field_value(&field.value)),
+        })
+        .collect::<Vec<_>>();
+    if should_spread {
+        printed.push("..".to_string());
+    }
+    format!("{{{}}}", printed.join(", "))
+}
+
+fn print_object(object: &Object) -> String {
+    print_object_fields(&object.fields, object.should_spread)
+}
+
+fn print_connection(connection: &EdgeConnection) -> String {
+    print_to_from(&connection.from_id, &connection.to_id)
+}
+
+fn print_to_from(from: &Option<IdType>, to: &Option<IdType>) -> String {
+    let mut out = String::new();
+    if let Some(from) = from {
+        out.push_str(&format!("::From({})", print_id(from)));
+    }
+    if let Some(to) = to {
+        out.push_str(&format!("::To({})", print_id(to)));
+    }
+    out
+}
+
+fn print_vector_data(data: &Option<VectorData>) -> String {
+    match data {
+        Some(VectorData::Vector(values)) => {
+            let values = values
+                .iter()
+                .map(|value| float(value.to_string()))
+                .collect::<Vec<_>>();
+            format!("[{}]", values.join(", "))
+        }
+        Some(VectorData::Identifier(name)) => name.clone(),
+        Some(VectorData::Embed(embed)) => {
+            let text = match &embed.value {
+                EvaluatesToString::Identifier(name) => name.clone(),
+                EvaluatesToString::StringLiteral(s) => quoted(s),
+            };
+            match &embed.model {
+                Some(model) => format!("Embed({text}, model: {model})"),
+                None => format!("Embed({text})"),
+            }
+        }
+        None => String::new(),
+    }
+}
+
+fn print_number(number: &Option<EvaluatesToNumber>) -> String {
+    let Some(number) = number else {
+        return String::new();
+    };
+    match &number.value {
+        EvaluatesToNumberType::I8(i) => i.to_string(),
+        EvaluatesToNumberType::I16(i) => i.to_string(),
+        EvaluatesToNumberType::I32(i) => i.to_string(),
+        EvaluatesToNumberType::I64(i) => i.to_string(),
+        EvaluatesToNumberType::U8(u) => u.to_string(),
+        EvaluatesToNumberType::U16(u) => u.to_string(),
+        EvaluatesToNumberType::U32(u) => u.to_string(),
+        EvaluatesToNumberType::U64(u) => u.to_string(),
+        EvaluatesToNumberType::U128(u) => u.to_string(),
+        EvaluatesToNumberType::F32(f) => float(f.to_string()),
+        EvaluatesToNumberType::F64(f) => float(f.to_string()),
+        EvaluatesToNumberType::Identifier(name) => name.clone(),
+    }
+}
+
+fn print_pre_filter(pre_filter: &Option<Box<Expression>>) -> String {
+    match pre_filter {
+        Some(expr) => format!("::PREFILTER({})", print_expression(expr)),
+        None => String::new(),
+    }
+}
+
+fn print_search_vector(search: &SearchVector) -> String {
+    format!(
+        "SearchV<{}>({}, {}){}",
+        search.vector_type.as_deref().unwrap_or_default(),
+        print_vector_data(&search.data),
+        print_number(&search.k),
+        print_pre_filter(&search.pre_filter)
+    )
+}
+
+fn print_search_hybrid(search: &SearchHybrid) -> String {
+    let text = search
+        .text_query
+        .as_ref()
+        .map(print_value_type)
+        .unwrap_or_default();
+    format!(
+        "SearchHybrid<{}>({}, {text}, {}){}",
+        search.vector_type.as_deref().unwrap_or_default(),
+        print_vector_data(&search.vec_data),
+        print_number(&search.k),
+        print_pre_filter(&search.pre_filter)
+    )
+}
+
+fn print_bm25_search(search: &BM25Search) -> String {
+    let text = search
+        .data
+        .as_ref()
+        .map(print_value_type)
+        .unwrap_or_default();
+    format!(
+        "SearchBM25<{}>({text}, {})",
+        search.type_arg.as_deref().unwrap_or_default(),
+        print_number(&search.k)
+    )
+}
+
+fn print_merge_nodes(merge: &MergeNodes) -> String {
+    let conflict = match merge.conflict {
+        ConflictPolicy::Keep => "",
+        ConflictPolicy::Overwrite => ", conflict: OVERWRITE",
+        ConflictPolicy::Fail => ", conflict: FAIL",
+    };
+    format!("MERGE_NODES({}, {}{conflict})", merge.keep, merge.remove)
+}
+
+fn print_ppr(ppr: &PPR) -> String {
+    let mut args = vec![
+        format!("seeds: {}", ppr.seeds.as_deref().unwrap_or_default()),
+        format!("universe: {}", ppr.universe.as_deref().unwrap_or_default()),
+    ];
+    if let Some(weights) = &ppr.weights {
+        let weights = weights
+            .iter()
+            .map(|(edge, weight)| format!("{edge}: {}", float(weight.to_string())))
+            .collect::<Vec<_>>();
+        args.push(format!("weights: {{{}}}", weights.join(", ")));
+    }
+    for (name, value) in [
+        ("depth", &ppr.depth),
+        ("damping", &ppr.damping),
+        ("limit", &ppr.limit),
+    ] {
+        if value.is_some() {
+            args.push(format!("{name}: {}", print_number(value)));
+        }
+    }
+    format!(
+        "PPR<{}>({})",
+        ppr.node_type.as_deref().unwrap_or_default(),
+        args.join(", ")
+    )
+}
+
+fn print_add_node(add: &AddNode) -> String {
+    let fields = match &add.fields {
+        Some(fields) => format!("({})", print_properties(fields)),
+        None => String::new(),
+    };
+    format!(
+        "AddN<{}>{fields}",
+        add.node_type.as_deref().unwrap_or_default()
+    )
+}
+
+fn print_add_vector(add: &AddVector) -> String {
+    let fields = match &add.fields {
+        Some(fields) => format!(", {}", print_properties(fields)),
+        None => String::new(),
+    };
+    format!(
+        "AddV<{}>({}{fields})",
+        add.vector_type.as_deref().unwrap_or_default(),
+        print_vector_data(&add.data)
+    )
+}
+
+fn print_add_edge(add: &AddEdge) -> String {
+    let fields = match &add.fields {
+        Some(fields) => format!("({})", print_properties(fields)),
+        None => String::new(),
+    };
+    format!(
+        "AddE<{}>{fields}{}",
+        add.edge_type.as_deref().unwrap_or_default(),
+        print_connection(&add.connection)
+    )
+}
+
+fn print_weight(weight: &Option<WeightExpression>) -> String {
+    match weight {
+        Some(WeightExpression::Expression(expr)) => print_expression(expr),
+        Some(WeightExpression::Property(property)) => format!("_::{{{property}}}"),
+        Some(WeightExpression::Default) | None => "1.0".to_string(),
+    }
+}
+
+fn print_graph_step(step: &GraphStep) -> String {
+    let type_arg = |name: &Option<String>| type_arg(name.as_deref().unwrap_or_default());
+    match &step.step {
+        GraphStepType::Out(label) => format!("Out<{label}>"),
+        GraphStepType::In(label) => format!("In<{label}>"),
+        GraphStepType::OutE(label) => format!("OutE<{label}>"),
+        GraphStepType::InE(label) => format!("InE<{label}>"),
+        GraphStepType::FromN => "FromN".to_string(),
+        GraphStepType::ToN => "ToN".to_string(),
+        GraphStepType::FromV => "FromV".to_string(),
+        GraphStepType::ToV => "ToV".to_string(),
+        GraphStepType::ShortestPath(path) => format!(
+            "ShortestPath{}{}",
+            type_arg(&path.type_arg),
+            print_to_from(&path.from, &path.to)
+        ),
+        GraphStepType::ShortestPathDijkstras(path) => format!(
+            "ShortestPathDijkstras{}({}){}",
+            type_arg(&path.type_arg),
+            print_weight(&path.weight_expr),
+            print_to_from(&path.from, &path.to)
+        ),
+        GraphStepType::ShortestPathBFS(path) => format!(
+            "ShortestPathBFS{}{}",
+            type_arg(&path.type_arg),
+            print_to_from(&path.from, &path.to)
+        ),
+        GraphStepType::ShortestPathAStar(path) => format!(
+            "ShortestPathAStar{}({}, {}){}",
+            type_arg(&path.type_arg),
+            print_weight(&path.weight_expr),
+            quoted(&path.heuristic_property),
+            print_to_from(&path.from, &path.to)
+        ),
+        GraphStepType::Subgraph(subgraph) => {
+            format!("SUBGRAPH(depth: {})", print_number(&Some(subgraph.depth.clone())))
+        }
+        GraphStepType::Ego(ego) => format!(
+            "EGO<{}>(depth: {})",
+            ego.label,
+            print_number(&Some(ego.depth.clone()))
+        ),
+        GraphStepType::SearchVector(search) => print_search_vector(search),
+    }
+}
+
+/// Print a single step as it appears after `::`
+pub fn print_step(step: &StepType) -> String {
+    match step {
+        StepType::Node(step) | StepType::Edge(step) => print_graph_step(step),
+        StepType::Where(expr) => format!("WHERE({})", print_expression(expr)),
+        StepType::BooleanOperation(op) => {
+            let (name, expr) = match &op.op {
+                BooleanOpType::And(exprs) => return format!("AND({})", expressions(exprs)),
+                BooleanOpType::Or(exprs) => return format!("OR({})", expressions(exprs)),
+                BooleanOpType::GreaterThan(expr) => ("GT", expr),
+                BooleanOpType::GreaterThanOrEqual(expr) => ("GTE", expr),
+                BooleanOpType::LessThan(expr) => ("LT", expr),
+                BooleanOpType::LessThanOrEqual(expr) => ("LTE", expr),
+                BooleanOpType::Equal(expr) => ("EQ", expr),
+                BooleanOpType::NotEqual(expr) => ("NEQ", expr),
+                BooleanOpType::Contains(expr) => ("CONTAINS", expr),
+                BooleanOpType::IsIn(expr) => ("IS_IN", expr),
+            };
+            format!("{name}({})", print_expression(expr))
+        }
+        StepType::Count => "COUNT".to_string(),
+        StepType::Paths => "PATHS".to_string(),
+        StepType::Degree(degree) => {
+            let direction = match degree.direction {
+                DegreeDirection::Out => "out",
+                DegreeDirection::In => "in",
+                DegreeDirection::Both => "both",
+            };
+            format!("DEGREE<{}>({direction})", degree.label)
+        }
+        StepType::NeighborCount => "NEIGHBOR_COUNT".to_string(),
+        StepType::Update(update) => format!("UPDATE({})", print_field_additions(&update.fields)),
+        // Only `UpsertN`, `UpsertE` and `UpsertV` are written in HQL
+        StepType::Upsert(upsert) => format!("UpsertN({})", print_field_additions(&upsert.fields)),
+        StepType::UpsertN(upsert) => format!("UpsertN({})", print_field_additions(&upsert.fields)),
+        StepType::UpsertE(upsert) => format!(
+            "UpsertE({}){}",
+            print_field_additions(&upsert.fields),
+            print_connection(&upsert.connection)
+        ),
+        StepType::UpsertV(upsert) => format!(
+            "UpsertV({}, {})",
+            print_vector_data(&upsert.data),
+            print_field_additions(&upsert.fields)
+        ),
+        StepType::Object(object) => print_object(object),
+        StepType::Exclude(exclude) => {
+            let fields = exclude
+                .fields
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>();
+            format!("!{{{}}}", fields.join(", "))
+        }
+        StepType::Closure(closure) => {
+            format!("|{}|{}", closure.identifier, print_object(&closure.object))
+        }
+        StepType::Range((start, end)) => format!(
+            "RANGE({}, {})",
+            print_expression(start),
+            print_expression(end)
+        ),
+        StepType::Sample(sample) => {
+            format!("SAMPLE({})", print_number(&Some(sample.size.clone())))
+        }
+        StepType::OrderBy(order_by) => {
+            let order = match order_by.order_by_type {
+                OrderByType::Asc => "Asc",
+                OrderByType::Desc => "Desc",
+            };
+            format!("ORDER<{order}>({})", print_expression(&order_by.expression))
+        }
+        StepType::Aggregate(aggregate) => {
+            format!("AGGREGATE_BY({})", aggregate.properties.join(", "))
+        }
+        StepType::GroupBy(group_by) => format!("GROUP_BY({})", group_by.properties.join(", ")),
+        StepType::BucketBy(bucket_by) => match &bucket_by.timezone {
+            Some(EvaluatesToString::StringLiteral(tz)) => format!(
+                "BUCKET_BY({}, {}, {})",
+                bucket_by.property,
+                bucket_by.interval,
+                quoted(tz)
+            ),
+            Some(EvaluatesToString::Identifier(tz)) => format!(
+                "BUCKET_BY({}, {}, {tz})",
+                bucket_by.property, bucket_by.interval
+            ),
+            None => format!("BUCKET_BY({}, {})", bucket_by.property, bucket_by.interval),
+        },
+        StepType::Stat(stat) => format!("{}({})", stat.function.name(), stat.property),
+        StepType::AddEdge(add) => print_add_edge(add),
+        StepType::First => "FIRST".to_string(),
+        StepType::Delete => "DELETE".to_string(),
+        StepType::RerankRRF(rerank) => match &rerank.k {
+            Some(k) => format!("RerankRRF(k: {})", print_expression(k)),
+            None => "RerankRRF".to_string(),
+        },
+        StepType::RerankMMR(rerank) => {
+            let distance = match &rerank.distance {
+                Some(MMRDistance::Cosine) => ", distance: \"cosine\"".to_string(),
+                Some(MMRDistance::Euclidean) => ", distance: \"euclidean\"".to_string(),
+                Some(MMRDistance::DotProduct) => ", distance: \"dotproduct\"".to_string(),
+                Some(MMRDistance::Identifier(name)) => format!(", distance: {}", quoted(name)),
+                None => String::new(),
+            };
+            format!(
+                "RerankMMR(lambda: {}{distance})",
+                print_expression(&rerank.lambda)
+            )
+        }
+        StepType::Boost(boost) => {
+            let mut args = boost
+                .signals
+                .iter()
+                .map(|signal| signal.as_str().to_string())
+                .collect::<Vec<_>>();
+            if let Some(half_life) = &boost.half_life {
+                args.push(format!("half_life: {}", print_expression(half_life)));
+            }
+            match args.is_empty() {
+                true => "BOOST".to_string(),
+                false => format!("BOOST({})", args.join(", ")),
+            }
+        }
+    }
+}
@@ -1,11 +1,57 @@
 use crate::helixc::parser::{
     HelixParser, ParserError, Rule,
-    location::HasLoc,
-    types::{BuiltInMacro, Parameter, Query, Statement, StatementType},
+    location::{HasLoc, Loc},
+    types::{BuiltInMacro, ParamCheck, Parameter, Query, Statement, StatementType},
 };
+use crate::protocol::request::Priority;
 use pest::iterators::Pair;
 use std::collections::HashSet;
 
+/// Parse a cache ttl such as `500ms`, `30s`, `5m` or `1h` into milliseconds
+fn parse_cache_ttl(ttl: &str) -> Result<u64, ParserError> {
+    let split = ttl.find(|c: char| !c.is_ascii_digit()).unwrap_or(ttl.len());
+    let (value, unit) = ttl.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| ParserError::from(format!("Invalid cache ttl `{ttl}`")))?;
+    let multiplier = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => {
+            return Err(ParserError::from(format!(
+                "Invalid cache ttl unit `{unit}`"
+            )));
+        }
+    };
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| ParserError::from(format!("Cache ttl `{ttl}` is too large")))
+}
+
+/// The comment lines directly above the query starting at `start` in `input`, without their
+/// slashes
+fn doc_comment(input: &str, start: usize) -> Option<String> {
+    let before = &input[..start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    if !before[line_start..].trim().is_empty() {
+        return None;
+    }
+    let mut doc: Vec<&str> = before[..line_start]
+        .lines()
+        .rev()
+        .map_while(|line| line.trim().strip_prefix("//"))
+        .map(|line| {
+            let line = line.trim_start_matches('/');
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect();
+    doc.reverse();
+    let doc = doc.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
 impl HelixParser {
     pub(super) fn parse_query_def(
         &self,
@@ -13,29 +59,70 @@ impl HelixParser {
         filepath: String,
     ) -> Result<Query, ParserError> {
         let original_query = pair.clone().as_str().to_string();
+        let doc = doc_comment(pair.as_span().get_input(), pair.as_span().start());
         let mut pairs = pair.clone().into_inner();
-        let built_in_macro = match pairs.peek() {
-            Some(pair) if pair.as_rule() == Rule::built_in_macro => {
-                let built_in_macro = match pair.into_inner().next() {
-                    Some(pair) => match pair.as_rule() {
-                        Rule::mcp_macro => Some(BuiltInMacro::MCP),
-                        Rule::model_macro => match pair.into_inner().next() {
-                            Some(model_name) => {
-                                Some(BuiltInMacro::Model(model_name.as_str().to_string()))
-                            }
-                            None => {
-                                return Err(ParserError::from("Model macro missing model name"));
-                            }
-                        },
-                        _ => None,
+        let mut built_in_macros = Vec::new();
+        while let Some(pair) = pairs.peek()
+            && pair.as_rule() == Rule::built_in_macro
+        {
+            let built_in_macro = match pair.into_inner().next() {
+                Some(pair) => match pair.as_rule() {
+                    Rule::mcp_macro => Some(BuiltInMacro::MCP),
+                    Rule::model_macro => match pair.into_inner().next() {
+                        Some(model_name) => {
+                            Some(BuiltInMacro::Model(model_name.as_str().to_string()))
+                        }
+                        None => {
+                            return Err(ParserError::from("Model macro missing model name"));
+                        }
+                    },
+                    Rule::priority_macro => match pair.into_inner().next() {
+                        Some(level) => Some(BuiltInMacro::Priority(
+                            level.as_str().parse::<Priority>().map_err(|_| {
+                                ParserError::from("Priority macro has an unknown level")
+                            })?,
+                        )),
+                        None => {
+                            return Err(ParserError::from("Priority macro missing level"));
+                        }
                     },
+                    Rule::cache_macro => match pair.into_inner().next() {
+                        Some(ttl) => Some(BuiltInMacro::Cache {
+                            ttl_ms: parse_cache_ttl(ttl.as_str())?,
+                        }),
+                        None => {
+                            return Err(ParserError::from("Cache macro missing ttl"));
+                        }
+                    },
+                    Rule::roles_macro => Some(BuiltInMacro::Roles(
+                        pair.into_inner()
+                            .map(|role| role.as_str().trim_matches('"').to_string())
+                            .collect(),
+                    )),
+                    Rule::concurrency_macro => {
+                        let limit = pair
+                            .into_inner()
+                            .next()
+                            .ok_or_else(|| {
+                                ParserError::from("Max concurrency macro missing limit")
+                            })?
+                            .as_str();
+                        match limit.parse::<usize>() {
+                            Ok(limit) if limit > 0 => Some(BuiltInMacro::MaxConcurrency(limit)),
+                            _ => {
+                                return Err(ParserError::from(format!(
+                                    "Invalid max concurrency `{limit}`, expected a positive number"
+                                )));
+                            }
+                        }
+                    }
                     _ => None,
-                };
-                pairs.next();
-                built_in_macro
-            }
-            _ => None,
-        };
+                },
+                _ => None,
+            };
+            pairs.next();
+            built_in_macros.extend(built_in_macro);
+        }
         let name = pairs
             .next()
             .ok_or_else(|| ParserError::from("Expected query name"))?
@@ -57,7 +144,8 @@ impl HelixParser {
         )?;
 
         Ok(Query {
-            built_in_macro,
+            doc,
+            built_in_macros,
             name,
             parameters,
             statements,
@@ -104,12 +192,17 @@ impl HelixParser {
                     param_type_pair,
                     Some(&self.source),
                 )?;
+                let checks = inner
+                    .skip(1)
+                    .map(|check| self.parse_param_check(check))
+                    .collect::<Result<Vec<_>, _>>()?;
 
                 if seen.insert(name.1.clone()) {
                     Ok(Parameter {
                         name,
                         param_type: (param_type_location, param_type),
                         is_optional,
+                        checks,
                         loc: pair.loc(),
                     })
                 } else {
@@ -129,6 +222,52 @@ impl HelixParser {
             .collect::<Result<Vec<_>, _>>()
     }
 
+    /// Parse a `@range`, `@length` or `@one_of` check following a parameter's type
+    fn parse_param_check(&self, pair: Pair<Rule>) -> Result<(Loc, ParamCheck), ParserError> {
+        let check = pair
+            .into_inner()
+            .next()
+            .ok_or_else(|| ParserError::from("Expected parameter check"))?;
+        let loc = check.loc();
+        let rule = check.as_rule();
+        let mut args = check.into_inner();
+        let mut bound = || {
+            args.next()
+                .ok_or_else(|| ParserError::from("Expected a bound"))
+                .map(|arg| arg.as_str().to_string())
+        };
+        let check = match rule {
+            Rule::range_check => {
+                let (min, max) = (bound()?, bound()?);
+                let parse = |bound: &str| {
+                    bound
+                        .parse::<f64>()
+                        .map_err(|_| ParserError::from(format!("Invalid bound `{bound}`")))
+                };
+                ParamCheck::Range(parse(&min)?, parse(&max)?)
+            }
+            Rule::length_check => {
+                let (min, max) = (bound()?, bound()?);
+                let parse = |bound: &str| {
+                    bound
+                        .parse::<usize>()
+                        .map_err(|_| ParserError::from(format!("Invalid length `{bound}`")))
+                };
+                ParamCheck::Length(parse(&min)?, parse(&max)?)
+            }
+            Rule::one_of_check => ParamCheck::OneOf(
+                args.map(|value| self.parse_string_literal(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            other => {
+                return Err(ParserError::from(format!(
+                    "Unexpected parameter check: {other:?}"
+                )));
+            }
+        };
+        Ok((loc, check))
+    }
+
     pub(super) fn parse_query_body(&self, pair: Pair<Rule>) -> Result<Vec<Statement>, ParserError> {
         pair.into_inner()
             .map(|p| match p.as_rule() {
@@ -275,8 +414,8 @@ mod tests {
 
         let parsed = result.unwrap();
         assert!(matches!(
-            parsed.queries[0].built_in_macro,
-            Some(BuiltInMacro::MCP)
+            parsed.queries[0].built_in_macros.as_slice(),
+            [BuiltInMacro::MCP]
         ));
     }
 
@@ -297,8 +436,119 @@ mod tests {
 
         let parsed = result.unwrap();
         assert!(matches!(
-            parsed.queries[0].built_in_macro,
-            Some(BuiltInMacro::Model(_))
+            parsed.queries[0].built_in_macros.as_slice(),
+            [BuiltInMacro::Model(_)]
+        ));
+    }
+
+    #[test]
+    fn test_parse_query_with_priority_macro() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[priority(batch)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        assert!(matches!(
+            parsed.queries[0].built_in_macros.as_slice(),
+            [BuiltInMacro::Priority(Priority::Batch)]
+        ));
+    }
+
+    #[test]
+    fn test_parse_query_with_cache_macro() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[cache(ttl: 30s)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        assert!(matches!(
+            parsed.queries[0].built_in_macros.as_slice(),
+            [BuiltInMacro::Cache { ttl_ms: 30_000 }]
+        ));
+    }
+
+    #[test]
+    fn test_parse_query_with_roles_macro() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[roles(admin, "org:editor")]
+            QUERY dropPerson(id: ID) =>
+                DROP N<Person>(id)
+                RETURN "done"
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        match parsed.queries[0].built_in_macros.as_slice() {
+            [BuiltInMacro::Roles(roles)] => assert_eq!(roles, &["admin", "org:editor"]),
+            other => panic!("unexpected macros: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_query_with_max_concurrency_macro() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[max_concurrency(2)]
+            QUERY allPeople() =>
+                people <- N<Person>
+                RETURN people
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        assert!(matches!(
+            parsed.queries[0].built_in_macros.as_slice(),
+            [BuiltInMacro::MaxConcurrency(2)]
+        ));
+
+        let content = write_to_temp_file(vec![&source.replace("(2)", "(0)")]);
+        assert!(HelixParser::parse_source(&content).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache_ttl_units() {
+        assert_eq!(parse_cache_ttl("250ms").unwrap(), 250);
+        assert_eq!(parse_cache_ttl("30s").unwrap(), 30_000);
+        assert_eq!(parse_cache_ttl("5m").unwrap(), 300_000);
+        assert_eq!(parse_cache_ttl("1h").unwrap(), 3_600_000);
+        assert!(parse_cache_ttl("99999999999999999999h").is_err());
+    }
+
+    #[test]
+    fn test_parse_query_with_multiple_macros() {
+        let source = r#"
+            N::Person { name: String }
+
+            #[mcp]
+            #[priority(interactive)]
+            QUERY getUser(id: ID) =>
+                user <- N<Person>(id)
+                RETURN user
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        assert!(matches!(
+            parsed.queries[0].built_in_macros.as_slice(),
+            [
+                BuiltInMacro::MCP,
+                BuiltInMacro::Priority(Priority::Interactive)
+            ]
         ));
     }
 
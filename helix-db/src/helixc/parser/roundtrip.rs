@@ -0,0 +1,313 @@
+// Copyright 2025 HelixDB Inc.
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Round trip check between the parser and the pretty-printer, run by the fuzz targets in
+//! `fuzz/` and over the hql-tests corpus.
+//!
+//! The parser doesn't keep everything it reads (comments, layout, item order within a file),
+//! so a round trip compares printed source rather than ASTs: printing a parsed input, parsing
+//! that again and printing it must give back the same text.
+
+use crate::helixc::parser::{
+    HelixParser,
+    errors::ParserError,
+    pretty::print_source,
+    types::{Content, HxFile, Source},
+};
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub enum RoundtripError {
+    /// The printed source no longer parses
+    Reparse { printed: String, error: ParserError },
+    /// The printed source parses to something that prints differently
+    Mismatch { printed: String, reprinted: String },
+}
+
+impl Display for RoundtripError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RoundtripError::Reparse { printed, error } => {
+                write!(f, "printed source does not parse: {error}\n{printed}")
+            }
+            RoundtripError::Mismatch { printed, reprinted } => write!(
+                f,
+                "printed source does not print the same\nfirst:\n{printed}\nsecond:\n{reprinted}"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Debug for RoundtripError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Parse the given files as one source
+pub fn parse(files: &[(&str, &str)]) -> Result<Source, ParserError> {
+    let content = Content {
+        content: String::new(),
+        source: Source::default(),
+        files: files
+            .iter()
+            .map(|(name, content)| HxFile {
+                name: name.to_string(),
+                content: content.to_string(),
+            })
+            .collect(),
+    };
+    HelixParser::parse_source(&content)
+}
+
+/// Check that the given files survive printing and parsing again.
+///
+/// Input the parser rejects has nothing to round trip and passes.
+pub fn check_roundtrip(files: &[(&str, &str)]) -> Result<(), RoundtripError> {
+    let Ok(source) = parse(files) else {
+        return Ok(());
+    };
+    let printed = print_source(&source);
+    let reparsed = match parse(&[("printed.hx", &printed)]) {
+        Ok(reparsed) => reparsed,
+        Err(error) => return Err(RoundtripError::Reparse { printed, error }),
+    };
+    let reprinted = print_source(&reparsed);
+    match printed == reprinted {
+        true => Ok(()),
+        false => Err(RoundtripError::Mismatch { printed, reprinted }),
+    }
+}
+
+/// Round trip every directory of `.hx` files under `root`, each directory parsed as one source.
+///
+/// Returns the number of directories checked and the failures among them.
+pub fn check_corpus(root: &Path) -> std::io::Result<(usize, Vec<(PathBuf, RoundtripError)>)> {
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "hx") {
+                let content = fs::read_to_string(&path)?;
+                files.push((path.display().to_string(), content));
+            }
+        }
+        if files.is_empty() {
+            continue;
+        }
+        files.sort();
+        checked += 1;
+        let files = files
+            .iter()
+            .map(|(name, content)| (name.as_str(), content.as_str()))
+            .collect::<Vec<_>>();
+        if let Err(error) = check_roundtrip(&files) {
+            failures.push((dir, error));
+        }
+    }
+    Ok((checked, failures))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVERYTHING: &str = r#"
+N::User {
+    UNIQUE INDEX email: String,
+    INDEX name: String DEFAULT "anon",
+    age: U32 DEFAULT 0,
+    score: F64 DEFAULT 1.5,
+    tags: [String],
+    meta: {a: I64, b: Boolean},
+    created_at: Date DEFAULT NOW,
+    follower_count: COUNT(_::In<Follows>) @materialized,
+}
+
+N::Post {
+    title: String,
+}
+
+E::Follows UNIQUE {
+    From: User,
+    To: User,
+    Properties: {
+        since: Date,
+        weight: F64,
+    }
+}
+
+E::Authored {
+    From: User [0..*],
+    To: Post [1],
+}
+
+E::Pinned {
+    From: User [0..1],
+    To: Post [2..5],
+}
+
+E::Manages UNIQUE @acyclic {
+    From: User,
+    To: User,
+}
+
+V::Doc {
+    text: String,
+}
+
+#[model("gemini:gemini-embedding-001")]
+V::Chunk {
+    text: String,
+}
+
+schema::2 {
+    N::User {
+        email: String,
+        name: String,
+    }
+}
+
+MIGRATION schema::1 => schema::2 {
+    N::User => N::User {
+        email: email AS String,
+        name: name,
+    }
+}
+
+VIEW Adults AS N<User>::WHERE(_::{age}::GT(17))::ORDER<Desc>(_::{age})::RANGE(0, 100)
+
+#[refresh(cron: "0 * * * *")]
+VIEW Followed AS N<User>::Out<Follows>
+
+ON AddN<Post> AS post DO {
+    author <- N<User>({email: "editor@example.com"})
+    AddE<Authored>::From(author)::To(post)
+}
+
+ON AddE<Follows> DO {
+    AddN<Post>({title: "followed"})
+}
+
+#[mcp]
+#[priority(batch)]
+#[cache(ttl: 5m)]
+#[roles("admin", "reader")]
+#[max_concurrency(2)]
+QUERY everything(id: ID, name: String, age?: U32, ids: [ID], filter: {a: I64}, vec: [F64]) =>
+    user <- N<User>(id)
+    by_email <- N<User>({email: name})
+    all <- N<User>::WHERE(AND(_::{age}::GT(18), !EXISTS(_::Out<Follows>), OR(_::{name}::EQ("a"), _::{age}::IS_IN([1, 2]))))
+    friends <- user::Out<Follows>::In<Follows>::OutE<Follows>::ToN::RANGE(0, 10)::ORDER<Desc>(_::{age})
+    count <- friends::COUNT
+    first <- N<User>::FIRST
+    adults <- N<Adults>::RANGE(0, 10)
+    remapped <- friends::{name, years: age, id: ID, posts: _::Out<Authored>::COUNT, ..}
+    closed <- friends::|f|{name: f::{name}}
+    excluded <- friends::!{age, email}
+    grouped <- friends::GROUP_BY(age)
+    aggregated <- friends::AGGREGATE_BY(age, name)
+    new_user <- AddN<User>({name: name, age: 3, score: 2.5, email: "x"})
+    AddE<Follows>({since: "2024-01-01"})::From(user)::To(new_user)
+    edge <- AddE<Authored>::From(user)::To(new_user)
+    updated <- N<User>(id)::UPDATE({name: "new", age: age})
+    upserted <- N<User>::WHERE(_::{name}::EQ(name))::UpsertN({name: name})
+    upserted_edge <- E<Follows>::UpsertE({weight: 1.0})::From(user)::To(new_user)
+    doc <- AddV<Doc>(vec, {text: "hello"})
+    embedded <- AddV<Doc>(Embed(name))
+    chunk <- AddV<Chunk>(Embed(name, model: "openai:text-embedding-3-small"), {text: name})
+    docs <- SearchV<Doc>(vec, 10)::PREFILTER(_::{text}::CONTAINS("a"))
+    hybrid <- SearchHybrid<Doc>(vec, "query", 5)::RerankRRF(k: 60)::RerankMMR(lambda: 0.5, distance: "cosine")
+    reranked <- docs::RerankRRF
+    boosted <- docs::BOOST::RerankRRF(k: 60)::BOOST(salience, recency, half_life: 7)::RerankMMR(lambda: 0.7)
+    bm25 <- SearchBM25<User>("text", 3)
+    ranked <- PPR<User>(seeds: ids, universe: ids, weights: {Follows: 2.0}, depth: 3, damping: 0.85, limit: 10)
+    merged <- MERGE_NODES(user, new_user, conflict: OVERWRITE)
+    path <- N<User>(id)::ShortestPathBFS<Follows>::To(new_user)
+    weighted <- N<User>(id)::ShortestPathDijkstras<Follows>(_::{weight})::To(new_user)
+    guided <- N<User>(id)::ShortestPathAStar<Follows>(MUL(_::{weight}, 2.0), "score")::To(new_user)
+    around <- N<User>(id)::SUBGRAPH(depth: 2)
+    network <- N<User>(id)::EGO<Follows>(depth: 2)
+    walked <- N<User>(id)::Out<Follows>::In<Follows>::PATHS
+    followers <- N<User>(id)::DEGREE<Follows>(in)
+    linked <- N<User>(id)::NEIGHBOR_COUNT
+    sampled <- N<User>::SAMPLE(100)
+    share <- N<User>::SAMPLE(0.01)
+    daily <- N<User>::BUCKET_BY(created, 1d)::COUNT
+    local <- N<User>::BUCKET_BY(created, 1w, "Europe/Berlin")
+    zoned <- N<User>::BUCKET_BY(created, 15m, name)
+    typical <- N<User>::MEDIAN(age)
+    slowest <- N<User>::P99(age)
+    spread <- N<User>::STDDEV(age)
+    purged <- N<User>::WHERE(_::{age}::LT(age))::DELETE
+    math <- ADD(age, POW(2, 3))
+    pi <- PI()
+    called <- CALL normalize(name, 1)
+    FOR u IN ids {
+        aged <- N<User>(u)::UPDATE({age: 1})
+    }
+    FOR {a, b} IN filter {
+        DROP N<User>(a)
+    }
+    DROP N<User>(id)::OutE<Follows>
+    RETURN user, count, NONE
+
+QUERY shaped(id: ID) =>
+    user <- N<User>(id)
+    count <- user::Out<Follows>::COUNT
+    RETURN {count: count, user: {name: user::{name}, items: [user, count]}}
+
+QUERY checked(name: String @length(1, 64), age?: I32 @range(-1, 120.5), role: String @one_of("admin", "user"), tags: [String] @length(0, 10) @one_of("a")) =>
+    users <- N<User>::WHERE(_::{age}::LT(age))
+    RETURN users
+
+#[model("gemini:text-embedding-004")]
+QUERY empty() =>
+    RETURN NONE
+"#;
+
+    #[test]
+    fn test_hql_tests_corpus_round_trips() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../hql-tests/tests");
+        let (checked, failures) = check_corpus(&root).unwrap();
+        assert!(checked > 0, "no .hx files under {}", root.display());
+        let failures = failures
+            .iter()
+            .map(|(dir, error)| format!("{}: {error}", dir.display()))
+            .collect::<Vec<_>>();
+        assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+    }
+
+    #[test]
+    fn test_every_construct_round_trips() {
+        parse(&[("everything.hx", EVERYTHING)]).unwrap();
+        check_roundtrip(&[("everything.hx", EVERYTHING)]).unwrap();
+    }
+
+    #[test]
+    fn test_printed_source_is_canonical() {
+        let input = r#"
+            N::User { name: String, age: U32 }
+            // dropped
+            QUERY get(id: ID) =>
+                u <- N<User>(id)::WHERE(_::{age}::GT(1))
+                RETURN u
+        "#;
+        let printed = print_source(&parse(&[("get.hx", input)]).unwrap());
+        let expected = "N::User {\n    name: String,\n    age: U32,\n}\n\nQUERY get(id: ID) =>\n    u <- N<User>(id)::WHERE(_::{age}::GT(1))\n    RETURN u\n";
+        assert_eq!(printed, expected);
+    }
+
+    #[test]
+    fn test_unparseable_input_is_skipped() {
+        check_roundtrip(&[("bad.hx", "QUERY broken( =>")]).unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2025 HelixDB Inc.
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Diagrams of a parsed schema, for architecture docs and onboarding.
+//!
+//! Node and vector types are boxes listing their fields, and edge types are arrows from their
+//! `From` to their `To` type, labelled with their name and properties. Edge cardinalities are
+//! shown at the ends they constrain: how many edges each `From` node must have at the `To` end
+//! and the other way around. Graphviz DOT and Mermaid class diagrams are supported.
+
+use crate::helixc::parser::{
+    pretty::print_field_type,
+    types::{Cardinality, EdgeSchema, Field, FieldPrefix, FieldType, Schema},
+};
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+/// Diagram language to write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// Graphviz, rendered with `dot -Tsvg`
+    Dot,
+    /// Mermaid class diagram, rendered by GitHub and most docs sites
+    Mermaid,
+}
+
+impl FromStr for DiagramFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(DiagramFormat::Dot),
+            "mermaid" => Ok(DiagramFormat::Mermaid),
+            _ => Err(format!(
+                "unknown diagram format '{s}', expected 'dot' or 'mermaid'"
+            )),
+        }
+    }
+}
+
+impl Display for DiagramFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagramFormat::Dot => write!(f, "dot"),
+            DiagramFormat::Mermaid => write!(f, "mermaid"),
+        }
+    }
+}
+
+/// The diagram of a schema's node, vector and edge types, in declaration order
+pub fn schema_diagram(schema: &Schema, format: DiagramFormat) -> String {
+    let mut out = String::new();
+    // Writing to a String can't fail
+    let _ = match format {
+        DiagramFormat::Dot => write_dot(&mut out, schema),
+        DiagramFormat::Mermaid => write_mermaid(&mut out, schema),
+    };
+    out
+}
+
+fn write_dot(out: &mut String, schema: &Schema) -> fmt::Result {
+    writeln!(out, "digraph schema {{")?;
+    writeln!(out, "    rankdir=LR;")?;
+    writeln!(out, "    node [shape=record];")?;
+    for node in &schema.node_schemas {
+        writeln!(
+            out,
+            "    {} [label=\"{{{}|{}}}\"];",
+            dot_id(&node.name.1),
+            dot_escape(&node.name.1),
+            dot_fields(&node.fields)
+        )?;
+    }
+    for vector in &schema.vector_schemas {
+        writeln!(
+            out,
+            "    {} [label=\"{{\\<\\<vector\\>\\>\\n{}|{}}}\", style=dashed];",
+            dot_id(&vector.name),
+            dot_escape(&vector.name),
+            dot_fields(&vector.fields)
+        )?;
+    }
+    for edge in &schema.edge_schemas {
+        let label = edge_label(edge, "\n")
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        let mut attributes = vec![format!("label=\"{label}\"")];
+        if let Some(cardinality) = &edge.to_cardinality {
+            attributes.push(format!("taillabel=\"{}\"", multiplicity(cardinality)));
+        }
+        if let Some(cardinality) = &edge.from_cardinality {
+            attributes.push(format!("headlabel=\"{}\"", multiplicity(cardinality)));
+        }
+        writeln!(
+            out,
+            "    {} -> {} [{}];",
+            dot_id(&edge.from.1),
+            dot_id(&edge.to.1),
+            attributes.join(", ")
+        )?;
+    }
+    writeln!(out, "}}")
+}
+
+fn write_mermaid(out: &mut String, schema: &Schema) -> fmt::Result {
+    writeln!(out, "classDiagram")?;
+    for node in &schema.node_schemas {
+        writeln!(out, "    class {} {{", node.name.1)?;
+        write_mermaid_fields(out, &node.fields)?;
+        writeln!(out, "    }}")?;
+    }
+    for vector in &schema.vector_schemas {
+        writeln!(out, "    class {} {{", vector.name)?;
+        writeln!(out, "        <<vector>>")?;
+        write_mermaid_fields(out, &vector.fields)?;
+        writeln!(out, "    }}")?;
+    }
+    for edge in &schema.edge_schemas {
+        let tail = edge
+            .to_cardinality
+            .as_ref()
+            .map(|c| format!(" \"{}\"", multiplicity(c)))
+            .unwrap_or_default();
+        let head = edge
+            .from_cardinality
+            .as_ref()
+            .map(|c| format!("\"{}\" ", multiplicity(c)))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "    {}{tail} --> {head}{} : {}",
+            edge.from.1,
+            edge.to.1,
+            edge_label(edge, ", ").replace(['{', '}'], "")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_mermaid_fields(out: &mut String, fields: &[Field]) -> fmt::Result {
+    for field in fields {
+        writeln!(
+            out,
+            "        {}{}: {}",
+            field.name,
+            prefix_marker(&field.prefix),
+            mermaid_type(&field.field_type)
+        )?;
+    }
+    Ok(())
+}
+
+/// The edge's name, with its properties on the following lines, joined by `separator`
+fn edge_label(edge: &EdgeSchema, separator: &str) -> String {
+    let mut name = edge.name.1.clone();
+    match (edge.unique, edge.acyclic) {
+        (true, true) => name.push_str(" (unique, acyclic)"),
+        (true, false) => name.push_str(" (unique)"),
+        (false, true) => name.push_str(" (acyclic)"),
+        (false, false) => {}
+    }
+    let mut lines = vec![name];
+    lines.extend(edge.properties.iter().flatten().map(field_line));
+    lines.join(separator)
+}
+
+fn field_line(field: &Field) -> String {
+    format!(
+        "{}{}: {}",
+        field.name,
+        prefix_marker(&field.prefix),
+        print_field_type(&field.field_type)
+    )
+}
+
+/// Marks indexed fields with `*`, unique ones with `!` and optional ones with `?`
+fn prefix_marker(prefix: &FieldPrefix) -> &'static str {
+    match prefix {
+        FieldPrefix::Index => "*",
+        FieldPrefix::UniqueIndex => "!",
+        FieldPrefix::Optional => "?",
+        FieldPrefix::Empty => "",
+    }
+}
+
+/// `1`, `0..3` or `1..*`
+fn multiplicity(cardinality: &Cardinality) -> String {
+    match cardinality.max {
+        Some(max) if max == cardinality.min => max.to_string(),
+        Some(max) => format!("{}..{max}", cardinality.min),
+        None => format!("{}..*", cardinality.min),
+    }
+}
+
+/// Types as Mermaid shows them, with arrays as `T[]` and objects, whose braces would end the
+/// class body, as `Object`
+fn mermaid_type(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Array(inner) => format!("{}[]", mermaid_type(inner)),
+        FieldType::Object(_) => "Object".to_string(),
+        other => print_field_type(other),
+    }
+}
+
+fn dot_fields(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}\\l", dot_escape(&field_line(field))))
+        .collect()
+}
+
+/// Type names are identifiers, but quoting keeps DOT keywords such as `Node` and `Edge` usable
+fn dot_id(name: &str) -> String {
+    format!("\"{name}\"")
+}
+
+/// Escape the characters that are special in record labels
+fn dot_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helixc::parser::{HelixParser, write_to_temp_file};
+
+    fn schema() -> Schema {
+        let content = write_to_temp_file(vec![
+            r#"
+            N::User { UNIQUE INDEX email: String, tags: [String] }
+            N::Post { title: String }
+            V::Embedding { source: String }
+            E::Wrote UNIQUE { From: User [0..*], To: Post [1], Properties: { at: Date } }
+            "#,
+        ]);
+        let source = HelixParser::parse_source(&content).unwrap();
+        source.get_latest_schema().unwrap().clone()
+    }
+
+    #[test]
+    fn test_dot_diagram_lists_types_and_edges() {
+        let diagram = schema_diagram(&schema(), DiagramFormat::Dot);
+
+        assert!(diagram.starts_with("digraph schema {\n"));
+        assert!(
+            diagram.contains("\"User\" [label=\"{User|email!: String\\ltags: [String]\\l}\"];")
+        );
+        assert!(diagram.contains("\"Embedding\" [label=\"{\\<\\<vector\\>\\>\\nEmbedding|"));
+        assert!(diagram.contains(
+            "\"User\" -> \"Post\" [label=\"Wrote (unique)\\nat: Date\", taillabel=\"1\", headlabel=\"0..*\"];"
+        ));
+    }
+
+    #[test]
+    fn test_mermaid_diagram_lists_types_and_edges() {
+        let diagram = schema_diagram(&schema(), DiagramFormat::Mermaid);
+
+        assert!(diagram.starts_with("classDiagram\n"));
+        assert!(
+            diagram.contains(
+                "    class User {\n        email!: String\n        tags: String[]\n    }"
+            )
+        );
+        assert!(diagram.contains("    class Embedding {\n        <<vector>>\n"));
+        assert!(diagram.contains("    User \"1\" --> \"0..*\" Post : Wrote (unique), at: Date"));
+    }
+
+    #[test]
+    fn test_diagram_format_parses_flag_values() {
+        assert_eq!("dot".parse::<DiagramFormat>(), Ok(DiagramFormat::Dot));
+        assert_eq!(
+            "mermaid".parse::<DiagramFormat>(),
+            Ok(DiagramFormat::Mermaid)
+        );
+        assert!("svg".parse::<DiagramFormat>().is_err());
+    }
+}
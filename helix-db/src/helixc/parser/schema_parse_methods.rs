@@ -2,9 +2,9 @@ use std::collections::HashMap;
 
 use crate::helixc::parser::{
     HelixParser, ParserError, Rule,
-    location::HasLoc,
+    location::{HasLoc, Loc},
     types::{
-        DefaultValue, EdgeSchema, Field, FieldPrefix, FieldType, Migration, MigrationItem,
+        Cardinality, ComputedField, DefaultValue, EdgeSchema, Field, FieldPrefix, FieldType, Migration, MigrationItem,
         MigrationItemMapping, MigrationPropertyMapping, NodeSchema, Source, ValueCast,
         VectorSchema,
     },
@@ -34,11 +34,18 @@ impl HelixParser {
         filepath: String,
     ) -> Result<VectorSchema, ParserError> {
         let mut pairs = pair.clone().into_inner();
+        let model = match pairs.peek() {
+            Some(p) if p.as_rule() == Rule::model_macro => {
+                Some(pairs.try_next_inner().try_next()?.as_str().to_string())
+            }
+            _ => None,
+        };
         let name = pairs.try_next()?.as_str().to_string();
         let fields = self.parse_node_body(pairs.try_next()?, filepath.clone())?;
         Ok(VectorSchema {
             name,
             fields,
+            model,
             loc: pair.loc_with_filepath(filepath),
         })
     }
@@ -458,7 +465,7 @@ impl HelixParser {
         filepath: String,
     ) -> Result<Field, ParserError> {
         let mut pairs = pair.clone().into_inner();
-        // structure is index? ~ identifier ~ ":" ~ param_type
+        // structure is index? ~ identifier ~ ":" ~ (computed_field | param_type ~ default?)
         let prefix = match pairs.peek().map(|p| p.as_rule()) {
             Some(Rule::index) => {
                 let index_pair = pairs.try_next()?; // consume index
@@ -480,6 +487,26 @@ impl HelixParser {
 
         let name = pairs.try_next()?.as_str().to_string();
 
+        // computed fields are counts, which start at zero
+        if let Some(Rule::computed_field) = pairs.peek().map(|p| p.as_rule()) {
+            let computed_pair = pairs.try_next()?;
+            let loc = computed_pair.loc_with_filepath(filepath.clone());
+            let mut computed_pairs = computed_pair.into_inner();
+            let traversal = self.parse_anon_traversal(computed_pairs.try_next()?)?;
+            return Ok(Field {
+                prefix,
+                defaults: Some(DefaultValue::I64(0)),
+                name,
+                field_type: FieldType::I64,
+                computed: Some(ComputedField {
+                    traversal: Box::new(traversal),
+                    materialized: computed_pairs.next().is_some(),
+                    loc,
+                }),
+                loc: pair.loc_with_filepath(filepath),
+            });
+        }
+
         let field_type =
             self.parse_field_type(pairs.try_next_inner().try_next()?, Some(&self.source))?;
 
@@ -490,6 +517,7 @@ impl HelixParser {
             defaults,
             name,
             field_type,
+            computed: None,
             loc: pair.loc_with_filepath(filepath),
         })
     }
@@ -506,33 +534,27 @@ impl HelixParser {
         let name = name_pair.as_str().to_string();
 
         let mut unique = false;
-        let next = pairs.try_next()?;
-
-        let body_pair = match next.as_rule() {
-            Rule::edge_modifier => {
-                // Currently only UNIQUE exists
-                unique = true;
-                pairs.try_next()?
-            }
-            Rule::edge_body => next,
-            _ => {
-                return Err(ParserError::ParseError(
-                    "edge_modifier or edge_body".to_string(),
-                ));
+        let mut acyclic = false;
+        let body_pair = loop {
+            let next = pairs.try_next()?;
+            match next.as_rule() {
+                Rule::edge_modifier => match next.into_inner().try_next()?.as_rule() {
+                    Rule::unique => unique = true,
+                    _ => acyclic = true,
+                },
+                Rule::edge_body => break next,
+                _ => {
+                    return Err(ParserError::ParseError(
+                        "edge_modifier or edge_body".to_string(),
+                    ));
+                }
             }
         };
 
         let mut body_pairs = body_pair.into_inner();
 
-        let from = {
-            let pair = body_pairs.try_next()?;
-            (pair.loc(), pair.as_str().to_string())
-        };
-
-        let to = {
-            let pair = body_pairs.try_next()?;
-            (pair.loc(), pair.as_str().to_string())
-        };
+        let (from, from_cardinality) = self.parse_edge_end(body_pairs.try_next()?)?;
+        let (to, to_cardinality) = self.parse_edge_end(body_pairs.try_next()?)?;
 
         let properties = match body_pairs.next() {
             Some(pair) => Some(self.parse_properties(pair, filepath.clone())?),
@@ -543,12 +565,49 @@ impl HelixParser {
             name: (name_pair.loc_with_filepath(filepath), name),
             loc: edge_loc,
             unique,
+            acyclic,
             from,
             to,
             properties,
+            from_cardinality,
+            to_cardinality,
         })
     }
 
+    /// The node type of an edge's end and the bounds on how many of the edges its nodes have
+    fn parse_edge_end(
+        &self,
+        pair: Pair<Rule>,
+    ) -> Result<((Loc, String), Option<Cardinality>), ParserError> {
+        let mut pairs = pair.into_inner();
+        let node_type = pairs.try_next()?;
+        let cardinality = pairs
+            .next()
+            .map(|pair| self.parse_cardinality(pair))
+            .transpose()?;
+        Ok((
+            (node_type.loc(), node_type.as_str().to_string()),
+            cardinality,
+        ))
+    }
+
+    fn parse_cardinality(&self, pair: Pair<Rule>) -> Result<Cardinality, ParserError> {
+        let loc = pair.loc();
+        let mut pairs = pair.into_inner();
+        let bound = |pair: Pair<Rule>| {
+            pair.as_str().parse::<u32>().map_err(|e| {
+                ParserError::from(format!("Invalid edge count '{}': {e}", pair.as_str()))
+            })
+        };
+        let min = bound(pairs.try_next()?)?;
+        let max = match pairs.next() {
+            None => Some(min),
+            Some(pair) if pair.as_rule() == Rule::many => None,
+            Some(pair) => Some(bound(pair)?),
+        };
+        Ok(Cardinality { min, max, loc })
+    }
+
     pub(super) fn parse_properties(
         &self,
         pair: Pair<Rule>,
@@ -832,6 +891,75 @@ mod tests {
         assert_eq!(props.len(), 2);
     }
 
+    #[test]
+    fn test_parse_edge_definition_with_cardinalities() {
+        let source = r#"
+            N::Comment { body: String }
+            N::User { name: String }
+
+            E::AuthoredBy {
+                From: Comment [1],
+                To: User [0..*],
+            }
+            E::Reviews {
+                From: User [0..1],
+                To: Comment [2..5],
+                Properties: {
+                    score: I32
+                }
+            }
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let schema = parsed.schema.get(&1).unwrap();
+        let bounds = |cardinality: &Option<Cardinality>| {
+            cardinality
+                .as_ref()
+                .map(|cardinality| (cardinality.min, cardinality.max))
+        };
+
+        let authored_by = &schema.edge_schemas[0];
+        assert_eq!(authored_by.from.1, "Comment");
+        assert_eq!(bounds(&authored_by.from_cardinality), Some((1, Some(1))));
+        assert_eq!(bounds(&authored_by.to_cardinality), Some((0, None)));
+        let reviews = &schema.edge_schemas[1];
+        assert_eq!(bounds(&reviews.from_cardinality), Some((0, Some(1))));
+        assert_eq!(bounds(&reviews.to_cardinality), Some((2, Some(5))));
+        assert_eq!(reviews.properties.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_acyclic_edge_definition() {
+        let source = r#"
+            N::Task { name: String }
+
+            E::DependsOn @acyclic {
+                From: Task,
+                To: Task,
+            }
+            E::Blocks UNIQUE @acyclic {
+                From: Task,
+                To: Task,
+            }
+            E::Follows {
+                From: Task,
+                To: Task,
+            }
+        "#;
+
+        let content = write_to_temp_file(vec![source]);
+        let parsed = HelixParser::parse_source(&content).unwrap();
+        let schema = parsed.schema.get(&1).unwrap();
+
+        let flags: Vec<(bool, bool)> = schema
+            .edge_schemas
+            .iter()
+            .map(|edge| (edge.unique, edge.acyclic))
+            .collect();
+        assert_eq!(flags, vec![(false, true), (true, true), (false, false)]);
+    }
+
     #[test]
     fn test_parse_edge_definition_self_referential() {
         let source = r#"
@@ -0,0 +1,67 @@
+use crate::helixc::parser::{
+    HelixParser, ParserError, Rule,
+    location::HasLoc,
+    types::{Query, Trigger, TriggerKind},
+};
+use pest::iterators::Pair;
+
+impl HelixParser {
+    pub(super) fn parse_trigger_def(
+        &self,
+        pair: Pair<Rule>,
+        filepath: String,
+    ) -> Result<Trigger, ParserError> {
+        let loc = pair.loc_with_filepath(filepath);
+        let mut pairs = pair.clone().into_inner();
+
+        let mut event = pairs
+            .next()
+            .ok_or_else(|| ParserError::from("Expected trigger event"))?
+            .into_inner();
+        let kind = match event.next().map(|p| p.as_str()) {
+            Some("AddN") => TriggerKind::AddN,
+            Some("AddE") => TriggerKind::AddE,
+            _ => return Err(ParserError::from("Unknown trigger event")),
+        };
+        let item_type = event
+            .next()
+            .ok_or_else(|| ParserError::from("Expected trigger item type"))?;
+        let item_type = (item_type.loc(), item_type.as_str().to_string());
+
+        let binding = match pairs.peek() {
+            Some(p) if p.as_rule() == Rule::identifier => {
+                pairs.next().map(|p| (p.loc(), p.as_str().to_string()))
+            }
+            _ => None,
+        };
+        let body = pairs
+            .next()
+            .ok_or_else(|| ParserError::from("Expected trigger statements"))?;
+        let original_query = body.as_str().to_string();
+        let statements = self.parse_query_body(body)?;
+
+        let trigger = Trigger {
+            kind,
+            item_type,
+            binding,
+            query: Query {
+                original_query,
+                doc: None,
+                built_in_macros: Vec::new(),
+                name: String::new(),
+                parameters: Vec::new(),
+                statements,
+                return_values: Vec::new(),
+                loc: loc.clone(),
+            },
+            loc,
+        };
+        Ok(Trigger {
+            query: Query {
+                name: format!("trigger_{}", trigger.name()),
+                ..trigger.query
+            },
+            ..trigger
+        })
+    }
+}
@@ -1,7 +1,8 @@
 use super::location::Loc;
 use crate::{
+    helix_engine::storage_core::{degrees::DegreeDirection, merge::ConflictPolicy},
     helixc::parser::{HelixParser, errors::ParserError},
-    protocol::value::Value,
+    protocol::{request::Priority, value::Value},
 };
 use chrono::{DateTime, NaiveDate, Utc};
 use itertools::Itertools;
@@ -33,6 +34,8 @@ impl Default for HelixParser {
                 source: String::new(),
                 schema: HashMap::new(),
                 migrations: Vec::new(),
+                views: Vec::new(),
+                triggers: Vec::new(),
                 queries: Vec::new(),
             },
         }
@@ -45,6 +48,8 @@ pub struct Source {
     pub source: String,
     pub schema: HashMap<usize, Schema>,
     pub migrations: Vec<Migration>,
+    pub views: Vec<View>,
+    pub triggers: Vec<Trigger>,
     pub queries: Vec<Query>,
 }
 
@@ -87,6 +92,9 @@ pub struct NodeSchema {
 pub struct VectorSchema {
     pub name: String,
     pub fields: Vec<Field>,
+    /// Model that `Embed` uses for this type when neither it nor its query names one,
+    /// from `#[model(...)]` on the declaration
+    pub model: Option<String>,
     pub loc: Loc,
 }
 
@@ -98,6 +106,32 @@ pub struct EdgeSchema {
     pub properties: Option<Vec<Field>>,
     pub loc: Loc,
     pub unique: bool,
+    /// Writes that would close a cycle of these edges are rejected
+    pub acyclic: bool,
+    /// How many of these edges each `From` node must have
+    pub from_cardinality: Option<Cardinality>,
+    /// How many of these edges each `To` node must have
+    pub to_cardinality: Option<Cardinality>,
+}
+
+/// Bounds on the number of edges of a type at each node of one end, written after the end's
+/// node type as `[1]`, `[0..1]` or `[1..*]`
+#[derive(Debug, Clone)]
+pub struct Cardinality {
+    pub min: u32,
+    /// `None` for no upper bound, `*`
+    pub max: Option<u32>,
+    pub loc: Loc,
+}
+
+impl Display for Cardinality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "[{max}]"),
+            Some(max) => write!(f, "[{}..{max}]", self.min),
+            None => write!(f, "[{}..*]", self.min),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -165,6 +199,8 @@ pub struct Field {
     pub defaults: Option<DefaultValue>,
     pub name: String,
     pub field_type: FieldType,
+    /// Set when the field's value comes from the graph rather than writes
+    pub computed: Option<ComputedField>,
     pub loc: Loc,
 }
 impl Field {
@@ -172,6 +208,16 @@ impl Field {
         self.prefix.is_indexed()
     }
 }
+
+/// A field counting what a traversal from the node reaches, e.g.
+/// `follower_count: COUNT(_::In<Follows>) @materialized`
+#[derive(Debug, Clone)]
+pub struct ComputedField {
+    pub traversal: Box<Traversal>,
+    /// Kept up to date by writes instead of computed on reads
+    pub materialized: bool,
+    pub loc: Loc,
+}
 impl PartialEq for Field {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -395,7 +441,9 @@ impl PartialEq<DefaultValue> for FieldType {
 #[derive(Debug, Clone)]
 pub struct Query {
     pub original_query: String,
-    pub built_in_macro: Option<BuiltInMacro>,
+    /// The `//` comment lines right above the query, without the slashes
+    pub doc: Option<String>,
+    pub built_in_macros: Vec<BuiltInMacro>,
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub statements: Vec<Statement>,
@@ -403,14 +451,87 @@ pub struct Query {
     pub loc: Loc,
 }
 
+/// A materialized view, `VIEW Name AS <traversal>`
+#[derive(Debug, Clone)]
+pub struct View {
+    pub name: (Loc, String),
+    /// Set with `#[refresh(cron: "...")]`; views without it refresh after writes
+    pub refresh_cron: Option<(Loc, String)>,
+    pub traversal: Box<Traversal>,
+    /// A query named after the view, without parameters or statements, that the traversal
+    /// is analyzed in
+    pub query: Query,
+    pub loc: Loc,
+}
+
+/// Writes a trigger can run after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerKind {
+    AddN,
+    AddE,
+}
+
+impl Display for TriggerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerKind::AddN => write!(f, "AddN"),
+            TriggerKind::AddE => write!(f, "AddE"),
+        }
+    }
+}
+
+/// Statements run after a write, `ON AddN<Order> AS order DO { ... }`
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub kind: TriggerKind,
+    /// Node or edge type whose writes run the trigger
+    pub item_type: (Loc, String),
+    /// Variable the written item is bound to in the statements
+    pub binding: Option<(Loc, String)>,
+    /// A query named after the trigger, holding its statements and their source, that they
+    /// are analyzed in
+    pub query: Query,
+    pub loc: Loc,
+}
+
+impl Trigger {
+    /// Name of the trigger, e.g. `AddN_Order`
+    pub fn name(&self) -> String {
+        format!("{}_{}", self.kind, self.item_type.1)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Parameter {
     pub name: (Loc, String),
     pub param_type: (Loc, FieldType),
     pub is_optional: bool,
+    pub checks: Vec<(Loc, ParamCheck)>,
     pub loc: Loc,
 }
 
+/// Check on the value of a query parameter, enforced by the gateway before the query runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamCheck {
+    /// `@range(min, max)`, inclusive bounds of a number
+    Range(f64, f64),
+    /// `@length(min, max)`, inclusive bounds of the characters in a string or items in a list
+    Length(usize, usize),
+    /// `@one_of("a", "b")`, the values a string may take
+    OneOf(Vec<String>),
+}
+
+impl ParamCheck {
+    /// Name of the check as written after `@`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParamCheck::Range(..) => "range",
+            ParamCheck::Length(..) => "length",
+            ParamCheck::OneOf(_) => "one_of",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Statement {
     pub loc: Loc,
@@ -591,6 +712,14 @@ pub struct MathFunctionCall {
     pub loc: Loc,
 }
 
+/// Call of a user-defined function the instance loads from a WASM module
+#[derive(Debug, Clone)]
+pub struct UdfCall {
+    pub name: String,
+    pub args: Vec<Expression>,
+    pub loc: Loc,
+}
+
 #[derive(Clone)]
 pub enum ExpressionType {
     Traversal(Box<Traversal>),
@@ -611,7 +740,9 @@ pub enum ExpressionType {
     SearchHybrid(SearchHybrid),
     PPR(PPR),
     BM25Search(BM25Search),
+    MergeNodes(MergeNodes),
     MathFunctionCall(MathFunctionCall),
+    UdfCall(UdfCall),
     Empty,
 }
 
@@ -644,7 +775,9 @@ impl Debug for ExpressionType {
             ExpressionType::SearchHybrid(sh) => write!(f, "SearchHybrid({sh:?})"),
             ExpressionType::PPR(ppr) => write!(f, "PPR({ppr:?})"),
             ExpressionType::BM25Search(bm25) => write!(f, "BM25Search({bm25:?})"),
+            ExpressionType::MergeNodes(merge) => write!(f, "MergeNodes({merge:?})"),
             ExpressionType::MathFunctionCall(mfc) => write!(f, "MathFunctionCall({mfc:?})"),
+            ExpressionType::UdfCall(call) => write!(f, "UdfCall({call:?})"),
             ExpressionType::Empty => write!(f, "Empty"),
         }
     }
@@ -670,9 +803,11 @@ impl Display for ExpressionType {
             ExpressionType::SearchHybrid(sh) => write!(f, "SearchHybrid({sh:?})"),
             ExpressionType::PPR(ppr) => write!(f, "PPR({ppr:?})"),
             ExpressionType::BM25Search(bm25) => write!(f, "BM25Search({bm25:?})"),
+            ExpressionType::MergeNodes(merge) => write!(f, "MergeNodes({merge:?})"),
             ExpressionType::MathFunctionCall(mfc) => {
                 write!(f, "{}({:?})", mfc.function.name(), mfc.args)
             }
+            ExpressionType::UdfCall(call) => write!(f, "CALL {}({:?})", call.name, call.args),
             ExpressionType::Empty => write!(f, "Empty"),
         }
     }
@@ -745,6 +880,54 @@ pub struct GroupBy {
     pub properties: Vec<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct BucketBy {
+    pub loc: Loc,
+    pub property: String,
+    /// A bucket width like `1d`, checked by the analyzer
+    pub interval: String,
+    /// IANA time zone, UTC when left out
+    pub timezone: Option<EvaluatesToString>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stat {
+    pub loc: Loc,
+    pub function: StatFunction,
+    pub property: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatFunction {
+    P50,
+    P95,
+    P99,
+    Median,
+    Stddev,
+}
+
+impl StatFunction {
+    /// The quantile a percentile function returns, or `None` for `STDDEV`
+    pub fn quantile(&self) -> Option<f64> {
+        match self {
+            StatFunction::P50 | StatFunction::Median => Some(0.5),
+            StatFunction::P95 => Some(0.95),
+            StatFunction::P99 => Some(0.99),
+            StatFunction::Stddev => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatFunction::P50 => "P50",
+            StatFunction::P95 => "P95",
+            StatFunction::P99 => "P99",
+            StatFunction::Median => "MEDIAN",
+            StatFunction::Stddev => "STDDEV",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RerankRRF {
     pub loc: Loc,
@@ -758,6 +941,34 @@ pub struct RerankMMR {
     pub distance: Option<MMRDistance>,
 }
 
+/// Multiplies scores by ranking signals stored on the items, e.g. `::BOOST(salience, recency)`
+#[derive(Debug, Clone)]
+pub struct Boost {
+    pub loc: Loc,
+    /// Signals to boost by, all of them when empty
+    pub signals: Vec<BoostSignal>,
+    /// Days for the recency boost to halve
+    pub half_life: Option<Expression>,
+}
+
+/// A property read as a boost factor, `recency` decaying from the `recencyTs` timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoostSignal {
+    Salience,
+    Confidence,
+    Recency,
+}
+
+impl BoostSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoostSignal::Salience => "salience",
+            BoostSignal::Confidence => "confidence",
+            BoostSignal::Recency => "recency",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum MMRDistance {
     Cosine,
@@ -773,6 +984,9 @@ pub enum StepType {
     Where(Box<Expression>),
     BooleanOperation(BooleanOp),
     Count,
+    Paths,
+    Degree(Degree),
+    NeighborCount,
     Update(Update),
     Upsert(Upsert),
     UpsertN(UpsertN),
@@ -782,13 +996,19 @@ pub enum StepType {
     Exclude(Exclude),
     Closure(Closure),
     Range((Expression, Expression)),
+    Sample(Sample),
     OrderBy(OrderBy),
     Aggregate(Aggregate),
     GroupBy(GroupBy),
+    BucketBy(BucketBy),
+    Stat(Stat),
     AddEdge(AddEdge),
     First,
+    /// Deletes the items in bounded batches, evaluating to how many were deleted
+    Delete,
     RerankRRF(RerankRRF),
     RerankMMR(RerankMMR),
+    Boost(Boost),
 }
 impl PartialEq<StepType> for StepType {
     fn eq(&self, other: &StepType) -> bool {
@@ -802,6 +1022,9 @@ impl PartialEq<StepType> for StepType {
                     &StepType::BooleanOperation(_)
                 )
                 | (&StepType::Count, &StepType::Count)
+                | (&StepType::Paths, &StepType::Paths)
+                | (&StepType::Degree(_), &StepType::Degree(_))
+                | (&StepType::NeighborCount, &StepType::NeighborCount)
                 | (&StepType::Update(_), &StepType::Update(_))
                 | (&StepType::Upsert(_), &StepType::Upsert(_))
                 | (&StepType::UpsertN(_), &StepType::UpsertN(_))
@@ -811,12 +1034,17 @@ impl PartialEq<StepType> for StepType {
                 | (&StepType::Exclude(_), &StepType::Exclude(_))
                 | (&StepType::Closure(_), &StepType::Closure(_))
                 | (&StepType::Range(_), &StepType::Range(_))
+                | (&StepType::Sample(_), &StepType::Sample(_))
                 | (&StepType::OrderBy(_), &StepType::OrderBy(_))
                 | (&StepType::AddEdge(_), &StepType::AddEdge(_))
                 | (&StepType::Aggregate(_), &StepType::Aggregate(_))
                 | (&StepType::GroupBy(_), &StepType::GroupBy(_))
+                | (&StepType::BucketBy(_), &StepType::BucketBy(_))
+                | (&StepType::Stat(_), &StepType::Stat(_))
+                | (&StepType::Delete, &StepType::Delete)
                 | (&StepType::RerankRRF(_), &StepType::RerankRRF(_))
                 | (&StepType::RerankMMR(_), &StepType::RerankMMR(_))
+                | (&StepType::Boost(_), &StepType::Boost(_))
         )
     }
 }
@@ -866,6 +1094,8 @@ pub enum GraphStepType {
     ShortestPathDijkstras(ShortestPathDijkstras),
     ShortestPathBFS(ShortestPathBFS),
     ShortestPathAStar(ShortestPathAStar),
+    Subgraph(Subgraph),
+    Ego(Ego),
     SearchVector(SearchVector),
 }
 impl GraphStep {
@@ -889,6 +1119,19 @@ pub struct ShortestPath {
     pub type_arg: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    pub loc: Loc,
+    pub depth: EvaluatesToNumber,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ego {
+    pub loc: Loc,
+    pub label: String,
+    pub depth: EvaluatesToNumber,
+}
+
 /// Weight calculation expression for shortest path
 #[derive(Debug, Clone)]
 pub enum WeightExpression {
@@ -963,6 +1206,8 @@ pub enum VectorData {
 pub struct Embed {
     pub loc: Loc,
     pub value: EvaluatesToString,
+    /// Model named with `Embed(text, model: "name")`, taking precedence over any default
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -1023,6 +1268,28 @@ pub struct BM25Search {
     pub k: Option<EvaluatesToNumber>,
 }
 
+#[derive(Debug, Clone)]
+pub struct MergeNodes {
+    pub loc: Loc,
+    pub keep: String,
+    pub remove: String,
+    pub conflict: ConflictPolicy,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub loc: Loc,
+    /// A number of items, or a fraction of them when a float
+    pub size: EvaluatesToNumber,
+}
+
+#[derive(Debug, Clone)]
+pub struct Degree {
+    pub loc: Loc,
+    pub label: String,
+    pub direction: DegreeDirection,
+}
+
 #[derive(Debug, Clone)]
 pub struct EvaluatesToNumber {
     pub loc: Loc,
@@ -1254,4 +1521,10 @@ pub struct Closure {
 pub enum BuiltInMacro {
     MCP,
     Model(String),
+    Priority(Priority),
+    Cache { ttl_ms: u64 },
+    /// Roles a JWT caller needs at least one of to call the query
+    Roles(Vec<String>),
+    /// Most calls of the query the gateway runs at once
+    MaxConcurrency(usize),
 }
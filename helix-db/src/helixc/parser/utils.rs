@@ -1,7 +1,7 @@
 use crate::helixc::parser::{
     HelixParser, ParserError, Rule,
     location::HasLoc,
-    types::{EdgeConnection, Expression, IdType},
+    types::{EdgeConnection, Embed, EvaluatesToString, Expression, IdType},
 };
 use pest::iterators::{Pair, Pairs};
 
@@ -59,6 +59,29 @@ impl HelixParser {
         Ok(literal)
     }
 
+    /// `Embed(text)`, optionally naming the model as `Embed(text, model: "name")`
+    pub(super) fn parse_embed(&self, pair: Pair<Rule>) -> Result<Embed, ParserError> {
+        let loc = pair.loc();
+        let mut pairs = pair.into_inner();
+        let inner = pairs.try_next()?;
+        let value = match inner.as_rule() {
+            Rule::identifier => EvaluatesToString::Identifier(inner.as_str().to_string()),
+            Rule::string_literal => EvaluatesToString::StringLiteral(inner.as_str().to_string()),
+            _ => {
+                return Err(ParserError::from(format!(
+                    "Unexpected rule in Embed: {:?} => {:?}",
+                    inner.as_rule(),
+                    inner,
+                )));
+            }
+        };
+        Ok(Embed {
+            loc,
+            value,
+            model: pairs.next().map(|model| model.as_str().to_string()),
+        })
+    }
+
     pub(super) fn parse_to_from(&self, pair: Pair<Rule>) -> Result<EdgeConnection, ParserError> {
         let pairs = pair.clone().into_inner();
         let mut from_id = None;
@@ -0,0 +1,54 @@
+use crate::helixc::parser::{
+    HelixParser, ParserError, Rule,
+    location::HasLoc,
+    types::{Query, View},
+};
+use pest::iterators::Pair;
+
+impl HelixParser {
+    pub(super) fn parse_view_def(
+        &self,
+        pair: Pair<Rule>,
+        filepath: String,
+    ) -> Result<View, ParserError> {
+        let loc = pair.loc_with_filepath(filepath);
+        let mut pairs = pair.clone().into_inner();
+
+        let refresh_cron = match pairs.peek() {
+            Some(p) if p.as_rule() == Rule::refresh_macro => {
+                let cron = pairs
+                    .next()
+                    .and_then(|p| p.into_inner().next())
+                    .ok_or_else(|| ParserError::from("Refresh macro missing cron expression"))?;
+                Some((cron.loc(), self.parse_string_literal(cron)?))
+            }
+            _ => None,
+        };
+        let name = pairs
+            .next()
+            .ok_or_else(|| ParserError::from("Expected view name"))?;
+        let name = (name.loc(), name.as_str().to_string());
+        let traversal = self.parse_traversal(
+            pairs
+                .next()
+                .ok_or_else(|| ParserError::from("Expected view traversal"))?,
+        )?;
+
+        Ok(View {
+            query: Query {
+                original_query: pair.as_str().to_string(),
+                doc: None,
+                built_in_macros: Vec::new(),
+                name: name.1.clone(),
+                parameters: Vec::new(),
+                statements: Vec::new(),
+                return_values: Vec::new(),
+                loc: loc.clone(),
+            },
+            name,
+            refresh_cron,
+            traversal: Box::new(traversal),
+            loc,
+        })
+    }
+}
@@ -1,3 +1,15 @@
+//! HelixDB, a graph-vector database.
+//!
+//! - [`helix_engine`] stores nodes, edges and vectors in LMDB and runs traversals over them.
+//! - [`helixc`] compiles HelixQL into the Rust handlers the engine runs.
+//! - [`helix_gateway`] serves compiled queries over HTTP, gRPC and Arrow Flight.
+//! - [`protocol`] holds the request, response and value types shared by all of them.
+//! - [`embedded`] runs compiled queries in-process, for applications linking the engine.
+//!
+//! The types most applications need are re-exported at the crate root. Builds with
+//! `default-features = false, features = ["embedded"]` leave out the gateway's servers.
+
+pub mod embedded;
 pub mod helix_engine;
 pub mod helix_gateway;
 #[cfg(feature = "compiler")]
@@ -5,7 +17,18 @@ pub mod helixc;
 pub mod protocol;
 pub mod utils;
 
+pub use embedded::{HelixEmbedded, QueryResults};
+pub use helix_engine::{
+    traversal_core::{HelixGraphEngine, config::Config},
+    types::GraphError,
+};
+pub use helix_gateway::router::router::{Handler, HandlerInput};
+pub use protocol::{Format, HelixError, Request, Response, value::Value};
+
+// Servers get mimalloc; applications embedding the engine choose their own allocator
+#[cfg(feature = "gateway")]
 use mimalloc::MiMalloc;
 
+#[cfg(feature = "gateway")]
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
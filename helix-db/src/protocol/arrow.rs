@@ -0,0 +1,214 @@
+//! Arrow encoding of query results.
+//!
+//! Results are laid out as a table (see `protocol::table`) and column types are inferred
+//! from the values: booleans, 64 bit integers, floats, strings and lists of floats
+//! (embeddings) map to their Arrow types, and columns mixing types or holding nested objects
+//! carry the JSON text of each value.
+
+use std::sync::Arc;
+
+use arrow_array::types::Float64Type;
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, ListArray, NullArray, RecordBatch,
+    RecordBatchOptions, StringArray, UInt64Array,
+};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, Field, Schema};
+use sonic_rs::{JsonContainerTrait, JsonType, JsonValueTrait, Value};
+
+use crate::protocol::table::Table;
+
+/// Media type of the Arrow IPC streaming format
+pub const ARROW_STREAM_MEDIA_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Lay a JSON result out as a record batch
+pub fn to_record_batch(result: &Value) -> Result<RecordBatch, ArrowError> {
+    record_batch(&Table::from_result(result))
+}
+
+pub fn record_batch(table: &Table) -> Result<RecordBatch, ArrowError> {
+    let mut fields = Vec::with_capacity(table.columns.len());
+    let mut arrays = Vec::with_capacity(table.columns.len());
+    for (name, values) in &table.columns {
+        let array = column_array(values);
+        fields.push(Field::new(name.as_str(), array.data_type().clone(), true));
+        arrays.push(array);
+    }
+    RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(fields)),
+        arrays,
+        &RecordBatchOptions::new().with_row_count(Some(table.rows)),
+    )
+}
+
+/// Encode a record batch in the Arrow IPC streaming format
+pub fn encode_stream(batch: &RecordBatch) -> Result<Vec<u8>, ArrowError> {
+    let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema())?;
+    writer.write(batch)?;
+    writer.into_inner()
+}
+
+/// Arrow type a column is stored as, widened as values are seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Null,
+    Boolean,
+    Int,
+    UInt,
+    Float,
+    String,
+    FloatList,
+    Json,
+}
+
+impl ColumnKind {
+    fn of(value: &Value) -> Self {
+        match value.get_type() {
+            JsonType::Null => ColumnKind::Null,
+            JsonType::Boolean => ColumnKind::Boolean,
+            JsonType::Number if value.is_i64() => ColumnKind::Int,
+            JsonType::Number if value.is_u64() => ColumnKind::UInt,
+            JsonType::Number => ColumnKind::Float,
+            JsonType::String => ColumnKind::String,
+            JsonType::Array
+                if value
+                    .as_array()
+                    .is_some_and(|a| a.iter().all(|v| v.is_number())) =>
+            {
+                ColumnKind::FloatList
+            }
+            JsonType::Array | JsonType::Object => ColumnKind::Json,
+        }
+    }
+
+    fn widen(self, other: ColumnKind) -> Self {
+        use ColumnKind::*;
+        match (self, other) {
+            (Null, kind) | (kind, Null) => kind,
+            (a, b) if a == b => a,
+            (Int | UInt | Float, Int | UInt | Float) => Float,
+            _ => Json,
+        }
+    }
+}
+
+fn column_array(values: &[Option<&Value>]) -> ArrayRef {
+    let kind = values
+        .iter()
+        .flatten()
+        .map(|v| ColumnKind::of(v))
+        .fold(ColumnKind::Null, ColumnKind::widen);
+    fn present<'a>(value: &Option<&'a Value>) -> Option<&'a Value> {
+        value.filter(|v| !v.is_null())
+    }
+    match kind {
+        ColumnKind::Null => Arc::new(NullArray::new(values.len())),
+        ColumnKind::Boolean => Arc::new(BooleanArray::from_iter(
+            values.iter().map(|v| present(v).and_then(|v| v.as_bool())),
+        )),
+        ColumnKind::Int => Arc::new(Int64Array::from_iter(
+            values.iter().map(|v| present(v).and_then(|v| v.as_i64())),
+        )),
+        ColumnKind::UInt => Arc::new(UInt64Array::from_iter(
+            values.iter().map(|v| present(v).and_then(|v| v.as_u64())),
+        )),
+        ColumnKind::Float => Arc::new(Float64Array::from_iter(
+            values.iter().map(|v| present(v).and_then(|v| v.as_f64())),
+        )),
+        ColumnKind::String => Arc::new(StringArray::from_iter(
+            values.iter().map(|v| present(v).and_then(|v| v.as_str())),
+        )),
+        ColumnKind::FloatList => Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(
+            values.iter().map(|v| {
+                present(v)
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().map(|x| x.as_f64()).collect::<Vec<_>>())
+            }),
+        )),
+        ColumnKind::Json => Arc::new(StringArray::from_iter(
+            values.iter().map(|v| present(v).map(|v| v.to_string())),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type};
+    use arrow_ipc::reader::StreamReader;
+    use arrow_schema::DataType;
+
+    fn batch(json: &str) -> RecordBatch {
+        to_record_batch(&sonic_rs::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_projection_rows() {
+        let batch = batch(
+            r#"{"users": [
+                {"name": "alice", "age": 31, "score": 1.5, "embedding": [0.1, 0.2]},
+                {"name": "bob", "age": 27, "score": 2, "active": true}
+            ]}"#,
+        );
+        assert_eq!(batch.num_rows(), 2);
+        let schema = batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(columns[0], ("name", DataType::Utf8));
+        assert_eq!(columns[1], ("age", DataType::Int64));
+        assert_eq!(columns[2], ("score", DataType::Float64));
+        assert!(matches!(columns[3], ("embedding", DataType::List(_))));
+        assert_eq!(columns[4], ("active", DataType::Boolean));
+
+        let ages = batch.column(1).as_primitive::<Int64Type>();
+        assert_eq!(ages.values(), &[31, 27]);
+        let embeddings = batch.column(3).as_list::<i32>();
+        assert!(embeddings.is_null(1));
+        assert_eq!(
+            embeddings.value(0).as_primitive::<Float64Type>().values(),
+            &[0.1, 0.2]
+        );
+        assert!(batch.column(4).is_null(0));
+    }
+
+    #[test]
+    fn test_aggregation_is_one_row() {
+        let batch = batch(r#"{"count": 3, "groups": {"a": 1}, "label": "x"}"#);
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 3);
+        assert_eq!(batch.column(1).as_string::<i32>().value(0), r#"{"a":1}"#);
+
+        let batch = self::batch(r#"{"names": ["a", "b", null]}"#);
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.schema().field(0).name(), "names");
+        assert!(batch.column(0).is_null(2));
+
+        let batch = self::batch("null");
+        assert_eq!((batch.num_rows(), batch.num_columns()), (0, 0));
+    }
+
+    #[test]
+    fn test_mixed_columns_fall_back_to_json() {
+        let batch = batch(r#"{"items": [{"v": 1}, {"v": "one"}, {"v": 2.5}]}"#);
+        let values = batch.column(0).as_string::<i32>();
+        assert_eq!(
+            values.iter().collect::<Vec<_>>(),
+            [Some("1"), Some("\"one\""), Some("2.5")]
+        );
+    }
+
+    #[test]
+    fn test_stream_roundtrip() {
+        let batch = batch(r#"{"rows": [{"id": 1}, {"id": 2}]}"#);
+        let bytes = encode_stream(&batch).unwrap();
+        let mut reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        let read = reader.next().unwrap().unwrap();
+        assert_eq!(read, batch);
+        assert!(reader.next().is_none());
+    }
+}
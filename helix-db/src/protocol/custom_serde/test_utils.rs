@@ -240,6 +240,8 @@ pub fn create_arena_vector<'arena>(
             deleted,
             level,
             distance: None,
+            search_distance: None,
+            reembedded: false,
             data: data_ref,
             properties: None,
         }
@@ -258,6 +260,8 @@ pub fn create_arena_vector<'arena>(
             deleted,
             level,
             distance: None,
+            search_distance: None,
+            reembedded: false,
             data: data_ref,
             properties: Some(props_map),
         }
@@ -103,6 +103,8 @@ impl<'de, 'txn, 'arena> serde::de::DeserializeSeed<'de> for VectorDeSeed<'txn, '
                     version,
                     level: 0,
                     distance: None,
+                    search_distance: None,
+                    reembedded: false,
                     data,
                     properties,
                 })
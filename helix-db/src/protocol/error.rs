@@ -1,17 +1,40 @@
-use axum::{body::Body, response::IntoResponse};
-use reqwest::header::CONTENT_TYPE;
+#[cfg(feature = "gateway")]
+use axum::{
+    body::Body,
+    http::header::{CONTENT_TYPE, RETRY_AFTER},
+    response::IntoResponse,
+};
 use serde::Serialize;
+use sonic_rs::json;
 use thiserror::Error;
 
 use crate::{
     helix_engine::types::{GraphError, VectorError},
-    protocol::request::RequestType,
+    protocol::{error_codes::RuntimeErrorCode, request::RequestType},
 };
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
-    code: &'static str,
+/// Rate limit headers from the IETF `RateLimit` header fields draft
+pub const RATE_LIMIT_LIMIT: &str = "ratelimit-limit";
+pub const RATE_LIMIT_REMAINING: &str = "ratelimit-remaining";
+pub const RATE_LIMIT_RESET: &str = "ratelimit-reset";
+
+/// Body of every error sent to clients, e.g. `{"code": "R503", "message": "Rate limit
+/// exceeded, retry after 2s", "details": {"limit": 100, "retry_after_secs": 2}, "retryable": true}`
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: RuntimeErrorCode,
+    pub message: String,
+    /// What clients can act on, such as the rejected parameters, `null` if there's nothing more
+    pub details: Option<sonic_rs::Value>,
+    /// Whether the same request may succeed if it's sent again later
+    pub retryable: bool,
+}
+
+impl ErrorBody {
+    /// Body sent when the error itself couldn't be encoded
+    #[cfg(feature = "gateway")]
+    const INTERNAL: &str =
+        r#"{"code":"R900","message":"Internal server error","details":null,"retryable":false}"#;
 }
 
 #[derive(Debug, Error)]
@@ -24,6 +47,51 @@ pub enum HelixError {
     NotFound { ty: RequestType, name: String },
     #[error("Invalid API key")]
     InvalidApiKey,
+    #[error("Missing `Authorization: Bearer` token")]
+    MissingBearerToken,
+    #[error("Invalid bearer token: {0}")]
+    InvalidToken(String),
+    #[error("API key is read-only and cannot call write route `{route}`")]
+    ReadOnlyApiKey { route: String },
+    #[error("Route `{route}` requires one of the roles: {}", required.join(", "))]
+    MissingRole {
+        route: String,
+        required: Vec<String>,
+    },
+    #[error("The admin API needs an `admin` scoped API key or the `admin` role")]
+    NotAdmin,
+    #[error("Server is shutting down")]
+    ShuttingDown,
+    #[error("Server is overloaded, retry after {retry_after_secs}s")]
+    Overloaded { retry_after_secs: u64 },
+    #[error("Rate limit exceeded, retry after {retry_after_secs}s")]
+    RateLimited { limit: u32, retry_after_secs: u64 },
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+    #[error("Batch has {len} queries, the maximum is {max}")]
+    BatchTooLarge { len: usize, max: usize },
+    #[error("Invalid subscription: {0}")]
+    InvalidSubscription(String),
+    #[error("Invalid GraphQL request: {0}")]
+    InvalidGraphql(String),
+    #[error("Invalid Cypher request: {0}")]
+    InvalidCypher(String),
+    #[error("Session `{0}` doesn't exist or has expired")]
+    SessionNotFound(String),
+    #[error("MCP connection `{0}` doesn't exist or belongs to another caller")]
+    McpConnectionNotFound(String),
+    #[error("Invalid session request: {0}")]
+    InvalidSession(String),
+    #[error("{max} sessions are already open")]
+    TooManySessions { max: usize },
+    #[error("Request body is larger than the limit of {max} bytes")]
+    BodyTooLarge { max: usize },
+    #[error("Invalid import: {0}")]
+    InvalidImport(String),
+    #[error("This instance is a standby, send requests to the leader at {leader}")]
+    Standby { leader: String },
+    #[error("Writes are refused, the stored data is incompatible with this build: {}", reasons.join("; "))]
+    IncompatibleSchema { reasons: Vec<String> },
 }
 
 impl Serialize for HelixError {
@@ -36,20 +104,109 @@ impl Serialize for HelixError {
 }
 
 impl HelixError {
-    fn code(&self) -> &'static str {
+    /// Stable code of the error clients can branch on (see `protocol::error_codes`)
+    pub fn code(&self) -> RuntimeErrorCode {
         match self {
-            HelixError::Graph(_) => "GRAPH_ERROR",
-            HelixError::Vector(_) => "VECTOR_ERROR",
-            HelixError::NotFound { .. } => "NOT_FOUND",
-            HelixError::InvalidApiKey => "INVALID_API_KEY",
+            HelixError::Graph(GraphError::InvalidParams(_)) => RuntimeErrorCode::R101,
+            HelixError::InvalidBatch(_) => RuntimeErrorCode::R102,
+            HelixError::BatchTooLarge { .. } => RuntimeErrorCode::R103,
+            HelixError::InvalidSubscription(_) => RuntimeErrorCode::R104,
+            HelixError::InvalidGraphql(_) => RuntimeErrorCode::R105,
+            HelixError::InvalidCypher(_) => RuntimeErrorCode::R106,
+            HelixError::InvalidSession(_) => RuntimeErrorCode::R107,
+            HelixError::BodyTooLarge { .. } => RuntimeErrorCode::R108,
+            HelixError::InvalidImport(_) => RuntimeErrorCode::R109,
+            HelixError::InvalidApiKey => RuntimeErrorCode::R201,
+            HelixError::MissingBearerToken => RuntimeErrorCode::R202,
+            HelixError::InvalidToken(_) => RuntimeErrorCode::R203,
+            HelixError::ReadOnlyApiKey { .. } => RuntimeErrorCode::R204,
+            HelixError::MissingRole { .. } => RuntimeErrorCode::R205,
+            HelixError::NotAdmin => RuntimeErrorCode::R206,
+            HelixError::NotFound { .. } => RuntimeErrorCode::R301,
+            HelixError::SessionNotFound(_) => RuntimeErrorCode::R302,
+            HelixError::Graph(GraphError::NodeNotFound) => RuntimeErrorCode::R303,
+            HelixError::Graph(GraphError::EdgeNotFound) => RuntimeErrorCode::R304,
+            HelixError::Graph(GraphError::LabelNotFound) => RuntimeErrorCode::R305,
+            HelixError::Graph(GraphError::ShortestPathNotFound) => RuntimeErrorCode::R306,
+            HelixError::Vector(VectorError::VectorNotFound(_)) => RuntimeErrorCode::R307,
+            HelixError::Graph(GraphError::ConfigFileNotFound) => RuntimeErrorCode::R308,
+            HelixError::McpConnectionNotFound(_) => RuntimeErrorCode::R309,
+            HelixError::Graph(GraphError::ConstraintViolation(_)) => RuntimeErrorCode::R401,
+            HelixError::Graph(GraphError::CycleDetected(_)) => RuntimeErrorCode::R402,
+            HelixError::Graph(GraphError::MergeConflict(_)) => RuntimeErrorCode::R403,
+            HelixError::ShuttingDown => RuntimeErrorCode::R501,
+            HelixError::Overloaded { .. } => RuntimeErrorCode::R502,
+            HelixError::RateLimited { .. } => RuntimeErrorCode::R503,
+            HelixError::TooManySessions { .. } => RuntimeErrorCode::R504,
+            HelixError::Standby { .. } => RuntimeErrorCode::R505,
+            HelixError::IncompatibleSchema { .. } => RuntimeErrorCode::R404,
+            HelixError::Graph(_) => RuntimeErrorCode::R901,
+            HelixError::Vector(_) => RuntimeErrorCode::R902,
         }
     }
-}
 
-impl IntoResponse for HelixError {
-    fn into_response(self) -> axum::response::Response {
-        let status = match &self {
+    /// Whether the same request may succeed if it's sent again later, which is only the case
+    /// when the server was too busy or going away
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            HelixError::ShuttingDown
+                | HelixError::Overloaded { .. }
+                | HelixError::RateLimited { .. }
+                | HelixError::TooManySessions { .. }
+        )
+    }
+
+    /// The fields of the error clients can act on, `None` if the message says it all
+    pub fn details(&self) -> Option<sonic_rs::Value> {
+        let details = match self {
+            HelixError::Graph(GraphError::InvalidParams(fields)) => json!({ "fields": fields }),
+            HelixError::Graph(GraphError::ConstraintViolation(violation)) => {
+                sonic_rs::to_value(violation).ok()?
+            }
+            HelixError::NotFound { ty, name } => json!({ "type": ty, "name": name }),
+            HelixError::ReadOnlyApiKey { route } => json!({ "route": route }),
+            HelixError::MissingRole { route, required } => {
+                json!({ "route": route, "required": required })
+            }
+            HelixError::Overloaded { retry_after_secs } => {
+                json!({ "retry_after_secs": retry_after_secs })
+            }
+            HelixError::RateLimited {
+                limit,
+                retry_after_secs,
+            } => json!({ "limit": limit, "retry_after_secs": retry_after_secs }),
+            HelixError::BatchTooLarge { len, max } => json!({ "len": len, "max": max }),
+            HelixError::SessionNotFound(session) => json!({ "session": session }),
+            HelixError::McpConnectionNotFound(connection_id) => {
+                json!({ "connection_id": connection_id })
+            }
+            HelixError::Standby { leader } => json!({ "leader": leader }),
+            HelixError::IncompatibleSchema { reasons } => json!({ "reasons": reasons }),
+            HelixError::TooManySessions { max } | HelixError::BodyTooLarge { max } => {
+                json!({ "max": max })
+            }
+            _ => return None,
+        };
+        Some(details)
+    }
+
+    /// The error as sent to clients
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+            retryable: self.retryable(),
+        }
+    }
+
+    #[cfg(feature = "gateway")]
+    pub fn status(&self) -> axum::http::StatusCode {
+        match self {
             HelixError::NotFound { .. }
+            | HelixError::SessionNotFound(_)
+            | HelixError::McpConnectionNotFound(_)
             | HelixError::Graph(
                 GraphError::ConfigFileNotFound
                 | GraphError::NodeNotFound
@@ -60,41 +217,92 @@ impl IntoResponse for HelixError {
             | HelixError::Vector(VectorError::VectorNotFound(_)) => {
                 axum::http::StatusCode::NOT_FOUND
             }
+            HelixError::Graph(
+                GraphError::ConstraintViolation(_)
+                | GraphError::CycleDetected(_)
+                | GraphError::MergeConflict(_),
+            ) => axum::http::StatusCode::CONFLICT,
+            HelixError::Graph(GraphError::InvalidParams(_)) => axum::http::StatusCode::BAD_REQUEST,
             HelixError::Graph(_) | HelixError::Vector(_) => {
                 axum::http::StatusCode::INTERNAL_SERVER_ERROR
             }
-            HelixError::InvalidApiKey => axum::http::StatusCode::FORBIDDEN,
-        };
+            HelixError::InvalidApiKey
+            | HelixError::ReadOnlyApiKey { .. }
+            | HelixError::MissingRole { .. }
+            | HelixError::NotAdmin => axum::http::StatusCode::FORBIDDEN,
+            HelixError::MissingBearerToken | HelixError::InvalidToken(_) => {
+                axum::http::StatusCode::UNAUTHORIZED
+            }
+            HelixError::ShuttingDown
+            | HelixError::Standby { .. }
+            | HelixError::IncompatibleSchema { .. } => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            HelixError::Overloaded { .. }
+            | HelixError::RateLimited { .. }
+            | HelixError::TooManySessions { .. } => axum::http::StatusCode::TOO_MANY_REQUESTS,
+            HelixError::InvalidBatch(_)
+            | HelixError::InvalidSubscription(_)
+            | HelixError::InvalidGraphql(_)
+            | HelixError::InvalidCypher(_)
+            | HelixError::InvalidSession(_)
+            | HelixError::InvalidImport(_) => axum::http::StatusCode::BAD_REQUEST,
+            HelixError::BatchTooLarge { .. } | HelixError::BodyTooLarge { .. } => {
+                axum::http::StatusCode::PAYLOAD_TOO_LARGE
+            }
+        }
+    }
+}
 
-        let error_response = ErrorResponse {
-            error: self.to_string(),
-            code: self.code(),
+#[cfg(feature = "gateway")]
+impl IntoResponse for HelixError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status();
+        let retry_after = match &self {
+            HelixError::Overloaded { retry_after_secs }
+            | HelixError::RateLimited {
+                retry_after_secs, ..
+            } => Some(*retry_after_secs),
+            _ => None,
         };
 
-        let body = sonic_rs::to_vec(&error_response).unwrap_or_else(|_| {
-            br#"{"error":"Internal serialization error","code":"INTERNAL_ERROR"}"#.to_vec()
+        let body = sonic_rs::to_vec(&self.body()).unwrap_or_else(|e| {
+            tracing::error!("Failed to encode error response: {e:?}");
+            ErrorBody::INTERNAL.as_bytes().to_vec()
         });
 
-        axum::response::Response::builder()
+        let mut builder = axum::response::Response::builder()
             .status(status)
-            .header(CONTENT_TYPE, "application/json")
-            .body(Body::from(body))
-            .unwrap_or_else(|e| {
-                // This should never happen with valid HTTP headers, but handle gracefully
-                tracing::error!("Failed to build error response: {e:?}");
-                axum::response::Response::builder()
-                    .status(500)
-                    .body(Body::from(
-                        r#"{"error":"Internal server error","code":"INTERNAL_ERROR"}"#,
-                    ))
-                    .expect("static response should always build")
-            })
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(secs) = retry_after {
+            builder = builder.header(RETRY_AFTER, secs);
+        }
+        if let HelixError::RateLimited {
+            limit,
+            retry_after_secs,
+        } = &self
+        {
+            builder = builder
+                .header(RATE_LIMIT_LIMIT, *limit)
+                .header(RATE_LIMIT_REMAINING, 0)
+                .header(RATE_LIMIT_RESET, *retry_after_secs);
+        }
+
+        builder.body(Body::from(body)).unwrap_or_else(|e| {
+            // This should never happen with valid HTTP headers, but handle gracefully
+            tracing::error!("Failed to build error response: {e:?}");
+            axum::response::Response::builder()
+                .status(500)
+                .body(Body::from(ErrorBody::INTERNAL))
+                .expect("static response should always build")
+        })
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "gateway"))]
 mod tests {
+    use sonic_rs::JsonValueTrait;
+
     use super::*;
+    use crate::helix_engine::types::{ConstraintViolation, EdgeConstraint};
 
     // ============================================================================
     // HelixError Variant Tests
@@ -194,13 +402,121 @@ mod tests {
     #[test]
     fn test_helix_error_code_graph() {
         let error = HelixError::Graph(GraphError::NodeNotFound);
-        assert_eq!(error.code(), "GRAPH_ERROR");
+        assert_eq!(error.code(), RuntimeErrorCode::R303);
+        let error = HelixError::Graph(GraphError::StorageError("disk full".to_string()));
+        assert_eq!(error.code(), RuntimeErrorCode::R901);
+    }
+
+    #[test]
+    fn test_helix_error_code_constraint_violation() {
+        let error = HelixError::Graph(GraphError::ConstraintViolation(ConstraintViolation {
+            node: uuid::Uuid::nil(),
+            constraint: EdgeConstraint {
+                node_label: "Comment".to_string(),
+                edge_label: "AuthoredBy".to_string(),
+                incoming: false,
+                min: 1,
+                max: Some(1),
+            },
+            edges: 0,
+        }));
+        assert_eq!(error.code(), RuntimeErrorCode::R401);
+        assert_eq!(error.status(), axum::http::StatusCode::CONFLICT);
+        assert!(
+            error
+                .to_string()
+                .contains("must have exactly 1 outgoing AuthoredBy edge, it has 0")
+        );
+        let details = error.details().unwrap();
+        assert_eq!(details["edges"].as_u64(), Some(0));
+        assert_eq!(
+            details["constraint"]["edge_label"].as_str(),
+            Some("AuthoredBy")
+        );
+    }
+
+    #[test]
+    fn test_helix_error_code_cycle_detected() {
+        let error = HelixError::Graph(GraphError::CycleDetected(
+            "DependsOn edge would close a cycle".to_string(),
+        ));
+        assert_eq!(error.code(), RuntimeErrorCode::R402);
+        assert_eq!(error.status(), axum::http::StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_helix_error_code_merge_conflict() {
+        let error = HelixError::Graph(GraphError::MergeConflict(
+            "both nodes set `name`, to Ada and Grace".to_string(),
+        ));
+        assert_eq!(error.code(), RuntimeErrorCode::R403);
+        assert_eq!(error.status(), axum::http::StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_helix_error_invalid_params_lists_fields() {
+        use crate::protocol::params::ParamError;
+
+        let error = HelixError::Graph(GraphError::InvalidParams(vec![
+            ParamError::new("age", "must be between 0 and 255"),
+            ParamError::new("name", "is required"),
+        ]));
+        assert_eq!(error.code(), RuntimeErrorCode::R101);
+        assert_eq!(error.status(), axum::http::StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(error.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: sonic_rs::Value = sonic_rs::from_slice(&body).unwrap();
+        assert_eq!(body["code"].as_str(), Some("R101"));
+        assert_eq!(
+            body["message"].as_str(),
+            Some("Invalid parameters: `age` must be between 0 and 255; `name` is required")
+        );
+        let fields = &body["details"]["fields"];
+        assert_eq!(fields[1]["field"].as_str(), Some("name"));
+        assert_eq!(fields[1]["message"].as_str(), Some("is required"));
+        assert_eq!(body["retryable"].as_bool(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_helix_error_body_always_has_every_field() {
+        let body = |error: HelixError| async {
+            let body = axum::body::to_bytes(error.into_response().into_body(), usize::MAX)
+                .await
+                .unwrap();
+            sonic_rs::from_slice::<sonic_rs::Value>(&body).unwrap()
+        };
+
+        let limited = body(HelixError::RateLimited {
+            limit: 100,
+            retry_after_secs: 2,
+        })
+        .await;
+        assert_eq!(limited["code"].as_str(), Some("R503"));
+        assert_eq!(
+            limited["message"].as_str(),
+            Some("Rate limit exceeded, retry after 2s")
+        );
+        assert_eq!(limited["details"]["limit"].as_u64(), Some(100));
+        assert_eq!(limited["details"]["retry_after_secs"].as_u64(), Some(2));
+        assert_eq!(limited["retryable"].as_bool(), Some(true));
+
+        let invalid_key = body(HelixError::InvalidApiKey).await;
+        assert_eq!(invalid_key["code"].as_str(), Some("R201"));
+        assert_eq!(invalid_key["message"].as_str(), Some("Invalid API key"));
+        assert!(invalid_key["details"].is_null());
+        assert!(invalid_key.get("details").is_some());
+        assert_eq!(invalid_key["retryable"].as_bool(), Some(false));
+
+        let missing = body(HelixError::Graph(GraphError::NodeNotFound)).await;
+        assert_eq!(missing["code"].as_str(), Some("R303"));
     }
 
     #[test]
     fn test_helix_error_code_vector() {
         let error = HelixError::Vector(VectorError::InvalidVectorLength);
-        assert_eq!(error.code(), "VECTOR_ERROR");
+        assert_eq!(error.code(), RuntimeErrorCode::R902);
     }
 
     #[test]
@@ -209,13 +525,13 @@ mod tests {
             ty: RequestType::Query,
             name: "test".to_string(),
         };
-        assert_eq!(error.code(), "NOT_FOUND");
+        assert_eq!(error.code(), RuntimeErrorCode::R301);
     }
 
     #[test]
     fn test_helix_error_code_invalid_api_key() {
         let error = HelixError::InvalidApiKey;
-        assert_eq!(error.code(), "INVALID_API_KEY");
+        assert_eq!(error.code(), RuntimeErrorCode::R201);
     }
 
     // ============================================================================
@@ -270,4 +586,39 @@ mod tests {
         let debug_str = format!("{:?}", error);
         assert!(debug_str.contains("InvalidApiKey"));
     }
+
+    #[test]
+    fn test_helix_error_shutting_down_into_response() {
+        let error = HelixError::ShuttingDown;
+        assert_eq!(error.code(), RuntimeErrorCode::R501);
+        let response = error.into_response();
+        assert_eq!(response.status(), 503);
+    }
+
+    #[test]
+    fn test_helix_error_overloaded_into_response() {
+        let error = HelixError::Overloaded {
+            retry_after_secs: 3,
+        };
+        assert_eq!(error.code(), RuntimeErrorCode::R502);
+        let response = error.into_response();
+        assert_eq!(response.status(), 429);
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_helix_error_rate_limited_into_response() {
+        let error = HelixError::RateLimited {
+            limit: 20,
+            retry_after_secs: 2,
+        };
+        assert_eq!(error.code(), RuntimeErrorCode::R503);
+        let response = error.into_response();
+        assert_eq!(response.status(), 429);
+        let headers = response.headers();
+        assert_eq!(headers.get(RETRY_AFTER).unwrap(), "2");
+        assert_eq!(headers.get(RATE_LIMIT_LIMIT).unwrap(), "20");
+        assert_eq!(headers.get(RATE_LIMIT_REMAINING).unwrap(), "0");
+        assert_eq!(headers.get(RATE_LIMIT_RESET).unwrap(), "2");
+    }
 }
@@ -0,0 +1,196 @@
+//! Stable codes of the errors the gateway returns.
+//!
+//! Codes are numbered like the analyzer's `Exxx` codes, grouped by hundreds: `R1xx` rejected
+//! requests, `R2xx` authentication and access, `R3xx` missing items, `R4xx` conflicts with the
+//! state of the graph, `R5xx` capacity and `R9xx` internal failures. A code keeps its meaning once
+//! released, so clients can branch on it; new failures get new codes.
+
+use core::fmt;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuntimeErrorCode {
+    // REQUEST ERRORS
+    /// `R101` – `invalid query parameters`
+    R101,
+    /// `R102` – `invalid batch`
+    R102,
+    /// `R103` – `batch too large`
+    R103,
+    /// `R104` – `invalid subscription`
+    R104,
+    /// `R105` – `invalid GraphQL request`
+    R105,
+    /// `R106` – `invalid Cypher request`
+    R106,
+    /// `R107` – `invalid session request`
+    R107,
+    /// `R108` – `request body too large`
+    R108,
+    /// `R109` – `invalid import`
+    R109,
+
+    // ACCESS ERRORS
+    /// `R201` – `invalid API key`
+    R201,
+    /// `R202` – `missing bearer token`
+    R202,
+    /// `R203` – `invalid bearer token`
+    R203,
+    /// `R204` – `read-only API key called a write route`
+    R204,
+    /// `R205` – `caller lacks a role the route requires`
+    R205,
+    /// `R206` – `admin API needs an admin key or role`
+    R206,
+
+    // NOT FOUND ERRORS
+    /// `R301` – `unknown query or MCP tool`
+    R301,
+    /// `R302` – `session not found`
+    R302,
+    /// `R303` – `node not found`
+    R303,
+    /// `R304` – `edge not found`
+    R304,
+    /// `R305` – `label not found`
+    R305,
+    /// `R306` – `no path between the nodes`
+    R306,
+    /// `R307` – `vector not found`
+    R307,
+    /// `R308` – `config file not found`
+    R308,
+    /// `R309` – `MCP connection not found`
+    R309,
+
+    // CONFLICT ERRORS
+    /// `R401` – `edge constraint violated`
+    R401,
+    /// `R402` – `edge would create a cycle`
+    R402,
+    /// `R403` – `merge conflict`
+    R403,
+    /// `R404` – `stored data is incompatible with this build`
+    R404,
+
+    // CAPACITY ERRORS
+    /// `R501` – `server is shutting down`
+    R501,
+    /// `R502` – `server is overloaded`
+    R502,
+    /// `R503` – `rate limit exceeded`
+    R503,
+    /// `R504` – `too many sessions`
+    R504,
+    /// `R505` – `instance is a standby`
+    R505,
+
+    // INTERNAL ERRORS
+    /// `R900` – `internal server error`
+    R900,
+    /// `R901` – `graph error`
+    R901,
+    /// `R902` – `vector error`
+    R902,
+}
+
+impl RuntimeErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeErrorCode::R101 => "R101",
+            RuntimeErrorCode::R102 => "R102",
+            RuntimeErrorCode::R103 => "R103",
+            RuntimeErrorCode::R104 => "R104",
+            RuntimeErrorCode::R105 => "R105",
+            RuntimeErrorCode::R106 => "R106",
+            RuntimeErrorCode::R107 => "R107",
+            RuntimeErrorCode::R108 => "R108",
+            RuntimeErrorCode::R109 => "R109",
+            RuntimeErrorCode::R201 => "R201",
+            RuntimeErrorCode::R202 => "R202",
+            RuntimeErrorCode::R203 => "R203",
+            RuntimeErrorCode::R204 => "R204",
+            RuntimeErrorCode::R205 => "R205",
+            RuntimeErrorCode::R206 => "R206",
+            RuntimeErrorCode::R301 => "R301",
+            RuntimeErrorCode::R302 => "R302",
+            RuntimeErrorCode::R303 => "R303",
+            RuntimeErrorCode::R304 => "R304",
+            RuntimeErrorCode::R305 => "R305",
+            RuntimeErrorCode::R306 => "R306",
+            RuntimeErrorCode::R307 => "R307",
+            RuntimeErrorCode::R308 => "R308",
+            RuntimeErrorCode::R309 => "R309",
+            RuntimeErrorCode::R401 => "R401",
+            RuntimeErrorCode::R402 => "R402",
+            RuntimeErrorCode::R403 => "R403",
+            RuntimeErrorCode::R404 => "R404",
+            RuntimeErrorCode::R501 => "R501",
+            RuntimeErrorCode::R502 => "R502",
+            RuntimeErrorCode::R503 => "R503",
+            RuntimeErrorCode::R504 => "R504",
+            RuntimeErrorCode::R505 => "R505",
+            RuntimeErrorCode::R900 => "R900",
+            RuntimeErrorCode::R901 => "R901",
+            RuntimeErrorCode::R902 => "R902",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RuntimeErrorCode::R101 => "invalid query parameters",
+            RuntimeErrorCode::R102 => "invalid batch",
+            RuntimeErrorCode::R103 => "batch too large",
+            RuntimeErrorCode::R104 => "invalid subscription",
+            RuntimeErrorCode::R105 => "invalid GraphQL request",
+            RuntimeErrorCode::R106 => "invalid Cypher request",
+            RuntimeErrorCode::R107 => "invalid session request",
+            RuntimeErrorCode::R108 => "request body too large",
+            RuntimeErrorCode::R109 => "invalid import",
+            RuntimeErrorCode::R201 => "invalid API key",
+            RuntimeErrorCode::R202 => "missing bearer token",
+            RuntimeErrorCode::R203 => "invalid bearer token",
+            RuntimeErrorCode::R204 => "read-only API key called a write route",
+            RuntimeErrorCode::R205 => "caller lacks a role the route requires",
+            RuntimeErrorCode::R206 => "admin API needs an admin key or role",
+            RuntimeErrorCode::R301 => "unknown query or MCP tool",
+            RuntimeErrorCode::R302 => "session not found",
+            RuntimeErrorCode::R303 => "node not found",
+            RuntimeErrorCode::R304 => "edge not found",
+            RuntimeErrorCode::R305 => "label not found",
+            RuntimeErrorCode::R306 => "no path between the nodes",
+            RuntimeErrorCode::R307 => "vector not found",
+            RuntimeErrorCode::R308 => "config file not found",
+            RuntimeErrorCode::R309 => "MCP connection not found",
+            RuntimeErrorCode::R401 => "edge constraint violated",
+            RuntimeErrorCode::R402 => "edge would create a cycle",
+            RuntimeErrorCode::R403 => "merge conflict",
+            RuntimeErrorCode::R404 => "stored data is incompatible with this build",
+            RuntimeErrorCode::R501 => "server is shutting down",
+            RuntimeErrorCode::R502 => "server is overloaded",
+            RuntimeErrorCode::R503 => "rate limit exceeded",
+            RuntimeErrorCode::R504 => "too many sessions",
+            RuntimeErrorCode::R505 => "instance is a standby",
+            RuntimeErrorCode::R900 => "internal server error",
+            RuntimeErrorCode::R901 => "graph error",
+            RuntimeErrorCode::R902 => "vector error",
+        }
+    }
+}
+
+impl fmt::Display for RuntimeErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for RuntimeErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
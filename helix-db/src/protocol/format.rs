@@ -1,22 +1,43 @@
 use std::fmt::Display;
 use std::{borrow::Cow, error::Error, ops::Deref, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 use tokio::io::BufWriter;
 
 use crate::helix_engine::types::GraphError;
 use crate::protocol::Response;
+use crate::protocol::arrow::{self, ARROW_STREAM_MEDIA_TYPE};
+use crate::protocol::params::{self, ParamError, ParamRule};
+use crate::protocol::parquet::{self, PARQUET_MEDIA_TYPE};
+use crate::protocol::table::Table;
+use crate::protocol::value::Value;
 
 /// This enum represents the formats that input or output values of HelixDB can be represented as
 /// It also includes tooling to facilitate copy or zero-copy formats
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
     /// JSON (JavaScript Object Notation)
     /// The current implementation uses sonic_rs
     #[default]
     Json,
+    /// MessagePack, a compact binary encoding of the same data model as JSON.
+    /// Structs are encoded as maps and values in their human readable form, so payloads
+    /// mirror their JSON counterparts.
+    MsgPack,
+    /// Arrow IPC stream of the result laid out as a table (see `protocol::arrow`).
+    /// Only supported for responses.
+    Arrow,
+    /// Newline delimited JSON, one object per row of the result laid out as a table
+    /// (see `protocol::table`). Only supported for responses.
+    NdJson,
+    /// CSV with a header line, one line per row of the result laid out as a table.
+    /// Only supported for responses.
+    Csv,
+    /// Parquet file of the result laid out as a table, typed as for Arrow
+    /// (see `protocol::parquet`). Only supported for responses.
+    Parquet,
 }
 
 /// Methods using to format for serialization/deserialization
@@ -31,6 +52,16 @@ impl Format {
     pub fn serialize<T: Serialize>(self, val: &T) -> Cow<'_, [u8]> {
         match self {
             Format::Json => sonic_rs::to_vec(val).unwrap().into(),
+            Format::MsgPack => {
+                let mut buf = Vec::new();
+                val.serialize(&mut msgpack_serializer(&mut buf))
+                    .expect("value should be encodable as MessagePack");
+                buf.into()
+            }
+            Format::Arrow | Format::NdJson | Format::Csv | Format::Parquet => self
+                .encode_table(val)
+                .expect("value should be encodable as a table")
+                .into(),
         }
     }
 
@@ -46,6 +77,15 @@ impl Format {
                 let encoded = sonic_rs::to_vec(val)?;
                 writer.write_all(&encoded).await?;
             }
+            Format::MsgPack => {
+                let mut encoded = Vec::new();
+                val.serialize(&mut msgpack_serializer(&mut encoded))?;
+                writer.write_all(&encoded).await?;
+            }
+            Format::Arrow | Format::NdJson | Format::Csv | Format::Parquet => {
+                let encoded = self.encode_table(val)?;
+                writer.write_all(&encoded).await?;
+            }
         }
         Ok(())
     }
@@ -69,6 +109,9 @@ impl Format {
                 sonic_rs::from_slice::<T>(val)
                     .map_err(|e| GraphError::DecodeError(e.to_string()))?,
             )),
+            Format::MsgPack | Format::Arrow | Format::NdJson | Format::Csv | Format::Parquet => {
+                Ok(MaybeOwned::Owned(self.deserialize_owned(val)?))
+            }
         }
     }
 
@@ -77,16 +120,98 @@ impl Format {
         match self {
             Format::Json => Ok(sonic_rs::from_slice::<T>(val)
                 .map_err(|e| GraphError::DecodeError(e.to_string()))?),
+            Format::MsgPack => T::deserialize(
+                &mut rmp_serde::Deserializer::from_read_ref(val).with_human_readable(),
+            )
+            .map_err(|e| GraphError::DecodeError(e.to_string())),
+            Format::Arrow | Format::NdJson | Format::Csv | Format::Parquet => Err(
+                GraphError::DecodeError(format!("{self} is only supported as a response format")),
+            ),
+        }
+    }
+
+    /// Deserialize the parameters of a query, checking and coercing them against its `rules`
+    /// (see `protocol::params`)
+    pub fn deserialize_params<T: DeserializeOwned>(
+        self,
+        val: &[u8],
+        rules: &[ParamRule],
+    ) -> Result<T, GraphError> {
+        let params = match self {
+            Format::Json => sonic_rs::from_slice::<sonic_rs::Value>(val).map_err(|e| e.to_string()),
+            // sonic's values only deserialize from JSON, so other formats go through our own
+            _ => self
+                .deserialize_owned::<Value>(val)
+                .map_err(|e| match e {
+                    GraphError::DecodeError(msg) => msg,
+                    e => e.to_string(),
+                })
+                .and_then(|params| sonic_rs::to_value(&params).map_err(|e| e.to_string())),
+        };
+        match params {
+            Ok(params) => params::parse_params(params, rules),
+            Err(msg) => Err(GraphError::InvalidParams(vec![ParamError::new("", msg)])),
+        }
+    }
+
+    /// Pick the response format from an `Accept` header, taking the first listed media type
+    /// that is supported. Wildcards and unsupported types fall back to JSON.
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .find_map(|media_type| media_type.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Lay the value out as a table and encode it in one of the row oriented formats
+    fn encode_table<T: Serialize>(self, val: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+        // Parsed from text rather than built with `to_value`, which loses field order
+        let value: sonic_rs::Value = sonic_rs::from_slice(&sonic_rs::to_vec(val)?)?;
+        let table = Table::from_result(&value);
+        let mut encoded = Vec::new();
+        match self {
+            Format::Arrow => encoded = arrow::encode_stream(&arrow::record_batch(&table)?)?,
+            Format::NdJson => table.write_ndjson(&mut encoded),
+            Format::Csv => table.write_csv(&mut encoded),
+            Format::Parquet => encoded = parquet::encode(&arrow::record_batch(&table)?)?,
+            Format::Json | Format::MsgPack => unreachable!("{self} is not a row oriented format"),
         }
+        Ok(encoded)
     }
 }
 
+/// MessagePack serializer writing structs as maps and values in their human readable form,
+/// so field names, ids and dates come out as they do in JSON
+fn msgpack_serializer(
+    buf: &mut Vec<u8>,
+) -> rmp_serde::Serializer<
+    &mut Vec<u8>,
+    rmp_serde::config::HumanReadableConfig<
+        rmp_serde::config::StructMapConfig<rmp_serde::config::DefaultConfig>,
+    >,
+> {
+    rmp_serde::Serializer::new(buf)
+        .with_struct_map()
+        .with_human_readable()
+}
+
 impl FromStr for Format {
     type Err = ();
 
+    /// Parse a media type, ignoring parameters such as `charset`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        let media_type = s.split(';').next().unwrap_or_default().trim();
+        match media_type.to_ascii_lowercase().as_str() {
             "application/json" => Ok(Format::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Ok(Format::MsgPack)
+            }
+            ARROW_STREAM_MEDIA_TYPE => Ok(Format::Arrow),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Ok(Format::NdJson)
+            }
+            "text/csv" => Ok(Format::Csv),
+            PARQUET_MEDIA_TYPE => Ok(Format::Parquet),
             _ => Err(()),
         }
     }
@@ -96,6 +221,11 @@ impl Display for Format {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Format::Json => write!(f, "application/json"),
+            Format::MsgPack => write!(f, "application/msgpack"),
+            Format::Arrow => write!(f, "{ARROW_STREAM_MEDIA_TYPE}"),
+            Format::NdJson => write!(f, "application/x-ndjson"),
+            Format::Csv => write!(f, "text/csv"),
+            Format::Parquet => write!(f, "{PARQUET_MEDIA_TYPE}"),
         }
     }
 }
@@ -260,10 +390,42 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_format_from_str_msgpack() {
+        for media_type in [
+            "application/msgpack",
+            "application/x-msgpack",
+            "application/vnd.msgpack",
+            "Application/MsgPack; charset=binary",
+        ] {
+            assert_eq!(
+                media_type.parse::<Format>(),
+                Ok(Format::MsgPack),
+                "{media_type}"
+            );
+        }
+        assert_eq!(
+            "application/json; charset=utf-8".parse::<Format>(),
+            Ok(Format::Json)
+        );
+    }
+
+    #[test]
+    fn test_format_from_accept() {
+        assert_eq!(Format::from_accept("application/msgpack"), Format::MsgPack);
+        assert_eq!(
+            Format::from_accept("text/html, application/x-msgpack;q=0.9, */*;q=0.1"),
+            Format::MsgPack
+        );
+        assert_eq!(Format::from_accept("*/*"), Format::Json);
+        assert_eq!(Format::from_accept("text/html"), Format::Json);
+    }
+
     #[test]
     fn test_format_display() {
         let fmt = Format::Json;
         assert_eq!(fmt.to_string(), "application/json");
+        assert_eq!(Format::MsgPack.to_string(), "application/msgpack");
     }
 
     #[test]
@@ -409,4 +571,164 @@ mod tests {
         let decoded: WithOption = Format::Json.deserialize_owned(&bytes).unwrap();
         assert_eq!(decoded, with_none);
     }
+
+    // ============================================================================
+    // MessagePack
+    // ============================================================================
+
+    #[test]
+    fn test_format_msgpack_roundtrip() {
+        let data = TestData {
+            name: "packed 世界".to_string(),
+            value: -7,
+        };
+
+        let bytes = Format::MsgPack.serialize(&data);
+        assert!(bytes.len() < Format::Json.serialize(&data).len());
+
+        let decoded: TestData = Format::MsgPack.deserialize_owned(&bytes).unwrap();
+        assert_eq!(decoded, data);
+        let decoded = Format::MsgPack.deserialize::<TestData>(&bytes).unwrap();
+        assert_eq!(*decoded, data);
+    }
+
+    #[test]
+    fn test_format_msgpack_values_match_json_shape() {
+        use crate::protocol::value::Value;
+        use std::collections::HashMap;
+
+        // Values are written untagged, as in JSON, so clients decode plain maps and scalars
+        let value = Value::Object(HashMap::from([
+            ("name".to_string(), Value::String("packed".to_string())),
+            ("value".to_string(), Value::I32(3)),
+        ]));
+        let bytes = Format::MsgPack.serialize(&value);
+        let decoded: TestData = Format::MsgPack.deserialize_owned(&bytes).unwrap();
+        assert_eq!(
+            decoded,
+            TestData {
+                name: "packed".to_string(),
+                value: 3
+            }
+        );
+
+        let embedding = vec![0.25f64, -1.5, 3.0];
+        let bytes = Format::MsgPack.serialize(&embedding);
+        let decoded: Vec<f64> = Format::MsgPack.deserialize_owned(&bytes).unwrap();
+        assert_eq!(decoded, embedding);
+    }
+
+    // ============================================================================
+    // Arrow
+    // ============================================================================
+
+    #[test]
+    fn test_format_arrow_response_is_ipc_stream() {
+        #[derive(Serialize)]
+        struct Result {
+            users: Vec<TestData>,
+        }
+
+        let result = Result {
+            users: vec![
+                TestData {
+                    name: "a".to_string(),
+                    value: 1,
+                },
+                TestData {
+                    name: "b".to_string(),
+                    value: 2,
+                },
+            ],
+        };
+        let response = Format::Arrow.create_response(&result);
+        assert_eq!(
+            response.fmt.to_string(),
+            "application/vnd.apache.arrow.stream"
+        );
+        let mut reader =
+            arrow_ipc::reader::StreamReader::try_new(response.body.as_slice(), None).unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        assert_eq!((batch.num_rows(), batch.num_columns()), (2, 2));
+        assert_eq!(batch.schema().field(0).name(), "name");
+    }
+
+    #[test]
+    fn test_format_arrow_is_response_only() {
+        assert_eq!(
+            "application/vnd.apache.arrow.stream".parse::<Format>(),
+            Ok(Format::Arrow)
+        );
+        let result: Result<TestData, GraphError> = Format::Arrow.deserialize_owned(b"{}");
+        assert!(matches!(result, Err(GraphError::DecodeError(_))));
+    }
+
+    #[test]
+    fn test_format_parquet_response() {
+        assert_eq!(
+            Format::from_accept("application/vnd.apache.parquet"),
+            Format::Parquet
+        );
+        let data = vec![
+            TestData {
+                name: "a".to_string(),
+                value: 1,
+            },
+            TestData {
+                name: "b".to_string(),
+                value: 2,
+            },
+        ];
+        let response = Format::Parquet.create_response(&data);
+        assert_eq!(response.fmt.to_string(), "application/vnd.apache.parquet");
+        let mut reader = ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            bytes::Bytes::from(response.body),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        assert_eq!((batch.num_rows(), batch.num_columns()), (2, 2));
+        assert_eq!(batch.schema().field(1).name(), "value");
+
+        let result: Result<TestData, GraphError> = Format::Parquet.deserialize_owned(b"PAR1");
+        assert!(matches!(result, Err(GraphError::DecodeError(_))));
+    }
+
+    #[test]
+    fn test_format_rows_as_ndjson_and_csv() {
+        assert_eq!("application/x-ndjson".parse::<Format>(), Ok(Format::NdJson));
+        assert_eq!("text/csv; charset=utf-8".parse::<Format>(), Ok(Format::Csv));
+        assert_eq!(
+            Format::from_accept("text/csv, application/json"),
+            Format::Csv
+        );
+
+        let data = vec![
+            TestData {
+                name: "a".to_string(),
+                value: 1,
+            },
+            TestData {
+                name: "b, c".to_string(),
+                value: 2,
+            },
+        ];
+        let ndjson = Format::NdJson.serialize(&data);
+        assert_eq!(
+            ndjson.as_ref(),
+            b"{\"name\":\"a\",\"value\":1}\n{\"name\":\"b, c\",\"value\":2}\n"
+        );
+        let csv = Format::Csv.serialize(&data);
+        assert_eq!(csv.as_ref(), b"name,value\na,1\n\"b, c\",2\n");
+
+        let result: Result<TestData, GraphError> = Format::Csv.deserialize_owned(b"name\na");
+        assert!(matches!(result, Err(GraphError::DecodeError(_))));
+    }
+
+    #[test]
+    fn test_format_msgpack_deserialize_invalid() {
+        let result: Result<TestData, GraphError> = Format::MsgPack.deserialize_owned(b"\xc1");
+        assert!(matches!(result, Err(GraphError::DecodeError(_))));
+    }
 }
@@ -1,9 +1,14 @@
+pub mod arrow;
 pub mod custom_serde;
 pub mod date;
 pub mod error;
+pub mod error_codes;
 pub mod format;
+pub mod params;
+pub mod parquet;
 pub mod request;
 pub mod response;
+pub mod table;
 pub mod value;
 
 pub use error::HelixError;
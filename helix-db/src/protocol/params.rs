@@ -0,0 +1,491 @@
+//! Validation and coercion of query parameters.
+//!
+//! Each generated query route describes its parameters as a table of [`ParamRule`]s, built from
+//! the parameter types and `@range`, `@length` and `@one_of` checks in the query. The request body
+//! is checked against that table before it's deserialized, so malformed input is rejected with an
+//! error for every offending field instead of one opaque decode error.
+
+use core::fmt;
+
+use serde::{Serialize, de::DeserializeOwned};
+use sonic_rs::{JsonValueMutTrait, JsonValueTrait, Value};
+use uuid::Uuid;
+
+use crate::{helix_engine::types::GraphError, protocol::date::Date};
+
+/// Expected type of a parameter
+#[derive(Debug, Clone, Copy)]
+pub enum ParamKind {
+    String,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Id,
+    Date,
+    Array(&'static ParamKind),
+    Object(&'static [ParamRule]),
+    /// Only checked when the parameters are deserialized
+    Any,
+}
+
+impl ParamKind {
+    /// Smallest and largest value of an integer kind
+    fn int_bounds(self) -> Option<(i128, i128)> {
+        match self {
+            ParamKind::I8 => Some((i8::MIN.into(), i8::MAX.into())),
+            ParamKind::I16 => Some((i16::MIN.into(), i16::MAX.into())),
+            ParamKind::I32 => Some((i32::MIN.into(), i32::MAX.into())),
+            ParamKind::I64 => Some((i64::MIN.into(), i64::MAX.into())),
+            ParamKind::U8 => Some((0, u8::MAX.into())),
+            ParamKind::U16 => Some((0, u16::MAX.into())),
+            ParamKind::U32 => Some((0, u32::MAX.into())),
+            ParamKind::U64 => Some((0, u64::MAX.into())),
+            ParamKind::U128 => Some((0, i128::MAX)),
+            _ => None,
+        }
+    }
+}
+
+/// A parameter of a query route and the checks its value has to pass
+#[derive(Debug, Clone, Copy)]
+pub struct ParamRule {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub optional: bool,
+    /// Inclusive bounds of a number
+    pub range: Option<(f64, f64)>,
+    /// Inclusive bounds of the characters in a string or the items in a list
+    pub length: Option<(usize, usize)>,
+    /// Values a string may take, any string if empty
+    pub one_of: &'static [&'static str],
+}
+
+impl ParamRule {
+    pub const fn new(name: &'static str, kind: ParamKind) -> Self {
+        Self {
+            name,
+            kind,
+            optional: false,
+            range: None,
+            length: None,
+            one_of: &[],
+        }
+    }
+
+    pub const fn optional(self) -> Self {
+        Self {
+            optional: true,
+            ..self
+        }
+    }
+
+    pub const fn range(self, min: f64, max: f64) -> Self {
+        Self {
+            range: Some((min, max)),
+            ..self
+        }
+    }
+
+    pub const fn length(self, min: usize, max: usize) -> Self {
+        Self {
+            length: Some((min, max)),
+            ..self
+        }
+    }
+
+    pub const fn one_of(self, values: &'static [&'static str]) -> Self {
+        Self {
+            one_of: values,
+            ..self
+        }
+    }
+}
+
+/// Why the value of a parameter was rejected. `field` is the path to the value, such as
+/// `people[2].age`, and is empty when the body as a whole was rejected.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ParamError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field.is_empty() {
+            true => write!(f, "{}", self.message),
+            false => write!(f, "`{}` {}", self.field, self.message),
+        }
+    }
+}
+
+/// Checks `params` against `rules` and deserializes them.
+///
+/// Values sent as a different but unambiguous type are coerced first: numbers sent as strings,
+/// integers sent as whole floats and booleans sent as `"true"` or `"false"`. Every parameter is
+/// checked before returning, so the error lists all the fields that were rejected.
+pub fn parse_params<T: DeserializeOwned>(
+    mut params: Value,
+    rules: &[ParamRule],
+) -> Result<T, GraphError> {
+    let mut errors = Vec::new();
+    match params.as_object_mut() {
+        Some(fields) => {
+            for rule in rules {
+                check_field(rule.name, rule, fields.get_mut(&rule.name), &mut errors);
+            }
+        }
+        None => errors.push(ParamError::new("", "parameters must be an object")),
+    }
+    if !errors.is_empty() {
+        return Err(GraphError::InvalidParams(errors));
+    }
+    sonic_rs::from_value(&params)
+        .map_err(|e| GraphError::InvalidParams(vec![ParamError::new("", e.to_string())]))
+}
+
+fn check_field(
+    path: &str,
+    rule: &ParamRule,
+    value: Option<&mut Value>,
+    errors: &mut Vec<ParamError>,
+) {
+    match value {
+        Some(value) if !value.is_null() => check_value(path, rule.kind, rule, value, errors),
+        _ if rule.optional => {}
+        _ => errors.push(ParamError::new(path, "is required")),
+    }
+}
+
+fn check_value(
+    path: &str,
+    kind: ParamKind,
+    rule: &ParamRule,
+    value: &mut Value,
+    errors: &mut Vec<ParamError>,
+) {
+    let checked = match kind {
+        ParamKind::Array(item_kind) => match value.as_array_mut() {
+            Some(items) => {
+                // The length applies to the list, the other checks to each item in it
+                let item_rule = ParamRule {
+                    length: None,
+                    ..*rule
+                };
+                for (i, item) in items.iter_mut().enumerate() {
+                    check_value(
+                        &format!("{path}[{i}]"),
+                        *item_kind,
+                        &item_rule,
+                        item,
+                        errors,
+                    );
+                }
+                match rule.length {
+                    Some((min, max)) if !(min..=max).contains(&items.len()) => {
+                        Err(format!("must have between {min} and {max} items"))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            None => Err("must be a list".to_string()),
+        },
+        ParamKind::Object(fields) => match value.as_object_mut() {
+            Some(object) => {
+                for field in fields {
+                    let path = format!("{path}.{}", field.name);
+                    check_field(&path, field, object.get_mut(&field.name), errors);
+                }
+                Ok(())
+            }
+            None => Err("must be an object".to_string()),
+        },
+        scalar => check_scalar(scalar, rule, value),
+    };
+    if let Err(message) = checked {
+        errors.push(ParamError::new(path, message));
+    }
+}
+
+fn check_scalar(kind: ParamKind, rule: &ParamRule, value: &mut Value) -> Result<(), String> {
+    match kind {
+        ParamKind::String => {
+            let string = value.as_str().ok_or("must be a string")?;
+            if let Some((min, max)) = rule.length
+                && !(min..=max).contains(&string.chars().count())
+            {
+                return Err(format!("must be between {min} and {max} characters long"));
+            }
+            if !rule.one_of.is_empty() && !rule.one_of.contains(&string) {
+                let allowed = rule
+                    .one_of
+                    .iter()
+                    .map(|v| format!("\"{v}\""))
+                    .collect::<Vec<_>>();
+                return Err(format!("must be one of {}", allowed.join(", ")));
+            }
+            Ok(())
+        }
+        ParamKind::Bool => {
+            if let Some(b) = value.as_str().and_then(|s| s.parse::<bool>().ok()) {
+                *value = Value::new_bool(b);
+            }
+            match value.is_boolean() {
+                true => Ok(()),
+                false => Err("must be true or false".to_string()),
+            }
+        }
+        ParamKind::Id => match value.as_str().map(Uuid::parse_str) {
+            Some(Ok(_)) => Ok(()),
+            _ => Err("must be a UUID".to_string()),
+        },
+        ParamKind::Date => sonic_rs::from_value::<Date>(value)
+            .map(|_| ())
+            .map_err(|_| "must be an RFC 3339 date or a unix timestamp".to_string()),
+        ParamKind::F32 | ParamKind::F64 => {
+            if let Some(number) = value.as_str().and_then(|s| s.trim().parse::<f64>().ok())
+                && let Some(number) = Value::new_f64(number)
+            {
+                *value = number;
+            }
+            let number = value.as_f64().ok_or("must be a number")?;
+            check_range(rule, number)
+        }
+        ParamKind::Any | ParamKind::Array(_) | ParamKind::Object(_) => Ok(()),
+        int => {
+            let (min, max) = int.int_bounds().unwrap_or((i128::MIN, i128::MAX));
+            coerce_int(value);
+            let number = match (value.as_i64(), value.as_u64()) {
+                (Some(n), _) => i128::from(n),
+                (None, Some(n)) => i128::from(n),
+                (None, None) => return Err("must be an integer".to_string()),
+            };
+            if !(min..=max).contains(&number) {
+                return Err(format!("must be between {min} and {max}"));
+            }
+            check_range(rule, number as f64)
+        }
+    }
+}
+
+/// Turns integers sent as strings or as whole floats into integers
+fn coerce_int(value: &mut Value) {
+    let number = match value.as_str() {
+        Some(s) => s.trim().parse::<i128>().ok(),
+        None if value.is_f64() => value
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() < 2f64.powi(63))
+            .map(|f| f as i128),
+        None => None,
+    };
+    if let Some(number) = number {
+        if let Ok(n) = i64::try_from(number) {
+            *value = Value::new_i64(n);
+        } else if let Ok(n) = u64::try_from(number) {
+            *value = Value::new_u64(n);
+        }
+    }
+}
+
+fn check_range(rule: &ParamRule, number: f64) -> Result<(), String> {
+    match rule.range {
+        Some((min, max)) if !(min..=max).contains(&number) => {
+            Err(format!("must be between {min} and {max}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::protocol::Format;
+
+    const ADDRESS: &[ParamRule] = &[
+        ParamRule::new("city", ParamKind::String).length(1, 32),
+        ParamRule::new("zip", ParamKind::U32),
+    ];
+
+    const RULES: &[ParamRule] = &[
+        ParamRule::new("name", ParamKind::String).length(1, 8),
+        ParamRule::new("age", ParamKind::U8).range(18.0, 120.0),
+        ParamRule::new("role", ParamKind::String)
+            .optional()
+            .one_of(&["admin", "user"]),
+        ParamRule::new("active", ParamKind::Bool),
+        ParamRule::new("score", ParamKind::F64).optional(),
+        ParamRule::new("tags", ParamKind::Array(&ParamKind::String)).length(0, 2),
+        ParamRule::new("addresses", ParamKind::Array(&ParamKind::Object(ADDRESS))),
+    ];
+
+    #[derive(Debug, Deserialize)]
+    struct Params {
+        name: String,
+        age: u8,
+        role: Option<String>,
+        active: bool,
+        score: Option<f64>,
+        tags: Vec<String>,
+        addresses: Vec<Address>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Address {
+        city: String,
+        zip: u32,
+    }
+
+    fn parse(body: &str) -> Result<Params, GraphError> {
+        Format::Json.deserialize_params(body.as_bytes(), RULES)
+    }
+
+    fn errors(body: &str) -> Vec<String> {
+        match parse(body) {
+            Err(GraphError::InvalidParams(errors)) => {
+                errors.iter().map(ToString::to_string).collect()
+            }
+            other => panic!("expected invalid params, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_params_are_deserialized() {
+        let params = parse(
+            r#"{"name": "ana", "age": 30, "role": "admin", "active": true, "tags": ["a"],
+                "addresses": [{"city": "Lisbon", "zip": 1000}]}"#,
+        )
+        .unwrap();
+        assert_eq!(params.name, "ana");
+        assert_eq!(params.age, 30);
+        assert_eq!(params.role.as_deref(), Some("admin"));
+        assert!(params.active);
+        assert_eq!(params.score, None);
+        assert_eq!(params.tags, vec!["a"]);
+        assert_eq!(params.addresses[0].city, "Lisbon");
+        assert_eq!(params.addresses[0].zip, 1000);
+    }
+
+    #[test]
+    fn test_unambiguous_values_are_coerced() {
+        let params = parse(
+            r#"{"name": "ana", "age": "30", "active": "false", "score": "1.5", "tags": [],
+                "addresses": [{"city": "Porto", "zip": 4000.0}]}"#,
+        )
+        .unwrap();
+        assert_eq!(params.age, 30);
+        assert!(!params.active);
+        assert_eq!(params.score, Some(1.5));
+        assert_eq!(params.addresses[0].zip, 4000);
+    }
+
+    #[test]
+    fn test_every_rejected_field_is_reported() {
+        let errors = errors(
+            r#"{"name": "", "age": 300, "role": "root", "active": "yes", "score": [],
+                "tags": ["a", "b", "c"], "addresses": [{"city": 1}, "home"]}"#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "`name` must be between 1 and 8 characters long",
+                "`age` must be between 0 and 255",
+                r#"`role` must be one of "admin", "user""#,
+                "`active` must be true or false",
+                "`score` must be a number",
+                "`tags` must have between 0 and 2 items",
+                "`addresses[0].city` must be a string",
+                "`addresses[0].zip` is required",
+                "`addresses[1]` must be an object",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declared_range_and_missing_params() {
+        assert_eq!(
+            errors(r#"{"age": 12.5, "active": true, "tags": [], "addresses": []}"#),
+            vec!["`name` is required", "`age` must be an integer"]
+        );
+        assert_eq!(
+            errors(r#"{"name": "a", "age": 12, "active": true, "tags": [], "addresses": []}"#),
+            vec!["`age` must be between 18 and 120"]
+        );
+    }
+
+    #[test]
+    fn test_malformed_body_is_invalid_params() {
+        assert!(matches!(
+            parse("{\"name\":"),
+            Err(GraphError::InvalidParams(_))
+        ));
+        assert_eq!(errors("[1, 2]"), vec!["parameters must be an object"]);
+    }
+
+    #[test]
+    fn test_msgpack_params_are_checked() {
+        let body = rmp_serde::to_vec_named(&sonic_rs::json!({
+            "name": "ana", "age": 17, "active": true, "tags": [], "addresses": []
+        }))
+        .unwrap();
+        match Format::MsgPack.deserialize_params::<Params>(&body, RULES) {
+            Err(GraphError::InvalidParams(errors)) => {
+                assert_eq!(
+                    errors,
+                    vec![ParamError::new("age", "must be between 18 and 120")]
+                )
+            }
+            other => panic!("expected invalid params, got {other:?}"),
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Checked {}
+
+    #[test]
+    fn test_ids_and_dates_are_checked() {
+        const RULES: &[ParamRule] = &[
+            ParamRule::new("id", ParamKind::Id),
+            ParamRule::new("at", ParamKind::Date),
+            ParamRule::new(
+                "nested",
+                ParamKind::Array(&ParamKind::Array(&ParamKind::I8)),
+            ),
+        ];
+        let check =
+            |body: &str| Format::Json.deserialize_params::<Checked>(body.as_bytes(), RULES);
+        assert!(
+            check(r#"{"id": "0b3c8b7e-6f4a-4f8e-9d2e-1a2b3c4d5e6f", "at": "2024-01-01", "nested": [[1]]}"#)
+                .is_ok()
+        );
+        match check(r#"{"id": "nope", "at": "soon", "nested": [[1, 200]]}"#) {
+            Err(GraphError::InvalidParams(errors)) => assert_eq!(
+                errors,
+                vec![
+                    ParamError::new("id", "must be a UUID"),
+                    ParamError::new("at", "must be an RFC 3339 date or a unix timestamp"),
+                    ParamError::new("nested[0][1]", "must be between -128 and 127"),
+                ]
+            ),
+            other => panic!("expected invalid params, got {other:?}"),
+        }
+    }
+}
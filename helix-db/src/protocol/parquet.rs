@@ -0,0 +1,113 @@
+//! Parquet encoding of query results and stored tables, for loading into Spark, DuckDB and
+//! warehouses.
+//!
+//! Results are laid out and typed as they are for Arrow (see `protocol::arrow`) and written as
+//! a single Snappy compressed row group.
+
+use arrow_array::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use serde::{Serialize, Serializer};
+use sonic_rs::Value as JsonValue;
+
+use crate::protocol::arrow;
+use crate::protocol::value::Value;
+
+/// Media type of Parquet files
+pub const PARQUET_MEDIA_TYPE: &str = "application/vnd.apache.parquet";
+
+/// Encode a record batch as a Parquet file
+pub fn encode(batch: &RecordBatch) -> Result<Vec<u8>, ParquetError> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_created_by(format!("HelixDB {}", env!("CARGO_PKG_VERSION")))
+        .build();
+    let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.into_inner()
+}
+
+/// Lay a JSON result out as a table and encode it as a Parquet file
+pub fn to_parquet(result: &JsonValue) -> Result<Vec<u8>, ParquetError> {
+    encode(&arrow::to_record_batch(result)?)
+}
+
+/// Encode rows of named values, such as the nodes or edges of one label, with a column per
+/// name in first-seen order
+pub fn encode_rows(rows: &[Vec<(String, Value)>]) -> Result<Vec<u8>, ParquetError> {
+    // Parsed from text rather than built with `to_value`, which loses field order
+    let text = sonic_rs::to_vec(&Rows(rows)).map_err(|e| ParquetError::External(Box::new(e)))?;
+    let value: JsonValue =
+        sonic_rs::from_slice(&text).map_err(|e| ParquetError::External(Box::new(e)))?;
+    to_parquet(&value)
+}
+
+struct Rows<'a>(&'a [Vec<(String, Value)>]);
+
+struct Row<'a>(&'a [(String, Value)]);
+
+impl Serialize for Rows<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|row| Row(row)))
+    }
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .filter(|(_, value)| !matches!(value, Value::Empty))
+                .map(|(key, value)| (key, value)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use bytes::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn read(bytes: Vec<u8>) -> RecordBatch {
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes))
+            .unwrap()
+            .build()
+            .unwrap();
+        reader.next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_result_roundtrip() {
+        let value: JsonValue =
+            sonic_rs::from_str(r#"{"users": [{"name": "alice", "age": 31}, {"name": "bob"}]}"#)
+                .unwrap();
+        let batch = arrow::to_record_batch(&value).unwrap();
+        assert_eq!(read(to_parquet(&value).unwrap()), batch);
+    }
+
+    #[test]
+    fn test_rows_keep_column_order() {
+        let rows = vec![
+            vec![
+                ("id".to_string(), Value::String("a".to_string())),
+                ("score".to_string(), Value::I32(3)),
+                ("note".to_string(), Value::Empty),
+            ],
+            vec![
+                ("id".to_string(), Value::String("b".to_string())),
+                ("note".to_string(), Value::String("late".to_string())),
+            ],
+        ];
+        let batch = read(encode_rows(&rows).unwrap());
+        let schema = batch.schema();
+        let names = schema.fields().iter().map(|f| f.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["id", "score", "note"]);
+        let scores = batch.column(1).as_primitive::<Int64Type>();
+        assert_eq!(scores.iter().collect::<Vec<_>>(), [Some(3), None]);
+    }
+}
@@ -1,17 +1,27 @@
-use axum::{body::Bytes, extract::FromRequest};
-use reqwest::{
-    StatusCode,
-    header::{ACCEPT, CONTENT_TYPE},
+#[cfg(feature = "gateway")]
+use axum::{
+    extract::FromRequest,
+    http::{
+        HeaderMap, StatusCode,
+        header::{ACCEPT, CONTENT_TYPE},
+    },
 };
+use bytes::Bytes;
 use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
+#[cfg(feature = "gateway")]
 use tracing::error;
+use tracing::{Span, info_span};
 
+use crate::helix_engine::traversal_core::step_profile::StepTiming;
 use crate::protocol::{Format, HelixError, Response};
 
 pub type RetChan = oneshot::Sender<Result<Response, HelixError>>;
 
-pub type ReqMsg = (Request, RetChan);
+pub type ReqMsg = (Request, RetChan, RequestTrace);
 
 #[derive(Debug, Clone)]
 pub struct Request {
@@ -30,6 +40,193 @@ pub enum RequestType {
     MCP,
 }
 
+/// Header a client can set to pick the scheduling lane for a request
+pub const PRIORITY_HEADER: &str = "x-helix-priority";
+
+/// Scheduling lane for read requests.
+///
+/// Interactive requests are served ahead of batch requests, but readers still take
+/// a waiting batch request regularly so long analytical queries are not starved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    Interactive,
+    Batch,
+}
+
+/// Header a client sets to run a read query on the snapshot of a session opened at `/session`
+pub const SESSION_HEADER: &str = "x-helix-session";
+
+/// Header a client sets to `true` to run a read query on the writer, after the writes it sent
+/// before it
+pub const READ_YOUR_WRITES_HEADER: &str = "x-helix-read-your-writes";
+
+/// Header a client sets to a tenant or label key to run a query on the partition the key
+/// hashes to, when the instance has several
+pub const PARTITION_HEADER: &str = "x-helix-partition";
+
+/// Package and service of the compiled queries' gRPC service, which the compiler describes
+/// and the gateway serves
+pub const GRPC_PACKAGE: &str = "helix.queries";
+pub const GRPC_SERVICE: &str = "HelixQueries";
+/// Message streamed by the `<name>Stream` rpcs
+pub const RESULT_ITEM_MESSAGE: &str = "ResultItem";
+
+/// Per-request scheduling hints taken from request headers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestHints {
+    /// Overrides the route's declared priority
+    pub priority: Option<Priority>,
+    /// Read session whose snapshot the query runs on
+    pub session: Option<String>,
+    /// Run a read on the writer instead of a reader, bypassing the result cache, so it sees
+    /// every write queued before it
+    pub read_your_writes: bool,
+    /// Tenant or label key picking the partition the query runs on
+    pub partition: Option<String>,
+    /// The authenticated caller, as `Caller::id`. Set by the gateway rather than a header;
+    /// MCP connections belong to the caller that opened them.
+    pub caller: Option<String>,
+}
+
+#[cfg(feature = "gateway")]
+impl RequestHints {
+    /// Hints set on a request's headers. An unrecognised priority falls back to the
+    /// route's declared lane.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        RequestHints {
+            priority: headers
+                .get(PRIORITY_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<Priority>().ok()),
+            session: headers
+                .get(SESSION_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            read_your_writes: headers
+                .get(READ_YOUR_WRITES_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| matches!(v.trim(), "true" | "1")),
+            partition: headers
+                .get(PARTITION_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            caller: None,
+        }
+    }
+}
+
+/// Spans and timings that follow a request from the gateway onto a worker thread
+#[derive(Debug)]
+pub struct RequestTrace {
+    /// Span of the whole request; handler execution and IO are recorded under it
+    pub request: Span,
+    /// Open while the request waits in a worker queue, closed when a worker takes it
+    pub queue_wait: Span,
+    pub enqueued_at: Instant,
+    /// Filled in by the worker, read back by the gateway
+    pub profile: Arc<QueryProfile>,
+    /// The authenticated caller from [`RequestHints::caller`]
+    pub caller: Option<String>,
+}
+
+impl Default for RequestTrace {
+    fn default() -> Self {
+        RequestTrace {
+            request: Span::none(),
+            queue_wait: Span::none(),
+            enqueued_at: Instant::now(),
+            profile: Arc::default(),
+            caller: None,
+        }
+    }
+}
+
+/// Where a request spent its time on the worker and how much it read
+#[derive(Debug, Default)]
+pub struct QueryProfile {
+    queue_wait_us: AtomicU64,
+    execute_us: AtomicU64,
+    items_scanned: AtomicU64,
+    /// Set when slow queries are logged, so the worker times each step of the query
+    profile_steps: AtomicBool,
+    steps: Mutex<Vec<StepTiming>>,
+}
+
+impl QueryProfile {
+    pub fn record_queue_wait(&self, elapsed: Duration) {
+        self.queue_wait_us
+            .store(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Record the handler's synchronous run, up to its result or its first IO yield
+    pub fn record_execution(&self, elapsed: Duration, items_scanned: u64) {
+        self.execute_us
+            .store(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.items_scanned.store(items_scanned, Ordering::Relaxed);
+    }
+
+    /// Have the worker time each step of the query
+    pub fn profile_steps(&self) {
+        self.profile_steps.store(true, Ordering::Relaxed);
+    }
+
+    pub fn steps_profiled(&self) -> bool {
+        self.profile_steps.load(Ordering::Relaxed)
+    }
+
+    /// Record the steps of the handler's synchronous run
+    pub fn record_steps(&self, steps: Vec<StepTiming>) {
+        *self.steps.lock().unwrap_or_else(PoisonError::into_inner) = steps;
+    }
+
+    pub fn steps(&self) -> Vec<StepTiming> {
+        self.steps
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn queue_wait(&self) -> Duration {
+        Duration::from_micros(self.queue_wait_us.load(Ordering::Relaxed))
+    }
+
+    pub fn execute(&self) -> Duration {
+        Duration::from_micros(self.execute_us.load(Ordering::Relaxed))
+    }
+
+    pub fn items_scanned(&self) -> u64 {
+        self.items_scanned.load(Ordering::Relaxed)
+    }
+}
+
+impl RequestTrace {
+    /// Start waiting in `lane` under the current span
+    pub fn queued(lane: &'static str) -> Self {
+        let request = Span::current();
+        let queue_wait = info_span!(parent: &request, "helix.queue_wait", lane);
+        RequestTrace {
+            request,
+            queue_wait,
+            ..Default::default()
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "interactive" => Ok(Priority::Interactive),
+            "batch" => Ok(Priority::Batch),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(feature = "gateway")]
 impl<S> FromRequest<S> for Request
 where
     S: Send + Sync,
@@ -81,7 +278,7 @@ where
 
         let out_fmt = match headers.get(ACCEPT) {
             Some(v) => match v.to_str() {
-                Ok(s) => s.parse().unwrap_or_default(),
+                Ok(s) => Format::from_accept(s),
                 Err(_) => return Err(StatusCode::BAD_REQUEST),
             },
             None => Format::default(),
@@ -89,6 +286,10 @@ where
 
         let body = match Bytes::from_request(req, state).await {
             Ok(b) => b,
+            // Bodies sent without a length are only found to be too large as they're read
+            Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
             Err(e) => {
                 error!(?e, "Error getting bytes");
                 return Err(StatusCode::INTERNAL_SERVER_ERROR);
@@ -107,7 +308,7 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "gateway"))]
 mod tests {
     use super::*;
 
@@ -333,4 +534,92 @@ mod tests {
         let cloned = request.clone();
         assert_eq!(cloned.api_key, request.api_key);
     }
+
+    // ============================================================================
+    // Priority Tests
+    // ============================================================================
+
+    #[test]
+    fn test_priority_default_is_interactive() {
+        assert_eq!(Priority::default(), Priority::Interactive);
+    }
+
+    #[test]
+    fn test_priority_from_str() {
+        assert_eq!("batch".parse::<Priority>(), Ok(Priority::Batch));
+        assert_eq!(
+            " Interactive ".parse::<Priority>(),
+            Ok(Priority::Interactive)
+        );
+        assert!("urgent".parse::<Priority>().is_err());
+    }
+
+    #[test]
+    fn test_hints_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            RequestHints::from_headers(&headers),
+            RequestHints::default()
+        );
+
+        headers.insert(PRIORITY_HEADER, "batch".parse().unwrap());
+        headers.insert(READ_YOUR_WRITES_HEADER, "true".parse().unwrap());
+        headers.insert(PARTITION_HEADER, "tenant-7".parse().unwrap());
+        let hints = RequestHints::from_headers(&headers);
+        assert_eq!(hints.priority, Some(Priority::Batch));
+        assert!(hints.read_your_writes);
+        assert_eq!(hints.partition.as_deref(), Some("tenant-7"));
+
+        headers.insert(READ_YOUR_WRITES_HEADER, "no".parse().unwrap());
+        assert!(!RequestHints::from_headers(&headers).read_your_writes);
+    }
+
+    // ============================================================================
+    // Format Negotiation Tests
+    // ============================================================================
+
+    #[cfg(not(feature = "api-key"))]
+    async fn negotiated(
+        content_type: Option<&str>,
+        accept: Option<&str>,
+    ) -> Result<Request, StatusCode> {
+        let mut builder = axum::http::Request::post("/get_users");
+        if let Some(content_type) = content_type {
+            builder = builder.header(CONTENT_TYPE, content_type);
+        }
+        if let Some(accept) = accept {
+            builder = builder.header(ACCEPT, accept);
+        }
+        let request = builder.body(axum::body::Body::empty()).unwrap();
+        Request::from_request(request, &()).await
+    }
+
+    #[cfg(not(feature = "api-key"))]
+    #[tokio::test]
+    async fn test_request_negotiates_formats() {
+        let request = negotiated(None, None).await.unwrap();
+        assert_eq!(
+            (request.in_fmt, request.out_fmt),
+            (Format::Json, Format::Json)
+        );
+
+        let request = negotiated(Some("application/msgpack"), Some("text/csv"))
+            .await
+            .unwrap();
+        assert_eq!(
+            (request.in_fmt, request.out_fmt),
+            (Format::MsgPack, Format::Csv)
+        );
+
+        let request = negotiated(None, Some("text/html, application/x-ndjson"))
+            .await
+            .unwrap();
+        assert_eq!(request.out_fmt, Format::NdJson);
+
+        let request = negotiated(None, Some("*/*")).await.unwrap();
+        assert_eq!(request.out_fmt, Format::Json);
+
+        let rejected = negotiated(Some("application/xml"), None).await;
+        assert_eq!(rejected.unwrap_err(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
 }
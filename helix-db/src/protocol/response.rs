@@ -1,5 +1,5 @@
-use axum::response::IntoResponse;
-use reqwest::header::CONTENT_TYPE;
+#[cfg(feature = "gateway")]
+use axum::{http::header::CONTENT_TYPE, response::IntoResponse};
 
 use crate::protocol::Format;
 #[derive(Debug)]
@@ -8,6 +8,7 @@ pub struct Response {
     pub fmt: Format,
 }
 
+#[cfg(feature = "gateway")]
 impl IntoResponse for Response {
     fn into_response(self) -> axum::response::Response {
         axum::response::Response::builder()
@@ -17,7 +18,7 @@ impl IntoResponse for Response {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "gateway"))]
 mod tests {
     use super::*;
 
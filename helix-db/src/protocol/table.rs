@@ -0,0 +1,211 @@
+//! Tabular layout of query results, shared by the row oriented response formats (Arrow, CSV
+//! and NDJSON).
+//!
+//! Results are JSON shaped, so they are laid out as a table before encoding: a result with a
+//! single field that is an array has one row per element, and any other result is a single
+//! row with one column per field (an aggregation, a count, a single node). Array elements
+//! that are objects are spread over one column per property; other elements fill a single
+//! column named after the field.
+
+use indexmap::IndexMap;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+
+/// Column holding array elements that are not objects, when the result has no field name
+const VALUE_COLUMN: &str = "value";
+
+pub struct Table<'a> {
+    pub rows: usize,
+    /// Values of each column by row, `None` where a row lacks the column
+    pub columns: IndexMap<String, Vec<Option<&'a Value>>>,
+}
+
+impl<'a> Table<'a> {
+    pub fn from_result(result: &'a Value) -> Self {
+        if let Some(object) = result.as_object() {
+            let mut fields = object.iter();
+            if let (Some((name, value)), None) = (fields.next(), fields.next())
+                && let Some(elements) = value.as_array()
+            {
+                return Table::from_elements(name, elements.iter());
+            }
+            return Table::from_rows(std::iter::once(result));
+        }
+        match result.as_array() {
+            Some(elements) => Table::from_elements(VALUE_COLUMN, elements.iter()),
+            None if result.is_null() => Table {
+                rows: 0,
+                columns: IndexMap::new(),
+            },
+            None => Table::from_elements(VALUE_COLUMN, std::iter::once(result)),
+        }
+    }
+
+    /// One row per element, spread over their properties when every element is an object
+    fn from_elements(name: &str, elements: impl ExactSizeIterator<Item = &'a Value>) -> Self {
+        let elements = elements.collect::<Vec<_>>();
+        if !elements.is_empty() && elements.iter().all(|e| e.is_object()) {
+            return Table::from_rows(elements.into_iter());
+        }
+        Table {
+            rows: elements.len(),
+            columns: IndexMap::from([(name.to_string(), elements.into_iter().map(Some).collect())]),
+        }
+    }
+
+    fn from_rows(rows: impl Iterator<Item = &'a Value>) -> Self {
+        let mut table = Table {
+            rows: 0,
+            columns: IndexMap::new(),
+        };
+        for row in rows {
+            for (key, value) in row.as_object().into_iter().flat_map(|o| o.iter()) {
+                table
+                    .columns
+                    .entry(key.to_string())
+                    .or_insert_with(|| vec![None; table.rows])
+                    .push(Some(value));
+            }
+            table.rows += 1;
+            for values in table.columns.values_mut() {
+                values.resize(table.rows, None);
+            }
+        }
+        table
+    }
+
+    /// Columns of row `index` the row has a value for
+    fn row(&self, index: usize) -> impl Iterator<Item = (&str, &'a Value)> {
+        self.columns
+            .iter()
+            .filter_map(move |(name, values)| Some((name.as_str(), values[index]?)))
+    }
+
+    /// Write the table as CSV with a header line. Strings are written as their text, nested
+    /// values as JSON, and nulls and missing values as empty fields.
+    pub fn write_csv(&self, out: &mut Vec<u8>) {
+        if self.columns.is_empty() {
+            return;
+        }
+        let header = self.columns.keys().map(|name| csv_field(name));
+        write_csv_line(out, header);
+        for index in 0..self.rows {
+            let fields = self.columns.values().map(|values| match values[index] {
+                None => String::new(),
+                Some(value) if value.is_null() => String::new(),
+                Some(value) => match value.as_str() {
+                    Some(text) => csv_field(text),
+                    None => csv_field(&value.to_string()),
+                },
+            });
+            write_csv_line(out, fields);
+        }
+    }
+
+    /// Write one JSON object per row, each on its own line
+    pub fn write_ndjson(&self, out: &mut Vec<u8>) {
+        for index in 0..self.rows {
+            out.push(b'{');
+            for (i, (name, value)) in self.row(index).enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend_from_slice(
+                    &sonic_rs::to_vec(name).expect("column names should serialize"),
+                );
+                out.push(b':');
+                out.extend_from_slice(value.to_string().as_bytes());
+            }
+            out.extend_from_slice(b"}\n");
+        }
+    }
+}
+
+fn write_csv_line(out: &mut Vec<u8>, fields: impl Iterator<Item = String>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        out.extend_from_slice(field.as_bytes());
+    }
+    out.push(b'\n');
+}
+
+/// Quote a field when it holds a delimiter, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(json: &str) -> String {
+        let value: Value = sonic_rs::from_str(json).unwrap();
+        let mut out = Vec::new();
+        Table::from_result(&value).write_csv(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    fn ndjson(json: &str) -> String {
+        let value: Value = sonic_rs::from_str(json).unwrap();
+        let mut out = Vec::new();
+        Table::from_result(&value).write_ndjson(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_layout() {
+        let value: Value = sonic_rs::from_str(
+            r#"{"users": [{"name": "alice", "age": 31}, {"name": "bob", "active": true}]}"#,
+        )
+        .unwrap();
+        let table = Table::from_result(&value);
+        assert_eq!(table.rows, 2);
+        assert_eq!(
+            table.columns.keys().collect::<Vec<_>>(),
+            ["name", "age", "active"]
+        );
+        assert!(table.columns["age"][1].is_none());
+        assert!(table.columns["active"][0].is_none());
+
+        let value: Value = sonic_rs::from_str(r#"{"count": 3, "label": "x"}"#).unwrap();
+        let table = Table::from_result(&value);
+        assert_eq!((table.rows, table.columns.len()), (1, 2));
+
+        let value: Value = sonic_rs::from_str(r#"{"names": ["a", "b"]}"#).unwrap();
+        let table = Table::from_result(&value);
+        assert_eq!(table.rows, 2);
+        assert_eq!(table.columns.keys().collect::<Vec<_>>(), ["names"]);
+    }
+
+    #[test]
+    fn test_csv() {
+        let text = csv(r#"{"users": [
+                {"name": "alice", "bio": "likes \"graphs\", vectors", "age": 31, "tags": ["a"]},
+                {"name": "bob", "bio": null, "age": 2.5}
+            ]}"#);
+        assert_eq!(
+            text,
+            "name,bio,age,tags\n\
+             alice,\"likes \"\"graphs\"\", vectors\",31,\"[\"\"a\"\"]\"\n\
+             bob,,2.5,\n"
+        );
+        assert_eq!(csv("null"), "");
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let lines = ndjson(
+            r#"{"users": [{"name": "alice", "meta": {"n": 1}}, {"name": "bob", "meta": null}]}"#,
+        );
+        assert_eq!(
+            lines,
+            "{\"name\":\"alice\",\"meta\":{\"n\":1}}\n{\"name\":\"bob\",\"meta\":null}\n"
+        );
+        assert_eq!(ndjson(r#"{"count": 3}"#), "{\"count\":3}\n");
+    }
+}
@@ -1,7 +1,7 @@
 use crate::debug_println;
 use crate::helix_gateway::mcp::tools::{FilterValues, Operator};
 use crate::protocol::date::Date;
-use crate::utils::id::ID;
+use crate::utils::id::{ID, uuid_str_from_buf};
 use crate::{helix_engine::types::GraphError, helixc::generator::utils::GenRef};
 use chrono::{DateTime, Utc};
 use serde::{
@@ -38,6 +38,106 @@ pub enum Value {
     Object(HashMap<String, Value>),
     #[default]
     Empty,
+    Path(Path),
+}
+
+/// A walk through the graph, as `::PATHS` returns it: `nodes[i]` and `nodes[i + 1]` are joined
+/// by `edges[i]`
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Path {
+    pub nodes: Vec<PathNode>,
+    pub edges: Vec<PathEdge>,
+}
+
+/// A node of a [`Path`]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PathNode {
+    pub id: u128,
+    pub label: String,
+    pub properties: HashMap<String, Value>,
+}
+
+/// An edge of a [`Path`]. It points against the direction of the walk where the path steps
+/// through an `In`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PathEdge {
+    pub id: u128,
+    pub label: String,
+    pub from_node: u128,
+    pub to_node: u128,
+    pub properties: HashMap<String, Value>,
+}
+
+/// Serializes like the nodes and edges of a query's response, with uuid string ids and the
+/// properties alongside them, or field by field for binary formats
+impl Serialize for Path {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Path", 2)?;
+        state.serialize_field("nodes", &self.nodes)?;
+        state.serialize_field("edges", &self.edges)?;
+        state.end()
+    }
+}
+
+impl Serialize for PathNode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::{SerializeMap, SerializeStruct};
+
+        if serializer.is_human_readable() {
+            let mut buffer = [0u8; 36];
+            let mut state = serializer.serialize_map(Some(2 + self.properties.len()))?;
+            state.serialize_entry("id", uuid_str_from_buf(self.id, &mut buffer))?;
+            state.serialize_entry("label", &self.label)?;
+            for (key, value) in &self.properties {
+                state.serialize_entry(key, value)?;
+            }
+            state.end()
+        } else {
+            let mut state = serializer.serialize_struct("PathNode", 3)?;
+            state.serialize_field("id", &self.id)?;
+            state.serialize_field("label", &self.label)?;
+            state.serialize_field("properties", &self.properties)?;
+            state.end()
+        }
+    }
+}
+
+impl Serialize for PathEdge {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::{SerializeMap, SerializeStruct};
+
+        if serializer.is_human_readable() {
+            let mut buffer = [0u8; 36];
+            let mut state = serializer.serialize_map(Some(4 + self.properties.len()))?;
+            state.serialize_entry("id", uuid_str_from_buf(self.id, &mut buffer))?;
+            state.serialize_entry("label", &self.label)?;
+            state.serialize_entry("from_node", uuid_str_from_buf(self.from_node, &mut buffer))?;
+            state.serialize_entry("to_node", uuid_str_from_buf(self.to_node, &mut buffer))?;
+            for (key, value) in &self.properties {
+                state.serialize_entry(key, value)?;
+            }
+            state.end()
+        } else {
+            let mut state = serializer.serialize_struct("PathEdge", 5)?;
+            state.serialize_field("id", &self.id)?;
+            state.serialize_field("label", &self.label)?;
+            state.serialize_field("from_node", &self.from_node)?;
+            state.serialize_field("to_node", &self.to_node)?;
+            state.serialize_field("properties", &self.properties)?;
+            state.end()
+        }
+    }
 }
 
 impl Value {
@@ -113,6 +213,7 @@ impl Value {
             Value::Array(_) => "Array",
             Value::Object(_) => "Object",
             Value::Empty => "Empty",
+            Value::Path(_) => "Path",
         }
     }
 
@@ -264,6 +365,7 @@ impl PartialEq<Value> for Value {
             (Value::Date(s), Value::Date(o)) => s == o,
             (Value::Boolean(s), Value::Boolean(o)) => s == o,
             (Value::Array(s), Value::Array(o)) => s == o,
+            (Value::Path(s), Value::Path(o)) => s == o,
             (Value::Empty, Value::Empty) => true,
             (Value::Empty, _) => false,
             (_, Value::Empty) => false,
@@ -288,7 +390,7 @@ impl PartialEq<Value> for Value {
 
 impl Value {
     /// Convert any numeric Value to f64 for type promotion
-    fn to_f64(&self) -> Option<f64> {
+    pub(crate) fn to_f64(&self) -> Option<f64> {
         match self {
             Value::I8(v) => Some(*v as f64),
             Value::I16(v) => Some(*v as f64),
@@ -1326,7 +1428,7 @@ impl PartialOrd<ID> for Value {
         match self {
             Value::Id(id) => id.partial_cmp(other),
             Value::String(s) => Some(ID::from(s).partial_cmp(other)?),
-            Value::U128(u) => Some(u.partial_cmp(other)?),
+            Value::U128(u) => u.partial_cmp(&**other),
             _ => None,
         }
     }
@@ -1379,6 +1481,7 @@ impl Serialize for Value {
                     map.end()
                 }
                 Value::Empty => serializer.serialize_none(),
+                Value::Path(path) => path.serialize(serializer),
             }
         } else {
             match self {
@@ -1404,6 +1507,9 @@ impl Serialize for Value {
                     serializer.serialize_newtype_variant("Value", 16, "Object", obj)
                 }
                 Value::Empty => serializer.serialize_unit_variant("Value", 17, "Empty"),
+                Value::Path(path) => {
+                    serializer.serialize_newtype_variant("Value", 18, "Path", path)
+                }
             }
         }
     }
@@ -1602,9 +1708,10 @@ impl<'de> Deserialize<'de> for Value {
                         variant_data.unit_variant()?;
                         Ok(Value::Empty)
                     }
+                    18 => Ok(Value::Path(variant_data.newtype_variant()?)),
                     _ => Err(serde::de::Error::invalid_value(
                         serde::de::Unexpected::Unsigned(variant_idx as u64),
-                        &"variant index 0 through 17",
+                        &"variant index 0 through 18",
                     )),
                 }
             }
@@ -1650,7 +1757,7 @@ impl<'de> Deserialize<'de> for Value {
                 "Value",
                 &[
                     "String", "F32", "F64", "I8", "I16", "I32", "I64", "U8", "U16", "U32", "U64",
-                    "U128", "Date", "Boolean", "Id", "Array", "Object", "Empty",
+                    "U128", "Date", "Boolean", "Id", "Array", "Object", "Empty", "Path",
                 ],
                 ValueVisitor,
             )
@@ -1957,6 +2064,7 @@ impl From<Value> for GenRef<String> {
             Value::Array(_a) => unimplemented!(),
             Value::Object(_o) => unimplemented!(),
             Value::Empty => GenRef::Literal("".to_string()),
+            Value::Path(_p) => unimplemented!(),
         }
     }
 }
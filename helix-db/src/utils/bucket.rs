@@ -0,0 +1,187 @@
+//! Time buckets for `::BUCKET_BY`. Buckets of days and longer follow the calendar of their
+//! time zone, starting at local midnight, on Mondays for weeks and on the 1st for months, so a
+//! day is 23 or 25 hours long across a daylight saving change. Shorter buckets are counted
+//! from local midnight.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::helix_engine::traversal_core::traversal_value::TraversalValue;
+use crate::protocol::{date::Date, value::Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// A bucket width like `15m` or `1d`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub count: u32,
+    pub unit: IntervalUnit,
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (count, unit) = s.split_at(split);
+        let unit = match unit {
+            "s" => IntervalUnit::Second,
+            "m" => IntervalUnit::Minute,
+            "h" => IntervalUnit::Hour,
+            "d" => IntervalUnit::Day,
+            "w" => IntervalUnit::Week,
+            "mo" => IntervalUnit::Month,
+            "y" => IntervalUnit::Year,
+            _ => return Err(format!("`{s}` needs a unit of s, m, h, d, w, mo or y")),
+        };
+        match count.parse::<u32>() {
+            Ok(count) if count > 0 => Ok(Interval { count, unit }),
+            _ => Err(format!("`{s}` needs a whole number of units above zero")),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            IntervalUnit::Second => "s",
+            IntervalUnit::Minute => "m",
+            IntervalUnit::Hour => "h",
+            IntervalUnit::Day => "d",
+            IntervalUnit::Week => "w",
+            IntervalUnit::Month => "mo",
+            IntervalUnit::Year => "y",
+        };
+        write!(f, "{}{unit}", self.count)
+    }
+}
+
+impl Interval {
+    /// The start of the bucket `time` falls in
+    pub fn bucket_start(&self, time: DateTime<Utc>, tz: Tz) -> DateTime<Tz> {
+        let local = time.with_timezone(&tz).naive_local();
+        let n = i64::from(self.count);
+        let date = local.date();
+        let start = match self.unit {
+            IntervalUnit::Second | IntervalUnit::Minute | IntervalUnit::Hour => {
+                let unit = match self.unit {
+                    IntervalUnit::Second => 1,
+                    IntervalUnit::Minute => 60,
+                    _ => 3600,
+                };
+                let seconds = local.signed_duration_since(date.and_time(NaiveTime::MIN));
+                let seconds = seconds.num_seconds();
+                date.and_time(NaiveTime::MIN) + TimeDelta::seconds(seconds - seconds % (n * unit))
+            }
+            IntervalUnit::Day => from_days(floor(i64::from(date.num_days_from_ce()), n)),
+            // 0001-01-01, day 1, was a Monday
+            IntervalUnit::Week => {
+                let week = (i64::from(date.num_days_from_ce()) - 1).div_euclid(7);
+                from_days(floor(week, n) * 7 + 1)
+            }
+            IntervalUnit::Month => {
+                let month = floor(i64::from(date.year()) * 12 + i64::from(date.month0()), n);
+                from_year_month(month.div_euclid(12), month.rem_euclid(12) + 1)
+            }
+            IntervalUnit::Year => from_year_month(floor(i64::from(date.year()), n), 1),
+        };
+        tz.from_local_datetime(&start)
+            .earliest()
+            // Midnight skipped by a daylight saving change, so the bucket starts after the gap
+            .or_else(|| {
+                tz.from_local_datetime(&(start + TimeDelta::hours(1)))
+                    .earliest()
+            })
+            .unwrap_or_else(|| tz.from_utc_datetime(&start))
+    }
+}
+
+fn floor(value: i64, n: i64) -> i64 {
+    value - value.rem_euclid(n)
+}
+
+fn from_days(days: i64) -> NaiveDateTime {
+    NaiveDate::from_num_days_from_ce_opt(days as i32)
+        .unwrap_or(NaiveDate::MIN)
+        .and_time(NaiveTime::MIN)
+}
+
+fn from_year_month(year: i64, month: i64) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year as i32, month as u32, 1)
+        .unwrap_or(NaiveDate::MIN)
+        .and_time(NaiveTime::MIN)
+}
+
+/// The time a property holds, from a date, a date string or a timestamp in seconds
+pub fn time_of(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Date(date) => Some(*date.inner()),
+        other => Date::new(other).ok().map(|date| *date.inner()),
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct Bucket<'arena> {
+    /// RFC 3339 start of the bucket, with the offset of its time zone
+    pub start: String,
+    pub count: usize,
+    /// Left out when only counting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<TraversalValue<'arena>>>,
+}
+
+/// Buckets in time order, leaving out the ones without items
+#[derive(Clone, Serialize)]
+#[serde(transparent)]
+pub struct Buckets<'arena>(pub Vec<Bucket<'arena>>);
+
+impl<'arena> Buckets<'arena> {
+    /// Sort timed items into buckets of `interval` in `tz`
+    pub fn new(
+        items: impl IntoIterator<Item = (DateTime<Utc>, TraversalValue<'arena>)>,
+        interval: Interval,
+        tz: Tz,
+        should_count: bool,
+    ) -> Self {
+        let mut buckets: BTreeMap<DateTime<Tz>, Bucket<'arena>> = BTreeMap::new();
+        for (time, item) in items {
+            let start = interval.bucket_start(time, tz);
+            let bucket = buckets.entry(start).or_insert_with(|| Bucket {
+                start: start.to_rfc3339(),
+                count: 0,
+                items: (!should_count).then(Vec::new),
+            });
+            bucket.count += 1;
+            if let Some(items) = &mut bucket.items {
+                items.push(item);
+            }
+        }
+        Buckets(buckets.into_values().collect())
+    }
+
+    pub fn into_count(self) -> Self {
+        Buckets(
+            self.0
+                .into_iter()
+                .map(|bucket| Bucket {
+                    items: None,
+                    ..bucket
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,10 @@
+use std::str::FromStr;
+
+/// Parse a cron expression, reading a standard five-field expression as running at second 0
+pub fn parse_cron(cron: &str) -> Result<cron::Schedule, cron::error::Error> {
+    if cron.split_whitespace().count() == 5 {
+        cron::Schedule::from_str(&format!("0 {cron}"))
+    } else {
+        cron::Schedule::from_str(cron)
+    }
+}
@@ -1,7 +1,10 @@
 pub mod aggregate;
+pub mod bucket;
+pub mod cron;
 pub mod group_by;
 pub mod id;
 pub mod items;
 pub mod label_hash;
 pub mod properties;
+pub mod stats;
 pub mod tqdm;
@@ -0,0 +1,190 @@
+//! Statistics for `::P50`, `::P95`, `::P99`, `::MEDIAN` and `::STDDEV`. Quantiles keep the
+//! values as they are until there are more than [`EXACT_LIMIT`] of them, so smaller sets get
+//! exact quantiles. Past that they are merged into a t-digest (Dunning, "Computing Extremely
+//! Accurate Quantiles Using t-Digests"), whose centroids stay small near the tails so P99 stays
+//! close while memory is bounded by the compression.
+
+use std::f64::consts::PI;
+
+/// Values held before merging them into centroids
+pub const EXACT_LIMIT: usize = 10_000;
+
+const DEFAULT_COMPRESSION: f64 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    /// A digest keeping around `compression` centroids once merged
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Adds a value, skipping NaN
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+        if self.buffer.len() >= EXACT_LIMIT {
+            self.merge();
+        }
+    }
+
+    /// How many values were added
+    pub fn count(&self) -> usize {
+        let merged: f64 = self.centroids.iter().map(|c| c.weight).sum();
+        merged as usize + self.buffer.len()
+    }
+
+    /// Whether quantiles are still exact
+    pub fn is_exact(&self) -> bool {
+        self.centroids.is_empty()
+    }
+
+    /// The value below which a `q` share of the values fall, interpolating between neighbours,
+    /// or `None` when nothing was added
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        let q = q.clamp(0.0, 1.0);
+        if self.is_exact() {
+            return exact_quantile(&mut self.buffer, q);
+        }
+        self.merge();
+
+        let centroids = &self.centroids;
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q * total;
+
+        // Half of each centroid's weight lies on either side of its mean, so the ends are
+        // interpolated towards the smallest and largest values seen
+        let first = centroids[0];
+        if target <= first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * target / (first.weight / 2.0));
+        }
+        let mut before = 0.0;
+        for pair in centroids.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            let left_center = before + left.weight / 2.0;
+            let right_center = before + left.weight + right.weight / 2.0;
+            if target <= right_center {
+                let share = (target - left_center) / (right_center - left_center);
+                return Some(left.mean + share * (right.mean - left.mean));
+            }
+            before += left.weight;
+        }
+        let last = centroids[centroids.len() - 1];
+        let past = (target - (total - last.weight / 2.0)) / (last.weight / 2.0);
+        Some((last.mean + past * (self.max - last.mean)).min(self.max))
+    }
+
+    /// Merges the held values into the centroids
+    fn merge(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all: Vec<Centroid> = self
+            .centroids
+            .drain(..)
+            .chain(
+                self.buffer
+                    .drain(..)
+                    .map(|mean| Centroid { mean, weight: 1.0 }),
+            )
+            .collect();
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+
+        let mut merged = Vec::with_capacity(self.compression as usize);
+        let mut current = all[0];
+        let mut before = 0.0;
+        let mut limit = total * self.share_limit(0.0);
+        for next in all.into_iter().skip(1) {
+            if before + current.weight + next.weight <= limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                merged.push(current);
+                limit = total * self.share_limit(before / total);
+                current = next;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// The furthest share a centroid starting at share `q` may reach, one step along the
+    /// k1 scale `k(q) = compression / 2π · asin(2q - 1)`
+    fn share_limit(&self, q: f64) -> f64 {
+        let k = self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let angle = (k + 1.0) * 2.0 * PI / self.compression;
+        if angle >= PI / 2.0 {
+            1.0
+        } else {
+            (angle.sin() + 1.0) / 2.0
+        }
+    }
+}
+
+fn exact_quantile(values: &mut [f64], q: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let position = q * (values.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    Some(values[below] + (values[above] - values[below]) * (position - below as f64))
+}
+
+/// Running mean and variance by Welford's method, so large values don't lose precision
+#[derive(Debug, Clone, Default)]
+pub struct Variance {
+    count: usize,
+    mean: f64,
+    squares: f64,
+}
+
+impl Variance {
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.squares += delta * (value - self.mean);
+    }
+
+    /// The sample standard deviation, or `None` for fewer than two values
+    pub fn stddev(&self) -> Option<f64> {
+        (self.count > 1).then(|| (self.squares / (self.count - 1) as f64).sqrt())
+    }
+}
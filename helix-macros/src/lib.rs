@@ -5,27 +5,79 @@ extern crate syn;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    Data, DeriveInput, Expr, FnArg, Ident, ItemFn, ItemStruct, ItemTrait, LitInt, Pat, Stmt, Token,
-    TraitItem,
+    Data, DeriveInput, Expr, FnArg, Ident, ItemFn, ItemStruct, ItemTrait, LitInt, LitStr, Pat,
+    Stmt, Token, TraitItem,
     parse::{Parse, ParseStream},
     parse_macro_input,
+    punctuated::Punctuated,
 };
 
 struct HandlerArgs {
     is_write: bool,
+    priority: Option<Ident>,
+    cache_ttl_ms: Option<LitInt>,
+    labels: Option<Vec<LitStr>>,
+    roles: Option<Vec<LitStr>>,
+    max_concurrency: Option<LitInt>,
 }
 
 impl Parse for HandlerArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.is_empty() {
-            return Ok(HandlerArgs { is_write: false });
-        }
-        let ident: Ident = input.parse()?;
-        if ident == "is_write" {
-            Ok(HandlerArgs { is_write: true })
-        } else {
-            Err(syn::Error::new(ident.span(), "expected `is_write`"))
+        let mut args = HandlerArgs {
+            is_write: false,
+            priority: None,
+            cache_ttl_ms: None,
+            labels: None,
+            roles: None,
+            max_concurrency: None,
+        };
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            if ident == "is_write" {
+                args.is_write = true;
+            } else if ident == "priority" {
+                input.parse::<Token![=]>()?;
+                let level: Ident = input.parse()?;
+                let variant = match level.to_string().as_str() {
+                    "interactive" => "Interactive",
+                    "batch" => "Batch",
+                    _ => {
+                        return Err(syn::Error::new(
+                            level.span(),
+                            "expected `interactive` or `batch`",
+                        ));
+                    }
+                };
+                args.priority = Some(Ident::new(variant, level.span()));
+            } else if ident == "cache_ttl_ms" {
+                input.parse::<Token![=]>()?;
+                args.cache_ttl_ms = Some(input.parse()?);
+            } else if ident == "labels" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let labels = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                args.labels = Some(labels.into_iter().collect());
+            } else if ident == "roles" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let roles = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                args.roles = Some(roles.into_iter().collect());
+            } else if ident == "max_concurrency" {
+                input.parse::<Token![=]>()?;
+                args.max_concurrency = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `is_write`, `priority = ...`, `cache_ttl_ms = ...`, `labels = [...]`, `roles = [...]` or `max_concurrency = ...`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
+        Ok(args)
     }
 }
 
@@ -36,6 +88,31 @@ pub fn handler(args: TokenStream, item: TokenStream) -> TokenStream {
     let fn_name = &input_fn.sig.ident;
     let fn_name_str = fn_name.to_string();
     let is_write = args.is_write;
+    let with_priority = args.priority.map(|variant| {
+        quote! {
+            .with_priority(::helix_db::protocol::request::Priority::#variant)
+        }
+    });
+    let with_cache_ttl = args.cache_ttl_ms.map(|ttl_ms| {
+        quote! {
+            .with_cache_ttl_ms(#ttl_ms)
+        }
+    });
+    let with_labels = args.labels.map(|labels| {
+        quote! {
+            .with_labels(&[#(#labels),*])
+        }
+    });
+    let with_roles = args.roles.map(|roles| {
+        quote! {
+            .with_roles(&[#(#roles),*])
+        }
+    });
+    let with_max_concurrency = args.max_concurrency.map(|limit| {
+        quote! {
+            .with_max_concurrency(#limit)
+        }
+    });
     // Create a unique static name for each handler
     let static_name = quote::format_ident!(
         "_MAIN_HANDLER_REGISTRATION_{}",
@@ -55,6 +132,11 @@ pub fn handler(args: TokenStream, item: TokenStream) -> TokenStream {
                         #fn_name,
                         #is_write
                     )
+                    #with_priority
+                    #with_cache_ttl
+                    #with_labels
+                    #with_roles
+                    #with_max_concurrency
                 )
             }
         };
@@ -62,11 +144,53 @@ pub fn handler(args: TokenStream, item: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+struct McpHandlerArgs {
+    is_write: bool,
+    roles: Option<Vec<LitStr>>,
+}
+
+impl Parse for McpHandlerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = McpHandlerArgs {
+            is_write: false,
+            roles: None,
+        };
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            if ident == "is_write" {
+                args.is_write = true;
+            } else if ident == "roles" {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let roles = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                args.roles = Some(roles.into_iter().collect());
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `is_write` or `roles = [...]`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
 #[proc_macro_attribute]
-pub fn mcp_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn mcp_handler(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as McpHandlerArgs);
     let input_fn = parse_macro_input!(item as ItemFn);
     let fn_name = &input_fn.sig.ident;
     let fn_name_str = fn_name.to_string();
+    let is_write = args.is_write;
+    let with_roles = args.roles.map(|roles| {
+        quote! {
+            .with_roles(&[#(#roles),*])
+        }
+    });
     // Create a unique static name for each handler
     let static_name = quote::format_ident!(
         "_MCP_HANDLER_REGISTRATION_{}",
@@ -83,8 +207,10 @@ pub fn mcp_handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 MCPHandlerSubmission(
                     MCPHandler::new(
                         #fn_name_str,
-                        #fn_name
+                        #fn_name,
+                        #is_write
                     )
+                    #with_roles
                 )
             }
         };
@@ -370,6 +496,142 @@ pub fn migration(args: TokenStream, item: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+// example:
+// #[view(PopularPosts, labels = ["Post"], refresh = "0 * * * *")]
+// pub fn view_popular_posts<'db>(db: &'db HelixGraphStorage, txn: &RoTxn<'db>) -> Result<Vec<u128>, GraphError> {
+//     ...
+// }
+
+struct ViewArgs {
+    name: Ident,
+    labels: Option<Vec<LitStr>>,
+    refresh: Option<LitStr>,
+}
+
+impl Parse for ViewArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ViewArgs {
+            name: input.parse()?,
+            labels: None,
+            refresh: None,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let ident: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if ident == "labels" {
+                let content;
+                syn::bracketed!(content in input);
+                let labels = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                args.labels = Some(labels.into_iter().collect());
+            } else if ident == "refresh" {
+                args.refresh = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `labels = [...]` or `refresh = \"...\"`",
+                ));
+            }
+        }
+        Ok(args)
+    }
+}
+
+#[proc_macro_attribute]
+pub fn view(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as ViewArgs);
+    let input_fn = parse_macro_input!(item as ItemFn);
+    let fn_name = &input_fn.sig.ident;
+    let name = &args.name;
+    let with_labels = args.labels.map(|labels| {
+        quote! {
+            .with_labels(&[#(#labels),*])
+        }
+    });
+    let with_refresh_cron = args.refresh.map(|cron| {
+        quote! {
+            .with_refresh_cron(#cron)
+        }
+    });
+
+    // Create a unique static name for each view
+    let static_name = quote::format_ident!(
+        "_MAIN_VIEW_REGISTRATION_{}",
+        fn_name.to_string().to_uppercase()
+    );
+
+    let expanded = quote! {
+        #input_fn
+
+        #[doc(hidden)]
+        #[used]
+        static #static_name: () = {
+            inventory::submit! {
+                ::helix_db::helix_engine::storage_core::views::ViewSubmission(
+                    ::helix_db::helix_engine::storage_core::views::View::new(
+                        stringify!(#name),
+                        #fn_name
+                    )
+                    #with_labels
+                    #with_refresh_cron
+                )
+            }
+        };
+    };
+    expanded.into()
+}
+
+// example:
+// #[trigger(AddN, Order)]
+// pub fn trigger_AddN_Order<'db>(db: &'db HelixGraphStorage, mut txn: &mut RwTxn<'db>, id: u128) -> Result<(), GraphError> {
+//     ...
+// }
+#[proc_macro_attribute]
+pub fn trigger(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Ident, Token![,]>::parse_terminated);
+    let input_fn = parse_macro_input!(item as ItemFn);
+    let fn_name = &input_fn.sig.ident;
+    let (event, label) = match args.iter().collect::<Vec<_>>().as_slice() {
+        [event, label] if *event == "AddN" || *event == "AddE" => (*event, *label),
+        _ => {
+            return syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "expected `#[trigger(AddN, Label)]` or `#[trigger(AddE, Label)]`",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    // Create a unique static name for each trigger
+    let static_name = quote::format_ident!(
+        "_MAIN_TRIGGER_REGISTRATION_{}",
+        fn_name.to_string().to_uppercase()
+    );
+
+    let expanded = quote! {
+        #input_fn
+
+        #[doc(hidden)]
+        #[used]
+        static #static_name: () = {
+            inventory::submit! {
+                ::helix_db::helix_engine::storage_core::triggers::TriggerSubmission(
+                    ::helix_db::helix_engine::storage_core::triggers::Trigger::new(
+                        ::helix_db::helix_engine::storage_core::triggers::TriggerEvent::#event,
+                        stringify!(#label),
+                        #fn_name
+                    )
+                )
+            }
+        };
+    };
+    expanded.into()
+}
+
 #[proc_macro_attribute]
 pub fn helix_node(_attr: TokenStream, input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as ItemStruct);
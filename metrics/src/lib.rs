@@ -1,4 +1,5 @@
 pub mod events;
+pub mod prometheus;
 
 use std::{
     cell::RefCell,
@@ -198,6 +199,21 @@ fn flush_local_buffer(buf: &mut Vec<events::RawEvent<events::EventData>>) {
     }
 }
 
+/// Flush the current thread's buffered events to the global channel
+/// Call this before a worker thread exits so its events are not lost
+pub fn flush_thread_local() {
+    if !*METRICS_ENABLED {
+        return;
+    }
+
+    EVENT_BUFFER.with(|buffer| {
+        let mut buf = buffer.borrow_mut();
+        if !buf.is_empty() {
+            flush_local_buffer(&mut buf);
+        }
+    });
+}
+
 /// Create a RawEvent with common metadata
 fn create_raw_event(
     event_type: events::EventType,
@@ -309,12 +325,7 @@ pub async fn flush_all() -> Option<JoinHandle<()>> {
     }
 
     // Flush all thread-local buffers first
-    EVENT_BUFFER.with(|buffer| {
-        let mut buf = buffer.borrow_mut();
-        if !buf.is_empty() {
-            flush_local_buffer(&mut buf);
-        }
-    });
+    flush_thread_local();
 
     // Process any remaining events in the channel
     process_batch(&METRICS_STATE.events_rx).await
@@ -0,0 +1,366 @@
+//! Instance-local metrics rendered in the Prometheus text exposition format.
+//!
+//! Unlike telemetry events these never leave the instance: they are scraped from the
+//! gateway's `/metrics` endpoint, so they are recorded even when `METRICS_ENABLED` is off.
+//!
+//! Like telemetry events they are recorded into thread-local buffers, so workers never
+//! contend on them. Each thread's buffers are registered once and merged when rendered.
+
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Write},
+    sync::{
+        Arc, LazyLock, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+/// Upper bounds (in seconds) of the latency histogram buckets
+pub const LATENCY_BUCKETS_SECS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Fixed-bucket latency histogram backed by atomics
+pub struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS_SECS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Histogram {
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS_SECS.len()],
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        // Buckets are stored non-cumulatively and summed when rendered
+        if let Some(i) = LATENCY_BUCKETS_SECS.iter().position(|le| secs <= *le) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Add the observations of `other` to this histogram
+    fn merge(&self, other: &Histogram) {
+        for (bucket, other) in self.buckets.iter().zip(&other.buckets) {
+            bucket.fetch_add(other.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.count.fetch_add(other.count(), Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(other.sum_micros.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Write the `_bucket`, `_sum` and `_count` series. `labels` is either empty or a
+    /// comma separated list of `key="value"` pairs.
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (le, bucket) in LATENCY_BUCKETS_SECS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels}{sep}le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let count = self.count();
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let braced = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        let _ = writeln!(out, "{name}_bucket{{{labels}{sep}le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{name}_sum{braced} {sum}");
+        let _ = writeln!(out, "{name}_count{braced} {count}");
+    }
+}
+
+#[derive(Default)]
+struct RouteMetrics {
+    success: AtomicU64,
+    error: AtomicU64,
+    latency: Histogram,
+}
+
+impl RouteMetrics {
+    fn merge(&self, other: &RouteMetrics) {
+        self.success
+            .fetch_add(other.success.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.error
+            .fetch_add(other.error.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.latency.merge(&other.latency);
+    }
+}
+
+/// One thread's metrics. Only its own thread records into them; the lock is only
+/// contended while they are being rendered.
+#[derive(Default)]
+struct ThreadMetrics {
+    routes: Mutex<HashMap<String, RouteMetrics>>,
+    hnsw_search: Histogram,
+}
+
+/// Buffers of every thread that has recorded a metric, kept after the thread exits so
+/// counters never go backwards
+static THREADS: LazyLock<Mutex<Vec<Arc<ThreadMetrics>>>> = LazyLock::new(Default::default);
+
+thread_local! {
+    static LOCAL_METRICS: Arc<ThreadMetrics> = {
+        let metrics = Arc::new(ThreadMetrics::default());
+        THREADS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::clone(&metrics));
+        metrics
+    };
+}
+
+/// Record a completed request for a known route
+pub fn observe_request(route_name: &str, success: bool, elapsed: Duration) {
+    LOCAL_METRICS.with(|local| {
+        let mut routes = local.routes.lock().unwrap_or_else(PoisonError::into_inner);
+        // Only a route's first request on this thread allocates its buffer
+        if !routes.contains_key(route_name) {
+            routes.insert(route_name.to_string(), RouteMetrics::default());
+        }
+        let metrics = &routes[route_name];
+        match success {
+            true => metrics.success.fetch_add(1, Ordering::Relaxed),
+            false => metrics.error.fetch_add(1, Ordering::Relaxed),
+        };
+        metrics.latency.observe(elapsed);
+    });
+}
+
+/// Route and vector search metrics summed over every thread
+fn merged() -> (BTreeMap<String, RouteMetrics>, Histogram) {
+    let mut routes = BTreeMap::<String, RouteMetrics>::new();
+    let hnsw_search = Histogram::new();
+    for thread in THREADS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+    {
+        for (name, metrics) in thread
+            .routes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            routes.entry(name.clone()).or_default().merge(metrics);
+        }
+        hnsw_search.merge(&thread.hnsw_search);
+    }
+    (routes, hnsw_search)
+}
+
+/// Requests a route has handled so far
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteCounters {
+    pub route: String,
+    pub success: u64,
+    pub error: u64,
+    pub mean_latency_ms: f64,
+}
+
+/// Counters of every route that has handled a request, sorted by route
+pub fn route_counters() -> Vec<RouteCounters> {
+    merged()
+        .0
+        .into_iter()
+        .map(|(route, metrics)| {
+            let count = metrics.latency.count();
+            let sum_micros = metrics.latency.sum_micros.load(Ordering::Relaxed);
+            RouteCounters {
+                route,
+                success: metrics.success.load(Ordering::Relaxed),
+                error: metrics.error.load(Ordering::Relaxed),
+                mean_latency_ms: match count {
+                    0 => 0.0,
+                    _ => sum_micros as f64 / count as f64 / 1000.0,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Record the duration of a single HNSW vector search
+pub fn observe_hnsw_search(elapsed: Duration) {
+    LOCAL_METRICS.with(|local| local.hnsw_search.observe(elapsed));
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write a single gauge with its `HELP` and `TYPE` lines
+pub fn write_gauge(out: &mut String, name: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Write a single counter with its `HELP` and `TYPE` lines
+pub fn write_counter(out: &mut String, name: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} counter");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Render the request and vector search metrics recorded so far
+pub fn render() -> String {
+    let mut out = String::new();
+    let (routes, hnsw_search) = merged();
+
+    let _ = writeln!(
+        out,
+        "# HELP helix_requests_total Requests handled per route and outcome"
+    );
+    let _ = writeln!(out, "# TYPE helix_requests_total counter");
+    for (name, metrics) in &routes {
+        let route = escape_label(name);
+        let _ = writeln!(
+            out,
+            "helix_requests_total{{route=\"{route}\",outcome=\"success\"}} {}",
+            metrics.success.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "helix_requests_total{{route=\"{route}\",outcome=\"error\"}} {}",
+            metrics.error.load(Ordering::Relaxed)
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP helix_request_duration_seconds Request latency per route"
+    );
+    let _ = writeln!(out, "# TYPE helix_request_duration_seconds histogram");
+    for (name, metrics) in &routes {
+        let labels = format!("route=\"{}\"", escape_label(name));
+        metrics
+            .latency
+            .render(&mut out, "helix_request_duration_seconds", &labels);
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP helix_hnsw_search_duration_seconds HNSW vector search latency"
+    );
+    let _ = writeln!(out, "# TYPE helix_hnsw_search_duration_seconds histogram");
+    hnsw_search.render(&mut out, "helix_hnsw_search_duration_seconds", "");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = Histogram::new();
+        histogram.observe(Duration::from_micros(100));
+        histogram.observe(Duration::from_millis(20));
+        histogram.observe(Duration::from_secs(10));
+
+        let mut out = String::new();
+        histogram.render(&mut out, "latency", "");
+
+        assert!(out.contains("latency_bucket{le=\"0.0005\"} 1\n"));
+        assert!(out.contains("latency_bucket{le=\"0.025\"} 2\n"));
+        assert!(out.contains("latency_bucket{le=\"2.5\"} 2\n"));
+        assert!(out.contains("latency_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("latency_count 3\n"));
+    }
+
+    #[test]
+    fn test_observe_request_renders_route_series() {
+        observe_request("prom_test_route", true, Duration::from_millis(3));
+        observe_request("prom_test_route", false, Duration::from_millis(3));
+
+        let out = render();
+        assert!(
+            out.contains("helix_requests_total{route=\"prom_test_route\",outcome=\"success\"} 1\n")
+        );
+        assert!(
+            out.contains("helix_requests_total{route=\"prom_test_route\",outcome=\"error\"} 1\n")
+        );
+        assert!(
+            out.contains("helix_request_duration_seconds_count{route=\"prom_test_route\"} 2\n")
+        );
+        assert!(out.contains("# TYPE helix_hnsw_search_duration_seconds histogram"));
+    }
+
+    #[test]
+    fn test_route_counters() {
+        observe_request("counters_test_route", true, Duration::from_millis(2));
+        observe_request("counters_test_route", true, Duration::from_millis(4));
+        observe_request("counters_test_route", false, Duration::from_millis(6));
+
+        let counters = route_counters();
+        let route = counters
+            .iter()
+            .find(|c| c.route == "counters_test_route")
+            .unwrap();
+        assert_eq!(route.success, 2);
+        assert_eq!(route.error, 1);
+        assert_eq!(route.mean_latency_ms, 4.0);
+        assert!(counters.windows(2).all(|w| w[0].route <= w[1].route));
+    }
+
+    #[test]
+    fn test_requests_on_other_threads_are_merged() {
+        observe_request("threads_test_route", true, Duration::from_millis(2));
+        std::thread::spawn(|| {
+            observe_request("threads_test_route", false, Duration::from_millis(4));
+        })
+        .join()
+        .unwrap();
+
+        let counters = route_counters();
+        let route = counters
+            .iter()
+            .find(|c| c.route == "threads_test_route")
+            .unwrap();
+        assert_eq!((route.success, route.error), (1, 1));
+        assert_eq!(route.mean_latency_ms, 3.0);
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_write_gauge() {
+        let mut out = String::new();
+        write_gauge(&mut out, "helix_queue_depth", "Queued requests", 4);
+        assert_eq!(
+            out,
+            "# HELP helix_queue_depth Queued requests\n# TYPE helix_queue_depth gauge\nhelix_queue_depth 4\n"
+        );
+    }
+}